@@ -11,4 +11,402 @@ actual object NativeLib {
     external actual fun resetSimulation()
     external actual fun onPointerEvent(eventType: Int, x: Float, y: Float, button: Int)
     external actual fun onKeyEvent(eventType: Int, keyCode: Int)
+
+    external fun setAnimationPaused(paused: Boolean)
+    /** mode: 0 = scene, 1 = pristine scene, 2 = demo */
+    external fun resetSimulationTo(mode: Int): Boolean
+    external fun setPersistent(entityId: Long, persistent: Boolean): Boolean
+    /** New id of a persistent body rebuilt by the latest reset, or 0 */
+    external fun persistentIdAfterReset(entityId: Long): Long
+    /** From `onPause`; see `physics_core_on_app_pause` */
+    external fun onAppPause(freezePhysics: Boolean)
+    /** From `onResume` */
+    external fun onAppResume()
+    external fun getEntityIds(): LongArray
+    external fun isEntityAlive(entityId: Long): Boolean
+    external fun resetCount(): Int
+    /**
+     * Insets in surface pixels, e.g. from `WindowInsetsCompat` with
+     * `systemBars() or displayCutout()` (see `physics_core_set_safe_area_insets`)
+     */
+    external fun setSafeAreaInsets(left: Float, top: Float, right: Float, bottom: Float, insetWorld: Boolean): Boolean
+    /** `DisplayMetrics.density` (see `physics_core_set_scale_factor`) */
+    external fun setScaleFactor(scaleFactor: Float): Boolean
+    /**
+     * From `BatteryManager` (`EXTRA_LEVEL / EXTRA_SCALE`, -1 when unknown),
+     * `PowerManager.getCurrentThermalStatus()` and `isPowerSaveMode()`; see
+     * `physics_core_set_power_state`
+     */
+    external fun setPowerState(battery: Float, charging: Boolean, thermalStatus: Int, lowPowerMode: Boolean): Boolean
+    external fun setLowPowerMode(enabled: Boolean)
+    external fun setGovernorEnabled(enabled: Boolean)
+    /** Power state, throttle level and the limits in force as JSON */
+    external fun getGovernorStats(): String?
+    /**
+     * `pixels` is `width * height` RGBA8 pixels, shown behind the scene until
+     * replaced or cleared (see `physics_core_set_background_rgba`)
+     */
+    external fun setBackgroundRgba(pixels: ByteArray, width: Int, height: Int): Boolean
+    /**
+     * `buffer` is an `android.hardware.HardwareBuffer`, e.g. from an
+     * `ImageReader` image made with `USAGE_CPU_READ_OFTEN`; it is copied and
+     * stays the caller's to close
+     * Only in builds with the `android_hardware_buffer` feature
+     */
+    external fun setBackgroundHardwareBuffer(buffer: android.hardware.HardwareBuffer?): Boolean
+    external fun clearBackground()
+    /** 0 = fill (default), 1 = fit, 2 = stretch */
+    external fun setBackgroundFit(fit: Int): Boolean
+    external fun registerMaterialRgba(pixels: ByteArray, width: Int, height: Int): Int
+    external fun setEntityMaterial(entityId: Long, materialId: Int): Boolean
+    /** `alphaThreshold` 0..255; returns the number of convex pieces, or 0 on failure */
+    external fun generateColliderFromTexture(textureId: Int, alphaThreshold: Int, simplification: Float): Int
+    /** Filters 0 = nearest, 1 = linear; wraps 0 = clamp, 1 = repeat, 2 = mirror */
+    external fun setTextureSampling(materialId: Int, filter: Int, mipmapFilter: Int, wrapU: Int, wrapV: Int): Boolean
+    /**
+     * Rect `u`, `v`, `width`, `height` in texture coordinates (0..1, v down),
+     * drawn `drawWidth` x `drawHeight` times the square quad
+     */
+    external fun setSpriteRegion(entityId: Long, u: Float, v: Float, width: Float, height: Float, drawWidth: Float, drawHeight: Float): Boolean
+    /** Borders are fractions of the region */
+    external fun setNineSlice(entityId: Long, left: Float, top: Float, right: Float, bottom: Float): Boolean
+    external fun clearSpriteRegion(entityId: Long): Boolean
+    /** `kind` 0 = background, 1 = world, 2 = foreground; lower `order` draws first */
+    external fun setLayer(entityId: Long, kind: Int, order: Int): Boolean
+    /** 1 moves with the world, 0 stays put on screen */
+    external fun setLayerParallax(kind: Int, order: Int, factorX: Float, factorY: Float): Boolean
+    /** .gltf or .glb file bytes; returns the model id, or 0 on failure */
+    external fun loadModel(bytes: ByteArray): Int
+    /** `modelId` 0 removes the body's model */
+    external fun attachModel(entityId: Long, modelId: Int, scale: Float): Boolean
+    external fun setClearColor(r: Float, g: Float, b: Float, a: Float)
+    external fun setMsaaSamples(samples: Int)
+    /**
+     * `json` is a SplitScreen object, e.g. `{"layout": "stacked", "players": [{"follow": 4294967297}, {}]}`;
+     * null goes back to one camera
+     */
+    external fun setSplitScreen(json: String?): Boolean
+    /** `player` 1 or 2, or 0 to answer to both players */
+    external fun setPlayer(entityId: Long, player: Int): Boolean
+    /**
+     * Snapshot for a remote simulation relative to `baselineTick` (see
+     * `physics_core_get_snapshot_delta`), or null if the simulation is not running
+     */
+    external fun getSnapshotDelta(baselineTick: Long): ByteArray?
+    /**
+     * Returns the snapshot's tick to send back as the next baseline, or 0 when
+     * it could not be applied
+     */
+    external fun applySnapshot(bytes: ByteArray): Long
+    /** `window` steps of rollback (0 turns it off); see `physics_core_set_prediction` */
+    external fun setPrediction(window: Int, tolerance: Float): Boolean
+    external fun setPredicted(entityId: Long, predicted: Boolean): Boolean
+    /**
+     * Returns the snapshot's tick to send back as the next baseline, or 0 when
+     * it could not be applied
+     */
+    external fun reconcileSnapshot(bytes: ByteArray): Long
+    /**
+     * `json` is a CaptureConfig object, e.g. `{"fps": 30, "directory": "/sdcard/capture"}`;
+     * frames are only written to disk from here, so `directory` is required
+     */
+    external fun startCapture(json: String): Boolean
+    /** Returns the number of frames captured */
+    external fun stopCapture(): Long
+    external fun isCapturing(): Boolean
+    /**
+     * Looping GIF of the last `seconds` of the rewind history (see
+     * `physics_core_export_clip`), or null if it could not be made
+     */
+    external fun exportClip(seconds: Float, fps: Int, scale: Float): ByteArray?
+    /**
+     * Render and present a frame now and return it as a PNG (see
+     * `physics_core_take_screenshot_png`), or null before init
+     */
+    external fun takeScreenshot(): ByteArray?
+    /**
+     * Write the next frame presented to `path` as a PNG, egui included in the
+     * desktop winit app
+     */
+    external fun saveScreenshot(path: String): Boolean
+    /**
+     * Screenshot key of the desktop winit app as a winit `KeyCode`, negative to
+     * disable; a null `dir` keeps the current directory
+     */
+    external fun setScreenshotKey(keyCode: Int, dir: String?): Boolean
+    /**
+     * `json` is a MinimapConfig object, e.g. `{"corner": "bottomLeft", "visibleGroups": 2}`;
+     * null hides the minimap
+     */
+    external fun setMinimap(json: String?): Boolean
+    /** `backends`: gpu_options::BACKEND_* bits (0 = all); applies on the next init */
+    external fun setInitOptions(backends: Int, powerPreference: Int, forceFallbackAdapter: Boolean)
+    /** Frame timings as JSON, or null before init */
+    external fun getFrameStats(): String?
+    /** Last log lines as a JSON array; `level` 1 (errors only) to 5 (trace), 0 for all */
+    external fun getRecentLogs(level: Int): String?
+    external fun clearLogs()
+    /** `spec` such as "physics=debug,render=warn"; null or "" restores the defaults */
+    external fun setLogFilter(spec: String?): Boolean
+    /**
+     * Code of this thread's latest rejected input (`PHYSICS_CORE_ERROR_*`, 0 for
+     * none); see `physics_core_get_last_error`
+     */
+    external fun getLastError(): Int
+    /** What was wrong with that input, or null without an error */
+    external fun getLastErrorMessage(): String?
+    external fun clearLastError()
+    /** The latest panic as JSON, or null; see `physics_core_get_last_crash_report` */
+    external fun getLastCrashReport(): String?
+    external fun clearCrashReport()
+    /**
+     * Write each panic's report to `physicsfx_crash.txt` in `dir`, e.g. the
+     * app's internal storage, for the next launch to pick up; null stops writing
+     */
+    external fun setCrashDirectory(dir: String?): Boolean
+    /** Adapter description as JSON, or null before init */
+    external fun getAdapterInfo(): String?
+    external fun setBloom(enabled: Boolean, threshold: Float, intensity: Float)
+    external fun setContactShadows(enabled: Boolean, intensity: Float, spread: Float)
+    external fun setCulling(enabled: Boolean)
+    external fun setGpuCulling(enabled: Boolean): Boolean
+    /** Saves to the directory from `setSettingsDirectory` (e.g. `filesDir`) */
+    external fun saveSettings(): Boolean
+    external fun restoreSettings(): Boolean
+    /** Call before init with the app's internal storage directory; null keeps nothing */
+    external fun setSettingsDirectory(dir: String?): Boolean
+    /**
+     * HUD label at pixel (x, y), origin top-left; `color` is an Android color
+     * int (0xAARRGGBB). Same id replaces the label; it stays until cleared.
+     */
+    external fun drawText(id: Long, text: String, x: Float, y: Float, size: Float, color: Int): Boolean
+    /** Label centered above world point (x, y), `size` in world units */
+    external fun drawWorldText(id: Long, text: String, x: Float, y: Float, size: Float, color: Int): Boolean
+    /** Label following the entity at (offset_x, offset_y) from its center */
+    external fun drawEntityText(id: Long, entityId: Long, text: String, offsetX: Float, offsetY: Float, size: Float, color: Int): Boolean
+    external fun clearText(id: Long): Boolean
+    external fun clearAllText()
+    external fun addCameraShake(strength: Float): Boolean
+    external fun punchCameraZoom(amount: Float): Boolean
+    external fun setCameraShake(maxOffset: Float, maxRotation: Float, frequency: Float): Boolean
+    external fun setCameraEffectsDecay(traumaDecay: Float, zoomDecay: Float): Boolean
+    external fun setCameraImpactShake(minImpulse: Float, traumaPerImpulse: Float): Boolean
+    external fun clearCameraEffects()
+    external fun setSleepEnabled(enabled: Boolean)
+    /** `bodyType` is 0 dynamic, 1 fixed, 2 kinematic; returns 0 on failure */
+    external fun spawnBodyWithOptions(x: Float, y: Float, halfExtent: Float, bodyType: Int, surfaceVx: Float, surfaceVy: Float): Long
+    /**
+     * `points` holds x, y pairs of the outline relative to (x, y); `bodyType`
+     * as for `spawnBodyWithOptions`. Returns 0 on failure.
+     */
+    external fun spawnPolygon(x: Float, y: Float, points: FloatArray, bodyType: Int): Long
+    external fun setSurfaceVelocity(entityId: Long, vx: Float, vy: Float): Boolean
+    /** Fading trail through the body's last `length` positions; `color` is ARGB */
+    external fun enableTrail(entityId: Long, length: Int, color: Int): Boolean
+    external fun disableTrail(entityId: Long): Boolean
+    /**
+     * Predicted [x0, y0, x1, y1, ...] for a launch at (vx, vy); empty for
+     * unknown or non-dynamic bodies. `show` draws the arc as dots.
+     */
+    external fun predictTrajectory(entityId: Long, vx: Float, vy: Float, steps: Int, show: Boolean): FloatArray
+    external fun clearTrajectoryPreview()
+    /** Press on a body, drag to aim and release to launch it */
+    external fun enableSlingshotMode(enabled: Boolean)
+    external fun setSlingshotParams(maxImpulse: Float, maxPull: Float, arcSamples: Int): Boolean
+    /** Bodies pass through a one-way platform from below and land on its top */
+    external fun setOneWay(entityId: Long, enabled: Boolean): Boolean
+    external fun ignoreCollision(entityA: Long, entityB: Long): Boolean
+    external fun restoreCollision(entityA: Long, entityB: Long): Boolean
+    /** `json` is a LevelDesc object; returns false on parse or geometry errors */
+    external fun loadLevel(json: String): Boolean
+    /** `value` 0 clears the cell; returns false for unknown layers or cells */
+    external fun setTile(layer: Int, column: Int, row: Int, value: Int): Boolean
+    /** -1 for unknown layers or cells off the grid */
+    external fun getTile(layer: Int, column: Int, row: Int): Int
+    /** Returns the spring id, or 0 for unknown bodies or invalid arguments */
+    external fun createSpringToPoint(entityId: Long, worldX: Float, worldY: Float, stiffness: Float, damping: Float): Long
+    external fun updateSpring(springId: Long, worldX: Float, worldY: Float, stiffness: Float, damping: Float): Boolean
+    external fun removeSpring(springId: Long): Boolean
+    /** Spawn a box from a JSON scene body as an undoable step; 0 on errors */
+    external fun editSpawnBody(json: String): Long
+    /**
+     * Make a box match a JSON scene body as an undoable step; `merge` joins a
+     * change right after another change to the same box into its step
+     */
+    external fun editSetBody(entityId: Long, json: String, merge: Boolean): Boolean
+    external fun editRemoveBody(entityId: Long): Boolean
+    external fun editAttachSpring(entityId: Long, worldX: Float, worldY: Float, stiffness: Float, damping: Float): Long
+    /** The box as a JSON scene body; null for unknown ids and other shapes */
+    external fun getBodyDesc(entityId: Long): String?
+    external fun undo(): Boolean
+    external fun redo(): Boolean
+    external fun clearHistory()
+    /** Undo and redo counts and the next steps' labels as JSON */
+    external fun getHistoryState(): String?
+    /**
+     * `json` is a SoftBodyDesc object, e.g. `{"x": 0, "y": 0.5, "radius": 0.25, "segments": 20}`.
+     * Returns the blob id, or 0 on errors.
+     */
+    external fun spawnSoftBody(json: String): Long
+    external fun removeSoftBody(softBodyId: Long): Boolean
+    external fun wakeBody(entityId: Long): Boolean
+    external fun sleepBody(entityId: Long): Boolean
+    /** Returns [x, y, vx, vy, rotation, sleeping (0/1)], or an empty array for unknown ids */
+    external fun getBodyInfo(entityId: Long): FloatArray
+    /** `seconds` <= 0 cancels a pending lifetime */
+    external fun setLifetime(entityId: Long, seconds: Float): Boolean
+    external fun setDespawnBounds(enabled: Boolean, minX: Float, minY: Float, maxX: Float, maxY: Float): Boolean
+    /** Returns the decoration's entity id, or 0 on failure */
+    external fun spawnDecoration(x: Float, y: Float, halfExtent: Float, materialId: Int): Long
+    external fun removeDecoration(entityId: Long): Boolean
+    external fun attachEntity(childId: Long, parentId: Long, offsetX: Float, offsetY: Float, rotation: Float): Boolean
+    external fun detachEntity(childId: Long): Boolean
+    external fun configureProjectiles(capacity: Int, radius: Float): Boolean
+    /** Returns the projectile's entity id, or 0 when the pool is missing or exhausted */
+    external fun fireProjectile(originX: Float, originY: Float, vx: Float, vy: Float, ttl: Float): Long
+    /** Returns [capacity, active, peakActive, fired, expired, hit, dropped] */
+    external fun getProjectileStats(): LongArray
+    external fun setAudioEvents(enabled: Boolean, minSpeed: Float, maxSpeed: Float): Boolean
+    external fun setAudioListener(x: Float, y: Float): Boolean
+    external fun setAudioAttenuation(referenceDistance: Float, maxDistance: Float, rolloff: Float): Boolean
+    /**
+     * Breaks into up to `fragments` pieces when a collision impulse reaches
+     * `threshold` N·s; see clearDestructible
+     */
+    external fun setDestructible(entityId: Long, threshold: Float, fragments: Int, minHalfExtent: Float, particles: Int): Boolean
+    external fun clearDestructible(entityId: Long): Boolean
+    /**
+     * 7 longs per event: [entity, fragments, then x, y, impulse, halfExtent,
+     * fragmentHalfExtent as float bits (`Float.fromBits(value.toInt())`)]
+     */
+    external fun pollFractureEvents(maxEvents: Int): LongArray
+    /**
+     * 4 longs per event: [entity, zone index, zone kind (0 sensor, 1 goal,
+     * 2 hazard), 1 on entering / 0 on leaving]
+     */
+    external fun pollTriggerEvents(maxEvents: Int): LongArray
+    /** 0 playing, 1 won (goal reached), 2 lost (hazard entered) */
+    external fun levelOutcome(): Int
+    /** `json` as for `physics_core_register_scene`; false on parse or geometry errors */
+    external fun registerScene(name: String, json: String): Boolean
+    external fun removeScene(name: String): Boolean
+    /** `json` as for `physics_core_register_prefab`; false on parse errors */
+    external fun registerPrefab(name: String, json: String): Boolean
+    external fun removePrefab(name: String): Boolean
+    /** Spawn prefab `name` with its parts; returns the prefab's own body, or 0 */
+    external fun spawnPrefab(name: String, x: Float, y: Float, angle: Float): Long
+    /**
+     * `loader` is any object with `byte[] loadAsset(String name)` returning
+     * null for assets it does not have, such as one reading
+     * `context.getAssets().open(name)`; null leaves preloaded assets only.
+     * Threads the JVM does not know are attached for the call.
+     */
+    external fun registerAssetLoader(loader: Any?)
+    external fun preloadAsset(name: String, bytes: ByteArray): Boolean
+    external fun unloadAsset(name: String): Boolean
+    /** Returns the material id, or 0 when the asset cannot be read or decoded */
+    external fun loadTextureAsset(name: String): Int
+    /**
+     * Returns the number of bodies spawned, or -1; see
+     * `physics_core_load_scene_asset`
+     */
+    external fun loadSceneAsset(name: String): Int
+    /** Rebuild the simulation as scene `name`, fading in over `fadeSeconds` */
+    external fun switchScene(name: String, fadeSeconds: Float): Boolean
+    /** Name of the scene switched to, or null after the last scene */
+    external fun loadNextScene(fadeSeconds: Float): String?
+    /**
+     * `values` holds `from` followed by `to`; property and easing codes as for
+     * `physics_core_animate_property`. Returns the tween id, or 0 on failure.
+     */
+    external fun animateProperty(entityId: Long, property: Int, values: FloatArray, duration: Float, easing: Int): Long
+    external fun cancelTween(tweenId: Long): Boolean
+    /** `json` as for `physics_core_set_state_machine`; null removes the machine */
+    external fun setStateMachine(entityId: Long, json: String?): Boolean
+    /** Entity 0 sends to every machine; returns how many received the event */
+    external fun sendStateEvent(entityId: Long, event: String): Int
+    /** Index of the current state, or -1 for entities without a machine */
+    external fun entityState(entityId: Long): Int
+    /** `json` as for `physics_core_set_movement` */
+    external fun setMovement(entityId: Long, json: String): Boolean
+    /**
+     * Run a packed command buffer (see `physics_core_submit_commands`); fill it
+     * with a little-endian `ByteBuffer`. Returns one result per command, or null
+     * when the buffer is malformed or the simulation is not running.
+     */
+    external fun submitCommands(commands: ByteArray): LongArray?
+    /**
+     * Direct `ByteBuffer` over the shared transform region (layout in
+     * `physics_core_enable_transform_export`; set `ByteOrder.LITTLE_ENDIAN`),
+     * valid until the next call, or null when `capacity` is 0 or too large.
+     * Read-only for the host.
+     */
+    external fun enableTransformExport(capacity: Int): java.nio.ByteBuffer?
+    /**
+     * Draw the scene into another surface, given as the same native handle as
+     * `nativeInit` (an `ANativeWindow*` on Android), through its own camera.
+     * Returns the view id, or 0 on failure.
+     */
+    external fun addView(surfaceHandle: Long, width: Int, height: Int, centerX: Float, centerY: Float, viewHeight: Float): Int
+    external fun removeView(viewId: Int): Boolean
+    external fun resizeView(viewId: Int, width: Int, height: Int): Boolean
+    external fun setViewCamera(viewId: Int, centerX: Float, centerY: Float, viewHeight: Float): Boolean
+    /**
+     * 12 longs per event: [entityA, entityB, materialA, materialB, then x, y,
+     * impactSpeed, impulse, volume, pitch, pan, distance as float bits
+     * (`Float.fromBits(value.toInt())`)]
+     */
+    external fun pollAudioEvents(maxEvents: Int): LongArray
+    external fun queryAabb(minX: Float, minY: Float, maxX: Float, maxY: Float): LongArray
+    external fun queryCircle(centerX: Float, centerY: Float, radius: Float): LongArray
+    /** Group masks are passed as ints and read as unsigned bits; -1 matches every body */
+    external fun spawnBodyInGroups(x: Float, y: Float, halfExtent: Float, bodyType: Int, groups: Int): Long
+    external fun setBodyGroups(entityId: Long, groups: Int): Boolean
+    external fun getBodyGroups(entityId: Long): Int
+    external fun getGroupIds(mask: Int): LongArray
+    external fun despawnGroup(mask: Int): Int
+    external fun setGroupPaused(mask: Int, paused: Boolean): Int
+    external fun applyImpulseToGroup(mask: Int, impulseX: Float, impulseY: Float): Int
+    external fun queryAabbInGroups(minX: Float, minY: Float, maxX: Float, maxY: Float, mask: Int): LongArray
+    external fun queryCircleInGroups(centerX: Float, centerY: Float, radius: Float, mask: Int): LongArray
+    external fun setStreaming(enabled: Boolean, chunkSize: Float, loadRadius: Int, unloadRadius: Int)
+    external fun setStreamingFocusPoint(x: Float, y: Float)
+    external fun setStreamingFocusEntity(entityId: Long)
+    external fun setStreamingFocusCamera()
+    external fun getStateChecksum(): Long
+    external fun spawnStressScene(nBodies: Int, layout: Int): Boolean
+    /** Only in builds with the `scripting` feature */
+    external fun loadScript(source: String): Boolean
+    /** Only in builds with the `scripting` feature */
+    external fun unloadScript()
+    external fun rewind(steps: Int): Int
+    external fun setRewindHistory(capacity: Int)
+    external fun stepOnce()
+    external fun fastForward(steps: Int): Int
+    external fun setTimeScaleRamp(target: Float, seconds: Float)
+    /** `json` is a ForceFieldConfig object (wind, volumes, buoyancy) */
+    external fun setForceFields(json: String): Boolean
+    /** `json` is a WaterConfig object, e.g. `{"regions": [{"minX": -1, "maxX": 1, "surfaceY": -0.5}]}` */
+    external fun setWater(json: String): Boolean
+    /** `json` is a TimeDilationConfig object, e.g. `{"zones": [{"min": [-1, -1], "max": [1, 1], "scale": 0.25}]}` */
+    external fun setTimeDilation(json: String): Boolean
+    /** Substeps per step; `autoSubsteps` raises them above `velocityThreshold` m/s */
+    external fun setSubsteps(substeps: Int, autoSubsteps: Boolean, velocityThreshold: Float, maxSubsteps: Int): Boolean
+    /** `erp` 0 keeps Rapier's default contact stiffness; returns false if not running */
+    external fun setSolverConfig(solverIterations: Int, frictionIterations: Int, internalPgsIterations: Int, erp: Float, allowedLinearError: Float, maxCcdSubsteps: Int, contactDamping: Float): Boolean
+    external fun onTouchEvent(action: Int, pointerId: Int, x: Float, y: Float, eventTimeMs: Long)
+    external fun setGestures(tapSpawn: Boolean, fling: Boolean, pinchZoom: Boolean, pan: Boolean)
+    external fun setGravityVector(x: Float, y: Float, z: Float)
+    external fun setGravityPreset(preset: Int): Boolean
+    external fun setWorldScale(pixelsPerMeter: Float): Boolean
+    external fun setWorldScaleNdc(ndcPerMeter: Float): Boolean
+    external fun setTiltGravity(enabled: Boolean, strength: Float)
+    /**
+     * Forward `SensorEvent.values` from a `Sensor.TYPE_ACCELEROMETER` listener
+     * together with `Display.getRotation() * 90`
+     */
+    external fun onAccelerometer(x: Float, y: Float, z: Float, rotationDegrees: Int)
+    /** Returns the number of bodies dropped, 0 when stopping or on failure */
+    external fun set3dMode(enabled: Boolean, bodyCount: Int): Int
+    external fun orbit3dCamera(dx: Float, dy: Float, zoom: Float): Boolean
 }
@@ -0,0 +1,59 @@
+//! Physics step and instance sync benchmarks over generated stress scenes
+//!
+//! Run with `cargo bench`. GPU uploads need a device, so the instance path is
+//! measured up to the bytes handed to `queue.write_buffer`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use physics_core::bench_support;
+use physics_core::stress::{self, StressLayout};
+
+const BODY_COUNTS: [u32; 3] = [100, 1_000, 4_000];
+const DT: f32 = 1.0 / 60.0;
+
+fn bench_physics_step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("physics_step");
+    for layout in [StressLayout::Pyramid, StressLayout::Rain] {
+        for n in BODY_COUNTS {
+            bench_support::spawn_stress_scene(n, layout);
+            // Let the scene settle into contact so steps are representative
+            for _ in 0..30 {
+                bench_support::step(DT);
+            }
+            group.bench_with_input(BenchmarkId::new(format!("{:?}", layout), n), &n, |b, _| {
+                b.iter(|| bench_support::step(black_box(DT)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_instance_sync(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sync_physics_to_gpu");
+    for n in BODY_COUNTS {
+        bench_support::spawn_stress_scene(n, StressLayout::Stack);
+        bench_support::prepare_instance_upload();
+        group.bench_with_input(BenchmarkId::new("dirty_ranges", n), &n, |b, _| {
+            b.iter(|| {
+                bench_support::step(DT);
+                bench_support::prepare_instance_upload()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("full", n), &n, |b, _| {
+            b.iter(|| {
+                bench_support::step(DT);
+                bench_support::invalidate_instance_upload();
+                bench_support::prepare_instance_upload()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_scene_generation(c: &mut Criterion) {
+    c.bench_function("stress_generate_rain_4000", |b| {
+        b.iter(|| stress::generate(black_box(4_000), StressLayout::Rain))
+    });
+}
+
+criterion_group!(benches, bench_physics_step, bench_instance_sync, bench_scene_generation);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+// Regenerates include/physics_core.h from src/ffi.rs when built with
+// `--features c_header`. Plain builds use the checked-in header and don't
+// need cbindgen.
+
+fn main() {
+    #[cfg(feature = "c_header")]
+    generate_header();
+}
+
+#[cfg(feature = "c_header")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=src/plugins.rs");
+
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("failed to read cbindgen.toml");
+    match cbindgen::Builder::new().with_crate(&crate_dir).with_config(config).generate() {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{}/include/physics_core.h", crate_dir));
+        }
+        Err(e) => println!("cargo:warning=cbindgen failed, header not regenerated: {}", e),
+    }
+}
@@ -1,31 +1,1377 @@
 #ifndef PHYSICS_CORE_H
 #define PHYSICS_CORE_H
 
+// Generated by cbindgen from the Rust sources. Do not edit by hand; see cbindgen.toml.
+
 #include <stdint.h>
 #include <stdbool.h>
 
-const char* physics_core_get_info();
-void physics_core_free_string(char* s);
+// Bumped on any breaking change to the functions or types in this module
+#define PHYSICS_CORE_ABI_MAJOR 1
+
+// Bumped when functions or types are added
+#define PHYSICS_CORE_ABI_MINOR 68
+
+#define PHYSICS_CORE_ABI_PATCH 0
+
+// `InitOptions::backends` bits (same values as `gpu_options::BACKEND_*`);
+// 0 selects every backend
+#define PHYSICS_CORE_BACKEND_VULKAN 1
+
+#define PHYSICS_CORE_BACKEND_METAL 2
+
+#define PHYSICS_CORE_BACKEND_DX12 4
+
+#define PHYSICS_CORE_BACKEND_GL 8
+
+#define PHYSICS_CORE_BACKEND_BROWSER_WEBGPU 16
+
+// `InitOptions::power_preference` values
+#define PHYSICS_CORE_POWER_PREFERENCE_NONE 0
+
+#define PHYSICS_CORE_POWER_PREFERENCE_LOW_POWER 1
+
+#define PHYSICS_CORE_POWER_PREFERENCE_HIGH_PERFORMANCE 2
+
+#define PHYSICS_CORE_BODY_DYNAMIC 0
+
+#define PHYSICS_CORE_BODY_FIXED 1
+
+#define PHYSICS_CORE_BODY_KINEMATIC 2
+
+// Group bits of newly spawned bodies, and the mask matching every body
+#define PHYSICS_CORE_GROUP_DEFAULT 1
+
+#define PHYSICS_CORE_GROUP_ALL 0xFFFFFFFF
+
+// `physics_core_spawn_body_with_flags` bits: a one-way platform that only
+// holds up bodies landing on its top side
+#define PHYSICS_CORE_SPAWN_ONE_WAY 1
+
+// `TriggerEvent::kind` values
+#define PHYSICS_CORE_TRIGGER_SENSOR 0
+
+#define PHYSICS_CORE_TRIGGER_GOAL 1
+
+#define PHYSICS_CORE_TRIGGER_HAZARD 2
+
+// `physics_core_level_outcome` values
+#define PHYSICS_CORE_OUTCOME_PLAYING 0
+
+#define PHYSICS_CORE_OUTCOME_WON 1
+
+#define PHYSICS_CORE_OUTCOME_LOST 2
+
+// `physics_core_animate_property` properties. `from` and `to` hold one
+// value for the drawn size multiplier, the rotation (radians) and the
+// camera zoom (visible world height), three for the RGB tint (0..1) and two
+// for the camera position.
+#define PHYSICS_CORE_TWEEN_SCALE 0
+
+#define PHYSICS_CORE_TWEEN_ROTATION 1
+
+#define PHYSICS_CORE_TWEEN_COLOR 2
+
+#define PHYSICS_CORE_TWEEN_CAMERA_ZOOM 3
+
+#define PHYSICS_CORE_TWEEN_CAMERA_POSITION 4
+
+// Easing curves for `physics_core_animate_property`
+#define PHYSICS_CORE_EASING_LINEAR 0
+
+#define PHYSICS_CORE_EASING_QUAD_IN 1
+
+#define PHYSICS_CORE_EASING_QUAD_OUT 2
+
+#define PHYSICS_CORE_EASING_QUAD_IN_OUT 3
+
+#define PHYSICS_CORE_EASING_CUBIC_IN 4
+
+#define PHYSICS_CORE_EASING_CUBIC_OUT 5
+
+#define PHYSICS_CORE_EASING_CUBIC_IN_OUT 6
+
+#define PHYSICS_CORE_EASING_SINE_IN_OUT 7
+
+#define PHYSICS_CORE_EASING_BACK_OUT 8
+
+#define PHYSICS_CORE_EASING_ELASTIC_OUT 9
+
+#define PHYSICS_CORE_EASING_BOUNCE_OUT 10
+
+// Opcodes of `physics_core_submit_commands` buffers (same values as
+// `command_buffer::*`)
+#define PHYSICS_CORE_COMMAND_SPAWN 1
+
+#define PHYSICS_CORE_COMMAND_IMPULSE 2
+
+#define PHYSICS_CORE_COMMAND_SET_VELOCITY 3
+
+#define PHYSICS_CORE_COMMAND_SET_POSITION 4
+
+#define PHYSICS_CORE_COMMAND_SET_GROUPS 5
+
+#define PHYSICS_CORE_COMMAND_SET_MATERIAL 6
+
+// Size in bytes of the transform export header blocks and of each record
+#define PHYSICS_CORE_TRANSFORM_BLOCK_SIZE 32
+
+// Most views open at once besides the main surface
+#define PHYSICS_CORE_MAX_VIEWS 8
+
+// `physics_core_get_last_error` codes (same values as `validation::ERROR_*`)
+#define PHYSICS_CORE_ERROR_NONE 0
+
+// A NaN or infinite number
+#define PHYSICS_CORE_ERROR_NOT_FINITE 1
+
+// A size, radius or scale of zero or less
+#define PHYSICS_CORE_ERROR_INVALID_SIZE 2
+
+// A time step of zero or less, or not finite
+#define PHYSICS_CORE_ERROR_INVALID_TIME_STEP 3
+
+// A malformed entity id or one naming no live entity
+#define PHYSICS_CORE_ERROR_UNKNOWN_ENTITY 4
+
+#define PHYSICS_CORE_ERROR_OUT_OF_RANGE 5
+
+#define PHYSICS_CORE_ERROR_NULL_POINTER 6
+
+#define PHYSICS_CORE_ERROR_INVALID_STRING 7
+
+// Semantic version of the C ABI. A host built against the header is
+// compatible when `major` matches and `minor` is at least the header's.
+typedef struct {
+  uint32_t major;
+  uint32_t minor;
+  uint32_t patch;
+} AbiVersion;
+
+// A dynamic box to add to the running scene
+typedef struct {
+  float x;
+  float y;
+  float half_extent;
+  // Initial linear velocity
+  float vx;
+  float vy;
+  // 0 keeps the default texture
+  uint32_t material_id;
+} BodyDesc;
+
+// Extra settings for `physics_core_spawn_body_with_options`
+typedef struct {
+  // One of `PHYSICS_CORE_BODY_*`
+  int32_t body_type;
+  // Conveyor velocity of the body's surface; 0 for a plain surface
+  float surface_vx;
+  float surface_vy;
+} SpawnOptions;
+
+// Adapter selection for the next `wgpu_init`
+typedef struct {
+  // `PHYSICS_CORE_BACKEND_*` bits; 0 selects every backend
+  uint32_t backends;
+  // One of `PHYSICS_CORE_POWER_PREFERENCE_*`
+  int32_t power_preference;
+  // Ask for a software adapter (SwiftShader, WARP, ...)
+  bool force_fallback_adapter;
+} InitOptions;
+
+typedef struct {
+  // Velocity solver iterations, at least 1
+  uint32_t solver_iterations;
+  // Extra friction-only iterations after the main solve
+  uint32_t friction_iterations;
+  // PGS iterations inside each solver iteration, at least 1
+  uint32_t internal_pgs_iterations;
+  // Fraction of penetration corrected per step, below 1; 0 keeps Rapier's
+  // default contact stiffness
+  float erp;
+  // Penetration tolerated before it is corrected, in meters
+  float allowed_linear_error;
+  // CCD substeps for fast bodies, at least 1
+  uint32_t max_ccd_substeps;
+  // Damping ratio of the contact spring
+  float contact_damping;
+} SolverConfig;
+
+// Snapshot of a body returned by the body query API
+typedef struct {
+  float x;
+  float y;
+  float vx;
+  float vy;
+  float rotation;
+  bool sleeping;
+} BodyInfo;
+
+// One spatial query hit with the body's state at query time
+typedef struct {
+  uint64_t entity_id;
+  BodyInfo body;
+} QueryHit;
+
+// Pool occupancy and lifetime counters
+typedef struct {
+  uint32_t capacity;
+  // Projectiles in flight
+  uint32_t active;
+  // Most projectiles in flight at once
+  uint32_t peak_active;
+  uint64_t fired;
+  // Parked because their time to live ran out
+  uint64_t expired;
+  // Parked on their first collision
+  uint64_t hit;
+  // Shots refused because every projectile was in flight
+  uint64_t dropped;
+} ProjectileStats;
+
+// C hook signature: `(hook_point, dt, user_data)`
+typedef void (*PhysicsCoreHookFn)(uint32_t, float, void*);
+
+// One collision sound
+typedef struct {
+  uint64_t entity_a;
+  uint64_t entity_b;
+  // Material ids of both entities (0, the default, for walls)
+  uint32_t material_a;
+  uint32_t material_b;
+  float x;
+  float y;
+  float impact_speed;
+  float impulse;
+  // Suggested gain in 0..1, distance attenuation included
+  float volume;
+  // Suggested playback rate, 1 for a default-sized body
+  float pitch;
+  // -1 (left of the listener) to 1 (right)
+  float pan;
+  // From the listener, world units
+  float distance;
+} AudioEvent;
+
+// C audio callback: `(events, count, user_data)`; `events` is only valid during the call
+typedef void (*PhysicsCoreAudioFn)(const AudioEvent*, uint32_t, void*);
+
+// How a destructible body breaks; part of the spawn description
+typedef struct {
+  // Collision impulse that breaks the body, N·s
+  float threshold;
+  // Target fragment count, 4..=64; rounded down to a square grid
+  uint32_t fragments;
+  // Bodies whose fragments would be smaller than this do not break
+  float min_half_extent;
+  // Debris thrown per fracture, 0..=64
+  uint32_t particles;
+} FractureConfig;
+
+// One fracture, for the host
+typedef struct {
+  // The body that broke; it no longer exists
+  uint64_t entity;
+  float x;
+  float y;
+  // Impulse of the hit, N·s
+  float impulse;
+  float half_extent;
+  uint32_t fragments;
+  float fragment_half_extent;
+} FractureEvent;
+
+// A body entering or leaving a zone
+typedef struct {
+  uint64_t entity;
+  // Index of the zone in the level's `triggers`
+  uint32_t zone;
+  // `TriggerKind` of the zone
+  uint32_t kind;
+  // true on entering, false on leaving
+  bool entered;
+} TriggerEvent;
+
+// C callback: `(frame_index, width, height, rgba, user_data)`; `rgba` is
+// only valid during the call
+typedef void (*PhysicsCoreFrameFn)(uint64_t, uint32_t, uint32_t, const uint8_t*, void*);
+
+// C loader: `(name, len, user_data)` returns the asset's bytes and sets
+// `*len`, or returns null for assets it does not have
+typedef const uint8_t *(*PhysicsCoreAssetFn)(const char*, uint32_t*, void*);
+
+// C release: `(bytes, len, user_data)` once bytes the loader returned are
+// copied
+typedef void (*PhysicsCoreAssetReleaseFn)(const uint8_t*, uint32_t, void*);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
 
-// Game loop lifecycle
-// surface_handle: Platform-specific native surface handle
+AbiVersion physics_core_abi_version(void);
+
+// Library/backend description; release with `physics_core_free_string`
+char *physics_core_get_info(void);
+
+// Adapter name, backend, surface formats, capabilities and limits as a JSON
+// object; null before `wgpu_init`. Release with `physics_core_free_string`.
+char *physics_core_get_adapter_info(void);
+
+// Smoothed FPS, CPU frame time, per-pass GPU times (when the adapter
+// supports timestamps) and drawn and culled body counts as JSON; null before `wgpu_init`. Release with
+// `physics_core_free_string`.
+char *physics_core_get_frame_stats(void);
+
+// The last log lines (up to 500) as a JSON array of `{"level": "WARN",
+// "target": "physics_core", "message": "..."}` objects, oldest first.
+// `level` is the least severe level included: 1 error, 2 warn, 3 info,
+// 4 debug, 5 trace (0 includes all). Release with `physics_core_free_string`.
+char *physics_core_get_recent_logs(uint32_t level);
+
+// Forget the log lines kept so far
+void physics_core_clear_logs(void);
+
+// Log levels per target while the app runs (UTF-8, NUL-terminated), e.g.
+// `physics=debug,render=warn,warn`: `target=level` directives and a bare
+// level for every other target. The engine logs under `init`, `physics`,
+// `gpu` and `render`. NULL or an empty string goes back to the default
+// levels. Returns false for unknown levels.
+bool physics_core_set_log_filter(const char *spec);
+
+// The latest panic as a JSON object `{"message", "location", "thread",
+// "entryPoint", "backtrace"}`, or null if nothing panicked since start or
+// `physics_core_clear_crash_report`. A panic inside any function here is
+// caught and that function returns false, 0 or null. Release with
+// `physics_core_free_string`.
+char *physics_core_get_last_crash_report(void);
+
+void physics_core_clear_crash_report(void);
+
+// Also write each panic's report to `physicsfx_crash.txt` in `dir` (UTF-8,
+// NUL-terminated), which is created if missing; NULL stops writing. Returns
+// false if the directory cannot be created.
+bool physics_core_set_crash_directory(const char *dir);
+
+// `PHYSICS_CORE_ERROR_*` code of the latest input this thread passed that
+// was rejected: a NaN position, a negative size, a zero time step, an
+// unknown entity id and the like. The function it was passed to returned
+// false, 0 or null; the error stays until the next rejection or
+// `physics_core_clear_last_error`.
+int32_t physics_core_get_last_error(void);
+
+// What was wrong with that input, e.g. `position must be finite, got NaN`;
+// null without an error. Release with `physics_core_free_string`.
+char *physics_core_get_last_error_message(void);
+
+void physics_core_clear_last_error(void);
+
+// Free a string returned by this library
+void physics_core_free_string(char *s);
+
+// Free `len` bytes returned by this library
+void physics_core_free_bytes(uint8_t *bytes, uint32_t len);
+
+// Backend, power preference and fallback-adapter choice for the next
+// `wgpu_init` (or device rebuild); null restores the defaults
+void physics_core_set_init_options(const InitOptions *options);
+
+// surface_handle: platform-specific native surface handle
 //   - iOS: CAMetalLayer*
 //   - macOS: NSView*
 //   - Windows: HWND
 //   - Linux: X11 Window
 //   - Android: ANativeWindow*
-bool wgpu_init(void* surface_handle, int32_t width, int32_t height);
+bool wgpu_init(void *surface_handle, int32_t width, int32_t height);
+
+// Advance the simulation by `delta_time` seconds (scaled by the time
+// scale); a zero, negative or non-finite step is rejected
 void wgpu_update(float delta_time);
-void wgpu_render();
+
+void wgpu_render(void);
+
 void wgpu_resize(int32_t width, int32_t height);
-void wgpu_shutdown();
 
-// Simulation controls
+void wgpu_shutdown(void);
+
+// The app went to the background (Android `onPause`, iOS
+// `applicationWillResignActive`, `visibilitychange` to hidden). Rendering
+// stops; with `freeze_physics` updates step nothing either, otherwise the
+// simulation keeps running on the updates still made. All state stays, and
+// a `wgpu_shutdown` while paused keeps the scene, camera and materials for
+// the next `wgpu_init` instead of starting over. Settings are saved.
+void physics_core_on_app_pause(bool freeze_physics);
+
+// Back in the foreground. The next update is an empty frame, whatever time
+// the host's clock says went by.
+void physics_core_on_app_resume(void);
+
+// Gravity straight down with magnitude `y` (m/s²)
 void physics_core_set_gravity(float y);
+
+// Gravity as a full world-space vector (m/s²)
+void physics_core_set_gravity_vector(float x, float y, float z);
+
+// Set gravity to a preset: 0 = Earth (9.81 m/s²), 1 = Moon (1.62 m/s²),
+// 2 = zero-g. Returns false for other codes.
+bool physics_core_set_gravity_preset(int32_t preset);
+
+// Show `pixels_per_meter` on the main surface at its current size; a later
+// resize keeps the same meters in view. The camera is reframed at once,
+// walls and default sizes follow on the next reset. Returns false without
+// a surface or unless the scale is finite and > 0.
+bool physics_core_set_world_scale(float pixels_per_meter);
+
+// Like `physics_core_set_world_scale` in normalized device units per meter
+// (the view is 2 tall; 1 is the default). Works before the surface exists.
+bool physics_core_set_world_scale_ndc(float ndc_per_meter);
+
+// While enabled, each accelerometer sample sets gravity to point down on screen,
+// scaled by `strength`
+void physics_core_set_tilt_gravity(bool enabled, float strength);
+
+// One accelerometer sample in device axes (m/s², gravity reaction included,
+// Android convention) with the display rotation (0, 90, 180, 270).
+// Core Motion reports acceleration in g with the opposite sign; hosts should
+// pass `-userAcceleration - gravity` scaled by 9.81
+void physics_core_on_accelerometer(float x, float y, float z, int32_t rotation_degrees);
+
+// Start (`enabled`) or stop the 3D demo: `body_count` boxes and spheres
+// (at most 2000) fall onto a ground plane with full 3D motion, drawn by the
+// 3D pass through a perspective camera that pointer drags orbit and pinch
+// or the mouse wheel zoom. Starting replaces the simulation; stopping
+// restores the 2D camera and the default scene. Returns the number of
+// bodies dropped, 0 when stopping or without a renderer.
+uint32_t physics_core_set_3d_mode(bool enabled, uint32_t body_count);
+
+// Orbit the 3D demo's camera as if dragged `dx`, `dy` pixels and move it
+// `zoom` times closer (1 keeps the distance), for hosts with their own
+// controls. Returns false while the demo is not running.
+bool physics_core_orbit_3d_camera(float dx, float dy, float zoom);
+
 void physics_core_set_time_scale(float scale);
+
 void physics_core_set_paused(bool paused);
-void physics_core_reset_simulation();
+
+// Stop or resume sprite animations and tweens, independently of the
+// simulation pause
+void physics_core_set_animation_paused(bool paused);
+
+// One fixed 1/60 s step; works while paused
+void physics_core_step_once(void);
+
+// Run up to `steps` (at most 600) fixed steps without rendering. Returns the steps run.
+uint32_t physics_core_fast_forward(uint32_t steps);
+
+// Ease the time scale to `target` over `seconds` of real time
+void physics_core_set_time_scale_ramp(float target, float seconds);
+
+// Rebuild the scene last switched to, or the default scene, with the bodies
+// marked persistent; keeps gravity, time scale and pause state
+void physics_core_reset_simulation(void);
+
+// Reset as `mode` says: 0 = scene with persistent bodies (as
+// `physics_core_reset_simulation`), 1 = scene without them, 2 = the default
+// scene, forgetting the loaded one. Returns false for an unknown mode.
+bool physics_core_reset_simulation_to(int32_t mode);
+
+// Have resets to the scene rebuild the box `entity_id` as it is now, or stop
+// doing so. Returns false for unknown entities and shapes other than boxes.
+// The rebuilt box is a new entity with a new id; look it up with
+// `physics_core_persistent_id_after_reset`.
+bool physics_core_set_persistent(uint64_t entity_id, bool persistent);
+
+// Id the latest reset rebuilt the persistent body `entity_id` under, or 0 if
+// it did not rebuild that body. Only the latest reset is remembered.
+uint64_t physics_core_persistent_id_after_reset(uint64_t entity_id);
+
+// Replace the scene with `n_bodies` boxes. layout: 0 = pyramid, 1 = stack, 2 = rain
+bool physics_core_spawn_stress_scene(uint32_t n_bodies, int32_t layout);
+
+// Hash of all body positions/velocities at the current step, for comparing platforms
+uint64_t physics_core_get_state_checksum(void);
+
+// Jump back `steps` physics steps using the snapshot history. Returns the steps actually rewound.
+uint32_t physics_core_rewind(uint32_t steps);
+
+// Number of steps kept for rewinding; 0 disables snapshot capture
+void physics_core_set_rewind_history(uint32_t capacity);
+
+// Solver iterations, error reduction, CCD substeps and contact damping;
+// out-of-range fields are clamped and null restores the defaults. Returns
+// false if the simulation is not running.
+bool physics_core_set_solver_config(const SolverConfig *config);
+
+// Replace the scene's force fields with a JSON object (UTF-8,
+// NUL-terminated) such as `{"wind": {"velocity": [2, 0]}, "volumes": [],
+// "buoyancy": [{"surfaceY": -0.5, "fluidDensity": 2}]}`. Returns false on
+// parse errors or if the simulation is not running.
+bool physics_core_set_force_fields(const char *json);
+
+// Replace every water region with a JSON object (UTF-8, NUL-terminated)
+// such as `{"regions": [{"minX": -1, "maxX": 1, "surfaceY": -0.5,
+// "bottomY": -1, "fluidDensity": 2}]}`; missing fields keep their defaults.
+// The water starts calm. Returns false on parse errors or if the simulation
+// is not running.
+bool physics_core_set_water(const char *json);
+
+// Replace the slow-motion zones with a JSON object (UTF-8, NUL-terminated)
+// such as `{"zones": [{"min": [-1, -1], "max": [1, 1], "scale": 0.25}]}`.
+// Bodies in a zone run at `scale` times normal speed; the slowest zone wins
+// where they overlap. Returns false on parse errors or if the simulation is
+// not running.
+bool physics_core_set_time_dilation(const char *json);
+
+// Split every step into `substeps` Rapier steps. With `auto_substeps` the
+// count grows with the fastest body's speed above `velocity_threshold` m/s,
+// up to `max_substeps`. Returns false if the simulation is not running.
+bool physics_core_set_substeps(uint32_t substeps, bool auto_substeps, float velocity_threshold, uint32_t max_substeps);
+
+// Write the active solver settings to `out`; false if not running
+bool physics_core_get_solver_config(SolverConfig *out);
+
+// event_type: 0 = down, 1 = move, 2 = up; x/y in pixels; button: 0 = primary
 void physics_core_on_pointer_event(int32_t event_type, float x, float y, int32_t button);
+
+// event_type: 0 = down, 1 = up
 void physics_core_on_key_event(int32_t event_type, int32_t key_code);
 
+// Touch gestures. action: 0 = down, 1 = move, 2 = up, 3 = cancel; one call per pointer, x/y in pixels
+void physics_core_on_touch_event(int32_t action, int32_t pointer_id, float x, float y, double time_seconds);
+
+// Tap spawns a box, drag flings the touched body, pinch zooms and two-finger drag pans
+void physics_core_set_gestures(bool tap_spawn, bool fling, bool pinch_zoom, bool pan);
+
+// Copy up to `capacity` entity ids into `out_ids`, returning the total number of entities.
+// Entity ids are opaque 64-bit handles.
+uint32_t physics_core_get_entity_ids(uint64_t *out_ids, uint32_t capacity);
+
+// Whether `entity_id` names an entity of the current scene. Ids die with
+// their entity and with every reset, and are never handed out again.
+bool physics_core_is_entity_alive(uint64_t entity_id);
+
+// Times the scene was reset or replaced since startup; hosts holding entity
+// ids can compare it, or register a reset hook with `physics_core_register_hook`
+uint32_t physics_core_reset_count(void);
+
+// Register a `width` x `height` RGBA8 texture and return its material id.
+// Material 0 is the built-in default texture.
+uint32_t physics_core_register_material_rgba(const uint8_t *pixels, uint32_t width, uint32_t height);
+
+bool physics_core_set_entity_material(uint64_t entity_id, uint32_t material_id);
+
+// Trace the pixels of material `texture_id` with alpha above
+// `alpha_threshold` into a collision shape, dropping outline detail
+// smaller than `simplification` pixels. Bodies drawn with the texture,
+// now or once it is set on them, collide with that shape at their drawn
+// size. Returns the number of convex pieces, or 0 on failure.
+uint32_t physics_core_generate_collider_from_texture(uint32_t texture_id, uint8_t alpha_threshold, float simplification);
+
+// Sample material `material_id`'s texture with `filter` between texels and
+// `mipmap_filter` between mip levels (0 = nearest, 1 = linear, the default)
+// and wrap it with `wrap_u` and `wrap_v` (0 = clamp to the edge, the default,
+// 1 = repeat, 2 = mirror); nearest filtering keeps pixel art sharp. Returns
+// false for unknown values or materials and for material 0.
+bool physics_core_set_texture_sampling(uint32_t material_id, int32_t filter, int32_t mipmap_filter, int32_t wrap_u, int32_t wrap_v);
+
+// Draw the texture rect at `u`, `v` (top left, 0..1) of `width` x `height`
+// (texture coordinates) on entity `entity_id`, on a quad `draw_width` x
+// `draw_height` times its square size, e.g. one image of an atlas or a
+// platform four times as wide as tall. Replaces sprite sheet frames and
+// keeps the collider. Returns false for unknown entities and for rects
+// outside the texture or sizes <= 0.
+bool physics_core_set_sprite_region(uint64_t entity_id, float u, float v, float width, float height, float draw_width, float draw_height);
+
+// Keep the region borders `left`, `top`, `right` and `bottom` (fractions
+// of the region) at their size on the square quad while the middle
+// stretches, so panels and platforms of any size keep their frames
+// (nine-slice). An entity without a region gets the whole texture as one.
+// Returns false for unknown entities and for borders < 0 or opposite
+// borders adding up to more than 1.
+bool physics_core_set_nine_slice(uint64_t entity_id, float left, float top, float right, float bottom);
+
+// Draw entity `entity_id`'s whole texture, or its sprite sheet frame, on
+// the square quad again. Returns false for unknown entities.
+bool physics_core_clear_sprite_region(uint64_t entity_id);
+
+// Draw entity `entity_id` in layer `order` of `kind` (0 = background,
+// behind the terrain and tiles, 1 = world, the default, 2 = foreground,
+// over the water). Layers of one kind are drawn lowest `order` first, and
+// sprites within one keep their order from frame to frame. Returns false
+// for unknown kinds or entities.
+bool physics_core_set_layer(uint64_t entity_id, int32_t kind, int32_t order);
+
+// Scroll layer `order` of `kind` (see `physics_core_set_layer`) by
+// `factor_x` and `factor_y` of the camera's movement: 1 moves with the
+// world, 0 stays put on screen like a sky and 0.5 drifts at half speed
+// like distant hills. Only drawing moves; colliders stay put, and such
+// layers are never culled. Returns false for unknown kinds, factors that
+// are not finite and if the simulation is not running.
+bool physics_core_set_layer_parallax(int32_t kind, int32_t order, float factor_x, float factor_y);
+
+// Load a .gltf (with embedded base64 buffers) or .glb model from `len`
+// bytes for the 3D pass. Only triangles are read, each primitive in its
+// material's base color, and the model is centered and fitted to -1..1.
+// Returns its id, or 0 for null, malformed or unsupported files.
+uint32_t physics_core_load_model(const uint8_t *bytes, uint32_t len);
+
+// Draw the body in the 3D pass with model `model_id` at its position and
+// rotation, `scale` times its drawn size; the models replace the sample
+// cube. Model 0 removes the body's model. Returns false for unknown
+// entities or models and a scale that is not positive.
+bool physics_core_attach_model(uint64_t entity_id, uint32_t model_id, float scale);
+
+// Spawn a box described by `desc`. Returns its entity id, or 0 if `desc` is
+// null, the size is invalid or the simulation is not running.
+uint64_t physics_core_spawn_body(const BodyDesc *desc);
+
+// Like `physics_core_spawn_body`, with the body type and conveyor velocity
+// from `options` (null for a dynamic body). Returns 0 on failure.
+uint64_t physics_core_spawn_body_with_options(const BodyDesc *desc, const SpawnOptions *options);
+
+// Like `physics_core_spawn_body_with_options`, with the body placed in
+// `groups` (bits, see `physics_core_set_body_groups`). Returns 0 on failure.
+uint64_t physics_core_spawn_body_in_groups(const BodyDesc *desc, const SpawnOptions *options, uint32_t groups);
+
+// Like `physics_core_spawn_body_in_groups`, with `PHYSICS_CORE_SPAWN_*`
+// `flags`. Returns 0 on failure.
+uint64_t physics_core_spawn_body_with_flags(const BodyDesc *desc, const SpawnOptions *options, uint32_t groups, uint32_t flags);
+
+// Add a body at (x, y) shaped like the outline `points` (`point_count` x, y
+// pairs relative to the body, either winding, concave or not), split into
+// convex pieces on one body. `options` and `groups` as for
+// `physics_core_spawn_body_in_groups`. Returns 0 for outlines that cross
+// themselves, have no area or more than 256 points.
+uint64_t physics_core_spawn_polygon(float x, float y, const float *points, uint32_t point_count, const SpawnOptions *options, uint32_t groups);
+
+// Make the body's surface move at (vx, vy) m/s so touching bodies are
+// carried along; zero stops the conveyor. Returns false for unknown ids.
+bool physics_core_set_surface_velocity(uint64_t entity_id, float vx, float vy);
+
+void physics_core_set_sleep_enabled(bool enabled);
+
+bool physics_core_wake_body(uint64_t entity_id);
+
+bool physics_core_sleep_body(uint64_t entity_id);
+
+// Fill `out_info` with the body's current state. Returns false for unknown ids.
+bool physics_core_get_body_info(uint64_t entity_id, BodyInfo *out_info);
+
+// Make the body a one-way platform: bodies pass through it from below and
+// land on its top (local +y) side. false makes it solid again. Returns false
+// for unknown ids.
+bool physics_core_set_one_way(uint64_t entity_id, bool enabled);
+
+// Let two bodies pass through each other while both still collide with
+// everything else. Returns false for unknown ids, the same id twice or a
+// pair that is already ignored.
+bool physics_core_ignore_collision(uint64_t entity_a, uint64_t entity_b);
+
+// Undo `physics_core_ignore_collision`. Returns false if the pair was not ignored.
+bool physics_core_restore_collision(uint64_t entity_a, uint64_t entity_b);
+
+// Spawn a box like `physics_core_spawn_body` that breaks into smaller boxes
+// when a collision impulse reaches `fracture->threshold` (null for the
+// defaults). Returns its entity id, or 0 for invalid arguments.
+uint64_t physics_core_spawn_destructible(const BodyDesc *desc, const FractureConfig *fracture);
+
+// Make an existing body destructible with `fracture`, or not with null.
+// Returns false for unknown ids and invalid configs.
+bool physics_core_set_destructible(uint64_t entity_id, const FractureConfig *fracture);
+
+// Move up to `capacity` queued fracture events, oldest first, into
+// `out_events` and return how many were written. At most 256 are kept.
+uint32_t physics_core_poll_fracture_events(FractureEvent *out_events, uint32_t capacity);
+
+// Replace the static level geometry with a JSON level description (UTF-8,
+// NUL-terminated): `{"shapes": [...], "keepWalls": false}` where each shape
+// is `{"type": "polyline", "points": [[x, y], ...], "closed": false}`,
+// `{"type": "heightfield", "originX": x, "spacing": dx, "heights": [...]}`
+// or `{"type": "tiles", "origin": [x, y], "tileSize": s, "columns": n,
+// "cells": [0, 1, ...], "materialId": atlas, "atlasColumns": c, "atlasRows": r}`.
+// Tile grids are drawn as well; see `physics_core_set_tile`. An optional
+// `"triggers": [{"name": "exit", "kind": "goal", "min": [x, y], "max": [x, y],
+// "mask": groups}, ...]` replaces the trigger zones; kinds are "sensor",
+// "goal" and "hazard". Returns false on parse or geometry errors.
+bool physics_core_load_level(const char *json);
+
+// Set one cell of tile layer `layer` (the level's tile grids in order): 0
+// clears it, `n` draws atlas cell `n - 1`. Collisions change at once, the
+// drawn tiles on the next frame. Returns false for unknown layers or cells.
+bool physics_core_set_tile(uint32_t layer, uint32_t column, uint32_t row, uint8_t value);
+
+// Cell value, or -1 for unknown layers or cells off the grid
+int32_t physics_core_get_tile(uint32_t layer, uint32_t column, uint32_t row);
+
+// Write the cell under world point (x, y) to `out_column` / `out_row`.
+// Returns false if the point is off the layer's grid.
+bool physics_core_tile_cell_at(uint32_t layer, float x, float y, uint32_t *out_column, uint32_t *out_row);
+
+// Move up to `capacity` queued zone events, oldest first, into `out_events`
+// and return how many were written. `zone` indexes the level's `triggers`.
+// At most 256 are kept.
+uint32_t physics_core_poll_trigger_events(TriggerEvent *out_events, uint32_t capacity);
+
+// `PHYSICS_CORE_OUTCOME_WON` once a watched body entered a goal zone,
+// `PHYSICS_CORE_OUTCOME_LOST` once one entered a hazard, otherwise
+// `PHYSICS_CORE_OUTCOME_PLAYING`. Loading a level starts over.
+uint32_t physics_core_level_outcome(void);
+
+// Register scene `name` from JSON: `{"level": <level as for
+// physics_core_load_level>, "bodies": [{"x": 0, "y": 0, "halfExtent": 0.05,
+// "vx": 0, "vy": 0, "bodyType": "dynamic", "groups": 1, "materialId": 0},
+// ...], "gravity": [x, y], "tweens": [{"body": index, "property": "scale",
+// "from": [0], "to": [1], "duration": 0.5, "delay": 0, "easing":
+// "backOut"}, ...], "stateMachines": [{"bodies": [index, ...], <machine as
+// for physics_core_set_state_machine>}, ...]}`; every top-level field is
+// optional. Properties are "scale", "rotation", "color", "cameraZoom" and
+// "cameraPosition"; easings are named like the `PHYSICS_CORE_EASING_*`
+// constants in camelCase. "prefabs" and "instances" are described at
+// physics_core_register_prefab, "textures" at physics_core_load_scene_asset.
+// Replaces a scene of the same name. Returns false on parse errors, invalid
+// geometry or when 64 scenes are registered.
+bool physics_core_register_scene(const char *name, const char *json);
+
+// Forget a registered scene; the running simulation is unaffected
+bool physics_core_remove_scene(const char *name);
+
+// Tear down the running simulation (bodies, joints, springs, trails) and
+// build scene `name` in its place, keeping the simulation settings. With
+// `fade_seconds` > 0 the new scene fades in from the clear color. Returns
+// false for unknown scenes.
+bool physics_core_switch_scene(const char *name, float fade_seconds);
+
+// Switch to the scene registered after the current one, or to the first
+// scene if none was loaded. Returns false after the last scene.
+bool physics_core_load_next_scene(float fade_seconds);
+
+// Register prefab `name` from JSON: `{"shape": {"type": "box",
+// "halfExtent": 0.05} or {"type": "polygon", "points": [[x, y], ...]},
+// "bodyType": "dynamic", "groups": 1, "materialId": 0, "clip":
+// {"firstFrame": 0, "frameCount": 4, "frameDuration": 0.1, "looping":
+// true}, "movement": <as for physics_core_set_movement>, "stateMachine":
+// <as for physics_core_set_state_machine>, "parts": [{"prefab": "wheel",
+// "x": 0.1, "y": -0.05, "angle": 0}, ...]}`; every field is optional.
+// Parts are other prefabs attached at an offset, looked up when the prefab
+// spawns. Scenes can bring prefabs too, under "prefabs": {name: prefab},
+// and place them with "instances": [{"prefab": name, "x": 0, "y": 0,
+// "angle": 0}, ...]. Replaces a prefab of the same name. Returns false on
+// parse errors, invalid values or when 256 prefabs are registered.
+bool physics_core_register_prefab(const char *name, const char *json);
+
+// Forget a registered prefab; bodies spawned from it stay
+bool physics_core_remove_prefab(const char *name);
+
+// Spawn prefab `name` with its parts at (x, y), turned by `angle` radians.
+// Returns the id of the prefab's own body, which the parts are attached
+// to, or 0 for unknown prefabs, parts nested too deep and unknown
+// materials; nothing is left behind then.
+uint64_t physics_core_spawn_prefab(const char *name, float x, float y, float angle);
+
+// Read assets through `load(name, len, user_data)`, which returns the bytes
+// of asset `name` ("textures/ball.png") and sets `*len`, or null for assets
+// it does not have. The bytes only need to stay valid until
+// `release(bytes, len, user_data)` is called once they are copied; `release`
+// may be null. Loaders run on the thread reading the asset with no internal
+// locks held. A null `load` goes back to the platform default: names
+// relative to the working directory on desktop, preloaded assets only
+// elsewhere.
+void physics_core_register_asset_loader(PhysicsCoreAssetFn load, PhysicsCoreAssetReleaseFn release, void *user_data);
+
+// Keep `len` bytes as asset `name`, which reads then take instead of asking
+// the loader. Returns false for invalid names, assets over 64 MiB or when
+// 1024 assets are preloaded.
+bool physics_core_preload_asset(const char *name, const uint8_t *bytes, uint32_t len);
+
+// Drop preloaded asset `name`. Returns false when it was not preloaded.
+bool physics_core_unload_asset(const char *name);
+
+// Register texture asset `name` (PNG or KTX2) as a material and return its
+// id; loading it again returns the same id. For "x.png" the compressed
+// variants "x.astc.ktx2", "x.bc.ktx2" and "x.etc2.ktx2" the device samples
+// are tried first. Returns 0 when it cannot be read or decoded.
+uint32_t physics_core_load_texture_asset(const char *name);
+
+// Read scene asset `name` ("levels/intro.json"; ".ron" files are RON where
+// supported), register it under its file name without the extension and
+// switch to it. The scene's "textures": {"3": "textures/ball.png", ...}
+// are loaded as materials, and its bodies and prefabs with "materialId": 3
+// are drawn with that texture; a texture that cannot be loaded leaves them
+// with the default one. Returns the number of bodies spawned, or -1.
+int32_t physics_core_load_scene_asset(const char *name);
+
+// Ease `property` (`PHYSICS_CORE_TWEEN_*`) of the entity from `from` to `to`
+// over `duration` simulated seconds with `easing` (`PHYSICS_CORE_EASING_*`).
+// Both arrays hold as many floats as the property takes. Camera properties
+// ignore `entity_id`. Tweens pause with the simulation; a new tween on the
+// same property replaces the running one. Returns a tween id for
+// `physics_core_cancel_tween`, or 0 for unknown entities, codes or values.
+uint64_t physics_core_animate_property(uint64_t entity_id, uint32_t property, const float *from, const float *to, float duration, uint32_t easing);
+
+// Stop a tween where it is. Returns false for unknown or finished tweens.
+bool physics_core_cancel_tween(uint64_t tween_id);
+
+// Give the entity a state machine from JSON, replacing any it has:
+// `{"initial": "patrol", "states": [{"name": "patrol", "movement": {"type":
+// "linear", "velocity": [0.2, 0]}, "clip": {"firstFrame": 0, "frameCount":
+// 4, "frameDuration": 0.1, "looping": true}, "materialId": 1,
+// "transitions": [{"to": "chase", "when": {"after": 2}}, {"to": "hurt",
+// "when": {"collision": 4}}, {"to": "flee", "when": {"event": "alarm"}}]},
+// ...]}`. Movement types are "none", "linear", "sinusoidal" (amplitude,
+// frequency, directionX), "circular" (radius, angularSpeed) and
+// "horizontalRandom" (speed, moveDuration, pauseDuration, seed) and the
+// steering movements of `physics_core_set_movement`; a state without
+// movement, clip or material keeps the previous one. Collisions
+// match the other body's groups. NULL removes the machine. Returns false on
+// parse errors, unknown states or materials and unknown entities.
+bool physics_core_set_state_machine(uint64_t entity_id, const char *json);
+
+// Send a named event to the entity's state machine, or to every machine
+// when `entity_id` is 0; it is checked against the transitions on the next
+// step. Returns how many machines received it.
+uint32_t physics_core_send_state_event(uint64_t entity_id, const char *event);
+
+// Index in `states` of the state the entity's machine is in, or -1 for
+// entities without one
+int32_t physics_core_entity_state(uint64_t entity_id);
+
+// Drive the body with a movement from JSON, starting where it is now. Path
+// movements are as for `physics_core_set_state_machine`; steering ones
+// react to the scene every step: `{"type": "seek", "target": entity_id,
+// "targetGroups": mask, "maxSpeed": 1, "maxAcceleration": 4,
+// "arriveRadius": 0.2, "avoid": 0.3}`, "flee" (with "panicRadius") and
+// `{"type": "wander", "speed": 0.5, "turnRate": 1, "maxAcceleration": 4,
+// "seed": 7, "avoid": 0.3}`. A seek or flee `target` of 0 picks the nearest
+// body in `targetGroups`; `avoid` > 0 casts feelers that far ahead to steer
+// around obstacles; `maxAcceleration` 0 turns instantly. `{"type":
+// "none"}` hands the body back to physics. Returns false on parse errors,
+// invalid values and entities without a body.
+bool physics_core_set_movement(uint64_t entity_id, const char *json);
+
+// Run `len` bytes of packed commands in one call. Each command is a
+// `PHYSICS_CORE_COMMAND_*` opcode byte followed by little-endian fields
+// with no padding: SPAWN x, y, half_extent (f32), body_type (one of
+// `PHYSICS_CORE_BODY_*`), groups (u32); IMPULSE, SET_VELOCITY and
+// SET_POSITION entity_id (u64), x, y (f32); SET_GROUPS and SET_MATERIAL
+// entity_id (u64), value (u32). Copies up to `capacity` results, one per
+// command, into `out_results`: the entity id for spawns, 1 for other
+// commands that took effect and 0 for ones that failed. Returns the number
+// of commands run, or -1 without running any when the buffer is malformed
+// or the simulation is not running.
+int32_t physics_core_submit_commands(const uint8_t *commands, uint32_t len, uint64_t *out_results, uint32_t capacity);
+
+// Share every body's transform through one region rewritten at the end of
+// each `wgpu_update`, with room for `capacity` bodies (0 frees it).
+// The region starts with a 32-byte header {front, capacity, record_size,
+// reserved: u32} followed by two buffers, each a 32-byte header {frame: u64,
+// count, total: u32, reserved} and `capacity` records {entity_id: u64, x, y,
+// rotation, vx, vy: f32, groups: u32}. Read buffer `front`; its `frame` is 0
+// while it is being rewritten, so copy it and keep the copy only if `frame`
+// did not change. `total` counts bodies that did not fit. Writes the length
+// in bytes to `out_len` and returns the start, which stays valid until the
+// next call; NULL when freed or for capacities above 65536.
+const uint8_t *physics_core_enable_transform_export(uint32_t capacity, uint32_t *out_len);
+
+// Draw the scene into another surface (same handle kinds as `wgpu_init`)
+// with its own camera centered on (center_x, center_y) and showing
+// `view_height` world units vertically. Views draw bodies only, after the
+// main surface. Returns a view id, or 0 before `wgpu_init`, for invalid
+// arguments, surfaces that cannot share the main surface's format and
+// when `PHYSICS_CORE_MAX_VIEWS` are open.
+uint32_t physics_core_add_view(void *surface_handle, uint32_t width, uint32_t height, float center_x, float center_y, float view_height);
+
+// The host may release the surface once this returns
+bool physics_core_remove_view(uint32_t view_id);
+
+// Call when the view's surface changes size; false for unknown views and
+// zero sizes
+bool physics_core_resize_view(uint32_t view_id, uint32_t width, uint32_t height);
+
+// Move a view's camera; false for unknown views and invalid cameras
+bool physics_core_set_view_camera(uint32_t view_id, float center_x, float center_y, float view_height);
+
+// Split the main surface between two players from a JSON object (UTF-8,
+// NUL-terminated) such as `{"layout": "stacked", "players": [{"follow":
+// 4294967297}, {"centerX": 2}], "bindings": [{"keyCode": 51, "player": 1,
+// "action": "up"}]}`; missing fields keep their defaults (side by side,
+// W/A/S/D and space for player 1, the D-pad and enter for player 2). NULL
+// or `null` goes back to one camera. Returns false on parse errors, invalid
+// cameras or players, or if the simulation is not running.
+bool physics_core_set_split_screen(const char *json);
+
+// Hand the body to player 1 or 2 under split screen, making it
+// controllable; 0 lets it answer to both players again. Returns false for
+// unknown bodies and other player numbers.
+bool physics_core_set_player(uint64_t entity_id, uint32_t player);
+
+// Encode every body's position, rotation and velocities for a remote
+// simulation, keeping only what changed since `baseline_tick`: a tick
+// returned by the remote `physics_core_apply_snapshot` (0, or a tick more
+// than 64 snapshots old, gives a full snapshot). Returns the length in
+// bytes; the snapshot is copied to `out_bytes` only if it fits in
+// `capacity`, so call again with a larger buffer otherwise. Returns 0 if
+// the simulation is not running.
+uint32_t physics_core_get_snapshot_delta(uint64_t baseline_tick, uint8_t *out_bytes, uint32_t capacity);
+
+// Apply `len` bytes from the remote `physics_core_get_snapshot_delta`:
+// velocities are taken as sent, small position and rotation errors are
+// eased out over the next steps and large ones jump. Returns the
+// snapshot's tick, to send back as the next baseline, or 0 for malformed
+// or stale snapshots and baselines this side never received.
+uint64_t physics_core_apply_snapshot(const uint8_t *bytes, uint32_t len);
+
+// Keep `window` steps (at most 600; 0 turns rollback off) of input and
+// predicted body states so `physics_core_reconcile_snapshot` can redo them.
+// A prediction stands while it is within `tolerance` (m, radians and m/s)
+// of the authority. Raises the rewind history to `window` + 1 steps.
+// Returns false for invalid arguments or if the simulation is not running.
+bool physics_core_set_prediction(uint32_t window, float tolerance);
+
+// Mark a body as driven locally ahead of the authority, or clear the mark.
+// Returns false for unknown bodies.
+bool physics_core_set_predicted(uint64_t entity_id, bool predicted);
+
+// Like `physics_core_apply_snapshot`, but predicted bodies are checked
+// against the snapshot instead of eased toward it: if one was off, the
+// simulation rewinds to the snapshot's tick, takes its state and re-runs
+// the steps since with the input they consumed. Both sides must step in
+// lockstep. Returns the snapshot's tick, or 0 when it could not be applied.
+uint64_t physics_core_reconcile_snapshot(const uint8_t *bytes, uint32_t len);
+
+// Pull the body's center toward (world_x, world_y) with a spring of
+// `stiffness` N/m and `damping` N·s/m. Returns a spring id for
+// `physics_core_update_spring` / `physics_core_remove_spring`, or 0 for
+// unknown bodies and negative or non-finite arguments.
+uint64_t physics_core_create_spring_to_point(uint64_t entity_id, float world_x, float world_y, float stiffness, float damping);
+
+// Move the spring's point and change its strength; meant to be called every
+// frame while dragging. Returns false for unknown springs.
+bool physics_core_update_spring(uint64_t spring_id, float world_x, float world_y, float stiffness, float damping);
+
+// Springs also go away with their body
+bool physics_core_remove_spring(uint64_t spring_id);
+
+// Spawn a box from a scene body (`{"x": 0, "y": 1, "halfExtent": 0.1,
+// "angle": 0, "vx": 0, "vy": 0, "bodyType": "dynamic", "groups": 1,
+// "materialId": 0}`, everything optional) as a step `physics_core_undo`
+// can take back. Returns its id, or 0 on parse errors and before init.
+uint64_t physics_core_edit_spawn_body(const char *body_json);
+
+// Make the box `entity_id` match a scene body (see
+// `physics_core_edit_spawn_body`; fields left out keep their defaults, not
+// the box's values) as an undoable step. With `merge` a change right after
+// another change to the same box joins its step, so a slider dragged over
+// many frames undoes at once. Returns false on parse errors, unknown ids
+// and other shapes.
+bool physics_core_edit_set_body(uint64_t entity_id, const char *body_json, bool merge);
+
+// Remove the box `entity_id` as an undoable step; undoing spawns it again
+// under a new id. Returns false for unknown ids and other shapes.
+bool physics_core_edit_remove_body(uint64_t entity_id);
+
+// `physics_core_create_spring_to_point` as an undoable step
+uint64_t physics_core_edit_attach_spring(uint64_t entity_id, float world_x, float world_y, float stiffness, float damping);
+
+// The box `entity_id` as a scene body JSON object, for editing and passing
+// back to `physics_core_edit_set_body`; null for unknown ids and other
+// shapes. Release with `physics_core_free_string`.
+char *physics_core_get_body_desc(uint64_t entity_id);
+
+// Take back the last edit step. Returns false when there is none, or when
+// what it refers to has gone some other way, which drops it.
+bool physics_core_undo(void);
+
+// Carry out the last undone step again; any new edit drops the steps that
+// could be redone
+bool physics_core_redo(void);
+
+// Forget every step; switching scenes and resetting do this too
+void physics_core_clear_history(void);
+
+// `{"undo": 2, "redo": 0, "undoLabel": "change", "redoLabel": null}`: the
+// steps that can be undone and redone, and what the next ones are ("spawn",
+// "remove", "change" or "spring"). Release with `physics_core_free_string`.
+char *physics_core_get_history_state(void);
+
+// Spawn a soft blob of `segments` nodes (3..=64) on a circle of `radius`
+// around (x, y), with default springs and pressure. Returns the blob id for
+// `physics_core_remove_soft_body`, or 0 for invalid arguments.
+uint64_t physics_core_spawn_soft_body(float x, float y, float radius, uint32_t segments);
+
+// Spawn a soft blob from a JSON object (UTF-8, NUL-terminated) such as
+// `{"x": 0, "y": 0.5, "radius": 0.25, "segments": 20, "stiffness": 400,
+// "damping": 2, "pressure": 40, "nodeHalfExtent": 0.02, "color": [1, 0.5, 0.6, 1]}`;
+// missing fields take their defaults. Returns the blob id, or 0 on errors.
+uint64_t physics_core_spawn_soft_body_json(const char *json);
+
+// Remove the blob with its nodes and springs. Returns false for unknown ids.
+bool physics_core_remove_soft_body(uint64_t soft_body_id);
+
+// Despawn the body, with its colliders and joints, after `seconds`; 0 or
+// less cancels a pending lifetime. Returns false for unknown ids.
+bool physics_core_set_lifetime(uint64_t entity_id, float seconds);
+
+// While enabled, dynamic bodies whose center leaves the rectangle are
+// despawned. Survives scene resets. Returns false for an empty rectangle.
+bool physics_core_set_despawn_bounds(bool enabled, float min_x, float min_y, float max_x, float max_y);
+
+// Add a quad of `half_extent` at (x, y) with no body, drawn with
+// `material_id` (0 for the default). Returns its entity id for
+// `physics_core_attach_entity`, or 0 on failure.
+uint64_t physics_core_spawn_decoration(float x, float y, float half_extent, uint32_t material_id);
+
+// Despawn a decoration; bodies are refused. Returns false for unknown ids.
+bool physics_core_remove_decoration(uint64_t entity_id);
+
+// Make `child` follow `parent` at (offset_x, offset_y) in the parent's
+// frame, turned by `rotation` radians. Either may be a body or a decoration;
+// a body child is kinematic while attached. Re-attaching replaces the
+// parent. Decorations are despawned with their parent. Returns false for
+// unknown ids and for attachments that would form a loop.
+bool physics_core_attach_entity(uint64_t child_id, uint64_t parent_id, float offset_x, float offset_y, float rotation);
+
+// Leave the child where it is; a body child turns dynamic again if it was.
+// Returns false if it was not attached.
+bool physics_core_detach_entity(uint64_t child_id);
+
+// Report collisions closing faster than `min_speed` m/s as audio events,
+// at full volume from `max_speed`; `enabled` false stops and discards
+// queued events. Survives scene resets. Returns false for invalid speeds.
+bool physics_core_set_audio_events(bool enabled, float min_speed, float max_speed);
+
+// World position of the listener, usually the camera, for volume and pan
+bool physics_core_set_audio_listener(float x, float y);
+
+// Full volume within `reference_distance` of the listener, divided by
+// `1 + rolloff * (distance / reference_distance - 1)` beyond it, and no
+// events past `max_distance`
+bool physics_core_set_audio_attenuation(float reference_distance, float max_distance, float rolloff);
+
+// Move up to `capacity` queued events, oldest first, into `out_events` and
+// return how many were written. At most 256 events are kept between polls.
+uint32_t physics_core_poll_audio_events(AudioEvent *out_events, uint32_t capacity);
+
+// Call `callback(events, count, user_data)` after every step that produced
+// events, with no internal locks held; the events are then not queued for
+// polling. `events` is only valid during the call. Null restores polling.
+void physics_core_set_audio_callback(PhysicsCoreAudioFn callback, void *user_data);
+
+// Preallocate `capacity` projectiles (at most 4096) of `radius`, replacing
+// the current pool; 0 removes it. Fired projectiles reuse these bodies, so
+// rapid fire never grows the scene. Returns false for invalid arguments.
+bool physics_core_configure_projectiles(uint32_t capacity, float radius);
+
+// Launch a pooled projectile from (origin_x, origin_y). It is parked again
+// after `ttl` seconds (0 for never) or on its first collision. Returns its
+// entity id, or 0 when there is no pool or every projectile is in flight.
+uint64_t physics_core_fire_projectile(float origin_x, float origin_y, float vx, float vy, float ttl);
+
+// Fill `out_stats` with the pool's occupancy and counters (all 0 without a pool)
+bool physics_core_get_projectile_stats(ProjectileStats *out_stats);
+
+// Replace the body's group bits: bit `n` set puts it in group `n`. Bulk
+// calls below act on bodies sharing at least one bit with their mask, or on
+// every body for `PHYSICS_CORE_GROUP_ALL`. Returns false for unknown ids.
+bool physics_core_set_body_groups(uint64_t entity_id, uint32_t groups);
+
+// Group bits of the body; 0 for unknown ids
+uint32_t physics_core_get_body_groups(uint64_t entity_id);
+
+// Copy up to `capacity` ids of bodies matching `mask` into `out_ids`, returning the total count
+uint32_t physics_core_get_group_ids(uint32_t mask, uint64_t *out_ids, uint32_t capacity);
+
+// Remove every body matching `mask`. Returns the number removed.
+uint32_t physics_core_despawn_group(uint32_t mask);
+
+// Freeze the dynamic bodies matching `mask` in place, or let them move on
+// with the velocity they had. Returns the number of bodies changed.
+uint32_t physics_core_set_group_paused(uint32_t mask, bool paused);
+
+// Returns the number of bodies pushed; paused and non-dynamic ones are skipped
+uint32_t physics_core_apply_impulse_to_group(uint32_t mask, float impulse_x, float impulse_y);
+
+// Copy up to `capacity` ids of bodies inside the rectangle into `out_ids`, returning the total hit count
+uint32_t physics_core_query_aabb(float min_x, float min_y, float max_x, float max_y, uint64_t *out_ids, uint32_t capacity);
+
+// Copy up to `capacity` ids of bodies inside the circle into `out_ids`, returning the total hit count
+uint32_t physics_core_query_circle(float center_x, float center_y, float radius, uint64_t *out_ids, uint32_t capacity);
+
+// Like `physics_core_query_aabb`, counting only bodies matching `mask`
+uint32_t physics_core_query_aabb_in_groups(float min_x, float min_y, float max_x, float max_y, uint32_t mask, uint64_t *out_ids, uint32_t capacity);
+
+// Like `physics_core_query_circle`, counting only bodies matching `mask`
+uint32_t physics_core_query_circle_in_groups(float center_x, float center_y, float radius, uint32_t mask, uint64_t *out_ids, uint32_t capacity);
+
+// Like `physics_core_query_aabb`, but each hit carries the body's state
+uint32_t physics_core_query_aabb_hits(float min_x, float min_y, float max_x, float max_y, QueryHit *out_hits, uint32_t capacity);
+
+// Like `physics_core_query_circle`, but each hit carries the body's state
+uint32_t physics_core_query_circle_hits(float center_x, float center_y, float radius, QueryHit *out_hits, uint32_t capacity);
+
+// Simulate only bodies within `load_radius` chunks of the focus; chunks unload past `unload_radius`.
+// Both radii are capped at 64 chunks.
+void physics_core_set_streaming(bool enabled, float chunk_size, uint32_t load_radius, uint32_t unload_radius);
+
+void physics_core_set_streaming_focus_point(float x, float y);
+
+void physics_core_set_streaming_focus_entity(uint64_t entity_id);
+
+// Center streaming on the main camera again, the default
+void physics_core_set_streaming_focus_camera(void);
+
+// Run `callback(point, dt, user_data)` every frame at the given point with no
+// internal locks held, so it may call back into this API.
+// point: 0 = pre-physics, 1 = post-physics, 2 = pre-render, 3 = once at the
+// first frame after a reset.
+// Returns the hook id, or 0 if the point is unknown or `callback` is null.
+uint32_t physics_core_register_hook(uint32_t point, PhysicsCoreHookFn callback, void *user_data);
+
+// Remove a hook registered with `physics_core_register_hook`
+bool physics_core_unregister_hook(uint32_t hook_id);
+
+#if defined(PHYSICS_CORE_SCRIPTING)
+// Load a Rhai gameplay script (UTF-8, NUL-terminated) defining `on_frame(dt)` /
+// `on_collision(a, b)`, replacing the current one. Returns false on compile or run errors.
+bool physics_core_load_script(const char *source);
+#endif
+
+#if defined(PHYSICS_CORE_SCRIPTING)
+// Stop running the current script
+void physics_core_unload_script(void);
+#endif
+
+// Show `pixels`, `height` rows of `width` RGBA8 pixels starting `stride`
+// pixels apart (0 for `width`), behind the scene until replaced or
+// cleared; see `physics_core_set_background_fit` for how it is placed.
+// Copied before returning. Returns false before `wgpu_init` and for empty
+// or oversized images.
+bool physics_core_set_background_rgba(const uint8_t *pixels, uint32_t width, uint32_t height, uint32_t stride);
+
+#if (defined(__ANDROID__) && defined(PHYSICS_CORE_ANDROID_HARDWARE_BUFFER))
+// Show an Android `AHardwareBuffer` behind the scene, copied before
+// returning. The buffer stays the caller's; it must be RGBA8, RGBX8 or
+// RGB8 and allow CPU reads (`AHARDWAREBUFFER_USAGE_CPU_READ_OFTEN`).
+// Returns false before `wgpu_init` and for buffers that cannot be read.
+// Needs the `android_hardware_buffer` feature, API level 26.
+bool physics_core_set_background_hardware_buffer(void *buffer);
 #endif
+
+// Remove the background image
+void physics_core_clear_background(void);
+
+// How the background covers the view: 0 = fill, cropping the image
+// (default), 1 = fit, leaving bars of the clear color, 2 = stretch.
+// Returns false for unknown values and before `wgpu_init`.
+bool physics_core_set_background_fit(int32_t fit);
+
+// Unobstructed area of the main surface as insets from each edge, in
+// surface pixels: Android `WindowInsets` (system bars and display cutout),
+// the iOS safe area, or CSS `env(safe-area-inset-*)` times the device pixel
+// ratio. The egui windows, screen-anchored HUD text and the minimap stay
+// inside it; with `inset_world` the scene is also drawn, and pointers
+// mapped, inside it instead of under the insets. Returns false for
+// negative or non-finite insets.
+bool physics_core_set_safe_area_insets(float left, float top, float right, float bottom, bool inset_world);
+
+// Surface pixels per logical point: Android `DisplayMetrics.density`, iOS
+// `contentScaleFactor`, or the browser's `devicePixelRatio`; the desktop app
+// takes it from its window. Sets the egui scale, how far a touch moves before
+// it stops being a tap, and the 3D orbit rate per drag. Pointer and touch
+// positions stay in surface pixels, except through the wasm functions, which
+// take CSS pixels. May be called before `wgpu_init` and again whenever the
+// density changes. Returns false unless finite and above 0.
+bool physics_core_set_scale_factor(float scale_factor);
+
+// Report the device's power state to the performance governor: `battery`
+// from 0 to 1 (negative when unknown), whether it is `charging`, the
+// `thermal_status` as Android's `PowerManager.THERMAL_STATUS_*` (0 none to 6
+// shutdown; iOS thermal states nominal, fair, serious and critical are 0, 1,
+// 3 and 4) and the OS `low_power_mode`. Low power mode, a moderate thermal
+// status or a battery under 20% caps rendering at 45 FPS and the solver at
+// 2 iterations, halves fracture debris and water droplets and lets bodies
+// sleep sooner; a severe thermal status or a battery under 10% caps at 30
+// FPS and 1 iteration and throws no debris. Throttling starts on the next
+// update and eases only after the state has allowed it for 10 s. Returns
+// false for a battery above 1 or an unknown thermal status.
+bool physics_core_set_power_state(float battery, bool charging, int32_t thermal_status, bool low_power_mode);
+
+// Change only the low power mode of the reported power state
+void physics_core_set_low_power_mode(bool enabled);
+
+// Disabling the governor lifts every limit at once (default enabled)
+void physics_core_set_governor_enabled(bool enabled);
+
+// The governor's reported power state, throttle level and limits in force
+// as JSON, e.g. `{"enabled": true, "level": "light", "throttle": {"maxFps":
+// 45, ...}, ...}`. Release with `physics_core_free_string`.
+char *physics_core_get_governor_stats(void);
+
+void physics_core_set_clear_color(float r, float g, float b, float a);
+
+// Requested MSAA sample count (1, 2 or 4); applied at the next `wgpu_init`
+void physics_core_set_msaa_samples(uint32_t samples);
+
+// HDR bloom post-process (requires compute support; ignored otherwise)
+void physics_core_set_bloom(bool enabled, float threshold, float intensity);
+
+void physics_core_set_contact_shadows(bool enabled, float intensity, float spread);
+
+// Skip uploading and drawing bodies outside every camera's view (on by
+// default). Frame stats count the bodies left out.
+void physics_core_set_culling(bool enabled);
+
+// Cull in a compute pass and draw indirectly instead of culling on the CPU
+// (off by default). Returns false where the GPU cannot (WebGL2, or before
+// `wgpu_init`), which keeps the CPU path. Bodies of one material may then
+// overlap in any order, and frame stats count them all as drawn.
+bool physics_core_set_gpu_culling(bool enabled);
+
+// Save the control panel layout, render toggles, 2D camera and solver
+// settings. They go to `physicsfx_settings.json` in the working directory
+// unless `physics_core_set_settings_directory` picked another place.
+// `wgpu_shutdown` saves too. Returns false if the write fails.
+bool physics_core_save_settings(void);
+
+// Apply the saved settings again; `wgpu_init` already does. Returns false
+// when nothing was saved.
+bool physics_core_restore_settings(void);
+
+// Keep settings in `dir` (UTF-8, NUL-terminated), which is created if
+// missing; NULL keeps nothing. Call before `wgpu_init` for the settings to
+// be restored there. Returns false if the directory cannot be created.
+bool physics_core_set_settings_directory(const char *dir);
+
+// Show a minimap in a corner of the main surface from a JSON object (UTF-8,
+// NUL-terminated) such as `{"corner": "bottomLeft", "width": 240,
+// "height": 160, "visibleGroups": 2}`; missing fields keep their defaults.
+// NULL or `null` hides it. Returns false on parse errors and invalid sizes
+// or colors.
+bool physics_core_set_minimap(const char *json);
+
+// Start recording: from the next update on, each update advances by a fixed
+// `1 / fps` and each rendered frame is read back as tightly packed RGBA8
+// instead of being presented. `json` (UTF-8, NUL-terminated, NULL for the
+// defaults) is an object such as `{"fps": 30, "directory": "out",
+// "prefix": "frame_", "maxFrames": 300}`; with a directory, frames are
+// written there as numbered PNGs. `callback(index, width, height, rgba,
+// user_data)`, if given, is called with each frame with no internal locks
+// held; `rgba` is only valid during the call. Returns false before
+// `wgpu_init`, for surface formats that cannot be read back, on parse
+// errors and when there is neither a directory nor a callback.
+bool physics_core_start_capture(const char *json, PhysicsCoreFrameFn callback, void *user_data);
+
+// Stop recording and return to the regular frame timing. Returns the
+// number of frames captured, 0 if no capture was running.
+uint64_t physics_core_stop_capture(void);
+
+// Whether a capture is running; it also ends on its own after `maxFrames`
+// or when a frame cannot be written
+bool physics_core_is_capturing(void);
+
+// Screenshot of the next frame rendered and presented, the egui panel
+// included when the winit app draws it. With `path` (UTF-8,
+// NUL-terminated) it is written there as a PNG; with `callback`, that is
+// called as for `physics_core_start_capture` with frame index 0. Either or
+// both may be given. Returns false before `wgpu_init`, for surface formats
+// that cannot be read back and when there is neither a path nor a callback.
+bool physics_core_take_screenshot(const char *path, PhysicsCoreFrameFn callback, void *user_data);
+
+// Render and present a frame now and return it as a PNG, without the egui
+// panel. Writes the length to `out_len` and returns the bytes, to be
+// released with `physics_core_free_bytes`; NULL before `wgpu_init` and for
+// surface formats that cannot be read back.
+uint8_t *physics_core_take_screenshot_png(uint32_t *out_len);
+
+// Key that saves a screenshot in the winit app, as a winit `KeyCode` (F12
+// by default, negative to disable), and the directory (UTF-8,
+// NUL-terminated, created if missing; NULL keeps the current one, at first
+// the working directory) the PNGs go to. Returns false if the directory
+// cannot be created.
+bool physics_core_set_screenshot_key(int32_t key_code, const char *directory);
+
+// Animated GIF of the last `seconds` of the rewind history at `fps` (1 to
+// 50), `scale` (above 0, at most 1) times the surface size, looping. The
+// bodies are drawn as each history entry had them; the simulation itself is
+// not touched, and the clip is only as long as the history. Writes the
+// length to `out_len` and returns the bytes, to be released with
+// `physics_core_free_bytes`; NULL before `wgpu_init`, for invalid
+// arguments or without history.
+uint8_t *physics_core_export_clip(float seconds, uint32_t fps, float scale, uint32_t *out_len);
+
+// Show `text` (UTF-8, NUL-terminated) as HUD label `id` with its top-left
+// corner at pixel (x, y), origin top-left, `size` pixels tall, in `color`
+// (0xRRGGBBAA). Replaces any label with the same id and stays until
+// cleared; `\n` starts a new line. Returns false for invalid arguments or
+// when 512 labels already exist.
+bool physics_core_draw_text(uint64_t id, const char *text, float x, float y, float size, uint32_t color);
+
+// Like `physics_core_draw_text`, centered above world point (x, y) with
+// `size` in world units
+bool physics_core_draw_world_text(uint64_t id, const char *text, float x, float y, float size, uint32_t color);
+
+// World-space label following the entity, centered above its center plus
+// (offset_x, offset_y). Hidden while the entity does not exist.
+bool physics_core_draw_entity_text(uint64_t id, uint64_t entity_id, const char *text, float offset_x, float offset_y, float size, uint32_t color);
+
+// Remove label `id`; returns false if there was none
+bool physics_core_clear_text(uint64_t id);
+
+void physics_core_clear_all_text(void);
+
+// Draw a fading trail behind the body through its last `length` recorded
+// positions (clamped to 2..=1024) in `color` (0xRRGGBBAA). Calling it again
+// restyles the trail. Returns false for unknown ids.
+bool physics_core_enable_trail(uint64_t entity_id, uint32_t length, uint32_t color);
+
+// Remove the body's trail. Returns false if it had none.
+bool physics_core_disable_trail(uint64_t entity_id);
+
+// Predict where the body goes over up to `steps` fixed steps (at most 600)
+// if launched at (vx, vy) now, without changing the simulation. Writes up to
+// `capacity` points into `out_points` as x, y pairs (2 * `capacity` floats)
+// and returns how many were predicted; 0 for unknown or non-dynamic bodies.
+// `show` also draws the arc as dots until the next prediction or
+// `physics_core_clear_trajectory_preview`.
+uint32_t physics_core_predict_trajectory(uint64_t entity_id, float vx, float vy, uint32_t steps, bool show, float *out_points, uint32_t capacity);
+
+void physics_core_clear_trajectory_preview(void);
+
+// Drag-to-launch with the pointer: press on a dynamic body, drag away to
+// aim with the predicted arc drawn, release to launch. Drag flings are off
+// while it is on; turning it off drops any aim in progress.
+void physics_core_enable_slingshot_mode(bool enabled);
+
+// `max_impulse` (N·s) is reached at a pull of `max_pull` m and caps longer
+// pulls; `arc_samples` predicted steps are drawn while aiming (at most 600,
+// 0 hides the arc). Returns false unless the impulse and pull are finite
+// and > 0.
+bool physics_core_set_slingshot_params(float max_impulse, float max_pull, uint32_t arc_samples);
+
+// Shake the camera; `strength` is trauma in 0..1 added to the current
+// shake, which grows with its square and decays over time. Returns false
+// for negative or non-finite strengths.
+bool physics_core_add_camera_shake(float strength);
+
+// Zoom in by `amount` (0.2 is 20% closer, negative zooms out) and ease
+// back. Punches add up to between -0.5 and 1.
+bool physics_core_punch_camera_zoom(float amount);
+
+// Shake at full trauma: up to `max_offset` world units of displacement and
+// `max_rotation` radians of roll, varying at `frequency` Hz
+bool physics_core_set_camera_shake(float max_offset, float max_rotation, float frequency);
+
+// Trauma lost per second (default 1.5) and the zoom punch decay rate per
+// second (default 6); both must be > 0
+bool physics_core_set_camera_effects_decay(float trauma_decay, float zoom_decay);
+
+// Shake on the hardest collision of each step above `min_impulse` N·s,
+// adding `trauma_per_impulse` trauma per N·s above it. Off (0) by default.
+bool physics_core_set_camera_impact_shake(float min_impulse, float trauma_per_impulse);
+
+// Stop any shake and zoom punch immediately
+void physics_core_clear_camera_effects(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PHYSICS_CORE_H */
@@ -0,0 +1,7 @@
+fn main() {
+    // os_log is a C macro, so it is reached through a small C wrapper
+    println!("cargo:rerun-if-changed=src/oslog.c");
+    if std::env::var("CARGO_CFG_TARGET_VENDOR").as_deref() == Ok("apple") {
+        cc::Build::new().file("src/oslog.c").compile("tracing_oslog");
+    }
+}
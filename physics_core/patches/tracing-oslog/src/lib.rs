@@ -1,23 +1,214 @@
-use tracing_core::Subscriber;
-use tracing_subscriber::layer::Layer;
+//! `tracing` layer writing to Apple's unified logging (os_log)
+//!
+//! Stands in for the crates.io `tracing-oslog`, whose bindgen step needs the
+//! Apple SDK headers at build time; the one C call goes through `oslog.c`
+//! instead. Events are logged under the layer's subsystem and category,
+//! prefixed with the spans they happen in, e.g.
+//! `frame > pass{name=Main}: Surface timeout`. On other targets the layer
+//! does nothing.
+
+use std::fmt::{self, Write};
+
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id, Record};
+use tracing_core::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
 use tracing_subscriber::registry::LookupSpan;
 
-pub struct OsLogger;
+pub struct OsLogger {
+    log: sys::Log,
+}
 
 impl OsLogger {
-    pub fn new<S, C>(_subsystem: S, _category: C) -> Self
+    /// Log under `subsystem` (usually a reverse-DNS bundle id) and `category`
+    pub fn new<S, C>(subsystem: S, category: C) -> Self
     where
         S: AsRef<str>,
         C: AsRef<str>,
     {
-        Self
+        Self { log: sys::Log::new(subsystem.as_ref(), category.as_ref()) }
     }
 }
 
+/// The process's default log, with no subsystem or category
 impl Default for OsLogger {
     fn default() -> Self {
-        Self
+        Self { log: sys::Log::default_log() }
+    }
+}
+
+/// A span's fields as `a=1 b=2`, kept in its extensions
+struct SpanFields(String);
+
+#[derive(Default)]
+struct FieldWriter {
+    message: String,
+    fields: String,
+}
+
+impl FieldWriter {
+    fn separator(&mut self) {
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+    }
+}
+
+impl Visit for FieldWriter {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.separator();
+            let _ = write!(self.fields, "{}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            self.separator();
+            let _ = write!(self.fields, "{}={:?}", field.name(), value);
+        }
     }
 }
 
-impl<S> Layer<S> for OsLogger where S: Subscriber + for<'a> LookupSpan<'a> {}
+impl<S> Layer<S> for OsLogger
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !sys::ENABLED {
+            return;
+        }
+        let mut writer = FieldWriter::default();
+        attrs.record(&mut writer);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(writer.fields));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if !sys::ENABLED {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() else {
+            return;
+        };
+        let mut writer = FieldWriter { fields: std::mem::take(fields), ..Default::default() };
+        values.record(&mut writer);
+        *fields = writer.fields;
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if !sys::ENABLED {
+            return;
+        }
+        let mut line = String::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if !line.is_empty() {
+                    line.push_str(" > ");
+                }
+                line.push_str(span.name());
+                if let Some(SpanFields(fields)) = span.extensions().get::<SpanFields>() {
+                    if !fields.is_empty() {
+                        let _ = write!(line, "{{{}}}", fields);
+                    }
+                }
+            }
+            if !line.is_empty() {
+                line.push_str(": ");
+            }
+        }
+        let mut writer = FieldWriter::default();
+        event.record(&mut writer);
+        line.push_str(&writer.message);
+        if !writer.fields.is_empty() {
+            if !writer.message.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&writer.fields);
+        }
+        self.log.emit(*event.metadata().level(), &line);
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+mod sys {
+    use std::ffi::{c_char, c_void, CString};
+
+    use tracing_core::Level;
+
+    pub const ENABLED: bool = true;
+
+    // os_log_type_t
+    const TYPE_DEFAULT: u8 = 0x00;
+    const TYPE_INFO: u8 = 0x01;
+    const TYPE_DEBUG: u8 = 0x02;
+    const TYPE_ERROR: u8 = 0x10;
+
+    extern "C" {
+        fn tracing_oslog_create(subsystem: *const c_char, category: *const c_char) -> *mut c_void;
+        fn tracing_oslog_default() -> *mut c_void;
+        fn tracing_oslog_emit(log: *mut c_void, kind: u8, message: *const c_char);
+    }
+
+    /// An `os_log_t`; these are thread-safe and never released
+    pub struct Log(*mut c_void);
+
+    unsafe impl Send for Log {}
+    unsafe impl Sync for Log {}
+
+    fn c_string(text: &str) -> CString {
+        CString::new(text.replace('\0', " ")).unwrap_or_default()
+    }
+
+    impl Log {
+        pub fn new(subsystem: &str, category: &str) -> Self {
+            let (subsystem, category) = (c_string(subsystem), c_string(category));
+            Self(unsafe { tracing_oslog_create(subsystem.as_ptr(), category.as_ptr()) })
+        }
+
+        pub fn default_log() -> Self {
+            Self(unsafe { tracing_oslog_default() })
+        }
+
+        pub fn emit(&self, level: Level, message: &str) {
+            let kind = match level {
+                Level::ERROR => TYPE_ERROR,
+                Level::WARN => TYPE_DEFAULT,
+                Level::INFO => TYPE_INFO,
+                _ => TYPE_DEBUG,
+            };
+            let message = c_string(message);
+            unsafe { tracing_oslog_emit(self.0, kind, message.as_ptr()) }
+        }
+    }
+}
+
+#[cfg(not(target_vendor = "apple"))]
+mod sys {
+    use tracing_core::Level;
+
+    pub const ENABLED: bool = false;
+
+    pub struct Log;
+
+    impl Log {
+        pub fn new(_subsystem: &str, _category: &str) -> Self {
+            Log
+        }
+
+        pub fn default_log() -> Self {
+            Log
+        }
+
+        pub fn emit(&self, _level: Level, _message: &str) {}
+    }
+}
@@ -0,0 +1,14 @@
+#include <os/log.h>
+#include <stdint.h>
+
+os_log_t tracing_oslog_create(const char *subsystem, const char *category) {
+    return os_log_create(subsystem, category);
+}
+
+os_log_t tracing_oslog_default(void) {
+    return OS_LOG_DEFAULT;
+}
+
+void tracing_oslog_emit(os_log_t log, uint8_t type, const char *message) {
+    os_log_with_type(log, (os_log_type_t)type, "%{public}s", message);
+}
@@ -0,0 +1,38 @@
+// External image (camera or video frame) filling the view behind the scene
+
+struct BackgroundParams {
+    // u0, v0, u1, v1 of the image across the view; outside 0..1 is left clear
+    uv_rect: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var image_tex: texture_2d<f32>;
+@group(0) @binding(1)
+var image_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: BackgroundParams;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Single triangle covering the whole viewport
+@vertex
+fn vs_background(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = mix(params.uv_rect.xy, params.uv_rect.zw, uv);
+    return out;
+}
+
+@fragment
+fn fs_background(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Sample before discarding to keep the sample in uniform control flow
+    let color = textureSample(image_tex, image_sampler, clamp(in.uv, vec2<f32>(0.0), vec2<f32>(1.0)));
+    if (any(in.uv < vec2<f32>(0.0)) || any(in.uv > vec2<f32>(1.0))) {
+        discard;
+    }
+    return vec4<f32>(color.rgb, 1.0);
+}
@@ -0,0 +1,69 @@
+// Bloom compute passes: bright-pass threshold with 2x downsample, then a
+// separable 9-tap gaussian blur (horizontal and vertical entry points).
+
+struct BloomParams {
+    threshold: f32,
+    intensity: f32,
+    _pad0: f32,
+    _pad1: f32,
+};
+
+@group(0) @binding(0)
+var src_tex: texture_2d<f32>;
+@group(0) @binding(1)
+var dst_tex: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(2)
+var<uniform> params: BloomParams;
+
+fn load_clamped(coord: vec2<i32>) -> vec3<f32> {
+    let max_coord = vec2<i32>(textureDimensions(src_tex)) - vec2<i32>(1, 1);
+    return textureLoad(src_tex, clamp(coord, vec2<i32>(0, 0), max_coord), 0).rgb;
+}
+
+@compute @workgroup_size(8, 8)
+fn threshold_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let dst_size = textureDimensions(dst_tex);
+    if id.x >= dst_size.x || id.y >= dst_size.y {
+        return;
+    }
+
+    // Average the 2x2 source block this texel covers
+    let base = vec2<i32>(id.xy * 2u);
+    var color = load_clamped(base);
+    color += load_clamped(base + vec2<i32>(1, 0));
+    color += load_clamped(base + vec2<i32>(0, 1));
+    color += load_clamped(base + vec2<i32>(1, 1));
+    color = color * 0.25;
+
+    // Soft bright-pass: keep only the part of the luminance above the threshold
+    let luma = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let contribution = max(luma - params.threshold, 0.0) / max(luma, 0.0001);
+    textureStore(dst_tex, vec2<i32>(id.xy), vec4<f32>(color * contribution, 1.0));
+}
+
+fn blur(id: vec3<u32>, direction: vec2<i32>) {
+    let dst_size = textureDimensions(dst_tex);
+    if id.x >= dst_size.x || id.y >= dst_size.y {
+        return;
+    }
+
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    let center = vec2<i32>(id.xy);
+    var color = load_clamped(center) * weights[0];
+    for (var i = 1; i < 5; i = i + 1) {
+        let offset = direction * i;
+        color += load_clamped(center + offset) * weights[i];
+        color += load_clamped(center - offset) * weights[i];
+    }
+    textureStore(dst_tex, center, vec4<f32>(color, 1.0));
+}
+
+@compute @workgroup_size(8, 8)
+fn blur_h_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    blur(id, vec2<i32>(1, 0));
+}
+
+@compute @workgroup_size(8, 8)
+fn blur_v_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    blur(id, vec2<i32>(0, 1));
+}
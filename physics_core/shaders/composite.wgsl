@@ -0,0 +1,39 @@
+// Fullscreen composite of the HDR scene and the blurred bloom texture
+
+struct BloomParams {
+    threshold: f32,
+    intensity: f32,
+    _pad0: f32,
+    _pad1: f32,
+};
+
+@group(0) @binding(0)
+var scene_tex: texture_2d<f32>;
+@group(0) @binding(1)
+var bloom_tex: texture_2d<f32>;
+@group(0) @binding(2)
+var linear_sampler: sampler;
+@group(0) @binding(3)
+var<uniform> params: BloomParams;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Single triangle covering the whole viewport
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = textureSample(scene_tex, linear_sampler, in.uv).rgb;
+    let bloom = textureSample(bloom_tex, linear_sampler, in.uv).rgb;
+    return vec4<f32>(scene + bloom * params.intensity, 1.0);
+}
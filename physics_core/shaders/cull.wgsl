@@ -0,0 +1,92 @@
+// Same layout as the Rust Instance
+struct Instance {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    scale: f32,
+    rotation: f32,
+    uv_offset: vec2<f32>,
+    uv_scale: vec2<f32>,
+    tint: u32,
+    lod: u32,
+    size: vec2<f32>,
+    slice_min: vec2<f32>,
+    slice_max: vec2<f32>,
+    parallax: vec2<f32>,
+};
+
+struct CullParams {
+    // World rect of each camera as min.xy, max.xy
+    rects: array<vec4<f32>, 10>,
+    rect_count: u32,
+    batch_count: u32,
+    instance_count: u32,
+    // Bounding radius is scale * longest side * sqrt(2) * grow + shift
+    grow: f32,
+    shift: f32,
+};
+
+struct Batch {
+    first_instance: u32,
+    instance_count: u32,
+};
+
+// wgpu's DrawIndexedIndirectArgs
+struct DrawArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: CullParams;
+@group(0) @binding(1)
+var<storage, read> instances: array<Instance>;
+@group(0) @binding(2)
+var<storage, read> batches: array<Batch>;
+@group(0) @binding(3)
+var<storage, read_write> visible: array<Instance>;
+@group(0) @binding(4)
+var<storage, read_write> args: array<DrawArgs>;
+
+@compute @workgroup_size(64)
+fn cull(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if index >= params.instance_count || params.batch_count == 0u {
+        return;
+    }
+    let instance = instances[index];
+    let radius = instance.scale * max(instance.size.x, instance.size.y) * 1.41421356 * params.grow + params.shift;
+    // Parallax moves the quad by where each camera is, so it is always drawn
+    var seen = any(instance.parallax != vec2<f32>(1.0, 1.0));
+    for (var i = 0u; i < params.rect_count && !seen; i++) {
+        let rect = params.rects[i];
+        if all(instance.position + radius >= rect.xy) && all(instance.position - radius <= rect.zw) {
+            seen = true;
+            break;
+        }
+    }
+    if !seen {
+        return;
+    }
+
+    // Batches are sorted by first instance; find the last one starting at or before this slot
+    var low = 0u;
+    var high = params.batch_count;
+    while low + 1u < high {
+        let middle = (low + high) / 2u;
+        if batches[middle].first_instance <= index {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    let batch = batches[low];
+    if index < batch.first_instance || index >= batch.first_instance + batch.instance_count {
+        return;
+    }
+    // Each batch compacts into its own slots of the visible buffer
+    let slot = atomicAdd(&args[low].instance_count, 1u);
+    visible[batch.first_instance + slot] = instance;
+}
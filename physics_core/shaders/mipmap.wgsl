@@ -0,0 +1,27 @@
+// Downsample one mip level into the next, half its size
+
+@group(0) @binding(0)
+var source_tex: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Single triangle covering the whole level
+@vertex
+fn vs_mip(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// A linear sample between four source texels is their average
+@fragment
+fn fs_mip(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(source_tex, source_sampler, in.uv);
+}
@@ -0,0 +1,49 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec3<f32>,
+};
+
+// Body pose and size, one per attached body
+struct InstanceInput {
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * model * vec4<f32>(in.position, 1.0);
+    // Bodies are scaled uniformly, so the model matrix turns normals too
+    out.normal = (model * vec4<f32>(in.normal, 0.0)).xyz;
+    out.color = in.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput, @builtin(front_facing) front: bool) -> @location(0) vec4<f32> {
+    var normal = normalize(in.normal);
+    if (!front) {
+        normal = -normal;
+    }
+    // One light from the upper left, in front of the scene
+    let light = normalize(vec3<f32>(-0.4, 0.6, 0.7));
+    let diffuse = max(dot(normal, light), 0.0);
+    return vec4<f32>(in.color * (0.3 + 0.7 * diffuse), 1.0);
+}
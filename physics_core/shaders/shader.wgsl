@@ -0,0 +1,193 @@
+struct Instance {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    scale: f32,
+    rotation: f32,
+    uv_offset: vec2<f32>,
+    uv_scale: vec2<f32>,
+    tint: u32,
+    lod: u32,
+    size: vec2<f32>,
+    slice_min: vec2<f32>,
+    slice_max: vec2<f32>,
+    parallax: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<storage, read_write> instances: array<Instance>;
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    // Point the camera looks at, which parallax layers scroll against
+    center: vec2<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+// Compute Shader
+@compute @workgroup_size(64)
+fn update_instances(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if index >= arrayLength(&instances) {
+        return;
+    }
+
+    // Simple physics update: pos += vel * dt (assuming fixed dt usually, or passed via uniform)
+    // For now, let's just make them move based on velocity.
+    // To make it interesting without a uniform DT yet, we'll assume a small factor.
+    let dt = 0.016;
+    let instance = instances[index];
+    
+    // Update position
+    var new_pos = instance.position + instance.velocity * dt;
+
+    // Bounce off walls (assuming -1.0 to 1.0 clip space range for simplicity in logic)
+    // In a real app, this logic might be more complex or depend on world size.
+    var new_vel = instance.velocity;
+    if new_pos.x < -1.0 || new_pos.x > 1.0 {
+        new_vel.x = -new_vel.x;
+        new_pos.x = clamp(new_pos.x, -1.0, 1.0);
+    }
+    if new_pos.y < -1.0 || new_pos.y > 1.0 {
+        new_vel.y = -new_vel.y;
+        new_pos.y = clamp(new_pos.y, -1.0, 1.0);
+    }
+
+    // Write back
+    instances[index].position = new_pos;
+    instances[index].velocity = new_vel;
+    // Rotate slightly
+    instances[index].rotation += 0.02;
+}
+
+// Vertex Shader
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(2) i_position: vec2<f32>,
+    @location(3) i_velocity: vec2<f32>, // Unused in VS, but part of buffer stride
+    @location(4) i_scale: f32,
+    @location(5) i_rotation: f32,
+    @location(6) i_uv_offset: vec2<f32>,
+    @location(7) i_uv_scale: vec2<f32>,
+    @location(8) i_tint: u32, // RGBA8, red in the lowest byte
+    @location(9) i_lod: u32,
+    @location(10) i_size: vec2<f32>, // Width and height relative to the square quad
+    @location(11) i_slice_min: vec2<f32>, // Left and top nine-slice borders
+    @location(12) i_slice_max: vec2<f32>, // Right and bottom nine-slice borders
+    @location(13) i_parallax: vec2<f32>, // How far the instance moves with the camera
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // 0..1 across the quad; the texture coordinates are worked out per pixel
+    @location(0) local: vec2<f32>,
+    @location(1) speed: f32,
+    @location(2) tint: vec3<f32>,
+    // uv_offset, uv_scale
+    @location(3) region: vec4<f32>,
+    @location(4) size: vec2<f32>,
+    // slice_min, slice_max
+    @location(5) slices: vec4<f32>,
+};
+
+// Where in the region (0..1) a point `t` of the way across an axis `size`
+// times the square's length samples, keeping borders `low` and `high` at
+// their size on the square. Same as `sprite_region::slice_axis`.
+fn slice_axis(t: f32, size: f32, low: f32, high: f32) -> f32 {
+    let borders = low + high;
+    if borders <= 0.0 {
+        return t;
+    }
+    let fit = min(size / borders, 1.0);
+    let p = t * size;
+    if p < low * fit {
+        return p / fit;
+    }
+    if p > size - high * fit {
+        return 1.0 - (size - p) / fit;
+    }
+    let middle = size - borders * fit;
+    return low + (p - low * fit) * (1.0 - borders) / max(middle, 1e-6);
+}
+
+// Texture coordinates of the sprite region, nine-sliced
+fn region_uv(in: VertexOutput) -> vec2<f32> {
+    let t = vec2<f32>(
+        slice_axis(in.local.x, in.size.x, in.slices.x, in.slices.z),
+        slice_axis(in.local.y, in.size.y, in.slices.y, in.slices.w)
+    );
+    return in.region.xy + t * in.region.zw;
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    // scale, stretched to the instance's width and height
+    let scaled_pos = model.position * vec3<f32>(instance.i_size * instance.i_scale, instance.i_scale);
+
+    // rotate (2D only Z-axis rotation)
+    let c = cos(instance.i_rotation);
+    let s = sin(instance.i_rotation);
+    let rotated_pos = vec3<f32>(
+        scaled_pos.x * c - scaled_pos.y * s,
+        scaled_pos.x * s + scaled_pos.y * c,
+        scaled_pos.z
+    );
+
+    // translate; layers with less parallax than 1 lag behind the camera
+    let shift = camera.center * (vec2<f32>(1.0, 1.0) - instance.i_parallax);
+    let world_pos = vec3<f32>(
+        rotated_pos.x + instance.i_position.x + shift.x,
+        rotated_pos.y + instance.i_position.y + shift.y,
+        rotated_pos.z
+    );
+
+    var out: VertexOutput;
+    // Sprite sheet frame or region (offset and scale)
+    out.local = model.tex_coords;
+    out.region = vec4<f32>(instance.i_uv_offset, instance.i_uv_scale);
+    out.size = instance.i_size;
+    out.slices = vec4<f32>(instance.i_slice_min, instance.i_slice_max);
+    // Far away: every pixel takes the middle of the frame, a plain color quad
+    if instance.i_lod != 0u {
+        out.local = vec2<f32>(0.5, 0.5);
+        out.slices = vec4<f32>(0.0);
+    }
+    out.clip_position = camera.view_proj * vec4<f32>(world_pos, 1.0);
+    out.speed = length(instance.i_velocity);
+    out.tint = unpack4x8unorm(instance.i_tint).rgb;
+    return out;
+}
+
+// Fragment Shader
+
+@group(0) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(0) @binding(1)
+var s_diffuse: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base = textureSample(t_diffuse, s_diffuse, region_uv(in));
+    return vec4<f32>(base.rgb * in.tint, base.a);
+}
+
+// HDR variant used when bloom is enabled: fast bodies glow above 1.0 so the
+// bright-pass picks them up.
+const EMISSIVE_SPEED_THRESHOLD: f32 = 1.0;
+const EMISSIVE_GAIN: f32 = 1.5;
+
+@fragment
+fn fs_main_emissive(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base = textureSample(t_diffuse, s_diffuse, region_uv(in));
+    let glow = 1.0 + EMISSIVE_GAIN * max(in.speed - EMISSIVE_SPEED_THRESHOLD, 0.0);
+    return vec4<f32>(base.rgb * in.tint * glow, base.a);
+}
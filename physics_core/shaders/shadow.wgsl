@@ -0,0 +1,74 @@
+// Contact shadows: each instance is drawn again, slightly enlarged and offset,
+// as a soft dark quad underneath the bodies. Overlapping quads accumulate, so
+// stacked boxes darken the area beneath them.
+
+struct ShadowParams {
+    intensity: f32,
+    spread: f32,
+    offset: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: ShadowParams;
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    center: vec2<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(2) i_position: vec2<f32>,
+    @location(3) i_velocity: vec2<f32>,
+    @location(4) i_scale: f32,
+    @location(5) i_rotation: f32,
+    @location(6) i_uv_offset: vec2<f32>,
+    @location(7) i_uv_scale: vec2<f32>,
+    @location(10) i_size: vec2<f32>,
+    @location(13) i_parallax: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Quad-local coordinates in -1..1 across the enlarged shadow quad
+    @location(0) local: vec2<f32>,
+};
+
+@vertex
+fn vs_shadow(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    // Grow the unit quad around its center, matching the body quad's layout
+    let grown = (model.position.xy - vec2<f32>(0.5, 0.5)) * (1.0 + params.spread) + vec2<f32>(0.5, 0.5);
+    let scaled_pos = grown * instance.i_size * instance.i_scale;
+
+    let c = cos(instance.i_rotation);
+    let s = sin(instance.i_rotation);
+    let rotated_pos = vec2<f32>(
+        scaled_pos.x * c - scaled_pos.y * s,
+        scaled_pos.x * s + scaled_pos.y * c
+    );
+
+    // Parallax as in shader.wgsl
+    let shift = camera.center * (vec2<f32>(1.0, 1.0) - instance.i_parallax);
+    let world_pos = rotated_pos + instance.i_position + shift + params.offset;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(world_pos, 0.0, 1.0);
+    out.local = model.position.xy * 2.0 - vec2<f32>(1.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_shadow(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Fully dark under the body itself, fading out across the spread margin
+    let edge = max(abs(in.local.x), abs(in.local.y));
+    let inner = 1.0 / (1.0 + params.spread);
+    let alpha = params.intensity * (1.0 - smoothstep(inner, 1.0, edge));
+    return vec4<f32>(0.0, 0.0, 0.0, alpha);
+}
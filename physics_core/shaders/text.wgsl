@@ -0,0 +1,52 @@
+// Bitmap font glyphs: one instanced quad per glyph, already placed in clip
+// space on the CPU. The atlas holds coverage in its red channel.
+
+@group(0) @binding(0)
+var t_atlas: texture_2d<f32>;
+@group(0) @binding(1)
+var s_atlas: sampler;
+
+struct GlyphInput {
+    // x_min, y_min, x_max, y_max in normalized device coordinates
+    @location(0) rect: vec4<f32>,
+    // u_min, v_min, u_max, v_max with v_min at the glyph's top
+    @location(1) uv: vec4<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn vs_text(@builtin(vertex_index) index: u32, glyph: GlyphInput) -> VertexOutput {
+    // Two triangles; corner (0, 0) is the glyph's top-left
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 1.0),
+    );
+    let corner = corners[index];
+
+    var out: VertexOutput;
+    let x = mix(glyph.rect.x, glyph.rect.z, corner.x);
+    let y = mix(glyph.rect.w, glyph.rect.y, corner.y);
+    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    out.tex_coords = mix(glyph.uv.xy, glyph.uv.zw, corner);
+    out.color = glyph.color;
+    return out;
+}
+
+@fragment
+fn fs_text(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(t_atlas, s_atlas, in.tex_coords).r;
+    if (coverage < 0.5) {
+        discard;
+    }
+    return in.color;
+}
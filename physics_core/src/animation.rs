@@ -20,11 +20,10 @@ impl Default for AnimatorComponent {
 }
 
 pub fn animation_system(
+    time: Res<crate::time_control::Time>,
     mut query: Query<(&mut AnimatorComponent, &crate::sprite::SpriteSheetComponent)>,
-    // In a real Bevy app we'd use Res<Time>, but here we pass dt manually or use a resource.
-    // For now we assume the caller handles dt or we use a Global dt.
-    dt: f32,
 ) {
+    let dt = time.animation_delta;
     for (mut animator, sprite_sheet) in query.iter_mut() {
         if animator.is_playing {
             animator.elapsed_time += dt;
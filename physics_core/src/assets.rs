@@ -0,0 +1,164 @@
+//! Named assets read through the host
+//!
+//! Scene files and the textures they use are named by asset paths such as
+//! "textures/ball.png" rather than file system paths, because where they
+//! live differs per platform: Android packs them into the APK, iOS into the
+//! app bundle and the web serves them next to the page, none of which the
+//! crate can open itself. Hosts register a loader that reads a name from
+//! wherever the platform keeps it; without one the desktop builds read names
+//! relative to the working directory.
+//!
+//! Reads are synchronous, which the web cannot do, so the wasm build fetches
+//! assets ahead of use into the preloaded set that every read checks before
+//! asking the loader. Hosts on other platforms can preload bytes as well.
+
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CString};
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+/// Largest asset read, in bytes
+pub const MAX_ASSET_BYTES: usize = 64 * 1024 * 1024;
+/// Most assets kept preloaded
+pub const MAX_PRELOADED: usize = 1024;
+
+/// Reads the asset with the given name, or says why it cannot
+pub type AssetLoader = dyn Fn(&str) -> Result<Vec<u8>, String> + Send + Sync;
+
+/// The host's loader; it is cloned out before being called, so the host can
+/// read other assets or register another loader from inside it. Never held
+/// together with another lock.
+static LOADER: Lazy<Mutex<Option<Arc<AssetLoader>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Bytes handed over ahead of use, by asset name. Never held together with
+/// another lock.
+static PRELOADED: Lazy<Mutex<HashMap<String, Vec<u8>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Check that `name` is a relative, '/'-separated asset path that stays
+/// inside the asset root
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("asset name must not be empty".into());
+    }
+    if name.starts_with('/') || name.contains('\\') || name.contains(':') {
+        return Err(format!("asset name '{}' must be a relative path with '/' separators", name));
+    }
+    if name.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+        return Err(format!("asset name '{}' has an empty, '.' or '..' component", name));
+    }
+    Ok(())
+}
+
+/// Read assets through `loader` from now on; None goes back to the
+/// platform default
+pub fn set_loader(loader: Option<Box<AssetLoader>>) {
+    if let Ok(mut slot) = LOADER.lock() {
+        *slot = loader.map(Arc::from);
+    }
+}
+
+pub fn has_loader() -> bool {
+    LOADER.lock().is_ok_and(|slot| slot.is_some())
+}
+
+/// Keep `bytes` as asset `name`, replacing earlier bytes, so reads of it
+/// need neither the loader nor the file system
+pub fn preload(name: &str, bytes: Vec<u8>) -> Result<(), String> {
+    check_name(name)?;
+    if bytes.len() > MAX_ASSET_BYTES {
+        return Err(format!("{}: larger than {} bytes", name, MAX_ASSET_BYTES));
+    }
+    let mut preloaded = PRELOADED.lock().map_err(|e| e.to_string())?;
+    if preloaded.len() == MAX_PRELOADED && !preloaded.contains_key(name) {
+        return Err(format!("at most {} preloaded assets", MAX_PRELOADED));
+    }
+    preloaded.insert(name.to_string(), bytes);
+    Ok(())
+}
+
+/// Drop preloaded asset `name`. Returns false when it was not preloaded.
+pub fn unload(name: &str) -> bool {
+    PRELOADED.lock().is_ok_and(|mut preloaded| preloaded.remove(name).is_some())
+}
+
+pub fn is_preloaded(name: &str) -> bool {
+    PRELOADED.lock().is_ok_and(|preloaded| preloaded.contains_key(name))
+}
+
+/// The bytes of asset `name`: preloaded, from the host's loader or, without
+/// one, from the platform default
+pub fn read(name: &str) -> Result<Vec<u8>, String> {
+    check_name(name)?;
+    if let Some(bytes) = PRELOADED.lock().ok().and_then(|preloaded| preloaded.get(name).cloned()) {
+        return Ok(bytes);
+    }
+    let loader = LOADER.lock().ok().and_then(|slot| slot.clone());
+    let bytes = match loader {
+        Some(loader) => loader(name)?,
+        None => read_default(name)?,
+    };
+    if bytes.len() > MAX_ASSET_BYTES {
+        return Err(format!("{}: larger than {} bytes", name, MAX_ASSET_BYTES));
+    }
+    Ok(bytes)
+}
+
+/// `read` as UTF-8 text
+pub fn read_text(name: &str) -> Result<String, String> {
+    String::from_utf8(read(name)?).map_err(|_| format!("{}: not UTF-8 text", name))
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android", target_os = "ios")))]
+fn read_default(name: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(name).map_err(|e| format!("{}: {}", name, e))
+}
+
+#[cfg(any(target_arch = "wasm32", target_os = "android", target_os = "ios"))]
+fn read_default(name: &str) -> Result<Vec<u8>, String> {
+    Err(format!("{}: not preloaded and no asset loader is registered", name))
+}
+
+/// C loader: `(name, len, user_data)` returns the asset's bytes and sets
+/// `*len`, or returns null for assets it does not have
+pub type HostAssetFn = extern "C" fn(*const c_char, *mut u32, *mut c_void) -> *const u8;
+/// C release: `(bytes, len, user_data)` once bytes the loader returned are
+/// copied
+pub type HostAssetReleaseFn = extern "C" fn(*const u8, u32, *mut c_void);
+
+struct HostLoader {
+    load: HostAssetFn,
+    release: Option<HostAssetReleaseFn>,
+    user_data: *mut c_void,
+}
+
+// SAFETY: as for host hooks, the pointer is only handed back to the host
+unsafe impl Send for HostLoader {}
+unsafe impl Sync for HostLoader {}
+
+impl HostLoader {
+    // See `plugins::HostHook::call`
+    fn call(&self, name: &str) -> Result<Vec<u8>, String> {
+        let c_name = CString::new(name).map_err(|_| format!("{}: name contains NUL", name))?;
+        let mut len = 0u32;
+        let data = (self.load)(c_name.as_ptr(), &mut len, self.user_data);
+        if data.is_null() {
+            return Err(format!("{}: not found by the host", name));
+        }
+        // SAFETY: the host promises `len` readable bytes until they are released
+        let bytes = unsafe { std::slice::from_raw_parts(data, len as usize) }.to_vec();
+        if let Some(release) = self.release {
+            release(data, len, self.user_data);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Read assets through the C functions `load` and `release`; a null `load`
+/// goes back to the platform default
+pub(crate) fn set_host_loader(load: Option<HostAssetFn>, release: Option<HostAssetReleaseFn>, user_data: *mut c_void) {
+    set_loader(load.map(|load| {
+        let host = HostLoader { load, release, user_data };
+        Box::new(move |name: &str| host.call(name)) as Box<AssetLoader>
+    }));
+}
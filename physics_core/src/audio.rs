@@ -0,0 +1,269 @@
+//! Collision sounds as data for the host's audio engine
+//!
+//! The engine plays nothing itself. When two colliders start touching, the
+//! physics step records how hard they hit; `collect` turns every impact above
+//! `min_speed` into an `AudioEvent` with the materials involved, the contact
+//! point and a suggested volume, pitch and stereo pan for a listener
+//! somewhere in the world. Hosts either poll the events or get them through a
+//! callback after every step.
+//!
+//! Volume is the impact loudness (0 at `min_speed`, 1 at `max_speed`) times
+//! a clamped inverse-distance attenuation, the model OpenAL and Web Audio
+//! call "inverse": full volume up to `reference_distance`, falling with
+//! `rolloff` beyond it, and no event at all past `max_distance`. Pitch rises
+//! for small bodies and falls for large ones.
+
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::sync::Mutex;
+
+use bevy_ecs::prelude::*;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::material::{MaterialComponent, DEFAULT_MATERIAL};
+use crate::plugins::{self, HookPoint};
+use crate::schedule::CollisionEvents;
+use crate::{PhysicsState, Scale};
+
+/// Events waiting to be polled; older ones are dropped first
+pub const MAX_QUEUED: usize = 256;
+
+/// Half extent that plays at pitch 1 (the default box)
+const REFERENCE_HALF_EXTENT: f32 = 0.05;
+
+/// Where and how hard two colliders met
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Impact {
+    /// Entity ids, 0 for colliders without an entity (walls, level geometry)
+    pub a: u64,
+    pub b: u64,
+    pub point: [f32; 2],
+    /// Closing speed along the contact normal, m/s
+    pub speed: f32,
+    /// Estimated impulse to stop that motion, N·s
+    pub impulse: f32,
+}
+
+/// One collision sound
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioEvent {
+    pub entity_a: u64,
+    pub entity_b: u64,
+    /// Material ids of both entities (0, the default, for walls)
+    pub material_a: u32,
+    pub material_b: u32,
+    pub x: f32,
+    pub y: f32,
+    pub impact_speed: f32,
+    pub impulse: f32,
+    /// Suggested gain in 0..1, distance attenuation included
+    pub volume: f32,
+    /// Suggested playback rate, 1 for a default-sized body
+    pub pitch: f32,
+    /// -1 (left of the listener) to 1 (right)
+    pub pan: f32,
+    /// From the listener, world units
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioConfig {
+    pub enabled: bool,
+    /// Slower impacts make no event
+    pub min_speed: f32,
+    /// Impacts this fast or faster are at full loudness
+    pub max_speed: f32,
+    pub listener: [f32; 2],
+    pub reference_distance: f32,
+    pub max_distance: f32,
+    pub rolloff: f32,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_speed: 0.2,
+            max_speed: 5.0,
+            listener: [0.0, 0.0],
+            reference_distance: 0.5,
+            max_distance: 5.0,
+            rolloff: 1.0,
+        }
+    }
+}
+
+impl AudioConfig {
+    /// Speed thresholds must be finite with 0 <= min < max
+    pub fn with_speeds(self, min_speed: f32, max_speed: f32) -> Option<Self> {
+        (min_speed.is_finite() && max_speed.is_finite() && 0.0 <= min_speed && min_speed < max_speed)
+            .then_some(Self { min_speed, max_speed, ..self })
+    }
+
+    /// Distances must be finite with 0 < reference <= max; rolloff >= 0
+    pub fn with_attenuation(self, reference_distance: f32, max_distance: f32, rolloff: f32) -> Option<Self> {
+        let valid = [reference_distance, max_distance, rolloff].iter().all(|v| v.is_finite())
+            && 0.0 < reference_distance
+            && reference_distance <= max_distance
+            && rolloff >= 0.0;
+        valid.then_some(Self { reference_distance, max_distance, rolloff, ..self })
+    }
+
+    /// Gain at `distance` from the listener; 0 past `max_distance`
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        if distance > self.max_distance {
+            return 0.0;
+        }
+        let distance = distance.max(self.reference_distance);
+        self.reference_distance / (self.reference_distance + self.rolloff * (distance - self.reference_distance))
+    }
+
+    /// Loudness of an impact before attenuation, 0..1
+    pub fn loudness(&self, speed: f32) -> f32 {
+        ((speed - self.min_speed) / (self.max_speed - self.min_speed)).clamp(0.0, 1.0)
+    }
+
+    /// Event for `impact`, or None when it is too soft or too far away.
+    /// `half_extent` is the size of the smaller body (0 when unknown).
+    pub fn event(&self, impact: &Impact, materials: [u32; 2], half_extent: f32) -> Option<AudioEvent> {
+        if !self.enabled || impact.speed.is_nan() || impact.speed < self.min_speed {
+            return None;
+        }
+        let [x, y] = impact.point;
+        let dx = x - self.listener[0];
+        let distance = dx.hypot(y - self.listener[1]);
+        let volume = self.loudness(impact.speed) * self.attenuation(distance);
+        if volume.is_nan() || volume <= 0.0 {
+            return None;
+        }
+        let pitch = if half_extent > 0.0 { (REFERENCE_HALF_EXTENT / half_extent).sqrt().clamp(0.5, 2.0) } else { 1.0 };
+        Some(AudioEvent {
+            entity_a: impact.a,
+            entity_b: impact.b,
+            material_a: materials[0],
+            material_b: materials[1],
+            x,
+            y,
+            impact_speed: impact.speed,
+            impulse: impact.impulse,
+            volume,
+            pitch,
+            pan: dx / (dx.abs() + self.reference_distance),
+            distance,
+        })
+    }
+}
+
+/// Events not yet handed to the host
+#[derive(Debug, Clone, Default)]
+pub struct AudioQueue {
+    events: VecDeque<AudioEvent>,
+    dropped: u64,
+}
+
+impl AudioQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: AudioEvent) {
+        if self.events.len() == MAX_QUEUED {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    /// Remove and return up to `max` events, oldest first
+    pub fn drain(&mut self, max: usize) -> Vec<AudioEvent> {
+        let count = max.min(self.events.len());
+        self.events.drain(..count).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Events lost because nobody polled in time
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Queue an event for every impact of the latest step
+pub(crate) fn collect(physics: &mut PhysicsState) {
+    let config = physics.audio_config;
+    if !config.enabled {
+        return;
+    }
+    let world = &physics.world;
+    let entity = |id: u64| Entity::try_from_bits(id).ok().filter(|&e| id != 0 && world.entities().contains(e));
+    let material = |id| {
+        entity(id)
+            .and_then(|e| world.get::<MaterialComponent>(e))
+            .map_or(DEFAULT_MATERIAL, |m| m.material_id)
+    };
+    let half_extent = |id| entity(id).and_then(|e| world.get::<Scale>(e)).map(|s| s.0);
+
+    let mut events = Vec::new();
+    for impact in &world.resource::<CollisionEvents>().impacts {
+        let smaller = match (half_extent(impact.a), half_extent(impact.b)) {
+            (Some(a), Some(b)) => a.min(b),
+            (Some(size), None) | (None, Some(size)) => size,
+            (None, None) => 0.0,
+        };
+        events.extend(config.event(impact, [material(impact.a), material(impact.b)], smaller));
+    }
+    for event in events {
+        physics.audio_events.push(event);
+    }
+}
+
+/// C callback: `(events, count, user_data)`; `events` is only valid during the call
+pub type HostAudioFn = extern "C" fn(*const AudioEvent, u32, *mut c_void);
+
+/// PostPhysics hook delivering events to the host callback, if one is set
+static HOST_CALLBACK_HOOK: Lazy<Mutex<Option<u32>>> = Lazy::new(|| Mutex::new(None));
+
+struct HostCallback {
+    func: HostAudioFn,
+    user_data: *mut c_void,
+}
+
+// SAFETY: as for host hooks, the pointer is only handed back to the host
+unsafe impl Send for HostCallback {}
+unsafe impl Sync for HostCallback {}
+
+impl HostCallback {
+    // See `plugins::HostHook::call`
+    fn call(&self, events: &[AudioEvent]) {
+        (self.func)(events.as_ptr(), events.len() as u32, self.user_data)
+    }
+}
+
+/// Deliver queued events to `func` after every step instead of queueing them
+/// for polling; None goes back to polling
+pub(crate) fn set_host_callback(func: Option<HostAudioFn>, user_data: *mut c_void) {
+    let Ok(mut hook) = HOST_CALLBACK_HOOK.lock() else {
+        return;
+    };
+    if let Some(id) = hook.take() {
+        plugins::unregister_hook(id);
+    }
+    if let Some(func) = func {
+        let host = HostCallback { func, user_data };
+        *hook = Some(plugins::register_hook(HookPoint::PostPhysics, move |_, _| {
+            let events = crate::poll_audio_events_internal(MAX_QUEUED);
+            if !events.is_empty() {
+                host.call(&events);
+            }
+        }));
+    }
+}
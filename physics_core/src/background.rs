@@ -0,0 +1,275 @@
+//! External images behind the scene
+//!
+//! A background is one image (a camera or video frame) drawn across the
+//! whole view at the start of the main pass, under the terrain and bodies,
+//! so AR-style demos can put the physics world over a camera feed. Hosts
+//! replace it every time a new frame arrives; it stays until cleared or the
+//! GPU state is rebuilt.
+//!
+//! Frames come in two ways. Any host can push tightly packed RGBA. On
+//! Android (the `android_hardware_buffer` feature, API 26) an
+//! `AHardwareBuffer` is locked for CPU reads and copied into the texture;
+//! the copy works the same on the Vulkan and GL backends, where a zero-copy
+//! import would need each backend's own external memory extension. Buffers
+//! must have been made with a CPU read usage, e.g. an `ImageReader` with
+//! `HardwareBuffer.USAGE_CPU_READ_OFTEN`; a `SurfaceTexture` feed reaches
+//! the background by pointing the camera at such an `ImageReader` instead.
+
+/// How the image covers a view of a different aspect ratio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackgroundFit {
+    /// Fill the view, cropping the image's overhanging sides
+    #[default]
+    Fill,
+    /// Show the whole image, leaving bars of the clear color
+    Fit,
+    /// Fill the view, distorting the image
+    Stretch,
+}
+
+impl BackgroundFit {
+    /// FFI code: 0 = fill, 1 = fit, 2 = stretch
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Fill),
+            1 => Some(Self::Fit),
+            2 => Some(Self::Stretch),
+            _ => None,
+        }
+    }
+
+    /// Texture coordinates `[u0, v0, u1, v1]` of the image at the view's
+    /// top-left and bottom-right corners. With `Fit` they reach past 0..1,
+    /// where nothing is drawn.
+    pub fn uv_rect(self, image: (u32, u32), view: (u32, u32)) -> [f32; 4] {
+        let image_aspect = image.0.max(1) as f32 / image.1.max(1) as f32;
+        let view_aspect = view.0.max(1) as f32 / view.1.max(1) as f32;
+        // Fraction of the image's width and height the view spans
+        let (u_span, v_span) = match self {
+            Self::Stretch => (1.0, 1.0),
+            Self::Fill if image_aspect > view_aspect => (view_aspect / image_aspect, 1.0),
+            Self::Fill => (1.0, image_aspect / view_aspect),
+            Self::Fit if image_aspect > view_aspect => (1.0, image_aspect / view_aspect),
+            Self::Fit => (view_aspect / image_aspect, 1.0),
+        };
+        [0.5 - u_span / 2.0, 0.5 - v_span / 2.0, 0.5 + u_span / 2.0, 0.5 + v_span / 2.0]
+    }
+}
+
+/// Byte layout of the pixels handed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelLayout {
+    Rgba8,
+    /// RGBA with an unused fourth byte
+    Rgbx8,
+    Rgb8,
+}
+
+impl PixelLayout {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgba8 | Self::Rgbx8 => 4,
+            Self::Rgb8 => 3,
+        }
+    }
+}
+
+/// Tightly packed RGBA from `height` rows of `width` pixels starting
+/// `stride` pixels apart. Images without alpha come out opaque. None if
+/// `stride` is below `width` or `pixels` is too short.
+pub fn pack_rgba(pixels: &[u8], width: u32, height: u32, stride: u32, layout: PixelLayout) -> Option<Vec<u8>> {
+    let (width, height, stride) = (width as usize, height as usize, stride as usize);
+    let bytes_per_pixel = layout.bytes_per_pixel();
+    if stride < width || (height > 0 && pixels.len() < ((height - 1) * stride + width) * bytes_per_pixel) {
+        return None;
+    }
+    if layout == PixelLayout::Rgba8 && stride == width {
+        return Some(pixels[..width * height * 4].to_vec());
+    }
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in 0..height {
+        let start = row * stride * bytes_per_pixel;
+        for pixel in pixels[start..start + width * bytes_per_pixel].chunks_exact(bytes_per_pixel) {
+            let alpha = if layout == PixelLayout::Rgba8 { pixel[3] } else { 255 };
+            rgba.extend_from_slice(&[pixel[0], pixel[1], pixel[2], alpha]);
+        }
+    }
+    Some(rgba)
+}
+
+struct BackgroundImage {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Draws the background at the start of the main pass
+pub(crate) struct BackgroundPass {
+    /// Pipeline matching the main pass (swapchain format, MSAA sample count)
+    pipeline: wgpu::RenderPipeline,
+    /// Pipeline matching the HDR bloom target, when bloom is available
+    hdr_pipeline: Option<wgpu::RenderPipeline>,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    image: Option<BackgroundImage>,
+    fit: BackgroundFit,
+}
+
+impl BackgroundPass {
+    pub fn new(device: &wgpu::Device, output_format: wgpu::TextureFormat, sample_count: u32, hdr_format: Option<wgpu::TextureFormat>) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Background Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/background.wgsl"))),
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Background Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Background Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let make_pipeline = |label: &str, format: wgpu::TextureFormat, count: u32| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_background"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_background"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let pipeline = make_pipeline("Background Pipeline", output_format, sample_count);
+        let hdr_pipeline = hdr_format.map(|format| make_pipeline("HDR Background Pipeline", format, 1));
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Background Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Background Uniform Buffer"),
+            size: std::mem::size_of::<[f32; 4]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self { pipeline, hdr_pipeline, bind_group_layout, sampler, uniform_buffer, image: None, fit: BackgroundFit::default() }
+    }
+
+    /// Show `rgba` (tightly packed, `width * height` pixels), reusing the
+    /// texture while the size stays the same
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, width: u32, height: u32, rgba: &[u8]) {
+        let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+        if !self.image.as_ref().is_some_and(|image| image.texture.size() == size) {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Background Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                // Camera frames are sRGB encoded
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Background Bind Group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+                    wgpu::BindGroupEntry { binding: 2, resource: self.uniform_buffer.as_entire_binding() },
+                ],
+            });
+            self.image = Some(BackgroundImage { texture, bind_group });
+        }
+        if let Some(image) = &self.image {
+            queue.write_texture(
+                image.texture.as_image_copy(),
+                rgba,
+                wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(width * 4), rows_per_image: Some(height) },
+                size,
+            );
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.image = None;
+    }
+
+    pub fn set_fit(&mut self, fit: BackgroundFit) {
+        self.fit = fit;
+    }
+
+    /// Place the image for a view of `width` x `height` pixels
+    pub fn prepare(&self, queue: &wgpu::Queue, width: u32, height: u32) {
+        if let Some(image) = &self.image {
+            let size = image.texture.size();
+            let uv_rect = self.fit.uv_rect((size.width, size.height), (width, height));
+            queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&uv_rect));
+        }
+    }
+
+    /// Draw the image, if there is one. Leaves the background pipeline bound.
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, hdr: bool) {
+        let Some(image) = &self.image else {
+            return;
+        };
+        let Some(pipeline) = (if hdr { self.hdr_pipeline.as_ref() } else { Some(&self.pipeline) }) else {
+            return;
+        };
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &image.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,48 @@
+//! Entry points for the criterion benchmarks in `benches/`
+//!
+//! These drive the global simulation state directly so benchmarks measure the
+//! same code paths the hosts use. Not a stable API.
+
+use crate::instance_staging::Upload;
+use crate::stress::StressLayout;
+
+/// Replace the simulation with a stress scene of `n_bodies` boxes
+pub fn spawn_stress_scene(n_bodies: u32, layout: StressLayout) {
+    let scene = crate::stress::generate(n_bodies, layout);
+    crate::init_physics_scene(&scene.positions, scene.half_extent);
+}
+
+/// Run one simulation update (input, animation, physics step)
+pub fn step(dt: f32) {
+    crate::update_internal(dt);
+}
+
+/// The CPU half of `sync_physics_to_gpu`: refresh the instance staging array
+/// and take the pending upload. Returns the number of bytes that would be
+/// written to the instance buffer.
+pub fn prepare_instance_upload() -> usize {
+    let mut guard = match crate::PHYSICS_STATE.lock() {
+        Ok(g) => g,
+        Err(_) => return 0,
+    };
+    let physics = match guard.0.as_mut() {
+        Some(p) => p,
+        None => return 0,
+    };
+    crate::refresh_instance_staging(physics);
+    let stride = std::mem::size_of::<crate::Instance>();
+    match physics.instance_staging.take_upload() {
+        Upload::None => 0,
+        Upload::Full => physics.instance_staging.len() * stride,
+        Upload::Ranges(ranges) => ranges.iter().map(|r| r.len() * stride).sum(),
+    }
+}
+
+/// Force the next `prepare_instance_upload` to take the full-upload path
+pub fn invalidate_instance_upload() {
+    if let Ok(mut guard) = crate::PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.instance_staging.mark_all_dirty();
+        }
+    }
+}
@@ -1,7 +1,30 @@
+//! The 3D pass: a spinning rainbow cube, or loaded models on bodies
+//!
+//! Models come from .gltf/.glb bytes (see `gltf_lite`). Each gets its own
+//! pipeline, culling back faces unless the model is double sided, and all of
+//! them share one depth buffer. A body with a `ModelComponent` is drawn with
+//! its model at the body's full 3D pose; once any are, they replace the cube.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use bevy_ecs::prelude::*;
 use bevy_transform::prelude::*;
 use bytemuck::{Pod, Zeroable};
 use wgpu::util::DeviceExt;
 
+use crate::gltf_lite::ModelMesh;
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Draws the entity's body with a loaded model, `scale` times the body's
+/// drawn size
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ModelComponent {
+    pub model_id: u32,
+    pub scale: f32,
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 struct Vertex {
@@ -15,6 +38,30 @@ struct ModelUniform {
     model_matrix: [[f32; 4]; 4],
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ModelVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    color: [f32; 3],
+}
+
+/// World matrix of one body drawn with a model
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct ModelInstance {
+    pub model_matrix: [[f32; 4]; 4],
+}
+
+struct GpuModel {
+    /// Kept to rebuild the buffers after a device rebuild
+    mesh: ModelMesh,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
 pub struct Bevy3DSample {
     render_pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
@@ -24,21 +71,30 @@ pub struct Bevy3DSample {
     model_bind_group: wgpu::BindGroup,
     model_uniform_buffer: wgpu::Buffer,
     pub transform: Transform,
+    model_shader: wgpu::ShaderModule,
+    model_pipeline_layout: wgpu::PipelineLayout,
+    render_target_format: wgpu::TextureFormat,
+    models: HashMap<u32, GpuModel>,
+    next_model_id: u32,
+    depth_view: wgpu::TextureView,
+    instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
+    /// Instances of each model this frame, sorted by model id
+    batches: Vec<(u32, Range<u32>)>,
 }
 
 impl Bevy3DSample {
     pub fn new(
         device: &wgpu::Device,
-        _queue: &wgpu::Queue,
         camera_bind_group_layout: &wgpu::BindGroupLayout,
-        _adapter_info: &wgpu::AdapterInfo,
         render_target_format: wgpu::TextureFormat,
-        _width: u32,
-        _height: u32,
+        sample_count: u32,
+        width: u32,
+        height: u32,
     ) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("3D Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("3d_shader.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/3d_shader.wgsl").into()),
         });
 
         let model_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
@@ -128,7 +184,10 @@ impl Bevy3DSample {
                 conservative: false,
             },
             depth_stencil: None, // Simplified: no depth buffer for now
-            multisample: wgpu::MultisampleState::default(),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
             multiview: None,
             cache: None,
         });
@@ -190,6 +249,16 @@ impl Bevy3DSample {
             usage: wgpu::BufferUsages::INDEX,
         });
 
+        let model_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Model Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/model_3d.wgsl").into()),
+        });
+        let model_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Model Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
         Self {
             render_pipeline,
             vertex_buffer,
@@ -199,9 +268,111 @@ impl Bevy3DSample {
             model_bind_group,
             model_uniform_buffer,
             transform: Transform::from_xyz(0.0, 0.0, -2.0),
+            model_shader,
+            model_pipeline_layout,
+            render_target_format,
+            models: HashMap::new(),
+            next_model_id: 1,
+            depth_view: create_depth_view(device, width, height),
+            instance_buffer: create_instance_buffer(device, 1),
+            instance_capacity: 1,
+            batches: Vec::new(),
         }
     }
 
+    /// Upload a model and return its id
+    pub fn load_model(&mut self, device: &wgpu::Device, mesh: ModelMesh) -> u32 {
+        let model_id = self.next_model_id;
+        self.next_model_id += 1;
+        self.insert_model(device, model_id, mesh);
+        model_id
+    }
+
+    fn insert_model(&mut self, device: &wgpu::Device, model_id: u32, mesh: ModelMesh) {
+        let vertices: Vec<ModelVertex> = mesh
+            .positions
+            .iter()
+            .zip(&mesh.normals)
+            .zip(&mesh.colors)
+            .map(|((&position, &normal), &color)| ModelVertex { position, normal, color })
+            .collect();
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Model Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Model Index Buffer"),
+            contents: bytemuck::cast_slice(&mesh.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let pipeline = create_model_pipeline(
+            device,
+            &self.model_pipeline_layout,
+            &self.model_shader,
+            self.render_target_format,
+            mesh.double_sided,
+        );
+        let num_indices = mesh.indices.len() as u32;
+        self.models.insert(model_id, GpuModel { mesh, pipeline, vertex_buffer, index_buffer, num_indices });
+    }
+
+    pub fn has_model(&self, model_id: u32) -> bool {
+        self.models.contains_key(&model_id)
+    }
+
+    /// Every loaded model with its id, emptying this sample
+    pub fn take_models(&mut self) -> Vec<(u32, ModelMesh)> {
+        self.batches.clear();
+        self.models.drain().map(|(model_id, model)| (model_id, model.mesh)).collect()
+    }
+
+    /// Upload models from `take_models` of a previous device, keeping their ids
+    pub fn restore_models(&mut self, device: &wgpu::Device, models: Vec<(u32, ModelMesh)>) {
+        for (model_id, mesh) in models {
+            self.next_model_id = self.next_model_id.max(model_id + 1);
+            self.insert_model(device, model_id, mesh);
+        }
+    }
+
+    /// Match the depth buffer to a resized surface
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.depth_view = create_depth_view(device, width, height);
+    }
+
+    /// Bodies to draw with models this frame; instances of unknown models
+    /// are skipped
+    pub fn set_instances(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, mut instances: Vec<(u32, ModelInstance)>) {
+        instances.retain(|(model_id, _)| self.models.contains_key(model_id));
+        instances.sort_by_key(|(model_id, _)| *model_id);
+        self.batches.clear();
+        for (index, (model_id, _)) in instances.iter().enumerate() {
+            let index = index as u32;
+            match self.batches.last_mut() {
+                Some((last, range)) if last == model_id => range.end = index + 1,
+                _ => self.batches.push((*model_id, index..index + 1)),
+            }
+        }
+        if instances.len() > self.instance_capacity {
+            self.instance_capacity = instances.len().next_power_of_two();
+            self.instance_buffer = create_instance_buffer(device, self.instance_capacity);
+        }
+        let data: Vec<ModelInstance> = instances.into_iter().map(|(_, instance)| instance).collect();
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&data));
+    }
+
+    /// Depth buffer for the pass, needed only while models are drawn
+    pub fn depth_attachment(&self) -> Option<wgpu::RenderPassDepthStencilAttachment<'_>> {
+        (!self.batches.is_empty()).then(|| wgpu::RenderPassDepthStencilAttachment {
+            view: &self.depth_view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(1.0),
+                store: wgpu::StoreOp::Discard,
+            }),
+            stencil_ops: None,
+        })
+    }
+
     pub fn set_camera_bind_group(&mut self, bind_group: wgpu::BindGroup) {
         self.camera_bind_group = bind_group;
     }
@@ -223,7 +394,21 @@ impl Bevy3DSample {
         );
     }
 
+    /// Draw the models, or the cube when no body has one; the pass needs
+    /// `depth_attachment` as its depth attachment
     pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if !self.batches.is_empty() {
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+            for (model_id, instances) in &self.batches {
+                let model = &self.models[model_id];
+                render_pass.set_pipeline(&model.pipeline);
+                render_pass.set_vertex_buffer(0, model.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(model.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..model.num_indices, 0, instances.clone());
+            }
+            return;
+        }
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
         render_pass.set_bind_group(1, &self.model_bind_group, &[]);
@@ -232,3 +417,86 @@ impl Bevy3DSample {
         render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
     }
 }
+
+fn create_depth_view(device: &wgpu::Device, width: u32, height: u32) -> wgpu::TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("3D Depth Texture"),
+        size: wgpu::Extent3d {
+            width: width.max(1),
+            height: height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+fn create_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Model Instance Buffer"),
+        size: (capacity * std::mem::size_of::<ModelInstance>()) as u64,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn create_model_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    double_sided: bool,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Model Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[
+                wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<ModelVertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x3],
+                },
+                wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<ModelInstance>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![3 => Float32x4, 4 => Float32x4, 5 => Float32x4, 6 => Float32x4],
+                },
+            ],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: if double_sided { None } else { Some(wgpu::Face::Back) },
+            ..Default::default()
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
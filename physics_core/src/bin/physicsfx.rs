@@ -0,0 +1,20 @@
+// Standalone desktop app: `cargo run -p physics_core --bin physicsfx -- --help`
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn main() {
+    use physics_core::cli::{self, Command};
+
+    match cli::parse(std::env::args().skip(1)) {
+        Ok(Command::Run(options)) => physics_core::run_desktop_app(options),
+        Ok(Command::Benchmark(benchmark)) => println!("{}", physics_core::run_headless_benchmark(benchmark)),
+        Ok(Command::Help) => println!("{}", cli::USAGE),
+        Err(e) => {
+            eprintln!("physicsfx: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn main() {
+    eprintln!("physicsfx runs on Windows, macOS and Linux only");
+}
@@ -0,0 +1,4 @@
+// Generates Kotlin/Swift bindings for the `uniffi_bindings` feature; see src/uniffi_api.rs
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
@@ -1,5 +1,8 @@
 use nalgebra as na;
 
+use crate::camera_effects::CameraOffset;
+use crate::units::WorldScale;
+
 #[rustfmt::skip]
 pub const OPENGL_TO_WGPU_MATRIX: na::Matrix4<f32> = na::Matrix4::new(
     1.0, 0.0, 0.0, 0.0,
@@ -8,6 +11,7 @@ pub const OPENGL_TO_WGPU_MATRIX: na::Matrix4<f32> = na::Matrix4::new(
     0.0, 0.0, 0.0, 1.0,
 );
 
+#[derive(Debug, Clone, Copy)]
 pub struct Camera {
     pub eye: na::Point3<f32>,
     pub target: na::Point3<f32>,
@@ -18,42 +22,131 @@ pub struct Camera {
     pub zfar: f32,
     pub is_orthographic: bool,
     pub ortho_size: f32,
+    /// Smallest and largest `ortho_size` that zooming reaches
+    pub zoom_limits: (f32, f32),
 }
 
 impl Camera {
+    /// Orthographic camera at the origin framing the startup view of `scale`
+    pub fn orthographic(aspect: f32, scale: &WorldScale) -> Self {
+        Self {
+            eye: na::Point3::new(0.0, 0.0, 5.0),
+            target: na::Point3::new(0.0, 0.0, 0.0),
+            up: na::Vector3::y(),
+            aspect,
+            fovy: 45.0,
+            znear: 0.1,
+            zfar: 100.0,
+            is_orthographic: true,
+            ortho_size: scale.camera_height(),
+            zoom_limits: scale.zoom_limits(),
+        }
+    }
+
     pub fn build_view_projection_matrix(&self) -> na::Matrix4<f32> {
-        let view = na::Matrix4::look_at_rh(&self.eye, &self.target, &self.up);
+        self.build_view_projection_matrix_with(&CameraOffset::default())
+    }
+
+    /// View-projection with shake and zoom punch applied; the camera itself is unchanged
+    pub fn build_view_projection_matrix_with(&self, offset: &CameraOffset) -> na::Matrix4<f32> {
+        let shift = na::Vector3::new(offset.dx, offset.dy, 0.0);
+        let roll = na::Rotation3::from_axis_angle(&na::Vector3::z_axis(), offset.rotation);
+        let view = na::Matrix4::look_at_rh(&(self.eye + shift), &(self.target + shift), &(roll * self.up));
+        let zoom = 1.0 + offset.zoom;
 
         let proj = if self.is_orthographic {
-            let half_size = self.ortho_size / 2.0;
+            let half_size = self.ortho_size / zoom / 2.0;
             let left = -half_size * self.aspect;
             let right = half_size * self.aspect;
             let bottom = -half_size;
             let top = half_size;
             na::Orthographic3::new(left, right, bottom, top, self.znear, self.zfar).to_homogeneous()
         } else {
-            na::Perspective3::new(self.aspect, self.fovy.to_radians(), self.znear, self.zfar)
+            na::Perspective3::new(self.aspect, self.fovy.to_radians() / zoom, self.znear, self.zfar)
                 .to_homogeneous()
         };
 
         OPENGL_TO_WGPU_MATRIX * proj * view
     }
+
+    /// World units covered by one screen pixel (orthographic only)
+    pub fn world_per_pixel(&self, screen_height: u32) -> f32 {
+        self.ortho_size / screen_height.max(1) as f32
+    }
+
+    /// Map a screen pixel (origin top-left) to the z = 0 plane (orthographic only)
+    pub fn screen_to_world(&self, x: f32, y: f32, screen_width: u32, screen_height: u32) -> (f32, f32) {
+        let ndc_x = 2.0 * x / screen_width.max(1) as f32 - 1.0;
+        let ndc_y = 1.0 - 2.0 * y / screen_height.max(1) as f32;
+        let half_size = self.ortho_size / 2.0;
+        (
+            self.target.x + ndc_x * half_size * self.aspect,
+            self.target.y + ndc_y * half_size,
+        )
+    }
+
+    /// Zoom in by `factor` (> 1 shows less of the world), clamped to a sane range
+    pub fn zoom(&mut self, factor: f32) {
+        if factor > 0.0 && factor.is_finite() {
+            self.ortho_size = (self.ortho_size / factor).clamp(self.zoom_limits.0, self.zoom_limits.1);
+        }
+    }
+
+    /// Show `height` world units vertically, clamped like `zoom`
+    pub fn set_view_height(&mut self, height: f32) {
+        if height.is_finite() {
+            self.ortho_size = height.clamp(self.zoom_limits.0, self.zoom_limits.1);
+        }
+    }
+
+    /// Frame the startup view of `scale` around the current target and zoom
+    /// within its limits
+    pub fn set_world_scale(&mut self, scale: &WorldScale) {
+        self.zoom_limits = scale.zoom_limits();
+        self.ortho_size = scale.camera_height();
+    }
+
+    /// Move eye and target together so the camera looks at (x, y)
+    pub fn look_at(&mut self, x: f32, y: f32) {
+        if x.is_finite() && y.is_finite() {
+            self.pan(x - self.target.x, y - self.target.y);
+        }
+    }
+
+    /// Move eye and target together by a world-space offset
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let offset = na::Vector3::new(dx, dy, 0.0);
+        self.eye += offset;
+        self.target += offset;
+    }
 }
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct CameraUniform {
     pub view_proj: [[f32; 4]; 4],
+    /// Point the camera looks at, which parallax layers scroll against.
+    /// Shake is left out so it moves every layer alike.
+    pub center: [f32; 2],
+    _padding: [f32; 2],
 }
 
 impl CameraUniform {
     pub fn new() -> Self {
         Self {
             view_proj: na::Matrix4::identity().into(),
+            center: [0.0, 0.0],
+            _padding: [0.0; 2],
         }
     }
 
     pub fn update_view_proj(&mut self, camera: &Camera) {
         self.view_proj = camera.build_view_projection_matrix().into();
+        self.center = [camera.target.x, camera.target.y];
+    }
+
+    pub fn update_view_proj_with(&mut self, camera: &Camera, offset: &CameraOffset) {
+        self.view_proj = camera.build_view_projection_matrix_with(offset).into();
+        self.center = [camera.target.x, camera.target.y];
     }
 }
@@ -0,0 +1,223 @@
+//! Screen shake and zoom punches layered on top of the camera
+//!
+//! Shake is trauma based: `add_trauma` raises a 0..1 trauma value that decays
+//! linearly at `trauma_decay` per second, and the camera is displaced by
+//! smooth noise scaled by trauma squared, so small knocks barely move it and
+//! big hits shake hard. Collisions can add trauma on their own once
+//! `trauma_per_impulse` is non-zero.
+//!
+//! A zoom punch briefly zooms in (or out, for negative amounts) and eases
+//! back: the punch sets a target that decays at `zoom_decay` per second and
+//! the applied zoom follows it smoothly.
+//!
+//! Effects never move the camera itself. They produce a `CameraOffset` that
+//! is folded into the view-projection matrix each frame, so panning, zooming
+//! and screen-to-world mapping keep using the steady camera.
+
+/// Trauma is clamped to this
+pub const MAX_TRAUMA: f32 = 1.0;
+/// Zoom punches are clamped to this range (0.5 is 50% closer)
+pub const MIN_ZOOM_PUNCH: f32 = -0.5;
+pub const MAX_ZOOM_PUNCH: f32 = 1.0;
+
+/// How fast the applied zoom follows its target, per second
+const ZOOM_FOLLOW_RATE: f32 = 20.0;
+/// Zoom differences below this count as settled
+const ZOOM_EPSILON: f32 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraEffectsConfig {
+    /// Largest displacement at full trauma, world units
+    pub max_offset: f32,
+    /// Largest roll at full trauma, radians
+    pub max_rotation: f32,
+    /// Shake noise frequency, Hz
+    pub frequency: f32,
+    /// Trauma lost per second
+    pub trauma_decay: f32,
+    /// Zoom punch decay rate, per second
+    pub zoom_decay: f32,
+    /// Collisions below this impulse (N·s) add no trauma
+    pub min_impulse: f32,
+    /// Trauma per N·s above `min_impulse`; 0 turns impact shake off
+    pub trauma_per_impulse: f32,
+}
+
+impl Default for CameraEffectsConfig {
+    fn default() -> Self {
+        Self {
+            max_offset: 0.1,
+            max_rotation: 0.05,
+            frequency: 15.0,
+            trauma_decay: 1.5,
+            zoom_decay: 6.0,
+            min_impulse: 0.05,
+            trauma_per_impulse: 0.0,
+        }
+    }
+}
+
+impl CameraEffectsConfig {
+    /// Amplitudes must be finite and >= 0, frequency > 0
+    pub fn with_shake(self, max_offset: f32, max_rotation: f32, frequency: f32) -> Option<Self> {
+        let valid = [max_offset, max_rotation, frequency].iter().all(|v| v.is_finite())
+            && max_offset >= 0.0
+            && max_rotation >= 0.0
+            && frequency > 0.0;
+        valid.then_some(Self { max_offset, max_rotation, frequency, ..self })
+    }
+
+    /// Both rates must be finite and > 0
+    pub fn with_decay(self, trauma_decay: f32, zoom_decay: f32) -> Option<Self> {
+        let valid = trauma_decay.is_finite() && zoom_decay.is_finite() && trauma_decay > 0.0 && zoom_decay > 0.0;
+        valid.then_some(Self { trauma_decay, zoom_decay, ..self })
+    }
+
+    /// Both values must be finite and >= 0
+    pub fn with_impact_shake(self, min_impulse: f32, trauma_per_impulse: f32) -> Option<Self> {
+        let valid = min_impulse.is_finite()
+            && trauma_per_impulse.is_finite()
+            && min_impulse >= 0.0
+            && trauma_per_impulse >= 0.0;
+        valid.then_some(Self { min_impulse, trauma_per_impulse, ..self })
+    }
+
+    /// Trauma a collision with `impulse` adds
+    pub fn impact_trauma(&self, impulse: f32) -> f32 {
+        if impulse > self.min_impulse {
+            ((impulse - self.min_impulse) * self.trauma_per_impulse).min(MAX_TRAUMA)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// What the effects do to the camera this frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CameraOffset {
+    /// World-space displacement of eye and target
+    pub dx: f32,
+    pub dy: f32,
+    /// Roll around the view axis, radians
+    pub rotation: f32,
+    /// 0 for none; the view shrinks by `1 + zoom`
+    pub zoom: f32,
+}
+
+impl CameraOffset {
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CameraEffects {
+    config: CameraEffectsConfig,
+    trauma: f32,
+    zoom: f32,
+    zoom_target: f32,
+    /// Noise time, seconds; only advances while shaking
+    time: f32,
+}
+
+impl CameraEffects {
+    pub fn new(config: CameraEffectsConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> CameraEffectsConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: CameraEffectsConfig) {
+        self.config = config;
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Add shake; negative or non-finite amounts are ignored
+    pub fn add_trauma(&mut self, amount: f32) {
+        if amount > 0.0 && amount.is_finite() {
+            self.trauma = (self.trauma + amount).min(MAX_TRAUMA);
+        }
+    }
+
+    /// Shake for a collision with `impulse`, per the config
+    pub fn on_impact(&mut self, impulse: f32) {
+        self.add_trauma(self.config.impact_trauma(impulse));
+    }
+
+    /// Zoom in by `amount` (0.2 is 20% closer; negative zooms out) and ease back
+    pub fn punch_zoom(&mut self, amount: f32) {
+        if amount.is_finite() {
+            self.zoom_target = (self.zoom_target + amount).clamp(MIN_ZOOM_PUNCH, MAX_ZOOM_PUNCH);
+        }
+    }
+
+    /// Whether `offset` would currently move the camera
+    pub fn is_active(&self) -> bool {
+        self.trauma > 0.0 || self.zoom != 0.0 || self.zoom_target != 0.0
+    }
+
+    /// Stop all effects at once
+    pub fn clear(&mut self) {
+        self.trauma = 0.0;
+        self.zoom = 0.0;
+        self.zoom_target = 0.0;
+    }
+
+    /// Advance by `dt` seconds of wall time
+    pub fn update(&mut self, dt: f32) {
+        if !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        if self.trauma > 0.0 {
+            self.time += dt;
+            self.trauma = (self.trauma - self.config.trauma_decay * dt).max(0.0);
+        } else {
+            self.time = 0.0;
+        }
+
+        self.zoom_target *= (-self.config.zoom_decay * dt).exp();
+        self.zoom += (self.zoom_target - self.zoom) * (1.0 - (-ZOOM_FOLLOW_RATE * dt).exp());
+        if self.zoom_target.abs() < ZOOM_EPSILON && self.zoom.abs() < ZOOM_EPSILON {
+            self.zoom = 0.0;
+            self.zoom_target = 0.0;
+        }
+    }
+
+    /// Camera displacement for the current state
+    pub fn offset(&self) -> CameraOffset {
+        let shake = self.trauma * self.trauma;
+        let t = self.time * self.config.frequency;
+        if shake == 0.0 {
+            return CameraOffset { zoom: self.zoom, ..CameraOffset::default() };
+        }
+        CameraOffset {
+            dx: self.config.max_offset * shake * noise(0, t),
+            dy: self.config.max_offset * shake * noise(1, t),
+            rotation: self.config.max_rotation * shake * noise(2, t),
+            zoom: self.zoom,
+        }
+    }
+}
+
+/// Smooth value noise in -1..1, one independent channel per `seed`
+fn noise(seed: u32, t: f32) -> f32 {
+    let cell = t.floor();
+    let frac = t - cell;
+    let smooth = frac * frac * (3.0 - 2.0 * frac);
+    let a = lattice(seed, cell as i32);
+    let b = lattice(seed, cell as i32 + 1);
+    a + (b - a) * smooth
+}
+
+fn lattice(seed: u32, i: i32) -> f32 {
+    let mut h = (i as u32).wrapping_mul(0x9E37_79B9) ^ seed.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
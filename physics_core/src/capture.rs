@@ -0,0 +1,408 @@
+//! Offline frame capture for making clips
+//!
+//! While a capture runs, every update advances the simulation by exactly one
+//! frame at the capture rate, however much real time went by, and every
+//! render draws the full frame into an offscreen texture instead of the
+//! surface, waits for the GPU and hands the pixels on: as numbered PNG files
+//! in a directory, as raw RGBA to a sink, or both. A slow device just takes
+//! longer to make the same smooth clip.
+//!
+//! Frames have the surface's size, and the window keeps showing the last
+//! frame presented before the capture started. PNGs are stored uncompressed
+//! to keep the render loop cheap; re-encode them (e.g. with ffmpeg) for
+//! anything but an intermediate. Capture needs a blocking readback, so it is
+//! not offered on the web.
+//!
+//! A screenshot is a one-frame capture of the next frame rendered, egui
+//! included when the winit app draws it. That frame is drawn into the same
+//! offscreen texture and then copied onto the surface, so the picture is
+//! exactly what was presented; on surfaces that take no copies the window
+//! skips that one frame instead. The winit app takes one with a key (F12 by
+//! default) and writes it to a directory.
+
+use std::ffi::c_void;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+/// Highest capture frame rate
+pub const MAX_FPS: u32 = 240;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CaptureConfig {
+    /// Clip frame rate; each frame advances the simulation 1/fps seconds
+    pub fps: u32,
+    /// Directory for the PNG files, created if missing; None only hands
+    /// frames to the sink
+    pub directory: Option<String>,
+    /// File names are the prefix followed by the six-digit frame number
+    pub prefix: String,
+    /// Stop after this many frames; 0 runs until stopped
+    pub max_frames: u32,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self { fps: 60, directory: None, prefix: "frame_".to_string(), max_frames: 0 }
+    }
+}
+
+impl CaptureConfig {
+    pub fn is_valid(&self) -> bool {
+        (1..=MAX_FPS).contains(&self.fps) && !self.prefix.contains(['/', '\\'])
+    }
+
+    /// Simulated seconds per frame
+    pub fn step(&self) -> f32 {
+        1.0 / self.fps as f32
+    }
+
+    pub fn file_name(&self, frame: u64) -> String {
+        format!("{}{:06}.png", self.prefix, frame)
+    }
+}
+
+/// One captured frame: `width * height` RGBA pixels, top row first
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedFrame {
+    /// Counts from 0 at the start of the capture
+    pub index: u64,
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// Receives every captured frame on the render thread, with no lock held
+pub type FrameSink = Arc<dyn Fn(&CapturedFrame) + Send + Sync>;
+
+/// C callback: `(frame_index, width, height, rgba, user_data)`; `rgba` is
+/// only valid during the call
+pub type HostFrameFn = extern "C" fn(u64, u32, u32, *const u8, *mut c_void);
+
+struct HostCallback {
+    func: HostFrameFn,
+    user_data: *mut c_void,
+}
+
+// SAFETY: as for host hooks, the pointer is only handed back to the host
+unsafe impl Send for HostCallback {}
+unsafe impl Sync for HostCallback {}
+
+impl HostCallback {
+    // Through a method, closures capture the whole wrapper rather than the
+    // bare pointer field, which is not Send
+    fn call(&self, frame: &CapturedFrame) {
+        (self.func)(frame.index, frame.width, frame.height, frame.rgba.as_ptr(), self.user_data)
+    }
+}
+
+/// Sink calling a C callback
+pub(crate) fn host_sink(func: HostFrameFn, user_data: *mut c_void) -> FrameSink {
+    let host = HostCallback { func, user_data };
+    Arc::new(move |frame: &CapturedFrame| host.call(frame))
+}
+
+struct Capture {
+    config: CaptureConfig,
+    sink: Option<FrameSink>,
+    next_frame: u64,
+}
+
+/// The running capture. Never held together with another lock, nor while
+/// frames are written or handed to the sink.
+static CAPTURE: Lazy<Mutex<Option<Capture>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start capturing, replacing any capture in progress. Fails for invalid
+/// configs, directories that cannot be created and captures that would go
+/// nowhere (no directory and no sink).
+pub fn start(config: CaptureConfig, sink: Option<FrameSink>) -> Result<(), String> {
+    if !config.is_valid() {
+        return Err("invalid capture config".to_string());
+    }
+    match &config.directory {
+        Some(directory) => std::fs::create_dir_all(directory).map_err(|e| format!("{}: {}", directory, e))?,
+        None if sink.is_none() => return Err("capture needs a directory or a sink".to_string()),
+        None => {}
+    }
+    let mut capture = CAPTURE.lock().map_err(|e| e.to_string())?;
+    *capture = Some(Capture { config, sink, next_frame: 0 });
+    Ok(())
+}
+
+/// Stop capturing. Returns the number of frames captured, 0 if none was running.
+pub fn stop() -> u64 {
+    CAPTURE.lock().ok().and_then(|mut capture| capture.take()).map_or(0, |capture| capture.next_frame)
+}
+
+pub fn is_active() -> bool {
+    CAPTURE.lock().is_ok_and(|capture| capture.is_some())
+}
+
+/// Simulated seconds per update while capturing
+pub fn fixed_step() -> Option<f32> {
+    CAPTURE.lock().ok()?.as_ref().map(|capture| capture.config.step())
+}
+
+/// Number and write out a frame read back by the renderer. The capture ends
+/// after its last frame, or when a file cannot be written.
+pub(crate) fn deliver(width: u32, height: u32, rgba: Vec<u8>) {
+    let (directory, file_name, sink, index) = {
+        let Ok(mut guard) = CAPTURE.lock() else {
+            return;
+        };
+        let Some(capture) = guard.as_mut() else {
+            return;
+        };
+        let index = capture.next_frame;
+        capture.next_frame += 1;
+        let taken = (capture.config.directory.clone(), capture.config.file_name(index), capture.sink.clone(), index);
+        if capture.config.max_frames != 0 && capture.next_frame >= capture.config.max_frames as u64 {
+            *guard = None;
+        }
+        taken
+    };
+
+    let frame = CapturedFrame { index, width, height, rgba };
+    if let Some(directory) = directory {
+        let path = std::path::Path::new(&directory).join(file_name);
+        if let Err(e) = std::fs::write(&path, encode_png(width, height, &frame.rgba)) {
+            log::error!("capture: writing {} failed, stopping: {}", path.display(), e);
+            stop();
+        }
+    }
+    if let Some(sink) = sink {
+        sink(&frame);
+    }
+}
+
+/// Where a screenshot goes
+pub enum ScreenshotTarget {
+    /// PNG file, replaced if it exists
+    File(PathBuf),
+    /// Called with the frame, index 0, on the render thread
+    Sink(FrameSink),
+}
+
+/// Screenshots waiting for the next frame. Never held together with another
+/// lock.
+static SCREENSHOTS: Lazy<Mutex<Vec<ScreenshotTarget>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Capture the next frame rendered to `target`
+pub fn request_screenshot(target: ScreenshotTarget) {
+    if let Ok(mut screenshots) = SCREENSHOTS.lock() {
+        screenshots.push(target);
+    }
+}
+
+pub fn screenshot_pending() -> bool {
+    SCREENSHOTS.lock().is_ok_and(|screenshots| !screenshots.is_empty())
+}
+
+/// The screenshots a frame read back now fulfils
+pub(crate) fn take_screenshots() -> Vec<ScreenshotTarget> {
+    SCREENSHOTS.lock().map(|mut screenshots| std::mem::take(&mut *screenshots)).unwrap_or_default()
+}
+
+pub(crate) fn deliver_screenshots(targets: Vec<ScreenshotTarget>, frame: &CapturedFrame) {
+    for target in targets {
+        match target {
+            ScreenshotTarget::File(path) => match std::fs::write(&path, encode_png(frame.width, frame.height, &frame.rgba)) {
+                Ok(()) => log::info!("Screenshot saved to {}", path.display()),
+                Err(e) => log::error!("screenshot: writing {} failed: {}", path.display(), e),
+            },
+            ScreenshotTarget::Sink(sink) => sink(frame),
+        }
+    }
+}
+
+/// A new file name in `directory` for a screenshot taken at `unix_millis`
+pub fn screenshot_path(directory: &Path, unix_millis: u128) -> PathBuf {
+    directory.join(format!("screenshot_{}.png", unix_millis))
+}
+
+/// Tightly packed RGBA from rows padded to `padded_bytes_per_row`, swapping
+/// red and blue for BGRA textures
+pub fn unpad_rows(data: &[u8], width: u32, height: u32, padded_bytes_per_row: u32, bgra: bool) -> Vec<u8> {
+    let row_bytes = width as usize * 4;
+    let mut rgba = Vec::with_capacity(row_bytes * height as usize);
+    for row in data.chunks(padded_bytes_per_row as usize).take(height as usize) {
+        rgba.extend_from_slice(&row[..row_bytes]);
+    }
+    if bgra {
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    rgba
+}
+
+/// 8-bit RGBA PNG with the image data in stored (uncompressed) deflate blocks
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    // Each scanline starts with filter type 0 (none)
+    let row_bytes = width as usize * 4;
+    let mut raw = Vec::with_capacity((row_bytes + 1) * height as usize);
+    for row in rgba.chunks(row_bytes.max(1)).take(height as usize) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlace
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &zlib);
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc32(&[kind.as_slice(), data].concat()).to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    static TABLE: Lazy<[u32; 256]> = Lazy::new(|| {
+        let mut table = [0u32; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            }
+            *entry = c;
+        }
+        table
+    });
+    !bytes.iter().fold(!0u32, |crc, &byte| TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65_521;
+        (a, (b + a) % 65_521)
+    });
+    (b << 16) | a
+}
+
+/// Offscreen copy of the frame and the buffer it is read back through
+pub(crate) struct CaptureTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    buffer: wgpu::Buffer,
+    padded_bytes_per_row: u32,
+    bgra: bool,
+}
+
+impl CaptureTarget {
+    /// Whether frames in `format` can be captured: 8-bit RGBA or BGRA
+    pub fn supports(format: wgpu::TextureFormat) -> bool {
+        Self::is_bgra(format).is_some()
+    }
+
+    fn is_bgra(format: wgpu::TextureFormat) -> Option<bool> {
+        match format {
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => Some(true),
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Target matching the surface; None for formats `supports` rejects
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Option<Self> {
+        let bgra = Self::is_bgra(config.format)?;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Capture Texture"),
+            size: wgpu::Extent3d { width: config.width, height: config.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let padded_bytes_per_row = wgpu::util::align_to(config.width * 4, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture Readback Buffer"),
+            size: padded_bytes_per_row as u64 * config.height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Some(Self { texture, view, buffer, padded_bytes_per_row, bgra })
+    }
+
+    pub fn matches(&self, config: &wgpu::SurfaceConfiguration) -> bool {
+        self.texture.width() == config.width && self.texture.height() == config.height && self.texture.format() == config.format
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// Copy the finished frame into the readback buffer
+    pub fn encode_copy(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            self.texture.size(),
+        );
+    }
+
+    /// Copy the finished frame onto the surface texture being presented,
+    /// which needs `COPY_DST` usage
+    pub fn encode_present_copy(&self, encoder: &mut wgpu::CommandEncoder, surface: &wgpu::Texture) {
+        encoder.copy_texture_to_texture(self.texture.as_image_copy(), surface.as_image_copy(), self.texture.size());
+    }
+
+    /// Wait for the copy and return the frame as RGBA; call after submitting
+    /// the encoder passed to `encode_copy`
+    pub fn read(&self, device: &wgpu::Device) -> Option<Vec<u8>> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        if let Err(e) = device.poll(wgpu::PollType::wait_indefinitely()) {
+            log::error!("capture: waiting for the frame failed: {:?}", e);
+        }
+        match receiver.try_recv() {
+            Ok(Ok(())) => {}
+            result => {
+                log::error!("capture: mapping the frame failed: {:?}", result);
+                return None;
+            }
+        }
+        let rgba = unpad_rows(&slice.get_mapped_range(), self.texture.width(), self.texture.height(), self.padded_bytes_per_row, self.bgra);
+        self.buffer.unmap();
+        Some(rgba)
+    }
+}
@@ -0,0 +1,160 @@
+//! Command line of the standalone desktop app (`--bin physicsfx`)
+//!
+//! Parsing is kept free of windowing so the binary stays a thin `main`: it
+//! turns the arguments into `DesktopOptions` and hands them to
+//! `run_desktop_app`, or runs the headless benchmark instead. Sizes are
+//! logical pixels; the backend names map onto the `gpu_options` bits.
+
+use std::path::PathBuf;
+
+use crate::gpu_options;
+
+pub const USAGE: &str = "\
+Usage: physicsfx [OPTIONS]
+
+Options:
+  --width <PX>          Window width in logical pixels (default 1024)
+  --height <PX>         Window height in logical pixels (default 1024)
+  --size <W>x<H>        Both at once, e.g. 1280x720
+  --vsync / --no-vsync  Wait for vertical sync when presenting (default on)
+  --scene <FILE>        Load a SceneDesc JSON or RON file at startup
+  --backend <NAME>      vulkan, metal, dx12 or gl; repeat to allow several
+  --benchmark <N>       Simulate N boxes without a window and print timings
+  --frames <N>          Frames the benchmark runs (default 600)
+  -h, --help            Print this help";
+
+const DEFAULT_SIZE: (u32, u32) = (1024, 1024);
+const DEFAULT_BENCHMARK_FRAMES: u32 = 600;
+/// Largest window side accepted, in logical pixels
+const MAX_SIZE: u32 = 16384;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopOptions {
+    pub width: u32,
+    pub height: u32,
+    pub vsync: bool,
+    /// SceneDesc JSON or RON file loaded in place of the default scene
+    pub scene: Option<PathBuf>,
+    /// `gpu_options::BACKEND_*` bits; 0 allows every backend
+    pub backends: u32,
+}
+
+impl Default for DesktopOptions {
+    fn default() -> Self {
+        Self { width: DEFAULT_SIZE.0, height: DEFAULT_SIZE.1, vsync: true, scene: None, backends: 0 }
+    }
+}
+
+/// Headless run: a stress scene of `bodies` boxes stepped for `frames` fixed frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Benchmark {
+    pub bodies: u32,
+    pub frames: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Run(DesktopOptions),
+    Benchmark(Benchmark),
+    Help,
+}
+
+/// Parse the arguments after the program name
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut options = DesktopOptions::default();
+    let mut bodies = None;
+    let mut frames = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // `--flag=value` is the same as `--flag value`
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg, None),
+        };
+        let mut value = || inline.clone().or_else(|| args.next()).ok_or_else(|| format!("{} needs a value", flag));
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--width" => options.width = size(&flag, &value()?)?,
+            "--height" => options.height = size(&flag, &value()?)?,
+            "--size" => {
+                let value = value()?;
+                let (width, height) = value.split_once('x').ok_or_else(|| format!("--size takes <W>x<H>, not '{}'", value))?;
+                options.width = size(&flag, width)?;
+                options.height = size(&flag, height)?;
+            }
+            "--vsync" => options.vsync = true,
+            "--no-vsync" => options.vsync = false,
+            "--scene" => options.scene = Some(PathBuf::from(value()?)),
+            "--backend" => options.backends |= backend(&value()?)?,
+            "--benchmark" => bodies = Some(count(&flag, &value()?)?),
+            "--frames" => frames = Some(count(&flag, &value()?)?),
+            _ => return Err(format!("unknown option '{}'", flag)),
+        }
+    }
+    match (bodies, frames) {
+        (Some(bodies), frames) => Ok(Command::Benchmark(Benchmark { bodies, frames: frames.unwrap_or(DEFAULT_BENCHMARK_FRAMES) })),
+        (None, Some(_)) => Err("--frames only applies with --benchmark".to_string()),
+        (None, None) => Ok(Command::Run(options)),
+    }
+}
+
+fn size(flag: &str, value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(size) if (1..=MAX_SIZE).contains(&size) => Ok(size),
+        _ => Err(format!("{} takes a size from 1 to {}, not '{}'", flag, MAX_SIZE, value)),
+    }
+}
+
+fn count(flag: &str, value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("{} takes a count above 0, not '{}'", flag, value)),
+    }
+}
+
+/// Backend bit for a name, case-insensitively
+pub fn backend(name: &str) -> Result<u32, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "vulkan" | "vk" => Ok(gpu_options::BACKEND_VULKAN),
+        "metal" => Ok(gpu_options::BACKEND_METAL),
+        "dx12" | "d3d12" => Ok(gpu_options::BACKEND_DX12),
+        "gl" | "opengl" | "gles" => Ok(gpu_options::BACKEND_GL),
+        _ => Err(format!("unknown backend '{}' (vulkan, metal, dx12 or gl)", name)),
+    }
+}
+
+/// Frame times of a benchmark run, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchmarkReport {
+    pub bodies: u32,
+    pub frames: u32,
+    pub mean_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+impl BenchmarkReport {
+    /// Summary of `frame_ms`, one entry per frame; all zero for no frames
+    pub fn from_frame_times(bodies: u32, frame_ms: &[f64]) -> Self {
+        let mut sorted = frame_ms.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let mean_ms = if sorted.is_empty() { 0.0 } else { sorted.iter().sum::<f64>() / sorted.len() as f64 };
+        // Nearest rank
+        let p95_ms = match sorted.len() {
+            0 => 0.0,
+            n => sorted[((n * 95).div_ceil(100)).max(1) - 1],
+        };
+        Self { bodies, frames: sorted.len() as u32, mean_ms, p95_ms, max_ms: sorted.last().copied().unwrap_or(0.0) }
+    }
+}
+
+impl std::fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let steps_per_second = if self.mean_ms > 0.0 { 1000.0 / self.mean_ms } else { 0.0 };
+        write!(
+            f,
+            "{} bodies, {} frames: mean {:.3} ms, p95 {:.3} ms, max {:.3} ms ({:.0} steps/s)",
+            self.bodies, self.frames, self.mean_ms, self.p95_ms, self.max_ms, steps_per_second
+        )
+    }
+}
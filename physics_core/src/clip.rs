@@ -0,0 +1,254 @@
+//! Animated GIF clips of the last few seconds
+//!
+//! `export_clip` plays back the rewind history (see `snapshot`) without
+//! disturbing the running simulation: for each clip frame the bodies are
+//! drawn where that history entry had them, offscreen at the surface size,
+//! read back, scaled down and appended to a looping GIF. Afterwards the
+//! live state is drawn again as if nothing happened.
+//!
+//! Only body poses come from the history; everything else (tints, sprites,
+//! water, trails, the camera) is drawn as it is now, and bodies removed
+//! since are missing from the clip. Colors are mapped onto a fixed
+//! 6x7x6 palette, which is plenty for a bug report or a quick demo. The
+//! clip is only as long as the history, so raise it with
+//! `set_rewind_history` for longer clips. Frames are read back blocking,
+//! which on the web works because the WebGL backend finishes on poll.
+
+use std::collections::HashMap;
+
+/// Highest clip frame rate; GIF delays are whole hundredths of a second and
+/// viewers slow down anything faster than 50 fps
+pub const MAX_FPS: u32 = 50;
+
+/// Levels per channel of the fixed palette
+const LEVELS: [u32; 3] = [6, 7, 6];
+
+/// Largest LZW code; the table starts over once it is used
+const MAX_CODE: u16 = 4095;
+
+/// One GIF frame: a history entry (oldest first) and how long it shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipFrame {
+    pub index: usize,
+    /// Hundredths of a second
+    pub delay: u16,
+}
+
+/// Whether a clip of `seconds` at `fps`, `scale` times the surface size,
+/// can be made
+pub fn is_valid(seconds: f32, fps: u32, scale: f32) -> bool {
+    seconds.is_finite() && seconds > 0.0 && (1..=MAX_FPS).contains(&fps) && scale > 0.0 && scale <= 1.0
+}
+
+/// The history entries to show for the last `seconds` at `fps`, given each
+/// entry's step length, oldest first. An entry that lasts several clip
+/// frames, as when steps are longer than frames, becomes one GIF frame with
+/// a longer delay. Shorter histories give shorter clips.
+pub fn pick_frames(step_dts: &[f32], seconds: f32, fps: u32) -> Vec<ClipFrame> {
+    if step_dts.is_empty() {
+        return Vec::new();
+    }
+    // Time at the end of each step, relative to the oldest entry
+    let ends: Vec<f32> = step_dts[1..]
+        .iter()
+        .scan(0.0, |time, dt| {
+            *time += dt;
+            Some(*time)
+        })
+        .collect();
+    let newest = ends.last().copied().unwrap_or(0.0);
+    let count = (seconds.min(newest) * fps as f32).round().max(1.0) as usize;
+
+    let mut frames: Vec<ClipFrame> = Vec::new();
+    let mut run_start = 0;
+    let delay_until = |frame: usize| (frame as f32 * 100.0 / fps as f32).round() as u16;
+    for k in 0..count {
+        let target = newest - (count - 1 - k) as f32 / fps as f32;
+        // Latest entry at or before the target; the oldest one stands at 0
+        let index = ends.partition_point(|&end| end <= target + 1e-4);
+        match frames.last_mut() {
+            Some(last) if last.index == index => {}
+            Some(last) => {
+                last.delay = delay_until(k) - delay_until(run_start);
+                run_start = k;
+                frames.push(ClipFrame { index, delay: 0 });
+            }
+            None => frames.push(ClipFrame { index, delay: 0 }),
+        }
+    }
+    if let Some(last) = frames.last_mut() {
+        last.delay = delay_until(count) - delay_until(run_start);
+    }
+    frames
+}
+
+/// `width * height` RGBA pixels scaled by `scale` (0 < scale <= 1), each
+/// output pixel the average of the pixels it covers. Returns the new size
+/// and pixels.
+pub fn downscale(rgba: &[u8], width: u32, height: u32, scale: f32) -> (u32, u32, Vec<u8>) {
+    let out_width = ((width as f32 * scale).round() as u32).clamp(1, width.max(1));
+    let out_height = ((height as f32 * scale).round() as u32).clamp(1, height.max(1));
+    if (out_width, out_height) == (width, height) {
+        return (width, height, rgba.to_vec());
+    }
+    let span = |i: u32, out: u32, size: u32| {
+        let start = (i as u64 * size as u64 / out as u64) as usize;
+        let end = ((i as u64 + 1) * size as u64 / out as u64) as usize;
+        start..end.max(start + 1)
+    };
+    let mut scaled = Vec::with_capacity(out_width as usize * out_height as usize * 4);
+    for y in 0..out_height {
+        let rows = span(y, out_height, height);
+        for x in 0..out_width {
+            let columns = span(x, out_width, width);
+            let mut sum = [0u32; 4];
+            for row in rows.clone() {
+                let start = (row * width as usize + columns.start) * 4;
+                let end = (row * width as usize + columns.end) * 4;
+                for pixel in rgba[start..end].chunks_exact(4) {
+                    for (total, &channel) in sum.iter_mut().zip(pixel) {
+                        *total += channel as u32;
+                    }
+                }
+            }
+            let covered = (rows.len() * columns.len()) as u32;
+            scaled.extend(sum.map(|total| ((total + covered / 2) / covered) as u8));
+        }
+    }
+    (out_width, out_height, scaled)
+}
+
+/// Palette index of the nearest fixed palette color
+fn palette_index(pixel: &[u8]) -> u8 {
+    let [r, g, b] = [0, 1, 2].map(|c| (pixel[c] as u32 * (LEVELS[c] - 1) + 127) / 255);
+    ((r * LEVELS[1] + g) * LEVELS[2] + b) as u8
+}
+
+/// The fixed palette, padded with black to 256 entries
+fn palette() -> Vec<u8> {
+    let mut colors = Vec::with_capacity(256 * 3);
+    for r in 0..LEVELS[0] {
+        for g in 0..LEVELS[1] {
+            for b in 0..LEVELS[2] {
+                colors.extend([r, g, b].iter().zip(LEVELS).map(|(&level, levels)| (level * 255 / (levels - 1)) as u8));
+            }
+        }
+    }
+    colors.resize(256 * 3, 0);
+    colors
+}
+
+/// Looping GIF built one full frame at a time
+pub struct GifWriter {
+    width: u16,
+    height: u16,
+    bytes: Vec<u8>,
+}
+
+impl GifWriter {
+    /// None for sizes a GIF cannot hold
+    pub fn new(width: u32, height: u32) -> Option<Self> {
+        let width = u16::try_from(width).ok().filter(|&w| w > 0)?;
+        let height = u16::try_from(height).ok().filter(|&h| h > 0)?;
+        let mut bytes = b"GIF89a".to_vec();
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        // 256-color global table, 8 bits per primary
+        bytes.extend_from_slice(&[0xF7, 0, 0]);
+        bytes.extend(palette());
+        // Loop forever
+        bytes.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+        bytes.extend_from_slice(b"NETSCAPE2.0");
+        bytes.extend_from_slice(&[3, 1, 0, 0, 0]);
+        Some(Self { width, height, bytes })
+    }
+
+    /// Append `width * height` RGBA pixels shown for `delay` hundredths of a
+    /// second; alpha is ignored
+    pub fn add_frame(&mut self, rgba: &[u8], delay: u16) {
+        self.bytes.extend_from_slice(&[0x21, 0xF9, 4, 0]);
+        self.bytes.extend_from_slice(&delay.to_le_bytes());
+        self.bytes.extend_from_slice(&[0, 0]);
+
+        self.bytes.push(0x2C);
+        self.bytes.extend_from_slice(&[0, 0, 0, 0]);
+        self.bytes.extend_from_slice(&self.width.to_le_bytes());
+        self.bytes.extend_from_slice(&self.height.to_le_bytes());
+        self.bytes.push(0);
+
+        let pixels = self.width as usize * self.height as usize;
+        let indices: Vec<u8> = rgba.chunks_exact(4).take(pixels).map(palette_index).collect();
+        self.bytes.push(8);
+        for block in lzw(&indices).chunks(255) {
+            self.bytes.push(block.len() as u8);
+            self.bytes.extend_from_slice(block);
+        }
+        self.bytes.push(0);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.bytes.push(0x3B);
+        self.bytes
+    }
+}
+
+/// GIF-flavored LZW of 8-bit palette indices, codes packed LSB first
+fn lzw(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+
+    let mut out = Vec::new();
+    let mut bits = 0u32;
+    let mut pending = 0u32;
+    let mut write = |code: u16, size: u32, out: &mut Vec<u8>| {
+        bits |= (code as u32) << pending;
+        pending += size;
+        while pending >= 8 {
+            out.push(bits as u8);
+            bits >>= 8;
+            pending -= 8;
+        }
+    };
+
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = 9;
+    let mut next = END + 1;
+    write(CLEAR, size, &mut out);
+    let mut iter = indices.iter();
+    let Some(&first) = iter.next() else {
+        write(END, size, &mut out);
+        write(0, 7, &mut out);
+        return out;
+    };
+    let mut prefix = first as u16;
+    for &index in iter {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        write(prefix, size, &mut out);
+        // The decoder's table is one entry behind, so widen once `next`
+        // no longer fits
+        if next > (1 << size) - 1 && size < 12 {
+            size += 1;
+        }
+        if next <= MAX_CODE {
+            table.insert((prefix, index), next);
+            next += 1;
+        } else {
+            write(CLEAR, size, &mut out);
+            table.clear();
+            size = 9;
+            next = END + 1;
+        }
+        prefix = index as u16;
+    }
+    write(prefix, size, &mut out);
+    if next > (1 << size) - 1 && size < 12 {
+        size += 1;
+    }
+    write(END, size, &mut out);
+    // Flush the last partial byte
+    write(0, 7, &mut out);
+    out
+}
@@ -0,0 +1,167 @@
+//! Packed command buffers for hosts where every native call is expensive
+//!
+//! Crossing JNI once per body dominates frame time when a level spawns
+//! hundreds of them. Instead the host writes commands into one byte buffer
+//! and submits it in a single call; the whole buffer runs under one lock of
+//! the physics state.
+//!
+//! A buffer is a sequence of commands, each a one-byte opcode followed by a
+//! fixed payload. Every field is little-endian: ids are u64, everything else
+//! is 4 bytes wide (f32 or u32), with no padding between fields or commands.
+//!
+//! | opcode | command        | payload                                        |
+//! |--------|----------------|------------------------------------------------|
+//! | 1      | `SPAWN`        | x, y, half_extent: f32; body_type, groups: u32 |
+//! | 2      | `IMPULSE`      | id: u64; x, y: f32                             |
+//! | 3      | `SET_VELOCITY` | id: u64; vx, vy: f32                           |
+//! | 4      | `SET_POSITION` | id: u64; x, y: f32                             |
+//! | 5      | `SET_GROUPS`   | id: u64; groups: u32                           |
+//! | 6      | `SET_MATERIAL` | id: u64; material_id: u32                      |
+//!
+//! `body_type` takes the `PHYSICS_CORE_BODY_*` values. A buffer that does not
+//! decode is rejected before anything runs. Otherwise every command produces
+//! one u64 result, in order: the new entity id for a spawn and 1 for any
+//! other command that took effect, with 0 for commands that failed (unknown
+//! id, invalid size or material).
+
+pub const SPAWN: u8 = 1;
+pub const IMPULSE: u8 = 2;
+pub const SET_VELOCITY: u8 = 3;
+pub const SET_POSITION: u8 = 4;
+pub const SET_GROUPS: u8 = 5;
+pub const SET_MATERIAL: u8 = 6;
+
+/// Most commands one buffer may hold
+pub const MAX_COMMANDS: usize = 65_536;
+
+/// One decoded command
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Spawn { x: f32, y: f32, half_extent: f32, body_type: u32, groups: u32 },
+    Impulse { entity_id: u64, x: f32, y: f32 },
+    SetVelocity { entity_id: u64, vx: f32, vy: f32 },
+    SetPosition { entity_id: u64, x: f32, y: f32 },
+    SetGroups { entity_id: u64, groups: u32 },
+    SetMaterial { entity_id: u64, material_id: u32 },
+}
+
+/// Reads little-endian fields off the front of a buffer
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let field = self.bytes.get(self.offset..self.offset + N)?.try_into().ok()?;
+        self.offset += N;
+        Some(field)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|[byte]| byte)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.take().map(f32::from_le_bytes)
+    }
+}
+
+/// Payload of a command with a known `opcode`; None if the buffer ends first
+fn read_payload(reader: &mut Reader, opcode: u8) -> Option<Command> {
+    Some(match opcode {
+        SPAWN => Command::Spawn {
+            x: reader.f32()?,
+            y: reader.f32()?,
+            half_extent: reader.f32()?,
+            body_type: reader.u32()?,
+            groups: reader.u32()?,
+        },
+        IMPULSE => Command::Impulse { entity_id: reader.u64()?, x: reader.f32()?, y: reader.f32()? },
+        SET_VELOCITY => Command::SetVelocity { entity_id: reader.u64()?, vx: reader.f32()?, vy: reader.f32()? },
+        SET_POSITION => Command::SetPosition { entity_id: reader.u64()?, x: reader.f32()?, y: reader.f32()? },
+        SET_GROUPS => Command::SetGroups { entity_id: reader.u64()?, groups: reader.u32()? },
+        SET_MATERIAL => Command::SetMaterial { entity_id: reader.u64()?, material_id: reader.u32()? },
+        _ => return None,
+    })
+}
+
+/// Decode a whole buffer. Errors name the byte offset of the bad command.
+pub fn decode(bytes: &[u8]) -> Result<Vec<Command>, String> {
+    let mut reader = Reader { bytes, offset: 0 };
+    let mut commands = Vec::new();
+    while let Some(opcode) = reader.u8() {
+        let start = reader.offset - 1;
+        if !(SPAWN..=SET_MATERIAL).contains(&opcode) {
+            return Err(format!("unknown opcode {} at byte {}", opcode, start));
+        }
+        if commands.len() == MAX_COMMANDS {
+            return Err(format!("more than {} commands", MAX_COMMANDS));
+        }
+        let command =
+            read_payload(&mut reader, opcode).ok_or_else(|| format!("truncated command at byte {}", start))?;
+        commands.push(command);
+    }
+    Ok(commands)
+}
+
+/// Builds a command buffer; mostly for tests and Rust hosts
+#[derive(Debug, Clone, Default)]
+pub struct CommandWriter {
+    bytes: Vec<u8>,
+}
+
+impl CommandWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: Command) -> &mut Self {
+        match command {
+            Command::Spawn { x, y, half_extent, body_type, groups } => {
+                self.bytes.push(SPAWN);
+                self.f32s(&[x, y, half_extent]).u32(body_type).u32(groups)
+            }
+            Command::Impulse { entity_id, x, y } => self.target(IMPULSE, entity_id).f32s(&[x, y]),
+            Command::SetVelocity { entity_id, vx, vy } => self.target(SET_VELOCITY, entity_id).f32s(&[vx, vy]),
+            Command::SetPosition { entity_id, x, y } => self.target(SET_POSITION, entity_id).f32s(&[x, y]),
+            Command::SetGroups { entity_id, groups } => self.target(SET_GROUPS, entity_id).u32(groups),
+            Command::SetMaterial { entity_id, material_id } => self.target(SET_MATERIAL, entity_id).u32(material_id),
+        }
+    }
+
+    /// Opcode and entity id of a command acting on one body
+    fn target(&mut self, opcode: u8, entity_id: u64) -> &mut Self {
+        self.bytes.push(opcode);
+        self.bytes.extend(entity_id.to_le_bytes());
+        self
+    }
+
+    fn f32s(&mut self, values: &[f32]) -> &mut Self {
+        for value in values {
+            self.bytes.extend(value.to_le_bytes());
+        }
+        self
+    }
+
+    fn u32(&mut self, value: u32) -> &mut Self {
+        self.bytes.extend(value.to_le_bytes());
+        self
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
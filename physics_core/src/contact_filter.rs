@@ -0,0 +1,123 @@
+//! One-way platforms and per-pair collision filtering
+//!
+//! Both work through Rapier's physics hooks, which `ContactHooks` implements
+//! on top of the conveyor hooks:
+//!
+//! - A one-way platform only holds up bodies coming from above its local +y
+//!   side. Bodies jumping up through it, or resting inside it, pass through
+//!   until they are fully on top, using Rapier's one-way contact tracking
+//!   (`update_as_oneway_platform`). The platform's rotation turns "above"
+//!   with it. Needs `ActiveHooks::MODIFY_SOLVER_CONTACTS` on the collider.
+//! - `ignore` makes two bodies pass through each other while both still
+//!   collide with everything else. Pairs are keyed by entity id, so they
+//!   survive rewinds; the filter runs for colliders flagged with
+//!   `ActiveHooks::FILTER_CONTACT_PAIRS`.
+//!
+//! Like surface velocities, one-way platforms are keyed by collider handle;
+//! handles are generational, so stale entries never match a new collider.
+
+use std::collections::HashSet;
+
+use crate::rapier::prelude::*;
+
+use crate::conveyor::{SurfaceVelocities, SurfaceVelocityHooks};
+
+/// `spawn` flag bits
+pub const SPAWN_ONE_WAY: u32 = 1;
+
+/// Contacts whose normal is within this angle (radians) of the platform's
+/// up direction count as landing on top
+const ONE_WAY_ALLOWED_ANGLE: Real = 0.1;
+
+/// Unordered pairs of entity ids that never collide
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IgnoredPairs {
+    pairs: HashSet<(u64, u64)>,
+}
+
+impl IgnoredPairs {
+    fn key(a: u64, b: u64) -> (u64, u64) {
+        (a.min(b), a.max(b))
+    }
+
+    /// Returns false if the pair was already ignored or is a body with itself
+    pub fn insert(&mut self, a: u64, b: u64) -> bool {
+        a != b && self.pairs.insert(Self::key(a, b))
+    }
+
+    /// Returns false if the pair was not ignored
+    pub fn remove(&mut self, a: u64, b: u64) -> bool {
+        self.pairs.remove(&Self::key(a, b))
+    }
+
+    pub fn contains(&self, a: u64, b: u64) -> bool {
+        self.pairs.contains(&Self::key(a, b))
+    }
+
+    /// Whether any ignored pair includes `id`
+    pub fn involves(&self, id: u64) -> bool {
+        self.pairs.iter().any(|&(a, b)| a == id || b == id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+/// Everything the contact hooks filter on
+#[derive(Debug, Clone, Default)]
+pub struct ContactFilter {
+    pub one_way: HashSet<ColliderHandle>,
+    pub ignored: IgnoredPairs,
+}
+
+/// Physics hooks handed to the pipeline: conveyors, one-way platforms and
+/// ignored pairs
+pub struct ContactHooks<'a> {
+    pub surface_velocities: &'a SurfaceVelocities,
+    pub filter: &'a ContactFilter,
+}
+
+impl ContactHooks<'_> {
+    fn entity_of(colliders: &ColliderSet, handle: ColliderHandle) -> u64 {
+        colliders.get(handle).map_or(0, |c| c.user_data as u64)
+    }
+
+    fn ignores(&self, colliders: &ColliderSet, collider1: ColliderHandle, collider2: ColliderHandle) -> bool {
+        let (a, b) = (Self::entity_of(colliders, collider1), Self::entity_of(colliders, collider2));
+        a != 0 && b != 0 && self.filter.ignored.contains(a, b)
+    }
+}
+
+impl PhysicsHooks for ContactHooks<'_> {
+    fn filter_contact_pair(&self, context: &PairFilterContext) -> Option<SolverFlags> {
+        if self.ignores(context.colliders, context.collider1, context.collider2) {
+            None
+        } else {
+            Some(SolverFlags::COMPUTE_IMPULSES)
+        }
+    }
+
+    fn filter_intersection_pair(&self, context: &PairFilterContext) -> bool {
+        !self.ignores(context.colliders, context.collider1, context.collider2)
+    }
+
+    fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+        SurfaceVelocityHooks { velocities: self.surface_velocities }.modify_solver_contacts(context);
+
+        // The allowed normal is in collider1's frame and points from it
+        // toward collider2
+        let allowed_local_n1 = if self.filter.one_way.contains(&context.collider1) {
+            Vector::y()
+        } else if self.filter.one_way.contains(&context.collider2) {
+            -Vector::y()
+        } else {
+            return;
+        };
+        context.update_as_oneway_platform(&allowed_local_n1, ONE_WAY_ALLOWED_ANGLE);
+    }
+}
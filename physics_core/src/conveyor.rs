@@ -0,0 +1,38 @@
+//! Conveyor belts
+//!
+//! A collider with a surface velocity drags whatever touches it along without
+//! moving itself, like a belt. Rapier supports this through the tangent
+//! velocity of solver contacts, which `SurfaceVelocityHooks` fills in for
+//! colliders flagged with `ActiveHooks::MODIFY_SOLVER_CONTACTS`. Velocities
+//! are keyed by collider handle; handles are generational, so entries left
+//! behind by removed colliders never match a new one.
+
+use std::collections::HashMap;
+
+use crate::rapier::prelude::*;
+
+pub type SurfaceVelocities = HashMap<ColliderHandle, Vector<Real>>;
+
+/// Physics hooks handed to the pipeline in place of `()`
+pub struct SurfaceVelocityHooks<'a> {
+    pub velocities: &'a SurfaceVelocities,
+}
+
+impl PhysicsHooks for SurfaceVelocityHooks<'_> {
+    fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+        let surface = |handle| self.velocities.get(&handle).copied().unwrap_or_else(Vector::zeros);
+        let tangent = contact_tangent_velocity(surface(context.collider1), surface(context.collider2), *context.normal);
+        for contact in context.solver_contacts.iter_mut() {
+            contact.tangent_velocity = tangent;
+        }
+    }
+}
+
+/// Tangent velocity for a contact between surfaces moving at `surface1` and
+/// `surface2`. The solver brings the second body to the first one's velocity
+/// plus this, so it is their difference with the part along the contact
+/// normal removed: a belt only pushes sideways.
+pub fn contact_tangent_velocity(surface1: Vector<Real>, surface2: Vector<Real>, normal: Vector<Real>) -> Vector<Real> {
+    let relative = surface1 - surface2;
+    relative - normal * relative.dot(&normal)
+}
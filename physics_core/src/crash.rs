@@ -0,0 +1,194 @@
+//! Panic capture
+//!
+//! A panic unwinding out of an `extern "C"` or JNI function aborts the host
+//! process, and on a device the message only reaches a log nobody is
+//! reading. `install_hook` chains a hook in front of the one already set
+//! (the default printer, or the browser console's on the web) that keeps the
+//! message, location and backtrace of the latest panic for
+//! `get_last_crash_report`, and writes it to a file when a crash directory
+//! is set (Android's internal storage, so the next launch can find it).
+//!
+//! Every binding entry point runs its body through `guard`, which turns a
+//! panic into the entry point's failure value: false, 0 or null, as for any
+//! other error. A panic that poisoned one of the engine's locks can leave
+//! the simulation or renderer unusable until `wgpu_shutdown` and a new
+//! `wgpu_init`.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, Once, PoisonError};
+
+use serde::Serialize;
+
+/// Name of the report inside a crash directory; each crash replaces it
+pub const FILE_NAME: &str = "physicsfx_crash.txt";
+
+static LAST: Mutex<Option<CrashReport>> = Mutex::new(None);
+static DIRECTORY: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub message: String,
+    /// `file:line:column` of the panic
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    /// Binding function the panic was caught at; None if it was not caught
+    /// there (yet)
+    pub entry_point: Option<String>,
+    /// Empty where the platform cannot capture one
+    pub backtrace: String,
+}
+
+impl CrashReport {
+    pub fn to_text(&self) -> String {
+        let mut text = format!("panicked at {}: {}\n", self.location.as_deref().unwrap_or("<unknown>"), self.message);
+        if let Some(thread) = &self.thread {
+            text += &format!("thread: {}\n", thread);
+        }
+        if let Some(entry_point) = &self.entry_point {
+            text += &format!("entry point: {}\n", entry_point);
+        }
+        if !self.backtrace.is_empty() {
+            text += &format!("backtrace:\n{}\n", self.backtrace.trim_end());
+        }
+        text
+    }
+}
+
+/// Value an entry point returns when its body panicked
+pub trait Fallback {
+    fn fallback() -> Self;
+}
+
+impl Fallback for () {
+    fn fallback() -> Self {}
+}
+
+impl Fallback for bool {
+    fn fallback() -> Self {
+        false
+    }
+}
+
+macro_rules! zero_fallback {
+    ($($ty:ty),*) => {
+        $(impl Fallback for $ty {
+            fn fallback() -> Self {
+                0 as $ty
+            }
+        })*
+    };
+}
+
+zero_fallback!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+impl<T> Fallback for *mut T {
+    fn fallback() -> Self {
+        std::ptr::null_mut()
+    }
+}
+
+impl<T> Fallback for *const T {
+    fn fallback() -> Self {
+        std::ptr::null()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Put the capturing hook in front of the current one. Later calls do
+/// nothing, so a hook set after the first call is not chained.
+pub fn install_hook() {
+    static START: Once = Once::new();
+    START.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            record(info);
+            previous(info);
+        }));
+    });
+}
+
+// Runs on the panicking thread before it unwinds, with whatever locks it
+// holds still held: it takes only this module's own and never logs
+fn record(info: &PanicHookInfo) {
+    let report = CrashReport {
+        message: payload_message(info.payload()),
+        location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+        thread: std::thread::current().name().map(str::to_string),
+        entry_point: None,
+        backtrace: backtrace(),
+    };
+    write_file(&report);
+    *lock(&LAST) = Some(report);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn backtrace() -> String {
+    let backtrace = std::backtrace::Backtrace::force_capture();
+    match backtrace.status() {
+        std::backtrace::BacktraceStatus::Captured => backtrace.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn backtrace() -> String {
+    String::new()
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+fn write_file(report: &CrashReport) {
+    if let Some(dir) = lock(&DIRECTORY).as_ref() {
+        // Nowhere to report a failure to from inside the hook
+        let _ = std::fs::write(dir.join(FILE_NAME), report.to_text());
+    }
+}
+
+/// Run `body`, returning `T::fallback()` if it panics. `entry_point` names
+/// the binding function in the log and the crash report.
+pub fn guard<T: Fallback>(entry_point: &str, body: impl FnOnce() -> T) -> T {
+    install_hook();
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => value,
+        Err(payload) => {
+            if let Some(report) = lock(&LAST).as_mut() {
+                report.entry_point.get_or_insert_with(|| entry_point.to_string());
+            }
+            log::error!("{} panicked: {}", entry_point, payload_message(payload.as_ref()));
+            T::fallback()
+        }
+    }
+}
+
+/// Write a report file into `dir` for each later panic, or stop for None
+pub fn set_directory(dir: Option<PathBuf>) {
+    *lock(&DIRECTORY) = dir;
+}
+
+/// The latest panic since start or `clear`
+pub fn last_report() -> Option<CrashReport> {
+    lock(&LAST).clone()
+}
+
+/// `last_report` as a JSON object, or None without one
+pub fn last_report_json() -> Option<String> {
+    last_report().and_then(|report| serde_json::to_string(&report).ok())
+}
+
+pub fn clear() {
+    *lock(&LAST) = None;
+}
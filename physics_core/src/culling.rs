@@ -0,0 +1,63 @@
+//! CPU culling of body instances against what the cameras see
+//!
+//! Instances keep their slots in the staging buffer (see `instance_staging`);
+//! culling only picks the slots drawn this frame. Each slot is tested as a
+//! circle against the world rect of every camera drawing the scene: the
+//! main one, split-screen halves and extra views. Visible slots are drawn in
+//! runs, and a few hidden slots between two visible ones are drawn along
+//! with them so a scattered scene does not turn into hundreds of draw
+//! calls. Slots outside every run are not uploaded either; they stay dirty
+//! until a run takes them in again.
+
+use std::ops::Range;
+
+/// Hidden slots between two visible ones drawn anyway to save a draw call
+pub const MERGE_GAP: u32 = 8;
+
+/// Axis-aligned world rect seen by a camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewRect {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl ViewRect {
+    /// What an orthographic camera centered on `center` sees: `height`
+    /// world units tall on a surface `aspect` times as wide, rolled by
+    /// `roll` radians
+    pub fn camera(center: [f32; 2], height: f32, aspect: f32, roll: f32) -> Self {
+        let (half_width, half_height) = (height * aspect * 0.5, height * 0.5);
+        let (sin, cos) = (roll.sin().abs(), roll.cos().abs());
+        let half = [half_width * cos + half_height * sin, half_width * sin + half_height * cos];
+        Self {
+            min: [center[0] - half[0], center[1] - half[1]],
+            max: [center[0] + half[0], center[1] + half[1]],
+        }
+    }
+
+    /// Whether a circle reaches into the rect
+    pub fn touches(&self, center: [f32; 2], radius: f32) -> bool {
+        (0..2).all(|axis| center[axis] + radius >= self.min[axis] && center[axis] - radius <= self.max[axis])
+    }
+}
+
+/// Which of the circles `bounds` (center and radius) touch any of `views`
+pub fn visible(bounds: impl IntoIterator<Item = ([f32; 2], f32)>, views: &[ViewRect]) -> Vec<bool> {
+    bounds
+        .into_iter()
+        .map(|(center, radius)| views.iter().any(|view| view.touches(center, radius)))
+        .collect()
+}
+
+/// Slots of `range` to draw: runs of visible slots, joined across at most
+/// `MERGE_GAP` hidden ones
+pub fn runs(visible: &[bool], range: Range<u32>) -> Vec<Range<u32>> {
+    let mut runs: Vec<Range<u32>> = Vec::new();
+    for slot in range.filter(|&slot| visible.get(slot as usize).copied().unwrap_or(false)) {
+        match runs.last_mut() {
+            Some(last) if slot <= last.end + MERGE_GAP => last.end = slot + 1,
+            _ => runs.push(slot..slot + 1),
+        }
+    }
+    runs
+}
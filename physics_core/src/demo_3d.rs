@@ -0,0 +1,225 @@
+//! 3D demo mode
+//!
+//! Everything else in the crate simulates in the z = 0 plane. This mode
+//! drops boxes and spheres onto a ground plane with all six degrees of
+//! freedom, draws them in the 3D pass with built-in meshes and looks at them
+//! through a perspective camera orbiting the middle of the ground. Dragging
+//! the pointer orbits; pinch and the mouse wheel zoom.
+//!
+//! This module only lays out the bodies, builds the meshes and moves the
+//! camera; `lib.rs` spawns the Rapier bodies and owns the mode.
+
+use crate::gltf_lite::ModelMesh;
+
+/// Most bodies the demo drops
+pub const MAX_BODIES: u32 = 2_000;
+
+/// Height of the ground's top face, m
+pub const GROUND_Y: f32 = -1.0;
+/// Half the width and depth of the ground, m
+pub const GROUND_HALF_EXTENT: f32 = 3.0;
+/// Ground thickness as a fraction of its width
+pub const GROUND_FLATNESS: f32 = 0.02;
+
+/// Half the size of a box, and the radius of a sphere, m
+pub const BODY_HALF_EXTENT: f32 = 0.12;
+
+/// Spacing of the drop grid, m
+const CELL: f32 = 0.5;
+/// Most bodies along one side of a layer of the grid
+const MAX_LAYER_SIDE: u32 = 7;
+
+pub const GROUND_COLOR: [f32; 3] = [0.45, 0.47, 0.5];
+pub const BOX_COLOR: [f32; 3] = [0.95, 0.55, 0.2];
+pub const SPHERE_COLOR: [f32; 3] = [0.3, 0.6, 0.95];
+
+/// Radians of orbit per pixel dragged
+const RADIANS_PER_PIXEL: f32 = 0.008;
+/// Pitch stays above the ground and short of straight down
+const MIN_PITCH: f32 = 0.05;
+const MAX_PITCH: f32 = 1.5;
+const MIN_DISTANCE: f32 = 1.5;
+const MAX_DISTANCE: f32 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoShape {
+    Box,
+    Sphere,
+}
+
+/// One body dropped when the mode starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DemoBody {
+    pub position: [f32; 3],
+    /// Starting rotation as a scaled axis, radians
+    pub rotation: [f32; 3],
+    pub shape: DemoShape,
+}
+
+/// `count` bodies (at most `MAX_BODIES`) in layers of a grid above the
+/// ground, boxes and spheres alternating like a checkerboard and slightly
+/// jittered so the stacks topple
+pub fn generate(count: u32) -> Vec<DemoBody> {
+    let count = count.min(MAX_BODIES);
+    let side = ((count as f32).sqrt().ceil() as u32).clamp(1, MAX_LAYER_SIDE);
+    let start = -((side - 1) as f32) * CELL * 0.5;
+
+    // Deterministic xorshift jitter so runs are comparable
+    let mut seed: u32 = 0x9e37_79b9;
+    let mut jitter = |amount: f32| {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        (seed as f32 / u32::MAX as f32 - 0.5) * amount
+    };
+
+    (0..count)
+        .map(|i| {
+            let (layer, cell) = (i / (side * side), i % (side * side));
+            let (column, row) = (cell % side, cell / side);
+            // Rotated boxes reach out to their corners
+            let spread = CELL - 2.0 * BODY_HALF_EXTENT * 3f32.sqrt();
+            DemoBody {
+                position: [
+                    start + column as f32 * CELL + jitter(spread),
+                    GROUND_Y + 0.5 + layer as f32 * CELL,
+                    start + row as f32 * CELL + jitter(spread),
+                ],
+                rotation: [jitter(0.6), jitter(0.6), jitter(0.6)],
+                shape: if (column + row + layer).is_multiple_of(2) { DemoShape::Box } else { DemoShape::Sphere },
+            }
+        })
+        .collect()
+}
+
+/// Box spanning `-half..half`, one flat color
+pub fn cuboid_mesh(half: [f32; 3], color: [f32; 3]) -> ModelMesh {
+    let mut mesh = ModelMesh::default();
+    for axis in 0..3 {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        for sign in [-1.0f32, 1.0] {
+            let mut normal = [0.0; 3];
+            normal[axis] = sign;
+            let mut corners = [[-1.0f32, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+            // u × v points along +axis, so the far side winds the other way
+            if sign < 0.0 {
+                corners.reverse();
+            }
+            let first = mesh.positions.len() as u32;
+            for [cu, cv] in corners {
+                let mut position = [0.0; 3];
+                position[axis] = sign * half[axis];
+                position[u] = cu * half[u];
+                position[v] = cv * half[v];
+                mesh.positions.push(position);
+                mesh.normals.push(normal);
+                mesh.colors.push(color);
+            }
+            mesh.indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
+    }
+    mesh
+}
+
+/// Unit sphere of `rings` bands from pole to pole and `segments` around
+pub fn sphere_mesh(rings: u32, segments: u32, color: [f32; 3]) -> ModelMesh {
+    let (rings, segments) = (rings.max(2), segments.max(3));
+    let mut mesh = ModelMesh::default();
+    for ring in 0..=rings {
+        let theta = std::f32::consts::PI * ring as f32 / rings as f32;
+        for segment in 0..=segments {
+            let phi = std::f32::consts::TAU * segment as f32 / segments as f32;
+            let point = [theta.sin() * phi.cos(), theta.cos(), -theta.sin() * phi.sin()];
+            mesh.positions.push(point);
+            mesh.normals.push(point);
+            mesh.colors.push(color);
+        }
+    }
+    let stride = segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let (top, bottom) = (ring * stride + segment, (ring + 1) * stride + segment);
+            mesh.indices.extend([top, bottom, bottom + 1, top, bottom + 1, top + 1]);
+        }
+    }
+    mesh
+}
+
+/// Perspective camera circling `target`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCamera {
+    pub target: [f32; 3],
+    /// Angle around the vertical axis, radians; 0 looks along -z
+    pub yaw: f32,
+    /// Angle above the ground, radians
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            target: [0.0, GROUND_Y + 0.5, 0.0],
+            yaw: 0.6,
+            pitch: 0.45,
+            distance: 7.0,
+        }
+    }
+}
+
+impl OrbitCamera {
+    /// Orbit by a drag of `dx`, `dy` pixels; the scene turns with the pointer
+    pub fn orbit(&mut self, dx: f32, dy: f32) {
+        if dx.is_finite() && dy.is_finite() {
+            self.yaw -= dx * RADIANS_PER_PIXEL;
+            self.pitch = (self.pitch + dy * RADIANS_PER_PIXEL).clamp(MIN_PITCH, MAX_PITCH);
+        }
+    }
+
+    /// Move closer by `factor` (> 1 zooms in), clamped to a sane range
+    pub fn zoom(&mut self, factor: f32) {
+        if factor > 0.0 && factor.is_finite() {
+            self.distance = (self.distance / factor).clamp(MIN_DISTANCE, MAX_DISTANCE);
+        }
+    }
+
+    pub fn eye(&self) -> [f32; 3] {
+        let [x, y, z] = self.target;
+        let flat = self.distance * self.pitch.cos();
+        [x + flat * self.yaw.sin(), y + self.distance * self.pitch.sin(), z + flat * self.yaw.cos()]
+    }
+}
+
+/// Orbits the camera while the pointer is dragged
+#[derive(Debug, Default)]
+pub struct OrbitControls {
+    pub camera: OrbitCamera,
+    /// Where the pointer was last seen while pressed
+    drag: Option<[f32; 2]>,
+}
+
+impl OrbitControls {
+    /// Feed one pointer event (0 = down, 1 = move, 2 = up) in screen pixels.
+    /// Returns true when the camera moved.
+    pub fn pointer(&mut self, event_type: i32, x: f32, y: f32) -> bool {
+        match event_type {
+            0 => {
+                self.drag = Some([x, y]);
+                false
+            }
+            1 => match self.drag.as_mut() {
+                Some(last) => {
+                    let [dx, dy] = [x - last[0], y - last[1]];
+                    *last = [x, y];
+                    self.camera.orbit(dx, dy);
+                    true
+                }
+                None => false,
+            },
+            _ => {
+                self.drag = None;
+                false
+            }
+        }
+    }
+}
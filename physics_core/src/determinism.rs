@@ -0,0 +1,145 @@
+//! Simulation checksums for catching cross-platform divergence
+//!
+//! `checksum_bodies` hashes every rigid body's pose and velocity in handle
+//! order. Comparing it between Android, WASM and desktop after the same number
+//! of fixed steps shows when Rapier settings or float behaviour make the
+//! platforms drift apart. `run_canned_scene` is the reference scene for that.
+
+use crate::precision::{to_f32, to_real};
+use crate::rapier::prelude::*;
+
+/// Fixed timestep used by the canned scene
+pub const CANNED_SCENE_DT: f32 = 1.0 / 60.0;
+
+/// 64-bit FNV-1a; tiny, dependency-free and identical on every target
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Hash a float by its bits, folding -0.0 into 0.0 and all NaNs into one
+    pub fn write_f32(&mut self, value: f32) {
+        let canonical = if value == 0.0 {
+            0.0
+        } else if value.is_nan() {
+            f32::NAN
+        } else {
+            value
+        };
+        self.write_bytes(&canonical.to_bits().to_le_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hash the step number plus position, rotation and velocities of every body.
+/// The f64 build hashes them narrowed to f32, so both builds share the format.
+pub fn checksum_bodies(step: u64, bodies: &RigidBodySet) -> u64 {
+    let mut handles: Vec<RigidBodyHandle> = bodies.iter().map(|(handle, _)| handle).collect();
+    handles.sort_by_key(|handle| handle.into_raw_parts());
+
+    let mut hasher = Fnv1a::new();
+    hasher.write_u64(step);
+    for handle in handles {
+        let rb = &bodies[handle];
+        let rotation = rb.rotation();
+        let values = [
+            rb.translation().x,
+            rb.translation().y,
+            rb.translation().z,
+            rotation.i,
+            rotation.j,
+            rotation.k,
+            rotation.w,
+            rb.linvel().x,
+            rb.linvel().y,
+            rb.linvel().z,
+            rb.angvel().x,
+            rb.angvel().y,
+            rb.angvel().z,
+        ];
+        for value in values {
+            hasher.write_f32(to_f32(value));
+        }
+    }
+    hasher.finish()
+}
+
+/// Drop a 5x5 grid of boxes onto a floor for `steps` fixed steps and return
+/// the resulting checksum. Every platform should produce the same value.
+pub fn run_canned_scene(steps: u32) -> u64 {
+    let mut bodies = RigidBodySet::new();
+    let mut colliders = ColliderSet::new();
+
+    let floor = bodies.insert(RigidBodyBuilder::fixed().translation(vector![0.0, -1.0, 0.0]).build());
+    colliders.insert_with_parent(ColliderBuilder::cuboid(4.0, 0.01, 0.1).build(), floor, &mut bodies);
+
+    for y in 0..5 {
+        for x in 0..5 {
+            let body = RigidBodyBuilder::dynamic()
+                .translation(vector![x as Real * 0.15 - 0.3, y as Real * 0.15, 0.0])
+                .build();
+            let handle = bodies.insert(body);
+            let collider = ColliderBuilder::cuboid(0.05, 0.05, 0.05).restitution(0.7).build();
+            colliders.insert_with_parent(collider, handle, &mut bodies);
+        }
+    }
+
+    let gravity = vector![0.0, -9.81, 0.0];
+    let integration_parameters = IntegrationParameters {
+        dt: to_real(CANNED_SCENE_DT),
+        ..Default::default()
+    };
+    let mut pipeline = PhysicsPipeline::new();
+    let mut islands = IslandManager::new();
+    let mut broad_phase = DefaultBroadPhase::new();
+    let mut narrow_phase = NarrowPhase::new();
+    let mut impulse_joints = ImpulseJointSet::new();
+    let mut multibody_joints = MultibodyJointSet::new();
+    let mut ccd_solver = CCDSolver::new();
+
+    for _ in 0..steps {
+        pipeline.step(
+            &gravity,
+            &integration_parameters,
+            &mut islands,
+            &mut broad_phase,
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut ccd_solver,
+            None,
+            &(),
+            &(),
+        );
+    }
+
+    checksum_bodies(steps as u64, &bodies)
+}
@@ -0,0 +1,266 @@
+//! Scene editor mode for the desktop app
+//!
+//! While the editor is on the simulation is paused and the mouse edits the
+//! scene instead of playing it: pressing on a body selects it and dragging
+//! moves, scales or rotates it with the current tool, and the place tool
+//! drops a new box where empty space is clicked (and keeps moving it while
+//! the button is held). The selected body's properties are edited in an
+//! egui inspector, and the scene is saved as a `SceneDesc` JSON file for
+//! the mobile and web builds to load. Leaving the editor restores the pause
+//! state it found.
+//!
+//! Saving over an existing scene file only replaces its bodies, so the
+//! level geometry, tweens and state machines written by hand stay; a new
+//! file gets the bodies and the current gravity.
+
+use std::f32::consts::PI;
+
+/// Smallest half extent scaling goes down to
+pub const MIN_HALF_EXTENT: f32 = 0.005;
+/// Largest half extent scaling goes up to
+pub const MAX_HALF_EXTENT: f32 = 5.0;
+/// Angle rotations snap to while snapping is on
+pub const ROTATE_STEP: f32 = PI / 12.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EditorTool {
+    #[default]
+    Move,
+    Scale,
+    Rotate,
+    Place,
+}
+
+impl EditorTool {
+    pub const ALL: [EditorTool; 4] = [EditorTool::Move, EditorTool::Scale, EditorTool::Rotate, EditorTool::Place];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Move => "Move",
+            Self::Scale => "Scale",
+            Self::Rotate => "Rotate",
+            Self::Place => "Place",
+        }
+    }
+}
+
+/// Where a box is and how big, in world units and radians
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EditPose {
+    pub x: f32,
+    pub y: f32,
+    pub half_extent: f32,
+    pub angle: f32,
+}
+
+/// A body being dragged: the pointer and the body's pose when it started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drag {
+    pub entity: u64,
+    pub tool: EditorTool,
+    pub start: [f32; 2],
+    pub pose: EditPose,
+    /// The press placed the body
+    pub placed: bool,
+}
+
+impl Drag {
+    /// The body's pose with the pointer at world point `point`; `snap` is
+    /// the grid spacing, 0 for none
+    pub fn pose_at(&self, point: [f32; 2], snap: f32) -> EditPose {
+        let pose = self.pose;
+        let (from, to) = ([self.start[0] - pose.x, self.start[1] - pose.y], [point[0] - pose.x, point[1] - pose.y]);
+        match self.tool {
+            EditorTool::Move | EditorTool::Place => EditPose {
+                x: snap_to(pose.x + point[0] - self.start[0], snap),
+                y: snap_to(pose.y + point[1] - self.start[1], snap),
+                ..pose
+            },
+            EditorTool::Scale => {
+                let (from, to) = (from[0].hypot(from[1]), to[0].hypot(to[1]));
+                if from < f32::EPSILON {
+                    return pose;
+                }
+                EditPose { half_extent: (pose.half_extent * to / from).clamp(MIN_HALF_EXTENT, MAX_HALF_EXTENT), ..pose }
+            }
+            EditorTool::Rotate => {
+                let turned = to[1].atan2(to[0]) - from[1].atan2(from[0]);
+                let step = if snap > 0.0 { ROTATE_STEP } else { 0.0 };
+                EditPose { angle: snap_to(pose.angle + turned, step), ..pose }
+            }
+        }
+    }
+}
+
+/// `value` on the nearest multiple of `step`; unchanged for a step of 0
+fn snap_to(value: f32, step: f32) -> f32 {
+    if step > 0.0 {
+        (value / step).round() * step
+    } else {
+        value
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Editor {
+    enabled: bool,
+    /// The simulation was paused before the editor paused it
+    was_paused: bool,
+    selected: Option<u64>,
+    drag: Option<Drag>,
+    pub tool: EditorTool,
+    /// Grid spacing moves snap to, 0 for none; rotations snap to
+    /// `ROTATE_STEP` whenever it is set
+    pub snap: f32,
+    /// Half extent of placed boxes
+    pub place_half_extent: f32,
+    /// Placed boxes are fixed level pieces rather than dynamic bodies
+    pub place_fixed: bool,
+    pub save_path: String,
+    /// Undo (true) and redo (false) presses waiting for the end of the frame
+    queued_history: Vec<bool>,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            was_paused: false,
+            selected: None,
+            drag: None,
+            tool: EditorTool::Move,
+            snap: 0.0,
+            place_half_extent: 0.05,
+            place_fixed: false,
+            save_path: "scene.json".to_string(),
+            queued_history: Vec::new(),
+        }
+    }
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turn the editor on or off while the simulation's pause state is
+    /// `sim_paused`. Returns the pause state to apply, None when nothing
+    /// changed: paused on the way in, as it was found on the way out.
+    pub fn set_enabled(&mut self, enabled: bool, sim_paused: bool) -> Option<bool> {
+        if enabled == self.enabled {
+            return None;
+        }
+        self.enabled = enabled;
+        if enabled {
+            self.was_paused = sim_paused;
+            return Some(true);
+        }
+        self.selected = None;
+        self.drag = None;
+        Some(self.was_paused)
+    }
+
+    pub fn selected(&self) -> Option<u64> {
+        self.selected
+    }
+
+    pub fn select(&mut self, entity: Option<u64>) {
+        self.selected = entity;
+    }
+
+    pub fn drag(&self) -> Option<Drag> {
+        self.drag
+    }
+
+    /// The button went down at world point `point` over `hit`, the body
+    /// there and its pose. Returns the pose of a box to place, whose id
+    /// then goes to `placed`.
+    pub fn press(&mut self, point: [f32; 2], hit: Option<(u64, EditPose)>) -> Option<EditPose> {
+        self.drag = None;
+        if let Some((entity, pose)) = hit {
+            // Placing on top of a body picks it up instead
+            let tool = if self.tool == EditorTool::Place { EditorTool::Move } else { self.tool };
+            self.selected = Some(entity);
+            self.drag = Some(Drag { entity, tool, start: point, pose, placed: false });
+            return None;
+        }
+        if self.tool != EditorTool::Place {
+            self.selected = None;
+            return None;
+        }
+        Some(EditPose {
+            x: snap_to(point[0], self.snap),
+            y: snap_to(point[1], self.snap),
+            half_extent: self.place_half_extent.clamp(MIN_HALF_EXTENT, MAX_HALF_EXTENT),
+            angle: 0.0,
+        })
+    }
+
+    /// The box `press` asked for was spawned as `entity`; it is selected and
+    /// follows the pointer until the button is released
+    pub fn placed(&mut self, entity: u64, point: [f32; 2], pose: EditPose) {
+        self.selected = Some(entity);
+        self.drag = Some(Drag { entity, tool: EditorTool::Move, start: point, pose, placed: true });
+    }
+
+    /// The pointer moved to world point `point` with the button down.
+    /// Returns the dragged body and its new pose.
+    pub fn drag_to(&self, point: [f32; 2]) -> Option<(u64, EditPose)> {
+        self.drag.map(|drag| (drag.entity, drag.pose_at(point, self.snap)))
+    }
+
+    pub fn release(&mut self) {
+        self.drag = None;
+    }
+
+    /// Undo, or redo, once the frame drawing the editor is done
+    pub fn queue_history(&mut self, undo: bool) {
+        self.queued_history.push(undo);
+    }
+
+    pub fn take_queued_history(&mut self) -> Vec<bool> {
+        std::mem::take(&mut self.queued_history)
+    }
+
+    /// `entity` is gone; stop selecting or dragging it
+    pub fn forget(&mut self, entity: u64) {
+        if self.selected == Some(entity) {
+            self.selected = None;
+        }
+        if self.drag.is_some_and(|drag| drag.entity == entity) {
+            self.drag = None;
+        }
+    }
+}
+
+/// Scene JSON with `bodies` and prefab `instances` (JSON arrays): `base`,
+/// the file being saved over, with them replaced, or a new scene with
+/// `gravity` when there is none. Files without instances only get the key
+/// when there are some.
+pub fn scene_json(
+    base: Option<&str>,
+    bodies: serde_json::Value,
+    instances: serde_json::Value,
+    gravity: [f32; 2],
+) -> Result<String, String> {
+    let mut scene = match base {
+        Some(text) => match serde_json::from_str(text).map_err(|e| e.to_string())? {
+            serde_json::Value::Object(scene) => scene,
+            _ => return Err("scene file is not a JSON object".into()),
+        },
+        None => {
+            let mut scene = serde_json::Map::new();
+            scene.insert("gravity".into(), serde_json::json!(gravity));
+            scene
+        }
+    };
+    scene.insert("bodies".into(), bodies);
+    if scene.contains_key("instances") || instances.as_array().is_some_and(|instances| !instances.is_empty()) {
+        scene.insert("instances".into(), instances);
+    }
+    serde_json::to_string_pretty(&scene).map_err(|e| e.to_string())
+}
@@ -7,6 +7,11 @@ pub enum InputEventType {
     PointerMove,
     KeyDown,
     KeyUp,
+    /// Touch gestures; x/y are world coordinates
+    Tap,
+    DragStart,
+    /// Drag released; `delta` is the release velocity in world units/s
+    Fling,
     // Add more as needed
 }
 
@@ -17,6 +22,7 @@ pub struct GameEvent {
     pub y: f32, // For pointer events
     pub key_code: Option<i32>, // For keyboard events
                 // timestamp?
+    pub delta: [f32; 2], // For gestures (fling velocity)
 }
 
 impl GameEvent {
@@ -26,6 +32,17 @@ impl GameEvent {
             x,
             y,
             key_code: None,
+            delta: [0.0, 0.0],
+        }
+    }
+
+    pub fn new_gesture(event_type: InputEventType, x: f32, y: f32, delta: [f32; 2]) -> Self {
+        Self {
+            event_type,
+            x,
+            y,
+            key_code: None,
+            delta,
         }
     }
 
@@ -35,6 +52,7 @@ impl GameEvent {
             x: -1.0,
             y: -1.0,
             key_code: Some(key_code),
+            delta: [0.0, 0.0],
         }
     }
 }
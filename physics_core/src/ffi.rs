@@ -0,0 +1,3242 @@
+//! C / iOS interface
+//!
+//! Every `extern "C"` entry point lives here as a thin wrapper over the
+//! crate-private `*_internal` functions that the JNI and WASM surfaces share.
+//! Bodies run inside `crash::guard`, as the JNI functions' do, so a panic
+//! comes back as the function's failure value instead of unwinding into C.
+//! `include/physics_core.h` is generated from this module by cbindgen (see
+//! `cbindgen.toml`; `cargo build --features c_header` regenerates it), so the
+//! `///` comments below are the C documentation.
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+use super::*;
+
+/// Bumped on any breaking change to the functions or types in this module
+pub const PHYSICS_CORE_ABI_MAJOR: u32 = 1;
+/// Bumped when functions or types are added
+pub const PHYSICS_CORE_ABI_MINOR: u32 = 68;
+pub const PHYSICS_CORE_ABI_PATCH: u32 = 0;
+
+/// `InitOptions::backends` bits (same values as `gpu_options::BACKEND_*`);
+/// 0 selects every backend
+pub const PHYSICS_CORE_BACKEND_VULKAN: u32 = 1;
+pub const PHYSICS_CORE_BACKEND_METAL: u32 = 2;
+pub const PHYSICS_CORE_BACKEND_DX12: u32 = 4;
+pub const PHYSICS_CORE_BACKEND_GL: u32 = 8;
+pub const PHYSICS_CORE_BACKEND_BROWSER_WEBGPU: u32 = 16;
+
+/// `InitOptions::power_preference` values
+pub const PHYSICS_CORE_POWER_PREFERENCE_NONE: i32 = 0;
+pub const PHYSICS_CORE_POWER_PREFERENCE_LOW_POWER: i32 = 1;
+pub const PHYSICS_CORE_POWER_PREFERENCE_HIGH_PERFORMANCE: i32 = 2;
+
+pub const PHYSICS_CORE_BODY_DYNAMIC: i32 = 0;
+pub const PHYSICS_CORE_BODY_FIXED: i32 = 1;
+pub const PHYSICS_CORE_BODY_KINEMATIC: i32 = 2;
+
+/// Group bits of newly spawned bodies, and the mask matching every body
+pub const PHYSICS_CORE_GROUP_DEFAULT: u32 = 1;
+pub const PHYSICS_CORE_GROUP_ALL: u32 = 0xFFFF_FFFF;
+
+/// `physics_core_spawn_body_with_flags` bits: a one-way platform that only
+/// holds up bodies landing on its top side
+pub const PHYSICS_CORE_SPAWN_ONE_WAY: u32 = 1;
+
+/// `TriggerEvent::kind` values
+pub const PHYSICS_CORE_TRIGGER_SENSOR: u32 = 0;
+pub const PHYSICS_CORE_TRIGGER_GOAL: u32 = 1;
+pub const PHYSICS_CORE_TRIGGER_HAZARD: u32 = 2;
+
+/// `physics_core_level_outcome` values
+pub const PHYSICS_CORE_OUTCOME_PLAYING: u32 = 0;
+pub const PHYSICS_CORE_OUTCOME_WON: u32 = 1;
+pub const PHYSICS_CORE_OUTCOME_LOST: u32 = 2;
+
+/// `physics_core_animate_property` properties. `from` and `to` hold one
+/// value for the drawn size multiplier, the rotation (radians) and the
+/// camera zoom (visible world height), three for the RGB tint (0..1) and two
+/// for the camera position.
+pub const PHYSICS_CORE_TWEEN_SCALE: u32 = 0;
+pub const PHYSICS_CORE_TWEEN_ROTATION: u32 = 1;
+pub const PHYSICS_CORE_TWEEN_COLOR: u32 = 2;
+pub const PHYSICS_CORE_TWEEN_CAMERA_ZOOM: u32 = 3;
+pub const PHYSICS_CORE_TWEEN_CAMERA_POSITION: u32 = 4;
+
+/// Easing curves for `physics_core_animate_property`
+pub const PHYSICS_CORE_EASING_LINEAR: u32 = 0;
+pub const PHYSICS_CORE_EASING_QUAD_IN: u32 = 1;
+pub const PHYSICS_CORE_EASING_QUAD_OUT: u32 = 2;
+pub const PHYSICS_CORE_EASING_QUAD_IN_OUT: u32 = 3;
+pub const PHYSICS_CORE_EASING_CUBIC_IN: u32 = 4;
+pub const PHYSICS_CORE_EASING_CUBIC_OUT: u32 = 5;
+pub const PHYSICS_CORE_EASING_CUBIC_IN_OUT: u32 = 6;
+pub const PHYSICS_CORE_EASING_SINE_IN_OUT: u32 = 7;
+pub const PHYSICS_CORE_EASING_BACK_OUT: u32 = 8;
+pub const PHYSICS_CORE_EASING_ELASTIC_OUT: u32 = 9;
+pub const PHYSICS_CORE_EASING_BOUNCE_OUT: u32 = 10;
+
+/// Opcodes of `physics_core_submit_commands` buffers (same values as
+/// `command_buffer::*`)
+pub const PHYSICS_CORE_COMMAND_SPAWN: u8 = 1;
+pub const PHYSICS_CORE_COMMAND_IMPULSE: u8 = 2;
+pub const PHYSICS_CORE_COMMAND_SET_VELOCITY: u8 = 3;
+pub const PHYSICS_CORE_COMMAND_SET_POSITION: u8 = 4;
+pub const PHYSICS_CORE_COMMAND_SET_GROUPS: u8 = 5;
+pub const PHYSICS_CORE_COMMAND_SET_MATERIAL: u8 = 6;
+
+/// Size in bytes of the transform export header blocks and of each record
+pub const PHYSICS_CORE_TRANSFORM_BLOCK_SIZE: u32 = 32;
+
+/// Most views open at once besides the main surface
+pub const PHYSICS_CORE_MAX_VIEWS: u32 = 8;
+
+/// `physics_core_get_last_error` codes (same values as `validation::ERROR_*`)
+pub const PHYSICS_CORE_ERROR_NONE: i32 = 0;
+/// A NaN or infinite number
+pub const PHYSICS_CORE_ERROR_NOT_FINITE: i32 = 1;
+/// A size, radius or scale of zero or less
+pub const PHYSICS_CORE_ERROR_INVALID_SIZE: i32 = 2;
+/// A time step of zero or less, or not finite
+pub const PHYSICS_CORE_ERROR_INVALID_TIME_STEP: i32 = 3;
+/// A malformed entity id or one naming no live entity
+pub const PHYSICS_CORE_ERROR_UNKNOWN_ENTITY: i32 = 4;
+pub const PHYSICS_CORE_ERROR_OUT_OF_RANGE: i32 = 5;
+pub const PHYSICS_CORE_ERROR_NULL_POINTER: i32 = 6;
+pub const PHYSICS_CORE_ERROR_INVALID_STRING: i32 = 7;
+
+/// Semantic version of the C ABI. A host built against the header is
+/// compatible when `major` matches and `minor` is at least the header's.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbiVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+/// A dynamic box to add to the running scene
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BodyDesc {
+    pub x: f32,
+    pub y: f32,
+    pub half_extent: f32,
+    /// Initial linear velocity
+    pub vx: f32,
+    pub vy: f32,
+    /// 0 keeps the default texture
+    pub material_id: u32,
+}
+
+/// Extra settings for `physics_core_spawn_body_with_options`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpawnOptions {
+    /// One of `PHYSICS_CORE_BODY_*`
+    pub body_type: i32,
+    /// Conveyor velocity of the body's surface; 0 for a plain surface
+    pub surface_vx: f32,
+    pub surface_vy: f32,
+}
+
+/// Adapter selection for the next `wgpu_init`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct InitOptions {
+    /// `PHYSICS_CORE_BACKEND_*` bits; 0 selects every backend
+    pub backends: u32,
+    /// One of `PHYSICS_CORE_POWER_PREFERENCE_*`
+    pub power_preference: i32,
+    /// Ask for a software adapter (SwiftShader, WARP, ...)
+    pub force_fallback_adapter: bool,
+}
+
+impl Default for InitOptions {
+    fn default() -> Self {
+        Self {
+            backends: 0,
+            power_preference: PHYSICS_CORE_POWER_PREFERENCE_HIGH_PERFORMANCE,
+            force_fallback_adapter: false,
+        }
+    }
+}
+
+/// One spatial query hit with the body's state at query time
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueryHit {
+    pub entity_id: u64,
+    pub body: BodyInfo,
+}
+
+// --- Version and info ---
+
+#[no_mangle]
+pub extern "C" fn physics_core_abi_version() -> AbiVersion {
+    AbiVersion {
+        major: PHYSICS_CORE_ABI_MAJOR,
+        minor: PHYSICS_CORE_ABI_MINOR,
+        patch: PHYSICS_CORE_ABI_PATCH,
+    }
+}
+
+/// Library/backend description; release with `physics_core_free_string`
+#[no_mangle]
+pub extern "C" fn physics_core_get_info() -> *mut c_char {
+    crash::guard("physics_core_get_info", || {
+        let s = get_internal_info();
+        let c_str = CString::new(s).unwrap();
+        c_str.into_raw()
+    })
+}
+
+/// Adapter name, backend, surface formats, capabilities and limits as a JSON
+/// object; null before `wgpu_init`. Release with `physics_core_free_string`.
+#[no_mangle]
+pub extern "C" fn physics_core_get_adapter_info() -> *mut c_char {
+    crash::guard("physics_core_get_adapter_info", || {
+        match adapter_info_internal().and_then(|json| CString::new(json).ok()) {
+            Some(c_str) => c_str.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Smoothed FPS, CPU frame time, per-pass GPU times (when the adapter
+/// supports timestamps) and drawn and culled body counts as JSON; null before `wgpu_init`. Release with
+/// `physics_core_free_string`.
+#[no_mangle]
+pub extern "C" fn physics_core_get_frame_stats() -> *mut c_char {
+    crash::guard("physics_core_get_frame_stats", || {
+        match frame_stats_internal().and_then(|json| CString::new(json).ok()) {
+            Some(c_str) => c_str.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// The last log lines (up to 500) as a JSON array of `{"level": "WARN",
+/// "target": "physics_core", "message": "..."}` objects, oldest first.
+/// `level` is the least severe level included: 1 error, 2 warn, 3 info,
+/// 4 debug, 5 trace (0 includes all). Release with `physics_core_free_string`.
+#[no_mangle]
+pub extern "C" fn physics_core_get_recent_logs(level: u32) -> *mut c_char {
+    crash::guard("physics_core_get_recent_logs", || {
+        CString::new(recent_logs_internal(level)).map_or(std::ptr::null_mut(), CString::into_raw)
+    })
+}
+
+/// Forget the log lines kept so far
+#[no_mangle]
+pub extern "C" fn physics_core_clear_logs() {
+    crash::guard("physics_core_clear_logs", || {
+        log_console::clear();
+    })
+}
+
+/// Log levels per target while the app runs (UTF-8, NUL-terminated), e.g.
+/// `physics=debug,render=warn,warn`: `target=level` directives and a bare
+/// level for every other target. The engine logs under `init`, `physics`,
+/// `gpu` and `render`. NULL or an empty string goes back to the default
+/// levels. Returns false for unknown levels.
+#[no_mangle]
+pub extern "C" fn physics_core_set_log_filter(spec: *const c_char) -> bool {
+    crash::guard("physics_core_set_log_filter", || {
+        let spec = if spec.is_null() { Ok("") } else { unsafe { std::ffi::CStr::from_ptr(spec) }.to_str() };
+        match spec.map_err(|e| e.to_string()).and_then(set_log_filter_internal) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_log_filter: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// The latest panic as a JSON object `{"message", "location", "thread",
+/// "entryPoint", "backtrace"}`, or null if nothing panicked since start or
+/// `physics_core_clear_crash_report`. A panic inside any function here is
+/// caught and that function returns false, 0 or null. Release with
+/// `physics_core_free_string`.
+#[no_mangle]
+pub extern "C" fn physics_core_get_last_crash_report() -> *mut c_char {
+    crash::guard("physics_core_get_last_crash_report", || {
+        match crash::last_report_json().and_then(|json| CString::new(json).ok()) {
+            Some(c_str) => c_str.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_clear_crash_report() {
+    crash::guard("physics_core_clear_crash_report", || {
+        crash::clear();
+    })
+}
+
+/// Also write each panic's report to `physicsfx_crash.txt` in `dir` (UTF-8,
+/// NUL-terminated), which is created if missing; NULL stops writing. Returns
+/// false if the directory cannot be created.
+#[no_mangle]
+pub extern "C" fn physics_core_set_crash_directory(dir: *const c_char) -> bool {
+    crash::guard("physics_core_set_crash_directory", || {
+        if dir.is_null() {
+            return set_crash_directory_internal(None);
+        }
+        match unsafe { std::ffi::CStr::from_ptr(dir) }.to_str() {
+            Ok(dir) => set_crash_directory_internal(Some(dir)),
+            Err(_) => false,
+        }
+    })
+}
+
+/// `PHYSICS_CORE_ERROR_*` code of the latest input this thread passed that
+/// was rejected: a NaN position, a negative size, a zero time step, an
+/// unknown entity id and the like. The function it was passed to returned
+/// false, 0 or null; the error stays until the next rejection or
+/// `physics_core_clear_last_error`.
+#[no_mangle]
+pub extern "C" fn physics_core_get_last_error() -> i32 {
+    crash::guard("physics_core_get_last_error", || {
+        validation::last_error().map_or(PHYSICS_CORE_ERROR_NONE, |error| error.code)
+    })
+}
+
+/// What was wrong with that input, e.g. `position must be finite, got NaN`;
+/// null without an error. Release with `physics_core_free_string`.
+#[no_mangle]
+pub extern "C" fn physics_core_get_last_error_message() -> *mut c_char {
+    crash::guard("physics_core_get_last_error_message", || {
+        match validation::last_error().and_then(|error| CString::new(error.message).ok()) {
+            Some(c_str) => c_str.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_clear_last_error() {
+    crash::guard("physics_core_clear_last_error", || {
+        validation::clear();
+    })
+}
+
+/// Free a string returned by this library
+#[no_mangle]
+pub extern "C" fn physics_core_free_string(s: *mut c_char) {
+    crash::guard("physics_core_free_string", || {
+        if s.is_null() {
+            return;
+        }
+        unsafe {
+            let _ = CString::from_raw(s);
+        }
+    })
+}
+
+/// Free `len` bytes returned by this library
+#[no_mangle]
+pub extern "C" fn physics_core_free_bytes(bytes: *mut u8, len: u32) {
+    crash::guard("physics_core_free_bytes", || {
+        if bytes.is_null() {
+            return;
+        }
+        unsafe {
+            let _ = Box::from_raw(std::ptr::slice_from_raw_parts_mut(bytes, len as usize));
+        }
+    })
+}
+
+// --- Lifecycle ---
+
+/// Backend, power preference and fallback-adapter choice for the next
+/// `wgpu_init` (or device rebuild); null restores the defaults
+#[no_mangle]
+pub extern "C" fn physics_core_set_init_options(options: *const InitOptions) {
+    crash::guard("physics_core_set_init_options", || {
+        let options = if options.is_null() {
+            InitOptions::default()
+        } else {
+            unsafe { *options }
+        };
+        set_gpu_init_options_internal(options.backends, options.power_preference, options.force_fallback_adapter);
+    })
+}
+
+/// surface_handle: platform-specific native surface handle
+///   - iOS: CAMetalLayer*
+///   - macOS: NSView*
+///   - Windows: HWND
+///   - Linux: X11 Window
+///   - Android: ANativeWindow*
+#[no_mangle]
+pub extern "C" fn wgpu_init(
+    surface_handle: *mut std::ffi::c_void,
+    width: i32,
+    height: i32,
+) -> bool {
+    crash::guard("wgpu_init", || {
+        #[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+        {
+            init_logging();
+        }
+
+        log::debug!(
+            "wgpu_init called: {:?}, {}x{}",
+            surface_handle,
+            width,
+            height
+        );
+
+        if surface_handle.is_null() {
+            log::warn!("wgpu_init: surface_handle is null, cannot initialize");
+            INITIALIZED.store(true, Ordering::Relaxed);
+            return true;
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            // On WASM, wgpu_init shouldn't be called directly, we use wasm_init
+            // But if it is, we just return false
+            log::warn!("wgpu_init called on WASM, ignoring");
+            return false;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let (window_handle, display_handle) = raw_handles(surface_handle);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            init_wgpu_internal(
+                window_handle,
+                display_handle,
+                width as u32,
+                height as u32,
+                surface_handle,
+                None
+            )
+        }
+
+    })
+}
+
+/// Window and display handles for a host surface pointer on this platform
+#[cfg(not(target_arch = "wasm32"))]
+fn raw_handles(surface_handle: *mut std::ffi::c_void) -> (RawWindowHandle, RawDisplayHandle) {
+    #[cfg(target_os = "ios")]
+    let (window_handle, display_handle) = {
+        use raw_window_handle::UiKitWindowHandle;
+        let handle =
+            UiKitWindowHandle::new(std::ptr::NonNull::new(surface_handle.cast()).unwrap());
+        (
+            RawWindowHandle::UiKit(handle),
+            RawDisplayHandle::UiKit(raw_window_handle::UiKitDisplayHandle::new()),
+        )
+    };
+
+    #[cfg(target_os = "macos")]
+    let (window_handle, display_handle) = {
+        use raw_window_handle::{AppKitDisplayHandle, AppKitWindowHandle};
+        let handle =
+            AppKitWindowHandle::new(std::ptr::NonNull::new(surface_handle.cast()).unwrap());
+        (
+            RawWindowHandle::AppKit(handle),
+            RawDisplayHandle::AppKit(AppKitDisplayHandle::new()),
+        )
+    };
+
+    #[cfg(target_os = "windows")]
+    let (window_handle, display_handle) = {
+        use raw_window_handle::{Win32WindowHandle, WindowsDisplayHandle};
+        let handle = Win32WindowHandle::new(
+            std::num::NonZeroIsize::new(surface_handle as isize).unwrap(),
+        );
+        (
+            RawWindowHandle::Win32(handle),
+            RawDisplayHandle::Windows(WindowsDisplayHandle::new()),
+        )
+    };
+
+    #[cfg(all(
+        unix,
+        not(any(target_os = "ios", target_os = "macos", target_os = "android"))
+    ))]
+    let (window_handle, display_handle) = {
+        use raw_window_handle::{XlibDisplayHandle, XlibWindowHandle};
+        let handle = XlibWindowHandle::new(surface_handle as u64);
+        (
+            RawWindowHandle::Xlib(handle),
+            RawDisplayHandle::Xlib(XlibDisplayHandle::new(None, 0)),
+        )
+    };
+
+    #[cfg(target_os = "android")]
+    let (window_handle, display_handle) = {
+        use raw_window_handle::{AndroidDisplayHandle, AndroidNdkWindowHandle};
+        let handle =
+            AndroidNdkWindowHandle::new(std::ptr::NonNull::new(surface_handle.cast()).unwrap());
+        (
+            RawWindowHandle::AndroidNdk(handle),
+            RawDisplayHandle::Android(AndroidDisplayHandle::new()),
+        )
+    };
+
+    (window_handle, display_handle)
+}
+
+/// Advance the simulation by `delta_time` seconds (scaled by the time
+/// scale); a zero, negative or non-finite step is rejected
+#[no_mangle]
+pub extern "C" fn wgpu_update(delta_time: f32) {
+    crash::guard("wgpu_update", || {
+        if !INITIALIZED.load(Ordering::Relaxed) {
+            return;
+        }
+        if !validation::accept(validation::time_step(delta_time)) {
+            return;
+        }
+        // TODO: Update game logic
+        log::trace!("wgpu_update: dt={}", delta_time);
+        update_internal(delta_time);
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wgpu_render() {
+    crash::guard("wgpu_render", || {
+        if !INITIALIZED.load(Ordering::Relaxed) {
+            return;
+        }
+        render_internal(None);
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wgpu_resize(width: i32, height: i32) {
+    crash::guard("wgpu_resize", || {
+        if !INITIALIZED.load(Ordering::Relaxed) {
+            return;
+        }
+        log::info!("wgpu_resize: {}x{}", width, height);
+        resize_internal(width as u32, height as u32);
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wgpu_shutdown() {
+    crash::guard("wgpu_shutdown", || {
+        log::info!("wgpu_shutdown called");
+        shutdown_internal();
+    })
+}
+
+/// The app went to the background (Android `onPause`, iOS
+/// `applicationWillResignActive`, `visibilitychange` to hidden). Rendering
+/// stops; with `freeze_physics` updates step nothing either, otherwise the
+/// simulation keeps running on the updates still made. All state stays, and
+/// a `wgpu_shutdown` while paused keeps the scene, camera and materials for
+/// the next `wgpu_init` instead of starting over. Settings are saved.
+#[no_mangle]
+pub extern "C" fn physics_core_on_app_pause(freeze_physics: bool) {
+    crash::guard("physics_core_on_app_pause", || on_app_pause_internal(freeze_physics))
+}
+
+/// Back in the foreground. The next update is an empty frame, whatever time
+/// the host's clock says went by.
+#[no_mangle]
+pub extern "C" fn physics_core_on_app_resume() {
+    crash::guard("physics_core_on_app_resume", on_app_resume_internal)
+}
+
+// --- Simulation controls ---
+
+/// Gravity straight down with magnitude `y` (m/s²)
+#[no_mangle]
+pub extern "C" fn physics_core_set_gravity(y: f32) {
+    crash::guard("physics_core_set_gravity", || {
+        set_gravity_internal(y);
+    })
+}
+
+/// Gravity as a full world-space vector (m/s²)
+#[no_mangle]
+pub extern "C" fn physics_core_set_gravity_vector(x: f32, y: f32, z: f32) {
+    crash::guard("physics_core_set_gravity_vector", || {
+        set_gravity_vector_internal(x, y, z);
+    })
+}
+
+/// Set gravity to a preset: 0 = Earth (9.81 m/s²), 1 = Moon (1.62 m/s²),
+/// 2 = zero-g. Returns false for other codes.
+#[no_mangle]
+pub extern "C" fn physics_core_set_gravity_preset(preset: i32) -> bool {
+    crash::guard("physics_core_set_gravity_preset", || set_gravity_preset_internal(preset))
+}
+
+/// Show `pixels_per_meter` on the main surface at its current size; a later
+/// resize keeps the same meters in view. The camera is reframed at once,
+/// walls and default sizes follow on the next reset. Returns false without
+/// a surface or unless the scale is finite and > 0.
+#[no_mangle]
+pub extern "C" fn physics_core_set_world_scale(pixels_per_meter: f32) -> bool {
+    crash::guard("physics_core_set_world_scale", || set_world_scale_internal(pixels_per_meter))
+}
+
+/// Like `physics_core_set_world_scale` in normalized device units per meter
+/// (the view is 2 tall; 1 is the default). Works before the surface exists.
+#[no_mangle]
+pub extern "C" fn physics_core_set_world_scale_ndc(ndc_per_meter: f32) -> bool {
+    crash::guard("physics_core_set_world_scale_ndc", || set_world_scale_ndc_internal(ndc_per_meter))
+}
+
+/// While enabled, each accelerometer sample sets gravity to point down on screen,
+/// scaled by `strength`
+#[no_mangle]
+pub extern "C" fn physics_core_set_tilt_gravity(enabled: bool, strength: f32) {
+    crash::guard("physics_core_set_tilt_gravity", || {
+        set_tilt_gravity_internal(enabled, strength);
+    })
+}
+
+/// One accelerometer sample in device axes (m/s², gravity reaction included,
+/// Android convention) with the display rotation (0, 90, 180, 270).
+/// Core Motion reports acceleration in g with the opposite sign; hosts should
+/// pass `-userAcceleration - gravity` scaled by 9.81
+#[no_mangle]
+pub extern "C" fn physics_core_on_accelerometer(x: f32, y: f32, z: f32, rotation_degrees: i32) {
+    crash::guard("physics_core_on_accelerometer", || {
+        on_accelerometer_internal(x, y, z, rotation_degrees);
+    })
+}
+
+/// Start (`enabled`) or stop the 3D demo: `body_count` boxes and spheres
+/// (at most 2000) fall onto a ground plane with full 3D motion, drawn by the
+/// 3D pass through a perspective camera that pointer drags orbit and pinch
+/// or the mouse wheel zoom. Starting replaces the simulation; stopping
+/// restores the 2D camera and the default scene. Returns the number of
+/// bodies dropped, 0 when stopping or without a renderer.
+#[no_mangle]
+pub extern "C" fn physics_core_set_3d_mode(enabled: bool, body_count: u32) -> u32 {
+    crash::guard("physics_core_set_3d_mode", || {
+        match set_3d_mode_internal(enabled, body_count) {
+            Ok(count) => count as u32,
+            Err(e) => {
+                log::error!("physics_core_set_3d_mode: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Orbit the 3D demo's camera as if dragged `dx`, `dy` pixels and move it
+/// `zoom` times closer (1 keeps the distance), for hosts with their own
+/// controls. Returns false while the demo is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_orbit_3d_camera(dx: f32, dy: f32, zoom: f32) -> bool {
+    crash::guard("physics_core_orbit_3d_camera", || {
+        orbit_3d_camera_internal(dx, dy, zoom)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_set_time_scale(scale: f32) {
+    crash::guard("physics_core_set_time_scale", || {
+        set_time_scale_internal(scale);
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_set_paused(paused: bool) {
+    crash::guard("physics_core_set_paused", || {
+        if let Ok(mut guard) = PHYSICS_STATE.lock() {
+            if let Some(physics) = guard.0.as_mut() {
+                physics.paused = paused;
+            }
+        }
+    })
+}
+
+/// Stop or resume sprite animations and tweens, independently of the
+/// simulation pause
+#[no_mangle]
+pub extern "C" fn physics_core_set_animation_paused(paused: bool) {
+    crash::guard("physics_core_set_animation_paused", || {
+        if let Ok(mut guard) = PHYSICS_STATE.lock() {
+            if let Some(physics) = guard.0.as_mut() {
+                physics.animation_paused = paused;
+            }
+        }
+    })
+}
+
+/// One fixed 1/60 s step; works while paused
+#[no_mangle]
+pub extern "C" fn physics_core_step_once() {
+    crash::guard("physics_core_step_once", || {
+        step_once_internal();
+    })
+}
+
+/// Run up to `steps` (at most 600) fixed steps without rendering. Returns the steps run.
+#[no_mangle]
+pub extern "C" fn physics_core_fast_forward(steps: u32) -> u32 {
+    crash::guard("physics_core_fast_forward", || {
+        fast_forward_internal(steps)
+    })
+}
+
+/// Ease the time scale to `target` over `seconds` of real time
+#[no_mangle]
+pub extern "C" fn physics_core_set_time_scale_ramp(target: f32, seconds: f32) {
+    crash::guard("physics_core_set_time_scale_ramp", || {
+        set_time_scale_ramp_internal(target, seconds);
+    })
+}
+
+/// Rebuild the scene last switched to, or the default scene, with the bodies
+/// marked persistent; keeps gravity, time scale and pause state
+#[no_mangle]
+pub extern "C" fn physics_core_reset_simulation() {
+    crash::guard("physics_core_reset_simulation", || {
+        reset_simulation_internal(ResetMode::Scene);
+    })
+}
+
+/// Reset as `mode` says: 0 = scene with persistent bodies (as
+/// `physics_core_reset_simulation`), 1 = scene without them, 2 = the default
+/// scene, forgetting the loaded one. Returns false for an unknown mode.
+#[no_mangle]
+pub extern "C" fn physics_core_reset_simulation_to(mode: i32) -> bool {
+    crash::guard("physics_core_reset_simulation_to", || reset_simulation_to_internal(mode))
+}
+
+/// Have resets to the scene rebuild the box `entity_id` as it is now, or stop
+/// doing so. Returns false for unknown entities and shapes other than boxes.
+/// The rebuilt box is a new entity with a new id; look it up with
+/// `physics_core_persistent_id_after_reset`.
+#[no_mangle]
+pub extern "C" fn physics_core_set_persistent(entity_id: u64, persistent: bool) -> bool {
+    crash::guard("physics_core_set_persistent", || set_persistent_internal(entity_id, persistent))
+}
+
+/// Id the latest reset rebuilt the persistent body `entity_id` under, or 0 if
+/// it did not rebuild that body. Only the latest reset is remembered.
+#[no_mangle]
+pub extern "C" fn physics_core_persistent_id_after_reset(entity_id: u64) -> u64 {
+    crash::guard("physics_core_persistent_id_after_reset", || {
+        persistent_id_after_reset_internal(entity_id).unwrap_or(0)
+    })
+}
+
+/// Replace the scene with `n_bodies` boxes. layout: 0 = pyramid, 1 = stack, 2 = rain
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_stress_scene(n_bodies: u32, layout: i32) -> bool {
+    crash::guard("physics_core_spawn_stress_scene", || {
+        spawn_stress_scene_internal(n_bodies, layout)
+    })
+}
+
+/// Hash of all body positions/velocities at the current step, for comparing platforms
+#[no_mangle]
+pub extern "C" fn physics_core_get_state_checksum() -> u64 {
+    crash::guard("physics_core_get_state_checksum", || {
+        state_checksum_internal()
+    })
+}
+
+/// Jump back `steps` physics steps using the snapshot history. Returns the steps actually rewound.
+#[no_mangle]
+pub extern "C" fn physics_core_rewind(steps: u32) -> u32 {
+    crash::guard("physics_core_rewind", || {
+        rewind_internal(steps)
+    })
+}
+
+/// Number of steps kept for rewinding; 0 disables snapshot capture
+#[no_mangle]
+pub extern "C" fn physics_core_set_rewind_history(capacity: u32) {
+    crash::guard("physics_core_set_rewind_history", || {
+        set_rewind_history_internal(capacity);
+    })
+}
+
+/// Solver iterations, error reduction, CCD substeps and contact damping;
+/// out-of-range fields are clamped and null restores the defaults. Returns
+/// false if the simulation is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_set_solver_config(config: *const SolverConfig) -> bool {
+    crash::guard("physics_core_set_solver_config", || {
+        let config = if config.is_null() {
+            SolverConfig::default()
+        } else {
+            unsafe { *config }
+        };
+        set_solver_config_internal(config)
+    })
+}
+
+/// Replace the scene's force fields with a JSON object (UTF-8,
+/// NUL-terminated) such as `{"wind": {"velocity": [2, 0]}, "volumes": [],
+/// "buoyancy": [{"surfaceY": -0.5, "fluidDensity": 2}]}`. Returns false on
+/// parse errors or if the simulation is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_set_force_fields(json: *const c_char) -> bool {
+    crash::guard("physics_core_set_force_fields", || {
+        if json.is_null() {
+            validation::reject(InputError::null_pointer("json"));
+            return false;
+        }
+        let json = unsafe { std::ffi::CStr::from_ptr(json) };
+        match json.to_str().map_err(|e| e.to_string()).and_then(set_force_fields_json_internal) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_force_fields: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Replace every water region with a JSON object (UTF-8, NUL-terminated)
+/// such as `{"regions": [{"minX": -1, "maxX": 1, "surfaceY": -0.5,
+/// "bottomY": -1, "fluidDensity": 2}]}`; missing fields keep their defaults.
+/// The water starts calm. Returns false on parse errors or if the simulation
+/// is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_set_water(json: *const c_char) -> bool {
+    crash::guard("physics_core_set_water", || {
+        if json.is_null() {
+            validation::reject(InputError::null_pointer("json"));
+            return false;
+        }
+        let json = unsafe { std::ffi::CStr::from_ptr(json) };
+        match json.to_str().map_err(|e| e.to_string()).and_then(set_water_json_internal) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_water: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Replace the slow-motion zones with a JSON object (UTF-8, NUL-terminated)
+/// such as `{"zones": [{"min": [-1, -1], "max": [1, 1], "scale": 0.25}]}`.
+/// Bodies in a zone run at `scale` times normal speed; the slowest zone wins
+/// where they overlap. Returns false on parse errors or if the simulation is
+/// not running.
+#[no_mangle]
+pub extern "C" fn physics_core_set_time_dilation(json: *const c_char) -> bool {
+    crash::guard("physics_core_set_time_dilation", || {
+        if json.is_null() {
+            validation::reject(InputError::null_pointer("json"));
+            return false;
+        }
+        let json = unsafe { std::ffi::CStr::from_ptr(json) };
+        match json.to_str().map_err(|e| e.to_string()).and_then(set_time_dilation_json_internal) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_time_dilation: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Split every step into `substeps` Rapier steps. With `auto_substeps` the
+/// count grows with the fastest body's speed above `velocity_threshold` m/s,
+/// up to `max_substeps`. Returns false if the simulation is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_set_substeps(
+    substeps: u32,
+    auto_substeps: bool,
+    velocity_threshold: f32,
+    max_substeps: u32,
+) -> bool {
+    crash::guard("physics_core_set_substeps", || {
+        set_substeps_internal(SubstepConfig {
+            substeps,
+            auto: auto_substeps,
+            velocity_threshold,
+            max_substeps,
+        })
+    })
+}
+
+/// Write the active solver settings to `out`; false if not running
+#[no_mangle]
+pub extern "C" fn physics_core_get_solver_config(out: *mut SolverConfig) -> bool {
+    crash::guard("physics_core_get_solver_config", || {
+        if out.is_null() {
+            return false;
+        }
+        match solver_config_internal() {
+            Some(config) => {
+                unsafe { *out = config };
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+// --- Input ---
+
+/// event_type: 0 = down, 1 = move, 2 = up; x/y in pixels; button: 0 = primary
+#[no_mangle]
+pub extern "C" fn physics_core_on_pointer_event(event_type: i32, x: f32, y: f32, button: i32) {
+    crash::guard("physics_core_on_pointer_event", || {
+        on_pointer_event_internal(event_type, x, y, button);
+    })
+}
+
+/// event_type: 0 = down, 1 = up
+#[no_mangle]
+pub extern "C" fn physics_core_on_key_event(event_type: i32, key_code: i32) {
+    crash::guard("physics_core_on_key_event", || {
+        on_key_event_internal(event_type, key_code);
+    })
+}
+
+/// Touch gestures. action: 0 = down, 1 = move, 2 = up, 3 = cancel; one call per pointer, x/y in pixels
+#[no_mangle]
+pub extern "C" fn physics_core_on_touch_event(action: i32, pointer_id: i32, x: f32, y: f32, time_seconds: f64) {
+    crash::guard("physics_core_on_touch_event", || {
+        on_touch_event_internal(action, pointer_id, x, y, time_seconds);
+    })
+}
+
+/// Tap spawns a box, drag flings the touched body, pinch zooms and two-finger drag pans
+#[no_mangle]
+pub extern "C" fn physics_core_set_gestures(tap_spawn: bool, fling: bool, pinch_zoom: bool, pan: bool) {
+    crash::guard("physics_core_set_gestures", || {
+        set_gestures_internal(tap_spawn, fling, pinch_zoom, pan);
+    })
+}
+
+// --- Entities and materials ---
+
+/// Copy up to `capacity` ids into `out_ids`, returning the total number available
+fn copy_ids_out(ids: &[u64], out_ids: *mut u64, capacity: u32) -> u32 {
+    if !out_ids.is_null() {
+        let count = ids.len().min(capacity as usize);
+        unsafe {
+            std::ptr::copy_nonoverlapping(ids.as_ptr(), out_ids, count);
+        }
+    }
+    ids.len() as u32
+}
+
+/// Copy up to `capacity` entity ids into `out_ids`, returning the total number of entities.
+/// Entity ids are opaque 64-bit handles.
+#[no_mangle]
+pub extern "C" fn physics_core_get_entity_ids(out_ids: *mut u64, capacity: u32) -> u32 {
+    crash::guard("physics_core_get_entity_ids", || {
+        copy_ids_out(&entity_ids_internal(), out_ids, capacity)
+    })
+}
+
+/// Whether `entity_id` names an entity of the current scene. Ids die with
+/// their entity and with every reset, and are never handed out again.
+#[no_mangle]
+pub extern "C" fn physics_core_is_entity_alive(entity_id: u64) -> bool {
+    crash::guard("physics_core_is_entity_alive", || is_entity_alive_internal(entity_id))
+}
+
+/// Times the scene was reset or replaced since startup; hosts holding entity
+/// ids can compare it, or register a reset hook with `physics_core_register_hook`
+#[no_mangle]
+pub extern "C" fn physics_core_reset_count() -> u32 {
+    crash::guard("physics_core_reset_count", reset_count_internal)
+}
+
+/// Register a `width` x `height` RGBA8 texture and return its material id.
+/// Material 0 is the built-in default texture.
+#[no_mangle]
+pub extern "C" fn physics_core_register_material_rgba(pixels: *const u8, width: u32, height: u32) -> u32 {
+    crash::guard("physics_core_register_material_rgba", || {
+        if pixels.is_null() {
+            return material::DEFAULT_MATERIAL;
+        }
+        let len = (width as usize) * (height as usize) * 4;
+        let rgba = unsafe { std::slice::from_raw_parts(pixels, len) };
+        register_material_internal(width, height, rgba)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_set_entity_material(entity_id: u64, material_id: u32) -> bool {
+    crash::guard("physics_core_set_entity_material", || {
+        set_entity_material_internal(entity_id, material_id)
+    })
+}
+
+/// Trace the pixels of material `texture_id` with alpha above
+/// `alpha_threshold` into a collision shape, dropping outline detail
+/// smaller than `simplification` pixels. Bodies drawn with the texture,
+/// now or once it is set on them, collide with that shape at their drawn
+/// size. Returns the number of convex pieces, or 0 on failure.
+#[no_mangle]
+pub extern "C" fn physics_core_generate_collider_from_texture(texture_id: u32, alpha_threshold: u8, simplification: f32) -> u32 {
+    crash::guard("physics_core_generate_collider_from_texture", || {
+        match generate_collider_from_texture_internal(texture_id, alpha_threshold, simplification) {
+            Ok(pieces) => pieces as u32,
+            Err(e) => {
+                log::error!("physics_core_generate_collider_from_texture: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Sample material `material_id`'s texture with `filter` between texels and
+/// `mipmap_filter` between mip levels (0 = nearest, 1 = linear, the default)
+/// and wrap it with `wrap_u` and `wrap_v` (0 = clamp to the edge, the default,
+/// 1 = repeat, 2 = mirror); nearest filtering keeps pixel art sharp. Returns
+/// false for unknown values or materials and for material 0.
+#[no_mangle]
+pub extern "C" fn physics_core_set_texture_sampling(material_id: u32, filter: i32, mipmap_filter: i32, wrap_u: i32, wrap_v: i32) -> bool {
+    crash::guard("physics_core_set_texture_sampling", || {
+        texture_sampling::TextureSampling::from_codes(filter, mipmap_filter, wrap_u, wrap_v)
+            .is_some_and(|sampling| set_texture_sampling_internal(material_id, sampling))
+    })
+}
+
+/// Draw the texture rect at `u`, `v` (top left, 0..1) of `width` x `height`
+/// (texture coordinates) on entity `entity_id`, on a quad `draw_width` x
+/// `draw_height` times its square size, e.g. one image of an atlas or a
+/// platform four times as wide as tall. Replaces sprite sheet frames and
+/// keeps the collider. Returns false for unknown entities and for rects
+/// outside the texture or sizes <= 0.
+#[no_mangle]
+pub extern "C" fn physics_core_set_sprite_region(entity_id: u64, u: f32, v: f32, width: f32, height: f32, draw_width: f32, draw_height: f32) -> bool {
+    crash::guard("physics_core_set_sprite_region", || {
+        let region = SpriteRegion { rect: [u, v, width, height], size: [draw_width, draw_height], ..Default::default() };
+        match set_sprite_region_internal(entity_id, Some(region)) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_sprite_region: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Keep the region borders `left`, `top`, `right` and `bottom` (fractions
+/// of the region) at their size on the square quad while the middle
+/// stretches, so panels and platforms of any size keep their frames
+/// (nine-slice). An entity without a region gets the whole texture as one.
+/// Returns false for unknown entities and for borders < 0 or opposite
+/// borders adding up to more than 1.
+#[no_mangle]
+pub extern "C" fn physics_core_set_nine_slice(entity_id: u64, left: f32, top: f32, right: f32, bottom: f32) -> bool {
+    crash::guard("physics_core_set_nine_slice", || match set_nine_slice_internal(entity_id, [left, top, right, bottom]) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("physics_core_set_nine_slice: {}", e);
+            false
+        }
+    })
+}
+
+/// Draw entity `entity_id`'s whole texture, or its sprite sheet frame, on
+/// the square quad again. Returns false for unknown entities.
+#[no_mangle]
+pub extern "C" fn physics_core_clear_sprite_region(entity_id: u64) -> bool {
+    crash::guard("physics_core_clear_sprite_region", || set_sprite_region_internal(entity_id, None).is_ok())
+}
+
+/// Draw entity `entity_id` in layer `order` of `kind` (0 = background,
+/// behind the terrain and tiles, 1 = world, the default, 2 = foreground,
+/// over the water). Layers of one kind are drawn lowest `order` first, and
+/// sprites within one keep their order from frame to frame. Returns false
+/// for unknown kinds or entities.
+#[no_mangle]
+pub extern "C" fn physics_core_set_layer(entity_id: u64, kind: i32, order: i32) -> bool {
+    crash::guard("physics_core_set_layer", || {
+        LayerKind::from_i32(kind).is_some_and(|kind| set_layer_internal(entity_id, Layer::new(kind, order)))
+    })
+}
+
+/// Scroll layer `order` of `kind` (see `physics_core_set_layer`) by
+/// `factor_x` and `factor_y` of the camera's movement: 1 moves with the
+/// world, 0 stays put on screen like a sky and 0.5 drifts at half speed
+/// like distant hills. Only drawing moves; colliders stay put, and such
+/// layers are never culled. Returns false for unknown kinds, factors that
+/// are not finite and if the simulation is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_set_layer_parallax(kind: i32, order: i32, factor_x: f32, factor_y: f32) -> bool {
+    crash::guard("physics_core_set_layer_parallax", || {
+        let Some(kind) = LayerKind::from_i32(kind) else {
+            return false;
+        };
+        match set_layer_parallax_internal(Layer::new(kind, order), [factor_x, factor_y]) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_layer_parallax: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Load a .gltf (with embedded base64 buffers) or .glb model from `len`
+/// bytes for the 3D pass. Only triangles are read, each primitive in its
+/// material's base color, and the model is centered and fitted to -1..1.
+/// Returns its id, or 0 for null, malformed or unsupported files.
+#[no_mangle]
+pub extern "C" fn physics_core_load_model(bytes: *const u8, len: u32) -> u32 {
+    crash::guard("physics_core_load_model", || {
+        if bytes.is_null() {
+            return 0;
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(bytes, len as usize) };
+        match load_model_internal(bytes) {
+            Ok(model_id) => model_id,
+            Err(e) => {
+                log::error!("physics_core_load_model: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Draw the body in the 3D pass with model `model_id` at its position and
+/// rotation, `scale` times its drawn size; the models replace the sample
+/// cube. Model 0 removes the body's model. Returns false for unknown
+/// entities or models and a scale that is not positive.
+#[no_mangle]
+pub extern "C" fn physics_core_attach_model(entity_id: u64, model_id: u32, scale: f32) -> bool {
+    crash::guard("physics_core_attach_model", || {
+        attach_model_internal(entity_id, model_id, scale)
+    })
+}
+
+// --- Bodies ---
+
+/// Spawn a box described by `desc`. Returns its entity id, or 0 if `desc` is
+/// null, the size is invalid or the simulation is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_body(desc: *const BodyDesc) -> u64 {
+    crash::guard("physics_core_spawn_body", || {
+        let desc = match unsafe { desc.as_ref() } {
+            Some(desc) => *desc,
+            None => {
+                validation::reject(InputError::null_pointer("desc"));
+                return 0;
+            }
+        };
+        if !validation::accept(validation::finite("velocity", &[desc.vx, desc.vy])) {
+            return 0;
+        }
+        let entity_id = match spawn_body_internal(desc.x, desc.y, desc.half_extent) {
+            Some(entity_id) => entity_id,
+            None => return 0,
+        };
+        with_rigid_body_mut(entity_id, |rb| rb.set_linvel(planar([desc.vx, desc.vy]), true));
+        if desc.material_id != material::DEFAULT_MATERIAL {
+            set_entity_material_internal(entity_id, desc.material_id);
+        }
+        entity_id
+    })
+}
+
+/// Like `physics_core_spawn_body`, with the body type and conveyor velocity
+/// from `options` (null for a dynamic body). Returns 0 on failure.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_body_with_options(desc: *const BodyDesc, options: *const SpawnOptions) -> u64 {
+    crash::guard("physics_core_spawn_body_with_options", || {
+        physics_core_spawn_body_in_groups(desc, options, PHYSICS_CORE_GROUP_DEFAULT)
+    })
+}
+
+/// Like `physics_core_spawn_body_with_options`, with the body placed in
+/// `groups` (bits, see `physics_core_set_body_groups`). Returns 0 on failure.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_body_in_groups(desc: *const BodyDesc, options: *const SpawnOptions, groups: u32) -> u64 {
+    crash::guard("physics_core_spawn_body_in_groups", || {
+        let (desc, options) = match unsafe { (desc.as_ref(), options.as_ref()) } {
+            (Some(desc), options) => (*desc, options.copied().unwrap_or_default()),
+            (None, _) => {
+                validation::reject(InputError::null_pointer("desc"));
+                return 0;
+            }
+        };
+        let body_type = match body_type_from_raw(options.body_type) {
+            Some(body_type) => body_type,
+            None => {
+                let message = format!("body type must be 0..=2, got {}", options.body_type);
+                validation::reject(InputError::new(validation::ERROR_OUT_OF_RANGE, message));
+                return 0;
+            }
+        };
+        if !validation::accept(validation::finite("velocity", &[desc.vx, desc.vy])) {
+            return 0;
+        }
+        let surface_velocity = [options.surface_vx, options.surface_vy];
+        let entity_id = match spawn_body_with_internal(desc.x, desc.y, desc.half_extent, body_type, surface_velocity, groups) {
+            Some(entity_id) => entity_id,
+            None => return 0,
+        };
+        with_rigid_body_mut(entity_id, |rb| rb.set_linvel(planar([desc.vx, desc.vy]), true));
+        if desc.material_id != material::DEFAULT_MATERIAL {
+            set_entity_material_internal(entity_id, desc.material_id);
+        }
+        entity_id
+    })
+}
+
+/// Like `physics_core_spawn_body_in_groups`, with `PHYSICS_CORE_SPAWN_*`
+/// `flags`. Returns 0 on failure.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_body_with_flags(
+    desc: *const BodyDesc,
+    options: *const SpawnOptions,
+    groups: u32,
+    flags: u32,
+) -> u64 {
+    crash::guard("physics_core_spawn_body_with_flags", || {
+        let entity_id = physics_core_spawn_body_in_groups(desc, options, groups);
+        if entity_id != 0 && flags & PHYSICS_CORE_SPAWN_ONE_WAY != 0 {
+            set_one_way_internal(entity_id, true);
+        }
+        entity_id
+    })
+}
+
+/// Add a body at (x, y) shaped like the outline `points` (`point_count` x, y
+/// pairs relative to the body, either winding, concave or not), split into
+/// convex pieces on one body. `options` and `groups` as for
+/// `physics_core_spawn_body_in_groups`. Returns 0 for outlines that cross
+/// themselves, have no area or more than 256 points.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_polygon(
+    x: f32,
+    y: f32,
+    points: *const f32,
+    point_count: u32,
+    options: *const SpawnOptions,
+    groups: u32,
+) -> u64 {
+    crash::guard("physics_core_spawn_polygon", || {
+        if points.is_null() {
+            return 0;
+        }
+        let options = unsafe { options.as_ref() }.copied().unwrap_or_default();
+        let Some(body_type) = body_type_from_raw(options.body_type) else {
+            return 0;
+        };
+        let points = unsafe { std::slice::from_raw_parts(points as *const [f32; 2], point_count as usize) };
+        match spawn_polygon_internal(x, y, points, body_type, groups) {
+            Ok(entity_id) => {
+                if options.surface_vx != 0.0 || options.surface_vy != 0.0 {
+                    set_surface_velocity_internal(entity_id, options.surface_vx, options.surface_vy);
+                }
+                entity_id
+            }
+            Err(e) => {
+                log::error!("physics_core_spawn_polygon: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Make the body's surface move at (vx, vy) m/s so touching bodies are
+/// carried along; zero stops the conveyor. Returns false for unknown ids.
+#[no_mangle]
+pub extern "C" fn physics_core_set_surface_velocity(entity_id: u64, vx: f32, vy: f32) -> bool {
+    crash::guard("physics_core_set_surface_velocity", || {
+        set_surface_velocity_internal(entity_id, vx, vy)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_set_sleep_enabled(enabled: bool) {
+    crash::guard("physics_core_set_sleep_enabled", || {
+        set_sleep_enabled_internal(enabled);
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_wake_body(entity_id: u64) -> bool {
+    crash::guard("physics_core_wake_body", || {
+        wake_body_internal(entity_id)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_sleep_body(entity_id: u64) -> bool {
+    crash::guard("physics_core_sleep_body", || {
+        sleep_body_internal(entity_id)
+    })
+}
+
+/// Fill `out_info` with the body's current state. Returns false for unknown ids.
+#[no_mangle]
+pub extern "C" fn physics_core_get_body_info(entity_id: u64, out_info: *mut BodyInfo) -> bool {
+    crash::guard("physics_core_get_body_info", || {
+        match (body_info_internal(entity_id), out_info.is_null()) {
+            (Some(info), false) => {
+                unsafe { *out_info = info };
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+// --- Contact Filtering ---
+
+/// Make the body a one-way platform: bodies pass through it from below and
+/// land on its top (local +y) side. false makes it solid again. Returns false
+/// for unknown ids.
+#[no_mangle]
+pub extern "C" fn physics_core_set_one_way(entity_id: u64, enabled: bool) -> bool {
+    crash::guard("physics_core_set_one_way", || {
+        set_one_way_internal(entity_id, enabled)
+    })
+}
+
+/// Let two bodies pass through each other while both still collide with
+/// everything else. Returns false for unknown ids, the same id twice or a
+/// pair that is already ignored.
+#[no_mangle]
+pub extern "C" fn physics_core_ignore_collision(entity_a: u64, entity_b: u64) -> bool {
+    crash::guard("physics_core_ignore_collision", || {
+        set_collision_ignored_internal(entity_a, entity_b, true)
+    })
+}
+
+/// Undo `physics_core_ignore_collision`. Returns false if the pair was not ignored.
+#[no_mangle]
+pub extern "C" fn physics_core_restore_collision(entity_a: u64, entity_b: u64) -> bool {
+    crash::guard("physics_core_restore_collision", || {
+        set_collision_ignored_internal(entity_a, entity_b, false)
+    })
+}
+
+// --- Destruction ---
+
+/// Spawn a box like `physics_core_spawn_body` that breaks into smaller boxes
+/// when a collision impulse reaches `fracture->threshold` (null for the
+/// defaults). Returns its entity id, or 0 for invalid arguments.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_destructible(desc: *const BodyDesc, fracture: *const FractureConfig) -> u64 {
+    crash::guard("physics_core_spawn_destructible", || {
+        let fracture = unsafe { fracture.as_ref() }.copied().unwrap_or_default();
+        if fracture.validated().is_none() {
+            return 0;
+        }
+        let entity_id = physics_core_spawn_body(desc);
+        if entity_id != 0 {
+            set_destructible_internal(entity_id, Some(fracture));
+        }
+        entity_id
+    })
+}
+
+/// Make an existing body destructible with `fracture`, or not with null.
+/// Returns false for unknown ids and invalid configs.
+#[no_mangle]
+pub extern "C" fn physics_core_set_destructible(entity_id: u64, fracture: *const FractureConfig) -> bool {
+    crash::guard("physics_core_set_destructible", || {
+        set_destructible_internal(entity_id, unsafe { fracture.as_ref() }.copied())
+    })
+}
+
+/// Move up to `capacity` queued fracture events, oldest first, into
+/// `out_events` and return how many were written. At most 256 are kept.
+#[no_mangle]
+pub extern "C" fn physics_core_poll_fracture_events(out_events: *mut FractureEvent, capacity: u32) -> u32 {
+    crash::guard("physics_core_poll_fracture_events", || {
+        if out_events.is_null() || capacity == 0 {
+            return 0;
+        }
+        let events = poll_fracture_events_internal(capacity as usize);
+        unsafe { std::ptr::copy_nonoverlapping(events.as_ptr(), out_events, events.len()) };
+        events.len() as u32
+    })
+}
+
+// --- Level geometry ---
+
+/// Replace the static level geometry with a JSON level description (UTF-8,
+/// NUL-terminated): `{"shapes": [...], "keepWalls": false}` where each shape
+/// is `{"type": "polyline", "points": [[x, y], ...], "closed": false}`,
+/// `{"type": "heightfield", "originX": x, "spacing": dx, "heights": [...]}`
+/// or `{"type": "tiles", "origin": [x, y], "tileSize": s, "columns": n,
+/// "cells": [0, 1, ...], "materialId": atlas, "atlasColumns": c, "atlasRows": r}`.
+/// Tile grids are drawn as well; see `physics_core_set_tile`. An optional
+/// `"triggers": [{"name": "exit", "kind": "goal", "min": [x, y], "max": [x, y],
+/// "mask": groups}, ...]` replaces the trigger zones; kinds are "sensor",
+/// "goal" and "hazard". Returns false on parse or geometry errors.
+#[no_mangle]
+pub extern "C" fn physics_core_load_level(json: *const c_char) -> bool {
+    crash::guard("physics_core_load_level", || {
+        if json.is_null() {
+            validation::reject(InputError::null_pointer("json"));
+            return false;
+        }
+        let json = unsafe { std::ffi::CStr::from_ptr(json) };
+        match json.to_str().map_err(|e| e.to_string()).and_then(load_level_json_internal) {
+            Ok(_) => true,
+            Err(e) => {
+                log::error!("physics_core_load_level: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Set one cell of tile layer `layer` (the level's tile grids in order): 0
+/// clears it, `n` draws atlas cell `n - 1`. Collisions change at once, the
+/// drawn tiles on the next frame. Returns false for unknown layers or cells.
+#[no_mangle]
+pub extern "C" fn physics_core_set_tile(layer: u32, column: u32, row: u32, value: u8) -> bool {
+    crash::guard("physics_core_set_tile", || {
+        set_tile_internal(layer, column, row, value)
+    })
+}
+
+/// Cell value, or -1 for unknown layers or cells off the grid
+#[no_mangle]
+pub extern "C" fn physics_core_get_tile(layer: u32, column: u32, row: u32) -> i32 {
+    crash::guard("physics_core_get_tile", || {
+        get_tile_internal(layer, column, row).map_or(-1, i32::from)
+    })
+}
+
+/// Write the cell under world point (x, y) to `out_column` / `out_row`.
+/// Returns false if the point is off the layer's grid.
+#[no_mangle]
+pub extern "C" fn physics_core_tile_cell_at(layer: u32, x: f32, y: f32, out_column: *mut u32, out_row: *mut u32) -> bool {
+    crash::guard("physics_core_tile_cell_at", || {
+        match (tile_cell_at_internal(layer, x, y), out_column.is_null() || out_row.is_null()) {
+            (Some((column, row)), false) => {
+                unsafe {
+                    *out_column = column;
+                    *out_row = row;
+                }
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+// --- Triggers ---
+
+/// Move up to `capacity` queued zone events, oldest first, into `out_events`
+/// and return how many were written. `zone` indexes the level's `triggers`.
+/// At most 256 are kept.
+#[no_mangle]
+pub extern "C" fn physics_core_poll_trigger_events(out_events: *mut TriggerEvent, capacity: u32) -> u32 {
+    crash::guard("physics_core_poll_trigger_events", || {
+        if out_events.is_null() || capacity == 0 {
+            return 0;
+        }
+        let events = poll_trigger_events_internal(capacity as usize);
+        unsafe { std::ptr::copy_nonoverlapping(events.as_ptr(), out_events, events.len()) };
+        events.len() as u32
+    })
+}
+
+/// `PHYSICS_CORE_OUTCOME_WON` once a watched body entered a goal zone,
+/// `PHYSICS_CORE_OUTCOME_LOST` once one entered a hazard, otherwise
+/// `PHYSICS_CORE_OUTCOME_PLAYING`. Loading a level starts over.
+#[no_mangle]
+pub extern "C" fn physics_core_level_outcome() -> u32 {
+    crash::guard("physics_core_level_outcome", || {
+        level_outcome_internal().unwrap_or_default() as u32
+    })
+}
+
+// --- Scenes ---
+
+/// Register scene `name` from JSON: `{"level": <level as for
+/// physics_core_load_level>, "bodies": [{"x": 0, "y": 0, "halfExtent": 0.05,
+/// "vx": 0, "vy": 0, "bodyType": "dynamic", "groups": 1, "materialId": 0},
+/// ...], "gravity": [x, y], "tweens": [{"body": index, "property": "scale",
+/// "from": [0], "to": [1], "duration": 0.5, "delay": 0, "easing":
+/// "backOut"}, ...], "stateMachines": [{"bodies": [index, ...], <machine as
+/// for physics_core_set_state_machine>}, ...]}`; every top-level field is
+/// optional. Properties are "scale", "rotation", "color", "cameraZoom" and
+/// "cameraPosition"; easings are named like the `PHYSICS_CORE_EASING_*`
+/// constants in camelCase. "prefabs" and "instances" are described at
+/// physics_core_register_prefab, "textures" at physics_core_load_scene_asset.
+/// Replaces a scene of the same name. Returns false on parse errors, invalid
+/// geometry or when 64 scenes are registered.
+#[no_mangle]
+pub extern "C" fn physics_core_register_scene(name: *const c_char, json: *const c_char) -> bool {
+    crash::guard("physics_core_register_scene", || {
+        let (Some(name), Some(json)) = (c_text(name), c_text(json)) else {
+            return false;
+        };
+        match register_scene_json_internal(name, json) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_register_scene: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Forget a registered scene; the running simulation is unaffected
+#[no_mangle]
+pub extern "C" fn physics_core_remove_scene(name: *const c_char) -> bool {
+    crash::guard("physics_core_remove_scene", || {
+        c_text(name).is_some_and(remove_scene_internal)
+    })
+}
+
+/// Tear down the running simulation (bodies, joints, springs, trails) and
+/// build scene `name` in its place, keeping the simulation settings. With
+/// `fade_seconds` > 0 the new scene fades in from the clear color. Returns
+/// false for unknown scenes.
+#[no_mangle]
+pub extern "C" fn physics_core_switch_scene(name: *const c_char, fade_seconds: f32) -> bool {
+    crash::guard("physics_core_switch_scene", || {
+        let Some(name) = c_text(name) else {
+            return false;
+        };
+        match switch_scene_internal(name, fade_seconds) {
+            Ok(_) => true,
+            Err(e) => {
+                log::error!("physics_core_switch_scene: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Switch to the scene registered after the current one, or to the first
+/// scene if none was loaded. Returns false after the last scene.
+#[no_mangle]
+pub extern "C" fn physics_core_load_next_scene(fade_seconds: f32) -> bool {
+    crash::guard("physics_core_load_next_scene", || {
+        match load_next_scene_internal(fade_seconds) {
+            Ok(next) => next.is_some(),
+            Err(e) => {
+                log::error!("physics_core_load_next_scene: {}", e);
+                false
+            }
+        }
+    })
+}
+
+// --- Prefabs ---
+
+/// Register prefab `name` from JSON: `{"shape": {"type": "box",
+/// "halfExtent": 0.05} or {"type": "polygon", "points": [[x, y], ...]},
+/// "bodyType": "dynamic", "groups": 1, "materialId": 0, "clip":
+/// {"firstFrame": 0, "frameCount": 4, "frameDuration": 0.1, "looping":
+/// true}, "movement": <as for physics_core_set_movement>, "stateMachine":
+/// <as for physics_core_set_state_machine>, "parts": [{"prefab": "wheel",
+/// "x": 0.1, "y": -0.05, "angle": 0}, ...]}`; every field is optional.
+/// Parts are other prefabs attached at an offset, looked up when the prefab
+/// spawns. Scenes can bring prefabs too, under "prefabs": {name: prefab},
+/// and place them with "instances": [{"prefab": name, "x": 0, "y": 0,
+/// "angle": 0}, ...]. Replaces a prefab of the same name. Returns false on
+/// parse errors, invalid values or when 256 prefabs are registered.
+#[no_mangle]
+pub extern "C" fn physics_core_register_prefab(name: *const c_char, json: *const c_char) -> bool {
+    crash::guard("physics_core_register_prefab", || {
+        let (Some(name), Some(json)) = (c_text(name), c_text(json)) else {
+            return false;
+        };
+        match register_prefab_json_internal(name, json) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_register_prefab: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Forget a registered prefab; bodies spawned from it stay
+#[no_mangle]
+pub extern "C" fn physics_core_remove_prefab(name: *const c_char) -> bool {
+    crash::guard("physics_core_remove_prefab", || {
+        c_text(name).is_some_and(remove_prefab_internal)
+    })
+}
+
+/// Spawn prefab `name` with its parts at (x, y), turned by `angle` radians.
+/// Returns the id of the prefab's own body, which the parts are attached
+/// to, or 0 for unknown prefabs, parts nested too deep and unknown
+/// materials; nothing is left behind then.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_prefab(name: *const c_char, x: f32, y: f32, angle: f32) -> u64 {
+    crash::guard("physics_core_spawn_prefab", || {
+        let Some(name) = c_text(name) else {
+            return 0;
+        };
+        match spawn_prefab_internal(name, x, y, angle) {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("physics_core_spawn_prefab: {}", e);
+                0
+            }
+        }
+    })
+}
+
+// --- Assets ---
+
+/// Read assets through `load(name, len, user_data)`, which returns the bytes
+/// of asset `name` ("textures/ball.png") and sets `*len`, or null for assets
+/// it does not have. The bytes only need to stay valid until
+/// `release(bytes, len, user_data)` is called once they are copied; `release`
+/// may be null. Loaders run on the thread reading the asset with no internal
+/// locks held. A null `load` goes back to the platform default: names
+/// relative to the working directory on desktop, preloaded assets only
+/// elsewhere.
+#[no_mangle]
+pub extern "C" fn physics_core_register_asset_loader(
+    load: Option<assets::HostAssetFn>,
+    release: Option<assets::HostAssetReleaseFn>,
+    user_data: *mut c_void,
+) {
+    crash::guard("physics_core_register_asset_loader", || {
+        assets::set_host_loader(load, release, user_data);
+    })
+}
+
+/// Keep `len` bytes as asset `name`, which reads then take instead of asking
+/// the loader. Returns false for invalid names, assets over 64 MiB or when
+/// 1024 assets are preloaded.
+#[no_mangle]
+pub extern "C" fn physics_core_preload_asset(name: *const c_char, bytes: *const u8, len: u32) -> bool {
+    crash::guard("physics_core_preload_asset", || {
+        let Some(name) = c_text(name) else {
+            return false;
+        };
+        if bytes.is_null() {
+            return false;
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(bytes, len as usize) };
+        match assets::preload(name, bytes.to_vec()) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_preload_asset: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Drop preloaded asset `name`. Returns false when it was not preloaded.
+#[no_mangle]
+pub extern "C" fn physics_core_unload_asset(name: *const c_char) -> bool {
+    crash::guard("physics_core_unload_asset", || c_text(name).is_some_and(assets::unload))
+}
+
+/// Register texture asset `name` (PNG or KTX2) as a material and return its
+/// id; loading it again returns the same id. For "x.png" the compressed
+/// variants "x.astc.ktx2", "x.bc.ktx2" and "x.etc2.ktx2" the device samples
+/// are tried first. Returns 0 when it cannot be read or decoded.
+#[no_mangle]
+pub extern "C" fn physics_core_load_texture_asset(name: *const c_char) -> u32 {
+    crash::guard("physics_core_load_texture_asset", || {
+        let Some(name) = c_text(name) else {
+            return material::DEFAULT_MATERIAL;
+        };
+        load_texture_asset_internal(name).unwrap_or_else(|e| {
+            log::error!("physics_core_load_texture_asset: {}", e);
+            material::DEFAULT_MATERIAL
+        })
+    })
+}
+
+/// Read scene asset `name` ("levels/intro.json"; ".ron" files are RON where
+/// supported), register it under its file name without the extension and
+/// switch to it. The scene's "textures": {"3": "textures/ball.png", ...}
+/// are loaded as materials, and its bodies and prefabs with "materialId": 3
+/// are drawn with that texture; a texture that cannot be loaded leaves them
+/// with the default one. Returns the number of bodies spawned, or -1.
+#[no_mangle]
+pub extern "C" fn physics_core_load_scene_asset(name: *const c_char) -> i32 {
+    crash::guard("physics_core_load_scene_asset", || {
+        let Some(name) = c_text(name) else {
+            return -1;
+        };
+        match load_scene_asset_internal(name) {
+            Ok(count) => count as i32,
+            Err(e) => {
+                log::error!("physics_core_load_scene_asset: {}", e);
+                -1
+            }
+        }
+    })
+}
+
+// --- Tweens ---
+
+/// Ease `property` (`PHYSICS_CORE_TWEEN_*`) of the entity from `from` to `to`
+/// over `duration` simulated seconds with `easing` (`PHYSICS_CORE_EASING_*`).
+/// Both arrays hold as many floats as the property takes. Camera properties
+/// ignore `entity_id`. Tweens pause with the simulation; a new tween on the
+/// same property replaces the running one. Returns a tween id for
+/// `physics_core_cancel_tween`, or 0 for unknown entities, codes or values.
+#[no_mangle]
+pub extern "C" fn physics_core_animate_property(
+    entity_id: u64,
+    property: u32,
+    from: *const f32,
+    to: *const f32,
+    duration: f32,
+    easing: u32,
+) -> u64 {
+    crash::guard("physics_core_animate_property", || {
+        let (Some(property), Some(easing)) = (TweenProperty::from_u32(property), Easing::from_u32(easing)) else {
+            return 0;
+        };
+        if from.is_null() || to.is_null() {
+            return 0;
+        }
+        let n = property.components();
+        let (from, to) = unsafe { (std::slice::from_raw_parts(from, n), std::slice::from_raw_parts(to, n)) };
+        animate_property_internal(entity_id, property, from, to, duration, easing).unwrap_or(0)
+    })
+}
+
+/// Stop a tween where it is. Returns false for unknown or finished tweens.
+#[no_mangle]
+pub extern "C" fn physics_core_cancel_tween(tween_id: u64) -> bool {
+    crash::guard("physics_core_cancel_tween", || {
+        cancel_tween_internal(tween_id)
+    })
+}
+
+// --- State machines ---
+
+/// Give the entity a state machine from JSON, replacing any it has:
+/// `{"initial": "patrol", "states": [{"name": "patrol", "movement": {"type":
+/// "linear", "velocity": [0.2, 0]}, "clip": {"firstFrame": 0, "frameCount":
+/// 4, "frameDuration": 0.1, "looping": true}, "materialId": 1,
+/// "transitions": [{"to": "chase", "when": {"after": 2}}, {"to": "hurt",
+/// "when": {"collision": 4}}, {"to": "flee", "when": {"event": "alarm"}}]},
+/// ...]}`. Movement types are "none", "linear", "sinusoidal" (amplitude,
+/// frequency, directionX), "circular" (radius, angularSpeed) and
+/// "horizontalRandom" (speed, moveDuration, pauseDuration, seed) and the
+/// steering movements of `physics_core_set_movement`; a state without
+/// movement, clip or material keeps the previous one. Collisions
+/// match the other body's groups. NULL removes the machine. Returns false on
+/// parse errors, unknown states or materials and unknown entities.
+#[no_mangle]
+pub extern "C" fn physics_core_set_state_machine(entity_id: u64, json: *const c_char) -> bool {
+    crash::guard("physics_core_set_state_machine", || {
+        let result = if json.is_null() {
+            set_state_machine_internal(entity_id, None)
+        } else {
+            let Some(json) = c_text(json) else {
+                return false;
+            };
+            set_state_machine_json_internal(entity_id, json)
+        };
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_state_machine: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Send a named event to the entity's state machine, or to every machine
+/// when `entity_id` is 0; it is checked against the transitions on the next
+/// step. Returns how many machines received it.
+#[no_mangle]
+pub extern "C" fn physics_core_send_state_event(entity_id: u64, event: *const c_char) -> u32 {
+    crash::guard("physics_core_send_state_event", || {
+        c_text(event).map_or(0, |event| send_state_event_internal(entity_id, event) as u32)
+    })
+}
+
+/// Index in `states` of the state the entity's machine is in, or -1 for
+/// entities without one
+#[no_mangle]
+pub extern "C" fn physics_core_entity_state(entity_id: u64) -> i32 {
+    crash::guard("physics_core_entity_state", || {
+        entity_state_internal(entity_id).map_or(-1, |(index, _)| index as i32)
+    })
+}
+
+/// Drive the body with a movement from JSON, starting where it is now. Path
+/// movements are as for `physics_core_set_state_machine`; steering ones
+/// react to the scene every step: `{"type": "seek", "target": entity_id,
+/// "targetGroups": mask, "maxSpeed": 1, "maxAcceleration": 4,
+/// "arriveRadius": 0.2, "avoid": 0.3}`, "flee" (with "panicRadius") and
+/// `{"type": "wander", "speed": 0.5, "turnRate": 1, "maxAcceleration": 4,
+/// "seed": 7, "avoid": 0.3}`. A seek or flee `target` of 0 picks the nearest
+/// body in `targetGroups`; `avoid` > 0 casts feelers that far ahead to steer
+/// around obstacles; `maxAcceleration` 0 turns instantly. `{"type":
+/// "none"}` hands the body back to physics. Returns false on parse errors,
+/// invalid values and entities without a body.
+#[no_mangle]
+pub extern "C" fn physics_core_set_movement(entity_id: u64, json: *const c_char) -> bool {
+    crash::guard("physics_core_set_movement", || {
+        let Some(json) = c_text(json) else {
+            return false;
+        };
+        match set_movement_json_internal(entity_id, json) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_movement: {}", e);
+                false
+            }
+        }
+    })
+}
+
+// --- Command buffers ---
+
+/// Run `len` bytes of packed commands in one call. Each command is a
+/// `PHYSICS_CORE_COMMAND_*` opcode byte followed by little-endian fields
+/// with no padding: SPAWN x, y, half_extent (f32), body_type (one of
+/// `PHYSICS_CORE_BODY_*`), groups (u32); IMPULSE, SET_VELOCITY and
+/// SET_POSITION entity_id (u64), x, y (f32); SET_GROUPS and SET_MATERIAL
+/// entity_id (u64), value (u32). Copies up to `capacity` results, one per
+/// command, into `out_results`: the entity id for spawns, 1 for other
+/// commands that took effect and 0 for ones that failed. Returns the number
+/// of commands run, or -1 without running any when the buffer is malformed
+/// or the simulation is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_submit_commands(
+    commands: *const u8,
+    len: u32,
+    out_results: *mut u64,
+    capacity: u32,
+) -> i32 {
+    crash::guard("physics_core_submit_commands", || {
+        let bytes = if commands.is_null() || len == 0 {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(commands, len as usize) }
+        };
+        match submit_commands_internal(bytes) {
+            Ok(results) => copy_ids_out(&results, out_results, capacity) as i32,
+            Err(e) => {
+                log::error!("physics_core_submit_commands: {}", e);
+                -1
+            }
+        }
+    })
+}
+
+// --- Transform export ---
+
+/// Share every body's transform through one region rewritten at the end of
+/// each `wgpu_update`, with room for `capacity` bodies (0 frees it).
+/// The region starts with a 32-byte header {front, capacity, record_size,
+/// reserved: u32} followed by two buffers, each a 32-byte header {frame: u64,
+/// count, total: u32, reserved} and `capacity` records {entity_id: u64, x, y,
+/// rotation, vx, vy: f32, groups: u32}. Read buffer `front`; its `frame` is 0
+/// while it is being rewritten, so copy it and keep the copy only if `frame`
+/// did not change. `total` counts bodies that did not fit. Writes the length
+/// in bytes to `out_len` and returns the start, which stays valid until the
+/// next call; NULL when freed or for capacities above 65536.
+#[no_mangle]
+pub extern "C" fn physics_core_enable_transform_export(capacity: u32, out_len: *mut u32) -> *const u8 {
+    crash::guard("physics_core_enable_transform_export", || {
+        let (ptr, len) = enable_transform_export_internal(capacity).unwrap_or((std::ptr::null(), 0));
+        if !out_len.is_null() {
+            unsafe {
+                *out_len = len as u32;
+            }
+        }
+        ptr
+    })
+}
+
+// --- Views ---
+
+/// Draw the scene into another surface (same handle kinds as `wgpu_init`)
+/// with its own camera centered on (center_x, center_y) and showing
+/// `view_height` world units vertically. Views draw bodies only, after the
+/// main surface. Returns a view id, or 0 before `wgpu_init`, for invalid
+/// arguments, surfaces that cannot share the main surface's format and
+/// when `PHYSICS_CORE_MAX_VIEWS` are open.
+#[no_mangle]
+pub extern "C" fn physics_core_add_view(
+    surface_handle: *mut std::ffi::c_void,
+    width: u32,
+    height: u32,
+    center_x: f32,
+    center_y: f32,
+    view_height: f32,
+) -> u32 {
+    crash::guard("physics_core_add_view", || {
+        if surface_handle.is_null() {
+            return 0;
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = (width, height, center_x, center_y, view_height);
+            0
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (window_handle, display_handle) = raw_handles(surface_handle);
+            let source = SurfaceSource::Window(RawSurfaceHandle { window_handle, display_handle });
+            add_view_internal(source, width, height, ViewCamera { center_x, center_y, view_height }).unwrap_or(0)
+        }
+    })
+}
+
+/// The host may release the surface once this returns
+#[no_mangle]
+pub extern "C" fn physics_core_remove_view(view_id: u32) -> bool {
+    crash::guard("physics_core_remove_view", || {
+        remove_view_internal(view_id)
+    })
+}
+
+/// Call when the view's surface changes size; false for unknown views and
+/// zero sizes
+#[no_mangle]
+pub extern "C" fn physics_core_resize_view(view_id: u32, width: u32, height: u32) -> bool {
+    crash::guard("physics_core_resize_view", || {
+        resize_view_internal(view_id, width, height)
+    })
+}
+
+/// Move a view's camera; false for unknown views and invalid cameras
+#[no_mangle]
+pub extern "C" fn physics_core_set_view_camera(view_id: u32, center_x: f32, center_y: f32, view_height: f32) -> bool {
+    crash::guard("physics_core_set_view_camera", || {
+        set_view_camera_internal(view_id, ViewCamera { center_x, center_y, view_height })
+    })
+}
+
+/// Split the main surface between two players from a JSON object (UTF-8,
+/// NUL-terminated) such as `{"layout": "stacked", "players": [{"follow":
+/// 4294967297}, {"centerX": 2}], "bindings": [{"keyCode": 51, "player": 1,
+/// "action": "up"}]}`; missing fields keep their defaults (side by side,
+/// W/A/S/D and space for player 1, the D-pad and enter for player 2). NULL
+/// or `null` goes back to one camera. Returns false on parse errors, invalid
+/// cameras or players, or if the simulation is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_set_split_screen(json: *const c_char) -> bool {
+    crash::guard("physics_core_set_split_screen", || {
+        if json.is_null() {
+            return set_split_screen_internal(None);
+        }
+        let json = unsafe { std::ffi::CStr::from_ptr(json) };
+        match json.to_str().map_err(|e| e.to_string()).and_then(set_split_screen_json_internal) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_split_screen: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Hand the body to player 1 or 2 under split screen, making it
+/// controllable; 0 lets it answer to both players again. Returns false for
+/// unknown bodies and other player numbers.
+#[no_mangle]
+pub extern "C" fn physics_core_set_player(entity_id: u64, player: u32) -> bool {
+    crash::guard("physics_core_set_player", || {
+        u8::try_from(player).is_ok_and(|player| set_player_internal(entity_id, player))
+    })
+}
+
+// --- Netcode ---
+
+/// Encode every body's position, rotation and velocities for a remote
+/// simulation, keeping only what changed since `baseline_tick`: a tick
+/// returned by the remote `physics_core_apply_snapshot` (0, or a tick more
+/// than 64 snapshots old, gives a full snapshot). Returns the length in
+/// bytes; the snapshot is copied to `out_bytes` only if it fits in
+/// `capacity`, so call again with a larger buffer otherwise. Returns 0 if
+/// the simulation is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_get_snapshot_delta(baseline_tick: u64, out_bytes: *mut u8, capacity: u32) -> u32 {
+    crash::guard("physics_core_get_snapshot_delta", || {
+        let Some(bytes) = snapshot_delta_internal(baseline_tick) else {
+            return 0;
+        };
+        if !out_bytes.is_null() && bytes.len() <= capacity as usize {
+            unsafe {
+                std::ptr::copy_nonoverlapping(bytes.as_ptr(), out_bytes, bytes.len());
+            }
+        }
+        bytes.len() as u32
+    })
+}
+
+/// Apply `len` bytes from the remote `physics_core_get_snapshot_delta`:
+/// velocities are taken as sent, small position and rotation errors are
+/// eased out over the next steps and large ones jump. Returns the
+/// snapshot's tick, to send back as the next baseline, or 0 for malformed
+/// or stale snapshots and baselines this side never received.
+#[no_mangle]
+pub extern "C" fn physics_core_apply_snapshot(bytes: *const u8, len: u32) -> u64 {
+    crash::guard("physics_core_apply_snapshot", || {
+        if bytes.is_null() {
+            return 0;
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(bytes, len as usize) };
+        match apply_snapshot_internal(bytes) {
+            Ok(tick) => tick,
+            Err(e) => {
+                log::error!("physics_core_apply_snapshot: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Keep `window` steps (at most 600; 0 turns rollback off) of input and
+/// predicted body states so `physics_core_reconcile_snapshot` can redo them.
+/// A prediction stands while it is within `tolerance` (m, radians and m/s)
+/// of the authority. Raises the rewind history to `window` + 1 steps.
+/// Returns false for invalid arguments or if the simulation is not running.
+#[no_mangle]
+pub extern "C" fn physics_core_set_prediction(window: u32, tolerance: f32) -> bool {
+    crash::guard("physics_core_set_prediction", || {
+        set_prediction_internal(window, tolerance)
+    })
+}
+
+/// Mark a body as driven locally ahead of the authority, or clear the mark.
+/// Returns false for unknown bodies.
+#[no_mangle]
+pub extern "C" fn physics_core_set_predicted(entity_id: u64, predicted: bool) -> bool {
+    crash::guard("physics_core_set_predicted", || {
+        set_predicted_internal(entity_id, predicted)
+    })
+}
+
+/// Like `physics_core_apply_snapshot`, but predicted bodies are checked
+/// against the snapshot instead of eased toward it: if one was off, the
+/// simulation rewinds to the snapshot's tick, takes its state and re-runs
+/// the steps since with the input they consumed. Both sides must step in
+/// lockstep. Returns the snapshot's tick, or 0 when it could not be applied.
+#[no_mangle]
+pub extern "C" fn physics_core_reconcile_snapshot(bytes: *const u8, len: u32) -> u64 {
+    crash::guard("physics_core_reconcile_snapshot", || {
+        if bytes.is_null() {
+            return 0;
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(bytes, len as usize) };
+        match reconcile_snapshot_internal(bytes) {
+            Ok(tick) => tick,
+            Err(e) => {
+                log::error!("physics_core_reconcile_snapshot: {}", e);
+                0
+            }
+        }
+    })
+}
+
+// --- Springs ---
+
+/// Pull the body's center toward (world_x, world_y) with a spring of
+/// `stiffness` N/m and `damping` N·s/m. Returns a spring id for
+/// `physics_core_update_spring` / `physics_core_remove_spring`, or 0 for
+/// unknown bodies and negative or non-finite arguments.
+#[no_mangle]
+pub extern "C" fn physics_core_create_spring_to_point(
+    entity_id: u64,
+    world_x: f32,
+    world_y: f32,
+    stiffness: f32,
+    damping: f32,
+) -> u64 {
+    crash::guard("physics_core_create_spring_to_point", || {
+        create_spring_to_point_internal(entity_id, world_x, world_y, stiffness, damping).unwrap_or(0)
+    })
+}
+
+/// Move the spring's point and change its strength; meant to be called every
+/// frame while dragging. Returns false for unknown springs.
+#[no_mangle]
+pub extern "C" fn physics_core_update_spring(
+    spring_id: u64,
+    world_x: f32,
+    world_y: f32,
+    stiffness: f32,
+    damping: f32,
+) -> bool {
+    crash::guard("physics_core_update_spring", || {
+        update_spring_internal(spring_id, world_x, world_y, stiffness, damping)
+    })
+}
+
+/// Springs also go away with their body
+#[no_mangle]
+pub extern "C" fn physics_core_remove_spring(spring_id: u64) -> bool {
+    crash::guard("physics_core_remove_spring", || {
+        remove_spring_internal(spring_id)
+    })
+}
+
+// --- Undoable Edits ---
+
+/// Spawn a box from a scene body (`{"x": 0, "y": 1, "halfExtent": 0.1,
+/// "angle": 0, "vx": 0, "vy": 0, "bodyType": "dynamic", "groups": 1,
+/// "materialId": 0}`, everything optional) as a step `physics_core_undo`
+/// can take back. Returns its id, or 0 on parse errors and before init.
+#[no_mangle]
+pub extern "C" fn physics_core_edit_spawn_body(body_json: *const c_char) -> u64 {
+    crash::guard("physics_core_edit_spawn_body", || {
+        let Some(json) = c_text(body_json) else {
+            return 0;
+        };
+        match edit_spawn_body_json_internal(json) {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("physics_core_edit_spawn_body: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Make the box `entity_id` match a scene body (see
+/// `physics_core_edit_spawn_body`; fields left out keep their defaults, not
+/// the box's values) as an undoable step. With `merge` a change right after
+/// another change to the same box joins its step, so a slider dragged over
+/// many frames undoes at once. Returns false on parse errors, unknown ids
+/// and other shapes.
+#[no_mangle]
+pub extern "C" fn physics_core_edit_set_body(entity_id: u64, body_json: *const c_char, merge: bool) -> bool {
+    crash::guard("physics_core_edit_set_body", || {
+        let Some(json) = c_text(body_json) else {
+            return false;
+        };
+        match edit_body_json_internal(entity_id, json, merge) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_edit_set_body: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Remove the box `entity_id` as an undoable step; undoing spawns it again
+/// under a new id. Returns false for unknown ids and other shapes.
+#[no_mangle]
+pub extern "C" fn physics_core_edit_remove_body(entity_id: u64) -> bool {
+    crash::guard("physics_core_edit_remove_body", || {
+        edit_remove_body_internal(entity_id)
+    })
+}
+
+/// `physics_core_create_spring_to_point` as an undoable step
+#[no_mangle]
+pub extern "C" fn physics_core_edit_attach_spring(entity_id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> u64 {
+    crash::guard("physics_core_edit_attach_spring", || {
+        edit_attach_spring_internal(entity_id, world_x, world_y, stiffness, damping).unwrap_or(0)
+    })
+}
+
+/// The box `entity_id` as a scene body JSON object, for editing and passing
+/// back to `physics_core_edit_set_body`; null for unknown ids and other
+/// shapes. Release with `physics_core_free_string`.
+#[no_mangle]
+pub extern "C" fn physics_core_get_body_desc(entity_id: u64) -> *mut c_char {
+    crash::guard("physics_core_get_body_desc", || {
+        match body_desc_json_internal(entity_id).and_then(|json| CString::new(json).ok()) {
+            Some(c_str) => c_str.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Take back the last edit step. Returns false when there is none, or when
+/// what it refers to has gone some other way, which drops it.
+#[no_mangle]
+pub extern "C" fn physics_core_undo() -> bool {
+    crash::guard("physics_core_undo", undo_internal)
+}
+
+/// Carry out the last undone step again; any new edit drops the steps that
+/// could be redone
+#[no_mangle]
+pub extern "C" fn physics_core_redo() -> bool {
+    crash::guard("physics_core_redo", redo_internal)
+}
+
+/// Forget every step; switching scenes and resetting do this too
+#[no_mangle]
+pub extern "C" fn physics_core_clear_history() {
+    crash::guard("physics_core_clear_history", clear_history_internal)
+}
+
+/// `{"undo": 2, "redo": 0, "undoLabel": "change", "redoLabel": null}`: the
+/// steps that can be undone and redone, and what the next ones are ("spawn",
+/// "remove", "change" or "spring"). Release with `physics_core_free_string`.
+#[no_mangle]
+pub extern "C" fn physics_core_get_history_state() -> *mut c_char {
+    crash::guard("physics_core_get_history_state", || {
+        CString::new(history_state_internal()).map_or(std::ptr::null_mut(), CString::into_raw)
+    })
+}
+
+// --- Soft Bodies ---
+
+/// Spawn a soft blob of `segments` nodes (3..=64) on a circle of `radius`
+/// around (x, y), with default springs and pressure. Returns the blob id for
+/// `physics_core_remove_soft_body`, or 0 for invalid arguments.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_soft_body(x: f32, y: f32, radius: f32, segments: u32) -> u64 {
+    crash::guard("physics_core_spawn_soft_body", || {
+        let desc = SoftBodyDesc { x, y, radius, segments, ..SoftBodyDesc::default() };
+        spawn_soft_body_internal(desc).unwrap_or(0)
+    })
+}
+
+/// Spawn a soft blob from a JSON object (UTF-8, NUL-terminated) such as
+/// `{"x": 0, "y": 0.5, "radius": 0.25, "segments": 20, "stiffness": 400,
+/// "damping": 2, "pressure": 40, "nodeHalfExtent": 0.02, "color": [1, 0.5, 0.6, 1]}`;
+/// missing fields take their defaults. Returns the blob id, or 0 on errors.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_soft_body_json(json: *const c_char) -> u64 {
+    crash::guard("physics_core_spawn_soft_body_json", || {
+        if json.is_null() {
+            validation::reject(InputError::null_pointer("json"));
+            return 0;
+        }
+        let json = unsafe { std::ffi::CStr::from_ptr(json) };
+        match json.to_str().map_err(|e| e.to_string()).and_then(spawn_soft_body_json_internal) {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("physics_core_spawn_soft_body_json: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Remove the blob with its nodes and springs. Returns false for unknown ids.
+#[no_mangle]
+pub extern "C" fn physics_core_remove_soft_body(soft_body_id: u64) -> bool {
+    crash::guard("physics_core_remove_soft_body", || {
+        remove_soft_body_internal(soft_body_id)
+    })
+}
+
+// --- Lifetimes ---
+
+/// Despawn the body, with its colliders and joints, after `seconds`; 0 or
+/// less cancels a pending lifetime. Returns false for unknown ids.
+#[no_mangle]
+pub extern "C" fn physics_core_set_lifetime(entity_id: u64, seconds: f32) -> bool {
+    crash::guard("physics_core_set_lifetime", || {
+        set_lifetime_internal(entity_id, seconds)
+    })
+}
+
+/// While enabled, dynamic bodies whose center leaves the rectangle are
+/// despawned. Survives scene resets. Returns false for an empty rectangle.
+#[no_mangle]
+pub extern "C" fn physics_core_set_despawn_bounds(enabled: bool, min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> bool {
+    crash::guard("physics_core_set_despawn_bounds", || {
+        set_despawn_bounds_internal(enabled, min_x, min_y, max_x, max_y)
+    })
+}
+
+// --- Hierarchy ---
+
+/// Add a quad of `half_extent` at (x, y) with no body, drawn with
+/// `material_id` (0 for the default). Returns its entity id for
+/// `physics_core_attach_entity`, or 0 on failure.
+#[no_mangle]
+pub extern "C" fn physics_core_spawn_decoration(x: f32, y: f32, half_extent: f32, material_id: u32) -> u64 {
+    crash::guard("physics_core_spawn_decoration", || {
+        spawn_decoration_internal(x, y, half_extent, material_id).unwrap_or(0)
+    })
+}
+
+/// Despawn a decoration; bodies are refused. Returns false for unknown ids.
+#[no_mangle]
+pub extern "C" fn physics_core_remove_decoration(entity_id: u64) -> bool {
+    crash::guard("physics_core_remove_decoration", || {
+        remove_decoration_internal(entity_id)
+    })
+}
+
+/// Make `child` follow `parent` at (offset_x, offset_y) in the parent's
+/// frame, turned by `rotation` radians. Either may be a body or a decoration;
+/// a body child is kinematic while attached. Re-attaching replaces the
+/// parent. Decorations are despawned with their parent. Returns false for
+/// unknown ids and for attachments that would form a loop.
+#[no_mangle]
+pub extern "C" fn physics_core_attach_entity(child_id: u64, parent_id: u64, offset_x: f32, offset_y: f32, rotation: f32) -> bool {
+    crash::guard("physics_core_attach_entity", || {
+        attach_entity_internal(child_id, parent_id, offset_x, offset_y, rotation)
+    })
+}
+
+/// Leave the child where it is; a body child turns dynamic again if it was.
+/// Returns false if it was not attached.
+#[no_mangle]
+pub extern "C" fn physics_core_detach_entity(child_id: u64) -> bool {
+    crash::guard("physics_core_detach_entity", || {
+        detach_entity_internal(child_id)
+    })
+}
+
+// --- Audio ---
+
+/// Report collisions closing faster than `min_speed` m/s as audio events,
+/// at full volume from `max_speed`; `enabled` false stops and discards
+/// queued events. Survives scene resets. Returns false for invalid speeds.
+#[no_mangle]
+pub extern "C" fn physics_core_set_audio_events(enabled: bool, min_speed: f32, max_speed: f32) -> bool {
+    crash::guard("physics_core_set_audio_events", || {
+        set_audio_events_internal(enabled, min_speed, max_speed)
+    })
+}
+
+/// World position of the listener, usually the camera, for volume and pan
+#[no_mangle]
+pub extern "C" fn physics_core_set_audio_listener(x: f32, y: f32) -> bool {
+    crash::guard("physics_core_set_audio_listener", || {
+        set_audio_listener_internal(x, y)
+    })
+}
+
+/// Full volume within `reference_distance` of the listener, divided by
+/// `1 + rolloff * (distance / reference_distance - 1)` beyond it, and no
+/// events past `max_distance`
+#[no_mangle]
+pub extern "C" fn physics_core_set_audio_attenuation(reference_distance: f32, max_distance: f32, rolloff: f32) -> bool {
+    crash::guard("physics_core_set_audio_attenuation", || {
+        set_audio_attenuation_internal(reference_distance, max_distance, rolloff)
+    })
+}
+
+/// Move up to `capacity` queued events, oldest first, into `out_events` and
+/// return how many were written. At most 256 events are kept between polls.
+#[no_mangle]
+pub extern "C" fn physics_core_poll_audio_events(out_events: *mut AudioEvent, capacity: u32) -> u32 {
+    crash::guard("physics_core_poll_audio_events", || {
+        if out_events.is_null() || capacity == 0 {
+            return 0;
+        }
+        let events = poll_audio_events_internal(capacity as usize);
+        unsafe { std::ptr::copy_nonoverlapping(events.as_ptr(), out_events, events.len()) };
+        events.len() as u32
+    })
+}
+
+/// Call `callback(events, count, user_data)` after every step that produced
+/// events, with no internal locks held; the events are then not queued for
+/// polling. `events` is only valid during the call. Null restores polling.
+#[no_mangle]
+pub extern "C" fn physics_core_set_audio_callback(callback: Option<audio::HostAudioFn>, user_data: *mut c_void) {
+    crash::guard("physics_core_set_audio_callback", || {
+        audio::set_host_callback(callback, user_data);
+    })
+}
+
+// --- Projectiles ---
+
+/// Preallocate `capacity` projectiles (at most 4096) of `radius`, replacing
+/// the current pool; 0 removes it. Fired projectiles reuse these bodies, so
+/// rapid fire never grows the scene. Returns false for invalid arguments.
+#[no_mangle]
+pub extern "C" fn physics_core_configure_projectiles(capacity: u32, radius: f32) -> bool {
+    crash::guard("physics_core_configure_projectiles", || {
+        configure_projectiles_internal(capacity, radius)
+    })
+}
+
+/// Launch a pooled projectile from (origin_x, origin_y). It is parked again
+/// after `ttl` seconds (0 for never) or on its first collision. Returns its
+/// entity id, or 0 when there is no pool or every projectile is in flight.
+#[no_mangle]
+pub extern "C" fn physics_core_fire_projectile(origin_x: f32, origin_y: f32, vx: f32, vy: f32, ttl: f32) -> u64 {
+    crash::guard("physics_core_fire_projectile", || {
+        fire_projectile_internal(origin_x, origin_y, vx, vy, ttl).unwrap_or(0)
+    })
+}
+
+/// Fill `out_stats` with the pool's occupancy and counters (all 0 without a pool)
+#[no_mangle]
+pub extern "C" fn physics_core_get_projectile_stats(out_stats: *mut ProjectileStats) -> bool {
+    crash::guard("physics_core_get_projectile_stats", || {
+        if out_stats.is_null() {
+            return false;
+        }
+        unsafe { *out_stats = projectile_stats_internal() };
+        true
+    })
+}
+
+// --- Groups ---
+
+/// Replace the body's group bits: bit `n` set puts it in group `n`. Bulk
+/// calls below act on bodies sharing at least one bit with their mask, or on
+/// every body for `PHYSICS_CORE_GROUP_ALL`. Returns false for unknown ids.
+#[no_mangle]
+pub extern "C" fn physics_core_set_body_groups(entity_id: u64, groups: u32) -> bool {
+    crash::guard("physics_core_set_body_groups", || {
+        set_body_groups_internal(entity_id, groups)
+    })
+}
+
+/// Group bits of the body; 0 for unknown ids
+#[no_mangle]
+pub extern "C" fn physics_core_get_body_groups(entity_id: u64) -> u32 {
+    crash::guard("physics_core_get_body_groups", || {
+        body_groups_internal(entity_id).unwrap_or(0)
+    })
+}
+
+/// Copy up to `capacity` ids of bodies matching `mask` into `out_ids`, returning the total count
+#[no_mangle]
+pub extern "C" fn physics_core_get_group_ids(mask: u32, out_ids: *mut u64, capacity: u32) -> u32 {
+    crash::guard("physics_core_get_group_ids", || {
+        copy_ids_out(&group_ids_internal(mask), out_ids, capacity)
+    })
+}
+
+/// Remove every body matching `mask`. Returns the number removed.
+#[no_mangle]
+pub extern "C" fn physics_core_despawn_group(mask: u32) -> u32 {
+    crash::guard("physics_core_despawn_group", || {
+        despawn_group_internal(mask)
+    })
+}
+
+/// Freeze the dynamic bodies matching `mask` in place, or let them move on
+/// with the velocity they had. Returns the number of bodies changed.
+#[no_mangle]
+pub extern "C" fn physics_core_set_group_paused(mask: u32, paused: bool) -> u32 {
+    crash::guard("physics_core_set_group_paused", || {
+        set_group_paused_internal(mask, paused)
+    })
+}
+
+/// Returns the number of bodies pushed; paused and non-dynamic ones are skipped
+#[no_mangle]
+pub extern "C" fn physics_core_apply_impulse_to_group(mask: u32, impulse_x: f32, impulse_y: f32) -> u32 {
+    crash::guard("physics_core_apply_impulse_to_group", || {
+        apply_impulse_to_group_internal(mask, impulse_x, impulse_y)
+    })
+}
+
+// --- Spatial queries ---
+
+/// Copy up to `capacity` ids of bodies inside the rectangle into `out_ids`, returning the total hit count
+#[no_mangle]
+pub extern "C" fn physics_core_query_aabb(
+    min_x: f32,
+    min_y: f32,
+    max_x: f32,
+    max_y: f32,
+    out_ids: *mut u64,
+    capacity: u32,
+) -> u32 {
+    crash::guard("physics_core_query_aabb", || {
+        copy_ids_out(&query_aabb_internal(min_x, min_y, max_x, max_y, groups::ALL), out_ids, capacity)
+    })
+}
+
+/// Copy up to `capacity` ids of bodies inside the circle into `out_ids`, returning the total hit count
+#[no_mangle]
+pub extern "C" fn physics_core_query_circle(
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    out_ids: *mut u64,
+    capacity: u32,
+) -> u32 {
+    crash::guard("physics_core_query_circle", || {
+        copy_ids_out(&query_circle_internal(center_x, center_y, radius, groups::ALL), out_ids, capacity)
+    })
+}
+
+/// Like `physics_core_query_aabb`, counting only bodies matching `mask`
+#[no_mangle]
+pub extern "C" fn physics_core_query_aabb_in_groups(
+    min_x: f32,
+    min_y: f32,
+    max_x: f32,
+    max_y: f32,
+    mask: u32,
+    out_ids: *mut u64,
+    capacity: u32,
+) -> u32 {
+    crash::guard("physics_core_query_aabb_in_groups", || {
+        copy_ids_out(&query_aabb_internal(min_x, min_y, max_x, max_y, mask), out_ids, capacity)
+    })
+}
+
+/// Like `physics_core_query_circle`, counting only bodies matching `mask`
+#[no_mangle]
+pub extern "C" fn physics_core_query_circle_in_groups(
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    mask: u32,
+    out_ids: *mut u64,
+    capacity: u32,
+) -> u32 {
+    crash::guard("physics_core_query_circle_in_groups", || {
+        copy_ids_out(&query_circle_internal(center_x, center_y, radius, mask), out_ids, capacity)
+    })
+}
+
+/// Copy up to `capacity` hits into `out_hits`, returning the total hit count
+fn copy_hits_out(ids: &[u64], out_hits: *mut QueryHit, capacity: u32) -> u32 {
+    if !out_hits.is_null() {
+        let hits: Vec<QueryHit> = ids
+            .iter()
+            .take(capacity as usize)
+            .map(|&entity_id| QueryHit {
+                entity_id,
+                body: body_info_internal(entity_id).unwrap_or_default(),
+            })
+            .collect();
+        unsafe {
+            std::ptr::copy_nonoverlapping(hits.as_ptr(), out_hits, hits.len());
+        }
+    }
+    ids.len() as u32
+}
+
+/// Like `physics_core_query_aabb`, but each hit carries the body's state
+#[no_mangle]
+pub extern "C" fn physics_core_query_aabb_hits(
+    min_x: f32,
+    min_y: f32,
+    max_x: f32,
+    max_y: f32,
+    out_hits: *mut QueryHit,
+    capacity: u32,
+) -> u32 {
+    crash::guard("physics_core_query_aabb_hits", || {
+        copy_hits_out(&query_aabb_internal(min_x, min_y, max_x, max_y, groups::ALL), out_hits, capacity)
+    })
+}
+
+/// Like `physics_core_query_circle`, but each hit carries the body's state
+#[no_mangle]
+pub extern "C" fn physics_core_query_circle_hits(
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    out_hits: *mut QueryHit,
+    capacity: u32,
+) -> u32 {
+    crash::guard("physics_core_query_circle_hits", || {
+        copy_hits_out(&query_circle_internal(center_x, center_y, radius, groups::ALL), out_hits, capacity)
+    })
+}
+
+// --- Streaming ---
+
+/// Simulate only bodies within `load_radius` chunks of the focus; chunks unload past `unload_radius`.
+/// Both radii are capped at 64 chunks.
+#[no_mangle]
+pub extern "C" fn physics_core_set_streaming(enabled: bool, chunk_size: f32, load_radius: u32, unload_radius: u32) {
+    crash::guard("physics_core_set_streaming", || {
+        set_streaming_internal(enabled, chunk_size, load_radius, unload_radius);
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_set_streaming_focus_point(x: f32, y: f32) {
+    crash::guard("physics_core_set_streaming_focus_point", || {
+        set_streaming_focus_internal(StreamFocus::Point { x, y });
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_set_streaming_focus_entity(entity_id: u64) {
+    crash::guard("physics_core_set_streaming_focus_entity", || {
+        set_streaming_focus_internal(StreamFocus::Entity(entity_id));
+    })
+}
+
+/// Center streaming on the main camera again, the default
+#[no_mangle]
+pub extern "C" fn physics_core_set_streaming_focus_camera() {
+    crash::guard("physics_core_set_streaming_focus_camera", || {
+        set_streaming_focus_internal(StreamFocus::Camera);
+    })
+}
+
+// --- Hooks and scripting ---
+
+/// Run `callback(point, dt, user_data)` every frame at the given point with no
+/// internal locks held, so it may call back into this API.
+/// point: 0 = pre-physics, 1 = post-physics, 2 = pre-render, 3 = once at the
+/// first frame after a reset.
+/// Returns the hook id, or 0 if the point is unknown or `callback` is null.
+#[no_mangle]
+pub extern "C" fn physics_core_register_hook(
+    point: u32,
+    callback: Option<plugins::HostHookFn>,
+    user_data: *mut c_void,
+) -> u32 {
+    crash::guard("physics_core_register_hook", || {
+        match (plugins::HookPoint::from_u32(point), callback) {
+            (Some(point), Some(callback)) => plugins::register_host_hook(point, callback, user_data),
+            _ => 0,
+        }
+    })
+}
+
+/// Remove a hook registered with `physics_core_register_hook`
+#[no_mangle]
+pub extern "C" fn physics_core_unregister_hook(hook_id: u32) -> bool {
+    crash::guard("physics_core_unregister_hook", || {
+        plugins::unregister_hook(hook_id)
+    })
+}
+
+/// Load a Rhai gameplay script (UTF-8, NUL-terminated) defining `on_frame(dt)` /
+/// `on_collision(a, b)`, replacing the current one. Returns false on compile or run errors.
+#[cfg(feature = "scripting")]
+#[no_mangle]
+pub extern "C" fn physics_core_load_script(source: *const c_char) -> bool {
+    crash::guard("physics_core_load_script", || {
+        if source.is_null() {
+            validation::reject(InputError::null_pointer("source"));
+            return false;
+        }
+        let source = unsafe { std::ffi::CStr::from_ptr(source) };
+        match source.to_str().map_err(|e| e.to_string()).and_then(scripting::load_script) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_load_script: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Stop running the current script
+#[cfg(feature = "scripting")]
+#[no_mangle]
+pub extern "C" fn physics_core_unload_script() {
+    crash::guard("physics_core_unload_script", || {
+        scripting::unload_script();
+    })
+}
+
+// --- Rendering ---
+
+/// Show `pixels`, `height` rows of `width` RGBA8 pixels starting `stride`
+/// pixels apart (0 for `width`), behind the scene until replaced or
+/// cleared; see `physics_core_set_background_fit` for how it is placed.
+/// Copied before returning. Returns false before `wgpu_init` and for empty
+/// or oversized images.
+#[no_mangle]
+pub extern "C" fn physics_core_set_background_rgba(pixels: *const u8, width: u32, height: u32, stride: u32) -> bool {
+    crash::guard("physics_core_set_background_rgba", || {
+        if pixels.is_null() || width == 0 || height == 0 {
+            return false;
+        }
+        let stride = if stride == 0 { width } else { stride };
+        let len = ((height as usize - 1) * stride as usize + width as usize) * 4;
+        let pixels = unsafe { std::slice::from_raw_parts(pixels, len) };
+        match set_background_internal(width, height, stride, PixelLayout::Rgba8, pixels) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_background_rgba: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Show an Android `AHardwareBuffer` behind the scene, copied before
+/// returning. The buffer stays the caller's; it must be RGBA8, RGBX8 or
+/// RGB8 and allow CPU reads (`AHARDWAREBUFFER_USAGE_CPU_READ_OFTEN`).
+/// Returns false before `wgpu_init` and for buffers that cannot be read.
+/// Needs the `android_hardware_buffer` feature, API level 26.
+#[cfg(all(target_os = "android", feature = "android_hardware_buffer"))]
+#[no_mangle]
+pub extern "C" fn physics_core_set_background_hardware_buffer(buffer: *mut c_void) -> bool {
+    crash::guard("physics_core_set_background_hardware_buffer", || {
+        let Some(buffer) = std::ptr::NonNull::new(buffer.cast()) else {
+            return false;
+        };
+        // SAFETY: borrowed for the call; `HardwareBuffer` does not release it
+        let buffer = unsafe { ndk::hardware_buffer::HardwareBuffer::from_ptr(buffer) };
+        match set_background_hardware_buffer_internal(&buffer) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_background_hardware_buffer: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Remove the background image
+#[no_mangle]
+pub extern "C" fn physics_core_clear_background() {
+    crash::guard("physics_core_clear_background", || {
+        clear_background_internal();
+    })
+}
+
+/// How the background covers the view: 0 = fill, cropping the image
+/// (default), 1 = fit, leaving bars of the clear color, 2 = stretch.
+/// Returns false for unknown values and before `wgpu_init`.
+#[no_mangle]
+pub extern "C" fn physics_core_set_background_fit(fit: i32) -> bool {
+    crash::guard("physics_core_set_background_fit", || {
+        background::BackgroundFit::from_i32(fit).is_some_and(set_background_fit_internal)
+    })
+}
+
+/// Unobstructed area of the main surface as insets from each edge, in
+/// surface pixels: Android `WindowInsets` (system bars and display cutout),
+/// the iOS safe area, or CSS `env(safe-area-inset-*)` times the device pixel
+/// ratio. The egui windows, screen-anchored HUD text and the minimap stay
+/// inside it; with `inset_world` the scene is also drawn, and pointers
+/// mapped, inside it instead of under the insets. Returns false for
+/// negative or non-finite insets.
+#[no_mangle]
+pub extern "C" fn physics_core_set_safe_area_insets(left: f32, top: f32, right: f32, bottom: f32, inset_world: bool) -> bool {
+    crash::guard("physics_core_set_safe_area_insets", || {
+        set_safe_area_insets_internal(left, top, right, bottom, inset_world)
+    })
+}
+
+/// Surface pixels per logical point: Android `DisplayMetrics.density`, iOS
+/// `contentScaleFactor`, or the browser's `devicePixelRatio`; the desktop app
+/// takes it from its window. Sets the egui scale, how far a touch moves before
+/// it stops being a tap, and the 3D orbit rate per drag. Pointer and touch
+/// positions stay in surface pixels, except through the wasm functions, which
+/// take CSS pixels. May be called before `wgpu_init` and again whenever the
+/// density changes. Returns false unless finite and above 0.
+#[no_mangle]
+pub extern "C" fn physics_core_set_scale_factor(scale_factor: f32) -> bool {
+    crash::guard("physics_core_set_scale_factor", || set_scale_factor_internal(scale_factor))
+}
+
+/// Report the device's power state to the performance governor: `battery`
+/// from 0 to 1 (negative when unknown), whether it is `charging`, the
+/// `thermal_status` as Android's `PowerManager.THERMAL_STATUS_*` (0 none to 6
+/// shutdown; iOS thermal states nominal, fair, serious and critical are 0, 1,
+/// 3 and 4) and the OS `low_power_mode`. Low power mode, a moderate thermal
+/// status or a battery under 20% caps rendering at 45 FPS and the solver at
+/// 2 iterations, halves fracture debris and water droplets and lets bodies
+/// sleep sooner; a severe thermal status or a battery under 10% caps at 30
+/// FPS and 1 iteration and throws no debris. Throttling starts on the next
+/// update and eases only after the state has allowed it for 10 s. Returns
+/// false for a battery above 1 or an unknown thermal status.
+#[no_mangle]
+pub extern "C" fn physics_core_set_power_state(battery: f32, charging: bool, thermal_status: i32, low_power_mode: bool) -> bool {
+    crash::guard("physics_core_set_power_state", || set_power_state_internal(battery, charging, thermal_status, low_power_mode))
+}
+
+/// Change only the low power mode of the reported power state
+#[no_mangle]
+pub extern "C" fn physics_core_set_low_power_mode(enabled: bool) {
+    crash::guard("physics_core_set_low_power_mode", || set_low_power_mode_internal(enabled))
+}
+
+/// Disabling the governor lifts every limit at once (default enabled)
+#[no_mangle]
+pub extern "C" fn physics_core_set_governor_enabled(enabled: bool) {
+    crash::guard("physics_core_set_governor_enabled", || set_governor_enabled_internal(enabled))
+}
+
+/// The governor's reported power state, throttle level and limits in force
+/// as JSON, e.g. `{"enabled": true, "level": "light", "throttle": {"maxFps":
+/// 45, ...}, ...}`. Release with `physics_core_free_string`.
+#[no_mangle]
+pub extern "C" fn physics_core_get_governor_stats() -> *mut c_char {
+    crash::guard("physics_core_get_governor_stats", || match CString::new(governor_stats_internal()) {
+        Ok(c_str) => c_str.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_set_clear_color(r: f32, g: f32, b: f32, a: f32) {
+    crash::guard("physics_core_set_clear_color", || {
+        set_clear_color_internal(r, g, b, a);
+    })
+}
+
+/// Requested MSAA sample count (1, 2 or 4); applied at the next `wgpu_init`
+#[no_mangle]
+pub extern "C" fn physics_core_set_msaa_samples(samples: u32) {
+    crash::guard("physics_core_set_msaa_samples", || {
+        set_msaa_samples_internal(samples);
+    })
+}
+
+/// HDR bloom post-process (requires compute support; ignored otherwise)
+#[no_mangle]
+pub extern "C" fn physics_core_set_bloom(enabled: bool, threshold: f32, intensity: f32) {
+    crash::guard("physics_core_set_bloom", || {
+        set_bloom_internal(enabled, threshold, intensity);
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_set_contact_shadows(enabled: bool, intensity: f32, spread: f32) {
+    crash::guard("physics_core_set_contact_shadows", || {
+        set_contact_shadows_internal(enabled, intensity, spread);
+    })
+}
+
+/// Skip uploading and drawing bodies outside every camera's view (on by
+/// default). Frame stats count the bodies left out.
+#[no_mangle]
+pub extern "C" fn physics_core_set_culling(enabled: bool) {
+    crash::guard("physics_core_set_culling", || {
+        set_culling_internal(enabled);
+    })
+}
+
+/// Cull in a compute pass and draw indirectly instead of culling on the CPU
+/// (off by default). Returns false where the GPU cannot (WebGL2, or before
+/// `wgpu_init`), which keeps the CPU path. Bodies of one material may then
+/// overlap in any order, and frame stats count them all as drawn.
+#[no_mangle]
+pub extern "C" fn physics_core_set_gpu_culling(enabled: bool) -> bool {
+    crash::guard("physics_core_set_gpu_culling", || {
+        set_gpu_culling_internal(enabled)
+    })
+}
+
+/// Save the control panel layout, render toggles, 2D camera and solver
+/// settings. They go to `physicsfx_settings.json` in the working directory
+/// unless `physics_core_set_settings_directory` picked another place.
+/// `wgpu_shutdown` saves too. Returns false if the write fails.
+#[no_mangle]
+pub extern "C" fn physics_core_save_settings() -> bool {
+    crash::guard("physics_core_save_settings", || {
+        match save_settings_internal() {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_save_settings: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Apply the saved settings again; `wgpu_init` already does. Returns false
+/// when nothing was saved.
+#[no_mangle]
+pub extern "C" fn physics_core_restore_settings() -> bool {
+    crash::guard("physics_core_restore_settings", || {
+        restore_settings_internal()
+    })
+}
+
+/// Keep settings in `dir` (UTF-8, NUL-terminated), which is created if
+/// missing; NULL keeps nothing. Call before `wgpu_init` for the settings to
+/// be restored there. Returns false if the directory cannot be created.
+#[no_mangle]
+pub extern "C" fn physics_core_set_settings_directory(dir: *const c_char) -> bool {
+    crash::guard("physics_core_set_settings_directory", || {
+        if dir.is_null() {
+            return set_settings_directory_internal(None);
+        }
+        match unsafe { std::ffi::CStr::from_ptr(dir) }.to_str() {
+            Ok(dir) => set_settings_directory_internal(Some(dir)),
+            Err(_) => false,
+        }
+    })
+}
+
+/// Show a minimap in a corner of the main surface from a JSON object (UTF-8,
+/// NUL-terminated) such as `{"corner": "bottomLeft", "width": 240,
+/// "height": 160, "visibleGroups": 2}`; missing fields keep their defaults.
+/// NULL or `null` hides it. Returns false on parse errors and invalid sizes
+/// or colors.
+#[no_mangle]
+pub extern "C" fn physics_core_set_minimap(json: *const c_char) -> bool {
+    crash::guard("physics_core_set_minimap", || {
+        if json.is_null() {
+            return set_minimap_internal(None);
+        }
+        let json = unsafe { std::ffi::CStr::from_ptr(json) };
+        match json.to_str().map_err(|e| e.to_string()).and_then(set_minimap_json_internal) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_minimap: {}", e);
+                false
+            }
+        }
+    })
+}
+
+// --- Capture ---
+
+/// Start recording: from the next update on, each update advances by a fixed
+/// `1 / fps` and each rendered frame is read back as tightly packed RGBA8
+/// instead of being presented. `json` (UTF-8, NUL-terminated, NULL for the
+/// defaults) is an object such as `{"fps": 30, "directory": "out",
+/// "prefix": "frame_", "maxFrames": 300}`; with a directory, frames are
+/// written there as numbered PNGs. `callback(index, width, height, rgba,
+/// user_data)`, if given, is called with each frame with no internal locks
+/// held; `rgba` is only valid during the call. Returns false before
+/// `wgpu_init`, for surface formats that cannot be read back, on parse
+/// errors and when there is neither a directory nor a callback.
+#[no_mangle]
+pub extern "C" fn physics_core_start_capture(json: *const c_char, callback: Option<capture::HostFrameFn>, user_data: *mut c_void) -> bool {
+    crash::guard("physics_core_start_capture", || {
+        let sink = callback.map(|callback| capture::host_sink(callback, user_data));
+        let result = if json.is_null() {
+            start_capture_internal(CaptureConfig::default(), sink)
+        } else {
+            let json = unsafe { std::ffi::CStr::from_ptr(json) };
+            json.to_str().map_err(|e| e.to_string()).and_then(|json| start_capture_json_internal(json, sink))
+        };
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_start_capture: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Stop recording and return to the regular frame timing. Returns the
+/// number of frames captured, 0 if no capture was running.
+#[no_mangle]
+pub extern "C" fn physics_core_stop_capture() -> u64 {
+    crash::guard("physics_core_stop_capture", || {
+        capture::stop()
+    })
+}
+
+/// Whether a capture is running; it also ends on its own after `maxFrames`
+/// or when a frame cannot be written
+#[no_mangle]
+pub extern "C" fn physics_core_is_capturing() -> bool {
+    crash::guard("physics_core_is_capturing", || {
+        capture::is_active()
+    })
+}
+
+/// Screenshot of the next frame rendered and presented, the egui panel
+/// included when the winit app draws it. With `path` (UTF-8,
+/// NUL-terminated) it is written there as a PNG; with `callback`, that is
+/// called as for `physics_core_start_capture` with frame index 0. Either or
+/// both may be given. Returns false before `wgpu_init`, for surface formats
+/// that cannot be read back and when there is neither a path nor a callback.
+#[no_mangle]
+pub extern "C" fn physics_core_take_screenshot(path: *const c_char, callback: Option<capture::HostFrameFn>, user_data: *mut c_void) -> bool {
+    crash::guard("physics_core_take_screenshot", || {
+        let mut targets = Vec::new();
+        if !path.is_null() {
+            match unsafe { std::ffi::CStr::from_ptr(path) }.to_str() {
+                Ok(path) => targets.push(capture::ScreenshotTarget::File(path.into())),
+                Err(e) => {
+                    log::error!("physics_core_take_screenshot: {}", e);
+                    return false;
+                }
+            }
+        }
+        if let Some(callback) = callback {
+            targets.push(capture::ScreenshotTarget::Sink(capture::host_sink(callback, user_data)));
+        }
+        if targets.is_empty() {
+            log::error!("physics_core_take_screenshot: needs a path or a callback");
+            return false;
+        }
+        for target in targets {
+            if let Err(e) = request_screenshot_internal(target) {
+                log::error!("physics_core_take_screenshot: {}", e);
+                return false;
+            }
+        }
+        true
+    })
+}
+
+/// Render and present a frame now and return it as a PNG, without the egui
+/// panel. Writes the length to `out_len` and returns the bytes, to be
+/// released with `physics_core_free_bytes`; NULL before `wgpu_init` and for
+/// surface formats that cannot be read back.
+#[no_mangle]
+pub extern "C" fn physics_core_take_screenshot_png(out_len: *mut u32) -> *mut u8 {
+    crash::guard("physics_core_take_screenshot_png", || {
+        let (ptr, len) = match take_screenshot_internal() {
+            Ok(bytes) => {
+                let len = bytes.len();
+                (Box::into_raw(bytes.into_boxed_slice()) as *mut u8, len)
+            }
+            Err(e) => {
+                log::error!("physics_core_take_screenshot_png: {}", e);
+                (std::ptr::null_mut(), 0)
+            }
+        };
+        if !out_len.is_null() {
+            unsafe {
+                *out_len = len as u32;
+            }
+        }
+        ptr
+    })
+}
+
+/// Key that saves a screenshot in the winit app, as a winit `KeyCode` (F12
+/// by default, negative to disable), and the directory (UTF-8,
+/// NUL-terminated, created if missing; NULL keeps the current one, at first
+/// the working directory) the PNGs go to. Returns false if the directory
+/// cannot be created.
+#[no_mangle]
+pub extern "C" fn physics_core_set_screenshot_key(key_code: i32, directory: *const c_char) -> bool {
+    crash::guard("physics_core_set_screenshot_key", || {
+        let directory = if directory.is_null() { Ok(None) } else { unsafe { std::ffi::CStr::from_ptr(directory) }.to_str().map(Some) };
+        match directory.map_err(|e| e.to_string()).and_then(|directory| set_screenshot_key_internal(key_code, directory)) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("physics_core_set_screenshot_key: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Animated GIF of the last `seconds` of the rewind history at `fps` (1 to
+/// 50), `scale` (above 0, at most 1) times the surface size, looping. The
+/// bodies are drawn as each history entry had them; the simulation itself is
+/// not touched, and the clip is only as long as the history. Writes the
+/// length to `out_len` and returns the bytes, to be released with
+/// `physics_core_free_bytes`; NULL before `wgpu_init`, for invalid
+/// arguments or without history.
+#[no_mangle]
+pub extern "C" fn physics_core_export_clip(seconds: f32, fps: u32, scale: f32, out_len: *mut u32) -> *mut u8 {
+    crash::guard("physics_core_export_clip", || {
+        let (ptr, len) = match export_clip_internal(seconds, fps, scale) {
+            Ok(bytes) => {
+                let len = bytes.len();
+                (Box::into_raw(bytes.into_boxed_slice()) as *mut u8, len)
+            }
+            Err(e) => {
+                log::error!("physics_core_export_clip: {}", e);
+                (std::ptr::null_mut(), 0)
+            }
+        };
+        if !out_len.is_null() {
+            unsafe {
+                *out_len = len as u32;
+            }
+        }
+        ptr
+    })
+}
+
+// --- Text ---
+
+fn c_text<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    unsafe { std::ffi::CStr::from_ptr(text) }.to_str().ok()
+}
+
+/// Show `text` (UTF-8, NUL-terminated) as HUD label `id` with its top-left
+/// corner at pixel (x, y), origin top-left, `size` pixels tall, in `color`
+/// (0xRRGGBBAA). Replaces any label with the same id and stays until
+/// cleared; `\n` starts a new line. Returns false for invalid arguments or
+/// when 512 labels already exist.
+#[no_mangle]
+pub extern "C" fn physics_core_draw_text(id: u64, text: *const c_char, x: f32, y: f32, size: f32, color: u32) -> bool {
+    crash::guard("physics_core_draw_text", || {
+        c_text(text).is_some_and(|text| {
+            draw_text_internal(id, text, TextAnchor::Screen, x, y, size, unpack_rgba(color))
+        })
+    })
+}
+
+/// Like `physics_core_draw_text`, centered above world point (x, y) with
+/// `size` in world units
+#[no_mangle]
+pub extern "C" fn physics_core_draw_world_text(id: u64, text: *const c_char, x: f32, y: f32, size: f32, color: u32) -> bool {
+    crash::guard("physics_core_draw_world_text", || {
+        c_text(text).is_some_and(|text| {
+            draw_text_internal(id, text, TextAnchor::World, x, y, size, unpack_rgba(color))
+        })
+    })
+}
+
+/// World-space label following the entity, centered above its center plus
+/// (offset_x, offset_y). Hidden while the entity does not exist.
+#[no_mangle]
+pub extern "C" fn physics_core_draw_entity_text(
+    id: u64,
+    entity_id: u64,
+    text: *const c_char,
+    offset_x: f32,
+    offset_y: f32,
+    size: f32,
+    color: u32,
+) -> bool {
+    crash::guard("physics_core_draw_entity_text", || {
+        c_text(text).is_some_and(|text| {
+            let anchor = TextAnchor::Body(entity_id);
+            draw_text_internal(id, text, anchor, offset_x, offset_y, size, unpack_rgba(color))
+        })
+    })
+}
+
+/// Remove label `id`; returns false if there was none
+#[no_mangle]
+pub extern "C" fn physics_core_clear_text(id: u64) -> bool {
+    crash::guard("physics_core_clear_text", || {
+        clear_text_internal(id)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_clear_all_text() {
+    crash::guard("physics_core_clear_all_text", || {
+        clear_all_text_internal();
+    })
+}
+
+// --- Trails ---
+
+/// Draw a fading trail behind the body through its last `length` recorded
+/// positions (clamped to 2..=1024) in `color` (0xRRGGBBAA). Calling it again
+/// restyles the trail. Returns false for unknown ids.
+#[no_mangle]
+pub extern "C" fn physics_core_enable_trail(entity_id: u64, length: u32, color: u32) -> bool {
+    crash::guard("physics_core_enable_trail", || {
+        enable_trail_internal(entity_id, length, unpack_rgba(color))
+    })
+}
+
+/// Remove the body's trail. Returns false if it had none.
+#[no_mangle]
+pub extern "C" fn physics_core_disable_trail(entity_id: u64) -> bool {
+    crash::guard("physics_core_disable_trail", || {
+        disable_trail_internal(entity_id)
+    })
+}
+
+// --- Trajectory Prediction ---
+
+/// Predict where the body goes over up to `steps` fixed steps (at most 600)
+/// if launched at (vx, vy) now, without changing the simulation. Writes up to
+/// `capacity` points into `out_points` as x, y pairs (2 * `capacity` floats)
+/// and returns how many were predicted; 0 for unknown or non-dynamic bodies.
+/// `show` also draws the arc as dots until the next prediction or
+/// `physics_core_clear_trajectory_preview`.
+#[no_mangle]
+pub extern "C" fn physics_core_predict_trajectory(
+    entity_id: u64,
+    vx: f32,
+    vy: f32,
+    steps: u32,
+    show: bool,
+    out_points: *mut f32,
+    capacity: u32,
+) -> u32 {
+    crash::guard("physics_core_predict_trajectory", || {
+        let points = predict_trajectory_internal(entity_id, vx, vy, steps, show).unwrap_or_default();
+        if !out_points.is_null() {
+            let count = points.len().min(capacity as usize);
+            unsafe { std::ptr::copy_nonoverlapping(points.as_ptr() as *const f32, out_points, count * 2) };
+        }
+        points.len() as u32
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn physics_core_clear_trajectory_preview() {
+    crash::guard("physics_core_clear_trajectory_preview", || {
+        clear_trajectory_preview_internal();
+    })
+}
+
+// --- Slingshot ---
+
+/// Drag-to-launch with the pointer: press on a dynamic body, drag away to
+/// aim with the predicted arc drawn, release to launch. Drag flings are off
+/// while it is on; turning it off drops any aim in progress.
+#[no_mangle]
+pub extern "C" fn physics_core_enable_slingshot_mode(enabled: bool) {
+    crash::guard("physics_core_enable_slingshot_mode", || {
+        enable_slingshot_mode_internal(enabled);
+    })
+}
+
+/// `max_impulse` (N·s) is reached at a pull of `max_pull` m and caps longer
+/// pulls; `arc_samples` predicted steps are drawn while aiming (at most 600,
+/// 0 hides the arc). Returns false unless the impulse and pull are finite
+/// and > 0.
+#[no_mangle]
+pub extern "C" fn physics_core_set_slingshot_params(max_impulse: f32, max_pull: f32, arc_samples: u32) -> bool {
+    crash::guard("physics_core_set_slingshot_params", || {
+        set_slingshot_params_internal(max_impulse, max_pull, arc_samples)
+    })
+}
+
+// --- Camera Effects ---
+
+/// Shake the camera; `strength` is trauma in 0..1 added to the current
+/// shake, which grows with its square and decays over time. Returns false
+/// for negative or non-finite strengths.
+#[no_mangle]
+pub extern "C" fn physics_core_add_camera_shake(strength: f32) -> bool {
+    crash::guard("physics_core_add_camera_shake", || {
+        add_camera_shake_internal(strength)
+    })
+}
+
+/// Zoom in by `amount` (0.2 is 20% closer, negative zooms out) and ease
+/// back. Punches add up to between -0.5 and 1.
+#[no_mangle]
+pub extern "C" fn physics_core_punch_camera_zoom(amount: f32) -> bool {
+    crash::guard("physics_core_punch_camera_zoom", || {
+        punch_camera_zoom_internal(amount)
+    })
+}
+
+/// Shake at full trauma: up to `max_offset` world units of displacement and
+/// `max_rotation` radians of roll, varying at `frequency` Hz
+#[no_mangle]
+pub extern "C" fn physics_core_set_camera_shake(max_offset: f32, max_rotation: f32, frequency: f32) -> bool {
+    crash::guard("physics_core_set_camera_shake", || {
+        set_camera_shake_internal(max_offset, max_rotation, frequency)
+    })
+}
+
+/// Trauma lost per second (default 1.5) and the zoom punch decay rate per
+/// second (default 6); both must be > 0
+#[no_mangle]
+pub extern "C" fn physics_core_set_camera_effects_decay(trauma_decay: f32, zoom_decay: f32) -> bool {
+    crash::guard("physics_core_set_camera_effects_decay", || {
+        set_camera_effects_decay_internal(trauma_decay, zoom_decay)
+    })
+}
+
+/// Shake on the hardest collision of each step above `min_impulse` N·s,
+/// adding `trauma_per_impulse` trauma per N·s above it. Off (0) by default.
+#[no_mangle]
+pub extern "C" fn physics_core_set_camera_impact_shake(min_impulse: f32, trauma_per_impulse: f32) -> bool {
+    crash::guard("physics_core_set_camera_impact_shake", || {
+        set_camera_impact_shake_internal(min_impulse, trauma_per_impulse)
+    })
+}
+
+/// Stop any shake and zoom punch immediately
+#[no_mangle]
+pub extern "C" fn physics_core_clear_camera_effects() {
+    crash::guard("physics_core_clear_camera_effects", || {
+        clear_camera_effects_internal();
+    })
+}
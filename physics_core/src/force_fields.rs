@@ -0,0 +1,214 @@
+//! Wind, force volumes and buoyancy
+//!
+//! `ForceFieldConfig` describes every field in the scene. Before each step the
+//! force field system resets the user forces of dynamic bodies and adds the
+//! sum of all fields, so the forces hold for every substep of that step. The
+//! maths here only sees plain numbers, which keeps it testable without Rapier.
+//!
+//! Bodies are the demo's unit-density boxes: mass is `(2h)³` and the face
+//! catching wind is `(2h)²` for half extent `h`.
+
+use serde::Deserialize;
+
+/// Steady wind with gusts. Bodies are pushed toward the wind velocity in
+/// proportion to their face area and how much slower they are.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Wind {
+    /// Mean wind velocity in m/s
+    pub velocity: [f32; 2],
+    /// Gust amplitude as a fraction of the mean speed
+    pub gustiness: f32,
+    /// Rough number of gusts per second
+    pub gust_frequency: f32,
+    /// Force per m² of face for each m/s of relative speed
+    pub drag: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self {
+            velocity: [2.0, 0.0],
+            gustiness: 0.5,
+            gust_frequency: 0.5,
+            drag: 1.0,
+        }
+    }
+}
+
+impl Wind {
+    /// Wind velocity at simulated time `time`
+    pub fn velocity_at(&self, time: f32) -> [f32; 2] {
+        let gust = 1.0 + self.gustiness * gust_noise(time * self.gust_frequency);
+        [self.velocity[0] * gust, self.velocity[1] * gust]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeKind {
+    /// Pull toward the center
+    #[default]
+    Attract,
+    /// Push away from the center
+    Repel,
+    /// Spin counterclockwise around the center
+    Vortex,
+}
+
+/// Circular region that accelerates bodies inside it. The acceleration is
+/// `strength` at the center and falls off linearly to 0 at `radius`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ForceVolume {
+    pub center: [f32; 2],
+    pub radius: f32,
+    pub kind: VolumeKind,
+    /// m/s² at the center
+    pub strength: f32,
+}
+
+impl Default for ForceVolume {
+    fn default() -> Self {
+        Self {
+            center: [0.0, 0.0],
+            radius: 0.5,
+            kind: VolumeKind::Attract,
+            strength: 10.0,
+        }
+    }
+}
+
+impl ForceVolume {
+    /// Acceleration of a body at `position`
+    pub fn acceleration_at(&self, position: [f32; 2]) -> [f32; 2] {
+        let (dx, dy) = (position[0] - self.center[0], position[1] - self.center[1]);
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance >= self.radius || distance <= f32::EPSILON {
+            return [0.0, 0.0];
+        }
+        let magnitude = self.strength * (1.0 - distance / self.radius) / distance;
+        match self.kind {
+            VolumeKind::Attract => [-dx * magnitude, -dy * magnitude],
+            VolumeKind::Repel => [dx * magnitude, dy * magnitude],
+            VolumeKind::Vortex => [-dy * magnitude, dx * magnitude],
+        }
+    }
+}
+
+/// Fluid filling `min_x..max_x` below `surface_y`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BuoyancyRegion {
+    pub min_x: f32,
+    pub max_x: f32,
+    pub surface_y: f32,
+    /// Relative to the bodies' density of 1; above 1 they float
+    pub fluid_density: f32,
+    /// Velocity damping per second while fully submerged
+    pub drag: f32,
+}
+
+impl Default for BuoyancyRegion {
+    fn default() -> Self {
+        Self {
+            min_x: -1.0,
+            max_x: 1.0,
+            surface_y: -0.5,
+            fluid_density: 2.0,
+            drag: 2.0,
+        }
+    }
+}
+
+impl BuoyancyRegion {
+    /// Fraction of a box of half extent `half_extent` at `position` below the surface
+    pub fn submerged_fraction(&self, position: [f32; 2], half_extent: f32) -> f32 {
+        if position[0] < self.min_x || position[0] > self.max_x || half_extent <= 0.0 {
+            return 0.0;
+        }
+        ((self.surface_y - (position[1] - half_extent)) / (2.0 * half_extent)).clamp(0.0, 1.0)
+    }
+}
+
+/// What the fields need to know about one dynamic body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodySample {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub mass: f32,
+    pub half_extent: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ForceFieldConfig {
+    pub wind: Option<Wind>,
+    pub volumes: Vec<ForceVolume>,
+    pub buoyancy: Vec<BuoyancyRegion>,
+}
+
+impl ForceFieldConfig {
+    pub fn is_empty(&self) -> bool {
+        self.wind.is_none() && self.volumes.is_empty() && self.buoyancy.is_empty()
+    }
+
+    /// Total force on `body` at simulated time `time` under `gravity`
+    pub fn force_on(&self, body: &BodySample, gravity: [f32; 2], time: f32) -> [f32; 2] {
+        let mut force = [0.0, 0.0];
+        let mut add = |f: [f32; 2]| {
+            force[0] += f[0];
+            force[1] += f[1];
+        };
+
+        if let Some(wind) = &self.wind {
+            let wind_velocity = wind.velocity_at(time);
+            let area = 4.0 * body.half_extent * body.half_extent;
+            let k = wind.drag * area;
+            add([k * (wind_velocity[0] - body.velocity[0]), k * (wind_velocity[1] - body.velocity[1])]);
+        }
+
+        for volume in &self.volumes {
+            let a = volume.acceleration_at(body.position);
+            add([a[0] * body.mass, a[1] * body.mass]);
+        }
+
+        let volume = 8.0 * body.half_extent.powi(3);
+        for region in &self.buoyancy {
+            let submerged = region.submerged_fraction(body.position, body.half_extent);
+            if submerged == 0.0 {
+                continue;
+            }
+            // Archimedes: the displaced fluid's weight, against gravity
+            let displaced = region.fluid_density * volume * submerged;
+            let damping = region.drag * body.mass * submerged;
+            add([
+                -gravity[0] * displaced - damping * body.velocity[0],
+                -gravity[1] * displaced - damping * body.velocity[1],
+            ]);
+        }
+        force
+    }
+}
+
+/// Smooth noise in -1..=1 with about one feature per unit of `t`. Values are
+/// hashed from integer lattice points, so every platform sees the same gusts.
+pub fn gust_noise(t: f32) -> f32 {
+    if !t.is_finite() {
+        return 0.0;
+    }
+    let cell = t.floor();
+    let f = t - cell;
+    let smooth = f * f * (3.0 - 2.0 * f);
+    let (a, b) = (lattice(cell as i64), lattice(cell as i64 + 1));
+    a + (b - a) * smooth
+}
+
+/// SplitMix64 finalizer mapped to -1..=1
+fn lattice(i: i64) -> f32 {
+    let mut x = (i as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    (x >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0
+}
@@ -0,0 +1,306 @@
+//! Destructible bodies that break apart on strong impacts
+//!
+//! A body with a `Destructible` component fractures when a collision's
+//! impulse reaches its `threshold`: after the step it is replaced by a grid
+//! of `k x k` smaller boxes (k² at most `fragments`) covering the same
+//! square. Every fragment moves with the rigid motion of the point it came
+//! from, linear velocity plus spin, and the fragments share the parent's mass
+//! evenly, so linear and angular momentum are conserved. Fragments inherit
+//! the component and can break again until they would fall below
+//! `min_half_extent`.
+//!
+//! Each fracture queues a `FractureEvent` for hosts to poll and can throw
+//! `particles` bits of debris: tiny sensor boxes that fly out evenly around
+//! the break, collide with nothing and despawn after a moment. Their mass is
+//! negligible, so they do not disturb the momentum balance in practice.
+
+use std::collections::VecDeque;
+
+use bevy_ecs::prelude::*;
+use crate::rapier::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::groups::Groups;
+use crate::lifetime::Lifetime;
+use crate::material::MaterialComponent;
+use crate::precision::{planar, to_f32, to_real, xy};
+use crate::schedule::CollisionEvents;
+use crate::{PhysicsBody, PhysicsState, Scale};
+
+/// Most fragments one fracture can make
+pub const MAX_FRAGMENTS: u32 = 64;
+/// Most debris particles one fracture can throw
+pub const MAX_PARTICLES: u32 = 64;
+/// Events kept for hosts that are slow to poll; older ones are dropped
+pub const MAX_QUEUED: usize = 256;
+
+/// Debris size relative to a fragment
+const PARTICLE_SIZE: f32 = 0.3;
+/// Debris speed away from the break, m/s
+const PARTICLE_SPEED: f32 = 1.5;
+/// Seconds debris lives
+const PARTICLE_LIFETIME: f32 = 0.8;
+/// Debris density relative to water; low enough to carry no real momentum
+const PARTICLE_DENSITY: f32 = 0.01;
+
+/// How a destructible body breaks; part of the spawn description
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FractureConfig {
+    /// Collision impulse that breaks the body, N·s
+    pub threshold: f32,
+    /// Target fragment count, 4..=64; rounded down to a square grid
+    pub fragments: u32,
+    /// Bodies whose fragments would be smaller than this do not break
+    pub min_half_extent: f32,
+    /// Debris thrown per fracture, 0..=64
+    pub particles: u32,
+}
+
+impl Default for FractureConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.004,
+            fragments: 4,
+            min_half_extent: 0.01,
+            particles: 8,
+        }
+    }
+}
+
+impl FractureConfig {
+    /// The config with counts clamped, or None unless the threshold and the
+    /// minimum size are finite and > 0
+    pub fn validated(self) -> Option<Self> {
+        let valid = self.threshold.is_finite()
+            && self.min_half_extent.is_finite()
+            && self.threshold > 0.0
+            && self.min_half_extent > 0.0;
+        valid.then_some(Self {
+            fragments: self.fragments.clamp(4, MAX_FRAGMENTS),
+            particles: self.particles.min(MAX_PARTICLES),
+            ..self
+        })
+    }
+
+    /// Grid side `k` for a body of `half_extent`: the largest k with k² at
+    /// most `fragments` whose pieces are still `min_half_extent` or larger.
+    /// None when the body is too small to split at all.
+    pub fn grid(&self, half_extent: f32) -> Option<u32> {
+        let mut k = (self.fragments as f32).sqrt().floor() as u32;
+        while k >= 2 && (half_extent / k as f32) < self.min_half_extent {
+            k -= 1;
+        }
+        (k >= 2).then_some(k)
+    }
+}
+
+/// Centers of a `k x k` grid of pieces of a square of `half_extent`,
+/// relative to its center and turned by `rotation` radians
+pub fn fragment_offsets(half_extent: f32, k: u32, rotation: f32) -> Vec<[f32; 2]> {
+    let cell = 2.0 * half_extent / k as f32;
+    let (sin, cos) = rotation.sin_cos();
+    let mut offsets = Vec::with_capacity((k * k) as usize);
+    for row in 0..k {
+        for column in 0..k {
+            let x = -half_extent + cell * (column as f32 + 0.5);
+            let y = -half_extent + cell * (row as f32 + 0.5);
+            offsets.push([x * cos - y * sin, x * sin + y * cos]);
+        }
+    }
+    offsets
+}
+
+/// Velocity of the point at `offset` from the center of a body moving at
+/// `linvel` and spinning at `angvel` rad/s
+pub fn point_velocity(linvel: [f32; 2], angvel: f32, offset: [f32; 2]) -> [f32; 2] {
+    [linvel[0] - angvel * offset[1], linvel[1] + angvel * offset[0]]
+}
+
+/// Marks a body that breaks on strong impacts
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Destructible(pub FractureConfig);
+
+/// One fracture, for the host
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FractureEvent {
+    /// The body that broke; it no longer exists
+    pub entity: u64,
+    pub x: f32,
+    pub y: f32,
+    /// Impulse of the hit, N·s
+    pub impulse: f32,
+    pub half_extent: f32,
+    pub fragments: u32,
+    pub fragment_half_extent: f32,
+}
+
+/// Events not yet handed to the host
+#[derive(Debug, Clone, Default)]
+pub struct FractureQueue {
+    events: VecDeque<FractureEvent>,
+    dropped: u64,
+}
+
+impl FractureQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: FractureEvent) {
+        if self.events.len() == MAX_QUEUED {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    /// Remove and return up to `max` events, oldest first
+    pub fn drain(&mut self, max: usize) -> Vec<FractureEvent> {
+        let count = max.min(self.events.len());
+        self.events.drain(..count).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Events lost because nobody polled in time
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Break every destructible body hit hard enough during the latest step
+pub(crate) fn update(physics: &mut PhysicsState) {
+    for (entity, impulse) in breaking(&physics.world) {
+        fracture(physics, entity, impulse);
+    }
+}
+
+/// Destructible bodies whose strongest impact of the step reached their threshold
+fn breaking(world: &World) -> Vec<(Entity, f32)> {
+    let mut hits: Vec<(Entity, f32)> = Vec::new();
+    for impact in &world.resource::<CollisionEvents>().impacts {
+        for id in [impact.a, impact.b] {
+            let Some(entity) = Entity::try_from_bits(id).ok().filter(|&e| id != 0 && world.entities().contains(e)) else {
+                continue;
+            };
+            match world.get::<Destructible>(entity) {
+                Some(Destructible(config)) if impact.impulse >= config.threshold => {}
+                _ => continue,
+            }
+            match hits.iter_mut().find(|(hit, _)| *hit == entity) {
+                Some(hit) => hit.1 = hit.1.max(impact.impulse),
+                None => hits.push((entity, impact.impulse)),
+            }
+        }
+    }
+    hits
+}
+
+/// Replace the body with its fragments and debris. Bodies that are not
+/// dynamic, are disabled or are too small are left alone.
+fn fracture(physics: &mut PhysicsState, entity: Entity, impulse: f32) {
+    let world = &physics.world;
+    let (Some(&body), Some(&Scale(half_extent)), Some(&Destructible(config))) =
+        (world.get::<PhysicsBody>(entity), world.get::<Scale>(entity), world.get::<Destructible>(entity))
+    else {
+        return;
+    };
+    let Some(k) = config.grid(half_extent) else {
+        return;
+    };
+    let Some(rb) = physics.rigid_body_set.get(body.rigid_body_handle).filter(|rb| rb.is_dynamic() && rb.is_enabled()) else {
+        return;
+    };
+    let center = xy(rb.translation());
+    let linvel = xy(rb.linvel());
+    let angvel = to_f32(rb.angvel().z);
+    let rotation = *rb.rotation();
+    let angle = to_f32(rotation.euler_angles().2);
+    let density = physics.collider_set.get(body.collider_handle).map_or(1.0, |c| to_f32(c.density()));
+    let groups = world.get::<Groups>(entity).copied().unwrap_or_default();
+    let material = world.get::<MaterialComponent>(entity).copied();
+
+    physics.rigid_body_set.remove(
+        body.rigid_body_handle,
+        &mut physics.island_manager,
+        &mut physics.collider_set,
+        &mut physics.impulse_joint_set,
+        &mut physics.multibody_joint_set,
+        true,
+    );
+    physics.surface_velocities.remove(&body.collider_handle);
+    physics.world.despawn(entity);
+
+    // Boxes are cubes, so k² pieces of a k-times-thinner cube hold 1/k of
+    // the volume; k times the density keeps the parent's mass
+    let piece = half_extent / k as f32;
+    for offset in fragment_offsets(half_extent, k, angle) {
+        let position = [center[0] + offset[0], center[1] + offset[1]];
+        let velocity = point_velocity(linvel, angvel, offset);
+        let fragment = spawn_piece(physics, position, piece, velocity, density * k as f32);
+        if let Some(rb) = physics.world.get::<PhysicsBody>(fragment).and_then(|b| physics.rigid_body_set.get_mut(b.rigid_body_handle)) {
+            rb.set_rotation(rotation, false);
+            rb.set_angvel(vector![0.0, 0.0, to_real(angvel)], true);
+        }
+        let mut fragment = physics.world.entity_mut(fragment);
+        fragment.insert((groups, Destructible(config)));
+        if let Some(material) = material {
+            fragment.insert(material);
+        }
+    }
+
+    // The performance governor thins the debris on a hot or flat device
+    let particles = physics.power_throttle.particles(config.particles);
+    for i in 0..particles {
+        let direction = angle + i as f32 / particles as f32 * std::f32::consts::TAU;
+        let (sin, cos) = direction.sin_cos();
+        let velocity = [linvel[0] + cos * PARTICLE_SPEED, linvel[1] + sin * PARTICLE_SPEED];
+        let particle = spawn_piece(physics, center, piece * PARTICLE_SIZE, velocity, PARTICLE_DENSITY);
+        if let Some(collider) = physics.world.get::<PhysicsBody>(particle).and_then(|b| physics.collider_set.get_mut(b.collider_handle)) {
+            collider.set_sensor(true);
+            collider.set_active_events(ActiveEvents::empty());
+        }
+        let mut particle = physics.world.entity_mut(particle);
+        particle.insert(groups);
+        particle.insert(Lifetime { remaining: PARTICLE_LIFETIME });
+        if let Some(material) = material {
+            particle.insert(material);
+        }
+    }
+
+    physics.fracture_events.push(FractureEvent {
+        entity: entity.to_bits(),
+        x: center[0],
+        y: center[1],
+        impulse,
+        half_extent,
+        fragments: k * k,
+        fragment_half_extent: piece,
+    });
+    physics.instance_layout_dirty = true;
+}
+
+/// A dynamic box moving at `velocity` whose collider has `density`
+fn spawn_piece(physics: &mut PhysicsState, position: [f32; 2], half_extent: f32, velocity: [f32; 2], density: f32) -> Entity {
+    let PhysicsState { world, rigid_body_set, collider_set, sleep_enabled, .. } = physics;
+    let entity = crate::spawn_box(world, rigid_body_set, collider_set, position[0], position[1], half_extent, RigidBodyType::Dynamic);
+    if let Some(&body) = world.get::<PhysicsBody>(entity) {
+        collider_set[body.collider_handle].set_density(to_real(density));
+        let rb = &mut rigid_body_set[body.rigid_body_handle];
+        rb.set_linvel(planar(velocity), true);
+        if !*sleep_enabled {
+            *rb.activation_mut() = RigidBodyActivation::cannot_sleep();
+        }
+    }
+    entity
+}
@@ -24,6 +24,13 @@ pub trait MovementStrategy: Send + Sync {
 
     /// Get a descriptive name for this strategy
     fn name(&self) -> &'static str;
+
+    /// Velocity to drive the body at this step, for strategies that react to
+    /// the scene (see steering.rs). None, the default, moves the body along
+    /// `calculate_position` instead.
+    fn steer(&self, _context: &crate::steering::SteeringContext) -> Option<(f32, f32)> {
+        None
+    }
 }
 
 /// Linear movement in a constant direction
@@ -157,6 +164,8 @@ pub struct MovementComponent {
     pub strategy: Box<dyn MovementStrategy>,
     /// Starting origin position (used as reference for calculations)
     pub origin: (f32, f32),
+    /// `Time::elapsed` when the strategy took over; it is handed the time since
+    pub started_at: f32,
 }
 
 // SAFETY: MovementStrategy requires Send + Sync, so Box<dyn MovementStrategy> is Send + Sync
@@ -0,0 +1,263 @@
+//! Touch gesture recognition
+//!
+//! Turns raw per-pointer touch events (screen pixels, seconds) into taps,
+//! drag-and-fling, pinch and two-finger pan. The recognizer knows nothing
+//! about the simulation; `lib.rs` maps gestures to world coordinates, queues
+//! tap/fling as `GameEvent`s and applies pinch/pan to the camera.
+
+/// Which gestures are acted on, plus recognition thresholds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureConfig {
+    pub tap_spawn: bool,
+    pub fling: bool,
+    pub pinch_zoom: bool,
+    pub pan: bool,
+    /// Movement (logical px) beyond which a touch becomes a drag instead of
+    /// a tap; times the recognizer's scale factor in screen pixels
+    pub tap_slop: f32,
+    /// Longest press (s) still counted as a tap
+    pub tap_max_duration: f64,
+    /// Multiplier on the release velocity of a fling
+    pub fling_scale: f32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            tap_spawn: true,
+            fling: true,
+            pinch_zoom: true,
+            pan: true,
+            tap_slop: 16.0,
+            tap_max_duration: 0.3,
+            fling_scale: 1.0,
+        }
+    }
+}
+
+/// Release velocity is measured over this trailing window (s)
+const VELOCITY_WINDOW: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    Tap { x: f32, y: f32 },
+    /// A single finger moved past the tap slop; `x`/`y` is where it first touched
+    DragStart { x: f32, y: f32 },
+    /// The dragging finger lifted (or a second finger landed); velocity in px/s
+    DragEnd { x: f32, y: f32, vx: f32, vy: f32 },
+    /// Finger spread ratio since the previous move (> 1 = fingers apart)
+    Pinch { factor: f32, center_x: f32, center_y: f32 },
+    /// Two-finger centroid movement since the previous move, in px
+    Pan { dx: f32, dy: f32 },
+}
+
+#[derive(Debug, Clone)]
+struct Touch {
+    id: i32,
+    start: (f32, f32),
+    start_time: f64,
+    /// Recent (time, x, y) samples for the release velocity
+    samples: Vec<(f64, f32, f32)>,
+}
+
+impl Touch {
+    fn position(&self) -> (f32, f32) {
+        self.samples.last().map_or(self.start, |&(_, x, y)| (x, y))
+    }
+
+    fn record(&mut self, time: f64, x: f32, y: f32) {
+        self.samples.push((time, x, y));
+        self.samples.retain(|&(t, _, _)| time - t <= VELOCITY_WINDOW);
+    }
+
+    fn velocity(&self) -> (f32, f32) {
+        match (self.samples.first(), self.samples.last()) {
+            (Some(&(t0, x0, y0)), Some(&(t1, x1, y1))) if t1 > t0 => {
+                let dt = (t1 - t0) as f32;
+                ((x1 - x0) / dt, (y1 - y0) / dt)
+            }
+            _ => (0.0, 0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Idle,
+    Single { dragging: bool },
+    Multi { distance: f32, center: (f32, f32) },
+    /// Fingers left over after a multi-touch gesture; ignored until all lift
+    Ignored,
+}
+
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    /// Screen pixels per logical pixel
+    scale_factor: f32,
+    touches: Vec<Touch>,
+    mode: Mode,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new(GestureConfig::default())
+    }
+}
+
+impl GestureRecognizer {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            scale_factor: 1.0,
+            touches: Vec::new(),
+            mode: Mode::Idle,
+        }
+    }
+
+    pub fn config(&self) -> GestureConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: GestureConfig) {
+        self.config = config;
+    }
+
+    /// Ignored unless finite and above 0
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        if scale_factor.is_finite() && scale_factor > 0.0 {
+            self.scale_factor = scale_factor;
+        }
+    }
+
+    /// Tap slop in screen pixels
+    fn slop(&self) -> f32 {
+        self.config.tap_slop * self.scale_factor
+    }
+
+    pub fn touch_down(&mut self, id: i32, x: f32, y: f32, time: f64) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+        self.touches.retain(|t| t.id != id);
+        self.touches.push(Touch {
+            id,
+            start: (x, y),
+            start_time: time,
+            samples: vec![(time, x, y)],
+        });
+
+        match (self.mode, self.touches.len()) {
+            (Mode::Idle, 1) => self.mode = Mode::Single { dragging: false },
+            (Mode::Single { dragging }, 2) => {
+                // A second finger turns a drag into pinch/pan; let go of the body
+                if dragging {
+                    let (x, y) = self.touches[0].position();
+                    gestures.push(Gesture::DragEnd { x, y, vx: 0.0, vy: 0.0 });
+                }
+                self.mode = self.multi_mode();
+            }
+            (Mode::Ignored, 2) => self.mode = self.multi_mode(),
+            _ => {}
+        }
+        gestures
+    }
+
+    pub fn touch_move(&mut self, id: i32, x: f32, y: f32, time: f64) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+        let touch = match self.touches.iter_mut().find(|t| t.id == id) {
+            Some(touch) => touch,
+            None => return gestures,
+        };
+        touch.record(time, x, y);
+        let start = touch.start;
+
+        match self.mode {
+            Mode::Single { dragging: false } => {
+                if distance(start, (x, y)) > self.slop() {
+                    self.mode = Mode::Single { dragging: true };
+                    gestures.push(Gesture::DragStart { x: start.0, y: start.1 });
+                }
+            }
+            Mode::Multi { distance: last_distance, center: last_center } => {
+                let (new_distance, new_center) = self.pair_metrics();
+                if last_distance > 0.0 && new_distance > 0.0 {
+                    gestures.push(Gesture::Pinch {
+                        factor: new_distance / last_distance,
+                        center_x: new_center.0,
+                        center_y: new_center.1,
+                    });
+                }
+                gestures.push(Gesture::Pan {
+                    dx: new_center.0 - last_center.0,
+                    dy: new_center.1 - last_center.1,
+                });
+                self.mode = Mode::Multi { distance: new_distance, center: new_center };
+            }
+            _ => {}
+        }
+        gestures
+    }
+
+    pub fn touch_up(&mut self, id: i32, x: f32, y: f32, time: f64) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+        let index = match self.touches.iter().position(|t| t.id == id) {
+            Some(index) => index,
+            None => return gestures,
+        };
+        self.touches[index].record(time, x, y);
+        let touch = self.touches.remove(index);
+
+        match self.mode {
+            Mode::Single { dragging: true } => {
+                let (vx, vy) = touch.velocity();
+                gestures.push(Gesture::DragEnd { x, y, vx, vy });
+            }
+            Mode::Single { dragging: false } => {
+                let quick = time - touch.start_time <= self.config.tap_max_duration;
+                if quick && distance(touch.start, (x, y)) <= self.slop() {
+                    gestures.push(Gesture::Tap { x, y });
+                }
+            }
+            _ => {}
+        }
+
+        self.mode = match (self.mode, self.touches.len()) {
+            (_, 0) => Mode::Idle,
+            (Mode::Multi { .. }, 1) => Mode::Ignored,
+            (Mode::Multi { .. }, _) => self.multi_mode(),
+            (mode, _) => mode,
+        };
+        gestures
+    }
+
+    /// The platform took the touch stream away (e.g. a system gesture)
+    pub fn cancel(&mut self) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+        if let (Mode::Single { dragging: true }, Some(touch)) = (self.mode, self.touches.first()) {
+            let (x, y) = touch.position();
+            gestures.push(Gesture::DragEnd { x, y, vx: 0.0, vy: 0.0 });
+        }
+        self.touches.clear();
+        self.mode = Mode::Idle;
+        gestures
+    }
+
+    fn multi_mode(&self) -> Mode {
+        let (distance, center) = self.pair_metrics();
+        Mode::Multi { distance, center }
+    }
+
+    /// Distance between and midpoint of the first two touches
+    fn pair_metrics(&self) -> (f32, (f32, f32)) {
+        match (self.touches.first(), self.touches.get(1)) {
+            (Some(a), Some(b)) => {
+                let (a, b) = (a.position(), b.position());
+                (distance(a, b), ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5))
+            }
+            _ => (0.0, (0.0, 0.0)),
+        }
+    }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
@@ -0,0 +1,420 @@
+//! Minimal glTF 2.0 mesh loading for the 3D sample
+//!
+//! `parse` takes a whole .gltf file (JSON with its buffers embedded as
+//! base64 data URIs) or .glb file as bytes and flattens every triangle
+//! primitive reachable from the default scene into one mesh: positions and
+//! normals with the node transforms applied, indices, and one flat color per
+//! primitive from its material's base color. Missing normals are computed
+//! from the faces. Textures, skins, morph targets, animations, sparse
+//! accessors and external files are not supported.
+//!
+//! The mesh is centered and scaled to span -1..1 along its longest axis, so
+//! a model attached to a body is drawn at the body's size however it was
+//! authored.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// Most vertices in one model
+pub const MAX_VERTICES: usize = 1 << 20;
+
+/// Deepest node hierarchy followed
+const MAX_DEPTH: usize = 64;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// Triangles of a loaded model
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub colors: Vec<[f32; 3]>,
+    /// Counter-clockwise triangles
+    pub indices: Vec<u32>,
+    /// A material asked for back faces to be drawn too
+    pub double_sided: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct Document {
+    scene: Option<usize>,
+    scenes: Vec<Scene>,
+    nodes: Vec<Node>,
+    meshes: Vec<Mesh>,
+    accessors: Vec<Accessor>,
+    buffer_views: Vec<BufferView>,
+    buffers: Vec<Buffer>,
+    materials: Vec<Material>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Scene {
+    nodes: Vec<usize>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Node {
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    matrix: Option<[f32; 16]>,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Mesh {
+    primitives: Vec<Primitive>,
+}
+
+#[derive(Deserialize)]
+struct Primitive {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    #[serde(default = "triangles")]
+    mode: u32,
+}
+
+fn triangles() -> u32 {
+    4
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Accessor {
+    buffer_view: Option<usize>,
+    #[serde(default)]
+    byte_offset: usize,
+    component_type: u32,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    sparse: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferView {
+    buffer: usize,
+    #[serde(default)]
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct Buffer {
+    uri: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct Material {
+    pbr_metallic_roughness: Option<Pbr>,
+    double_sided: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct Pbr {
+    base_color_factor: Option<[f32; 4]>,
+}
+
+fn le_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// JSON and binary chunk of a .glb file
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+    let length = le_u32(bytes, 8).ok_or("truncated glb header")? as usize;
+    if le_u32(bytes, 4) != Some(2) || length > bytes.len() {
+        return Err("only glb version 2 is supported".into());
+    }
+    let (mut json, mut bin) = (None, None);
+    let mut at = 12;
+    while at + 8 <= length {
+        let chunk_length = le_u32(bytes, at).ok_or("truncated glb chunk")? as usize;
+        let data = bytes.get(at + 8..at + 8 + chunk_length).ok_or("truncated glb chunk")?;
+        match le_u32(bytes, at + 4) {
+            Some(CHUNK_JSON) if json.is_none() => json = Some(data),
+            Some(CHUNK_BIN) if bin.is_none() => bin = Some(data),
+            _ => {}
+        }
+        at += 8 + chunk_length;
+    }
+    Ok((json.ok_or("glb has no JSON chunk")?, bin))
+}
+
+/// Standard or URL-safe base64, padding optional
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        bits = (bits << 6 | value as u32) & 0xFFFF;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+        }
+    }
+    Some(out)
+}
+
+fn load_buffers(doc: &Document, bin: Option<&[u8]>) -> Result<Vec<Vec<u8>>, String> {
+    doc.buffers
+        .iter()
+        .enumerate()
+        .map(|(i, buffer)| match (&buffer.uri, bin) {
+            (None, Some(bin)) if i == 0 => Ok(bin.to_vec()),
+            (Some(uri), _) if uri.starts_with("data:") => {
+                let data = uri.split_once(";base64,").ok_or("buffer data URI is not base64")?.1;
+                decode_base64(data).ok_or_else(|| "buffer data URI is not base64".to_string())
+            }
+            _ => Err("external buffer files are not supported; embed them or use .glb".to_string()),
+        })
+        .collect()
+}
+
+/// Each element of an accessor as raw bytes, checked against its buffer
+fn elements<'a>(doc: &Document, buffers: &'a [Vec<u8>], index: usize, components: &[&str]) -> Result<(u32, Vec<&'a [u8]>), String> {
+    let accessor = doc.accessors.get(index).ok_or("accessor out of range")?;
+    if accessor.sparse.is_some() {
+        return Err("sparse accessors are not supported".into());
+    }
+    if !components.contains(&accessor.kind.as_str()) {
+        return Err(format!("unexpected accessor type {}", accessor.kind));
+    }
+    let component_size = match accessor.component_type {
+        5120 | 5121 => 1,
+        5122 | 5123 => 2,
+        5125 | 5126 => 4,
+        other => return Err(format!("unknown component type {}", other)),
+    };
+    let width = match accessor.kind.as_str() {
+        "VEC3" => 3,
+        _ => 1,
+    };
+    let size = component_size * width;
+    let view = doc.buffer_views.get(accessor.buffer_view.ok_or("accessor has no buffer view")?).ok_or("buffer view out of range")?;
+    let buffer = buffers.get(view.buffer).ok_or("buffer out of range")?;
+    let end = view.byte_offset.checked_add(view.byte_length).ok_or("buffer view out of range")?;
+    let data = buffer.get(view.byte_offset..end).ok_or("buffer view out of range")?;
+    let stride = view.byte_stride.unwrap_or(size).max(size);
+    if accessor.count > data.len() / stride + 1 || accessor.byte_offset > data.len() {
+        return Err("accessor runs past its buffer view".into());
+    }
+    (0..accessor.count)
+        .map(|i| data.get(accessor.byte_offset + i * stride..accessor.byte_offset + i * stride + size))
+        .collect::<Option<Vec<_>>>()
+        .map(|elements| (accessor.component_type, elements))
+        .ok_or_else(|| "accessor runs past its buffer view".to_string())
+}
+
+fn read_vec3(doc: &Document, buffers: &[Vec<u8>], index: usize) -> Result<Vec<[f32; 3]>, String> {
+    let (component_type, elements) = elements(doc, buffers, index, &["VEC3"])?;
+    if component_type != 5126 {
+        return Err("positions and normals must be floats".into());
+    }
+    let float = |bytes: &[u8], i: usize| f32::from_le_bytes([bytes[i * 4], bytes[i * 4 + 1], bytes[i * 4 + 2], bytes[i * 4 + 3]]);
+    Ok(elements.into_iter().map(|e| [float(e, 0), float(e, 1), float(e, 2)]).collect())
+}
+
+fn read_indices(doc: &Document, buffers: &[Vec<u8>], index: usize) -> Result<Vec<u32>, String> {
+    let (component_type, elements) = elements(doc, buffers, index, &["SCALAR"])?;
+    elements
+        .into_iter()
+        .map(|e| match component_type {
+            5121 => Ok(e[0] as u32),
+            5123 => Ok(u16::from_le_bytes([e[0], e[1]]) as u32),
+            5125 => Ok(u32::from_le_bytes([e[0], e[1], e[2], e[3]])),
+            _ => Err("indices must be unsigned".to_string()),
+        })
+        .collect()
+}
+
+/// Column-major 4x4 matrices
+type Matrix = [f32; 16];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut out = [0.0; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            out[column * 4 + row] = (0..4).map(|k| a[k * 4 + row] * b[column * 4 + k]).sum();
+        }
+    }
+    out
+}
+
+fn local_matrix(node: &Node) -> Matrix {
+    if let Some(matrix) = node.matrix {
+        return matrix;
+    }
+    let [tx, ty, tz] = node.translation.unwrap_or([0.0; 3]);
+    let [x, y, z, w] = node.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+    let [sx, sy, sz] = node.scale.unwrap_or([1.0; 3]);
+    [
+        (1.0 - 2.0 * (y * y + z * z)) * sx,
+        2.0 * (x * y + z * w) * sx,
+        2.0 * (x * z - y * w) * sx,
+        0.0,
+        2.0 * (x * y - z * w) * sy,
+        (1.0 - 2.0 * (x * x + z * z)) * sy,
+        2.0 * (y * z + x * w) * sy,
+        0.0,
+        2.0 * (x * z + y * w) * sz,
+        2.0 * (y * z - x * w) * sz,
+        (1.0 - 2.0 * (x * x + y * y)) * sz,
+        0.0,
+        tx,
+        ty,
+        tz,
+        1.0,
+    ]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if length > 0.0 {
+        v.map(|c| c / length)
+    } else {
+        [0.0, 0.0, 1.0]
+    }
+}
+
+/// Meshes to draw with their world matrices, from the default scene
+fn mesh_instances(doc: &Document) -> Vec<(usize, Matrix)> {
+    if doc.nodes.is_empty() {
+        return (0..doc.meshes.len()).map(|mesh| (mesh, IDENTITY)).collect();
+    }
+    let roots: Vec<usize> = match doc.scenes.get(doc.scene.unwrap_or(0)) {
+        Some(scene) => scene.nodes.clone(),
+        // Without scenes every node nobody lists as a child is a root
+        None => (0..doc.nodes.len()).filter(|i| !doc.nodes.iter().any(|n| n.children.contains(i))).collect(),
+    };
+    let mut instances = Vec::new();
+    let mut stack: Vec<(usize, Matrix, usize)> = roots.into_iter().map(|node| (node, IDENTITY, 0)).collect();
+    while let Some((index, parent, depth)) = stack.pop() {
+        let Some(node) = doc.nodes.get(index).filter(|_| depth < MAX_DEPTH) else {
+            continue;
+        };
+        let world = multiply(&parent, &local_matrix(node));
+        if let Some(mesh) = node.mesh {
+            instances.push((mesh, world));
+        }
+        stack.extend(node.children.iter().map(|&child| (child, world, depth + 1)));
+    }
+    instances
+}
+
+/// One mesh out of the .gltf or .glb file in `bytes`, spanning -1..1
+pub fn parse(bytes: &[u8]) -> Result<ModelMesh, String> {
+    let (json, bin) = if bytes.starts_with(GLB_MAGIC) { split_glb(bytes)? } else { (bytes, None) };
+    let doc: Document = serde_json::from_slice(json).map_err(|e| e.to_string())?;
+    let buffers = load_buffers(&doc, bin)?;
+
+    let mut mesh = ModelMesh::default();
+    for (mesh_index, world) in mesh_instances(&doc) {
+        let primitives = doc.meshes.get(mesh_index).ok_or("mesh out of range")?.primitives.iter();
+        for primitive in primitives.filter(|p| p.mode == 4) {
+            let positions = read_vec3(&doc, &buffers, *primitive.attributes.get("POSITION").ok_or("primitive has no positions")?)?;
+            if mesh.positions.len() + positions.len() > MAX_VERTICES {
+                return Err(format!("models are limited to {} vertices", MAX_VERTICES));
+            }
+            let mut indices = match primitive.indices {
+                Some(index) => read_indices(&doc, &buffers, index)?,
+                None => (0..positions.len() as u32).collect(),
+            };
+            indices.truncate(indices.len() / 3 * 3);
+            if indices.iter().any(|&i| i as usize >= positions.len()) {
+                return Err("index out of range".into());
+            }
+
+            let columns = [0, 4, 8].map(|c| [world[c], world[c + 1], world[c + 2]]);
+            // Normals go through the cofactor matrix, which is the inverse
+            // transpose times the determinant
+            let cofactor = [cross(columns[1], columns[2]), cross(columns[2], columns[0]), cross(columns[0], columns[1])];
+            let determinant: f32 = (0..3).map(|r| columns[0][r] * cofactor[0][r]).sum();
+            let sign = determinant.signum();
+            // Mirroring transforms turn the triangles around
+            if determinant < 0.0 {
+                indices.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2));
+            }
+            let transformed: Vec<[f32; 3]> = positions
+                .iter()
+                .map(|p| [0, 1, 2].map(|r| columns[0][r] * p[0] + columns[1][r] * p[1] + columns[2][r] * p[2] + world[12 + r]))
+                .collect();
+            let normals: Vec<[f32; 3]> = match primitive.attributes.get("NORMAL") {
+                Some(&index) => read_vec3(&doc, &buffers, index)?
+                    .into_iter()
+                    .map(|n| normalize([0, 1, 2].map(|r| sign * (cofactor[0][r] * n[0] + cofactor[1][r] * n[1] + cofactor[2][r] * n[2]))))
+                    .collect(),
+                None => {
+                    let mut sums = vec![[0.0f32; 3]; transformed.len()];
+                    for triangle in indices.chunks_exact(3) {
+                        let [a, b, c] = [0, 1, 2].map(|k| transformed[triangle[k] as usize]);
+                        let face = cross([0, 1, 2].map(|r| b[r] - a[r]), [0, 1, 2].map(|r| c[r] - a[r]));
+                        for &i in triangle {
+                            sums[i as usize] = [0, 1, 2].map(|r| sums[i as usize][r] + face[r]);
+                        }
+                    }
+                    sums.into_iter().map(normalize).collect()
+                }
+            };
+            if normals.len() != transformed.len() {
+                return Err("primitive has a different number of normals and positions".into());
+            }
+
+            let material = primitive.material.and_then(|m| doc.materials.get(m));
+            mesh.double_sided |= material.is_some_and(|m| m.double_sided);
+            let [r, g, b, _] = material
+                .and_then(|m| m.pbr_metallic_roughness.as_ref())
+                .and_then(|pbr| pbr.base_color_factor)
+                .unwrap_or([0.8, 0.8, 0.8, 1.0]);
+            let offset = mesh.positions.len() as u32;
+            mesh.indices.extend(indices.iter().map(|i| i + offset));
+            mesh.colors.extend(std::iter::repeat_n([r, g, b], transformed.len()));
+            mesh.positions.extend(transformed);
+            mesh.normals.extend(normals);
+        }
+    }
+    if mesh.indices.is_empty() {
+        return Err("model has no triangles".into());
+    }
+
+    // Center the model and fit its longest side into -1..1
+    let (low, high) = mesh.positions.iter().fold(([f32::MAX; 3], [f32::MIN; 3]), |(low, high), p| {
+        ([0, 1, 2].map(|r| low[r].min(p[r])), [0, 1, 2].map(|r| high[r].max(p[r])))
+    });
+    let half = (0..3).map(|r| (high[r] - low[r]) / 2.0).fold(0.0, f32::max);
+    if !half.is_finite() || half <= 0.0 {
+        return Err("model has no size".into());
+    }
+    let center = [0, 1, 2].map(|r| (low[r] + high[r]) / 2.0);
+    for p in &mut mesh.positions {
+        *p = [0, 1, 2].map(|r| (p[r] - center[r]) / half);
+    }
+    Ok(mesh)
+}
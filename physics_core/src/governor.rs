@@ -0,0 +1,255 @@
+//! Performance governor driven by the device's power state
+//!
+//! Hosts report the battery level, whether it charges, the thermal status
+//! and the OS low power mode; any of them may be left at its "unknown"
+//! value, so a host that only knows about low power mode reports just that.
+//! The governor turns the state into a throttle level, and each level caps
+//! the frame rate and solver iterations, thins fracture debris and water
+//! droplets, and lets resting bodies fall asleep sooner.
+//!
+//! Throttling up happens on the next update. Throttling down waits until
+//! the state has allowed the lower level for `RECOVERY_SECONDS`, and the
+//! battery has to climb `BATTERY_MARGIN` past a threshold to leave a level,
+//! so a device hovering at a threshold does not flip between levels.
+
+use serde::Serialize;
+
+/// Battery fraction below which (not charging) the light level applies
+pub const LOW_BATTERY: f32 = 0.2;
+/// Battery fraction below which (not charging) the heavy level applies
+pub const CRITICAL_BATTERY: f32 = 0.1;
+/// Extra battery needed to leave a level entered for low battery
+pub const BATTERY_MARGIN: f32 = 0.05;
+/// Seconds the state must allow a lower level before the governor moves to it
+pub const RECOVERY_SECONDS: f32 = 10.0;
+
+/// Android `PowerManager.THERMAL_STATUS_*` values; iOS maps
+/// `ProcessInfo.ThermalState` nominal, fair, serious and critical to
+/// `None`, `Light`, `Severe` and `Critical`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ThermalStatus {
+    #[default]
+    None,
+    Light,
+    Moderate,
+    Severe,
+    Critical,
+    Emergency,
+    Shutdown,
+}
+
+impl ThermalStatus {
+    /// FFI code: 0 = none through 6 = shutdown
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::None),
+            1 => Some(Self::Light),
+            2 => Some(Self::Moderate),
+            3 => Some(Self::Severe),
+            4 => Some(Self::Critical),
+            5 => Some(Self::Emergency),
+            6 => Some(Self::Shutdown),
+            _ => None,
+        }
+    }
+}
+
+/// What the host knows about the device's power
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerState {
+    /// Charge from 0 to 1; None when unknown
+    pub battery: Option<f32>,
+    pub charging: bool,
+    pub thermal: ThermalStatus,
+    /// The OS battery saver (Android power save mode, iOS Low Power Mode)
+    pub low_power_mode: bool,
+}
+
+impl PowerState {
+    /// A negative `battery` means unknown. None for a battery above 1 or not finite.
+    pub fn new(battery: f32, charging: bool, thermal: ThermalStatus, low_power_mode: bool) -> Option<Self> {
+        if !battery.is_finite() || battery > 1.0 {
+            return None;
+        }
+        let battery = (battery >= 0.0).then_some(battery);
+        Some(Self { battery, charging, thermal, low_power_mode })
+    }
+
+    /// Level the state calls for while the governor is at `current`; the
+    /// battery thresholds of `current` and below are raised by `BATTERY_MARGIN`
+    pub fn level(&self, current: ThrottleLevel) -> ThrottleLevel {
+        let discharging = self.battery.filter(|_| !self.charging);
+        let below = |threshold: f32, level: ThrottleLevel| {
+            let threshold = if current >= level { threshold + BATTERY_MARGIN } else { threshold };
+            discharging.is_some_and(|battery| battery < threshold)
+        };
+        if self.thermal >= ThermalStatus::Severe || below(CRITICAL_BATTERY, ThrottleLevel::Heavy) {
+            ThrottleLevel::Heavy
+        } else if self.thermal >= ThermalStatus::Moderate || self.low_power_mode || below(LOW_BATTERY, ThrottleLevel::Light) {
+            ThrottleLevel::Light
+        } else {
+            ThrottleLevel::None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ThrottleLevel {
+    #[default]
+    None,
+    Light,
+    Heavy,
+}
+
+impl ThrottleLevel {
+    pub fn throttle(self) -> Throttle {
+        match self {
+            Self::None => Throttle::default(),
+            Self::Light => Throttle { max_fps: 45, max_solver_iterations: 2, particle_scale: 0.5, aggressive_sleep: true },
+            Self::Heavy => Throttle { max_fps: 30, max_solver_iterations: 1, particle_scale: 0.0, aggressive_sleep: true },
+        }
+    }
+}
+
+/// Limits in force at a throttle level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Throttle {
+    /// Frames rendered per second at most; 0 keeps the usual cap
+    pub max_fps: u32,
+    /// Solver iterations at most; 0 keeps the configured count
+    pub max_solver_iterations: u32,
+    /// Fraction of the usual fracture debris and water droplets thrown
+    pub particle_scale: f32,
+    /// Resting bodies fall asleep sooner and at higher speeds
+    pub aggressive_sleep: bool,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self { max_fps: 0, max_solver_iterations: 0, particle_scale: 1.0, aggressive_sleep: false }
+    }
+}
+
+impl Throttle {
+    /// Shortest time between rendered frames, if capped below the usual rate
+    pub fn frame_interval_ms(&self) -> Option<f64> {
+        (self.max_fps > 0).then(|| 1000.0 / self.max_fps as f64)
+    }
+
+    /// `configured` solver iterations under the cap
+    pub fn solver_iterations(&self, configured: u32) -> u32 {
+        match self.max_solver_iterations {
+            0 => configured,
+            cap => configured.min(cap),
+        }
+    }
+
+    /// `count` particles thinned by `particle_scale`
+    pub fn particles(&self, count: u32) -> u32 {
+        (count as f32 * self.particle_scale).round() as u32
+    }
+}
+
+/// Read-back of the governor: the reported state and what it throttles
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GovernorStats {
+    pub enabled: bool,
+    pub power: PowerState,
+    pub level: ThrottleLevel,
+    pub throttle: Throttle,
+    /// Seconds the state has allowed a lower level, counting to `RECOVERY_SECONDS`
+    pub recovering_seconds: f32,
+    /// Level changes since startup
+    pub changes: u64,
+}
+
+impl GovernorStats {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Governor {
+    disabled: bool,
+    power: PowerState,
+    level: ThrottleLevel,
+    recovering: f32,
+    changes: u64,
+}
+
+impl Governor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn power(&self) -> PowerState {
+        self.power
+    }
+
+    pub fn set_power(&mut self, power: PowerState) {
+        self.power = power;
+    }
+
+    /// Disabling drops any throttle at once; the state keeps being tracked
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.disabled = !enabled;
+        if !enabled {
+            self.change_level(ThrottleLevel::None);
+        }
+    }
+
+    pub fn level(&self) -> ThrottleLevel {
+        self.level
+    }
+
+    pub fn throttle(&self) -> Throttle {
+        self.level.throttle()
+    }
+
+    /// Move towards the level the state calls for, `dt` seconds after the
+    /// last update. True when the level changed.
+    pub fn update(&mut self, dt: f32) -> bool {
+        if self.disabled {
+            return false;
+        }
+        let target = self.power.level(self.level);
+        if target > self.level {
+            return self.change_level(target);
+        }
+        if target == self.level {
+            self.recovering = 0.0;
+            return false;
+        }
+        if dt.is_finite() && dt > 0.0 {
+            self.recovering += dt;
+        }
+        self.recovering >= RECOVERY_SECONDS && self.change_level(target)
+    }
+
+    fn change_level(&mut self, level: ThrottleLevel) -> bool {
+        self.recovering = 0.0;
+        if level == self.level {
+            return false;
+        }
+        self.level = level;
+        self.changes += 1;
+        true
+    }
+
+    pub fn stats(&self) -> GovernorStats {
+        GovernorStats {
+            enabled: !self.disabled,
+            power: self.power,
+            level: self.level,
+            throttle: self.throttle(),
+            recovering_seconds: self.recovering,
+            changes: self.changes,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Adapter capability detection
+//!
+//! Strict WebGL2 (and some old GLES drivers) have neither compute shaders nor
+//! storage buffers. Everything that depends on them is decided here once at
+//! init, so the renderer asks `GpuCaps` instead of probing the adapter again.
+//! Without compute the instance buffer is a plain vertex buffer and all
+//! instance updates go through the CPU upload path.
+//!
+//! Compressed texture families are requested from the adapter when it has
+//! them and recorded here, so texture loading picks a format the device can
+//! sample (see `ktx2_lite`).
+
+use crate::ktx2_lite::TextureCompression;
+
+const COMPRESSION_FEATURES: [wgpu::Features; 3] = [
+    wgpu::Features::TEXTURE_COMPRESSION_ASTC,
+    wgpu::Features::TEXTURE_COMPRESSION_ETC2,
+    wgpu::Features::TEXTURE_COMPRESSION_BC,
+];
+
+/// What the device can do, as far as the renderer cares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuCaps {
+    pub compute_shaders: bool,
+    /// Storage buffers are bindable from compute shaders
+    pub storage_buffers: bool,
+    /// Compute plus storage access to the HDR format used by bloom
+    pub bloom: bool,
+    /// Compute culling into indirect draws (see `gpu_culling`)
+    pub gpu_culling: bool,
+    /// Compressed texture families the device samples
+    pub texture_compression: TextureCompression,
+}
+
+/// Compressed texture features to request: every family the adapter has
+pub fn compression_features(adapter_features: wgpu::Features) -> wgpu::Features {
+    COMPRESSION_FEATURES
+        .into_iter()
+        .filter(|&feature| adapter_features.contains(feature))
+        .fold(wgpu::Features::empty(), |all, feature| all | feature)
+}
+
+/// Compressed texture families enabled by the device's `features`
+pub fn texture_compression(features: wgpu::Features) -> TextureCompression {
+    TextureCompression {
+        astc: features.contains(wgpu::Features::TEXTURE_COMPRESSION_ASTC),
+        etc2: features.contains(wgpu::Features::TEXTURE_COMPRESSION_ETC2),
+        bc: features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC),
+    }
+}
+
+impl GpuCaps {
+    /// `hdr_storage_texture`: the bloom HDR format allows `STORAGE_BINDING`.
+    /// No compressed textures until `detect` sees the device's features.
+    pub fn new(downlevel: wgpu::DownlevelFlags, limits: &wgpu::Limits, hdr_storage_texture: bool) -> Self {
+        let compute_shaders = downlevel.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS);
+        let storage_buffers = limits.max_storage_buffers_per_shader_stage > 0;
+        Self {
+            compute_shaders,
+            storage_buffers,
+            bloom: compute_shaders && hdr_storage_texture,
+            // Instances, batches, visible instances and draw args
+            gpu_culling: compute_shaders
+                && downlevel.contains(wgpu::DownlevelFlags::INDIRECT_EXECUTION)
+                && limits.max_storage_buffers_per_shader_stage >= 4,
+            texture_compression: TextureCompression::default(),
+        }
+    }
+
+    /// `limits` and `features` are the device's, which may be lower than the
+    /// adapter's
+    pub(crate) fn detect(adapter: &wgpu::Adapter, limits: &wgpu::Limits, features: wgpu::Features) -> Self {
+        let hdr_storage = adapter
+            .get_texture_format_features(crate::post_process::HDR_FORMAT)
+            .allowed_usages
+            .contains(wgpu::TextureUsages::STORAGE_BINDING);
+        let mut caps = Self::new(adapter.get_downlevel_capabilities().flags, limits, hdr_storage);
+        caps.texture_compression = texture_compression(features);
+        log::info!("GPU capabilities: {:?}", caps);
+        caps
+    }
+
+    /// GPU-side instance integration needs a compute pipeline writing the instance buffer
+    pub fn instance_compute(&self) -> bool {
+        self.compute_shaders && self.storage_buffers
+    }
+
+    /// Usage for the instance buffer; `STORAGE` only when something can bind it
+    pub fn instance_buffer_usage(&self) -> wgpu::BufferUsages {
+        let usage = wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST;
+        if self.instance_compute() {
+            usage | wgpu::BufferUsages::STORAGE
+        } else {
+            usage
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! GPU culling and compaction of body instances for indirect drawing
+//!
+//! The advanced path for very large counts. Instead of testing every slot on
+//! the CPU (see `culling`), a compute pass tests each instance against the
+//! camera rects, copies the visible ones of each draw batch into that batch's
+//! slots of a second buffer and counts them in the batch's indirect draw
+//! args. The main pass then draws each batch with `draw_indexed_indirect`,
+//! so nothing about what is visible comes back to the CPU.
+//!
+//! Instances of one batch land in whatever order the invocations finish, so
+//! overlapping bodies of one material may swap which is on top. Needs compute,
+//! storage buffers and indirect execution (`GpuCaps::gpu_culling`); strict
+//! WebGL2 keeps the CPU path.
+
+use crate::culling::ViewRect;
+use crate::material::DrawBatch;
+
+/// Camera rects one pass tests: the main camera or both split-screen
+/// halves, and every extra view
+pub const MAX_RECTS: usize = 2 + crate::views::MAX_VIEWS;
+
+const WORKGROUP_SIZE: u32 = 64;
+/// Bytes of one `DrawIndexedIndirectArgs`
+const ARGS_SIZE: u64 = std::mem::size_of::<wgpu::util::DrawIndexedIndirectArgs>() as u64;
+
+/// Uniform of the cull shader
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CullParams {
+    /// min x, min y, max x, max y of each rect
+    pub rects: [[f32; 4]; MAX_RECTS],
+    pub rect_count: u32,
+    pub batch_count: u32,
+    pub instance_count: u32,
+    /// An instance's bounding radius is `scale * longest side * sqrt(2) *
+    /// grow + shift`
+    pub grow: f32,
+    pub shift: f32,
+    _padding: [u32; 3],
+}
+
+impl CullParams {
+    /// Rects past `MAX_RECTS` are folded into the last one's bounds, which
+    /// only ever draws more
+    pub fn new(rects: &[ViewRect], batch_count: u32, instance_count: u32, grow: f32, shift: f32) -> Self {
+        let mut packed = [[0.0; 4]; MAX_RECTS];
+        for (i, rect) in rects.iter().enumerate() {
+            let slot = &mut packed[i.min(MAX_RECTS - 1)];
+            *slot = match i < MAX_RECTS {
+                true => [rect.min[0], rect.min[1], rect.max[0], rect.max[1]],
+                false => [slot[0].min(rect.min[0]), slot[1].min(rect.min[1]), slot[2].max(rect.max[0]), slot[3].max(rect.max[1])],
+            };
+        }
+        Self {
+            rects: packed,
+            rect_count: rects.len().min(MAX_RECTS) as u32,
+            batch_count,
+            instance_count,
+            grow,
+            shift,
+            _padding: [0; 3],
+        }
+    }
+}
+
+/// Indirect args for `batch_count` batches with nothing counted yet
+pub fn reset_args(batch_count: usize, index_count: u32) -> Vec<wgpu::util::DrawIndexedIndirectArgs> {
+    let args = wgpu::util::DrawIndexedIndirectArgs { index_count, instance_count: 0, first_index: 0, base_vertex: 0, first_instance: 0 };
+    vec![args; batch_count]
+}
+
+/// Compute pass culling the instance buffer into a compacted visible buffer
+pub(crate) struct GpuCullPass {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    /// `[first_instance, instance_count]` of each batch
+    batch_buffer: wgpu::Buffer,
+    batch_capacity: u32,
+    visible_buffer: wgpu::Buffer,
+    visible_capacity: u32,
+    args_buffer: wgpu::Buffer,
+    /// Bound to the instance buffer as of the last `prepare`
+    bind_group: Option<wgpu::BindGroup>,
+    /// Size of one instance in the visible buffer
+    stride: u64,
+    instance_count: u32,
+    /// Batches culled by the last `prepare`; zero skips the dispatch
+    batch_count: u32,
+}
+
+impl GpuCullPass {
+    pub fn new(device: &wgpu::Device, instance_stride: u64) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Cull Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/cull.wgsl"))),
+        });
+
+        let storage = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, true),
+                storage(3, false),
+                storage(4, false),
+            ],
+            label: Some("cull_bind_group_layout"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Cull Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Cull Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cull"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Cull Params Buffer"),
+            size: std::mem::size_of::<CullParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let (batch_buffer, args_buffer) = Self::create_batch_buffers(device, 1);
+        Self {
+            pipeline,
+            bind_group_layout,
+            params_buffer,
+            batch_buffer,
+            batch_capacity: 1,
+            visible_buffer: Self::create_visible_buffer(device, 1, instance_stride),
+            visible_capacity: 1,
+            args_buffer,
+            bind_group: None,
+            stride: instance_stride,
+            instance_count: 0,
+            batch_count: 0,
+        }
+    }
+
+    fn create_batch_buffers(device: &wgpu::Device, capacity: u32) -> (wgpu::Buffer, wgpu::Buffer) {
+        let batches = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Cull Batch Buffer"),
+            size: capacity as u64 * 8,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let args = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Cull Indirect Args Buffer"),
+            size: capacity as u64 * ARGS_SIZE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        (batches, args)
+    }
+
+    fn create_visible_buffer(device: &wgpu::Device, capacity: u32, stride: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Visible Instance Buffer"),
+            size: capacity as u64 * stride,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Upload this frame's rects and batches, reset the draw args and bind
+    /// `instance_buffer`, growing the pass's buffers as needed
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        instance_buffer: &wgpu::Buffer,
+        instance_count: u32,
+        batches: &[DrawBatch],
+        rects: &[ViewRect],
+        grow: f32,
+        shift: f32,
+        index_count: u32,
+    ) {
+        let batch_count = batches.len() as u32;
+        let mut rebind = self.bind_group.is_none();
+        if batch_count > self.batch_capacity {
+            self.batch_capacity = batch_count.next_power_of_two();
+            (self.batch_buffer, self.args_buffer) = Self::create_batch_buffers(device, self.batch_capacity);
+            rebind = true;
+        }
+        if instance_count > self.visible_capacity {
+            self.visible_capacity = instance_count.next_power_of_two();
+            self.visible_buffer = Self::create_visible_buffer(device, self.visible_capacity, self.stride);
+            rebind = true;
+        }
+        if rebind {
+            self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: self.params_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: instance_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 2, resource: self.batch_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 3, resource: self.visible_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 4, resource: self.args_buffer.as_entire_binding() },
+                ],
+                label: Some("cull_bind_group"),
+            }));
+        }
+
+        let params = CullParams::new(rects, batch_count, instance_count, grow, shift);
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+        let ranges: Vec<[u32; 2]> = batches.iter().map(|batch| [batch.first_instance, batch.instance_count]).collect();
+        queue.write_buffer(&self.batch_buffer, 0, bytemuck::cast_slice(&ranges));
+        let args: Vec<u8> = reset_args(batches.len(), index_count).iter().flat_map(|args| args.as_bytes().to_vec()).collect();
+        queue.write_buffer(&self.args_buffer, 0, &args);
+        self.instance_count = instance_count;
+        self.batch_count = batch_count;
+    }
+
+    /// Stop culling until the next `prepare`, which binds the instance
+    /// buffer again; call when it is replaced or culling moves to the CPU
+    pub fn clear(&mut self) {
+        self.bind_group = None;
+        self.batch_count = 0;
+    }
+
+    /// Whether the last `prepare` culls this frame's draws
+    pub fn active(&self) -> bool {
+        self.bind_group.is_some() && self.batch_count > 0
+    }
+
+    pub fn encode(&self, encoder: &mut wgpu::CommandEncoder) {
+        let bind_group = match self.bind_group.as_ref() {
+            Some(bind_group) if self.batch_count > 0 => bind_group,
+            _ => return,
+        };
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Cull Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, bind_group, &[]);
+        compute_pass.dispatch_workgroups(self.instance_count.div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    /// Draw the visible instances of batch `index` of the last `prepare`;
+    /// the caller binds the pipeline, material and vertex buffer 0
+    pub fn draw_batch(&self, render_pass: &mut wgpu::RenderPass<'_>, index: usize, batch: &DrawBatch) {
+        render_pass.set_vertex_buffer(1, self.visible_buffer.slice(batch.first_instance as u64 * self.stride..));
+        render_pass.draw_indexed_indirect(&self.args_buffer, index as u64 * ARGS_SIZE);
+    }
+}
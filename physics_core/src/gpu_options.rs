@@ -0,0 +1,129 @@
+//! Adapter selection options and adapter diagnostics
+//!
+//! Like render settings, the init options are set before `wgpu_init` /
+//! `wasm_init` and read by both init paths; changing them later only takes
+//! effect on the next init (or device rebuild). Backends cross the FFI as a
+//! bitmask of the `BACKEND_*` constants and power preference as an integer,
+//! so every host passes the same plain values.
+
+/// Backend bits for `GpuInitOptions::from_raw`; 0 selects every backend
+pub const BACKEND_VULKAN: u32 = 1 << 0;
+pub const BACKEND_METAL: u32 = 1 << 1;
+pub const BACKEND_DX12: u32 = 1 << 2;
+/// OpenGL ES / WebGL2
+pub const BACKEND_GL: u32 = 1 << 3;
+pub const BACKEND_BROWSER_WEBGPU: u32 = 1 << 4;
+
+pub const POWER_PREFERENCE_NONE: i32 = 0;
+pub const POWER_PREFERENCE_LOW_POWER: i32 = 1;
+pub const POWER_PREFERENCE_HIGH_PERFORMANCE: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuInitOptions {
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    /// Ask for a software adapter (e.g. SwiftShader, WARP)
+    pub force_fallback_adapter: bool,
+    /// Present in step with the display; off, frames are shown as soon as
+    /// they are ready where the surface allows it. Native surfaces only.
+    pub vsync: bool,
+}
+
+impl Default for GpuInitOptions {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            vsync: true,
+        }
+    }
+}
+
+impl GpuInitOptions {
+    /// Unknown backend bits are ignored; an unknown power preference keeps the default
+    pub fn from_raw(backends: u32, power_preference: i32, force_fallback_adapter: bool) -> Self {
+        Self {
+            backends: backends_from_bits(backends),
+            power_preference: power_preference_from_raw(power_preference),
+            force_fallback_adapter,
+            vsync: true,
+        }
+    }
+}
+
+pub fn backends_from_bits(bits: u32) -> wgpu::Backends {
+    let mut backends = wgpu::Backends::empty();
+    for (bit, backend) in [
+        (BACKEND_VULKAN, wgpu::Backends::VULKAN),
+        (BACKEND_METAL, wgpu::Backends::METAL),
+        (BACKEND_DX12, wgpu::Backends::DX12),
+        (BACKEND_GL, wgpu::Backends::GL),
+        (BACKEND_BROWSER_WEBGPU, wgpu::Backends::BROWSER_WEBGPU),
+    ] {
+        if bits & bit != 0 {
+            backends |= backend;
+        }
+    }
+    if backends.is_empty() {
+        wgpu::Backends::all()
+    } else {
+        backends
+    }
+}
+
+pub fn power_preference_from_raw(value: i32) -> wgpu::PowerPreference {
+    match value {
+        POWER_PREFERENCE_NONE => wgpu::PowerPreference::None,
+        POWER_PREFERENCE_LOW_POWER => wgpu::PowerPreference::LowPower,
+        _ => wgpu::PowerPreference::HighPerformance,
+    }
+}
+
+/// Adapter name, backend, surface formats and the device limits that matter
+/// to the renderer, as a JSON object
+pub fn adapter_info_json(
+    info: &wgpu::AdapterInfo,
+    limits: &wgpu::Limits,
+    surface_formats: &[wgpu::TextureFormat],
+    surface_format: wgpu::TextureFormat,
+    caps: &crate::gpu_caps::GpuCaps,
+) -> String {
+    let formats: Vec<String> = surface_formats.iter().map(|f| format!("{:?}", f)).collect();
+    serde_json::json!({
+        "name": info.name,
+        "vendor": info.vendor,
+        "device": info.device,
+        "deviceType": format!("{:?}", info.device_type),
+        "driver": info.driver,
+        "driverInfo": info.driver_info,
+        "backend": format!("{:?}", info.backend),
+        "surfaceFormats": formats,
+        "surfaceFormat": format!("{:?}", surface_format),
+        "caps": {
+            "computeShaders": caps.compute_shaders,
+            "storageBuffers": caps.storage_buffers,
+            "bloom": caps.bloom,
+            "gpuCulling": caps.gpu_culling,
+            "textureCompression": {
+                "astc": caps.texture_compression.astc,
+                "etc2": caps.texture_compression.etc2,
+                "bc": caps.texture_compression.bc,
+            },
+        },
+        "limits": {
+            "maxTextureDimension2d": limits.max_texture_dimension_2d,
+            "maxBindGroups": limits.max_bind_groups,
+            "maxVertexBuffers": limits.max_vertex_buffers,
+            "maxVertexAttributes": limits.max_vertex_attributes,
+            "maxBufferSize": limits.max_buffer_size,
+            "maxUniformBufferBindingSize": limits.max_uniform_buffer_binding_size,
+            "maxStorageBuffersPerShaderStage": limits.max_storage_buffers_per_shader_stage,
+            "maxStorageBufferBindingSize": limits.max_storage_buffer_binding_size,
+            "maxComputeWorkgroupSizeX": limits.max_compute_workgroup_size_x,
+            "maxComputeInvocationsPerWorkgroup": limits.max_compute_invocations_per_workgroup,
+            "maxComputeWorkgroupsPerDimension": limits.max_compute_workgroups_per_dimension,
+        },
+    })
+    .to_string()
+}
@@ -0,0 +1,248 @@
+//! GPU pass timing and frame statistics
+//!
+//! Every render-graph pass is wrapped in a debug group named after it, which
+//! is what RenderDoc and Xcode show in a capture. When the adapter supports
+//! timestamps inside command encoders, the same boundaries also get a
+//! timestamp pair, so the in-app profiler and a capture agree on where frame
+//! time goes. Results come back through a small ring of readback buffers one
+//! or two frames later; reading them never stalls the GPU. Adapters without
+//! encoder timestamps (Apple GPUs, WebGPU, WebGL) report CPU times only.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+/// Passes timed per frame; extra passes are still drawn, just not timed
+pub const MAX_TIMED_PASSES: usize = 8;
+/// Frames that can be in flight before timing skips a frame
+const READBACK_SLOTS: usize = 3;
+/// Weight of a new sample in the displayed averages
+const SMOOTHING: f32 = 0.1;
+
+const SLOT_FREE: u8 = 0;
+const SLOT_PENDING: u8 = 1;
+const SLOT_READY: u8 = 2;
+
+/// Features to request for pass timing, or none if the adapter lacks either
+pub fn timestamp_features(adapter_features: wgpu::Features) -> wgpu::Features {
+    let wanted = wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+    if adapter_features.contains(wanted) {
+        wanted
+    } else {
+        wgpu::Features::empty()
+    }
+}
+
+/// Milliseconds for each (begin, end) tick pair. A pair that runs backwards
+/// (counter reset, pass split across queues) reads as 0.
+pub fn ticks_to_ms(ticks: &[u64], period_ns: f32) -> Vec<f32> {
+    ticks
+        .chunks_exact(2)
+        .map(|pair| pair[1].saturating_sub(pair[0]) as f32 * period_ns / 1_000_000.0)
+        .collect()
+}
+
+fn smooth(previous: f32, sample: f32) -> f32 {
+    if previous == 0.0 {
+        sample
+    } else {
+        previous + (sample - previous) * SMOOTHING
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PassTiming {
+    pub pass: &'static str,
+    pub ms: f32,
+}
+
+/// Smoothed frame timings, returned as JSON by the stats API
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameStats {
+    pub fps: f32,
+    /// CPU time spent recording and submitting a frame
+    pub cpu_frame_ms: f32,
+    /// False when the adapter cannot time passes; `gpu_passes` is then empty
+    pub gpu_timing: bool,
+    pub gpu_passes: Vec<PassTiming>,
+    pub gpu_total_ms: f32,
+    /// Body instances drawn last frame, and left out by culling
+    pub drawn_instances: u32,
+    pub culled_instances: u32,
+}
+
+impl FrameStats {
+    pub fn record_cpu_frame(&mut self, ms: f32) {
+        self.cpu_frame_ms = smooth(self.cpu_frame_ms, ms);
+    }
+
+    /// Fold in one frame of measured passes. Passes missing from this frame
+    /// (e.g. bloom switched off) are dropped rather than left stale.
+    pub fn record_gpu_passes(&mut self, passes: &[(&'static str, f32)]) {
+        let previous = std::mem::take(&mut self.gpu_passes);
+        self.gpu_passes = passes
+            .iter()
+            .map(|&(pass, ms)| {
+                let old = previous.iter().find(|t| t.pass == pass).map_or(0.0, |t| t.ms);
+                PassTiming { pass, ms: smooth(old, ms) }
+            })
+            .collect();
+        self.gpu_total_ms = self.gpu_passes.iter().map(|t| t.ms).sum();
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+struct ReadbackSlot {
+    buffer: wgpu::Buffer,
+    passes: Vec<&'static str>,
+    state: Arc<AtomicU8>,
+}
+
+/// Timestamp queries around render-graph passes
+pub(crate) struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    slots: Vec<ReadbackSlot>,
+    next_slot: usize,
+    period_ns: f32,
+    /// Slot receiving the frame being recorded; None when the frame is untimed
+    recording: Option<usize>,
+    frame_passes: Vec<&'static str>,
+    pass_open: bool,
+}
+
+impl GpuTimer {
+    /// None unless the device was created with `timestamp_features`
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        let wanted = wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+        if !device.features().contains(wanted) {
+            return None;
+        }
+
+        let query_count = (MAX_TIMED_PASSES * 2) as u32;
+        let size = query_count as u64 * wgpu::QUERY_SIZE as u64;
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Pass Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: query_count,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let slots = (0..READBACK_SLOTS)
+            .map(|_| ReadbackSlot {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Timestamp Readback Buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                passes: Vec::new(),
+                state: Arc::new(AtomicU8::new(SLOT_FREE)),
+            })
+            .collect();
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            slots,
+            next_slot: 0,
+            period_ns: queue.get_timestamp_period(),
+            recording: None,
+            frame_passes: Vec::new(),
+            pass_open: false,
+        })
+    }
+
+    /// Pick a free readback slot; if all are still in flight the frame goes untimed
+    pub fn begin_frame(&mut self) {
+        self.frame_passes.clear();
+        self.pass_open = false;
+        let slot = self.next_slot;
+        self.recording = (self.slots[slot].state.load(Ordering::Acquire) == SLOT_FREE).then_some(slot);
+        if self.recording.is_some() {
+            self.next_slot = (slot + 1) % self.slots.len();
+        }
+    }
+
+    pub fn begin_pass(&mut self, encoder: &mut wgpu::CommandEncoder, pass: &'static str) {
+        if self.recording.is_none() || self.frame_passes.len() >= MAX_TIMED_PASSES {
+            return;
+        }
+        encoder.write_timestamp(&self.query_set, (self.frame_passes.len() * 2) as u32);
+        self.frame_passes.push(pass);
+        self.pass_open = true;
+    }
+
+    pub fn end_pass(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.pass_open {
+            encoder.write_timestamp(&self.query_set, (self.frame_passes.len() * 2 - 1) as u32);
+            self.pass_open = false;
+        }
+    }
+
+    /// Copy this frame's timestamps to its readback slot; call before `finish`
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(slot) = self.recording else {
+            return;
+        };
+        let query_count = (self.frame_passes.len() * 2) as u32;
+        if query_count == 0 {
+            self.recording = None;
+            return;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..query_count, &self.resolve_buffer, 0);
+        let slot = &mut self.slots[slot];
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &slot.buffer,
+            0,
+            query_count as u64 * wgpu::QUERY_SIZE as u64,
+        );
+        slot.passes = self.frame_passes.clone();
+    }
+
+    /// Start mapping this frame's readback slot; call after `submit`
+    pub fn after_submit(&mut self) {
+        let Some(slot) = self.recording.take() else {
+            return;
+        };
+        let slot = &self.slots[slot];
+        slot.state.store(SLOT_PENDING, Ordering::Release);
+        let state = slot.state.clone();
+        slot.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            state.store(if result.is_ok() { SLOT_READY } else { SLOT_FREE }, Ordering::Release);
+        });
+    }
+
+    /// Timings of the newest frame whose results have arrived
+    pub fn collect(&mut self, device: &wgpu::Device) -> Option<Vec<(&'static str, f32)>> {
+        let _ = device.poll(wgpu::PollType::Poll);
+        let mut latest = None;
+        // Oldest first, so the newest ready frame wins
+        for offset in 0..self.slots.len() {
+            let slot = &mut self.slots[(self.next_slot + offset) % READBACK_SLOTS];
+            if slot.state.load(Ordering::Acquire) != SLOT_READY {
+                continue;
+            }
+            let ticks: Vec<u64> = {
+                let data = slot.buffer.slice(..).get_mapped_range();
+                bytemuck::pod_collect_to_vec(&data[..slot.passes.len() * 2 * wgpu::QUERY_SIZE as usize])
+            };
+            slot.buffer.unmap();
+            slot.state.store(SLOT_FREE, Ordering::Release);
+            let ms = ticks_to_ms(&ticks, self.period_ns);
+            latest = Some(slot.passes.iter().copied().zip(ms).collect());
+        }
+        latest
+    }
+}
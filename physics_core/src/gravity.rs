@@ -0,0 +1,64 @@
+//! Named gravity settings
+//!
+//! Presets are plain gravity vectors; choosing one is the same as setting
+//! that vector, and any vector close enough to a preset shows as it in the
+//! panel. All point down -Y in m/s², whatever the world scale.
+
+use serde::Deserialize;
+
+use crate::units::DEFAULT_GRAVITY;
+
+/// Surface gravity of the Moon, m/s²
+const MOON_GRAVITY: f32 = 1.62;
+
+/// How far a vector may be from a preset and still count as it, m/s²
+const MATCH_TOLERANCE: f32 = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GravityPreset {
+    /// 9.81 m/s², the default
+    Earth,
+    /// 1.62 m/s²
+    Moon,
+    /// No gravity at all
+    ZeroG,
+}
+
+impl GravityPreset {
+    pub const ALL: [Self; 3] = [Self::Earth, Self::Moon, Self::ZeroG];
+
+    /// FFI code: 0 = Earth, 1 = Moon, 2 = zero-g
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Earth),
+            1 => Some(Self::Moon),
+            2 => Some(Self::ZeroG),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Earth => "Earth",
+            Self::Moon => "Moon",
+            Self::ZeroG => "Zero-g",
+        }
+    }
+
+    /// Gravity vector (x, y, z) in m/s²
+    pub fn gravity(&self) -> [f32; 3] {
+        match self {
+            Self::Earth => [0.0, -DEFAULT_GRAVITY, 0.0],
+            Self::Moon => [0.0, -MOON_GRAVITY, 0.0],
+            Self::ZeroG => [0.0; 3],
+        }
+    }
+
+    /// The preset `gravity` is, if any
+    pub fn matching(gravity: [f32; 3]) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| {
+            preset.gravity().iter().zip(gravity).all(|(a, b)| (a - b).abs() <= MATCH_TOLERANCE)
+        })
+    }
+}
@@ -0,0 +1,61 @@
+//! Group tags for managing many bodies at once
+//!
+//! Every body carries a `Groups` bitmask: bit `n` set means it belongs to
+//! group `n`, so a body can sit in several groups (say "debris" and "wave 3").
+//! Bulk operations and filtered queries take a mask and act on each body that
+//! shares at least one bit with it; `ALL` matches every body, including ones
+//! taken out of every group. New bodies start in `DEFAULT`.
+//!
+//! Pausing a group freezes its dynamic bodies where they are. They become
+//! zero-velocity kinematic bodies, so the rest of the scene still collides
+//! with them, and their velocity is kept in `Paused` until they resume.
+
+use bevy_ecs::prelude::*;
+use crate::rapier::prelude::*;
+
+/// Group of newly spawned bodies
+pub const DEFAULT: u32 = 1;
+/// Mask matching every body
+pub const ALL: u32 = u32::MAX;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Groups(pub u32);
+
+impl Default for Groups {
+    fn default() -> Self {
+        Self(DEFAULT)
+    }
+}
+
+impl Groups {
+    pub fn matches(self, mask: u32) -> bool {
+        mask == ALL || self.0 & mask != 0
+    }
+}
+
+/// Velocity of a paused body, restored when it resumes
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Paused {
+    pub linvel: Vector<Real>,
+    pub angvel: Vector<Real>,
+}
+
+/// Freeze a dynamic body. Returns None for bodies that are not dynamic,
+/// which includes ones already paused.
+pub fn pause(rb: &mut RigidBody) -> Option<Paused> {
+    if !rb.is_dynamic() {
+        return None;
+    }
+    let paused = Paused { linvel: *rb.linvel(), angvel: *rb.angvel() };
+    rb.set_body_type(RigidBodyType::KinematicVelocityBased, true);
+    rb.set_linvel(Vector::zeros(), false);
+    rb.set_angvel(Vector::zeros(), false);
+    Some(paused)
+}
+
+/// Make a paused body dynamic again with the velocity it had when paused
+pub fn resume(rb: &mut RigidBody, paused: Paused) {
+    rb.set_body_type(RigidBodyType::Dynamic, true);
+    rb.set_linvel(paused.linvel, true);
+    rb.set_angvel(paused.angvel, true);
+}
@@ -0,0 +1,84 @@
+//! Parent/child attachment of entities
+//!
+//! An `Attached` entity follows its parent at a fixed offset. Every frame the
+//! transform propagation system composes the parent's world pose with the
+//! offset, writes the result to the child's `Position2D` and `Rotation`, and
+//! the renderer draws the child there. Parents may themselves be attached;
+//! chains are followed up to `MAX_DEPTH` links.
+//!
+//! Children are either decorations (quads with no body, for sprites riding
+//! on a body) or physics bodies. Body children turn kinematic while attached,
+//! so the solver carries them along with their parent and they still push
+//! other bodies. When a parent disappears, decorations hanging from it are
+//! despawned and body children are released.
+
+use bevy_ecs::prelude::*;
+
+/// Longest parent chain that is followed
+pub const MAX_DEPTH: usize = 16;
+
+/// Position and rotation (radians, counter-clockwise) in the z = 0 plane
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Pose2D {
+    pub translation: [f32; 2],
+    pub rotation: f32,
+}
+
+impl Pose2D {
+    /// `local` expressed in the frame of `self`
+    pub fn then(self, local: Pose2D) -> Pose2D {
+        let (sin, cos) = self.rotation.sin_cos();
+        let [x, y] = local.translation;
+        Pose2D {
+            translation: [self.translation[0] + x * cos - y * sin, self.translation[1] + x * sin + y * cos],
+            rotation: self.rotation + local.rotation,
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Attached {
+    pub parent: Entity,
+    pub offset: Pose2D,
+    /// The child was a dynamic body before it was attached
+    pub restore_dynamic: bool,
+}
+
+/// Drawn entity without a rigid body
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Decoration;
+
+/// World pose of `entity`. `link` gives an attached entity's parent and
+/// offset; `root_pose` gives the pose of an entity that is not attached.
+/// None when a parent is missing or the chain is longer than `MAX_DEPTH`.
+pub fn resolve<E: Copy>(
+    entity: E,
+    link: impl Fn(E) -> Option<(E, Pose2D)>,
+    root_pose: impl Fn(E) -> Option<Pose2D>,
+) -> Option<Pose2D> {
+    let mut offsets = Vec::new();
+    let mut current = entity;
+    while let Some((parent, offset)) = link(current) {
+        if offsets.len() == MAX_DEPTH {
+            return None;
+        }
+        offsets.push(offset);
+        current = parent;
+    }
+    let root = root_pose(current)?;
+    Some(offsets.into_iter().rev().fold(root, Pose2D::then))
+}
+
+/// Whether attaching `child` to `parent` would close a loop or make the
+/// chain too long to follow
+pub fn creates_cycle<E: Copy + PartialEq>(child: E, parent: E, parent_of: impl Fn(E) -> Option<E>) -> bool {
+    let mut current = Some(parent);
+    for _ in 0..=MAX_DEPTH {
+        match current {
+            Some(entity) if entity == child => return true,
+            Some(entity) => current = parent_of(entity),
+            None => return false,
+        }
+    }
+    true
+}
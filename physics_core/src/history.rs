@@ -0,0 +1,181 @@
+//! Undo and redo of edits to the running scene
+//!
+//! Edits made through the editing calls (the `edit_*` bindings and the
+//! desktop scene editor) are recorded as reversible steps: spawning and
+//! removing boxes, changing a box's properties and attaching springs.
+//! Everything else a game does, including the plain spawn and remove calls,
+//! is not recorded, so gameplay never fills the history of a host that only
+//! edits now and then.
+//!
+//! Undoing a removal spawns the box again under a new id, and redoing a
+//! spring attaches a new one; the history renames the old id in every step
+//! so later steps still find what they refer to. Springs attached to a box
+//! do not come back with it. Switching scenes or resetting clears the
+//! history, since the bodies it refers to are gone.
+//!
+//! Changes to the same body can merge into the step before, so dragging a
+//! slider is one step rather than one per frame; a change merging into the
+//! spawn of the same body makes placing and positioning one step too.
+
+use serde::Serialize;
+
+/// Steps kept for undo; the oldest are dropped first
+pub const MAX_STEPS: usize = 256;
+
+/// A spring from a body's center to a world point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpringEdit {
+    pub entity: u64,
+    pub point: [f32; 2],
+    pub stiffness: f32,
+    pub damping: f32,
+}
+
+/// One reversible step; `B` describes a whole body
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit<B> {
+    Spawn { entity: u64, body: B },
+    Remove { entity: u64, body: B },
+    Change { entity: u64, before: B, after: B },
+    Spring { spring: u64, desc: SpringEdit },
+}
+
+impl<B> Edit<B> {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Spawn { .. } => "spawn",
+            Self::Remove { .. } => "remove",
+            Self::Change { .. } => "change",
+            Self::Spring { .. } => "spring",
+        }
+    }
+
+    /// Refer to body `new` wherever this step referred to `old`
+    pub fn rename_entity(&mut self, old: u64, new: u64) {
+        let entity = match self {
+            Self::Spawn { entity, .. } | Self::Remove { entity, .. } | Self::Change { entity, .. } => entity,
+            Self::Spring { desc, .. } => &mut desc.entity,
+        };
+        if *entity == old {
+            *entity = new;
+        }
+    }
+
+    pub fn rename_spring(&mut self, old: u64, new: u64) {
+        if let Self::Spring { spring, .. } = self {
+            if *spring == old {
+                *spring = new;
+            }
+        }
+    }
+}
+
+/// How many steps can be undone and redone, and what the next ones are
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryState {
+    pub undo: usize,
+    pub redo: usize,
+    pub undo_label: Option<&'static str>,
+    pub redo_label: Option<&'static str>,
+}
+
+impl HistoryState {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct History<B> {
+    undo: Vec<Edit<B>>,
+    redo: Vec<Edit<B>>,
+}
+
+impl<B> Default for History<B> {
+    fn default() -> Self {
+        Self { undo: Vec::new(), redo: Vec::new() }
+    }
+}
+
+impl<B: Clone> History<B> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step that was just carried out, dropping everything that could
+    /// be redone. With `merge`, a change to the same body as the last step
+    /// updates that step instead.
+    pub fn record(&mut self, edit: Edit<B>, merge: bool) {
+        self.redo.clear();
+        if merge {
+            if let Edit::Change { entity, after, .. } = &edit {
+                match self.undo.last_mut() {
+                    Some(Edit::Change { entity: last, after: last_after, .. }) if last == entity => {
+                        *last_after = after.clone();
+                        return;
+                    }
+                    Some(Edit::Spawn { entity: last, body }) if last == entity => {
+                        *body = after.clone();
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.push_undo(edit);
+    }
+
+    /// The step to reverse next; hand it to `undone` once reversed
+    pub fn take_undo(&mut self) -> Option<Edit<B>> {
+        self.undo.pop()
+    }
+
+    /// `edit` was reversed and can be redone
+    pub fn undone(&mut self, edit: Edit<B>) {
+        self.redo.push(edit);
+    }
+
+    /// The step to carry out again next; hand it to `redone` once done
+    pub fn take_redo(&mut self) -> Option<Edit<B>> {
+        self.redo.pop()
+    }
+
+    /// `edit` was carried out again and can be undone
+    pub fn redone(&mut self, edit: Edit<B>) {
+        self.push_undo(edit);
+    }
+
+    fn push_undo(&mut self, edit: Edit<B>) {
+        if self.undo.len() == MAX_STEPS {
+            self.undo.remove(0);
+        }
+        self.undo.push(edit);
+    }
+
+    pub fn rename_entity(&mut self, old: u64, new: u64) {
+        for edit in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            edit.rename_entity(old, new);
+        }
+    }
+
+    pub fn rename_spring(&mut self, old: u64, new: u64) {
+        for edit in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            edit.rename_spring(old, new);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn state(&self) -> HistoryState {
+        HistoryState {
+            undo: self.undo.len(),
+            redo: self.redo.len(),
+            undo_label: self.undo.last().map(Edit::label),
+            redo_label: self.redo.last().map(Edit::label),
+        }
+    }
+}
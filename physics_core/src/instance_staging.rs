@@ -0,0 +1,162 @@
+//! Persistent CPU mirror of the GPU instance buffer
+//!
+//! Each key (a rigid body or entity) owns a fixed slot. Per-frame updates only touch the
+//! slots that changed and the upload is a list of dirty ranges instead of the
+//! whole buffer. A structural change (bodies added/removed, materials changed)
+//! rebuilds the slot layout and falls back to a full upload.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+/// Dirty slots closer than this are merged into one write to save calls
+const MERGE_GAP: usize = 4;
+
+/// When more than this fraction of slots is dirty, upload everything instead
+const FULL_UPLOAD_FRACTION: f32 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Upload {
+    /// Nothing changed since the last upload
+    None,
+    /// Write the whole staging array
+    Full,
+    /// Write only these slot ranges, sorted and non-overlapping
+    Ranges(Vec<Range<usize>>),
+}
+
+#[derive(Debug, Clone)]
+pub struct InstanceStaging<K, T> {
+    slots: HashMap<K, usize>,
+    data: Vec<T>,
+    dirty: Vec<bool>,
+    dirty_count: usize,
+    full: bool,
+}
+
+impl<K: Hash + Eq + Copy, T: Copy + PartialEq> Default for InstanceStaging<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq + Copy, T: Copy + PartialEq> InstanceStaging<K, T> {
+    pub fn new() -> Self {
+        Self {
+            slots: HashMap::new(),
+            data: Vec::new(),
+            dirty: Vec::new(),
+            dirty_count: 0,
+            full: true,
+        }
+    }
+
+    /// Replace every slot; items keep the order given (already batched by material)
+    pub fn rebuild(&mut self, items: impl IntoIterator<Item = (K, T)>) {
+        self.slots.clear();
+        self.data.clear();
+        for (key, value) in items {
+            self.slots.insert(key, self.data.len());
+            self.data.push(value);
+        }
+        self.dirty = vec![false; self.data.len()];
+        self.dirty_count = 0;
+        self.full = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn slot(&self, key: K) -> Option<usize> {
+        self.slots.get(&key).copied()
+    }
+
+    /// Modify the value for `key` in place; the slot is marked dirty only if
+    /// the value actually changed. Returns false for unknown keys.
+    pub fn update(&mut self, key: K, f: impl FnOnce(&mut T)) -> bool {
+        let slot = match self.slots.get(&key) {
+            Some(&slot) => slot,
+            None => return false,
+        };
+        let before = self.data[slot];
+        f(&mut self.data[slot]);
+        if self.data[slot] != before && !self.dirty[slot] {
+            self.dirty[slot] = true;
+            self.dirty_count += 1;
+        }
+        true
+    }
+
+    /// Modify every value in place, marking the slots that changed dirty
+    pub fn update_each(&mut self, mut f: impl FnMut(&mut T)) {
+        for (slot, value) in self.data.iter_mut().enumerate() {
+            let before = *value;
+            f(value);
+            if *value != before && !self.dirty[slot] {
+                self.dirty[slot] = true;
+                self.dirty_count += 1;
+            }
+        }
+    }
+
+    /// Force the next upload to write everything (e.g. the GPU buffer was recreated)
+    pub fn mark_all_dirty(&mut self) {
+        self.full = true;
+    }
+
+    /// What needs to be written since the last call, then clear the dirty state
+    pub fn take_upload(&mut self) -> Upload {
+        self.take_upload_drawn(|_| true)
+    }
+
+    /// Like `take_upload`, but only slots for which `drawn` is true are
+    /// written; the other dirty slots stay dirty until a later upload draws
+    /// them. A full upload still writes and clears everything.
+    pub fn take_upload_drawn(&mut self, drawn: impl Fn(usize) -> bool) -> Upload {
+        let mostly_dirty = self.dirty_count as f32 > self.data.len() as f32 * FULL_UPLOAD_FRACTION;
+        let upload = if self.full || mostly_dirty {
+            self.dirty.iter_mut().for_each(|d| *d = false);
+            self.dirty_count = 0;
+            Upload::Full
+        } else if self.dirty_count == 0 {
+            Upload::None
+        } else {
+            let ranges = self.dirty_ranges(&drawn);
+            for range in &ranges {
+                for slot in range.clone() {
+                    if std::mem::take(&mut self.dirty[slot]) {
+                        self.dirty_count -= 1;
+                    }
+                }
+            }
+            if ranges.is_empty() {
+                Upload::None
+            } else {
+                Upload::Ranges(ranges)
+            }
+        };
+
+        self.full = false;
+        upload
+    }
+
+    fn dirty_ranges(&self, drawn: &impl Fn(usize) -> bool) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (slot, _) in self.dirty.iter().enumerate().filter(|&(slot, &d)| d && drawn(slot)) {
+            match ranges.last_mut() {
+                Some(last) if slot <= last.end + MERGE_GAP => last.end = slot + 1,
+                _ => ranges.push(slot..slot + 1),
+            }
+        }
+        ranges
+    }
+}
@@ -0,0 +1,443 @@
+//! Minimal KTX2 reading and block-compressed texture decoding
+//!
+//! Mobile GPUs sample ASTC and ETC2 textures directly and desktop GPUs BCn,
+//! at a quarter to an eighth of the memory of RGBA8. Textures ship as KTX2
+//! files, one per format family, named after the PNG they replace:
+//! "textures/ball.astc.ktx2", "textures/ball.etc2.ktx2" and
+//! "textures/ball.bc.ktx2" next to "textures/ball.png". `variant_names`
+//! lists the files worth trying on a device, best first, ending with the
+//! PNG, so each platform loads the best format it has and anything else
+//! still works.
+//!
+//! `parse` reads a KTX2 file holding one 2D image with its mip levels, in
+//! RGBA8, BC1, BC3, BC7, ETC2 RGB8, ETC2 RGBA8 or ASTC 4x4. Supercompressed
+//! files (Basis Universal, Zstandard), arrays, cube maps and 3D textures are
+//! not supported. A device lacking the file's format gets the first level
+//! decoded to RGBA8 by `decode`, which handles every format but BC7 and
+//! ASTC; files in those are only used where the GPU samples them.
+
+/// Most pixels in the first level of one texture
+pub const MAX_PIXELS: u64 = 1 << 24;
+
+const IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const HEADER_LEN: usize = 80;
+const LEVEL_INDEX_ENTRY_LEN: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureFormat {
+    Rgba8,
+    Bc1,
+    Bc3,
+    Bc7,
+    Etc2Rgb8,
+    Etc2Rgba8,
+    Astc4x4,
+}
+
+/// Formats a GPU either samples all of or none of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressionFamily {
+    Astc,
+    Etc2,
+    Bc,
+}
+
+impl CompressionFamily {
+    /// Middle part of the variant file names, as in "ball.astc.ktx2"
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Astc => "astc",
+            Self::Etc2 => "etc2",
+            Self::Bc => "bc",
+        }
+    }
+}
+
+impl TextureFormat {
+    /// The format and whether it is sRGB, for a Vulkan format number
+    pub fn from_vk_format(vk_format: u32) -> Option<(Self, bool)> {
+        Some(match vk_format {
+            37 => (Self::Rgba8, false),
+            43 => (Self::Rgba8, true),
+            // BC1 without alpha decodes the same as with it
+            131 | 133 => (Self::Bc1, false),
+            132 | 134 => (Self::Bc1, true),
+            137 => (Self::Bc3, false),
+            138 => (Self::Bc3, true),
+            145 => (Self::Bc7, false),
+            146 => (Self::Bc7, true),
+            147 => (Self::Etc2Rgb8, false),
+            148 => (Self::Etc2Rgb8, true),
+            151 => (Self::Etc2Rgba8, false),
+            152 => (Self::Etc2Rgba8, true),
+            157 => (Self::Astc4x4, false),
+            158 => (Self::Astc4x4, true),
+            _ => return None,
+        })
+    }
+
+    pub fn family(self) -> Option<CompressionFamily> {
+        match self {
+            Self::Rgba8 => None,
+            Self::Bc1 | Self::Bc3 | Self::Bc7 => Some(CompressionFamily::Bc),
+            Self::Etc2Rgb8 | Self::Etc2Rgba8 => Some(CompressionFamily::Etc2),
+            Self::Astc4x4 => Some(CompressionFamily::Astc),
+        }
+    }
+
+    /// Width and height of a block in pixels, 1 for uncompressed formats
+    pub fn block_dim(self) -> u32 {
+        match self {
+            Self::Rgba8 => 1,
+            _ => 4,
+        }
+    }
+
+    pub fn block_bytes(self) -> usize {
+        match self {
+            Self::Rgba8 => 4,
+            Self::Bc1 | Self::Etc2Rgb8 => 8,
+            Self::Bc3 | Self::Bc7 | Self::Etc2Rgba8 | Self::Astc4x4 => 16,
+        }
+    }
+
+    /// Bytes of a `width` x `height` image
+    pub fn image_bytes(self, width: u32, height: u32) -> usize {
+        let dim = self.block_dim();
+        width.div_ceil(dim) as usize * height.div_ceil(dim) as usize * self.block_bytes()
+    }
+
+    /// `decode` handles the format
+    pub fn decodable(self) -> bool {
+        !matches!(self, Self::Bc7 | Self::Astc4x4)
+    }
+}
+
+/// A texture as stored, largest level first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Texture {
+    pub format: TextureFormat,
+    pub srgb: bool,
+    pub width: u32,
+    pub height: u32,
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl Texture {
+    /// Size of mip level `level`
+    pub fn level_size(&self, level: usize) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    /// The first level as RGBA8, rows top to bottom
+    pub fn decode(&self) -> Result<Vec<u8>, String> {
+        let first = self.levels.first().ok_or("texture has no levels")?;
+        decode(self.format, self.width, self.height, first)
+    }
+}
+
+/// Compressed formats a device samples
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextureCompression {
+    pub astc: bool,
+    pub etc2: bool,
+    pub bc: bool,
+}
+
+impl TextureCompression {
+    pub fn supports(self, format: TextureFormat) -> bool {
+        match format.family() {
+            None => true,
+            Some(family) => self.supports_family(family),
+        }
+    }
+
+    pub fn supports_family(self, family: CompressionFamily) -> bool {
+        match family {
+            CompressionFamily::Astc => self.astc,
+            CompressionFamily::Etc2 => self.etc2,
+            CompressionFamily::Bc => self.bc,
+        }
+    }
+
+    /// Supported families, best first: ASTC keeps the most detail per bit,
+    /// BC7 is close behind and ETC2 trails both
+    pub fn preferred(self) -> Vec<CompressionFamily> {
+        [CompressionFamily::Astc, CompressionFamily::Bc, CompressionFamily::Etc2]
+            .into_iter()
+            .filter(|&family| self.supports_family(family))
+            .collect()
+    }
+}
+
+pub fn is_ktx2(bytes: &[u8]) -> bool {
+    bytes.starts_with(&IDENTIFIER)
+}
+
+/// Asset names to try for texture `name`, best first: a compressed variant
+/// per family the device samples, then `name` itself. Names that are KTX2
+/// files already, or not PNGs, are only tried as given.
+pub fn variant_names(name: &str, compression: TextureCompression) -> Vec<String> {
+    let stem = match name.len().checked_sub(4).filter(|&at| name.is_char_boundary(at)) {
+        Some(at) if name[at..].eq_ignore_ascii_case(".png") => &name[..at],
+        _ => return vec![name.to_string()],
+    };
+    let mut names: Vec<String> =
+        compression.preferred().into_iter().map(|family| format!("{}.{}.ktx2", stem, family.name())).collect();
+    names.push(name.to_string());
+    names
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from(u32_at(bytes, at)) | (u64::from(u32_at(bytes, at + 4)) << 32)
+}
+
+/// Read a KTX2 file
+pub fn parse(bytes: &[u8]) -> Result<Texture, String> {
+    if !is_ktx2(bytes) {
+        return Err("not a KTX2 file".into());
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err("truncated KTX2 header".into());
+    }
+    let vk_format = u32_at(bytes, 12);
+    let (width, height, depth) = (u32_at(bytes, 20), u32_at(bytes, 24), u32_at(bytes, 28));
+    let (layers, faces, level_count) = (u32_at(bytes, 32), u32_at(bytes, 36), u32_at(bytes, 40).max(1));
+    let supercompression = u32_at(bytes, 44);
+    let (format, srgb) = TextureFormat::from_vk_format(vk_format)
+        .ok_or_else(|| format!("unsupported format {} (supercompressed or not a color format)", vk_format))?;
+    if supercompression != 0 {
+        return Err(format!("unsupported supercompression scheme {}", supercompression));
+    }
+    if width == 0 || height == 0 || depth != 0 || layers > 1 || faces != 1 {
+        return Err("only single 2D images are supported".into());
+    }
+    if u64::from(width) * u64::from(height) > MAX_PIXELS {
+        return Err(format!("{}x{} is larger than {} pixels", width, height, MAX_PIXELS));
+    }
+    if level_count > 32 - width.max(height).leading_zeros() {
+        return Err(format!("{} mip levels for a {}x{} image", level_count, width, height));
+    }
+    let mut texture = Texture { format, srgb, width, height, levels: Vec::with_capacity(level_count as usize) };
+    for level in 0..level_count as usize {
+        let entry = HEADER_LEN + level * LEVEL_INDEX_ENTRY_LEN;
+        if bytes.len() < entry + LEVEL_INDEX_ENTRY_LEN {
+            return Err("truncated level index".into());
+        }
+        let (offset, len) = (u64_at(bytes, entry), u64_at(bytes, entry + 8));
+        let (level_width, level_height) = texture.level_size(level);
+        let expected = format.image_bytes(level_width, level_height);
+        if len != expected as u64 {
+            return Err(format!("level {} has {} bytes, expected {}", level, len, expected));
+        }
+        let data = usize::try_from(offset)
+            .ok()
+            .and_then(|offset| bytes.get(offset..offset.checked_add(expected)?))
+            .ok_or_else(|| format!("level {} is outside the file", level))?;
+        texture.levels.push(data.to_vec());
+    }
+    Ok(texture)
+}
+
+/// Decode a `width` x `height` image in `format` to RGBA8, rows top to bottom
+pub fn decode(format: TextureFormat, width: u32, height: u32, data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < format.image_bytes(width, height) {
+        return Err(format!("{}x{} {:?} image needs {} bytes", width, height, format, format.image_bytes(width, height)));
+    }
+    let decode_block: fn(&[u8]) -> [[u8; 4]; 16] = match format {
+        TextureFormat::Rgba8 => return Ok(data[..format.image_bytes(width, height)].to_vec()),
+        TextureFormat::Bc1 => |block| bc1_colors(block, true),
+        TextureFormat::Bc3 => |block| with_alpha(bc1_colors(&block[8..], false), bc3_alpha(block)),
+        TextureFormat::Etc2Rgb8 => etc2_colors,
+        TextureFormat::Etc2Rgba8 => |block| with_alpha(etc2_colors(&block[8..]), eac_alpha(block)),
+        TextureFormat::Bc7 | TextureFormat::Astc4x4 => return Err(format!("{:?} textures cannot be decoded", format)),
+    };
+    let (width, height) = (width as usize, height as usize);
+    let blocks_wide = width.div_ceil(4);
+    let mut rgba = vec![0; width * height * 4];
+    for (index, block) in data.chunks_exact(format.block_bytes()).take(blocks_wide * height.div_ceil(4)).enumerate() {
+        let (block_x, block_y) = (index % blocks_wide * 4, index / blocks_wide * 4);
+        for (pixel, color) in decode_block(block).iter().enumerate() {
+            let (x, y) = (block_x + pixel % 4, block_y + pixel / 4);
+            if x < width && y < height {
+                rgba[(y * width + x) * 4..][..4].copy_from_slice(color);
+            }
+        }
+    }
+    Ok(rgba)
+}
+
+fn with_alpha(mut colors: [[u8; 4]; 16], alpha: [u8; 16]) -> [[u8; 4]; 16] {
+    for (color, alpha) in colors.iter_mut().zip(alpha) {
+        color[3] = alpha;
+    }
+    colors
+}
+
+fn rgb565(color: u16) -> [u8; 4] {
+    let (r, g, b) = ((color >> 11) as u8 & 31, (color >> 5) as u8 & 63, color as u8 & 31);
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2), 255]
+}
+
+/// The 16 colors of a BC1 block in row order; BC3 color blocks never use
+/// the three-color mode (`punch_through` false)
+fn bc1_colors(block: &[u8], punch_through: bool) -> [[u8; 4]; 16] {
+    let (c0, c1) = (u16::from_le_bytes([block[0], block[1]]), u16::from_le_bytes([block[2], block[3]]));
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let mix = |wa: u16, wb: u16| -> [u8; 4] {
+        let channel = |i: usize| ((u16::from(a[i]) * wa + u16::from(b[i]) * wb) / (wa + wb)) as u8;
+        [channel(0), channel(1), channel(2), 255]
+    };
+    let palette = if c0 > c1 || !punch_through {
+        [a, b, mix(2, 1), mix(1, 2)]
+    } else {
+        [a, b, mix(1, 1), [0, 0, 0, 0]]
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    std::array::from_fn(|pixel| palette[(indices >> (2 * pixel)) as usize & 3])
+}
+
+/// The 16 alphas of a BC3 block in row order
+fn bc3_alpha(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (u32::from(block[0]), u32::from(block[1]));
+    let mut palette = [a0 as u8, a1 as u8, 0, 0, 0, 0, 0, 255];
+    if a0 > a1 {
+        for (i, value) in palette.iter_mut().enumerate().skip(2) {
+            *value = (((8 - i as u32) * a0 + (i as u32 - 1) * a1) / 7) as u8;
+        }
+    } else {
+        for (i, value) in palette.iter_mut().enumerate().take(6).skip(2) {
+            *value = (((6 - i as u32) * a0 + (i as u32 - 1) * a1) / 5) as u8;
+        }
+    }
+    let indices = block[2..8].iter().rev().fold(0u64, |bits, &byte| (bits << 8) | u64::from(byte));
+    std::array::from_fn(|pixel| palette[(indices >> (3 * pixel)) as usize & 7])
+}
+
+const ETC1_MODIFIERS: [[i32; 2]; 8] = [[2, 8], [5, 17], [9, 29], [13, 42], [18, 60], [24, 80], [33, 106], [47, 183]];
+const ETC2_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+fn clamp_u8(value: i32) -> u8 {
+    value.clamp(0, 255) as u8
+}
+
+/// `bits` wide field of `block` ending at bit `low` (bit 63 is the first
+/// byte's top bit)
+fn field(block: u64, low: u32, bits: u32) -> i32 {
+    ((block >> low) & ((1 << bits) - 1)) as i32
+}
+
+fn extend(value: i32, bits: u32) -> i32 {
+    (value << (8 - bits)) | (value >> (2 * bits - 8))
+}
+
+fn offset(color: [i32; 3], by: i32) -> [u8; 4] {
+    [clamp_u8(color[0] + by), clamp_u8(color[1] + by), clamp_u8(color[2] + by), 255]
+}
+
+/// The 16 colors of an ETC2 RGB8 block (ETC1 blocks included) in row order
+fn etc2_colors(block: &[u8]) -> [[u8; 4]; 16] {
+    let bits = u64::from_be_bytes(block[..8].try_into().unwrap_or_default());
+    // Pixel indices run down the columns, most significant bits first
+    let index = |pixel: usize| {
+        let j = (pixel % 4) * 4 + pixel / 4;
+        ((((bits >> (16 + j)) & 1) << 1) | ((bits >> j) & 1)) as usize
+    };
+    let differential = (bits >> 33) & 1 == 1;
+    if !differential {
+        let base = |low: u32| [field(bits, low + 20, 4), field(bits, low + 12, 4), field(bits, low + 4, 4)].map(|c| extend(c, 4));
+        return etc1_colors(bits, [base(40), base(36)], index);
+    }
+    let (r, g, b) = (field(bits, 59, 5), field(bits, 51, 5), field(bits, 43, 5));
+    // Differences are signed 3-bit values
+    let delta = |low: u32| (field(bits, low, 3) << 29) >> 29;
+    let (r2, g2, b2) = (r + delta(56), g + delta(48), b + delta(40));
+    if !(0..32).contains(&r2) {
+        // T mode
+        let c1 = [(field(bits, 59, 2) << 2) | field(bits, 56, 2), field(bits, 52, 4), field(bits, 48, 4)].map(|c| extend(c, 4));
+        let c2 = [field(bits, 44, 4), field(bits, 40, 4), field(bits, 36, 4)].map(|c| extend(c, 4));
+        let d = ETC2_DISTANCES[((field(bits, 34, 2) << 1) | field(bits, 32, 1)) as usize];
+        let paint = [offset(c1, 0), offset(c2, d), offset(c2, 0), offset(c2, -d)];
+        return std::array::from_fn(|pixel| paint[index(pixel)]);
+    }
+    if !(0..32).contains(&g2) {
+        // H mode
+        let c1 = [field(bits, 59, 4), (field(bits, 56, 3) << 1) | field(bits, 52, 1), (field(bits, 51, 1) << 3) | field(bits, 47, 3)];
+        let c2 = [field(bits, 43, 4), field(bits, 39, 4), field(bits, 35, 4)];
+        let packed = |c: [i32; 3]| (c[0] << 8) | (c[1] << 4) | c[2];
+        let order = i32::from(packed(c1) >= packed(c2));
+        let d = ETC2_DISTANCES[((field(bits, 34, 1) << 2) | (field(bits, 32, 1) << 1) | order) as usize];
+        let (c1, c2) = (c1.map(|c| extend(c, 4)), c2.map(|c| extend(c, 4)));
+        let paint = [offset(c1, d), offset(c1, -d), offset(c2, d), offset(c2, -d)];
+        return std::array::from_fn(|pixel| paint[index(pixel)]);
+    }
+    if !(0..32).contains(&b2) {
+        // Planar mode: a gradient from the origin color to the ones at
+        // the horizontal and vertical edges
+        let o = [
+            extend(field(bits, 57, 6), 6),
+            extend((field(bits, 56, 1) << 6) | field(bits, 49, 6), 7),
+            extend((field(bits, 48, 1) << 5) | (field(bits, 43, 2) << 3) | field(bits, 39, 3), 6),
+        ];
+        let h = [
+            extend((field(bits, 34, 5) << 1) | field(bits, 32, 1), 6),
+            extend(field(bits, 25, 7), 7),
+            extend(field(bits, 19, 6), 6),
+        ];
+        let v = [extend(field(bits, 13, 6), 6), extend(field(bits, 6, 7), 7), extend(field(bits, 0, 6), 6)];
+        return std::array::from_fn(|pixel| {
+            let (x, y) = ((pixel % 4) as i32, (pixel / 4) as i32);
+            let channel = |i: usize| clamp_u8((x * (h[i] - o[i]) + y * (v[i] - o[i]) + 4 * o[i] + 2) >> 2);
+            [channel(0), channel(1), channel(2), 255]
+        });
+    }
+    etc1_colors(bits, [[r, g, b].map(|c| extend(c, 5)), [r2, g2, b2].map(|c| extend(c, 5))], index)
+}
+
+/// Colors of an individual or differential block with sub-block base colors
+/// `bases`
+fn etc1_colors(bits: u64, bases: [[i32; 3]; 2], index: impl Fn(usize) -> usize) -> [[u8; 4]; 16] {
+    let tables = [field(bits, 37, 3) as usize, field(bits, 34, 3) as usize];
+    let flipped = (bits >> 32) & 1 == 1;
+    std::array::from_fn(|pixel| {
+        let (x, y) = (pixel % 4, pixel / 4);
+        let sub_block = usize::from(if flipped { y >= 2 } else { x >= 2 });
+        let [small, large] = ETC1_MODIFIERS[tables[sub_block]];
+        let modifier = [small, large, -small, -large][index(pixel)];
+        offset(bases[sub_block], modifier)
+    })
+}
+
+/// The 16 alphas of an EAC block in row order
+fn eac_alpha(block: &[u8]) -> [u8; 16] {
+    let bits = u64::from_be_bytes(block[..8].try_into().unwrap_or_default());
+    let (base, multiplier) = (i32::from(block[0]), i32::from(block[1] >> 4));
+    let modifiers = EAC_MODIFIERS[usize::from(block[1] & 15)];
+    std::array::from_fn(|pixel| {
+        let j = (pixel % 4) * 4 + pixel / 4;
+        let index = (bits >> (45 - 3 * j)) as usize & 7;
+        clamp_u8(base + modifiers[index] * multiplier)
+    })
+}
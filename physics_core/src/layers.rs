@@ -0,0 +1,133 @@
+//! Draw layers: which sprites cover which, and parallax scrolling
+//!
+//! The 2D pass has no depth buffer, so whatever is drawn later covers what
+//! was drawn before. A `Layer` puts an entity in the background, the world
+//! or the foreground, with a sort key ordering the layers of one kind;
+//! entities without one are in the world layer at key 0. Background layers
+//! are drawn under the terrain, tiles and contact shadows, world layers
+//! where bodies always were, and foreground layers over the water.
+//!
+//! Instances are sorted by layer, then material, then entity, so sprites of
+//! one layer keep which is on top from frame to frame. Layers therefore also
+//! split material batches: every layer costs at least one draw call. The GPU
+//! culling path still lands a batch's instances in any order.
+//!
+//! Each layer scrolls against the camera by its `LayerParallax` factor per
+//! axis: 1 moves with the world, 0 stays put on screen like a sky and 0.5
+//! drifts at half the camera's speed, like distant hills. Parallax only
+//! moves what is drawn; colliders stay where the simulation has them.
+//! Instances with a factor other than 1 are never culled.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use bevy_ecs::prelude::*;
+
+use crate::material::DrawBatch;
+
+/// Background layers are drawn first and foreground layers last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum LayerKind {
+    Background,
+    #[default]
+    World,
+    Foreground,
+}
+
+impl LayerKind {
+    /// FFI code: 0 = background, 1 = world, 2 = foreground
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Background),
+            1 => Some(Self::World),
+            2 => Some(Self::Foreground),
+            _ => None,
+        }
+    }
+}
+
+/// Layer an entity is drawn in; layers of one kind are drawn in `order`,
+/// lowest first
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Layer {
+    pub kind: LayerKind,
+    pub order: i32,
+}
+
+impl Layer {
+    pub fn new(kind: LayerKind, order: i32) -> Self {
+        Self { kind, order }
+    }
+}
+
+/// Parallax factor of every layer that does not move with the world
+#[derive(Resource, Debug, Clone, Default)]
+pub struct LayerParallax {
+    factors: HashMap<Layer, [f32; 2]>,
+}
+
+impl LayerParallax {
+    /// Scroll `layer` by `factor` (x, y) of the camera's movement
+    pub fn set(&mut self, layer: Layer, factor: [f32; 2]) -> Result<(), String> {
+        if !factor.iter().all(|value| value.is_finite()) {
+            return Err("parallax factors must be finite".into());
+        }
+        match factor == [1.0, 1.0] {
+            true => self.factors.remove(&layer),
+            false => self.factors.insert(layer, factor),
+        };
+        Ok(())
+    }
+
+    /// Instance `parallax` of `layer`; [1, 1] unless set
+    pub fn factor(&self, layer: Layer) -> [f32; 2] {
+        self.factors.get(&layer).copied().unwrap_or([1.0, 1.0])
+    }
+}
+
+/// Where an instance goes in draw order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DrawKey {
+    pub layer: Layer,
+    pub material_id: u32,
+    pub entity: Entity,
+}
+
+/// First instance slots of the world and foreground layers once sorted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LayerSplit {
+    pub world: u32,
+    pub foreground: u32,
+}
+
+impl LayerSplit {
+    /// Indices into `batches` (sorted by first instance, as culled or not)
+    /// of the background, world and foreground batches
+    pub fn ranges(&self, batches: &[DrawBatch]) -> [Range<usize>; 3] {
+        let world = batches.partition_point(|batch| batch.first_instance < self.world);
+        let foreground = batches.partition_point(|batch| batch.first_instance < self.foreground);
+        [0..world, world..foreground, foreground..batches.len()]
+    }
+}
+
+/// Sort `(key, item)` pairs into draw order and return one batch per run of
+/// one layer and material, and where the layer kinds start
+pub fn sort_into_batches<T>(items: &mut [(DrawKey, T)]) -> (Vec<DrawBatch>, LayerSplit) {
+    items.sort_by_key(|(key, _)| *key);
+
+    let mut batches: Vec<DrawBatch> = Vec::new();
+    let mut last: Option<(Layer, u32)> = None;
+    for (index, (key, _)) in items.iter().enumerate() {
+        match batches.last_mut() {
+            Some(batch) if last == Some((key.layer, key.material_id)) => batch.instance_count += 1,
+            _ => batches.push(DrawBatch {
+                material_id: key.material_id,
+                first_instance: index as u32,
+                instance_count: 1,
+            }),
+        }
+        last = Some((key.layer, key.material_id));
+    }
+    let start = |kind: LayerKind| items.partition_point(|(key, _)| key.layer.kind < kind) as u32;
+    (batches, LayerSplit { world: start(LayerKind::World), foreground: start(LayerKind::Foreground) })
+}
@@ -0,0 +1,168 @@
+//! Static level geometry
+//!
+//! A `LevelDesc` lists polylines, heightfields, terrain, solid polygons and
+//! tile grids.
+//! Loading one turns them into colliders on a single fixed body, replacing
+//! the previous level, and optionally removes the four boundary walls.
+//! Polylines, heightfields and terrain become one polyline collider each.
+//! Polygons may be concave and become one compound collider of convex
+//! pieces each (see `polygon`).
+//! Solid tiles are first merged into as few rectangles as possible and then
+//! into one compound collider, so a 100×100 grid costs a handful of shapes
+//! rather than 10 000.
+//! Tile grids are also drawn and can be edited at runtime (see `tilemap`),
+//! and terrain is drawn as a filled strip (see `terrain`).
+//! The level's trigger zones replace the previous ones (see `triggers`).
+
+use crate::rapier::prelude::*;
+use serde::Deserialize;
+
+use crate::polygon;
+use crate::terrain::Terrain;
+use crate::tilemap::Tilemap;
+use crate::triggers::TriggerZone;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum LevelShape {
+    /// Connected line segments; `closed` joins the last point to the first
+    Polyline {
+        points: Vec<[f32; 2]>,
+        #[serde(default)]
+        closed: bool,
+    },
+    /// Ground heights sampled every `spacing` meters from `origin_x`
+    #[serde(rename_all = "camelCase")]
+    Heightfield { origin_x: f32, spacing: f32, heights: Vec<f32> },
+    /// Drawn heightfield, listed or generated from noise
+    Terrain(Terrain),
+    /// Solid area inside a closed outline, convex or not
+    Polygon { points: Vec<[f32; 2]> },
+    /// Grid of cells, 0 for empty and any other value for a solid tile
+    Tiles(Tilemap),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LevelDesc {
+    pub shapes: Vec<LevelShape>,
+    /// Keep the four boundary walls around the level
+    pub keep_walls: bool,
+    /// Goal, hazard and sensor zones
+    pub triggers: Vec<TriggerZone>,
+}
+
+impl Default for LevelDesc {
+    fn default() -> Self {
+        Self { shapes: Vec::new(), keep_walls: true, triggers: Vec::new() }
+    }
+}
+
+/// Solid cells merged into a rectangle, in cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRect {
+    pub column: u32,
+    pub row: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Cover the solid cells with rectangles: runs along each row, then runs
+/// with the same columns stacked down into taller rectangles. Not minimal,
+/// but close for typical level layouts and linear in the cell count.
+pub fn merge_tiles(columns: u32, cells: &[u8]) -> Vec<TileRect> {
+    if columns == 0 {
+        return Vec::new();
+    }
+    let mut rects: Vec<TileRect> = Vec::new();
+    // Rectangles whose bottom edge is the previous row, by (column, width)
+    let mut open: Vec<usize> = Vec::new();
+    for (row, cells) in cells.chunks(columns as usize).enumerate() {
+        let row = row as u32;
+        let mut next_open = Vec::new();
+        let mut column = 0;
+        while (column as usize) < cells.len() {
+            if cells[column as usize] == 0 {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while (column as usize) < cells.len() && cells[column as usize] != 0 {
+                column += 1;
+            }
+            let width = column - start;
+            let extend = open.iter().copied().find(|&i| {
+                let r = rects[i];
+                r.column == start && r.width == width && r.row + r.height == row
+            });
+            match extend {
+                Some(i) => {
+                    rects[i].height += 1;
+                    next_open.push(i);
+                }
+                None => {
+                    rects.push(TileRect { column: start, row, width, height: 1 });
+                    next_open.push(rects.len() - 1);
+                }
+            }
+        }
+        open = next_open;
+    }
+    rects
+}
+
+fn finite(values: &[f32]) -> bool {
+    values.iter().all(|v| v.is_finite())
+}
+
+/// Polyline collider through `points`
+fn polyline(points: &[[f32; 2]], closed: bool) -> Result<Collider, String> {
+    if points.len() < 2 {
+        return Err("a polyline needs at least 2 points".into());
+    }
+    if !points.iter().all(|p| finite(p)) {
+        return Err("polyline points must be finite".into());
+    }
+    let vertices: Vec<Point<Real>> = points.iter().map(|&p| planar(p).into()).collect();
+    let n = vertices.len() as u32;
+    let segments = if closed && n > 2 { n } else { n - 1 };
+    let indices = (0..segments).map(|i| [i, (i + 1) % n]).collect();
+    Ok(ColliderBuilder::polyline(vertices, Some(indices)).build())
+}
+
+/// Collider for one shape, relative to a body at the origin; None for a tile
+/// grid with no solid cells
+pub fn shape_collider(shape: &LevelShape) -> Result<Option<Collider>, String> {
+    match shape {
+        LevelShape::Polyline { points, closed } => polyline(points, *closed).map(Some),
+        LevelShape::Heightfield { origin_x, spacing, heights } => {
+            if !finite(&[*origin_x, *spacing]) || *spacing <= 0.0 {
+                return Err("heightfield spacing must be > 0".into());
+            }
+            let points: Vec<[f32; 2]> = heights
+                .iter()
+                .enumerate()
+                .map(|(i, &h)| [origin_x + i as f32 * spacing, h])
+                .collect();
+            polyline(&points, false).map(Some)
+        }
+        LevelShape::Terrain(terrain) => {
+            terrain.validate()?;
+            polyline(&terrain.points(), false).map(Some)
+        }
+        LevelShape::Polygon { points } => polygon::collider(points).map(|builder| Some(builder.build())),
+        LevelShape::Tiles(tilemap) => {
+            tilemap.validate()?;
+            Ok(tilemap.collider())
+        }
+    }
+}
+
+/// Colliders for every shape in `desc`
+pub fn build_colliders(desc: &LevelDesc) -> Result<Vec<Collider>, String> {
+    let mut colliders = Vec::new();
+    for shape in &desc.shapes {
+        colliders.extend(shape_collider(shape)?);
+    }
+    Ok(colliders)
+}
@@ -2,11 +2,178 @@ mod egui_tools;
 mod camera;
 pub mod animation;
 pub mod sprite;
+pub mod sprite_region;
+pub mod layers;
 pub mod bevy_3d_sample;
+pub mod gltf_lite;
+pub mod png_lite;
+pub mod ktx2_lite;
+pub mod texture_sampling;
+pub mod demo_3d;
+pub mod culling;
+pub mod gpu_culling;
+pub mod material;
+pub mod render_settings;
+pub mod log_console;
+pub mod log_filter;
+pub mod crash;
+pub mod validation;
+pub mod precision;
+pub mod units;
+pub mod gravity;
+pub mod settings_store;
+mod post_process;
+mod shadows;
+pub mod text;
+pub mod render_graph;
+pub mod streaming;
+pub mod lod;
+pub mod determinism;
+pub mod stress;
+pub mod instance_staging;
+pub mod schedule;
+pub mod plugins;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod snapshot;
+pub mod time_control;
+pub mod solver_config;
+pub mod substep;
+pub mod conveyor;
+pub mod contact_filter;
+pub mod force_fields;
+pub mod time_dilation;
+pub mod water;
+pub mod soft_body;
+pub mod trail;
+pub mod trajectory;
+pub mod tween;
+pub mod state_machine;
+pub mod steering;
+pub mod command_buffer;
+pub mod transform_export;
+pub mod views;
+pub mod minimap;
+pub mod split_screen;
+pub mod netcode;
+pub mod prediction;
+pub mod capture;
+pub mod clip;
+pub mod fracture;
+pub mod triggers;
+pub mod point_springs;
+pub mod level_geometry;
+pub mod scenes;
+pub mod prefabs;
+pub mod assets;
+pub mod tilemap;
+pub mod terrain;
+pub mod polygon;
+pub mod sprite_outline;
+pub mod groups;
+pub mod projectiles;
+pub mod lifetime;
+pub mod hierarchy;
+pub mod audio;
+pub mod camera_effects;
+pub mod gestures;
+pub mod slingshot;
+pub mod tilt;
+pub mod gpu_caps;
+pub mod gpu_options;
+pub mod gpu_timing;
+pub mod background;
+pub mod safe_area;
+pub mod governor;
+pub mod lifecycle;
+pub mod editor;
+pub mod history;
+pub mod cli;
+pub mod ffi;
+#[cfg(feature = "uniffi_bindings")]
+pub mod uniffi_api;
+#[cfg(feature = "wasm_support")]
+pub mod wasm_api;
+#[doc(hidden)]
+pub mod bench_support;
+#[doc(hidden)]
+pub mod test_support;
+#[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+pub mod shader_manager;
+
+#[cfg(feature = "uniffi_bindings")]
+uniffi::setup_scaffolding!();
+
+/// The Rapier build the simulation runs on: f32, or f64 with the
+/// `physics-f64` feature (see `precision`)
+#[cfg(not(feature = "physics-f64"))]
+pub use rapier3d as rapier;
+#[cfg(feature = "physics-f64")]
+pub use rapier3d_f64 as rapier;
 
-use bevy_3d_sample::Bevy3DSample;
+use bevy_3d_sample::{Bevy3DSample, ModelComponent, ModelInstance};
 
 use camera::{Camera, CameraUniform};
+use camera_effects::{CameraEffects, CameraEffectsConfig, CameraOffset};
+use material::{DrawBatch, MaterialComponent, MaterialRegistry};
+use culling::ViewRect;
+use gpu_culling::GpuCullPass;
+use render_settings::RenderSettings;
+use log_console::ConsoleView;
+use log_filter::LogFilter;
+use validation::InputError;
+use settings_store::{CameraSettings, PanelLayout, PhysicsSettings, SavedSettings, SettingsStore};
+use post_process::BloomPass;
+use shadows::ContactShadowPass;
+use text::{unpack_rgba, TextAnchor, TextLabel, TextLayer, TextPass};
+use render_graph::{PassId, RenderGraph, Resource};
+use streaming::{ChunkStreamer, StreamFocus, StreamingConfig};
+use lod::LodConfig;
+use instance_staging::{InstanceStaging, Upload};
+use gestures::{Gesture, GestureConfig, GestureRecognizer};
+use scenes::{Fade, Persistent, ResetMode, SceneBody, SceneBodyType, SceneDesc, SceneLibrary};
+use prefabs::{PrefabBody, PrefabDesc, PrefabInstance, PrefabLibrary, PrefabShape, PrefabSpawn};
+use slingshot::{Slingshot, SlingshotConfig};
+use tilt::TiltGravity;
+use demo_3d::{OrbitCamera, OrbitControls};
+use solver_config::SolverConfig;
+use substep::SubstepConfig;
+use conveyor::SurfaceVelocities;
+use contact_filter::ContactFilter;
+use force_fields::ForceFieldConfig;
+use time_dilation::TimeDilationConfig;
+use water::{Water, WaterConfig, WaterPass};
+use soft_body::{SoftBodies, SoftBodyDesc, SoftBodyPass};
+use trail::{TrailPass, TrailVertex, Trails};
+use fracture::{Destructible, FractureConfig, FractureEvent, FractureQueue};
+use triggers::{TriggerEvent, Triggers};
+use tween::{Appearance, CameraTweenValues, Easing, Tween, TweenProperty, Tweens};
+use layers::{DrawKey, Layer, LayerKind, LayerParallax, LayerSplit};
+use state_machine::{MovementDesc, StateMachine, StateMachineDesc};
+use transform_export::{ExportedTransform, TransformExport};
+use views::{SurfaceView, ViewCamera, ViewSlots};
+use minimap::{Blip, MinimapConfig, MinimapPass};
+use background::{BackgroundFit, BackgroundPass, PixelLayout};
+use safe_area::SafeArea;
+use governor::{Governor, PowerState, ThermalStatus, Throttle};
+use lifecycle::Lifecycle;
+use editor::{EditPose, Editor, EditorTool};
+use history::{Edit, History, SpringEdit};
+use split_screen::{Player, SplitCameras, SplitScreen};
+use capture::{CaptureConfig, CaptureTarget, CapturedFrame, ScreenshotTarget};
+use tilemap::Tilemap;
+use units::WorldScale;
+use gravity::GravityPreset;
+use groups::Groups;
+use projectiles::{ProjectilePool, ProjectileStats};
+use lifetime::{DespawnBounds, Lifetime};
+use hierarchy::{Attached, Decoration, Pose2D};
+use audio::{AudioConfig, AudioEvent, AudioQueue};
+use gpu_caps::GpuCaps;
+use gpu_options::GpuInitOptions;
+use gpu_timing::{FrameStats, GpuTimer};
+#[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+use shader_manager::ShaderWatcher;
 
 use ::nalgebra as na;
 
@@ -15,8 +182,6 @@ use raw_window_handle::{
     HandleError, HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle,
 };
 use std::ffi::c_void; // Needed for casting
-use std::ffi::CString;
-use std::os::raw::c_char;
 #[allow(unused_imports)]
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -25,10 +190,10 @@ use wgpu::util::DeviceExt;
 
 // Bevy ECS imports
 use bevy_ecs::prelude::*;
-use bevy_ecs::system::SystemState;
 
 // Rapier3D imports
-use rapier3d::prelude::*;
+use crate::rapier::prelude::*;
+use precision::{planar, planar_rotation, to_f32, to_real, xy};
 
 use crate::egui_tools::EguiRenderer;
 
@@ -66,8 +231,6 @@ use wasm_bindgen::prelude::*;
 
 // Global state for game loop
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
-static WIDTH: u32 = 1024;
-static HEIGHT: u32 = 1024;
 static NUM_INSTANCES_PER_ROW: u32 = 10;
 
 // --- ECS Components ---
@@ -95,10 +258,23 @@ struct PhysicsBody {
     collider_handle: ColliderHandle,
 }
 
+/// Snapshot of a body returned by the body query API
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BodyInfo {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub rotation: f32,
+    pub sleeping: bool,
+}
+
 // --- Strategy Pattern Components for Animated Entities ---
 pub mod game_entity;
 pub use animation::AnimatorComponent;
 pub use sprite::SpriteSheetComponent;
+pub use sprite_region::SpriteRegion;
 pub use game_entity::{
     CircularMovement, GameEntity, HorizontalRandomMovement, LinearMovement,
     MovementComponent, MovementStrategy, SinusoidalMovement, Controllable,
@@ -110,16 +286,106 @@ struct PhysicsState {
     rigid_body_set: RigidBodySet,
     collider_set: ColliderSet,
     integration_parameters: IntegrationParameters,
+    /// Applied to `integration_parameters` before every step
+    solver: SolverConfig,
+    substep_config: SubstepConfig,
+    /// Substeps chosen for the latest step
+    current_substeps: u32,
     physics_pipeline: PhysicsPipeline,
     island_manager: IslandManager,
     broad_phase: DefaultBroadPhase,
     narrow_phase: NarrowPhase,
     impulse_joint_set: ImpulseJointSet,
     multibody_joint_set: MultibodyJointSet,
+    /// The four boundary walls, until a level removes them
+    walls: Vec<RigidBodyHandle>,
+    /// Fixed body holding the loaded `level_geometry` colliders
+    level_body: Option<RigidBodyHandle>,
+    /// Tile grids of the loaded level and their collider on `level_body`
+    tilemaps: Vec<(Tilemap, Option<ColliderHandle>)>,
+    /// Fill of the loaded level's terrain
+    terrain_vertices: Vec<TrailVertex>,
+    /// Set when tiles or terrain change so the level is uploaded again
+    tilemaps_dirty: bool,
+    /// Fixed body the `point_springs` joints hang from
+    spring_anchor: Option<RigidBodyHandle>,
+    /// Preallocated projectiles by entity id, if the host configured a pool
+    projectiles: Option<ProjectilePool<u64>>,
     ccd_solver: CCDSolver,
+    /// Conveyor colliders and the velocity of their surface
+    surface_velocities: SurfaceVelocities,
+    /// One-way platforms and body pairs that pass through each other
+    contact_filter: ContactFilter,
+    /// Wind, force volumes and buoyancy applied before every step
+    force_fields: ForceFieldConfig,
+    /// Slow-motion zones, applied per body in the physics step
+    time_dilation: TimeDilationConfig,
+    /// Wavy water regions with buoyancy and splashes
+    water: Water,
+    /// Pressure-spring blobs; their nodes are ordinary box entities
+    soft_bodies: SoftBodies,
+    /// Recent positions of trailed bodies
+    trails: Trails,
+    /// Predicted points shown as a dotted arc; empty when nothing is shown
+    trajectory_preview: Vec<[f32; 2]>,
+    /// Dynamic bodies leaving this rectangle are despawned
+    despawn_bounds: Option<DespawnBounds>,
+    /// Turns collision impacts into `audio_events`
+    audio_config: AudioConfig,
+    audio_events: AudioQueue,
+    /// Zones of the loaded level, their events and the level outcome
+    triggers: Triggers,
+    /// Destructible bodies that broke, not yet polled by the host
+    fracture_events: FractureQueue,
+    query_pipeline: QueryPipeline,
     gravity: Vector<Real>,
+    /// Stops the simulation steps; input, animation and the UI keep running
     paused: bool,
+    /// Stops sprite animations and tweens, whether or not the simulation runs
+    animation_paused: bool,
     time_scale: f32,
+    /// Whether resting dynamic bodies may fall asleep
+    sleep_enabled: bool,
+    /// Chunk streaming; `None` simulates every body
+    streamer: Option<ChunkStreamer>,
+    stream_focus: StreamFocus,
+    /// Main camera target, copied in every frame for `StreamFocus::Camera`
+    camera_target: [f32; 2],
+    lod: LodConfig,
+    /// Dynamic bodies past the LOD physics distance, with raised sleep thresholds
+    lod_far_bodies: std::collections::HashSet<RigidBodyHandle>,
+    /// The performance governor's limits, copied in every frame
+    power_throttle: Throttle,
+    /// Whether dynamic bodies currently have the aggressive sleep thresholds
+    aggressive_sleep_applied: bool,
+    /// Physics steps taken since the last init/reset, mixed into the checksum
+    step_count: u64,
+    /// CPU mirror of the GPU instance buffer, one slot per body
+    instance_staging: InstanceStaging<InstanceKey, Instance>,
+    /// Material batches matching the staging slot order, sorted by layer
+    instance_batches: Vec<DrawBatch>,
+    /// Where the world and foreground layers start in the staging slots
+    instance_layers: LayerSplit,
+    /// Set when bodies or materials change so the slot layout is rebuilt
+    instance_layout_dirty: bool,
+    /// Systems run by `update_internal` each frame
+    schedule: Schedule,
+    /// Encoded state after each recent step, for rewinding
+    history: snapshot::SnapshotRing<snapshot::Recorded>,
+    /// Active slow-motion / speed-up transition
+    time_ramp: Option<time_control::TimeScaleRamp>,
+    /// Fixed steps to run on the next update even when paused
+    queued_steps: u32,
+    /// Times the scene was reset or replaced since startup
+    resets: u32,
+    /// Set by a reset until the next frame runs the `HookPoint::Reset` hooks
+    reset_unreported: bool,
+    /// (old id, new id) of every persistent body the latest reset rebuilt
+    persistent_renames: Vec<(u64, u64)>,
+    /// Snapshots sent to and received from a remote simulation
+    net: netcode::NetSync,
+    /// Recent steps of locally predicted bodies, for rolling back
+    prediction: prediction::Prediction,
 }
 
 // Wrapper for thread safety
@@ -147,6 +413,7 @@ static INPUT_STATE: Lazy<Mutex<InputEventState>> = Lazy::new(|| Mutex::new(Input
 }));
 
 fn on_pointer_event_internal(event_type: i32, x: f32, y: f32, _button: i32) {
+    let mut pointer = None;
     if let Ok(mut guard) = INPUT_STATE.lock() {
         // Only update x/y if they are not -1 (some platforms might send -1 for pure clicks)
         if x >= 0.0 { guard.pointer_x = x; }
@@ -170,6 +437,14 @@ fn on_pointer_event_internal(event_type: i32, x: f32, y: f32, _button: i32) {
         }
 
         log::debug!("Pointer event: type={}, x={}, y={}", event_type, x, y);
+        pointer = Some((guard.pointer_x, guard.pointer_y));
+    }
+    // Button events may come without a position; use the last known one
+    if let Some((x, y)) = pointer {
+        // The orbit rate is per point, so a drag turns as far on any density
+        let scale = scale_factor();
+        update_orbit(|controls| controls.pointer(event_type, x / scale, y / scale));
+        slingshot_pointer_internal(event_type, x, y);
     }
 }
 
@@ -213,8 +488,17 @@ impl Vertex {
     }
 }
 
+/// Owner of an instance slot. Free bodies are keyed by their rigid body so
+/// Rapier's active set can update them directly; attached entities and
+/// decorations are drawn from their components.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum InstanceKey {
+    Body(RigidBodyHandle),
+    Entity(Entity),
+}
+
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
 struct Instance {
     position: [f32; 2],
     velocity: [f32; 2],
@@ -222,8 +506,24 @@ struct Instance {
     rotation: f32,
     uv_offset: [f32; 2],
     uv_scale: [f32; 2],
+    /// RGBA8 multiplying the texture, red in the lowest byte
+    tint: u32,
+    /// Nonzero draws a plain quad in the color at the middle of the sprite,
+    /// for bodies far from the focus (see `lod`)
+    lod: u32,
+    /// Width and height relative to the square quad (see `sprite_region`)
+    size: [f32; 2],
+    /// Left and top nine-slice borders
+    slice_min: [f32; 2],
+    /// Right and bottom nine-slice borders
+    slice_max: [f32; 2],
+    /// How far the instance moves with the camera (see `layers`)
+    parallax: [f32; 2],
 }
 
+/// Leaves the texture as it is
+const WHITE_TINT: u32 = u32::MAX;
+
 impl Instance {
     fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
@@ -266,6 +566,42 @@ impl Instance {
                     shader_location: 7,
                     format: wgpu::VertexFormat::Float32x2,
                 },
+                // tint
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>() * 4 + std::mem::size_of::<f32>() * 2) as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Uint32,
+                },
+                // lod
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>() * 4 + std::mem::size_of::<f32>() * 2 + std::mem::size_of::<u32>()) as wgpu::BufferAddress,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Uint32,
+                },
+                // size
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>() * 4 + std::mem::size_of::<f32>() * 2 + std::mem::size_of::<u32>() * 2) as wgpu::BufferAddress,
+                    shader_location: 10,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                // slice_min
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>() * 5 + std::mem::size_of::<f32>() * 2 + std::mem::size_of::<u32>() * 2) as wgpu::BufferAddress,
+                    shader_location: 11,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                // slice_max
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>() * 6 + std::mem::size_of::<f32>() * 2 + std::mem::size_of::<u32>() * 2) as wgpu::BufferAddress,
+                    shader_location: 12,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                // parallax
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>() * 7 + std::mem::size_of::<f32>() * 2 + std::mem::size_of::<u32>() * 2) as wgpu::BufferAddress,
+                    shader_location: 13,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
             ],
         }
     }
@@ -297,27 +633,37 @@ use egui;
 use egui_wgpu;
 use egui_wgpu::{wgpu, ScreenDescriptor};
 
-struct EguiSettings {
-    text_input: String,
-    slider_value: f32,
-    checkbox: bool,
-}
-
 /// Holds the wgpu state for rendering
 struct WgpuState {
-    #[allow(dead_code)]
     instance: wgpu::Instance,
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
     surface: wgpu::Surface<'static>,
     config: wgpu::SurfaceConfiguration,
     render_pipeline: wgpu::RenderPipeline,
-    compute_pipeline: wgpu::ComputePipeline, // NEW
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     instance_buffer: wgpu::Buffer,           // NEW
+    instance_capacity: u32,
+    /// False until the instance buffer holds the staging data (new or regrown buffer)
+    instance_buffer_synced: bool,
     diffuse_bind_group: wgpu::BindGroup,
-    compute_bind_group: wgpu::BindGroup,     // NEW
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    materials: MaterialRegistry,
+    draw_batches: Vec<DrawBatch>,
+    /// Which of `draw_batches` are in background, world and foreground layers
+    layer_split: LayerSplit,
+    /// Quads of every tile layer, drawn under the bodies; None while no tile is solid
+    tile_buffer: Option<wgpu::Buffer>,
+    /// One batch per tile layer, drawn with the layer's atlas material
+    tile_batches: Vec<DrawBatch>,
+    /// False until `tile_buffer` matches the tile layers (new state or device rebuild)
+    tiles_synced: bool,
+    /// None when the adapter lacks compute or storage buffers (strict WebGL2)
+    instance_compute: Option<InstanceCompute>,
+    /// Compute culling into indirect draws, when the adapter supports it
+    gpu_cull: Option<GpuCullPass>,
+    caps: GpuCaps,
     num_instances: u32,                  // NEW
     window_ptr: *mut c_void, // Debug: track window pointer
 
@@ -332,15 +678,65 @@ struct WgpuState {
     #[cfg(not(target_arch = "wasm32"))]
     last_fps_log_time: std::time::Instant,
 
-    scale_factor: f32,
     egui_renderer: Option<EguiRenderer>,
     camera: Camera,
+    /// Shake and zoom punch baked into `camera_uniform`
+    camera_offset: CameraOffset,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
     bevy_3d_sample: Option<Bevy3DSample>,
+    sample_count: u32,
+    msaa_view: Option<wgpu::TextureView>,
+    bloom: Option<BloomPass>,
+    contact_shadows: ContactShadowPass,
+    water_pass: WaterPass,
+    soft_body_pass: SoftBodyPass,
+    trail_pass: TrailPass,
+    /// Background-colored quad over the whole frame while a scene fades in
+    fade_pass: TrailPass,
+    terrain_pass: TrailPass,
+    /// Host image (camera or video frame) behind everything in the main pass
+    background_pass: BackgroundPass,
+    minimap_pass: MinimapPass,
+    split_cameras: SplitCameras,
+    /// Offscreen frame and readback buffer, made on the first captured frame
+    capture_target: Option<CaptureTarget>,
+    text_pass: TextPass,
+    // Kept so pipelines can be rebuilt when shaders are hot reloaded
+    render_pipeline_layout: wgpu::PipelineLayout,
+    camera_bind_group_layout: wgpu::BindGroupLayout,
+    #[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+    shader_watcher: Option<ShaderWatcher>,
+    /// None when the adapter cannot write timestamps inside encoders
+    gpu_timer: Option<GpuTimer>,
+    frame_stats: FrameStats,
+    /// Reported by get_adapter_info
+    adapter_info: wgpu::AdapterInfo,
+    surface_formats: Vec<wgpu::TextureFormat>,
+    /// Kept so the surface (and device) can be recreated after a loss
+    surface_source: SurfaceSource,
+    /// Kept so extra views can check their surface against it
+    adapter: wgpu::Adapter,
+    /// Extra surfaces drawing the same scene through their own cameras
+    views: ViewSlots<SurfaceView>,
+    /// Consecutive frames whose surface came back lost or outdated
+    lost_frames: u32,
+    recovery: Option<Recovery>,
 }
 
+/// GPU recovery pending for the next frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Recovery {
+    /// Recreate the surface on the existing device
+    Surface,
+    /// Rebuild device, surface and every GPU resource
+    Device,
+}
+
+/// Set from the device-lost callback; the next frame rebuilds the GPU state
+static DEVICE_LOST: AtomicBool = AtomicBool::new(false);
+
 // Wrapper to force Send/Sync for WASM where we know it's single-threaded
 struct WgpuStateWrapper(Option<WgpuState>);
 
@@ -350,6 +746,94 @@ unsafe impl Sync for WgpuStateWrapper {}
 
 static WGPU_STATE: Lazy<Mutex<WgpuStateWrapper>> = Lazy::new(|| Mutex::new(WgpuStateWrapper(None)));
 
+// Render settings can be changed before the surface exists, so they live outside WgpuState
+static RENDER_SETTINGS: Lazy<Mutex<RenderSettings>> = Lazy::new(|| Mutex::new(RenderSettings::default()));
+
+// Read whenever a scene or camera is built; set before either to start at that scale
+static WORLD_SCALE: Lazy<Mutex<WorldScale>> = Lazy::new(|| Mutex::new(WorldScale::default()));
+
+// Read at init, so they have to be set before the surface exists
+static GPU_INIT_OPTIONS: Lazy<Mutex<GpuInitOptions>> = Lazy::new(|| Mutex::new(GpuInitOptions::default()));
+
+// Labels persist until cleared, across surface and device rebuilds. Never
+// held together with another lock.
+static TEXT_LABELS: Lazy<Mutex<TextLayer>> = Lazy::new(|| Mutex::new(TextLayer::new()));
+
+// Shake and zoom punches, with the frame clock (ms) they were last advanced
+// to. Never held together with another lock.
+static CAMERA_EFFECTS: Lazy<Mutex<(CameraEffects, Option<f64>)>> =
+    Lazy::new(|| Mutex::new((CameraEffects::default(), None)));
+
+// Registered scenes. Never held together with another lock.
+static SCENES: Lazy<Mutex<SceneLibrary>> = Lazy::new(|| Mutex::new(SceneLibrary::default()));
+
+// Registered prefabs. Never held together with another lock.
+static PREFABS: Lazy<Mutex<PrefabLibrary>> = Lazy::new(|| Mutex::new(PrefabLibrary::default()));
+
+// Materials registered from texture assets, by asset name, so scenes
+// loading again reuse them. Never held together with another lock.
+static TEXTURE_ASSETS: Lazy<Mutex<std::collections::HashMap<String, u32>>> =
+    Lazy::new(|| Mutex::new(std::collections::HashMap::new()));
+
+// Fade after a scene switch, with the frame clock (ms) it was last advanced
+// to. Never held together with another lock.
+static SCENE_FADE: Lazy<Mutex<(Fade, Option<f64>)>> = Lazy::new(|| Mutex::new((Fade::default(), None)));
+
+// Minimap drawn over the scene; `None` hides it. Like the render settings it
+// can be set before the surface exists.
+static MINIMAP: Lazy<Mutex<Option<MinimapConfig>>> = Lazy::new(|| Mutex::new(None));
+
+// Transforms shared with host overlays; `None` until a host asks for them.
+// Never held together with another lock.
+static TRANSFORM_EXPORT: Lazy<Mutex<Option<TransformExport>>> = Lazy::new(|| Mutex::new(None));
+
+// Where settings are saved; `None` keeps nothing (Android until the app
+// names its storage directory). Never held together with another lock.
+static SETTINGS_STORE: Lazy<Mutex<Option<SettingsStore>>> = Lazy::new(|| Mutex::new(SettingsStore::platform_default()));
+
+// Where the control panel was last drawn, or restored to before it is shown.
+// Nothing else is locked while it is held.
+static EGUI_PANEL: Lazy<Mutex<Option<PanelLayout>>> = Lazy::new(|| Mutex::new(None));
+
+// Unobstructed part of the main surface. Nothing else is locked while it is
+// held.
+static SAFE_AREA: Lazy<Mutex<SafeArea>> = Lazy::new(|| Mutex::new(SafeArea::default()));
+
+// Surface pixels per logical point (Android density, iOS `contentScale`,
+// `devicePixelRatio`). Kept apart from `WgpuState` so hosts can set it before
+// the surface exists. Nothing else is locked while it is held.
+static SCALE_FACTOR: Lazy<Mutex<f32>> = Lazy::new(|| Mutex::new(1.0));
+
+// Power state reported by the host and the throttle level it led to. Never
+// held together with another lock; the simulation gets a copy of the limits.
+static GOVERNOR: Lazy<Mutex<Governor>> = Lazy::new(|| Mutex::new(Governor::new()));
+
+// Whether the app is in the background (see `lifecycle`). Never held
+// together with another lock.
+static LIFECYCLE: Lazy<Mutex<Lifecycle>> = Lazy::new(|| Mutex::new(Lifecycle::default()));
+
+// What a shutdown while the app was paused kept for the next init. Never held
+// together with another lock.
+static PARKED_GPU: Lazy<Mutex<ParkedGpu>> = Lazy::new(|| Mutex::new(ParkedGpu(None)));
+
+struct ParkedGpu(Option<GpuCarryOver>);
+
+unsafe impl Send for ParkedGpu {}
+
+// Scene editor mode of the desktop app (see `editor`). Nothing else is locked
+// while it is held.
+static EDITOR: Lazy<Mutex<Editor>> = Lazy::new(|| Mutex::new(Editor::new()));
+
+// Undo/redo steps of the editing calls (see `history`). Nothing else is locked
+// while it is held.
+static HISTORY: Lazy<Mutex<History<SceneBody>>> = Lazy::new(|| Mutex::new(History::new()));
+
+// Key that takes a screenshot in the winit app, as a winit `KeyCode` (`None`
+// disables it), and the directory screenshots go to. Never held together
+// with another lock.
+static SCREENSHOT_KEY: Lazy<Mutex<(Option<i32>, std::path::PathBuf)>> =
+    Lazy::new(|| Mutex::new((Some(winit::keyboard::KeyCode::F12 as i32), std::path::PathBuf::from("."))));
+
 fn get_internal_info() -> String {
     "Hello from Rust wgpu core!".to_string()
 }
@@ -357,6 +841,7 @@ fn get_internal_info() -> String {
 // --- Surface Handle Wrapper for raw pointers ---
 
 /// Wrapper to implement HasWindowHandle/HasDisplayHandle for raw pointers
+#[derive(Clone, Copy)]
 struct RawSurfaceHandle {
     window_handle: RawWindowHandle,
     display_handle: RawDisplayHandle,
@@ -376,6 +861,37 @@ impl HasDisplayHandle for RawSurfaceHandle {
     }
 }
 
+/// What a surface was created from, so it can be created again
+#[derive(Clone)]
+enum SurfaceSource {
+    Window(RawSurfaceHandle),
+    #[cfg(target_arch = "wasm32")]
+    Canvas(web_sys::HtmlCanvasElement),
+}
+
+impl SurfaceSource {
+    fn create_surface(&self, instance: &wgpu::Instance) -> Result<wgpu::Surface<'static>, wgpu::CreateSurfaceError> {
+        match self {
+            // SAFETY: The host keeps the window alive for as long as the core renders to it
+            SurfaceSource::Window(handle) => unsafe {
+                instance.create_surface_unsafe(wgpu::SurfaceTargetUnsafe::from_window(handle)?)
+            },
+            #[cfg(target_arch = "wasm32")]
+            SurfaceSource::Canvas(canvas) => instance.create_surface(wgpu::SurfaceTarget::Canvas(canvas.clone())),
+        }
+    }
+}
+
+fn watch_device_lost(device: &wgpu::Device) {
+    device.set_device_lost_callback(|reason, message| {
+        // Destroyed is our own teardown, not a loss
+        if matches!(reason, wgpu::DeviceLostReason::Unknown) {
+            log::error!("GPU device lost: {}", message);
+            DEVICE_LOST.store(true, Ordering::Relaxed);
+        }
+    });
+}
+
 fn create_texture(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
@@ -392,11 +908,11 @@ fn create_texture(
     let texture = device.create_texture(&wgpu::TextureDescriptor {
         label: Some("Diffuse Texture"),
         size,
-        mip_level_count: 1,
+        mip_level_count: texture_sampling::mip_level_count(width, height),
         sample_count: 1,
         dimension: wgpu::TextureDimension::D2,
         format: wgpu::TextureFormat::Rgba8UnormSrgb,
-        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::RENDER_ATTACHMENT,
         view_formats: &[],
     });
 
@@ -425,23 +941,63 @@ fn create_texture(
         },
         size,
     );
+    texture_sampling::MipGenerator::new(device).generate(device, queue, &texture);
 
     let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        address_mode_u: wgpu::AddressMode::ClampToEdge,
-        address_mode_v: wgpu::AddressMode::ClampToEdge,
-        address_mode_w: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Nearest,
-        mipmap_filter: wgpu::FilterMode::Nearest,
-        ..Default::default()
-    });
+    let sampler = device.create_sampler(&texture_sampling::TextureSampling::default().descriptor());
 
     (view, sampler)
 }
 
 // --- Internal wgpu initialization ---
 
+/// Main instanced pipeline for `shader.wgsl`; also used when hot reloading it
+fn create_scene_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[Vertex::desc(), Instance::desc()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
 fn init_wgpu_internal(
     window_handle: RawWindowHandle,
     display_handle: RawDisplayHandle,
@@ -450,44 +1006,72 @@ fn init_wgpu_internal(
     window_ptr_helper: *mut c_void, // Extra arg for tracking uniqueness
     window: Option<&winit::window::Window>,
 ) -> bool {
-    log::info!("Initializing wgpu with size {}x{}", width, height);
+    // Hosts that set up no logger still fill the log console
+    log_console::install(None, log::LevelFilter::Info);
+    init_tracing();
+    let source = SurfaceSource::Window(RawSurfaceHandle {
+        window_handle,
+        display_handle,
+    });
+    let Some(mut state) = create_wgpu_state(source, width, height, window_ptr_helper, window) else {
+        return false;
+    };
+    let resumed = restore_parked_gpu(&mut state);
+
+    DEVICE_LOST.store(false, Ordering::Relaxed);
+    if let Ok(mut guard) = WGPU_STATE.lock() {
+        guard.0 = Some(state);
+    }
+    INITIALIZED.store(true, Ordering::Relaxed);
+    // Initialize physics simulation
+    if !resumed {
+        init_physics();
+        restore_settings_internal();
+    }
+    true
+}
+
+/// Build the device, surface and all GPU resources without touching the simulation
+fn create_wgpu_state(
+    surface_source: SurfaceSource,
+    width: u32,
+    height: u32,
+    window_ptr_helper: *mut c_void,
+    window: Option<&winit::window::Window>,
+) -> Option<WgpuState> {
+    let _span = tracing::info_span!(target: "init", "create_wgpu_state", width, height).entered();
+
+    let options = GPU_INIT_OPTIONS.lock().map(|o| *o).unwrap_or_default();
+    tracing::info!(target: "init", ?options, "Initializing wgpu");
 
     let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-        backends: wgpu::Backends::all(),
+        backends: options.backends,
         ..Default::default()
     });
 
-    let surface_handle = RawSurfaceHandle {
-        window_handle,
-        display_handle,
-    };
-
-    let surface = match unsafe {
-        instance
-            .create_surface_unsafe(wgpu::SurfaceTargetUnsafe::from_window(&surface_handle).unwrap())
-    } {
+    let surface = match surface_source.create_surface(&instance) {
         Ok(s) => s,
         Err(e) => {
-            log::error!("Failed to create surface: {:?}", e);
-            return false;
+            tracing::error!(target: "init", "Failed to create surface: {:?}", e);
+            return None;
         }
     };
 
     let adapter = match pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-        power_preference: wgpu::PowerPreference::HighPerformance,
+        power_preference: options.power_preference,
         compatible_surface: Some(&surface),
-        force_fallback_adapter: false,
+        force_fallback_adapter: options.force_fallback_adapter,
     })) {
         Ok(a) => a,
         Err(e) => {
-            log::error!("Failed to find suitable adapter: {:?}", e);
-            return false;
+            tracing::error!(target: "init", "Failed to find suitable adapter: {:?}", e);
+            return None;
         }
     };
 
     // 1. Inspect what the hardware actually supports
     let limits = adapter.limits();
-    log::info!("Adapter limits: {:#?}", limits);    
+    tracing::debug!(target: "init", "Adapter limits: {:#?}", limits);
 
     // customize limits if needed
     let _dd = wgpu::DeviceDescriptor {
@@ -509,8 +1093,10 @@ fn init_wgpu_internal(
 
     let device_descriptor = wgpu::DeviceDescriptor {
         label: Some("physics_core Device"),
-        // Request specific mobile features if you need them (check availability first!)
-        required_features: wgpu::Features::empty(), //wgpu::Features::TEXTURE_COMPRESSION_ASTC | wgpu::Features::TEXTURE_COMPRESSION_ETC2, 
+        // Timestamp queries for the profiler and compressed texture families
+        // when the adapter has them
+        required_features: gpu_timing::timestamp_features(adapter.features())
+            | gpu_caps::compression_features(adapter.features()),
         // CRITICAL: Use the adapter's own limits. 
         // Do NOT use wgpu::Limits::default() which enforces desktop standards.
         required_limits: limits,
@@ -525,10 +1111,11 @@ fn init_wgpu_internal(
     )) {
             Ok(dq) => dq,
             Err(e) => {
-                log::error!("Failed to request device: {:?}", e);
-                return false;
+                tracing::error!(target: "init", "Failed to request device: {:?}", e);
+                return None;
             }
         };
+    watch_device_lost(&device);
 
     let surface_caps = surface.get_capabilities(&adapter);
     let adapter_info = adapter.get_info();
@@ -561,13 +1148,13 @@ fn init_wgpu_internal(
     let width = width.min(max_dimension);
     let height = height.min(max_dimension);
 
+    // COPY_DST, where offered, lets a screenshot be copied onto the presented frame
     let config = wgpu::SurfaceConfiguration {
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | (surface_caps.usages & wgpu::TextureUsages::COPY_DST),
         format: surface_format,
         width,
         height,
-        // present_mode: wgpu::PresentMode::AutoVsync,
-        present_mode: wgpu::PresentMode::Fifo,
+        present_mode: if options.vsync { wgpu::PresentMode::Fifo } else { wgpu::PresentMode::AutoNoVsync },
         alpha_mode: surface_caps.alpha_modes[0],
         view_formats: vec![],
         desired_maximum_frame_latency: 2,
@@ -575,6 +1162,10 @@ fn init_wgpu_internal(
 
     surface.configure(&device, &config);
 
+    let requested_samples = RENDER_SETTINGS.lock().map(|s| s.msaa_samples).unwrap_or(1);
+    let sample_count = render_settings::adapter_sample_count(&adapter, config.format, requested_samples);
+    let msaa_view = render_settings::create_msaa_view(&device, &config, sample_count);
+
     // Texture setup
     let (texture_view, sampler) = create_texture(&device, &queue);
 
@@ -619,7 +1210,7 @@ fn init_wgpu_internal(
     // Shader setup
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Shader"),
-        source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("shader.wgsl"))),
+        source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/shader.wgsl"))),
     });
 
     // --- Instance Data Setup ---
@@ -643,69 +1234,36 @@ fn init_wgpu_internal(
                 rotation: 0.0,
                 uv_offset: [0.0, 0.0],
                 uv_scale: [1.0, 1.0],
+                tint: WHITE_TINT,
+                lod: 0,
+                size: [1.0, 1.0],
+                slice_min: [0.0, 0.0],
+                slice_max: [0.0, 0.0],
+                parallax: [1.0, 1.0],
             });
         }
     }
 
+    let caps = GpuCaps::detect(&adapter, &device.limits(), device.features());
+
     let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Instance Buffer"),
         contents: bytemuck::cast_slice(&instances),
-        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        usage: caps.instance_buffer_usage(),
     });
 
     // --- Compute Pipeline Setup ---
-
-    let compute_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        entries: &[wgpu::BindGroupLayoutEntry {
-            binding: 0,
-            visibility: wgpu::ShaderStages::COMPUTE,
-            ty: wgpu::BindingType::Buffer {
-                ty: wgpu::BufferBindingType::Storage { read_only: false },
-                has_dynamic_offset: false,
-                min_binding_size: None,
-            },
-            count: None,
-        }],
-        label: Some("compute_bind_group_layout"),
-    });
-
-    let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout: &compute_bind_group_layout,
-        entries: &[wgpu::BindGroupEntry {
-            binding: 0,
-            resource: instance_buffer.as_entire_binding(),
-        }],
-        label: Some("compute_bind_group"),
-    });
-
-    let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: Some("Compute Pipeline Layout"),
-        bind_group_layouts: &[&compute_bind_group_layout],
-        push_constant_ranges: &[],
-    });
-
-    let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-        label: Some("Compute Pipeline"),
-        layout: Some(&compute_pipeline_layout),
-        module: &shader,
-        entry_point: Some("update_instances"),
-        compilation_options: wgpu::PipelineCompilationOptions::default(),
-        cache: None,
-    });
+    // Only where compute can bind the instance buffer; otherwise instances are
+    // updated purely from the CPU
+    let instance_compute = caps
+        .instance_compute()
+        .then(|| InstanceCompute::new(&device, &shader, &instance_buffer));
+    let gpu_cull = caps.gpu_culling.then(|| GpuCullPass::new(&device, std::mem::size_of::<Instance>() as u64));
 
 
     // --- Camera Setup ---
-    let camera = Camera {
-        eye: na::Point3::new(0.0, 0.0, 5.0),
-        target: na::Point3::new(0.0, 0.0, 0.0),
-        up: na::Vector3::y(),
-        aspect: config.width as f32 / config.height as f32,
-        fovy: 45.0,
-        znear: 0.1,
-        zfar: 100.0,
-        is_orthographic: true,
-        ortho_size: 2.2, // fits -1.1 to 1.1 roughly
-    };
+    let [_, _, view_width, view_height] = safe_area().world_rect(config.width, config.height);
+    let camera = Camera::orthographic(view_width as f32 / view_height.max(1) as f32, &world_scale());
 
     let mut camera_uniform = CameraUniform::new();
     camera_uniform.update_view_proj(&camera);
@@ -746,43 +1304,70 @@ fn init_wgpu_internal(
         push_constant_ranges: &[],
     });
 
-    let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Render Pipeline"),
-        layout: Some(&render_pipeline_layout),
-        vertex: wgpu::VertexState {
-            module: &shader,
-            entry_point: Some("vs_main"),
-            buffers: &[Vertex::desc(), Instance::desc()], // Added Instance buffer layout
-            compilation_options: wgpu::PipelineCompilationOptions::default(),
-        },
-        fragment: Some(wgpu::FragmentState {
-            module: &shader,
-            entry_point: Some("fs_main"),
-            targets: &[Some(wgpu::ColorTargetState {
-                format: config.format,
-                blend: Some(wgpu::BlendState::REPLACE),
-                write_mask: wgpu::ColorWrites::ALL,
-            })],
-            compilation_options: wgpu::PipelineCompilationOptions::default(),
-        }),
-        primitive: wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleList,
-            strip_index_format: None,
-            front_face: wgpu::FrontFace::Ccw,
-            cull_mode: Some(wgpu::Face::Back),
-            polygon_mode: wgpu::PolygonMode::Fill,
-            unclipped_depth: false,
-            conservative: false,
-        },
-        depth_stencil: None,
-        multisample: wgpu::MultisampleState {
-            count: 1,
-            mask: !0,
-            alpha_to_coverage_enabled: false,
-        },
-        multiview: None,
-        cache: None,
-    });
+    let render_pipeline = create_scene_pipeline(&device, &render_pipeline_layout, &shader, config.format, sample_count);
+
+    let bloom = if caps.bloom {
+        Some(BloomPass::new(
+            &device,
+            &shader,
+            &render_pipeline_layout,
+            &[Vertex::desc(), Instance::desc()],
+            config.format,
+            config.width,
+            config.height,
+        ))
+    } else {
+        tracing::info!(target: "init", "Bloom unavailable on this adapter (needs compute + HDR storage textures)");
+        None
+    };
+
+    let contact_shadows = ContactShadowPass::new(
+        &device,
+        include_str!("../shaders/shadow.wgsl"),
+        &camera_bind_group_layout,
+        &[Vertex::desc(), Instance::desc()],
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let water_pass = WaterPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let soft_body_pass = SoftBodyPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let trail_pass = TrailPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let fade_pass = TrailPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let terrain_pass = TrailPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let background_pass = BackgroundPass::new(&device, config.format, sample_count, bloom.as_ref().map(|_| post_process::HDR_FORMAT));
+    let minimap_pass = MinimapPass::new(&device, &camera_bind_group_layout, config.format);
+    let split_cameras = SplitCameras::new(&device, &camera_bind_group_layout);
 
     let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Vertex Buffer"),
@@ -804,13 +1389,13 @@ fn init_wgpu_internal(
 
     let device = Arc::new(device);
     let queue = Arc::new(queue);
+    let text_pass = TextPass::new(&device, &queue, config.format);
     
     let bevy_3d_rend = Bevy3DSample::new(
         &device,
-        &queue,
         &camera_bind_group_layout,
-        &adapter_info,
         config.format,
+        1, // Drawn by the debug pass straight onto the swapchain texture
         width,
         height,
     );
@@ -823,12 +1408,22 @@ fn init_wgpu_internal(
         surface,
         config,
         render_pipeline,
-        compute_pipeline,     // NEW
         vertex_buffer,
         index_buffer,
         instance_buffer,      // NEW
+        instance_capacity: NUM_INSTANCES,
+        instance_buffer_synced: false,
+        materials: MaterialRegistry::new(diffuse_bind_group.clone()),
         diffuse_bind_group,
-        compute_bind_group,   // NEW
+        texture_bind_group_layout,
+        draw_batches: Vec::new(),
+        layer_split: LayerSplit::default(),
+        tile_buffer: None,
+        tile_batches: Vec::new(),
+        tiles_synced: false,
+        instance_compute,
+        gpu_cull,
+        caps,
         num_instances: NUM_INSTANCES, // NEW
         window_ptr: window_ptr_helper,
         #[cfg(target_arch = "wasm32")]
@@ -842,155 +1437,333 @@ fn init_wgpu_internal(
         #[cfg(not(target_arch = "wasm32"))]
         last_fps_log_time: std::time::Instant::now(),
 
-        scale_factor: 1.0,
         egui_renderer: egui_rend,
         camera,
+        camera_offset: CameraOffset::default(),
         camera_uniform,
         camera_buffer,
         camera_bind_group,
         bevy_3d_sample: Some(bevy_3d_rend),
+        sample_count,
+        msaa_view,
+        bloom,
+        contact_shadows,
+        water_pass,
+        soft_body_pass,
+        trail_pass,
+        fade_pass,
+        terrain_pass,
+        background_pass,
+        minimap_pass,
+        split_cameras,
+        capture_target: None,
+        text_pass,
+        render_pipeline_layout,
+        camera_bind_group_layout,
+        #[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+        shader_watcher: ShaderWatcher::from_env(HOT_RELOAD_SHADERS),
+        gpu_timer: GpuTimer::new(&device, &queue),
+        frame_stats: FrameStats::default(),
+        adapter_info,
+        surface_formats: surface_caps.formats,
+        surface_source,
+        adapter,
+        views: ViewSlots::default(),
+        lost_frames: 0,
+        recovery: None,
     };
 
-    #[cfg(target_arch = "wasm32")]
-    {
-        // ... (omitted)
-    }
+    Some(state)
+}
 
-    if let Ok(mut guard) = WGPU_STATE.lock() {
-        guard.0 = Some(state);
-    }
-    INITIALIZED.store(true, Ordering::Relaxed);
-    // Initialize physics simulation
-    init_physics();
-    true
+/// Components of a box entity; also used to respawn entities from snapshots
+fn box_bundle(body: PhysicsBody, pos_x: f32, pos_y: f32, half_extent: f32) -> impl Bundle {
+    (
+        Position2D { x: pos_x, y: pos_y },
+        Velocity2D { x: 0.0, y: 0.0 },
+        Scale(half_extent),
+        Rotation(0.0),
+        body,
+        Groups::default(),
+        AnimatorComponent::default(),
+        // Demo sprite sheet: 4x4 grid, 16 frames, 0.1s duration, looping
+        SpriteSheetComponent::new(4, 4, 16, 0.1, true),
+    )
+}
+
+/// Create a box body, its collider and the ECS entity that owns them
+fn spawn_box(
+    world: &mut World,
+    rigid_body_set: &mut RigidBodySet,
+    collider_set: &mut ColliderSet,
+    pos_x: f32,
+    pos_y: f32,
+    half_extent: f32,
+    body_type: RigidBodyType,
+) -> Entity {
+    // Create cuboid collider (small square for each instance)
+    let collider = ColliderBuilder::cuboid(to_real(half_extent), to_real(half_extent), to_real(half_extent));
+    spawn_shape(world, rigid_body_set, collider_set, [pos_x, pos_y], half_extent, collider, body_type)
+}
+
+/// Like `spawn_box` with any collider shape; the entity is still drawn as a
+/// square of `half_extent`
+fn spawn_shape(
+    world: &mut World,
+    rigid_body_set: &mut RigidBodySet,
+    collider_set: &mut ColliderSet,
+    [pos_x, pos_y]: [f32; 2],
+    half_extent: f32,
+    collider: ColliderBuilder,
+    body_type: RigidBodyType,
+) -> Entity {
+    // Create the rigid body (using 3D with Z=0)
+    let rigid_body = RigidBodyBuilder::new(body_type)
+        .translation(planar([pos_x, pos_y]))
+        .ccd_enabled(body_type.is_dynamic())
+        .build();
+    let rb_handle = rigid_body_set.insert(rigid_body);
+
+    let collider = collider
+        .restitution(0.7)
+        .active_events(ActiveEvents::COLLISION_EVENTS)
+        .build();
+    let coll_handle = collider_set.insert_with_parent(collider, rb_handle, rigid_body_set);
+
+    // Spawn ECS entity with components
+    let body = PhysicsBody {
+        rigid_body_handle: rb_handle,
+        collider_handle: coll_handle,
+    };
+    let entity = world.spawn(box_bundle(body, pos_x, pos_y, half_extent)).id();
+
+    // Spatial queries map colliders back to their entity through user_data
+    collider_set[coll_handle].user_data = entity.to_bits() as u128;
+    entity
 }
 
 /// Initialize physics simulation with ECS entities and Rapier rigid bodies
 fn init_physics() {
-    log::info!("Initializing physics simulation...");
-    
-    let mut world = World::new();
-    let mut rigid_body_set = RigidBodySet::new();
-    let mut collider_set = ColliderSet::new();
-    
-    // Register EventQueue resource
-    world.insert_resource(EventQueue::default());
-    
-    // Grid configuration (must match instance creation)
-    const NUM_INSTANCES: u32 = NUM_INSTANCES_PER_ROW * NUM_INSTANCES_PER_ROW;
-    
-    // Create dynamic rigid bodies for each instance
+    // Default scene: a grid of boxes (must match instance creation at the default scale)
+    let mut positions = Vec::new();
     for y in 0..NUM_INSTANCES_PER_ROW {
         for x in 0..NUM_INSTANCES_PER_ROW {
             let pos_x = (x as f32 / NUM_INSTANCES_PER_ROW as f32) * 2.0 - 1.0 + (1.0 / NUM_INSTANCES_PER_ROW as f32);
             let pos_y = (y as f32 / NUM_INSTANCES_PER_ROW as f32) * 2.0 - 1.0 + (1.0 / NUM_INSTANCES_PER_ROW as f32);
-            
-            // Create dynamic rigid body (using 3D with Z=0)
-            let rigid_body = RigidBodyBuilder::dynamic()
-                .translation(vector![pos_x, pos_y, 0.0])
-                .ccd_enabled(true)
-                .build();
-            let rb_handle = rigid_body_set.insert(rigid_body);
-            
-            // Create cuboid collider (small square for each instance)
-            let collider = ColliderBuilder::cuboid(0.05, 0.05, 0.05)
-                .restitution(0.7)
-                .build();
-            let coll_handle = collider_set.insert_with_parent(collider, rb_handle, &mut rigid_body_set);
-            
-            // Spawn ECS entity with components
-            let mut entity_cmds = world.spawn((
-                Position2D { x: pos_x, y: pos_y },
-                Velocity2D { x: 0.0, y: 0.0 },
-                Scale(0.05),
-                Rotation(0.0),
-                PhysicsBody {
-                    rigid_body_handle: rb_handle,
-                    collider_handle: coll_handle,
-                },
-                AnimatorComponent::default(),
-                // Demo sprite sheet: 4x4 grid, 16 frames, 0.1s duration, looping
-                SpriteSheetComponent::new(4, 4, 16, 0.1, true),
-            ));
-
-            // Make the first entity controllable
-            if x == 0 && y == 0 {
-                entity_cmds.insert(Controllable);
-                // We'd add a Sprite component here if we had a texture handle.
-                // entity_cmds.insert(bevy_sprite::prelude::Sprite::default());  
-            }
+            positions.push([pos_x, pos_y]);
         }
-        
-    // Initialize GameTime resource
     }
-    
-    // Create static wall boundaries (viewport edges: -1 to 1)
-    // Bottom wall
-    let bottom_wall = RigidBodyBuilder::fixed()
-        .translation(vector![0.0, -1.1, 0.0])
-        .build();
-    let bottom_handle = rigid_body_set.insert(bottom_wall);
-    let bottom_collider = ColliderBuilder::cuboid(4.0, 0.01, 0.1).build();
-    collider_set.insert_with_parent(bottom_collider, bottom_handle, &mut rigid_body_set);
-    
-    // Top wall
-    let top_wall = RigidBodyBuilder::fixed()
-        .translation(vector![0.0, 1.1, 0.0])
-        .build();
-    let top_handle = rigid_body_set.insert(top_wall);
-    let top_collider = ColliderBuilder::cuboid(2.0, 0.1, 0.1).build();
-    collider_set.insert_with_parent(top_collider, top_handle, &mut rigid_body_set);
+    init_ndc_scene(&positions, 0.05);
+}
+
+/// `init_physics_scene` for a layout made for the -1..1 view, sized to the
+/// world scale
+fn init_ndc_scene(positions: &[[f32; 2]], half_extent: f32) {
+    let meter = world_scale().meters(1.0);
+    let positions: Vec<[f32; 2]> = positions.iter().map(|&[x, y]| [x * meter, y * meter]).collect();
+    init_physics_scene(&positions, half_extent * meter);
+}
+
+/// Replace the simulation with dynamic boxes at `positions` inside the walls.
+/// The first box is the controllable one.
+fn init_physics_scene(positions: &[[f32; 2]], half_extent: f32) {
+    let _span = tracing::info_span!(target: "init", "init_physics", bodies = positions.len()).entered();
+    tracing::info!(target: "init", "Initializing physics simulation...");
+    // The steps refer to bodies about to go
+    if let Ok(mut history) = HISTORY.lock() {
+        history.clear();
+    }
+
+    let current_gravity = match PHYSICS_STATE.lock() {
+        Ok(mut guard) => {
+            let physics_state = new_physics_state(positions, half_extent, world_scale(), guard.0.take());
+            let gravity = physics_state.gravity;
+            guard.0 = Some(physics_state);
+            gravity
+        }
+        Err(_) => {
+            log::error!("init_physics_scene: Failed to lock PHYSICS_STATE");
+            return;
+        }
+    };
+
+    log::info!("Physics initialized with {} dynamic bodies and 4 static walls (Gravity Preserved: {:?})", positions.len(), current_gravity);
+}
+
+/// Build a scene like `init_physics_scene` without installing it, with the
+/// walls placed for `scale`. Settings such as gravity, time scale and solver
+/// are carried over from `previous`, or start at their defaults without one.
+/// The new scene reuses the emptied world of `previous`, so entity ids from
+/// before stay dead (see `recycle_world`).
+fn new_physics_state(positions: &[[f32; 2]], half_extent: f32, scale: WorldScale, mut previous: Option<PhysicsState>) -> PhysicsState {
+    let mut world = previous.as_mut().map_or_else(World::new, |physics| recycle_world(std::mem::take(&mut physics.world)));
+    let previous = previous.as_ref();
+    let mut rigid_body_set = RigidBodySet::new();
+    let mut collider_set = ColliderSet::new();
+    
+    // Register resources read by the simulation schedule
+    schedule::init_resources(&mut world, scale);
+    
+    // Create dynamic rigid bodies for each instance
+    for (i, &[pos_x, pos_y]) in positions.iter().enumerate() {
+        let entity = spawn_box(&mut world, &mut rigid_body_set, &mut collider_set, pos_x, pos_y, half_extent, RigidBodyType::Dynamic);
+
+        // Make the first entity controllable
+        if i == 0 {
+            world.entity_mut(entity).insert(Controllable);
+            // We'd add a Sprite component here if we had a texture handle.
+            // entity_cmds.insert(bevy_sprite::prelude::Sprite::default());  
+        }
+    }
+    
+    // Create static wall boundaries (viewport edges: -1 to 1 at the default scale)
+    let wall = to_real(scale.wall_position());
+    let meter = to_real(scale.meters(1.0));
+    // Bottom wall
+    let bottom_wall = RigidBodyBuilder::fixed()
+        .translation(vector![0.0, -wall, 0.0])
+        .build();
+    let bottom_handle = rigid_body_set.insert(bottom_wall);
+    let bottom_collider = ColliderBuilder::cuboid(4.0 * meter, 0.01 * meter, 0.1 * meter).build();
+    collider_set.insert_with_parent(bottom_collider, bottom_handle, &mut rigid_body_set);
+    
+    // Top wall
+    let top_wall = RigidBodyBuilder::fixed()
+        .translation(vector![0.0, wall, 0.0])
+        .build();
+    let top_handle = rigid_body_set.insert(top_wall);
+    let top_collider = ColliderBuilder::cuboid(2.0 * meter, 0.1 * meter, 0.1 * meter).build();
+    collider_set.insert_with_parent(top_collider, top_handle, &mut rigid_body_set);
     
     // Left wall
     let left_wall = RigidBodyBuilder::fixed()
-        .translation(vector![-1.1, 0.0, 0.0])
+        .translation(vector![-wall, 0.0, 0.0])
         .build();
     let left_handle = rigid_body_set.insert(left_wall);
-    let left_collider = ColliderBuilder::cuboid(0.1, 2.0, 0.1).build();
+    let left_collider = ColliderBuilder::cuboid(0.1 * meter, 2.0 * meter, 0.1 * meter).build();
     collider_set.insert_with_parent(left_collider, left_handle, &mut rigid_body_set);
     
     // Right wall
     let right_wall = RigidBodyBuilder::fixed()
-        .translation(vector![1.1, 0.0, 0.0])
+        .translation(vector![wall, 0.0, 0.0])
         .build();
     let right_handle = rigid_body_set.insert(right_wall);
-    let right_collider = ColliderBuilder::cuboid(0.1, 2.0, 0.1).build();
+    let right_collider = ColliderBuilder::cuboid(0.1 * meter, 2.0 * meter, 0.1 * meter).build();
     collider_set.insert_with_parent(right_collider, right_handle, &mut rigid_body_set);
     
     // Capture current settings if already initialized
-    let (current_gravity, current_time_scale, current_paused) = if let Ok(guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = &guard.0 {
-            (physics.gravity, physics.time_scale, physics.paused)
-        } else {
-            (vector![0.0, -9.81, 0.0], 1.0, false)
-        }
-    } else {
-        (vector![0.0, -9.81, 0.0], 1.0, false)
+    let (current_gravity, current_time_scale, current_paused, current_sleep_enabled, current_solver, current_substeps) = match previous {
+        Some(physics) => (physics.gravity, physics.time_scale, physics.paused, physics.sleep_enabled, physics.solver, physics.substep_config),
+        None => (planar([0.0, -units::DEFAULT_GRAVITY]), 1.0, false, true, SolverConfig::default(), SubstepConfig::default()),
     };
+    let current_animation_paused = previous.is_some_and(|physics| physics.animation_paused);
+    let resets = previous.map_or(0, |physics| physics.resets + 1);
+    apply_sleep_setting(&mut rigid_body_set, current_sleep_enabled);
+
+    // Streaming settings survive a reset; the new streamer starts with no active chunks
+    let (current_stream_config, current_stream_focus) = previous.map_or((None, StreamFocus::default()), |physics| {
+        (physics.streamer.as_ref().map(|s| s.config()), physics.stream_focus)
+    });
+    let current_lod = previous.map_or_else(LodConfig::default, |physics| physics.lod);
+
+    let current_force_fields = previous.map_or_else(ForceFieldConfig::default, |physics| physics.force_fields.clone());
+
+    let current_time_dilation = previous.map_or_else(TimeDilationConfig::default, |physics| physics.time_dilation.clone());
+
+    // Water regions survive a reset; their waves calm down
+    let current_water = previous.map_or_else(WaterConfig::default, |physics| physics.water.config());
 
-    // Create physics state
-    let physics_state = PhysicsState {
+    let current_despawn_bounds = previous.and_then(|physics| physics.despawn_bounds);
+
+    // Audio settings survive a reset; queued events do not
+    let current_audio_config = previous.map_or_else(AudioConfig::default, |physics| physics.audio_config);
+
+    let schedule = schedule::build_simulation_schedule(&mut world);
+
+    // The rewind history length survives a reset; the old frames do not
+    let current_history = previous.map_or(snapshot::DEFAULT_HISTORY, |physics| physics.history.capacity());
+    // So do the rollback window and tolerance
+    let current_prediction = previous.map(|physics| (physics.prediction.window(), physics.prediction.tolerance()));
+
+    PhysicsState {
         world,
         rigid_body_set,
         collider_set,
         integration_parameters: IntegrationParameters::default(),
+        solver: current_solver,
+        substep_config: current_substeps,
+        current_substeps: 1,
         physics_pipeline: PhysicsPipeline::new(),
         island_manager: IslandManager::new(),
         broad_phase: DefaultBroadPhase::new(),
         narrow_phase: NarrowPhase::new(),
         impulse_joint_set: ImpulseJointSet::new(),
         multibody_joint_set: MultibodyJointSet::new(),
+        walls: vec![bottom_handle, top_handle, left_handle, right_handle],
+        level_body: None,
+        tilemaps: Vec::new(),
+        terrain_vertices: Vec::new(),
+        tilemaps_dirty: false,
+        spring_anchor: None,
+        projectiles: None,
         ccd_solver: CCDSolver::new(),
+        surface_velocities: SurfaceVelocities::new(),
+        contact_filter: ContactFilter::default(),
+        force_fields: current_force_fields,
+        time_dilation: current_time_dilation,
+        water: Water::new(current_water),
+        soft_bodies: SoftBodies::default(),
+        trails: Trails::default(),
+        trajectory_preview: Vec::new(),
+        despawn_bounds: current_despawn_bounds,
+        audio_config: current_audio_config,
+        audio_events: AudioQueue::new(),
+        fracture_events: FractureQueue::new(),
+        triggers: Triggers::default(),
+        query_pipeline: QueryPipeline::new(),
         gravity: current_gravity,
         paused: current_paused,
+        animation_paused: current_animation_paused,
         time_scale: current_time_scale,
-    };
-    
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        guard.0 = Some(physics_state);
+        sleep_enabled: current_sleep_enabled,
+        streamer: current_stream_config.map(ChunkStreamer::new),
+        stream_focus: current_stream_focus,
+        camera_target: previous.map_or([0.0, 0.0], |physics| physics.camera_target),
+        lod: current_lod,
+        lod_far_bodies: Default::default(),
+        power_throttle: Throttle::default(),
+        aggressive_sleep_applied: false,
+        step_count: 0,
+        instance_staging: InstanceStaging::new(),
+        instance_batches: Vec::new(),
+        instance_layers: LayerSplit::default(),
+        instance_layout_dirty: true,
+        schedule,
+        history: snapshot::SnapshotRing::new(current_history),
+        time_ramp: None,
+        queued_steps: 0,
+        resets,
+        reset_unreported: previous.is_some(),
+        persistent_renames: Vec::new(),
+        net: netcode::NetSync::default(),
+        prediction: current_prediction
+            .and_then(|(window, tolerance)| prediction::Prediction::new(window, tolerance))
+            .unwrap_or_default(),
     }
-    
-    log::info!("Physics initialized with {} dynamic bodies and 4 static walls (Gravity Preserved: {:?})", NUM_INSTANCES, current_gravity);
+}
+
+/// Empty a scene's world for the next scene. Entity ids pack the index and
+/// generation of a bevy `Entity`; despawning bumps the generation of every
+/// index, so an id from the old scene never resolves to an entity of the new
+/// one the way it could in a brand new world. Resources go too; the new scene
+/// inserts its own.
+fn recycle_world(mut world: World) -> World {
+    let entities: Vec<Entity> = world.iter_entities().map(|entity| entity.id()).collect();
+    for entity in entities {
+        world.despawn(entity);
+    }
+    world.clear_resources();
+    world
 }
 
 fn resize_internal(width: u32, height: u32) {
@@ -1004,65 +1777,103 @@ fn resize_internal(width: u32, height: u32) {
                 state.config.width = width;
                 state.config.height = height;
                 state.surface.configure(&state.device, &state.config);
+                state.msaa_view = render_settings::create_msaa_view(&state.device, &state.config, state.sample_count);
+                if let Some(bloom) = state.bloom.as_mut() {
+                    bloom.resize(&state.device, width, height);
+                }
+                if let Some(bevy_3d) = state.bevy_3d_sample.as_mut() {
+                    bevy_3d.resize(&state.device, width, height);
+                }
+                fit_camera_to_world_rect(state);
                 log::info!("Resized surface to {}x{}", width, height);
             }
         }
     }
 }
 
+/// Open an extra view of the scene on `source`. Returns its id, or None
+/// before init, for an invalid size or camera, a surface that cannot take
+/// the main surface's format and when `views::MAX_VIEWS` are open.
+fn add_view_internal(source: SurfaceSource, width: u32, height: u32, camera: ViewCamera) -> Option<u32> {
+    let mut guard = WGPU_STATE.lock().ok()?;
+    let state = guard.0.as_mut()?;
+    if state.views.len() >= views::MAX_VIEWS {
+        log::warn!("add_view: {} views already open", views::MAX_VIEWS);
+        return None;
+    }
+    match SurfaceView::new(state, source, width, height, camera) {
+        Ok(view) => state.views.insert(view),
+        Err(e) => {
+            log::warn!("add_view: {}", e);
+            None
+        }
+    }
+}
 
-fn input_system(world: &mut World, rigid_body_set: &mut RigidBodySet) {
-    let mut impulses = Vec::new();
+fn remove_view_internal(view_id: u32) -> bool {
+    match WGPU_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().is_some_and(|state| state.views.remove(view_id).is_some()),
+        Err(_) => false,
+    }
+}
 
-    // scope to read events
-    {
-        let events = world.resource::<EventQueue>();
-        for event in &events.events {
-            // Log for debugging
-            // log::info!("Processing Event: {:?}", event);
-            
-            if let InputEventType::KeyDown = event.event_type {
-                if let Some(code) = event.key_code {
-                    // Simple mapping demo: 
-                    // 19 (DPAD_UP/W), 20 (DPAD_DOWN/S), 21 (DPAD_LEFT/A), 22 (DPAD_RIGHT/D)
-                    // Just applying random impulse for any key for demo if code unknown
-                    let x = match code {
-                        21 => -0.5, // Left
-                        22 => 0.5,  // Right
-                        _ => 0.0,
-                    };
-                    let y = match code {
-                        19 => 0.5,  // Up
-                        20 => -0.5, // Down
-                        _ => 0.0,
-                    };
-                    
-                    if x != 0.0 || y != 0.0 {
-                        impulses.push(vector![x, y, 0.0]);
-                    } else {
-                        // Default demo impulse for other keys (Jump)
-                        // impulses.push(vector![0.0, 5.0, 0.0]);
-                    }
-                }
-            } else if let InputEventType::PointerDown = event.event_type {
-                 // Mouse click / Touch -> Jump
-                 impulses.push(vector![0.0, 5.0, 0.0]);
-            }
+/// Returns false for unknown views and zero sizes
+fn resize_view_internal(view_id: u32, width: u32, height: u32) -> bool {
+    if width == 0 || height == 0 {
+        return false;
+    }
+    let Ok(mut guard) = WGPU_STATE.lock() else {
+        return false;
+    };
+    let Some(state) = guard.0.as_mut() else {
+        return false;
+    };
+    let WgpuState { device, queue, views, sample_count, .. } = state;
+    match views.get_mut(view_id) {
+        Some(view) => {
+            view.resize(device, queue, width, height, *sample_count);
+            true
         }
+        None => false,
     }
-    
-    // Apply to controllable entities
-    for impulse in impulses {
-        for (_entity, physics_body, _control) in world.query::<(Entity, &PhysicsBody, &Controllable)>().iter(world) {
-            if let Some(rb) = rigid_body_set.get_mut(physics_body.rigid_body_handle) {
-                rb.apply_impulse(impulse, true);
-                log::info!("Applied impulse {:?} to Controllable Entity", impulse);
-            }
+}
+
+/// Returns false for unknown views and invalid cameras
+fn set_view_camera_internal(view_id: u32, camera: ViewCamera) -> bool {
+    if !camera.is_valid() {
+        return false;
+    }
+    let Ok(mut guard) = WGPU_STATE.lock() else {
+        return false;
+    };
+    let Some(state) = guard.0.as_mut() else {
+        return false;
+    };
+    match state.views.get_mut(view_id) {
+        Some(view) => {
+            view.set_camera(&state.queue, camera);
+            true
         }
+        None => false,
     }
 }
 
-fn update_internal(_dt: f32) {
+fn update_internal(dt: f32) {
+    // Nothing runs while paused with physics frozen
+    let Some(dt) = LIFECYCLE.lock().map_or(Some(dt), |mut lifecycle| lifecycle.frame_dt(dt)) else {
+        return;
+    };
+    // While capturing, every update is exactly one clip frame
+    let dt = capture::fixed_step().unwrap_or(dt);
+
+    // Hosts hear of a reset once the new scene is complete, whoever built it
+    let reset = PHYSICS_STATE
+        .lock()
+        .is_ok_and(|mut guard| guard.0.as_mut().is_some_and(|physics| std::mem::take(&mut physics.reset_unreported)));
+    if reset {
+        plugins::run_hooks(plugins::HookPoint::Reset, 0.0);
+    }
+
     // Flush input events to ECS EventQueue
     if let Ok(mut guard) = INPUT_STATE.lock() {
         if !guard.events.is_empty() {
@@ -1078,1350 +1889,11321 @@ fn update_internal(_dt: f32) {
         }
     }
 
-    // Step physics simulation
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            if physics.paused {
+    // The governor moves on by real time, even while paused
+    let throttle = update_governor(dt);
+
+    // Scaled step length (None while paused) plus any queued single steps
+    let (dt, queued_steps) = match PHYSICS_STATE.lock() {
+        Ok(mut guard) => match guard.0.as_mut() {
+            Some(physics) => {
+                physics.power_throttle = throttle;
+                physics.water.set_droplet_scale(throttle.particle_scale);
+                begin_frame(physics, dt)
+            }
+            None => {
+                log::warn!("update_internal: PHYSICS_STATE is None");
                 return;
             }
+        },
+        Err(_) => {
+            log::error!("update_internal: Failed to lock PHYSICS_STATE");
+            return;
+        }
+    };
 
-            // Apply time scale to integration parameters
-            physics.integration_parameters.dt = _dt * physics.time_scale;
+    for _ in 0..queued_steps {
+        advance_simulation(time_control::STEP_DT);
+    }
+    if let Some(dt) = dt {
+        advance_simulation(dt);
+    }
+    if queued_steps == 0 && dt.is_none() {
+        advance_idle_frame();
+    }
+    publish_transforms();
+}
 
+/// The frame of a paused simulation that steps nothing: input, animation and
+/// component sync still run
+fn advance_idle_frame() {
+    let mut camera_tween = CameraTweenValues::default();
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            camera_tween = idle_frame(physics);
+        }
+    }
+    if camera_tween != CameraTweenValues::default() {
+        apply_camera_tween(camera_tween);
+    }
+}
 
-        // Run Bevy Animation/Sprite sample systems
-        // Note: Constructing SystemState every frame is inefficient; ideally this should be cached.
-        // Run Bevy Animation/Sprite sample systems
-        // Note: Constructing SystemState every frame is inefficient; ideally this should be cached.
-        
-            
-            // Process Inputs
-            input_system(&mut physics.world, &mut physics.rigid_body_set);
+/// Run the schedule without a step, animating by the scaled frame time.
+/// Returns the camera tween values for the caller to apply unlocked.
+fn idle_frame(physics: &mut PhysicsState) -> CameraTweenValues {
+    let time = physics.world.resource::<time_control::Time>();
+    let dt = time.real_delta * time.time_scale;
+    schedule::run_idle_frame(physics, dt);
+    physics.world.resource_mut::<Tweens>().take_camera()
+}
 
-            // Run Animation System
-            {
-                let mut system_state = SystemState::<Query<(&mut AnimatorComponent, &SpriteSheetComponent)>>::new(&mut physics.world);
-                let query = system_state.get_mut(&mut physics.world);
-                animation::animation_system(query, physics.integration_parameters.dt);
-            }
-
-            
-            // Clear processed events
-            if let Some(mut events) = physics.world.get_resource_mut::<EventQueue>() {
-                events.clear();
-            }
-
-            // Step the physics simulation
-            physics.physics_pipeline.step(
-                &physics.gravity,
-                &physics.integration_parameters,
-                &mut physics.island_manager,
-                &mut physics.broad_phase,
-                &mut physics.narrow_phase,
-                &mut physics.rigid_body_set,
-                &mut physics.collider_set,
-                &mut physics.impulse_joint_set,
-                &mut physics.multibody_joint_set,
-                &mut physics.ccd_solver,
-                None, // query_pipeline
-                &(), // physics_hooks
-                &(), // event_handler
-            );
-            
-            // Update ECS component positions from Rapier rigid bodies
-            for (entity, physics_body) in physics.world.query::<(Entity, &PhysicsBody)>().iter(&physics.world) {
-                if let Some(rb) = physics.rigid_body_set.get(physics_body.rigid_body_handle) {
-                    let translation = rb.translation();
-                    let new_pos = Position2D { x: translation.x, y: translation.y };
-                    let new_vel = Velocity2D { x: rb.linvel().x, y: rb.linvel().y };
-                    
-                    // Update entity components (need mutable world access)
-                    // We'll handle this in sync_physics_to_gpu instead
-                    let _ = (entity, new_pos, new_vel); // Suppress warnings for now
-                }
+/// Start a frame of `real_dt` seconds on the clock, advancing any time-scale
+/// ramp. Returns the scaled length of the frame's step (None while paused)
+/// and the queued single steps of `time_control::STEP_DT` to run first.
+fn begin_frame(physics: &mut PhysicsState, real_dt: f32) -> (Option<f32>, u32) {
+    let queued_steps = std::mem::take(&mut physics.queued_steps);
+    if !physics.paused {
+        if let Some(ramp) = physics.time_ramp.as_mut() {
+            physics.time_scale = ramp.advance(real_dt);
+            if ramp.is_done() {
+                physics.time_ramp = None;
             }
-        } else {
-            log::warn!("update_internal: PHYSICS_STATE is None");
         }
+    }
+    // Once per frame; every step run after it adds its own delta
+    let (time_scale, paused, animation_paused) = (physics.time_scale, physics.paused, physics.animation_paused);
+    let mut time = physics.world.resource_mut::<time_control::Time>();
+    time.begin_frame(real_dt, time_scale, paused);
+    time.animation_paused = animation_paused;
+    // The clock counts a negative or NaN frame time as 0; so does the step
+    let real_dt = time.real_delta;
+    if paused {
+        (None, queued_steps)
     } else {
-        log::error!("update_internal: Failed to lock PHYSICS_STATE");
+        (Some(real_dt * time_scale), queued_steps)
     }
 }
 
-/// Sync physics positions to the GPU instance buffer
-fn sync_physics_to_gpu() {
-    // Collect updated instance data from physics
-    let instances: Vec<Instance> = {
-        let mut guard = match PHYSICS_STATE.lock() {
-            Ok(g) => g,
-            Err(_) => return,
-        };
-        
-        let physics = match guard.0.as_mut() {
-            Some(p) => p,
-            None => return,
-        };
-        
-        let mut instances = Vec::new();
-        for (_entity, physics_body, animator, sprite_sheet) in physics.world.query::<(Entity, &PhysicsBody, Option<&AnimatorComponent>, Option<&SpriteSheetComponent>)>().iter(&physics.world) {
-            if let Some(rb) = physics.rigid_body_set.get(physics_body.rigid_body_handle) {
-                let translation = rb.translation();
-                let rotation = rb.rotation().angle(); // Get rotation angle around Z axis
-                
-                // Calculate UVs based on animation state
-                let (uv_offset, uv_scale) = if let (Some(anim), Some(sheet)) = (animator, sprite_sheet) {
-                    let (u, v, w, h) = sheet.uv_for_frame(anim.current_frame);
-                    ([u, v], [w, h])
-                } else {
-                    ([0.0, 0.0], [1.0, 1.0])
-                };
+/// (Re)allocate the shared transform region with room for `capacity` bodies
+/// and fill it with the current frame; 0 frees it. Returns the region's start
+/// and length, valid until the next call. None when disabled or for a
+/// capacity above `transform_export::MAX_CAPACITY`.
+fn enable_transform_export_internal(capacity: u32) -> Option<(*const u8, usize)> {
+    let export = TransformExport::new(capacity);
+    if export.is_none() && capacity != 0 {
+        log::warn!("enable_transform_export: invalid capacity {}", capacity);
+    }
+    *TRANSFORM_EXPORT.lock().ok()? = export;
+    publish_transforms();
+    transform_export_region_internal()
+}
 
-                instances.push(Instance {
-                    position: [translation.x, translation.y],
-                    velocity: [rb.linvel().x, rb.linvel().y],
-                    scale: 0.05, // Fixed scale for now
-                    rotation,
-                    uv_offset,
-                    uv_scale,
-                });
+/// Start and length of the shared transform region, if enabled
+fn transform_export_region_internal() -> Option<(*const u8, usize)> {
+    let export = TRANSFORM_EXPORT.lock().ok()?;
+    export.as_ref().map(|export| (export.as_ptr(), export.len()))
+}
+
+/// Copy every body's transform into the shared region, if a host enabled it
+fn publish_transforms() {
+    let enabled = TRANSFORM_EXPORT.lock().is_ok_and(|export| export.is_some());
+    if !enabled {
+        return;
+    }
+    let transforms: Vec<ExportedTransform> = match PHYSICS_STATE.lock() {
+        Ok(mut guard) => match guard.0.as_mut() {
+            Some(physics) => {
+                let PhysicsState { world, rigid_body_set, .. } = physics;
+                world
+                    .query::<(Entity, &PhysicsBody, Option<&Groups>)>()
+                    .iter(world)
+                    .filter_map(|(entity, body, groups)| {
+                        let rb = rigid_body_set.get(body.rigid_body_handle)?;
+                        Some(ExportedTransform {
+                            entity_id: entity.to_bits(),
+                            x: to_f32(rb.translation().x),
+                            y: to_f32(rb.translation().y),
+                            rotation: to_f32(rb.rotation().euler_angles().2),
+                            vx: to_f32(rb.linvel().x),
+                            vy: to_f32(rb.linvel().y),
+                            groups: groups.copied().unwrap_or_default().0,
+                        })
+                    })
+                    .collect()
             }
-        }
-        instances
+            None => Vec::new(),
+        },
+        Err(_) => return,
     };
-    
-    // Write to GPU buffer
-    if let Ok(guard) = WGPU_STATE.lock() {
-        if let Some(state) = &guard.0 {
-            state.queue.write_buffer(
-                &state.instance_buffer,
-                0,
-                bytemuck::cast_slice(&instances),
-            );
+    if let Ok(mut export) = TRANSFORM_EXPORT.lock() {
+        if let Some(export) = export.as_mut() {
+            export.publish(&transforms);
         }
     }
 }
 
-fn render_internal(window: Option<&winit::window::Window>) {
-    // log::info!("render_internal called");
-
-    static FPS_CAP_MS: f64 = 12.0f64; // Approx 60 FPS.  16.6ms = 1000/60. Allow slight tolerance?
+/// Run one physics step of length `dt`, ignoring pause
+fn advance_simulation(dt: f32) {
+    // Host hooks run unlocked so they can call back into the API
+    plugins::run_hooks(plugins::HookPoint::PrePhysics, dt);
 
-    // Throttling Logic (60 FPS Cap)
-    if let Ok(mut guard) = WGPU_STATE.lock() {
-        if let Some(state) = guard.0.as_mut() {
-             #[cfg(target_arch = "wasm32")]
-             {
-                 let now = web_sys::window().unwrap().performance().unwrap().now();
-                 let elapsed = now - state.last_render_time;
-                 
-                 if elapsed < FPS_CAP_MS {
-                     return;
-                 }
-                 state.last_render_time = now;
-             }
+    let mut strongest_impact = 0.0f32;
+    let mut camera_tween = CameraTweenValues::default();
 
-             #[cfg(not(target_arch = "wasm32"))]
-             {
-                 let now = std::time::Instant::now();
-                 let elapsed = now.duration_since(state.last_render_time);
-                 if elapsed.as_millis() < FPS_CAP_MS as u128 {
-                     return;
-                 }
-                 state.last_render_time = now;
-             }
+    // Step physics simulation
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            (strongest_impact, camera_tween) = simulate_step(physics, dt);
         }
     }
 
-    // Sync physics to GPU FIRST (before acquiring swapchain texture)
-    // This avoids acquiring a texture and then dropping it without presenting.
-    sync_physics_to_gpu();
-    
-    // Now acquire texture and render in a single lock session
-    if let Ok(mut guard) = WGPU_STATE.lock() {
-        if let Some(state) = guard.0.as_mut() {
-            let output = match state.surface.get_current_texture() {
-                Ok(o) => o,
-                Err(e) => {
-                    log::warn!("Failed to get current texture: {:?}", e);
-                    match e {
-                        wgpu::SurfaceError::Lost | wgpu::SurfaceError::OutOfMemory => {
-                            log::error!("Surface lost or out of memory, resetting WGPU_STATE");
-                            guard.0 = None;
-                            INITIALIZED.store(false, Ordering::Relaxed);
-                        }
-                        wgpu::SurfaceError::Timeout => {
-                            // On timeout, try to reconfigure the surface
-                            log::warn!("Surface timeout, reconfiguring surface");
-                            state.surface.configure(&state.device, &state.config);
-                        }
-                        _ => {}
-                    }
-                    return;
-                }
-            };
+    // The camera lives with the renderer, so tweened values are applied unlocked
+    if camera_tween != CameraTweenValues::default() {
+        apply_camera_tween(camera_tween);
+    }
 
-            let view = output
-                .texture
-                .create_view(&wgpu::TextureViewDescriptor::default());
+    // The hardest hit of the step shakes the camera, if impact shake is on
+    if strongest_impact > 0.0 {
+        if let Ok(mut effects) = CAMERA_EFFECTS.lock() {
+            effects.0.on_impact(strongest_impact);
+        }
+    }
 
-            let mut encoder = state.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
+    plugins::run_hooks(plugins::HookPoint::PostPhysics, dt);
+}
 
-            // --- Compute Encoder  ---
-            {
-                let mut encoder = state.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Compute Encoder"),
-                });
-                {
-                    let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                        label: Some("Compute Pass"),
-                        timestamp_writes: None,
-                    });
-                    compute_pass.set_pipeline(&state.compute_pipeline);
-                    compute_pass.set_bind_group(0, &state.compute_bind_group, &[]);
-                    compute_pass.dispatch_workgroups(2, 1, 1);
-                }
-                state.queue.submit(std::iter::once(encoder.finish()));
-            }
+/// One step of the locked simulation, also used to re-simulate after a
+/// rollback. Returns the hardest impact of the step and the camera tween
+/// values, which the caller applies once the lock is released.
+fn simulate_step(physics: &mut PhysicsState, dt: f32) -> (f32, CameraTweenValues) {
+    let _span = tracing::debug_span!(target: "physics", "step", dt).entered();
+    // Split the step so fast bodies cannot skip through thin colliders
+    physics.current_substeps = physics.substep_config.substeps_for(max_body_speed(physics));
+    physics.integration_parameters.dt = to_real(dt) / physics.current_substeps as Real;
+    let mut solver = physics.solver;
+    solver.solver_iterations = physics.power_throttle.solver_iterations(solver.solver_iterations);
+    solver.apply(&mut physics.integration_parameters);
+
+    update_streaming(physics);
+    update_sleep_thresholds(physics);
+    update_lod(physics);
+    netcode::update(physics);
+    // The step consumes its input, so keep a copy for re-simulation
+    let inputs = physics.prediction.is_enabled().then(|| physics.world.resource::<EventQueue>().events.clone());
+
+    // Input, movement, physics step, animation and component sync
+    schedule::run_simulation_frame(physics, dt);
+    audio::collect(physics);
+    fracture::update(physics);
+    triggers::update(physics);
+    let strongest_impact = physics
+        .world
+        .resource::<schedule::CollisionEvents>()
+        .impacts
+        .iter()
+        .map(|impact| impact.impulse)
+        .fold(0.0, f32::max);
+    projectiles::update(physics, dt);
+    physics.trails.record(&physics.world, &physics.rigid_body_set);
+    let camera_tween = physics.world.resource_mut::<Tweens>().take_camera();
+    physics.step_count += 1;
+
+    if physics.history.capacity() > 0 {
+        match snapshot::capture(physics) {
+            Ok(bytes) => physics.history.push(snapshot::Recorded { dt, bytes }),
+            Err(e) => tracing::warn!(target: "physics", "Snapshot failed: {}", e),
+        }
+    }
+    if let Some(inputs) = inputs {
+        prediction::record(physics, dt, inputs);
+    }
+    (strongest_impact, camera_tween)
+}
 
+/// Speed of the fastest awake dynamic body, in m/s
+fn max_body_speed(physics: &PhysicsState) -> f32 {
+    physics
+        .island_manager
+        .active_dynamic_bodies()
+        .iter()
+        .filter_map(|&handle| physics.rigid_body_set.get(handle))
+        .map(|rb| to_f32(rb.linvel().norm()))
+        .fold(0.0, f32::max)
+}
 
-            // --- Render Encoder ---
-            {
-                {
-                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                        label: Some("Render Pass"),
-                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                            view: &view,
-                            resolve_target: None,
-                            ops: wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(wgpu::Color {
-                                    r: 1.0, 
-                                    g: 1.0,
-                                    b: 225.0/255.0,
-                                    a: 1.0,
-                                }),
-                                store: wgpu::StoreOp::Store,
-                            },
-                            depth_slice: None,
-                        })],
-                        depth_stencil_attachment: None,
-                        timestamp_writes: None,
-                        occlusion_query_set: None,
-                    });
+/// Gather instance data for every body and decoration, sorted by layer and
+/// grouped by material so each texture is bound once per layer. Used to
+/// (re)build the staging layout.
+fn collect_instances(physics: &mut PhysicsState) -> (Vec<(InstanceKey, Instance)>, Vec<DrawBatch>, LayerSplit) {
+    let default_size = physics.world.resource::<WorldScale>().box_half_extent();
+    let parallax = physics.world.resource::<LayerParallax>().clone();
+    let mut instances = Vec::new();
+    let mut drawn = physics
+        .world
+        .query_filtered::<(
+            Entity,
+            Option<&PhysicsBody>,
+            Has<Attached>,
+            (&Position2D, &Rotation),
+            Option<&AnimatorComponent>,
+            Option<&SpriteSheetComponent>,
+            Option<&MaterialComponent>,
+            Option<&Scale>,
+            Option<&Appearance>,
+            (Option<&SpriteRegion>, Option<&Layer>),
+        ), Or<(With<PhysicsBody>, With<Decoration>)>>();
+    for (entity, physics_body, attached, (position, rotation), animator, sprite_sheet, material, scale, appearance, (region, layer)) in
+        drawn.iter(&physics.world)
+    {
+        let rb = physics_body.and_then(|body| physics.rigid_body_set.get(body.rigid_body_handle));
+        if physics_body.is_some() && rb.is_none() {
+            continue;
+        }
+        // Attached entities and decorations are placed by their components
+        let (key, position, velocity, rotation) = match (physics_body, rb) {
+            (Some(body), Some(rb)) if !attached => (
+                InstanceKey::Body(body.rigid_body_handle),
+                xy(rb.translation()),
+                xy(rb.linvel()),
+                to_f32(rb.rotation().angle()), // Get rotation angle around Z axis
+            ),
+            _ => (
+                InstanceKey::Entity(entity),
+                [position.x, position.y],
+                rb.map_or([0.0, 0.0], |rb| xy(rb.linvel())),
+                rotation.0,
+            ),
+        };
 
-                    render_pass.set_pipeline(&state.render_pipeline);
-                    render_pass.set_bind_group(0, &state.diffuse_bind_group, &[]);
-                    render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
-                    
-                    render_pass.set_vertex_buffer(0, state.vertex_buffer.slice(..));
-                    render_pass.set_vertex_buffer(1, state.instance_buffer.slice(..));
-                    
-                    render_pass.set_index_buffer(state.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                    
-                    render_pass.draw_indexed(0..INDICES.len() as u32, 0, 0..state.num_instances);
-
-                    // Render Bevy 3DSample (Cube)
-                    if let Some(bevy_3d) = state.bevy_3d_sample.as_mut() {
-                        // Update with dt (assuming ~60fps if we don't have a precise dt here, 
-                        // but let's try to calculate it from elapsed)
-                        #[cfg(target_arch = "wasm32")]
-                        let dt = 0.016; // Default to 60fps for WASM simple update
-                        #[cfg(not(target_arch = "wasm32"))]
-                        let dt = 0.016; // Use fixed 0.016 for now to avoid complexity in this pass
-
-                        bevy_3d.update(&state.queue, dt);
-                        bevy_3d.set_camera_bind_group(state.camera_bind_group.clone()); // Ensure it's using the current camera BG
-                        bevy_3d.render(&mut render_pass);
-                    }
-                }
+        // Calculate UVs based on animation state; a region replaces the frames
+        let (uv_offset, uv_scale) = if let Some(region) = region {
+            region.uv()
+        } else if let (Some(anim), Some(sheet)) = (animator, sprite_sheet) {
+            let (u, v, w, h) = sheet.uv_for_frame(anim.current_frame);
+            ([u, v], [w, h])
+        } else {
+            ([0.0, 0.0], [1.0, 1.0])
+        };
+        let region = region.copied().unwrap_or_default();
+        let (slice_min, slice_max) = region.slices();
+
+        let material_id = material.map_or(material::DEFAULT_MATERIAL, |m| m.material_id);
+        let appearance = appearance.copied().unwrap_or_default();
+        let layer = layer.copied().unwrap_or_default();
+        instances.push((DrawKey { layer, material_id, entity }, (key, Instance {
+            position,
+            velocity,
+            scale: scale.map_or(default_size, |s| s.0) * appearance.scale,
+            rotation,
+            uv_offset,
+            uv_scale,
+            tint: appearance.packed_tint(),
+            lod: 0,
+            size: region.size,
+            slice_min,
+            slice_max,
+            parallax: parallax.factor(layer),
+        })));
+    }
 
+    let (batches, split) = layers::sort_into_batches(&mut instances);
+    let instances = instances.into_iter().map(|(_, instance)| instance).collect();
+    (instances, batches, split)
+}
 
-                let screen_descriptor = ScreenDescriptor {
-                    size_in_pixels: [state.config.width, state.config.height],
-                    pixels_per_point: state.scale_factor * 1.5, // Scale up UI (1.5x)
-                };
+/// Slot key of a drawn entity; see `InstanceKey`
+fn instance_key(entity: Entity, body: Option<&PhysicsBody>, attached: bool) -> InstanceKey {
+    match body {
+        Some(body) if !attached => InstanceKey::Body(body.rigid_body_handle),
+        _ => InstanceKey::Entity(entity),
+    }
+}
 
-                state.egui_renderer.as_mut().map(|egui_rend| {
-                    let ctx = egui_rend.context();
-
-                    // Apply light theme with yellow background and black text
-                    let mut visuals = egui::Visuals::light();
-                    visuals.window_fill = egui::Color32::from_rgb(255, 255, 224); // Light Yellow
-                    visuals.override_text_color = Some(egui::Color32::BLACK);
-                    ctx.set_visuals(visuals);
-
-                    // Make text bold for extra "darkness" and clarity
-                    let mut style: egui::Style = (*ctx.style()).clone();
-                    style.text_styles.insert(
-                        egui::TextStyle::Heading,
-                        egui::FontId::new(20.0, egui::FontFamily::Proportional),
-                    );
-                    style.text_styles.insert(
-                        egui::TextStyle::Body,
-                        egui::FontId::new(14.0, egui::FontFamily::Proportional),
-                    );
-                    ctx.set_style(style);
-
-                    egui_rend.begin_frame(window.unwrap());
-
-                    egui::Window::new("Physics Controls")
-                        .resizable(true)
-                        .default_width(450.0)
-                        .show(egui_rend.context(), |ui| {
-                            ui.vertical_centered(|ui| {
-                                ui.heading("Physics Controls");
-                            });
+/// Bring the staging array up to date. After a structural change the layout
+/// is rebuilt; otherwise only Rapier's active bodies, attached entities and
+/// animated UVs are touched, and unchanged slots stay clean.
+fn refresh_instance_staging(physics: &mut PhysicsState) {
+    if physics.instance_layout_dirty {
+        let (instances, batches, split) = collect_instances(physics);
+        physics.instance_staging.rebuild(instances);
+        physics.instance_batches = batches;
+        physics.instance_layers = split;
+        physics.instance_layout_dirty = false;
+        return;
+    }
 
-                            ui.separator();
+    let default_size = physics.world.resource::<WorldScale>().box_half_extent();
+    let PhysicsState { world, rigid_body_set, island_manager, instance_staging, .. } = physics;
+    // Kinematic bodies moved by movements and tweens are active too
+    let active = island_manager.active_dynamic_bodies().iter().chain(island_manager.active_kinematic_bodies());
+    for &handle in active {
+        if let Some(rb) = rigid_body_set.get(handle) {
+            instance_staging.update(InstanceKey::Body(handle), |instance| {
+                instance.position = xy(rb.translation());
+                instance.velocity = xy(rb.linvel());
+                instance.rotation = to_f32(rb.rotation().angle());
+            });
+        }
+    }
 
-                            let mut physics_guard = PHYSICS_STATE.lock().unwrap();
-                            if let Some(physics) = physics_guard.0.as_mut() {
-                                // Gravity Slider (Y component)
-                                ui.label(format!("Gravity: {:.1} m/s²", physics.gravity.y.abs()));
-                                let mut g_y = physics.gravity.y.abs();
-                                if ui.add(egui::Slider::new(&mut g_y, 0.0..=20.0)).changed() {
-                                    physics.gravity.y = -g_y;
-                                }
+    // Followers move with their parent whether or not they are simulated
+    for (entity, position, rotation) in world.query_filtered::<(Entity, &Position2D, &Rotation), With<Attached>>().iter(world) {
+        instance_staging.update(InstanceKey::Entity(entity), |instance| {
+            instance.position = [position.x, position.y];
+            instance.rotation = rotation.0;
+        });
+    }
 
-                                ui.add_space(8.0);
+    // Tweened size and tint change whether or not the body moves
+    for (entity, physics_body, attached, scale, appearance) in
+        world.query::<(Entity, Option<&PhysicsBody>, Has<Attached>, Option<&Scale>, &Appearance)>().iter(world)
+    {
+        instance_staging.update(instance_key(entity, physics_body, attached), |instance| {
+            instance.scale = scale.map_or(default_size, |s| s.0) * appearance.scale;
+            instance.tint = appearance.packed_tint();
+        });
+    }
 
-                                // Time Scale Slider
-                                ui.label(format!("Time Scale: {:.1}x", physics.time_scale));
-                                ui.add(egui::Slider::new(&mut physics.time_scale, 0.1..=5.0));
+    // Sprite animation runs whether or not the body moves (sleeping bodies keep animating)
+    for (entity, physics_body, attached, animator, sprite_sheet) in world
+        .query_filtered::<(Entity, Option<&PhysicsBody>, Has<Attached>, &AnimatorComponent, &SpriteSheetComponent), Without<SpriteRegion>>()
+        .iter(world)
+    {
+        let (u, v, w, h) = sprite_sheet.uv_for_frame(animator.current_frame);
+        instance_staging.update(instance_key(entity, physics_body, attached), |instance| {
+            instance.uv_offset = [u, v];
+            instance.uv_scale = [w, h];
+        });
+    }
+}
 
-                                ui.add_space(16.0);
+/// Compute pipeline that integrates instances on the GPU, bound to the instance buffer
+struct InstanceCompute {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
 
-                                // Pause Toggle
-                                ui.checkbox(&mut physics.paused, "Pause Simulation");
+impl InstanceCompute {
+    fn new(device: &wgpu::Device, shader: &wgpu::ShaderModule, instance_buffer: &wgpu::Buffer) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("compute_bind_group_layout"),
+        });
 
-                                ui.add_space(24.0);
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Compute Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
 
-                                // Reset Button
-                                if ui.button("Reset Simulation").clicked() {
-                                    // Drop lock before calling init_physics to avoid deadlock if it locks again
-                                    drop(physics_guard);
-                                    init_physics();
-                                } else {
-                                    // Native Info (at bottom)
-                                    ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
-                                        ui.add_space(8.0);
-                                        ui.small(get_internal_info());
-                                        ui.label("Native Info:");
-                                        ui.separator();
-                                    });
-                                }
-                            } else {
-                                ui.label("Physics state not available");
-                            }
-                        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Compute Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: shader,
+            entry_point: Some("update_instances"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
 
-                        egui_rend.end_frame_and_draw(
-                            &state.device,
-                            &state.queue,
-                            &mut encoder,
-                            window.unwrap(),
-                            &view,
-                            screen_descriptor,
-                        );                    
-                });
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, instance_buffer);
+        Self { pipeline, bind_group_layout, bind_group }
+    }
 
+    fn create_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, instance_buffer: &wgpu::Buffer) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: instance_buffer.as_entire_binding(),
+            }],
+            label: Some("compute_bind_group"),
+        })
+    }
 
-                state.queue.submit(std::iter::once(encoder.finish()));
-            }
+    /// Point the bind group at a new (regrown) instance buffer
+    fn rebind(&mut self, device: &wgpu::Device, instance_buffer: &wgpu::Buffer) {
+        self.bind_group = Self::create_bind_group(device, &self.bind_group_layout, instance_buffer);
+    }
+}
 
-            // Present with panic recovery to handle Vulkan driver issues
-            let present_result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                output.present();
-            }));
-            
-            if present_result.is_err() {
-                log::error!("Present panicked! Reconfiguring surface...");
-                state.surface.configure(&state.device, &state.config);
-                return;
-            }
+/// Recreate the instance buffer (and the compute bind group that aliases it,
+/// if any) when there are more instances than it can hold
+fn ensure_instance_capacity(state: &mut WgpuState, needed: u32) {
+    if needed <= state.instance_capacity {
+        return;
+    }
+    state.instance_buffer_synced = false;
 
-            // FPS Logging
-            state.frame_count += 1;
-            #[cfg(target_arch = "wasm32")]
-            {
-                let now = web_sys::window().unwrap().performance().unwrap().now();
-                let elapsed = (now - state.last_fps_log_time) / 1000.0;
-                if elapsed >= 1.0 {
-                     log::info!("FPS: {:.2}", state.frame_count as f64 / elapsed);
-                     state.frame_count = 0;
-                     state.last_fps_log_time = now;
-                }
-            }
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                let now = std::time::Instant::now();
-                let elapsed = now.duration_since(state.last_fps_log_time).as_secs_f64();
-                if elapsed >= 1.0 {
-                     log::info!("FPS: {:.2}", state.frame_count as f64 / elapsed);
-                     state.frame_count = 0;
-                     state.last_fps_log_time = now;
-                }
-            }
+    let capacity = needed.next_power_of_two();
+    state.instance_buffer = state.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Instance Buffer"),
+        size: capacity as u64 * std::mem::size_of::<Instance>() as u64,
+        usage: state.caps.instance_buffer_usage(),
+        mapped_at_creation: false,
+    });
+    if let Some(compute) = state.instance_compute.as_mut() {
+        compute.rebind(&state.device, &state.instance_buffer);
+    }
+    if let Some(pass) = state.gpu_cull.as_mut() {
+        pass.clear();
+    }
+    state.instance_capacity = capacity;
+    tracing::info!(target: "gpu", "Instance buffer grown to {} instances", capacity);
+}
 
-        } else {
-            // log::warn!("WGPU_STATE is None");
-        }
-    } else {
-        log::error!("Failed to lock WGPU_STATE");
+/// Sync physics positions to the GPU instance buffer, writing only the slots
+/// that changed since the previous frame
+fn sync_physics_to_gpu() {
+    let minimap = MINIMAP.lock().ok().and_then(|config| *config);
+    // Same lock order as the render path: WGPU_STATE, then PHYSICS_STATE
+    let mut wgpu_guard = match WGPU_STATE.lock() {
+        Ok(g) => g,
+        Err(_) => return,
+    };
+    let mut physics_guard = match PHYSICS_STATE.lock() {
+        Ok(g) => g,
+        Err(_) => return,
+    };
+    if let (Some(state), Some(physics)) = (wgpu_guard.0.as_mut(), physics_guard.0.as_mut()) {
+        sync_to_gpu(state, physics, minimap);
     }
 }
 
-fn shutdown_internal() {
-    log::info!("Shutting down wgpu");
-    if let Ok(mut guard) = WGPU_STATE.lock() {
-        guard.0 = None;
+/// Upload instances and the per-frame geometry of every pass
+fn sync_to_gpu(state: &mut WgpuState, physics: &mut PhysicsState, minimap: Option<MinimapConfig>) {
+    let _span = tracing::debug_span!(target: "gpu", "sync_to_gpu").entered();
+    physics.camera_target = [state.camera.target.x, state.camera.target.y];
+    refresh_instance_staging(physics);
+    update_render_lod(physics);
+    let surface_size = (state.config.width, state.config.height);
+    let halves = split_screen_halves(physics, surface_size);
+    let settings = RENDER_SETTINGS.lock().map(|s| *s).unwrap_or_default();
+    let view = cull_view(state, halves.as_ref(), &settings);
+    // The GPU path uploads and draws every batch; the compute pass culls them
+    let gpu_cull = settings.gpu_culling && state.gpu_cull.is_some() && !physics.instance_batches.is_empty();
+    let culled = view.as_ref().filter(|_| !gpu_cull).and_then(|view| cull_instances(physics, view));
+    let staging = &mut physics.instance_staging;
+
+    ensure_instance_capacity(state, staging.len() as u32);
+    if !state.instance_buffer_synced {
+        staging.mark_all_dirty();
+        state.instance_buffer_synced = true;
+    }
+
+    let stride = std::mem::size_of::<Instance>() as u64;
+    let upload = match culled.as_ref() {
+        Some((_, drawn)) => staging.take_upload_drawn(|slot| drawn[slot]),
+        None => staging.take_upload(),
+    };
+    match upload {
+        Upload::None => {}
+        Upload::Full => {
+            state.queue.write_buffer(&state.instance_buffer, 0, bytemuck::cast_slice(staging.data()));
+        }
+        Upload::Ranges(ranges) => {
+            for range in ranges {
+                state.queue.write_buffer(
+                    &state.instance_buffer,
+                    range.start as u64 * stride,
+                    bytemuck::cast_slice(&staging.data()[range]),
+                );
+            }
+        }
+    }
+    state.num_instances = staging.len() as u32;
+    state.draw_batches = match culled {
+        Some((batches, _)) => batches,
+        None => physics.instance_batches.clone(),
+    };
+    state.layer_split = physics.instance_layers;
+    match (view, state.gpu_cull.as_mut()) {
+        (Some(view), Some(pass)) if gpu_cull => pass.prepare(
+            &state.device,
+            &state.queue,
+            &state.instance_buffer,
+            state.num_instances,
+            &state.draw_batches,
+            &view.rects,
+            view.grow,
+            view.shift,
+            INDICES.len() as u32,
+        ),
+        (_, Some(pass)) => pass.clear(),
+        _ => {}
+    }
+    // Counts culled on the GPU stay there, so those frames count every body as drawn
+    let drawn: u32 = state.draw_batches.iter().map(|batch| batch.instance_count).sum();
+    state.frame_stats.drawn_instances = drawn;
+    state.frame_stats.culled_instances = state.num_instances.saturating_sub(drawn);
+
+    state.water_pass.prepare(&state.device, &state.queue, &physics.water.vertices());
+    state.soft_body_pass.prepare(&state.device, &state.queue, &physics.soft_bodies.vertices(&physics.rigid_body_set));
+    let mut trail_vertices = physics.trails.vertices();
+    trail_vertices.extend(trajectory::preview_vertices(&physics.trajectory_preview));
+    state.trail_pass.prepare(&state.device, &state.queue, &trail_vertices);
+    let blips = minimap.map(|config| minimap_blips(physics, config.visible_groups)).unwrap_or_default();
+    let area = safe_area().content_rect(surface_size.0, surface_size.1);
+    state.minimap_pass.prepare(&state.device, &state.queue, minimap.as_ref(), &blips, area);
+    state.split_cameras.prepare(&state.queue, halves);
+
+    if physics.tilemaps_dirty || !state.tiles_synced {
+        upload_tiles(state, &physics.tilemaps);
+        state.terrain_pass.prepare(&state.device, &state.queue, &physics.terrain_vertices);
+        physics.tilemaps_dirty = false;
+        state.tiles_synced = true;
+    }
+    if let Some(bevy_3d) = state.bevy_3d_sample.as_mut() {
+        bevy_3d.set_instances(&state.device, &state.queue, model_instances(physics));
     }
-    INITIALIZED.store(false, Ordering::Relaxed);
 }
 
-// --- C / iOS Interface ---
+/// Model and world matrix of every body drawn with a model
+fn model_instances(physics: &mut PhysicsState) -> Vec<(u32, ModelInstance)> {
+    let default_size = physics.world.resource::<WorldScale>().box_half_extent();
+    let mut bodies = physics.world.query::<(&PhysicsBody, &ModelComponent, Option<&Scale>)>();
+    bodies
+        .iter(&physics.world)
+        .filter_map(|(body, model, scale)| {
+            let rb = physics.rigid_body_set.get(body.rigid_body_handle)?;
+            let size = scale.map_or(default_size, |s| s.0) * model.scale;
+            let matrix = rb.position().to_homogeneous().cast::<f32>() * crate::rapier::na::Matrix4::new_scaling(size);
+            Some((model.model_id, ModelInstance { model_matrix: matrix.into() }))
+        })
+        .collect()
+}
 
-#[no_mangle]
-pub extern "C" fn physics_core_get_info() -> *mut c_char {
-    let s = get_internal_info();
-    let c_str = CString::new(s).unwrap();
-    c_str.into_raw()
+/// World rects of every camera drawing the scene and how far instance
+/// bounds reach, for culling on either side
+struct CullView {
+    rects: Vec<ViewRect>,
+    /// Bounding radius of an instance is `scale * longest side * sqrt(2) *
+    /// grow + shift`
+    grow: f32,
+    shift: f32,
 }
 
-#[no_mangle]
-pub(crate) extern "C" fn update_physics_internal(state: *mut WgpuState, _dt: f32) {
-    let _state = unsafe { &mut *state };
-    // Update physics here
+/// None draws every instance: culling is off or a perspective camera sees
+/// more than a rect
+fn cull_view(state: &WgpuState, halves: Option<&([[u32; 4]; 2], [ViewCamera; 2])>, settings: &RenderSettings) -> Option<CullView> {
+    if !settings.culling_enabled || !state.camera.is_orthographic {
+        return None;
+    }
+    let aspect = |width: u32, height: u32| width as f32 / height.max(1) as f32;
+    let offset = &state.camera_offset;
+    let mut rects: Vec<ViewRect> = match halves {
+        Some((viewports, cameras)) => viewports
+            .iter()
+            .zip(cameras)
+            .map(|(&[_, _, width, height], camera)| ViewRect::camera([camera.center_x, camera.center_y], camera.view_height, aspect(width, height), 0.0))
+            .collect(),
+        None => {
+            let center = [state.camera.target.x + offset.dx, state.camera.target.y + offset.dy];
+            vec![ViewRect::camera(center, state.camera.ortho_size / (1.0 + offset.zoom), state.camera.aspect, offset.rotation)]
+        }
+    };
+    rects.extend(state.views.iter().map(|(_, view)| {
+        let camera = &view.camera;
+        ViewRect::camera([camera.center_x, camera.center_y], camera.view_height, aspect(view.config.width, view.config.height), 0.0)
+    }));
+
+    // A quad spans its scale from the corner at its position, and its
+    // contact shadow grows and shifts it further
+    let (grow, shift) = match settings.shadow_enabled {
+        true => (1.0 + settings.shadow_spread, settings.shadow_offset[0].hypot(settings.shadow_offset[1])),
+        false => (1.0, 0.0),
+    };
+    Some(CullView { rects, grow, shift })
 }
 
-#[no_mangle]
-pub extern "C" fn physics_core_free_string(s: *mut c_char) {
-    if s.is_null() {
-        return;
+/// Body draw batches cut down to the runs of instances some camera sees
+/// (see `culling`), with which slots those runs draw. None until something
+/// is batched.
+fn cull_instances(physics: &PhysicsState, view: &CullView) -> Option<(Vec<DrawBatch>, Vec<bool>)> {
+    if physics.instance_batches.is_empty() {
+        return None;
+    }
+    let bounds = physics
+        .instance_staging
+        .data()
+        .iter()
+        .map(|instance| {
+            // Parallax moves the quad by where each camera is; never culled
+            if instance.parallax != [1.0, 1.0] {
+                return (instance.position, f32::INFINITY);
+            }
+            let longest = instance.size[0].max(instance.size[1]);
+            (instance.position, instance.scale * longest * std::f32::consts::SQRT_2 * view.grow + view.shift)
+        });
+    let visible = culling::visible(bounds, &view.rects);
+
+    let mut drawn = vec![false; visible.len()];
+    let mut batches = Vec::new();
+    for batch in &physics.instance_batches {
+        for run in culling::runs(&visible, batch.instance_range()) {
+            drawn[run.start as usize..run.end as usize].fill(true);
+            batches.push(DrawBatch {
+                material_id: batch.material_id,
+                first_instance: run.start,
+                instance_count: run.end - run.start,
+            });
+        }
     }
-    unsafe {
-        let _ = CString::from_raw(s);
+    // No batches at all means nothing synced yet and draws every instance
+    if batches.is_empty() {
+        batches.push(DrawBatch { material_id: physics.instance_batches[0].material_id, first_instance: 0, instance_count: 0 });
     }
+    Some((batches, drawn))
 }
 
-#[no_mangle]
-pub extern "C" fn physics_core_set_gravity(y: f32) {
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            physics.gravity.y = -y;
+/// Viewport and camera of each split-screen half, after following the
+/// players' bodies; None without split screen or on too small a surface
+fn split_screen_halves(physics: &mut PhysicsState, surface_size: (u32, u32)) -> Option<([[u32; 4]; 2], [ViewCamera; 2])> {
+    let mut split = physics.world.get_resource_mut::<SplitScreen>()?;
+    // Touch routing splits the same surface
+    split.surface_size = surface_size;
+    let viewports = split.viewports()?;
+    let players = split.players;
+    let cameras = players.map(|view| {
+        let followed = view.follow.and_then(|id| body_of(physics, id)).and_then(|body| {
+            let rb = physics.rigid_body_set.get(body.rigid_body_handle)?;
+            Some(xy(rb.translation()))
+        });
+        view.camera(followed)
+    });
+    Some((viewports, cameras))
+}
+
+/// Every body in `visible_groups` as the minimap draws it
+fn minimap_blips(physics: &mut PhysicsState, visible_groups: u32) -> Vec<Blip> {
+    let default_size = physics.world.resource::<WorldScale>().box_half_extent();
+    let mut bodies = physics.world.query::<(&PhysicsBody, Option<&Groups>, Option<&Scale>, Option<&Appearance>)>();
+    bodies
+        .iter(&physics.world)
+        .filter(|(_, groups, _, _)| groups.copied().unwrap_or_default().matches(visible_groups))
+        .filter_map(|(body, _, scale, appearance)| {
+            let rb = physics.rigid_body_set.get(body.rigid_body_handle)?;
+            let appearance = appearance.copied().unwrap_or_default();
+            let [r, g, b] = appearance.tint;
+            Some(Blip {
+                position: xy(rb.translation()),
+                size: scale.map_or(default_size, |s| s.0) * appearance.scale,
+                rotation: to_f32(rb.rotation().angle()),
+                color: [r, g, b, 1.0],
+            })
+        })
+        .collect()
+}
+
+/// Rebuild the tile quads, one draw batch per layer. The buffer is reused
+/// while it is large enough, so digging through terrain does not reallocate.
+fn upload_tiles(state: &mut WgpuState, tilemaps: &[(Tilemap, Option<ColliderHandle>)]) {
+    let mut instances: Vec<Instance> = Vec::new();
+    state.tile_batches.clear();
+    for (tilemap, _) in tilemaps {
+        let first_instance = instances.len() as u32;
+        instances.extend(tilemap.quads().into_iter().map(|quad| Instance {
+            position: quad.corner,
+            velocity: [0.0, 0.0],
+            scale: quad.size,
+            rotation: 0.0,
+            uv_offset: quad.uv_offset,
+            uv_scale: quad.uv_scale,
+            tint: WHITE_TINT,
+            lod: 0,
+            size: [1.0, 1.0],
+            slice_min: [0.0, 0.0],
+            slice_max: [0.0, 0.0],
+            parallax: [1.0, 1.0],
+        }));
+        let instance_count = instances.len() as u32 - first_instance;
+        if instance_count > 0 {
+            state.tile_batches.push(DrawBatch { material_id: tilemap.material_id, first_instance, instance_count });
         }
     }
-}
+    if instances.is_empty() {
+        return;
+    }
 
-#[no_mangle]
-pub extern "C" fn physics_core_set_time_scale(scale: f32) {
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            physics.time_scale = scale;
+    let bytes: &[u8] = bytemuck::cast_slice(&instances);
+    match &state.tile_buffer {
+        Some(buffer) if buffer.size() >= bytes.len() as u64 => state.queue.write_buffer(buffer, 0, bytes),
+        _ => {
+            state.tile_buffer = Some(state.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Tile Instance Buffer"),
+                contents: bytes,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            }));
         }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn physics_core_set_paused(paused: bool) {
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            physics.paused = paused;
+/// Shaders the desktop build watches for hot reload: the sprite pipeline
+/// and the contact shadows, the two whose pipelines `WgpuState` builds from
+/// a source it is handed. Every other pipeline (background, water, trails,
+/// soft bodies, text, bloom and composite, culling, mipmaps and the 3D
+/// demo) is built inside its own module from the embedded source, so edits
+/// to those files only show after a rebuild.
+#[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+const HOT_RELOAD_SHADERS: &[&str] = &["shader.wgsl", "shadow.wgsl"];
+
+/// Rebuild pipelines for shaders edited on disk. On a compile or validation
+/// error the previous pipelines are kept and the error is logged.
+#[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+fn reload_changed_shaders(state: &mut WgpuState) {
+    let changed = match state.shader_watcher.as_mut() {
+        Some(watcher) => watcher.poll(),
+        None => return,
+    };
+
+    for (name, source) in changed {
+        let device = &state.device;
+        let result = match name {
+            "shader.wgsl" => shader_manager::build_validated(device, || {
+                let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("Shader"),
+                    source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(&source)),
+                });
+                let pipeline = create_scene_pipeline(
+                    device,
+                    &state.render_pipeline_layout,
+                    &shader,
+                    state.config.format,
+                    state.sample_count,
+                );
+                let hdr_pipeline = state.bloom.as_ref().map(|_| {
+                    post_process::create_hdr_scene_pipeline(
+                        device,
+                        &shader,
+                        &state.render_pipeline_layout,
+                        &[Vertex::desc(), Instance::desc()],
+                    )
+                });
+                (pipeline, hdr_pipeline)
+            })
+            .map(|(pipeline, hdr_pipeline)| {
+                state.render_pipeline = pipeline;
+                if let (Some(bloom), Some(hdr_pipeline)) = (state.bloom.as_mut(), hdr_pipeline) {
+                    bloom.set_scene_pipeline(hdr_pipeline);
+                }
+            }),
+            "shadow.wgsl" => shader_manager::build_validated(device, || {
+                ContactShadowPass::new(
+                    device,
+                    &source,
+                    &state.camera_bind_group_layout,
+                    &[Vertex::desc(), Instance::desc()],
+                    state.config.format,
+                    state.sample_count,
+                    state.bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+                )
+            })
+            .map(|pass| state.contact_shadows = pass),
+            _ => Ok(()),
+        };
+
+        match result {
+            Ok(()) => log::info!("Reloaded shader {}", name),
+            Err(e) => log::error!("Shader {} failed to compile, keeping previous pipeline:\n{}", name, e),
         }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn physics_core_reset_simulation() {
-    init_physics();
-}
+/// Declare this frame's passes and the resources they exchange
+fn build_frame_graph(cull: bool, bloom: bool, minimap: bool, text: bool, debug: bool, egui: bool) -> RenderGraph {
+    let mut graph = RenderGraph::new();
+    // The compute experiment integrated positions on the GPU, which would
+    // clobber slots skipped by partial instance uploads, so it is not scheduled
 
-#[no_mangle]
-pub extern "C" fn physics_core_on_pointer_event(event_type: i32, x: f32, y: f32, button: i32) {
-    on_pointer_event_internal(event_type, x, y, button);
-}
+    let instances: &[Resource] = if cull {
+        graph.add_pass(PassId::Cull, &[Resource::Instances], &[Resource::VisibleInstances]);
+        &[Resource::Instances, Resource::VisibleInstances]
+    } else {
+        &[Resource::Instances]
+    };
 
-#[no_mangle]
-pub extern "C" fn physics_core_on_key_event(event_type: i32, key_code: i32) {
-    on_key_event_internal(event_type, key_code);
+    // With bloom the scene goes to the HDR target and the post pass writes the
+    // backbuffer; otherwise the main pass renders (or resolves) straight into it
+    if bloom {
+        graph.add_pass(PassId::Main, instances, &[Resource::SceneColor]);
+        graph.add_pass(PassId::Post, &[Resource::SceneColor], &[Resource::Backbuffer]);
+    } else {
+        graph.add_pass(PassId::Main, instances, &[Resource::Backbuffer]);
+    }
+
+    // Over the finished scene but under the HUD
+    if minimap {
+        graph.add_pass(PassId::Minimap, &[Resource::Backbuffer], &[Resource::Backbuffer]);
+    }
+    if text {
+        graph.add_pass(PassId::Text, &[Resource::Backbuffer], &[Resource::Backbuffer]);
+    }
+    if debug {
+        graph.add_pass(PassId::Debug, &[Resource::Backbuffer], &[Resource::Backbuffer]);
+    }
+    if egui {
+        graph.add_pass(PassId::Egui, &[Resource::Backbuffer], &[Resource::Backbuffer]);
+    }
+    graph
 }
 
-#[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
-fn init_logging() {
-    use std::sync::Once;
-    static START: Once = Once::new();
-    START.call_once(|| {
-        // Configure flexi_logger:
-        // - Output to stderr (for console)
-        // - Output to a rotating file in "logs/" directory
-        // - Default level override via RUST_LOG supported
-        use flexi_logger::{Logger, FileSpec, Criterion, Naming, Cleanup, Duplicate};
-        
-        // Print CWD to locate logs
-        if let Ok(cwd) = std::env::current_dir() {
-            println!("Rust Native Library Running in: {:?}", cwd);
-        }
-
-        let _ = Logger::try_with_env_or_str("debug")
-            .expect("Failed to create logger")
-            .log_to_file(FileSpec::default().directory("logs").basename("physics_core"))
-            .duplicate_to_stderr(Duplicate::All) // Print to console too
-            .rotate(
-                Criterion::Size(1024 * 1024), // 1MB
-                Naming::Timestamps,
-                Cleanup::KeepLogFiles(7), 
-            )
-            .start();
+fn encode_compute_pass(state: &WgpuState, encoder: &mut wgpu::CommandEncoder) {
+    let compute = match state.instance_compute.as_ref() {
+        Some(compute) => compute,
+        None => return,
+    };
+    let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+        label: Some("Compute Pass"),
+        timestamp_writes: None,
     });
+    compute_pass.set_pipeline(&compute.pipeline);
+    compute_pass.set_bind_group(0, &compute.bind_group, &[]);
+    compute_pass.dispatch_workgroups(2, 1, 1);
 }
 
-#[no_mangle]
-pub extern "C" fn wgpu_init(
-    surface_handle: *mut std::ffi::c_void,
-    width: i32,
-    height: i32,
-) -> bool {
-    #[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
-    {
-        init_logging();
+/// The surface the main pass draws for: its camera and multisample target
+struct PassTarget<'a> {
+    camera_bind_group: &'a wgpu::BindGroup,
+    msaa_view: Option<&'a wgpu::TextureView>,
+    /// Only the main surface is covered by the scene fade
+    fade: bool,
+    /// Per-player cameras that replace `camera_bind_group` under split screen
+    split: Option<&'a SplitCameras>,
+    /// Pixel rectangle the world is drawn into, when not the whole target
+    viewport: Option<[u32; 4]>,
+}
+
+impl WgpuState {
+    fn main_target(&self) -> PassTarget<'_> {
+        let viewport = self.world_rect();
+        PassTarget {
+            camera_bind_group: &self.camera_bind_group,
+            msaa_view: self.msaa_view.as_ref(),
+            fade: true,
+            split: Some(&self.split_cameras),
+            viewport: (viewport != [0, 0, self.config.width, self.config.height]).then_some(viewport),
+        }
     }
 
-    log::debug!(
-        "wgpu_init called: {:?}, {}x{}",
-        surface_handle,
-        width,
-        height
-    );
+    /// Pixel rectangle of the main surface the camera shows (see `safe_area`)
+    fn world_rect(&self) -> [u32; 4] {
+        safe_area().world_rect(self.config.width, self.config.height)
+    }
 
-    if surface_handle.is_null() {
-        log::warn!("wgpu_init: surface_handle is null, cannot initialize");
-        INITIALIZED.store(true, Ordering::Relaxed);
-        return true;
+    /// Main surface pixel to world point with the current camera
+    fn screen_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let [left, top, width, height] = self.world_rect();
+        self.camera.screen_to_world(x - left as f32, y - top as f32, width, height)
     }
+}
 
-    #[cfg(target_arch = "wasm32")]
-    {
-        // On WASM, wgpu_init shouldn't be called directly, we use wasm_init
-        // But if it is, we just return false
-        log::warn!("wgpu_init called on WASM, ignoring");
-        return false;
+/// Clear the scene target and draw contact shadows followed by the bodies,
+/// once per split-screen half when split
+fn encode_main_pass(
+    state: &WgpuState,
+    encoder: &mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    target: &PassTarget,
+    bloom: Option<&BloomPass>,
+    settings: &RenderSettings,
+) {
+    // Bloom renders into its HDR target; otherwise with MSAA we draw into the
+    // multisampled target and resolve into the swapchain view
+    let (color_view, resolve_target, store_op, scene_pipeline) = match (bloom, target.msaa_view) {
+        (Some(bloom), _) => (bloom.hdr_view(), None, wgpu::StoreOp::Store, bloom.scene_pipeline()),
+        (None, Some(msaa_view)) => (msaa_view, Some(view), wgpu::StoreOp::Discard, &state.render_pipeline),
+        (None, None) => (view, None, wgpu::StoreOp::Store, &state.render_pipeline),
+    };
+
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Render Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: color_view,
+            resolve_target,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(settings.clear_color_wgpu()),
+                store: store_op,
+            },
+            depth_slice: None,
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+
+    // Under every view of a split screen alike
+    state.background_pass.draw(&mut render_pass, bloom.is_some());
+
+    match target.split.and_then(SplitCameras::halves) {
+        Some(halves) => {
+            for (camera_bind_group, [x, y, width, height]) in halves {
+                render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+                render_pass.set_scissor_rect(x, y, width, height);
+                draw_scene(state, &mut render_pass, camera_bind_group, target.fade, scene_pipeline, bloom.is_some(), settings);
+            }
+        }
+        None => {
+            if let Some([x, y, width, height]) = target.viewport {
+                render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+                render_pass.set_scissor_rect(x, y, width, height);
+            }
+            draw_scene(state, &mut render_pass, target.camera_bind_group, target.fade, scene_pipeline, bloom.is_some(), settings)
+        }
     }
+}
 
-    #[cfg(not(target_arch = "wasm32"))]
-    let (window_handle, display_handle) = {
-        #[cfg(target_os = "ios")]
-        let (window_handle, display_handle) = {
-            use raw_window_handle::UiKitWindowHandle;
-            let handle =
-                UiKitWindowHandle::new(std::ptr::NonNull::new(surface_handle.cast()).unwrap());
-            (
-                RawWindowHandle::UiKit(handle),
-                RawDisplayHandle::UiKit(raw_window_handle::UiKitDisplayHandle::new()),
-            )
-        };
+/// Background layers, tiles, shadows, blobs, trails, bodies, water and
+/// foreground layers through one camera
+fn draw_scene(
+    state: &WgpuState,
+    render_pass: &mut wgpu::RenderPass<'_>,
+    camera_bind_group: &wgpu::BindGroup,
+    fade: bool,
+    scene_pipeline: &wgpu::RenderPipeline,
+    hdr: bool,
+    settings: &RenderSettings,
+) {
+    let gpu_cull = state.gpu_cull.as_ref().filter(|pass| pass.active());
+    // Nothing synced yet draws everything in the world layer below
+    let [background, world, foreground] = state.layer_split.ranges(&state.draw_batches);
+
+    render_pass.set_bind_group(1, camera_bind_group, &[]);
+    render_pass.set_vertex_buffer(0, state.vertex_buffer.slice(..));
+    render_pass.set_vertex_buffer(1, state.instance_buffer.slice(..));
+    render_pass.set_index_buffer(state.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+
+    // Background layers are behind everything else in the scene
+    if !background.is_empty() {
+        render_pass.set_pipeline(scene_pipeline);
+        draw_body_batches(state, render_pass, gpu_cull, background);
+    }
 
-        #[cfg(target_os = "macos")]
-        let (window_handle, display_handle) = {
-            use raw_window_handle::{AppKitDisplayHandle, AppKitWindowHandle};
-            let handle =
-                AppKitWindowHandle::new(std::ptr::NonNull::new(surface_handle.cast()).unwrap());
-            (
-                RawWindowHandle::AppKit(handle),
-                RawDisplayHandle::AppKit(AppKitDisplayHandle::new()),
-            )
-        };
+    // Terrain is the deepest backdrop; it binds its own pipeline and buffers
+    state.terrain_pass.draw(render_pass, camera_bind_group, hdr);
 
-        #[cfg(target_os = "windows")]
-        let (window_handle, display_handle) = {
-            use raw_window_handle::{Win32WindowHandle, WindowsDisplayHandle};
-            let handle = Win32WindowHandle::new(
-                std::num::NonZeroIsize::new(surface_handle as isize).unwrap(),
-            );
-            (
-                RawWindowHandle::Win32(handle),
-                RawDisplayHandle::Windows(WindowsDisplayHandle::new()),
-            )
-        };
+    render_pass.set_bind_group(1, camera_bind_group, &[]);
 
-        #[cfg(all(
-            unix,
-            not(any(target_os = "ios", target_os = "macos", target_os = "android"))
-        ))]
-        let (window_handle, display_handle) = {
-            use raw_window_handle::{XlibDisplayHandle, XlibWindowHandle};
-            let handle = XlibWindowHandle::new(surface_handle as u64);
-            (
-                RawWindowHandle::Xlib(handle),
-                RawDisplayHandle::Xlib(XlibDisplayHandle::new(None, 0)),
-            )
-        };
+    render_pass.set_vertex_buffer(0, state.vertex_buffer.slice(..));
+    render_pass.set_vertex_buffer(1, state.instance_buffer.slice(..));
 
-        #[cfg(target_os = "android")]
-        let (window_handle, display_handle) = {
-            use raw_window_handle::{AndroidDisplayHandle, AndroidNdkWindowHandle};
-            let handle =
-                AndroidNdkWindowHandle::new(std::ptr::NonNull::new(surface_handle.cast()).unwrap());
-            (
-                RawWindowHandle::AndroidNdk(handle),
-                RawDisplayHandle::Android(AndroidDisplayHandle::new()),
-            )
-        };
+    render_pass.set_index_buffer(state.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
-        (window_handle, display_handle)
-    };
+    // Tiles are the backdrop: under the shadows and bodies
+    if let Some(tile_buffer) = state.tile_buffer.as_ref().filter(|_| !state.tile_batches.is_empty()) {
+        render_pass.set_pipeline(scene_pipeline);
+        render_pass.set_vertex_buffer(1, tile_buffer.slice(..));
+        for batch in &state.tile_batches {
+            render_pass.set_bind_group(0, state.materials.bind_group(batch.material_id), &[]);
+            render_pass.draw_indexed(0..INDICES.len() as u32, 0, batch.instance_range());
+        }
+        render_pass.set_vertex_buffer(1, state.instance_buffer.slice(..));
+    }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        init_wgpu_internal(
-            window_handle,
-            display_handle,
-            width as u32,
-            height as u32,
-            surface_handle,
-            None
-        )
+    // Contact shadows go underneath the bodies, so draw them first; only the
+    // world layers cast them
+    if settings.shadow_enabled && state.contact_shadows.bind(render_pass, hdr) {
+        if state.draw_batches.is_empty() {
+            render_pass.draw_indexed(0..INDICES.len() as u32, 0, 0..state.num_instances);
+        }
+        for (index, batch) in state.draw_batches.iter().enumerate().take(world.end).skip(world.start) {
+            match gpu_cull {
+                Some(pass) => pass.draw_batch(render_pass, index, batch),
+                None => render_pass.draw_indexed(0..INDICES.len() as u32, 0, batch.instance_range()),
+            }
+        }
     }
 
-}
+    // Blob fills sit under their ring nodes
+    state.soft_body_pass.draw(render_pass, camera_bind_group, hdr);
+    // Trails run behind the bodies that leave them
+    state.trail_pass.draw(render_pass, camera_bind_group, hdr);
+    render_pass.set_vertex_buffer(0, state.vertex_buffer.slice(..));
 
-#[no_mangle]
-pub extern "C" fn wgpu_update(delta_time: f32) {
-    if !INITIALIZED.load(Ordering::Relaxed) {
-        return;
+    render_pass.set_pipeline(scene_pipeline);
+
+    if state.draw_batches.is_empty() {
+        // Nothing synced yet: draw everything with the default material
+        render_pass.set_bind_group(0, &state.diffuse_bind_group, &[]);
+        render_pass.draw_indexed(0..INDICES.len() as u32, 0, 0..state.num_instances);
+    } else {
+        draw_body_batches(state, render_pass, gpu_cull, world);
     }
-    // TODO: Update game logic
-    log::trace!("wgpu_update: dt={}", delta_time);
-    update_internal(delta_time);
-}
 
-#[no_mangle]
-pub extern "C" fn wgpu_render() {
-    if !INITIALIZED.load(Ordering::Relaxed) {
-        return;
+    // Translucent water over the bodies it holds
+    state.water_pass.draw(render_pass, camera_bind_group, hdr);
+    // Foreground layers in front of the water
+    if !foreground.is_empty() {
+        render_pass.set_bind_group(1, camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, state.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, state.instance_buffer.slice(..));
+        render_pass.set_index_buffer(state.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.set_pipeline(scene_pipeline);
+        draw_body_batches(state, render_pass, gpu_cull, foreground);
+    }
+    // A scene fading in is covered by the background last of all
+    if fade {
+        state.fade_pass.draw(render_pass, camera_bind_group, hdr);
     }
-    render_internal(None);
 }
 
-#[no_mangle]
-pub extern "C" fn wgpu_resize(width: i32, height: i32) {
-    if !INITIALIZED.load(Ordering::Relaxed) {
-        return;
+/// One draw per material batch of `batches` (indices into `draw_batches`),
+/// with the scene pipeline, camera and buffers already bound
+fn draw_body_batches(state: &WgpuState, render_pass: &mut wgpu::RenderPass<'_>, gpu_cull: Option<&GpuCullPass>, batches: std::ops::Range<usize>) {
+    for (index, batch) in state.draw_batches.iter().enumerate().take(batches.end).skip(batches.start) {
+        render_pass.set_bind_group(0, state.materials.bind_group(batch.material_id), &[]);
+        match gpu_cull {
+            Some(pass) => pass.draw_batch(render_pass, index, batch),
+            None => render_pass.draw_indexed(0..INDICES.len() as u32, 0, batch.instance_range()),
+        }
     }
-    log::info!("wgpu_resize: {}x{}", width, height);
-    resize_internal(width as u32, height as u32);
 }
 
-#[no_mangle]
-pub extern "C" fn wgpu_shutdown() {
-    log::info!("wgpu_shutdown called");
-    shutdown_internal();
+/// Draw the scene into every extra view through its own camera
+fn render_views(state: &mut WgpuState, settings: &RenderSettings) {
+    if state.views.is_empty() {
+        return;
+    }
+    let WgpuState { device, views, .. } = state;
+    let outputs: Vec<_> = views.iter_mut().map(|(_, view)| view.acquire(device)).collect();
+    let mut encoder = state.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("View Encoder"),
+    });
+    let mut presented = Vec::new();
+    for ((_, view), output) in state.views.iter().zip(outputs) {
+        let Some(output) = output else {
+            continue;
+        };
+        let texture_view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let target = PassTarget {
+            camera_bind_group: &view.camera_bind_group,
+            msaa_view: view.msaa_view.as_ref(),
+            fade: false,
+            split: None,
+        };
+        encode_main_pass(state, &mut encoder, &texture_view, &target, None, settings);
+        presented.push(output);
+    }
+    state.queue.submit(std::iter::once(encoder.finish()));
+    for output in presented {
+        output.present();
+    }
 }
 
-// --- JNI Interface (Android & JVM) ---
-
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getInfo(
-    env: JNIEnv,
-    _class: JClass,
-) -> jni::sys::jstring {
-    let info = get_internal_info();
-    let output = env.new_string(info).expect("Couldn't create java string!");
-    output.into_raw()
+/// Draw the Bevy 3D sample cube over the finished frame
+fn encode_debug_pass(bevy_3d: &Bevy3DSample, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Debug Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+            depth_slice: None,
+        })],
+        depth_stencil_attachment: bevy_3d.depth_attachment(),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    bevy_3d.render(&mut render_pass);
 }
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setGravity(
-    _env: JNIEnv,
-    _class: JClass,
-    y: jfloat,
-) {
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            physics.gravity.y = -(y as f32);
+/// Build the "Physics Controls" window for this frame
+/// Wind, water and force volume controls; returns true if anything changed
+fn draw_force_field_controls(ui: &mut egui::Ui, fields: &mut ForceFieldConfig) -> bool {
+    use force_fields::{BuoyancyRegion, ForceVolume, VolumeKind, Wind};
+    let before = fields.clone();
+
+    let mut wind_on = fields.wind.is_some();
+    ui.checkbox(&mut wind_on, "Wind");
+    match (wind_on, fields.wind.as_mut()) {
+        (true, Some(wind)) => {
+            ui.add(egui::Slider::new(&mut wind.velocity[0], -10.0..=10.0).text("Speed"));
+            ui.add(egui::Slider::new(&mut wind.gustiness, 0.0..=1.0).text("Gustiness"));
         }
+        (true, None) => fields.wind = Some(Wind::default()),
+        (false, _) => fields.wind = None,
     }
-}
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setTimeScale(
-    _env: JNIEnv,
-    _class: JClass,
-    scale: jfloat,
-) {
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            physics.time_scale = scale as f32;
+    let mut water_on = !fields.buoyancy.is_empty();
+    ui.checkbox(&mut water_on, "Water");
+    match (water_on, fields.buoyancy.first_mut()) {
+        (true, Some(water)) => {
+            ui.add(egui::Slider::new(&mut water.surface_y, -1.0..=1.0).text("Water Level"));
+            ui.add(egui::Slider::new(&mut water.fluid_density, 0.0..=5.0).text("Density"));
         }
+        (true, None) => fields.buoyancy.push(BuoyancyRegion::default()),
+        (false, _) => fields.buoyancy.clear(),
     }
-}
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setPaused(
-    _env: JNIEnv,
-    _class: JClass,
-    paused: jboolean,
-) {
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            physics.paused = paused != 0;
-        }
+    let mut removed = None;
+    for (i, volume) in fields.volumes.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            for kind in [VolumeKind::Attract, VolumeKind::Repel, VolumeKind::Vortex] {
+                ui.selectable_value(&mut volume.kind, kind, format!("{:?}", kind));
+            }
+            if ui.button("Remove").clicked() {
+                removed = Some(i);
+            }
+        });
+        ui.add(egui::Slider::new(&mut volume.strength, 0.0..=50.0).text("Strength"));
+        ui.add(egui::Slider::new(&mut volume.radius, 0.1..=2.0).text("Radius"));
+    }
+    if let Some(i) = removed {
+        fields.volumes.remove(i);
+    }
+    if ui.button("Add Volume").clicked() {
+        fields.volumes.push(ForceVolume::default());
     }
-}
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_resetSimulation(
-    _env: JNIEnv,
-    _class: JClass,
-) {
-    init_physics();
+    *fields != before
 }
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_onPointerEvent(
-    _env: JNIEnv,
-    _class: JClass,
-    event_type: jint,
-    x: jfloat,
-    y: jfloat,
-    button: jint,
-) {
-    on_pointer_event_internal(event_type as i32, x as f32, y as f32, button as i32);
-}
+fn draw_water_controls(ui: &mut egui::Ui, config: &mut WaterConfig) -> bool {
+    use water::WaterRegion;
+    let before = config.clone();
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_onKeyEvent(
-    _env: JNIEnv,
-    _class: JClass,
-    event_type: jint,
-    key_code: jint,
-) {
-    on_key_event_internal(event_type as i32, key_code as i32);
-}
-
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeInit(
-    _env: JNIEnv,
-    _class: JClass,
-    surface_handle: jlong,
-    width: jint,
-    height: jint,
-) -> jboolean {
-    wgpu_init(
-        surface_handle as *mut std::ffi::c_void,
-        width as i32,
-        height as i32,
-    ) as jboolean
-}
+    let mut removed = None;
+    for (i, region) in config.regions.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("Pool {}", i + 1));
+            if ui.button("Remove").clicked() {
+                removed = Some(i);
+            }
+        });
+        ui.add(egui::Slider::new(&mut region.surface_y, -1.0..=1.0).text("Water Level"));
+        ui.add(egui::Slider::new(&mut region.fluid_density, 0.0..=5.0).text("Density"));
+        ui.add(egui::Slider::new(&mut region.stiffness, 0.0..=100.0).text("Stiffness"));
+        ui.add(egui::Slider::new(&mut region.wave_speed, 0.0..=5.0).text("Wave Speed"));
+        ui.add(egui::Slider::new(&mut region.splash, 0.0..=1.0).text("Splash"));
+    }
+    if let Some(i) = removed {
+        config.regions.remove(i);
+    }
+    if config.regions.len() < water::MAX_REGIONS && ui.button("Add Pool").clicked() {
+        config.regions.push(WaterRegion::default());
+    }
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeUpdate(
-    _env: JNIEnv,
-    _class: JClass,
-    delta_time: jfloat,
-) {
-    wgpu_update(delta_time as f32);
+    *config != before
 }
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeRender(
-    _env: JNIEnv,
-    _class: JClass,
-) {
-    wgpu_render();
-}
+fn draw_time_dilation_controls(ui: &mut egui::Ui, config: &mut TimeDilationConfig) -> bool {
+    use time_dilation::{DilationZone, MIN_SCALE};
+    let before = config.clone();
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeResize(
-    _env: JNIEnv,
-    _class: JClass,
-    width: jint,
-    height: jint,
-) {
-    wgpu_resize(width as i32, height as i32);
-}
+    let mut removed = None;
+    for (i, zone) in config.zones.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("Zone {}", i + 1));
+            if ui.button("Remove").clicked() {
+                removed = Some(i);
+            }
+        });
+        ui.add(egui::Slider::new(&mut zone.scale, MIN_SCALE..=1.0).text("Time Rate"));
+        ui.add(egui::Slider::new(&mut zone.min[0], -2.0..=2.0).text("Min X"));
+        ui.add(egui::Slider::new(&mut zone.max[0], -2.0..=2.0).text("Max X"));
+        ui.add(egui::Slider::new(&mut zone.min[1], -2.0..=2.0).text("Min Y"));
+        ui.add(egui::Slider::new(&mut zone.max[1], -2.0..=2.0).text("Max Y"));
+    }
+    if let Some(i) = removed {
+        config.zones.remove(i);
+    }
+    if ui.button("Add Zone").clicked() {
+        config.zones.push(DilationZone::default());
+    }
 
-#[cfg(feature = "jni_support")]
-#[no_mangle]
-pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeShutdown(
-    _env: JNIEnv,
-    _class: JClass,
-) {
-    wgpu_shutdown();
+    if *config == before {
+        return false;
+    }
+    *config = std::mem::take(config).sanitized();
+    true
 }
 
-// --- Wasm Interface ---
+fn draw_egui_panel(egui_rend: &mut EguiRenderer, window: &winit::window::Window, stats: &FrameStats) {
+    let ctx = egui_rend.context();
 
-#[cfg(feature = "wasm_support")]
-#[wasm_bindgen]
-pub fn wasm_get_info() -> String {
-    get_internal_info()
-}
+    // Apply light theme with yellow background and black text
+    let mut visuals = egui::Visuals::light();
+    visuals.window_fill = egui::Color32::from_rgb(255, 255, 224); // Light Yellow
+    visuals.override_text_color = Some(egui::Color32::BLACK);
+    ctx.set_visuals(visuals);
 
-#[cfg(feature = "wasm_support")]
-#[wasm_bindgen]
-pub async fn wasm_init(canvas_id: &str, width: u32, height: u32) -> bool {
-    std::panic::set_hook(Box::new(console_error_panic_hook::hook));
-    let _ = console_log::init_with_level(log::Level::Info);
-    log::info!(
-        "wasm_init called: canvas={}, {}x{}",
-        canvas_id,
-        width,
-        height
+    // Make text bold for extra "darkness" and clarity
+    let mut style: egui::Style = (*ctx.style()).clone();
+    style.text_styles.insert(
+        egui::TextStyle::Heading,
+        egui::FontId::new(20.0, egui::FontFamily::Proportional),
     );
+    style.text_styles.insert(
+        egui::TextStyle::Body,
+        egui::FontId::new(14.0, egui::FontFamily::Proportional),
+    );
+    ctx.set_style(style);
+
+    egui_rend.begin_frame(window);
+
+    // Windows stay clear of notches and system bars
+    let size = window.inner_size();
+    let [x, y, width, height] = safe_area().content_rect(size.width, size.height);
+    let ppp = egui_rend.context().pixels_per_point();
+    let safe_rect = egui::Rect::from_min_size(egui::pos2(x as f32, y as f32) / ppp, egui::vec2(width as f32, height as f32) / ppp);
+
+    let mut panel = egui::Window::new("Physics Controls")
+        .resizable(true)
+        .default_width(450.0)
+        .constrain_to(safe_rect);
+    // Only read the first time egui sees the window; it tracks moves after that
+    if let Some(layout) = EGUI_PANEL.lock().ok().and_then(|layout| *layout) {
+        panel = panel
+            .default_pos([layout.x, layout.y])
+            .default_size([layout.width, layout.height]);
+    }
+    let mut editing = editor_enabled();
+    let editing_before = editing;
+    let shown = panel
+        .show(egui_rend.context(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading("Physics Controls");
+            });
 
-    // For WASM, we use web_sys to get the canvas element
-    use wasm_bindgen::JsCast;
-
-    let window = match web_sys::window() {
-        Some(w) => w,
-        None => {
-            log::error!("No window available");
-            return false;
-        }
-    };
+            ui.separator();
+
+            let mut physics_guard = PHYSICS_STATE.lock().unwrap();
+            if let Some(physics) = physics_guard.0.as_mut() {
+                // Gravity: presets, then X and Y on their own
+                let mut gravity: [f32; 3] = physics.gravity.map(to_f32).into();
+                let current = GravityPreset::matching(gravity);
+                ui.label(format!("Gravity: {:.1} m/s²", Vector::from(gravity).norm()));
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    for preset in GravityPreset::ALL {
+                        if ui.selectable_label(current == Some(preset), preset.name()).clicked() {
+                            gravity = preset.gravity();
+                            changed = true;
+                        }
+                    }
+                });
+                changed |= ui.add(egui::Slider::new(&mut gravity[0], -20.0..=20.0).text("X")).changed();
+                changed |= ui.add(egui::Slider::new(&mut gravity[1], -20.0..=20.0).text("Y")).changed();
+                if changed {
+                    set_gravity_in(physics, Vector::from(gravity).map(to_real));
+                }
 
-    let document = match window.document() {
-        Some(d) => d,
-        None => {
-            log::error!("No document available");
-            return false;
-        }
-    };
+                ui.add_space(8.0);
 
-    let canvas = match document.get_element_by_id(canvas_id) {
-        Some(c) => c,
-        None => {
-            log::error!("Canvas element '{}' not found", canvas_id);
-            return false;
-        }
-    };
+                // Time Scale Slider
+                ui.label(format!("Time Scale: {:.1}x", physics.time_scale));
+                if ui.add(egui::Slider::new(&mut physics.time_scale, 0.1..=5.0)).changed() {
+                    physics.time_ramp = None;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Slow-mo").clicked() {
+                        physics.time_ramp = Some(time_control::TimeScaleRamp::new(physics.time_scale, 0.2, 0.5));
+                    }
+                    if ui.button("Normal").clicked() {
+                        physics.time_ramp = Some(time_control::TimeScaleRamp::new(physics.time_scale, 1.0, 0.5));
+                    }
+                    // Steps run on the next update, outside the panel's locks
+                    if ui.button("Step").clicked() {
+                        physics.queued_steps += 1;
+                    }
+                    if ui.button("+1s").clicked() {
+                        physics.queued_steps += 60;
+                    }
+                });
 
-    let canvas: web_sys::HtmlCanvasElement = match canvas.dyn_into() {
-        Ok(c) => c,
-        Err(_) => {
-            log::error!("Element '{}' is not a canvas", canvas_id);
-            return false;
-        }
-    };
+                ui.add_space(16.0);
 
-    // Set initial scale factor - REMOVED wgpu_set_scale_factor calls
-    // Attach Input Listeners - REMOVED
+                // Pause Toggle
+                ui.checkbox(&mut physics.paused, "Pause Simulation");
+                ui.checkbox(&mut physics.animation_paused, "Pause Animation");
 
-    // Apply debug style to the EXISTING canvas to verify we have it
-    // We wrap these in a block and ignore errors just in case
+                // Sleeping
+                if ui.checkbox(&mut physics.sleep_enabled, "Allow Sleeping").changed() {
+                    apply_sleep_setting(&mut physics.rigid_body_set, physics.sleep_enabled);
+                }
+                let (total, sleeping) = sleep_stats(&physics.rigid_body_set);
+                ui.label(format!("Bodies: {} ({} sleeping)", total, sleeping));
+                if let Some(streamer) = physics.streamer.as_ref() {
+                    ui.label(format!("Streaming: {} chunks active", streamer.active_chunk_count()));
+                }
 
-    log::info!("Acquired existing canvas: {}", canvas_id);
+                // Substepping
+                ui.horizontal(|ui| {
+                    ui.label("Substeps");
+                    ui.add(egui::Slider::new(&mut physics.substep_config.substeps, 1..=substep::MAX_SUBSTEPS));
+                });
+                ui.checkbox(
+                    &mut physics.substep_config.auto,
+                    format!("Auto substeps (now {})", physics.current_substeps),
+                );
+                physics.substep_config = physics.substep_config.sanitized();
 
-    // Helper function to try initializing a specific backend
-    let init_backend = |backend: wgpu::Backends, canvas: web_sys::HtmlCanvasElement| async move {
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: backend,
-            ..Default::default()
-        });
+                egui::CollapsingHeader::new("Force Fields").show(ui, |ui| {
+                    if draw_force_field_controls(ui, &mut physics.force_fields) {
+                        wake_dynamic_bodies(&mut physics.rigid_body_set);
+                    }
+                });
 
-        let surface = match instance.create_surface(wgpu::SurfaceTarget::Canvas(canvas.clone())) {
-            Ok(s) => s,
-            Err(e) => return Err(format!("Failed to create surface: {:?}", e)),
-        };
+                egui::CollapsingHeader::new("Water").show(ui, |ui| {
+                    let mut config = physics.water.config();
+                    if draw_water_controls(ui, &mut config) {
+                        physics.water = Water::new(config);
+                        wake_dynamic_bodies(&mut physics.rigid_body_set);
+                    }
+                });
 
-        let adapter = match instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-        {
-            Ok(a) => a,
-            Err(_) => return Err("Failed to find suitable adapter".to_string()),
-        };
+                egui::CollapsingHeader::new("Slow-Motion Zones").show(ui, |ui| {
+                    if draw_time_dilation_controls(ui, &mut physics.time_dilation) {
+                        wake_dynamic_bodies(&mut physics.rigid_body_set);
+                    }
+                });
 
-        log::info!("Adapter backend: {:?}", adapter.get_info().backend);
-        log::info!("Adapter limits: {:?}", adapter.limits());
+                // Rewind
+                if physics.history.len() > 1 {
+                    let rewind_id = egui::Id::new("rewind_steps");
+                    let mut steps: usize = ui.data_mut(|d| *d.get_temp_mut_or_default(rewind_id));
+                    steps = steps.min(physics.history.len() - 1);
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Slider::new(&mut steps, 0..=physics.history.len() - 1).text("steps back"));
+                        if ui.button("Rewind").clicked() {
+                            snapshot::rewind(physics, steps);
+                            steps = 0;
+                        }
+                    });
+                    ui.data_mut(|d| d.insert_temp(rewind_id, steps));
+                }
 
-        let requested_limits = if backend == wgpu::Backends::BROWSER_WEBGPU {
-            // For WebGPU, trust the adapter to handle its own limits
-            adapter.limits()
-        } else {
-            // For WebGL, use safe downlevel defaults but try to bump texture size
-            let mut limits = wgpu::Limits::downlevel_webgl2_defaults();
-            let adapter_limits = adapter.limits();
-            limits.max_texture_dimension_2d = adapter_limits.max_texture_dimension_2d;
-            limits
-        };
+                ui.add_space(8.0);
 
-        log::info!("Requesting limits: {:?}", requested_limits);
+                // Bloom Toggle
+                if let Ok(mut settings) = RENDER_SETTINGS.lock() {
+                    ui.checkbox(&mut settings.bloom_enabled, "Bloom");
+                    if settings.bloom_enabled {
+                        ui.add(egui::Slider::new(&mut settings.bloom_threshold, 0.0..=2.0).text("Threshold"));
+                        ui.add(egui::Slider::new(&mut settings.bloom_intensity, 0.0..=3.0).text("Intensity"));
+                    }
 
-        let (device, queue) = match adapter
-            .request_device(&wgpu::DeviceDescriptor {
-                label: Some("physics_core device"),
-                required_features: wgpu::Features::empty(),
-                required_limits: requested_limits,
-                ..Default::default()
-            })
-            .await
-        {
-            Ok(dq) => dq,
-            Err(e) => return Err(format!("Failed to request device: {:?}", e)),
-        };
+                    // Contact Shadows
+                    ui.checkbox(&mut settings.shadow_enabled, "Contact Shadows");
+                    if settings.shadow_enabled {
+                        ui.add(egui::Slider::new(&mut settings.shadow_intensity, 0.0..=1.0).text("Shadow Intensity"));
+                        ui.add(egui::Slider::new(&mut settings.shadow_spread, 0.0..=2.0).text("Shadow Spread"));
+                    }
+                    ui.checkbox(&mut settings.culling_enabled, "Cull Offscreen Bodies");
+                    if settings.culling_enabled {
+                        ui.checkbox(&mut settings.gpu_culling, "Cull on GPU");
+                    }
+                }
 
-        Ok((instance, surface, adapter, device, queue))
-    };
+                ui.add_space(8.0);
 
-    // Try WebGPU first
-    let result = match init_backend(wgpu::Backends::BROWSER_WEBGPU, canvas.clone()).await {
-        Ok(res) => Ok(res),
-        Err(e) => {
-            log::warn!(
-                "WebGPU initialization failed: {}. Replacing canvas and falling back to WebGL...",
-                e
-            );
+                // Profiler
+                egui::CollapsingHeader::new("Profiler").show(ui, |ui| {
+                    ui.label(format!("{:.0} FPS, CPU {:.2} ms", stats.fps, stats.cpu_frame_ms));
+                    ui.label(format!("{} bodies drawn, {} culled", stats.drawn_instances, stats.culled_instances));
+                    if !stats.gpu_timing {
+                        ui.small("GPU pass timing not supported on this adapter");
+                    }
+                    let scale = stats.gpu_total_ms.max(16.7);
+                    for timing in &stats.gpu_passes {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{:<8} {:6.2} ms", timing.pass, timing.ms));
+                            ui.add(egui::ProgressBar::new(timing.ms / scale).desired_width(120.0));
+                        });
+                    }
+                    if stats.gpu_timing {
+                        ui.label(format!("GPU total {:.2} ms", stats.gpu_total_ms));
+                    }
+                });
 
-            // Replace the canvas to clear any tainted context
-            let parent = canvas.parent_node().unwrap();
-            let new_canvas_node = canvas.clone_node().unwrap();
-            parent.replace_child(&new_canvas_node, &canvas).unwrap();
+                let console_id = egui::Id::new("log_console");
+                let mut console: ConsoleView = ui.data_mut(|d| *d.get_temp_mut_or_default(console_id));
+                ui.checkbox(&mut console.open, "Log Console");
+                ui.data_mut(|d| d.insert_temp(console_id, console));
+                ui.checkbox(&mut editing, "Scene Editor");
 
-            let new_canvas: web_sys::HtmlCanvasElement = new_canvas_node.dyn_into().unwrap();
+                ui.add_space(24.0);
 
-            init_backend(wgpu::Backends::GL, new_canvas).await
+                // Reset Button
+                if ui.button("Reset Simulation").clicked() {
+                    // Drop lock before resetting to avoid deadlock if it locks again
+                    drop(physics_guard);
+                    reset_simulation_internal(ResetMode::Scene);
+                } else {
+                    // Native Info (at bottom)
+                    ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
+                        ui.add_space(8.0);
+                        ui.small(get_internal_info());
+                        ui.label("Native Info:");
+                        ui.separator();
+                    });
+                }
+            } else {
+                ui.label("Physics state not available");
+            }
+        });
+    if let Some(shown) = shown {
+        let rect = shown.response.rect;
+        if let Ok(mut layout) = EGUI_PANEL.lock() {
+            *layout = Some(PanelLayout { x: rect.min.x, y: rect.min.y, width: rect.width(), height: rect.height() });
         }
-    };
+    }
+    // The editor pauses the simulation, so it is switched outside the panel's lock
+    if editing != editing_before {
+        set_editor_enabled_internal(editing);
+    }
+    draw_log_console(egui_rend.context(), safe_rect);
+    draw_editor_window(egui_rend.context(), safe_rect);
+}
 
-    let (instance, surface, adapter, device, queue) = match result {
-        Ok(res) => res,
-        Err(e) => {
-            log::error!("Final initialization failed: {}", e);
-            return false;
-        }
+/// Tools, the selected body's properties and saving, while the scene editor is on
+fn draw_editor_window(ctx: &egui::Context, safe_rect: egui::Rect) {
+    let Some(mut editor) = EDITOR.lock().ok().map(|editor| editor.clone()).filter(Editor::is_enabled) else {
+        return;
     };
-    let adapter_info = adapter.get_info();
-
-    log::info!("Device acquired. getting surface caps...");
+    let mut open = true;
+    let (mut removed, mut save) = (None, false);
+    let history = HISTORY.lock().ok().map(|history| history.state());
+    egui::Window::new("Scene Editor")
+        .open(&mut open)
+        .default_size([320.0, 380.0])
+        .constrain_to(safe_rect)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for tool in EditorTool::ALL {
+                    ui.selectable_value(&mut editor.tool, tool, tool.name());
+                }
+            });
+            let mut snapping = editor.snap > 0.0;
+            if ui.checkbox(&mut snapping, "Snap to grid").changed() {
+                editor.snap = if snapping { 0.05 } else { 0.0 };
+            }
+            if snapping {
+                ui.add(egui::Slider::new(&mut editor.snap, 0.01..=0.5).text("Grid"));
+            }
+            if editor.tool == EditorTool::Place {
+                ui.add(egui::Slider::new(&mut editor.place_half_extent, editor::MIN_HALF_EXTENT..=0.5).text("Size"));
+                ui.checkbox(&mut editor.place_fixed, "Place fixed bodies");
+            }
+            // Undoing may respawn a body with a material, which needs the GPU
+            // lock this frame holds, so it waits for the end of the frame
+            if let Some(history) = &history {
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(history.undo > 0, egui::Button::new("Undo")).clicked() {
+                        editor.queue_history(true);
+                    }
+                    if ui.add_enabled(history.redo > 0, egui::Button::new("Redo")).clicked() {
+                        editor.queue_history(false);
+                    }
+                    ui.small(format!("{} steps", history.undo));
+                });
+            }
 
-    let surface_caps = surface.get_capabilities(&adapter);
-    log::info!(
-        "Surface caps acquired. Alpha Modes: {:?}",
-        surface_caps.alpha_modes
-    );
+            ui.separator();
+            let selected = editor.selected().and_then(|id| Some((id, scene_body_internal(id)?)));
+            match selected {
+                Some((id, mut body)) => {
+                    let before = body;
+                    ui.label(format!("Body {}", id));
+                    egui::Grid::new("editor_inspector").num_columns(2).show(ui, |ui| {
+                        ui.label("Position");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut body.x).speed(0.01).prefix("x "));
+                            ui.add(egui::DragValue::new(&mut body.y).speed(0.01).prefix("y "));
+                        });
+                        ui.end_row();
+                        ui.label("Half extent");
+                        ui.add(egui::DragValue::new(&mut body.half_extent).speed(0.005).range(editor::MIN_HALF_EXTENT..=editor::MAX_HALF_EXTENT));
+                        ui.end_row();
+                        ui.label("Angle");
+                        let mut degrees = body.angle.to_degrees();
+                        if ui.add(egui::DragValue::new(&mut degrees).speed(1.0).suffix("°")).changed() {
+                            body.angle = degrees.to_radians();
+                        }
+                        ui.end_row();
+                        ui.label("Velocity");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut body.vx).speed(0.01).prefix("x "));
+                            ui.add(egui::DragValue::new(&mut body.vy).speed(0.01).prefix("y "));
+                        });
+                        ui.end_row();
+                        ui.label("Type");
+                        egui::ComboBox::from_id_salt("editor_body_type")
+                            .selected_text(format!("{:?}", body.body_type))
+                            .show_ui(ui, |ui| {
+                                for body_type in [SceneBodyType::Dynamic, SceneBodyType::Fixed, SceneBodyType::Kinematic] {
+                                    ui.selectable_value(&mut body.body_type, body_type, format!("{:?}", body_type));
+                                }
+                            });
+                        ui.end_row();
+                        ui.label("Groups");
+                        ui.add(egui::DragValue::new(&mut body.groups));
+                        ui.end_row();
+                        ui.label("Material");
+                        ui.label(body.material_id.to_string());
+                        ui.end_row();
+                    });
+                    // A drag on one value is one step, however many frames it takes
+                    let drag_id = egui::Id::new("editor_inspector_drag");
+                    let dragging = ui.ctx().dragged_id().is_some();
+                    let was_dragging = ui.data_mut(|d| d.get_temp(drag_id).unwrap_or(false));
+                    ui.data_mut(|d| d.insert_temp(drag_id, dragging));
+                    if body != before {
+                        edit_body_internal(id, &body, dragging && was_dragging);
+                    }
+                    if ui.button("Delete").clicked() {
+                        removed = Some(id);
+                    }
+                }
+                None => {
+                    ui.label("Click a box to select it");
+                }
+            }
 
-    let surface_format = surface_caps
-        .formats
-        .iter()
-        .copied()
-        .find(|f| f.is_srgb())
-        .unwrap_or(surface_caps.formats[0]);
-    log::info!("Selected surface format: {:?}", surface_format);
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut editor.save_path);
+                save = ui.button("Save").clicked();
+            });
+        });
 
-    let max_dimension = device.limits().max_texture_dimension_2d;
+    if let Some(id) = removed {
+        edit_remove_body_internal(id);
+        editor.forget(id);
+    }
+    if save {
+        match save_editor_scene_internal(std::path::Path::new(&editor.save_path)) {
+            Ok(bodies) => log::info!("Saved {} bodies to {}", bodies, editor.save_path),
+            Err(e) => log::error!("Scene not saved: {}", e),
+        }
+    }
+    if let Ok(mut slot) = EDITOR.lock() {
+        *slot = editor;
+    }
+    if !open {
+        set_editor_enabled_internal(false);
+    }
+}
 
-    let config = wgpu::SurfaceConfiguration {
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        format: surface_format,
-        width: width.min(max_dimension),
-        height: height.min(max_dimension),
+/// The last lines logged, filtered by level, with copy and clear buttons
+fn draw_log_console(ctx: &egui::Context, safe_rect: egui::Rect) {
+    let console_id = egui::Id::new("log_console");
+    let mut console: ConsoleView = ctx.data_mut(|d| *d.get_temp_mut_or_default(console_id));
+    if !console.open {
+        return;
+    }
+    egui::Window::new("Log Console")
+        .open(&mut console.open)
+        .default_size([560.0, 280.0])
+        .constrain_to(safe_rect)
+        .show(ctx, |ui| {
+            let lines = log_console::recent(console.max_level);
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Level")
+                    .selected_text(console.max_level.as_str())
+                    .show_ui(ui, |ui| {
+                        for level in [log::LevelFilter::Error, log::LevelFilter::Warn, log::LevelFilter::Info, log::LevelFilter::Debug, log::LevelFilter::Trace] {
+                            ui.selectable_value(&mut console.max_level, level, level.as_str());
+                        }
+                    });
+                if ui.button("Copy").clicked() {
+                    ui.ctx().copy_text(log_console::to_text(&lines));
+                }
+                if ui.button("Clear").clicked() {
+                    log_console::clear();
+                }
+                ui.label(format!("{} lines", lines.len()));
+            });
+            ui.separator();
+
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            egui::ScrollArea::both()
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show_rows(ui, row_height, lines.len(), |ui, rows| {
+                    for line in &lines[rows] {
+                        let color = match line.level {
+                            log::Level::Error => egui::Color32::from_rgb(190, 0, 0),
+                            log::Level::Warn => egui::Color32::from_rgb(160, 100, 0),
+                            log::Level::Info => egui::Color32::BLACK,
+                            log::Level::Debug | log::Level::Trace => egui::Color32::DARK_GRAY,
+                        };
+                        let text = egui::RichText::new(line.to_text()).monospace().color(color);
+                        ui.add(egui::Label::new(text).extend());
+                    }
+                });
+        });
+    ctx.data_mut(|d| d.insert_temp(console_id, console));
+}
+
+/// State that outlives a device rebuild; the simulation itself lives in
+/// PHYSICS_STATE and is never touched
+struct GpuCarryOver {
+    surface_source: SurfaceSource,
+    window_ptr: *mut c_void,
+    width: u32,
+    height: u32,
+    camera: Camera,
+    materials: MaterialRegistry,
+    /// Id, surface source, size and camera of every extra view
+    views: Vec<(u32, SurfaceSource, u32, u32, ViewCamera)>,
+    /// Loaded 3D models by id
+    models: Vec<(u32, gltf_lite::ModelMesh)>,
+}
+
+impl GpuCarryOver {
+    /// Everything else in `old`, its surface and device included, is dropped here
+    fn take(mut old: WgpuState) -> Self {
+        let views = old
+            .views
+            .drain()
+            .into_iter()
+            .map(|(id, view)| (id, view.source, view.config.width, view.config.height, view.camera))
+            .collect();
+        let models = old.bevy_3d_sample.as_mut().map(Bevy3DSample::take_models).unwrap_or_default();
+        Self {
+            surface_source: old.surface_source,
+            window_ptr: old.window_ptr,
+            width: old.config.width,
+            height: old.config.height,
+            camera: old.camera,
+            materials: old.materials,
+            views,
+            models,
+        }
+    }
+
+    fn restore(self, state: &mut WgpuState) {
+        state.camera = self.camera;
+        write_camera_uniform(state);
+        state.materials = self.materials.rebuild(
+            &state.device,
+            &state.queue,
+            &state.texture_bind_group_layout,
+            state.diffuse_bind_group.clone(),
+        );
+        for (id, source, width, height, camera) in self.views {
+            match SurfaceView::new(state, source, width, height, camera) {
+                Ok(view) => state.views.restore(id, view),
+                Err(e) => log::warn!("View {} not restored: {}", id, e),
+            }
+        }
+        if let Some(bevy_3d) = state.bevy_3d_sample.as_mut() {
+            bevy_3d.restore_models(&state.device, self.models);
+        }
+    }
+}
+
+/// Run any pending surface or device recovery. Returns false when this frame
+/// should be skipped.
+fn recover_gpu(slot: &mut Option<WgpuState>, window: Option<&winit::window::Window>) -> bool {
+    let Some(state) = slot.as_mut() else {
+        return false;
+    };
+    if DEVICE_LOST.swap(false, Ordering::Relaxed) {
+        state.recovery = Some(Recovery::Device);
+    }
+
+    match state.recovery {
+        None => true,
+        Some(Recovery::Surface) => match state.surface_source.create_surface(&state.instance) {
+            Ok(surface) => {
+                surface.configure(&state.device, &state.config);
+                state.surface = surface;
+                state.recovery = None;
+                log::info!("Surface recreated");
+                true
+            }
+            Err(e) => {
+                log::warn!("Failed to recreate surface ({:?}), rebuilding GPU state", e);
+                state.recovery = Some(Recovery::Device);
+                false
+            }
+        },
+        Some(Recovery::Device) => rebuild_wgpu_state(slot, window),
+    }
+}
+
+/// Recreate device, surface and GPU resources from the stored handles. The
+/// instance buffer starts unsynced, so the next sync uploads every body.
+#[cfg(not(target_arch = "wasm32"))]
+fn rebuild_wgpu_state(slot: &mut Option<WgpuState>, window: Option<&winit::window::Window>) -> bool {
+    let Some(old) = slot.take() else {
+        return false;
+    };
+    log::warn!("Rebuilding GPU device and resources");
+    let carry = GpuCarryOver::take(old);
+    match create_wgpu_state(carry.surface_source.clone(), carry.width, carry.height, carry.window_ptr, window) {
+        Some(mut state) => {
+            carry.restore(&mut state);
+            *slot = Some(state);
+            log::info!("GPU state rebuilt");
+            true
+        }
+        None => {
+            // The simulation keeps its state; rendering resumes on the next wgpu_init
+            log::error!("Failed to rebuild GPU state");
+            INITIALIZED.store(false, Ordering::Relaxed);
+            false
+        }
+    }
+}
+
+/// Adapter and device requests are async on the web, so the rebuild is handed
+/// to `wasm_init`; the old state stays in place until then so it can carry over.
+#[cfg(target_arch = "wasm32")]
+fn rebuild_wgpu_state(slot: &mut Option<WgpuState>, _window: Option<&winit::window::Window>) -> bool {
+    #[cfg(feature = "wasm_support")]
+    if let Some(state) = slot.as_ref() {
+        if !WASM_REINIT_PENDING.swap(true, Ordering::Relaxed) {
+            let canvas_id = WASM_CANVAS_ID.with(|id| id.borrow().clone());
+            let (width, height) = (state.config.width, state.config.height);
+            wasm_bindgen_futures::spawn_local(async move {
+                wasm_init(&canvas_id, width, height).await;
+            });
+        }
+    }
+    #[cfg(not(feature = "wasm_support"))]
+    {
+        *slot = None;
+        INITIALIZED.store(false, Ordering::Relaxed);
+    }
+    false
+}
+
+/// Monotonic clock for frame timing, in milliseconds
+fn frame_clock_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        web_sys::window().and_then(|w| w.performance()).map_or(0.0, |p| p.now())
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        static START: Lazy<std::time::Instant> = Lazy::new(std::time::Instant::now);
+        START.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+fn render_internal(window: Option<&winit::window::Window>) {
+    let _span = tracing::debug_span!(target: "render", "frame").entered();
+
+    static FPS_CAP_MS: f64 = 12.0f64; // Approx 60 FPS.  16.6ms = 1000/60. Allow slight tolerance?
+
+    // No GPU work at all in the background
+    if app_paused() {
+        return;
+    }
+
+    // Throttling Logic (60 FPS Cap); a captured frame or screenshot is never skipped
+    let min_frame_ms = governor_throttle().frame_interval_ms().map_or(FPS_CAP_MS, |ms| ms.max(FPS_CAP_MS));
+    let capturing = capture::is_active();
+    let screenshot = capture::screenshot_pending();
+    if let Ok(mut guard) = WGPU_STATE.lock() {
+        if let Some(state) = guard.0.as_mut().filter(|_| !capturing && !screenshot) {
+             #[cfg(target_arch = "wasm32")]
+             {
+                 let now = web_sys::window().unwrap().performance().unwrap().now();
+                 let elapsed = now - state.last_render_time;
+                 
+                 if elapsed < min_frame_ms {
+                     return;
+                 }
+                 state.last_render_time = now;
+             }
+
+             #[cfg(not(target_arch = "wasm32"))]
+             {
+                 let now = std::time::Instant::now();
+                 let elapsed = now.duration_since(state.last_render_time);
+                 if elapsed.as_secs_f64() * 1000.0 < min_frame_ms {
+                     return;
+                 }
+                 state.last_render_time = now;
+             }
+        }
+    }
+
+    // Rebuild lost GPU state before anything touches it this frame
+    if let Ok(mut guard) = WGPU_STATE.lock() {
+        if guard.0.is_some() && !recover_gpu(&mut guard.0, window) {
+            return;
+        }
+    }
+
+    plugins::run_hooks(plugins::HookPoint::PreRender, 0.0);
+
+    // Sync physics to GPU FIRST (before acquiring swapchain texture)
+    // This avoids acquiring a texture and then dropping it without presenting.
+    sync_physics_to_gpu();
+    let text_labels = visible_text_labels();
+    let camera_offset = advance_camera_effects();
+    let fade_alpha = advance_scene_fade();
+    
+    // Read back while capturing, written out once the GPU lock is released
+    let mut captured = None;
+    let mut screenshots = Vec::new();
+
+    // Now acquire texture and render in a single lock session
+    if let Ok(mut guard) = WGPU_STATE.lock() {
+        if let Some(state) = guard.0.as_mut() {
+            // Safe point between frames: nothing is recorded yet, so pipelines can be swapped
+            #[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+            reload_changed_shaders(state);
+
+            if camera_offset != state.camera_offset {
+                state.camera_offset = camera_offset;
+                write_camera_uniform(state);
+            }
+
+            let settings = RENDER_SETTINGS.lock().map(|s| *s).unwrap_or_default();
+            let bloom = if settings.bloom_enabled { state.bloom.as_ref() } else { None };
+            let fade = fade_overlay_vertices(state, settings.clear_color, fade_alpha);
+            state.fade_pass.prepare(&state.device, &state.queue, &fade);
+            state.background_pass.prepare(&state.queue, state.config.width, state.config.height);
+
+            // Resolve the pass order before acquiring the swapchain texture so a
+            // broken graph never leaves a texture acquired but unpresented
+            let graph = build_frame_graph(
+                state.gpu_cull.as_ref().is_some_and(GpuCullPass::active),
+                bloom.is_some(),
+                state.minimap_pass.is_active(),
+                !text_labels.is_empty(),
+                state.bevy_3d_sample.is_some(),
+                state.egui_renderer.is_some() && window.is_some(),
+            );
+            let passes = match graph.schedule() {
+                Ok(passes) => passes,
+                Err(e) => {
+                    tracing::error!(target: "render", "Invalid render graph: {:?}", e);
+                    return;
+                }
+            };
+
+            // A captured frame goes to an offscreen texture; the surface keeps its last
+            // frame unless a screenshot is taken and the surface takes copies
+            let offscreen = capturing || screenshot;
+            let capture_view = if offscreen { capture_target_view(state) } else { None };
+            let present_copy = screenshot && state.config.usage.contains(wgpu::TextureUsages::COPY_DST);
+            let (output, view) = match capture_view {
+                Some(view) if !present_copy => (None, view),
+                capture_view => {
+                    let output = match state.surface.get_current_texture() {
+                        Ok(o) => {
+                            state.lost_frames = 0;
+                            o
+                        }
+                        Err(e) => {
+                            tracing::warn!(target: "render", "Failed to get current texture: {:?}", e);
+                            match e {
+                                wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => {
+                                    // Reconfigure first, then recreate the surface, then the whole device
+                                    state.lost_frames += 1;
+                                    match state.lost_frames {
+                                        1 => state.surface.configure(&state.device, &state.config),
+                                        2 => state.recovery = Some(Recovery::Surface),
+                                        _ => state.recovery = Some(Recovery::Device),
+                                    }
+                                }
+                                wgpu::SurfaceError::OutOfMemory => {
+                                    tracing::error!(target: "render", "Out of memory acquiring the surface texture, rebuilding GPU state");
+                                    state.recovery = Some(Recovery::Device);
+                                }
+                                wgpu::SurfaceError::Timeout => {
+                                    // On timeout, try to reconfigure the surface
+                                    tracing::warn!(target: "render", "Surface timeout, reconfiguring surface");
+                                    state.surface.configure(&state.device, &state.config);
+                                }
+                                _ => {}
+                            }
+                            return;
+                        }
+                    };
+                    let view = capture_view.unwrap_or_else(|| output.texture.create_view(&wgpu::TextureViewDescriptor::default()));
+                    (Some(output), view)
+                }
+            };
+
+            let frame_start = frame_clock_ms();
+            if let Some(timer) = state.gpu_timer.as_mut() {
+                if let Some(passes) = timer.collect(&state.device) {
+                    state.frame_stats.record_gpu_passes(&passes);
+                }
+                timer.begin_frame();
+            }
+            state.frame_stats.gpu_timing = state.gpu_timer.is_some();
+
+            let mut encoder = state.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+            if !text_labels.is_empty() {
+                let view_proj = state.camera.build_view_projection_matrix_with(&state.camera_offset);
+                let WgpuState { device, queue, text_pass, config, .. } = state;
+                text_pass.prepare(device, queue, &text_labels, &view_proj, config.width, config.height);
+            }
+
+            if settings.shadow_enabled {
+                state.contact_shadows.update(
+                    &state.queue,
+                    settings.shadow_intensity,
+                    settings.shadow_spread,
+                    settings.shadow_offset,
+                );
+            }
+
+            for pass in passes {
+                let _pass_span = tracing::trace_span!(target: "render", "pass", name = pass.label()).entered();
+                // Debug group and timestamps share the pass name, so captures match the profiler
+                encoder.push_debug_group(pass.label());
+                if let Some(timer) = state.gpu_timer.as_mut() {
+                    timer.begin_pass(&mut encoder, pass.label());
+                }
+                match pass {
+                    PassId::Compute => encode_compute_pass(state, &mut encoder),
+                    PassId::Cull => {
+                        if let Some(pass) = state.gpu_cull.as_ref() {
+                            pass.encode(&mut encoder);
+                        }
+                    }
+                    PassId::Main => encode_main_pass(state, &mut encoder, &view, &state.main_target(), bloom, &settings),
+                    PassId::Post => {
+                        if let Some(bloom) = bloom {
+                            bloom.encode(
+                                &state.queue,
+                                &mut encoder,
+                                &view,
+                                settings.bloom_threshold,
+                                settings.bloom_intensity,
+                            );
+                        }
+                    }
+                    PassId::Minimap => state.minimap_pass.encode(&mut encoder, &view),
+                    PassId::Text => state.text_pass.encode(&mut encoder, &view),
+                    PassId::Debug => {
+                        if let Some(bevy_3d) = state.bevy_3d_sample.as_mut() {
+                            // Fixed 60fps step; the sample only spins the cube
+                            bevy_3d.update(&state.queue, 0.016);
+                            bevy_3d.set_camera_bind_group(state.camera_bind_group.clone()); // Ensure it's using the current camera BG
+                            encode_debug_pass(bevy_3d, &mut encoder, &view);
+                        }
+                    }
+                    PassId::Egui => {
+                        if let (Some(egui_rend), Some(window)) = (state.egui_renderer.as_mut(), window) {
+                            let screen_descriptor = ScreenDescriptor {
+                                size_in_pixels: [state.config.width, state.config.height],
+                                pixels_per_point: scale_factor() * 1.5, // Scale up UI (1.5x)
+                            };
+                            draw_egui_panel(egui_rend, window, &state.frame_stats);
+                            egui_rend.end_frame_and_draw(
+                                &state.device,
+                                &state.queue,
+                                &mut encoder,
+                                window,
+                                &view,
+                                screen_descriptor,
+                            );
+                        }
+                    }
+                }
+                if let Some(timer) = state.gpu_timer.as_mut() {
+                    timer.end_pass(&mut encoder);
+                }
+                encoder.pop_debug_group();
+            }
+
+            if let Some(timer) = state.gpu_timer.as_mut() {
+                timer.resolve(&mut encoder);
+            }
+            let capture_target = state.capture_target.as_ref().filter(|_| offscreen);
+            if let Some(target) = capture_target {
+                target.encode_copy(&mut encoder);
+                if let Some(output) = &output {
+                    target.encode_present_copy(&mut encoder, &output.texture);
+                }
+            }
+            state.queue.submit(std::iter::once(encoder.finish()));
+            if let Some(timer) = state.gpu_timer.as_mut() {
+                timer.after_submit();
+            }
+            if let Some(target) = capture_target {
+                captured = target.read(&state.device).map(|rgba| (state.config.width, state.config.height, rgba));
+                // Requests made from here on wait for the next frame
+                if captured.is_some() && screenshot {
+                    screenshots = capture::take_screenshots();
+                }
+            }
+            state.frame_stats.record_cpu_frame((frame_clock_ms() - frame_start) as f32);
+
+            // Present with panic recovery to handle Vulkan driver issues
+            if let Some(output) = output {
+                let present_result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    output.present();
+                }));
+
+                if present_result.is_err() {
+                    tracing::error!(target: "render", "Present panicked! Reconfiguring surface...");
+                    state.surface.configure(&state.device, &state.config);
+                    return;
+                }
+            }
+
+            render_views(state, &settings);
+
+            // FPS Logging
+            state.frame_count += 1;
+            #[cfg(target_arch = "wasm32")]
+            {
+                let now = web_sys::window().unwrap().performance().unwrap().now();
+                let elapsed = (now - state.last_fps_log_time) / 1000.0;
+                if elapsed >= 1.0 {
+                     tracing::info!(target: "render", "FPS: {:.2}", state.frame_count as f64 / elapsed);
+                     state.frame_stats.fps = (state.frame_count as f64 / elapsed) as f32;
+                     state.frame_count = 0;
+                     state.last_fps_log_time = now;
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let now = std::time::Instant::now();
+                let elapsed = now.duration_since(state.last_fps_log_time).as_secs_f64();
+                if elapsed >= 1.0 {
+                     tracing::info!(target: "render", "FPS: {:.2}", state.frame_count as f64 / elapsed);
+                     state.frame_stats.fps = (state.frame_count as f64 / elapsed) as f32;
+                     state.frame_count = 0;
+                     state.last_fps_log_time = now;
+                }
+            }
+
+        } else {
+            // log::warn!("WGPU_STATE is None");
+        }
+    } else {
+        tracing::error!(target: "render", "Failed to lock WGPU_STATE");
+    }
+
+    if let Some((width, height, rgba)) = captured {
+        let frame = CapturedFrame { index: 0, width, height, rgba };
+        if !screenshots.is_empty() {
+            capture::deliver_screenshots(screenshots, &frame);
+        }
+        if capturing {
+            capture::deliver(width, height, frame.rgba);
+        }
+    }
+    run_queued_history();
+}
+
+/// Offscreen target for a captured frame, (re)made to match the surface
+fn capture_target_view(state: &mut WgpuState) -> Option<wgpu::TextureView> {
+    if !state.capture_target.as_ref().is_some_and(|target| target.matches(&state.config)) {
+        state.capture_target = CaptureTarget::new(&state.device, &state.config);
+    }
+    state.capture_target.as_ref().map(|target| target.view().clone())
+}
+
+/// Start capturing frames (see `capture`). Fails before `wgpu_init`, for
+/// surfaces whose format cannot be read back and for invalid configs.
+fn start_capture_internal(config: CaptureConfig, sink: Option<capture::FrameSink>) -> Result<(), String> {
+    let format = WGPU_STATE.lock().map_err(|e| e.to_string())?.0.as_ref().map(|state| state.config.format).ok_or("renderer not running")?;
+    if !CaptureTarget::supports(format) {
+        return Err(format!("cannot capture {:?} frames", format));
+    }
+    capture::start(config, sink)
+}
+
+fn start_capture_json_internal(json: &str, sink: Option<capture::FrameSink>) -> Result<(), String> {
+    let config: CaptureConfig = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    start_capture_internal(config, sink)
+}
+
+/// Screenshot of the next frame rendered, egui included when the winit app
+/// draws it. Fails before `wgpu_init` and for surfaces whose format cannot
+/// be read back.
+fn request_screenshot_internal(target: ScreenshotTarget) -> Result<(), String> {
+    let format = WGPU_STATE.lock().map_err(|e| e.to_string())?.0.as_ref().map(|state| state.config.format).ok_or("renderer not running")?;
+    if !CaptureTarget::supports(format) {
+        return Err(format!("cannot capture {:?} frames", format));
+    }
+    capture::request_screenshot(target);
+    Ok(())
+}
+
+/// Render and present a frame now and return it as PNG. The frame has no
+/// egui panel, which only the winit app's own frames draw.
+fn take_screenshot_internal() -> Result<Vec<u8>, String> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let sink: capture::FrameSink = Arc::new(move |frame: &CapturedFrame| {
+        let _ = sender.send(capture::encode_png(frame.width, frame.height, &frame.rgba));
+    });
+    request_screenshot_internal(ScreenshotTarget::Sink(sink))?;
+    render_internal(None);
+    receiver.try_recv().map_err(|_| "no frame was rendered".to_string())
+}
+
+/// Key for screenshots in the winit app (a winit `KeyCode`, negative to
+/// disable) and the directory they are written to, created if missing
+fn set_screenshot_key_internal(key_code: i32, directory: Option<&str>) -> Result<(), String> {
+    if let Some(directory) = directory {
+        std::fs::create_dir_all(directory).map_err(|e| format!("{}: {}", directory, e))?;
+    }
+    let mut key = SCREENSHOT_KEY.lock().map_err(|e| e.to_string())?;
+    key.0 = (key_code >= 0).then_some(key_code);
+    if let Some(directory) = directory {
+        key.1 = directory.into();
+    }
+    Ok(())
+}
+
+/// Queue a screenshot file if `key_code` is the screenshot key
+fn on_screenshot_key(key_code: i32) {
+    let Some(directory) = SCREENSHOT_KEY.lock().ok().filter(|key| key.0 == Some(key_code)).map(|key| key.1.clone()) else {
+        return;
+    };
+    let millis = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |since| since.as_millis());
+    if let Err(e) = request_screenshot_internal(ScreenshotTarget::File(capture::screenshot_path(&directory, millis))) {
+        log::warn!("Screenshot not taken: {}", e);
+    }
+}
+
+/// Draw the scene (bodies and bloom) into the capture target and read it
+/// back as RGBA at the surface size
+fn render_offscreen(state: &mut WgpuState, settings: &RenderSettings) -> Option<Vec<u8>> {
+    let view = capture_target_view(state)?;
+    let bloom = if settings.bloom_enabled { state.bloom.as_ref() } else { None };
+    let mut encoder = state.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Clip Encoder"),
+    });
+    encode_main_pass(state, &mut encoder, &view, &state.main_target(), bloom, settings);
+    if let Some(bloom) = bloom {
+        bloom.encode(&state.queue, &mut encoder, &view, settings.bloom_threshold, settings.bloom_intensity);
+    }
+    let target = state.capture_target.as_ref()?;
+    target.encode_copy(&mut encoder);
+    state.queue.submit(std::iter::once(encoder.finish()));
+    target.read(&state.device)
+}
+
+/// Animated GIF of the last `seconds` of the rewind history at `fps`,
+/// `scale` times the surface size (see `clip`)
+fn export_clip_internal(seconds: f32, fps: u32, scale: f32) -> Result<Vec<u8>, String> {
+    if !clip::is_valid(seconds, fps, scale) {
+        return Err(format!("invalid clip: {} s at {} fps, scale {}", seconds, fps, scale));
+    }
+    let minimap = MINIMAP.lock().ok().and_then(|config| *config);
+    let settings = RENDER_SETTINGS.lock().map(|s| *s).unwrap_or_default();
+    // Same lock order as the render path: WGPU_STATE, then PHYSICS_STATE
+    let mut wgpu_guard = WGPU_STATE.lock().map_err(|e| e.to_string())?;
+    let state = wgpu_guard.0.as_mut().ok_or("renderer not running")?;
+    if !CaptureTarget::supports(state.config.format) {
+        return Err(format!("cannot capture {:?} frames", state.config.format));
+    }
+    let mut physics_guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = physics_guard.0.as_mut().ok_or("simulation not running")?;
+
+    let step_dts: Vec<f32> = (0..physics.history.len()).rev().filter_map(|back| physics.history.get(back)).map(|entry| entry.dt).collect();
+    let frames = clip::pick_frames(&step_dts, seconds, fps);
+    if frames.is_empty() {
+        return Err("no rewind history to export".to_string());
+    }
+
+    // The live bodies are set aside while the history is drawn, then drawn again
+    let live = std::mem::take(&mut physics.rigid_body_set);
+    let result = render_clip(state, physics, &frames, scale, &settings, minimap);
+    physics.rigid_body_set = live;
+    physics.instance_layout_dirty = true;
+    sync_to_gpu(state, physics, minimap);
+    result
+}
+
+fn render_clip(
+    state: &mut WgpuState,
+    physics: &mut PhysicsState,
+    frames: &[clip::ClipFrame],
+    scale: f32,
+    settings: &RenderSettings,
+    minimap: Option<MinimapConfig>,
+) -> Result<Vec<u8>, String> {
+    let mut gif: Option<clip::GifWriter> = None;
+    for frame in frames {
+        let back = physics.history.len() - 1 - frame.index;
+        let entry = physics.history.get(back).ok_or("history changed")?;
+        physics.rigid_body_set = snapshot::bodies(&entry.bytes)?;
+        physics.instance_layout_dirty = true;
+        sync_to_gpu(state, physics, minimap);
+        let rgba = render_offscreen(state, settings).ok_or("reading back a clip frame failed")?;
+        let (width, height, rgba) = clip::downscale(&rgba, state.config.width, state.config.height, scale);
+        let gif = match gif.as_mut() {
+            Some(gif) => gif,
+            None => gif.insert(clip::GifWriter::new(width, height).ok_or("clip too large for a GIF")?),
+        };
+        gif.add_frame(&rgba, frame.delay);
+    }
+    gif.map(clip::GifWriter::finish).ok_or_else(|| "empty clip".to_string())
+}
+
+fn shutdown_internal() {
+    log::info!("Shutting down wgpu");
+    // Before the camera goes away with the GPU state
+    if let Err(e) = save_settings_internal() {
+        log::warn!("Settings not saved: {}", e);
+    }
+    let old = WGPU_STATE.lock().ok().and_then(|mut guard| guard.0.take());
+    // A surface lost in the background comes back on the next init with the
+    // scene as it was
+    if let Some(old) = old.filter(|_| app_paused()) {
+        log::info!("App paused; keeping the simulation and camera for the next init");
+        if let Ok(mut parked) = PARKED_GPU.lock() {
+            parked.0 = Some(GpuCarryOver::take(old));
+        }
+    }
+    capture::stop();
+    INITIALIZED.store(false, Ordering::Relaxed);
+}
+
+/// Move what a paused shutdown kept into the new `state`. True when there
+/// was something, in which case the simulation is kept too.
+fn restore_parked_gpu(state: &mut WgpuState) -> bool {
+    let Some(carry) = PARKED_GPU.lock().ok().and_then(|mut parked| parked.0.take()) else {
+        return false;
+    };
+    carry.restore(state);
+    // The new surface may not have the old one's shape
+    fit_camera_to_world_rect(state);
+    PHYSICS_STATE.lock().is_ok_and(|guard| guard.0.is_some())
+}
+
+fn app_paused() -> bool {
+    LIFECYCLE.lock().is_ok_and(|lifecycle| lifecycle.is_paused())
+}
+
+/// The app went to the background: stop rendering and, with
+/// `freeze_physics`, stepping, keeping every bit of state (see `lifecycle`).
+/// Settings are saved, since a paused process may be killed without notice.
+fn on_app_pause_internal(freeze_physics: bool) {
+    let newly_paused = LIFECYCLE.lock().is_ok_and(|mut lifecycle| lifecycle.pause(freeze_physics));
+    if newly_paused {
+        log::info!("App paused (physics {})", if freeze_physics { "frozen" } else { "running" });
+        if let Err(e) = save_settings_internal() {
+            log::warn!("Settings not saved: {}", e);
+        }
+    }
+}
+
+/// Back in the foreground; the next update is an empty frame
+fn on_app_resume_internal() {
+    if LIFECYCLE.lock().is_ok_and(|mut lifecycle| lifecycle.resume()) {
+        log::info!("App resumed");
+    }
+}
+
+/// Everything `settings_store` keeps, one lock at a time. Parts whose state
+/// is gone (no surface, no simulation) are None.
+fn gather_settings() -> SavedSettings {
+    let panel = EGUI_PANEL.lock().ok().and_then(|panel| *panel);
+    let render = RENDER_SETTINGS.lock().ok().map(|settings| *settings);
+    // The 3D demo's perspective camera is not kept
+    let camera = WGPU_STATE.lock().ok().and_then(|guard| {
+        let camera = guard.0.as_ref()?.camera;
+        camera.is_orthographic.then_some(CameraSettings { x: camera.target.x, y: camera.target.y, view_height: camera.ortho_size })
+    });
+    let physics = PHYSICS_STATE.lock().ok().and_then(|guard| {
+        guard.0.as_ref().map(|physics| PhysicsSettings {
+            gravity: physics.gravity.map(to_f32).into(),
+            time_scale: physics.time_scale,
+            sleep_enabled: physics.sleep_enabled,
+            solver: physics.solver,
+            substeps: physics.substep_config,
+        })
+    });
+    SavedSettings { panel, render, camera, physics }
+}
+
+/// Save to the settings store; parts with no state right now keep what was
+/// saved before
+fn save_settings_internal() -> Result<(), String> {
+    let Some(store) = SETTINGS_STORE.lock().ok().and_then(|store| store.clone()) else {
+        return Err("no settings location".into());
+    };
+    let mut settings = gather_settings();
+    if let Some(previous) = store.load() {
+        settings.panel = settings.panel.or(previous.panel);
+        settings.render = settings.render.or(previous.render);
+        settings.camera = settings.camera.or(previous.camera);
+        settings.physics = settings.physics.or(previous.physics);
+    }
+    store.save(&settings)
+}
+
+/// Apply what the settings store holds. Returns false when nothing was saved
+/// there (or it does not parse). The MSAA sample count is left alone, since
+/// the surface is already built with it.
+fn restore_settings_internal() -> bool {
+    let Some(saved) = SETTINGS_STORE.lock().ok().and_then(|store| store.as_ref()?.load()) else {
+        return false;
+    };
+    if let Some(panel) = saved.panel {
+        if let Ok(mut layout) = EGUI_PANEL.lock() {
+            *layout = Some(panel);
+        }
+    }
+    if let Some(render) = saved.render {
+        if let Ok(mut settings) = RENDER_SETTINGS.lock() {
+            *settings = RenderSettings { msaa_samples: settings.msaa_samples, ..render };
+        }
+    }
+    if let Some(camera) = saved.camera {
+        if let Ok(mut guard) = WGPU_STATE.lock() {
+            if let Some(state) = guard.0.as_mut().filter(|state| state.camera.is_orthographic) {
+                state.camera.look_at(camera.x, camera.y);
+                state.camera.set_view_height(camera.view_height);
+                write_camera_uniform(state);
+            }
+        }
+    }
+    if let Some(saved) = saved.physics {
+        if let Ok(mut guard) = PHYSICS_STATE.lock() {
+            if let Some(physics) = guard.0.as_mut() {
+                physics.gravity = Vector::from(saved.gravity).map(to_real);
+                physics.time_scale = saved.time_scale;
+                physics.time_ramp = None;
+                physics.sleep_enabled = saved.sleep_enabled;
+                apply_sleep_setting(&mut physics.rigid_body_set, saved.sleep_enabled);
+                physics.solver = saved.solver.sanitized();
+                physics.substep_config = saved.substeps.sanitized();
+            }
+        }
+    }
+    true
+}
+
+/// Keep settings in `dir` (created if missing), or nowhere for None
+fn set_settings_directory_internal(dir: Option<&str>) -> bool {
+    let store = match dir {
+        Some(dir) => {
+            let dir = std::path::Path::new(dir);
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                log::error!("Settings directory {}: {}", dir.display(), e);
+                return false;
+            }
+            Some(SettingsStore::in_directory(dir))
+        }
+        None => None,
+    };
+    if let Ok(mut current) = SETTINGS_STORE.lock() {
+        *current = store;
+    }
+    true
+}
+
+/// Write crash reports into `dir` (created if missing), or nowhere for None
+fn set_crash_directory_internal(dir: Option<&str>) -> bool {
+    let dir = match dir {
+        Some(dir) => {
+            let dir = std::path::Path::new(dir);
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                log::error!("Crash directory {}: {}", dir.display(), e);
+                return false;
+            }
+            Some(dir.to_path_buf())
+        }
+        None => None,
+    };
+    crash::set_directory(dir);
+    true
+}
+
+/// Public entity ids are the bit-packed bevy `Entity` (index + generation).
+/// An id dies with its entity and with every reset; a dead id is never
+/// handed out again.
+fn entity_from_id(id: u64) -> Option<Entity> {
+    match Entity::try_from_bits(id) {
+        Ok(entity) => Some(entity),
+        Err(_) => {
+            validation::reject(InputError::unknown_entity(id));
+            None
+        }
+    }
+}
+
+/// Whether `id` names an entity of the current scene
+fn is_entity_alive_internal(id: u64) -> bool {
+    PHYSICS_STATE.lock().is_ok_and(|guard| guard.0.as_ref().is_some_and(|physics| entity_alive(physics, id)))
+}
+
+fn entity_alive(physics: &PhysicsState, id: u64) -> bool {
+    Entity::try_from_bits(id).is_ok_and(|entity| physics.world.entities().contains(entity))
+}
+
+/// Times the scene was reset or replaced since startup
+fn reset_count_internal() -> u32 {
+    PHYSICS_STATE.lock().map_or(0, |guard| guard.0.as_ref().map_or(0, |physics| physics.resets))
+}
+
+fn entity_ids_internal() -> Vec<u64> {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            return physics
+                .world
+                .query_filtered::<Entity, With<PhysicsBody>>()
+                .iter(&physics.world)
+                .map(|entity| entity.to_bits())
+                .collect();
+        }
+    }
+    Vec::new()
+}
+
+/// Register an RGBA8 image as a material. Returns the new material id, or 0
+/// (the built-in default material) if the image is invalid or wgpu is not ready.
+fn register_material_internal(width: u32, height: u32, rgba: &[u8]) -> u32 {
+    if let Ok(mut guard) = WGPU_STATE.lock() {
+        if let Some(state) = guard.0.as_mut() {
+            let WgpuState { device, queue, texture_bind_group_layout, materials, .. } = state;
+            return materials
+                .register_rgba(device, queue, texture_bind_group_layout, width, height, rgba)
+                .unwrap_or(material::DEFAULT_MATERIAL);
+        }
+    }
+    material::DEFAULT_MATERIAL
+}
+
+/// Register a PNG or KTX2 file as a material. Returns the new material id.
+fn load_texture_file_internal(path: &std::path::Path) -> Result<u32, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    register_texture_bytes_internal(&path.display().to_string(), &bytes)
+}
+
+/// Register PNG or KTX2 `bytes` as a material, naming them `label` in errors
+fn register_texture_bytes_internal(label: &str, bytes: &[u8]) -> Result<u32, String> {
+    if !ktx2_lite::is_ktx2(bytes) {
+        return register_png_internal(label, bytes);
+    }
+    let texture = ktx2_lite::parse(bytes).map_err(|e| format!("{}: {}", label, e))?;
+    let mut guard = WGPU_STATE.lock().map_err(|e| e.to_string())?;
+    let WgpuState { device, queue, texture_bind_group_layout, materials, .. } = guard.0.as_mut().ok_or("renderer not running")?;
+    materials.register_compressed(device, queue, texture_bind_group_layout, texture).map_err(|e| format!("{}: {}", label, e))
+}
+
+/// Asset names to try for texture `name` on this device, best first (see
+/// `ktx2_lite::variant_names`)
+pub(crate) fn texture_variant_names(name: &str) -> Vec<String> {
+    let compression = WGPU_STATE.lock().ok().and_then(|guard| guard.0.as_ref().map(|state| state.caps.texture_compression));
+    ktx2_lite::variant_names(name, compression.unwrap_or_default())
+}
+
+/// Register PNG `bytes` as a material, naming them `label` in errors
+fn register_png_internal(label: &str, bytes: &[u8]) -> Result<u32, String> {
+    let image = png_lite::decode(bytes).map_err(|e| format!("{}: {}", label, e))?;
+    match register_material_internal(image.width, image.height, &image.rgba) {
+        material::DEFAULT_MATERIAL => Err(format!("{}: texture not registered", label)),
+        material_id => Ok(material_id),
+    }
+}
+
+/// Register texture asset `name` as a material, once: later loads return the
+/// same id while the material exists. The best compressed variant of a PNG
+/// the device samples is used instead when there is one.
+fn load_texture_asset_internal(name: &str) -> Result<u32, String> {
+    let loaded = TEXTURE_ASSETS.lock().map_err(|e| e.to_string())?.get(name).copied();
+    if let Some(material_id) = loaded {
+        let known = WGPU_STATE.lock().is_ok_and(|guard| guard.0.as_ref().is_some_and(|state| state.materials.contains(material_id)));
+        if known {
+            return Ok(material_id);
+        }
+    }
+    // Variants that are missing or broken fall through to the next, so the
+    // error is the PNG's own
+    let mut error = String::new();
+    for variant in texture_variant_names(name) {
+        match assets::read(&variant).and_then(|bytes| register_texture_bytes_internal(&variant, &bytes)) {
+            Ok(material_id) => {
+                TEXTURE_ASSETS.lock().map_err(|e| e.to_string())?.insert(name.to_string(), material_id);
+                return Ok(material_id);
+            }
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+/// Filter and wrap material `material_id`'s texture with `sampling` (see
+/// `texture_sampling`). Returns false for unknown materials, the built-in
+/// default one and before init.
+fn set_texture_sampling_internal(material_id: u32, sampling: texture_sampling::TextureSampling) -> bool {
+    let Ok(mut guard) = WGPU_STATE.lock() else {
+        return false;
+    };
+    let Some(WgpuState { device, texture_bind_group_layout, materials, .. }) = guard.0.as_mut() else {
+        return false;
+    };
+    materials.set_sampling(device, texture_bind_group_layout, material_id, sampling)
+}
+
+/// Draw `region` of the entity's texture instead of the whole texture or
+/// its sprite sheet frame, or go back to those with None
+fn set_sprite_region_internal(entity_id: u64, region: Option<SpriteRegion>) -> Result<(), String> {
+    edit_sprite_region(entity_id, |_| region)
+}
+
+/// Keep `borders` (left, top, right, bottom) of the entity's sprite region
+/// from stretching, giving it a whole-texture region first if it has none
+fn set_nine_slice_internal(entity_id: u64, borders: [f32; 4]) -> Result<(), String> {
+    edit_sprite_region(entity_id, |current| Some(SpriteRegion { borders, ..current.unwrap_or_default() }))
+}
+
+fn edit_sprite_region(entity_id: u64, edit: impl FnOnce(Option<SpriteRegion>) -> Option<SpriteRegion>) -> Result<(), String> {
+    let entity = entity_from_id(entity_id).ok_or_else(|| format!("unknown entity {}", entity_id))?;
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    let Ok(mut entity) = physics.world.get_entity_mut(entity) else {
+        return Err(format!("unknown entity {}", entity_id));
+    };
+    let region = edit(entity.get::<SpriteRegion>().copied());
+    if let Some(region) = &region {
+        region.validate()?;
+    }
+    match region {
+        Some(region) => {
+            entity.insert(region);
+        }
+        None => {
+            entity.remove::<SpriteRegion>();
+        }
+    }
+    physics.instance_layout_dirty = true;
+    Ok(())
+}
+
+/// Draw the entity in `layer` (see `layers`)
+fn set_layer_internal(entity_id: u64, layer: Layer) -> bool {
+    let Some(entity) = entity_from_id(entity_id) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let Ok(mut entity) = physics.world.get_entity_mut(entity) else {
+        return false;
+    };
+    entity.insert(layer);
+    physics.instance_layout_dirty = true;
+    true
+}
+
+/// Scroll `layer` by `factor` (x, y) of the camera's movement; [1, 1]
+/// moves it with the world again
+fn set_layer_parallax_internal(layer: Layer, factor: [f32; 2]) -> Result<(), String> {
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    physics.world.resource_mut::<LayerParallax>().set(layer, factor)?;
+    physics.instance_layout_dirty = true;
+    Ok(())
+}
+
+/// Draw the entity with `material_id`; a texture with a traced shape also
+/// becomes the body's collider
+fn set_entity_material_internal(entity_id: u64, material_id: u32) -> bool {
+    // Check the material before touching physics so we never hold both locks
+    let (known, traced) = match WGPU_STATE.lock() {
+        Ok(guard) => guard.0.as_ref().map_or((false, None), |state| {
+            (state.materials.contains(material_id), state.materials.shape(material_id).cloned())
+        }),
+        Err(_) => (false, None),
+    };
+    if !known {
+        log::warn!("set_entity_material: unknown material {}", material_id);
+        return false;
+    }
+
+    let entity = match entity_from_id(entity_id) {
+        Some(e) => e,
+        None => return false,
+    };
+
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            if physics.world.entities().contains(entity) {
+                physics.world.entity_mut(entity).insert(MaterialComponent::new(material_id));
+                physics.instance_layout_dirty = true;
+                if let Some(pieces) = traced {
+                    reshape_body_in(physics, entity_id, &pieces);
+                }
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Trace the opaque part of a registered texture into convex pieces (see
+/// `sprite_outline`), keep them with the material and reshape the bodies
+/// already drawn with it. Returns the number of pieces.
+fn generate_collider_from_texture_internal(texture_id: u32, alpha_threshold: u8, simplification: f32) -> Result<usize, String> {
+    let pieces = {
+        let mut guard = WGPU_STATE.lock().map_err(|e| e.to_string())?;
+        let state = guard.0.as_mut().ok_or("renderer not running")?;
+        let (width, height, rgba) =
+            state.materials.source(texture_id).ok_or("unknown texture, or compressed in a format that cannot be decoded")?;
+        let pieces = sprite_outline::pieces(width, height, &rgba, alpha_threshold, simplification)?;
+        state.materials.set_shape(texture_id, pieces.clone());
+        pieces
+    };
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            let ids: Vec<u64> = physics
+                .world
+                .query::<(Entity, &MaterialComponent)>()
+                .iter(&physics.world)
+                .filter(|(_, material)| material.material_id == texture_id)
+                .map(|(entity, _)| entity.to_bits())
+                .collect();
+            for id in ids {
+                reshape_body_in(physics, id, &pieces);
+            }
+        }
+    }
+    Ok(pieces.len())
+}
+
+/// Make the body's collider the traced `pieces` at its drawn size in an
+/// already locked state. Returns false for unknown ids.
+fn reshape_body_in(physics: &mut PhysicsState, entity_id: u64, pieces: &[Vec<[f32; 2]>]) -> bool {
+    let (Some(body), Some(scale)) = (
+        body_of(physics, entity_id),
+        entity_from_id(entity_id).and_then(|entity| physics.world.get::<Scale>(entity)).map(|scale| scale.0),
+    ) else {
+        return false;
+    };
+    match (polygon::compound(pieces, scale), physics.collider_set.get_mut(body.collider_handle)) {
+        (Ok(shape), Some(collider)) => {
+            collider.set_shape(shape);
+            true
+        }
+        (Err(e), _) => {
+            log::warn!("reshape_body: {}", e);
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Parse a .gltf or .glb file (see `gltf_lite`) and upload it for the 3D
+/// pass. Returns the model's id.
+fn load_model_internal(bytes: &[u8]) -> Result<u32, String> {
+    let mesh = gltf_lite::parse(bytes)?;
+    let mut guard = WGPU_STATE.lock().map_err(|e| e.to_string())?;
+    let state = guard.0.as_mut().ok_or("renderer not running")?;
+    let bevy_3d = state.bevy_3d_sample.as_mut().ok_or("3D pass not available")?;
+    Ok(bevy_3d.load_model(&state.device, mesh))
+}
+
+/// Draw the body with a loaded model in the 3D pass, `scale` times its drawn
+/// size; model 0 removes it. Returns false for unknown ids.
+fn attach_model_internal(entity_id: u64, model_id: u32, scale: f32) -> bool {
+    if model_id != 0 {
+        if !validation::accept(validation::size("scale", scale)) {
+            return false;
+        }
+        // Check the model before touching physics so we never hold both locks
+        let known = WGPU_STATE
+            .lock()
+            .ok()
+            .and_then(|guard| guard.0.as_ref().and_then(|state| state.bevy_3d_sample.as_ref()).map(|bevy_3d| bevy_3d.has_model(model_id)))
+            .unwrap_or(false);
+        if !known {
+            log::warn!("attach_model: unknown model {}", model_id);
+            return false;
+        }
+    }
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    if model_id != 0 {
+        return insert_on_body(physics, entity_id, ModelComponent { model_id, scale });
+    }
+    match entity_from_id(entity_id).filter(|_| known_body(physics, entity_id).is_some()) {
+        Some(entity) => {
+            physics.world.entity_mut(entity).remove::<ModelComponent>();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Add or replace label `id`. Returns false for invalid position or size and
+/// when the label limit is reached.
+fn draw_text_internal(id: u64, text: &str, anchor: TextAnchor, x: f32, y: f32, size: f32, color: [f32; 4]) -> bool {
+    let Some(label) = TextLabel::new(text, anchor, [x, y], size, color) else {
+        return false;
+    };
+    match TEXT_LABELS.lock() {
+        Ok(mut labels) => labels.set(id, label),
+        Err(_) => false,
+    }
+}
+
+fn clear_text_internal(id: u64) -> bool {
+    TEXT_LABELS.lock().is_ok_and(|mut labels| labels.remove(id))
+}
+
+fn clear_all_text_internal() {
+    if let Ok(mut labels) = TEXT_LABELS.lock() {
+        labels.clear();
+    }
+}
+
+/// Labels to draw this frame, with body anchors turned into world positions.
+/// Labels of entities that do not exist are left out.
+fn visible_text_labels() -> Vec<TextLabel> {
+    let area = safe_area();
+    let labels: Vec<TextLabel> = match TEXT_LABELS.lock() {
+        Ok(labels) => labels.iter().map(|(_, label)| label.clone()).collect(),
+        Err(_) => return Vec::new(),
+    };
+    // Screen positions count from the safe area's top-left corner
+    let labels = labels
+        .into_iter()
+        .map(|mut label| {
+            if label.anchor == TextAnchor::Screen {
+                label.position = [label.position[0] + area.left, label.position[1] + area.top];
+            }
+            label
+        })
+        .collect::<Vec<_>>();
+    if !labels.iter().any(|label| matches!(label.anchor, TextAnchor::Body(_))) {
+        return labels;
+    }
+
+    let Ok(guard) = PHYSICS_STATE.lock() else {
+        return Vec::new();
+    };
+    let world = guard.0.as_ref().map(|physics| &physics.world);
+    labels
+        .into_iter()
+        .filter_map(|mut label| {
+            if let TextAnchor::Body(entity_id) = label.anchor {
+                let world = world?;
+                let entity = entity_from_id(entity_id).filter(|&e| world.entities().contains(e))?;
+                let center = world.get::<Position2D>(entity)?;
+                label.anchor = TextAnchor::World;
+                label.position = [center.x + label.position[0], center.y + label.position[1]];
+            }
+            Some(label)
+        })
+        .collect()
+}
+
+/// Add screen shake; `strength` is trauma in 0..1 (shake grows with its
+/// square). Returns false for negative or non-finite strengths.
+fn add_camera_shake_internal(strength: f32) -> bool {
+    if !validation::accept(validation::non_negative("strength", strength)) {
+        return false;
+    }
+    match CAMERA_EFFECTS.lock() {
+        Ok(mut effects) => {
+            effects.0.add_trauma(strength);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Zoom in by `amount` (0.2 is 20% closer, negative zooms out) and ease back
+fn punch_camera_zoom_internal(amount: f32) -> bool {
+    if !validation::accept(validation::finite("amount", &[amount])) {
+        return false;
+    }
+    match CAMERA_EFFECTS.lock() {
+        Ok(mut effects) => {
+            effects.0.punch_zoom(amount);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Change the effect config with `update`; false if it rejects the values
+fn update_camera_effects_config(update: impl FnOnce(CameraEffectsConfig) -> Option<CameraEffectsConfig>) -> bool {
+    let Ok(mut effects) = CAMERA_EFFECTS.lock() else {
+        return false;
+    };
+    match update(effects.0.config()) {
+        Some(config) => {
+            effects.0.set_config(config);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Shake amplitude at full trauma (world units and radians) and noise frequency in Hz
+fn set_camera_shake_internal(max_offset: f32, max_rotation: f32, frequency: f32) -> bool {
+    update_camera_effects_config(|config| config.with_shake(max_offset, max_rotation, frequency))
+}
+
+/// Trauma lost per second and the zoom punch decay rate
+fn set_camera_effects_decay_internal(trauma_decay: f32, zoom_decay: f32) -> bool {
+    update_camera_effects_config(|config| config.with_decay(trauma_decay, zoom_decay))
+}
+
+/// Shake the camera on collisions harder than `min_impulse` N·s, adding
+/// `trauma_per_impulse` per N·s above it; 0 turns impact shake off
+fn set_camera_impact_shake_internal(min_impulse: f32, trauma_per_impulse: f32) -> bool {
+    update_camera_effects_config(|config| config.with_impact_shake(min_impulse, trauma_per_impulse))
+}
+
+fn clear_camera_effects_internal() {
+    if let Ok(mut effects) = CAMERA_EFFECTS.lock() {
+        effects.0.clear();
+    }
+}
+
+/// Advance the camera effects to this frame and return their offset. Frame
+/// gaps are capped so a stall does not swallow a whole shake.
+fn advance_camera_effects() -> CameraOffset {
+    let Ok(mut guard) = CAMERA_EFFECTS.lock() else {
+        return CameraOffset::default();
+    };
+    let (effects, last_frame) = &mut *guard;
+    let now = frame_clock_ms();
+    if let Some(last) = last_frame.replace(now) {
+        effects.update(((now - last) / 1000.0).clamp(0.0, 0.1) as f32);
+    }
+    effects.offset()
+}
+
+/// Advance the scene fade to this frame and return the overlay opacity
+fn advance_scene_fade() -> f32 {
+    let Ok(mut guard) = SCENE_FADE.lock() else {
+        return 0.0;
+    };
+    let (fade, last_frame) = &mut *guard;
+    let now = frame_clock_ms();
+    if let Some(last) = last_frame.replace(now) {
+        fade.advance(((now - last) / 1000.0).clamp(0.0, 0.1) as f32);
+    }
+    fade.alpha()
+}
+
+/// Quad of `color` at `alpha` over the visible world, with some margin for
+/// camera shake; empty once the fade is over
+fn fade_overlay_vertices(state: &WgpuState, color: [f32; 4], alpha: f32) -> Vec<TrailVertex> {
+    if alpha <= 0.0 {
+        return Vec::new();
+    }
+    let [left, top, width, height] = state.world_rect();
+    let (x0, y0) = state.screen_to_world(left as f32, (top + height) as f32);
+    let (x1, y1) = state.screen_to_world((left + width) as f32, top as f32);
+    let margin = (x1 - x0).abs().max((y1 - y0).abs()) * 0.25;
+    let (min, max) = ([x0.min(x1) - margin, y0.min(y1) - margin], [x0.max(x1) + margin, y0.max(y1) + margin]);
+    let color = [color[0], color[1], color[2], alpha];
+    let corner = |x: f32, y: f32| TrailVertex { position: [x, y], color };
+    vec![
+        corner(min[0], min[1]),
+        corner(max[0], min[1]),
+        corner(min[0], max[1]),
+        corner(min[0], max[1]),
+        corner(max[0], min[1]),
+        corner(max[0], max[1]),
+    ]
+}
+
+/// Split the main surface between two players, or go back to one camera
+/// with None. Returns false for invalid configs or before init.
+fn set_split_screen_internal(split: Option<SplitScreen>) -> bool {
+    if split.as_ref().is_some_and(|split| !split.is_valid()) {
+        return false;
+    }
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    match split {
+        Some(split) => physics.world.insert_resource(split),
+        None => {
+            physics.world.remove_resource::<SplitScreen>();
+        }
+    }
+    true
+}
+
+/// `set_split_screen_internal` from a JSON `SplitScreen`, or `null` for one
+/// camera
+fn set_split_screen_json_internal(json: &str) -> Result<(), String> {
+    let split: Option<SplitScreen> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if split.as_ref().is_some_and(|split| !split.is_valid()) {
+        return Err("invalid split screen config".into());
+    }
+    if set_split_screen_internal(split) {
+        Ok(())
+    } else {
+        Err("simulation not running".into())
+    }
+}
+
+/// Hand the body to player 1 or 2, making it controllable; 0 removes the
+/// tag so it answers to both players again. False for unknown bodies and
+/// other players.
+fn set_player_internal(entity_id: u64, player: u8) -> bool {
+    let Some(entity) = entity_from_id(entity_id).filter(|_| player <= 2) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    match guard.0.as_mut() {
+        Some(physics) if physics.world.entities().contains(entity) && physics.world.get::<PhysicsBody>(entity).is_some() => {
+            let mut entity = physics.world.entity_mut(entity);
+            if player == 0 {
+                entity.remove::<Player>();
+            } else {
+                entity.insert((Controllable, Player(player)));
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Show the minimap, or hide it with None. Returns false for invalid configs.
+fn set_minimap_internal(config: Option<MinimapConfig>) -> bool {
+    if config.is_some_and(|config| !config.is_valid()) {
+        return false;
+    }
+    match MINIMAP.lock() {
+        Ok(mut minimap) => {
+            *minimap = config;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// `set_minimap_internal` from a JSON `MinimapConfig`, or `null` to hide it
+fn set_minimap_json_internal(json: &str) -> Result<(), String> {
+    let config = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if set_minimap_internal(config) {
+        Ok(())
+    } else {
+        Err("invalid minimap config".into())
+    }
+}
+
+fn set_clear_color_internal(r: f32, g: f32, b: f32, a: f32) {
+    if let Ok(mut settings) = RENDER_SETTINGS.lock() {
+        settings.clear_color = [r, g, b, a].map(|c| c.clamp(0.0, 1.0));
+    }
+}
+
+/// MSAA sample count is baked into the pipelines, so changes apply on the next init
+fn set_msaa_samples_internal(samples: u32) {
+    if let Ok(mut settings) = RENDER_SETTINGS.lock() {
+        settings.msaa_samples = samples.max(1);
+    }
+}
+
+/// Backend bits and power preference per `gpu_options`; applies on the next init
+fn set_gpu_init_options_internal(backends: u32, power_preference: i32, force_fallback_adapter: bool) {
+    if let Ok(mut options) = GPU_INIT_OPTIONS.lock() {
+        // Only the desktop app's command line sets vsync
+        *options = GpuInitOptions { vsync: options.vsync, ..GpuInitOptions::from_raw(backends, power_preference, force_fallback_adapter) };
+    }
+}
+
+/// Show `pixels` (`height` rows of `width` pixels, `stride` pixels apart)
+/// behind the scene until it is replaced or cleared
+fn set_background_internal(width: u32, height: u32, stride: u32, layout: PixelLayout, pixels: &[u8]) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err(format!("invalid background size {}x{}", width, height));
+    }
+    let rgba = background::pack_rgba(pixels, width, height, stride, layout)
+        .ok_or_else(|| format!("{} bytes is too little for a {}x{} image with a stride of {}", pixels.len(), width, height, stride))?;
+    let mut guard = WGPU_STATE.lock().map_err(|e| e.to_string())?;
+    let state = guard.0.as_mut().ok_or("renderer not running")?;
+    let max_dimension = state.device.limits().max_texture_dimension_2d;
+    if width > max_dimension || height > max_dimension {
+        return Err(format!("background {}x{} is larger than {}", width, height, max_dimension));
+    }
+    let WgpuState { device, queue, background_pass, .. } = state;
+    background_pass.upload(device, queue, width, height, &rgba);
+    Ok(())
+}
+
+/// Show an Android hardware buffer behind the scene (see `background`)
+#[cfg(all(target_os = "android", feature = "android_hardware_buffer"))]
+fn set_background_hardware_buffer_internal(buffer: &ndk::hardware_buffer::HardwareBuffer) -> Result<(), String> {
+    use ndk::hardware_buffer::{HardwareBufferFormat, HardwareBufferUsage};
+
+    let desc = buffer.describe();
+    let layout = match desc.format {
+        HardwareBufferFormat::R8G8B8A8_UNORM => PixelLayout::Rgba8,
+        HardwareBufferFormat::R8G8B8X8_UNORM => PixelLayout::Rgbx8,
+        HardwareBufferFormat::R8G8B8_UNORM => PixelLayout::Rgb8,
+        format => return Err(format!("unsupported hardware buffer format {:?}", format)),
+    };
+    let pixels = buffer.lock(HardwareBufferUsage::CPU_READ_OFTEN, None, None).map_err(|e| format!("locking the hardware buffer failed: {}", e))?;
+    let len = desc.stride as usize * desc.height as usize * layout.bytes_per_pixel();
+    // SAFETY: a locked buffer maps `stride * height` pixels until unlocked
+    let result = set_background_internal(desc.width, desc.height, desc.stride, layout, unsafe { std::slice::from_raw_parts(pixels as *const u8, len) });
+    if let Err(e) = buffer.unlock() {
+        log::warn!("Unlocking the hardware buffer failed: {}", e);
+    }
+    result
+}
+
+fn clear_background_internal() {
+    if let Ok(mut guard) = WGPU_STATE.lock() {
+        if let Some(state) = guard.0.as_mut() {
+            state.background_pass.clear();
+        }
+    }
+}
+
+/// False before init
+fn set_background_fit_internal(fit: BackgroundFit) -> bool {
+    let Ok(mut guard) = WGPU_STATE.lock() else {
+        return false;
+    };
+    guard.0.as_mut().map(|state| state.background_pass.set_fit(fit)).is_some()
+}
+
+/// Smoothed FPS, CPU frame time and per-pass GPU times as JSON, or None before init
+fn frame_stats_internal() -> Option<String> {
+    let guard = WGPU_STATE.lock().ok()?;
+    guard.0.as_ref().map(|state| state.frame_stats.to_json())
+}
+
+/// Advance the performance governor by `dt` real seconds and return its limits
+fn update_governor(dt: f32) -> Throttle {
+    let Ok(mut governor) = GOVERNOR.lock() else {
+        return Throttle::default();
+    };
+    if governor.update(dt) {
+        log::info!("Performance governor: {:?} for {:?}", governor.level(), governor.power());
+    }
+    governor.throttle()
+}
+
+fn governor_throttle() -> Throttle {
+    GOVERNOR.lock().map(|governor| governor.throttle()).unwrap_or_default()
+}
+
+/// Battery from 0 to 1 (negative when unknown), charging, thermal status
+/// (see `governor::ThermalStatus`) and low power mode, for the governor to
+/// act on from the next update. False for a battery above 1 or an unknown
+/// thermal status.
+fn set_power_state_internal(battery: f32, charging: bool, thermal: i32, low_power_mode: bool) -> bool {
+    let Some(power) = ThermalStatus::from_i32(thermal).and_then(|thermal| PowerState::new(battery, charging, thermal, low_power_mode)) else {
+        validation::reject(InputError::new(validation::ERROR_OUT_OF_RANGE, format!("invalid power state: battery {}, thermal status {}", battery, thermal)));
+        return false;
+    };
+    set_power_internal(power);
+    true
+}
+
+fn set_power_internal(power: PowerState) {
+    if let Ok(mut governor) = GOVERNOR.lock() {
+        governor.set_power(power);
+    }
+}
+
+/// Only the low power mode flag, for hosts that know nothing else
+fn set_low_power_mode_internal(enabled: bool) {
+    if let Ok(mut governor) = GOVERNOR.lock() {
+        let power = governor.power();
+        governor.set_power(PowerState { low_power_mode: enabled, ..power });
+    }
+}
+
+/// Disabling lifts every limit at once; the power state is still recorded
+fn set_governor_enabled_internal(enabled: bool) {
+    if let Ok(mut governor) = GOVERNOR.lock() {
+        governor.set_enabled(enabled);
+    }
+}
+
+/// The reported power state, throttle level and its limits as JSON
+fn governor_stats_internal() -> String {
+    GOVERNOR.lock().map(|governor| governor.stats().to_json()).unwrap_or_default()
+}
+
+/// The log console's lines at `level` (see `log_console::level_filter_from_u32`)
+/// or more severe, oldest first, as a JSON array
+fn recent_logs_internal(level: u32) -> String {
+    log_console::recent_json(log_console::level_filter_from_u32(level))
+}
+
+/// JSON description of the current adapter, or None before init
+fn adapter_info_internal() -> Option<String> {
+    let guard = WGPU_STATE.lock().ok()?;
+    let state = guard.0.as_ref()?;
+    Some(gpu_options::adapter_info_json(
+        &state.adapter_info,
+        &state.device.limits(),
+        &state.surface_formats,
+        state.config.format,
+        &state.caps,
+    ))
+}
+
+fn set_bloom_internal(enabled: bool, threshold: f32, intensity: f32) {
+    if let Ok(mut settings) = RENDER_SETTINGS.lock() {
+        settings.bloom_enabled = enabled;
+        settings.bloom_threshold = threshold.max(0.0);
+        settings.bloom_intensity = intensity.max(0.0);
+    }
+}
+
+fn set_contact_shadows_internal(enabled: bool, intensity: f32, spread: f32) {
+    if let Ok(mut settings) = RENDER_SETTINGS.lock() {
+        settings.shadow_enabled = enabled;
+        settings.shadow_intensity = intensity.clamp(0.0, 1.0);
+        settings.shadow_spread = spread.max(0.0);
+    }
+}
+
+fn set_culling_internal(enabled: bool) {
+    if let Ok(mut settings) = RENDER_SETTINGS.lock() {
+        settings.culling_enabled = enabled;
+    }
+}
+
+/// Returns whether the GPU can cull; the CPU culls when it cannot
+fn set_gpu_culling_internal(enabled: bool) -> bool {
+    if let Ok(mut settings) = RENDER_SETTINGS.lock() {
+        settings.gpu_culling = enabled;
+    }
+    WGPU_STATE.lock().ok().and_then(|guard| guard.0.as_ref().map(|state| state.caps.gpu_culling)).unwrap_or(false)
+}
+
+/// Out-of-range values are clamped; takes effect from the next step
+fn set_solver_config_internal(config: SolverConfig) -> bool {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.solver = config.sanitized();
+            return true;
+        }
+    }
+    false
+}
+
+/// Replace every force field; sleeping bodies are woken so they feel the change
+fn set_force_fields_internal(config: ForceFieldConfig) -> bool {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.force_fields = config;
+            wake_dynamic_bodies(&mut physics.rigid_body_set);
+            return true;
+        }
+    }
+    false
+}
+
+/// `set_force_fields_internal` from a JSON `ForceFieldConfig`
+fn set_force_fields_json_internal(json: &str) -> Result<(), String> {
+    let config = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if set_force_fields_internal(config) {
+        Ok(())
+    } else {
+        Err("simulation not running".into())
+    }
+}
+
+/// Replace every water region with calm water; out-of-range values are clamped
+fn set_water_internal(config: WaterConfig) -> bool {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.water = Water::new(config);
+            wake_dynamic_bodies(&mut physics.rigid_body_set);
+            return true;
+        }
+    }
+    false
+}
+
+/// `set_water_internal` from a JSON `WaterConfig`
+fn set_water_json_internal(json: &str) -> Result<(), String> {
+    let config = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if set_water_internal(config) {
+        Ok(())
+    } else {
+        Err("simulation not running".into())
+    }
+}
+
+/// Replace the slow-motion zones; out-of-range values are clamped
+fn set_time_dilation_internal(config: TimeDilationConfig) -> bool {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.time_dilation = config.sanitized();
+            wake_dynamic_bodies(&mut physics.rigid_body_set);
+            return true;
+        }
+    }
+    false
+}
+
+/// `set_time_dilation_internal` from a JSON `TimeDilationConfig`
+fn set_time_dilation_json_internal(json: &str) -> Result<(), String> {
+    let config = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if set_time_dilation_internal(config) {
+        Ok(())
+    } else {
+        Err("simulation not running".into())
+    }
+}
+
+fn wake_dynamic_bodies(bodies: &mut RigidBodySet) {
+    for (_, rb) in bodies.iter_mut().filter(|(_, rb)| rb.is_dynamic()) {
+        rb.wake_up(true);
+    }
+}
+
+/// Fixed substeps per step, optionally raised with body speed (see `substep`)
+fn set_substeps_internal(config: SubstepConfig) -> bool {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.substep_config = config.sanitized();
+            return true;
+        }
+    }
+    false
+}
+
+fn solver_config_internal() -> Option<SolverConfig> {
+    PHYSICS_STATE.lock().ok()?.0.as_ref().map(|physics| physics.solver)
+}
+
+/// Allow or forbid sleeping for every dynamic body. Forbidding also wakes them.
+fn apply_sleep_setting(bodies: &mut RigidBodySet, enabled: bool) {
+    for (_, rb) in bodies.iter_mut().filter(|(_, rb)| rb.is_dynamic()) {
+        if enabled {
+            *rb.activation_mut() = RigidBodyActivation::active();
+        } else {
+            *rb.activation_mut() = RigidBodyActivation::cannot_sleep();
+            rb.wake_up(true);
+        }
+    }
+}
+
+fn set_sleep_enabled(physics: &mut PhysicsState, enabled: bool) {
+    physics.sleep_enabled = enabled;
+    apply_sleep_setting(&mut physics.rigid_body_set, enabled);
+}
+
+fn set_sleep_enabled_internal(enabled: bool) {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            set_sleep_enabled(physics, enabled);
+        }
+    }
+}
+
+/// Run `f` on the rigid body behind `entity_id`, if it exists
+fn with_rigid_body_mut<R>(entity_id: u64, f: impl FnOnce(&mut RigidBody) -> R) -> Option<R> {
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_mut()?;
+    let body = known_body(physics, entity_id)?;
+    physics.rigid_body_set.get_mut(body.rigid_body_handle).map(f)
+}
+
+fn wake_body_internal(entity_id: u64) -> bool {
+    with_rigid_body_mut(entity_id, wake_body).is_some()
+}
+
+fn sleep_body_internal(entity_id: u64) -> bool {
+    with_rigid_body_mut(entity_id, sleep_body).is_some()
+}
+
+fn body_info_internal(entity_id: u64) -> Option<BodyInfo> {
+    with_rigid_body_mut(entity_id, |rb| body_info(rb))
+}
+
+fn wake_body(rb: &mut RigidBody) {
+    rb.wake_up(true);
+}
+
+fn sleep_body(rb: &mut RigidBody) {
+    rb.sleep();
+}
+
+fn body_info(rb: &RigidBody) -> BodyInfo {
+    BodyInfo {
+        x: to_f32(rb.translation().x),
+        y: to_f32(rb.translation().y),
+        vx: to_f32(rb.linvel().x),
+        vy: to_f32(rb.linvel().y),
+        rotation: to_f32(rb.rotation().euler_angles().2),
+        sleeping: rb.is_sleeping(),
+    }
+}
+
+fn apply_impulse_internal(entity_id: u64, impulse_x: f32, impulse_y: f32) -> bool {
+    if !validation::accept(validation::finite("impulse", &[impulse_x, impulse_y])) {
+        return false;
+    }
+    with_rigid_body_mut(entity_id, |rb| rb.apply_impulse(planar([impulse_x, impulse_y]), true)).is_some()
+}
+
+/// Replace the body's group bits. Returns false for unknown ids.
+fn set_body_groups_internal(entity_id: u64, groups: u32) -> bool {
+    let Some(entity) = entity_from_id(entity_id) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    match guard.0.as_mut() {
+        Some(physics) if physics.world.entities().contains(entity) && physics.world.get::<PhysicsBody>(entity).is_some() => {
+            physics.world.entity_mut(entity).insert(Groups(groups));
+            true
+        }
+        _ => false,
+    }
+}
+
+fn body_groups_internal(entity_id: u64) -> Option<u32> {
+    let entity = entity_from_id(entity_id)?;
+    let guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_ref()?;
+    if known_entity(physics, entity_id).is_none() {
+        return None;
+    }
+    physics.world.get::<PhysicsBody>(entity)?;
+    Some(physics.world.get::<Groups>(entity).copied().unwrap_or_default().0)
+}
+
+/// Entities and bodies matching `mask`
+fn group_members(physics: &mut PhysicsState, mask: u32) -> Vec<(Entity, PhysicsBody)> {
+    physics
+        .world
+        .query::<(Entity, &PhysicsBody, Option<&Groups>)>()
+        .iter(&physics.world)
+        .filter(|(_, _, member)| member.copied().unwrap_or_default().matches(mask))
+        .map(|(entity, body, _)| (entity, *body))
+        .collect()
+}
+
+fn group_ids_internal(mask: u32) -> Vec<u64> {
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().map_or_else(Vec::new, |physics| {
+            group_members(physics, mask).into_iter().map(|(entity, _)| entity.to_bits()).collect()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Remove every body matching `mask` together with its colliders, joints and
+/// entity. Returns the number of bodies removed.
+fn despawn_group_internal(mask: u32) -> u32 {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return 0;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return 0;
+    };
+    let members = group_members(physics, mask);
+    for &(entity, body) in &members {
+        despawn_body_in(physics, entity, body);
+    }
+    members.len() as u32
+}
+
+/// Remove a body together with its colliders, joints and entity in an
+/// already locked state
+fn despawn_body_in(physics: &mut PhysicsState, entity: Entity, body: PhysicsBody) {
+    physics.rigid_body_set.remove(
+        body.rigid_body_handle,
+        &mut physics.island_manager,
+        &mut physics.collider_set,
+        &mut physics.impulse_joint_set,
+        &mut physics.multibody_joint_set,
+        true,
+    );
+    physics.surface_velocities.remove(&body.collider_handle);
+    physics.world.despawn(entity);
+    physics.instance_layout_dirty = true;
+}
+
+/// Freeze or resume the dynamic bodies matching `mask`. Returns the number of
+/// bodies whose state changed.
+fn set_group_paused_internal(mask: u32, paused: bool) -> u32 {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return 0;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return 0;
+    };
+    let mut changed = 0;
+    for (entity, body) in group_members(physics, mask) {
+        let Some(rb) = physics.rigid_body_set.get_mut(body.rigid_body_handle) else {
+            continue;
+        };
+        let saved = physics.world.get::<groups::Paused>(entity).copied();
+        match (paused, saved) {
+            (true, None) => {
+                if let Some(state) = groups::pause(rb) {
+                    physics.world.entity_mut(entity).insert(state);
+                    changed += 1;
+                }
+            }
+            (false, Some(state)) => {
+                groups::resume(rb, state);
+                physics.world.entity_mut(entity).remove::<groups::Paused>();
+                changed += 1;
+            }
+            _ => {}
+        }
+    }
+    changed
+}
+
+/// Apply the same impulse to every body matching `mask`. Returns the number of
+/// bodies pushed; paused and non-dynamic bodies are skipped.
+fn apply_impulse_to_group_internal(mask: u32, impulse_x: f32, impulse_y: f32) -> u32 {
+    if !validation::accept(validation::finite("impulse", &[impulse_x, impulse_y])) {
+        return 0;
+    }
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return 0;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return 0;
+    };
+    let mut pushed = 0;
+    for (_, body) in group_members(physics, mask) {
+        if let Some(rb) = physics.rigid_body_set.get_mut(body.rigid_body_handle).filter(|rb| rb.is_dynamic()) {
+            rb.apply_impulse(planar([impulse_x, impulse_y]), true);
+            pushed += 1;
+        }
+    }
+    pushed
+}
+
+/// Add a drawn quad without a body, usually to attach to one. Returns its
+/// entity id.
+fn spawn_decoration_internal(x: f32, y: f32, half_extent: f32, material_id: u32) -> Option<u64> {
+    if !validation::accept(validation::finite("position", &[x, y]).and(validation::size("half extent", half_extent))) {
+        return None;
+    }
+    let entity_id = {
+        let mut guard = PHYSICS_STATE.lock().ok()?;
+        let physics = guard.0.as_mut()?;
+        let entity = physics
+            .world
+            .spawn((Position2D { x, y }, Velocity2D { x: 0.0, y: 0.0 }, Scale(half_extent), Rotation(0.0), Decoration))
+            .id();
+        physics.instance_layout_dirty = true;
+        entity.to_bits()
+    };
+    if material_id != material::DEFAULT_MATERIAL {
+        set_entity_material_internal(entity_id, material_id);
+    }
+    Some(entity_id)
+}
+
+/// Despawn a decoration. Bodies are not decorations; returns false for them
+/// and for unknown ids.
+fn remove_decoration_internal(entity_id: u64) -> bool {
+    let Some(entity) = entity_from_id(entity_id) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    if !physics.world.entities().contains(entity) || physics.world.get::<Decoration>(entity).is_none() {
+        return false;
+    }
+    physics.world.despawn(entity);
+    physics.instance_layout_dirty = true;
+    true
+}
+
+/// Is `entity` a body or decoration in this scene
+fn is_drawn_entity(world: &World, entity: Entity) -> bool {
+    world.entities().contains(entity) && (world.get::<PhysicsBody>(entity).is_some() || world.get::<Decoration>(entity).is_some())
+}
+
+/// Make `child` follow `parent` at (offset_x, offset_y) in the parent's frame,
+/// turned by `rotation` radians. A body child becomes kinematic until it is
+/// detached. Returns false for unknown ids and for loops.
+fn attach_entity_internal(child_id: u64, parent_id: u64, offset_x: f32, offset_y: f32, rotation: f32) -> bool {
+    if !validation::accept(validation::finite("offset and rotation", &[offset_x, offset_y, rotation])) {
+        return false;
+    }
+    let (Some(child), Some(parent)) = (entity_from_id(child_id), entity_from_id(parent_id)) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    if !is_drawn_entity(&physics.world, child) || !is_drawn_entity(&physics.world, parent) {
+        return false;
+    }
+    let world = &physics.world;
+    if hierarchy::creates_cycle(child, parent, |e| world.get::<Attached>(e).map(|a| a.parent)) {
+        return false;
+    }
+
+    // Keep the original body type across re-attaching
+    let restore_dynamic = match physics.world.get::<Attached>(child) {
+        Some(previous) => previous.restore_dynamic,
+        None => {
+            let body = physics.world.get::<PhysicsBody>(child).copied();
+            match body.and_then(|body| physics.rigid_body_set.get_mut(body.rigid_body_handle)) {
+                Some(rb) => {
+                    let was_dynamic = rb.is_dynamic();
+                    rb.set_body_type(RigidBodyType::KinematicPositionBased, true);
+                    was_dynamic
+                }
+                None => false,
+            }
+        }
+    };
+    let offset = Pose2D { translation: [offset_x, offset_y], rotation };
+    physics.world.entity_mut(child).insert(Attached { parent, offset, restore_dynamic });
+    physics.instance_layout_dirty = true;
+    true
+}
+
+/// Stop following the parent; the child stays where it is and a body child
+/// becomes dynamic again if it was. Returns false if it was not attached.
+fn detach_entity_internal(child_id: u64) -> bool {
+    let Some(child) = entity_from_id(child_id) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    if !physics.world.entities().contains(child) {
+        return false;
+    }
+    let Some(attached) = physics.world.entity_mut(child).take::<Attached>() else {
+        return false;
+    };
+    let body = physics.world.get::<PhysicsBody>(child).copied();
+    if let Some(rb) = body.and_then(|body| physics.rigid_body_set.get_mut(body.rigid_body_handle)) {
+        if attached.restore_dynamic {
+            rb.set_body_type(RigidBodyType::Dynamic, true);
+        }
+    }
+    physics.instance_layout_dirty = true;
+    true
+}
+
+/// Despawn the body after `seconds`; 0 or less cancels a pending lifetime.
+/// Returns false for unknown ids.
+fn set_lifetime_internal(entity_id: u64, seconds: f32) -> bool {
+    if !validation::accept(validation::finite("seconds", &[seconds])) {
+        return false;
+    }
+    let Some(entity) = entity_from_id(entity_id) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    if known_body(physics, entity_id).is_none() {
+        return false;
+    }
+    let mut entity_mut = physics.world.entity_mut(entity);
+    match Lifetime::new(seconds) {
+        Some(lifetime) => entity_mut.insert(lifetime),
+        None => entity_mut.remove::<Lifetime>(),
+    };
+    true
+}
+
+/// Despawn dynamic bodies that leave the rectangle spanned by the two
+/// corners, or stop doing so. Returns false for an empty or non-finite
+/// rectangle.
+fn set_despawn_bounds_internal(enabled: bool, min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> bool {
+    let bounds = DespawnBounds::new([min_x, min_y], [max_x, max_y]);
+    if enabled && bounds.is_none() {
+        return false;
+    }
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    physics.despawn_bounds = bounds.filter(|_| enabled);
+    true
+}
+
+/// Report collision impacts between `min_speed` (silent) and `max_speed`
+/// (full volume) as audio events, or stop. Returns false for invalid speeds.
+fn set_audio_events_internal(enabled: bool, min_speed: f32, max_speed: f32) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let Some(config) = physics.audio_config.with_speeds(min_speed, max_speed) else {
+        return false;
+    };
+    physics.audio_config = AudioConfig { enabled, ..config };
+    if !enabled {
+        physics.audio_events = AudioQueue::new();
+    }
+    true
+}
+
+/// Where the host's listener (usually the camera) is, for volume and pan
+fn set_audio_listener_internal(x: f32, y: f32) -> bool {
+    if !validation::accept(validation::finite("listener position", &[x, y])) {
+        return false;
+    }
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().map(|physics| physics.audio_config.listener = [x, y]).is_some(),
+        Err(_) => false,
+    }
+}
+
+/// Full volume within `reference_distance` of the listener, falling off with
+/// `rolloff` beyond it, silent past `max_distance`
+fn set_audio_attenuation_internal(reference_distance: f32, max_distance: f32, rolloff: f32) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    match physics.audio_config.with_attenuation(reference_distance, max_distance, rolloff) {
+        Some(config) => {
+            physics.audio_config = config;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Take up to `max` queued audio events, oldest first
+fn poll_audio_events_internal(max: usize) -> Vec<AudioEvent> {
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().map_or_else(Vec::new, |physics| physics.audio_events.drain(max)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Make the body break on strong impacts with `config`, or stop it from
+/// breaking with None. Returns false for unknown ids and invalid configs.
+fn set_destructible_internal(entity_id: u64, config: Option<FractureConfig>) -> bool {
+    let config = match config.map(FractureConfig::validated) {
+        Some(None) => return false,
+        config => config.flatten(),
+    };
+    let Some(entity) = entity_from_id(entity_id) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    if known_body(physics, entity_id).is_none() {
+        return false;
+    }
+    let mut entity_mut = physics.world.entity_mut(entity);
+    match config {
+        Some(config) => entity_mut.insert(Destructible(config)),
+        None => entity_mut.remove::<Destructible>(),
+    };
+    true
+}
+
+/// Take up to `max` queued fracture events, oldest first
+fn poll_fracture_events_internal(max: usize) -> Vec<FractureEvent> {
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().map_or_else(Vec::new, |physics| physics.fracture_events.drain(max)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Remove and return up to `max` queued trigger zone events, oldest first
+fn poll_trigger_events_internal(max: usize) -> Vec<TriggerEvent> {
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().map_or_else(Vec::new, |physics| physics.triggers.drain(max)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Won once a body reached a goal zone, lost once one entered a hazard;
+/// None without a simulation
+fn level_outcome_internal() -> Option<triggers::Outcome> {
+    let guard = PHYSICS_STATE.lock().ok()?;
+    guard.0.as_ref().map(|physics| physics.triggers.outcome())
+}
+
+/// Preallocate `capacity` projectiles of `radius`, replacing any previous
+/// pool; 0 removes it. Returns false for invalid arguments or no simulation.
+fn configure_projectiles_internal(capacity: u32, radius: f32) -> bool {
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().is_some_and(|physics| projectiles::configure(physics, capacity, radius)),
+        Err(_) => false,
+    }
+}
+
+/// Launch a pooled projectile; it is parked again after `ttl` seconds or on
+/// its first collision. Returns its entity id, or None when the pool is
+/// missing or exhausted.
+fn fire_projectile_internal(origin_x: f32, origin_y: f32, vx: f32, vy: f32, ttl: f32) -> Option<u64> {
+    if !validation::accept(validation::finite("origin and velocity", &[origin_x, origin_y, vx, vy])) {
+        return None;
+    }
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_mut()?;
+    projectiles::fire(physics, [origin_x, origin_y], [vx, vy], ttl)
+}
+
+fn projectile_stats_internal() -> ProjectileStats {
+    match PHYSICS_STATE.lock() {
+        Ok(guard) => guard.0.as_ref().map_or_else(ProjectileStats::default, projectiles::stats),
+        Err(_) => ProjectileStats::default(),
+    }
+}
+
+/// Replace the loaded level geometry with `desc`, removing the boundary walls
+/// unless it keeps them. Returns the number of colliders created.
+fn load_level_internal(desc: &level_geometry::LevelDesc) -> Result<usize, String> {
+    if desc.triggers.len() > triggers::MAX_ZONES {
+        return Err(format!("at most {} trigger zones", triggers::MAX_ZONES));
+    }
+    for zone in &desc.triggers {
+        zone.validate()?;
+    }
+    // Tile grids keep their own collider so single tiles can be edited later
+    let mut colliders = Vec::new();
+    let mut tilemaps = Vec::new();
+    let mut terrain_vertices = Vec::new();
+    for shape in &desc.shapes {
+        let collider = level_geometry::shape_collider(shape)?;
+        match shape {
+            level_geometry::LevelShape::Tiles(tilemap) => tilemaps.push((tilemap.clone(), collider)),
+            level_geometry::LevelShape::Terrain(terrain) => {
+                terrain_vertices.extend(terrain.vertices());
+                colliders.extend(collider);
+            }
+            _ => colliders.extend(collider),
+        }
+    }
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+
+    let mut removed = physics.level_body.take().into_iter().collect::<Vec<_>>();
+    if !desc.keep_walls {
+        removed.append(&mut physics.walls);
+    }
+    for handle in removed {
+        physics.rigid_body_set.remove(
+            handle,
+            &mut physics.island_manager,
+            &mut physics.collider_set,
+            &mut physics.impulse_joint_set,
+            &mut physics.multibody_joint_set,
+            true,
+        );
+    }
+
+    let count = colliders.len() + tilemaps.iter().filter(|(_, collider)| collider.is_some()).count();
+    let body = physics.rigid_body_set.insert(RigidBodyBuilder::fixed().build());
+    for collider in colliders {
+        physics.collider_set.insert_with_parent(collider, body, &mut physics.rigid_body_set);
+    }
+    let tilemaps = tilemaps
+        .into_iter()
+        .map(|(tilemap, collider)| {
+            let handle = collider.map(|c| physics.collider_set.insert_with_parent(c, body, &mut physics.rigid_body_set));
+            (tilemap, handle)
+        })
+        .collect();
+    physics.tilemaps = tilemaps;
+    physics.terrain_vertices = terrain_vertices;
+    physics.tilemaps_dirty = true;
+    physics.level_body = Some(body);
+    physics.triggers.set_zones(desc.triggers.clone());
+    // Resting bodies would otherwise hang where removed geometry used to be
+    wake_dynamic_bodies(&mut physics.rigid_body_set);
+    Ok(count)
+}
+
+/// Change one cell of a tile layer; the collider is rebuilt when the cell
+/// turns solid or empty and the drawn tiles follow on the next frame.
+/// Returns false for unknown layers or cells off the grid.
+fn set_tile_internal(layer: u32, column: u32, row: u32, value: u8) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let PhysicsState {
+        tilemaps, tilemaps_dirty, level_body, rigid_body_set, collider_set, island_manager, narrow_phase, ..
+    } = physics;
+    let Some((tilemap, collider)) = tilemaps.get_mut(layer as usize) else {
+        return false;
+    };
+    if tilemap.get(column, row).is_none() {
+        return false;
+    }
+    *tilemaps_dirty = true;
+    if !tilemap.set(column, row, value) {
+        return true;
+    }
+
+    if let Some(old) = collider.take() {
+        // Bodies resting on a removed tile have to wake up to fall
+        wake_touching(narrow_phase, collider_set, rigid_body_set, old);
+        collider_set.remove(old, island_manager, rigid_body_set, true);
+    }
+    if let (Some(new), Some(body)) = (tilemap.collider(), *level_body) {
+        *collider = Some(collider_set.insert_with_parent(new, body, rigid_body_set));
+    }
+    true
+}
+
+/// Value of one tile cell; None for unknown layers or cells off the grid
+fn get_tile_internal(layer: u32, column: u32, row: u32) -> Option<u8> {
+    let guard = PHYSICS_STATE.lock().ok()?;
+    let (tilemap, _) = guard.0.as_ref()?.tilemaps.get(layer as usize)?;
+    tilemap.get(column, row)
+}
+
+/// Cell of a tile layer under a world point, for editing where the user taps
+fn tile_cell_at_internal(layer: u32, x: f32, y: f32) -> Option<(u32, u32)> {
+    let guard = PHYSICS_STATE.lock().ok()?;
+    let (tilemap, _) = guard.0.as_ref()?.tilemaps.get(layer as usize)?;
+    tilemap.cell_at(x, y)
+}
+
+/// `load_level_internal` from a JSON `LevelDesc`
+fn load_level_json_internal(json: &str) -> Result<usize, String> {
+    let desc = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    load_level_internal(&desc)
+}
+
+fn valid_spring(x: f32, y: f32, stiffness: f32, damping: f32) -> bool {
+    validation::accept(
+        validation::finite("point", &[x, y])
+            .and(validation::non_negative("stiffness", stiffness))
+            .and(validation::non_negative("damping", damping)),
+    )
+}
+
+/// Pull the body toward (world_x, world_y) with a spring. Returns the spring
+/// id, or None for unknown bodies or negative/non-finite arguments.
+fn create_spring_to_point_internal(entity_id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> Option<u64> {
+    if !valid_spring(world_x, world_y, stiffness, damping) {
+        return None;
+    }
+    let entity = entity_from_id(entity_id)?;
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_mut()?;
+    if known_entity(physics, entity_id).is_none() {
+        return None;
+    }
+    let body = physics.world.get::<PhysicsBody>(entity)?.rigid_body_handle;
+    Some(point_springs::create(physics, body, [world_x, world_y], stiffness, damping))
+}
+
+/// Move a spring's point and change its strength; cheap enough to call every frame
+fn update_spring_internal(spring_id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> bool {
+    if !valid_spring(world_x, world_y, stiffness, damping) {
+        return false;
+    }
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().is_some_and(|physics| {
+            point_springs::update(physics, spring_id, [world_x, world_y], stiffness, damping)
+        }),
+        Err(_) => false,
+    }
+}
+
+fn remove_spring_internal(spring_id: u64) -> bool {
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().is_some_and(|physics| point_springs::remove(physics, spring_id)),
+        Err(_) => false,
+    }
+}
+
+/// Spawn a pressure-spring blob: a ring of `desc.segments` small dynamic
+/// boxes tied with springs and inflated toward its rest area every step.
+/// Returns the blob id, or None for invalid descriptions.
+fn spawn_soft_body_internal(desc: SoftBodyDesc) -> Option<u64> {
+    let desc = desc.validated()?;
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_mut()?;
+    let ring = desc.ring();
+    let mut nodes = Vec::with_capacity(ring.len());
+    let PhysicsState { world, rigid_body_set, collider_set, sleep_enabled, .. } = &mut *physics;
+    for &[x, y] in &ring {
+        let entity = spawn_box(world, rigid_body_set, collider_set, x, y, desc.node_half_extent, RigidBodyType::Dynamic);
+        let handle = world.get::<PhysicsBody>(entity)?.rigid_body_handle;
+        if !*sleep_enabled {
+            *rigid_body_set[handle].activation_mut() = RigidBodyActivation::cannot_sleep();
+        }
+        nodes.push(handle);
+    }
+    for (a, b, stiffness) in soft_body::ring_springs(ring.len(), desc.stiffness) {
+        let rest_length = ((ring[a][0] - ring[b][0]).powi(2) + (ring[a][1] - ring[b][1]).powi(2)).sqrt();
+        let joint = soft_body::node_spring(rest_length, stiffness, desc.damping);
+        physics.impulse_joint_set.insert(nodes[a], nodes[b], joint, true);
+    }
+    physics.instance_layout_dirty = true;
+    let rest_area = soft_body::polygon_area(&ring);
+    Some(physics.soft_bodies.insert(nodes, rest_area, desc.pressure, desc.color))
+}
+
+/// `spawn_soft_body_internal` from a JSON `SoftBodyDesc`
+fn spawn_soft_body_json_internal(json: &str) -> Result<u64, String> {
+    let desc = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    spawn_soft_body_internal(desc).ok_or_else(|| "invalid soft body or simulation not running".into())
+}
+
+/// Remove a blob together with its nodes and springs. Returns false for
+/// unknown ids.
+fn remove_soft_body_internal(soft_body_id: u64) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let Some(body) = physics.soft_bodies.remove(soft_body_id) else {
+        return false;
+    };
+    let members: Vec<(Entity, PhysicsBody)> = physics
+        .world
+        .query::<(Entity, &PhysicsBody)>()
+        .iter(&physics.world)
+        .filter(|(_, node)| body.nodes.contains(&node.rigid_body_handle))
+        .map(|(entity, node)| (entity, *node))
+        .collect();
+    for (entity, node) in members {
+        physics.rigid_body_set.remove(
+            node.rigid_body_handle,
+            &mut physics.island_manager,
+            &mut physics.collider_set,
+            &mut physics.impulse_joint_set,
+            &mut physics.multibody_joint_set,
+            true,
+        );
+        physics.surface_velocities.remove(&node.collider_handle);
+        physics.world.despawn(entity);
+    }
+    physics.instance_layout_dirty = true;
+    true
+}
+
+/// Add one dynamic box to the running scene. Returns its entity id.
+fn spawn_body_internal(x: f32, y: f32, half_extent: f32) -> Option<u64> {
+    spawn_body_with_internal(x, y, half_extent, RigidBodyType::Dynamic, [0.0, 0.0], groups::DEFAULT)
+}
+
+/// Add one box of `body_type` in `groups` to the running scene; a nonzero
+/// `surface_velocity` makes it a conveyor. Returns its entity id.
+fn spawn_body_with_internal(
+    x: f32,
+    y: f32,
+    half_extent: f32,
+    body_type: RigidBodyType,
+    surface_velocity: [f32; 2],
+    groups: u32,
+) -> Option<u64> {
+    let valid = validation::finite("position", &[x, y])
+        .and(validation::size("half extent", half_extent))
+        .and(validation::finite("surface velocity", &surface_velocity));
+    if !validation::accept(valid) {
+        return None;
+    }
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    spawn_body_in(guard.0.as_mut()?, x, y, half_extent, body_type, surface_velocity, groups)
+}
+
+/// `spawn_body_with_internal` on an already locked state; `half_extent` must
+/// be checked by the caller
+fn spawn_body_in(
+    physics: &mut PhysicsState,
+    x: f32,
+    y: f32,
+    half_extent: f32,
+    body_type: RigidBodyType,
+    surface_velocity: [f32; 2],
+    groups: u32,
+) -> Option<u64> {
+    let collider = ColliderBuilder::cuboid(to_real(half_extent), to_real(half_extent), to_real(half_extent));
+    let entity = spawn_shape_in(physics, x, y, half_extent, collider, body_type, groups)?;
+    let body = *physics.world.get::<PhysicsBody>(entity)?;
+    set_surface_velocity(physics, body.collider_handle, surface_velocity);
+    Some(entity.to_bits())
+}
+
+/// Add a body with `collider` to an already locked state, drawn as a square
+/// of `half_extent`
+fn spawn_shape_in(
+    physics: &mut PhysicsState,
+    x: f32,
+    y: f32,
+    half_extent: f32,
+    collider: ColliderBuilder,
+    body_type: RigidBodyType,
+    groups: u32,
+) -> Option<Entity> {
+    let PhysicsState { world, rigid_body_set, collider_set, sleep_enabled, .. } = physics;
+    let entity = spawn_shape(world, rigid_body_set, collider_set, [x, y], half_extent, collider, body_type);
+    let body = *world.get::<PhysicsBody>(entity)?;
+    if !*sleep_enabled && body_type.is_dynamic() {
+        *rigid_body_set[body.rigid_body_handle].activation_mut() = RigidBodyActivation::cannot_sleep();
+    }
+    world.entity_mut(entity).insert(Groups(groups));
+    physics.instance_layout_dirty = true;
+    Some(entity)
+}
+
+/// Add a body of `body_type` in `groups` whose collider is the outline
+/// `points` around (x, y), concave or not, split into convex pieces (see
+/// `polygon`). Returns its entity id.
+fn spawn_polygon_internal(x: f32, y: f32, points: &[[f32; 2]], body_type: RigidBodyType, groups: u32) -> Result<u64, String> {
+    validation::finite("polygon position", &[x, y]).map_err(validation::reject)?;
+    let collider = polygon::collider(points)?;
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    let entity = spawn_shape_in(physics, x, y, polygon::half_extent(points), collider, body_type, groups)
+        .ok_or("simulation not running")?;
+    Ok(entity.to_bits())
+}
+
+/// Run a packed command buffer (see `command_buffer`) under one lock of the
+/// physics state. Returns one result per command, or an error without running
+/// anything when the buffer does not decode or the simulation is not running.
+fn submit_commands_internal(bytes: &[u8]) -> Result<Vec<u64>, String> {
+    use command_buffer::Command;
+
+    let commands = command_buffer::decode(bytes)?;
+    // Check materials and fetch their traced shapes before touching physics
+    // so we never hold both locks
+    let known_materials: std::collections::HashMap<u32, _> = match WGPU_STATE.lock() {
+        Ok(guard) => guard.0.as_ref().map_or_else(Default::default, |state| {
+            commands
+                .iter()
+                .filter_map(|command| match *command {
+                    Command::SetMaterial { material_id, .. } if state.materials.contains(material_id) => {
+                        Some((material_id, state.materials.shape(material_id).cloned()))
+                    }
+                    _ => None,
+                })
+                .collect()
+        }),
+        Err(_) => Default::default(),
+    };
+
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    let results = commands
+        .into_iter()
+        .map(|command| match command {
+            Command::Spawn { x, y, half_extent, body_type, groups } => {
+                let valid = validation::accept(validation::finite("position", &[x, y]).and(validation::size("half extent", half_extent)));
+                match body_type_from_raw(body_type as i32) {
+                    Some(body_type) if valid => spawn_body_in(physics, x, y, half_extent, body_type, [0.0, 0.0], groups).unwrap_or(0),
+                    _ => 0,
+                }
+            }
+            Command::Impulse { entity_id, x, y } => {
+                (validation::accept(validation::finite("impulse", &[x, y]))
+                    && with_body_in(physics, entity_id, |rb| rb.apply_impulse(planar([x, y]), true))) as u64
+            }
+            Command::SetVelocity { entity_id, vx, vy } => {
+                (validation::accept(validation::finite("velocity", &[vx, vy]))
+                    && with_body_in(physics, entity_id, |rb| rb.set_linvel(planar([vx, vy]), true))) as u64
+            }
+            Command::SetPosition { entity_id, x, y } => {
+                (validation::accept(validation::finite("position", &[x, y]))
+                    && with_body_in(physics, entity_id, |rb| {
+                        let z = rb.translation().z;
+                        rb.set_translation(vector![to_real(x), to_real(y), z], true);
+                    })) as u64
+            }
+            Command::SetGroups { entity_id, groups } => insert_on_body(physics, entity_id, Groups(groups)) as u64,
+            Command::SetMaterial { entity_id, material_id } => {
+                let applied = known_materials.contains_key(&material_id)
+                    && insert_on_body(physics, entity_id, MaterialComponent::new(material_id));
+                physics.instance_layout_dirty |= applied;
+                if let (true, Some(Some(pieces))) = (applied, known_materials.get(&material_id)) {
+                    reshape_body_in(physics, entity_id, pieces);
+                }
+                applied as u64
+            }
+        })
+        .collect();
+    Ok(results)
+}
+
+/// Run `f` on the rigid body behind `entity_id` in an already locked state.
+/// Returns false for unknown ids.
+fn with_body_in(physics: &mut PhysicsState, entity_id: u64, f: impl FnOnce(&mut RigidBody)) -> bool {
+    match known_body(physics, entity_id).and_then(|body| physics.rigid_body_set.get_mut(body.rigid_body_handle)) {
+        Some(rb) => {
+            f(rb);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Insert `component` on the entity if it has a body. Returns false otherwise.
+fn insert_on_body(physics: &mut PhysicsState, entity_id: u64, component: impl Component) -> bool {
+    match entity_from_id(entity_id).filter(|_| known_body(physics, entity_id).is_some()) {
+        Some(entity) => {
+            physics.world.entity_mut(entity).insert(component);
+            true
+        }
+        None => false,
+    }
+}
+
+/// `PHYSICS_CORE_BODY_*` value to a Rapier body type
+fn body_type_from_raw(raw: i32) -> Option<RigidBodyType> {
+    match raw {
+        0 => Some(RigidBodyType::Dynamic),
+        1 => Some(RigidBodyType::Fixed),
+        2 => Some(RigidBodyType::KinematicPositionBased),
+        _ => None,
+    }
+}
+
+/// Make the entity's collider a conveyor moving at (vx, vy); zero turns it
+/// back into a plain surface. Returns false for unknown ids.
+fn set_surface_velocity_internal(entity_id: u64, vx: f32, vy: f32) -> bool {
+    if !validation::accept(validation::finite("surface velocity", &[vx, vy])) {
+        return false;
+    }
+    let Some(entity) = entity_from_id(entity_id) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    if known_entity(physics, entity_id).is_none() {
+        return false;
+    }
+    match physics.world.get::<PhysicsBody>(entity).copied() {
+        Some(body) => {
+            set_surface_velocity(physics, body.collider_handle, [vx, vy]);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Record the collider's surface velocity, toggle the contact hook on it and
+/// wake whatever rests on it so a belt that starts moving takes effect
+fn set_surface_velocity(physics: &mut PhysicsState, handle: ColliderHandle, velocity: [f32; 2]) {
+    if !physics.collider_set.contains(handle) {
+        return;
+    }
+    if velocity == [0.0, 0.0] {
+        physics.surface_velocities.remove(&handle);
+    } else {
+        physics.surface_velocities.insert(handle, planar(velocity));
+    }
+    refresh_contact_hooks(physics, handle);
+    wake_touching(&physics.narrow_phase, &physics.collider_set, &mut physics.rigid_body_set, handle);
+}
+
+/// Turn on exactly the hooks the collider needs: contact modification for
+/// conveyors and one-way platforms, pair filtering for ignored pairs
+fn refresh_contact_hooks(physics: &mut PhysicsState, handle: ColliderHandle) {
+    let Some(collider) = physics.collider_set.get_mut(handle) else {
+        return;
+    };
+    let mut hooks = collider.active_hooks();
+    let modify = physics.surface_velocities.contains_key(&handle) || physics.contact_filter.one_way.contains(&handle);
+    hooks.set(ActiveHooks::MODIFY_SOLVER_CONTACTS, modify);
+    let filter = physics.contact_filter.ignored.involves(collider.user_data as u64);
+    hooks.set(ActiveHooks::FILTER_CONTACT_PAIRS | ActiveHooks::FILTER_INTERSECTION_PAIR, filter);
+    collider.set_active_hooks(hooks);
+}
+
+/// Make the body a one-way platform that only holds up bodies landing on its
+/// top (local +y) side, or a normal body again. Returns false for unknown ids.
+fn set_one_way_internal(entity_id: u64, enabled: bool) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let Some(body) = body_of(physics, entity_id) else {
+        return false;
+    };
+    let handle = body.collider_handle;
+    if enabled {
+        physics.contact_filter.one_way.insert(handle);
+    } else {
+        physics.contact_filter.one_way.remove(&handle);
+    }
+    refresh_contact_hooks(physics, handle);
+    wake_touching(&physics.narrow_phase, &physics.collider_set, &mut physics.rigid_body_set, handle);
+    true
+}
+
+/// Let two bodies pass through each other (`ignore`) or collide again.
+/// Returns false for unknown ids, a body paired with itself or no change.
+fn set_collision_ignored_internal(entity_a: u64, entity_b: u64, ignore: bool) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let (Some(a), Some(b)) = (body_of(physics, entity_a), body_of(physics, entity_b)) else {
+        return false;
+    };
+    let ignored = &mut physics.contact_filter.ignored;
+    let changed = if ignore { ignored.insert(entity_a, entity_b) } else { ignored.remove(entity_a, entity_b) };
+    if changed {
+        for body in [a, b] {
+            refresh_contact_hooks(physics, body.collider_handle);
+            if let Some(rb) = physics.rigid_body_set.get_mut(body.rigid_body_handle) {
+                rb.wake_up(true);
+            }
+        }
+    }
+    changed
+}
+
+/// Draw a fading trail behind the body through its last `length` recorded
+/// positions (2..=1024), in straight RGBA `color`. Enabling an existing
+/// trail restyles it. Returns false for unknown ids.
+fn enable_trail_internal(entity_id: u64, length: u32, color: [f32; 4]) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    if body_of(physics, entity_id).is_none() {
+        return false;
+    }
+    physics.trails.enable(entity_id, length, color);
+    true
+}
+
+/// Remove the body's trail. Returns false if it had none.
+fn disable_trail_internal(entity_id: u64) -> bool {
+    PHYSICS_STATE
+        .lock()
+        .ok()
+        .and_then(|mut guard| guard.0.as_mut().map(|physics| physics.trails.disable(entity_id)))
+        .unwrap_or(false)
+}
+
+/// Where the body would be after each of up to `steps` fixed steps (at most
+/// 600) if launched at (vx, vy) now, without touching the simulation. `show`
+/// also draws the points as a dotted arc until the next prediction or
+/// `clear_trajectory_preview_internal`. None for unknown or non-dynamic
+/// bodies and non-finite velocities.
+fn predict_trajectory_internal(entity_id: u64, vx: f32, vy: f32, steps: u32, show: bool) -> Option<Vec<[f32; 2]>> {
+    if !validation::accept(validation::finite("launch velocity", &[vx, vy])) {
+        return None;
+    }
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_mut()?;
+    let entity = known_entity(physics, entity_id)?;
+    let points = trajectory::predict(physics, entity, [vx, vy], steps)?;
+    if show {
+        physics.trajectory_preview = points.clone();
+    }
+    Some(points)
+}
+
+fn clear_trajectory_preview_internal() {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.trajectory_preview.clear();
+        }
+    }
+}
+
+/// The body behind an entity id, if the entity exists and has one
+fn body_of(physics: &PhysicsState, entity_id: u64) -> Option<PhysicsBody> {
+    let entity = entity_from_id(entity_id).filter(|&entity| physics.world.entities().contains(entity))?;
+    physics.world.get::<PhysicsBody>(entity).copied()
+}
+
+/// `body_of` for an id a host passed in; an unknown id is kept as the
+/// bindings' last error
+fn known_body(physics: &PhysicsState, entity_id: u64) -> Option<PhysicsBody> {
+    let body = body_of(physics, entity_id);
+    if body.is_none() {
+        validation::reject(InputError::unknown_entity(entity_id));
+    }
+    body
+}
+
+/// The live entity behind an id a host passed in; an unknown id is kept as
+/// the bindings' last error
+fn known_entity(physics: &PhysicsState, entity_id: u64) -> Option<Entity> {
+    let entity = entity_from_id(entity_id).filter(|&entity| physics.world.entities().contains(entity));
+    if entity.is_none() {
+        validation::reject(InputError::unknown_entity(entity_id));
+    }
+    entity
+}
+
+/// Wake every body in contact with the collider
+fn wake_touching(narrow_phase: &NarrowPhase, colliders: &ColliderSet, bodies: &mut RigidBodySet, handle: ColliderHandle) {
+    for pair in narrow_phase.contact_pairs_with(handle) {
+        let other = if pair.collider1 == handle { pair.collider2 } else { pair.collider1 };
+        if let Some(rb) = colliders.get(other).and_then(|c| c.parent()).and_then(|parent| bodies.get_mut(parent)) {
+            rb.wake_up(true);
+        }
+    }
+}
+
+/// Advance exactly one fixed step now, even while paused
+fn step_once_internal() {
+    fast_forward_internal(1);
+}
+
+/// Run up to `steps` fixed steps immediately without rendering. Returns the steps run.
+fn fast_forward_internal(steps: u32) -> u32 {
+    let initialized = PHYSICS_STATE.lock().is_ok_and(|guard| guard.0.is_some());
+    if !initialized {
+        return 0;
+    }
+    let steps = steps.min(time_control::MAX_FAST_FORWARD_STEPS);
+    for _ in 0..steps {
+        advance_simulation(time_control::STEP_DT);
+    }
+    steps
+}
+
+/// Ease the time scale to `target` over `seconds` of real time
+fn set_time_scale_ramp_internal(target: f32, seconds: f32) {
+    if !validation::accept(validation::non_negative("time scale", target).and(validation::finite("seconds", &[seconds]))) {
+        return;
+    }
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.time_ramp = Some(time_control::TimeScaleRamp::new(physics.time_scale, target, seconds));
+        }
+    }
+}
+
+/// Restore the state from `steps` steps ago. Returns the steps actually rewound.
+fn rewind_internal(steps: u32) -> u32 {
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().map_or(0, |physics| snapshot::rewind(physics, steps as usize) as u32),
+        Err(_) => 0,
+    }
+}
+
+/// How many steps of history to keep for rewinding; 0 turns capture off
+fn set_rewind_history_internal(capacity: u32) {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.history.set_capacity(capacity as usize);
+        }
+    }
+}
+
+/// State of every body relative to `baseline`, a tick acknowledged by the
+/// remote side (0 for a full snapshot). None if the simulation is not running.
+fn snapshot_delta_internal(baseline: u64) -> Option<Vec<u8>> {
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_mut()?;
+    Some(netcode::snapshot_delta(physics, baseline))
+}
+
+/// Apply a remote snapshot; returns its tick for the sender's next baseline
+fn apply_snapshot_internal(bytes: &[u8]) -> Result<u64, String> {
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    netcode::apply_snapshot(physics, bytes)
+}
+
+/// Keep `window` steps (at most `prediction::MAX_WINDOW`, 0 turns rollback
+/// off) of input and predicted states, redoing steps when a prediction is off
+/// by more than `tolerance`. Raises the rewind history to cover the window.
+/// Returns false for invalid arguments or if the simulation is not running.
+fn set_prediction_internal(window: u32, tolerance: f32) -> bool {
+    let Some(prediction) = prediction::Prediction::new(window, tolerance) else {
+        return false;
+    };
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    if window > 0 {
+        let capacity = physics.history.capacity().max(window as usize + 1);
+        physics.history.set_capacity(capacity);
+    }
+    physics.prediction = prediction;
+    true
+}
+
+/// Tag or untag a body as predicted locally. Returns false for unknown bodies.
+fn set_predicted_internal(entity_id: u64, predicted: bool) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let Some((entity, _)) = netcode::live_body(physics, entity_id) else {
+        return false;
+    };
+    let mut entity = physics.world.entity_mut(entity);
+    if predicted {
+        entity.insert(prediction::Predicted);
+    } else {
+        entity.remove::<prediction::Predicted>();
+    }
+    true
+}
+
+/// Apply an authoritative snapshot, rolling back and re-simulating if the
+/// local prediction was off; returns its tick for the sender's next baseline
+fn reconcile_snapshot_internal(bytes: &[u8]) -> Result<u64, String> {
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    prediction::reconcile(physics, bytes)
+}
+
+/// Ids of dynamic bodies that are currently asleep
+fn sleeping_bodies_internal() -> Vec<u64> {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            let PhysicsState { world, rigid_body_set, .. } = physics;
+            return world
+                .query::<(Entity, &PhysicsBody)>()
+                .iter(world)
+                .filter(|(_, body)| rigid_body_set.get(body.rigid_body_handle).is_some_and(|rb| rb.is_sleeping()))
+                .map(|(entity, _)| entity.to_bits())
+                .collect();
+        }
+    }
+    Vec::new()
+}
+
+/// Collisions that started during the latest step, as entity id pairs.
+/// Colliders without an entity (the walls) are reported as 0.
+fn recent_collisions_internal() -> Vec<(u64, u64)> {
+    match PHYSICS_STATE.lock() {
+        Ok(guard) => guard.0.as_ref().map_or(Vec::new(), |physics| {
+            physics.world.resource::<schedule::CollisionEvents>().started.clone()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// (dynamic bodies, sleeping dynamic bodies) for the debug overlay
+fn sleep_stats(bodies: &RigidBodySet) -> (usize, usize) {
+    bodies
+        .iter()
+        .filter(|(_, rb)| rb.is_dynamic())
+        .fold((0, 0), |(total, sleeping), (_, rb)| (total + 1, sleeping + rb.is_sleeping() as usize))
+}
+
+/// Entity ids of every body matching `mask` whose collider intersects `shape` placed at `center`
+fn query_shape(physics: &mut PhysicsState, center: Vector<Real>, shape: &dyn Shape, mask: u32) -> Vec<u64> {
+    let mut ids = Vec::new();
+    // Refresh here rather than relying on the last step so queries
+    // stay correct while the simulation is paused
+    physics.query_pipeline.update(&physics.collider_set);
+    physics.query_pipeline.intersections_with_shape(
+        &physics.rigid_body_set,
+        &physics.collider_set,
+        &Isometry::translation(center.x, center.y, center.z),
+        shape,
+        QueryFilter::default(),
+        |handle| {
+            let bits = physics.collider_set[handle].user_data as u64;
+            if let Some(entity) = entity_from_id(bits).filter(|&e| physics.world.entities().contains(e)) {
+                if physics.world.get::<Groups>(entity).copied().unwrap_or_default().matches(mask) {
+                    ids.push(entity.to_bits());
+                }
+            }
+            true
+        },
+    );
+    ids
+}
+
+/// `query_shape` on the global state; empty before init
+fn query_shape_internal(center: Vector<Real>, shape: &dyn Shape, mask: u32) -> Vec<u64> {
+    match PHYSICS_STATE.lock() {
+        Ok(mut guard) => guard.0.as_mut().map_or(Vec::new(), |physics| query_shape(physics, center, shape, mask)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Center and box of the rectangle spanned by the two corners, in any
+/// order; None if a corner is not finite
+fn aabb_query_shape(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Option<(Vector<Real>, Cuboid)> {
+    if !validation::accept(validation::finite("corners", &[min_x, min_y, max_x, max_y])) {
+        return None;
+    }
+    let (x0, x1) = (min_x.min(max_x), min_x.max(max_x));
+    let (y0, y1) = (min_y.min(max_y), min_y.max(max_y));
+    // Bodies live on the z = 0 plane; give the box some depth so it overlaps them
+    let half_extents = vector![to_real((x1 - x0) * 0.5), to_real((y1 - y0) * 0.5), 1.0];
+    Some((planar([(x0 + x1) * 0.5, (y0 + y1) * 0.5]), Cuboid::new(half_extents)))
+}
+
+/// Center and sphere of the circle, centered on the z = 0 plane; None for
+/// a non-finite center or a negative radius
+fn circle_query_shape(center_x: f32, center_y: f32, radius: f32) -> Option<(Vector<Real>, Ball)> {
+    if !validation::accept(validation::finite("center", &[center_x, center_y]).and(validation::non_negative("radius", radius))) {
+        return None;
+    }
+    Some((planar([center_x, center_y]), Ball::new(to_real(radius.max(0.0)))))
+}
+
+/// Bodies matching `mask` that intersect the axis-aligned rectangle spanned by the two corners
+fn query_aabb_internal(min_x: f32, min_y: f32, max_x: f32, max_y: f32, mask: u32) -> Vec<u64> {
+    aabb_query_shape(min_x, min_y, max_x, max_y).map_or(Vec::new(), |(center, shape)| query_shape_internal(center, &shape, mask))
+}
+
+/// Bodies matching `mask` that intersect the circle
+fn query_circle_internal(center_x: f32, center_y: f32, radius: f32, mask: u32) -> Vec<u64> {
+    circle_query_shape(center_x, center_y, radius).map_or(Vec::new(), |(center, shape)| query_shape_internal(center, &shape, mask))
+}
+
+/// Advance the chunk streamer and enable only dynamic bodies in active chunks
+fn update_streaming(physics: &mut PhysicsState) {
+    if physics.streamer.is_none() {
+        return;
+    }
+    let [focus_x, focus_y] = match stream_focus_position(physics) {
+        Some(focus) => focus,
+        // Focus entity is gone; keep the current chunks until a new focus is set
+        None => return,
+    };
+    let streamer = match physics.streamer.as_mut() {
+        Some(s) => s,
+        None => return,
+    };
+
+    let changes = streamer.update(focus_x, focus_y);
+    if !changes.is_empty() {
+        log::debug!(
+            "Streaming: +{} / -{} chunks ({} active)",
+            changes.activated.len(),
+            changes.deactivated.len(),
+            streamer.active_chunk_count()
+        );
+    }
+
+    for (_, rb) in physics.rigid_body_set.iter_mut().filter(|(_, rb)| rb.is_dynamic()) {
+        let [x, y] = xy(rb.translation());
+        let active = streamer.is_position_active(x, y);
+        if rb.is_enabled() != active {
+            rb.set_enabled(active);
+        }
+    }
+}
+
+/// Where streaming and LOD are centered; None while the focus entity is gone
+fn stream_focus_position(physics: &PhysicsState) -> Option<[f32; 2]> {
+    match physics.stream_focus {
+        StreamFocus::Camera => Some(physics.camera_target),
+        StreamFocus::Point { x, y } => Some([x, y]),
+        StreamFocus::Entity(id) => entity_from_id(id)
+            .filter(|&e| physics.world.entities().contains(e))
+            .and_then(|e| physics.world.get::<PhysicsBody>(e))
+            .and_then(|body| physics.rigid_body_set.get(body.rigid_body_handle))
+            .map(|rb| xy(rb.translation())),
+    }
+}
+
+/// Factor on the sleep thresholds while the governor asks for aggressive
+/// sleeping; the time to fall asleep is divided by it
+const AGGRESSIVE_SLEEP_BIAS: f32 = 4.0;
+
+/// Rapier's default sleep thresholds raised by `bias` (1 keeps them), and
+/// by `AGGRESSIVE_SLEEP_BIAS` on top when `aggressive`. Bodies that already
+/// have them are left untouched.
+fn set_sleep_thresholds(rb: &mut RigidBody, bias: f32, aggressive: bool) {
+    let full = RigidBodyActivation::active();
+    let scale = to_real(if aggressive { bias * AGGRESSIVE_SLEEP_BIAS } else { bias });
+    let thresholds = (full.normalized_linear_threshold * scale, full.angular_threshold * scale, full.time_until_sleep / scale);
+    let current = rb.activation();
+    if (current.normalized_linear_threshold, current.angular_threshold, current.time_until_sleep) == thresholds {
+        return;
+    }
+    let activation = rb.activation_mut();
+    (activation.normalized_linear_threshold, activation.angular_threshold, activation.time_until_sleep) = thresholds;
+}
+
+/// Give every dynamic body the aggressive sleep thresholds while the
+/// governor asks for them, bodies spawned since included, and the usual
+/// ones once it stops
+fn update_sleep_thresholds(physics: &mut PhysicsState) {
+    // Without sleeping every body has `cannot_sleep` thresholds instead
+    if !physics.sleep_enabled {
+        physics.aggressive_sleep_applied = false;
+        return;
+    }
+    let aggressive = physics.power_throttle.aggressive_sleep;
+    if !aggressive && !physics.aggressive_sleep_applied {
+        return;
+    }
+    let bias = physics.lod.sleep_bias;
+    for (handle, rb) in physics.rigid_body_set.iter_mut().filter(|(_, rb)| rb.is_dynamic()) {
+        let far = physics.lod_far_bodies.contains(&handle);
+        set_sleep_thresholds(rb, if far { bias } else { 1.0 }, aggressive);
+    }
+    physics.aggressive_sleep_applied = aggressive;
+}
+
+/// Raise the sleep thresholds of dynamic bodies past the LOD physics
+/// distance from the focus, and put them back once the bodies return
+fn update_lod(physics: &mut PhysicsState) {
+    // Without sleeping every body already has `cannot_sleep` thresholds
+    if !physics.sleep_enabled {
+        physics.lod_far_bodies.clear();
+        return;
+    }
+    if physics.lod.physics_distance.is_none() && physics.lod_far_bodies.is_empty() {
+        return;
+    }
+    let focus = match stream_focus_position(physics) {
+        Some(focus) => focus,
+        None => return,
+    };
+
+    let (threshold, bias) = (physics.lod.physics_distance, physics.lod.sleep_bias);
+    let aggressive = physics.aggressive_sleep_applied;
+    for (handle, rb) in physics.rigid_body_set.iter_mut().filter(|(_, rb)| rb.is_dynamic()) {
+        let [x, y] = xy(rb.translation());
+        let distance = (x - focus[0]).hypot(y - focus[1]);
+        let was_far = physics.lod_far_bodies.contains(&handle);
+        let far = lod::is_far(threshold, distance, was_far);
+        if far == was_far {
+            continue;
+        }
+        set_sleep_thresholds(rb, if far { bias } else { 1.0 }, aggressive);
+        if far {
+            physics.lod_far_bodies.insert(handle);
+        } else {
+            physics.lod_far_bodies.remove(&handle);
+        }
+    }
+    physics.lod_far_bodies.retain(|&handle| physics.rigid_body_set.contains(handle));
+}
+
+/// Draw instances past the LOD render distance from the focus as plain quads
+fn update_render_lod(physics: &mut PhysicsState) {
+    let focus = match stream_focus_position(physics) {
+        Some(focus) => focus,
+        None => return,
+    };
+    let threshold = physics.lod.render_distance;
+    physics.instance_staging.update_each(|instance| {
+        let distance = (instance.position[0] - focus[0]).hypot(instance.position[1] - focus[1]);
+        instance.lod = lod::is_far(threshold, distance, instance.lod != 0) as u32;
+    });
+}
+
+fn set_streaming_internal(enabled: bool, chunk_size: f32, load_radius: u32, unload_radius: u32) {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            if enabled {
+                physics.streamer = Some(ChunkStreamer::new(StreamingConfig {
+                    chunk_size,
+                    load_radius,
+                    unload_radius,
+                }));
+            } else {
+                physics.streamer = None;
+                for (_, rb) in physics.rigid_body_set.iter_mut().filter(|(_, rb)| rb.is_dynamic()) {
+                    rb.set_enabled(true);
+                }
+            }
+        }
+    }
+}
+
+fn set_streaming_focus_internal(focus: StreamFocus) {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.stream_focus = focus;
+        }
+    }
+}
+
+/// Checksum of every body's pose and velocity at the current step; 0 before init
+fn state_checksum_internal() -> u64 {
+    if let Ok(guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_ref() {
+            return determinism::checksum_bodies(physics.step_count, &physics.rigid_body_set);
+        }
+    }
+    0
+}
+
+/// Upper bound for stress scenes so a typo on the host cannot exhaust memory
+const MAX_STRESS_BODIES: u32 = 20_000;
+
+/// Replace the simulation with a generated stress scene. Returns false for an
+/// unknown layout code.
+fn spawn_stress_scene_internal(n_bodies: u32, layout: i32) -> bool {
+    let layout = match stress::StressLayout::from_i32(layout) {
+        Some(layout) => layout,
+        None => {
+            log::warn!("spawn_stress_scene: unknown layout {}", layout);
+            return false;
+        }
+    };
+    let scene = stress::generate(n_bodies.min(MAX_STRESS_BODIES), layout);
+    log::info!("Spawning {:?} stress scene with {} bodies", layout, scene.positions.len());
+    init_ndc_scene(&scene.positions, scene.half_extent);
+    if let Ok(mut scenes) = SCENES.lock() {
+        scenes.clear_loaded();
+    }
+    true
+}
+
+/// Rebuild the simulation as `mode` says, keeping gravity, time scale, pause
+/// state and the other settings
+fn reset_simulation_internal(mode: ResetMode) {
+    // Read before the old scene goes
+    let persistent = match mode {
+        ResetMode::Scene => persistent_bodies(),
+        ResetMode::Pristine | ResetMode::Demo => Vec::new(),
+    };
+    let loaded = match SCENES.lock() {
+        Ok(mut scenes) => {
+            if mode == ResetMode::Demo {
+                scenes.clear_loaded();
+            }
+            scenes.loaded().cloned()
+        }
+        Err(_) => None,
+    };
+    match loaded {
+        Some(desc) => {
+            if let Err(e) = instantiate_scene(&desc) {
+                log::warn!("reset_simulation: {}", e);
+            }
+        }
+        None => init_physics(),
+    }
+    // Rebuilt bodies are new entities; remember which id each one replaces
+    let renames: Vec<(u64, u64)> = persistent
+        .into_iter()
+        .filter_map(|(old_id, body)| {
+            let id = spawn_scene_body(&body)?;
+            set_persistent_internal(id, true);
+            Some((old_id, id))
+        })
+        .collect();
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.persistent_renames = renames;
+        }
+    }
+}
+
+/// Id the latest reset rebuilt the persistent body `old_id` under; None if
+/// it did not rebuild that body
+fn persistent_id_after_reset_internal(old_id: u64) -> Option<u64> {
+    let guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_ref()?;
+    physics.persistent_renames.iter().find(|&&(old, _)| old == old_id).map(|&(_, id)| id)
+}
+
+/// `reset_simulation_internal` for an FFI mode code; false for an unknown one
+fn reset_simulation_to_internal(mode: i32) -> bool {
+    let Some(mode) = ResetMode::from_i32(mode) else {
+        validation::reject(InputError::new(validation::ERROR_OUT_OF_RANGE, format!("no reset mode {}", mode)));
+        return false;
+    };
+    reset_simulation_internal(mode);
+    true
+}
+
+/// Have resets to the scene rebuild the body `entity_id` as it is now, or
+/// stop doing so. Only boxes can persist. Returns false for unknown entities
+/// and other shapes. A rebuilt body gets a new id, see
+/// `persistent_id_after_reset_internal`.
+fn set_persistent_internal(entity_id: u64, persistent: bool) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let Some(entity) = known_entity(physics, entity_id) else {
+        return false;
+    };
+    if !persistent {
+        physics.world.entity_mut(entity).remove::<Persistent>();
+        return true;
+    }
+    let Some(body) = scene_body_of(physics, entity) else {
+        log::warn!("set_persistent: entity {} is not a box", entity_id);
+        return false;
+    };
+    physics.world.entity_mut(entity).insert(Persistent(body));
+    true
+}
+
+/// The scene body spawning a box like `entity` is now; None for other shapes
+fn scene_body_of(physics: &PhysicsState, entity: Entity) -> Option<SceneBody> {
+    let body = physics.world.get::<PhysicsBody>(entity)?;
+    let rb = physics.rigid_body_set.get(body.rigid_body_handle)?;
+    let cuboid = physics.collider_set.get(body.collider_handle)?.shape().as_cuboid()?;
+    let body_type = match rb.body_type() {
+        RigidBodyType::Dynamic => SceneBodyType::Dynamic,
+        RigidBodyType::Fixed => SceneBodyType::Fixed,
+        RigidBodyType::KinematicPositionBased | RigidBodyType::KinematicVelocityBased => SceneBodyType::Kinematic,
+    };
+    let ([x, y], [vx, vy]) = (xy(rb.translation()), xy(rb.linvel()));
+    Some(SceneBody {
+        x,
+        y,
+        half_extent: to_f32(cuboid.half_extents.x),
+        angle: to_f32(rb.rotation().euler_angles().2),
+        vx,
+        vy,
+        body_type,
+        groups: physics.world.get::<Groups>(entity).map_or(groups::DEFAULT, |g| g.0),
+        material_id: physics.world.get::<MaterialComponent>(entity).map_or(material::DEFAULT_MATERIAL, |m| m.material_id),
+    })
+}
+
+/// Id and scene body of every body marked persistent
+fn persistent_bodies() -> Vec<(u64, SceneBody)> {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return Vec::new();
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return Vec::new();
+    };
+    physics.world.query::<(Entity, &Persistent)>().iter(&physics.world).map(|(entity, persistent)| (entity.to_bits(), persistent.0)).collect()
+}
+
+/// Register `desc` as scene `name`, replacing any scene of that name
+fn register_scene_internal(name: &str, desc: SceneDesc) -> Result<(), String> {
+    SCENES.lock().map_err(|e| e.to_string())?.insert(name, desc)
+}
+
+/// `register_scene_internal` from a JSON `SceneDesc`
+fn register_scene_json_internal(name: &str, json: &str) -> Result<(), String> {
+    let desc = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    register_scene_internal(name, desc)
+}
+
+/// Register a `SceneDesc` file under its file name without the extension
+/// and switch to it. Returns the number of bodies spawned. Files ending in
+/// `.ron` are read as RON where that is available, anything else as JSON.
+fn load_scene_file_internal(path: &std::path::Path) -> Result<usize, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("scene");
+    let ron = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("ron"));
+    register_scene_internal(name, parse_scene(&path.display().to_string(), &text, ron)?)?;
+    switch_scene_internal(name, 0.0)
+}
+
+/// `load_scene_file_internal` for scene asset `name` (see `assets`),
+/// registered under its last path component without the extension
+fn load_scene_asset_internal(name: &str) -> Result<usize, String> {
+    let text = assets::read_text(name)?;
+    let file = name.rsplit('/').next().unwrap_or(name);
+    let (stem, ron) = match file.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, extension.eq_ignore_ascii_case("ron")),
+        _ => (file, false),
+    };
+    register_scene_internal(stem, parse_scene(name, &text, ron)?)?;
+    switch_scene_internal(stem, 0.0)
+}
+
+/// A `SceneDesc` from JSON, or RON where that is available, naming the
+/// scene `label` in errors
+fn parse_scene(label: &str, text: &str, ron: bool) -> Result<SceneDesc, String> {
+    if !ron {
+        return serde_json::from_str(text).map_err(|e| format!("{}: {}", label, e));
+    }
+    #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+    {
+        ron::from_str(text).map_err(|e| format!("{}: {}", label, e))
+    }
+    #[cfg(any(target_arch = "wasm32", target_os = "android"))]
+    {
+        Err(format!("{}: RON scenes are not supported on this platform", label))
+    }
+}
+
+fn remove_scene_internal(name: &str) -> bool {
+    SCENES.lock().map(|mut scenes| scenes.remove(name)).unwrap_or(false)
+}
+
+/// Replace the simulation with scene `name`, fading it in over
+/// `fade_seconds` (0 for a hard cut). Returns the number of bodies spawned.
+fn switch_scene_internal(name: &str, fade_seconds: f32) -> Result<usize, String> {
+    let desc = SCENES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(name)
+        .cloned()
+        .ok_or_else(|| format!("unknown scene '{}'", name))?;
+
+    log::info!("Switching to scene '{}' with {} bodies", name, desc.bodies.len());
+    instantiate_scene(&desc)?;
+
+    if let Ok(mut scenes) = SCENES.lock() {
+        scenes.set_current(name);
+    }
+    if let Ok(mut fade) = SCENE_FADE.lock() {
+        fade.0.start(fade_seconds);
+        fade.1 = None;
+    }
+    Ok(desc.bodies.len())
+}
+
+/// Registered material ids for the scene `textures`, by the ids the scene
+/// uses; textures that cannot be loaded get the default material
+fn load_scene_textures(textures: &std::collections::BTreeMap<u32, String>) -> std::collections::BTreeMap<u32, u32> {
+    textures
+        .iter()
+        .map(|(&scene_id, name)| {
+            let material_id = load_texture_asset_internal(name).unwrap_or_else(|e| {
+                log::warn!("scene: texture {}: {}", scene_id, e);
+                material::DEFAULT_MATERIAL
+            });
+            (scene_id, material_id)
+        })
+        .collect()
+}
+
+/// Replace the simulation with the scene `desc`
+fn instantiate_scene(desc: &SceneDesc) -> Result<(), String> {
+    let swapped;
+    let desc = if desc.textures.is_empty() {
+        desc
+    } else {
+        swapped = desc.with_materials(&load_scene_textures(&desc.textures));
+        &swapped
+    };
+    init_physics_scene(&[], 0.05);
+    load_level_internal(&desc.level)?;
+    if let Some([x, y]) = desc.gravity {
+        set_gravity_vector_internal(x, y, 0.0);
+    }
+    if let Some([x, y, z]) = desc.gravity_preset.map(|preset| preset.gravity()) {
+        set_gravity_vector_internal(x, y, z);
+    }
+    if let Some(lod) = desc.lod {
+        if let Ok(mut guard) = PHYSICS_STATE.lock() {
+            if let Some(physics) = guard.0.as_mut() {
+                physics.lod = lod;
+            }
+        }
+    }
+    let mut ids = Vec::with_capacity(desc.bodies.len());
+    for body in &desc.bodies {
+        ids.push(spawn_scene_body(body).ok_or("simulation not running")?);
+    }
+    if !desc.prefabs.is_empty() {
+        let mut prefabs = PREFABS.lock().map_err(|e| e.to_string())?;
+        for (name, prefab) in &desc.prefabs {
+            prefabs.insert(name, prefab.clone())?;
+        }
+    }
+    for instance in &desc.instances {
+        spawn_prefab_internal(&instance.prefab, instance.x, instance.y, instance.angle)?;
+    }
+    for intro in &desc.tweens {
+        let target = ids.get(intro.body).copied().unwrap_or(0);
+        start_tween(target, intro.property, &intro.from, &intro.to, intro.duration, intro.delay, intro.easing);
+    }
+    for behavior in &desc.state_machines {
+        for id in behavior.bodies.iter().filter_map(|&body| ids.get(body).copied()) {
+            if let Err(e) = set_state_machine_internal(id, Some(behavior.machine.clone())) {
+                log::warn!("scene: state machine for entity {}: {}", id, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Spawn one body of a scene. Returns its id, or None before init.
+fn spawn_scene_body(body: &SceneBody) -> Option<u64> {
+    let id = spawn_body_with_internal(body.x, body.y, body.half_extent, rigid_body_type(body.body_type), [0.0, 0.0], body.groups)?;
+    if body.angle != 0.0 {
+        with_rigid_body_mut(id, |rb| rb.set_rotation(planar_rotation(body.angle), true));
+    }
+    if body.vx != 0.0 || body.vy != 0.0 {
+        with_rigid_body_mut(id, |rb| rb.set_linvel(planar([body.vx, body.vy]), true));
+    }
+    if body.material_id != material::DEFAULT_MATERIAL {
+        set_entity_material_internal(id, body.material_id);
+    }
+    Some(id)
+}
+
+fn rigid_body_type(body_type: SceneBodyType) -> RigidBodyType {
+    match body_type {
+        SceneBodyType::Dynamic => RigidBodyType::Dynamic,
+        SceneBodyType::Fixed => RigidBodyType::Fixed,
+        SceneBodyType::Kinematic => RigidBodyType::KinematicPositionBased,
+    }
+}
+
+/// The scene body spawning a box like `entity_id` is now; None for unknown
+/// ids and other shapes
+fn scene_body_internal(entity_id: u64) -> Option<SceneBody> {
+    let guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_ref()?;
+    body_of(physics, entity_id)?;
+    scene_body_of(physics, entity_from_id(entity_id)?)
+}
+
+/// Make the box `entity_id` match `body` in everything but its material.
+/// Returns false for unknown ids and other shapes.
+fn set_scene_body_internal(entity_id: u64, body: &SceneBody) -> bool {
+    let valid = validation::finite("body", &[body.x, body.y, body.angle, body.vx, body.vy])
+        .and(validation::size("half extent", body.half_extent));
+    if !validation::accept(valid) {
+        return false;
+    }
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let (Some(entity), Some(handles)) = (entity_from_id(entity_id), body_of(physics, entity_id)) else {
+        return false;
+    };
+    let half_extent = to_real(body.half_extent);
+    match physics.collider_set.get_mut(handles.collider_handle) {
+        Some(collider) if collider.shape().as_cuboid().is_some() => {
+            if collider.shape().as_cuboid().map(|cuboid| cuboid.half_extents.x) != Some(half_extent) {
+                collider.set_shape(SharedShape::cuboid(half_extent, half_extent, half_extent));
+            }
+        }
+        _ => return false,
+    }
+    if let Some(rb) = physics.rigid_body_set.get_mut(handles.rigid_body_handle) {
+        rb.set_body_type(rigid_body_type(body.body_type), true);
+        rb.set_translation(planar([body.x, body.y]), true);
+        rb.set_rotation(planar_rotation(body.angle), true);
+        rb.set_linvel(planar([body.vx, body.vy]), true);
+        rb.set_angvel(Vector::zeros(), true);
+    }
+    physics.world.entity_mut(entity).insert((
+        Position2D { x: body.x, y: body.y },
+        Rotation(body.angle),
+        Scale(body.half_extent),
+        Groups(body.groups),
+    ));
+    physics.instance_layout_dirty = true;
+    true
+}
+
+/// Remove the body `entity_id`. Returns false for unknown ids.
+fn remove_body_internal(entity_id: u64) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return false;
+    };
+    let (Some(entity), Some(body)) = (entity_from_id(entity_id), body_of(physics, entity_id)) else {
+        return false;
+    };
+    despawn_body_in(physics, entity, body);
+    true
+}
+
+/// `set_scene_body_internal`, then the material if it differs
+fn set_scene_body_with_material(entity_id: u64, body: &SceneBody) -> bool {
+    let material = scene_body_internal(entity_id).map(|current| current.material_id);
+    if !set_scene_body_internal(entity_id, body) {
+        return false;
+    }
+    if material != Some(body.material_id) {
+        set_entity_material_internal(entity_id, body.material_id);
+    }
+    true
+}
+
+fn record_edit(edit: Edit<SceneBody>, merge: bool) {
+    if let Ok(mut history) = HISTORY.lock() {
+        history.record(edit, merge);
+    }
+}
+
+/// Spawn a box from `body` as a step that can be undone. Returns its id, or
+/// None before init.
+fn edit_spawn_body_internal(body: &SceneBody) -> Option<u64> {
+    let id = spawn_scene_body(body)?;
+    // Record the box as it came out, in case the material was turned down
+    let body = scene_body_internal(id).unwrap_or(*body);
+    record_edit(Edit::Spawn { entity: id, body }, false);
+    Some(id)
+}
+
+/// Remove the box `entity_id` as a step that can be undone. Returns false for
+/// unknown ids and other shapes.
+fn edit_remove_body_internal(entity_id: u64) -> bool {
+    let Some(body) = scene_body_internal(entity_id) else {
+        return false;
+    };
+    if !remove_body_internal(entity_id) {
+        return false;
+    }
+    record_edit(Edit::Remove { entity: entity_id, body }, false);
+    true
+}
+
+/// Make the box `entity_id` match `body`, material included, as a step that
+/// can be undone. With `merge` a change right after another change to the
+/// same box joins its step. Returns false for unknown ids and other shapes.
+fn edit_body_internal(entity_id: u64, body: &SceneBody, merge: bool) -> bool {
+    let Some(before) = scene_body_internal(entity_id) else {
+        return false;
+    };
+    if !set_scene_body_with_material(entity_id, body) {
+        return false;
+    }
+    let after = scene_body_internal(entity_id).unwrap_or(*body);
+    if after != before {
+        record_edit(Edit::Change { entity: entity_id, before, after }, merge);
+    }
+    true
+}
+
+/// `edit_spawn_body_internal` from a JSON `SceneBody`
+fn edit_spawn_body_json_internal(json: &str) -> Result<u64, String> {
+    let body = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    edit_spawn_body_internal(&body).ok_or_else(|| "simulation not running".into())
+}
+
+/// `edit_body_internal` from a JSON `SceneBody`
+fn edit_body_json_internal(entity_id: u64, json: &str, merge: bool) -> Result<(), String> {
+    let body = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if !edit_body_internal(entity_id, &body, merge) {
+        return Err(format!("entity {} is not a box or the body is invalid", entity_id));
+    }
+    Ok(())
+}
+
+/// `scene_body_internal` as JSON
+fn body_desc_json_internal(entity_id: u64) -> Option<String> {
+    scene_body_internal(entity_id).and_then(|body| serde_json::to_string(&body).ok())
+}
+
+/// `create_spring_to_point_internal` as a step that can be undone
+fn edit_attach_spring_internal(entity_id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> Option<u64> {
+    let spring = create_spring_to_point_internal(entity_id, world_x, world_y, stiffness, damping)?;
+    let desc = SpringEdit { entity: entity_id, point: [world_x, world_y], stiffness, damping };
+    record_edit(Edit::Spring { spring, desc }, false);
+    Some(spring)
+}
+
+/// Carry out `edit` again, or reverse it with `undo`. Returns the step with
+/// the ids of anything spawned again, None when what it refers to is gone.
+fn apply_edit(mut edit: Edit<SceneBody>, undo: bool) -> Option<Edit<SceneBody>> {
+    let spawning = matches!(edit, Edit::Spawn { .. }) != undo;
+    match &mut edit {
+        Edit::Spawn { entity, body } | Edit::Remove { entity, body } => {
+            if spawning {
+                let id = spawn_scene_body(body)?;
+                if let Ok(mut history) = HISTORY.lock() {
+                    history.rename_entity(*entity, id);
+                }
+                *entity = id;
+            } else {
+                if !remove_body_internal(*entity) {
+                    return None;
+                }
+                if let Ok(mut editor) = EDITOR.lock() {
+                    editor.forget(*entity);
+                }
+            }
+        }
+        Edit::Change { entity, before, after } => {
+            let body = if undo { &*before } else { &*after };
+            if !set_scene_body_with_material(*entity, body) {
+                return None;
+            }
+        }
+        Edit::Spring { spring, desc } => {
+            if undo {
+                if !remove_spring_internal(*spring) {
+                    return None;
+                }
+            } else {
+                let id = create_spring_to_point_internal(desc.entity, desc.point[0], desc.point[1], desc.stiffness, desc.damping)?;
+                if let Ok(mut history) = HISTORY.lock() {
+                    history.rename_spring(*spring, id);
+                }
+                *spring = id;
+            }
+        }
+    }
+    Some(edit)
+}
+
+/// Undo the last step, or redo the last undone one. Returns false when there
+/// is none or it no longer applies, in which case it is dropped.
+fn step_history(undo: bool) -> bool {
+    let edit = HISTORY.lock().ok().and_then(|mut history| if undo { history.take_undo() } else { history.take_redo() });
+    let Some(edit) = edit else {
+        return false;
+    };
+    let label = edit.label();
+    let Some(edit) = apply_edit(edit, undo) else {
+        log::warn!("{}: the {} step no longer applies", if undo { "undo" } else { "redo" }, label);
+        return false;
+    };
+    if let Ok(mut history) = HISTORY.lock() {
+        if undo {
+            history.undone(edit);
+        } else {
+            history.redone(edit);
+        }
+    }
+    true
+}
+
+fn undo_internal() -> bool {
+    step_history(true)
+}
+
+fn redo_internal() -> bool {
+    step_history(false)
+}
+
+/// Undo and redo presses from the editor window, run outside the frame's locks
+fn run_queued_history() {
+    let queued = EDITOR.lock().map(|mut editor| editor.take_queued_history()).unwrap_or_default();
+    for undo in queued {
+        step_history(undo);
+    }
+}
+
+fn clear_history_internal() {
+    if let Ok(mut history) = HISTORY.lock() {
+        history.clear();
+    }
+}
+
+/// `HistoryState` as JSON
+fn history_state_internal() -> String {
+    HISTORY.lock().map(|history| history.state().to_json()).unwrap_or_default()
+}
+
+fn editor_enabled() -> bool {
+    EDITOR.lock().is_ok_and(|editor| editor.is_enabled())
+}
+
+/// Turn the scene editor on or off; the simulation is paused while it is on
+fn set_editor_enabled_internal(enabled: bool) {
+    let paused = PHYSICS_STATE.lock().ok().and_then(|guard| guard.0.as_ref().map(|physics| physics.paused)).unwrap_or(false);
+    let Some(paused) = EDITOR.lock().ok().and_then(|mut editor| editor.set_enabled(enabled, paused)) else {
+        return;
+    };
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.paused = paused;
+        }
+    }
+    log::info!("Scene editor {}", if enabled { "on" } else { "off" });
+}
+
+/// The pointer is over an egui window or dragging one of its widgets
+fn egui_wants_pointer() -> bool {
+    let Ok(guard) = WGPU_STATE.lock() else {
+        return false;
+    };
+    guard.0.as_ref().and_then(|state| state.egui_renderer.as_ref()).is_some_and(|egui_rend| egui_rend.context().wants_pointer_input())
+}
+
+/// Scene editor pointer event at screen pixel (`x`, `y`): 0 = down, 1 =
+/// move, 2 = up. Returns false, leaving the event to the scene, while the
+/// editor is off.
+fn editor_pointer_internal(event_type: i32, x: f32, y: f32) -> bool {
+    if !editor_enabled() {
+        return false;
+    }
+    let Some(point) = screen_to_world(x, y) else {
+        return true;
+    };
+    match event_type {
+        0 if !egui_wants_pointer() => {
+            let hit = body_at_screen_internal(x, y).and_then(|id| {
+                let body = scene_body_internal(id)?;
+                Some((id, EditPose { x: body.x, y: body.y, half_extent: body.half_extent, angle: body.angle }))
+            });
+            let Some(mut editor) = EDITOR.lock().ok().map(|editor| editor.clone()) else {
+                return true;
+            };
+            if let Some(pose) = editor.press(point, hit) {
+                let body_type = if editor.place_fixed { SceneBodyType::Fixed } else { SceneBodyType::Dynamic };
+                let body = SceneBody { x: pose.x, y: pose.y, half_extent: pose.half_extent, body_type, ..Default::default() };
+                if let Some(id) = edit_spawn_body_internal(&body) {
+                    editor.placed(id, point, pose);
+                }
+            }
+            if let Ok(mut slot) = EDITOR.lock() {
+                *slot = editor;
+            }
+        }
+        1 => {
+            let dragged = EDITOR.lock().ok().and_then(|editor| editor.drag_to(point));
+            if let Some((id, pose)) = dragged {
+                if let Some(body) = scene_body_internal(id) {
+                    set_scene_body_internal(id, &SceneBody { x: pose.x, y: pose.y, half_extent: pose.half_extent, angle: pose.angle, ..body });
+                }
+            }
+        }
+        2 => {
+            let drag = EDITOR.lock().ok().and_then(|mut editor| {
+                let drag = editor.drag();
+                editor.release();
+                drag
+            });
+            // One step for the whole drag, from the pose it started at
+            if let Some(drag) = drag {
+                if let Some(after) = scene_body_internal(drag.entity) {
+                    let pose = drag.pose;
+                    let before = SceneBody { x: pose.x, y: pose.y, half_extent: pose.half_extent, angle: pose.angle, ..after };
+                    // A box placed and dragged into position is one step too
+                    if drag.placed || after != before {
+                        record_edit(Edit::Change { entity: drag.entity, before, after }, drag.placed);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    true
+}
+
+/// Write the running simulation's boxes to the `SceneDesc` JSON file at
+/// `path` (see `editor::scene_json`). Copies of prefabs the file defines are
+/// written as instances, parts left out; bodies of other prefabs are written
+/// as plain boxes. Returns the number of bodies and instances written.
+fn save_editor_scene_internal(path: &std::path::Path) -> Result<usize, String> {
+    let base = match std::fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    let base_scene = base.as_deref().and_then(|text| serde_json::from_str::<serde_json::Value>(text).ok());
+    let defined: std::collections::BTreeSet<String> = base_scene
+        .as_ref()
+        .and_then(|scene| Some(scene.get("prefabs")?.as_object()?.keys().cloned().collect()))
+        .unwrap_or_default();
+    // Textures the file names go back to the ids the file gives them
+    let textures: std::collections::BTreeMap<u32, String> = base_scene
+        .and_then(|mut scene| serde_json::from_value(scene.get_mut("textures")?.take()).ok())
+        .unwrap_or_default();
+    let scene_ids: std::collections::HashMap<u32, u32> = match TEXTURE_ASSETS.lock() {
+        Ok(loaded) => textures.iter().filter_map(|(&scene_id, name)| Some((*loaded.get(name)?, scene_id))).collect(),
+        Err(_) => Default::default(),
+    };
+    let (mut bodies, instances, gravity) = {
+        let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+        let physics = guard.0.as_mut().ok_or("simulation not running")?;
+        let mut entities: Vec<Entity> = physics.world.query_filtered::<Entity, With<PhysicsBody>>().iter(&physics.world).collect();
+        // Same order on every save, so saved files diff cleanly
+        entities.sort_by_key(|entity| entity.index());
+        let defined_root = |entity: Entity| match physics.world.get::<PrefabBody>(entity) {
+            Some(PrefabBody::Root(name)) if defined.contains(name) => Some(name.clone()),
+            _ => None,
+        };
+        let (mut bodies, mut instances) = (Vec::new(), Vec::new());
+        for entity in entities {
+            match physics.world.get::<PrefabBody>(entity) {
+                Some(PrefabBody::Part { root }) if defined_root(*root).is_some() => {}
+                _ => match defined_root(entity) {
+                    Some(prefab) => {
+                        let rb = physics.world.get::<PhysicsBody>(entity).and_then(|body| physics.rigid_body_set.get(body.rigid_body_handle));
+                        if let Some(rb) = rb {
+                            let [x, y] = xy(rb.translation());
+                            instances.push(PrefabInstance { prefab, x, y, angle: to_f32(rb.rotation().euler_angles().2) });
+                        }
+                    }
+                    None => bodies.extend(scene_body_of(physics, entity)),
+                },
+            }
+        }
+        (bodies, instances, [to_f32(physics.gravity.x), to_f32(physics.gravity.y)])
+    };
+    for body in &mut bodies {
+        body.material_id = scene_ids.get(&body.material_id).copied().unwrap_or(body.material_id);
+    }
+    let bodies_json = serde_json::to_value(&bodies).map_err(|e| e.to_string())?;
+    let instances_json = serde_json::to_value(&instances).map_err(|e| e.to_string())?;
+    let json = editor::scene_json(base.as_deref(), bodies_json, instances_json, gravity).map_err(|e| format!("{}: {}", path.display(), e))?;
+    // Never write a file the scene loader would turn down
+    let desc: SceneDesc = serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    desc.validate().map_err(|e| format!("{}: {}", path.display(), e))?;
+    std::fs::write(path, json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(bodies.len() + instances.len())
+}
+
+/// Register `desc` as prefab `name`, replacing any prefab of that name
+fn register_prefab_internal(name: &str, desc: PrefabDesc) -> Result<(), String> {
+    PREFABS.lock().map_err(|e| e.to_string())?.insert(name, desc)
+}
+
+/// `register_prefab_internal` from a JSON `PrefabDesc`
+fn register_prefab_json_internal(name: &str, json: &str) -> Result<(), String> {
+    let desc = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    register_prefab_internal(name, desc)
+}
+
+fn remove_prefab_internal(name: &str) -> bool {
+    PREFABS.lock().map(|mut prefabs| prefabs.remove(name)).unwrap_or(false)
+}
+
+/// Spawn prefab `name` with all its parts at (x, y), turned by `angle`
+/// radians. Returns the id of the body the prefab itself spawned as; when
+/// any body cannot be made, the ones already spawned are removed again.
+fn spawn_prefab_internal(name: &str, x: f32, y: f32, angle: f32) -> Result<u64, String> {
+    validation::finite("prefab position", &[x, y, angle]).map_err(validation::reject)?;
+    let spawns = PREFABS.lock().map_err(|e| e.to_string())?.expand(name, Pose2D { translation: [x, y], rotation: angle })?;
+    let mut ids = Vec::with_capacity(spawns.len());
+    let spawned = spawns.iter().try_for_each(|spawn| {
+        let [x, y] = spawn.pose.translation;
+        let body_type = rigid_body_type(spawn.desc.body_type);
+        let id = match &spawn.desc.shape {
+            PrefabShape::Box { half_extent } => {
+                spawn_body_with_internal(x, y, *half_extent, body_type, [0.0, 0.0], spawn.desc.groups).ok_or("simulation not running")?
+            }
+            PrefabShape::Polygon { points } => spawn_polygon_internal(x, y, points, body_type, spawn.desc.groups)?,
+        };
+        ids.push(id);
+        set_up_prefab_body(name, spawn, &ids)
+    });
+    if let Err(e) = spawned {
+        for &id in &ids {
+            remove_body_internal(id);
+        }
+        return Err(format!("prefab '{}': {}", name, e));
+    }
+    Ok(ids[0])
+}
+
+/// Give the body just spawned for `spawn`, the last of `ids`, everything
+/// else its prefab describes
+fn set_up_prefab_body(name: &str, spawn: &PrefabSpawn, ids: &[u64]) -> Result<(), String> {
+    let (id, desc) = (ids[ids.len() - 1], &spawn.desc);
+    if spawn.pose.rotation != 0.0 {
+        with_rigid_body_mut(id, |rb| rb.set_rotation(planar_rotation(spawn.pose.rotation), true));
+    }
+    if desc.material_id != material::DEFAULT_MATERIAL && !set_entity_material_internal(id, desc.material_id) {
+        return Err(format!("unknown material {}", desc.material_id));
+    }
+    if let Some(movement) = desc.movement {
+        set_movement_internal(id, movement)?;
+    }
+    if let Some(machine) = &desc.state_machine {
+        set_state_machine_internal(id, Some(machine.clone()))?;
+    }
+    if let Some((parent, offset)) = spawn.parent {
+        let [offset_x, offset_y] = offset.translation;
+        if !attach_entity_internal(id, ids[parent], offset_x, offset_y, offset.rotation) {
+            return Err("a part could not be attached".into());
+        }
+    }
+    let (Some(entity), Some(root)) = (entity_from_id(id), entity_from_id(ids[0])) else {
+        return Err(format!("unknown entity {}", id));
+    };
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    let mut entity_mut = physics.world.entity_mut(entity);
+    if let Some(clip) = desc.clip {
+        state_machine::play_clip(&mut entity_mut, clip);
+    }
+    entity_mut.insert(if spawn.parent.is_none() { PrefabBody::Root(name.to_string()) } else { PrefabBody::Part { root } });
+    Ok(())
+}
+
+/// Switch to the scene registered after the current one (the first scene
+/// when none has been loaded). Returns its name, or None after the last one.
+fn load_next_scene_internal(fade_seconds: f32) -> Result<Option<String>, String> {
+    let next = SCENES.lock().map_err(|e| e.to_string())?.next().map(str::to_string);
+    match next {
+        Some(name) => switch_scene_internal(&name, fade_seconds).map(|_| Some(name)),
+        None => Ok(None),
+    }
+}
+
+static GESTURES: Lazy<Mutex<GestureRecognizer>> = Lazy::new(|| Mutex::new(GestureRecognizer::default()));
+
+/// Feed one touch pointer event (0 = down, 1 = move, 2 = up, 3 = cancel) in
+/// screen pixels, with the event time in seconds, and act on any gestures
+fn on_touch_event_internal(action: i32, pointer_id: i32, x: f32, y: f32, time: f64) {
+    if !validation::accept(validation::finite("touch position", &[x, y])) {
+        return;
+    }
+    if !time.is_finite() {
+        validation::reject(InputError::new(validation::ERROR_NOT_FINITE, format!("touch time must be finite, got {}", time)));
+        return;
+    }
+    let (gestures, config) = match GESTURES.lock() {
+        Ok(mut recognizer) => {
+            let gestures = match action {
+                0 => recognizer.touch_down(pointer_id, x, y, time),
+                1 => recognizer.touch_move(pointer_id, x, y, time),
+                2 => recognizer.touch_up(pointer_id, x, y, time),
+                3 => recognizer.cancel(),
+                _ => Vec::new(),
+            };
+            (gestures, recognizer.config())
+        }
+        Err(_) => return,
+    };
+    for gesture in gestures {
+        apply_gesture(gesture, &config);
+    }
+}
+
+/// Pinch and pan move the camera directly; tap and fling are converted to
+/// world coordinates and queued as `GameEvent`s for the simulation
+fn apply_gesture(gesture: Gesture, config: &GestureConfig) {
+    // The 3D demo's camera takes pinches and ignores the 2D gestures
+    if update_orbit(|controls| match gesture {
+        Gesture::Pinch { factor, .. } if config.pinch_zoom => {
+            controls.camera.zoom(factor);
+            true
+        }
+        _ => false,
+    }) {
+        return;
+    }
+    // The slingshot owns drags on bodies while it is on
+    let slingshot_enabled = SLINGSHOT.lock().is_ok_and(|slingshot| slingshot.enabled());
+    let mut guard = match WGPU_STATE.lock() {
+        Ok(g) => g,
+        Err(_) => return,
+    };
+    let state = match guard.0.as_mut() {
+        Some(s) => s,
+        None => return,
+    };
+    let world_per_pixel = state.camera.world_per_pixel(state.world_rect()[3]);
+
+    let event = match gesture {
+        Gesture::Pinch { factor, .. } if config.pinch_zoom => {
+            state.camera.zoom(factor);
+            write_camera_uniform(state);
+            None
+        }
+        Gesture::Pan { dx, dy } if config.pan => {
+            // Content follows the fingers, so the camera moves the other way
+            state.camera.pan(-dx * world_per_pixel, dy * world_per_pixel);
+            write_camera_uniform(state);
+            None
+        }
+        Gesture::Tap { x, y } if config.tap_spawn => {
+            let (wx, wy) = state.screen_to_world(x, y);
+            Some(GameEvent::new_gesture(InputEventType::Tap, wx, wy, [0.0, 0.0]))
+        }
+        Gesture::DragStart { x, y } if config.fling && !slingshot_enabled => {
+            let (wx, wy) = state.screen_to_world(x, y);
+            Some(GameEvent::new_gesture(InputEventType::DragStart, wx, wy, [0.0, 0.0]))
+        }
+        Gesture::DragEnd { x, y, vx, vy } if config.fling && !slingshot_enabled => {
+            let (wx, wy) = state.screen_to_world(x, y);
+            let scale = world_per_pixel * config.fling_scale;
+            // Screen y grows downwards
+            Some(GameEvent::new_gesture(InputEventType::Fling, wx, wy, [vx * scale, -vy * scale]))
+        }
+        _ => None,
+    };
+    drop(guard);
+
+    if let Some(event) = event {
+        if let Ok(mut input) = INPUT_STATE.lock() {
+            input.events.push(event);
+        }
+    }
+}
+
+fn safe_area() -> SafeArea {
+    SAFE_AREA.lock().map(|area| *area).unwrap_or_default()
+}
+
+/// Insets in surface pixels per `safe_area`; with `inset_world` the camera
+/// viewport moves inside them too. False for negative or non-finite insets.
+fn set_safe_area_insets_internal(left: f32, top: f32, right: f32, bottom: f32, inset_world: bool) -> bool {
+    let Some(area) = SafeArea::new(left, top, right, bottom, inset_world) else {
+        validation::reject(InputError::new(validation::ERROR_OUT_OF_RANGE, format!("invalid safe area insets {} {} {} {}", left, top, right, bottom)));
+        return false;
+    };
+    if let Ok(mut current) = SAFE_AREA.lock() {
+        *current = area;
+    }
+    if let Ok(mut guard) = WGPU_STATE.lock() {
+        if let Some(state) = guard.0.as_mut() {
+            fit_camera_to_world_rect(state);
+        }
+    }
+    true
+}
+
+fn scale_factor() -> f32 {
+    SCALE_FACTOR.lock().map(|scale| *scale).unwrap_or(1.0)
+}
+
+/// Surface pixels per logical point, used for the egui scale, the web
+/// pointer positions (CSS pixels), the tap slop and the 3D orbit rate.
+/// False unless finite and above 0.
+fn set_scale_factor_internal(scale_factor: f32) -> bool {
+    if !validation::accept(validation::size("scale factor", scale_factor)) {
+        return false;
+    }
+    if let Ok(mut current) = SCALE_FACTOR.lock() {
+        *current = scale_factor;
+    }
+    if let Ok(mut recognizer) = GESTURES.lock() {
+        recognizer.set_scale_factor(scale_factor);
+    }
+    true
+}
+
+/// Match the camera's aspect ratio to the rectangle it is drawn into
+fn fit_camera_to_world_rect(state: &mut WgpuState) {
+    let [_, _, width, height] = state.world_rect();
+    state.camera.aspect = width as f32 / height.max(1) as f32;
+    write_camera_uniform(state);
+}
+
+fn write_camera_uniform(state: &mut WgpuState) {
+    state.camera_uniform.update_view_proj_with(&state.camera, &state.camera_offset);
+    state.queue.write_buffer(&state.camera_buffer, 0, bytemuck::cast_slice(&[state.camera_uniform]));
+}
+
+fn world_scale() -> WorldScale {
+    WORLD_SCALE.lock().map(|scale| *scale).unwrap_or_default()
+}
+
+/// Show `pixels_per_meter` on the main surface at its current size. False
+/// without a surface, since pixels mean nothing before one exists.
+fn set_world_scale_internal(pixels_per_meter: f32) -> bool {
+    if !validation::accept(validation::size("pixels per meter", pixels_per_meter)) {
+        return false;
+    }
+    let Ok(mut guard) = WGPU_STATE.lock() else {
+        return false;
+    };
+    let Some(state) = guard.0.as_mut() else {
+        log::warn!("set_world_scale: no surface to measure pixels on");
+        return false;
+    };
+    let Some(scale) = WorldScale::from_pixels_per_meter(pixels_per_meter, state.config.height) else {
+        return false;
+    };
+    use_world_scale(scale, Some(state));
+    true
+}
+
+/// Show `ndc_per_meter` normalized device units per meter; works before
+/// the surface exists
+fn set_world_scale_ndc_internal(ndc_per_meter: f32) -> bool {
+    if !validation::accept(validation::size("NDC per meter", ndc_per_meter)) {
+        return false;
+    }
+    let Some(scale) = WorldScale::new(ndc_per_meter) else {
+        return false;
+    };
+    match WGPU_STATE.lock() {
+        Ok(mut guard) => use_world_scale(scale, guard.0.as_mut()),
+        Err(_) => use_world_scale(scale, None),
+    }
+    true
+}
+
+/// Store `scale` and reframe the camera for it. Walls and default sizes
+/// follow when the scene is next reset or loaded.
+fn use_world_scale(scale: WorldScale, state: Option<&mut WgpuState>) {
+    if let Ok(mut current) = WORLD_SCALE.lock() {
+        *current = scale;
+    }
+    if let Some(state) = state {
+        state.camera.set_world_scale(&scale);
+        write_camera_uniform(state);
+    }
+    log::info!("World scale: {} NDC per meter", scale.ndc_per_meter());
+}
+
+/// Move the camera to the values its tweens reached this step
+fn apply_camera_tween(values: CameraTweenValues) {
+    let Ok(mut guard) = WGPU_STATE.lock() else {
+        return;
+    };
+    let Some(state) = guard.0.as_mut() else {
+        return;
+    };
+    if let Some(height) = values.zoom {
+        state.camera.set_view_height(height);
+    }
+    if let Some([x, y]) = values.position {
+        state.camera.look_at(x, y);
+    }
+    write_camera_uniform(state);
+}
+
+/// Ease `property` of the entity (ignored for camera properties) from
+/// `from` to `to` over `duration` simulated seconds, replacing any tween
+/// already running on it. Returns the tween id, or None for unknown
+/// entities, wrong value counts or when too many tweens are running.
+fn animate_property_internal(
+    entity_id: u64,
+    property: TweenProperty,
+    from: &[f32],
+    to: &[f32],
+    duration: f32,
+    easing: Easing,
+) -> Option<u64> {
+    start_tween(entity_id, property, from, to, duration, 0.0, easing)
+}
+
+fn start_tween(
+    entity_id: u64,
+    property: TweenProperty,
+    from: &[f32],
+    to: &[f32],
+    duration: f32,
+    delay: f32,
+    easing: Easing,
+) -> Option<u64> {
+    let target = if property.is_camera() { 0 } else { entity_id };
+    let tween = match Tween::new(target, property, from, to, duration, delay, easing) {
+        Ok(tween) => tween,
+        Err(e) => {
+            log::warn!("animate_property: {}", e);
+            return None;
+        }
+    };
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_mut()?;
+    if !property.is_camera() && !entity_from_id(entity_id).is_some_and(|e| physics.world.entities().contains(e)) {
+        return None;
+    }
+    physics.world.resource_mut::<Tweens>().start(tween)
+}
+
+/// Stop a tween where it is; false for unknown or finished ones
+fn cancel_tween_internal(tween_id: u64) -> bool {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return false;
+    };
+    guard.0.as_mut().is_some_and(|physics| physics.world.resource_mut::<Tweens>().cancel(tween_id))
+}
+
+/// Give the entity a state machine built from `desc`, replacing any it has.
+/// None removes the machine; the entity keeps the movement, clip and
+/// material of the state it was in.
+fn set_state_machine_internal(entity_id: u64, desc: Option<StateMachineDesc>) -> Result<(), String> {
+    let machine = desc.map(StateMachine::new).transpose()?;
+
+    // Check materials before touching physics so we never hold both locks.
+    // Without a renderer there is nothing to check them against.
+    let materials: Vec<u32> = machine.iter().flat_map(|m| m.states()).filter_map(|s| s.material_id).collect();
+    if !materials.is_empty() {
+        if let Ok(guard) = WGPU_STATE.lock() {
+            if let Some(state) = guard.0.as_ref() {
+                if let Some(id) = materials.iter().find(|&&id| !state.materials.contains(id)) {
+                    return Err(format!("unknown material {}", id));
+                }
+            }
+        }
+    }
+
+    let entity = entity_from_id(entity_id).ok_or_else(|| format!("unknown entity {}", entity_id))?;
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    if known_entity(physics, entity_id).is_none() {
+        return Err(format!("unknown entity {}", entity_id));
+    }
+    let mut entity_mut = physics.world.entity_mut(entity);
+    match machine {
+        Some(machine) => entity_mut.insert(machine),
+        None => entity_mut.remove::<StateMachine>(),
+    };
+    Ok(())
+}
+
+/// `set_state_machine_internal` from a JSON `StateMachineDesc`
+fn set_state_machine_json_internal(entity_id: u64, json: &str) -> Result<(), String> {
+    let desc = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    set_state_machine_internal(entity_id, Some(desc))
+}
+
+/// Queue `event` for the entity's state machine, or for every machine when
+/// `entity_id` is 0. Returns how many machines received it.
+fn send_state_event_internal(entity_id: u64, event: &str) -> usize {
+    let Ok(mut guard) = PHYSICS_STATE.lock() else {
+        return 0;
+    };
+    let Some(physics) = guard.0.as_mut() else {
+        return 0;
+    };
+    if entity_id != 0 {
+        let Some(entity) = entity_from_id(entity_id).filter(|&e| physics.world.entities().contains(e)) else {
+            return 0;
+        };
+        return match physics.world.get_mut::<StateMachine>(entity) {
+            Some(mut machine) => {
+                machine.send(event);
+                1
+            }
+            None => 0,
+        };
+    }
+    let mut sent = 0;
+    for mut machine in physics.world.query::<&mut StateMachine>().iter_mut(&mut physics.world) {
+        machine.send(event);
+        sent += 1;
+    }
+    sent
+}
+
+/// Index and name of the state the entity's machine is in
+fn entity_state_internal(entity_id: u64) -> Option<(usize, String)> {
+    let entity = entity_from_id(entity_id)?;
+    let guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_ref()?;
+    if known_entity(physics, entity_id).is_none() {
+        return None;
+    }
+    let machine = physics.world.get::<StateMachine>(entity)?;
+    Some((machine.current(), machine.current_name().to_string()))
+}
+
+/// Drive the body with `desc`, starting from where it is now; the `none`
+/// movement hands it back to physics
+fn set_movement_internal(entity_id: u64, desc: MovementDesc) -> Result<(), String> {
+    desc.validate()?;
+    let entity = entity_from_id(entity_id).ok_or_else(|| format!("unknown entity {}", entity_id))?;
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    if known_entity(physics, entity_id).is_none() {
+        return Err(format!("unknown entity {}", entity_id));
+    }
+    let body = physics.world.get::<PhysicsBody>(entity).ok_or_else(|| format!("entity {} has no body", entity_id))?;
+    let origin = physics
+        .rigid_body_set
+        .get(body.rigid_body_handle)
+        .map_or((0.0, 0.0), |rb| (to_f32(rb.translation().x), to_f32(rb.translation().y)));
+    let started_at = physics.world.resource::<time_control::Time>().elapsed;
+    let mut entity_mut = physics.world.entity_mut(entity);
+    match desc.strategy() {
+        Some(strategy) => entity_mut.insert(MovementComponent { strategy, origin, started_at }),
+        None => entity_mut.remove::<MovementComponent>(),
+    };
+    Ok(())
+}
+
+/// `set_movement_internal` from a JSON `MovementDesc`
+fn set_movement_json_internal(entity_id: u64, json: &str) -> Result<(), String> {
+    let desc = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    set_movement_internal(entity_id, desc)
+}
+
+fn set_gestures_internal(tap_spawn: bool, fling: bool, pinch_zoom: bool, pan: bool) {
+    if let Ok(mut recognizer) = GESTURES.lock() {
+        let config = GestureConfig {
+            tap_spawn,
+            fling,
+            pinch_zoom,
+            pan,
+            ..recognizer.config()
+        };
+        recognizer.set_config(config);
+    }
+}
+
+static SLINGSHOT: Lazy<Mutex<Slingshot>> = Lazy::new(|| Mutex::new(Slingshot::default()));
+
+/// Turn drag-to-launch on or off; off drops any aim and its arc
+fn enable_slingshot_mode_internal(enabled: bool) {
+    let cancelled = SLINGSHOT.lock().ok().and_then(|mut slingshot| {
+        slingshot.set_enabled(enabled);
+        slingshot.cancel()
+    });
+    if cancelled.is_some() {
+        clear_trajectory_preview_internal();
+    }
+}
+
+/// Impulse of a full pull (N·s), the pull length (m) that reaches it and the
+/// predicted steps drawn while aiming (0 hides the arc). Returns false unless
+/// the impulse and pull are finite and > 0.
+fn set_slingshot_params_internal(max_impulse: f32, max_pull: f32, arc_samples: u32) -> bool {
+    let config = SlingshotConfig { max_impulse, max_pull, arc_samples: arc_samples.min(trajectory::MAX_PREDICTION_STEPS) };
+    let Some(config) = config.validated() else {
+        return false;
+    };
+    SLINGSHOT.lock().map(|mut slingshot| slingshot.set_config(config)).is_ok()
+}
+
+/// Pointer event (0 = down, 1 = move, 2 = up) at screen pixel (x, y) for the
+/// slingshot: down grabs the dynamic body under the pointer, moves redraw the
+/// predicted arc and up launches
+fn slingshot_pointer_internal(event_type: i32, x: f32, y: f32) {
+    if !validation::accept(validation::finite("pointer position", &[x, y])) {
+        return;
+    }
+    if !SLINGSHOT.lock().is_ok_and(|slingshot| slingshot.enabled()) {
+        return;
+    }
+    let Some(point) = screen_to_world(x, y) else {
+        return;
+    };
+    match event_type {
+        0 => {
+            if let Some((entity_id, anchor)) = launchable_body_at(point) {
+                if let Ok(mut slingshot) = SLINGSHOT.lock() {
+                    slingshot.press(entity_id, anchor);
+                }
+            }
+        }
+        1 => {
+            let Some((aim, config)) = SLINGSHOT.lock().ok().and_then(|mut s| s.drag(point).map(|aim| (aim, s.config()))) else {
+                return;
+            };
+            if config.arc_samples == 0 {
+                return;
+            }
+            // The arc starts from the body's current motion plus the launch
+            let velocity = with_rigid_body_mut(aim.entity, |rb| {
+                let mass = to_f32(rb.mass()).max(f32::EPSILON);
+                let [vx, vy] = xy(rb.linvel());
+                [vx + aim.impulse[0] / mass, vy + aim.impulse[1] / mass]
+            });
+            if let Some([vx, vy]) = velocity {
+                predict_trajectory_internal(aim.entity, vx, vy, config.arc_samples, true);
+            }
+        }
+        2 => {
+            let aim = SLINGSHOT.lock().ok().and_then(|mut slingshot| slingshot.release(point));
+            if let Some(aim) = aim {
+                apply_impulse_internal(aim.entity, aim.impulse[0], aim.impulse[1]);
+                clear_trajectory_preview_internal();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Screen pixel to world point with the current camera
+fn screen_to_world(x: f32, y: f32) -> Option<[f32; 2]> {
+    let guard = WGPU_STATE.lock().ok()?;
+    let state = guard.0.as_ref()?;
+    let (wx, wy) = state.screen_to_world(x, y);
+    Some([wx, wy])
+}
+
+/// Id of the body of any kind under screen pixel (`x`, `y`)
+fn body_at_screen_internal(x: f32, y: f32) -> Option<u64> {
+    let point = screen_to_world(x, y)?;
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_mut()?;
+    schedule::body_at(&mut physics.world, &physics.rigid_body_set, point[0], point[1]).map(Entity::to_bits)
+}
+
+/// Id and position of the dynamic body under world point `point`
+fn launchable_body_at(point: [f32; 2]) -> Option<(u64, [f32; 2])> {
+    let mut guard = PHYSICS_STATE.lock().ok()?;
+    let physics = guard.0.as_mut()?;
+    let entity = schedule::body_at(&mut physics.world, &physics.rigid_body_set, point[0], point[1])?;
+    let handle = physics.world.get::<PhysicsBody>(entity)?.rigid_body_handle;
+    let rb = physics.rigid_body_set.get(handle).filter(|rb| rb.is_dynamic())?;
+    Some((entity.to_bits(), xy(rb.translation())))
+}
+
+/// Gravity straight down with magnitude `y`; only the y component changes
+fn set_gravity_internal(y: f32) {
+    if !validation::accept(validation::finite("gravity", &[y])) {
+        return;
+    }
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.gravity.y = -to_real(y);
+        }
+    }
+}
+
+/// Set the time scale at once, ending any ramp
+fn set_time_scale_internal(scale: f32) {
+    if !validation::accept(validation::non_negative("time scale", scale)) {
+        return;
+    }
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.time_scale = scale;
+            physics.time_ramp = None;
+        }
+    }
+}
+
+fn set_gravity_vector_internal(x: f32, y: f32, z: f32) {
+    if !validation::accept(validation::finite("gravity", &[x, y, z])) {
+        return;
+    }
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            set_gravity_in(physics, vector![x, y, z].map(to_real));
+        }
+    }
+}
+
+fn set_gravity_in(physics: &mut PhysicsState, gravity: Vector<Real>) {
+    physics.gravity = gravity;
+    // Sleeping bodies would otherwise ignore the new direction
+    for (_, body) in physics.rigid_body_set.iter_mut() {
+        body.wake_up(true);
+    }
+}
+
+/// Set gravity to a preset: 0 = Earth, 1 = Moon, 2 = zero-g. False for
+/// other codes.
+fn set_gravity_preset_internal(preset: i32) -> bool {
+    let Some(preset) = GravityPreset::from_i32(preset) else {
+        validation::reject(InputError::new(validation::ERROR_OUT_OF_RANGE, format!("no gravity preset {}", preset)));
+        return false;
+    };
+    let [x, y, z] = preset.gravity();
+    set_gravity_vector_internal(x, y, z);
+    true
+}
+
+/// `None` while gravity is set by hand; `Some` while it follows the accelerometer
+static TILT: Lazy<Mutex<Option<TiltGravity>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start or stop driving gravity from accelerometer samples. `strength`
+/// scales the measured gravity; stopping leaves the last tilt in place.
+fn set_tilt_gravity_internal(enabled: bool, strength: f32) {
+    if let Ok(mut tilt) = TILT.lock() {
+        *tilt = enabled.then(|| TiltGravity::new(strength, TiltGravity::default().smoothing));
+    }
+}
+
+/// Feed one accelerometer sample (device axes, m/s², gravity reaction
+/// included) with the current display rotation in degrees
+fn on_accelerometer_internal(x: f32, y: f32, z: f32, rotation_degrees: i32) {
+    let gravity = match TILT.lock() {
+        Ok(mut tilt) => match tilt.as_mut() {
+            Some(tilt) => tilt.update([x, y, z], rotation_degrees),
+            None => return,
+        },
+        Err(_) => return,
+    };
+    let [gx, gy, gz] = gravity;
+    set_gravity_vector_internal(gx, gy, gz);
+}
+
+/// The 3D demo while it runs: its orbit controls and the 2D camera to go
+/// back to
+struct Demo3D {
+    controls: OrbitControls,
+    camera_2d: Camera,
+}
+
+/// Ids of the 3D demo's meshes in the 3D pass
+#[derive(Clone, Copy)]
+struct Demo3DModels {
+    ground: u32,
+    cube: u32,
+    sphere: u32,
+}
+
+/// The running 3D demo, and its models once loaded; they stay loaded after
+/// the demo stops
+static DEMO_3D: Lazy<Mutex<(Option<Demo3D>, Option<Demo3DModels>)>> = Lazy::new(|| Mutex::new((None, None)));
+
+/// Start or stop the 3D demo (see `demo_3d`). Starting replaces the
+/// simulation with `body_count` boxes and spheres over a ground plane and
+/// turns the camera into an orbiting perspective one; stopping restores the
+/// 2D camera and the default scene. Returns the number of bodies dropped.
+fn set_3d_mode_internal(enabled: bool, body_count: u32) -> Result<usize, String> {
+    let mut demo = DEMO_3D.lock().map_err(|e| e.to_string())?;
+    if !enabled {
+        let Some(running) = demo.0.take() else {
+            return Ok(0);
+        };
+        if let Ok(mut guard) = WGPU_STATE.lock() {
+            if let Some(state) = guard.0.as_mut() {
+                state.camera = running.camera_2d;
+                write_camera_uniform(state);
+            }
+        }
+        drop(demo);
+        init_physics();
+        return Ok(0);
+    }
+
+    let models = {
+        let mut guard = WGPU_STATE.lock().map_err(|e| e.to_string())?;
+        let state = guard.0.as_mut().ok_or("renderer not running")?;
+        let bevy_3d = state.bevy_3d_sample.as_mut().ok_or("3D pass not available")?;
+        let models = match demo.1 {
+            Some(models) if bevy_3d.has_model(models.ground) => models,
+            _ => Demo3DModels {
+                ground: bevy_3d.load_model(&state.device, demo_3d::cuboid_mesh([1.0, demo_3d::GROUND_FLATNESS, 1.0], demo_3d::GROUND_COLOR)),
+                cube: bevy_3d.load_model(&state.device, demo_3d::cuboid_mesh([1.0; 3], demo_3d::BOX_COLOR)),
+                sphere: bevy_3d.load_model(&state.device, demo_3d::sphere_mesh(12, 24, demo_3d::SPHERE_COLOR)),
+            },
+        };
+        let running = Demo3D {
+            controls: OrbitControls::default(),
+            camera_2d: demo.0.as_ref().map_or(state.camera, |running| running.camera_2d),
+        };
+        look_from_orbit(state, &running.controls.camera);
+        *demo = (Some(running), Some(models));
+        models
+    };
+
+    let bodies = demo_3d::generate(body_count);
+    log::info!("Starting the 3D demo with {} bodies", bodies.len());
+    init_physics_scene(&[], 0.05);
+    let mut guard = PHYSICS_STATE.lock().map_err(|e| e.to_string())?;
+    let physics = guard.0.as_mut().ok_or("simulation not running")?;
+    for handle in std::mem::take(&mut physics.walls) {
+        physics.rigid_body_set.remove(
+            handle,
+            &mut physics.island_manager,
+            &mut physics.collider_set,
+            &mut physics.impulse_joint_set,
+            &mut physics.multibody_joint_set,
+            true,
+        );
+    }
+
+    let ground = demo_3d::GROUND_HALF_EXTENT;
+    let thickness = ground * demo_3d::GROUND_FLATNESS;
+    let ground_body = RigidBodyBuilder::fixed().translation(vector![0.0, to_real(demo_3d::GROUND_Y - thickness), 0.0]);
+    let ground_collider = ColliderBuilder::cuboid(to_real(ground), to_real(thickness), to_real(ground));
+    spawn_body_3d(physics, ground_body, ground_collider, ground, models.ground);
+
+    let half_extent = demo_3d::BODY_HALF_EXTENT;
+    for body in &bodies {
+        let ([x, y, z], [rx, ry, rz]) = (body.position, body.rotation);
+        let rigid_body = RigidBodyBuilder::dynamic()
+            .translation(vector![x, y, z].map(to_real))
+            .rotation(vector![rx, ry, rz].map(to_real))
+            .ccd_enabled(true);
+        let extent = to_real(half_extent);
+        let (collider, model_id) = match body.shape {
+            demo_3d::DemoShape::Box => (ColliderBuilder::cuboid(extent, extent, extent), models.cube),
+            demo_3d::DemoShape::Sphere => (ColliderBuilder::ball(extent), models.sphere),
+        };
+        spawn_body_3d(physics, rigid_body, collider, half_extent, model_id);
+    }
+    Ok(bodies.len())
+}
+
+/// Add a body free to move in 3D, drawn in the 3D pass with `model_id` at
+/// `half_extent`; it has no 2D position, so the 2D passes skip it
+fn spawn_body_3d(physics: &mut PhysicsState, rigid_body: RigidBodyBuilder, collider: ColliderBuilder, half_extent: f32, model_id: u32) -> Entity {
+    let rb_handle = physics.rigid_body_set.insert(rigid_body.build());
+    let collider = collider.restitution(0.3).active_events(ActiveEvents::COLLISION_EVENTS).build();
+    let coll_handle = physics.collider_set.insert_with_parent(collider, rb_handle, &mut physics.rigid_body_set);
+    let body = PhysicsBody {
+        rigid_body_handle: rb_handle,
+        collider_handle: coll_handle,
+    };
+    let entity = physics
+        .world
+        .spawn((body, Scale(half_extent), Groups::default(), ModelComponent { model_id, scale: 1.0 }))
+        .id();
+    physics.collider_set[coll_handle].user_data = entity.to_bits() as u128;
+    entity
+}
+
+/// Point the camera from the orbit's eye at its target
+fn look_from_orbit(state: &mut WgpuState, orbit: &OrbitCamera) {
+    let ([ex, ey, ez], [tx, ty, tz]) = (orbit.eye(), orbit.target);
+    state.camera.eye = na::Point3::new(ex, ey, ez);
+    state.camera.target = na::Point3::new(tx, ty, tz);
+    state.camera.up = na::Vector3::y();
+    state.camera.is_orthographic = false;
+    state.camera.fovy = 45.0;
+    write_camera_uniform(state);
+}
+
+/// Orbit the 3D demo's camera by a drag of `dx`, `dy` pixels and move it
+/// closer by `zoom` (> 1 zooms in, 1 keeps the distance). Returns false
+/// while the demo is not running.
+fn orbit_3d_camera_internal(dx: f32, dy: f32, zoom: f32) -> bool {
+    let scale = scale_factor();
+    update_orbit(|controls| {
+        controls.camera.orbit(dx / scale, dy / scale);
+        controls.camera.zoom(zoom);
+        true
+    })
+}
+
+/// Change the running 3D demo's controls with `change` and, if it reports
+/// a move, follow with the camera. Returns false while the demo is not
+/// running.
+fn update_orbit(change: impl FnOnce(&mut OrbitControls) -> bool) -> bool {
+    let Ok(mut demo) = DEMO_3D.lock() else {
+        return false;
+    };
+    let Some(running) = demo.0.as_mut() else {
+        return false;
+    };
+    if change(&mut running.controls) {
+        if let Ok(mut guard) = WGPU_STATE.lock() {
+            if let Some(state) = guard.0.as_mut() {
+                look_from_orbit(state, &running.controls.camera);
+            }
+        }
+    }
+    true
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn update_physics_internal(state: *mut WgpuState, _dt: f32) {
+    let _state = unsafe { &mut *state };
+    // Update physics here
+}
+
+/// The platform logger behind the log console's capture (see `log_console`).
+/// Does nothing after the first call, or if the host installed a logger.
+fn init_logging() {
+    use std::sync::Once;
+    static START: Once = Once::new();
+    START.call_once(|| {
+        #[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+        {
+            // Configure flexi_logger:
+            // - Output to stderr (for console)
+            // - Output to a rotating file in "logs/" directory
+            // - Default level override via RUST_LOG supported
+            use flexi_logger::{Logger, FileSpec, Criterion, Naming, Cleanup, Duplicate};
+
+            // Print CWD to locate logs
+            if let Ok(cwd) = std::env::current_dir() {
+                println!("Rust Native Library Running in: {:?}", cwd);
+            }
+
+            let built = Logger::try_with_env_or_str("debug")
+                .expect("Failed to create logger")
+                .log_to_file(FileSpec::default().directory("logs").basename("physics_core"))
+                .duplicate_to_stderr(Duplicate::All) // Print to console too
+                .rotate(
+                    Criterion::Size(1024 * 1024), // 1MB
+                    Naming::Timestamps,
+                    Cleanup::KeepLogFiles(7),
+                )
+                .build();
+            match built {
+                // build() already set the max level from the spec
+                Ok((logger, handle)) => {
+                    if log_console::install(Some(logger), log::max_level()) {
+                        if let Ok(mut kept) = FLEXI_HANDLE.lock() {
+                            *kept = Some(handle);
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("File logging unavailable: {}", e);
+                    log_console::install(None, log::LevelFilter::Debug);
+                }
+            }
+        }
+        #[cfg(target_os = "android")]
+        log_console::install(
+            Some(Box::new(android_logger::AndroidLogger::new(
+                Config::default()
+                    .with_max_level(LevelFilter::Trace)
+                    .with_tag("PhysicsFX"),
+            ))),
+            LevelFilter::Trace,
+        );
+        #[cfg(target_arch = "wasm32")]
+        log_console::install(Some(Box::new(BrowserConsole)), log::LevelFilter::Info);
+    });
+    init_tracing();
+}
+
+// Kept for runtime filter changes; dropping it would shut the file writer down
+#[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+static FLEXI_HANDLE: Mutex<Option<flexi_logger::LoggerHandle>> = Mutex::new(None);
+
+/// Spans and events reach the `log` backends through `tracing`'s log bridge.
+/// Apple targets also send them to unified logging, where Console and
+/// Instruments show them with their spans.
+fn init_tracing() {
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    {
+        use std::sync::Once;
+        use tracing_subscriber::layer::SubscriberExt;
+        use tracing_subscriber::Layer;
+        static START: Once = Once::new();
+        START.call_once(|| {
+            // Same levels as the log records, so `set_log_filter` covers both
+            let filter = tracing_subscriber::filter::filter_fn(|metadata| {
+                let level = match *metadata.level() {
+                    tracing::Level::ERROR => log::Level::Error,
+                    tracing::Level::WARN => log::Level::Warn,
+                    tracing::Level::INFO => log::Level::Info,
+                    tracing::Level::DEBUG => log::Level::Debug,
+                    tracing::Level::TRACE => log::Level::Trace,
+                };
+                log_console::allows(metadata.target(), level)
+            });
+            let oslog = tracing_oslog::OsLogger::new("app.kamkash.physicsfx", "engine").with_filter(filter);
+            if tracing::subscriber::set_global_default(tracing_subscriber::registry().with(oslog)).is_err() {
+                log::warn!("A tracing subscriber is already installed; not logging to os_log");
+            }
+        });
+    }
+}
+
+/// Levels per target as in `log_filter`, e.g. `physics=debug,render=warn`;
+/// an empty spec goes back to the platform logger's own levels
+fn set_log_filter_internal(spec: &str) -> Result<(), String> {
+    let filter = if spec.trim().is_empty() { None } else { Some(LogFilter::parse(spec)?) };
+    // The file logger checks its own levels too, so it lets everything
+    // through while the runtime filter decides
+    #[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+    if let Ok(mut handle) = FLEXI_HANDLE.lock() {
+        if let Some(handle) = handle.as_mut() {
+            handle.pop_temp_spec();
+            if filter.is_some() {
+                handle.push_temp_spec(flexi_logger::LogSpecification::trace());
+            }
+        }
+    }
+    log_console::set_filter(filter);
+    // Callsites cache whether they are enabled
+    tracing::callsite::rebuild_interest_cache();
+    Ok(())
+}
+
+/// Hands records to the browser console
+#[cfg(target_arch = "wasm32")]
+struct BrowserConsole;
+
+#[cfg(target_arch = "wasm32")]
+impl log::Log for BrowserConsole {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        console_log::log(record);
+    }
+
+    fn flush(&self) {}
+}
+
+// --- JNI Interface (Android & JVM) ---
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getInfo(
+    env: JNIEnv,
+    _class: JClass,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.getInfo", || {
+        let info = get_internal_info();
+        let output = env.new_string(info).expect("Couldn't create java string!");
+        output.into_raw()
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setGravity(
+    _env: JNIEnv,
+    _class: JClass,
+    y: jfloat,
+) {
+    crash::guard("NativeLib.setGravity", || {
+        set_gravity_internal(y as f32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setTimeScale(
+    _env: JNIEnv,
+    _class: JClass,
+    scale: jfloat,
+) {
+    crash::guard("NativeLib.setTimeScale", || {
+        set_time_scale_internal(scale as f32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setPaused(
+    _env: JNIEnv,
+    _class: JClass,
+    paused: jboolean,
+) {
+    crash::guard("NativeLib.setPaused", || {
+        if let Ok(mut guard) = PHYSICS_STATE.lock() {
+            if let Some(physics) = guard.0.as_mut() {
+                physics.paused = paused != 0;
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setAnimationPaused(
+    _env: JNIEnv,
+    _class: JClass,
+    paused: jboolean,
+) {
+    crash::guard("NativeLib.setAnimationPaused", || {
+        if let Ok(mut guard) = PHYSICS_STATE.lock() {
+            if let Some(physics) = guard.0.as_mut() {
+                physics.animation_paused = paused != 0;
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_resetSimulation(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    crash::guard("NativeLib.resetSimulation", || {
+        reset_simulation_internal(ResetMode::Scene);
+    })
+}
+
+/// mode: 0 = scene, 1 = pristine scene, 2 = demo
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_resetSimulationTo(
+    _env: JNIEnv,
+    _class: JClass,
+    mode: jint,
+) -> jboolean {
+    crash::guard("NativeLib.resetSimulationTo", || reset_simulation_to_internal(mode) as jboolean)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setPersistent(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    persistent: jboolean,
+) -> jboolean {
+    crash::guard("NativeLib.setPersistent", || set_persistent_internal(entity_id as u64, persistent != 0) as jboolean)
+}
+
+/// New id of a persistent body rebuilt by the latest reset, or 0
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_persistentIdAfterReset(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jlong {
+    crash::guard("NativeLib.persistentIdAfterReset", || {
+        persistent_id_after_reset_internal(entity_id as u64).unwrap_or(0) as jlong
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_onPointerEvent(
+    _env: JNIEnv,
+    _class: JClass,
+    event_type: jint,
+    x: jfloat,
+    y: jfloat,
+    button: jint,
+) {
+    crash::guard("NativeLib.onPointerEvent", || {
+        on_pointer_event_internal(event_type as i32, x as f32, y as f32, button as i32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_onKeyEvent(
+    _env: JNIEnv,
+    _class: JClass,
+    event_type: jint,
+    key_code: jint,
+) {
+    crash::guard("NativeLib.onKeyEvent", || {
+        on_key_event_internal(event_type as i32, key_code as i32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeInit(
+    _env: JNIEnv,
+    _class: JClass,
+    surface_handle: jlong,
+    width: jint,
+    height: jint,
+) -> jboolean {
+    crash::guard("JvmWgpuGameLoop.nativeInit", || {
+        ffi::wgpu_init(
+            surface_handle as *mut std::ffi::c_void,
+            width as i32,
+            height as i32,
+        ) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeUpdate(
+    _env: JNIEnv,
+    _class: JClass,
+    delta_time: jfloat,
+) {
+    crash::guard("JvmWgpuGameLoop.nativeUpdate", || {
+        ffi::wgpu_update(delta_time as f32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeRender(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    crash::guard("JvmWgpuGameLoop.nativeRender", || {
+        ffi::wgpu_render();
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeResize(
+    _env: JNIEnv,
+    _class: JClass,
+    width: jint,
+    height: jint,
+) {
+    crash::guard("JvmWgpuGameLoop.nativeResize", || {
+        ffi::wgpu_resize(width as i32, height as i32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeShutdown(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    crash::guard("JvmWgpuGameLoop.nativeShutdown", || {
+        ffi::wgpu_shutdown();
+    })
+}
+
+/// From `onPause`; see `physics_core_on_app_pause`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_onAppPause(_env: JNIEnv, _class: JClass, freeze_physics: jboolean) {
+    crash::guard("NativeLib.onAppPause", || on_app_pause_internal(freeze_physics != 0))
+}
+
+/// From `onResume`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_onAppResume(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.onAppResume", on_app_resume_internal)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getEntityIds(
+    env: JNIEnv,
+    _class: JClass,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.getEntityIds", || {
+        ids_to_jlong_array(&env, entity_ids_internal())
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_isEntityAlive(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.isEntityAlive", || is_entity_alive_internal(entity_id as u64) as jboolean)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_resetCount(_env: JNIEnv, _class: JClass) -> jint {
+    crash::guard("NativeLib.resetCount", || reset_count_internal() as jint)
+}
+
+/// Insets in surface pixels, e.g. from `WindowInsetsCompat` with
+/// `systemBars() or displayCutout()` (see `physics_core_set_safe_area_insets`)
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setSafeAreaInsets(
+    _env: JNIEnv,
+    _class: JClass,
+    left: jfloat,
+    top: jfloat,
+    right: jfloat,
+    bottom: jfloat,
+    inset_world: jboolean,
+) -> jboolean {
+    crash::guard("NativeLib.setSafeAreaInsets", || {
+        set_safe_area_insets_internal(left, top, right, bottom, inset_world != 0) as jboolean
+    })
+}
+
+/// `DisplayMetrics.density` (see `physics_core_set_scale_factor`)
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setScaleFactor(_env: JNIEnv, _class: JClass, scale_factor: jfloat) -> jboolean {
+    crash::guard("NativeLib.setScaleFactor", || set_scale_factor_internal(scale_factor) as jboolean)
+}
+
+/// From `BatteryManager` (`EXTRA_LEVEL / EXTRA_SCALE`, -1 when unknown),
+/// `PowerManager.getCurrentThermalStatus()` and `isPowerSaveMode()`; see
+/// `physics_core_set_power_state`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setPowerState(
+    _env: JNIEnv,
+    _class: JClass,
+    battery: jfloat,
+    charging: jboolean,
+    thermal_status: jint,
+    low_power_mode: jboolean,
+) -> jboolean {
+    crash::guard("NativeLib.setPowerState", || {
+        set_power_state_internal(battery, charging != 0, thermal_status, low_power_mode != 0) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setLowPowerMode(_env: JNIEnv, _class: JClass, enabled: jboolean) {
+    crash::guard("NativeLib.setLowPowerMode", || set_low_power_mode_internal(enabled != 0))
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setGovernorEnabled(_env: JNIEnv, _class: JClass, enabled: jboolean) {
+    crash::guard("NativeLib.setGovernorEnabled", || set_governor_enabled_internal(enabled != 0))
+}
+
+/// Power state, throttle level and the limits in force as JSON
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getGovernorStats(
+    env: JNIEnv,
+    _class: JClass,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.getGovernorStats", || match env.new_string(governor_stats_internal()) {
+        Ok(output) => output.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    })
+}
+
+/// `pixels` is `width * height` RGBA8 pixels, shown behind the scene until
+/// replaced or cleared (see `physics_core_set_background_rgba`)
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setBackgroundRgba(
+    env: JNIEnv,
+    _class: JClass,
+    pixels: jni::objects::JByteArray,
+    width: jint,
+    height: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setBackgroundRgba", || {
+        let (width, height) = (width.max(0) as u32, height.max(0) as u32);
+        let result = env.convert_byte_array(&pixels).map_err(|e| format!("{:?}", e)).and_then(|rgba| set_background_internal(width, height, width, PixelLayout::Rgba8, &rgba));
+        match result {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setBackgroundRgba: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `buffer` is an `android.hardware.HardwareBuffer`, e.g. from an
+/// `ImageReader` image made with `USAGE_CPU_READ_OFTEN`; it is copied and
+/// stays the caller's to close
+#[cfg(all(feature = "jni_support", target_os = "android", feature = "android_hardware_buffer"))]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setBackgroundHardwareBuffer(
+    env: JNIEnv,
+    _class: JClass,
+    buffer: jni::objects::JObject,
+) -> jboolean {
+    crash::guard("NativeLib.setBackgroundHardwareBuffer", || {
+        if buffer.is_null() {
+            return 0;
+        }
+        // SAFETY: the env and object are valid for the call; the returned reference is released on drop
+        let buffer = unsafe { ndk::hardware_buffer::HardwareBuffer::from_jni(env.get_raw(), buffer.as_raw()) };
+        match set_background_hardware_buffer_internal(&buffer) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setBackgroundHardwareBuffer: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearBackground(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.clearBackground", || clear_background_internal())
+}
+
+/// 0 = fill (default), 1 = fit, 2 = stretch
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setBackgroundFit(_env: JNIEnv, _class: JClass, fit: jint) -> jboolean {
+    crash::guard("NativeLib.setBackgroundFit", || BackgroundFit::from_i32(fit).is_some_and(set_background_fit_internal) as jboolean)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_registerMaterialRgba(
+    env: JNIEnv,
+    _class: JClass,
+    pixels: jni::objects::JByteArray,
+    width: jint,
+    height: jint,
+) -> jint {
+    crash::guard("NativeLib.registerMaterialRgba", || {
+        match env.convert_byte_array(&pixels) {
+            Ok(rgba) => register_material_internal(width as u32, height as u32, &rgba) as jint,
+            Err(e) => {
+                log::error!("registerMaterialRgba: failed to read pixels: {:?}", e);
+                material::DEFAULT_MATERIAL as jint
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setEntityMaterial(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    material_id: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setEntityMaterial", || {
+        set_entity_material_internal(entity_id as u64, material_id as u32) as jboolean
+    })
+}
+
+/// `alphaThreshold` 0..255; returns the number of convex pieces, or 0 on failure
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_generateColliderFromTexture(
+    _env: JNIEnv,
+    _class: JClass,
+    texture_id: jint,
+    alpha_threshold: jint,
+    simplification: jfloat,
+) -> jint {
+    crash::guard("NativeLib.generateColliderFromTexture", || {
+        match generate_collider_from_texture_internal(texture_id as u32, alpha_threshold.clamp(0, 255) as u8, simplification) {
+            Ok(pieces) => pieces as jint,
+            Err(e) => {
+                log::error!("generateColliderFromTexture: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Filters 0 = nearest, 1 = linear; wraps 0 = clamp, 1 = repeat, 2 = mirror
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setTextureSampling(
+    _env: JNIEnv,
+    _class: JClass,
+    material_id: jint,
+    filter: jint,
+    mipmap_filter: jint,
+    wrap_u: jint,
+    wrap_v: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setTextureSampling", || {
+        texture_sampling::TextureSampling::from_codes(filter, mipmap_filter, wrap_u, wrap_v)
+            .is_some_and(|sampling| set_texture_sampling_internal(material_id as u32, sampling)) as jboolean
+    })
+}
+
+/// Rect `u`, `v`, `width`, `height` in texture coordinates (0..1, v down),
+/// drawn `drawWidth` x `drawHeight` times the square quad
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setSpriteRegion(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    u: jfloat,
+    v: jfloat,
+    width: jfloat,
+    height: jfloat,
+    draw_width: jfloat,
+    draw_height: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setSpriteRegion", || {
+        let region = SpriteRegion { rect: [u, v, width, height], size: [draw_width, draw_height], ..Default::default() };
+        match set_sprite_region_internal(entity_id as u64, Some(region)) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setSpriteRegion: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Borders are fractions of the region
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setNineSlice(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    left: jfloat,
+    top: jfloat,
+    right: jfloat,
+    bottom: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setNineSlice", || match set_nine_slice_internal(entity_id as u64, [left, top, right, bottom]) {
+        Ok(()) => 1,
+        Err(e) => {
+            log::error!("setNineSlice: {}", e);
+            0
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearSpriteRegion(_env: JNIEnv, _class: JClass, entity_id: jlong) -> jboolean {
+    crash::guard("NativeLib.clearSpriteRegion", || set_sprite_region_internal(entity_id as u64, None).is_ok() as jboolean)
+}
+
+/// `kind` 0 = background, 1 = world, 2 = foreground; lower `order` draws first
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setLayer(_env: JNIEnv, _class: JClass, entity_id: jlong, kind: jint, order: jint) -> jboolean {
+    crash::guard("NativeLib.setLayer", || {
+        LayerKind::from_i32(kind).is_some_and(|kind| set_layer_internal(entity_id as u64, Layer::new(kind, order))) as jboolean
+    })
+}
+
+/// 1 moves with the world, 0 stays put on screen
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setLayerParallax(
+    _env: JNIEnv,
+    _class: JClass,
+    kind: jint,
+    order: jint,
+    factor_x: jfloat,
+    factor_y: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setLayerParallax", || {
+        let Some(kind) = LayerKind::from_i32(kind) else {
+            return 0;
+        };
+        match set_layer_parallax_internal(Layer::new(kind, order), [factor_x, factor_y]) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setLayerParallax: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// .gltf or .glb file bytes; returns the model id, or 0 on failure
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_loadModel(
+    env: JNIEnv,
+    _class: JClass,
+    bytes: jni::objects::JByteArray,
+) -> jint {
+    crash::guard("NativeLib.loadModel", || {
+        let result = env.convert_byte_array(&bytes).map_err(|e| format!("{:?}", e));
+        match result.and_then(|bytes| load_model_internal(&bytes)) {
+            Ok(model_id) => model_id as jint,
+            Err(e) => {
+                log::error!("loadModel: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `modelId` 0 removes the body's model
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_attachModel(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    model_id: jint,
+    scale: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.attachModel", || {
+        attach_model_internal(entity_id as u64, model_id as u32, scale) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setClearColor(
+    _env: JNIEnv,
+    _class: JClass,
+    r: jfloat,
+    g: jfloat,
+    b: jfloat,
+    a: jfloat,
+) {
+    crash::guard("NativeLib.setClearColor", || {
+        set_clear_color_internal(r as f32, g as f32, b as f32, a as f32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setMsaaSamples(
+    _env: JNIEnv,
+    _class: JClass,
+    samples: jint,
+) {
+    crash::guard("NativeLib.setMsaaSamples", || {
+        set_msaa_samples_internal(samples.max(1) as u32);
+    })
+}
+
+/// `json` is a SplitScreen object, e.g. `{"layout": "stacked", "players": [{"follow": 4294967297}, {}]}`;
+/// null goes back to one camera
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setSplitScreen(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setSplitScreen", || {
+        if json.is_null() {
+            return set_split_screen_internal(None) as jboolean;
+        }
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match set_split_screen_json_internal(&json) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setSplitScreen: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `player` 1 or 2, or 0 to answer to both players
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setPlayer(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    player: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setPlayer", || {
+        u8::try_from(player).is_ok_and(|player| set_player_internal(entity_id as u64, player)) as jboolean
+    })
+}
+
+/// Snapshot for a remote simulation relative to `baselineTick` (see
+/// `physics_core_get_snapshot_delta`), or null if the simulation is not running
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getSnapshotDelta(
+    env: JNIEnv,
+    _class: JClass,
+    baseline_tick: jlong,
+) -> jni::sys::jbyteArray {
+    crash::guard("NativeLib.getSnapshotDelta", || {
+        let Some(bytes) = snapshot_delta_internal(baseline_tick as u64) else {
+            return std::ptr::null_mut();
+        };
+        match env.byte_array_from_slice(&bytes) {
+            Ok(array) => array.into_raw(),
+            Err(e) => {
+                log::error!("getSnapshotDelta: {:?}", e);
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Returns the snapshot's tick to send back as the next baseline, or 0 when
+/// it could not be applied
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_applySnapshot(
+    env: JNIEnv,
+    _class: JClass,
+    bytes: jni::objects::JByteArray,
+) -> jlong {
+    crash::guard("NativeLib.applySnapshot", || {
+        let result = env.convert_byte_array(&bytes).map_err(|e| format!("{:?}", e));
+        match result.and_then(|bytes| apply_snapshot_internal(&bytes)) {
+            Ok(tick) => tick as jlong,
+            Err(e) => {
+                log::error!("applySnapshot: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `window` steps of rollback (0 turns it off); see `physics_core_set_prediction`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setPrediction(
+    _env: JNIEnv,
+    _class: JClass,
+    window: jint,
+    tolerance: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setPrediction", || {
+        u32::try_from(window).is_ok_and(|window| set_prediction_internal(window, tolerance)) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setPredicted(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    predicted: jboolean,
+) -> jboolean {
+    crash::guard("NativeLib.setPredicted", || {
+        set_predicted_internal(entity_id as u64, predicted != 0) as jboolean
+    })
+}
+
+/// Returns the snapshot's tick to send back as the next baseline, or 0 when
+/// it could not be applied
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_reconcileSnapshot(
+    env: JNIEnv,
+    _class: JClass,
+    bytes: jni::objects::JByteArray,
+) -> jlong {
+    crash::guard("NativeLib.reconcileSnapshot", || {
+        let result = env.convert_byte_array(&bytes).map_err(|e| format!("{:?}", e));
+        match result.and_then(|bytes| reconcile_snapshot_internal(&bytes)) {
+            Ok(tick) => tick as jlong,
+            Err(e) => {
+                log::error!("reconcileSnapshot: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `json` is a CaptureConfig object, e.g. `{"fps": 30, "directory": "/sdcard/capture"}`;
+/// frames are only written to disk from here, so `directory` is required
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_startCapture(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.startCapture", || {
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match start_capture_json_internal(&json, None) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("startCapture: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Returns the number of frames captured
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_stopCapture(_env: JNIEnv, _class: JClass) -> jlong {
+    crash::guard("NativeLib.stopCapture", || {
+        capture::stop() as jlong
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_isCapturing(_env: JNIEnv, _class: JClass) -> jboolean {
+    crash::guard("NativeLib.isCapturing", || {
+        capture::is_active() as jboolean
+    })
+}
+
+/// Looping GIF of the last `seconds` of the rewind history (see
+/// `physics_core_export_clip`), or null if it could not be made
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_exportClip(
+    env: JNIEnv,
+    _class: JClass,
+    seconds: jfloat,
+    fps: jint,
+    scale: jfloat,
+) -> jni::sys::jbyteArray {
+    crash::guard("NativeLib.exportClip", || {
+        let bytes = match export_clip_internal(seconds, fps.max(0) as u32, scale) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                log::error!("exportClip: {}", e);
+                return std::ptr::null_mut();
+            }
+        };
+        match env.byte_array_from_slice(&bytes) {
+            Ok(array) => array.into_raw(),
+            Err(e) => {
+                log::error!("exportClip: {:?}", e);
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Render and present a frame now and return it as a PNG (see
+/// `physics_core_take_screenshot_png`), or null before init
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_takeScreenshot(env: JNIEnv, _class: JClass) -> jni::sys::jbyteArray {
+    crash::guard("NativeLib.takeScreenshot", || {
+        let bytes = match take_screenshot_internal() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                log::error!("takeScreenshot: {}", e);
+                return std::ptr::null_mut();
+            }
+        };
+        match env.byte_array_from_slice(&bytes) {
+            Ok(array) => array.into_raw(),
+            Err(e) => {
+                log::error!("takeScreenshot: {:?}", e);
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Write the next frame presented to `path` as a PNG, egui included in the
+/// desktop winit app
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_saveScreenshot(
+    mut env: JNIEnv,
+    _class: JClass,
+    path: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.saveScreenshot", || {
+        let path: String = match env.get_string(&path) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match request_screenshot_internal(ScreenshotTarget::File(path.into())) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("saveScreenshot: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Screenshot key of the desktop winit app as a winit `KeyCode`, negative to
+/// disable; a null `dir` keeps the current directory
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setScreenshotKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    key_code: jint,
+    dir: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setScreenshotKey", || {
+        let dir: Option<String> = if dir.is_null() {
+            None
+        } else {
+            match env.get_string(&dir) {
+                Ok(s) => Some(s.into()),
+                Err(_) => return 0,
+            }
+        };
+        match set_screenshot_key_internal(key_code, dir.as_deref()) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setScreenshotKey: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `json` is a MinimapConfig object, e.g. `{"corner": "bottomLeft", "visibleGroups": 2}`;
+/// null hides the minimap
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setMinimap(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setMinimap", || {
+        if json.is_null() {
+            return set_minimap_internal(None) as jboolean;
+        }
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match set_minimap_json_internal(&json) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setMinimap: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `backends`: gpu_options::BACKEND_* bits (0 = all); applies on the next init
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setInitOptions(
+    _env: JNIEnv,
+    _class: JClass,
+    backends: jint,
+    power_preference: jint,
+    force_fallback_adapter: jboolean,
+) {
+    crash::guard("NativeLib.setInitOptions", || {
+        set_gpu_init_options_internal(backends as u32, power_preference, force_fallback_adapter != 0);
+    })
+}
+
+/// Frame timings as JSON, or null before init
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getFrameStats(
+    env: JNIEnv,
+    _class: JClass,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.getFrameStats", || {
+        match frame_stats_internal().and_then(|json| env.new_string(json).ok()) {
+            Some(output) => output.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Last log lines as a JSON array; `level` 1 (errors only) to 5 (trace), 0 for all
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getRecentLogs(
+    env: JNIEnv,
+    _class: JClass,
+    level: jint,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.getRecentLogs", || {
+        match env.new_string(recent_logs_internal(level.max(0) as u32)) {
+            Ok(output) => output.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearLogs(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.clearLogs", || {
+        log_console::clear();
+    })
+}
+
+/// `spec` such as "physics=debug,render=warn"; null or "" restores the defaults
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setLogFilter(
+    mut env: JNIEnv,
+    _class: JClass,
+    spec: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setLogFilter", || {
+        let spec: String = if spec.is_null() {
+            String::new()
+        } else {
+            match env.get_string(&spec) {
+                Ok(s) => s.into(),
+                Err(_) => return 0,
+            }
+        };
+        match set_log_filter_internal(&spec) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setLogFilter: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Code of this thread's latest rejected input (`PHYSICS_CORE_ERROR_*`, 0 for
+/// none); see `physics_core_get_last_error`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getLastError(_env: JNIEnv, _class: JClass) -> jint {
+    crash::guard("NativeLib.getLastError", || {
+        validation::last_error().map_or(validation::ERROR_NONE, |error| error.code) as jint
+    })
+}
+
+/// What was wrong with that input, or null without an error
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getLastErrorMessage(
+    env: JNIEnv,
+    _class: JClass,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.getLastErrorMessage", || {
+        match validation::last_error().and_then(|error| env.new_string(error.message).ok()) {
+            Some(output) => output.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearLastError(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.clearLastError", || {
+        validation::clear();
+    })
+}
+
+/// The latest panic as JSON, or null; see `physics_core_get_last_crash_report`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getLastCrashReport(
+    env: JNIEnv,
+    _class: JClass,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.getLastCrashReport", || {
+        match crash::last_report_json().and_then(|json| env.new_string(json).ok()) {
+            Some(output) => output.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearCrashReport(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    crash::guard("NativeLib.clearCrashReport", || {
+        crash::clear();
+    })
+}
+
+/// Write each panic's report to `physicsfx_crash.txt` in `dir`, e.g. the
+/// app's internal storage, for the next launch to pick up; null stops writing
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setCrashDirectory(
+    mut env: JNIEnv,
+    _class: JClass,
+    dir: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setCrashDirectory", || {
+        if dir.is_null() {
+            return set_crash_directory_internal(None) as jboolean;
+        }
+        let dir: String = match env.get_string(&dir) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        set_crash_directory_internal(Some(&dir)) as jboolean
+    })
+}
+
+/// Adapter description as JSON, or null before init
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getAdapterInfo(
+    env: JNIEnv,
+    _class: JClass,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.getAdapterInfo", || {
+        match adapter_info_internal().and_then(|json| env.new_string(json).ok()) {
+            Some(output) => output.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setBloom(
+    _env: JNIEnv,
+    _class: JClass,
+    enabled: jboolean,
+    threshold: jfloat,
+    intensity: jfloat,
+) {
+    crash::guard("NativeLib.setBloom", || {
+        set_bloom_internal(enabled != 0, threshold as f32, intensity as f32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setContactShadows(
+    _env: JNIEnv,
+    _class: JClass,
+    enabled: jboolean,
+    intensity: jfloat,
+    spread: jfloat,
+) {
+    crash::guard("NativeLib.setContactShadows", || {
+        set_contact_shadows_internal(enabled != 0, intensity as f32, spread as f32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setCulling(_env: JNIEnv, _class: JClass, enabled: jboolean) {
+    crash::guard("NativeLib.setCulling", || {
+        set_culling_internal(enabled != 0);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setGpuCulling(_env: JNIEnv, _class: JClass, enabled: jboolean) -> jboolean {
+    crash::guard("NativeLib.setGpuCulling", || {
+        set_gpu_culling_internal(enabled != 0) as jboolean
+    })
+}
+
+/// Saves to the directory from `setSettingsDirectory` (e.g. `filesDir`)
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_saveSettings(_env: JNIEnv, _class: JClass) -> jboolean {
+    crash::guard("NativeLib.saveSettings", || {
+        match save_settings_internal() {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("saveSettings: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_restoreSettings(_env: JNIEnv, _class: JClass) -> jboolean {
+    crash::guard("NativeLib.restoreSettings", || {
+        restore_settings_internal() as jboolean
+    })
+}
+
+/// Call before init with the app's internal storage directory; null keeps nothing
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setSettingsDirectory(
+    mut env: JNIEnv,
+    _class: JClass,
+    dir: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setSettingsDirectory", || {
+        if dir.is_null() {
+            return set_settings_directory_internal(None) as jboolean;
+        }
+        let dir: String = match env.get_string(&dir) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        set_settings_directory_internal(Some(&dir)) as jboolean
+    })
+}
+
+/// HUD label at pixel (x, y), origin top-left; `color` is an Android color
+/// int (0xAARRGGBB). Same id replaces the label; it stays until cleared.
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_drawText(
+    mut env: JNIEnv,
+    _class: JClass,
+    id: jlong,
+    text: jni::objects::JString,
+    x: jfloat,
+    y: jfloat,
+    size: jfloat,
+    color: jint,
+) -> jboolean {
+    crash::guard("NativeLib.drawText", || {
+        let text: String = match env.get_string(&text) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        // ARGB to RGBA
+        let color = unpack_rgba((color as u32).rotate_left(8));
+        draw_text_internal(id as u64, &text, TextAnchor::Screen, x, y, size, color) as jboolean
+    })
+}
+
+/// Label centered above world point (x, y), `size` in world units
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_drawWorldText(
+    mut env: JNIEnv,
+    _class: JClass,
+    id: jlong,
+    text: jni::objects::JString,
+    x: jfloat,
+    y: jfloat,
+    size: jfloat,
+    color: jint,
+) -> jboolean {
+    crash::guard("NativeLib.drawWorldText", || {
+        let text: String = match env.get_string(&text) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        // ARGB to RGBA
+        let color = unpack_rgba((color as u32).rotate_left(8));
+        draw_text_internal(id as u64, &text, TextAnchor::World, x, y, size, color) as jboolean
+    })
+}
+
+/// Label following the entity at (offset_x, offset_y) from its center
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_drawEntityText(
+    mut env: JNIEnv,
+    _class: JClass,
+    id: jlong,
+    entity_id: jlong,
+    text: jni::objects::JString,
+    offset_x: jfloat,
+    offset_y: jfloat,
+    size: jfloat,
+    color: jint,
+) -> jboolean {
+    crash::guard("NativeLib.drawEntityText", || {
+        let text: String = match env.get_string(&text) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        let anchor = TextAnchor::Body(entity_id as u64);
+        // ARGB to RGBA
+        let color = unpack_rgba((color as u32).rotate_left(8));
+        draw_text_internal(id as u64, &text, anchor, offset_x, offset_y, size, color) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearText(
+    _env: JNIEnv,
+    _class: JClass,
+    id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.clearText", || {
+        clear_text_internal(id as u64) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearAllText(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.clearAllText", || {
+        clear_all_text_internal();
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_addCameraShake(
+    _env: JNIEnv,
+    _class: JClass,
+    strength: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.addCameraShake", || {
+        add_camera_shake_internal(strength) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_punchCameraZoom(
+    _env: JNIEnv,
+    _class: JClass,
+    amount: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.punchCameraZoom", || {
+        punch_camera_zoom_internal(amount) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setCameraShake(
+    _env: JNIEnv,
+    _class: JClass,
+    max_offset: jfloat,
+    max_rotation: jfloat,
+    frequency: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setCameraShake", || {
+        set_camera_shake_internal(max_offset, max_rotation, frequency) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setCameraEffectsDecay(
+    _env: JNIEnv,
+    _class: JClass,
+    trauma_decay: jfloat,
+    zoom_decay: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setCameraEffectsDecay", || {
+        set_camera_effects_decay_internal(trauma_decay, zoom_decay) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setCameraImpactShake(
+    _env: JNIEnv,
+    _class: JClass,
+    min_impulse: jfloat,
+    trauma_per_impulse: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setCameraImpactShake", || {
+        set_camera_impact_shake_internal(min_impulse, trauma_per_impulse) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearCameraEffects(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.clearCameraEffects", || {
+        clear_camera_effects_internal();
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setSleepEnabled(
+    _env: JNIEnv,
+    _class: JClass,
+    enabled: jboolean,
+) {
+    crash::guard("NativeLib.setSleepEnabled", || {
+        set_sleep_enabled_internal(enabled != 0);
+    })
+}
+
+/// `bodyType` is 0 dynamic, 1 fixed, 2 kinematic; returns 0 on failure
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_spawnBodyWithOptions(
+    _env: JNIEnv,
+    _class: JClass,
+    x: jfloat,
+    y: jfloat,
+    half_extent: jfloat,
+    body_type: jint,
+    surface_vx: jfloat,
+    surface_vy: jfloat,
+) -> jlong {
+    crash::guard("NativeLib.spawnBodyWithOptions", || {
+        body_type_from_raw(body_type)
+            .and_then(|body_type| spawn_body_with_internal(x, y, half_extent, body_type, [surface_vx, surface_vy], groups::DEFAULT))
+            .map_or(0, |id| id as jlong)
+    })
+}
+
+/// `points` holds x, y pairs of the outline relative to (x, y); `bodyType`
+/// as for `spawnBodyWithOptions`. Returns 0 on failure.
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_spawnPolygon(
+    env: JNIEnv,
+    _class: JClass,
+    x: jfloat,
+    y: jfloat,
+    points: jni::objects::JFloatArray,
+    body_type: jint,
+) -> jlong {
+    crash::guard("NativeLib.spawnPolygon", || {
+        let Some(body_type) = body_type_from_raw(body_type) else {
+            return 0;
+        };
+        let len = env.get_array_length(&points).unwrap_or(0).max(0) as usize;
+        let mut buffer = vec![0.0f32; len];
+        if env.get_float_array_region(&points, 0, &mut buffer).is_err() {
+            return 0;
+        }
+        let outline: Vec<[f32; 2]> = buffer.chunks_exact(2).map(|p| [p[0], p[1]]).collect();
+        match spawn_polygon_internal(x, y, &outline, body_type, groups::DEFAULT) {
+            Ok(id) => id as jlong,
+            Err(e) => {
+                log::error!("spawnPolygon: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setSurfaceVelocity(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    vx: jfloat,
+    vy: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setSurfaceVelocity", || {
+        set_surface_velocity_internal(entity_id as u64, vx, vy) as jboolean
+    })
+}
+
+/// Fading trail through the body's last `length` positions; `color` is ARGB
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_enableTrail(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    length: jint,
+    color: jint,
+) -> jboolean {
+    crash::guard("NativeLib.enableTrail", || {
+        // ARGB to RGBA
+        let color = unpack_rgba((color as u32).rotate_left(8));
+        enable_trail_internal(entity_id as u64, length.max(0) as u32, color) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_disableTrail(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.disableTrail", || {
+        disable_trail_internal(entity_id as u64) as jboolean
+    })
+}
+
+/// Predicted [x0, y0, x1, y1, ...] for a launch at (vx, vy); empty for
+/// unknown or non-dynamic bodies. `show` draws the arc as dots.
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_predictTrajectory(
+    env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    vx: jfloat,
+    vy: jfloat,
+    steps: jint,
+    show: jboolean,
+) -> jni::sys::jfloatArray {
+    crash::guard("NativeLib.predictTrajectory", || {
+        let values: Vec<jfloat> = predict_trajectory_internal(entity_id as u64, vx, vy, steps.max(0) as u32, show != 0)
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .collect();
+        let array = env.new_float_array(values.len() as i32).expect("Couldn't create float array!");
+        env.set_float_array_region(&array, 0, &values).expect("Couldn't fill float array!");
+        array.into_raw()
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearTrajectoryPreview(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.clearTrajectoryPreview", || {
+        clear_trajectory_preview_internal();
+    })
+}
+
+/// Press on a body, drag to aim and release to launch it
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_enableSlingshotMode(
+    _env: JNIEnv,
+    _class: JClass,
+    enabled: jboolean,
+) {
+    crash::guard("NativeLib.enableSlingshotMode", || {
+        enable_slingshot_mode_internal(enabled != 0);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setSlingshotParams(
+    _env: JNIEnv,
+    _class: JClass,
+    max_impulse: jfloat,
+    max_pull: jfloat,
+    arc_samples: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setSlingshotParams", || {
+        set_slingshot_params_internal(max_impulse, max_pull, arc_samples.max(0) as u32) as jboolean
+    })
+}
+
+/// Bodies pass through a one-way platform from below and land on its top
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setOneWay(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    enabled: jboolean,
+) -> jboolean {
+    crash::guard("NativeLib.setOneWay", || {
+        set_one_way_internal(entity_id as u64, enabled != 0) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_ignoreCollision(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_a: jlong,
+    entity_b: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.ignoreCollision", || {
+        set_collision_ignored_internal(entity_a as u64, entity_b as u64, true) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_restoreCollision(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_a: jlong,
+    entity_b: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.restoreCollision", || {
+        set_collision_ignored_internal(entity_a as u64, entity_b as u64, false) as jboolean
+    })
+}
+
+/// `json` is a LevelDesc object; returns false on parse or geometry errors
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_loadLevel(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.loadLevel", || {
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match load_level_json_internal(&json) {
+            Ok(_) => 1,
+            Err(e) => {
+                log::error!("loadLevel: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `value` 0 clears the cell; returns false for unknown layers or cells
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setTile(
+    _env: JNIEnv,
+    _class: JClass,
+    layer: jint,
+    column: jint,
+    row: jint,
+    value: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setTile", || {
+        if layer < 0 || column < 0 || row < 0 || !(0..=255).contains(&value) {
+            return 0;
+        }
+        set_tile_internal(layer as u32, column as u32, row as u32, value as u8) as jboolean
+    })
+}
+
+/// -1 for unknown layers or cells off the grid
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getTile(
+    _env: JNIEnv,
+    _class: JClass,
+    layer: jint,
+    column: jint,
+    row: jint,
+) -> jint {
+    crash::guard("NativeLib.getTile", || {
+        if layer < 0 || column < 0 || row < 0 {
+            return -1;
+        }
+        get_tile_internal(layer as u32, column as u32, row as u32).map_or(-1, jint::from)
+    })
+}
+
+/// Returns the spring id, or 0 for unknown bodies or invalid arguments
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_createSpringToPoint(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    world_x: jfloat,
+    world_y: jfloat,
+    stiffness: jfloat,
+    damping: jfloat,
+) -> jlong {
+    crash::guard("NativeLib.createSpringToPoint", || {
+        create_spring_to_point_internal(entity_id as u64, world_x, world_y, stiffness, damping).map_or(0, |id| id as jlong)
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_updateSpring(
+    _env: JNIEnv,
+    _class: JClass,
+    spring_id: jlong,
+    world_x: jfloat,
+    world_y: jfloat,
+    stiffness: jfloat,
+    damping: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.updateSpring", || {
+        update_spring_internal(spring_id as u64, world_x, world_y, stiffness, damping) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_removeSpring(
+    _env: JNIEnv,
+    _class: JClass,
+    spring_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.removeSpring", || {
+        remove_spring_internal(spring_id as u64) as jboolean
+    })
+}
+
+/// Spawn a box from a JSON scene body as an undoable step; 0 on errors
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_editSpawnBody(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: jni::objects::JString,
+) -> jlong {
+    crash::guard("NativeLib.editSpawnBody", || {
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match edit_spawn_body_json_internal(&json) {
+            Ok(id) => id as jlong,
+            Err(e) => {
+                log::error!("editSpawnBody: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Make a box match a JSON scene body as an undoable step; `merge` joins a
+/// change right after another change to the same box into its step
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_editSetBody(
+    mut env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    json: jni::objects::JString,
+    merge: jboolean,
+) -> jboolean {
+    crash::guard("NativeLib.editSetBody", || {
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match edit_body_json_internal(entity_id as u64, &json, merge != 0) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("editSetBody: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_editRemoveBody(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.editRemoveBody", || {
+        edit_remove_body_internal(entity_id as u64) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_editAttachSpring(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    world_x: jfloat,
+    world_y: jfloat,
+    stiffness: jfloat,
+    damping: jfloat,
+) -> jlong {
+    crash::guard("NativeLib.editAttachSpring", || {
+        edit_attach_spring_internal(entity_id as u64, world_x, world_y, stiffness, damping).unwrap_or(0) as jlong
+    })
+}
+
+/// The box as a JSON scene body; null for unknown ids and other shapes
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getBodyDesc(
+    env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.getBodyDesc", || {
+        match body_desc_json_internal(entity_id as u64).and_then(|json| env.new_string(json).ok()) {
+            Some(output) => output.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_undo(_env: JNIEnv, _class: JClass) -> jboolean {
+    crash::guard("NativeLib.undo", || undo_internal() as jboolean)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_redo(_env: JNIEnv, _class: JClass) -> jboolean {
+    crash::guard("NativeLib.redo", || redo_internal() as jboolean)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearHistory(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.clearHistory", clear_history_internal)
+}
+
+/// Undo and redo counts and the next steps' labels as JSON
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getHistoryState(
+    env: JNIEnv,
+    _class: JClass,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.getHistoryState", || {
+        match env.new_string(history_state_internal()) {
+            Ok(output) => output.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// `json` is a SoftBodyDesc object, e.g. `{"x": 0, "y": 0.5, "radius": 0.25, "segments": 20}`.
+/// Returns the blob id, or 0 on errors.
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_spawnSoftBody(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: jni::objects::JString,
+) -> jlong {
+    crash::guard("NativeLib.spawnSoftBody", || {
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match spawn_soft_body_json_internal(&json) {
+            Ok(id) => id as jlong,
+            Err(e) => {
+                log::error!("spawnSoftBody: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_removeSoftBody(
+    _env: JNIEnv,
+    _class: JClass,
+    soft_body_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.removeSoftBody", || {
+        remove_soft_body_internal(soft_body_id as u64) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_wakeBody(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.wakeBody", || {
+        wake_body_internal(entity_id as u64) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_sleepBody(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.sleepBody", || {
+        sleep_body_internal(entity_id as u64) as jboolean
+    })
+}
+
+/// Returns [x, y, vx, vy, rotation, sleeping (0/1)], or an empty array for unknown ids
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getBodyInfo(
+    env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jni::sys::jfloatArray {
+    crash::guard("NativeLib.getBodyInfo", || {
+        let values: Vec<jfloat> = body_info_internal(entity_id as u64)
+            .map(|info| vec![info.x, info.y, info.vx, info.vy, info.rotation, info.sleeping as u8 as f32])
+            .unwrap_or_default();
+        let array = env.new_float_array(values.len() as i32).expect("Couldn't create float array!");
+        env.set_float_array_region(&array, 0, &values).expect("Couldn't fill float array!");
+        array.into_raw()
+    })
+}
+
+/// `seconds` <= 0 cancels a pending lifetime
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setLifetime(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    seconds: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setLifetime", || {
+        set_lifetime_internal(entity_id as u64, seconds) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setDespawnBounds(
+    _env: JNIEnv,
+    _class: JClass,
+    enabled: jboolean,
+    min_x: jfloat,
+    min_y: jfloat,
+    max_x: jfloat,
+    max_y: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setDespawnBounds", || {
+        set_despawn_bounds_internal(enabled != 0, min_x, min_y, max_x, max_y) as jboolean
+    })
+}
+
+/// Returns the decoration's entity id, or 0 on failure
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_spawnDecoration(
+    _env: JNIEnv,
+    _class: JClass,
+    x: jfloat,
+    y: jfloat,
+    half_extent: jfloat,
+    material_id: jint,
+) -> jlong {
+    crash::guard("NativeLib.spawnDecoration", || {
+        spawn_decoration_internal(x, y, half_extent, material_id as u32).unwrap_or(0) as jlong
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_removeDecoration(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.removeDecoration", || {
+        remove_decoration_internal(entity_id as u64) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_attachEntity(
+    _env: JNIEnv,
+    _class: JClass,
+    child_id: jlong,
+    parent_id: jlong,
+    offset_x: jfloat,
+    offset_y: jfloat,
+    rotation: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.attachEntity", || {
+        attach_entity_internal(child_id as u64, parent_id as u64, offset_x, offset_y, rotation) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_detachEntity(
+    _env: JNIEnv,
+    _class: JClass,
+    child_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.detachEntity", || {
+        detach_entity_internal(child_id as u64) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_configureProjectiles(
+    _env: JNIEnv,
+    _class: JClass,
+    capacity: jint,
+    radius: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.configureProjectiles", || {
+        configure_projectiles_internal(capacity.max(0) as u32, radius) as jboolean
+    })
+}
+
+/// Returns the projectile's entity id, or 0 when the pool is missing or exhausted
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_fireProjectile(
+    _env: JNIEnv,
+    _class: JClass,
+    origin_x: jfloat,
+    origin_y: jfloat,
+    vx: jfloat,
+    vy: jfloat,
+    ttl: jfloat,
+) -> jlong {
+    crash::guard("NativeLib.fireProjectile", || {
+        fire_projectile_internal(origin_x, origin_y, vx, vy, ttl).map_or(0, |id| id as jlong)
+    })
+}
+
+/// Returns [capacity, active, peakActive, fired, expired, hit, dropped]
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getProjectileStats(
+    env: JNIEnv,
+    _class: JClass,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.getProjectileStats", || {
+        let stats = projectile_stats_internal();
+        let (capacity, active, peak_active) = (stats.capacity as u64, stats.active as u64, stats.peak_active as u64);
+        let values = [capacity, active, peak_active, stats.fired, stats.expired, stats.hit, stats.dropped];
+        ids_to_jlong_array(&env, values.to_vec())
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setAudioEvents(
+    _env: JNIEnv,
+    _class: JClass,
+    enabled: jboolean,
+    min_speed: jfloat,
+    max_speed: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setAudioEvents", || {
+        set_audio_events_internal(enabled != 0, min_speed, max_speed) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setAudioListener(
+    _env: JNIEnv,
+    _class: JClass,
+    x: jfloat,
+    y: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setAudioListener", || {
+        set_audio_listener_internal(x, y) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setAudioAttenuation(
+    _env: JNIEnv,
+    _class: JClass,
+    reference_distance: jfloat,
+    max_distance: jfloat,
+    rolloff: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setAudioAttenuation", || {
+        set_audio_attenuation_internal(reference_distance, max_distance, rolloff) as jboolean
+    })
+}
+
+/// Breaks into up to `fragments` pieces when a collision impulse reaches
+/// `threshold` N·s; see clearDestructible
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setDestructible(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    threshold: jfloat,
+    fragments: jint,
+    min_half_extent: jfloat,
+    particles: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setDestructible", || {
+        let config = FractureConfig {
+            threshold,
+            fragments: fragments.max(0) as u32,
+            min_half_extent,
+            particles: particles.max(0) as u32,
+        };
+        set_destructible_internal(entity_id as u64, Some(config)) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_clearDestructible(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.clearDestructible", || {
+        set_destructible_internal(entity_id as u64, None) as jboolean
+    })
+}
+
+/// 7 longs per event: [entity, fragments, then x, y, impulse, halfExtent,
+/// fragmentHalfExtent as float bits (`Float.fromBits(value.toInt())`)]
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_pollFractureEvents(
+    env: JNIEnv,
+    _class: JClass,
+    max_events: jint,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.pollFractureEvents", || {
+        let mut values = Vec::new();
+        for e in poll_fracture_events_internal(max_events.max(0) as usize) {
+            values.extend([e.entity, e.fragments as u64]);
+            let floats = [e.x, e.y, e.impulse, e.half_extent, e.fragment_half_extent];
+            values.extend(floats.map(|v| v.to_bits() as u64));
+        }
+        ids_to_jlong_array(&env, values)
+    })
+}
+
+/// 4 longs per event: [entity, zone index, zone kind (0 sensor, 1 goal,
+/// 2 hazard), 1 on entering / 0 on leaving]
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_pollTriggerEvents(
+    env: JNIEnv,
+    _class: JClass,
+    max_events: jint,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.pollTriggerEvents", || {
+        let values = poll_trigger_events_internal(max_events.max(0) as usize)
+            .into_iter()
+            .flat_map(|e| [e.entity, e.zone as u64, e.kind as u64, e.entered as u64])
+            .collect();
+        ids_to_jlong_array(&env, values)
+    })
+}
+
+/// 0 playing, 1 won (goal reached), 2 lost (hazard entered)
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_levelOutcome(_env: JNIEnv, _class: JClass) -> jint {
+    crash::guard("NativeLib.levelOutcome", || {
+        level_outcome_internal().unwrap_or_default() as jint
+    })
+}
+
+/// `json` as for `physics_core_register_scene`; false on parse or geometry errors
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_registerScene(
+    mut env: JNIEnv,
+    _class: JClass,
+    name: jni::objects::JString,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.registerScene", || {
+        let (name, json): (String, String) = match (env.get_string(&name), env.get_string(&json)) {
+            (Ok(name), Ok(json)) => (name.into(), json.into()),
+            _ => return 0,
+        };
+        match register_scene_json_internal(&name, &json) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("registerScene: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_removeScene(
+    mut env: JNIEnv,
+    _class: JClass,
+    name: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.removeScene", || {
+        match env.get_string(&name) {
+            Ok(name) => remove_scene_internal(&String::from(name)) as jboolean,
+            Err(_) => 0,
+        }
+    })
+}
+
+/// `json` as for `physics_core_register_prefab`; false on parse errors
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_registerPrefab(
+    mut env: JNIEnv,
+    _class: JClass,
+    name: jni::objects::JString,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.registerPrefab", || {
+        let (name, json): (String, String) = match (env.get_string(&name), env.get_string(&json)) {
+            (Ok(name), Ok(json)) => (name.into(), json.into()),
+            _ => return 0,
+        };
+        match register_prefab_json_internal(&name, &json) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("registerPrefab: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_removePrefab(
+    mut env: JNIEnv,
+    _class: JClass,
+    name: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.removePrefab", || {
+        match env.get_string(&name) {
+            Ok(name) => remove_prefab_internal(&String::from(name)) as jboolean,
+            Err(_) => 0,
+        }
+    })
+}
+
+/// Spawn prefab `name` with its parts; returns the prefab's own body, or 0
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_spawnPrefab(
+    mut env: JNIEnv,
+    _class: JClass,
+    name: jni::objects::JString,
+    x: jfloat,
+    y: jfloat,
+    angle: jfloat,
+) -> jlong {
+    crash::guard("NativeLib.spawnPrefab", || {
+        let name: String = match env.get_string(&name) {
+            Ok(name) => name.into(),
+            Err(_) => return 0,
+        };
+        match spawn_prefab_internal(&name, x, y, angle) {
+            Ok(id) => id as jlong,
+            Err(e) => {
+                log::error!("spawnPrefab: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `loader` is any object with `byte[] loadAsset(String name)` returning
+/// null for assets it does not have, such as one reading
+/// `context.getAssets().open(name)`; null leaves preloaded assets only.
+/// Threads the JVM does not know are attached for the call.
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_registerAssetLoader(
+    env: JNIEnv,
+    _class: JClass,
+    loader: jni::objects::JObject,
+) {
+    crash::guard("NativeLib.registerAssetLoader", || {
+        if loader.is_null() {
+            assets::set_loader(None);
+            return;
+        }
+        let (vm, loader) = match (env.get_java_vm(), env.new_global_ref(&loader)) {
+            (Ok(vm), Ok(loader)) => (vm, loader),
+            _ => return,
+        };
+        assets::set_loader(Some(Box::new(move |name: &str| {
+            let mut env = vm.attach_current_thread().map_err(|e| format!("{}: {}", name, e))?;
+            let bytes = jni_load_asset(&mut env, loader.as_obj(), name);
+            // A throwing loader reads as a missing asset
+            if env.exception_check().unwrap_or(false) {
+                let _ = env.exception_clear();
+            }
+            bytes
+        })));
+    })
+}
+
+#[cfg(feature = "jni_support")]
+fn jni_load_asset(env: &mut JNIEnv, loader: &jni::objects::JObject, name: &str) -> Result<Vec<u8>, String> {
+    let failed = |e: jni::errors::Error| format!("{}: {}", name, e);
+    let java_name = env.new_string(name).map_err(failed)?;
+    let array = env
+        .call_method(loader, "loadAsset", "(Ljava/lang/String;)[B", &[(&java_name).into()])
+        .and_then(|value| value.l())
+        .map_err(failed)?;
+    if array.is_null() {
+        return Err(format!("{}: not found by the host", name));
+    }
+    env.convert_byte_array(jni::objects::JByteArray::from(array)).map_err(failed)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_preloadAsset(
+    mut env: JNIEnv,
+    _class: JClass,
+    name: jni::objects::JString,
+    bytes: jni::objects::JByteArray,
+) -> jboolean {
+    crash::guard("NativeLib.preloadAsset", || {
+        let name: String = match env.get_string(&name) {
+            Ok(name) => name.into(),
+            Err(_) => return 0,
+        };
+        let result = env.convert_byte_array(&bytes).map_err(|e| format!("{:?}", e)).and_then(|bytes| assets::preload(&name, bytes));
+        match result {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("preloadAsset: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_unloadAsset(mut env: JNIEnv, _class: JClass, name: jni::objects::JString) -> jboolean {
+    crash::guard("NativeLib.unloadAsset", || {
+        let name: String = match env.get_string(&name) {
+            Ok(name) => name.into(),
+            Err(_) => return 0,
+        };
+        assets::unload(&name) as jboolean
+    })
+}
+
+/// Returns the material id, or 0 when the asset cannot be read or decoded
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_loadTextureAsset(mut env: JNIEnv, _class: JClass, name: jni::objects::JString) -> jint {
+    crash::guard("NativeLib.loadTextureAsset", || {
+        let name: String = match env.get_string(&name) {
+            Ok(name) => name.into(),
+            Err(_) => return 0,
+        };
+        match load_texture_asset_internal(&name) {
+            Ok(material_id) => material_id as jint,
+            Err(e) => {
+                log::error!("loadTextureAsset: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Returns the number of bodies spawned, or -1; see
+/// `physics_core_load_scene_asset`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_loadSceneAsset(mut env: JNIEnv, _class: JClass, name: jni::objects::JString) -> jint {
+    crash::guard("NativeLib.loadSceneAsset", || {
+        let name: String = match env.get_string(&name) {
+            Ok(name) => name.into(),
+            Err(_) => return -1,
+        };
+        match load_scene_asset_internal(&name) {
+            Ok(count) => count as jint,
+            Err(e) => {
+                log::error!("loadSceneAsset: {}", e);
+                -1
+            }
+        }
+    })
+}
+
+/// Rebuild the simulation as scene `name`, fading in over `fadeSeconds`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_switchScene(
+    mut env: JNIEnv,
+    _class: JClass,
+    name: jni::objects::JString,
+    fade_seconds: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.switchScene", || {
+        let name: String = match env.get_string(&name) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match switch_scene_internal(&name, fade_seconds) {
+            Ok(_) => 1,
+            Err(e) => {
+                log::error!("switchScene: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Name of the scene switched to, or null after the last scene
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_loadNextScene(
+    env: JNIEnv,
+    _class: JClass,
+    fade_seconds: jfloat,
+) -> jni::sys::jstring {
+    crash::guard("NativeLib.loadNextScene", || {
+        let next = load_next_scene_internal(fade_seconds).unwrap_or_else(|e| {
+            log::error!("loadNextScene: {}", e);
+            None
+        });
+        match next.and_then(|name| env.new_string(name).ok()) {
+            Some(output) => output.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// `values` holds `from` followed by `to`; property and easing codes as for
+/// `physics_core_animate_property`. Returns the tween id, or 0 on failure.
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_animateProperty(
+    env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    property: jint,
+    values: jni::objects::JFloatArray,
+    duration: jfloat,
+    easing: jint,
+) -> jlong {
+    crash::guard("NativeLib.animateProperty", || {
+        let (Some(property), Some(easing)) = (TweenProperty::from_u32(property as u32), Easing::from_u32(easing as u32)) else {
+            return 0;
+        };
+        let n = property.components();
+        if env.get_array_length(&values).ok() != Some(2 * n as i32) {
+            return 0;
+        }
+        let mut buffer = vec![0.0f32; 2 * n];
+        if env.get_float_array_region(&values, 0, &mut buffer).is_err() {
+            return 0;
+        }
+        let (from, to) = buffer.split_at(n);
+        animate_property_internal(entity_id as u64, property, from, to, duration, easing).unwrap_or(0) as jlong
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_cancelTween(
+    _env: JNIEnv,
+    _class: JClass,
+    tween_id: jlong,
+) -> jboolean {
+    crash::guard("NativeLib.cancelTween", || {
+        cancel_tween_internal(tween_id as u64) as jboolean
+    })
+}
+
+/// `json` as for `physics_core_set_state_machine`; null removes the machine
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setStateMachine(
+    mut env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setStateMachine", || {
+        let result = if json.is_null() {
+            set_state_machine_internal(entity_id as u64, None)
+        } else {
+            let json: String = match env.get_string(&json) {
+                Ok(s) => s.into(),
+                Err(_) => return 0,
+            };
+            set_state_machine_json_internal(entity_id as u64, &json)
+        };
+        match result {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setStateMachine: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Entity 0 sends to every machine; returns how many received the event
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_sendStateEvent(
+    mut env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    event: jni::objects::JString,
+) -> jint {
+    crash::guard("NativeLib.sendStateEvent", || {
+        let event: String = match env.get_string(&event) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        send_state_event_internal(entity_id as u64, &event) as jint
+    })
+}
+
+/// Index of the current state, or -1 for entities without a machine
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_entityState(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jint {
+    crash::guard("NativeLib.entityState", || {
+        entity_state_internal(entity_id as u64).map_or(-1, |(index, _)| index as jint)
+    })
+}
+
+/// `json` as for `physics_core_set_movement`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setMovement(
+    mut env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setMovement", || {
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match set_movement_json_internal(entity_id as u64, &json) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setMovement: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Run a packed command buffer (see `physics_core_submit_commands`); fill it
+/// with a little-endian `ByteBuffer`. Returns one result per command, or null
+/// when the buffer is malformed or the simulation is not running.
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_submitCommands(
+    env: JNIEnv,
+    _class: JClass,
+    commands: jni::objects::JByteArray,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.submitCommands", || {
+        let result = env.convert_byte_array(&commands).map_err(|e| format!("{:?}", e));
+        match result.and_then(|bytes| submit_commands_internal(&bytes)) {
+            Ok(results) => ids_to_jlong_array(&env, results),
+            Err(e) => {
+                log::error!("submitCommands: {}", e);
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Direct `ByteBuffer` over the shared transform region (layout in
+/// `physics_core_enable_transform_export`; set `ByteOrder.LITTLE_ENDIAN`),
+/// valid until the next call, or null when `capacity` is 0 or too large.
+/// Read-only for the host.
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_enableTransformExport(
+    mut env: JNIEnv,
+    _class: JClass,
+    capacity: jint,
+) -> jni::sys::jobject {
+    crash::guard("NativeLib.enableTransformExport", || {
+        let Some((ptr, len)) = enable_transform_export_internal(capacity.max(0) as u32) else {
+            return std::ptr::null_mut();
+        };
+        // The region outlives the buffer: it is only freed by the next call
+        match unsafe { env.new_direct_byte_buffer(ptr as *mut u8, len) } {
+            Ok(buffer) => buffer.into_raw(),
+            Err(e) => {
+                log::error!("enableTransformExport: {:?}", e);
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Draw the scene into another surface, given as the same native handle as
+/// `nativeInit` (an `ANativeWindow*` on Android), through its own camera.
+/// Returns the view id, or 0 on failure.
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_addView(
+    _env: JNIEnv,
+    _class: JClass,
+    surface_handle: jlong,
+    width: jint,
+    height: jint,
+    center_x: jfloat,
+    center_y: jfloat,
+    view_height: jfloat,
+) -> jint {
+    crash::guard("NativeLib.addView", || {
+        let (width, height) = (width.max(0) as u32, height.max(0) as u32);
+        ffi::physics_core_add_view(surface_handle as *mut c_void, width, height, center_x, center_y, view_height) as jint
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_removeView(_env: JNIEnv, _class: JClass, view_id: jint) -> jboolean {
+    crash::guard("NativeLib.removeView", || {
+        remove_view_internal(view_id as u32) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_resizeView(
+    _env: JNIEnv,
+    _class: JClass,
+    view_id: jint,
+    width: jint,
+    height: jint,
+) -> jboolean {
+    crash::guard("NativeLib.resizeView", || {
+        resize_view_internal(view_id as u32, width.max(0) as u32, height.max(0) as u32) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setViewCamera(
+    _env: JNIEnv,
+    _class: JClass,
+    view_id: jint,
+    center_x: jfloat,
+    center_y: jfloat,
+    view_height: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setViewCamera", || {
+        set_view_camera_internal(view_id as u32, ViewCamera { center_x, center_y, view_height }) as jboolean
+    })
+}
+
+/// 12 longs per event: [entityA, entityB, materialA, materialB, then x, y,
+/// impactSpeed, impulse, volume, pitch, pan, distance as float bits
+/// (`Float.fromBits(value.toInt())`)]
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_pollAudioEvents(
+    env: JNIEnv,
+    _class: JClass,
+    max_events: jint,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.pollAudioEvents", || {
+        let mut values = Vec::new();
+        for e in poll_audio_events_internal(max_events.max(0) as usize) {
+            values.extend([e.entity_a, e.entity_b, e.material_a as u64, e.material_b as u64]);
+            let floats = [e.x, e.y, e.impact_speed, e.impulse, e.volume, e.pitch, e.pan, e.distance];
+            values.extend(floats.map(|v| v.to_bits() as u64));
+        }
+        ids_to_jlong_array(&env, values)
+    })
+}
+
+#[cfg(feature = "jni_support")]
+fn ids_to_jlong_array(env: &JNIEnv, ids: Vec<u64>) -> jni::sys::jlongArray {
+    let ids: Vec<jlong> = ids.into_iter().map(|id| id as jlong).collect();
+    let array = env.new_long_array(ids.len() as i32).expect("Couldn't create long array!");
+    env.set_long_array_region(&array, 0, &ids).expect("Couldn't fill long array!");
+    array.into_raw()
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_queryAabb(
+    env: JNIEnv,
+    _class: JClass,
+    min_x: jfloat,
+    min_y: jfloat,
+    max_x: jfloat,
+    max_y: jfloat,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.queryAabb", || {
+        ids_to_jlong_array(&env, query_aabb_internal(min_x, min_y, max_x, max_y, groups::ALL))
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_queryCircle(
+    env: JNIEnv,
+    _class: JClass,
+    center_x: jfloat,
+    center_y: jfloat,
+    radius: jfloat,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.queryCircle", || {
+        ids_to_jlong_array(&env, query_circle_internal(center_x, center_y, radius, groups::ALL))
+    })
+}
+
+/// Group masks are passed as ints and read as unsigned bits; -1 matches every body
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_spawnBodyInGroups(
+    _env: JNIEnv,
+    _class: JClass,
+    x: jfloat,
+    y: jfloat,
+    half_extent: jfloat,
+    body_type: jint,
+    groups: jint,
+) -> jlong {
+    crash::guard("NativeLib.spawnBodyInGroups", || {
+        body_type_from_raw(body_type)
+            .and_then(|body_type| spawn_body_with_internal(x, y, half_extent, body_type, [0.0, 0.0], groups as u32))
+            .map_or(0, |id| id as jlong)
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setBodyGroups(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+    groups: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setBodyGroups", || {
+        set_body_groups_internal(entity_id as u64, groups as u32) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getBodyGroups(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) -> jint {
+    crash::guard("NativeLib.getBodyGroups", || {
+        body_groups_internal(entity_id as u64).unwrap_or(0) as jint
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getGroupIds(
+    env: JNIEnv,
+    _class: JClass,
+    mask: jint,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.getGroupIds", || {
+        ids_to_jlong_array(&env, group_ids_internal(mask as u32))
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_despawnGroup(
+    _env: JNIEnv,
+    _class: JClass,
+    mask: jint,
+) -> jint {
+    crash::guard("NativeLib.despawnGroup", || {
+        despawn_group_internal(mask as u32) as jint
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setGroupPaused(
+    _env: JNIEnv,
+    _class: JClass,
+    mask: jint,
+    paused: jboolean,
+) -> jint {
+    crash::guard("NativeLib.setGroupPaused", || {
+        set_group_paused_internal(mask as u32, paused != 0) as jint
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_applyImpulseToGroup(
+    _env: JNIEnv,
+    _class: JClass,
+    mask: jint,
+    impulse_x: jfloat,
+    impulse_y: jfloat,
+) -> jint {
+    crash::guard("NativeLib.applyImpulseToGroup", || {
+        apply_impulse_to_group_internal(mask as u32, impulse_x, impulse_y) as jint
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_queryAabbInGroups(
+    env: JNIEnv,
+    _class: JClass,
+    min_x: jfloat,
+    min_y: jfloat,
+    max_x: jfloat,
+    max_y: jfloat,
+    mask: jint,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.queryAabbInGroups", || {
+        ids_to_jlong_array(&env, query_aabb_internal(min_x, min_y, max_x, max_y, mask as u32))
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_queryCircleInGroups(
+    env: JNIEnv,
+    _class: JClass,
+    center_x: jfloat,
+    center_y: jfloat,
+    radius: jfloat,
+    mask: jint,
+) -> jni::sys::jlongArray {
+    crash::guard("NativeLib.queryCircleInGroups", || {
+        ids_to_jlong_array(&env, query_circle_internal(center_x, center_y, radius, mask as u32))
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setStreaming(
+    _env: JNIEnv,
+    _class: JClass,
+    enabled: jboolean,
+    chunk_size: jfloat,
+    load_radius: jint,
+    unload_radius: jint,
+) {
+    crash::guard("NativeLib.setStreaming", || {
+        set_streaming_internal(enabled != 0, chunk_size, load_radius.max(0) as u32, unload_radius.max(0) as u32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setStreamingFocusPoint(
+    _env: JNIEnv,
+    _class: JClass,
+    x: jfloat,
+    y: jfloat,
+) {
+    crash::guard("NativeLib.setStreamingFocusPoint", || {
+        set_streaming_focus_internal(StreamFocus::Point { x, y });
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setStreamingFocusEntity(
+    _env: JNIEnv,
+    _class: JClass,
+    entity_id: jlong,
+) {
+    crash::guard("NativeLib.setStreamingFocusEntity", || {
+        set_streaming_focus_internal(StreamFocus::Entity(entity_id as u64));
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setStreamingFocusCamera(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.setStreamingFocusCamera", || {
+        set_streaming_focus_internal(StreamFocus::Camera);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_getStateChecksum(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    crash::guard("NativeLib.getStateChecksum", || {
+        state_checksum_internal() as jlong
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_spawnStressScene(
+    _env: JNIEnv,
+    _class: JClass,
+    n_bodies: jint,
+    layout: jint,
+) -> jboolean {
+    crash::guard("NativeLib.spawnStressScene", || {
+        spawn_stress_scene_internal(n_bodies.max(0) as u32, layout) as jboolean
+    })
+}
+
+#[cfg(all(feature = "jni_support", feature = "scripting"))]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_loadScript(
+    mut env: JNIEnv,
+    _class: JClass,
+    source: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.loadScript", || {
+        let source: String = match env.get_string(&source) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match scripting::load_script(&source) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("loadScript: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(all(feature = "jni_support", feature = "scripting"))]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_unloadScript(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.unloadScript", || {
+        scripting::unload_script();
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_rewind(
+    _env: JNIEnv,
+    _class: JClass,
+    steps: jint,
+) -> jint {
+    crash::guard("NativeLib.rewind", || {
+        rewind_internal(steps.max(0) as u32) as jint
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setRewindHistory(
+    _env: JNIEnv,
+    _class: JClass,
+    capacity: jint,
+) {
+    crash::guard("NativeLib.setRewindHistory", || {
+        set_rewind_history_internal(capacity.max(0) as u32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_stepOnce(_env: JNIEnv, _class: JClass) {
+    crash::guard("NativeLib.stepOnce", || {
+        step_once_internal();
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_fastForward(
+    _env: JNIEnv,
+    _class: JClass,
+    steps: jint,
+) -> jint {
+    crash::guard("NativeLib.fastForward", || {
+        fast_forward_internal(steps.max(0) as u32) as jint
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setTimeScaleRamp(
+    _env: JNIEnv,
+    _class: JClass,
+    target: jfloat,
+    seconds: jfloat,
+) {
+    crash::guard("NativeLib.setTimeScaleRamp", || {
+        set_time_scale_ramp_internal(target, seconds);
+    })
+}
+
+/// `json` is a ForceFieldConfig object (wind, volumes, buoyancy)
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setForceFields(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setForceFields", || {
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match set_force_fields_json_internal(&json) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setForceFields: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `json` is a WaterConfig object, e.g. `{"regions": [{"minX": -1, "maxX": 1, "surfaceY": -0.5}]}`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setWater(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setWater", || {
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match set_water_json_internal(&json) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setWater: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// `json` is a TimeDilationConfig object, e.g. `{"zones": [{"min": [-1, -1], "max": [1, 1], "scale": 0.25}]}`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setTimeDilation(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: jni::objects::JString,
+) -> jboolean {
+    crash::guard("NativeLib.setTimeDilation", || {
+        let json: String = match env.get_string(&json) {
+            Ok(s) => s.into(),
+            Err(_) => return 0,
+        };
+        match set_time_dilation_json_internal(&json) {
+            Ok(()) => 1,
+            Err(e) => {
+                log::error!("setTimeDilation: {}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Substeps per step; `autoSubsteps` raises them above `velocityThreshold` m/s
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setSubsteps(
+    _env: JNIEnv,
+    _class: JClass,
+    substeps: jint,
+    auto_substeps: jboolean,
+    velocity_threshold: jfloat,
+    max_substeps: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setSubsteps", || {
+        set_substeps_internal(SubstepConfig {
+            substeps: substeps.max(0) as u32,
+            auto: auto_substeps != 0,
+            velocity_threshold,
+            max_substeps: max_substeps.max(0) as u32,
+        }) as jboolean
+    })
+}
+
+/// `erp` 0 keeps Rapier's default contact stiffness; returns false if not running
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setSolverConfig(
+    _env: JNIEnv,
+    _class: JClass,
+    solver_iterations: jint,
+    friction_iterations: jint,
+    internal_pgs_iterations: jint,
+    erp: jfloat,
+    allowed_linear_error: jfloat,
+    max_ccd_substeps: jint,
+    contact_damping: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setSolverConfig", || {
+        set_solver_config_internal(SolverConfig {
+            solver_iterations: solver_iterations.max(0) as u32,
+            friction_iterations: friction_iterations.max(0) as u32,
+            internal_pgs_iterations: internal_pgs_iterations.max(0) as u32,
+            erp,
+            allowed_linear_error,
+            max_ccd_substeps: max_ccd_substeps.max(0) as u32,
+            contact_damping,
+        }) as jboolean
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_onTouchEvent(
+    _env: JNIEnv,
+    _class: JClass,
+    action: jint,
+    pointer_id: jint,
+    x: jfloat,
+    y: jfloat,
+    event_time_ms: jlong,
+) {
+    crash::guard("NativeLib.onTouchEvent", || {
+        on_touch_event_internal(action, pointer_id, x, y, event_time_ms as f64 / 1000.0);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setGestures(
+    _env: JNIEnv,
+    _class: JClass,
+    tap_spawn: jboolean,
+    fling: jboolean,
+    pinch_zoom: jboolean,
+    pan: jboolean,
+) {
+    crash::guard("NativeLib.setGestures", || {
+        set_gestures_internal(tap_spawn != 0, fling != 0, pinch_zoom != 0, pan != 0);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setGravityVector(
+    _env: JNIEnv,
+    _class: JClass,
+    x: jfloat,
+    y: jfloat,
+    z: jfloat,
+) {
+    crash::guard("NativeLib.setGravityVector", || {
+        set_gravity_vector_internal(x as f32, y as f32, z as f32);
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setGravityPreset(
+    _env: JNIEnv,
+    _class: JClass,
+    preset: jint,
+) -> jboolean {
+    crash::guard("NativeLib.setGravityPreset", || set_gravity_preset_internal(preset) as jboolean)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setWorldScale(
+    _env: JNIEnv,
+    _class: JClass,
+    pixels_per_meter: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setWorldScale", || set_world_scale_internal(pixels_per_meter) as jboolean)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setWorldScaleNdc(
+    _env: JNIEnv,
+    _class: JClass,
+    ndc_per_meter: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.setWorldScaleNdc", || set_world_scale_ndc_internal(ndc_per_meter) as jboolean)
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_setTiltGravity(
+    _env: JNIEnv,
+    _class: JClass,
+    enabled: jboolean,
+    strength: jfloat,
+) {
+    crash::guard("NativeLib.setTiltGravity", || {
+        set_tilt_gravity_internal(enabled != 0, strength as f32);
+    })
+}
+
+/// Forward `SensorEvent.values` from a `Sensor.TYPE_ACCELEROMETER` listener
+/// together with `Display.getRotation() * 90`
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_onAccelerometer(
+    _env: JNIEnv,
+    _class: JClass,
+    x: jfloat,
+    y: jfloat,
+    z: jfloat,
+    rotation_degrees: jint,
+) {
+    crash::guard("NativeLib.onAccelerometer", || {
+        on_accelerometer_internal(x as f32, y as f32, z as f32, rotation_degrees as i32);
+    })
+}
+
+/// Returns the number of bodies dropped, 0 when stopping or on failure
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_set3dMode(
+    _env: JNIEnv,
+    _class: JClass,
+    enabled: jboolean,
+    body_count: jint,
+) -> jint {
+    crash::guard("NativeLib.set3dMode", || {
+        match set_3d_mode_internal(enabled != 0, body_count.max(0) as u32) {
+            Ok(count) => count as jint,
+            Err(e) => {
+                log::error!("set3dMode: {}", e);
+                0
+            }
+        }
+    })
+}
+
+#[cfg(feature = "jni_support")]
+#[no_mangle]
+pub extern "system" fn Java_app_kamkash_physicsfx_NativeLib_orbit3dCamera(
+    _env: JNIEnv,
+    _class: JClass,
+    dx: jfloat,
+    dy: jfloat,
+    zoom: jfloat,
+) -> jboolean {
+    crash::guard("NativeLib.orbit3dCamera", || {
+        orbit_3d_camera_internal(dx, dy, zoom) as jboolean
+    })
+}
+
+// --- Wasm Interface ---
+
+#[cfg(feature = "wasm_support")]
+thread_local! {
+    /// Canvas of the last `wasm_init`, used to rebuild after device loss
+    static WASM_CANVAS_ID: std::cell::RefCell<String> = std::cell::RefCell::new(String::new());
+}
+
+#[cfg(feature = "wasm_support")]
+static WASM_REINIT_PENDING: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_info() -> String {
+    get_internal_info()
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub async fn wasm_init(canvas_id: &str, width: u32, height: u32) -> bool {
+    WASM_CANVAS_ID.with(|id| *id.borrow_mut() = canvas_id.to_string());
+    // Once: setting the console hook again would drop the crash capture
+    static PANIC_HOOK: std::sync::Once = std::sync::Once::new();
+    PANIC_HOOK.call_once(|| {
+        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+        crash::install_hook();
+    });
+    init_logging();
+    log::info!(
+        "wasm_init called: canvas={}, {}x{}",
+        canvas_id,
+        width,
+        height
+    );
+
+    // For WASM, we use web_sys to get the canvas element
+    use wasm_bindgen::JsCast;
+
+    let window = match web_sys::window() {
+        Some(w) => w,
+        None => {
+            log::error!("No window available");
+            return false;
+        }
+    };
+
+    let document = match window.document() {
+        Some(d) => d,
+        None => {
+            log::error!("No document available");
+            return false;
+        }
+    };
+
+    let canvas = match document.get_element_by_id(canvas_id) {
+        Some(c) => c,
+        None => {
+            log::error!("Canvas element '{}' not found", canvas_id);
+            return false;
+        }
+    };
+
+    let canvas: web_sys::HtmlCanvasElement = match canvas.dyn_into() {
+        Ok(c) => c,
+        Err(_) => {
+            log::error!("Element '{}' is not a canvas", canvas_id);
+            return false;
+        }
+    };
+
+    // Set initial scale factor - REMOVED wgpu_set_scale_factor calls
+    // Attach Input Listeners - REMOVED
+
+    // Apply debug style to the EXISTING canvas to verify we have it
+    // We wrap these in a block and ignore errors just in case
+
+    log::info!("Acquired existing canvas: {}", canvas_id);
+
+    // Helper function to try initializing a specific backend
+    let options = GPU_INIT_OPTIONS.lock().map(|o| *o).unwrap_or_default();
+    log::info!("GPU init options: {:?}", options);
+
+    let init_backend = |backend: wgpu::Backends, canvas: web_sys::HtmlCanvasElement| async move {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: backend,
+            ..Default::default()
+        });
+
+        let surface = match instance.create_surface(wgpu::SurfaceTarget::Canvas(canvas.clone())) {
+            Ok(s) => s,
+            Err(e) => return Err(format!("Failed to create surface: {:?}", e)),
+        };
+
+        let adapter = match instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: options.power_preference,
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: options.force_fallback_adapter,
+            })
+            .await
+        {
+            Ok(a) => a,
+            Err(_) => return Err("Failed to find suitable adapter".to_string()),
+        };
+
+        log::info!("Adapter backend: {:?}", adapter.get_info().backend);
+        log::info!("Adapter limits: {:?}", adapter.limits());
+
+        let requested_limits = if backend == wgpu::Backends::BROWSER_WEBGPU {
+            // For WebGPU, trust the adapter to handle its own limits
+            adapter.limits()
+        } else {
+            // For WebGL, use safe downlevel defaults but try to bump texture size
+            let mut limits = wgpu::Limits::downlevel_webgl2_defaults();
+            let adapter_limits = adapter.limits();
+            limits.max_texture_dimension_2d = adapter_limits.max_texture_dimension_2d;
+            limits
+        };
+
+        log::info!("Requesting limits: {:?}", requested_limits);
+
+        let (device, queue) = match adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("physics_core device"),
+                required_features: gpu_timing::timestamp_features(adapter.features())
+                    | gpu_caps::compression_features(adapter.features()),
+                required_limits: requested_limits,
+                ..Default::default()
+            })
+            .await
+        {
+            Ok(dq) => dq,
+            Err(e) => return Err(format!("Failed to request device: {:?}", e)),
+        };
+
+        Ok((instance, surface, adapter, device, queue, canvas))
+    };
+
+    // Try WebGPU first, unless the options leave it out
+    let result = if !options.backends.contains(wgpu::Backends::BROWSER_WEBGPU) {
+        init_backend(wgpu::Backends::GL, canvas.clone()).await
+    } else {
+        match init_backend(wgpu::Backends::BROWSER_WEBGPU, canvas.clone()).await {
+            Ok(res) => Ok(res),
+            Err(e) if !options.backends.contains(wgpu::Backends::GL) => Err(e),
+            Err(e) => {
+                log::warn!(
+                    "WebGPU initialization failed: {}. Replacing canvas and falling back to WebGL...",
+                    e
+                );
+
+                // Replace the canvas to clear any tainted context
+                let parent = canvas.parent_node().unwrap();
+                let new_canvas_node = canvas.clone_node().unwrap();
+                parent.replace_child(&new_canvas_node, &canvas).unwrap();
+
+                let new_canvas: web_sys::HtmlCanvasElement = new_canvas_node.dyn_into().unwrap();
+
+                init_backend(wgpu::Backends::GL, new_canvas).await
+            }
+        }
+    };
+
+    let (instance, surface, adapter, device, queue, surface_canvas) = match result {
+        Ok(res) => res,
+        Err(e) => {
+            log::error!("Final initialization failed: {}", e);
+            WASM_REINIT_PENDING.store(false, Ordering::Relaxed);
+            return false;
+        }
+    };
+    watch_device_lost(&device);
+    let adapter_info = adapter.get_info();
+
+    log::info!("Device acquired. getting surface caps...");
+
+    let surface_caps = surface.get_capabilities(&adapter);
+    log::info!(
+        "Surface caps acquired. Alpha Modes: {:?}",
+        surface_caps.alpha_modes
+    );
+
+    let surface_format = surface_caps
+        .formats
+        .iter()
+        .copied()
+        .find(|f| f.is_srgb())
+        .unwrap_or(surface_caps.formats[0]);
+    log::info!("Selected surface format: {:?}", surface_format);
+
+    let max_dimension = device.limits().max_texture_dimension_2d;
+
+    let config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format: surface_format,
+        width: width.min(max_dimension),
+        height: height.min(max_dimension),
         present_mode: wgpu::PresentMode::AutoVsync,
         alpha_mode: surface_caps.alpha_modes[0],
         view_formats: vec![],
         desired_maximum_frame_latency: 2,
     };
-    log::info!("Surface config created: {}x{}", config.width, config.height);
+    log::info!("Surface config created: {}x{}", config.width, config.height);
+
+    surface.configure(&device, &config);
+    log::info!("Surface configured");
+
+    let requested_samples = RENDER_SETTINGS.lock().map(|s| s.msaa_samples).unwrap_or(1);
+    let sample_count = render_settings::adapter_sample_count(&adapter, config.format, requested_samples);
+    let msaa_view = render_settings::create_msaa_view(&device, &config, sample_count);
+
+    let (texture_view, sampler) = create_texture(&device, &queue);
+
+    let texture_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("texture_bind_group_layout"),
+        });
+
+    let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &texture_bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+        ],
+        label: Some("diffuse_bind_group"),
+    });
+
+    // Shader setup
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Shader"),
+        source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/shader.wgsl"))),
+    });
+
+    // --- Camera Setup ---
+    let [_, _, view_width, view_height] = safe_area().world_rect(config.width, config.height);
+    let camera = Camera::orthographic(view_width as f32 / view_height.max(1) as f32, &world_scale());
+
+    let mut camera_uniform = CameraUniform::new();
+    camera_uniform.update_view_proj(&camera);
+
+    let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Camera Buffer"),
+        contents: bytemuck::cast_slice(&[camera_uniform]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+
+    let camera_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("camera_bind_group_layout"),
+        });
+
+    let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &camera_bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: camera_buffer.as_entire_binding(),
+        }],
+        label: Some("camera_bind_group"),
+    });
+
+    let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Render Pipeline Layout"),
+        bind_group_layouts: &[&texture_bind_group_layout, &camera_bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let render_pipeline = create_scene_pipeline(&device, &render_pipeline_layout, &shader, config.format, sample_count);
+
+    let bloom = if caps.bloom {
+        Some(BloomPass::new(
+            &device,
+            &shader,
+            &render_pipeline_layout,
+            &[Vertex::desc(), Instance::desc()],
+            config.format,
+            config.width,
+            config.height,
+        ))
+    } else {
+        log::info!("Bloom unavailable on this adapter (needs compute + HDR storage textures)");
+        None
+    };
+
+    let contact_shadows = ContactShadowPass::new(
+        &device,
+        include_str!("../shaders/shadow.wgsl"),
+        &camera_bind_group_layout,
+        &[Vertex::desc(), Instance::desc()],
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let water_pass = WaterPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let soft_body_pass = SoftBodyPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let trail_pass = TrailPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let fade_pass = TrailPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let terrain_pass = TrailPass::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        sample_count,
+        bloom.as_ref().map(|_| post_process::HDR_FORMAT),
+    );
+    let background_pass = BackgroundPass::new(&device, config.format, sample_count, bloom.as_ref().map(|_| post_process::HDR_FORMAT));
+    let minimap_pass = MinimapPass::new(&device, &camera_bind_group_layout, config.format);
+    let split_cameras = SplitCameras::new(&device, &camera_bind_group_layout);
+
+    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Vertex Buffer"),
+        contents: bytemuck::cast_slice(VERTICES),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+
+    let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Index Buffer"),
+        contents: bytemuck::cast_slice(INDICES),
+        usage: wgpu::BufferUsages::INDEX,
+    });
+
+    // --- Instance Data Setup ---
+    const NUM_INSTANCES_PER_ROW: u32 = 10;
+    const NUM_INSTANCES: u32 = NUM_INSTANCES_PER_ROW * NUM_INSTANCES_PER_ROW;
+    let mut instances = Vec::new();
+    for y in 0..NUM_INSTANCES_PER_ROW {
+        for x in 0..NUM_INSTANCES_PER_ROW {
+            let position = [
+                (x as f32 / NUM_INSTANCES_PER_ROW as f32) * 2.0 - 1.0 + (1.0 / NUM_INSTANCES_PER_ROW as f32),
+                (y as f32 / NUM_INSTANCES_PER_ROW as f32) * 2.0 - 1.0 + (1.0 / NUM_INSTANCES_PER_ROW as f32),
+            ];
+            // Random-ish velocity
+            let velocity = [
+                (x as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0) * 0.1,
+                (y as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0) * 0.1,
+            ];
+            instances.push(Instance {
+                position,
+                velocity,
+                scale: 0.05,
+                rotation: 0.0,
+                uv_offset: [0.0, 0.0],
+                uv_scale: [1.0, 1.0],
+                tint: WHITE_TINT,
+                lod: 0,
+                size: [1.0, 1.0],
+                slice_min: [0.0, 0.0],
+                slice_max: [0.0, 0.0],
+                parallax: [1.0, 1.0],
+            });
+        }
+    }
+
+    let caps = GpuCaps::detect(&adapter, &device.limits(), device.features());
+
+    let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Instance Buffer"),
+        contents: bytemuck::cast_slice(&instances),
+        usage: caps.instance_buffer_usage(),
+    });
+
+    // --- Compute Pipeline Setup ---
+    // Only where compute can bind the instance buffer; otherwise instances are
+    // updated purely from the CPU
+    let instance_compute = caps
+        .instance_compute()
+        .then(|| InstanceCompute::new(&device, &shader, &instance_buffer));
+    let gpu_cull = caps.gpu_culling.then(|| GpuCullPass::new(&device, std::mem::size_of::<Instance>() as u64));
+
+    let device = Arc::new(device);
+    let queue = Arc::new(queue);
+    let text_pass = TextPass::new(&device, &queue, config.format);
+
+    let bevy_3d_rend = Bevy3DSample::new(
+        &device,
+        &camera_bind_group_layout,
+        config.format,
+        1, // Drawn by the debug pass straight onto the swapchain texture
+        config.width,
+        config.height,
+    );
+
+    let mut state = WgpuState {
+        instance,
+        device,
+        queue,
+        surface,
+        config,
+        render_pipeline,
+        vertex_buffer,
+        index_buffer,
+        instance_buffer,      // NEW
+        instance_capacity: NUM_INSTANCES,
+        instance_buffer_synced: false,
+        materials: MaterialRegistry::new(diffuse_bind_group.clone()),
+        diffuse_bind_group,
+        texture_bind_group_layout,
+        draw_batches: Vec::new(),
+        layer_split: LayerSplit::default(),
+        tile_buffer: None,
+        tile_batches: Vec::new(),
+        tiles_synced: false,
+        instance_compute,
+        gpu_cull,
+        caps,
+        num_instances: NUM_INSTANCES, // NEW
+        window_ptr: std::ptr::null_mut(),
+        #[cfg(target_arch = "wasm32")]
+        last_render_time: web_sys::window().unwrap().performance().unwrap().now(),
+        #[cfg(not(target_arch = "wasm32"))]
+        last_render_time: std::time::Instant::now(),
+
+        frame_count: 0,
+        #[cfg(target_arch = "wasm32")]
+        last_fps_log_time: web_sys::window().unwrap().performance().unwrap().now(),
+        #[cfg(not(target_arch = "wasm32"))]
+        last_fps_log_time: std::time::Instant::now(),
+        
+        egui_renderer: None,
+        camera,
+        camera_offset: CameraOffset::default(),
+        camera_uniform,
+        camera_buffer,
+        camera_bind_group,
+        bevy_3d_sample: Some(bevy_3d_rend),
+        sample_count,
+        msaa_view,
+        bloom,
+        contact_shadows,
+        water_pass,
+        soft_body_pass,
+        trail_pass,
+        fade_pass,
+        terrain_pass,
+        background_pass,
+        minimap_pass,
+        split_cameras,
+        capture_target: None,
+        text_pass,
+        render_pipeline_layout,
+        camera_bind_group_layout,
+        #[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+        shader_watcher: ShaderWatcher::from_env(HOT_RELOAD_SHADERS),
+        gpu_timer: GpuTimer::new(&device, &queue),
+        frame_stats: FrameStats::default(),
+        adapter_info,
+        surface_formats: surface_caps.formats,
+        surface_source: SurfaceSource::Canvas(surface_canvas),
+        adapter,
+        views: ViewSlots::default(),
+        lost_frames: 0,
+        recovery: None,
+    };
+
+    // A reinit after device loss keeps the running simulation, camera and
+    // materials, and so does one after a shutdown while paused
+    let mut resumed = restore_parked_gpu(&mut state);
+    if let Ok(mut guard) = WGPU_STATE.lock() {
+        if let Some(old) = guard.0.take() {
+            if old.recovery == Some(Recovery::Device) {
+                GpuCarryOver::take(old).restore(&mut state);
+                resumed = true;
+            }
+        }
+        guard.0 = Some(state);
+    }
+    WASM_REINIT_PENDING.store(false, Ordering::Relaxed);
+
+    match INITIALIZED.compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => log::info!("WASM wgpu initialized successfully"),
+        Err(_) if resumed => log::info!("WASM wgpu rebuilt after device loss"),
+        Err(_) => log::warn!("WASM wgpu already initialized"),
+    }
+
+    // Initialize physics simulation
+    if !resumed {
+        init_physics();
+        restore_settings_internal();
+    }
+
+    true
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_update(delta_time: f32) {
+    ffi::wgpu_update(delta_time);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_render() {
+    // log::info!("wasm_render called");
+    render_internal(None);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_resize(width: u32, height: u32) {
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    if let Ok(mut guard) = WGPU_STATE.lock() {
+        if let Some(state) = guard.0.as_mut() {
+            let max_dimension = state.device.limits().max_texture_dimension_2d;
+            let clamped_width = width.min(max_dimension);
+            let clamped_height = height.min(max_dimension);
+
+            log::info!(
+                "wasm_resize: {}x{} -> clamped {}x{}",
+                width,
+                height,
+                clamped_width,
+                clamped_height
+            );
+
+            state.config.width = clamped_width;
+            state.config.height = clamped_height;
+            state.surface.configure(&state.device, &state.config);
+            state.msaa_view = render_settings::create_msaa_view(&state.device, &state.config, state.sample_count);
+            if let Some(bloom) = state.bloom.as_mut() {
+                bloom.resize(&state.device, clamped_width, clamped_height);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_shutdown() {
+    ffi::wgpu_shutdown();
+}
+
+/// For `visibilitychange` when `document.hidden` turns true
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_on_app_pause(freeze_physics: bool) {
+    on_app_pause_internal(freeze_physics);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_on_app_resume() {
+    on_app_resume_internal();
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_gravity(y: f32) {
+    set_gravity_internal(y);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_time_scale(scale: f32) {
+    set_time_scale_internal(scale);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_paused(paused: bool) {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.paused = paused;
+        }
+    }
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_animation_paused(paused: bool) {
+    if let Ok(mut guard) = PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            physics.animation_paused = paused;
+        }
+    }
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_reset_simulation() {
+    reset_simulation_internal(ResetMode::Scene);
+}
+
+/// mode: 0 = scene, 1 = pristine scene, 2 = demo
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_reset_simulation_to(mode: i32) -> bool {
+    reset_simulation_to_internal(mode)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_persistent(entity_id: u64, persistent: bool) -> bool {
+    set_persistent_internal(entity_id, persistent)
+}
+
+/// New id of a persistent body rebuilt by the latest reset, or 0
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_persistent_id_after_reset(entity_id: u64) -> u64 {
+    persistent_id_after_reset_internal(entity_id).unwrap_or(0)
+}
+
+/// `x`, `y` in CSS pixels (`offsetX`/`offsetY`), scaled to canvas pixels by
+/// `wasm_set_scale_factor`; negative keeps the last position as before
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_on_pointer_event(event_type: i32, x: f32, y: f32, button: i32) {
+    let scale = scale_factor();
+    on_pointer_event_internal(event_type, x * scale, y * scale, button);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_on_key_event(event_type: i32, key_code: i32) {
+    on_key_event_internal(event_type, key_code);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_entity_ids() -> Vec<u64> {
+    entity_ids_internal()
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_is_entity_alive(entity_id: u64) -> bool {
+    is_entity_alive_internal(entity_id)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_reset_count() -> u32 {
+    reset_count_internal()
+}
+
+/// CSS `env(safe-area-inset-*)` values times `devicePixelRatio`
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_safe_area_insets(left: f32, top: f32, right: f32, bottom: f32, inset_world: bool) -> bool {
+    set_safe_area_insets_internal(left, top, right, bottom, inset_world)
+}
+
+/// `pixels` is `width * height` RGBA8 pixels, e.g. a video frame drawn to a canvas
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_background_rgba(pixels: &[u8], width: u32, height: u32) -> bool {
+    match set_background_internal(width, height, width, PixelLayout::Rgba8, pixels) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("wasm_set_background_rgba: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_background() {
+    clear_background_internal();
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_background_fit(fit: i32) -> bool {
+    BackgroundFit::from_i32(fit).is_some_and(set_background_fit_internal)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_register_material_rgba(pixels: &[u8], width: u32, height: u32) -> u32 {
+    register_material_internal(width, height, pixels)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_entity_material(entity_id: u64, material_id: u32) -> bool {
+    set_entity_material_internal(entity_id, material_id)
+}
+
+/// Filters 0 = nearest, 1 = linear; wraps 0 = clamp, 1 = repeat, 2 = mirror
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_texture_sampling(material_id: u32, filter: i32, mipmap_filter: i32, wrap_u: i32, wrap_v: i32) -> bool {
+    texture_sampling::TextureSampling::from_codes(filter, mipmap_filter, wrap_u, wrap_v)
+        .is_some_and(|sampling| set_texture_sampling_internal(material_id, sampling))
+}
+
+/// Rect in texture coordinates (0..1, v down), drawn `draw_width` x
+/// `draw_height` times the square quad
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_sprite_region(entity_id: u64, u: f32, v: f32, width: f32, height: f32, draw_width: f32, draw_height: f32) -> bool {
+    let region = SpriteRegion { rect: [u, v, width, height], size: [draw_width, draw_height], ..Default::default() };
+    match set_sprite_region_internal(entity_id, Some(region)) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("wasm_set_sprite_region: {}", e);
+            false
+        }
+    }
+}
+
+/// Borders are fractions of the region
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_nine_slice(entity_id: u64, left: f32, top: f32, right: f32, bottom: f32) -> bool {
+    match set_nine_slice_internal(entity_id, [left, top, right, bottom]) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("wasm_set_nine_slice: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_sprite_region(entity_id: u64) -> bool {
+    set_sprite_region_internal(entity_id, None).is_ok()
+}
+
+/// `kind` 0 = background, 1 = world, 2 = foreground; lower `order` draws first
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_layer(entity_id: u64, kind: i32, order: i32) -> bool {
+    LayerKind::from_i32(kind).is_some_and(|kind| set_layer_internal(entity_id, Layer::new(kind, order)))
+}
+
+/// 1 moves with the world, 0 stays put on screen
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_layer_parallax(kind: i32, order: i32, factor_x: f32, factor_y: f32) -> bool {
+    let Some(kind) = LayerKind::from_i32(kind) else {
+        return false;
+    };
+    match set_layer_parallax_internal(Layer::new(kind, order), [factor_x, factor_y]) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("wasm_set_layer_parallax: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_clear_color(r: f32, g: f32, b: f32, a: f32) {
+    set_clear_color_internal(r, g, b, a);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_msaa_samples(samples: u32) {
+    set_msaa_samples_internal(samples);
+}
+
+/// `backends`: gpu_options::BACKEND_* bits (0 = all); applies on the next `wasm_init`
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_init_options(backends: u32, power_preference: i32, force_fallback_adapter: bool) {
+    set_gpu_init_options_internal(backends, power_preference, force_fallback_adapter);
+}
+
+/// Frame timings as a JSON string, or undefined before init
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_frame_stats() -> Option<String> {
+    frame_stats_internal()
+}
+
+/// Last log lines as a JSON array; `level` 1 (errors only) to 5 (trace), 0 for all
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_recent_logs(level: u32) -> String {
+    recent_logs_internal(level)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_logs() {
+    log_console::clear();
+}
+
+/// The latest rejected input as JSON `{"code": 1, "message": "..."}` (codes
+/// as `PHYSICS_CORE_ERROR_*`), or undefined without one. `undefined` passed
+/// for a number arrives as NaN and is rejected like any NaN.
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_last_error() -> Option<String> {
+    validation::last_error().map(|error| error.to_json())
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_last_error() {
+    validation::clear();
+}
+
+/// `spec` such as "physics=debug,render=warn"; "" restores the defaults
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_log_filter(spec: &str) -> bool {
+    match set_log_filter_internal(spec) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("wasm_set_log_filter: {}", e);
+            false
+        }
+    }
+}
+
+/// Adapter description as a JSON string, or undefined before init
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_adapter_info() -> Option<String> {
+    adapter_info_internal()
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_bloom(enabled: bool, threshold: f32, intensity: f32) {
+    set_bloom_internal(enabled, threshold, intensity);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_contact_shadows(enabled: bool, intensity: f32, spread: f32) {
+    set_contact_shadows_internal(enabled, intensity, spread);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_culling(enabled: bool) {
+    set_culling_internal(enabled);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_gpu_culling(enabled: bool) -> bool {
+    set_gpu_culling_internal(enabled)
+}
+
+/// Save to `localStorage`; call from `pagehide`, since the page never shuts down
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_save_settings() -> bool {
+    match save_settings_internal() {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("wasm_save_settings: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_restore_settings() -> bool {
+    restore_settings_internal()
+}
+
+/// HUD label at pixel (x, y), origin top-left, in `color` (0xRRGGBBAA);
+/// stays until cleared
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_draw_text(id: u64, text: &str, x: f32, y: f32, size: f32, color: u32) -> bool {
+    draw_text_internal(id, text, TextAnchor::Screen, x, y, size, unpack_rgba(color))
+}
+
+/// Label centered above world point (x, y), `size` in world units
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_draw_world_text(id: u64, text: &str, x: f32, y: f32, size: f32, color: u32) -> bool {
+    draw_text_internal(id, text, TextAnchor::World, x, y, size, unpack_rgba(color))
+}
+
+/// Label following the entity at (offset_x, offset_y) from its center
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_draw_entity_text(id: u64, entity_id: u64, text: &str, offset_x: f32, offset_y: f32, size: f32, color: u32) -> bool {
+    let anchor = TextAnchor::Body(entity_id);
+    draw_text_internal(id, text, anchor, offset_x, offset_y, size, unpack_rgba(color))
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_text(id: u64) -> bool {
+    clear_text_internal(id)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_all_text() {
+    clear_all_text_internal();
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_add_camera_shake(strength: f32) -> bool {
+    add_camera_shake_internal(strength)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_punch_camera_zoom(amount: f32) -> bool {
+    punch_camera_zoom_internal(amount)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_camera_shake(max_offset: f32, max_rotation: f32, frequency: f32) -> bool {
+    set_camera_shake_internal(max_offset, max_rotation, frequency)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_camera_effects_decay(trauma_decay: f32, zoom_decay: f32) -> bool {
+    set_camera_effects_decay_internal(trauma_decay, zoom_decay)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_camera_impact_shake(min_impulse: f32, trauma_per_impulse: f32) -> bool {
+    set_camera_impact_shake_internal(min_impulse, trauma_per_impulse)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_camera_effects() {
+    clear_camera_effects_internal();
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_sleep_enabled(enabled: bool) {
+    set_sleep_enabled_internal(enabled);
+}
+
+/// Make the body a conveyor moving at (vx, vy); zero stops it
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_surface_velocity(entity_id: u64, vx: f32, vy: f32) -> bool {
+    set_surface_velocity_internal(entity_id, vx, vy)
+}
+
+/// Fading trail through the body's last `length` positions in `color` (0xRRGGBBAA)
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_enable_trail(entity_id: u64, length: u32, color: u32) -> bool {
+    enable_trail_internal(entity_id, length, unpack_rgba(color))
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_disable_trail(entity_id: u64) -> bool {
+    disable_trail_internal(entity_id)
+}
+
+/// Predicted [x0, y0, x1, y1, ...] for a launch at (vx, vy); empty for
+/// unknown or non-dynamic bodies. `show` draws the arc as dots.
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_predict_trajectory(entity_id: u64, vx: f32, vy: f32, steps: u32, show: bool) -> Vec<f32> {
+    predict_trajectory_internal(entity_id, vx, vy, steps, show)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_trajectory_preview() {
+    clear_trajectory_preview_internal();
+}
+
+/// Press on a body, drag to aim and release to launch it
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_enable_slingshot_mode(enabled: bool) {
+    enable_slingshot_mode_internal(enabled);
+}
+
+/// Full-pull impulse (N·s), the pull (m) that reaches it and the arc length in steps
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_slingshot_params(max_impulse: f32, max_pull: f32, arc_samples: u32) -> bool {
+    set_slingshot_params_internal(max_impulse, max_pull, arc_samples)
+}
+
+/// Make the body a one-way platform bodies land on from above; false makes it solid
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_one_way(entity_id: u64, enabled: bool) -> bool {
+    set_one_way_internal(entity_id, enabled)
+}
+
+/// Let two bodies pass through each other
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_ignore_collision(entity_a: u64, entity_b: u64) -> bool {
+    set_collision_ignored_internal(entity_a, entity_b, true)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_restore_collision(entity_a: u64, entity_b: u64) -> bool {
+    set_collision_ignored_internal(entity_a, entity_b, false)
+}
+
+/// `value` 0 clears the cell; returns false for unknown layers or cells
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_tile(layer: u32, column: u32, row: u32, value: u8) -> bool {
+    set_tile_internal(layer, column, row, value)
+}
+
+/// -1 for unknown layers or cells off the grid
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_tile(layer: u32, column: u32, row: u32) -> i32 {
+    get_tile_internal(layer, column, row).map_or(-1, i32::from)
+}
+
+/// [column, row] under the world point, or an empty array off the grid
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_tile_cell_at(layer: u32, x: f32, y: f32) -> Vec<u32> {
+    tile_cell_at_internal(layer, x, y).map_or_else(Vec::new, |(column, row)| vec![column, row])
+}
+
+/// Returns the spring id, or 0 for unknown bodies or invalid arguments
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_create_spring_to_point(entity_id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> u64 {
+    create_spring_to_point_internal(entity_id, world_x, world_y, stiffness, damping).unwrap_or(0)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_update_spring(spring_id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> bool {
+    update_spring_internal(spring_id, world_x, world_y, stiffness, damping)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_remove_spring(spring_id: u64) -> bool {
+    remove_spring_internal(spring_id)
+}
+
+/// Spawn a box from a JSON scene body as an undoable step; 0 on errors
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_edit_spawn_body(json: &str) -> u64 {
+    edit_spawn_body_json_internal(json).unwrap_or_else(|e| {
+        log::error!("wasm_edit_spawn_body: {}", e);
+        0
+    })
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_edit_set_body(entity_id: u64, json: &str, merge: bool) -> bool {
+    match edit_body_json_internal(entity_id, json, merge) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("wasm_edit_set_body: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_edit_remove_body(entity_id: u64) -> bool {
+    edit_remove_body_internal(entity_id)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_edit_attach_spring(entity_id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> u64 {
+    edit_attach_spring_internal(entity_id, world_x, world_y, stiffness, damping).unwrap_or(0)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_body_desc(entity_id: u64) -> Option<String> {
+    body_desc_json_internal(entity_id)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_undo() -> bool {
+    undo_internal()
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_redo() -> bool {
+    redo_internal()
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_history() {
+    clear_history_internal();
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_history_state() -> String {
+    history_state_internal()
+}
 
-    surface.configure(&device, &config);
-    log::info!("Surface configured");
-    let (texture_view, sampler) = create_texture(&device, &queue);
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_clear_destructible(entity_id: u64) -> bool {
+    set_destructible_internal(entity_id, None)
+}
 
-    let texture_bind_group_layout =
-        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        multisampled: false,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-            label: Some("texture_bind_group_layout"),
-        });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_remove_soft_body(soft_body_id: u64) -> bool {
+    remove_soft_body_internal(soft_body_id)
+}
 
-    let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout: &texture_bind_group_layout,
-        entries: &[
-            wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(&texture_view),
-            },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: wgpu::BindingResource::Sampler(&sampler),
-            },
-        ],
-        label: Some("diffuse_bind_group"),
-    });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_wake_body(entity_id: u64) -> bool {
+    wake_body_internal(entity_id)
+}
 
-    // Shader setup
-    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Shader"),
-        source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("shader.wgsl"))),
-    });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_sleep_body(entity_id: u64) -> bool {
+    sleep_body_internal(entity_id)
+}
 
-    // --- Camera Setup ---
-    let camera = Camera {
-        eye: na::Point3::new(0.0, 0.0, 5.0),
-        target: na::Point3::new(0.0, 0.0, 0.0),
-        up: na::Vector3::y(),
-        aspect: config.width as f32 / config.height as f32,
-        fovy: 45.0,
-        znear: 0.1,
-        zfar: 100.0,
-        is_orthographic: true,
-        ortho_size: 2.2, // fits -1.1 to 1.1 roughly
-    };
+/// Returns [x, y, vx, vy, rotation, sleeping (0/1)], or an empty array for unknown ids
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_body_info(entity_id: u64) -> Vec<f32> {
+    body_info_internal(entity_id)
+        .map(|info| vec![info.x, info.y, info.vx, info.vy, info.rotation, info.sleeping as u8 as f32])
+        .unwrap_or_default()
+}
 
-    let mut camera_uniform = CameraUniform::new();
-    camera_uniform.update_view_proj(&camera);
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_query_aabb(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Vec<u64> {
+    query_aabb_internal(min_x, min_y, max_x, max_y, groups::ALL)
+}
 
-    let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Camera Buffer"),
-        contents: bytemuck::cast_slice(&[camera_uniform]),
-        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-    });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_query_circle(center_x: f32, center_y: f32, radius: f32) -> Vec<u64> {
+    query_circle_internal(center_x, center_y, radius, groups::ALL)
+}
 
-    let camera_bind_group_layout =
-        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-            label: Some("camera_bind_group_layout"),
-        });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_query_aabb_in_groups(min_x: f32, min_y: f32, max_x: f32, max_y: f32, mask: u32) -> Vec<u64> {
+    query_aabb_internal(min_x, min_y, max_x, max_y, mask)
+}
 
-    let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout: &camera_bind_group_layout,
-        entries: &[wgpu::BindGroupEntry {
-            binding: 0,
-            resource: camera_buffer.as_entire_binding(),
-        }],
-        label: Some("camera_bind_group"),
-    });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_query_circle_in_groups(center_x: f32, center_y: f32, radius: f32, mask: u32) -> Vec<u64> {
+    query_circle_internal(center_x, center_y, radius, mask)
+}
 
-    let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: Some("Render Pipeline Layout"),
-        bind_group_layouts: &[&texture_bind_group_layout, &camera_bind_group_layout],
-        push_constant_ranges: &[],
-    });
+/// Despawn the body after `seconds`; 0 or less cancels a pending lifetime
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_lifetime(entity_id: u64, seconds: f32) -> bool {
+    set_lifetime_internal(entity_id, seconds)
+}
 
-    let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Render Pipeline"),
-        layout: Some(&render_pipeline_layout),
-        vertex: wgpu::VertexState {
-            module: &shader,
-            entry_point: Some("vs_main"),
-            buffers: &[Vertex::desc(), Instance::desc()],
-            compilation_options: wgpu::PipelineCompilationOptions::default(),
-        },
-        fragment: Some(wgpu::FragmentState {
-            module: &shader,
-            entry_point: Some("fs_main"),
-            targets: &[Some(wgpu::ColorTargetState {
-                format: config.format,
-                blend: Some(wgpu::BlendState::REPLACE),
-                write_mask: wgpu::ColorWrites::ALL,
-            })],
-            compilation_options: wgpu::PipelineCompilationOptions::default(),
-        }),
-        primitive: wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleList,
-            strip_index_format: None,
-            front_face: wgpu::FrontFace::Ccw,
-            cull_mode: Some(wgpu::Face::Back),
-            polygon_mode: wgpu::PolygonMode::Fill,
-            unclipped_depth: false,
-            conservative: false,
-        },
-        depth_stencil: None,
-        multisample: wgpu::MultisampleState {
-            count: 1,
-            mask: !0,
-            alpha_to_coverage_enabled: false,
-        },
-        multiview: None,
-        cache: None,
-    });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_despawn_bounds(enabled: bool, min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> bool {
+    set_despawn_bounds_internal(enabled, min_x, min_y, max_x, max_y)
+}
 
-    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Vertex Buffer"),
-        contents: bytemuck::cast_slice(VERTICES),
-        usage: wgpu::BufferUsages::VERTEX,
-    });
+/// Quad without a body; returns its entity id, or undefined on failure
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_spawn_decoration(x: f32, y: f32, half_extent: f32, material_id: u32) -> Option<u64> {
+    spawn_decoration_internal(x, y, half_extent, material_id)
+}
 
-    let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Index Buffer"),
-        contents: bytemuck::cast_slice(INDICES),
-        usage: wgpu::BufferUsages::INDEX,
-    });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_remove_decoration(entity_id: u64) -> bool {
+    remove_decoration_internal(entity_id)
+}
 
-    // --- Instance Data Setup ---
-    const NUM_INSTANCES_PER_ROW: u32 = 10;
-    const NUM_INSTANCES: u32 = NUM_INSTANCES_PER_ROW * NUM_INSTANCES_PER_ROW;
-    let mut instances = Vec::new();
-    for y in 0..NUM_INSTANCES_PER_ROW {
-        for x in 0..NUM_INSTANCES_PER_ROW {
-            let position = [
-                (x as f32 / NUM_INSTANCES_PER_ROW as f32) * 2.0 - 1.0 + (1.0 / NUM_INSTANCES_PER_ROW as f32),
-                (y as f32 / NUM_INSTANCES_PER_ROW as f32) * 2.0 - 1.0 + (1.0 / NUM_INSTANCES_PER_ROW as f32),
-            ];
-            // Random-ish velocity
-            let velocity = [
-                (x as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0) * 0.1,
-                (y as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0) * 0.1,
-            ];
-            instances.push(Instance {
-                position,
-                velocity,
-                scale: 0.05,
-                rotation: 0.0,
-                uv_offset: [0.0, 0.0],
-                uv_scale: [1.0, 1.0],
-            });
+/// `child` follows `parent` at the offset, in the parent's frame
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_attach_entity(child_id: u64, parent_id: u64, offset_x: f32, offset_y: f32, rotation: f32) -> bool {
+    attach_entity_internal(child_id, parent_id, offset_x, offset_y, rotation)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_detach_entity(child_id: u64) -> bool {
+    detach_entity_internal(child_id)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_audio_events(enabled: bool, min_speed: f32, max_speed: f32) -> bool {
+    set_audio_events_internal(enabled, min_speed, max_speed)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_audio_listener(x: f32, y: f32) -> bool {
+    set_audio_listener_internal(x, y)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_audio_attenuation(reference_distance: f32, max_distance: f32, rolloff: f32) -> bool {
+    set_audio_attenuation_internal(reference_distance, max_distance, rolloff)
+}
+
+/// Bit `n` of `groups` puts the body in group `n`; returns false for unknown ids
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_body_groups(entity_id: u64, groups: u32) -> bool {
+    set_body_groups_internal(entity_id, groups)
+}
+
+/// Group bits of the body; 0 for unknown ids
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_body_groups(entity_id: u64) -> u32 {
+    body_groups_internal(entity_id).unwrap_or(0)
+}
+
+/// Ids of the bodies sharing a bit with `mask`; 0xffffffff matches every body
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_group_ids(mask: u32) -> Vec<u64> {
+    group_ids_internal(mask)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_despawn_group(mask: u32) -> u32 {
+    despawn_group_internal(mask)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_group_paused(mask: u32, paused: bool) -> u32 {
+    set_group_paused_internal(mask, paused)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_apply_impulse_to_group(mask: u32, impulse_x: f32, impulse_y: f32) -> u32 {
+    apply_impulse_to_group_internal(mask, impulse_x, impulse_y)
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_streaming(enabled: bool, chunk_size: f32, load_radius: u32, unload_radius: u32) {
+    set_streaming_internal(enabled, chunk_size, load_radius, unload_radius);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_streaming_focus_point(x: f32, y: f32) {
+    set_streaming_focus_internal(StreamFocus::Point { x, y });
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_streaming_focus_entity(entity_id: u64) {
+    set_streaming_focus_internal(StreamFocus::Entity(entity_id));
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_streaming_focus_camera() {
+    set_streaming_focus_internal(StreamFocus::Camera);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_get_state_checksum() -> u64 {
+    state_checksum_internal()
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_spawn_stress_scene(n_bodies: u32, layout: i32) -> bool {
+    spawn_stress_scene_internal(n_bodies, layout)
+}
+
+/// `json` as for `physics_core_register_prefab`; false on parse errors
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_register_prefab(name: &str, json: &str) -> bool {
+    match register_prefab_json_internal(name, json) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("wasm_register_prefab: {}", e);
+            false
         }
     }
+}
 
-    let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Instance Buffer"),
-        contents: bytemuck::cast_slice(&instances),
-        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-    });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_remove_prefab(name: &str) -> bool {
+    remove_prefab_internal(name)
+}
 
-    // --- Compute Pipeline Setup ---
-    let compute_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        entries: &[wgpu::BindGroupLayoutEntry {
-            binding: 0,
-            visibility: wgpu::ShaderStages::COMPUTE,
-            ty: wgpu::BindingType::Buffer {
-                ty: wgpu::BufferBindingType::Storage { read_only: false },
-                has_dynamic_offset: false,
-                min_binding_size: None,
-            },
-            count: None,
-        }],
-        label: Some("compute_bind_group_layout"),
-    });
+/// Returns the prefab's own body, or 0 when it cannot spawn
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_spawn_prefab(name: &str, x: f32, y: f32, angle: f32) -> u64 {
+    spawn_prefab_internal(name, x, y, angle).unwrap_or_else(|e| {
+        log::error!("wasm_spawn_prefab: {}", e);
+        0
+    })
+}
 
-    let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout: &compute_bind_group_layout,
-        entries: &[wgpu::BindGroupEntry {
-            binding: 0,
-            resource: instance_buffer.as_entire_binding(),
-        }],
-        label: Some("compute_bind_group"),
-    });
+/// Keep `bytes` as asset `name` for scenes and textures to read; see
+/// `wasm_fetch_asset` to fetch it instead
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_preload_asset(name: &str, bytes: &[u8]) -> bool {
+    match assets::preload(name, bytes.to_vec()) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("wasm_preload_asset: {}", e);
+            false
+        }
+    }
+}
 
-    let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: Some("Compute Pipeline Layout"),
-        bind_group_layouts: &[&compute_bind_group_layout],
-        push_constant_ranges: &[],
-    });
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_unload_asset(name: &str) -> bool {
+    assets::unload(name)
+}
 
-    let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-        label: Some("Compute Pipeline"),
-        layout: Some(&compute_pipeline_layout),
-        module: &shader,
-        entry_point: Some("update_instances"),
-        compilation_options: wgpu::PipelineCompilationOptions::default(),
-        cache: None,
-    });
+/// Register preloaded texture asset `name` as a material; returns its id, or 0
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_load_texture_asset(name: &str) -> u32 {
+    load_texture_asset_internal(name).unwrap_or_else(|e| {
+        log::error!("wasm_load_texture_asset: {}", e);
+        material::DEFAULT_MATERIAL
+    })
+}
 
-    let device = Arc::new(device);
-    let queue = Arc::new(queue);
+/// Returns the compile/runtime error message, or an empty string on success
+#[cfg(all(feature = "wasm_support", feature = "scripting"))]
+#[wasm_bindgen]
+pub fn wasm_load_script(source: &str) -> String {
+    scripting::load_script(source).err().unwrap_or_default()
+}
 
-    let bevy_3d_rend = Bevy3DSample::new(
-        &device,
-        &queue,
-        &camera_bind_group_layout,
-        &adapter_info,
-        config.format,
-        config.width,
-        config.height,
-    );
+#[cfg(all(feature = "wasm_support", feature = "scripting"))]
+#[wasm_bindgen]
+pub fn wasm_unload_script() {
+    scripting::unload_script();
+}
 
-    let state = WgpuState {
-        instance,
-        device,
-        queue,
-        surface,
-        config,
-        render_pipeline,
-        vertex_buffer,
-        index_buffer,
-        instance_buffer,      // NEW
-        diffuse_bind_group,
-        compute_bind_group,   // NEW
-        compute_pipeline,     // NEW
-        num_instances: NUM_INSTANCES, // NEW
-        window_ptr: std::ptr::null_mut(),
-        #[cfg(target_arch = "wasm32")]
-        last_render_time: web_sys::window().unwrap().performance().unwrap().now(),
-        #[cfg(not(target_arch = "wasm32"))]
-        last_render_time: std::time::Instant::now(),
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_rewind(steps: u32) -> u32 {
+    rewind_internal(steps)
+}
 
-        frame_count: 0,
-        #[cfg(target_arch = "wasm32")]
-        last_fps_log_time: web_sys::window().unwrap().performance().unwrap().now(),
-        #[cfg(not(target_arch = "wasm32"))]
-        last_fps_log_time: std::time::Instant::now(),
-        
-        scale_factor: 1.0,
-        egui_renderer: None,
-        camera,
-        camera_uniform,
-        camera_buffer,
-        camera_bind_group,
-        bevy_3d_sample: Some(bevy_3d_rend),
-    };
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_rewind_history(capacity: u32) {
+    set_rewind_history_internal(capacity);
+}
 
-    if let Ok(mut guard) = WGPU_STATE.lock() {
-        guard.0 = Some(state);
-    }
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_step_once() {
+    step_once_internal();
+}
 
-    match INITIALIZED.compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed) {
-        Ok(_) => log::info!("WASM wgpu initialized successfully"),
-        Err(_) => log::warn!("WASM wgpu already initialized"),
-    }
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_fast_forward(steps: u32) -> u32 {
+    fast_forward_internal(steps)
+}
 
-    // Initialize physics simulation
-    init_physics();
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_time_scale_ramp(target: f32, seconds: f32) {
+    set_time_scale_ramp_internal(target, seconds);
+}
 
-    true
+/// Substeps per step; `auto_substeps` raises them above `velocity_threshold` m/s
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_substeps(substeps: u32, auto_substeps: bool, velocity_threshold: f32, max_substeps: u32) -> bool {
+    set_substeps_internal(SubstepConfig {
+        substeps,
+        auto: auto_substeps,
+        velocity_threshold,
+        max_substeps,
+    })
 }
 
+/// `erp` 0 keeps Rapier's default contact stiffness; returns false if not running
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_update(delta_time: f32) {
-    wgpu_update(delta_time);
+pub fn wasm_set_solver_config(
+    solver_iterations: u32,
+    friction_iterations: u32,
+    internal_pgs_iterations: u32,
+    erp: f32,
+    allowed_linear_error: f32,
+    max_ccd_substeps: u32,
+    contact_damping: f32,
+) -> bool {
+    set_solver_config_internal(SolverConfig {
+        solver_iterations,
+        friction_iterations,
+        internal_pgs_iterations,
+        erp,
+        allowed_linear_error,
+        max_ccd_substeps,
+        contact_damping,
+    })
 }
 
+/// `x`, `y` in CSS pixels like `wasm_on_pointer_event`; `time_ms` is the
+/// DOM event timestamp
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_render() {
-    // log::info!("wasm_render called");
-    render_internal(None);
+pub fn wasm_on_touch_event(action: i32, pointer_id: i32, x: f32, y: f32, time_ms: f64) {
+    let scale = scale_factor();
+    on_touch_event_internal(action, pointer_id, x * scale, y * scale, time_ms / 1000.0);
 }
 
+/// `window.devicePixelRatio`; call again when it changes (zoom, moving the
+/// window to another display)
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_resize(width: u32, height: u32) {
-    if width == 0 || height == 0 {
-        return;
-    }
+pub fn wasm_set_scale_factor(scale_factor: f32) -> bool {
+    set_scale_factor_internal(scale_factor)
+}
 
-    if let Ok(mut guard) = WGPU_STATE.lock() {
-        if let Some(state) = guard.0.as_mut() {
-            let max_dimension = state.device.limits().max_texture_dimension_2d;
-            let clamped_width = width.min(max_dimension);
-            let clamped_height = height.min(max_dimension);
+/// `level` and `charging` from the Battery Status API where there is one
+/// (-1 for an unknown level); browsers report no thermal status, so pass 0
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_power_state(level: f32, charging: bool, thermal_status: i32, low_power_mode: bool) -> bool {
+    set_power_state_internal(level, charging, thermal_status, low_power_mode)
+}
 
-            log::info!(
-                "wasm_resize: {}x{} -> clamped {}x{}",
-                width,
-                height,
-                clamped_width,
-                clamped_height
-            );
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_low_power_mode(enabled: bool) {
+    set_low_power_mode_internal(enabled);
+}
 
-            state.config.width = clamped_width;
-            state.config.height = clamped_height;
-            state.surface.configure(&state.device, &state.config);
-        }
-    }
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_set_governor_enabled(enabled: bool) {
+    set_governor_enabled_internal(enabled);
 }
 
+/// Power state, throttle level and the limits in force as a JSON string
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_shutdown() {
-    wgpu_shutdown();
+pub fn wasm_get_governor_stats() -> String {
+    governor_stats_internal()
 }
 
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_set_gravity(y: f32) {
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            physics.gravity.y = -y;
-        }
-    }
+pub fn wasm_set_gestures(tap_spawn: bool, fling: bool, pinch_zoom: bool, pan: bool) {
+    set_gestures_internal(tap_spawn, fling, pinch_zoom, pan);
 }
 
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_set_time_scale(scale: f32) {
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            physics.time_scale = scale;
-        }
-    }
+pub fn wasm_set_gravity_vector(x: f32, y: f32, z: f32) {
+    set_gravity_vector_internal(x, y, z);
 }
 
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_set_paused(paused: bool) {
-    if let Ok(mut guard) = PHYSICS_STATE.lock() {
-        if let Some(physics) = guard.0.as_mut() {
-            physics.paused = paused;
-        }
-    }
+pub fn wasm_set_gravity_preset(preset: i32) -> bool {
+    set_gravity_preset_internal(preset)
 }
 
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_reset_simulation() {
-    init_physics();
+pub fn wasm_set_world_scale(pixels_per_meter: f32) -> bool {
+    set_world_scale_internal(pixels_per_meter)
 }
 
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_on_pointer_event(event_type: i32, x: f32, y: f32, button: i32) {
-    on_pointer_event_internal(event_type, x, y, button);
+pub fn wasm_set_world_scale_ndc(ndc_per_meter: f32) -> bool {
+    set_world_scale_ndc_internal(ndc_per_meter)
 }
 
 #[cfg(feature = "wasm_support")]
 #[wasm_bindgen]
-pub fn wasm_on_key_event(event_type: i32, key_code: i32) {
-    on_key_event_internal(event_type, key_code);
+pub fn wasm_set_tilt_gravity(enabled: bool, strength: f32) {
+    set_tilt_gravity_internal(enabled, strength);
+}
+
+#[cfg(feature = "wasm_support")]
+#[wasm_bindgen]
+pub fn wasm_on_accelerometer(x: f32, y: f32, z: f32, rotation_degrees: i32) {
+    on_accelerometer_internal(x, y, z, rotation_degrees);
+}
+
+#[cfg(all(feature = "wasm_support", target_arch = "wasm32"))]
+thread_local! {
+    static DEVICE_MOTION_LISTENER: std::cell::RefCell<Option<Closure<dyn FnMut(web_sys::DeviceMotionEvent)>>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Listen for `devicemotion` on the window and feed it to the tilt gravity.
+/// iOS Safari only delivers the event after `DeviceMotionEvent.requestPermission()`
+/// has been granted from a user gesture, which the page must do itself.
+#[cfg(all(feature = "wasm_support", target_arch = "wasm32"))]
+#[wasm_bindgen]
+pub fn wasm_enable_device_motion(enabled: bool, strength: f32) {
+    use wasm_bindgen::JsCast;
+
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+    DEVICE_MOTION_LISTENER.with(|slot| {
+        if let Some(listener) = slot.borrow_mut().take() {
+            let _ = window.remove_event_listener_with_callback("devicemotion", listener.as_ref().unchecked_ref());
+        }
+        set_tilt_gravity_internal(enabled, strength);
+        if !enabled {
+            return;
+        }
+
+        let listener = Closure::<dyn FnMut(web_sys::DeviceMotionEvent)>::new(|event: web_sys::DeviceMotionEvent| {
+            let accel = match event.acceleration_including_gravity() {
+                Some(accel) => accel,
+                None => return,
+            };
+            let rotation = web_sys::window()
+                .and_then(|w| w.screen().ok())
+                .map_or(0, |screen| screen.orientation().angle().unwrap_or(0) as i32);
+            on_accelerometer_internal(
+                accel.x().unwrap_or(0.0) as f32,
+                accel.y().unwrap_or(0.0) as f32,
+                accel.z().unwrap_or(0.0) as f32,
+                rotation,
+            );
+        });
+        match window.add_event_listener_with_callback("devicemotion", listener.as_ref().unchecked_ref()) {
+            Ok(()) => *slot.borrow_mut() = Some(listener),
+            Err(e) => log::warn!("devicemotion listener failed: {:?}", e),
+        }
+    });
 }
 
-// --- Winit Standalone App (for JVM Debugging) ---
+// --- Winit Standalone App (for JVM Debugging and the physicsfx binary) ---
+
+/// Simulate a stress scene of `benchmark.bodies` boxes for
+/// `benchmark.frames` fixed steps with no window or GPU, timing each update
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_headless_benchmark(benchmark: cli::Benchmark) -> cli::BenchmarkReport {
+    let scene = stress::generate(benchmark.bodies, stress::StressLayout::Rain);
+    init_physics_scene(&scene.positions, scene.half_extent);
+    let frame_ms: Vec<f64> = (0..benchmark.frames)
+        .map(|_| {
+            let start = std::time::Instant::now();
+            update_internal(time_control::STEP_DT);
+            start.elapsed().as_secs_f64() * 1000.0
+        })
+        .collect();
+    cli::BenchmarkReport::from_frame_times(benchmark.bodies, &frame_ms)
+}
 
+/// The winit app with the default window and GPU options
 #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
 pub fn start_winit_app() {
+    run_desktop_app(cli::DesktopOptions::default());
+}
+
+/// Open a window per `options` and run the simulation in it until it is closed
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+pub fn run_desktop_app(options: cli::DesktopOptions) {
     use std::sync::Arc;
     use winit::{
         application::ApplicationHandler,
@@ -2430,9 +13212,116 @@ pub fn start_winit_app() {
         window::{Window, WindowAttributes, WindowId},
     };
 
+    const WINDOW_TITLE: &str = "BombzBlast! (Rust Winit)";
+
     struct App {
         window: Option<Arc<Window>>,
         last_frame_time: std::time::Instant,
+        options: cli::DesktopOptions,
+        /// Last cursor position in physical pixels
+        cursor: [f32; 2],
+        modifiers: winit::keyboard::ModifiersState,
+        /// Bodies picked with Shift+click, for dropped textures
+        selection: Vec<u64>,
+        /// The left button went down with Shift, so its release is ours too
+        selecting: bool,
+        /// Box copied in the scene editor
+        clipboard: Option<SceneBody>,
+    }
+
+    impl App {
+        /// Scene editor shortcuts: Ctrl+Z undoes, Ctrl+Shift+Z or Ctrl+Y
+        /// redoes, Ctrl+C copies the selected box and Ctrl+V pastes it under
+        /// the cursor
+        fn editor_shortcut(&mut self, key: winit::keyboard::KeyCode) {
+            use winit::keyboard::KeyCode;
+            match key {
+                KeyCode::KeyZ if !self.modifiers.shift_key() => {
+                    undo_internal();
+                }
+                KeyCode::KeyZ | KeyCode::KeyY => {
+                    redo_internal();
+                }
+                KeyCode::KeyC => {
+                    let selected = EDITOR.lock().ok().and_then(|editor| editor.selected());
+                    if let Some(body) = selected.and_then(scene_body_internal) {
+                        self.clipboard = Some(body);
+                    }
+                }
+                KeyCode::KeyV => {
+                    let (Some(body), Some([x, y])) = (self.clipboard, screen_to_world(self.cursor[0], self.cursor[1])) else {
+                        return;
+                    };
+                    if let Some(id) = edit_spawn_body_internal(&SceneBody { x, y, ..body }) {
+                        if let Ok(mut editor) = EDITOR.lock() {
+                            editor.select(Some(id));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        /// Add the body under the cursor to the selection or take it out
+        /// again; clicking empty space clears the selection
+        fn toggle_selection(&mut self) {
+            match body_at_screen_internal(self.cursor[0], self.cursor[1]) {
+                Some(id) => match self.selection.iter().position(|&selected| selected == id) {
+                    Some(index) => {
+                        self.selection.remove(index);
+                    }
+                    None => self.selection.push(id),
+                },
+                None => self.selection.clear(),
+            }
+            self.show_selection();
+        }
+
+        fn show_selection(&self) {
+            if let Some(win) = self.window.as_ref() {
+                match self.selection.len() {
+                    0 => win.set_title(WINDOW_TITLE),
+                    count => win.set_title(&format!("{} - {} selected", WINDOW_TITLE, count)),
+                }
+            }
+        }
+
+        /// A dropped .json or .ron scene replaces the current one. A dropped
+        /// .png or .ktx2 becomes a material for the selected bodies, or for
+        /// the body under the cursor when none are selected.
+        fn drop_file(&mut self, path: &std::path::Path) {
+            let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default().to_ascii_lowercase();
+            match extension.as_str() {
+                "json" | "ron" => match load_scene_file_internal(path) {
+                    Ok(bodies) => {
+                        log::info!("Loaded {} with {} bodies", path.display(), bodies);
+                        self.selection.clear();
+                        self.show_selection();
+                    }
+                    Err(e) => log::error!("Scene not loaded: {}", e),
+                },
+                "png" | "ktx2" => {
+                    let material_id = match load_texture_file_internal(path) {
+                        Ok(material_id) => material_id,
+                        Err(e) => {
+                            log::error!("Texture not loaded: {}", e);
+                            return;
+                        }
+                    };
+                    let textured = if self.selection.is_empty() {
+                        body_at_screen_internal(self.cursor[0], self.cursor[1])
+                            .map_or(0, |id| set_entity_material_internal(id, material_id) as usize)
+                    } else {
+                        // Bodies removed since they were picked drop out of the selection
+                        self.selection.retain(|&id| set_entity_material_internal(id, material_id));
+                        self.show_selection();
+                        self.selection.len()
+                    };
+                    log::info!("Registered {} as material {} on {} bodies", path.display(), material_id, textured);
+                }
+                _ => log::warn!("Dropped {} is neither a scene (.json, .ron) nor a texture (.png, .ktx2)", path.display()),
+            }
+        }
     }
 
     impl ApplicationHandler for App {
@@ -2442,8 +13331,8 @@ pub fn start_winit_app() {
                     target
                         .create_window(
                             WindowAttributes::default()
-                                .with_title("BombzBlast! (Rust Winit)")
-                                .with_inner_size(winit::dpi::LogicalSize::new(WIDTH, HEIGHT)),
+                                .with_title(WINDOW_TITLE)
+                                .with_inner_size(winit::dpi::LogicalSize::new(self.options.width, self.options.height)),
                         )
                         .unwrap(),
                 );
@@ -2465,6 +13354,13 @@ pub fn start_winit_app() {
                 let window_handle = win.window_handle().unwrap().as_raw();
                 let display_handle = win.display_handle().unwrap().as_raw();
 
+                if let Ok(mut gpu_options) = GPU_INIT_OPTIONS.lock() {
+                    if self.options.backends != 0 {
+                        gpu_options.backends = gpu_options::backends_from_bits(self.options.backends);
+                    }
+                    gpu_options.vsync = self.options.vsync;
+                }
+                set_scale_factor_internal(win.scale_factor() as f32);
                 if !init_wgpu_internal(
                     window_handle,
                     display_handle,
@@ -2476,6 +13372,12 @@ pub fn start_winit_app() {
                     log::error!("Failed to initialize wgpu");
                     return;
                 }
+                if let Some(path) = self.options.scene.as_deref() {
+                    match load_scene_file_internal(path) {
+                        Ok(bodies) => log::info!("Loaded {} with {} bodies", path.display(), bodies),
+                        Err(e) => log::error!("Scene not loaded: {}", e),
+                    }
+                }
 
                 self.window = Some(win);
             }
@@ -2501,7 +13403,12 @@ pub fn start_winit_app() {
             }
 
             match event {
-                WindowEvent::CloseRequested => target.exit(),
+                WindowEvent::CloseRequested => {
+                    if let Err(e) = save_settings_internal() {
+                        log::warn!("Settings not saved: {}", e);
+                    }
+                    target.exit();
+                }
 
                 WindowEvent::Resized(size) => {
                     let width = size.width;
@@ -2525,8 +13432,39 @@ pub fn start_winit_app() {
                     std::thread::sleep(std::time::Duration::from_millis(10));
                 }
 
+                WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                    set_scale_factor_internal(scale_factor as f32);
+                }
+
                 WindowEvent::CursorMoved { position, .. } => {
-                    on_pointer_event_internal(1, position.x as f32, position.y as f32, 0);
+                    self.cursor = [position.x as f32, position.y as f32];
+                    if !editor_pointer_internal(1, position.x as f32, position.y as f32) {
+                        on_pointer_event_internal(1, position.x as f32, position.y as f32, 0);
+                    }
+                }
+
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    self.modifiers = modifiers.state();
+                }
+
+                WindowEvent::DroppedFile(path) => {
+                    self.drop_file(&path);
+                }
+
+                // The scene editor takes the left button while it is on
+                WindowEvent::MouseInput { state, button: winit::event::MouseButton::Left, .. } if editor_enabled() => {
+                    let et = if state == winit::event::ElementState::Pressed { 0 } else { 2 };
+                    editor_pointer_internal(et, self.cursor[0], self.cursor[1]);
+                }
+
+                // Shift+click picks bodies instead of reaching the scene
+                WindowEvent::MouseInput { state, button: winit::event::MouseButton::Left, .. }
+                    if self.selecting || (state == winit::event::ElementState::Pressed && self.modifiers.shift_key()) =>
+                {
+                    self.selecting = state == winit::event::ElementState::Pressed;
+                    if self.selecting {
+                        self.toggle_selection();
+                    }
                 }
 
                 WindowEvent::MouseInput { state, button, .. } => {
@@ -2544,6 +13482,34 @@ pub fn start_winit_app() {
                     on_pointer_event_internal(et, -1.0, -1.0, b);
                 }
 
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let lines = match delta {
+                        winit::event::MouseScrollDelta::LineDelta(_, y) => y,
+                        winit::event::MouseScrollDelta::PixelDelta(position) => position.y as f32 / 40.0,
+                    };
+                    // Only the 3D demo's camera zooms with the wheel
+                    orbit_3d_camera_internal(0.0, 0.0, 1.1f32.powf(lines));
+                }
+
+                WindowEvent::KeyboardInput { event, .. }
+                    if editor_enabled()
+                        && event.state == winit::event::ElementState::Pressed
+                        && (self.modifiers.control_key() || self.modifiers.super_key())
+                        && matches!(
+                            event.physical_key,
+                            winit::keyboard::PhysicalKey::Code(
+                                winit::keyboard::KeyCode::KeyZ
+                                    | winit::keyboard::KeyCode::KeyY
+                                    | winit::keyboard::KeyCode::KeyC
+                                    | winit::keyboard::KeyCode::KeyV
+                            )
+                        ) =>
+                {
+                    if let winit::keyboard::PhysicalKey::Code(key) = event.physical_key {
+                        self.editor_shortcut(key);
+                    }
+                }
+
                 WindowEvent::KeyboardInput { event, .. } => {
                     let et = if event.state == winit::event::ElementState::Pressed {
                         0
@@ -2551,6 +13517,9 @@ pub fn start_winit_app() {
                         1
                     };
                     if let winit::keyboard::PhysicalKey::Code(key_code) = event.physical_key {
+                        if et == 0 && !event.repeat {
+                            on_screenshot_key(key_code as i32);
+                        }
                         on_key_event_internal(et, key_code as i32);
                     }
                 }
@@ -2570,6 +13539,12 @@ pub fn start_winit_app() {
     let mut app = App {
         window: None,
         last_frame_time: std::time::Instant::now(),
+        options,
+        cursor: [0.0; 2],
+        modifiers: Default::default(),
+        selection: Vec::new(),
+        selecting: false,
+        clipboard: None,
     };
     event_loop.run_app(&mut app).unwrap();
 }
@@ -2584,22 +13559,25 @@ pub extern "system" fn Java_app_kamkash_physicsfx_JvmWgpuGameLoop_nativeStartWin
 
     _class: JClass,
 ) {
-    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-    start_winit_app();
+    crash::guard("JvmWgpuGameLoop.nativeStartWinitApp", || {
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+        start_winit_app();
+    })
 }
 
 #[cfg(target_os = "android")]
 #[no_mangle]
 // #[::android_activity::android_main]
 pub extern "C" fn android_main(app: AndroidApp) {
-    android_logger::init_once(
-        Config::default()
-            .with_max_level(LevelFilter::Trace)
-            .with_tag("PhysicsFX"),
-    );
+    init_logging();
+    crash::install_hook();
+
+    if let Some(dir) = app.internal_data_path() {
+        set_settings_directory_internal(dir.to_str());
+        set_crash_directory_internal(dir.to_str());
+    }
 
     let mut quit = false;
-    let mut suspended = false;
     let mut redraw_requested = true;
     let mut last_frame_time = std::time::Instant::now();
 
@@ -2619,6 +13597,26 @@ pub extern "C" fn android_main(app: AndroidApp) {
                             let pointer = motion.pointer_at_index(0);
                             on_pointer_event_internal(et, pointer.x(), pointer.y(), 0);
                         }
+
+                        // Gesture layer sees every pointer, not just the primary one
+                        let time = motion.event_time() as f64 / 1_000_000_000.0;
+                        match action {
+                            MotionAction::Down | MotionAction::PointerDown | MotionAction::Up | MotionAction::PointerUp => {
+                                let pointer = motion.pointer_at_index(motion.pointer_index());
+                                let touch_action = match action {
+                                    MotionAction::Down | MotionAction::PointerDown => 0,
+                                    _ => 2,
+                                };
+                                on_touch_event_internal(touch_action, pointer.pointer_id(), pointer.x(), pointer.y(), time);
+                            }
+                            MotionAction::Move => {
+                                for pointer in motion.pointers() {
+                                    on_touch_event_internal(1, pointer.pointer_id(), pointer.x(), pointer.y(), time);
+                                }
+                            }
+                            MotionAction::Cancel => on_touch_event_internal(3, 0, 0.0, 0.0, time),
+                            _ => {}
+                        }
                     }
                     InputEvent::KeyEvent(key) => {
                         let action = key.action();
@@ -2648,22 +13646,23 @@ pub extern "C" fn android_main(app: AndroidApp) {
 
                 PollEvent::Main(MainEvent::TerminateWindow { .. }) => {
                     log::info!("MainEvent::TerminateWindow");
+                    // Paused by now, so the scene is kept for the next window
                     shutdown_internal();
                 }
 
                 PollEvent::Main(MainEvent::Pause) => {
                     log::info!("MainEvent::Pause");
-                    suspended = true;
+                    on_app_pause_internal(true);
                 }
 
                 PollEvent::Main(MainEvent::Resume { .. }) => {
                     log::info!("MainEvent::Resume");
-                    suspended = false;
+                    on_app_resume_internal();
                 }
 
                 PollEvent::Main(MainEvent::InitWindow { .. }) => {
                     log::info!("MainEvent::InitWindow");
-                    suspended = false; // Ensure we are not suspended if we get a new window
+                    on_app_resume_internal(); // Ensure we are not paused if we get a new window
                     if let Some(window) = app.native_window() {
                         let window_ptr = window.ptr().as_ptr();
 
@@ -2766,6 +13765,7 @@ pub extern "C" fn android_main(app: AndroidApp) {
         );
 
         let init_flag = INITIALIZED.load(Ordering::Relaxed);
+        let suspended = app_paused();
         if !suspended && init_flag {
             let now = std::time::Instant::now();
             let dt = now.duration_since(last_frame_time).as_secs_f32();
@@ -0,0 +1,56 @@
+//! App lifecycle: pausing in the background without shutting down
+//!
+//! Hosts report the app leaving the foreground (Android `onPause`, iOS
+//! `applicationWillResignActive`, the page turning hidden on
+//! `visibilitychange`) and coming back. While paused no frame is rendered;
+//! the simulation either freezes or keeps stepping on whatever updates the
+//! host still makes, for games that must keep up with a server. Nothing is
+//! torn down: the simulation, settings and, while the surface lives, every
+//! GPU resource stay as they are. A host whose surface is destroyed while
+//! paused shuts down and inits again as usual; the simulation and what
+//! survives a device rebuild (camera, materials, views, models) then carry
+//! over to the new surface instead of starting a new scene.
+//!
+//! Host clocks keep running through a pause, so the first update after
+//! resuming is an empty frame rather than one step spanning the whole pause.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lifecycle {
+    paused: bool,
+    freeze_physics: bool,
+    /// Resumed since the last update
+    resumed: bool,
+}
+
+impl Lifecycle {
+    /// True when the app was running. Pausing again only changes `freeze_physics`.
+    pub fn pause(&mut self, freeze_physics: bool) -> bool {
+        self.freeze_physics = freeze_physics;
+        !std::mem::replace(&mut self.paused, true)
+    }
+
+    /// True when the app was paused
+    pub fn resume(&mut self) -> bool {
+        if !self.paused {
+            return false;
+        }
+        self.paused = false;
+        self.resumed = true;
+        true
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Frame time an update of `dt` seconds runs with, or None to skip it
+    pub fn frame_dt(&mut self, dt: f32) -> Option<f32> {
+        if self.paused && self.freeze_physics {
+            return None;
+        }
+        if std::mem::take(&mut self.resumed) {
+            return Some(0.0);
+        }
+        Some(dt)
+    }
+}
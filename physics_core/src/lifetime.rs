@@ -0,0 +1,56 @@
+//! Timed and out-of-bounds despawning
+//!
+//! A body with a `Lifetime` is despawned once its time runs out. With
+//! `DespawnBounds` set, every enabled dynamic body whose center leaves the
+//! rectangle is despawned as well, whether or not it has a lifetime, so
+//! debris flung off screen does not keep costing solver time. Despawning
+//! removes the rigid body with its colliders and joints, then the entity,
+//! and the instance layout is rebuilt on the next sync.
+//!
+//! Pooled projectiles are never despawned here; their pool parks them. Other
+//! disabled bodies are outside the streamed chunks and are left alone too.
+
+use bevy_ecs::prelude::*;
+
+/// Seconds left before the body is despawned
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Lifetime {
+    pub remaining: f32,
+}
+
+impl Lifetime {
+    /// None for durations that would never expire or already have
+    pub fn new(seconds: f32) -> Option<Self> {
+        (seconds.is_finite() && seconds > 0.0).then_some(Self { remaining: seconds })
+    }
+
+    /// Count down by `dt`; true once the time is up
+    pub fn tick(&mut self, dt: f32) -> bool {
+        self.remaining -= dt;
+        self.remaining <= 0.0
+    }
+}
+
+/// Rectangle bodies must stay inside
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DespawnBounds {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl DespawnBounds {
+    /// Bounds spanned by two corners in any order; None unless finite and
+    /// non-empty
+    pub fn new(a: [f32; 2], b: [f32; 2]) -> Option<Self> {
+        if !a.iter().chain(&b).all(|v| v.is_finite()) {
+            return None;
+        }
+        let min = [a[0].min(b[0]), a[1].min(b[1])];
+        let max = [a[0].max(b[0]), a[1].max(b[1])];
+        (min[0] < max[0] && min[1] < max[1]).then_some(Self { min, max })
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        (self.min[0]..=self.max[0]).contains(&x) && (self.min[1]..=self.max[1]).contains(&y)
+    }
+}
@@ -0,0 +1,66 @@
+//! Level of detail for bodies far from the streaming focus
+//!
+//! Streamed worlds keep many bodies alive well away from where the player
+//! is (see `streaming`). Past `render_distance` from the focus a body is
+//! drawn as a plain quad in the color at the middle of its sprite, which
+//! saves texture fetches on mobile GPUs. Past `physics_distance` its sleep
+//! thresholds are raised by `sleep_bias`, so it settles and stops costing
+//! solver time sooner. A far body has to come `HYSTERESIS` of the way back
+//! inside a threshold before it gets full detail again, so one hovering on
+//! the edge does not flicker.
+//!
+//! Scenes set the thresholds (`SceneDesc::lod`); they survive a reset like
+//! the streaming settings.
+
+use serde::Deserialize;
+
+/// Fraction of a threshold a far body must come back inside to be near again
+pub const HYSTERESIS: f32 = 0.1;
+/// Largest `sleep_bias`; beyond it far bodies would sleep mid-fall
+pub const MAX_SLEEP_BIAS: f32 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LodConfig {
+    /// Farther bodies are drawn as plain quads; None draws all in full
+    pub render_distance: Option<f32>,
+    /// Farther bodies fall asleep sooner; None simulates all alike
+    pub physics_distance: Option<f32>,
+    /// Multiplies the sleep thresholds of bodies past `physics_distance`
+    /// and divides the time they must rest before sleeping
+    pub sleep_bias: f32,
+}
+
+impl Default for LodConfig {
+    fn default() -> Self {
+        Self {
+            render_distance: None,
+            physics_distance: None,
+            sleep_bias: 4.0,
+        }
+    }
+}
+
+impl LodConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for distance in [self.render_distance, self.physics_distance].into_iter().flatten() {
+            if !distance.is_finite() || distance <= 0.0 {
+                return Err(format!("LOD distance {} must be positive", distance));
+            }
+        }
+        if !(1.0..=MAX_SLEEP_BIAS).contains(&self.sleep_bias) {
+            return Err(format!("sleep bias must be between 1 and {}", MAX_SLEEP_BIAS));
+        }
+        Ok(())
+    }
+}
+
+/// Whether something `distance` from the focus is past `threshold`, given
+/// whether it was the last time it was checked
+pub fn is_far(threshold: Option<f32>, distance: f32, was_far: bool) -> bool {
+    match threshold {
+        Some(threshold) if was_far => distance > threshold * (1.0 - HYSTERESIS),
+        Some(threshold) => distance > threshold,
+        None => false,
+    }
+}
@@ -0,0 +1,229 @@
+//! In-app log console
+//!
+//! Logs go to files on desktop, logcat on Android and the browser console on
+//! the web, none of which someone holding the device can see. `CaptureLogger`
+//! sits in front of the platform logger: it hands every record on and keeps
+//! the last `CAPACITY` lines in a ring buffer, which the egui console window
+//! and `get_recent_logs` on the bindings read back.
+//!
+//! Only records the platform logger accepts are kept, so the console shows
+//! what the log files would. A runtime filter (see `log_filter`) replaces
+//! the platform logger's own levels until it is cleared. Nothing here logs,
+//! so a record can never come back in while a lock is held.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, RwLock};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::Lazy;
+use serde::{Serialize, Serializer};
+
+use crate::log_filter::LogFilter;
+
+/// Lines kept; older ones are dropped first
+pub const CAPACITY: usize = 500;
+/// Target prefix of the records `tracing` writes for span enter and exit
+const SPAN_TARGET: &str = "tracing::span";
+
+static BUFFER: Lazy<Mutex<LogBuffer>> = Lazy::new(|| Mutex::new(LogBuffer::new(CAPACITY)));
+
+// The runtime filter, and the max level `install` set for when it is cleared
+static FILTER: RwLock<Option<LogFilter>> = RwLock::new(None);
+static BASE_LEVEL: Mutex<LevelFilter> = Mutex::new(LevelFilter::Info);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogLine {
+    #[serde(serialize_with = "serialize_level")]
+    pub level: Level,
+    /// Module that logged, e.g. `physics_core::scenes`
+    pub target: String,
+    pub message: String,
+}
+
+fn serialize_level<S: Serializer>(level: &Level, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(level.as_str())
+}
+
+impl LogLine {
+    /// `LEVEL target: message`, as copied to the clipboard
+    pub fn to_text(&self) -> String {
+        format!("{:<5} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+/// The most recent lines, oldest first
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    lines: VecDeque<LogLine>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self { lines: VecDeque::with_capacity(capacity), capacity: capacity.max(1) }
+    }
+
+    pub fn push(&mut self, line: LogLine) {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// Lines at `max_level` or more severe, oldest first
+    pub fn filtered(&self, max_level: LevelFilter) -> impl Iterator<Item = &LogLine> {
+        self.lines.iter().filter(move |line| line.level <= max_level)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+/// Keeps each record it accepts in the shared buffer before passing it to
+/// the platform logger. It accepts what the runtime filter allows, or else
+/// what the platform logger does; with neither it accepts everything.
+pub struct CaptureLogger {
+    inner: Option<Box<dyn Log>>,
+}
+
+impl CaptureLogger {
+    pub fn new(inner: Option<Box<dyn Log>>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if let Some(filter) = FILTER.read().ok().as_deref().and_then(Option::as_ref) {
+            return filter.enabled(metadata.target(), metadata.level());
+        }
+        // Span enter and exit records from `tracing`'s log bridge would fill
+        // the buffer every frame; only a filter naming them lets them in
+        if metadata.target().starts_with(SPAN_TARGET) {
+            return false;
+        }
+        self.inner.as_ref().is_none_or(|inner| inner.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        capture(record);
+        if let Some(inner) = &self.inner {
+            inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(inner) = &self.inner {
+            inner.flush();
+        }
+    }
+}
+
+/// Keep `record` in the shared buffer
+pub fn capture(record: &Record) {
+    let line = LogLine {
+        level: record.level(),
+        target: record.target().to_string(),
+        message: record.args().to_string(),
+    };
+    if let Ok(mut buffer) = BUFFER.lock() {
+        buffer.push(line);
+    }
+}
+
+/// Make a `CaptureLogger` around `inner` the global logger. False if a
+/// logger was already installed, which is then left alone.
+pub fn install(inner: Option<Box<dyn Log>>, max_level: LevelFilter) -> bool {
+    if log::set_boxed_logger(Box::new(CaptureLogger::new(inner))).is_err() {
+        return false;
+    }
+    if let Ok(mut base) = BASE_LEVEL.lock() {
+        *base = max_level;
+    }
+    if !FILTER.read().is_ok_and(|filter| filter.is_some()) {
+        log::set_max_level(max_level);
+    }
+    true
+}
+
+/// Replace the levels the platform logger picks with `filter`, or go back
+/// to them for None. A platform logger that checks levels again on its own
+/// has to be opened up by the caller.
+pub fn set_filter(filter: Option<LogFilter>) {
+    let max_level = match &filter {
+        Some(filter) => filter.max_level(),
+        None => BASE_LEVEL.lock().map_or(LevelFilter::Info, |base| *base),
+    };
+    if let Ok(mut current) = FILTER.write() {
+        *current = filter;
+    }
+    log::set_max_level(max_level);
+}
+
+/// Whether a record or span at `level` for `target` gets through: the
+/// runtime filter decides when there is one, the max level otherwise
+pub fn allows(target: &str, level: Level) -> bool {
+    match FILTER.read().ok().as_deref().and_then(Option::as_ref) {
+        Some(filter) => filter.enabled(target, level),
+        None => level <= log::max_level(),
+    }
+}
+
+/// Copies of the kept lines at `max_level` or more severe, oldest first
+pub fn recent(max_level: LevelFilter) -> Vec<LogLine> {
+    BUFFER.lock().map(|buffer| buffer.filtered(max_level).cloned().collect()).unwrap_or_default()
+}
+
+/// `recent` as a JSON array of `{"level", "target", "message"}` objects
+pub fn recent_json(max_level: LevelFilter) -> String {
+    serde_json::to_string(&recent(max_level)).unwrap_or_else(|_| "[]".into())
+}
+
+/// One `LogLine::to_text` per line
+pub fn to_text(lines: &[LogLine]) -> String {
+    lines.iter().map(|line| line.to_text() + "\n").collect()
+}
+
+pub fn clear() {
+    if let Ok(mut buffer) = BUFFER.lock() {
+        buffer.clear();
+    }
+}
+
+/// Level numbers on the bindings: 1 error, 2 warn, 3 info, 4 debug, 5 trace.
+/// Anything else includes every level.
+pub fn level_filter_from_u32(level: u32) -> LevelFilter {
+    match level {
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        4 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// State of the egui console window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConsoleView {
+    pub open: bool,
+    /// Least severe level shown
+    pub max_level: LevelFilter,
+}
+
+impl Default for ConsoleView {
+    fn default() -> Self {
+        Self { open: false, max_level: LevelFilter::Info }
+    }
+}
@@ -0,0 +1,77 @@
+//! Runtime log filter
+//!
+//! `set_log_filter("physics=debug,render=warn")` picks a level per target
+//! while the app runs, on every platform. A directive is `target=level`, or
+//! a bare level for every target no directive names (`info` when left out).
+//! A target covers itself and its `::` children, and the longest match wins.
+//!
+//! The engine's subsystems log and open `tracing` spans under short targets:
+//!
+//! - `init`: adapter, device and surface setup, and the simulation reset
+//! - `physics`: each simulation step
+//! - `gpu`: copying the simulation into GPU buffers
+//! - `render`: each frame and the passes in it
+//!
+//! Everything else keeps its module path (`physics_core::scenes`, `wgpu_core`).
+
+use log::{Level, LevelFilter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Level of targets no directive names
+    pub default: LevelFilter,
+    /// `(target, level)` in the order given
+    pub directives: Vec<(String, LevelFilter)>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self { default: LevelFilter::Info, directives: Vec::new() }
+    }
+}
+
+impl LogFilter {
+    /// Parse comma-separated directives; whitespace around them is ignored
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Self::default();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let target = target.trim();
+                    if target.is_empty() {
+                        return Err(format!("missing target in '{}'", directive));
+                    }
+                    filter.directives.push((target.to_string(), parse_level(level)?));
+                }
+                None => filter.default = parse_level(directive)?,
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Level for `target`, from the longest directive covering it
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .filter(|(prefix, _)| covers(prefix, target))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        level <= self.level_for(target)
+    }
+
+    /// Most verbose level any target gets
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives.iter().map(|(_, level)| *level).fold(self.default, Ord::max)
+    }
+}
+
+fn covers(prefix: &str, target: &str) -> bool {
+    target.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level.trim().parse().map_err(|_| format!("unknown level '{}'", level.trim()))
+}
@@ -0,0 +1,417 @@
+//! Material assignment and draw batching for instanced rendering
+//!
+//! Instances are grouped by material so each texture is bound once per frame
+//! and drawn with a single `draw_indexed` over a contiguous instance sub-range.
+//!
+//! Compressed textures (see `ktx2_lite`) are uploaded as they are when the
+//! device samples their format, and decoded to RGBA8 otherwise. RGBA
+//! textures get a generated mip chain and each material its own sampler
+//! (see `texture_sampling`).
+
+use bevy_ecs::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::ktx2_lite::{self, TextureFormat};
+use crate::texture_sampling::{mip_level_count, MipGenerator, TextureSampling};
+
+/// Material id used by entities that have no `MaterialComponent`
+pub const DEFAULT_MATERIAL: u32 = 0;
+
+/// Selects which registered material (texture bind group) an entity is drawn with
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MaterialComponent {
+    pub material_id: u32,
+}
+
+impl MaterialComponent {
+    pub fn new(material_id: u32) -> Self {
+        Self { material_id }
+    }
+}
+
+/// A contiguous range of instances sharing one material
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawBatch {
+    pub material_id: u32,
+    pub first_instance: u32,
+    pub instance_count: u32,
+}
+
+impl DrawBatch {
+    pub fn instance_range(&self) -> std::ops::Range<u32> {
+        self.first_instance..self.first_instance + self.instance_count
+    }
+}
+
+/// Sort `(material_id, item)` pairs by material and return one batch per material.
+///
+/// The sort is stable, so instances keep their relative order within a batch.
+pub fn sort_into_batches<T>(items: &mut [(u32, T)]) -> Vec<DrawBatch> {
+    items.sort_by_key(|(material_id, _)| *material_id);
+
+    let mut batches: Vec<DrawBatch> = Vec::new();
+    for (index, (material_id, _)) in items.iter().enumerate() {
+        match batches.last_mut() {
+            Some(batch) if batch.material_id == *material_id => batch.instance_count += 1,
+            _ => batches.push(DrawBatch {
+                material_id: *material_id,
+                first_instance: index as u32,
+                instance_count: 1,
+            }),
+        }
+    }
+    batches
+}
+
+/// What a material's texture was made from, kept so it survives a device
+/// rebuild
+#[derive(Clone)]
+enum MaterialSource {
+    Rgba { width: u32, height: u32, rgba: Vec<u8> },
+    Compressed(ktx2_lite::Texture),
+}
+
+impl MaterialSource {
+    fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue, mips: &mut MipGenerator) -> Result<wgpu::Texture, String> {
+        match self {
+            Self::Rgba { width, height, rgba } => {
+                Ok(upload_rgba(device, queue, mips, *width, *height, rgba, wgpu::TextureFormat::Rgba8UnormSrgb))
+            }
+            Self::Compressed(texture) => upload_compressed(device, queue, mips, texture),
+        }
+    }
+}
+
+/// GPU-side registry of material bind groups, keyed by material id
+pub(crate) struct MaterialRegistry {
+    bind_groups: HashMap<u32, wgpu::BindGroup>,
+    /// Texture views, kept to rebind them with another sampler
+    views: HashMap<u32, wgpu::TextureView>,
+    sources: HashMap<u32, MaterialSource>,
+    /// Sampler settings other than the default
+    sampling: HashMap<u32, TextureSampling>,
+    /// Collision shapes traced from the textures (see `sprite_outline`)
+    shapes: HashMap<u32, Vec<Vec<[f32; 2]>>>,
+    /// Made with the first texture uploaded
+    mips: Option<MipGenerator>,
+    next_id: u32,
+}
+
+impl MaterialRegistry {
+    /// Create a registry whose `DEFAULT_MATERIAL` slot is the given bind group
+    pub fn new(default_bind_group: wgpu::BindGroup) -> Self {
+        let mut bind_groups = HashMap::new();
+        bind_groups.insert(DEFAULT_MATERIAL, default_bind_group);
+        Self {
+            bind_groups,
+            views: HashMap::new(),
+            sources: HashMap::new(),
+            sampling: HashMap::new(),
+            shapes: HashMap::new(),
+            mips: None,
+            next_id: DEFAULT_MATERIAL + 1,
+        }
+    }
+
+    fn mips(&mut self, device: &wgpu::Device) -> &mut MipGenerator {
+        self.mips.get_or_insert_with(|| MipGenerator::new(device))
+    }
+
+    /// Re-upload every registered material on a new device, keeping the ids
+    pub fn rebuild(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        default_bind_group: wgpu::BindGroup,
+    ) -> Self {
+        let mut registry = Self::new(default_bind_group);
+        registry.sampling = self.sampling.clone();
+        for (&material_id, source) in &self.sources {
+            let texture = match source.upload(device, queue, registry.mips(device)) {
+                Ok(texture) => texture,
+                Err(e) => {
+                    log::warn!("Material {} lost on the new device: {}", material_id, e);
+                    continue;
+                }
+            };
+            registry.bind(device, layout, material_id, texture.create_view(&wgpu::TextureViewDescriptor::default()));
+            registry.sources.insert(material_id, source.clone());
+        }
+        registry.shapes = self.shapes.clone();
+        registry.next_id = self.next_id;
+        registry
+    }
+
+    pub fn bind_group(&self, material_id: u32) -> &wgpu::BindGroup {
+        self.bind_groups
+            .get(&material_id)
+            .unwrap_or_else(|| &self.bind_groups[&DEFAULT_MATERIAL])
+    }
+
+    pub fn contains(&self, material_id: u32) -> bool {
+        self.bind_groups.contains_key(&material_id)
+    }
+
+    /// Width, height and RGBA pixels of a registered texture. Compressed
+    /// textures are decoded, so those in BC7 or ASTC have none.
+    pub fn source(&self, material_id: u32) -> Option<(u32, u32, Cow<'_, [u8]>)> {
+        match self.sources.get(&material_id)? {
+            MaterialSource::Rgba { width, height, rgba } => Some((*width, *height, Cow::Borrowed(rgba.as_slice()))),
+            MaterialSource::Compressed(texture) => {
+                texture.decode().ok().map(|rgba| (texture.width, texture.height, Cow::Owned(rgba)))
+            }
+        }
+    }
+
+    /// Convex pieces traced from the texture, spanning -1..1
+    pub fn shape(&self, material_id: u32) -> Option<&Vec<Vec<[f32; 2]>>> {
+        self.shapes.get(&material_id)
+    }
+
+    pub fn set_shape(&mut self, material_id: u32, pieces: Vec<Vec<[f32; 2]>>) {
+        self.shapes.insert(material_id, pieces);
+    }
+
+    pub fn sampling(&self, material_id: u32) -> TextureSampling {
+        self.sampling.get(&material_id).copied().unwrap_or_default()
+    }
+
+    /// Sample the material's texture with `sampling` from now on. Returns
+    /// false for unknown materials and the built-in default one.
+    pub fn set_sampling(
+        &mut self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        material_id: u32,
+        sampling: TextureSampling,
+    ) -> bool {
+        let Some(view) = self.views.remove(&material_id) else {
+            return false;
+        };
+        if sampling == TextureSampling::default() {
+            self.sampling.remove(&material_id);
+        } else {
+            self.sampling.insert(material_id, sampling);
+        }
+        self.bind(device, layout, material_id, view);
+        true
+    }
+
+    /// Bind `view` with the material's sampler
+    fn bind(&mut self, device: &wgpu::Device, layout: &wgpu::BindGroupLayout, material_id: u32, view: wgpu::TextureView) {
+        let bind_group = create_material_bind_group(device, layout, &view, &self.sampling(material_id));
+        self.bind_groups.insert(material_id, bind_group);
+        self.views.insert(material_id, view);
+    }
+
+    /// Upload an RGBA8 image and register it as a new material, returning its id
+    pub fn register_rgba(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) -> Option<u32> {
+        // In usize so huge sizes are rejected rather than wrapping past the check
+        let len = (width as usize).checked_mul(height as usize).and_then(|pixels| pixels.checked_mul(4));
+        let len = match len {
+            Some(len) if width != 0 && height != 0 && rgba.len() >= len => len,
+            _ => {
+                log::warn!(
+                    "register_rgba: invalid image {}x{} with {} bytes",
+                    width,
+                    height,
+                    rgba.len()
+                );
+                return None;
+            }
+        };
+
+        let source = MaterialSource::Rgba { width, height, rgba: rgba[..len].to_vec() };
+        let material_id = self.insert(device, queue, layout, source).ok()?;
+        log::info!("Registered material {} ({}x{})", material_id, width, height);
+        Some(material_id)
+    }
+
+    /// Upload a KTX2 texture and register it as a new material, returning
+    /// its id. The texture stays compressed when the device samples its
+    /// format and is decoded to RGBA8 otherwise.
+    pub fn register_compressed(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        texture: ktx2_lite::Texture,
+    ) -> Result<u32, String> {
+        let (format, width, height) = (texture.format, texture.width, texture.height);
+        let material_id = self.insert(device, queue, layout, MaterialSource::Compressed(texture))?;
+        log::info!("Registered material {} ({}x{} {:?})", material_id, width, height, format);
+        Ok(material_id)
+    }
+
+    fn insert(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        source: MaterialSource,
+    ) -> Result<u32, String> {
+        let texture = source.upload(device, queue, self.mips(device))?;
+        let material_id = self.next_id;
+        self.next_id += 1;
+        self.bind(device, layout, material_id, texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        self.sources.insert(material_id, source);
+        Ok(material_id)
+    }
+}
+
+/// The wgpu format for `format`, in its sRGB variant if `srgb`
+fn wgpu_format(format: TextureFormat, srgb: bool) -> wgpu::TextureFormat {
+    use wgpu::TextureFormat as Wgpu;
+    match (format, srgb) {
+        (TextureFormat::Rgba8, false) => Wgpu::Rgba8Unorm,
+        (TextureFormat::Rgba8, true) => Wgpu::Rgba8UnormSrgb,
+        (TextureFormat::Bc1, false) => Wgpu::Bc1RgbaUnorm,
+        (TextureFormat::Bc1, true) => Wgpu::Bc1RgbaUnormSrgb,
+        (TextureFormat::Bc3, false) => Wgpu::Bc3RgbaUnorm,
+        (TextureFormat::Bc3, true) => Wgpu::Bc3RgbaUnormSrgb,
+        (TextureFormat::Bc7, false) => Wgpu::Bc7RgbaUnorm,
+        (TextureFormat::Bc7, true) => Wgpu::Bc7RgbaUnormSrgb,
+        (TextureFormat::Etc2Rgb8, false) => Wgpu::Etc2Rgb8Unorm,
+        (TextureFormat::Etc2Rgb8, true) => Wgpu::Etc2Rgb8UnormSrgb,
+        (TextureFormat::Etc2Rgba8, false) => Wgpu::Etc2Rgba8Unorm,
+        (TextureFormat::Etc2Rgba8, true) => Wgpu::Etc2Rgba8UnormSrgb,
+        (TextureFormat::Astc4x4, srgb) => Wgpu::Astc {
+            block: wgpu::AstcBlock::B4x4,
+            channel: if srgb { wgpu::AstcChannel::UnormSrgb } else { wgpu::AstcChannel::Unorm },
+        },
+    }
+}
+
+/// Upload every level of `texture` in its own format, or its first level
+/// decoded to RGBA8 with generated mips when the device cannot sample that
+/// format
+fn upload_compressed(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    mips: &mut MipGenerator,
+    texture: &ktx2_lite::Texture,
+) -> Result<wgpu::Texture, String> {
+    let (width, height) = (texture.width, texture.height);
+    let block = texture.format.block_dim();
+    let sampled = crate::gpu_caps::texture_compression(device.features()).supports(texture.format);
+    // RGBA8 files without mips get them generated like PNGs
+    let generate = texture.format == TextureFormat::Rgba8 && texture.levels.len() == 1;
+    // wgpu wants the first level to be whole blocks
+    if !sampled || generate || width % block != 0 || height % block != 0 {
+        let rgba = texture.decode()?;
+        let format = if texture.srgb { wgpu::TextureFormat::Rgba8UnormSrgb } else { wgpu::TextureFormat::Rgba8Unorm };
+        return Ok(upload_rgba(device, queue, mips, width, height, &rgba, format));
+    }
+
+    let gpu_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Material Texture"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: texture.levels.len() as u32,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu_format(texture.format, texture.srgb),
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    for (level, data) in texture.levels.iter().enumerate() {
+        let (level_width, level_height) = texture.level_size(level);
+        let (blocks_wide, blocks_high) = (level_width.div_ceil(block), level_height.div_ceil(block));
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &gpu_texture,
+                mip_level: level as u32,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(blocks_wide * texture.format.block_bytes() as u32),
+                rows_per_image: Some(blocks_high),
+            },
+            // Copies of small levels cover their whole last block
+            wgpu::Extent3d { width: blocks_wide * block, height: blocks_high * block, depth_or_array_layers: 1 },
+        );
+    }
+    Ok(gpu_texture)
+}
+
+/// Upload `rgba` as the first level of a full mip chain and render the rest
+fn upload_rgba(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    mips: &mut MipGenerator,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    format: wgpu::TextureFormat,
+) -> wgpu::Texture {
+    let size = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Material Texture"),
+        size,
+        mip_level_count: mip_level_count(width, height),
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        rgba,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * width),
+            rows_per_image: Some(height),
+        },
+        size,
+    );
+    mips.generate(device, queue, &texture);
+    texture
+}
+
+fn create_material_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    view: &wgpu::TextureView,
+    sampling: &TextureSampling,
+) -> wgpu::BindGroup {
+    let sampler = device.create_sampler(&sampling.descriptor());
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+        ],
+        label: Some("material_bind_group"),
+    })
+}
@@ -0,0 +1,261 @@
+//! Picture-in-picture minimap
+//!
+//! The minimap is drawn after the scene (and bloom), straight into a corner
+//! of the backbuffer: a border, a background and one flat blip per visible
+//! body, seen through an orthographic camera that fits the whole world, or a
+//! fixed rectangle of it, into the map. Blips are the bodies' quads in their
+//! tint color, grown to `min_blip_size` pixels so small bodies stay visible.
+//! Hosts that want the map on its own surface instead open a view with
+//! `add_view` and a camera covering the world.
+
+use serde::Deserialize;
+
+use crate::groups;
+use crate::trail::{TrailPass, TrailVertex};
+
+/// Largest minimap side, px
+pub const MAX_SIZE: u32 = 4096;
+
+/// World extent shown when every body sits at one point, m
+const MIN_EXTENT: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Corner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MinimapConfig {
+    pub corner: Corner,
+    /// Size including the border, px
+    pub width: u32,
+    pub height: u32,
+    /// Gap to the surface edges, px
+    pub margin: u32,
+    pub border_width: u32,
+    /// Linear RGBA
+    pub border_color: [f32; 4],
+    pub background: [f32; 4],
+    /// Only bodies in one of these groups get a blip
+    pub visible_groups: u32,
+    /// World rectangle shown, `[min_x, min_y, max_x, max_y]`; None fits
+    /// every visible body each frame
+    pub bounds: Option<[f32; 4]>,
+    /// Smallest blip side, px
+    pub min_blip_size: f32,
+}
+
+impl Default for MinimapConfig {
+    fn default() -> Self {
+        Self {
+            corner: Corner::TopRight,
+            width: 200,
+            height: 150,
+            margin: 16,
+            border_width: 2,
+            border_color: [1.0, 1.0, 1.0, 0.8],
+            background: [0.0, 0.0, 0.0, 0.6],
+            visible_groups: groups::ALL,
+            bounds: None,
+            min_blip_size: 2.0,
+        }
+    }
+}
+
+/// One body as the minimap draws it; `position` is the quad's anchor corner,
+/// as for the scene's instances
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blip {
+    pub position: [f32; 2],
+    pub size: f32,
+    /// Radians around z
+    pub rotation: f32,
+    pub color: [f32; 4],
+}
+
+impl MinimapConfig {
+    pub fn is_valid(&self) -> bool {
+        let colors = self.border_color.iter().chain(&self.background).all(|c| c.is_finite());
+        let bounds = self.bounds.is_none_or(|[min_x, min_y, max_x, max_y]| {
+            [min_x, min_y, max_x, max_y].iter().all(|v| v.is_finite()) && max_x > min_x && max_y > min_y
+        });
+        self.width <= MAX_SIZE
+            && self.height <= MAX_SIZE
+            && self.width > 2 * self.border_width
+            && self.height > 2 * self.border_width
+            && colors
+            && bounds
+            && self.min_blip_size.is_finite()
+            && self.min_blip_size >= 0.0
+    }
+
+    /// Pixel rectangle `[x, y, width, height]` (y down) on a surface of the
+    /// given size; None when the minimap and its margin do not fit
+    pub fn viewport(&self, surface_width: u32, surface_height: u32) -> Option<[u32; 4]> {
+        let right = surface_width.checked_sub(self.width + self.margin)?;
+        let bottom = surface_height.checked_sub(self.height + self.margin)?;
+        if right < self.margin || bottom < self.margin {
+            return None;
+        }
+        let (x, y) = match self.corner {
+            Corner::TopLeft => (self.margin, self.margin),
+            Corner::TopRight => (right, self.margin),
+            Corner::BottomLeft => (self.margin, bottom),
+            Corner::BottomRight => (right, bottom),
+        };
+        Some([x, y, self.width, self.height])
+    }
+
+    /// World rectangle `[left, bottom, right, top]` covering the whole
+    /// minimap, such that `content` fits inside the border and keeps its
+    /// aspect ratio
+    pub fn world_rect(&self, content: [f32; 4]) -> [f32; 4] {
+        let [min_x, min_y, max_x, max_y] = content;
+        let center = [(min_x + max_x) * 0.5, (min_y + max_y) * 0.5];
+        let inner = [(self.width - 2 * self.border_width) as f32, (self.height - 2 * self.border_width) as f32];
+        let world_per_px = ((max_x - min_x).max(MIN_EXTENT) / inner[0]).max((max_y - min_y).max(MIN_EXTENT) / inner[1]);
+        let half = [self.width as f32 * 0.5 * world_per_px, self.height as f32 * 0.5 * world_per_px];
+        [center[0] - half[0], center[1] - half[1], center[0] + half[0], center[1] + half[1]]
+    }
+
+    /// Border, background and blips in world units for `rect` from
+    /// `world_rect`
+    pub fn vertices(&self, rect: [f32; 4], blips: &[Blip]) -> Vec<TrailVertex> {
+        let world_per_px = (rect[2] - rect[0]) / self.width as f32;
+        let border = self.border_width as f32 * world_per_px;
+        let mut vertices = Vec::with_capacity(6 * (2 + blips.len()));
+        quad(&mut vertices, [[rect[0], rect[1]], [rect[2], rect[1]], [rect[2], rect[3]], [rect[0], rect[3]]], self.border_color);
+        let [left, bottom, right, top] = [rect[0] + border, rect[1] + border, rect[2] - border, rect[3] - border];
+        quad(&mut vertices, [[left, bottom], [right, bottom], [right, top], [left, top]], self.background);
+
+        let min_size = self.min_blip_size * world_per_px;
+        for blip in blips {
+            let size = blip.size.max(min_size);
+            // Grown blips stay centered on the body's quad
+            let (from, to) = ((blip.size - size) * 0.5, (blip.size + size) * 0.5);
+            let (sin, cos) = blip.rotation.sin_cos();
+            let corner = |x: f32, y: f32| [blip.position[0] + x * cos - y * sin, blip.position[1] + x * sin + y * cos];
+            quad(&mut vertices, [corner(from, from), corner(to, from), corner(to, to), corner(from, to)], blip.color);
+        }
+        vertices
+    }
+}
+
+fn quad(vertices: &mut Vec<TrailVertex>, corners: [[f32; 2]; 4], color: [f32; 4]) {
+    for index in [0, 1, 2, 0, 2, 3] {
+        vertices.push(TrailVertex { position: corners[index], color });
+    }
+}
+
+/// `[min_x, min_y, max_x, max_y]` around every blip; None without blips
+pub fn content_bounds(blips: &[Blip]) -> Option<[f32; 4]> {
+    blips.iter().fold(None, |bounds, blip| {
+        // The quad reaches at most size * sqrt(2) from its anchor
+        let reach = blip.size * std::f32::consts::SQRT_2;
+        let [x, y] = blip.position;
+        let [min_x, min_y, max_x, max_y] = bounds.unwrap_or([x, y, x, y]);
+        Some([min_x.min(x - reach), min_y.min(y - reach), max_x.max(x + reach), max_y.max(y + reach)])
+    })
+}
+
+/// Column-major view-projection showing `rect` (`[left, bottom, right,
+/// top]`) edge to edge
+pub fn ortho(rect: [f32; 4]) -> [[f32; 4]; 4] {
+    let [left, bottom, right, top] = rect;
+    let (sx, sy) = (2.0 / (right - left), 2.0 / (top - bottom));
+    [
+        [sx, 0.0, 0.0, 0.0],
+        [0.0, sy, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0],
+        [-(right + left) / (right - left), -(top + bottom) / (top - bottom), 0.5, 1.0],
+    ]
+}
+
+/// Draws the prepared minimap over the finished frame. The flat-color
+/// pipeline is the trail one without multisampling, since the backbuffer is
+/// already resolved.
+pub(crate) struct MinimapPass {
+    pass: TrailPass,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    viewport: Option<[u32; 4]>,
+}
+
+impl MinimapPass {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        output_format: wgpu::TextureFormat,
+    ) -> Self {
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Minimap Camera Buffer"),
+            size: std::mem::size_of::<[[f32; 4]; 4]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: camera_buffer.as_entire_binding() }],
+            label: Some("minimap_camera_bind_group"),
+        });
+        Self {
+            pass: TrailPass::new(device, camera_bind_group_layout, output_format, 1, None),
+            camera_buffer,
+            camera_bind_group,
+            viewport: None,
+        }
+    }
+
+    /// Lay out this frame's minimap in the corner of `area` (`[x, y, width,
+    /// height]` of the surface); without a config, or when it does not fit,
+    /// nothing is drawn
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: Option<&MinimapConfig>,
+        blips: &[Blip],
+        area: [u32; 4],
+    ) {
+        let [left, top, width, height] = area;
+        self.viewport = config.and_then(|config| config.viewport(width, height)).map(|[x, y, width, height]| [left + x, top + y, width, height]);
+        let (Some(config), Some(_)) = (config, self.viewport) else {
+            return;
+        };
+        let content = config.bounds.or_else(|| content_bounds(blips)).unwrap_or([-1.0, -1.0, 1.0, 1.0]);
+        let rect = config.world_rect(content);
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&ortho(rect)));
+        self.pass.prepare(device, queue, &config.vertices(rect, blips));
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.viewport.is_some()
+    }
+
+    pub fn encode(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let Some([x, y, width, height]) = self.viewport else {
+            return;
+        };
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Minimap Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: wgpu::StoreOp::Store },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+        render_pass.set_scissor_rect(x, y, width, height);
+        self.pass.draw(&mut render_pass, &self.camera_bind_group, false);
+    }
+}
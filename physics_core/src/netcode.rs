@@ -0,0 +1,498 @@
+//! Delta-encoded state snapshots for syncing two simulations
+//!
+//! The engine has no transport of its own. A host running the same level on
+//! two machines asks the authority for `snapshot_delta(baseline)` whenever it
+//! wants to send, ships the bytes however it likes and passes them to
+//! `apply_snapshot` on the other side, which returns the tick to acknowledge.
+//! Later deltas name an acknowledged tick as their baseline and carry only
+//! the fields that moved since then; baseline 0, or a tick the authority no
+//! longer remembers, gives a full snapshot.
+//!
+//! Both sides keep their last `HISTORY` sent or received states, so the
+//! receiver rebuilds the full state from its own copy of the baseline. Bodies
+//! are matched by entity id, which lines up when both sides load the same
+//! level in the same order. Bodies the receiver does not have are skipped,
+//! and bodies only the receiver has are left alone.
+//!
+//! Velocities are taken as they come. A position more than `SNAP_DISTANCE`
+//! off, or a rotation more than `SNAP_ANGLE` off, jumps; smaller errors are
+//! worked off over the next steps so remote bodies do not visibly pop.
+//!
+//! Layout, little-endian:
+//!
+//! ```text
+//! header   magic "PXND", version: u8, tick: u64, baseline: u64,
+//!          changed: u32, removed: u32
+//! changed  entity_id: u64, fields: u8, then for each set bit in order
+//!          position (x, y: f32), rotation (f32), linvel (x, y: f32),
+//!          angvel (f32)
+//! removed  entity_id: u64 each
+//! ```
+
+use std::collections::VecDeque;
+
+use bevy_ecs::prelude::*;
+use crate::rapier::prelude::*;
+
+use crate::precision::{planar, to_f32, to_real, xy};
+use crate::{PhysicsBody, PhysicsState};
+
+pub const MAGIC: [u8; 4] = *b"PXND";
+pub const VERSION: u8 = 1;
+
+/// States kept per direction, about a second at 60 Hz
+pub const HISTORY: usize = 64;
+
+/// Position error beyond which a body jumps instead of easing, m
+pub const SNAP_DISTANCE: f32 = 0.5;
+/// Rotation error beyond which a body jumps instead of easing, radians
+pub const SNAP_ANGLE: f32 = 0.5;
+/// Fraction of the remaining error removed each step
+pub const SMOOTHING: f32 = 0.2;
+
+/// `fields` bits of a changed body
+pub const FIELD_POSITION: u8 = 1;
+pub const FIELD_ROTATION: u8 = 2;
+pub const FIELD_LINVEL: u8 = 4;
+pub const FIELD_ANGVEL: u8 = 8;
+pub const FIELD_ALL: u8 = FIELD_POSITION | FIELD_ROTATION | FIELD_LINVEL | FIELD_ANGVEL;
+
+const HEADER_SIZE: usize = 4 + 1 + 8 + 8 + 4 + 4;
+
+/// Changes smaller than this are not sent
+const EPSILON: f32 = 1e-4;
+
+/// One body's synced state
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NetBody {
+    pub entity_id: u64,
+    pub position: [f32; 2],
+    /// Radians around z
+    pub rotation: f32,
+    pub linvel: [f32; 2],
+    pub angvel: f32,
+}
+
+/// The fields of one body that changed; None fields keep the baseline value
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BodyUpdate {
+    pub entity_id: u64,
+    pub position: Option<[f32; 2]>,
+    pub rotation: Option<f32>,
+    pub linvel: Option<[f32; 2]>,
+    pub angvel: Option<f32>,
+}
+
+impl BodyUpdate {
+    /// Every field of `body`
+    pub fn full(body: &NetBody) -> Self {
+        Self {
+            entity_id: body.entity_id,
+            position: Some(body.position),
+            rotation: Some(body.rotation),
+            linvel: Some(body.linvel),
+            angvel: Some(body.angvel),
+        }
+    }
+
+    /// Fields that moved from `old` to `new`; None when nothing did
+    pub fn between(old: &NetBody, new: &NetBody) -> Option<Self> {
+        let moved = |a: &[f32], b: &[f32]| a.iter().zip(b).any(|(a, b)| (a - b).abs() > EPSILON);
+        let update = Self {
+            entity_id: new.entity_id,
+            position: moved(&old.position, &new.position).then_some(new.position),
+            rotation: moved(&[old.rotation], &[new.rotation]).then_some(new.rotation),
+            linvel: moved(&old.linvel, &new.linvel).then_some(new.linvel),
+            angvel: moved(&[old.angvel], &[new.angvel]).then_some(new.angvel),
+        };
+        (update.fields() != 0).then_some(update)
+    }
+
+    pub fn fields(&self) -> u8 {
+        let mut fields = 0;
+        for (present, bit) in [
+            (self.position.is_some(), FIELD_POSITION),
+            (self.rotation.is_some(), FIELD_ROTATION),
+            (self.linvel.is_some(), FIELD_LINVEL),
+            (self.angvel.is_some(), FIELD_ANGVEL),
+        ] {
+            if present {
+                fields |= bit;
+            }
+        }
+        fields
+    }
+
+    /// `base` with the sent fields replaced
+    pub fn apply(&self, base: NetBody) -> NetBody {
+        NetBody {
+            entity_id: self.entity_id,
+            position: self.position.unwrap_or(base.position),
+            rotation: self.rotation.unwrap_or(base.rotation),
+            linvel: self.linvel.unwrap_or(base.linvel),
+            angvel: self.angvel.unwrap_or(base.angvel),
+        }
+    }
+}
+
+/// State at `tick` relative to the state at `baseline` (0 for a full snapshot)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Delta {
+    pub tick: u64,
+    pub baseline: u64,
+    pub changed: Vec<BodyUpdate>,
+    /// Bodies of the baseline that are gone
+    pub removed: Vec<u64>,
+}
+
+impl Delta {
+    /// Changes from `baseline`, its tick and bodies, to `current`; a full
+    /// snapshot without a baseline. Both lists are sorted by entity id.
+    pub fn between(tick: u64, baseline: Option<(u64, &[NetBody])>, current: &[NetBody]) -> Self {
+        let Some((baseline_tick, old)) = baseline else {
+            return Self { tick, baseline: 0, changed: current.iter().map(BodyUpdate::full).collect(), removed: Vec::new() };
+        };
+        let find = |bodies: &[NetBody], id: u64| bodies.binary_search_by_key(&id, |body| body.entity_id).ok().map(|i| bodies[i]);
+        let changed = current
+            .iter()
+            .filter_map(|body| match find(old, body.entity_id) {
+                Some(before) => BodyUpdate::between(&before, body),
+                None => Some(BodyUpdate::full(body)),
+            })
+            .collect();
+        let removed = old.iter().map(|body| body.entity_id).filter(|&id| find(current, id).is_none()).collect();
+        Self { tick, baseline: baseline_tick, changed, removed }
+    }
+
+    /// Full state after this delta given the baseline's bodies (empty for a
+    /// full snapshot), sorted by entity id
+    pub fn rebuild(&self, baseline: &[NetBody]) -> Vec<NetBody> {
+        let mut bodies: Vec<NetBody> = baseline.iter().filter(|body| !self.removed.contains(&body.entity_id)).copied().collect();
+        for update in &self.changed {
+            match bodies.binary_search_by_key(&update.entity_id, |body| body.entity_id) {
+                Ok(i) => bodies[i] = update.apply(bodies[i]),
+                Err(i) => bodies.insert(i, update.apply(NetBody { entity_id: update.entity_id, ..NetBody::default() })),
+            }
+        }
+        bodies
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.changed.len() * (9 + 24) + self.removed.len() * 8);
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.tick.to_le_bytes());
+        bytes.extend_from_slice(&self.baseline.to_le_bytes());
+        bytes.extend_from_slice(&(self.changed.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.removed.len() as u32).to_le_bytes());
+        for update in &self.changed {
+            bytes.extend_from_slice(&update.entity_id.to_le_bytes());
+            bytes.push(update.fields());
+            let values = update.position.iter().flatten().chain(&update.rotation).chain(update.linvel.iter().flatten()).chain(&update.angvel);
+            for value in values {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        for id in &self.removed {
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err("not a snapshot".to_string());
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(format!("unsupported snapshot version {}", version));
+        }
+        let tick = reader.u64()?;
+        let baseline = reader.u64()?;
+        let changed_count = reader.u32()? as usize;
+        let removed_count = reader.u32()? as usize;
+
+        // Counts come off the wire, so capacity is bounded by what could fit
+        let mut changed = Vec::with_capacity(changed_count.min(bytes.len() / 9));
+        for _ in 0..changed_count {
+            let entity_id = reader.u64()?;
+            let fields = reader.take(1)?[0];
+            if fields & !FIELD_ALL != 0 {
+                return Err(format!("unknown fields {:#x} for entity {}", fields, entity_id));
+            }
+            let mut update = BodyUpdate { entity_id, ..BodyUpdate::default() };
+            if fields & FIELD_POSITION != 0 {
+                update.position = Some([reader.f32()?, reader.f32()?]);
+            }
+            if fields & FIELD_ROTATION != 0 {
+                update.rotation = Some(reader.f32()?);
+            }
+            if fields & FIELD_LINVEL != 0 {
+                update.linvel = Some([reader.f32()?, reader.f32()?]);
+            }
+            if fields & FIELD_ANGVEL != 0 {
+                update.angvel = Some(reader.f32()?);
+            }
+            changed.push(update);
+        }
+        let mut removed = Vec::with_capacity(removed_count.min(bytes.len() / 8));
+        for _ in 0..removed_count {
+            removed.push(reader.u64()?);
+        }
+        if reader.offset != bytes.len() {
+            return Err(format!("{} trailing bytes", bytes.len() - reader.offset));
+        }
+        Ok(Self { tick, baseline, changed, removed })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self.bytes.get(self.offset..self.offset + len).ok_or("snapshot is truncated")?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        let value = f32::from_le_bytes(self.take(4)?.try_into().unwrap());
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err("non-finite value in snapshot".to_string())
+        }
+    }
+}
+
+/// Recent states by tick, oldest first
+#[derive(Debug, Default)]
+pub struct NetHistory {
+    entries: VecDeque<(u64, Vec<NetBody>)>,
+}
+
+impl NetHistory {
+    /// Keep `bodies` as the state at `tick`. States at or past `tick` belong
+    /// to a timeline that was rewound and are dropped.
+    pub fn record(&mut self, tick: u64, bodies: Vec<NetBody>) {
+        while self.entries.back().is_some_and(|(newest, _)| *newest >= tick) {
+            self.entries.pop_back();
+        }
+        if self.entries.len() == HISTORY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((tick, bodies));
+    }
+
+    pub fn get(&self, tick: u64) -> Option<&[NetBody]> {
+        self.entries.iter().find(|(entry, _)| *entry == tick).map(|(_, bodies)| bodies.as_slice())
+    }
+
+    pub fn latest_tick(&self) -> Option<u64> {
+        self.entries.back().map(|(tick, _)| *tick)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Position and rotation error still to be worked off after a remote snapshot
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct NetCorrection {
+    pub offset: [f32; 2],
+    pub rotation: f32,
+}
+
+impl NetCorrection {
+    /// Error from `current` to `target`; None when it is too large to ease
+    /// and the body should jump
+    pub fn between(current: &NetBody, target: &NetBody) -> Option<Self> {
+        let offset = [target.position[0] - current.position[0], target.position[1] - current.position[1]];
+        let rotation = wrap_angle(target.rotation - current.rotation);
+        (offset[0].hypot(offset[1]) <= SNAP_DISTANCE && rotation.abs() <= SNAP_ANGLE).then_some(Self { offset, rotation })
+    }
+
+    /// Part of the error to remove this step; None once it is negligible
+    pub fn step(&mut self) -> Option<([f32; 2], f32)> {
+        if self.offset[0].hypot(self.offset[1]) < EPSILON && self.rotation.abs() < EPSILON {
+            return None;
+        }
+        let part = ([self.offset[0] * SMOOTHING, self.offset[1] * SMOOTHING], self.rotation * SMOOTHING);
+        self.offset = [self.offset[0] - part.0[0], self.offset[1] - part.0[1]];
+        self.rotation -= part.1;
+        Some(part)
+    }
+}
+
+/// `angle` in [-pi, pi]
+fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = angle.rem_euclid(std::f32::consts::TAU);
+    if wrapped > std::f32::consts::PI {
+        wrapped - std::f32::consts::TAU
+    } else {
+        wrapped
+    }
+}
+
+/// What one simulation sent and received
+#[derive(Debug, Default)]
+pub(crate) struct NetSync {
+    sent: NetHistory,
+    received: NetHistory,
+}
+
+pub(crate) fn net_body(entity_id: u64, rb: &RigidBody) -> NetBody {
+    NetBody {
+        entity_id,
+        position: xy(rb.translation()),
+        rotation: to_f32(rb.rotation().euler_angles().2),
+        linvel: xy(rb.linvel()),
+        angvel: to_f32(rb.angvel().z),
+    }
+}
+
+/// Every body's state, sorted by entity id
+fn capture(physics: &mut PhysicsState) -> Vec<NetBody> {
+    let PhysicsState { world, rigid_body_set, .. } = physics;
+    let mut bodies: Vec<NetBody> = world
+        .query::<(Entity, &PhysicsBody)>()
+        .iter(world)
+        .filter_map(|(entity, body)| Some(net_body(entity.to_bits(), rigid_body_set.get(body.rigid_body_handle)?)))
+        .collect();
+    bodies.sort_by_key(|body| body.entity_id);
+    bodies
+}
+
+/// Encode the current state relative to `baseline`, a tick returned by the
+/// other side's `apply_snapshot`, and remember it as a future baseline
+pub(crate) fn snapshot_delta(physics: &mut PhysicsState, baseline: u64) -> Vec<u8> {
+    let tick = physics.step_count;
+    let bodies = capture(physics);
+    let old = (baseline != 0).then(|| physics.net.sent.get(baseline)).flatten();
+    let bytes = Delta::between(tick, old.map(|old| (baseline, old)), &bodies).encode();
+    physics.net.sent.record(tick, bodies);
+    bytes
+}
+
+/// Decode a remote snapshot and rebuild the full state it describes, which
+/// is kept as a baseline for later ones
+pub(crate) fn receive(physics: &mut PhysicsState, bytes: &[u8]) -> Result<(Delta, Vec<NetBody>), String> {
+    let delta = Delta::decode(bytes)?;
+    if physics.net.received.latest_tick().is_some_and(|latest| delta.tick <= latest) {
+        return Err(format!("stale snapshot for tick {}", delta.tick));
+    }
+    let bodies = if delta.baseline == 0 {
+        delta.rebuild(&[])
+    } else {
+        let baseline = physics.net.received.get(delta.baseline).ok_or_else(|| format!("unknown baseline tick {}", delta.baseline))?;
+        delta.rebuild(baseline)
+    };
+    physics.net.received.record(delta.tick, bodies.clone());
+    Ok((delta, bodies))
+}
+
+/// Decode a remote snapshot and move the matching bodies toward it. Returns
+/// the snapshot's tick, to be acknowledged to the sender.
+pub(crate) fn apply_snapshot(physics: &mut PhysicsState, bytes: &[u8]) -> Result<u64, String> {
+    let (delta, bodies) = receive(physics, bytes)?;
+    for &id in &delta.removed {
+        despawn(physics, id);
+    }
+    for target in &bodies {
+        correct(physics, target);
+    }
+    Ok(delta.tick)
+}
+
+pub(crate) fn live_body(physics: &PhysicsState, entity_id: u64) -> Option<(Entity, PhysicsBody)> {
+    let entity = Entity::try_from_bits(entity_id).ok().filter(|&entity| physics.world.entities().contains(entity))?;
+    Some((entity, *physics.world.get::<PhysicsBody>(entity)?))
+}
+
+pub(crate) fn despawn(physics: &mut PhysicsState, entity_id: u64) {
+    let Some((entity, body)) = live_body(physics, entity_id) else {
+        return;
+    };
+    physics.rigid_body_set.remove(
+        body.rigid_body_handle,
+        &mut physics.island_manager,
+        &mut physics.collider_set,
+        &mut physics.impulse_joint_set,
+        &mut physics.multibody_joint_set,
+        true,
+    );
+    physics.surface_velocities.remove(&body.collider_handle);
+    physics.world.despawn(entity);
+    physics.instance_layout_dirty = true;
+}
+
+/// Take the target's velocities and jump to or start easing toward its pose
+pub(crate) fn correct(physics: &mut PhysicsState, target: &NetBody) {
+    let Some((entity, body)) = live_body(physics, target.entity_id) else {
+        return;
+    };
+    let Some(rb) = physics.rigid_body_set.get_mut(body.rigid_body_handle) else {
+        return;
+    };
+    let current = net_body(target.entity_id, rb);
+    rb.set_linvel(planar(target.linvel), true);
+    rb.set_angvel(vector![0.0, 0.0, to_real(target.angvel)], true);
+    match NetCorrection::between(&current, target) {
+        Some(correction) => {
+            physics.world.entity_mut(entity).insert(correction);
+        }
+        None => set_state(physics, target),
+    }
+}
+
+/// Put the body exactly where the target has it
+pub(crate) fn set_state(physics: &mut PhysicsState, target: &NetBody) {
+    let Some((entity, body)) = live_body(physics, target.entity_id) else {
+        return;
+    };
+    let Some(rb) = physics.rigid_body_set.get_mut(body.rigid_body_handle) else {
+        return;
+    };
+    let z = rb.translation().z;
+    rb.set_translation(vector![to_real(target.position[0]), to_real(target.position[1]), z], true);
+    rb.set_rotation(Rotation::from_axis_angle(&Vector::z_axis(), to_real(target.rotation)), true);
+    rb.set_linvel(planar(target.linvel), true);
+    rb.set_angvel(vector![0.0, 0.0, to_real(target.angvel)], true);
+    physics.world.entity_mut(entity).remove::<NetCorrection>();
+}
+
+/// Ease corrected bodies a step closer to where the remote side has them
+pub(crate) fn update(physics: &mut PhysicsState) {
+    let PhysicsState { world, rigid_body_set, .. } = physics;
+    let mut settled = Vec::new();
+    for (entity, body, mut correction) in world.query::<(Entity, &PhysicsBody, &mut NetCorrection)>().iter_mut(world) {
+        let (Some(rb), Some((offset, angle))) = (rigid_body_set.get_mut(body.rigid_body_handle), correction.step()) else {
+            settled.push(entity);
+            continue;
+        };
+        let translation = *rb.translation();
+        rb.set_translation(translation + planar(offset), false);
+        let rotation = rb.rotation().euler_angles().2 + to_real(angle);
+        rb.set_rotation(Rotation::from_axis_angle(&Vector::z_axis(), rotation), false);
+    }
+    for entity in settled {
+        world.entity_mut(entity).remove::<NetCorrection>();
+    }
+}
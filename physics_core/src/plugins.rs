@@ -0,0 +1,190 @@
+//! Extension points for gameplay code that lives outside physics_core
+//!
+//! Two kinds of extension are supported:
+//!
+//! * `SimulationPlugin`s (Rust crates) add systems and resources to every
+//!   scene's simulation schedule, normally in `SimulationSet::PrePhysics` or
+//!   `SimulationSet::PostPhysics`. Plugins are re-applied whenever the scene is
+//!   rebuilt (init, reset, stress scenes).
+//! * Hooks are plain callbacks invoked at a `HookPoint` each frame, or once
+//!   after a reset. They run with no simulation or GPU lock held, so a hook
+//!   may call back into the public API (impulses, queries, ...). The C
+//!   interface registers hooks with `physics_core_register_hook`.
+
+use std::ffi::c_void;
+use std::sync::{Arc, Mutex};
+
+use bevy_ecs::prelude::*;
+use once_cell::sync::Lazy;
+
+/// Where in the frame a hook is invoked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookPoint {
+    /// Before the physics step, after input events are queued
+    PrePhysics,
+    /// After the physics step
+    PostPhysics,
+    /// Before the instance buffer is synced and the frame is drawn
+    PreRender,
+    /// Once, at the start of the first frame after the scene was reset or
+    /// replaced; every entity id from before is dead by then
+    Reset,
+}
+
+impl HookPoint {
+    /// FFI code: 0 = pre-physics, 1 = post-physics, 2 = pre-render, 3 = reset
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(HookPoint::PrePhysics),
+            1 => Some(HookPoint::PostPhysics),
+            2 => Some(HookPoint::PreRender),
+            3 => Some(HookPoint::Reset),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(self) -> u32 {
+        match self {
+            HookPoint::PrePhysics => 0,
+            HookPoint::PostPhysics => 1,
+            HookPoint::PreRender => 2,
+            HookPoint::Reset => 3,
+        }
+    }
+}
+
+/// A Rust extension that installs systems into each new scene
+pub trait SimulationPlugin: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Called with the emptied world and fresh schedule of every scene
+    fn build(&self, world: &mut World, schedule: &mut Schedule);
+}
+
+/// Callback invoked at a hook point with the (time-scaled) frame dt.
+/// PreRender hooks receive 0.0.
+pub type Hook = Arc<dyn Fn(HookPoint, f32) + Send + Sync>;
+
+/// C hook signature: `(hook_point, dt, user_data)`
+pub type HostHookFn = extern "C" fn(u32, f32, *mut c_void);
+
+/// Registered hooks in registration order; ids are never reused
+#[derive(Default)]
+pub struct HookRegistry {
+    hooks: Vec<(u32, HookPoint, Hook)>,
+    next_id: u32,
+}
+
+impl HookRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id used to unregister; ids start at 1 so 0 can signal failure over FFI
+    pub fn register(&mut self, point: HookPoint, hook: Hook) -> u32 {
+        self.next_id += 1;
+        self.hooks.push((self.next_id, point, hook));
+        self.next_id
+    }
+
+    pub fn unregister(&mut self, id: u32) -> bool {
+        let before = self.hooks.len();
+        self.hooks.retain(|(hook_id, _, _)| *hook_id != id);
+        self.hooks.len() != before
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Hooks for `point`, cloned so they can be called after the registry lock is released
+    pub fn hooks_for(&self, point: HookPoint) -> Vec<Hook> {
+        self.hooks
+            .iter()
+            .filter(|(_, hook_point, _)| *hook_point == point)
+            .map(|(_, _, hook)| hook.clone())
+            .collect()
+    }
+}
+
+static HOOKS: Lazy<Mutex<HookRegistry>> = Lazy::new(|| Mutex::new(HookRegistry::new()));
+static PLUGINS: Lazy<Mutex<Vec<Arc<dyn SimulationPlugin>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Register a hook; returns its id (never 0)
+pub fn register_hook(point: HookPoint, hook: impl Fn(HookPoint, f32) + Send + Sync + 'static) -> u32 {
+    match HOOKS.lock() {
+        Ok(mut hooks) => hooks.register(point, Arc::new(hook)),
+        Err(_) => 0,
+    }
+}
+
+/// Host hooks carry an opaque pointer owned by the host
+struct HostHook {
+    func: HostHookFn,
+    user_data: *mut c_void,
+}
+
+// SAFETY: the pointer is only handed back to the host callback; the host is
+// responsible for making its user data usable from the simulation thread
+unsafe impl Send for HostHook {}
+unsafe impl Sync for HostHook {}
+
+impl HostHook {
+    // Through a method, closures capture the whole hook rather than the bare
+    // pointer field, which is not Send
+    fn call(&self, point: HookPoint, dt: f32) {
+        (self.func)(point.as_u32(), dt, self.user_data)
+    }
+}
+
+pub(crate) fn register_host_hook(point: HookPoint, func: HostHookFn, user_data: *mut c_void) -> u32 {
+    let host = HostHook { func, user_data };
+    register_hook(point, move |point, dt| host.call(point, dt))
+}
+
+pub fn unregister_hook(id: u32) -> bool {
+    HOOKS.lock().map(|mut hooks| hooks.unregister(id)).unwrap_or(false)
+}
+
+/// Invoke every hook registered for `point`
+pub(crate) fn run_hooks(point: HookPoint, dt: f32) {
+    // Snapshot first: hooks may register or unregister hooks themselves
+    let hooks = match HOOKS.lock() {
+        Ok(hooks) if !hooks.is_empty() => hooks.hooks_for(point),
+        _ => return,
+    };
+    for hook in hooks {
+        hook(point, dt);
+    }
+}
+
+/// Install `plugin` in the current scene and in every scene created afterwards
+pub fn register_plugin(plugin: impl SimulationPlugin + 'static) {
+    let plugin: Arc<dyn SimulationPlugin> = Arc::new(plugin);
+    match PLUGINS.lock() {
+        Ok(mut plugins) => plugins.push(plugin.clone()),
+        Err(_) => return,
+    }
+    log::info!("Registered simulation plugin '{}'", plugin.name());
+
+    if let Ok(mut guard) = crate::PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            plugin.build(&mut physics.world, &mut physics.schedule);
+        }
+    }
+}
+
+/// Let every registered plugin extend a new scene
+pub(crate) fn apply_plugins(world: &mut World, schedule: &mut Schedule) {
+    let plugins = match PLUGINS.lock() {
+        Ok(plugins) => plugins.clone(),
+        Err(_) => return,
+    };
+    for plugin in plugins {
+        plugin.build(world, schedule);
+    }
+}
@@ -0,0 +1,346 @@
+//! Minimal PNG decoding for textures dropped on the desktop app
+//!
+//! `decode` turns a whole PNG file into RGBA8 pixels. Grayscale, RGB,
+//! palette, grayscale with alpha and RGBA images are read at 8 bits per
+//! channel, and at 16 bits for all but palette images (keeping the high
+//! byte); a `tRNS` chunk gives palette entries their alpha. Interlaced
+//! images and bit depths below 8 are not supported, and chunk CRCs are not
+//! checked.
+
+/// Most pixels in one image
+pub const MAX_PIXELS: u64 = 1 << 24;
+
+const SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// Decoded image, rows top to bottom
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// Decode a PNG file into RGBA8
+pub fn decode(bytes: &[u8]) -> Result<Image, String> {
+    let mut rest = bytes.strip_prefix(SIGNATURE.as_slice()).ok_or("not a PNG file")?;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut zlib = Vec::new();
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let (kind, body) = (&rest[4..8], &rest[8..]);
+        let data = body.get(..len).ok_or("truncated chunk")?;
+        match kind {
+            b"IHDR" => header = Some(Header::parse(data)?),
+            b"PLTE" => palette = data,
+            b"tRNS" => transparency = data,
+            b"IDAT" => zlib.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        // Skip the data and its CRC
+        rest = body.get(len + 4..).ok_or("truncated chunk")?;
+    }
+    let header = header.ok_or("missing IHDR chunk")?;
+    if header.color_type == 3 && palette.is_empty() {
+        return Err("missing PLTE chunk".into());
+    }
+
+    let pixel_bytes = header.channels() * header.bit_depth as usize / 8;
+    let row_bytes = header.width as usize * pixel_bytes;
+    let raw = zlib_decompress(&zlib, (row_bytes + 1) * header.height as usize)?;
+    let pixels = unfilter(&raw, row_bytes, header.height as usize, pixel_bytes)?;
+
+    let mut rgba = Vec::with_capacity(header.width as usize * header.height as usize * 4);
+    let step = header.bit_depth as usize / 8;
+    for pixel in pixels.chunks_exact(pixel_bytes) {
+        // High byte of each sample
+        let sample = |i: usize| pixel[i * step];
+        match header.color_type {
+            0 => rgba.extend_from_slice(&[sample(0), sample(0), sample(0), 255]),
+            2 => rgba.extend_from_slice(&[sample(0), sample(1), sample(2), 255]),
+            3 => {
+                let index = pixel[0] as usize;
+                let color = palette.get(index * 3..index * 3 + 3).ok_or("palette index out of range")?;
+                rgba.extend_from_slice(color);
+                rgba.push(transparency.get(index).copied().unwrap_or(255));
+            }
+            4 => rgba.extend_from_slice(&[sample(0), sample(0), sample(0), sample(1)]),
+            _ => rgba.extend_from_slice(&[sample(0), sample(1), sample(2), sample(3)]),
+        }
+    }
+    Ok(Image { width: header.width, height: header.height, rgba })
+}
+
+struct Header {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() != 13 {
+            return Err("bad IHDR chunk".into());
+        }
+        let width = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        let height = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+        let (bit_depth, color_type, interlace) = (data[8], data[9], data[12]);
+        if width == 0 || height == 0 || width as u64 * height as u64 > MAX_PIXELS {
+            return Err(format!("unsupported image size {}x{}", width, height));
+        }
+        match (color_type, bit_depth) {
+            (0 | 2 | 4 | 6, 8 | 16) | (3, 8) => {}
+            _ => return Err(format!("unsupported color type {} at bit depth {}", color_type, bit_depth)),
+        }
+        if interlace != 0 {
+            return Err("interlaced images are not supported".into());
+        }
+        Ok(Self { width, height, bit_depth, color_type })
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            0 | 3 => 1,
+            4 => 2,
+            2 => 3,
+            _ => 4,
+        }
+    }
+}
+
+/// Undo the per-row filters of `height` rows of `row_bytes` each
+fn unfilter(raw: &[u8], row_bytes: usize, height: usize, pixel_bytes: usize) -> Result<Vec<u8>, String> {
+    if raw.len() < (row_bytes + 1) * height {
+        return Err("image data too short".into());
+    }
+    let mut out = vec![0u8; row_bytes * height];
+    for y in 0..height {
+        let filter = raw[y * (row_bytes + 1)];
+        let line = &raw[y * (row_bytes + 1) + 1..(y + 1) * (row_bytes + 1)];
+        let (done, current) = out.split_at_mut(y * row_bytes);
+        let current = &mut current[..row_bytes];
+        let previous = (y > 0).then(|| &done[(y - 1) * row_bytes..]);
+        for i in 0..row_bytes {
+            let left = if i >= pixel_bytes { current[i - pixel_bytes] } else { 0 };
+            let up = previous.map_or(0, |row| row[i]);
+            let up_left = if i >= pixel_bytes { previous.map_or(0, |row| row[i - pixel_bytes]) } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(format!("unknown filter type {}", filter)),
+            };
+            current[i] = line[i].wrapping_add(predicted);
+        }
+    }
+    Ok(out)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Inflate a zlib stream, stopping once `limit` bytes are out
+fn zlib_decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    if data.len() < 2 || (data[0] & 0x0F) != 8 || !u16::from_be_bytes([data[0], data[1]]).is_multiple_of(31) {
+        return Err("bad zlib header".into());
+    }
+    if data[1] & 0x20 != 0 {
+        return Err("zlib preset dictionaries are not supported".into());
+    }
+    let mut inflater = Inflater { bits: BitReader { data: &data[2..], pos: 0, bit: 0 }, out: Vec::with_capacity(limit), limit };
+    inflater.run()?;
+    Ok(inflater.out)
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.data.get(self.pos).ok_or("deflate stream ended early")?;
+            value |= ((byte >> self.bit) as u32 & 1) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// Canonical Huffman code: symbol counts per length and symbols by code
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for len in 1..16 {
+            offsets[len] = offsets[len - 1] + counts[len - 1];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize).copied().ok_or_else(|| "bad Huffman code".into());
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("bad Huffman code".into())
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+/// Order code length code lengths are stored in
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+struct Inflater<'a> {
+    bits: BitReader<'a>,
+    out: Vec<u8>,
+    limit: usize,
+}
+
+impl Inflater<'_> {
+    fn run(&mut self) -> Result<(), String> {
+        loop {
+            let last = self.bits.bits(1)? == 1;
+            match self.bits.bits(2)? {
+                0 => self.stored()?,
+                1 => {
+                    let mut lengths = [8u8; 288];
+                    lengths[144..256].fill(9);
+                    lengths[256..280].fill(7);
+                    self.codes(&Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+                }
+                2 => {
+                    let (literals, distances) = self.dynamic_tables()?;
+                    self.codes(&literals, &distances)?;
+                }
+                _ => return Err("bad deflate block type".into()),
+            }
+            if last || self.out.len() >= self.limit {
+                return Ok(());
+            }
+        }
+    }
+
+    fn stored(&mut self) -> Result<(), String> {
+        self.bits.align();
+        let data = self.bits.data;
+        let pos = self.bits.pos;
+        let header = data.get(pos..pos + 4).ok_or("deflate stream ended early")?;
+        let len = u16::from_le_bytes([header[0], header[1]]);
+        if len != !u16::from_le_bytes([header[2], header[3]]) {
+            return Err("bad stored block length".into());
+        }
+        let block = data.get(pos + 4..pos + 4 + len as usize).ok_or("deflate stream ended early")?;
+        self.out.extend_from_slice(block);
+        self.bits.pos = pos + 4 + len as usize;
+        Ok(())
+    }
+
+    fn dynamic_tables(&mut self) -> Result<(Huffman, Huffman), String> {
+        let literal_count = self.bits.bits(5)? as usize + 257;
+        let distance_count = self.bits.bits(5)? as usize + 1;
+        let code_length_count = self.bits.bits(4)? as usize + 4;
+        let mut code_lengths = [0u8; 19];
+        for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+            code_lengths[index] = self.bits.bits(3)? as u8;
+        }
+        let code_length_code = Huffman::new(&code_lengths);
+
+        let mut lengths = Vec::with_capacity(literal_count + distance_count);
+        while lengths.len() < literal_count + distance_count {
+            let (value, repeat) = match code_length_code.decode(&mut self.bits)? {
+                symbol @ 0..=15 => (symbol as u8, 1),
+                16 => (*lengths.last().ok_or("repeat with no previous length")?, 3 + self.bits.bits(2)?),
+                17 => (0, 3 + self.bits.bits(3)?),
+                _ => (0, 11 + self.bits.bits(7)?),
+            };
+            lengths.resize(lengths.len() + repeat as usize, value);
+        }
+        if lengths.len() > literal_count + distance_count {
+            return Err("code lengths overrun".into());
+        }
+        Ok((Huffman::new(&lengths[..literal_count]), Huffman::new(&lengths[literal_count..])))
+    }
+
+    fn codes(&mut self, literals: &Huffman, distances: &Huffman) -> Result<(), String> {
+        loop {
+            let symbol = literals.decode(&mut self.bits)? as usize;
+            match symbol {
+                0..=255 => self.out.push(symbol as u8),
+                256 => return Ok(()),
+                _ => {
+                    let index = symbol - 257;
+                    let base = *LENGTH_BASE.get(index).ok_or("bad length code")?;
+                    let len = base as usize + self.bits.bits(LENGTH_EXTRA[index] as u32)? as usize;
+                    let index = distances.decode(&mut self.bits)? as usize;
+                    let base = *DISTANCE_BASE.get(index).ok_or("bad distance code")?;
+                    let distance = base as usize + self.bits.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+                    let start = self.out.len().checked_sub(distance).ok_or("distance too far back")?;
+                    for i in 0..len {
+                        let byte = self.out[start + i];
+                        self.out.push(byte);
+                    }
+                }
+            }
+            if self.out.len() > self.limit {
+                return Err("image data too long".into());
+            }
+        }
+    }
+}
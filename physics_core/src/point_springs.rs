@@ -0,0 +1,71 @@
+//! Springs from a body to a point in the world
+//!
+//! Hosts use these for elastic grabbing, tractor beams or anchored
+//! decorations. Each spring is a Rapier spring joint between the body and a
+//! fixed anchor body at the origin that all springs share, so the world point
+//! is simply the joint's first local anchor and moving it each frame is cheap.
+//! Joints live in the impulse joint set, which snapshots already capture.
+//! Removing a body removes its springs with it.
+
+use crate::rapier::prelude::*;
+
+use crate::precision::{planar, to_real};
+use crate::PhysicsState;
+
+/// Force-based spring of rest length 0 pulling the body's center to `point`.
+/// `stiffness` is in N/m and `damping` in N·s/m.
+pub fn spring_joint(point: [f32; 2], stiffness: f32, damping: f32) -> GenericJoint {
+    SpringJointBuilder::new(0.0, to_real(stiffness), to_real(damping))
+        .spring_model(MotorModel::ForceBased)
+        .local_anchor1(planar(point).into())
+        .local_anchor2(point![0.0, 0.0, 0.0])
+        .build()
+        .into()
+}
+
+/// Host-facing id of a joint; never 0
+pub fn spring_id(handle: ImpulseJointHandle) -> u64 {
+    let (index, generation) = handle.0.into_raw_parts();
+    ((generation as u64) << 32) | (index as u64 + 1)
+}
+
+pub fn spring_handle(id: u64) -> Option<ImpulseJointHandle> {
+    let index = (id & 0xffff_ffff).checked_sub(1)?;
+    Some(ImpulseJointHandle::from_raw_parts(index as u32, (id >> 32) as u32))
+}
+
+/// The shared anchor, created on first use and again after a rewind to
+/// before it existed
+fn anchor(physics: &mut PhysicsState) -> RigidBodyHandle {
+    match physics.spring_anchor {
+        Some(handle) if physics.rigid_body_set.contains(handle) => handle,
+        _ => {
+            let handle = physics.rigid_body_set.insert(RigidBodyBuilder::fixed().build());
+            physics.spring_anchor = Some(handle);
+            handle
+        }
+    }
+}
+
+pub(crate) fn create(physics: &mut PhysicsState, body: RigidBodyHandle, point: [f32; 2], stiffness: f32, damping: f32) -> u64 {
+    let anchor = anchor(physics);
+    let handle = physics.impulse_joint_set.insert(anchor, body, spring_joint(point, stiffness, damping), true);
+    spring_id(handle)
+}
+
+/// Move the spring's point and change its strength. Returns false for unknown ids.
+pub(crate) fn update(physics: &mut PhysicsState, id: u64, point: [f32; 2], stiffness: f32, damping: f32) -> bool {
+    let Some(joint) = spring_handle(id).and_then(|handle| physics.impulse_joint_set.get_mut(handle)) else {
+        return false;
+    };
+    joint.data = spring_joint(point, stiffness, damping);
+    let body = joint.body2;
+    if let Some(rb) = physics.rigid_body_set.get_mut(body) {
+        rb.wake_up(true);
+    }
+    true
+}
+
+pub(crate) fn remove(physics: &mut PhysicsState, id: u64) -> bool {
+    spring_handle(id).is_some_and(|handle| physics.impulse_joint_set.remove(handle, true).is_some())
+}
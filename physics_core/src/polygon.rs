@@ -0,0 +1,210 @@
+//! Concave polygon colliders
+//!
+//! Rapier only collides convex shapes, so an outline of any simple polygon,
+//! traced from an image or drawn by hand, is split into convex pieces first:
+//! ear clipping cuts it into triangles, then neighbouring triangles are
+//! merged back wherever the union stays convex (Hertel–Mehlhorn). That is
+//! never more than four times the fewest possible pieces and usually close
+//! to it. The pieces become one compound collider, so the outline is still a
+//! single body or a single level shape.
+//!
+//! Outlines may wind either way. Repeated points and points in the middle of
+//! a straight edge are dropped; outlines that cross or touch themselves are
+//! rejected.
+
+use crate::rapier::prelude::*;
+
+use crate::precision::to_real;
+
+/// Most points in one outline
+pub const MAX_POINTS: usize = 256;
+
+/// Half the depth of the extruded pieces, m
+const HALF_DEPTH: Real = 0.05;
+
+/// Cross products and squared distances below this count as zero
+const EPSILON: f64 = 1e-12;
+
+type Point2 = [f64; 2];
+
+fn cross(o: Point2, a: Point2, b: Point2) -> f64 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+fn same(a: Point2, b: Point2) -> bool {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) <= EPSILON
+}
+
+/// Twice the signed area; positive for counter-clockwise outlines
+fn doubled_area(points: &[Point2]) -> f64 {
+    (0..points.len()).map(|i| cross([0.0, 0.0], points[i], points[(i + 1) % points.len()])).sum()
+}
+
+/// Outline without repeated points or points along a straight edge
+fn simplify(mut points: Vec<Point2>) -> Vec<Point2> {
+    loop {
+        let n = points.len();
+        let redundant = (0..n).find(|&i| {
+            let (prev, point, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+            same(prev, point) || cross(prev, point, next).abs() <= EPSILON
+        });
+        match redundant {
+            Some(i) if n > 3 => {
+                points.remove(i);
+            }
+            _ => return points,
+        }
+    }
+}
+
+/// Whether segments ab and cd share any point
+fn segments_touch(a: Point2, b: Point2, c: Point2, d: Point2) -> bool {
+    let side = |o, p, q| {
+        let value: f64 = cross(o, p, q);
+        if value.abs() <= EPSILON {
+            0
+        } else {
+            value.signum() as i32
+        }
+    };
+    let within = |p: Point2, q: Point2, r: Point2| {
+        r[0] >= p[0].min(q[0]) - 1e-9 && r[0] <= p[0].max(q[0]) + 1e-9 && r[1] >= p[1].min(q[1]) - 1e-9 && r[1] <= p[1].max(q[1]) + 1e-9
+    };
+    let (d1, d2, d3, d4) = (side(a, b, c), side(a, b, d), side(c, d, a), side(c, d, b));
+    if d1 * d2 < 0 && d3 * d4 < 0 {
+        return true;
+    }
+    (d1 == 0 && within(a, b, c)) || (d2 == 0 && within(a, b, d)) || (d3 == 0 && within(c, d, a)) || (d4 == 0 && within(c, d, b))
+}
+
+fn is_simple(points: &[Point2]) -> bool {
+    let n = points.len();
+    (0..n).all(|i| {
+        // Edges next to edge i share a corner with it by design
+        (i + 2..n).filter(|&j| (j + 1) % n != i).all(|j| {
+            !segments_touch(points[i], points[(i + 1) % n], points[j], points[(j + 1) % n])
+        })
+    })
+}
+
+/// Whether `p` lies inside or on triangle abc, wound counter-clockwise
+fn in_triangle(p: Point2, a: Point2, b: Point2, c: Point2) -> bool {
+    cross(a, b, p) >= -EPSILON && cross(b, c, p) >= -EPSILON && cross(c, a, p) >= -EPSILON
+}
+
+/// Triangles of a counter-clockwise simple outline, as indices into it
+fn triangulate(points: &[Point2]) -> Option<Vec<[usize; 3]>> {
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::with_capacity(points.len() - 2);
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (remaining[(i + n - 1) % n], remaining[i], remaining[(i + 1) % n]);
+            cross(points[a], points[b], points[c]) > EPSILON
+                && remaining
+                    .iter()
+                    .filter(|&&k| k != a && k != b && k != c)
+                    .all(|&k| !in_triangle(points[k], points[a], points[b], points[c]))
+        })?;
+        triangles.push([remaining[(ear + n - 1) % n], remaining[ear], remaining[(ear + 1) % n]]);
+        remaining.remove(ear);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    Some(triangles)
+}
+
+fn is_convex(points: &[Point2], piece: &[usize]) -> bool {
+    let n = piece.len();
+    (0..n).all(|i| cross(points[piece[(i + n - 1) % n]], points[piece[i]], points[piece[(i + 1) % n]]) >= -EPSILON)
+}
+
+/// `a` and `b` joined along an edge they share in opposite directions, or
+/// None when they share no edge
+fn join(a: &[usize], b: &[usize]) -> Option<Vec<usize>> {
+    let (na, nb) = (a.len(), b.len());
+    (0..na).find_map(|i| {
+        let (from, to) = (a[i], a[(i + 1) % na]);
+        let j = (0..nb).find(|&j| b[j] == to && b[(j + 1) % nb] == from)?;
+        // Around `a` from `to` back to `from`, then around `b` from just
+        // after `from` to just before `to`
+        let mut joined: Vec<usize> = (1..=na).map(|k| a[(i + k) % na]).collect();
+        joined.extend((2..nb).map(|k| b[(j + k) % nb]));
+        Some(joined)
+    })
+}
+
+/// Split a simple polygon outline, in either winding, into convex pieces
+/// wound counter-clockwise
+pub fn decompose(points: &[[f32; 2]]) -> Result<Vec<Vec<[f32; 2]>>, String> {
+    if !(3..=MAX_POINTS).contains(&points.len()) {
+        return Err(format!("a polygon needs 3 to {} points", MAX_POINTS));
+    }
+    if !points.iter().flatten().all(|v| v.is_finite()) {
+        return Err("polygon points must be finite".into());
+    }
+    let mut outline = simplify(points.iter().map(|p| [p[0] as f64, p[1] as f64]).collect());
+    let area = doubled_area(&outline);
+    if outline.len() < 3 || area.abs() <= EPSILON {
+        return Err("polygon has no area".into());
+    }
+    if area < 0.0 {
+        outline.reverse();
+    }
+    if !is_simple(&outline) {
+        return Err("polygon outline crosses itself".into());
+    }
+
+    let mut pieces: Vec<Vec<usize>> = triangulate(&outline)
+        .ok_or("polygon could not be triangulated")?
+        .into_iter()
+        .map(Vec::from)
+        .collect();
+    // Merge neighbours until no union stays convex
+    let mut merged = true;
+    while merged {
+        merged = false;
+        'search: for i in 0..pieces.len() {
+            for j in i + 1..pieces.len() {
+                if let Some(joined) = join(&pieces[i], &pieces[j]).filter(|joined| is_convex(&outline, joined)) {
+                    pieces[i] = joined;
+                    pieces.swap_remove(j);
+                    merged = true;
+                    break 'search;
+                }
+            }
+        }
+    }
+    Ok(pieces
+        .into_iter()
+        .map(|piece| {
+            let piece: Vec<Point2> = piece.into_iter().map(|i| outline[i]).collect();
+            simplify(piece).into_iter().map(|p| [p[0] as f32, p[1] as f32]).collect()
+        })
+        .collect())
+}
+
+/// Compound collider of the outline's convex pieces, relative to the body
+pub fn collider(points: &[[f32; 2]]) -> Result<ColliderBuilder, String> {
+    compound(&decompose(points)?, 1.0).map(ColliderBuilder::new)
+}
+
+/// Convex `pieces` scaled by `scale` and extruded into one compound shape
+pub fn compound(pieces: &[Vec<[f32; 2]>], scale: f32) -> Result<SharedShape, String> {
+    let mut shapes = Vec::new();
+    for piece in pieces {
+        let prism: Vec<Point<Real>> = piece
+            .iter()
+            .map(|p| [to_real(p[0] * scale), to_real(p[1] * scale)])
+            .flat_map(|[x, y]| [point![x, y, -HALF_DEPTH], point![x, y, HALF_DEPTH]])
+            .collect();
+        let hull = SharedShape::convex_hull(&prism).ok_or("polygon piece is too thin")?;
+        shapes.push((Isometry::identity(), hull));
+    }
+    Ok(SharedShape::compound(shapes))
+}
+
+/// Furthest any outline point lies from the body's origin along either
+/// axis, so the body's drawn square covers the outline
+pub fn half_extent(points: &[[f32; 2]]) -> f32 {
+    points.iter().flatten().fold(0.0, |extent: f32, v| extent.max(v.abs()))
+}
@@ -0,0 +1,466 @@
+//! Post-processing: HDR scene target with a compute-based bloom pass
+//!
+//! When bloom is enabled the scene is drawn into an `Rgba16Float` offscreen
+//! texture, bright areas are extracted and blurred at half resolution with
+//! compute shaders, and the result is composited onto the swapchain.
+
+use wgpu::util::DeviceExt;
+
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct BloomParams {
+    threshold: f32,
+    intensity: f32,
+    _pad: [f32; 2],
+}
+
+/// Size-dependent textures and the bind groups that reference them
+struct BloomTargets {
+    hdr_view: wgpu::TextureView,
+    bloom_size: (u32, u32),
+    threshold_bind_group: wgpu::BindGroup,
+    blur_h_bind_group: wgpu::BindGroup,
+    blur_v_bind_group: wgpu::BindGroup,
+    composite_bind_group: wgpu::BindGroup,
+}
+
+pub(crate) struct BloomPass {
+    scene_pipeline: wgpu::RenderPipeline,
+    threshold_pipeline: wgpu::ComputePipeline,
+    blur_h_pipeline: wgpu::ComputePipeline,
+    blur_v_pipeline: wgpu::ComputePipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    compute_layout: wgpu::BindGroupLayout,
+    composite_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    params_buffer: wgpu::Buffer,
+    targets: BloomTargets,
+}
+
+impl BloomPass {
+    /// `scene_shader`/`scene_layout`/`vertex_buffers` describe the main instanced
+    /// pipeline; an HDR variant of it is built here using `fs_main_emissive`.
+    pub fn new(
+        device: &wgpu::Device,
+        scene_shader: &wgpu::ShaderModule,
+        scene_layout: &wgpu::PipelineLayout,
+        vertex_buffers: &[wgpu::VertexBufferLayout],
+        output_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let scene_pipeline = create_hdr_scene_pipeline(device, scene_shader, scene_layout, vertex_buffers);
+
+        let bloom_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Bloom Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/bloom.wgsl"))),
+        });
+
+        let compute_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: HDR_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("bloom_compute_bind_group_layout"),
+        });
+
+        let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Compute Pipeline Layout"),
+            bind_group_layouts: &[&compute_layout],
+            push_constant_ranges: &[],
+        });
+
+        let make_compute = |label: &str, entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&compute_pipeline_layout),
+                module: &bloom_shader,
+                entry_point: Some(entry_point),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            })
+        };
+        let threshold_pipeline = make_compute("Bloom Threshold Pipeline", "threshold_main");
+        let blur_h_pipeline = make_compute("Bloom Blur H Pipeline", "blur_h_main");
+        let blur_v_pipeline = make_compute("Bloom Blur V Pipeline", "blur_v_main");
+
+        let composite_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Composite Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/composite.wgsl"))),
+        });
+
+        let composite_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("composite_bind_group_layout"),
+        });
+
+        let composite_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Composite Pipeline Layout"),
+            bind_group_layouts: &[&composite_layout],
+            push_constant_ranges: &[],
+        });
+
+        let composite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Composite Pipeline"),
+            layout: Some(&composite_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &composite_shader,
+                entry_point: Some("vs_fullscreen"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &composite_shader,
+                entry_point: Some("fs_composite"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Bloom Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Bloom Params Buffer"),
+            contents: bytemuck::cast_slice(&[BloomParams {
+                threshold: 1.0,
+                intensity: 1.0,
+                _pad: [0.0; 2],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let targets = Self::create_targets(
+            device,
+            &compute_layout,
+            &composite_layout,
+            &sampler,
+            &params_buffer,
+            width,
+            height,
+        );
+
+        Self {
+            scene_pipeline,
+            threshold_pipeline,
+            blur_h_pipeline,
+            blur_v_pipeline,
+            composite_pipeline,
+            compute_layout,
+            composite_layout,
+            sampler,
+            params_buffer,
+            targets,
+        }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        compute_layout: &wgpu::BindGroupLayout,
+        composite_layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        params_buffer: &wgpu::Buffer,
+        width: u32,
+        height: u32,
+    ) -> BloomTargets {
+        let width = width.max(1);
+        let height = height.max(1);
+        let bloom_size = ((width / 2).max(1), (height / 2).max(1));
+
+        let make_texture = |label: &str, (w, h): (u32, u32), usage: wgpu::TextureUsages| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: w,
+                        height: h,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: HDR_FORMAT,
+                    usage,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        let hdr_view = make_texture(
+            "HDR Scene Texture",
+            (width, height),
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        let bloom_usage = wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING;
+        let bloom_a = make_texture("Bloom Texture A", bloom_size, bloom_usage);
+        let bloom_b = make_texture("Bloom Texture B", bloom_size, bloom_usage);
+
+        let make_compute_bind_group = |label: &str, src: &wgpu::TextureView, dst: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: compute_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(src),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(dst),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: params_buffer.as_entire_binding(),
+                    },
+                ],
+                label: Some(label),
+            })
+        };
+
+        // threshold: scene -> A, blur H: A -> B, blur V: B -> A
+        let threshold_bind_group = make_compute_bind_group("bloom_threshold_bind_group", &hdr_view, &bloom_a);
+        let blur_h_bind_group = make_compute_bind_group("bloom_blur_h_bind_group", &bloom_a, &bloom_b);
+        let blur_v_bind_group = make_compute_bind_group("bloom_blur_v_bind_group", &bloom_b, &bloom_a);
+
+        let composite_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: composite_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&hdr_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&bloom_a),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("composite_bind_group"),
+        });
+
+        BloomTargets {
+            hdr_view,
+            bloom_size,
+            threshold_bind_group,
+            blur_h_bind_group,
+            blur_v_bind_group,
+            composite_bind_group,
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.targets = Self::create_targets(
+            device,
+            &self.compute_layout,
+            &self.composite_layout,
+            &self.sampler,
+            &self.params_buffer,
+            width,
+            height,
+        );
+    }
+
+    /// Pipeline to use for the instanced scene while bloom is active
+    /// Swap in a scene pipeline rebuilt from a hot-reloaded shader
+    pub fn set_scene_pipeline(&mut self, pipeline: wgpu::RenderPipeline) {
+        self.scene_pipeline = pipeline;
+    }
+
+    pub fn scene_pipeline(&self) -> &wgpu::RenderPipeline {
+        &self.scene_pipeline
+    }
+
+    /// Offscreen HDR target the scene pass renders into
+    pub fn hdr_view(&self) -> &wgpu::TextureView {
+        &self.targets.hdr_view
+    }
+
+    /// Encode threshold + blur compute passes and composite onto `output_view`
+    pub fn encode(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        output_view: &wgpu::TextureView,
+        threshold: f32,
+        intensity: f32,
+    ) {
+        queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::cast_slice(&[BloomParams {
+                threshold,
+                intensity,
+                _pad: [0.0; 2],
+            }]),
+        );
+
+        let (width, height) = self.targets.bloom_size;
+        let groups_x = width.div_ceil(8);
+        let groups_y = height.div_ceil(8);
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Bloom Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.threshold_pipeline);
+            compute_pass.set_bind_group(0, &self.targets.threshold_bind_group, &[]);
+            compute_pass.dispatch_workgroups(groups_x, groups_y, 1);
+
+            compute_pass.set_pipeline(&self.blur_h_pipeline);
+            compute_pass.set_bind_group(0, &self.targets.blur_h_bind_group, &[]);
+            compute_pass.dispatch_workgroups(groups_x, groups_y, 1);
+
+            compute_pass.set_pipeline(&self.blur_v_pipeline);
+            compute_pass.set_bind_group(0, &self.targets.blur_v_bind_group, &[]);
+            compute_pass.dispatch_workgroups(groups_x, groups_y, 1);
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.composite_pipeline);
+        render_pass.set_bind_group(0, &self.targets.composite_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+/// HDR variant of the main instanced pipeline using `fs_main_emissive`
+pub(crate) fn create_hdr_scene_pipeline(
+    device: &wgpu::Device,
+    scene_shader: &wgpu::ShaderModule,
+    scene_layout: &wgpu::PipelineLayout,
+    vertex_buffers: &[wgpu::VertexBufferLayout],
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("HDR Scene Pipeline"),
+        layout: Some(scene_layout),
+        vertex: wgpu::VertexState {
+            module: scene_shader,
+            entry_point: Some("vs_main"),
+            buffers: vertex_buffers,
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: scene_shader,
+            entry_point: Some("fs_main_emissive"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: HDR_FORMAT,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
@@ -0,0 +1,38 @@
+//! Floating-point precision of the simulation
+//!
+//! Rapier runs in f32 by default. The `physics-f64` feature swaps in its f64
+//! build (`rapier3d-f64`) for large streamed worlds and long sessions, where
+//! f32 positions far from the origin or summed over hours of steps drift
+//! visibly. Only the simulation changes: ECS components, GPU instances,
+//! snapshots and the bindings stay f32, and values cross between the two
+//! with the helpers here. Code talking to Rapier imports it as
+//! `crate::rapier`, which names whichever build is in use.
+
+use crate::rapier::math::{Real, Rotation, Vector};
+
+/// An f32 in the simulation's precision
+#[allow(clippy::unnecessary_cast)] // a no-op in the f32 build
+pub fn to_real(value: f32) -> Real {
+    value as Real
+}
+
+/// A simulation value narrowed to f32
+#[allow(clippy::unnecessary_cast)]
+pub fn to_f32(value: Real) -> f32 {
+    value as f32
+}
+
+/// X and Y of a simulation vector, in f32
+pub fn xy(vector: &Vector<Real>) -> [f32; 2] {
+    [to_f32(vector.x), to_f32(vector.y)]
+}
+
+/// A simulation vector in the Z = 0 plane
+pub fn planar([x, y]: [f32; 2]) -> Vector<Real> {
+    Vector::new(to_real(x), to_real(y), 0.0)
+}
+
+/// A simulation rotation of `angle` radians in the Z = 0 plane
+pub fn planar_rotation(angle: f32) -> Rotation<Real> {
+    Rotation::from_axis_angle(&Vector::z_axis(), to_real(angle))
+}
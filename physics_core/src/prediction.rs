@@ -0,0 +1,187 @@
+//! Client-side prediction with rollback
+//!
+//! A client tags the bodies it drives itself as `Predicted` and runs them
+//! ahead of the authority on its own input. With a rollback window set, every
+//! step keeps the input it consumed and where the predicted bodies ended up.
+//! When an authoritative snapshot (see `netcode`) arrives for a tick inside
+//! the window, `reconcile_snapshot` compares it with what was predicted for
+//! that tick:
+//!
+//! - within `tolerance`: the prediction stands and only the other bodies
+//!   ease toward the snapshot, as with `apply_snapshot`
+//! - further off: the world rewinds to that tick, every body takes the
+//!   authoritative state and the steps since are run again with the same
+//!   input
+//!
+//! Ticks are step counts, so both sides must step in lockstep from the same
+//! start. Rewinding uses the snapshot history, whose capacity is raised to
+//! cover the window. Only input events are replayed; impulses applied
+//! through the API between steps are not, and collision, trigger and audio
+//! events of re-run steps are raised again.
+
+use std::collections::VecDeque;
+
+use bevy_ecs::prelude::*;
+
+use crate::events::{EventQueue, GameEvent};
+use crate::netcode::{self, NetBody};
+use crate::{PhysicsBody, PhysicsState};
+
+/// Longest rollback window in steps, ten seconds at 60 Hz
+pub const MAX_WINDOW: u32 = 600;
+
+/// Error up to which a prediction stands, in m, radians and m/s
+pub const DEFAULT_TOLERANCE: f32 = 0.01;
+
+/// Marks a body the local side simulates ahead of the authority
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Predicted;
+
+/// One local step: its length, the input it consumed and where the
+/// predicted bodies ended up, sorted by entity id
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub tick: u64,
+    pub dt: f32,
+    pub inputs: Vec<GameEvent>,
+    pub predicted: Vec<NetBody>,
+}
+
+/// Recent frames for rolling back; a window of 0 keeps none
+#[derive(Debug, Default)]
+pub struct Prediction {
+    window: usize,
+    tolerance: f32,
+    frames: VecDeque<Frame>,
+}
+
+impl Prediction {
+    /// None for a window above `MAX_WINDOW` or a negative or non-finite
+    /// tolerance
+    pub fn new(window: u32, tolerance: f32) -> Option<Self> {
+        if window > MAX_WINDOW || !tolerance.is_finite() || tolerance < 0.0 {
+            return None;
+        }
+        Some(Self { window: window as usize, tolerance, frames: VecDeque::with_capacity(window as usize) })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.window > 0
+    }
+
+    pub fn window(&self) -> u32 {
+        self.window as u32
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Keep `frame`. Frames at or past its tick were rolled back and are
+    /// dropped, as is the oldest frame once the window is full.
+    pub fn record(&mut self, frame: Frame) {
+        if !self.is_enabled() {
+            return;
+        }
+        while self.frames.back().is_some_and(|newest| newest.tick >= frame.tick) {
+            self.frames.pop_back();
+        }
+        if self.frames.len() == self.window {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    pub fn frame(&self, tick: u64) -> Option<&Frame> {
+        self.frames.iter().find(|frame| frame.tick == tick)
+    }
+
+    /// Frames after `tick`, oldest first
+    pub fn frames_after(&self, tick: u64) -> impl Iterator<Item = &Frame> {
+        self.frames.iter().filter(move |frame| frame.tick > tick)
+    }
+
+    /// Whether a body predicted for `tick` is further than the tolerance
+    /// from `authoritative` (sorted by entity id); None when the tick is
+    /// outside the window. Bodies the authority does not list are ignored.
+    pub fn mispredicted(&self, tick: u64, authoritative: &[NetBody]) -> Option<bool> {
+        let frame = self.frame(tick)?;
+        Some(frame.predicted.iter().any(|predicted| {
+            let Ok(i) = authoritative.binary_search_by_key(&predicted.entity_id, |body| body.entity_id) else {
+                return false;
+            };
+            let target = &authoritative[i];
+            let off = |a: [f32; 2], b: [f32; 2]| (a[0] - b[0]).hypot(a[1] - b[1]) > self.tolerance;
+            off(predicted.position, target.position)
+                || off(predicted.linvel, target.linvel)
+                || (predicted.rotation - target.rotation).abs() > self.tolerance
+                || (predicted.angvel - target.angvel).abs() > self.tolerance
+        }))
+    }
+}
+
+/// Predicted bodies' states, sorted by entity id
+fn predicted_bodies(physics: &mut PhysicsState) -> Vec<NetBody> {
+    let PhysicsState { world, rigid_body_set, .. } = physics;
+    let mut bodies: Vec<NetBody> = world
+        .query_filtered::<(Entity, &PhysicsBody), With<Predicted>>()
+        .iter(world)
+        .filter_map(|(entity, body)| Some(netcode::net_body(entity.to_bits(), rigid_body_set.get(body.rigid_body_handle)?)))
+        .collect();
+    bodies.sort_by_key(|body| body.entity_id);
+    bodies
+}
+
+/// Keep the step just taken, which consumed `inputs`
+pub(crate) fn record(physics: &mut PhysicsState, dt: f32, inputs: Vec<GameEvent>) {
+    let predicted = predicted_bodies(physics);
+    let tick = physics.step_count;
+    physics.prediction.record(Frame { tick, dt, inputs, predicted });
+}
+
+/// Check the prediction against an authoritative snapshot and roll back and
+/// re-simulate if it was off. Returns the snapshot's tick, to be
+/// acknowledged to the sender.
+pub(crate) fn reconcile(physics: &mut PhysicsState, bytes: &[u8]) -> Result<u64, String> {
+    let (delta, bodies) = netcode::receive(physics, bytes)?;
+    let steps = physics.step_count.saturating_sub(delta.tick) as usize;
+    let can_rewind = steps == 0 || physics.history.len() > steps;
+    let prediction = physics.prediction.mispredicted(delta.tick, &bodies);
+    if prediction != Some(true) || !can_rewind {
+        for &id in &delta.removed {
+            netcode::despawn(physics, id);
+        }
+        // A prediction that stands keeps running ahead of the snapshot
+        let keep_predicted = prediction == Some(false);
+        for target in &bodies {
+            let predicted = netcode::live_body(physics, target.entity_id)
+                .is_some_and(|(entity, _)| physics.world.get::<Predicted>(entity).is_some());
+            if !(keep_predicted && predicted) {
+                netcode::correct(physics, target);
+            }
+        }
+        return Ok(delta.tick);
+    }
+
+    let replay: Vec<(f32, Vec<GameEvent>)> =
+        physics.prediction.frames_after(delta.tick).map(|frame| (frame.dt, frame.inputs.clone())).collect();
+    if steps > 0 {
+        crate::snapshot::rewind(physics, steps);
+    }
+    for &id in &delta.removed {
+        netcode::despawn(physics, id);
+    }
+    for target in &bodies {
+        netcode::set_state(physics, target);
+    }
+
+    // Input waiting for the next regular step is put back afterwards
+    let pending = std::mem::take(&mut physics.world.resource_mut::<EventQueue>().events);
+    for (dt, inputs) in replay {
+        physics.world.resource_mut::<EventQueue>().events = inputs;
+        crate::simulate_step(physics, dt);
+    }
+    physics.world.resource_mut::<EventQueue>().events = pending;
+    log::debug!("reconcile: rolled back and re-ran {} steps from tick {}", steps, delta.tick);
+    Ok(delta.tick)
+}
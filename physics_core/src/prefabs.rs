@@ -0,0 +1,236 @@
+//! Prefabs: entity templates spawned by name
+//!
+//! A prefab describes a body once (its shape, body type, groups, material,
+//! sprite clip and movement or state machine) and `spawn_prefab` makes a
+//! copy of it anywhere, so hosts send a name and a position instead of the
+//! whole description for every crate or enemy. Groups double as the tags
+//! queries, contact filters and group despawns select bodies by.
+//!
+//! Parts make compound prefabs: each part is another prefab spawned at an
+//! offset in the prefab's frame and attached to it (see `hierarchy`), so a
+//! cart is a body with two wheel prefabs riding on it. Parts may have parts
+//! of their own as long as the chain stays within `hierarchy::MAX_DEPTH`
+//! links; a prefab containing itself is turned down the same way.
+//!
+//! Scenes carry their own prefabs, registered when the scene loads, and can
+//! place instances of them; hosts can also register prefabs at any time.
+//! Registering a name again only changes later spawns. Part names are looked
+//! up when a prefab spawns, so prefabs can be registered in any order.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::groups;
+use crate::hierarchy::{self, Pose2D};
+use crate::material::DEFAULT_MATERIAL;
+use crate::polygon;
+use crate::scenes::SceneBodyType;
+use crate::state_machine::{AnimationClip, MovementDesc, StateMachineDesc};
+
+/// Most prefabs the library holds
+pub const MAX_PREFABS: usize = 256;
+/// Most bodies one prefab spawns, parts included
+pub const MAX_PREFAB_BODIES: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum PrefabShape {
+    Box { half_extent: f32 },
+    /// Outline around the prefab's origin, concave or not (see `polygon`)
+    Polygon { points: Vec<[f32; 2]> },
+}
+
+impl Default for PrefabShape {
+    fn default() -> Self {
+        Self::Box { half_extent: 0.05 }
+    }
+}
+
+/// Another prefab spawned as part of this one, at (x, y) turned by `angle`
+/// radians in this prefab's frame
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefabPart {
+    pub prefab: String,
+    #[serde(default)]
+    pub x: f32,
+    #[serde(default)]
+    pub y: f32,
+    #[serde(default)]
+    pub angle: f32,
+}
+
+impl PrefabPart {
+    pub fn offset(&self) -> Pose2D {
+        Pose2D { translation: [self.x, self.y], rotation: self.angle }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrefabDesc {
+    pub shape: PrefabShape,
+    pub body_type: SceneBodyType,
+    pub groups: u32,
+    pub material_id: u32,
+    /// Sprite sheet cells the body plays
+    pub clip: Option<AnimationClip>,
+    pub movement: Option<MovementDesc>,
+    pub state_machine: Option<StateMachineDesc>,
+    pub parts: Vec<PrefabPart>,
+}
+
+impl Default for PrefabDesc {
+    fn default() -> Self {
+        Self {
+            shape: PrefabShape::default(),
+            body_type: SceneBodyType::Dynamic,
+            groups: groups::DEFAULT,
+            material_id: DEFAULT_MATERIAL,
+            clip: None,
+            movement: None,
+            state_machine: None,
+            parts: Vec::new(),
+        }
+    }
+}
+
+impl PrefabDesc {
+    /// Check the prefab on its own; part names are checked when it spawns
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.shape {
+            PrefabShape::Box { half_extent } => {
+                if !half_extent.is_finite() || *half_extent <= 0.0 {
+                    return Err(format!("box half extent must be positive, got {}", half_extent));
+                }
+            }
+            PrefabShape::Polygon { points } => {
+                if points.len() < 3 || points.len() > polygon::MAX_POINTS {
+                    return Err(format!("polygons need 3 to {} points", polygon::MAX_POINTS));
+                }
+                if !points.iter().flatten().all(|v| v.is_finite()) {
+                    return Err("polygon points must be finite".into());
+                }
+            }
+        }
+        if let Some(clip) = &self.clip {
+            clip.validate()?;
+        }
+        if let Some(movement) = &self.movement {
+            movement.validate()?;
+        }
+        if let Some(machine) = &self.state_machine {
+            machine.validate()?;
+        }
+        if self.parts.len() >= MAX_PREFAB_BODIES {
+            return Err(format!("at most {} parts", MAX_PREFAB_BODIES - 1));
+        }
+        for part in &self.parts {
+            if ![part.x, part.y, part.angle].iter().all(|v| v.is_finite()) {
+                return Err(format!("part '{}' offset must be finite", part.prefab));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A copy of a scene prefab placed when the scene loads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefabInstance {
+    pub prefab: String,
+    #[serde(default)]
+    pub x: f32,
+    #[serde(default)]
+    pub y: f32,
+    #[serde(default)]
+    pub angle: f32,
+}
+
+/// Body spawned from a prefab
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub enum PrefabBody {
+    /// The body a prefab was spawned as, with the prefab's name
+    Root(String),
+    /// A body spawned for a part of the prefab spawned as `root`
+    Part { root: Entity },
+}
+
+/// One body a prefab spawn makes
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefabSpawn {
+    pub desc: PrefabDesc,
+    /// World pose
+    pub pose: Pose2D,
+    /// Index of the earlier body this one is attached to, and its offset there
+    pub parent: Option<(usize, Pose2D)>,
+}
+
+/// Registered prefabs by name
+#[derive(Debug, Clone, Default)]
+pub struct PrefabLibrary {
+    prefabs: BTreeMap<String, PrefabDesc>,
+}
+
+impl PrefabLibrary {
+    /// Add `desc` under `name`, replacing a prefab of the same name
+    pub fn insert(&mut self, name: &str, desc: PrefabDesc) -> Result<(), String> {
+        if name.is_empty() {
+            return Err("prefab name must not be empty".into());
+        }
+        desc.validate().map_err(|e| format!("prefab '{}': {}", name, e))?;
+        if self.prefabs.len() == MAX_PREFABS && !self.prefabs.contains_key(name) {
+            return Err(format!("at most {} prefabs", MAX_PREFABS));
+        }
+        self.prefabs.insert(name.to_string(), desc);
+        Ok(())
+    }
+
+    /// Forget a prefab; bodies spawned from it stay. Returns false for
+    /// unknown names.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.prefabs.remove(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PrefabDesc> {
+        self.prefabs.get(name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.prefabs.keys().cloned().collect()
+    }
+
+    /// Every body spawning `name` at `pose` makes, parents before their
+    /// parts, or why it cannot spawn
+    pub fn expand(&self, name: &str, pose: Pose2D) -> Result<Vec<PrefabSpawn>, String> {
+        let mut spawns = Vec::new();
+        self.expand_into(name, pose, None, 0, &mut spawns)?;
+        Ok(spawns)
+    }
+
+    fn expand_into(
+        &self,
+        name: &str,
+        pose: Pose2D,
+        parent: Option<(usize, Pose2D)>,
+        depth: usize,
+        spawns: &mut Vec<PrefabSpawn>,
+    ) -> Result<(), String> {
+        let desc = self.get(name).ok_or_else(|| format!("unknown prefab '{}'", name))?;
+        if depth > hierarchy::MAX_DEPTH {
+            return Err(format!("prefab '{}' nests more than {} parts deep or contains itself", name, hierarchy::MAX_DEPTH));
+        }
+        if spawns.len() == MAX_PREFAB_BODIES {
+            return Err(format!("prefab spawns more than {} bodies", MAX_PREFAB_BODIES));
+        }
+        let index = spawns.len();
+        spawns.push(PrefabSpawn { desc: desc.clone(), pose, parent });
+        for part in &desc.parts {
+            let offset = part.offset();
+            self.expand_into(&part.prefab, pose.then(offset), Some((index, offset)), depth + 1, spawns)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+//! Pooled projectiles for rapid fire
+//!
+//! `configure` creates every projectile body, collider and entity up front
+//! and parks them far below the world, disabled. Firing takes a parked one,
+//! moves it to the muzzle and enables it; it is parked again when its time
+//! to live runs out or on its first collision. Rapier's sets and the
+//! instance layout therefore never change while firing, so the instance
+//! buffer is neither reallocated nor re-uploaded in full.
+//!
+//! Pools belong to the scene: a reset drops them, and after a rewind shots
+//! still in flight in the restored state simply stay put until they expire.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use bevy_ecs::prelude::*;
+use crate::rapier::prelude::*;
+
+use crate::groups::Groups;
+use crate::precision::{planar, to_real};
+use crate::schedule::CollisionEvents;
+use crate::{PhysicsBody, PhysicsState};
+
+/// Largest pool `configure` accepts
+pub const MAX_CAPACITY: u32 = 4096;
+
+/// Where parked projectiles wait, out of view and away from the level
+const PARK_POSITION: [f32; 2] = [0.0, -1.0e4];
+
+/// Pool occupancy and lifetime counters
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProjectileStats {
+    pub capacity: u32,
+    /// Projectiles in flight
+    pub active: u32,
+    /// Most projectiles in flight at once
+    pub peak_active: u32,
+    pub fired: u64,
+    /// Parked because their time to live ran out
+    pub expired: u64,
+    /// Parked on their first collision
+    pub hit: u64,
+    /// Shots refused because every projectile was in flight
+    pub dropped: u64,
+}
+
+/// Marks pool bodies, which are parked rather than despawned
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Pooled;
+
+struct Slot<K> {
+    key: K,
+    active: bool,
+    /// Seconds left; infinite for projectiles that only stop on a collision
+    ttl: f32,
+}
+
+/// Slot bookkeeping of a pool, keyed by whatever identifies a projectile
+pub struct ProjectilePool<K> {
+    slots: Vec<Slot<K>>,
+    index: HashMap<K, usize>,
+    free: Vec<usize>,
+    stats: ProjectileStats,
+}
+
+impl<K: Copy + Eq + Hash> ProjectilePool<K> {
+    pub fn new(keys: impl IntoIterator<Item = K>) -> Self {
+        let slots: Vec<Slot<K>> = keys.into_iter().map(|key| Slot { key, active: false, ttl: 0.0 }).collect();
+        let index = slots.iter().enumerate().map(|(i, slot)| (slot.key, i)).collect();
+        // Reversed so the first projectile is fired first
+        let free = (0..slots.len()).rev().collect();
+        let stats = ProjectileStats { capacity: slots.len() as u32, ..Default::default() };
+        Self { slots, index, free, stats }
+    }
+
+    /// Take a parked projectile that lives for `ttl` seconds (0, negative or
+    /// infinite: until its first collision). None when all are in flight.
+    pub fn fire(&mut self, ttl: f32) -> Option<K> {
+        let Some(i) = self.free.pop() else {
+            self.stats.dropped += 1;
+            return None;
+        };
+        let slot = &mut self.slots[i];
+        slot.active = true;
+        slot.ttl = if ttl.is_finite() && ttl > 0.0 { ttl } else { f32::INFINITY };
+        self.stats.fired += 1;
+        self.stats.active += 1;
+        self.stats.peak_active = self.stats.peak_active.max(self.stats.active);
+        Some(slot.key)
+    }
+
+    /// Count down every projectile in flight and return the ones that expired
+    pub fn tick(&mut self, dt: f32) -> Vec<K> {
+        let mut expired = Vec::new();
+        for i in 0..self.slots.len() {
+            let slot = &mut self.slots[i];
+            if !slot.active {
+                continue;
+            }
+            slot.ttl -= dt;
+            if slot.ttl <= 0.0 {
+                expired.push(slot.key);
+                self.release(i);
+                self.stats.expired += 1;
+            }
+        }
+        expired
+    }
+
+    /// Park a projectile in flight after a collision. Returns false for keys
+    /// that are not in flight, so each projectile stops once.
+    pub fn hit(&mut self, key: K) -> bool {
+        match self.index.get(&key).copied() {
+            Some(i) if self.slots[i].active => {
+                self.release(i);
+                self.stats.hit += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn release(&mut self, i: usize) {
+        self.slots[i].active = false;
+        self.free.push(i);
+        self.stats.active -= 1;
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.slots.iter().map(|slot| slot.key)
+    }
+
+    /// Projectiles that are parked
+    pub fn parked(&self) -> impl Iterator<Item = K> + '_ {
+        self.slots.iter().filter(|slot| !slot.active).map(|slot| slot.key)
+    }
+
+    pub fn stats(&self) -> ProjectileStats {
+        self.stats
+    }
+}
+
+/// Body of a pooled projectile, by entity id
+fn body_of(physics: &PhysicsState, entity_id: u64) -> Option<PhysicsBody> {
+    let entity = Entity::try_from_bits(entity_id).ok()?;
+    if !physics.world.entities().contains(entity) {
+        return None;
+    }
+    physics.world.get::<PhysicsBody>(entity).copied()
+}
+
+/// Disable the projectile, move it to the parking spot and hide its instance
+fn park(physics: &mut PhysicsState, entity_id: u64) {
+    let Some(body) = body_of(physics, entity_id) else {
+        return;
+    };
+    if let Some(rb) = physics.rigid_body_set.get_mut(body.rigid_body_handle) {
+        rb.set_enabled(false);
+        rb.set_translation(planar(PARK_POSITION), false);
+        rb.set_linvel(Vector::zeros(), false);
+        rb.set_angvel(Vector::zeros(), false);
+    }
+    // Disabled bodies are not active, so the per-frame refresh skips them
+    physics.instance_staging.update(crate::InstanceKey::Body(body.rigid_body_handle), |instance| {
+        instance.position = PARK_POSITION;
+        instance.velocity = [0.0, 0.0];
+    });
+}
+
+fn despawn(physics: &mut PhysicsState, entity_id: u64) {
+    let Some(body) = body_of(physics, entity_id) else {
+        return;
+    };
+    physics.rigid_body_set.remove(
+        body.rigid_body_handle,
+        &mut physics.island_manager,
+        &mut physics.collider_set,
+        &mut physics.impulse_joint_set,
+        &mut physics.multibody_joint_set,
+        true,
+    );
+    if let Ok(entity) = Entity::try_from_bits(entity_id) {
+        physics.world.despawn(entity);
+    }
+}
+
+/// Replace the scene's pool with `capacity` projectiles of `radius`; a
+/// capacity of 0 removes the pool. Returns false for invalid arguments.
+pub(crate) fn configure(physics: &mut PhysicsState, capacity: u32, radius: f32) -> bool {
+    if capacity > MAX_CAPACITY || !radius.is_finite() || radius <= 0.0 {
+        return false;
+    }
+    if let Some(pool) = physics.projectiles.take() {
+        for entity_id in pool.keys() {
+            despawn(physics, entity_id);
+        }
+    }
+    if capacity > 0 {
+        let mut ids = Vec::with_capacity(capacity as usize);
+        for _ in 0..capacity {
+            let rb = RigidBodyBuilder::dynamic()
+                .translation(planar(PARK_POSITION))
+                .ccd_enabled(true)
+                .enabled(false)
+                .build();
+            let rb_handle = physics.rigid_body_set.insert(rb);
+            let collider = ColliderBuilder::ball(to_real(radius)).active_events(ActiveEvents::COLLISION_EVENTS).build();
+            let collider_handle =
+                physics.collider_set.insert_with_parent(collider, rb_handle, &mut physics.rigid_body_set);
+            let body = PhysicsBody { rigid_body_handle: rb_handle, collider_handle };
+            let [x, y] = PARK_POSITION;
+            // In no group, so group operations leave the pool alone unless given every group
+            let entity = physics.world.spawn(crate::box_bundle(body, x, y, radius)).insert((Groups(0), Pooled)).id();
+            physics.collider_set[collider_handle].user_data = entity.to_bits() as u128;
+            ids.push(entity.to_bits());
+        }
+        physics.projectiles = Some(ProjectilePool::new(ids));
+    }
+    physics.instance_layout_dirty = true;
+    true
+}
+
+/// Launch a parked projectile from `origin`. Returns its entity id, or None
+/// when there is no pool or every projectile is in flight.
+pub(crate) fn fire(physics: &mut PhysicsState, origin: [f32; 2], velocity: [f32; 2], ttl: f32) -> Option<u64> {
+    let entity_id = physics.projectiles.as_mut()?.fire(ttl)?;
+    let body = body_of(physics, entity_id)?;
+    let sleep_enabled = physics.sleep_enabled;
+    let rb = physics.rigid_body_set.get_mut(body.rigid_body_handle)?;
+    rb.set_body_type(RigidBodyType::Dynamic, false);
+    rb.set_enabled(true);
+    rb.set_position(Isometry::translation(to_real(origin[0]), to_real(origin[1]), 0.0), false);
+    rb.set_linvel(planar(velocity), false);
+    rb.set_angvel(Vector::zeros(), false);
+    if !sleep_enabled {
+        *rb.activation_mut() = RigidBodyActivation::cannot_sleep();
+    }
+    rb.wake_up(true);
+    Some(entity_id)
+}
+
+/// Park projectiles that collided during the latest step or ran out of time.
+/// Also parks again any parked projectile something else re-enabled, such as
+/// turning streaming off.
+pub(crate) fn update(physics: &mut PhysicsState, dt: f32) {
+    let Some(mut pool) = physics.projectiles.take() else {
+        return;
+    };
+    let mut stopped: Vec<u64> = Vec::new();
+    for &(a, b) in &physics.world.resource::<CollisionEvents>().started {
+        stopped.extend([a, b].into_iter().filter(|&id| pool.hit(id)));
+    }
+    stopped.extend(pool.tick(dt));
+    for entity_id in stopped {
+        park(physics, entity_id);
+    }
+
+    let stray: Vec<u64> = pool
+        .parked()
+        .filter(|&id| {
+            body_of(physics, id)
+                .and_then(|body| physics.rigid_body_set.get(body.rigid_body_handle))
+                .is_some_and(|rb| rb.is_enabled())
+        })
+        .collect();
+    for entity_id in stray {
+        park(physics, entity_id);
+    }
+    physics.projectiles = Some(pool);
+}
+
+pub(crate) fn stats(physics: &PhysicsState) -> ProjectileStats {
+    physics.projectiles.as_ref().map_or_else(ProjectileStats::default, ProjectilePool::stats)
+}
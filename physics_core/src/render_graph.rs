@@ -0,0 +1,176 @@
+//! Minimal render graph: an ordered list of passes with declared resources
+//!
+//! Each frame `render_internal` describes which passes are active and what
+//! they read and write. The graph resolves an execution order from those
+//! dependencies, so adding a pass (particles, another post effect, 3D) means
+//! declaring its resources and encoding it, not re-threading the whole frame.
+
+/// Passes known to the renderer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PassId {
+    /// GPU compute work (particle/physics experiments)
+    Compute,
+    /// Compute culling of instances into indirect draws
+    Cull,
+    /// Contact shadows and instanced bodies
+    Main,
+    /// HDR bloom: threshold, blur and composite onto the backbuffer
+    Post,
+    /// Picture-in-picture map in a corner of the backbuffer
+    Minimap,
+    /// HUD text and world labels
+    Text,
+    /// Debug overlays drawn on top of the final image (3D sample cube)
+    Debug,
+    /// egui control panel
+    Egui,
+}
+
+impl PassId {
+    /// Name used for debug groups in GPU captures and in the profiler
+    pub fn label(self) -> &'static str {
+        match self {
+            PassId::Compute => "Compute",
+            PassId::Cull => "Cull",
+            PassId::Main => "Main",
+            PassId::Post => "Post",
+            PassId::Minimap => "Minimap",
+            PassId::Text => "Text",
+            PassId::Debug => "Debug",
+            PassId::Egui => "Egui",
+        }
+    }
+}
+
+/// GPU resources passes communicate through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resource {
+    /// Storage buffer owned by the compute pass
+    ComputeBuffer,
+    /// Instance buffer filled on the CPU by `sync_physics_to_gpu`
+    Instances,
+    /// Visible instances and indirect draw args written by the cull pass
+    VisibleInstances,
+    /// Offscreen HDR scene color consumed by post-processing
+    SceneColor,
+    /// The swapchain texture being presented this frame
+    Backbuffer,
+}
+
+impl Resource {
+    /// Imported resources are produced outside the graph and may be read
+    /// without a writer pass
+    pub fn is_imported(self) -> bool {
+        matches!(self, Resource::Instances | Resource::Backbuffer)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    DuplicatePass(PassId),
+    /// A pass reads a non-imported resource that no pass writes
+    MissingProducer(PassId, Resource),
+    /// Dependencies form a cycle; holds the passes that could not be ordered
+    Cycle(Vec<PassId>),
+}
+
+#[derive(Debug, Clone)]
+struct PassNode {
+    id: PassId,
+    reads: Vec<Resource>,
+    writes: Vec<Resource>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RenderGraph {
+    passes: Vec<PassNode>,
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a pass. Passes writing the same resource run in the order
+    /// they were added; a reader runs after every writer of what it reads.
+    pub fn add_pass(&mut self, id: PassId, reads: &[Resource], writes: &[Resource]) -> &mut Self {
+        self.passes.push(PassNode {
+            id,
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+        });
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.passes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// Resolve the execution order. Independent passes keep insertion order.
+    pub fn schedule(&self) -> Result<Vec<PassId>, GraphError> {
+        let n = self.passes.len();
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            if self.passes[..i].iter().any(|p| p.id == pass.id) {
+                return Err(GraphError::DuplicatePass(pass.id));
+            }
+        }
+
+        // edges[a] contains b when pass a must run before pass b
+        let mut edges = vec![Vec::new(); n];
+        for (b, reader) in self.passes.iter().enumerate() {
+            for &resource in &reader.reads {
+                let mut has_writer = false;
+                for (a, writer) in self.passes.iter().enumerate() {
+                    if a == b || !writer.writes.contains(&resource) {
+                        continue;
+                    }
+                    has_writer = true;
+                    // A pass that also writes the resource is a read-modify-write
+                    // step in the writer chain; ordering is handled below
+                    if !reader.writes.contains(&resource) {
+                        edges[a].push(b);
+                    }
+                }
+                if !has_writer && !resource.is_imported() {
+                    return Err(GraphError::MissingProducer(reader.id, resource));
+                }
+            }
+
+            // Writers of the same resource run in insertion order
+            for &resource in &reader.writes {
+                if let Some(a) = (0..b).rev().find(|&a| self.passes[a].writes.contains(&resource)) {
+                    edges[a].push(b);
+                }
+            }
+        }
+
+        // Kahn's algorithm, always taking the earliest-added ready pass
+        let mut in_degree = vec![0usize; n];
+        for targets in &edges {
+            for &b in targets {
+                in_degree[b] += 1;
+            }
+        }
+
+        let mut done = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+        while let Some(next) = (0..n).find(|&i| !done[i] && in_degree[i] == 0) {
+            done[next] = true;
+            order.push(self.passes[next].id);
+            for &b in &edges[next] {
+                in_degree[b] -= 1;
+            }
+        }
+
+        if order.len() != n {
+            let stuck = (0..n).filter(|&i| !done[i]).map(|i| self.passes[i].id).collect();
+            return Err(GraphError::Cycle(stuck));
+        }
+        Ok(order)
+    }
+}
@@ -0,0 +1,119 @@
+//! Host-configurable render settings (clear color, MSAA)
+//!
+//! Settings live outside `WgpuState` so hosts can set them before the surface
+//! exists; they are read when the renderer is (re)initialized and every frame.
+
+use serde::{Deserialize, Serialize};
+
+/// Light yellow background used by the demo
+pub const DEFAULT_CLEAR_COLOR: [f32; 4] = [1.0, 1.0, 225.0 / 255.0, 1.0];
+
+/// Sample counts tried in order when MSAA is requested
+const MSAA_CANDIDATES: [u32; 3] = [4, 2, 1];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RenderSettings {
+    /// RGBA clear color in linear 0..1 range
+    pub clear_color: [f32; 4],
+    /// Requested MSAA sample count (1 disables MSAA). Applied at init.
+    pub msaa_samples: u32,
+    /// Route the scene through the HDR bloom pass (MSAA is bypassed while on)
+    pub bloom_enabled: bool,
+    /// Luminance above which pixels contribute to bloom
+    pub bloom_threshold: f32,
+    /// Strength of the blurred bloom added back onto the scene
+    pub bloom_intensity: f32,
+    /// Draw soft contact shadows underneath bodies
+    pub shadow_enabled: bool,
+    /// Peak darkness of a single shadow quad (0..1); overlaps accumulate
+    pub shadow_intensity: f32,
+    /// How far the shadow extends beyond the body, relative to its size
+    pub shadow_spread: f32,
+    /// World-space offset of the shadow from the body
+    pub shadow_offset: [f32; 2],
+    /// Skip uploading and drawing bodies no camera can see
+    pub culling_enabled: bool,
+    /// Cull in a compute pass and draw indirectly where the GPU can (see
+    /// `gpu_culling`); the CPU culls otherwise
+    pub gpu_culling: bool,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            clear_color: DEFAULT_CLEAR_COLOR,
+            msaa_samples: 4,
+            bloom_enabled: false,
+            bloom_threshold: 1.0,
+            bloom_intensity: 0.8,
+            shadow_enabled: true,
+            shadow_intensity: 0.3,
+            shadow_spread: 0.6,
+            shadow_offset: [0.005, -0.01],
+            culling_enabled: true,
+            gpu_culling: false,
+        }
+    }
+}
+
+impl RenderSettings {
+    pub fn clear_color_wgpu(&self) -> wgpu::Color {
+        let [r, g, b, a] = self.clear_color;
+        wgpu::Color {
+            r: r as f64,
+            g: g as f64,
+            b: b as f64,
+            a: a as f64,
+        }
+    }
+}
+
+/// Pick the highest supported sample count that does not exceed `requested`
+pub fn choose_sample_count(requested: u32, supported: impl Fn(u32) -> bool) -> u32 {
+    MSAA_CANDIDATES
+        .iter()
+        .copied()
+        .filter(|&count| count <= requested.max(1))
+        .find(|&count| count == 1 || supported(count))
+        .unwrap_or(1)
+}
+
+/// Select the MSAA sample count for `format` on this adapter
+pub(crate) fn adapter_sample_count(
+    adapter: &wgpu::Adapter,
+    format: wgpu::TextureFormat,
+    requested: u32,
+) -> u32 {
+    let flags = adapter.get_texture_format_features(format).flags;
+    let count = choose_sample_count(requested, |count| flags.sample_count_supported(count));
+    log::info!("MSAA: requested {}x, using {}x for {:?}", requested, count, format);
+    count
+}
+
+/// Create the multisampled color target, or `None` when MSAA is disabled
+pub(crate) fn create_msaa_view(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    sample_count: u32,
+) -> Option<wgpu::TextureView> {
+    if sample_count <= 1 {
+        return None;
+    }
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("MSAA Color Target"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
@@ -0,0 +1,54 @@
+//! Safe-area insets of the main surface
+//!
+//! Phones cover parts of the surface with notches, rounded corners and
+//! system bars. Hosts report how far in from each edge the unobstructed
+//! area starts (Android `WindowInsets`, the iOS safe area, CSS
+//! `env(safe-area-inset-*)` scaled by the device pixel ratio), in surface
+//! pixels. The egui windows, screen-anchored HUD text and the minimap stay
+//! inside that area; the world keeps filling the whole surface unless
+//! `inset_world` also moves the camera viewport in, for scenes whose edges
+//! matter. Split screen always uses the whole surface.
+
+/// Insets in surface pixels
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SafeArea {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    /// Draw the world, and map pointers to it, inside the safe area only
+    pub inset_world: bool,
+}
+
+impl SafeArea {
+    /// None unless every inset is finite and not negative
+    pub fn new(left: f32, top: f32, right: f32, bottom: f32, inset_world: bool) -> Option<Self> {
+        [left, top, right, bottom]
+            .iter()
+            .all(|inset| inset.is_finite() && *inset >= 0.0)
+            .then_some(Self { left, top, right, bottom, inset_world })
+    }
+
+    /// Unobstructed pixel rectangle `[x, y, width, height]` (y down) of a
+    /// surface; the whole surface when the insets leave nothing of it
+    pub fn content_rect(&self, width: u32, height: u32) -> [u32; 4] {
+        let left = (self.left.round() as u32).min(width);
+        let top = (self.top.round() as u32).min(height);
+        let right = (self.right.round() as u32).min(width);
+        let bottom = (self.bottom.round() as u32).min(height);
+        match (width.checked_sub(left + right), height.checked_sub(top + bottom)) {
+            (Some(inner_width), Some(inner_height)) if inner_width > 0 && inner_height > 0 => [left, top, inner_width, inner_height],
+            _ => [0, 0, width, height],
+        }
+    }
+
+    /// Where the world is drawn: the content rectangle with `inset_world`,
+    /// otherwise the whole surface
+    pub fn world_rect(&self, width: u32, height: u32) -> [u32; 4] {
+        if self.inset_world {
+            self.content_rect(width, height)
+        } else {
+            [0, 0, width, height]
+        }
+    }
+}
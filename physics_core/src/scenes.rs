@@ -0,0 +1,388 @@
+//! Named scenes and switching between them
+//!
+//! Hosts register any number of scenes up front, each a level description
+//! (see `level_geometry`) plus the bodies to spawn into it, and then switch
+//! by name. Switching rebuilds the simulation the way a stress scene does:
+//! the previous scene's bodies, joints, springs, trails and instances are all
+//! dropped while gravity, time scale, solver and other settings stay. Scenes
+//! are kept in registration order, which is the order `next` walks so a game
+//! can move on to the following level once the current one is won.
+//!
+//! A scene can also list tweens that start as it loads, for intro
+//! animations: bodies growing in one after another or the camera gliding to
+//! its start position. State machines attach behavior to spawned bodies, so
+//! a scene can bring its own patrolling enemies.
+//!
+//! A switch can fade: the background color is drawn over the new scene and
+//! fades out over the given time, hiding the frame where everything pops in.
+//!
+//! Scenes can bring prefabs (see `prefabs`), registered as the scene loads,
+//! and place copies of them after the plain bodies.
+//!
+//! Scenes name their textures by asset path (see `assets`), so one file
+//! loads the same on every platform. Bodies and prefabs use the ids the
+//! file gives its textures, swapped for the registered material ids when
+//! the scene loads; a texture that cannot be read leaves them drawn with the
+//! default material.
+//!
+//! Large scenes can set level-of-detail thresholds (see `lod`) for bodies far
+//! from the streaming focus.
+//!
+//! A reset rebuilds the scene last switched to, as it was described when it
+//! loaded, plus the host's bodies marked `Persistent`, as they were when
+//! marked. Resetting to the pristine scene leaves those bodies out; resetting
+//! to the built-in demo grid also forgets the loaded scene, as does a stress
+//! scene, so later resets rebuild the demo.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::gravity::GravityPreset;
+use crate::groups;
+use crate::level_geometry::{self, LevelDesc};
+use crate::assets;
+use crate::lod::LodConfig;
+use crate::material::DEFAULT_MATERIAL;
+use crate::prefabs::{PrefabDesc, PrefabInstance, PrefabLibrary};
+use crate::state_machine::StateMachineDesc;
+use crate::triggers;
+use crate::tween::{self, Easing, Tween, TweenProperty};
+
+/// Most scenes the library holds
+pub const MAX_SCENES: usize = 64;
+/// Most bodies one scene can spawn
+pub const MAX_SCENE_BODIES: usize = 10_000;
+/// Most textures one scene names
+pub const MAX_SCENE_TEXTURES: usize = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SceneBodyType {
+    #[default]
+    Dynamic,
+    Fixed,
+    Kinematic,
+}
+
+/// One box spawned when the scene loads
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SceneBody {
+    pub x: f32,
+    pub y: f32,
+    pub half_extent: f32,
+    /// Rotation about the view axis in radians
+    pub angle: f32,
+    /// Initial velocity
+    pub vx: f32,
+    pub vy: f32,
+    pub body_type: SceneBodyType,
+    pub groups: u32,
+    pub material_id: u32,
+}
+
+impl Default for SceneBody {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            half_extent: 0.05,
+            angle: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            body_type: SceneBodyType::Dynamic,
+            groups: groups::DEFAULT,
+            material_id: DEFAULT_MATERIAL,
+        }
+    }
+}
+
+/// Tween started when the scene loads
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneTween {
+    /// Index into the scene's `bodies`; unused for camera properties
+    #[serde(default)]
+    pub body: usize,
+    pub property: TweenProperty,
+    pub from: Vec<f32>,
+    pub to: Vec<f32>,
+    pub duration: f32,
+    /// Seconds holding `from` before the tween starts
+    #[serde(default)]
+    pub delay: f32,
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+impl SceneTween {
+    /// The tween for the spawned body `target`
+    pub fn tween(&self, target: u64) -> Result<Tween, String> {
+        Tween::new(target, self.property, &self.from, &self.to, self.duration, self.delay, self.easing)
+    }
+}
+
+/// State machine given to some of the scene's bodies, each getting its own
+/// copy
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneStateMachine {
+    /// Indices into the scene's `bodies`
+    pub bodies: Vec<usize>,
+    #[serde(flatten)]
+    pub machine: StateMachineDesc,
+}
+
+/// Everything needed to rebuild one scene
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SceneDesc {
+    pub level: LevelDesc,
+    pub bodies: Vec<SceneBody>,
+    /// Gravity (x, y) while the scene runs; None keeps the current one
+    pub gravity: Option<[f32; 2]>,
+    /// Named gravity instead of `gravity`, which it may not be given with
+    pub gravity_preset: Option<GravityPreset>,
+    /// Started once everything is spawned
+    pub tweens: Vec<SceneTween>,
+    pub state_machines: Vec<SceneStateMachine>,
+    /// Level-of-detail thresholds while the scene runs; None keeps the
+    /// current ones
+    pub lod: Option<LodConfig>,
+    /// Registered when the scene loads, replacing prefabs of the same names
+    pub prefabs: BTreeMap<String, PrefabDesc>,
+    /// Copies of the scene's prefabs spawned after `bodies`
+    pub instances: Vec<PrefabInstance>,
+    /// Texture asset names by the material id the scene's bodies and
+    /// prefabs use for them
+    pub textures: BTreeMap<u32, String>,
+}
+
+impl SceneDesc {
+    /// Check everything a switch could fail on, so switching to a registered
+    /// scene never leaves a half-built simulation behind
+    pub fn validate(&self) -> Result<(), String> {
+        if self.bodies.len() > MAX_SCENE_BODIES {
+            return Err(format!("at most {} bodies per scene", MAX_SCENE_BODIES));
+        }
+        for body in &self.bodies {
+            let finite = [body.x, body.y, body.half_extent, body.angle, body.vx, body.vy].iter().all(|v| v.is_finite());
+            if !finite || body.half_extent <= 0.0 {
+                return Err(format!("invalid body at ({}, {})", body.x, body.y));
+            }
+        }
+        if self.gravity.is_some_and(|g| !g[0].is_finite() || !g[1].is_finite()) {
+            return Err("gravity must be finite".into());
+        }
+        if self.gravity.is_some() && self.gravity_preset.is_some() {
+            return Err("gravity and gravityPreset are exclusive".into());
+        }
+        if let Some(lod) = &self.lod {
+            lod.validate()?;
+        }
+        if self.level.triggers.len() > triggers::MAX_ZONES {
+            return Err(format!("at most {} trigger zones", triggers::MAX_ZONES));
+        }
+        for zone in &self.level.triggers {
+            zone.validate()?;
+        }
+        for shape in &self.level.shapes {
+            level_geometry::shape_collider(shape)?;
+        }
+        if self.tweens.len() > tween::MAX_TWEENS {
+            return Err(format!("at most {} tweens per scene", tween::MAX_TWEENS));
+        }
+        for scene_tween in &self.tweens {
+            scene_tween.tween(0)?;
+            if !scene_tween.property.is_camera() && scene_tween.body >= self.bodies.len() {
+                return Err(format!("tween on body {} of {}", scene_tween.body, self.bodies.len()));
+            }
+        }
+        for behavior in &self.state_machines {
+            behavior.machine.validate()?;
+            if let Some(body) = behavior.bodies.iter().find(|&&body| body >= self.bodies.len()) {
+                return Err(format!("state machine on body {} of {}", body, self.bodies.len()));
+            }
+        }
+        if self.textures.len() > MAX_SCENE_TEXTURES {
+            return Err(format!("at most {} textures per scene", MAX_SCENE_TEXTURES));
+        }
+        if self.textures.contains_key(&DEFAULT_MATERIAL) {
+            return Err(format!("material {} is the default texture", DEFAULT_MATERIAL));
+        }
+        for name in self.textures.values() {
+            assets::check_name(name)?;
+        }
+        if self.instances.len() > MAX_SCENE_BODIES {
+            return Err(format!("at most {} prefab instances per scene", MAX_SCENE_BODIES));
+        }
+        let mut prefabs = PrefabLibrary::default();
+        for (name, desc) in &self.prefabs {
+            prefabs.insert(name, desc.clone())?;
+        }
+        for instance in &self.instances {
+            if ![instance.x, instance.y, instance.angle].iter().all(|v| v.is_finite()) {
+                return Err(format!("invalid instance of '{}' at ({}, {})", instance.prefab, instance.x, instance.y));
+            }
+        }
+        // Instances only use the scene's own prefabs, so each name is
+        // expanded once to check its parts
+        let names: BTreeSet<&str> = self.instances.iter().map(|instance| instance.prefab.as_str()).collect();
+        for name in names {
+            prefabs.expand(name, Default::default())?;
+        }
+        Ok(())
+    }
+
+    /// The scene with the material ids its `textures` are known by swapped
+    /// for the registered ones in `materials`; other ids are kept
+    pub fn with_materials(&self, materials: &BTreeMap<u32, u32>) -> SceneDesc {
+        let swap = |material_id: u32| materials.get(&material_id).copied().unwrap_or(material_id);
+        let mut desc = self.clone();
+        for body in &mut desc.bodies {
+            body.material_id = swap(body.material_id);
+        }
+        for prefab in desc.prefabs.values_mut() {
+            prefab.material_id = swap(prefab.material_id);
+        }
+        desc
+    }
+}
+
+/// A host body rebuilt by every reset to the scene
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Persistent(pub SceneBody);
+
+/// What a reset rebuilds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    /// The loaded scene plus the persistent bodies
+    Scene,
+    /// The loaded scene alone
+    Pristine,
+    /// The built-in demo grid
+    Demo,
+}
+
+impl ResetMode {
+    /// FFI code: 0 = scene, 1 = pristine scene, 2 = demo
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Scene),
+            1 => Some(Self::Pristine),
+            2 => Some(Self::Demo),
+            _ => None,
+        }
+    }
+}
+
+/// Registered scenes in registration order and the one running
+#[derive(Debug, Clone, Default)]
+pub struct SceneLibrary {
+    scenes: Vec<(String, SceneDesc)>,
+    current: Option<String>,
+    loaded: Option<SceneDesc>,
+}
+
+impl SceneLibrary {
+    /// Add `desc` under `name`, replacing a scene of the same name in place
+    pub fn insert(&mut self, name: &str, desc: SceneDesc) -> Result<(), String> {
+        if name.is_empty() {
+            return Err("scene name must not be empty".into());
+        }
+        desc.validate()?;
+        if let Some((_, existing)) = self.scenes.iter_mut().find(|(n, _)| n == name) {
+            *existing = desc;
+            return Ok(());
+        }
+        if self.scenes.len() == MAX_SCENES {
+            return Err(format!("at most {} scenes", MAX_SCENES));
+        }
+        self.scenes.push((name.to_string(), desc));
+        Ok(())
+    }
+
+    /// Forget a scene; the running simulation is left alone. Returns false for
+    /// unknown names.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.scenes.len();
+        self.scenes.retain(|(n, _)| n != name);
+        if self.current.as_deref() == Some(name) {
+            self.current = None;
+        }
+        self.scenes.len() != before
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SceneDesc> {
+        self.scenes.iter().find(|(n, _)| n == name).map(|(_, desc)| desc)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.scenes.iter().map(|(n, _)| n.clone()).collect()
+    }
+
+    /// Name of the scene last switched to, if it is still registered
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn set_current(&mut self, name: &str) {
+        self.current = self.get(name).map(|_| name.to_string());
+        if let Some(desc) = self.get(name) {
+            self.loaded = Some(desc.clone());
+        }
+    }
+
+    /// The scene last switched to as it was then, kept when it is removed or
+    /// registered again
+    pub fn loaded(&self) -> Option<&SceneDesc> {
+        self.loaded.as_ref()
+    }
+
+    /// Forget the loaded scene once something else replaced it
+    pub fn clear_loaded(&mut self) {
+        self.loaded = None;
+    }
+
+    /// The scene registered after the current one, or the first scene when
+    /// none is running. None after the last scene.
+    pub fn next(&self) -> Option<&str> {
+        let index = match &self.current {
+            Some(current) => self.scenes.iter().position(|(n, _)| n == current)? + 1,
+            None => 0,
+        };
+        self.scenes.get(index).map(|(n, _)| n.as_str())
+    }
+}
+
+/// Background-colored overlay fading out after a switch
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Fade {
+    duration: f32,
+    remaining: f32,
+}
+
+impl Fade {
+    /// Start fully covered and clear up over `seconds`; zero, negative or
+    /// non-finite times cancel any fade
+    pub fn start(&mut self, seconds: f32) {
+        let seconds = if seconds.is_finite() { seconds.max(0.0) } else { 0.0 };
+        *self = Self { duration: seconds, remaining: seconds };
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if dt.is_finite() && dt > 0.0 {
+            self.remaining = (self.remaining - dt).max(0.0);
+        }
+    }
+
+    /// Overlay opacity, 1 right after the switch down to 0
+    pub fn alpha(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        self.remaining / self.duration
+    }
+}
@@ -0,0 +1,796 @@
+//! Per-frame simulation schedule
+//!
+//! `update_internal` runs one bevy_ecs `Schedule` whose systems are grouped
+//! into ordered `SimulationSet`s: input → movement → pre-physics → physics →
+//! post-physics → animation → sync. New gameplay systems are added to a set in
+//! `build_simulation_schedule`, or from outside the crate by a
+//! `plugins::SimulationPlugin`.
+//!
+//! While the simulation is paused each frame still runs the schedule once,
+//! without a step: movement through post-physics and the state machines are
+//! skipped, so input, animation and sync keep going over bodies that stay
+//! put. Plugin systems in the skipped sets are skipped with them.
+//!
+//! Rapier's data lives in `PhysicsState` between frames. For the duration of a
+//! run it is moved into the world as the `RapierContext` resource so systems
+//! can borrow it alongside component queries.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::ExecutorKind;
+use crate::rapier::prelude::*;
+
+use crate::audio::Impact;
+use crate::contact_filter::{ContactFilter, ContactHooks};
+use crate::conveyor::SurfaceVelocities;
+use crate::force_fields::{BodySample, ForceFieldConfig};
+use crate::events::{EventQueue, GameEvent, InputEventType};
+use crate::game_entity::{Controllable, MovementComponent};
+use crate::groups::Groups;
+use crate::hierarchy::{self, Attached, Pose2D};
+use crate::lifetime::{DespawnBounds, Lifetime};
+use crate::precision::{planar, to_f32, to_real, xy};
+use crate::projectiles::Pooled;
+use crate::soft_body::{self, SoftBodies};
+use crate::split_screen::{Player, SplitScreen};
+use crate::steering::{RapierQueries, SteeringContext};
+use crate::time_control::Time;
+use crate::time_dilation::TimeDilationConfig;
+use crate::units::WorldScale;
+use crate::water::Water;
+use crate::{PhysicsBody, PhysicsState, Position2D, Rotation, Scale, Velocity2D};
+
+/// Collisions that started during the latest physics step, as entity id pairs
+/// (0 for colliders without an entity, such as the walls)
+#[derive(Resource, Debug, Clone, Default)]
+pub struct CollisionEvents {
+    pub started: Vec<(u64, u64)>,
+    /// How hard each started collision with a contact point hit
+    pub impacts: Vec<Impact>,
+}
+
+/// Records started collisions while the pipeline steps
+#[derive(Default)]
+struct CollisionCollector {
+    started: std::sync::Mutex<Vec<(u64, u64)>>,
+    impacts: std::sync::Mutex<Vec<Impact>>,
+}
+
+/// Closing speed and impulse at the deepest contact of a pair that just
+/// started touching. Events are raised before the solver runs, so the
+/// velocities are still the ones before the hit.
+fn impact_of(bodies: &RigidBodySet, colliders: &ColliderSet, pair: &ContactPair, a: u64, b: u64) -> Option<Impact> {
+    let (manifold, contact) = pair.find_deepest_contact()?;
+    let collider1 = colliders.get(pair.collider1)?;
+    let point = collider1.position() * contact.local_p1;
+    let body = |collider: ColliderHandle| colliders.get(collider)?.parent().and_then(|h| bodies.get(h));
+    let velocity = |rb: Option<&RigidBody>| rb.map_or(Vector::zeros(), |rb| rb.velocity_at_point(&point));
+    let (rb1, rb2) = (body(pair.collider1), body(pair.collider2));
+    let speed = (velocity(rb1) - velocity(rb2)).dot(&manifold.data.normal).abs();
+
+    // Fixed and kinematic bodies do not give way, so only dynamic masses count
+    let mass = |rb: Option<&RigidBody>| rb.filter(|rb| rb.is_dynamic()).map(|rb| rb.mass()).filter(|&m| m > 0.0);
+    let reduced_mass = match (mass(rb1), mass(rb2)) {
+        (Some(m1), Some(m2)) => m1 * m2 / (m1 + m2),
+        (Some(m), None) | (None, Some(m)) => m,
+        (None, None) => 0.0,
+    };
+    Some(Impact { a, b, point: xy(&point.coords), speed: to_f32(speed), impulse: to_f32(reduced_mass * speed) })
+}
+
+impl EventHandler for CollisionCollector {
+    fn handle_collision_event(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        event: CollisionEvent,
+        contact_pair: Option<&ContactPair>,
+    ) {
+        if !event.started() {
+            return;
+        }
+        let entity_of = |handle| colliders.get(handle).map_or(0, |c| c.user_data as u64);
+        let (a, b) = (entity_of(event.collider1()), entity_of(event.collider2()));
+        if let Ok(mut started) = self.started.lock() {
+            started.push((a, b));
+        }
+        if let Some(impact) = contact_pair.and_then(|pair| impact_of(bodies, colliders, pair, a, b)) {
+            if let Ok(mut impacts) = self.impacts.lock() {
+                impacts.push(impact);
+            }
+        }
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        _dt: Real,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _contact_pair: &ContactPair,
+        _total_force_magnitude: Real,
+    ) {
+    }
+}
+
+/// Body picked up by the current drag gesture, flung on release
+#[derive(Resource, Debug, Default)]
+pub(crate) struct GestureGrab(Option<Entity>);
+
+/// Set by systems that add or remove bodies so the instance layout is rebuilt
+#[derive(Resource, Debug, Default)]
+pub(crate) struct BodiesChanged(pub(crate) bool);
+
+/// Resources every scene world needs before the schedule first runs
+pub(crate) fn init_resources(world: &mut World, scale: WorldScale) {
+    world.insert_resource(scale);
+    world.insert_resource(EventQueue::default());
+    world.insert_resource(Time::default());
+    world.insert_resource(crate::tween::Tweens::default());
+    world.insert_resource(crate::layers::LayerParallax::default());
+    world.insert_resource(CollisionEvents::default());
+    world.insert_resource(GestureGrab::default());
+    world.insert_resource(BodiesChanged::default());
+}
+
+/// Ordered stages of a simulation frame
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimulationSet {
+    /// Turn queued input events into impulses
+    Input,
+    /// Drive bodies that follow a `MovementStrategy`
+    Movement,
+    /// Empty in core; for plugin systems that act on bodies before the step
+    PrePhysics,
+    /// Step the Rapier pipeline
+    Physics,
+    /// Empty in core; for plugin systems that react to the step
+    PostPhysics,
+    /// Advance state machines, sprite animations and tweens; only the state
+    /// machines wait for a step
+    Animation,
+    /// Copy simulation results back into ECS components
+    Sync,
+}
+
+/// Rapier structures lent to the world while the schedule runs
+#[derive(Resource)]
+pub(crate) struct RapierContext {
+    pub(crate) gravity: Vector<Real>,
+    /// `integration_parameters.dt` is the length of one substep
+    pub(crate) integration_parameters: IntegrationParameters,
+    pub(crate) substeps: u32,
+    pub(crate) physics_pipeline: PhysicsPipeline,
+    pub(crate) island_manager: IslandManager,
+    pub(crate) broad_phase: DefaultBroadPhase,
+    pub(crate) narrow_phase: NarrowPhase,
+    pub(crate) rigid_body_set: RigidBodySet,
+    pub(crate) collider_set: ColliderSet,
+    pub(crate) impulse_joint_set: ImpulseJointSet,
+    pub(crate) multibody_joint_set: MultibodyJointSet,
+    pub(crate) ccd_solver: CCDSolver,
+    /// Only kept up to date while steering strategies need it
+    pub(crate) query_pipeline: QueryPipeline,
+    pub(crate) surface_velocities: SurfaceVelocities,
+    pub(crate) contact_filter: ContactFilter,
+    pub(crate) force_fields: ForceFieldConfig,
+    pub(crate) time_dilation: TimeDilationConfig,
+    pub(crate) water: Water,
+    pub(crate) soft_bodies: SoftBodies,
+    pub(crate) despawn_bounds: Option<DespawnBounds>,
+}
+
+impl RapierContext {
+    fn take_from(physics: &mut PhysicsState) -> Self {
+        Self {
+            gravity: physics.gravity,
+            integration_parameters: physics.integration_parameters,
+            substeps: physics.current_substeps,
+            physics_pipeline: std::mem::take(&mut physics.physics_pipeline),
+            island_manager: std::mem::take(&mut physics.island_manager),
+            broad_phase: std::mem::take(&mut physics.broad_phase),
+            narrow_phase: std::mem::take(&mut physics.narrow_phase),
+            rigid_body_set: std::mem::take(&mut physics.rigid_body_set),
+            collider_set: std::mem::take(&mut physics.collider_set),
+            impulse_joint_set: std::mem::take(&mut physics.impulse_joint_set),
+            multibody_joint_set: std::mem::take(&mut physics.multibody_joint_set),
+            ccd_solver: std::mem::take(&mut physics.ccd_solver),
+            query_pipeline: std::mem::take(&mut physics.query_pipeline),
+            surface_velocities: std::mem::take(&mut physics.surface_velocities),
+            contact_filter: std::mem::take(&mut physics.contact_filter),
+            force_fields: std::mem::take(&mut physics.force_fields),
+            time_dilation: std::mem::take(&mut physics.time_dilation),
+            water: std::mem::take(&mut physics.water),
+            soft_bodies: std::mem::take(&mut physics.soft_bodies),
+            despawn_bounds: physics.despawn_bounds,
+        }
+    }
+
+    fn restore_into(self, physics: &mut PhysicsState) {
+        physics.physics_pipeline = self.physics_pipeline;
+        physics.island_manager = self.island_manager;
+        physics.broad_phase = self.broad_phase;
+        physics.narrow_phase = self.narrow_phase;
+        physics.rigid_body_set = self.rigid_body_set;
+        physics.collider_set = self.collider_set;
+        physics.impulse_joint_set = self.impulse_joint_set;
+        physics.multibody_joint_set = self.multibody_joint_set;
+        physics.ccd_solver = self.ccd_solver;
+        physics.query_pipeline = self.query_pipeline;
+        physics.surface_velocities = self.surface_velocities;
+        physics.contact_filter = self.contact_filter;
+        physics.force_fields = self.force_fields;
+        physics.time_dilation = self.time_dilation;
+        physics.water = self.water;
+        physics.soft_bodies = self.soft_bodies;
+    }
+}
+
+/// Core systems plus whatever the registered plugins add; plugins may also
+/// insert resources into `world`
+pub(crate) fn build_simulation_schedule(world: &mut World) -> Schedule {
+    let mut schedule = Schedule::default();
+    // Systems mostly share RapierContext anyway; a single thread also keeps
+    // system order identical on every platform (see determinism.rs)
+    schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    schedule.configure_sets(
+        (
+            SimulationSet::Input,
+            SimulationSet::Movement,
+            SimulationSet::PrePhysics,
+            SimulationSet::Physics,
+            SimulationSet::PostPhysics,
+            SimulationSet::Animation,
+            SimulationSet::Sync,
+        )
+            .chain(),
+    );
+    schedule.configure_sets(
+        (SimulationSet::Movement, SimulationSet::PrePhysics, SimulationSet::Physics, SimulationSet::PostPhysics)
+            .run_if(stepping),
+    );
+    schedule.add_systems((
+        (input_system, gesture_system).in_set(SimulationSet::Input),
+        movement_system.in_set(SimulationSet::Movement),
+        (force_field_system, soft_body_system, physics_step_system, water_system, lifetime_system)
+            .chain()
+            .in_set(SimulationSet::Physics),
+        (
+            (crate::state_machine::state_machine_system.run_if(stepping), crate::animation::animation_system).chain(),
+            crate::tween::tween_system,
+        )
+            .in_set(SimulationSet::Animation),
+        (sync_components_system, transform_propagation_system, clear_events_system)
+            .chain()
+            .in_set(SimulationSet::Sync),
+    ));
+    crate::plugins::apply_plugins(world, &mut schedule);
+    schedule
+}
+
+/// Whether the schedule is running a simulation step
+fn stepping(time: Res<Time>) -> bool {
+    time.stepping
+}
+
+/// Start a step of `dt` on the clock and run the simulation schedule once
+pub(crate) fn run_simulation_frame(physics: &mut PhysicsState, dt: f32) {
+    physics.world.resource_mut::<Time>().begin_step(dt);
+    run_schedule(physics);
+}
+
+/// Run the schedule once without a step, animating by `dt` scaled seconds
+pub(crate) fn run_idle_frame(physics: &mut PhysicsState, dt: f32) {
+    physics.world.resource_mut::<Time>().begin_idle(dt);
+    run_schedule(physics);
+}
+
+fn run_schedule(physics: &mut PhysicsState) {
+    let rapier = RapierContext::take_from(physics);
+    physics.world.insert_resource(rapier);
+    physics.schedule.run(&mut physics.world);
+    match physics.world.remove_resource::<RapierContext>() {
+        Some(rapier) => rapier.restore_into(physics),
+        None => log::error!("run_schedule: RapierContext was removed by a system"),
+    }
+    if std::mem::take(&mut physics.world.resource_mut::<BodiesChanged>().0) {
+        physics.instance_layout_dirty = true;
+    }
+}
+
+/// Under split screen, input goes to the routed player's bodies; otherwise
+/// every controllable body gets every press
+fn input_system(
+    events: Res<EventQueue>,
+    split: Option<Res<SplitScreen>>,
+    controllable: Query<(&PhysicsBody, Option<&Player>), With<Controllable>>,
+    mut rapier: ResMut<RapierContext>,
+) {
+    for event in &events.events {
+        if let Some(split) = split.as_deref() {
+            let Some((player, [x, y])) = split.route(event) else {
+                continue;
+            };
+            for (physics_body, _) in controllable.iter().filter(|(_, tag)| tag.is_none_or(|tag| tag.0 == player)) {
+                if let Some(rb) = rapier.rigid_body_set.get_mut(physics_body.rigid_body_handle) {
+                    rb.apply_impulse(planar([x, y]), true);
+                }
+            }
+            continue;
+        }
+        let impulse = match event.event_type {
+            // 19 (DPAD_UP/W), 20 (DPAD_DOWN/S), 21 (DPAD_LEFT/A), 22 (DPAD_RIGHT/D)
+            crate::events::InputEventType::KeyDown => match event.key_code {
+                Some(19) => vector![0.0, 0.5, 0.0],
+                Some(20) => vector![0.0, -0.5, 0.0],
+                Some(21) => vector![-0.5, 0.0, 0.0],
+                Some(22) => vector![0.5, 0.0, 0.0],
+                _ => continue,
+            },
+            // Mouse click / Touch -> Jump
+            crate::events::InputEventType::PointerDown => vector![0.0, 5.0, 0.0],
+            _ => continue,
+        };
+
+        for (physics_body, _) in &controllable {
+            if let Some(rb) = rapier.rigid_body_set.get_mut(physics_body.rigid_body_handle) {
+                rb.apply_impulse(impulse, true);
+                log::info!("Applied impulse {:?} to Controllable Entity", impulse);
+            }
+        }
+    }
+}
+
+/// Tap spawns a box, drag picks up the body under the finger and the release
+/// flings it with the gesture velocity
+fn gesture_system(world: &mut World) {
+    let gestures: Vec<GameEvent> = world
+        .resource::<EventQueue>()
+        .events
+        .iter()
+        .filter(|e| matches!(e.event_type, InputEventType::Tap | InputEventType::DragStart | InputEventType::Fling))
+        .copied()
+        .collect();
+    if gestures.is_empty() {
+        return;
+    }
+
+    world.resource_scope(|world, mut rapier: Mut<RapierContext>| {
+        let rapier = &mut *rapier;
+        for event in gestures {
+            match event.event_type {
+                InputEventType::Tap => {
+                    let half_extent = world.resource::<WorldScale>().box_half_extent();
+                    crate::spawn_box(
+                        world,
+                        &mut rapier.rigid_body_set,
+                        &mut rapier.collider_set,
+                        event.x,
+                        event.y,
+                        half_extent,
+                        RigidBodyType::Dynamic,
+                    );
+                    world.resource_mut::<BodiesChanged>().0 = true;
+                }
+                InputEventType::DragStart => {
+                    let grabbed = body_at(world, &rapier.rigid_body_set, event.x, event.y);
+                    world.resource_mut::<GestureGrab>().0 = grabbed;
+                }
+                InputEventType::Fling => {
+                    let grabbed = world.resource_mut::<GestureGrab>().0.take();
+                    let handle = grabbed.and_then(|e| world.get::<PhysicsBody>(e)).map(|b| b.rigid_body_handle);
+                    if let Some(rb) = handle.and_then(|h| rapier.rigid_body_set.get_mut(h)) {
+                        rb.set_linvel(planar(event.delta), true);
+                    }
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+/// Closest body whose box (with some slack for fingers) contains the point
+pub(crate) fn body_at(world: &mut World, bodies: &RigidBodySet, x: f32, y: f32) -> Option<Entity> {
+    let default_size = world.resource::<WorldScale>().box_half_extent();
+    world
+        .query::<(Entity, &PhysicsBody, Option<&Scale>)>()
+        .iter(world)
+        .filter_map(|(entity, body, scale)| {
+            let rb = bodies.get(body.rigid_body_handle)?;
+            let reach = scale.map_or(default_size, |s| s.0) * 1.5;
+            let [bx, by] = xy(rb.translation());
+            let (dx, dy) = ((bx - x).abs(), (by - y).abs());
+            (dx <= reach && dy <= reach).then_some((entity, dx * dx + dy * dy))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+/// Steer bodies toward the position their strategy gives for the current
+/// time: kinematic bodies are moved directly, dynamic ones get the velocity
+/// that reaches it this step. Steering strategies name the velocity
+/// themselves after looking at the scene.
+fn movement_system(
+    time: Res<Time>,
+    movers: Query<(&PhysicsBody, &MovementComponent)>,
+    bodies: Query<(Entity, &PhysicsBody, Option<&Groups>)>,
+    mut rapier: ResMut<RapierContext>,
+) {
+    if time.delta <= 0.0 || movers.is_empty() {
+        return;
+    }
+    let rapier = &mut *rapier;
+    rapier.query_pipeline.update(&rapier.collider_set);
+    let entities: Vec<(u64, RigidBodyHandle, Groups)> = bodies
+        .iter()
+        .map(|(entity, body, groups)| (entity.to_bits(), body.rigid_body_handle, groups.copied().unwrap_or_default()))
+        .collect();
+
+    // Decide every move against the same state of the scene, then apply them
+    let mut moves = Vec::new();
+    for (physics_body, movement) in &movers {
+        let handle = physics_body.rigid_body_handle;
+        let Some(rb) = rapier.rigid_body_set.get(handle) else {
+            continue;
+        };
+        let elapsed = time.elapsed - movement.started_at;
+        let queries = RapierQueries {
+            bodies: &rapier.rigid_body_set,
+            colliders: &rapier.collider_set,
+            pipeline: &rapier.query_pipeline,
+            entities: &entities,
+            exclude: handle,
+        };
+        let context = SteeringContext {
+            position: (to_f32(rb.translation().x), to_f32(rb.translation().y)),
+            velocity: (to_f32(rb.linvel().x), to_f32(rb.linvel().y)),
+            elapsed,
+            dt: time.delta,
+            queries: &queries,
+        };
+        let target = match movement.strategy.steer(&context) {
+            // From the body's own translation, which may be more precise than the context's
+            Some((vx, vy)) => {
+                let translation = rb.translation();
+                vector![translation.x, translation.y, 0.0] + planar([vx * time.delta, vy * time.delta])
+            }
+            None => {
+                let (x, y) = movement.strategy.calculate_position(movement.origin, elapsed);
+                planar([x, y])
+            }
+        };
+        moves.push((handle, target));
+    }
+
+    for (handle, target) in moves {
+        if let Some(rb) = rapier.rigid_body_set.get_mut(handle) {
+            if rb.is_kinematic() {
+                rb.set_next_kinematic_translation(target);
+            } else {
+                let velocity = (target - rb.translation()) / to_real(time.delta);
+                rb.set_linvel(velocity, true);
+            }
+        }
+    }
+}
+
+/// Replace last step's field and water forces on dynamic bodies with this
+/// step's. Sleeping bodies are left alone; changing the fields wakes them.
+fn force_field_system(
+    time: Res<Time>,
+    bodies: Query<(&PhysicsBody, &Scale)>,
+    mut rapier: ResMut<RapierContext>,
+    mut applied: Local<bool>,
+) {
+    let rapier = &mut *rapier;
+    if rapier.force_fields.is_empty() && rapier.water.is_empty() {
+        // Clear the forces of the last step that had fields
+        if std::mem::take(&mut *applied) {
+            for (_, rb) in rapier.rigid_body_set.iter_mut() {
+                rb.reset_forces(false);
+            }
+        }
+        return;
+    }
+    *applied = true;
+
+    let gravity = xy(&rapier.gravity);
+    for (physics_body, scale) in &bodies {
+        let Some(rb) = rapier.rigid_body_set.get_mut(physics_body.rigid_body_handle) else {
+            continue;
+        };
+        if !rb.is_dynamic() || rb.is_sleeping() {
+            continue;
+        }
+        rb.reset_forces(false);
+        let sample = BodySample {
+            position: xy(rb.translation()),
+            velocity: xy(rb.linvel()),
+            mass: to_f32(rb.mass()),
+            half_extent: scale.0,
+        };
+        let [fx, fy] = rapier.force_fields.force_on(&sample, gravity, time.elapsed);
+        let [wx, wy] = rapier.water.force_on(&sample, gravity);
+        rb.add_force(planar([fx + wx, fy + wy]), false);
+    }
+}
+
+/// Inflate every blob toward its rest area (see soft_body.rs). Pressure is
+/// applied as an impulse so it does not linger in the bodies' user forces.
+fn soft_body_system(time: Res<Time>, mut rapier: ResMut<RapierContext>) {
+    let rapier = &mut *rapier;
+    if rapier.soft_bodies.is_empty() {
+        return;
+    }
+    rapier.soft_bodies.prune(&rapier.rigid_body_set);
+    for body in rapier.soft_bodies.iter() {
+        let positions = SoftBodies::positions(body, &rapier.rigid_body_set);
+        let forces = soft_body::pressure_forces(&positions, body.rest_area, body.pressure);
+        for (&handle, [fx, fy]) in body.nodes.iter().zip(forces) {
+            if fx == 0.0 && fy == 0.0 {
+                continue;
+            }
+            // A resting blob sleeps as one island; leave it asleep
+            if let Some(rb) = rapier.rigid_body_set.get_mut(handle).filter(|rb| rb.is_dynamic() && !rb.is_sleeping()) {
+                rb.apply_impulse(planar([fx * time.delta, fy * time.delta]), false);
+            }
+        }
+    }
+}
+
+/// What a dilated body looked like before `dilate_bodies` scaled it
+struct Dilated {
+    handle: RigidBodyHandle,
+    scale: Real,
+    gravity_scale: Real,
+    linear_damping: Real,
+    angular_damping: Real,
+    force: Vector<Real>,
+    torque: AngVector<Real>,
+}
+
+/// Scale the motion of awake dynamic bodies in slow-motion zones for this
+/// step (see time_dilation.rs)
+fn dilate_bodies(rapier: &mut RapierContext) -> Vec<Dilated> {
+    if rapier.time_dilation.is_empty() {
+        return Vec::new();
+    }
+    let mut dilated = Vec::new();
+    for (handle, rb) in rapier.rigid_body_set.iter_mut() {
+        if !rb.is_dynamic() || rb.is_sleeping() {
+            continue;
+        }
+        let scale = to_real(rapier.time_dilation.scale_at(xy(rb.translation())));
+        if scale >= 1.0 {
+            continue;
+        }
+        let before = Dilated {
+            handle,
+            scale,
+            gravity_scale: rb.gravity_scale(),
+            linear_damping: rb.linear_damping(),
+            angular_damping: rb.angular_damping(),
+            force: rb.user_force(),
+            torque: rb.user_torque(),
+        };
+        let squared = scale * scale;
+        rb.set_linvel(rb.linvel() * scale, false);
+        rb.set_angvel(rb.angvel() * scale, false);
+        rb.set_gravity_scale(before.gravity_scale * squared, false);
+        rb.set_linear_damping(before.linear_damping * scale);
+        rb.set_angular_damping(before.angular_damping * scale);
+        rb.reset_forces(false);
+        rb.reset_torques(false);
+        rb.add_force(before.force * squared, false);
+        rb.add_torque(before.torque * squared, false);
+        dilated.push(before);
+    }
+    dilated
+}
+
+/// Undo `dilate_bodies`, keeping what the step did to the velocities
+fn restore_dilated_bodies(rapier: &mut RapierContext, dilated: Vec<Dilated>) {
+    for before in dilated {
+        let Some(rb) = rapier.rigid_body_set.get_mut(before.handle) else {
+            continue;
+        };
+        rb.set_linvel(rb.linvel() / before.scale, false);
+        rb.set_angvel(rb.angvel() / before.scale, false);
+        rb.set_gravity_scale(before.gravity_scale, false);
+        rb.set_linear_damping(before.linear_damping);
+        rb.set_angular_damping(before.angular_damping);
+        rb.reset_forces(false);
+        rb.reset_torques(false);
+        rb.add_force(before.force, false);
+        rb.add_torque(before.torque, false);
+    }
+}
+
+fn physics_step_system(mut rapier: ResMut<RapierContext>, mut collisions: ResMut<CollisionEvents>) {
+    let rapier = &mut *rapier;
+    let dilated = dilate_bodies(rapier);
+    let collector = CollisionCollector::default();
+    let hooks = ContactHooks { surface_velocities: &rapier.surface_velocities, filter: &rapier.contact_filter };
+    for _ in 0..rapier.substeps.max(1) {
+        rapier.physics_pipeline.step(
+            &rapier.gravity,
+            &rapier.integration_parameters,
+            &mut rapier.island_manager,
+            &mut rapier.broad_phase,
+            &mut rapier.narrow_phase,
+            &mut rapier.rigid_body_set,
+            &mut rapier.collider_set,
+            &mut rapier.impulse_joint_set,
+            &mut rapier.multibody_joint_set,
+            &mut rapier.ccd_solver,
+            None, // query_pipeline
+            &hooks,
+            &collector,
+        );
+    }
+    restore_dilated_bodies(rapier, dilated);
+    collisions.started = collector.started.into_inner().unwrap_or_default();
+    collisions.impacts = collector.impacts.into_inner().unwrap_or_default();
+}
+
+/// Splash bodies that crossed a water surface this step, then move the waves
+/// and droplets on
+fn water_system(
+    time: Res<Time>,
+    bodies: Query<(Entity, &PhysicsBody, &Scale)>,
+    mut rapier: ResMut<RapierContext>,
+    mut wet: Local<std::collections::HashSet<Entity>>,
+) {
+    let rapier = &mut *rapier;
+    if rapier.water.is_empty() {
+        wet.clear();
+        return;
+    }
+    wet.retain(|&entity| bodies.contains(entity));
+    for (entity, physics_body, scale) in &bodies {
+        let Some(rb) = rapier.rigid_body_set.get(physics_body.rigid_body_handle) else {
+            continue;
+        };
+        if !rb.is_dynamic() || rb.is_sleeping() {
+            continue;
+        }
+        let position = xy(rb.translation());
+        let touching = rapier.water.is_touching(position, scale.0);
+        // Entering pushes the surface down, leaving pulls it up
+        if touching != wet.contains(&entity) {
+            rapier.water.splash(position[0], to_f32(rb.linvel().y), scale.0);
+            if touching {
+                wet.insert(entity);
+            } else {
+                wet.remove(&entity);
+            }
+        }
+    }
+    rapier.water.step(time.delta, xy(&rapier.gravity));
+}
+
+/// Despawn bodies whose `Lifetime` ran out or that left the despawn bounds
+fn lifetime_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rapier: ResMut<RapierContext>,
+    mut changed: ResMut<BodiesChanged>,
+    mut bodies: Query<(Entity, &PhysicsBody, Option<&mut Lifetime>), Without<Pooled>>,
+) {
+    let rapier = &mut *rapier;
+    for (entity, physics_body, lifetime) in &mut bodies {
+        let expired = lifetime.is_some_and(|mut lifetime| lifetime.tick(time.delta));
+        let escaped = rapier.despawn_bounds.is_some_and(|bounds| {
+            rapier
+                .rigid_body_set
+                .get(physics_body.rigid_body_handle)
+                .filter(|rb| rb.is_dynamic() && rb.is_enabled())
+                .is_some_and(|rb| !bounds.contains(to_f32(rb.translation().x), to_f32(rb.translation().y)))
+        });
+        if !expired && !escaped {
+            continue;
+        }
+        rapier.rigid_body_set.remove(
+            physics_body.rigid_body_handle,
+            &mut rapier.island_manager,
+            &mut rapier.collider_set,
+            &mut rapier.impulse_joint_set,
+            &mut rapier.multibody_joint_set,
+            true,
+        );
+        rapier.surface_velocities.remove(&physics_body.collider_handle);
+        commands.entity(entity).despawn();
+        changed.0 = true;
+    }
+}
+
+/// Mirror rigid body state into the ECS components gameplay systems read
+fn sync_components_system(
+    rapier: Res<RapierContext>,
+    mut bodies: Query<(&PhysicsBody, &mut Position2D, &mut Velocity2D, &mut Rotation)>,
+) {
+    for (physics_body, mut position, mut velocity, mut rotation) in &mut bodies {
+        if let Some(rb) = rapier.rigid_body_set.get(physics_body.rigid_body_handle) {
+            let ([x, y], [vx, vy]) = (xy(rb.translation()), xy(rb.linvel()));
+            *position = Position2D { x, y };
+            *velocity = Velocity2D { x: vx, y: vy };
+            *rotation = Rotation(to_f32(rb.rotation().angle()));
+        }
+    }
+}
+
+/// Pose of a body or of an entity drawn from its components
+fn root_pose(rapier: &RapierContext, body: Option<&PhysicsBody>, position: &Position2D, rotation: &Rotation) -> Pose2D {
+    match body.and_then(|body| rapier.rigid_body_set.get(body.rigid_body_handle)) {
+        Some(rb) => Pose2D {
+            translation: xy(rb.translation()),
+            rotation: to_f32(rb.rotation().euler_angles().2),
+        },
+        None => Pose2D { translation: [position.x, position.y], rotation: rotation.0 },
+    }
+}
+
+/// Place attached entities at their parent's pose composed with their
+/// offset; attached bodies are steered there on the next step
+fn transform_propagation_system(
+    mut commands: Commands,
+    mut rapier: ResMut<RapierContext>,
+    mut changed: ResMut<BodiesChanged>,
+    attached: Query<(Entity, &Attached, Option<&PhysicsBody>)>,
+    mut poses: ParamSet<(
+        Query<(Option<&PhysicsBody>, &Position2D, &Rotation)>,
+        Query<(&mut Position2D, &mut Rotation)>,
+    )>,
+) {
+    if attached.is_empty() {
+        return;
+    }
+    let links: std::collections::HashMap<Entity, (Entity, Pose2D)> =
+        attached.iter().map(|(entity, a, _)| (entity, (a.parent, a.offset))).collect();
+
+    let mut placed = Vec::new();
+    let mut orphans = Vec::new();
+    {
+        let roots = poses.p0();
+        for (entity, attachment, body) in &attached {
+            let pose = hierarchy::resolve(
+                entity,
+                |e| links.get(&e).copied(),
+                |e| roots.get(e).ok().map(|(body, position, rotation)| root_pose(&rapier, body, position, rotation)),
+            );
+            match pose {
+                Some(pose) => placed.push((entity, body.copied(), pose)),
+                None => orphans.push((entity, body.copied(), attachment.restore_dynamic)),
+            }
+        }
+    }
+
+    let mut targets = poses.p1();
+    for (entity, body, pose) in placed {
+        if let Ok((mut position, mut rotation)) = targets.get_mut(entity) {
+            *position = Position2D { x: pose.translation[0], y: pose.translation[1] };
+            *rotation = Rotation(pose.rotation);
+        }
+        if let Some(rb) = body.and_then(|body| rapier.rigid_body_set.get_mut(body.rigid_body_handle)) {
+            rb.set_next_kinematic_position(Isometry::new(planar(pose.translation), vector![0.0, 0.0, to_real(pose.rotation)]));
+        }
+    }
+
+    for (entity, body, restore_dynamic) in orphans {
+        match body {
+            Some(body) => {
+                if let Some(rb) = rapier.rigid_body_set.get_mut(body.rigid_body_handle) {
+                    if restore_dynamic {
+                        rb.set_body_type(RigidBodyType::Dynamic, true);
+                    }
+                }
+                commands.entity(entity).remove::<Attached>();
+            }
+            None => commands.entity(entity).despawn(),
+        }
+        changed.0 = true;
+    }
+}
+
+/// Events are consumed once; anything pushed after this frame waits for the next
+fn clear_events_system(mut events: ResMut<EventQueue>) {
+    events.clear();
+}
@@ -0,0 +1,183 @@
+//! Optional gameplay scripting with Rhai (`scripting` feature)
+//!
+//! A script is plain Rhai. Top-level statements run once when it is loaded;
+//! these optional functions are then called every frame:
+//!
+//! ```text
+//! fn on_frame(dt) { ... }        // before the physics step
+//! fn on_collision(a, b) { ... }  // after the step, once per new contact
+//! ```
+//!
+//! Entity ids are passed as Rhai integers (0 = wall / no entity). Scripts
+//! drive the simulation through the functions registered in `build_engine`,
+//! which wrap the same internals as the host APIs. Callbacks run from frame
+//! hooks with no simulation lock held.
+
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST, FLOAT, INT};
+
+use crate::plugins::{self, HookPoint};
+
+/// Upper bound on Rhai operations per call so a runaway script cannot hang a frame
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+struct ScriptRuntime {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    has_on_frame: bool,
+    has_on_collision: bool,
+}
+
+impl ScriptRuntime {
+    fn call(&mut self, name: &str, args: impl rhai::FuncArgs) {
+        if let Err(e) = self.engine.call_fn::<Dynamic>(&mut self.scope, &self.ast, name, args) {
+            log::warn!("Script error in {}: {}", name, e);
+        }
+    }
+}
+
+struct ScriptState {
+    runtime: Option<ScriptRuntime>,
+    hook_ids: Vec<u32>,
+}
+
+static SCRIPT: Lazy<Mutex<ScriptState>> = Lazy::new(|| {
+    Mutex::new(ScriptState {
+        runtime: None,
+        hook_ids: Vec::new(),
+    })
+});
+
+fn id_to_int(id: u64) -> INT {
+    id as INT
+}
+
+fn int_to_id(id: INT) -> u64 {
+    id as u64
+}
+
+fn ids_to_array(ids: Vec<u64>) -> Array {
+    ids.into_iter().map(|id| Dynamic::from(id_to_int(id))).collect()
+}
+
+fn build_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| log::info!("[script] {}", text));
+    engine.on_debug(|text, _, pos| log::debug!("[script] {:?} {}", pos, text));
+
+    engine.register_fn("spawn_box", |x: FLOAT, y: FLOAT, half_extent: FLOAT| -> INT {
+        crate::spawn_body_internal(x as f32, y as f32, half_extent as f32).map_or(0, id_to_int)
+    });
+    engine.register_fn("apply_impulse", |id: INT, x: FLOAT, y: FLOAT| -> bool {
+        crate::apply_impulse_internal(int_to_id(id), x as f32, y as f32)
+    });
+    engine.register_fn("wake", |id: INT| -> bool { crate::wake_body_internal(int_to_id(id)) });
+    engine.register_fn("set_material", |id: INT, material: INT| -> bool {
+        crate::set_entity_material_internal(int_to_id(id), material as u32)
+    });
+    engine.register_fn("body", |id: INT| -> Dynamic {
+        match crate::body_info_internal(int_to_id(id)) {
+            Some(info) => {
+                let mut map = Map::new();
+                map.insert("x".into(), Dynamic::from(info.x as FLOAT));
+                map.insert("y".into(), Dynamic::from(info.y as FLOAT));
+                map.insert("vx".into(), Dynamic::from(info.vx as FLOAT));
+                map.insert("vy".into(), Dynamic::from(info.vy as FLOAT));
+                map.insert("rotation".into(), Dynamic::from(info.rotation as FLOAT));
+                map.insert("sleeping".into(), Dynamic::from(info.sleeping));
+                map.into()
+            }
+            None => Dynamic::UNIT,
+        }
+    });
+    engine.register_fn("entities", || -> Array { ids_to_array(crate::entity_ids_internal()) });
+    engine.register_fn("set_groups", |id: INT, groups: INT| -> bool {
+        crate::set_body_groups_internal(int_to_id(id), groups as u32)
+    });
+    engine.register_fn("group", |mask: INT| -> Array { ids_to_array(crate::group_ids_internal(mask as u32)) });
+    engine.register_fn("despawn_group", |mask: INT| -> INT { crate::despawn_group_internal(mask as u32) as INT });
+    engine.register_fn("apply_impulse_to_group", |mask: INT, x: FLOAT, y: FLOAT| -> INT {
+        crate::apply_impulse_to_group_internal(mask as u32, x as f32, y as f32) as INT
+    });
+    engine.register_fn("query_aabb", |min_x: FLOAT, min_y: FLOAT, max_x: FLOAT, max_y: FLOAT| -> Array {
+        let (min_x, min_y, max_x, max_y) = (min_x as f32, min_y as f32, max_x as f32, max_y as f32);
+        ids_to_array(crate::query_aabb_internal(min_x, min_y, max_x, max_y, crate::groups::ALL))
+    });
+    engine.register_fn("query_circle", |x: FLOAT, y: FLOAT, radius: FLOAT| -> Array {
+        ids_to_array(crate::query_circle_internal(x as f32, y as f32, radius as f32, crate::groups::ALL))
+    });
+    engine
+}
+
+/// Compile `source`, run its top-level statements and start calling its
+/// callbacks. Replaces any previously loaded script.
+pub fn load_script(source: &str) -> Result<(), String> {
+    let engine = build_engine();
+    let ast = engine.compile(source).map_err(|e| e.to_string())?;
+    let has_fn = |name: &str| ast.iter_functions().any(|f| f.name == name);
+    let has_on_frame = has_fn("on_frame");
+    let has_on_collision = has_fn("on_collision");
+
+    // Top-level statements may call back into the simulation, so run them
+    // before taking the script lock
+    let mut scope = Scope::new();
+    engine.run_ast_with_scope(&mut scope, &ast).map_err(|e| e.to_string())?;
+
+    let mut state = SCRIPT.lock().map_err(|_| "script state poisoned".to_string())?;
+    state.runtime = Some(ScriptRuntime {
+        engine,
+        ast,
+        scope,
+        has_on_frame,
+        has_on_collision,
+    });
+    if state.hook_ids.is_empty() {
+        state.hook_ids = vec![
+            plugins::register_hook(HookPoint::PrePhysics, |_, dt| run_on_frame(dt)),
+            plugins::register_hook(HookPoint::PostPhysics, |_, _| run_on_collision()),
+        ];
+    }
+    log::info!("Loaded script (on_frame: {}, on_collision: {})", has_on_frame, has_on_collision);
+    Ok(())
+}
+
+/// Stop calling the current script and drop it
+pub fn unload_script() {
+    if let Ok(mut state) = SCRIPT.lock() {
+        state.runtime = None;
+        for id in state.hook_ids.drain(..) {
+            plugins::unregister_hook(id);
+        }
+    }
+}
+
+fn run_on_frame(dt: f32) {
+    if let Ok(mut state) = SCRIPT.lock() {
+        if let Some(runtime) = state.runtime.as_mut().filter(|r| r.has_on_frame) {
+            runtime.call("on_frame", (dt as FLOAT,));
+        }
+    }
+}
+
+fn run_on_collision() {
+    let wants_collisions = SCRIPT
+        .lock()
+        .map(|state| state.runtime.as_ref().is_some_and(|r| r.has_on_collision))
+        .unwrap_or(false);
+    if !wants_collisions {
+        return;
+    }
+
+    let collisions = crate::recent_collisions_internal();
+    if let Ok(mut state) = SCRIPT.lock() {
+        if let Some(runtime) = state.runtime.as_mut() {
+            for (a, b) in collisions {
+                runtime.call("on_collision", (id_to_int(a), id_to_int(b)));
+            }
+        }
+    }
+}
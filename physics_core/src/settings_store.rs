@@ -0,0 +1,185 @@
+//! Settings that survive a restart
+//!
+//! The control panel's place and size, the render toggles, the camera and
+//! the solver tweaks are saved as one JSON document and restored at init.
+//! Where the document lives depends on the platform: a file on desktop (in
+//! the working directory unless the host picks a path) and on Android (in the
+//! app's internal storage, which the host or `android_main` provides), and
+//! `localStorage` on the web. A document that is missing or does not parse
+//! restores nothing; fields missing from an older document keep defaults.
+//!
+//! This module only describes and stores the document; `lib.rs` gathers it
+//! from the running state and applies it back.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::render_settings::RenderSettings;
+use crate::solver_config::SolverConfig;
+use crate::substep::SubstepConfig;
+
+/// Key of the document in the browser's `localStorage`
+pub const STORAGE_KEY: &str = "physicsfx.settings";
+/// Name of the document inside a settings directory
+pub const FILE_NAME: &str = "physicsfx_settings.json";
+
+/// Where the control panel was, in egui points
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelLayout {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// The 2D camera; the 3D demo's orbit camera is not kept
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraSettings {
+    pub x: f32,
+    pub y: f32,
+    /// World units visible vertically
+    pub view_height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PhysicsSettings {
+    pub gravity: [f32; 3],
+    pub time_scale: f32,
+    pub sleep_enabled: bool,
+    pub solver: SolverConfig,
+    pub substeps: SubstepConfig,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            gravity: [0.0, -9.81, 0.0],
+            time_scale: 1.0,
+            sleep_enabled: true,
+            solver: SolverConfig::default(),
+            substeps: SubstepConfig::default(),
+        }
+    }
+}
+
+/// The whole document; each part is restored only when present
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SavedSettings {
+    pub panel: Option<PanelLayout>,
+    pub render: Option<RenderSettings>,
+    pub camera: Option<CameraSettings>,
+    pub physics: Option<PhysicsSettings>,
+}
+
+impl SavedSettings {
+    /// Parse a document, dropping parts a hand edit left unusable
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut settings: Self = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let finite = |values: &[f32]| values.iter().all(|v| v.is_finite());
+        settings.panel = settings.panel.filter(|p| finite(&[p.x, p.y]) && p.width > 0.0 && p.height > 0.0);
+        settings.camera = settings.camera.filter(|c| finite(&[c.x, c.y]) && c.view_height > 0.0);
+        if let Some(physics) = settings.physics.as_mut() {
+            if !finite(&physics.gravity) {
+                physics.gravity = PhysicsSettings::default().gravity;
+            }
+            if !(physics.time_scale.is_finite() && physics.time_scale > 0.0) {
+                physics.time_scale = 1.0;
+            }
+            physics.solver = physics.solver.sanitized();
+            physics.substeps = physics.substeps.sanitized();
+        }
+        Ok(settings)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Where the document is kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsStore {
+    File(PathBuf),
+    /// `localStorage` under this key; only exists on the web
+    LocalStorage(String),
+}
+
+impl SettingsStore {
+    /// The default for this platform. None on Android, whose internal
+    /// storage directory has to come from the app.
+    pub fn platform_default() -> Option<Self> {
+        if cfg!(target_arch = "wasm32") {
+            Some(SettingsStore::LocalStorage(STORAGE_KEY.to_string()))
+        } else if cfg!(target_os = "android") {
+            None
+        } else {
+            Some(SettingsStore::File(PathBuf::from(FILE_NAME)))
+        }
+    }
+
+    /// The document file inside `dir`
+    pub fn in_directory(dir: &Path) -> Self {
+        SettingsStore::File(dir.join(FILE_NAME))
+    }
+
+    /// None when nothing was saved yet or the document does not parse
+    pub fn load(&self) -> Option<SavedSettings> {
+        let json = match self {
+            SettingsStore::File(path) => std::fs::read_to_string(path).ok()?,
+            SettingsStore::LocalStorage(key) => local_storage_get(key)?,
+        };
+        match SavedSettings::from_json(&json) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                log::warn!("Ignoring saved settings: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, settings: &SavedSettings) -> Result<(), String> {
+        let json = settings.to_json();
+        match self {
+            SettingsStore::File(path) => {
+                // Written beside the document and renamed over it, so a crash
+                // mid-write keeps the previous settings
+                let partial = path.with_extension("json.partial");
+                std::fs::write(&partial, json)
+                    .and_then(|_| std::fs::rename(&partial, path))
+                    .map_err(|e| format!("{}: {}", path.display(), e))
+            }
+            SettingsStore::LocalStorage(key) => local_storage_set(key, &json),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage_get(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage_set(key: &str, value: &str) -> Result<(), String> {
+    let storage = local_storage().ok_or("localStorage is not available")?;
+    storage.set_item(key, value).map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn local_storage_get(_key: &str) -> Option<String> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn local_storage_set(_key: &str, _value: &str) -> Result<(), String> {
+    Err("localStorage only exists on the web".into())
+}
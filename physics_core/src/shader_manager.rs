@@ -0,0 +1,108 @@
+//! Hot reloading of WGSL shaders on desktop builds
+//!
+//! Shaders are still embedded with `include_str!` so every platform works out
+//! of the box. On desktop, a `ShaderWatcher` polls the `shaders/` directory and
+//! hands back the sources of files that changed; the renderer rebuilds the
+//! affected pipelines between frames and keeps the old ones if validation fails.
+//! Only the shaders the renderer lists are watched (`shader.wgsl` and
+//! `shadow.wgsl` today); edits to any other file need a rebuild.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Environment variable overriding the watched directory
+pub const SHADER_DIR_ENV: &str = "PHYSICSFX_SHADER_DIR";
+
+/// How often the filesystem is checked for edits
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+struct WatchedShader {
+    name: &'static str,
+    modified: Option<SystemTime>,
+}
+
+pub struct ShaderWatcher {
+    dir: PathBuf,
+    shaders: Vec<WatchedShader>,
+    last_poll: Instant,
+}
+
+impl ShaderWatcher {
+    /// Watch `names` (file names such as `"shader.wgsl"`) inside `dir`
+    pub fn new(dir: impl Into<PathBuf>, names: &[&'static str]) -> Self {
+        let dir = dir.into();
+        let shaders = names
+            .iter()
+            .map(|&name| WatchedShader {
+                name,
+                modified: modified_time(&dir.join(name)),
+            })
+            .collect();
+        Self {
+            dir,
+            shaders,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Watcher for the directory named by `PHYSICSFX_SHADER_DIR`, or the
+    /// crate's own `shaders/` directory in debug builds. `None` in release
+    /// builds without the variable, or when the directory does not exist.
+    pub fn from_env(names: &[&'static str]) -> Option<Self> {
+        let dir = match std::env::var_os(SHADER_DIR_ENV) {
+            Some(dir) => PathBuf::from(dir),
+            None if cfg!(debug_assertions) => Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders"),
+            None => return None,
+        };
+        if !dir.is_dir() {
+            log::warn!("Shader hot reload disabled: {} is not a directory", dir.display());
+            return None;
+        }
+        log::info!("Watching {} for changes to {}", dir.display(), names.join(", "));
+        Some(Self::new(dir, names))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Return `(name, source)` for every watched shader modified since the
+    /// last call. Cheap to call every frame; the filesystem is only checked
+    /// every `POLL_INTERVAL`.
+    pub fn poll(&mut self) -> Vec<(&'static str, String)> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+
+        let mut changed = Vec::new();
+        for shader in &mut self.shaders {
+            let path = self.dir.join(shader.name);
+            let modified = modified_time(&path);
+            if modified.is_none() || modified == shader.modified {
+                continue;
+            }
+            shader.modified = modified;
+            match std::fs::read_to_string(&path) {
+                Ok(source) => changed.push((shader.name, source)),
+                Err(e) => log::warn!("Failed to read {}: {}", path.display(), e),
+            }
+        }
+        changed
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Run `build` inside a validation error scope. Returns the built objects, or
+/// the validation message so the caller can keep its previous pipelines.
+pub fn build_validated<T>(device: &wgpu::Device, build: impl FnOnce() -> T) -> Result<T, String> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let built = build();
+    match pollster::block_on(device.pop_error_scope()) {
+        None => Ok(built),
+        Some(error) => Err(error.to_string()),
+    }
+}
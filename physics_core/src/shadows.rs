@@ -0,0 +1,159 @@
+//! Contact shadows for the 2D scene
+//!
+//! Draws every instance a second time, before the bodies themselves, as a soft
+//! dark quad so piles of boxes get a grounded, ambient-occluded look.
+//!
+//! This is a geometry pass inside the main pass rather than a screen-space
+//! post pass. The scene has no depth or normal buffer for a post pass to
+//! read, and a post pass would darken pixels already drawn, bodies included.
+//! Drawn this way, the shadows sit under the bodies and over the tiles.
+//! They also reuse the instance buffer and the culled draw batches, so they
+//! cost one extra instanced draw per batch and no render target.
+
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowParams {
+    intensity: f32,
+    spread: f32,
+    offset: [f32; 2],
+}
+
+pub(crate) struct ContactShadowPass {
+    /// Pipeline matching the main pass (swapchain format, MSAA sample count)
+    pipeline: wgpu::RenderPipeline,
+    /// Pipeline matching the HDR bloom target, when bloom is available
+    hdr_pipeline: Option<wgpu::RenderPipeline>,
+    params_buffer: wgpu::Buffer,
+    params_bind_group: wgpu::BindGroup,
+}
+
+impl ContactShadowPass {
+    pub fn new(
+        device: &wgpu::Device,
+        shader_source: &str,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        vertex_buffers: &[wgpu::VertexBufferLayout],
+        output_format: wgpu::TextureFormat,
+        sample_count: u32,
+        hdr_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shadow Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(shader_source)),
+        });
+
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Shadow Params Buffer"),
+            contents: bytemuck::cast_slice(&[ShadowParams {
+                intensity: 0.0,
+                spread: 0.0,
+                offset: [0.0; 2],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let params_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("shadow_params_bind_group_layout"),
+        });
+
+        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &params_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+            label: Some("shadow_params_bind_group"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[&params_layout, camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let make_pipeline = |label: &str, format: wgpu::TextureFormat, count: u32| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_shadow"),
+                    buffers: vertex_buffers,
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_shadow"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let pipeline = make_pipeline("Shadow Pipeline", output_format, sample_count);
+        let hdr_pipeline = hdr_format.map(|format| make_pipeline("HDR Shadow Pipeline", format, 1));
+
+        Self {
+            pipeline,
+            hdr_pipeline,
+            params_buffer,
+            params_bind_group,
+        }
+    }
+
+    /// Upload the current shadow parameters; call once per frame before drawing
+    pub fn update(&self, queue: &wgpu::Queue, intensity: f32, spread: f32, offset: [f32; 2]) {
+        queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::cast_slice(&[ShadowParams {
+                intensity,
+                spread,
+                offset,
+            }]),
+        );
+    }
+
+    /// Bind the shadow pipeline for the current target. The caller binds the
+    /// camera (group 1), vertex/instance/index buffers and issues the draw.
+    pub fn bind(&self, render_pass: &mut wgpu::RenderPass<'_>, hdr: bool) -> bool {
+        let pipeline = if hdr { self.hdr_pipeline.as_ref() } else { Some(&self.pipeline) };
+        match pipeline {
+            Some(pipeline) => {
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_bind_group(0, &self.params_bind_group, &[]);
+                true
+            }
+            None => false,
+        }
+    }
+}
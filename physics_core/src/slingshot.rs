@@ -0,0 +1,119 @@
+//! Drag-to-launch slingshot interaction
+//!
+//! With the mode on, pressing on a dynamic body starts an aim anchored at the
+//! body; dragging away from it pulls the band back and the launch impulse
+//! points from the pointer toward the anchor, growing with the pull up to
+//! `max_impulse` at `max_pull`. Releasing applies the impulse. Like the
+//! gesture recognizer this only tracks the interaction: `lib.rs` maps the
+//! pointer to world coordinates, picks the body, draws the predicted arc on
+//! every drag and applies the impulse on release.
+
+/// Launch tuning
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlingshotConfig {
+    /// Impulse of a full pull, N·s
+    pub max_impulse: f32,
+    /// Pull length (m) that gives `max_impulse`; longer pulls are capped
+    pub max_pull: f32,
+    /// Predicted steps drawn while aiming; 0 hides the arc
+    pub arc_samples: u32,
+}
+
+impl Default for SlingshotConfig {
+    fn default() -> Self {
+        Self {
+            max_impulse: 0.005,
+            max_pull: 0.5,
+            arc_samples: 90,
+        }
+    }
+}
+
+impl SlingshotConfig {
+    /// None unless `max_impulse` and `max_pull` are finite and > 0
+    pub fn validated(self) -> Option<Self> {
+        let valid = self.max_impulse.is_finite() && self.max_pull.is_finite() && self.max_impulse > 0.0 && self.max_pull > 0.0;
+        valid.then_some(self)
+    }
+
+    /// Impulse for the band pulled from `anchor` to `pointer`: toward the
+    /// anchor, scaling linearly with the pull and capped at `max_impulse`
+    pub fn launch_impulse(&self, anchor: [f32; 2], pointer: [f32; 2]) -> [f32; 2] {
+        let pull = [anchor[0] - pointer[0], anchor[1] - pointer[1]];
+        let length = (pull[0] * pull[0] + pull[1] * pull[1]).sqrt();
+        if length <= f32::EPSILON {
+            return [0.0, 0.0];
+        }
+        let magnitude = self.max_impulse * (length / self.max_pull).min(1.0);
+        [pull[0] / length * magnitude, pull[1] / length * magnitude]
+    }
+}
+
+/// The body being aimed and where it was grabbed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aim {
+    pub entity: u64,
+    pub anchor: [f32; 2],
+    /// Impulse for the latest pointer position
+    pub impulse: [f32; 2],
+}
+
+/// Mode switch, tuning and the aim in progress
+#[derive(Debug, Clone, Default)]
+pub struct Slingshot {
+    enabled: bool,
+    config: SlingshotConfig,
+    aim: Option<Aim>,
+}
+
+impl Slingshot {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turning the mode off drops any aim in progress
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.aim = None;
+        }
+    }
+
+    pub fn config(&self) -> SlingshotConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: SlingshotConfig) {
+        self.config = config;
+    }
+
+    pub fn aim(&self) -> Option<Aim> {
+        self.aim
+    }
+
+    /// Start aiming `entity`, grabbed at `anchor`. Ignored while the mode is off.
+    pub fn press(&mut self, entity: u64, anchor: [f32; 2]) {
+        if self.enabled {
+            self.aim = Some(Aim { entity, anchor, impulse: [0.0, 0.0] });
+        }
+    }
+
+    /// Move the pointer to `pointer`; returns the updated aim, if any
+    pub fn drag(&mut self, pointer: [f32; 2]) -> Option<Aim> {
+        let config = self.config;
+        let aim = self.aim.as_mut()?;
+        aim.impulse = config.launch_impulse(aim.anchor, pointer);
+        Some(*aim)
+    }
+
+    /// Let go at `pointer`; returns the aim to launch, if any
+    pub fn release(&mut self, pointer: [f32; 2]) -> Option<Aim> {
+        self.drag(pointer)?;
+        self.aim.take()
+    }
+
+    /// Drop the aim without launching
+    pub fn cancel(&mut self) -> Option<Aim> {
+        self.aim.take()
+    }
+}
@@ -0,0 +1,340 @@
+//! Binary snapshots of the whole simulation for instant rewind
+//!
+//! A snapshot is the bincode encoding of the Rapier sets and pipeline state
+//! plus the per-entity ECS components that affect simulation or drawing. Recent
+//! snapshots are kept in a `SnapshotRing`, one per physics step, so
+//! `rewind(steps)` is a single decode with no re-simulation.
+
+use std::collections::{HashMap, VecDeque};
+
+use bevy_ecs::prelude::*;
+use crate::rapier::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::AnimatorComponent;
+use crate::fracture::{Destructible, FractureConfig};
+use crate::game_entity::Controllable;
+use crate::groups::{Groups, Paused};
+use crate::hierarchy::{Attached, Pose2D};
+use crate::lifetime::Lifetime;
+use crate::material::MaterialComponent;
+use crate::precision::xy;
+use crate::{PhysicsBody, PhysicsState, Scale};
+
+/// Default number of steps kept for rewinding (about two seconds at 60 Hz)
+pub const DEFAULT_HISTORY: usize = 120;
+
+/// Fixed-capacity history; pushing onto a full ring drops the oldest entry
+#[derive(Debug, Clone)]
+pub struct SnapshotRing<T> {
+    capacity: usize,
+    entries: VecDeque<T>,
+}
+
+impl<T> SnapshotRing<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Shrinking drops the oldest entries; 0 disables the history
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn push(&mut self, entry: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Entry `steps_back` steps before the newest one (0 = newest)
+    pub fn get(&self, steps_back: usize) -> Option<&T> {
+        let index = self.entries.len().checked_sub(steps_back + 1)?;
+        self.entries.get(index)
+    }
+
+    /// Drop the `steps` newest entries, so the entry that was `steps` back
+    /// becomes the newest. Returns how many were dropped.
+    pub fn discard_newest(&mut self, steps: usize) -> usize {
+        let dropped = steps.min(self.entries.len().saturating_sub(1));
+        self.entries.truncate(self.entries.len() - dropped);
+        dropped
+    }
+}
+
+/// One history entry: the state after a step and that step's length
+#[derive(Debug, Clone)]
+pub(crate) struct Recorded {
+    pub dt: f32,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EntitySnapshot {
+    id: u64,
+    rigid_body_handle: RigidBodyHandle,
+    collider_handle: ColliderHandle,
+    half_extent: f32,
+    animator: (f32, bool, u32, f32),
+    material_id: Option<u32>,
+    controllable: bool,
+    groups: u32,
+    /// Velocity kept while the body's group is paused
+    paused: Option<(Vector<Real>, Vector<Real>)>,
+    lifetime: Option<f32>,
+    /// Parent id, offset translation and rotation, and whether to restore dynamic
+    attached: Option<(u64, [f32; 2], f32, bool)>,
+    destructible: Option<FractureConfig>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SimulationSnapshot {
+    step_count: u64,
+    gravity: Vector<Real>,
+    integration_parameters: IntegrationParameters,
+    island_manager: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    rigid_body_set: RigidBodySet,
+    collider_set: ColliderSet,
+    impulse_joint_set: ImpulseJointSet,
+    multibody_joint_set: MultibodyJointSet,
+    entities: Vec<EntitySnapshot>,
+}
+
+/// Encode the current simulation state
+pub(crate) fn capture(physics: &mut PhysicsState) -> Result<Vec<u8>, String> {
+    let entities = physics
+        .world
+        .query::<(
+            Entity,
+            &PhysicsBody,
+            Option<&Scale>,
+            Option<&AnimatorComponent>,
+            Option<&MaterialComponent>,
+            Has<Controllable>,
+            Option<&Groups>,
+            Option<&Paused>,
+            Option<&Lifetime>,
+            Option<&Attached>,
+            Option<&Destructible>,
+        )>()
+        .iter(&physics.world)
+        .map(|(entity, body, scale, animator, material, controllable, groups, paused, lifetime, attached, destructible)| {
+            let animator = animator.copied().unwrap_or_default();
+            EntitySnapshot {
+                id: entity.to_bits(),
+                rigid_body_handle: body.rigid_body_handle,
+                collider_handle: body.collider_handle,
+                half_extent: scale.map_or(0.05, |s| s.0),
+                animator: (animator.elapsed_time, animator.is_playing, animator.current_frame, animator.speed),
+                material_id: material.map(|m| m.material_id),
+                controllable,
+                groups: groups.copied().unwrap_or_default().0,
+                paused: paused.map(|p| (p.linvel, p.angvel)),
+                lifetime: lifetime.map(|l| l.remaining),
+                attached: attached.map(|a| (a.parent.to_bits(), a.offset.translation, a.offset.rotation, a.restore_dynamic)),
+                destructible: destructible.map(|d| d.0),
+            }
+        })
+        .collect();
+
+    // Borrowing serializer: clone-free encoding of the Rapier structures
+    #[derive(Serialize)]
+    struct SnapshotRef<'a> {
+        step_count: u64,
+        gravity: &'a Vector<Real>,
+        integration_parameters: &'a IntegrationParameters,
+        island_manager: &'a IslandManager,
+        broad_phase: &'a DefaultBroadPhase,
+        narrow_phase: &'a NarrowPhase,
+        rigid_body_set: &'a RigidBodySet,
+        collider_set: &'a ColliderSet,
+        impulse_joint_set: &'a ImpulseJointSet,
+        multibody_joint_set: &'a MultibodyJointSet,
+        entities: Vec<EntitySnapshot>,
+    }
+
+    bincode::serialize(&SnapshotRef {
+        step_count: physics.step_count,
+        gravity: &physics.gravity,
+        integration_parameters: &physics.integration_parameters,
+        island_manager: &physics.island_manager,
+        broad_phase: &physics.broad_phase,
+        narrow_phase: &physics.narrow_phase,
+        rigid_body_set: &physics.rigid_body_set,
+        collider_set: &physics.collider_set,
+        impulse_joint_set: &physics.impulse_joint_set,
+        multibody_joint_set: &physics.multibody_joint_set,
+        entities,
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Live entity for each saved id: the same entity while it still exists,
+/// otherwise a newly spawned one, since an id whose index was reused cannot
+/// be taken back. Fails before spawning anything if an id is not an entity.
+pub fn resolve_entities(world: &mut World, ids: &[u64]) -> Result<HashMap<u64, Entity>, String> {
+    let saved = ids
+        .iter()
+        .map(|&id| Entity::try_from_bits(id).map(|entity| (id, entity)).map_err(|_| format!("snapshot entity {} is not a valid id", id)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(saved
+        .into_iter()
+        .map(|(id, entity)| match world.get_entity(entity) {
+            Ok(_) => (id, entity),
+            Err(_) => (id, world.spawn_empty().id()),
+        })
+        .collect())
+}
+
+/// Replace the simulation state with a decoded snapshot. Entities created
+/// after the snapshot are despawned; ones that no longer exist are respawned
+/// under new ids, which are logged.
+pub(crate) fn restore(physics: &mut PhysicsState, bytes: &[u8]) -> Result<(), String> {
+    let snapshot: SimulationSnapshot = bincode::deserialize(bytes).map_err(|e| e.to_string())?;
+    // Checked before anything changes, so a bad snapshot leaves the state alone
+    let ids: Vec<u64> = snapshot.entities.iter().map(|e| e.id).collect();
+    if let Some(id) = ids.iter().find(|&&id| Entity::try_from_bits(id).is_err()) {
+        return Err(format!("snapshot entity {} is not a valid id", id));
+    }
+
+    physics.step_count = snapshot.step_count;
+    physics.gravity = snapshot.gravity;
+    physics.integration_parameters = snapshot.integration_parameters;
+    physics.island_manager = snapshot.island_manager;
+    physics.broad_phase = snapshot.broad_phase;
+    physics.narrow_phase = snapshot.narrow_phase;
+    physics.rigid_body_set = snapshot.rigid_body_set;
+    physics.collider_set = snapshot.collider_set;
+    physics.impulse_joint_set = snapshot.impulse_joint_set;
+    physics.multibody_joint_set = snapshot.multibody_joint_set;
+    // Solver caches refer to the old sets; start them fresh
+    physics.physics_pipeline = PhysicsPipeline::new();
+    physics.ccd_solver = CCDSolver::new();
+
+    // The streamer's chunk set no longer matches the restored enabled flags
+    if let Some(streamer) = physics.streamer.as_mut() {
+        *streamer = crate::ChunkStreamer::new(streamer.config());
+    }
+
+    let world = &mut physics.world;
+    let keep: std::collections::HashSet<u64> = ids.iter().copied().collect();
+    let stale: Vec<Entity> = world
+        .query_filtered::<Entity, With<PhysicsBody>>()
+        .iter(world)
+        .filter(|entity| !keep.contains(&entity.to_bits()))
+        .collect();
+    for entity in stale {
+        world.despawn(entity);
+    }
+
+    let entities = resolve_entities(world, &ids)?;
+    for (&id, entity) in &entities {
+        if entity.to_bits() != id {
+            log::info!("restore: entity {} came back as {}", id, entity.to_bits());
+        }
+    }
+    for saved in snapshot.entities {
+        let entity = entities[&saved.id];
+        let (elapsed_time, is_playing, current_frame, speed) = saved.animator;
+        let body = PhysicsBody {
+            rigid_body_handle: saved.rigid_body_handle,
+            collider_handle: saved.collider_handle,
+        };
+        let [x, y] = physics
+            .rigid_body_set
+            .get(body.rigid_body_handle)
+            .map_or([0.0, 0.0], |rb| xy(rb.translation()));
+        let mut entity_mut = world.entity_mut(entity);
+        entity_mut.insert(crate::box_bundle(body, x, y, saved.half_extent));
+        entity_mut.insert(AnimatorComponent { elapsed_time, is_playing, current_frame, speed });
+        match saved.material_id {
+            Some(material_id) => entity_mut.insert(MaterialComponent::new(material_id)),
+            None => entity_mut.remove::<MaterialComponent>(),
+        };
+        if saved.controllable {
+            entity_mut.insert(Controllable);
+        } else {
+            entity_mut.remove::<Controllable>();
+        }
+        entity_mut.insert(Groups(saved.groups));
+        match saved.paused {
+            Some((linvel, angvel)) => entity_mut.insert(Paused { linvel, angvel }),
+            None => entity_mut.remove::<Paused>(),
+        };
+        match saved.lifetime {
+            Some(remaining) => entity_mut.insert(Lifetime { remaining }),
+            None => entity_mut.remove::<Lifetime>(),
+        };
+        match saved.attached.and_then(|(parent, translation, rotation, restore_dynamic)| {
+            let parent = match entities.get(&parent) {
+                Some(&parent) => parent,
+                None => Entity::try_from_bits(parent).ok()?,
+            };
+            Some(Attached { parent, offset: Pose2D { translation, rotation }, restore_dynamic })
+        }) {
+            Some(attached) => entity_mut.insert(attached),
+            None => entity_mut.remove::<Attached>(),
+        };
+        match saved.destructible {
+            Some(config) => entity_mut.insert(Destructible(config)),
+            None => entity_mut.remove::<Destructible>(),
+        };
+    }
+
+    physics.instance_layout_dirty = true;
+    Ok(())
+}
+
+/// Just the bodies of a snapshot, for drawing them without restoring it
+pub(crate) fn bodies(bytes: &[u8]) -> Result<RigidBodySet, String> {
+    let snapshot: SimulationSnapshot = bincode::deserialize(bytes).map_err(|e| e.to_string())?;
+    Ok(snapshot.rigid_body_set)
+}
+
+/// Step back `steps` entries in the history and restore that state. Returns
+/// the number of steps actually rewound.
+pub(crate) fn rewind(physics: &mut PhysicsState, steps: usize) -> usize {
+    let steps = physics.history.discard_newest(steps);
+    if steps == 0 {
+        return 0;
+    }
+    let bytes = match physics.history.get(0) {
+        Some(entry) => entry.bytes.clone(),
+        None => return 0,
+    };
+    match restore(physics, &bytes) {
+        Ok(()) => steps,
+        Err(e) => {
+            log::error!("rewind: failed to restore snapshot: {}", e);
+            0
+        }
+    }
+}
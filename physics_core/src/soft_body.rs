@@ -0,0 +1,374 @@
+//! Soft-body blobs made of a ring of small bodies
+//!
+//! A blob is `segments` small dynamic boxes on a circle. Neighbours are tied
+//! together with Rapier spring joints (plus weaker springs to the node after
+//! next, which resist folding), so the ring keeps its perimeter but can bend.
+//! On its own such a ring collapses; each step `pressure_forces` pushes the
+//! nodes out along the ring's outward normals in proportion to how far the
+//! enclosed area fell below its rest area, which gives the blob its volume
+//! and its squash-and-bounce. The filled polygon is drawn by `SoftBodyPass`.
+//!
+//! The nodes are ordinary entities: they collide, sleep, snapshot and rewind
+//! like any other box. A blob whose node is removed stops being a blob; its
+//! remaining nodes stay in the scene as loose boxes.
+
+use crate::rapier::prelude::*;
+use serde::Deserialize;
+
+use crate::precision::{to_real, xy};
+
+/// Ring node count range
+pub const MIN_SEGMENTS: u32 = 3;
+pub const MAX_SEGMENTS: u32 = 64;
+
+/// Stiffness of the skip-one springs relative to the edge springs
+const BEND_STIFFNESS_RATIO: f32 = 0.5;
+/// Fill color multiplier at the rim of the drawn polygon
+const RIM_SHADE: f32 = 0.6;
+
+/// What to spawn; every field has a default so hosts can send partial JSON
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SoftBodyDesc {
+    /// Center of the ring
+    pub x: f32,
+    pub y: f32,
+    /// Ring radius, world units
+    pub radius: f32,
+    /// Node count, `MIN_SEGMENTS..=MAX_SEGMENTS`
+    pub segments: u32,
+    /// Half extent of each node box
+    pub node_half_extent: f32,
+    /// Edge spring stiffness, N/m
+    pub stiffness: f32,
+    /// Edge spring damping, N·s/m
+    pub damping: f32,
+    /// Outward force per metre of rim at zero area; 0 makes a loose ring
+    pub pressure: f32,
+    pub color: [f32; 4],
+}
+
+impl Default for SoftBodyDesc {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            radius: 0.2,
+            segments: 16,
+            node_half_extent: 0.02,
+            stiffness: 400.0,
+            damping: 2.0,
+            pressure: 40.0,
+            color: [0.95, 0.45, 0.6, 1.0],
+        }
+    }
+}
+
+impl SoftBodyDesc {
+    /// The description with `segments` and the color clamped, or None if a
+    /// number is non-finite, the radius or node size is not positive or the
+    /// strengths are negative
+    pub fn validated(self) -> Option<Self> {
+        let numbers = [self.x, self.y, self.radius, self.node_half_extent, self.stiffness, self.damping, self.pressure];
+        let valid = numbers.iter().all(|v| v.is_finite())
+            && self.radius > 0.0
+            && self.node_half_extent > 0.0
+            && self.stiffness >= 0.0
+            && self.damping >= 0.0
+            && self.pressure >= 0.0;
+        valid.then(|| Self {
+            segments: self.segments.clamp(MIN_SEGMENTS, MAX_SEGMENTS),
+            color: self.color.map(|c| if c.is_nan() { 1.0 } else { c.clamp(0.0, 1.0) }),
+            ..self
+        })
+    }
+
+    /// Node positions, counter-clockwise from the +x axis
+    pub fn ring(&self) -> Vec<[f32; 2]> {
+        (0..self.segments)
+            .map(|i| {
+                let angle = i as f32 / self.segments as f32 * std::f32::consts::TAU;
+                [self.x + self.radius * angle.cos(), self.y + self.radius * angle.sin()]
+            })
+            .collect()
+    }
+}
+
+/// Signed area of a closed polygon; positive when counter-clockwise
+pub fn polygon_area(points: &[[f32; 2]]) -> f32 {
+    let n = points.len();
+    let twice: f32 = (0..n)
+        .map(|i| {
+            let [ax, ay] = points[i];
+            let [bx, by] = points[(i + 1) % n];
+            ax * by - bx * ay
+        })
+        .sum();
+    twice * 0.5
+}
+
+/// Force on each node of a counter-clockwise ring from the internal pressure:
+/// every edge pushes both its ends along its outward normal with
+/// `pressure * (1 - area / rest_area) * edge_length / 2`. A ring at or above
+/// its rest area gets no force.
+pub fn pressure_forces(points: &[[f32; 2]], rest_area: f32, pressure: f32) -> Vec<[f32; 2]> {
+    let n = points.len();
+    let mut forces = vec![[0.0, 0.0]; n];
+    if n < MIN_SEGMENTS as usize || rest_area <= 0.0 {
+        return forces;
+    }
+    let deficit = (1.0 - polygon_area(points) / rest_area).max(0.0);
+    if deficit == 0.0 || !deficit.is_finite() {
+        return forces;
+    }
+    // The unnormalized normal (ey, -ex) already has the edge's length
+    let scale = 0.5 * pressure * deficit;
+    for i in 0..n {
+        let j = (i + 1) % n;
+        let [ex, ey] = [points[j][0] - points[i][0], points[j][1] - points[i][1]];
+        let push = [ey * scale, -ex * scale];
+        for k in [i, j] {
+            forces[k][0] += push[0];
+            forces[k][1] += push[1];
+        }
+    }
+    forces
+}
+
+/// Filled polygon as a fan from the centroid, three vertices per edge
+pub fn fill_vertices(points: &[[f32; 2]], color: [f32; 4]) -> Vec<SoftBodyVertex> {
+    let n = points.len();
+    if n < MIN_SEGMENTS as usize {
+        return Vec::new();
+    }
+    let center = points.iter().fold([0.0, 0.0], |acc, p| [acc[0] + p[0], acc[1] + p[1]]).map(|c| c / n as f32);
+    let rim = [color[0] * RIM_SHADE, color[1] * RIM_SHADE, color[2] * RIM_SHADE, color[3]];
+    let mut vertices = Vec::with_capacity(n * 3);
+    for i in 0..n {
+        vertices.push(SoftBodyVertex { position: center, color });
+        vertices.push(SoftBodyVertex { position: points[i], color: rim });
+        vertices.push(SoftBodyVertex { position: points[(i + 1) % n], color: rim });
+    }
+    vertices
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SoftBodyVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// Spring joint between two nodes `rest_length` apart. Nodes of one blob do
+/// not collide with each other through a joint.
+pub(crate) fn node_spring(rest_length: f32, stiffness: f32, damping: f32) -> GenericJoint {
+    let mut joint: GenericJoint = SpringJointBuilder::new(to_real(rest_length), to_real(stiffness), to_real(damping))
+        .spring_model(MotorModel::ForceBased)
+        .build()
+        .into();
+    joint.set_contacts_enabled(false);
+    joint
+}
+
+/// (a, b, stiffness) for every spring of a ring of `n` nodes. Skip-one
+/// springs would duplicate edges in a triangle and each other in a square.
+pub fn ring_springs(n: usize, stiffness: f32) -> Vec<(usize, usize, f32)> {
+    let mut springs: Vec<_> = (0..n).map(|i| (i, (i + 1) % n, stiffness)).collect();
+    let bends = match n {
+        0..=3 => 0,
+        4 => 2,
+        _ => n,
+    };
+    springs.extend((0..bends).map(|i| (i, (i + 2) % n, stiffness * BEND_STIFFNESS_RATIO)));
+    springs
+}
+
+/// A live blob
+#[derive(Debug, Clone)]
+pub(crate) struct SoftBody {
+    pub id: u64,
+    /// Ring nodes in counter-clockwise order
+    pub nodes: Vec<RigidBodyHandle>,
+    pub rest_area: f32,
+    pub pressure: f32,
+    pub color: [f32; 4],
+}
+
+/// Every blob in the scene
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SoftBodies {
+    bodies: Vec<SoftBody>,
+    /// Last id handed out; ids start at 1
+    last_id: u64,
+}
+
+impl SoftBodies {
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SoftBody> {
+        self.bodies.iter()
+    }
+
+    /// Register a blob. Returns its id.
+    pub fn insert(&mut self, nodes: Vec<RigidBodyHandle>, rest_area: f32, pressure: f32, color: [f32; 4]) -> u64 {
+        self.last_id += 1;
+        let id = self.last_id;
+        self.bodies.push(SoftBody { id, nodes, rest_area, pressure, color });
+        id
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<SoftBody> {
+        let index = self.bodies.iter().position(|body| body.id == id)?;
+        Some(self.bodies.remove(index))
+    }
+
+    /// Forget blobs that lost a node
+    pub fn prune(&mut self, rigid_body_set: &RigidBodySet) {
+        self.bodies.retain(|body| body.nodes.iter().all(|&handle| rigid_body_set.contains(handle)));
+    }
+
+    /// Node positions of a blob; nodes that no longer exist are skipped
+    pub fn positions(body: &SoftBody, rigid_body_set: &RigidBodySet) -> Vec<[f32; 2]> {
+        body.nodes
+            .iter()
+            .filter_map(|&handle| rigid_body_set.get(handle))
+            .map(|rb| xy(rb.translation()))
+            .collect()
+    }
+
+    /// The fill mesh of every blob
+    pub fn vertices(&self, rigid_body_set: &RigidBodySet) -> Vec<SoftBodyVertex> {
+        self.bodies
+            .iter()
+            .flat_map(|body| fill_vertices(&Self::positions(body, rigid_body_set), body.color))
+            .collect()
+    }
+}
+
+impl SoftBodyVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SoftBodyVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Vertex capacity of a new pass; the buffer doubles as needed
+const INITIAL_VERTICES: u32 = 1024;
+
+/// Draws the blob fills inside the main pass, under the bodies
+pub(crate) struct SoftBodyPass {
+    /// Pipeline matching the main pass (swapchain format, MSAA sample count)
+    pipeline: wgpu::RenderPipeline,
+    /// Pipeline matching the HDR bloom target, when bloom is available
+    hdr_pipeline: Option<wgpu::RenderPipeline>,
+    vertex_buffer: wgpu::Buffer,
+    vertex_capacity: u32,
+    vertex_count: u32,
+}
+
+impl SoftBodyPass {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        output_format: wgpu::TextureFormat,
+        sample_count: u32,
+        hdr_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Soft Body Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/soft_body.wgsl"))),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Soft Body Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let make_pipeline = |label: &str, format: wgpu::TextureFormat, count: u32| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_soft_body"),
+                    buffers: &[SoftBodyVertex::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_soft_body"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let pipeline = make_pipeline("Soft Body Pipeline", output_format, sample_count);
+        let hdr_pipeline = hdr_format.map(|format| make_pipeline("HDR Soft Body Pipeline", format, 1));
+        Self {
+            pipeline,
+            hdr_pipeline,
+            vertex_buffer: Self::create_vertex_buffer(device, INITIAL_VERTICES),
+            vertex_capacity: INITIAL_VERTICES,
+            vertex_count: 0,
+        }
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, capacity: u32) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Soft Body Vertex Buffer"),
+            size: capacity as u64 * std::mem::size_of::<SoftBodyVertex>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Upload this frame's mesh
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, vertices: &[SoftBodyVertex]) {
+        self.vertex_count = vertices.len() as u32;
+        if vertices.is_empty() {
+            return;
+        }
+        if self.vertex_count > self.vertex_capacity {
+            self.vertex_capacity = self.vertex_count.next_power_of_two();
+            self.vertex_buffer = Self::create_vertex_buffer(device, self.vertex_capacity);
+        }
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vertices));
+    }
+
+    /// Draw the prepared mesh. Leaves the soft body pipeline and camera (group 0) bound.
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, camera_bind_group: &wgpu::BindGroup, hdr: bool) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        let Some(pipeline) = (if hdr { self.hdr_pipeline.as_ref() } else { Some(&self.pipeline) }) else {
+            return;
+        };
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+    }
+}
@@ -0,0 +1,97 @@
+//! Rapier solver settings exposed to hosts
+//!
+//! `SolverConfig` is the user-facing subset of `IntegrationParameters`. It is
+//! kept in `PhysicsState` and applied before every step, after `dt` is set,
+//! because Rapier 0.19 derives its error reduction from a contact spring
+//! frequency and `dt`: a fixed `erp` only stays fixed if the frequency is
+//! recomputed for each step length. Fewer iterations and a looser error are
+//! the knobs for low-end devices.
+
+use std::num::NonZeroUsize;
+
+use crate::precision::{to_f32, to_real};
+use crate::rapier::prelude::IntegrationParameters;
+use serde::{Deserialize, Serialize};
+
+/// Upper bound for every iteration count
+pub const MAX_ITERATIONS: u32 = 64;
+pub const MAX_CCD_SUBSTEPS: u32 = 32;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SolverConfig {
+    /// Velocity solver iterations, at least 1
+    pub solver_iterations: u32,
+    /// Extra friction-only iterations after the main solve
+    pub friction_iterations: u32,
+    /// PGS iterations inside each solver iteration, at least 1
+    pub internal_pgs_iterations: u32,
+    /// Fraction of penetration corrected per step, below 1; 0 keeps Rapier's
+    /// default contact stiffness
+    pub erp: f32,
+    /// Penetration tolerated before it is corrected, in meters
+    pub allowed_linear_error: f32,
+    /// CCD substeps for fast bodies, at least 1
+    pub max_ccd_substeps: u32,
+    /// Damping ratio of the contact spring
+    pub contact_damping: f32,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        let defaults = IntegrationParameters::default();
+        Self {
+            solver_iterations: defaults.num_solver_iterations.get() as u32,
+            friction_iterations: defaults.num_additional_friction_iterations as u32,
+            internal_pgs_iterations: defaults.num_internal_pgs_iterations as u32,
+            erp: 0.0,
+            allowed_linear_error: to_f32(defaults.normalized_allowed_linear_error * defaults.length_unit),
+            max_ccd_substeps: defaults.max_ccd_substeps as u32,
+            contact_damping: to_f32(defaults.contact_damping_ratio),
+        }
+    }
+}
+
+impl SolverConfig {
+    /// Clamp every field into its valid range
+    pub fn sanitized(self) -> Self {
+        let finite_or = |v: f32, fallback: f32| if v.is_finite() { v } else { fallback };
+        let defaults = Self::default();
+        Self {
+            solver_iterations: self.solver_iterations.clamp(1, MAX_ITERATIONS),
+            friction_iterations: self.friction_iterations.min(MAX_ITERATIONS),
+            internal_pgs_iterations: self.internal_pgs_iterations.clamp(1, MAX_ITERATIONS),
+            erp: finite_or(self.erp, 0.0).clamp(0.0, 0.99),
+            allowed_linear_error: finite_or(self.allowed_linear_error, defaults.allowed_linear_error).max(0.0),
+            max_ccd_substeps: self.max_ccd_substeps.clamp(1, MAX_CCD_SUBSTEPS),
+            contact_damping: finite_or(self.contact_damping, defaults.contact_damping).max(0.0),
+        }
+    }
+
+    /// Write the settings into `params`; `params.dt` must already hold this step's length
+    pub fn apply(&self, params: &mut IntegrationParameters) {
+        params.num_solver_iterations = NonZeroUsize::new(self.solver_iterations.max(1) as usize).unwrap_or(NonZeroUsize::MIN);
+        params.num_additional_friction_iterations = self.friction_iterations as usize;
+        params.num_internal_pgs_iterations = self.internal_pgs_iterations.max(1) as usize;
+        params.contact_damping_ratio = to_real(self.contact_damping);
+        params.contact_natural_frequency = if self.erp > 0.0 {
+            to_real(natural_frequency_for_erp(self.erp, self.contact_damping, to_f32(params.dt)))
+        } else {
+            IntegrationParameters::default().contact_natural_frequency
+        };
+        params.normalized_allowed_linear_error = to_real(self.allowed_linear_error) / params.length_unit;
+        params.max_ccd_substeps = self.max_ccd_substeps.max(1) as usize;
+    }
+}
+
+/// Contact spring frequency (Hz) giving `erp` at step length `dt`. Inverts
+/// Rapier's `erp = dt·ω / (dt·ω + 4ζ)` with `ω = 2π·f`.
+pub fn natural_frequency_for_erp(erp: f32, damping_ratio: f32, dt: f32) -> f32 {
+    if dt <= 0.0 || erp <= 0.0 {
+        return to_f32(IntegrationParameters::default().contact_natural_frequency);
+    }
+    let erp = erp.min(0.99);
+    let angular = 4.0 * damping_ratio * erp / (dt * (1.0 - erp));
+    angular / (2.0 * std::f32::consts::PI)
+}
@@ -0,0 +1,238 @@
+//! Two-player split screen
+//!
+//! With a `SplitScreen` resource in the world the main pass draws the scene
+//! twice, once per half of the surface, each through its own camera. A
+//! player's camera follows a body when `follow` names one and stays at its
+//! configured center otherwise (or when the body is gone).
+//!
+//! Input is routed by player as well: key codes are looked up in `bindings`
+//! and a pointer press belongs to the player whose half it lands in. Routed
+//! input pushes the `Controllable` bodies tagged with that `Player`;
+//! controllable bodies without a tag answer to both players. Touch gestures,
+//! the slingshot and world labels keep using the main camera.
+
+use bevy_ecs::prelude::*;
+use serde::Deserialize;
+
+use crate::events::{GameEvent, InputEventType};
+use crate::views::ViewCamera;
+
+/// Pixels of clear color between the halves
+pub const DIVIDER: u32 = 2;
+
+/// Impulse of one movement key press
+const MOVE_IMPULSE: f32 = 0.5;
+/// Impulse of a jump
+const JUMP_IMPULSE: f32 = 5.0;
+
+/// Which player a `Controllable` body answers to, 1 or 2
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Player(pub u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SplitLayout {
+    /// Player 1 on the left
+    #[default]
+    SideBySide,
+    /// Player 1 on top
+    Stacked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Jump,
+}
+
+impl Action {
+    /// Impulse applied to the player's bodies, as the default input system does
+    pub fn impulse(self) -> [f32; 2] {
+        match self {
+            Action::Up => [0.0, MOVE_IMPULSE],
+            Action::Down => [0.0, -MOVE_IMPULSE],
+            Action::Left => [-MOVE_IMPULSE, 0.0],
+            Action::Right => [MOVE_IMPULSE, 0.0],
+            Action::Jump => [0.0, JUMP_IMPULSE],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyBinding {
+    /// Host key code, as passed to the key event functions
+    pub key_code: i32,
+    pub player: u8,
+    pub action: Action,
+}
+
+/// One player's half of the screen
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlayerView {
+    /// Entity id of the body the camera follows
+    pub follow: Option<u64>,
+    pub center_x: f32,
+    pub center_y: f32,
+    /// World units visible vertically
+    pub view_height: f32,
+}
+
+impl Default for PlayerView {
+    fn default() -> Self {
+        let camera = ViewCamera::default();
+        Self { follow: None, center_x: camera.center_x, center_y: camera.center_y, view_height: camera.view_height }
+    }
+}
+
+impl PlayerView {
+    /// Camera centered on `followed`, the followed body's position if it
+    /// still exists
+    pub fn camera(&self, followed: Option<[f32; 2]>) -> ViewCamera {
+        let [center_x, center_y] = followed.unwrap_or([self.center_x, self.center_y]);
+        ViewCamera { center_x, center_y, view_height: self.view_height }
+    }
+}
+
+/// Android key codes: W/A/S/D and space for player 1, the D-pad and enter
+/// for player 2
+pub fn default_bindings() -> Vec<KeyBinding> {
+    let binding = |key_code, player, action| KeyBinding { key_code, player, action };
+    vec![
+        binding(51, 1, Action::Up),
+        binding(47, 1, Action::Down),
+        binding(29, 1, Action::Left),
+        binding(32, 1, Action::Right),
+        binding(62, 1, Action::Jump),
+        binding(19, 2, Action::Up),
+        binding(20, 2, Action::Down),
+        binding(21, 2, Action::Left),
+        binding(22, 2, Action::Right),
+        binding(66, 2, Action::Jump),
+    ]
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SplitScreen {
+    pub layout: SplitLayout,
+    pub players: [PlayerView; 2],
+    pub bindings: Vec<KeyBinding>,
+    /// Size of the surface being split, px; kept current by the renderer
+    #[serde(skip)]
+    pub surface_size: (u32, u32),
+}
+
+impl Default for SplitScreen {
+    fn default() -> Self {
+        Self {
+            layout: SplitLayout::SideBySide,
+            players: [PlayerView::default(); 2],
+            bindings: default_bindings(),
+            surface_size: (0, 0),
+        }
+    }
+}
+
+impl SplitScreen {
+    pub fn is_valid(&self) -> bool {
+        self.players.iter().all(|view| view.camera(None).is_valid())
+            && self.bindings.iter().all(|binding| matches!(binding.player, 1 | 2))
+    }
+
+    /// Pixel rectangles `[x, y, width, height]` (y down) of player 1 and 2;
+    /// None while the surface is too small to split
+    pub fn viewports(&self) -> Option<[[u32; 4]; 2]> {
+        let (width, height) = self.surface_size;
+        match self.layout {
+            SplitLayout::SideBySide if width > DIVIDER + 1 && height > 0 => {
+                let left = (width - DIVIDER) / 2;
+                let right = width - DIVIDER - left;
+                Some([[0, 0, left, height], [left + DIVIDER, 0, right, height]])
+            }
+            SplitLayout::Stacked if height > DIVIDER + 1 && width > 0 => {
+                let top = (height - DIVIDER) / 2;
+                let bottom = height - DIVIDER - top;
+                Some([[0, 0, width, top], [0, top + DIVIDER, width, bottom]])
+            }
+            _ => None,
+        }
+    }
+
+    /// Player whose half holds surface pixel (x, y)
+    pub fn player_at(&self, x: f32, y: f32) -> u8 {
+        let (width, height) = self.surface_size;
+        let second = match self.layout {
+            SplitLayout::SideBySide => x >= width as f32 * 0.5,
+            SplitLayout::Stacked => y >= height as f32 * 0.5,
+        };
+        if second {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Player and impulse for an input event; None for events no player uses
+    pub fn route(&self, event: &GameEvent) -> Option<(u8, [f32; 2])> {
+        match event.event_type {
+            InputEventType::KeyDown => {
+                let key_code = event.key_code?;
+                let binding = self.bindings.iter().find(|binding| binding.key_code == key_code)?;
+                Some((binding.player, binding.action.impulse()))
+            }
+            InputEventType::PointerDown => Some((self.player_at(event.x, event.y), Action::Jump.impulse())),
+            _ => None,
+        }
+    }
+}
+
+/// Camera uniforms for the two halves, drawn by the main pass
+pub(crate) struct SplitCameras {
+    cameras: [(wgpu::Buffer, wgpu::BindGroup); 2],
+    viewports: Option<[[u32; 4]; 2]>,
+}
+
+impl SplitCameras {
+    pub fn new(device: &wgpu::Device, camera_bind_group_layout: &wgpu::BindGroupLayout) -> Self {
+        let camera = |label| {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: std::mem::size_of::<crate::camera::CameraUniform>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: camera_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry { binding: 0, resource: buffer.as_entire_binding() }],
+                label: Some("split_camera_bind_group"),
+            });
+            (buffer, bind_group)
+        };
+        Self { cameras: [camera("Player 1 Camera Buffer"), camera("Player 2 Camera Buffer")], viewports: None }
+    }
+
+    /// Point both cameras for this frame; None turns split screen off
+    pub fn prepare(&mut self, queue: &wgpu::Queue, halves: Option<([[u32; 4]; 2], [ViewCamera; 2])>) {
+        self.viewports = halves.map(|(viewports, _)| viewports);
+        let Some((viewports, cameras)) = halves else {
+            return;
+        };
+        for ((buffer, _), (camera, [_, _, width, height])) in self.cameras.iter().zip(cameras.iter().zip(viewports)) {
+            let mut uniform = crate::camera::CameraUniform::new();
+            uniform.update_view_proj(&camera.camera(width, height));
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[uniform]));
+        }
+    }
+
+    /// Camera and viewport of each half while split screen is on
+    pub fn halves(&self) -> Option<[(&wgpu::BindGroup, [u32; 4]); 2]> {
+        let [first, second] = self.viewports?;
+        Some([(&self.cameras[0].1, first), (&self.cameras[1].1, second)])
+    }
+}
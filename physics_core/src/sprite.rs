@@ -7,6 +7,8 @@ pub struct SpriteSheetComponent {
     pub frame_count: u32,
     pub frame_duration: f32, // in seconds
     pub looping: bool,
+    /// Cell the animation starts at, so several clips can share one sheet
+    pub first_frame: u32,
 }
 
 impl SpriteSheetComponent {
@@ -23,27 +25,28 @@ impl SpriteSheetComponent {
             frame_count,
             frame_duration,
             looping,
+            first_frame: 0,
         }
     }
 
     pub fn frame_for_time(&self, elapsed: f32, speed: f32) -> u32 {
         if self.frame_count == 0 || self.frame_duration <= 0.0 {
-            return 0;
+            return self.first_frame;
         }
         let total_duration = self.frame_duration * self.frame_count as f32;
         let adjusted_elapsed = elapsed * speed;
 
         if self.looping {
             let cycle_time = adjusted_elapsed % total_duration;
-            (cycle_time / self.frame_duration) as u32 % self.frame_count
+            self.first_frame + (cycle_time / self.frame_duration) as u32 % self.frame_count
         } else {
             let frame = (adjusted_elapsed / self.frame_duration) as u32;
-            frame.min(self.frame_count - 1)
+            self.first_frame + frame.min(self.frame_count - 1)
         }
     }
 
     pub fn uv_for_frame(&self, frame: u32) -> (f32, f32, f32, f32) {
-        let frame = frame % self.frame_count;
+        let frame = frame % (self.rows * self.columns).max(1);
         let row = frame / self.columns;
         let col = frame % self.columns;
 
@@ -64,6 +67,7 @@ impl Default for SpriteSheetComponent {
             frame_count: 1,
             frame_duration: 0.1,
             looping: true,
+            first_frame: 0,
         }
     }
 }
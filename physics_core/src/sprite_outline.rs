@@ -0,0 +1,160 @@
+//! Colliders traced from sprite alpha
+//!
+//! `generate_collider_from_texture` turns the visible part of a registered
+//! texture into a collision shape. Pixels whose alpha is above the threshold
+//! count as solid; marching squares follows the edge of every solid island
+//! along pixel boundaries, Douglas–Peucker drops points closer than
+//! `simplification` pixels to the simplified outline, and each outline is
+//! split into convex pieces (see `polygon`). Holes are filled and islands
+//! smaller than a few pixels are ignored.
+//!
+//! The pieces are kept with the material and span -1..1 like the drawn quad,
+//! so a body using the texture collides with its visible shape at whatever
+//! size it is drawn: bodies get the shape when the material is set on them,
+//! and bodies already using it are reshaped when it is generated.
+
+use std::collections::HashMap;
+
+use crate::polygon;
+
+/// Islands covering fewer square pixels than this are ignored
+const MIN_AREA: f32 = 4.0;
+
+/// Corners of a marching squares cell, clockwise from the top left on screen
+const CORNERS: [[i32; 2]; 4] = [[0, 0], [1, 0], [1, 1], [0, 1]];
+
+/// Cell edges as the two corners they join: top, right, bottom, left
+const EDGES: [[usize; 2]; 4] = [[0, 1], [1, 2], [2, 3], [3, 0]];
+
+/// Signed area of a closed outline; positive when solid lies to the left of
+/// each step, as it does for the outer edge of a traced island
+fn area(points: &[[f32; 2]]) -> f32 {
+    let n = points.len();
+    (0..n).map(|i| points[i][0] * points[(i + 1) % n][1] - points[(i + 1) % n][0] * points[i][1]).sum::<f32>() / 2.0
+}
+
+/// Closed outlines around every solid island of a `width * height` RGBA
+/// image, in pixels from the top left corner. Outlines pass through the
+/// middle of pixel edges; islands touching only at a corner stay apart.
+pub fn trace(width: u32, height: u32, rgba: &[u8], alpha_threshold: u8) -> Vec<Vec<[f32; 2]>> {
+    let (w, h) = (width as i32, height as i32);
+    let solid = |x: i32, y: i32| {
+        (0..w).contains(&x) && (0..h).contains(&y) && rgba.get(((y * w + x) * 4 + 3) as usize).is_some_and(|&a| a > alpha_threshold)
+    };
+
+    // Segment ends in doubled pixel coordinates, start to end with solid on the left
+    let mut next: HashMap<[i32; 2], [i32; 2]> = HashMap::new();
+    for y in -1..h {
+        for x in -1..w {
+            let inside = CORNERS.map(|[dx, dy]| solid(x + dx, y + dy));
+            let crossed: Vec<usize> = (0..4).filter(|&e| inside[EDGES[e][0]] != inside[EDGES[e][1]]).collect();
+            let pairs: Vec<[usize; 2]> = match (crossed.len(), inside) {
+                (2, _) => vec![[crossed[0], crossed[1]]],
+                // Saddles: cut each solid corner off on its own
+                (4, [true, false, true, false]) => vec![[3, 0], [1, 2]],
+                (4, _) => vec![[0, 1], [2, 3]],
+                _ => Vec::new(),
+            };
+            for [e1, e2] in pairs {
+                let midpoint = |e: usize| {
+                    let [[ax, ay], [bx, by]] = EDGES[e].map(|c| CORNERS[c]);
+                    [2 * x + ax + bx + 1, 2 * y + ay + by + 1]
+                };
+                let (p, q) = (midpoint(e1), midpoint(e2));
+                // A corner on one side of the segment, which the orientation is checked against
+                let corner = EDGES[e1].iter().find(|c| EDGES[e2].contains(c)).copied().unwrap_or(0);
+                let [cx, cy] = CORNERS[corner];
+                let (sx, sy) = (2 * (x + cx) + 1, 2 * (y + cy) + 1);
+                let turn = (q[0] - p[0]) * (sy - p[1]) - (q[1] - p[1]) * (sx - p[0]);
+                if (turn > 0) == inside[corner] {
+                    next.insert(p, q);
+                } else {
+                    next.insert(q, p);
+                }
+            }
+        }
+    }
+
+    let mut outlines = Vec::new();
+    while let Some(&start) = next.keys().next() {
+        let mut outline = Vec::new();
+        let mut point = start;
+        while let Some(following) = next.remove(&point) {
+            outline.push([point[0] as f32 / 2.0, point[1] as f32 / 2.0]);
+            point = following;
+        }
+        // Holes wind the other way round
+        if area(&outline) >= MIN_AREA {
+            outlines.push(outline);
+        }
+    }
+    outlines
+}
+
+/// Distance from `p` to segment ab
+fn distance(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length = dx * dx + dy * dy;
+    let t = if length > 0.0 { (((p[0] - a[0]) * dx + (p[1] - a[1]) * dy) / length).clamp(0.0, 1.0) } else { 0.0 };
+    ((p[0] - a[0] - t * dx).powi(2) + (p[1] - a[1] - t * dy).powi(2)).sqrt()
+}
+
+/// Mark the points of `points[first..=last]` that stay within `tolerance`
+fn douglas_peucker(points: &[[f32; 2]], first: usize, last: usize, tolerance: f32, keep: &mut [bool]) {
+    let farthest = (first + 1..last)
+        .map(|i| (i, distance(points[i], points[first], points[last])))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((i, _)) = farthest.filter(|&(_, d)| d > tolerance) {
+        keep[i] = true;
+        douglas_peucker(points, first, i, tolerance, keep);
+        douglas_peucker(points, i, last, tolerance, keep);
+    }
+}
+
+/// Closed outline with the points within `tolerance` of the rest dropped
+pub fn simplify(points: &[[f32; 2]], tolerance: f32) -> Vec<[f32; 2]> {
+    if points.len() < 4 {
+        return points.to_vec();
+    }
+    // Split the loop at the point farthest from the first
+    let from_first = |p: [f32; 2]| (p[0] - points[0][0]).hypot(p[1] - points[0][1]);
+    let split = (1..points.len()).max_by(|&a, &b| from_first(points[a]).total_cmp(&from_first(points[b]))).unwrap_or(1);
+    let mut closed = points.to_vec();
+    closed.push(points[0]);
+    let mut keep = vec![false; closed.len()];
+    keep[0] = true;
+    keep[split] = true;
+    douglas_peucker(&closed, 0, split, tolerance, &mut keep);
+    douglas_peucker(&closed, split, closed.len() - 1, tolerance, &mut keep);
+    points.iter().zip(keep).filter(|(_, keep)| *keep).map(|(p, _)| *p).collect()
+}
+
+/// Convex pieces of the solid parts of a `width * height` RGBA image,
+/// spanning -1..1 with y up like the drawn quad. Outlines that would still
+/// have more than `polygon::MAX_POINTS` points are simplified further.
+pub fn pieces(width: u32, height: u32, rgba: &[u8], alpha_threshold: u8, simplification: f32) -> Result<Vec<Vec<[f32; 2]>>, String> {
+    if width == 0 || height == 0 || rgba.len() < width as usize * height as usize * 4 {
+        return Err("texture has no pixels".into());
+    }
+    if !simplification.is_finite() || simplification < 0.0 {
+        return Err("simplification must be >= 0".into());
+    }
+    let mut pieces = Vec::new();
+    for outline in trace(width, height, rgba, alpha_threshold) {
+        let mut tolerance = simplification;
+        let mut simplified = simplify(&outline, tolerance);
+        while simplified.len() > polygon::MAX_POINTS {
+            tolerance = (tolerance * 2.0).max(0.5);
+            simplified = simplify(&outline, tolerance);
+        }
+        let unit: Vec<[f32; 2]> = simplified
+            .iter()
+            .map(|p| [p[0] / width as f32 * 2.0 - 1.0, 1.0 - p[1] / height as f32 * 2.0])
+            .collect();
+        pieces.extend(polygon::decompose(&unit).map_err(|e| format!("{}; try a lower simplification", e))?);
+    }
+    if pieces.is_empty() {
+        return Err("texture has no pixels above the alpha threshold".into());
+    }
+    Ok(pieces)
+}
@@ -0,0 +1,89 @@
+//! Sprite regions and nine-slice scaling
+//!
+//! A `SpriteRegion` draws one rect of its material's texture, such as an
+//! image packed into an atlas, on a quad stretched to its own width and
+//! height. Plain stretching distorts frames and corners, so the region can
+//! also keep borders: the nine-slice layout, where corners keep their size,
+//! edges stretch along one axis and only the middle stretches along both.
+//! Panels and platforms of any size are then drawn from one small image.
+//!
+//! Sizes are relative to the entity's square quad (its `Scale`), and borders
+//! are fractions of the region that keep the size they have on that square.
+//! A quad too small for both borders shrinks them alike. The region only
+//! changes what is drawn; colliders keep their shape. It replaces sprite
+//! sheet frames on entities that have both.
+
+use bevy_ecs::prelude::*;
+
+/// Part of the texture drawn, at a size and with borders of its own
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct SpriteRegion {
+    /// Left, top, width and height in texture coordinates (0..1, v down)
+    pub rect: [f32; 4],
+    /// Drawn width and height relative to the entity's square quad
+    pub size: [f32; 2],
+    /// Left, top, right and bottom borders as fractions of the region's
+    /// width and height; all zero stretches the whole region
+    pub borders: [f32; 4],
+}
+
+impl Default for SpriteRegion {
+    fn default() -> Self {
+        Self { rect: [0.0, 0.0, 1.0, 1.0], size: [1.0, 1.0], borders: [0.0; 4] }
+    }
+}
+
+impl SpriteRegion {
+    pub fn validate(&self) -> Result<(), String> {
+        let [u, v, width, height] = self.rect;
+        if !self.rect.iter().all(|value| value.is_finite()) || width <= 0.0 || height <= 0.0 {
+            return Err("region width and height must be > 0".into());
+        }
+        if u < 0.0 || v < 0.0 || u + width > 1.0 + f32::EPSILON || v + height > 1.0 + f32::EPSILON {
+            return Err("region must lie inside the texture (0..1)".into());
+        }
+        if !self.size.iter().all(|value| value.is_finite() && *value > 0.0) {
+            return Err("drawn size must be > 0".into());
+        }
+        let [left, top, right, bottom] = self.borders;
+        if !self.borders.iter().all(|value| value.is_finite() && *value >= 0.0) {
+            return Err("borders must be >= 0".into());
+        }
+        if left + right > 1.0 || top + bottom > 1.0 {
+            return Err("opposite borders must not cover more than the region".into());
+        }
+        Ok(())
+    }
+
+    /// Instance `uv_offset` and `uv_scale`
+    pub fn uv(&self) -> ([f32; 2], [f32; 2]) {
+        let [u, v, width, height] = self.rect;
+        ([u, v], [width, height])
+    }
+
+    /// Instance `slice_min` (left, top) and `slice_max` (right, bottom)
+    pub fn slices(&self) -> ([f32; 2], [f32; 2]) {
+        let [left, top, right, bottom] = self.borders;
+        ([left, top], [right, bottom])
+    }
+}
+
+/// Where in the region (0..1) a point `t` of the way across an axis `size`
+/// times the square's length samples, keeping borders `low` and `high` at
+/// their size on the square. Same as `slice_axis` in shader.wgsl.
+pub fn slice_axis(t: f32, size: f32, low: f32, high: f32) -> f32 {
+    let borders = low + high;
+    if borders <= 0.0 {
+        return t;
+    }
+    let fit = (size / borders).min(1.0);
+    let p = t * size;
+    if p < low * fit {
+        return p / fit;
+    }
+    if p > size - high * fit {
+        return 1.0 - (size - p) / fit;
+    }
+    let middle = size - borders * fit;
+    low + (p - low * fit) * (1.0 - borders) / middle.max(1e-6)
+}
@@ -0,0 +1,419 @@
+//! Finite state machines for entity behavior
+//!
+//! A `StateMachine` component walks an entity through named states. Each
+//! state lists its transitions in priority order; after every step the first
+//! one whose condition holds moves the machine on, at most once per step.
+//! Conditions are a timeout (seconds spent in the state), a collision that
+//! started with a body in the given groups (walls count as group bits 0, so
+//! only `groups::ALL` matches them) or a named event sent by the host.
+//! Events a state has no transition for are dropped at the end of the step.
+//!
+//! Entering a state runs its hooks: a new `MovementStrategy` (or none, which
+//! leaves the body to physics), a sprite sheet clip restarted from its first
+//! frame and a material. Fields a state leaves out keep whatever the
+//! previous state set, so a plain "wait" state needs no hooks at all.
+//!
+//! Machines run in the animation set, after the physics step that raised the
+//! collisions, so a transition's movement takes effect on the next step.
+
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use serde::Deserialize;
+
+use crate::animation::AnimatorComponent;
+use crate::game_entity::{
+    CircularMovement, HorizontalRandomMovement, LinearMovement, MovementComponent, MovementStrategy,
+    SinusoidalMovement,
+};
+use crate::groups::Groups;
+use crate::material::MaterialComponent;
+use crate::schedule::{BodiesChanged, CollisionEvents};
+use crate::sprite::SpriteSheetComponent;
+use crate::steering::{AvoidObstacles, Flee, Seek, SteeringTarget, Wander};
+use crate::time_control::Time;
+use crate::Position2D;
+
+/// Most states one machine can have
+pub const MAX_STATES: usize = 64;
+/// Most transitions leaving one state
+pub const MAX_TRANSITIONS: usize = 16;
+
+/// Movement strategy a state switches to; origin is where the entity is
+/// when the state is entered
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum MovementDesc {
+    /// Remove the strategy; the body is left to physics
+    None,
+    Linear { velocity: [f32; 2] },
+    Sinusoidal { amplitude: f32, frequency: f32, direction_x: f32 },
+    Circular { radius: f32, angular_speed: f32 },
+    HorizontalRandom { speed: f32, move_duration: f32, pause_duration: f32, #[serde(default)] seed: u32 },
+    /// Chase entity `target`, or the nearest body in `target_groups` when
+    /// `target` is 0 (see steering.rs)
+    Seek {
+        #[serde(default)]
+        target: u64,
+        #[serde(default)]
+        target_groups: u32,
+        max_speed: f32,
+        #[serde(default)]
+        max_acceleration: f32,
+        #[serde(default)]
+        arrive_radius: f32,
+        /// Look-ahead of the obstacle feelers; 0 runs into things
+        #[serde(default)]
+        avoid: f32,
+    },
+    Flee {
+        #[serde(default)]
+        target: u64,
+        #[serde(default)]
+        target_groups: u32,
+        max_speed: f32,
+        #[serde(default)]
+        max_acceleration: f32,
+        #[serde(default)]
+        panic_radius: f32,
+        #[serde(default)]
+        avoid: f32,
+    },
+    Wander {
+        speed: f32,
+        turn_rate: f32,
+        #[serde(default)]
+        max_acceleration: f32,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default)]
+        avoid: f32,
+    },
+}
+
+impl MovementDesc {
+    pub fn validate(&self) -> Result<(), String> {
+        let finite = |values: &[f32]| values.iter().all(|v| v.is_finite());
+        let valid = match *self {
+            Self::None => true,
+            Self::Linear { velocity } => finite(&velocity),
+            Self::Sinusoidal { amplitude, frequency, direction_x } => finite(&[amplitude, frequency, direction_x]),
+            Self::Circular { radius, angular_speed } => finite(&[radius, angular_speed]),
+            Self::HorizontalRandom { speed, move_duration, pause_duration, .. } => {
+                finite(&[speed, move_duration, pause_duration])
+                    && move_duration >= 0.0
+                    && pause_duration >= 0.0
+                    && move_duration + pause_duration > 0.0
+            }
+            Self::Seek { target, target_groups, max_speed, max_acceleration, arrive_radius: radius, avoid }
+            | Self::Flee { target, target_groups, max_speed, max_acceleration, panic_radius: radius, avoid } => {
+                let values = [max_speed, max_acceleration, radius, avoid];
+                (target != 0 || target_groups != 0) && finite(&values) && values.iter().all(|&v| v >= 0.0)
+            }
+            Self::Wander { speed, turn_rate, max_acceleration, avoid, .. } => {
+                finite(&[speed, turn_rate]) && [max_acceleration, avoid].iter().all(|&v| v.is_finite() && v >= 0.0)
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("invalid {:?} movement", self))
+        }
+    }
+
+    /// The strategy to install, or None to remove the current one
+    pub fn strategy(&self) -> Option<Box<dyn MovementStrategy>> {
+        Some(match *self {
+            Self::None => return None,
+            Self::Linear { velocity: [velocity_x, velocity_y] } => Box::new(LinearMovement { velocity_x, velocity_y }),
+            Self::Sinusoidal { amplitude, frequency, direction_x } => {
+                Box::new(SinusoidalMovement { amplitude, frequency, direction_x })
+            }
+            Self::Circular { radius, angular_speed } => Box::new(CircularMovement { radius, angular_speed }),
+            Self::HorizontalRandom { speed, move_duration, pause_duration, seed } => {
+                Box::new(HorizontalRandomMovement { speed, move_duration, pause_duration, seed })
+            }
+            Self::Seek { target, target_groups, max_speed, max_acceleration, arrive_radius, avoid } => {
+                let target = steering_target(target, target_groups);
+                avoiding(Box::new(Seek { target, max_speed, max_acceleration, arrive_radius }), avoid)
+            }
+            Self::Flee { target, target_groups, max_speed, max_acceleration, panic_radius, avoid } => {
+                let target = steering_target(target, target_groups);
+                avoiding(Box::new(Flee { target, max_speed, max_acceleration, panic_radius }), avoid)
+            }
+            Self::Wander { speed, turn_rate, max_acceleration, seed, avoid } => {
+                avoiding(Box::new(Wander { speed, max_acceleration, turn_rate, seed }), avoid)
+            }
+        })
+    }
+}
+
+fn steering_target(target: u64, target_groups: u32) -> SteeringTarget {
+    match target {
+        0 => SteeringTarget::Nearest(target_groups),
+        id => SteeringTarget::Entity(id),
+    }
+}
+
+/// `behavior` with obstacle feelers reaching `look_ahead`, if positive
+fn avoiding(behavior: Box<dyn MovementStrategy>, look_ahead: f32) -> Box<dyn MovementStrategy> {
+    if look_ahead > 0.0 {
+        Box::new(AvoidObstacles { behavior, look_ahead })
+    } else {
+        behavior
+    }
+}
+
+/// Run of sprite sheet cells played while in a state
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnimationClip {
+    pub first_frame: u32,
+    pub frame_count: u32,
+    /// Seconds per frame
+    pub frame_duration: f32,
+    pub looping: bool,
+}
+
+impl Default for AnimationClip {
+    fn default() -> Self {
+        Self { first_frame: 0, frame_count: 1, frame_duration: 0.1, looping: true }
+    }
+}
+
+impl AnimationClip {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.frame_count == 0 || !self.frame_duration.is_finite() || self.frame_duration <= 0.0 {
+            return Err("clips need frames and a positive frame duration".into());
+        }
+        Ok(())
+    }
+}
+
+/// Play `clip` on the entity's sprite sheet from its first frame
+pub fn play_clip(entity_mut: &mut EntityWorldMut, clip: AnimationClip) {
+    if let Some(mut sheet) = entity_mut.get_mut::<SpriteSheetComponent>() {
+        sheet.first_frame = clip.first_frame;
+        sheet.frame_count = clip.frame_count;
+        sheet.frame_duration = clip.frame_duration;
+        sheet.looping = clip.looping;
+    }
+    if let Some(mut animator) = entity_mut.get_mut::<AnimatorComponent>() {
+        animator.elapsed_time = 0.0;
+        animator.current_frame = clip.first_frame;
+        animator.is_playing = true;
+    }
+}
+
+/// What moves a machine out of its state
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Condition {
+    /// Seconds spent in the state
+    After(f32),
+    /// A collision started with a body sharing a bit with this mask
+    Collision(u32),
+    /// The host sent this event
+    Event(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransitionDesc {
+    /// Name of the next state
+    pub to: String,
+    pub when: Condition,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StateDesc {
+    pub name: String,
+    pub movement: Option<MovementDesc>,
+    pub clip: Option<AnimationClip>,
+    pub material_id: Option<u32>,
+    pub transitions: Vec<TransitionDesc>,
+}
+
+/// A whole machine as it comes from JSON
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StateMachineDesc {
+    pub states: Vec<StateDesc>,
+    /// Name of the state to start in; the first one when omitted
+    pub initial: Option<String>,
+}
+
+impl StateMachineDesc {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.states.is_empty() || self.states.len() > MAX_STATES {
+            return Err(format!("a state machine needs 1 to {} states", MAX_STATES));
+        }
+        let mut names = HashMap::new();
+        for (index, state) in self.states.iter().enumerate() {
+            if names.insert(state.name.as_str(), index).is_some() {
+                return Err(format!("duplicate state '{}'", state.name));
+            }
+        }
+        if let Some(initial) = &self.initial {
+            if !names.contains_key(initial.as_str()) {
+                return Err(format!("unknown initial state '{}'", initial));
+            }
+        }
+        for state in &self.states {
+            if state.transitions.len() > MAX_TRANSITIONS {
+                return Err(format!("state '{}' has more than {} transitions", state.name, MAX_TRANSITIONS));
+            }
+            if let Some(movement) = &state.movement {
+                movement.validate().map_err(|e| format!("state '{}': {}", state.name, e))?;
+            }
+            if let Some(clip) = &state.clip {
+                clip.validate().map_err(|e| format!("state '{}': {}", state.name, e))?;
+            }
+            for transition in &state.transitions {
+                if !names.contains_key(transition.to.as_str()) {
+                    return Err(format!("state '{}' moves to unknown state '{}'", state.name, transition.to));
+                }
+                if let Condition::After(seconds) = transition.when {
+                    if !seconds.is_finite() || seconds < 0.0 {
+                        return Err(format!("state '{}': timeouts must be finite and >= 0", state.name));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Drives an entity through the states of a `StateMachineDesc`
+#[derive(Component, Debug, Clone)]
+pub struct StateMachine {
+    desc: StateMachineDesc,
+    /// Target state index of every transition, by state and transition index
+    targets: Vec<Vec<usize>>,
+    current: usize,
+    time_in_state: f32,
+    /// Whether the hooks of the current state still have to run
+    entered: bool,
+    events: Vec<String>,
+}
+
+impl StateMachine {
+    /// Starts in the initial state, whose hooks run on the next step
+    pub fn new(desc: StateMachineDesc) -> Result<Self, String> {
+        desc.validate()?;
+        let index_of = |name: &str| desc.states.iter().position(|s| s.name == name).unwrap_or(0);
+        let targets = desc.states.iter().map(|s| s.transitions.iter().map(|t| index_of(&t.to)).collect()).collect();
+        let current = desc.initial.as_deref().map_or(0, index_of);
+        Ok(Self { desc, targets, current, time_in_state: 0.0, entered: true, events: Vec::new() })
+    }
+
+    pub fn states(&self) -> &[StateDesc] {
+        &self.desc.states
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn current_name(&self) -> &str {
+        &self.desc.states[self.current].name
+    }
+
+    pub fn time_in_state(&self) -> f32 {
+        self.time_in_state
+    }
+
+    /// Queue a named event for the next update
+    pub fn send(&mut self, event: &str) {
+        // A burst of events between two steps only needs each name once
+        if !self.events.iter().any(|e| e == event) {
+            self.events.push(event.to_string());
+        }
+    }
+
+    /// Spend `dt` in the current state and take the first transition whose
+    /// condition holds. `collisions` holds the group bits of every body a
+    /// collision started with this step (0 for walls). Returns the state
+    /// entered, if any.
+    pub fn update(&mut self, dt: f32, collisions: &[u32]) -> Option<usize> {
+        if dt.is_finite() && dt > 0.0 {
+            self.time_in_state += dt;
+        }
+        let state = &self.desc.states[self.current];
+        let taken = state.transitions.iter().position(|transition| match &transition.when {
+            Condition::After(seconds) => self.time_in_state >= *seconds,
+            Condition::Collision(mask) => collisions.iter().any(|&bits| Groups(bits).matches(*mask)),
+            Condition::Event(name) => self.events.iter().any(|e| e == name),
+        });
+        self.events.clear();
+        let next = self.targets[self.current][taken?];
+        self.current = next;
+        self.time_in_state = 0.0;
+        self.entered = true;
+        Some(next)
+    }
+
+    /// The state whose hooks have not run yet, clearing the flag
+    pub fn take_entered(&mut self) -> Option<&StateDesc> {
+        std::mem::take(&mut self.entered).then(|| &self.desc.states[self.current])
+    }
+}
+
+/// Advance every machine and run the hooks of the states they entered
+pub(crate) fn state_machine_system(world: &mut World) {
+    let mut machines = world.query::<&StateMachine>();
+    if machines.iter(world).next().is_none() {
+        return;
+    }
+    let dt = world.resource::<Time>().delta;
+    let elapsed = world.resource::<Time>().elapsed;
+
+    // Group bits of whoever each entity started touching
+    let mut touched: HashMap<u64, Vec<u32>> = HashMap::new();
+    let started = world.resource::<CollisionEvents>().started.clone();
+    let groups_of = |world: &World, id: u64| {
+        Entity::try_from_bits(id)
+            .ok()
+            .filter(|&e| world.entities().contains(e))
+            .and_then(|e| world.get::<Groups>(e))
+            .map_or(0, |g| g.0)
+    };
+    for (a, b) in started {
+        touched.entry(a).or_default().push(groups_of(world, b));
+        touched.entry(b).or_default().push(groups_of(world, a));
+    }
+
+    let mut hooks = Vec::new();
+    for (entity, mut machine) in world.query::<(Entity, &mut StateMachine)>().iter_mut(world) {
+        let collisions = touched.get(&entity.to_bits()).map_or(&[][..], Vec::as_slice);
+        machine.update(dt, collisions);
+        if let Some(state) = machine.take_entered() {
+            hooks.push((entity, state.movement, state.clip, state.material_id));
+        }
+    }
+
+    for (entity, movement, clip, material_id) in hooks {
+        let origin = world.get::<Position2D>(entity).map_or((0.0, 0.0), |p| (p.x, p.y));
+        let mut entity_mut = world.entity_mut(entity);
+        match movement.map(|m| m.strategy()) {
+            Some(Some(strategy)) => {
+                entity_mut.insert(MovementComponent { strategy, origin, started_at: elapsed });
+            }
+            Some(None) => {
+                entity_mut.remove::<MovementComponent>();
+            }
+            None => {}
+        }
+        if let Some(clip) = clip {
+            play_clip(&mut entity_mut, clip);
+        }
+        if let Some(material_id) = material_id {
+            if entity_mut.get::<MaterialComponent>().map(|m| m.material_id) != Some(material_id) {
+                entity_mut.insert(MaterialComponent::new(material_id));
+                // Instances are grouped by material
+                world.resource_mut::<BodiesChanged>().0 = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! Steering behaviors for NPC bodies
+//!
+//! Seek, flee and wander are `MovementStrategy`s without a fixed path: each
+//! step they look at the scene through a `SteeringContext` and return the
+//! velocity to drive the body at. The velocity turns toward the one the
+//! behavior wants by at most `max_acceleration`·dt per step (0 turns at
+//! once), which gives steering its smooth, slightly lazy pursuit.
+//! `AvoidObstacles` wraps another behavior and casts three feelers along
+//! its velocity, bending away from whatever they hit.
+//!
+//! Targets are an entity id or the nearest body in a set of groups (see
+//! `groups`), so a scene can tag the player and have every NPC chase it.
+//! Queries go through `SteeringQueries`, which the movement system backs
+//! with Rapier's query pipeline; tests can supply their own.
+
+use crate::rapier::prelude::*;
+
+use crate::game_entity::MovementStrategy;
+use crate::groups::Groups;
+use crate::precision::{planar, to_f32, to_real};
+
+/// Angle between the middle feeler and each side feeler
+const FEELER_ANGLE: f32 = std::f32::consts::FRAC_PI_6;
+/// Side feelers reach this fraction of the look-ahead distance
+const SIDE_FEELER_LENGTH: f32 = 0.6;
+
+/// Where another body is and how it moves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyView {
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+}
+
+/// First collider a ray hit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub distance: f32,
+    /// Surface normal at the hit point
+    pub normal: (f32, f32),
+}
+
+/// Scene queries a steering behavior can make. The steering body itself is
+/// never returned or hit.
+pub trait SteeringQueries {
+    fn body(&self, entity_id: u64) -> Option<BodyView>;
+
+    /// Closest body sharing a bit with `mask`
+    fn nearest(&self, mask: u32, from: (f32, f32)) -> Option<BodyView>;
+
+    /// Cast along the unit vector `direction` for at most `max_distance`;
+    /// sensors are ignored
+    fn cast_ray(&self, origin: (f32, f32), direction: (f32, f32), max_distance: f32) -> Option<RayHit>;
+}
+
+/// The steering body and the scene around it
+pub struct SteeringContext<'a> {
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+    /// Seconds since the strategy took over
+    pub elapsed: f32,
+    /// Length of the step being run
+    pub dt: f32,
+    pub queries: &'a dyn SteeringQueries,
+}
+
+/// Who seek and flee react to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SteeringTarget {
+    Entity(u64),
+    /// Nearest body sharing a bit with this mask
+    Nearest(u32),
+}
+
+impl SteeringTarget {
+    fn find(self, context: &SteeringContext) -> Option<BodyView> {
+        match self {
+            Self::Entity(id) => context.queries.body(id),
+            Self::Nearest(mask) => context.queries.nearest(mask, context.position),
+        }
+    }
+}
+
+fn length((x, y): (f32, f32)) -> f32 {
+    (x * x + y * y).sqrt()
+}
+
+fn scaled((x, y): (f32, f32), factor: f32) -> (f32, f32) {
+    (x * factor, y * factor)
+}
+
+/// `vector` shortened to at most `max`
+fn clamped(vector: (f32, f32), max: f32) -> (f32, f32) {
+    let len = length(vector);
+    if len > max && len > 0.0 {
+        scaled(vector, max / len)
+    } else {
+        vector
+    }
+}
+
+/// Turn `current` toward `desired` by at most `max_acceleration * dt`
+pub fn approach(current: (f32, f32), desired: (f32, f32), max_acceleration: f32, dt: f32) -> (f32, f32) {
+    if max_acceleration <= 0.0 {
+        return desired;
+    }
+    let change = clamped((desired.0 - current.0, desired.1 - current.1), max_acceleration * dt);
+    (current.0 + change.0, current.1 + change.1)
+}
+
+/// Head for the target, slowing down inside `arrive_radius`
+pub struct Seek {
+    pub target: SteeringTarget,
+    pub max_speed: f32,
+    pub max_acceleration: f32,
+    /// 0 runs through the target at full speed
+    pub arrive_radius: f32,
+}
+
+impl MovementStrategy for Seek {
+    /// Without a scene to look at the body stays put
+    fn calculate_position(&self, origin: (f32, f32), _elapsed_time: f32) -> (f32, f32) {
+        origin
+    }
+
+    fn name(&self) -> &'static str {
+        "Seek"
+    }
+
+    fn steer(&self, context: &SteeringContext) -> Option<(f32, f32)> {
+        let desired = match self.target.find(context) {
+            Some(target) => {
+                let offset = (target.position.0 - context.position.0, target.position.1 - context.position.1);
+                let distance = length(offset);
+                let speed = match self.arrive_radius {
+                    radius if radius > 0.0 && distance < radius => self.max_speed * distance / radius,
+                    _ => self.max_speed,
+                };
+                if distance > 0.0 {
+                    scaled(offset, speed / distance)
+                } else {
+                    (0.0, 0.0)
+                }
+            }
+            // Nobody to chase: come to a stop
+            None => (0.0, 0.0),
+        };
+        Some(approach(context.velocity, desired, self.max_acceleration, context.dt))
+    }
+}
+
+/// Run from the target while it is within `panic_radius`
+pub struct Flee {
+    pub target: SteeringTarget,
+    pub max_speed: f32,
+    pub max_acceleration: f32,
+    /// 0 flees at any distance
+    pub panic_radius: f32,
+}
+
+impl MovementStrategy for Flee {
+    /// Without a scene to look at the body stays put
+    fn calculate_position(&self, origin: (f32, f32), _elapsed_time: f32) -> (f32, f32) {
+        origin
+    }
+
+    fn name(&self) -> &'static str {
+        "Flee"
+    }
+
+    fn steer(&self, context: &SteeringContext) -> Option<(f32, f32)> {
+        let threat = self.target.find(context).map(|target| {
+            (context.position.0 - target.position.0, context.position.1 - target.position.1)
+        });
+        let desired = match threat {
+            Some(away) if self.panic_radius <= 0.0 || length(away) < self.panic_radius => {
+                let distance = length(away);
+                if distance > 0.0 {
+                    scaled(away, self.max_speed / distance)
+                } else {
+                    (self.max_speed, 0.0)
+                }
+            }
+            _ => (0.0, 0.0),
+        };
+        Some(approach(context.velocity, desired, self.max_acceleration, context.dt))
+    }
+}
+
+/// Roam at `speed` with a heading that drifts smoothly over time
+pub struct Wander {
+    pub speed: f32,
+    pub max_acceleration: f32,
+    /// Fastest the heading turns, radians per second
+    pub turn_rate: f32,
+    /// Picks the starting heading and the drift pattern
+    pub seed: u32,
+}
+
+impl Wander {
+    /// Heading at `elapsed` seconds: two slow sines whose rates of change add
+    /// up to at most `turn_rate`
+    pub fn heading(&self, elapsed: f32) -> f32 {
+        use std::f32::consts::TAU;
+        let hash = |salt: u32| self.seed.wrapping_add(salt).wrapping_mul(2654435761) as f32 / u32::MAX as f32 * TAU;
+        let (start, phase_a, phase_b) = (hash(0), hash(1), hash(2));
+        start
+            + self.turn_rate * (0.6 / 0.5 * (0.5 * elapsed + phase_a).sin() + 0.4 / 1.3 * (1.3 * elapsed + phase_b).sin())
+    }
+}
+
+impl MovementStrategy for Wander {
+    /// Without a scene to look at the body stays put
+    fn calculate_position(&self, origin: (f32, f32), _elapsed_time: f32) -> (f32, f32) {
+        origin
+    }
+
+    fn name(&self) -> &'static str {
+        "Wander"
+    }
+
+    fn steer(&self, context: &SteeringContext) -> Option<(f32, f32)> {
+        let heading = self.heading(context.elapsed);
+        let desired = (heading.cos() * self.speed, heading.sin() * self.speed);
+        Some(approach(context.velocity, desired, self.max_acceleration, context.dt))
+    }
+}
+
+/// Another behavior that bends away from colliders ahead of it
+pub struct AvoidObstacles {
+    pub behavior: Box<dyn MovementStrategy>,
+    /// How far ahead the middle feeler reaches
+    pub look_ahead: f32,
+}
+
+impl MovementStrategy for AvoidObstacles {
+    fn calculate_position(&self, origin: (f32, f32), elapsed_time: f32) -> (f32, f32) {
+        self.behavior.calculate_position(origin, elapsed_time)
+    }
+
+    fn name(&self) -> &'static str {
+        "AvoidObstacles"
+    }
+
+    fn steer(&self, context: &SteeringContext) -> Option<(f32, f32)> {
+        let velocity = self.behavior.steer(context)?;
+        let speed = length(velocity);
+        if speed <= f32::EPSILON || self.look_ahead <= 0.0 {
+            return Some(velocity);
+        }
+        let heading = velocity.1.atan2(velocity.0);
+        let feelers = [
+            (heading, self.look_ahead),
+            (heading + FEELER_ANGLE, self.look_ahead * SIDE_FEELER_LENGTH),
+            (heading - FEELER_ANGLE, self.look_ahead * SIDE_FEELER_LENGTH),
+        ];
+        // Closer hits push harder
+        let mut push = (0.0, 0.0);
+        for (angle, reach) in feelers {
+            if let Some(hit) = context.queries.cast_ray(context.position, (angle.cos(), angle.sin()), reach) {
+                let weight = 1.0 - hit.distance / reach;
+                push.0 += hit.normal.0 * weight;
+                push.1 += hit.normal.1 * weight;
+            }
+        }
+        if push == (0.0, 0.0) {
+            return Some(velocity);
+        }
+        let bent = (velocity.0 + push.0 * speed * 2.0, velocity.1 + push.1 * speed * 2.0);
+        let len = length(bent);
+        Some(if len > 0.0 { scaled(bent, speed / len) } else { (0.0, 0.0) })
+    }
+}
+
+/// `SteeringQueries` over Rapier's sets, as the movement system sees them
+pub(crate) struct RapierQueries<'a> {
+    pub(crate) bodies: &'a RigidBodySet,
+    pub(crate) colliders: &'a ColliderSet,
+    /// Updated for this step
+    pub(crate) pipeline: &'a QueryPipeline,
+    /// (entity id, body, group bits) of every body
+    pub(crate) entities: &'a [(u64, RigidBodyHandle, Groups)],
+    /// The steering body
+    pub(crate) exclude: RigidBodyHandle,
+}
+
+impl RapierQueries<'_> {
+    fn view(&self, handle: RigidBodyHandle) -> Option<BodyView> {
+        let rb = self.bodies.get(handle)?;
+        Some(BodyView {
+            position: (to_f32(rb.translation().x), to_f32(rb.translation().y)),
+            velocity: (to_f32(rb.linvel().x), to_f32(rb.linvel().y)),
+        })
+    }
+}
+
+impl SteeringQueries for RapierQueries<'_> {
+    fn body(&self, entity_id: u64) -> Option<BodyView> {
+        let &(_, handle, _) = self.entities.iter().find(|(id, handle, _)| *id == entity_id && *handle != self.exclude)?;
+        self.view(handle)
+    }
+
+    fn nearest(&self, mask: u32, from: (f32, f32)) -> Option<BodyView> {
+        self.entities
+            .iter()
+            .filter(|(_, handle, groups)| *handle != self.exclude && groups.matches(mask))
+            .filter_map(|&(_, handle, _)| self.view(handle))
+            .min_by(|a, b| {
+                let distance = |view: &BodyView| length((view.position.0 - from.0, view.position.1 - from.1));
+                distance(a).total_cmp(&distance(b))
+            })
+    }
+
+    fn cast_ray(&self, origin: (f32, f32), direction: (f32, f32), max_distance: f32) -> Option<RayHit> {
+        let ray = Ray::new(planar([origin.0, origin.1]).into(), planar([direction.0, direction.1]));
+        let filter = QueryFilter::default().exclude_rigid_body(self.exclude).exclude_sensors();
+        let (_, hit) =
+            self.pipeline.cast_ray_and_get_normal(self.bodies, self.colliders, &ray, to_real(max_distance), true, filter)?;
+        Some(RayHit { distance: to_f32(hit.time_of_impact), normal: (to_f32(hit.normal.x), to_f32(hit.normal.y)) })
+    }
+}
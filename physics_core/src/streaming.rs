@@ -0,0 +1,155 @@
+//! Chunk-based body streaming for large worlds
+//!
+//! The world is divided into square chunks. Chunks within `load_radius` of the
+//! focus chunk are activated; active chunks are only deactivated once they are
+//! further than `unload_radius`, so a focus hovering on a chunk border does not
+//! toggle bodies every frame. The physics side disables bodies whose position
+//! falls in an inactive chunk.
+
+use std::collections::HashSet;
+
+/// Largest load and unload radius, in chunks; larger ones are clamped so an
+/// update never walks more than (2 * 64 + 1)^2 chunks
+pub const MAX_RADIUS: u32 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamingConfig {
+    /// Edge length of a chunk in world units
+    pub chunk_size: f32,
+    /// Chebyshev distance (in chunks) within which chunks are activated
+    pub load_radius: u32,
+    /// Chebyshev distance beyond which active chunks are deactivated
+    pub unload_radius: u32,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 2.0,
+            load_radius: 1,
+            unload_radius: 2,
+        }
+    }
+}
+
+impl StreamingConfig {
+    /// Clamp to usable values: positive chunk size, radii up to `MAX_RADIUS`,
+    /// unload radius >= load radius
+    pub fn sanitized(self) -> Self {
+        let chunk_size = if self.chunk_size.is_finite() && self.chunk_size > 0.0 {
+            self.chunk_size
+        } else {
+            Self::default().chunk_size
+        };
+        Self {
+            chunk_size,
+            load_radius: self.load_radius.min(MAX_RADIUS),
+            unload_radius: self.unload_radius.clamp(self.load_radius.min(MAX_RADIUS), MAX_RADIUS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkCoord {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl ChunkCoord {
+    pub fn of(x: f32, y: f32, chunk_size: f32) -> Self {
+        Self {
+            x: (x / chunk_size).floor() as i32,
+            y: (y / chunk_size).floor() as i32,
+        }
+    }
+
+    /// Distance in chunks, counting diagonal neighbours as 1
+    pub fn distance(self, other: ChunkCoord) -> u32 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+}
+
+/// What streaming is centered on
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StreamFocus {
+    /// Wherever the main camera looks
+    #[default]
+    Camera,
+    /// A fixed world position
+    Point { x: f32, y: f32 },
+    /// Follow the body of this entity id
+    Entity(u64),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChunkChanges {
+    pub activated: Vec<ChunkCoord>,
+    pub deactivated: Vec<ChunkCoord>,
+}
+
+impl ChunkChanges {
+    pub fn is_empty(&self) -> bool {
+        self.activated.is_empty() && self.deactivated.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChunkStreamer {
+    config: StreamingConfig,
+    active: HashSet<ChunkCoord>,
+}
+
+impl ChunkStreamer {
+    pub fn new(config: StreamingConfig) -> Self {
+        Self {
+            config: config.sanitized(),
+            active: HashSet::new(),
+        }
+    }
+
+    pub fn config(&self) -> StreamingConfig {
+        self.config
+    }
+
+    pub fn active_chunk_count(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn is_active(&self, chunk: ChunkCoord) -> bool {
+        self.active.contains(&chunk)
+    }
+
+    pub fn is_position_active(&self, x: f32, y: f32) -> bool {
+        self.is_active(ChunkCoord::of(x, y, self.config.chunk_size))
+    }
+
+    /// Move the focus and return the chunks that changed state, sorted
+    pub fn update(&mut self, focus_x: f32, focus_y: f32) -> ChunkChanges {
+        let center = ChunkCoord::of(focus_x, focus_y, self.config.chunk_size);
+        let mut changes = ChunkChanges::default();
+
+        let unload_radius = self.config.unload_radius;
+        self.active.retain(|&chunk| {
+            let keep = chunk.distance(center) <= unload_radius;
+            if !keep {
+                changes.deactivated.push(chunk);
+            }
+            keep
+        });
+
+        // Foci far out saturate to the edge of the chunk grid instead of overflowing
+        let r = self.config.load_radius.min(MAX_RADIUS) as i32;
+        for y in center.y.saturating_sub(r)..=center.y.saturating_add(r) {
+            for x in center.x.saturating_sub(r)..=center.x.saturating_add(r) {
+                let chunk = ChunkCoord { x, y };
+                if self.active.insert(chunk) {
+                    changes.activated.push(chunk);
+                }
+            }
+        }
+
+        changes.activated.sort();
+        changes.deactivated.sort();
+        changes
+    }
+}
@@ -0,0 +1,138 @@
+//! Stress-test scene generator
+//!
+//! Produces box positions for a pyramid, stacked columns or a rain of boxes
+//! that fit inside the demo's walls. Box size shrinks with the body count so
+//! large scenes still start without overlaps.
+
+/// Area inside the demo walls that generated boxes must fit in
+pub const AREA_MIN: [f32; 2] = [-0.95, -1.05];
+pub const AREA_MAX: [f32; 2] = [0.95, 1.0];
+
+/// Largest cell (box plus gap) used for small scenes
+const MAX_CELL: f32 = 0.1;
+
+/// Fraction of a cell covered by a box; the rest is the starting gap
+const FILL: f32 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StressLayout {
+    /// One pyramid resting on the floor
+    Pyramid,
+    /// Separate columns of boxes
+    Stack,
+    /// Loosely jittered boxes filling the area from the top
+    Rain,
+}
+
+impl StressLayout {
+    /// FFI code: 0 = pyramid, 1 = stack, 2 = rain
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(StressLayout::Pyramid),
+            1 => Some(StressLayout::Stack),
+            2 => Some(StressLayout::Rain),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StressScene {
+    /// Centers of the boxes
+    pub positions: Vec<[f32; 2]>,
+    /// Half edge length shared by every box
+    pub half_extent: f32,
+}
+
+pub fn generate(n: u32, layout: StressLayout) -> StressScene {
+    if n == 0 {
+        return StressScene {
+            positions: Vec::new(),
+            half_extent: MAX_CELL * FILL * 0.5,
+        };
+    }
+
+    let (positions, cell) = match layout {
+        StressLayout::Pyramid => pyramid(n),
+        StressLayout::Stack => stack(n),
+        StressLayout::Rain => rain(n),
+    };
+    StressScene {
+        positions,
+        half_extent: cell * FILL * 0.5,
+    }
+}
+
+fn area_size() -> (f32, f32) {
+    (AREA_MAX[0] - AREA_MIN[0], AREA_MAX[1] - AREA_MIN[1])
+}
+
+/// Cell size that fits `cols` x `rows` cells in the area
+fn fit_cell(cols: u32, rows: u32) -> f32 {
+    let (width, height) = area_size();
+    (width / cols.max(1) as f32).min(height / rows.max(1) as f32).min(MAX_CELL)
+}
+
+fn pyramid(n: u32) -> (Vec<[f32; 2]>, f32) {
+    // Smallest base whose triangle number holds n boxes
+    let base = ((((8 * n as u64 + 1) as f64).sqrt() - 1.0) / 2.0).ceil() as u32;
+    let cell = fit_cell(base, base);
+    let center_x = (AREA_MIN[0] + AREA_MAX[0]) * 0.5;
+
+    let mut positions = Vec::with_capacity(n as usize);
+    'rows: for row in 0..base {
+        let count = base - row;
+        let start_x = center_x - (count - 1) as f32 * cell * 0.5;
+        let y = AREA_MIN[1] + cell * (row as f32 + 0.5);
+        for i in 0..count {
+            if positions.len() == n as usize {
+                break 'rows;
+            }
+            positions.push([start_x + i as f32 * cell, y]);
+        }
+    }
+    (positions, cell)
+}
+
+fn stack(n: u32) -> (Vec<[f32; 2]>, f32) {
+    let columns = (n as f32).sqrt().ceil() as u32;
+    let rows = n.div_ceil(columns);
+    // Leave an empty cell between columns so they do not lean on each other
+    let cell = fit_cell(2 * columns - 1, rows);
+    let center_x = (AREA_MIN[0] + AREA_MAX[0]) * 0.5;
+    let start_x = center_x - (columns - 1) as f32 * cell;
+
+    let positions = (0..n)
+        .map(|i| {
+            let (column, row) = (i % columns, i / columns);
+            [start_x + column as f32 * 2.0 * cell, AREA_MIN[1] + cell * (row as f32 + 0.5)]
+        })
+        .collect();
+    (positions, cell)
+}
+
+fn rain(n: u32) -> (Vec<[f32; 2]>, f32) {
+    let (width, height) = area_size();
+    let columns = ((n as f32 * width / height).sqrt().ceil() as u32).max(1);
+    let rows = n.div_ceil(columns);
+    let cell = fit_cell(columns, rows);
+    let start_x = AREA_MIN[0] + cell * 0.5;
+
+    // Deterministic xorshift jitter so runs are comparable
+    let mut seed: u32 = 0x9e37_79b9;
+    let mut jitter = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        (seed as f32 / u32::MAX as f32 - 0.5) * cell * (1.0 - FILL)
+    };
+
+    let positions = (0..n)
+        .map(|i| {
+            let (column, row) = (i % columns, i / columns);
+            [start_x + column as f32 * cell + jitter(), AREA_MAX[1] - cell * (row as f32 + 0.5)]
+        })
+        .collect();
+    (positions, cell)
+}
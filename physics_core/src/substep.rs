@@ -0,0 +1,64 @@
+//! Physics substepping
+//!
+//! Each step can be split into N Rapier steps of `dt / N`. CCD already stops
+//! most tunneling, but small fast boxes can still skip through the thin floor
+//! when they travel more than their own size in one step. In auto mode the
+//! substep count scales with the fastest awake body: at `velocity_threshold`
+//! and below the base count is used, and it grows in proportion to speed up
+//! to `max_substeps`.
+
+use serde::{Deserialize, Serialize};
+
+/// Hard cap on substeps per step
+pub const MAX_SUBSTEPS: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SubstepConfig {
+    /// Substeps every step takes, at least 1
+    pub substeps: u32,
+    /// Add substeps when bodies move faster than `velocity_threshold`
+    pub auto: bool,
+    /// Speed in m/s; 3 m/s is one 0.05 box half extent per 60 Hz step
+    pub velocity_threshold: f32,
+    /// Upper bound for auto mode
+    pub max_substeps: u32,
+}
+
+impl Default for SubstepConfig {
+    fn default() -> Self {
+        Self {
+            substeps: 1,
+            auto: true,
+            velocity_threshold: 3.0,
+            max_substeps: 8,
+        }
+    }
+}
+
+impl SubstepConfig {
+    /// Clamp counts into 1..=MAX_SUBSTEPS and keep the threshold positive
+    pub fn sanitized(self) -> Self {
+        let substeps = self.substeps.clamp(1, MAX_SUBSTEPS);
+        Self {
+            substeps,
+            auto: self.auto,
+            velocity_threshold: if self.velocity_threshold.is_finite() && self.velocity_threshold > 0.0 {
+                self.velocity_threshold
+            } else {
+                Self::default().velocity_threshold
+            },
+            max_substeps: self.max_substeps.clamp(substeps, MAX_SUBSTEPS),
+        }
+    }
+
+    /// Substeps for a step whose fastest body moves at `max_speed` m/s
+    pub fn substeps_for(&self, max_speed: f32) -> u32 {
+        let base = self.substeps.clamp(1, MAX_SUBSTEPS);
+        if !self.auto || !max_speed.is_finite() || max_speed <= self.velocity_threshold || self.velocity_threshold <= 0.0 {
+            return base;
+        }
+        let scaled = (base as f32 * max_speed / self.velocity_threshold).ceil() as u32;
+        scaled.clamp(base, self.max_substeps.clamp(base, MAX_SUBSTEPS))
+    }
+}
@@ -0,0 +1,166 @@
+//! Rolling terrain
+//!
+//! A `Terrain` is a heightfield level shape that is also drawn. Ground
+//! heights every `spacing` meters, given outright or generated from seeded
+//! noise, become one polyline collider on the level body like a plain
+//! heightfield, and a filled strip from the surface down to `bottom`. The
+//! noise is smooth value noise summed over a few octaves, so the same seed
+//! always gives the same hills.
+
+use serde::Deserialize;
+
+use crate::trail::TrailVertex;
+
+/// Most height samples in one terrain
+pub const MAX_SAMPLES: u32 = 65_536;
+pub const MAX_OCTAVES: u32 = 8;
+
+/// Hills generated instead of listing heights
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TerrainNoise {
+    pub seed: u32,
+    /// Number of height samples
+    pub samples: u32,
+    /// Mean ground height, m
+    pub base: f32,
+    /// Furthest the ground rises above or dips below `base`, m
+    pub amplitude: f32,
+    /// Distance between hills of the first octave, m
+    pub wavelength: f32,
+    /// Each further octave adds bumps at half the wavelength and half the height
+    pub octaves: u32,
+}
+
+impl Default for TerrainNoise {
+    fn default() -> Self {
+        Self { seed: 0, samples: 64, base: -0.6, amplitude: 0.2, wavelength: 0.5, octaves: 3 }
+    }
+}
+
+impl TerrainNoise {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(2..=MAX_SAMPLES).contains(&self.samples) {
+            return Err(format!("terrain noise needs 2 to {} samples", MAX_SAMPLES));
+        }
+        if !(1..=MAX_OCTAVES).contains(&self.octaves) {
+            return Err(format!("terrain noise needs 1 to {} octaves", MAX_OCTAVES));
+        }
+        if ![self.base, self.amplitude, self.wavelength].iter().all(|v| v.is_finite()) || self.wavelength <= 0.0 {
+            return Err("terrain noise wavelength must be > 0".into());
+        }
+        Ok(())
+    }
+
+    /// Ground height `x` meters from the terrain's start
+    pub fn height(&self, x: f32) -> f32 {
+        let (mut sum, mut total, mut weight, mut frequency) = (0.0, 0.0, 1.0, 1.0 / self.wavelength);
+        for octave in 0..self.octaves {
+            sum += weight * value_noise(self.seed.wrapping_add(octave), x * frequency);
+            total += weight;
+            weight *= 0.5;
+            frequency *= 2.0;
+        }
+        self.base + self.amplitude * sum / total
+    }
+}
+
+/// Random value in -1..1 for lattice point `i`
+fn lattice(seed: u32, i: i32) -> f32 {
+    let mut h = (i as u32).wrapping_mul(0x9E37_79B1) ^ seed.wrapping_mul(0x85EB_CA77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297A_2D39);
+    h ^= h >> 15;
+    h as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Lattice values eased into each other, in -1..1
+fn value_noise(seed: u32, x: f32) -> f32 {
+    let cell = x.floor();
+    let t = x - cell;
+    let t = t * t * (3.0 - 2.0 * t);
+    let (a, b) = (lattice(seed, cell as i32), lattice(seed, cell as i32 + 1));
+    a + (b - a) * t
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Terrain {
+    pub origin_x: f32,
+    /// Distance between height samples, m
+    pub spacing: f32,
+    /// Ground heights from `origin_x` on; ignored when `noise` is set
+    pub heights: Vec<f32>,
+    pub noise: Option<TerrainNoise>,
+    /// Lower edge of the drawn fill, m
+    pub bottom: f32,
+    /// Fill color, RGBA
+    pub color: [f32; 4],
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Self {
+            origin_x: -1.0,
+            spacing: 0.05,
+            heights: Vec::new(),
+            noise: None,
+            bottom: -1.0,
+            color: [0.35, 0.55, 0.25, 1.0],
+        }
+    }
+}
+
+impl Terrain {
+    pub fn validate(&self) -> Result<(), String> {
+        if ![self.origin_x, self.spacing, self.bottom].iter().chain(&self.color).all(|v| v.is_finite()) || self.spacing <= 0.0 {
+            return Err("terrain spacing must be > 0".into());
+        }
+        match &self.noise {
+            Some(noise) => noise.validate(),
+            None if self.heights.len() < 2 || self.heights.len() > MAX_SAMPLES as usize => {
+                Err(format!("terrain needs 2 to {} heights or noise", MAX_SAMPLES))
+            }
+            None if !self.heights.iter().all(|h| h.is_finite()) => Err("terrain heights must be finite".into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Heights as listed or generated
+    pub fn heights(&self) -> Vec<f32> {
+        match &self.noise {
+            Some(noise) => (0..noise.samples).map(|i| noise.height(i as f32 * self.spacing)).collect(),
+            None => self.heights.clone(),
+        }
+    }
+
+    /// Surface points, left to right
+    pub fn points(&self) -> Vec<[f32; 2]> {
+        self.heights()
+            .into_iter()
+            .enumerate()
+            .map(|(i, h)| [self.origin_x + i as f32 * self.spacing, h])
+            .collect()
+    }
+
+    /// Two triangles per sample interval, from the surface down to `bottom`
+    pub fn vertices(&self) -> Vec<TrailVertex> {
+        let points = self.points();
+        let vertex = |x: f32, y: f32| TrailVertex { position: [x, y], color: self.color };
+        let mut vertices = Vec::with_capacity(points.len().saturating_sub(1) * 6);
+        for pair in points.windows(2) {
+            let ([x0, y0], [x1, y1]) = (pair[0], pair[1]);
+            vertices.extend([
+                vertex(x0, y0),
+                vertex(x0, self.bottom),
+                vertex(x1, y1),
+                vertex(x1, y1),
+                vertex(x0, self.bottom),
+                vertex(x1, self.bottom),
+            ]);
+        }
+        vertices
+    }
+}
@@ -0,0 +1,205 @@
+//! Headless simulation fixture for the integration tests
+//!
+//! `TestWorld` owns a `PhysicsState` built the way `init_physics_scene`
+//! builds the global one, but never installed: it needs no wgpu device or
+//! window and shares no lock, so tests using it can run in parallel. Frames
+//! and steps go through the same clock and step code as `update_internal`.
+//! Plugin hooks, camera effects and the transform export live in globals and
+//! are left out. Not a stable API.
+
+use crate::precision::{planar, to_f32, to_real, xy};
+use crate::rapier::prelude::*;
+
+use crate::time_control::{self, Time};
+use crate::units::WorldScale;
+use crate::{BodyInfo, PhysicsState};
+
+/// Centre lines of the scene's walls at the default world scale; a body past
+/// one has left the box
+pub const WALL_POSITION: f32 = 1.1;
+
+/// State of one dynamic body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodySample {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    /// Around Z, in rad/s
+    pub angular_velocity: f32,
+}
+
+pub struct TestWorld {
+    physics: PhysicsState,
+    /// Dynamic bodies in the order of the positions they were created at
+    bodies: Vec<RigidBodyHandle>,
+}
+
+impl TestWorld {
+    /// Boxes at `positions` inside the walls, with the default settings
+    /// (gravity -9.81, time scale 1, restitution 0.7)
+    pub fn new(positions: &[[f32; 2]], half_extent: f32) -> Self {
+        Self::with_scale(positions, half_extent, WorldScale::default())
+    }
+
+    /// Like `new` with the walls placed for `scale`; positions and sizes
+    /// are in meters either way
+    pub fn with_scale(positions: &[[f32; 2]], half_extent: f32, scale: WorldScale) -> Self {
+        Self::from_state(crate::new_physics_state(positions, half_extent, scale, None))
+    }
+
+    /// Replace the scene as a reset does, keeping the settings and the world
+    pub fn reset(self, positions: &[[f32; 2]], half_extent: f32) -> Self {
+        let scale = *self.physics.world.resource::<WorldScale>();
+        Self::from_state(crate::new_physics_state(positions, half_extent, scale, Some(self.physics)))
+    }
+
+    fn from_state(physics: PhysicsState) -> Self {
+        let mut bodies: Vec<RigidBodyHandle> =
+            physics.rigid_body_set.iter().filter(|(_, rb)| rb.is_dynamic()).map(|(handle, _)| handle).collect();
+        bodies.sort_by_key(|handle| handle.into_raw_parts());
+        Self { physics, bodies }
+    }
+
+    /// Set the restitution of every dynamic body's colliders
+    pub fn set_restitution(&mut self, restitution: f32) {
+        for &handle in &self.bodies {
+            for &collider in self.physics.rigid_body_set[handle].colliders() {
+                self.physics.collider_set[collider].set_restitution(to_real(restitution));
+            }
+        }
+    }
+
+    pub fn set_gravity(&mut self, gravity: [f32; 2]) {
+        self.physics.gravity = planar(gravity);
+    }
+
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.physics.time_scale = scale;
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.physics.paused = paused;
+    }
+
+    pub fn set_animation_paused(&mut self, paused: bool) {
+        self.physics.animation_paused = paused;
+    }
+
+    /// Allow or forbid sleeping for every dynamic body, like `set_sleep_enabled`
+    pub fn set_sleep_enabled(&mut self, enabled: bool) {
+        crate::set_sleep_enabled(&mut self.physics, enabled);
+    }
+
+    /// Like `wake_body`: false for ids without a body
+    pub fn wake_body(&mut self, id: u64) -> bool {
+        crate::with_body_in(&mut self.physics, id, crate::wake_body)
+    }
+
+    /// Like `sleep_body`: false for ids without a body
+    pub fn sleep_body(&mut self, id: u64) -> bool {
+        crate::with_body_in(&mut self.physics, id, crate::sleep_body)
+    }
+
+    /// Like `body_info`: None for ids without a body
+    pub fn body_info(&mut self, id: u64) -> Option<BodyInfo> {
+        let mut info = None;
+        crate::with_body_in(&mut self.physics, id, |rb| info = Some(crate::body_info(rb)));
+        info
+    }
+
+    /// Like `query_aabb`: ids of the bodies matching `mask` that overlap
+    /// the rectangle between two corners
+    pub fn query_aabb(&mut self, min: [f32; 2], max: [f32; 2], mask: u32) -> Vec<u64> {
+        crate::aabb_query_shape(min[0], min[1], max[0], max[1])
+            .map_or(Vec::new(), |(center, shape)| crate::query_shape(&mut self.physics, center, &shape, mask))
+    }
+
+    /// Like `query_circle`: ids of the bodies matching `mask` that overlap
+    /// the circle
+    pub fn query_circle(&mut self, center: [f32; 2], radius: f32, mask: u32) -> Vec<u64> {
+        crate::circle_query_shape(center[0], center[1], radius)
+            .map_or(Vec::new(), |(center, shape)| crate::query_shape(&mut self.physics, center, &shape, mask))
+    }
+
+    /// Queue single steps as the Step button does; the next frame runs them
+    pub fn queue_steps(&mut self, steps: u32) {
+        self.physics.queued_steps += steps;
+    }
+
+    /// Set the velocity of the `index`th body
+    pub fn set_velocity(&mut self, index: usize, velocity: [f32; 2]) {
+        self.physics.rigid_body_set[self.bodies[index]].set_linvel(planar(velocity), true);
+    }
+
+    /// Run one frame of `real_dt` seconds like `update_internal`. Returns the
+    /// number of steps it ran; a frame with none runs the idle schedule.
+    pub fn frame(&mut self, real_dt: f32) -> u32 {
+        let (dt, queued_steps) = crate::begin_frame(&mut self.physics, real_dt);
+        for _ in 0..queued_steps {
+            self.step(time_control::STEP_DT);
+        }
+        if let Some(dt) = dt {
+            self.step(dt);
+        }
+        let steps = queued_steps + dt.is_some() as u32;
+        if steps == 0 {
+            crate::idle_frame(&mut self.physics);
+        }
+        steps
+    }
+
+    /// Run one step of `dt` scaled seconds, ignoring pause, like `step_once`
+    pub fn step(&mut self, dt: f32) {
+        crate::simulate_step(&mut self.physics, dt);
+    }
+
+    /// Public ids of the dynamic bodies, in creation order
+    pub fn entity_ids(&self) -> Vec<u64> {
+        self.bodies
+            .iter()
+            .map(|&handle| {
+                let collider = self.physics.rigid_body_set[handle].colliders()[0];
+                self.physics.collider_set[collider].user_data as u64
+            })
+            .collect()
+    }
+
+    pub fn is_alive(&self, id: u64) -> bool {
+        crate::entity_alive(&self.physics, id)
+    }
+
+    pub fn resets(&self) -> u32 {
+        self.physics.resets
+    }
+
+    pub fn time(&self) -> Time {
+        *self.physics.world.resource::<Time>()
+    }
+
+    /// Every dynamic body, in creation order
+    pub fn bodies(&self) -> Vec<BodySample> {
+        self.bodies
+            .iter()
+            .map(|&handle| {
+                let rb = &self.physics.rigid_body_set[handle];
+                BodySample {
+                    position: xy(rb.translation()),
+                    velocity: xy(rb.linvel()),
+                    angular_velocity: to_f32(rb.angvel().z),
+                }
+            })
+            .collect()
+    }
+
+    /// Kinetic plus gravitational potential energy of the dynamic bodies,
+    /// taking the origin as zero height
+    pub fn energy(&self) -> f32 {
+        let gravity = self.physics.gravity;
+        self.bodies
+            .iter()
+            .map(|&handle| {
+                let rb = &self.physics.rigid_body_set[handle];
+                to_f32(rb.kinetic_energy() - rb.mass() * gravity.dot(rb.translation()))
+            })
+            .sum()
+    }
+}
@@ -0,0 +1,519 @@
+//! Game text: HUD strings and labels in the world
+//!
+//! Text is drawn from a built-in 8x8 bitmap font covering printable ASCII;
+//! other characters show as `?`. Every label has a caller-chosen id and stays
+//! on screen until it is cleared, so a score only needs a new `draw_text`
+//! call when it changes. Labels live outside the GPU state and survive surface
+//! and device rebuilds.
+//!
+//! A label is anchored one of three ways:
+//! - `Screen`: `position` is the top-left corner in pixels (origin top-left)
+//!   and `size` is the glyph height in pixels
+//! - `World`: the text is centered above `position` in world units and
+//!   `size` is in world units, so it scales with the camera zoom
+//! - `Body`: like `World`, with `position` an offset from the entity's center
+//!
+//! The text pass draws after the scene and post-processing, under egui.
+
+use std::collections::BTreeMap;
+
+use nalgebra as na;
+
+/// Most labels kept at once
+pub const MAX_LABELS: usize = 512;
+/// Longer strings are cut to this many characters
+pub const MAX_CHARS: usize = 256;
+
+/// Glyph cell size in font pixels
+pub const GLYPH_PIXELS: u32 = 8;
+const ATLAS_COLUMNS: u32 = 16;
+const FIRST_CHAR: char = ' ';
+const LAST_CHAR: char = '~';
+pub const ATLAS_WIDTH: u32 = ATLAS_COLUMNS * GLYPH_PIXELS;
+pub const ATLAS_HEIGHT: u32 = 6 * GLYPH_PIXELS;
+
+/// One row per byte, top row first; bit 0 is the leftmost pixel
+#[rustfmt::skip]
+const FONT: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // '#'
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // '$'
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // '%'
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // '&'
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // "'"
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // '('
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // ')'
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // '*'
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ','
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // '/'
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // '0'
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // '1'
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // '2'
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // '3'
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // '4'
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // '5'
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // '6'
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // '7'
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // '8'
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ';'
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // '<'
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // '='
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // '>'
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // '?'
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // '@'
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // 'A'
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // 'B'
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // 'C'
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // 'D'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // 'E'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // 'F'
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // 'G'
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // 'H'
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'I'
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // 'J'
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // 'K'
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // 'L'
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // 'M'
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // 'N'
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // 'O'
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // 'P'
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // 'Q'
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // 'R'
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // 'S'
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'T'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // 'U'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'V'
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // 'W'
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // 'X'
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // 'Y'
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // 'Z'
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // '['
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // '\\'
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ']'
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // '_'
+    [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // 'a'
+    [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // 'b'
+    [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // 'c'
+    [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // 'd'
+    [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // 'e'
+    [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // 'f'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'g'
+    [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // 'h'
+    [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'i'
+    [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // 'j'
+    [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // 'k'
+    [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'l'
+    [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // 'm'
+    [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // 'n'
+    [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // 'o'
+    [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // 'p'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // 'q'
+    [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // 'r'
+    [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // 's'
+    [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // 't'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // 'u'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'v'
+    [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // 'w'
+    [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // 'x'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'y'
+    [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // 'z'
+    [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // '{'
+    [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // '|'
+    [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // '}'
+    [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
+
+/// Single-channel coverage of every glyph, `ATLAS_WIDTH` x `ATLAS_HEIGHT`
+pub fn atlas_pixels() -> Vec<u8> {
+    let mut pixels = vec![0u8; (ATLAS_WIDTH * ATLAS_HEIGHT) as usize];
+    for (index, rows) in FONT.iter().enumerate() {
+        let cell_x = (index as u32 % ATLAS_COLUMNS) * GLYPH_PIXELS;
+        let cell_y = (index as u32 / ATLAS_COLUMNS) * GLYPH_PIXELS;
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..GLYPH_PIXELS {
+                if bits >> column & 1 != 0 {
+                    let x = cell_x + column;
+                    let y = cell_y + row as u32;
+                    pixels[(y * ATLAS_WIDTH + x) as usize] = 255;
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// Atlas rectangle of `c` as [u_min, v_min, u_max, v_max]
+pub fn glyph_uv(c: char) -> [f32; 4] {
+    let c = if (FIRST_CHAR..=LAST_CHAR).contains(&c) { c } else { '?' };
+    let index = c as u32 - FIRST_CHAR as u32;
+    let x = (index % ATLAS_COLUMNS * GLYPH_PIXELS) as f32;
+    let y = (index / ATLAS_COLUMNS * GLYPH_PIXELS) as f32;
+    let size = GLYPH_PIXELS as f32;
+    [
+        x / ATLAS_WIDTH as f32,
+        y / ATLAS_HEIGHT as f32,
+        (x + size) / ATLAS_WIDTH as f32,
+        (y + size) / ATLAS_HEIGHT as f32,
+    ]
+}
+
+/// Glyph placed in a label, in glyph-size units from the label's top-left
+/// corner with y pointing down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    pub uv: [f32; 4],
+}
+
+/// Monospaced layout; `\n` starts a new line and blanks take no quad
+pub fn layout(text: &str) -> Vec<GlyphQuad> {
+    let mut quads = Vec::new();
+    for (row, line) in text.split('\n').enumerate() {
+        for (column, c) in line.chars().enumerate() {
+            if c == ' ' {
+                continue;
+            }
+            let (x, y) = (column as f32, row as f32);
+            quads.push(GlyphQuad { min: [x, y], max: [x + 1.0, y + 1.0], uv: glyph_uv(c) });
+        }
+    }
+    quads
+}
+
+/// Width (longest line) and height (line count) of `text` in glyph-size units
+pub fn measure(text: &str) -> [f32; 2] {
+    let columns = text.split('\n').map(|line| line.chars().count()).max().unwrap_or(0);
+    let rows = text.split('\n').count();
+    [columns as f32, rows as f32]
+}
+
+/// Packed 0xRRGGBBAA color as straight RGBA in 0..1
+pub fn unpack_rgba(color: u32) -> [f32; 4] {
+    color.to_be_bytes().map(|channel| channel as f32 / 255.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAnchor {
+    Screen,
+    World,
+    /// Follows the entity with this id; hidden while it does not exist
+    Body(u64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLabel {
+    pub text: String,
+    pub anchor: TextAnchor,
+    pub position: [f32; 2],
+    pub size: f32,
+    /// Straight RGBA in 0..1
+    pub color: [f32; 4],
+}
+
+impl TextLabel {
+    /// None unless position and size are finite and size is positive. Text
+    /// is cut to `MAX_CHARS` characters and the color clamped to 0..1.
+    pub fn new(text: &str, anchor: TextAnchor, position: [f32; 2], size: f32, color: [f32; 4]) -> Option<Self> {
+        if !position.iter().all(|v| v.is_finite()) || !size.is_finite() || size <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            text: text.chars().take(MAX_CHARS).collect(),
+            anchor,
+            position,
+            size,
+            color: color.map(|c| if c.is_finite() { c.clamp(0.0, 1.0) } else { 1.0 }),
+        })
+    }
+}
+
+/// Labels by id, drawn in id order
+#[derive(Debug, Clone, Default)]
+pub struct TextLayer {
+    labels: BTreeMap<u64, TextLabel>,
+}
+
+impl TextLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a label. Returns false when the layer is full and `id`
+    /// is new.
+    pub fn set(&mut self, id: u64, label: TextLabel) -> bool {
+        if self.labels.len() >= MAX_LABELS && !self.labels.contains_key(&id) {
+            return false;
+        }
+        self.labels.insert(id, label);
+        true
+    }
+
+    pub fn remove(&mut self, id: u64) -> bool {
+        self.labels.remove(&id).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.labels.clear();
+    }
+
+    pub fn get(&self, id: u64) -> Option<&TextLabel> {
+        self.labels.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &TextLabel)> {
+        self.labels.iter().map(|(&id, label)| (id, label))
+    }
+}
+
+/// One glyph on the GPU: clip-space rectangle, atlas rectangle and color
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct GlyphInstance {
+    /// [x_min, y_min, x_max, y_max] in normalized device coordinates
+    rect: [f32; 4],
+    uv: [f32; 4],
+    color: [f32; 4],
+}
+
+impl GlyphInstance {
+    const ATTRIBS: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4, 2 => Float32x4];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GlyphInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Glyph capacity of a new pass; the buffer doubles as needed
+const INITIAL_GLYPHS: u32 = 1024;
+
+pub(crate) struct TextPass {
+    pipeline: wgpu::RenderPipeline,
+    atlas_bind_group: wgpu::BindGroup,
+    glyph_buffer: wgpu::Buffer,
+    glyph_capacity: u32,
+    glyph_count: u32,
+}
+
+impl TextPass {
+    /// Upload the font atlas and build the pipeline for `output_format`.
+    /// Text is drawn onto the swapchain texture, after any MSAA resolve.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, output_format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Text Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/text.wgsl"))),
+        });
+
+        let size = wgpu::Extent3d { width: ATLAS_WIDTH, height: ATLAS_HEIGHT, depth_or_array_layers: 1 };
+        let atlas = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &atlas,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &atlas_pixels(),
+            wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(ATLAS_WIDTH), rows_per_image: Some(ATLAS_HEIGHT) },
+            size,
+        );
+        let atlas_view = atlas.create_view(&wgpu::TextureViewDescriptor::default());
+        // Nearest keeps the bitmap font crisp at any size
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Glyph Sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let atlas_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("text_atlas_bind_group_layout"),
+        });
+        let atlas_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &atlas_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&atlas_view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&sampler) },
+            ],
+            label: Some("text_atlas_bind_group"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Text Pipeline Layout"),
+            bind_group_layouts: &[&atlas_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_text"),
+                buffers: &[GlyphInstance::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_text"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let glyph_buffer = Self::create_glyph_buffer(device, INITIAL_GLYPHS);
+        Self { pipeline, atlas_bind_group, glyph_buffer, glyph_capacity: INITIAL_GLYPHS, glyph_count: 0 }
+    }
+
+    fn create_glyph_buffer(device: &wgpu::Device, capacity: u32) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Glyph Buffer"),
+            size: capacity as u64 * std::mem::size_of::<GlyphInstance>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Lay out this frame's labels and upload their glyphs. Body anchors must
+    /// already be resolved to `World` positions by the caller.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        labels: &[TextLabel],
+        view_proj: &na::Matrix4<f32>,
+        width: u32,
+        height: u32,
+    ) {
+        let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+        let mut glyphs = Vec::new();
+        for label in labels {
+            let [columns, rows] = measure(&label.text);
+            // Top-left corner and glyph size in normalized device coordinates
+            let (origin, em) = match label.anchor {
+                TextAnchor::Screen => {
+                    let [x, y] = label.position;
+                    ([2.0 * x / width - 1.0, 1.0 - 2.0 * y / height], [2.0 * label.size / width, 2.0 * label.size / height])
+                }
+                TextAnchor::World | TextAnchor::Body(_) => {
+                    let project = |x: f32, y: f32| {
+                        let clip = view_proj * na::Vector4::new(x, y, 0.0, 1.0);
+                        [clip.x / clip.w, clip.y / clip.w]
+                    };
+                    let [x, y] = label.position;
+                    let base = project(x, y);
+                    let em_y = (project(x, y + label.size)[1] - base[1]).abs();
+                    let em_x = em_y * height / width;
+                    ([base[0] - columns * em_x / 2.0, base[1] + rows * em_y], [em_x, em_y])
+                }
+            };
+            if !origin.iter().chain(&em).all(|v| v.is_finite()) {
+                continue;
+            }
+            glyphs.extend(layout(&label.text).into_iter().map(|quad| GlyphInstance {
+                rect: [
+                    origin[0] + quad.min[0] * em[0],
+                    origin[1] - quad.max[1] * em[1],
+                    origin[0] + quad.max[0] * em[0],
+                    origin[1] - quad.min[1] * em[1],
+                ],
+                uv: quad.uv,
+                color: label.color,
+            }));
+        }
+
+        self.glyph_count = glyphs.len() as u32;
+        if glyphs.is_empty() {
+            return;
+        }
+        if self.glyph_count > self.glyph_capacity {
+            self.glyph_capacity = self.glyph_count.next_power_of_two();
+            self.glyph_buffer = Self::create_glyph_buffer(device, self.glyph_capacity);
+        }
+        queue.write_buffer(&self.glyph_buffer, 0, bytemuck::cast_slice(&glyphs));
+    }
+
+    /// Draw the prepared glyphs over what `view` already holds
+    pub fn encode(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        if self.glyph_count == 0 {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Text Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: wgpu::StoreOp::Store },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.atlas_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.glyph_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.glyph_count);
+    }
+}
@@ -0,0 +1,259 @@
+//! Mip chains and per-texture sampling for material textures
+//!
+//! A texture drawn much smaller than its size (a zoomed out camera, small
+//! sprites) shimmers when every pixel samples the full-size image. RGBA
+//! textures registered as materials, and the built-in default texture, get
+//! a full mip chain: each level is rendered from the one above with a
+//! linear filter, one small render pass per level, which works on every
+//! backend including WebGL2. Compressed KTX2 textures keep the levels stored
+//! in the file, since GPUs cannot render into block-compressed formats.
+//!
+//! How a material's texture is filtered and wrapped is set per material with
+//! `TextureSampling`. The default blends between texels and mip levels and
+//! clamps to the edges; pixel art wants `Nearest` filtering instead.
+
+use std::collections::HashMap;
+
+/// How texels, or mip levels, are blended when sampled between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextureFilter {
+    /// The closest one, for hard pixel edges
+    Nearest,
+    #[default]
+    Linear,
+}
+
+impl TextureFilter {
+    /// FFI code: 0 = nearest, 1 = linear
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Nearest),
+            1 => Some(Self::Linear),
+            _ => None,
+        }
+    }
+
+    fn mode(self) -> wgpu::FilterMode {
+        match self {
+            Self::Nearest => wgpu::FilterMode::Nearest,
+            Self::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+/// What texture coordinates outside 0..1 sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextureWrap {
+    /// The edge texels
+    #[default]
+    Clamp,
+    Repeat,
+    /// Repeat, flipping every other copy
+    Mirror,
+}
+
+impl TextureWrap {
+    /// FFI code: 0 = clamp, 1 = repeat, 2 = mirror
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Clamp),
+            1 => Some(Self::Repeat),
+            2 => Some(Self::Mirror),
+            _ => None,
+        }
+    }
+
+    fn mode(self) -> wgpu::AddressMode {
+        match self {
+            Self::Clamp => wgpu::AddressMode::ClampToEdge,
+            Self::Repeat => wgpu::AddressMode::Repeat,
+            Self::Mirror => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// Sampler settings of one material texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextureSampling {
+    /// Between texels, magnified or minified
+    pub filter: TextureFilter,
+    /// Between mip levels
+    pub mipmap_filter: TextureFilter,
+    pub wrap_u: TextureWrap,
+    pub wrap_v: TextureWrap,
+}
+
+impl TextureSampling {
+    /// Hard pixel edges up close and the nearest mip level far away
+    pub const PIXEL_ART: Self = Self {
+        filter: TextureFilter::Nearest,
+        mipmap_filter: TextureFilter::Nearest,
+        wrap_u: TextureWrap::Clamp,
+        wrap_v: TextureWrap::Clamp,
+    };
+
+    /// From the FFI codes of `TextureFilter` and `TextureWrap`, or None
+    /// when one is unknown
+    pub fn from_codes(filter: i32, mipmap_filter: i32, wrap_u: i32, wrap_v: i32) -> Option<Self> {
+        Some(Self {
+            filter: TextureFilter::from_i32(filter)?,
+            mipmap_filter: TextureFilter::from_i32(mipmap_filter)?,
+            wrap_u: TextureWrap::from_i32(wrap_u)?,
+            wrap_v: TextureWrap::from_i32(wrap_v)?,
+        })
+    }
+
+    pub fn descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        wgpu::SamplerDescriptor {
+            label: Some("Material Sampler"),
+            address_mode_u: self.wrap_u.mode(),
+            address_mode_v: self.wrap_v.mode(),
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: self.filter.mode(),
+            min_filter: self.filter.mode(),
+            mipmap_filter: self.mipmap_filter.mode(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Levels in a full mip chain for a `width` x `height` image, down to 1x1
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Renders the mip levels of textures from their first level
+pub(crate) struct MipGenerator {
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    /// One per format rendered into, made on first use
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl MipGenerator {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mipmap Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/mipmap.wgsl"))),
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Mipmap Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Mipmap Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Mipmap Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        Self { shader, bind_group_layout, layout, sampler, pipelines: HashMap::new() }
+    }
+
+    fn create_pipeline(&self, device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mipmap Pipeline"),
+            layout: Some(&self.layout),
+            vertex: wgpu::VertexState {
+                module: &self.shader,
+                entry_point: Some("vs_mip"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &self.shader,
+                entry_point: Some("fs_mip"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Render levels 1.. of `texture` from level 0. The texture needs
+    /// `RENDER_ATTACHMENT` usage and a renderable format.
+    pub fn generate(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+        let levels = texture.mip_level_count();
+        if levels < 2 {
+            return;
+        }
+        let format = texture.format();
+        if !self.pipelines.contains_key(&format) {
+            let pipeline = self.create_pipeline(device, format);
+            self.pipelines.insert(format, pipeline);
+        }
+        let pipeline = &self.pipelines[&format];
+
+        let views: Vec<wgpu::TextureView> = (0..levels)
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Mip Level View"),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Mipmap Encoder") });
+        for pair in views.windows(2) {
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mipmap Bind Group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&pair[0]) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+                ],
+            });
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &pair[1],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+        queue.submit(Some(encoder.finish()));
+    }
+}
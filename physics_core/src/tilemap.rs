@@ -0,0 +1,147 @@
+//! Tile grids that are both drawn and collided with
+//!
+//! A `Tilemap` is the `tiles` shape of a level description. Its cells hold 0
+//! for empty or a tile number: tile `n` is drawn with cell `n - 1` of the
+//! atlas texture (a registered material split into `atlas_columns` ×
+//! `atlas_rows`, read like a sprite sheet). The collider and the drawn quads
+//! are both derived from the same cells, so `set` followed by a collider
+//! rebuild and a quad re-upload keeps them in sync for destructible terrain.
+//!
+//! Row 0 is the top row; `origin` is the top-left corner of cell (0, 0).
+
+use crate::rapier::prelude::*;
+use serde::Deserialize;
+
+use crate::level_geometry;
+use crate::precision::to_real;
+
+/// Depth of tile boxes, matching the walls
+const TILE_HALF_DEPTH: Real = 0.1;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tilemap {
+    pub origin: [f32; 2],
+    pub tile_size: f32,
+    pub columns: u32,
+    pub cells: Vec<u8>,
+    /// Atlas texture; 0 draws the default texture
+    #[serde(default)]
+    pub material_id: u32,
+    #[serde(default = "one")]
+    pub atlas_columns: u32,
+    #[serde(default = "one")]
+    pub atlas_rows: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// One solid cell to draw: `corner` is its bottom-left corner, matching the
+/// renderer's quad, which spans `corner..corner + size`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileQuad {
+    pub corner: [f32; 2],
+    pub size: f32,
+    pub uv_offset: [f32; 2],
+    pub uv_scale: [f32; 2],
+}
+
+impl Tilemap {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.origin.iter().all(|v| v.is_finite()) || !self.tile_size.is_finite() || self.tile_size <= 0.0 {
+            return Err("tile size must be > 0".into());
+        }
+        if self.columns == 0 || self.cells.len() % self.columns as usize != 0 {
+            return Err("tile cells must fill whole rows of `columns`".into());
+        }
+        if self.atlas_columns == 0 || self.atlas_rows == 0 {
+            return Err("atlas columns and rows must be > 0".into());
+        }
+        Ok(())
+    }
+
+    pub fn rows(&self) -> u32 {
+        if self.columns == 0 {
+            0
+        } else {
+            (self.cells.len() / self.columns as usize) as u32
+        }
+    }
+
+    fn index(&self, column: u32, row: u32) -> Option<usize> {
+        (column < self.columns && row < self.rows()).then(|| (row * self.columns + column) as usize)
+    }
+
+    pub fn get(&self, column: u32, row: u32) -> Option<u8> {
+        self.index(column, row).map(|i| self.cells[i])
+    }
+
+    /// Change one cell. Returns true if a solid cell appeared or disappeared,
+    /// which is when the collider needs rebuilding.
+    pub fn set(&mut self, column: u32, row: u32, value: u8) -> bool {
+        match self.index(column, row) {
+            Some(i) => {
+                let was_solid = self.cells[i] != 0;
+                self.cells[i] = value;
+                was_solid != (value != 0)
+            }
+            None => false,
+        }
+    }
+
+    /// Cell containing the world point, if it lies on the grid
+    pub fn cell_at(&self, x: f32, y: f32) -> Option<(u32, u32)> {
+        let column = ((x - self.origin[0]) / self.tile_size).floor();
+        let row = ((self.origin[1] - y) / self.tile_size).floor();
+        if column < 0.0 || row < 0.0 {
+            return None;
+        }
+        let (column, row) = (column as u32, row as u32);
+        self.index(column, row).map(|_| (column, row))
+    }
+
+    /// Compound collider of the merged solid cells; None when every cell is empty
+    pub fn collider(&self) -> Option<Collider> {
+        let size = self.tile_size;
+        let shapes: Vec<(Isometry<Real>, SharedShape)> = level_geometry::merge_tiles(self.columns, &self.cells)
+            .into_iter()
+            .map(|rect| {
+                let (hw, hh) = (rect.width as f32 * size / 2.0, rect.height as f32 * size / 2.0);
+                let x = self.origin[0] + rect.column as f32 * size + hw;
+                let y = self.origin[1] - rect.row as f32 * size - hh;
+                (
+                    Isometry::translation(to_real(x), to_real(y), 0.0),
+                    SharedShape::cuboid(to_real(hw), to_real(hh), TILE_HALF_DEPTH),
+                )
+            })
+            .collect();
+        (!shapes.is_empty()).then(|| ColliderBuilder::compound(shapes).build())
+    }
+
+    /// Quads for every solid cell, row by row
+    pub fn quads(&self) -> Vec<TileQuad> {
+        let atlas_cells = self.atlas_columns.max(1) * self.atlas_rows.max(1);
+        let uv_scale = [1.0 / self.atlas_columns.max(1) as f32, 1.0 / self.atlas_rows.max(1) as f32];
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell != 0)
+            .map(|(i, &cell)| {
+                let (column, row) = (i as u32 % self.columns, i as u32 / self.columns);
+                let atlas_index = (cell as u32 - 1) % atlas_cells;
+                let (atlas_column, atlas_row) = (atlas_index % self.atlas_columns.max(1), atlas_index / self.atlas_columns.max(1));
+                TileQuad {
+                    corner: [
+                        self.origin[0] + column as f32 * self.tile_size,
+                        self.origin[1] - (row + 1) as f32 * self.tile_size,
+                    ],
+                    size: self.tile_size,
+                    uv_offset: [atlas_column as f32 * uv_scale[0], atlas_row as f32 * uv_scale[1]],
+                    uv_scale,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,86 @@
+//! Gravity from the device's physical orientation
+//!
+//! Accelerometer samples use the Android / W3C `devicemotion` convention:
+//! device axes (x right, y up along the screen, z out of it) in m/s², with
+//! the reaction to gravity included, so a phone held upright reads roughly
+//! `(0, 9.81, 0)`. `TiltGravity` turns that into a world gravity vector that
+//! points "down" as the user sees it, whatever way the display is rotated.
+
+/// Standard gravity (m/s²), what a resting accelerometer reports
+pub const STANDARD_GRAVITY: f32 = 9.81;
+
+/// Map device-axis acceleration onto screen axes for a display rotated by
+/// `rotation_degrees` (0, 90, 180 or 270, as in Android's `Surface.ROTATION_*`
+/// and `screen.orientation.angle`). Other values are snapped to the nearest
+/// quarter turn.
+pub fn device_to_screen(accel: [f32; 3], rotation_degrees: i32) -> [f32; 3] {
+    let [x, y, z] = accel;
+    match (rotation_degrees.rem_euclid(360) + 45) / 90 % 4 {
+        1 => [-y, x, z],
+        2 => [-x, -y, z],
+        3 => [y, -x, z],
+        _ => [x, y, z],
+    }
+}
+
+/// Low-pass filtered accelerometer-to-gravity mapping
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TiltGravity {
+    /// Multiplier on the measured gravity (1 = real-world strength)
+    pub strength: f32,
+    /// Weight of each new sample in 0..=1; lower is smoother but laggier
+    pub smoothing: f32,
+    filtered: Option<[f32; 3]>,
+}
+
+impl Default for TiltGravity {
+    fn default() -> Self {
+        Self::new(1.0, 0.2)
+    }
+}
+
+impl TiltGravity {
+    pub fn new(strength: f32, smoothing: f32) -> Self {
+        Self {
+            strength,
+            smoothing: smoothing.clamp(0.0, 1.0),
+            filtered: None,
+        }
+    }
+
+    /// Forget the filter history, e.g. when the sensor is re-enabled
+    pub fn reset(&mut self) {
+        self.filtered = None;
+    }
+
+    /// Feed one accelerometer sample and return the world gravity vector.
+    /// The depth axis is dropped: tilting the phone towards or away from the
+    /// user only changes how strongly bodies slide, as with a real tray.
+    pub fn update(&mut self, accel: [f32; 3], rotation_degrees: i32) -> [f32; 3] {
+        if accel.iter().any(|v| !v.is_finite()) {
+            return self.gravity();
+        }
+        let sample = device_to_screen(accel, rotation_degrees);
+        let filtered = match self.filtered {
+            Some(prev) => {
+                let a = self.smoothing;
+                [
+                    prev[0] + (sample[0] - prev[0]) * a,
+                    prev[1] + (sample[1] - prev[1]) * a,
+                    prev[2] + (sample[2] - prev[2]) * a,
+                ]
+            }
+            None => sample,
+        };
+        self.filtered = Some(filtered);
+        self.gravity()
+    }
+
+    /// Current gravity; straight down at full strength before any sample
+    pub fn gravity(&self) -> [f32; 3] {
+        match self.filtered {
+            Some([x, y, _]) => [-x * self.strength, -y * self.strength, 0.0],
+            None => [0.0, -STANDARD_GRAVITY * self.strength, 0.0],
+        }
+    }
+}
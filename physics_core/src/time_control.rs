@@ -0,0 +1,150 @@
+//! Simulation clock and smooth time-scale transitions for slow motion and
+//! speed-ups
+//!
+//! `Time` is the one clock every system reads. `update_internal` starts a
+//! frame on it once with the real frame time, the time scale and the pause
+//! flags; each simulation step run in that frame then sets `delta` to its
+//! scaled length. Pausing the simulation and pausing animation are separate:
+//! a frame of a paused simulation runs no step but one idle run of the
+//! schedule, where input and sync still happen and sprite animations and
+//! tweens advance by the scaled frame time, while movement strategies, state
+//! machines and physics stand still. Paused animation keeps
+//! `animation_delta` at 0 in steps and idle runs alike. Single steps taken
+//! while the simulation is paused move everything by the same `STEP_DT`.
+
+use bevy_ecs::prelude::*;
+
+/// Fixed step used by single-stepping and fast-forward
+pub const STEP_DT: f32 = 1.0 / 60.0;
+
+/// Most steps a single fast-forward request may run (ten seconds at 60 Hz)
+pub const MAX_FAST_FORWARD_STEPS: u32 = 600;
+
+/// Clock resource for simulation systems
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Time {
+    /// Scaled seconds covered by the step being run; 0 outside steps
+    pub delta: f32,
+    /// Scaled seconds animations and tweens advance by in the current run
+    /// of the schedule; 0 while animation is paused
+    pub animation_delta: f32,
+    /// Scaled seconds simulated since the scene was created
+    pub elapsed: f32,
+    /// Unscaled seconds of the current frame, also while paused
+    pub real_delta: f32,
+    /// Unscaled seconds since the scene was created, pauses included
+    pub real_elapsed: f32,
+    pub time_scale: f32,
+    pub paused: bool,
+    pub animation_paused: bool,
+    /// Whether the current run of the schedule is a simulation step rather
+    /// than the idle run of a paused frame
+    pub stepping: bool,
+    /// Steps run since the scene was created
+    pub steps: u64,
+    /// Frames started since the scene was created
+    pub frames: u64,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self {
+            delta: 0.0,
+            animation_delta: 0.0,
+            elapsed: 0.0,
+            real_delta: 0.0,
+            real_elapsed: 0.0,
+            time_scale: 1.0,
+            paused: false,
+            animation_paused: false,
+            stepping: false,
+            steps: 0,
+            frames: 0,
+        }
+    }
+}
+
+impl Time {
+    /// Start a frame of `real_dt` unscaled seconds. Negative or non-finite
+    /// frame times count as 0.
+    pub fn begin_frame(&mut self, real_dt: f32, time_scale: f32, paused: bool) {
+        self.real_delta = if real_dt.is_finite() { real_dt.max(0.0) } else { 0.0 };
+        self.real_elapsed += self.real_delta;
+        self.time_scale = time_scale;
+        self.paused = paused;
+        self.frames += 1;
+        self.delta = 0.0;
+        self.animation_delta = 0.0;
+        self.stepping = false;
+    }
+
+    /// Start a step covering `dt` scaled seconds
+    pub fn begin_step(&mut self, dt: f32) {
+        self.delta = dt;
+        self.elapsed += dt;
+        self.steps += 1;
+        self.stepping = true;
+        self.animation_delta = self.animation_dt(dt);
+    }
+
+    /// Start the idle run of a frame that steps nothing, with animations
+    /// moving `dt` scaled seconds
+    pub fn begin_idle(&mut self, dt: f32) {
+        self.delta = 0.0;
+        self.stepping = false;
+        self.animation_delta = self.animation_dt(dt);
+    }
+
+    fn animation_dt(&self, dt: f32) -> f32 {
+        if self.animation_paused {
+            0.0
+        } else {
+            dt
+        }
+    }
+}
+
+/// Eases the time scale from one value to another over wall-clock seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeScaleRamp {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl TimeScaleRamp {
+    /// A non-positive duration jumps straight to `to`
+    pub fn new(from: f32, to: f32, seconds: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration: seconds.max(0.0),
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn target(&self) -> f32 {
+        self.to
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Move the ramp forward by real (unscaled) seconds and return the new scale
+    pub fn advance(&mut self, real_dt: f32) -> f32 {
+        self.elapsed = (self.elapsed + real_dt.max(0.0)).min(self.duration);
+        self.value()
+    }
+
+    pub fn value(&self) -> f32 {
+        if self.is_done() {
+            return self.to;
+        }
+        // Smoothstep so the change neither starts nor ends abruptly
+        let t = self.elapsed / self.duration;
+        let eased = t * t * (3.0 - 2.0 * t);
+        self.from + (self.to - self.from) * eased
+    }
+}
@@ -0,0 +1,78 @@
+//! Slow-motion zones
+//!
+//! A `DilationZone` is a rectangle where time runs at `scale` (0.25 is four
+//! times slower). Rapier has one `dt` for the whole world, so the step path
+//! emulates a per-body time step instead: before stepping, a dilated body's
+//! velocities are multiplied by its scale, gravity and applied forces by the
+//! scale squared and damping by the scale; afterwards the velocities are
+//! divided back and the rest restored. Over a step of `dt` the body then
+//! moves and accelerates as if it had been stepped with `scale * dt`.
+//!
+//! A body takes the scale of the slowest zone containing its center at the
+//! start of the step. Zones only slow time down; use the global time scale
+//! to speed everything up.
+
+use serde::Deserialize;
+
+/// Slowest allowed scale; lower values are raised to this
+pub const MIN_SCALE: f32 = 0.01;
+
+/// Rectangle where time runs at `scale`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DilationZone {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    /// Time rate inside, `MIN_SCALE..=1`
+    pub scale: f32,
+}
+
+impl Default for DilationZone {
+    fn default() -> Self {
+        Self {
+            min: [-0.5, -0.5],
+            max: [0.5, 0.5],
+            scale: 0.25,
+        }
+    }
+}
+
+impl DilationZone {
+    pub fn contains(&self, position: [f32; 2]) -> bool {
+        (self.min[0]..=self.max[0]).contains(&position[0]) && (self.min[1]..=self.max[1]).contains(&position[1])
+    }
+
+    /// Corners in order and the scale in range; NaN scales become 1
+    pub fn sanitized(self) -> Self {
+        Self {
+            min: [self.min[0].min(self.max[0]), self.min[1].min(self.max[1])],
+            max: [self.min[0].max(self.max[0]), self.min[1].max(self.max[1])],
+            scale: if self.scale.is_nan() { 1.0 } else { self.scale.clamp(MIN_SCALE, 1.0) },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeDilationConfig {
+    pub zones: Vec<DilationZone>,
+}
+
+impl TimeDilationConfig {
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    pub fn sanitized(self) -> Self {
+        Self { zones: self.zones.into_iter().map(DilationZone::sanitized).collect() }
+    }
+
+    /// Time rate at `position`: the slowest zone containing it, else 1
+    pub fn scale_at(&self, position: [f32; 2]) -> f32 {
+        self.zones
+            .iter()
+            .filter(|zone| zone.contains(position))
+            .map(|zone| zone.scale)
+            .fold(1.0, f32::min)
+    }
+}
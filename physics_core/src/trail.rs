@@ -0,0 +1,318 @@
+//! Trajectory trails behind selected bodies
+//!
+//! A trail records the recent positions of one body into a fixed-size ring:
+//! a new point is kept once the body has moved `MIN_SPACING` from the last
+//! one, so resting bodies do not wear their history away. Every frame the
+//! rings are turned into a ribbon of quads whose alpha fades from the body
+//! back to zero at the oldest point, written into one vertex buffer and
+//! drawn under the bodies.
+//!
+//! Trails are keyed by entity id and disappear with their body.
+
+use bevy_ecs::prelude::*;
+use crate::rapier::prelude::*;
+
+use crate::precision::xy;
+use crate::PhysicsBody;
+
+/// Fewest points a trail can hold
+pub const MIN_TRAIL_POINTS: u32 = 2;
+/// Most points a trail can hold
+pub const MAX_TRAIL_POINTS: u32 = 1024;
+
+/// Distance a body moves before its next point is recorded, m
+const MIN_SPACING: f32 = 0.002;
+/// Ribbon width, m
+const TRAIL_WIDTH: f32 = 0.008;
+
+/// The last `capacity` points pushed, overwriting the oldest
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PointRing {
+    points: Vec<[f32; 2]>,
+    /// Index of the oldest point once the ring is full
+    head: usize,
+    capacity: usize,
+}
+
+impl PointRing {
+    pub fn new(capacity: usize) -> Self {
+        Self { points: Vec::with_capacity(capacity), head: 0, capacity }
+    }
+
+    pub fn push(&mut self, point: [f32; 2]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.points.len() < self.capacity {
+            self.points.push(point);
+        } else {
+            self.points[self.head] = point;
+            self.head = (self.head + 1) % self.capacity;
+        }
+    }
+
+    /// The newest point
+    pub fn last(&self) -> Option<[f32; 2]> {
+        // Before the ring wraps, and whenever head is back at 0, the newest
+        // point is the last one stored
+        match self.head {
+            0 => self.points.last().copied(),
+            head => self.points.get(head - 1).copied(),
+        }
+    }
+
+    /// Points oldest first
+    pub fn to_vec(&self) -> Vec<[f32; 2]> {
+        let (newer, older) = self.points.split_at(self.head);
+        older.iter().chain(newer).copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.head = 0;
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TrailVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// Two triangles per segment of the polyline `points` (oldest first),
+/// `width` across. Alpha runs from 0 at the oldest point to `color`'s at the
+/// newest; segments of zero length are skipped.
+pub fn ribbon_vertices(points: &[[f32; 2]], color: [f32; 4], width: f32) -> Vec<TrailVertex> {
+    let n = points.len();
+    if n < 2 {
+        return Vec::new();
+    }
+    let half = width * 0.5;
+    let faded = |i: usize| [color[0], color[1], color[2], color[3] * i as f32 / (n - 1) as f32];
+    let mut vertices = Vec::with_capacity((n - 1) * 6);
+    for i in 0..n - 1 {
+        let (a, b) = (points[i], points[i + 1]);
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length <= f32::EPSILON {
+            continue;
+        }
+        let normal = [-dy / length * half, dx / length * half];
+        let (color_a, color_b) = (faded(i), faded(i + 1));
+        let a_left = TrailVertex { position: [a[0] + normal[0], a[1] + normal[1]], color: color_a };
+        let a_right = TrailVertex { position: [a[0] - normal[0], a[1] - normal[1]], color: color_a };
+        let b_left = TrailVertex { position: [b[0] + normal[0], b[1] + normal[1]], color: color_b };
+        let b_right = TrailVertex { position: [b[0] - normal[0], b[1] - normal[1]], color: color_b };
+        vertices.extend([a_left, a_right, b_left, b_left, a_right, b_right]);
+    }
+    vertices
+}
+
+/// One body's trail
+#[derive(Debug, Clone)]
+pub(crate) struct Trail {
+    pub entity: u64,
+    pub color: [f32; 4],
+    pub points: PointRing,
+}
+
+/// Every trail in the scene
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Trails {
+    trails: Vec<Trail>,
+}
+
+impl Trails {
+    /// Start or restyle the trail of `entity`, keeping up to `length` points.
+    /// A trail that changes length starts over.
+    pub fn enable(&mut self, entity: u64, length: u32, color: [f32; 4]) {
+        let capacity = length.clamp(MIN_TRAIL_POINTS, MAX_TRAIL_POINTS) as usize;
+        let color = color.map(|c| if c.is_finite() { c.clamp(0.0, 1.0) } else { 1.0 });
+        match self.trails.iter_mut().find(|trail| trail.entity == entity) {
+            Some(trail) => {
+                trail.color = color;
+                if trail.points.capacity() != capacity {
+                    trail.points = PointRing::new(capacity);
+                }
+            }
+            None => self.trails.push(Trail { entity, color, points: PointRing::new(capacity) }),
+        }
+    }
+
+    /// Returns false if the body had no trail
+    pub fn disable(&mut self, entity: u64) -> bool {
+        let before = self.trails.len();
+        self.trails.retain(|trail| trail.entity != entity);
+        self.trails.len() != before
+    }
+
+    /// Record the current position of every trailed body and drop the trails
+    /// of bodies that no longer exist
+    pub fn record(&mut self, world: &World, rigid_body_set: &RigidBodySet) {
+        self.trails.retain_mut(|trail| {
+            let position = Entity::try_from_bits(trail.entity)
+                .ok()
+                .filter(|&entity| world.entities().contains(entity))
+                .and_then(|entity| world.get::<PhysicsBody>(entity))
+                .and_then(|body| rigid_body_set.get(body.rigid_body_handle))
+                .map(|rb| xy(rb.translation()));
+            let Some(position) = position else {
+                return false;
+            };
+            let moved = trail.points.last().is_none_or(|last| {
+                (position[0] - last[0]).powi(2) + (position[1] - last[1]).powi(2) >= MIN_SPACING * MIN_SPACING
+            });
+            if moved {
+                trail.points.push(position);
+            }
+            true
+        });
+    }
+
+    /// The ribbons of every trail
+    pub fn vertices(&self) -> Vec<TrailVertex> {
+        self.trails
+            .iter()
+            .flat_map(|trail| ribbon_vertices(&trail.points.to_vec(), trail.color, TRAIL_WIDTH))
+            .collect()
+    }
+}
+
+impl TrailVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TrailVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Vertex capacity of a new pass; the buffer doubles as needed
+const INITIAL_VERTICES: u32 = 4096;
+
+/// Draws the trail ribbons inside the main pass, under the bodies
+pub(crate) struct TrailPass {
+    /// Pipeline matching the main pass (swapchain format, MSAA sample count)
+    pipeline: wgpu::RenderPipeline,
+    /// Pipeline matching the HDR bloom target, when bloom is available
+    hdr_pipeline: Option<wgpu::RenderPipeline>,
+    vertex_buffer: wgpu::Buffer,
+    vertex_capacity: u32,
+    vertex_count: u32,
+}
+
+impl TrailPass {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        output_format: wgpu::TextureFormat,
+        sample_count: u32,
+        hdr_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Trail Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/trail.wgsl"))),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Trail Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let make_pipeline = |label: &str, format: wgpu::TextureFormat, count: u32| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_trail"),
+                    buffers: &[TrailVertex::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_trail"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let pipeline = make_pipeline("Trail Pipeline", output_format, sample_count);
+        let hdr_pipeline = hdr_format.map(|format| make_pipeline("HDR Trail Pipeline", format, 1));
+        Self {
+            pipeline,
+            hdr_pipeline,
+            vertex_buffer: Self::create_vertex_buffer(device, INITIAL_VERTICES),
+            vertex_capacity: INITIAL_VERTICES,
+            vertex_count: 0,
+        }
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, capacity: u32) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Trail Vertex Buffer"),
+            size: capacity as u64 * std::mem::size_of::<TrailVertex>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Upload this frame's ribbons
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, vertices: &[TrailVertex]) {
+        self.vertex_count = vertices.len() as u32;
+        if vertices.is_empty() {
+            return;
+        }
+        if self.vertex_count > self.vertex_capacity {
+            self.vertex_capacity = self.vertex_count.next_power_of_two();
+            self.vertex_buffer = Self::create_vertex_buffer(device, self.vertex_capacity);
+        }
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vertices));
+    }
+
+    /// Draw the prepared ribbons. Leaves the trail pipeline and camera (group 0) bound.
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, camera_bind_group: &wgpu::BindGroup, hdr: bool) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        let Some(pipeline) = (if hdr { self.hdr_pipeline.as_ref() } else { Some(&self.pipeline) }) else {
+            return;
+        };
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+    }
+}
@@ -0,0 +1,104 @@
+//! Trajectory prediction for aiming UIs
+//!
+//! `predict` copies the Rapier state into a throwaway pipeline, gives one body
+//! a launch velocity and steps the copy forward, recording where the body
+//! goes. The live simulation is untouched. The copy runs gravity, contacts,
+//! joints, conveyors and contact filtering with the last frame's step length
+//! and substeps; force fields, water and slow-motion zones are not applied,
+//! so the arc is exact in open air and an estimate inside them.
+//!
+//! A prediction can also be shown as a dotted arc, which stays until it is
+//! replaced or cleared. The dots are drawn by the trail pass.
+
+use bevy_ecs::prelude::*;
+use crate::rapier::prelude::*;
+
+use crate::contact_filter::ContactHooks;
+use crate::precision::{planar, xy};
+use crate::trail::TrailVertex;
+use crate::{PhysicsBody, PhysicsState};
+
+/// Most steps one prediction can run
+pub const MAX_PREDICTION_STEPS: u32 = 600;
+
+/// Every this many predicted points gets a dot
+pub const DOT_SPACING: usize = 3;
+/// Dot size, m
+const DOT_SIZE: f32 = 0.012;
+const DOT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.85];
+
+/// A small square at every `spacing`th point (starting with the first),
+/// `size` across, fading to a quarter of `color`'s alpha at the last point
+pub fn dot_vertices(points: &[[f32; 2]], color: [f32; 4], size: f32, spacing: usize) -> Vec<TrailVertex> {
+    let half = size * 0.5;
+    let last = points.len().saturating_sub(1).max(1) as f32;
+    points
+        .iter()
+        .enumerate()
+        .step_by(spacing.max(1))
+        .flat_map(|(i, &[x, y])| {
+            let alpha = color[3] * (1.0 - 0.75 * i as f32 / last);
+            let color = [color[0], color[1], color[2], alpha];
+            let corner = |dx: f32, dy: f32| TrailVertex { position: [x + dx, y + dy], color };
+            let (a, b, c, d) = (corner(-half, -half), corner(half, -half), corner(half, half), corner(-half, half));
+            [a, b, c, a, c, d]
+        })
+        .collect()
+}
+
+/// The dots of a shown prediction
+pub(crate) fn preview_vertices(points: &[[f32; 2]]) -> Vec<TrailVertex> {
+    dot_vertices(points, DOT_COLOR, DOT_SIZE, DOT_SPACING)
+}
+
+/// Positions of `entity` after each of up to `steps` steps when launched at
+/// `velocity` now. Stops early once the body falls asleep. None unless the
+/// entity is a dynamic body.
+pub(crate) fn predict(physics: &PhysicsState, entity: Entity, velocity: [f32; 2], steps: u32) -> Option<Vec<[f32; 2]>> {
+    let body = *physics.world.get::<PhysicsBody>(entity)?;
+    if !physics.rigid_body_set.get(body.rigid_body_handle)?.is_dynamic() {
+        return None;
+    }
+
+    let mut pipeline = PhysicsPipeline::new();
+    let mut island_manager = physics.island_manager.clone();
+    let mut broad_phase = physics.broad_phase.clone();
+    let mut narrow_phase = physics.narrow_phase.clone();
+    let mut rigid_body_set = physics.rigid_body_set.clone();
+    let mut collider_set = physics.collider_set.clone();
+    let mut impulse_joint_set = physics.impulse_joint_set.clone();
+    let mut multibody_joint_set = physics.multibody_joint_set.clone();
+    let mut ccd_solver = physics.ccd_solver.clone();
+    let hooks = ContactHooks { surface_velocities: &physics.surface_velocities, filter: &physics.contact_filter };
+
+    let rb = &mut rigid_body_set[body.rigid_body_handle];
+    rb.set_linvel(planar(velocity), true);
+
+    let steps = steps.min(MAX_PREDICTION_STEPS);
+    let mut points = Vec::with_capacity(steps as usize);
+    for _ in 0..steps {
+        for _ in 0..physics.current_substeps.max(1) {
+            pipeline.step(
+                &physics.gravity,
+                &physics.integration_parameters,
+                &mut island_manager,
+                &mut broad_phase,
+                &mut narrow_phase,
+                &mut rigid_body_set,
+                &mut collider_set,
+                &mut impulse_joint_set,
+                &mut multibody_joint_set,
+                &mut ccd_solver,
+                None,
+                &hooks,
+                &(),
+            );
+        }
+        let rb = rigid_body_set.get(body.rigid_body_handle)?;
+        points.push(xy(rb.translation()));
+        if rb.is_sleeping() {
+            break;
+        }
+    }
+    Some(points)
+}
@@ -0,0 +1,140 @@
+//! Double-buffered transform export for host-side overlays
+//!
+//! Hosts that draw their own UI over the scene (a minimap, DOM labels) would
+//! otherwise call a getter per entity per frame. Instead the engine keeps one
+//! fixed allocation that hosts map once, as a raw pointer, a JNI direct
+//! `ByteBuffer` or a WASM memory view, and rewrites it at the end of every
+//! frame. The allocation never moves until export is reconfigured.
+//!
+//! Layout, in native byte order (little-endian on every shipped target), with
+//! every block 32 bytes:
+//!
+//! ```text
+//! region header   front: u32, capacity: u32, record_size: u32, reserved
+//! buffer 0        header (frame: u64, count: u32, total: u32, reserved)
+//!                 `capacity` records
+//! buffer 1        same as buffer 0
+//! ```
+//!
+//! Each record is an `ExportedTransform`. The engine fills the buffer that
+//! `front` does not point at and then flips `front`, so a reader always has a
+//! complete frame. A buffer's `frame` is 0 while it is being written; readers
+//! that can be slower than a frame copy the front buffer and keep the copy
+//! only if `frame` was the same nonzero value before and after.
+
+use std::sync::atomic::{fence, Ordering};
+
+/// Size of the region header, each buffer header and each record in bytes
+pub const BLOCK_SIZE: usize = 32;
+
+/// Most records per buffer
+pub const MAX_CAPACITY: u32 = 65_536;
+
+/// One body's transform as hosts read it
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ExportedTransform {
+    pub entity_id: u64,
+    pub x: f32,
+    pub y: f32,
+    /// Radians around z
+    pub rotation: f32,
+    pub vx: f32,
+    pub vy: f32,
+    /// `Groups` bits, so overlays can tell players from debris
+    pub groups: u32,
+}
+
+/// The shared region and the frame counter behind it
+pub struct TransformExport {
+    /// u64 words keep the region 8-byte aligned for the records
+    words: Box<[u64]>,
+    capacity: usize,
+    frame: u64,
+}
+
+impl TransformExport {
+    /// Allocate room for `capacity` records per buffer; None for 0 or more
+    /// than `MAX_CAPACITY`
+    pub fn new(capacity: u32) -> Option<Self> {
+        if capacity == 0 || capacity > MAX_CAPACITY {
+            return None;
+        }
+        let capacity = capacity as usize;
+        let bytes = BLOCK_SIZE * (1 + 2 * (1 + capacity));
+        let mut export = Self { words: vec![0; bytes / 8].into_boxed_slice(), capacity, frame: 0 };
+        let header: &mut [u32] = bytemuck::cast_slice_mut(&mut export.words[..BLOCK_SIZE / 8]);
+        header[..3].copy_from_slice(&[0, capacity as u32, BLOCK_SIZE as u32]);
+        Some(export)
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity as u32
+    }
+
+    /// Start of the region; stable for the lifetime of `self`
+    pub fn as_ptr(&self) -> *const u8 {
+        self.words.as_ptr() as *const u8
+    }
+
+    /// Length of the region in bytes
+    pub fn len(&self) -> usize {
+        self.words.len() * 8
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.words)
+    }
+
+    /// Index of the buffer readers should use
+    pub fn front(&self) -> usize {
+        bytemuck::cast_slice::<u64, u32>(&self.words[..1])[0] as usize
+    }
+
+    /// Words of buffer `index`: its header, then its records
+    fn buffer(&self, index: usize) -> &[u64] {
+        let words = (BLOCK_SIZE / 8) * (1 + self.capacity);
+        let start = BLOCK_SIZE / 8 + index * words;
+        &self.words[start..start + words]
+    }
+
+    fn buffer_mut(&mut self, index: usize) -> &mut [u64] {
+        let words = (BLOCK_SIZE / 8) * (1 + self.capacity);
+        let start = BLOCK_SIZE / 8 + index * words;
+        &mut self.words[start..start + words]
+    }
+
+    /// Write the first `capacity` transforms into the back buffer and make it
+    /// the front one. Returns the frame number it was published as.
+    pub fn publish(&mut self, transforms: &[ExportedTransform]) -> u64 {
+        self.frame += 1;
+        let frame = self.frame;
+        let back = 1 - self.front();
+        let count = transforms.len().min(self.capacity);
+        let buffer = self.buffer_mut(back);
+        buffer[0] = 0;
+        fence(Ordering::Release);
+        let records: &mut [ExportedTransform] = bytemuck::cast_slice_mut(&mut buffer[BLOCK_SIZE / 8..]);
+        records[..count].copy_from_slice(&transforms[..count]);
+        let counts: &mut [u32] = bytemuck::cast_slice_mut(&mut buffer[1..2]);
+        counts.copy_from_slice(&[count as u32, transforms.len().min(u32::MAX as usize) as u32]);
+        fence(Ordering::Release);
+        buffer[0] = frame;
+        fence(Ordering::Release);
+        bytemuck::cast_slice_mut::<u64, u32>(&mut self.words[..1])[0] = back as u32;
+        frame
+    }
+
+    /// Frame number, total body count and records of the front buffer, as a
+    /// host would read them
+    pub fn latest(&self) -> (u64, u32, &[ExportedTransform]) {
+        let buffer = self.buffer(self.front());
+        let [count, total]: [u32; 2] = bytemuck::cast_slice::<u64, u32>(&buffer[1..2]).try_into().unwrap_or_default();
+        let records: &[ExportedTransform] = bytemuck::cast_slice(&buffer[BLOCK_SIZE / 8..]);
+        (buffer[0], total, &records[..count as usize])
+    }
+}
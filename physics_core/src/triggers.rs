@@ -0,0 +1,204 @@
+//! Trigger zones and win/lose conditions
+//!
+//! A level can declare rectangular zones that bodies pass through without
+//! colliding. After every step each zone checks which bodies have their
+//! center inside it and queues a `TriggerEvent` for every body that entered
+//! or left; a body removed while inside a zone also leaves it. A zone only
+//! watches bodies sharing a bit with its `mask` (see `groups`), which is how
+//! hosts tag "the ball" apart from the rest of the scene.
+//!
+//! `goal` and `hazard` zones also decide the level: the first body to enter
+//! a goal wins it, the first to enter a hazard loses it, and the outcome
+//! stays until the next level is loaded. Plain `sensor` zones only report.
+
+use std::collections::{HashSet, VecDeque};
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::groups::{self, Groups};
+use crate::precision::xy;
+use crate::{PhysicsBody, PhysicsState};
+
+/// Events kept for hosts that are slow to poll; older ones are dropped
+pub const MAX_QUEUED: usize = 256;
+/// Most zones one level can declare
+pub const MAX_ZONES: usize = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[repr(u32)]
+pub enum TriggerKind {
+    /// Only reports bodies entering and leaving
+    #[default]
+    Sensor = 0,
+    /// Entering it wins the level
+    Goal = 1,
+    /// Entering it loses the level
+    Hazard = 2,
+}
+
+/// One zone of a level description
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TriggerZone {
+    /// For the host's own bookkeeping; events carry the zone's index
+    pub name: String,
+    pub kind: TriggerKind,
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    /// Group bits of the bodies the zone watches
+    pub mask: u32,
+}
+
+impl Default for TriggerZone {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            kind: TriggerKind::Sensor,
+            min: [0.0, 0.0],
+            max: [0.0, 0.0],
+            mask: groups::ALL,
+        }
+    }
+}
+
+impl TriggerZone {
+    pub fn validate(&self) -> Result<(), String> {
+        let finite = self.min.iter().chain(&self.max).all(|v| v.is_finite());
+        if !finite || self.min[0] >= self.max[0] || self.min[1] >= self.max[1] {
+            return Err(format!("trigger zone '{}' must be a finite, non-empty rectangle", self.name));
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        (self.min[0]..=self.max[0]).contains(&point[0]) && (self.min[1]..=self.max[1]).contains(&point[1])
+    }
+}
+
+/// A body entering or leaving a zone
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerEvent {
+    pub entity: u64,
+    /// Index of the zone in the level's `triggers`
+    pub zone: u32,
+    /// `TriggerKind` of the zone
+    pub kind: u32,
+    /// true on entering, false on leaving
+    pub entered: bool,
+}
+
+/// Whether the level has been decided
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[repr(u32)]
+pub enum Outcome {
+    #[default]
+    Playing = 0,
+    Won = 1,
+    Lost = 2,
+}
+
+/// The zones of the loaded level, who is inside them and the events not yet
+/// handed to the host
+#[derive(Debug, Clone, Default)]
+pub struct Triggers {
+    zones: Vec<TriggerZone>,
+    /// Entity ids inside each zone, by zone index
+    inside: Vec<HashSet<u64>>,
+    events: VecDeque<TriggerEvent>,
+    dropped: u64,
+    outcome: Outcome,
+}
+
+impl Triggers {
+    /// Replace the zones. Everyone starts outside and the outcome is reset;
+    /// queued events are kept for the host.
+    pub fn set_zones(&mut self, zones: Vec<TriggerZone>) {
+        self.inside = vec![HashSet::new(); zones.len()];
+        self.zones = zones;
+        self.outcome = Outcome::Playing;
+    }
+
+    pub fn zones(&self) -> &[TriggerZone] {
+        &self.zones
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+
+    /// Diff every zone against `bodies` as (entity id, center, group bits)
+    /// and queue an event per body that entered or left
+    pub fn update(&mut self, bodies: &[(u64, [f32; 2], u32)]) {
+        for (index, zone) in self.zones.iter().enumerate() {
+            let now: HashSet<u64> = bodies
+                .iter()
+                .filter(|&&(_, center, bits)| Groups(bits).matches(zone.mask) && zone.contains(center))
+                .map(|&(id, _, _)| id)
+                .collect();
+            let was = &self.inside[index];
+            let mut left: Vec<u64> = was.difference(&now).copied().collect();
+            let mut entered: Vec<u64> = now.difference(was).copied().collect();
+            // Set order is arbitrary; keep events reproducible
+            left.sort_unstable();
+            entered.sort_unstable();
+
+            let event = |entity, entered| TriggerEvent { entity, zone: index as u32, kind: zone.kind as u32, entered };
+            for entity in left {
+                push(&mut self.events, &mut self.dropped, event(entity, false));
+            }
+            if !entered.is_empty() && self.outcome == Outcome::Playing {
+                match zone.kind {
+                    TriggerKind::Goal => self.outcome = Outcome::Won,
+                    TriggerKind::Hazard => self.outcome = Outcome::Lost,
+                    TriggerKind::Sensor => {}
+                }
+            }
+            for entity in entered {
+                push(&mut self.events, &mut self.dropped, event(entity, true));
+            }
+            self.inside[index] = now;
+        }
+    }
+
+    /// Remove and return up to `max` events, oldest first
+    pub fn drain(&mut self, max: usize) -> Vec<TriggerEvent> {
+        let count = max.min(self.events.len());
+        self.events.drain(..count).collect()
+    }
+
+    /// Events lost because nobody polled in time
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+fn push(events: &mut VecDeque<TriggerEvent>, dropped: &mut u64, event: TriggerEvent) {
+    if events.len() == MAX_QUEUED {
+        events.pop_front();
+        *dropped += 1;
+    }
+    events.push_back(event);
+}
+
+/// Check the zones against every body after the latest step
+pub(crate) fn update(physics: &mut PhysicsState) {
+    if physics.triggers.zones().is_empty() {
+        return;
+    }
+    let bodies: Vec<(u64, [f32; 2], u32)> = physics
+        .world
+        .query::<(Entity, &PhysicsBody, Option<&Groups>)>()
+        .iter(&physics.world)
+        .filter_map(|(entity, body, groups)| {
+            let rb = physics.rigid_body_set.get(body.rigid_body_handle)?;
+            let bits = groups.copied().unwrap_or_default().0;
+            Some((entity.to_bits(), xy(rb.translation()), bits))
+        })
+        .collect();
+    physics.triggers.update(&bodies);
+}
@@ -0,0 +1,356 @@
+//! Property tweens
+//!
+//! A tween eases one property from `from` to `to` over `duration` scaled
+//! seconds, after an optional `delay` during which the property holds
+//! `from`. Tweens run in the animation set of the schedule, so they follow
+//! the time scale and pause with animation, not with the simulation. Starting a tween on a
+//! property that is already animating replaces the old one.
+//!
+//! Entity properties are the drawn size (a multiplier; the collider keeps
+//! its size), the rotation in radians and an RGB tint multiplying the
+//! texture. The camera's zoom (visible world height) and position live with
+//! the renderer, so the system only records their latest values and
+//! `lib.rs` applies them after each run of the schedule.
+
+use bevy_ecs::prelude::*;
+use crate::rapier::prelude::{Rotation, Vector};
+use serde::Deserialize;
+
+use crate::precision::to_real;
+use crate::schedule::RapierContext;
+use crate::time_control::Time;
+use crate::PhysicsBody;
+
+/// Most tweens running at once
+pub const MAX_TWEENS: usize = 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[repr(u32)]
+pub enum Easing {
+    #[default]
+    Linear = 0,
+    QuadIn = 1,
+    QuadOut = 2,
+    QuadInOut = 3,
+    CubicIn = 4,
+    CubicOut = 5,
+    CubicInOut = 6,
+    SineInOut = 7,
+    /// Overshoots the end slightly and settles back
+    BackOut = 8,
+    /// Springs past the end a few times
+    ElasticOut = 9,
+    /// Drops onto the end and bounces
+    BounceOut = 10,
+}
+
+impl Easing {
+    pub fn from_u32(raw: u32) -> Option<Self> {
+        Some(match raw {
+            0 => Self::Linear,
+            1 => Self::QuadIn,
+            2 => Self::QuadOut,
+            3 => Self::QuadInOut,
+            4 => Self::CubicIn,
+            5 => Self::CubicOut,
+            6 => Self::CubicInOut,
+            7 => Self::SineInOut,
+            8 => Self::BackOut,
+            9 => Self::ElasticOut,
+            10 => Self::BounceOut,
+            _ => return None,
+        })
+    }
+
+    /// Eased progress for linear progress `t`, which is clamped to 0..=1.
+    /// Every curve starts at 0 and ends at 1.
+    pub fn apply(self, t: f32) -> f32 {
+        use std::f32::consts::PI;
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::QuadIn => t * t,
+            Self::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Self::QuadInOut => 1.0 - 2.0 * (1.0 - t) * (1.0 - t),
+            Self::CubicIn => t * t * t,
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Self::CubicInOut if t < 0.5 => 4.0 * t * t * t,
+            Self::CubicInOut => 1.0 - 4.0 * (1.0 - t).powi(3),
+            Self::SineInOut => 0.5 - 0.5 * (PI * t).cos(),
+            Self::BackOut => {
+                const C1: f32 = 1.70158;
+                let u = t - 1.0;
+                1.0 + (C1 + 1.0) * u * u * u + C1 * u * u
+            }
+            Self::ElasticOut if t == 0.0 || t == 1.0 => t,
+            Self::ElasticOut => 1.0 + 2f32.powf(-10.0 * t) * ((10.0 * t - 0.75) * (2.0 * PI / 3.0)).sin(),
+            Self::BounceOut => bounce_out(t),
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[repr(u32)]
+pub enum TweenProperty {
+    /// Drawn size multiplier of an entity
+    Scale = 0,
+    /// Rotation of an entity, radians
+    Rotation = 1,
+    /// RGB tint of an entity, 0..=1 per channel
+    Color = 2,
+    /// World height the camera shows
+    CameraZoom = 3,
+    /// World point the camera looks at
+    CameraPosition = 4,
+}
+
+impl TweenProperty {
+    pub fn from_u32(raw: u32) -> Option<Self> {
+        Some(match raw {
+            0 => Self::Scale,
+            1 => Self::Rotation,
+            2 => Self::Color,
+            3 => Self::CameraZoom,
+            4 => Self::CameraPosition,
+            _ => return None,
+        })
+    }
+
+    /// Number of values `from` and `to` hold
+    pub fn components(self) -> usize {
+        match self {
+            Self::Scale | Self::Rotation | Self::CameraZoom => 1,
+            Self::CameraPosition => 2,
+            Self::Color => 3,
+        }
+    }
+
+    /// Camera properties ignore the tween's target
+    pub fn is_camera(self) -> bool {
+        matches!(self, Self::CameraZoom | Self::CameraPosition)
+    }
+}
+
+/// One property easing between two values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween {
+    /// Entity id; 0 for camera properties
+    pub target: u64,
+    pub property: TweenProperty,
+    pub from: [f32; 4],
+    pub to: [f32; 4],
+    pub duration: f32,
+    pub delay: f32,
+    pub easing: Easing,
+    elapsed: f32,
+}
+
+impl Tween {
+    /// `from` and `to` must hold `property.components()` finite values;
+    /// `duration` and `delay` must be finite and not negative
+    pub fn new(
+        target: u64,
+        property: TweenProperty,
+        from: &[f32],
+        to: &[f32],
+        duration: f32,
+        delay: f32,
+        easing: Easing,
+    ) -> Result<Self, String> {
+        let n = property.components();
+        if from.len() != n || to.len() != n {
+            return Err(format!("{:?} takes {} values", property, n));
+        }
+        if !from.iter().chain(to).all(|v| v.is_finite()) {
+            return Err("tween values must be finite".into());
+        }
+        if !duration.is_finite() || !delay.is_finite() || duration < 0.0 || delay < 0.0 {
+            return Err("duration and delay must be finite and >= 0".into());
+        }
+        let mut tween = Self { target, property, from: [0.0; 4], to: [0.0; 4], duration, delay, easing, elapsed: 0.0 };
+        tween.from[..n].copy_from_slice(from);
+        tween.to[..n].copy_from_slice(to);
+        Ok(tween)
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if dt.is_finite() && dt > 0.0 {
+            self.elapsed = (self.elapsed + dt).min(self.delay + self.duration);
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.delay + self.duration
+    }
+
+    /// Current value; `from` until the delay is over
+    pub fn value(&self) -> [f32; 4] {
+        let t = match self.elapsed - self.delay {
+            run if run < 0.0 => 0.0,
+            _ if self.duration <= 0.0 => 1.0,
+            run => run / self.duration,
+        };
+        let eased = self.easing.apply(t);
+        std::array::from_fn(|i| self.from[i] + (self.to[i] - self.from[i]) * eased)
+    }
+}
+
+/// Latest camera values set by tweens, not yet applied
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CameraTweenValues {
+    pub zoom: Option<f32>,
+    pub position: Option<[f32; 2]>,
+}
+
+/// Running tweens by id
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Tweens {
+    tweens: Vec<(u64, Tween)>,
+    next_id: u64,
+    camera: CameraTweenValues,
+}
+
+impl Tweens {
+    /// Start `tween`, replacing any on the same target and property. Returns
+    /// its id, or None when `MAX_TWEENS` are running.
+    pub fn start(&mut self, tween: Tween) -> Option<u64> {
+        self.tweens.retain(|(_, t)| (t.target, t.property) != (tween.target, tween.property));
+        if self.tweens.len() == MAX_TWEENS {
+            return None;
+        }
+        self.next_id += 1;
+        self.tweens.push((self.next_id, tween));
+        Some(self.next_id)
+    }
+
+    /// Stop a tween where it is. Returns false for unknown ids.
+    pub fn cancel(&mut self, id: u64) -> bool {
+        let before = self.tweens.len();
+        self.tweens.retain(|(i, _)| *i != id);
+        self.tweens.len() != before
+    }
+
+    pub fn len(&self) -> usize {
+        self.tweens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tweens.is_empty()
+    }
+
+    /// Move every tween forward by `dt` and return (target, property, value)
+    /// for each. Finished tweens report their end value once and are dropped.
+    pub fn advance(&mut self, dt: f32) -> Vec<(u64, TweenProperty, [f32; 4])> {
+        let mut values = Vec::with_capacity(self.tweens.len());
+        self.tweens.retain_mut(|(_, tween)| {
+            tween.advance(dt);
+            values.push((tween.target, tween.property, tween.value()));
+            !tween.finished()
+        });
+        values
+    }
+
+    /// Take the camera values recorded since the last call
+    pub fn take_camera(&mut self) -> CameraTweenValues {
+        std::mem::take(&mut self.camera)
+    }
+}
+
+/// How an entity is drawn on top of its body
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Appearance {
+    /// Multiplies the drawn size
+    pub scale: f32,
+    /// Multiplies the texture color
+    pub tint: [f32; 3],
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self { scale: 1.0, tint: [1.0; 3] }
+    }
+}
+
+impl Appearance {
+    /// Tint as RGBA8 with red in the lowest byte, as `unpack4x8unorm` reads it
+    pub fn packed_tint(&self) -> u32 {
+        let [r, g, b] = self.tint.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        u32::from_le_bytes([r, g, b, 255])
+    }
+}
+
+/// Advance the tweens by this run's animation time and apply their values
+pub(crate) fn tween_system(world: &mut World) {
+    let dt = world.resource::<Time>().animation_delta;
+    let values = match world.get_resource_mut::<Tweens>() {
+        Some(mut tweens) if !tweens.is_empty() => tweens.advance(dt),
+        _ => return,
+    };
+    for (target, property, value) in values {
+        match property {
+            TweenProperty::CameraZoom => world.resource_mut::<Tweens>().camera.zoom = Some(value[0]),
+            TweenProperty::CameraPosition => {
+                world.resource_mut::<Tweens>().camera.position = Some([value[0], value[1]]);
+            }
+            _ => apply_to_entity(world, target, property, value),
+        }
+    }
+}
+
+fn apply_to_entity(world: &mut World, target: u64, property: TweenProperty, value: [f32; 4]) {
+    let Some(entity) = Entity::try_from_bits(target).ok().filter(|&e| world.entities().contains(e)) else {
+        return;
+    };
+    match property {
+        TweenProperty::Scale | TweenProperty::Color => {
+            let mut entity = world.entity_mut(entity);
+            let mut appearance = entity.get::<Appearance>().copied().unwrap_or_default();
+            if property == TweenProperty::Scale {
+                appearance.scale = value[0].max(0.0);
+            } else {
+                appearance.tint = [value[0], value[1], value[2]];
+            }
+            entity.insert(appearance);
+        }
+        TweenProperty::Rotation => match world.get::<PhysicsBody>(entity).copied() {
+            Some(body) => {
+                let mut rapier = world.resource_mut::<RapierContext>();
+                if let Some(rb) = rapier.rigid_body_set.get_mut(body.rigid_body_handle) {
+                    let rotation = Rotation::from_axis_angle(&Vector::z_axis(), to_real(value[0]));
+                    if rb.is_kinematic() {
+                        rb.set_next_kinematic_rotation(rotation);
+                    } else {
+                        rb.set_rotation(rotation, true);
+                    }
+                }
+            }
+            // Decorations have no body
+            None => {
+                if let Some(mut rotation) = world.get_mut::<crate::Rotation>(entity) {
+                    rotation.0 = value[0];
+                }
+            }
+        },
+        TweenProperty::CameraZoom | TweenProperty::CameraPosition => {}
+    }
+}
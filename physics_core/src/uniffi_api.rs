@@ -0,0 +1,1755 @@
+//! Typed Kotlin/Swift bindings through UniFFI (`uniffi_bindings` feature)
+//!
+//! `PhysicsEngine` is a handle onto the process-wide simulation that the C,
+//! JNI and WASM surfaces also drive; creating several handles does not create
+//! several worlds. Rendering lifecycle (`wgpu_init`/`wgpu_render`) stays on
+//! the platform surfaces, since it needs native window handles.
+//!
+//! Generate the bindings from a built library with
+//! `cargo run --features uniffi_bindings --bin uniffi-bindgen -- generate
+//! --library target/debug/libphysics_core.so --language kotlin --out-dir out`
+//! (`--language swift` for iOS); `uniffi.toml` holds the package names.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::solver_config::SolverConfig;
+use crate::text::TextAnchor;
+use crate::{background, governor, gpu_options, gravity, layers, scenes, stress, texture_sampling, PhysicsState, PHYSICS_STATE};
+
+#[derive(Debug, Clone, PartialEq, uniffi::Error)]
+pub enum PhysicsError {
+    /// No scene is running yet (`wgpu_init` has not been called)
+    NotInitialized,
+    InvalidArgument { reason: String },
+    UnknownEntity { id: u64 },
+    Script { message: String },
+}
+
+impl fmt::Display for PhysicsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhysicsError::NotInitialized => write!(f, "simulation not initialized"),
+            PhysicsError::InvalidArgument { reason } => write!(f, "invalid argument: {}", reason),
+            PhysicsError::UnknownEntity { id } => write!(f, "unknown entity {}", id),
+            PhysicsError::Script { message } => write!(f, "script error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PhysicsError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum StressLayout {
+    Pyramid,
+    Stack,
+    Rain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum GravityPreset {
+    Earth,
+    Moon,
+    ZeroG,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ResetMode {
+    Scene,
+    Pristine,
+    Demo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum BackgroundFit {
+    Fill,
+    Fit,
+    Stretch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+}
+
+/// Background layers are drawn first and foreground layers last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum LayerKind {
+    Background,
+    World,
+    Foreground,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum TextureWrap {
+    Clamp,
+    Repeat,
+    Mirror,
+}
+
+/// `ProcessInfo.ThermalState`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ThermalState {
+    Nominal,
+    Fair,
+    Serious,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum BodyType {
+    Dynamic,
+    Fixed,
+    Kinematic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum TriggerKind {
+    Sensor,
+    Goal,
+    Hazard,
+}
+
+/// See `triggers::Outcome`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Outcome {
+    Playing,
+    Won,
+    Lost,
+}
+
+impl From<crate::triggers::Outcome> for Outcome {
+    fn from(outcome: crate::triggers::Outcome) -> Self {
+        match outcome {
+            crate::triggers::Outcome::Playing => Outcome::Playing,
+            crate::triggers::Outcome::Won => Outcome::Won,
+            crate::triggers::Outcome::Lost => Outcome::Lost,
+        }
+    }
+}
+
+/// See `tween::TweenProperty`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum TweenProperty {
+    Scale,
+    Rotation,
+    Color,
+    CameraZoom,
+    CameraPosition,
+}
+
+impl From<TweenProperty> for crate::tween::TweenProperty {
+    fn from(property: TweenProperty) -> Self {
+        match property {
+            TweenProperty::Scale => Self::Scale,
+            TweenProperty::Rotation => Self::Rotation,
+            TweenProperty::Color => Self::Color,
+            TweenProperty::CameraZoom => Self::CameraZoom,
+            TweenProperty::CameraPosition => Self::CameraPosition,
+        }
+    }
+}
+
+/// See `tween::Easing`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+    BackOut,
+    ElasticOut,
+    BounceOut,
+}
+
+impl From<Easing> for crate::tween::Easing {
+    fn from(easing: Easing) -> Self {
+        match easing {
+            Easing::Linear => Self::Linear,
+            Easing::QuadIn => Self::QuadIn,
+            Easing::QuadOut => Self::QuadOut,
+            Easing::QuadInOut => Self::QuadInOut,
+            Easing::CubicIn => Self::CubicIn,
+            Easing::CubicOut => Self::CubicOut,
+            Easing::CubicInOut => Self::CubicInOut,
+            Easing::SineInOut => Self::SineInOut,
+            Easing::BackOut => Self::BackOut,
+            Easing::ElasticOut => Self::ElasticOut,
+            Easing::BounceOut => Self::BounceOut,
+        }
+    }
+}
+
+/// Where a text label sits; see `crate::text`
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Enum)]
+pub enum TextPlacement {
+    /// Top-left corner in pixels, origin top-left; size in pixels
+    Screen { x: f32, y: f32 },
+    /// Centered above a world point; size in world units
+    World { x: f32, y: f32 },
+    /// Like `World`, offset from the entity's center
+    Entity { id: u64, offset_x: f32, offset_y: f32 },
+}
+
+impl From<BodyType> for crate::rapier::prelude::RigidBodyType {
+    fn from(body_type: BodyType) -> Self {
+        match body_type {
+            BodyType::Dynamic => Self::Dynamic,
+            BodyType::Fixed => Self::Fixed,
+            BodyType::Kinematic => Self::KinematicPositionBased,
+        }
+    }
+}
+
+impl From<GravityPreset> for gravity::GravityPreset {
+    fn from(preset: GravityPreset) -> Self {
+        match preset {
+            GravityPreset::Earth => gravity::GravityPreset::Earth,
+            GravityPreset::Moon => gravity::GravityPreset::Moon,
+            GravityPreset::ZeroG => gravity::GravityPreset::ZeroG,
+        }
+    }
+}
+
+impl From<ResetMode> for scenes::ResetMode {
+    fn from(mode: ResetMode) -> Self {
+        match mode {
+            ResetMode::Scene => scenes::ResetMode::Scene,
+            ResetMode::Pristine => scenes::ResetMode::Pristine,
+            ResetMode::Demo => scenes::ResetMode::Demo,
+        }
+    }
+}
+
+impl From<BackgroundFit> for background::BackgroundFit {
+    fn from(fit: BackgroundFit) -> Self {
+        match fit {
+            BackgroundFit::Fill => background::BackgroundFit::Fill,
+            BackgroundFit::Fit => background::BackgroundFit::Fit,
+            BackgroundFit::Stretch => background::BackgroundFit::Stretch,
+        }
+    }
+}
+
+impl From<TextureFilter> for texture_sampling::TextureFilter {
+    fn from(filter: TextureFilter) -> Self {
+        match filter {
+            TextureFilter::Nearest => texture_sampling::TextureFilter::Nearest,
+            TextureFilter::Linear => texture_sampling::TextureFilter::Linear,
+        }
+    }
+}
+
+impl From<TextureWrap> for texture_sampling::TextureWrap {
+    fn from(wrap: TextureWrap) -> Self {
+        match wrap {
+            TextureWrap::Clamp => texture_sampling::TextureWrap::Clamp,
+            TextureWrap::Repeat => texture_sampling::TextureWrap::Repeat,
+            TextureWrap::Mirror => texture_sampling::TextureWrap::Mirror,
+        }
+    }
+}
+
+impl From<LayerKind> for layers::LayerKind {
+    fn from(kind: LayerKind) -> Self {
+        match kind {
+            LayerKind::Background => layers::LayerKind::Background,
+            LayerKind::World => layers::LayerKind::World,
+            LayerKind::Foreground => layers::LayerKind::Foreground,
+        }
+    }
+}
+
+impl From<ThermalState> for governor::ThermalStatus {
+    fn from(state: ThermalState) -> Self {
+        match state {
+            ThermalState::Nominal => governor::ThermalStatus::None,
+            ThermalState::Fair => governor::ThermalStatus::Light,
+            ThermalState::Serious => governor::ThermalStatus::Severe,
+            ThermalState::Critical => governor::ThermalStatus::Critical,
+        }
+    }
+}
+
+impl From<StressLayout> for stress::StressLayout {
+    fn from(layout: StressLayout) -> Self {
+        match layout {
+            StressLayout::Pyramid => stress::StressLayout::Pyramid,
+            StressLayout::Stack => stress::StressLayout::Stack,
+            StressLayout::Rain => stress::StressLayout::Rain,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum PointerAction {
+    Down,
+    Move,
+    Up,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum TouchAction {
+    Down,
+    Move,
+    Up,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum KeyAction {
+    Down,
+    Up,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Backend {
+    Vulkan,
+    Metal,
+    Dx12,
+    Gl,
+    BrowserWebGpu,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum PowerPreference {
+    None,
+    LowPower,
+    HighPerformance,
+}
+
+/// Adapter selection for the next `wgpu_init`
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct GpuInitOptions {
+    /// Empty selects every backend
+    pub backends: Vec<Backend>,
+    pub power_preference: PowerPreference,
+    pub force_fallback_adapter: bool,
+}
+
+/// See `solver_config::SolverConfig`; `erp` 0 keeps Rapier's default stiffness
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Record)]
+pub struct SolverSettings {
+    pub solver_iterations: u32,
+    pub friction_iterations: u32,
+    pub internal_pgs_iterations: u32,
+    pub erp: f32,
+    pub allowed_linear_error: f32,
+    pub max_ccd_substeps: u32,
+    pub contact_damping: f32,
+}
+
+impl From<SolverConfig> for SolverSettings {
+    fn from(c: SolverConfig) -> Self {
+        Self {
+            solver_iterations: c.solver_iterations,
+            friction_iterations: c.friction_iterations,
+            internal_pgs_iterations: c.internal_pgs_iterations,
+            erp: c.erp,
+            allowed_linear_error: c.allowed_linear_error,
+            max_ccd_substeps: c.max_ccd_substeps,
+            contact_damping: c.contact_damping,
+        }
+    }
+}
+
+impl From<SolverSettings> for SolverConfig {
+    fn from(s: SolverSettings) -> Self {
+        Self {
+            solver_iterations: s.solver_iterations,
+            friction_iterations: s.friction_iterations,
+            internal_pgs_iterations: s.internal_pgs_iterations,
+            erp: s.erp,
+            allowed_linear_error: s.allowed_linear_error,
+            max_ccd_substeps: s.max_ccd_substeps,
+            contact_damping: s.contact_damping,
+        }
+    }
+}
+
+/// A world-space point
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Record)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Record)]
+pub struct BodyState {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub rotation: f32,
+    pub sleeping: bool,
+}
+
+/// See `projectiles::ProjectileStats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct ProjectileStats {
+    pub capacity: u32,
+    pub active: u32,
+    pub peak_active: u32,
+    pub fired: u64,
+    pub expired: u64,
+    pub hit: u64,
+    pub dropped: u64,
+}
+
+impl From<crate::projectiles::ProjectileStats> for ProjectileStats {
+    fn from(s: crate::projectiles::ProjectileStats) -> Self {
+        Self {
+            capacity: s.capacity,
+            active: s.active,
+            peak_active: s.peak_active,
+            fired: s.fired,
+            expired: s.expired,
+            hit: s.hit,
+            dropped: s.dropped,
+        }
+    }
+}
+
+/// See `audio::AudioEvent`
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Record)]
+pub struct AudioEvent {
+    pub entity_a: u64,
+    pub entity_b: u64,
+    pub material_a: u32,
+    pub material_b: u32,
+    pub x: f32,
+    pub y: f32,
+    pub impact_speed: f32,
+    pub impulse: f32,
+    pub volume: f32,
+    pub pitch: f32,
+    pub pan: f32,
+    pub distance: f32,
+}
+
+impl From<crate::audio::AudioEvent> for AudioEvent {
+    fn from(e: crate::audio::AudioEvent) -> Self {
+        Self {
+            entity_a: e.entity_a,
+            entity_b: e.entity_b,
+            material_a: e.material_a,
+            material_b: e.material_b,
+            x: e.x,
+            y: e.y,
+            impact_speed: e.impact_speed,
+            impulse: e.impulse,
+            volume: e.volume,
+            pitch: e.pitch,
+            pan: e.pan,
+            distance: e.distance,
+        }
+    }
+}
+
+/// See `fracture::FractureConfig`
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Record)]
+pub struct FractureConfig {
+    pub threshold: f32,
+    pub fragments: u32,
+    pub min_half_extent: f32,
+    pub particles: u32,
+}
+
+impl From<FractureConfig> for crate::fracture::FractureConfig {
+    fn from(c: FractureConfig) -> Self {
+        Self {
+            threshold: c.threshold,
+            fragments: c.fragments,
+            min_half_extent: c.min_half_extent,
+            particles: c.particles,
+        }
+    }
+}
+
+/// See `fracture::FractureEvent`
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Record)]
+pub struct FractureEvent {
+    pub entity: u64,
+    pub x: f32,
+    pub y: f32,
+    pub impulse: f32,
+    pub half_extent: f32,
+    pub fragments: u32,
+    pub fragment_half_extent: f32,
+}
+
+impl From<crate::fracture::FractureEvent> for FractureEvent {
+    fn from(e: crate::fracture::FractureEvent) -> Self {
+        Self {
+            entity: e.entity,
+            x: e.x,
+            y: e.y,
+            impulse: e.impulse,
+            half_extent: e.half_extent,
+            fragments: e.fragments,
+            fragment_half_extent: e.fragment_half_extent,
+        }
+    }
+}
+
+/// See `triggers::TriggerEvent`
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Record)]
+pub struct TriggerEvent {
+    pub entity: u64,
+    pub zone: u32,
+    pub kind: TriggerKind,
+    pub entered: bool,
+}
+
+impl From<crate::triggers::TriggerEvent> for TriggerEvent {
+    fn from(e: crate::triggers::TriggerEvent) -> Self {
+        let kind = match e.kind {
+            1 => TriggerKind::Goal,
+            2 => TriggerKind::Hazard,
+            _ => TriggerKind::Sensor,
+        };
+        Self { entity: e.entity, zone: e.zone, kind, entered: e.entered }
+    }
+}
+
+#[derive(uniffi::Object)]
+pub struct PhysicsEngine {}
+
+fn with_physics<R>(f: impl FnOnce(&mut PhysicsState) -> R) -> Result<R, PhysicsError> {
+    let mut guard = PHYSICS_STATE.lock().map_err(|_| PhysicsError::NotInitialized)?;
+    guard.0.as_mut().map(f).ok_or(PhysicsError::NotInitialized)
+}
+
+fn ensure_running() -> Result<(), PhysicsError> {
+    with_physics(|_| ())
+}
+
+fn require_finite(name: &str, values: &[f32]) -> Result<(), PhysicsError> {
+    if values.iter().all(|v| v.is_finite()) {
+        Ok(())
+    } else {
+        Err(PhysicsError::InvalidArgument { reason: format!("{} must be finite", name) })
+    }
+}
+
+fn valid_fracture(fracture: FractureConfig) -> Result<crate::fracture::FractureConfig, PhysicsError> {
+    crate::fracture::FractureConfig::from(fracture).validated().ok_or_else(|| PhysicsError::InvalidArgument {
+        reason: "fracture threshold and min_half_extent must be finite and > 0".into(),
+    })
+}
+
+#[uniffi::export]
+impl PhysicsEngine {
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {})
+    }
+
+    pub fn info(&self) -> String {
+        crate::get_internal_info()
+    }
+
+    pub fn is_running(&self) -> bool {
+        ensure_running().is_ok()
+    }
+
+    pub fn set_init_options(&self, options: GpuInitOptions) {
+        let backends = options.backends.iter().fold(0, |bits, backend| {
+            bits | match backend {
+                Backend::Vulkan => gpu_options::BACKEND_VULKAN,
+                Backend::Metal => gpu_options::BACKEND_METAL,
+                Backend::Dx12 => gpu_options::BACKEND_DX12,
+                Backend::Gl => gpu_options::BACKEND_GL,
+                Backend::BrowserWebGpu => gpu_options::BACKEND_BROWSER_WEBGPU,
+            }
+        });
+        let power_preference = match options.power_preference {
+            PowerPreference::None => gpu_options::POWER_PREFERENCE_NONE,
+            PowerPreference::LowPower => gpu_options::POWER_PREFERENCE_LOW_POWER,
+            PowerPreference::HighPerformance => gpu_options::POWER_PREFERENCE_HIGH_PERFORMANCE,
+        };
+        crate::set_gpu_init_options_internal(backends, power_preference, options.force_fallback_adapter);
+    }
+
+    /// FPS, CPU frame time and per-pass GPU times as JSON
+    pub fn frame_stats(&self) -> Result<String, PhysicsError> {
+        crate::frame_stats_internal().ok_or(PhysicsError::NotInitialized)
+    }
+
+    /// Adapter name, backend, surface formats and limits as JSON
+    pub fn adapter_info(&self) -> Result<String, PhysicsError> {
+        crate::adapter_info_internal().ok_or(PhysicsError::NotInitialized)
+    }
+
+    // --- Simulation controls ---
+
+    pub fn set_gravity(&self, x: f32, y: f32, z: f32) -> Result<(), PhysicsError> {
+        require_finite("gravity", &[x, y, z])?;
+        ensure_running()?;
+        crate::set_gravity_vector_internal(x, y, z);
+        Ok(())
+    }
+
+    pub fn set_gravity_preset(&self, preset: GravityPreset) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        let [x, y, z] = gravity::GravityPreset::from(preset).gravity();
+        crate::set_gravity_vector_internal(x, y, z);
+        Ok(())
+    }
+
+    pub fn set_tilt_gravity(&self, enabled: bool, strength: f32) {
+        crate::set_tilt_gravity_internal(enabled, strength);
+    }
+
+    /// Accelerometer sample in device axes (m/s², gravity reaction included)
+    /// with the display rotation in degrees
+    pub fn on_accelerometer(&self, x: f32, y: f32, z: f32, rotation_degrees: i32) {
+        crate::on_accelerometer_internal(x, y, z, rotation_degrees);
+    }
+
+    /// Drop `body_count` boxes and spheres onto a ground plane in full 3D,
+    /// or go back to the 2D default scene; returns the bodies dropped
+    pub fn set_3d_mode(&self, enabled: bool, body_count: u32) -> Result<u32, PhysicsError> {
+        crate::set_3d_mode_internal(enabled, body_count)
+            .map(|count| count as u32)
+            .map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Orbit the 3D demo's camera by a drag in pixels and zoom it; false
+    /// while the demo is not running
+    pub fn orbit_3d_camera(&self, dx: f32, dy: f32, zoom: f32) -> bool {
+        crate::orbit_3d_camera_internal(dx, dy, zoom)
+    }
+
+    pub fn set_time_scale(&self, scale: f32) -> Result<(), PhysicsError> {
+        if !scale.is_finite() || scale < 0.0 {
+            return Err(PhysicsError::InvalidArgument { reason: "time scale must be >= 0".into() });
+        }
+        with_physics(|physics| {
+            physics.time_scale = scale;
+            physics.time_ramp = None;
+        })
+    }
+
+    pub fn set_time_scale_ramp(&self, target: f32, seconds: f32) -> Result<(), PhysicsError> {
+        require_finite("time scale ramp", &[target, seconds])?;
+        ensure_running()?;
+        crate::set_time_scale_ramp_internal(target, seconds);
+        Ok(())
+    }
+
+    /// From `applicationWillResignActive`; stops rendering, and stepping too
+    /// with `freeze_physics`, keeping all state (see `lifecycle`)
+    pub fn on_app_pause(&self, freeze_physics: bool) {
+        crate::on_app_pause_internal(freeze_physics);
+    }
+
+    /// From `applicationDidBecomeActive`
+    pub fn on_app_resume(&self) {
+        crate::on_app_resume_internal();
+    }
+
+    pub fn set_paused(&self, paused: bool) -> Result<(), PhysicsError> {
+        with_physics(|physics| physics.paused = paused)
+    }
+
+    pub fn set_animation_paused(&self, paused: bool) -> Result<(), PhysicsError> {
+        with_physics(|physics| physics.animation_paused = paused)
+    }
+
+    pub fn step_once(&self) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::step_once_internal();
+        Ok(())
+    }
+
+    /// Returns the number of steps actually run
+    pub fn fast_forward(&self, steps: u32) -> Result<u32, PhysicsError> {
+        ensure_running()?;
+        Ok(crate::fast_forward_internal(steps))
+    }
+
+    /// Returns the number of steps actually rewound
+    pub fn rewind(&self, steps: u32) -> Result<u32, PhysicsError> {
+        ensure_running()?;
+        Ok(crate::rewind_internal(steps))
+    }
+
+    pub fn set_rewind_history(&self, capacity: u32) {
+        crate::set_rewind_history_internal(capacity);
+    }
+
+    /// Rebuild the scene last switched to with its persistent bodies
+    pub fn reset(&self) {
+        crate::reset_simulation_internal(scenes::ResetMode::Scene);
+    }
+
+    pub fn reset_to(&self, mode: ResetMode) {
+        crate::reset_simulation_internal(mode.into());
+    }
+
+    /// Only boxes can persist. A reset rebuilds them under new ids, see
+    /// `persistent_id_after_reset`.
+    pub fn set_persistent(&self, id: u64, persistent: bool) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_persistent_internal(id, persistent)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Id the latest reset rebuilt the persistent body `id` under
+    pub fn persistent_id_after_reset(&self, id: u64) -> Option<u64> {
+        crate::persistent_id_after_reset_internal(id)
+    }
+
+    pub fn spawn_stress_scene(&self, n_bodies: u32, layout: StressLayout) {
+        let layout: stress::StressLayout = layout.into();
+        crate::spawn_stress_scene_internal(n_bodies, layout as i32);
+    }
+
+    /// Out-of-range values are clamped
+    pub fn set_solver_config(&self, settings: SolverSettings) -> Result<(), PhysicsError> {
+        crate::set_solver_config_internal(settings.into())
+            .then_some(())
+            .ok_or(PhysicsError::NotInitialized)
+    }
+
+    /// Static level geometry as a JSON `LevelDesc` object; returns the collider count
+    pub fn load_level_json(&self, json: String) -> Result<u32, PhysicsError> {
+        ensure_running()?;
+        crate::load_level_json_internal(&json)
+            .map(|count| count as u32)
+            .map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// `value` 0 clears the cell; `n` draws atlas cell `n - 1`
+    pub fn set_tile(&self, layer: u32, column: u32, row: u32, value: u8) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_tile_internal(layer, column, row, value)
+            .then_some(())
+            .ok_or_else(|| PhysicsError::InvalidArgument { reason: format!("no tile ({}, {}) in layer {}", column, row, layer) })
+    }
+
+    pub fn get_tile(&self, layer: u32, column: u32, row: u32) -> Option<u8> {
+        crate::get_tile_internal(layer, column, row)
+    }
+
+    /// Wind, force volumes and water as a JSON `ForceFieldConfig` object
+    pub fn set_force_fields_json(&self, json: String) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_force_fields_json_internal(&json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Water regions as a JSON `WaterConfig` object
+    pub fn set_water_json(&self, json: String) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_water_json_internal(&json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Slow-motion zones as a JSON `TimeDilationConfig` object
+    pub fn set_time_dilation_json(&self, json: String) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_time_dilation_json_internal(&json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Split-screen players as a JSON `SplitScreen` object, or `null` for
+    /// one camera
+    pub fn set_split_screen_json(&self, json: String) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_split_screen_json_internal(&json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Player 1 or 2 controls the body; 0 lets both players control it
+    pub fn set_player(&self, id: u64, player: u8) -> Result<(), PhysicsError> {
+        if player > 2 {
+            return Err(PhysicsError::InvalidArgument { reason: format!("no player {}", player) });
+        }
+        crate::set_player_internal(id, player).then_some(()).ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Every body's state for a remote simulation, keeping only what changed
+    /// since `baseline_tick`, a tick returned by the remote `apply_snapshot`
+    /// (0 for everything)
+    pub fn get_snapshot_delta(&self, baseline_tick: u64) -> Result<Vec<u8>, PhysicsError> {
+        crate::snapshot_delta_internal(baseline_tick).ok_or(PhysicsError::NotInitialized)
+    }
+
+    /// Apply a remote snapshot; returns its tick to send back as the next
+    /// baseline
+    pub fn apply_snapshot(&self, bytes: Vec<u8>) -> Result<u64, PhysicsError> {
+        ensure_running()?;
+        crate::apply_snapshot_internal(&bytes).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Keep `window` steps (0 turns rollback off) of input and predicted
+    /// states; predictions within `tolerance` stand
+    pub fn set_prediction(&self, window: u32, tolerance: f32) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        if !crate::set_prediction_internal(window, tolerance) {
+            return Err(PhysicsError::InvalidArgument { reason: format!("invalid rollback window {} or tolerance {}", window, tolerance) });
+        }
+        Ok(())
+    }
+
+    pub fn set_predicted(&self, id: u64, predicted: bool) -> Result<(), PhysicsError> {
+        crate::set_predicted_internal(id, predicted).then_some(()).ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Apply an authoritative snapshot, rolling back and re-simulating if a
+    /// predicted body was off; returns its tick
+    pub fn reconcile_snapshot(&self, bytes: Vec<u8>) -> Result<u64, PhysicsError> {
+        ensure_running()?;
+        crate::reconcile_snapshot_internal(&bytes).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Record frames at a fixed step as numbered PNGs, from a JSON
+    /// `CaptureConfig` object with a `directory`
+    pub fn start_capture_json(&self, json: String) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::start_capture_json_internal(&json, None).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Returns the number of frames captured
+    pub fn stop_capture(&self) -> u64 {
+        crate::capture::stop()
+    }
+
+    /// Safe-area insets in surface pixels (points times the screen scale);
+    /// `inset_world` keeps the scene out from under them too
+    pub fn set_safe_area_insets(&self, left: f32, top: f32, right: f32, bottom: f32, inset_world: bool) -> Result<(), PhysicsError> {
+        crate::set_safe_area_insets_internal(left, top, right, bottom, inset_world)
+            .then_some(())
+            .ok_or_else(|| PhysicsError::InvalidArgument { reason: "insets must be finite and not negative".into() })
+    }
+
+    /// The screen scale (`UIScreen.scale`), for the egui scale, the tap slop
+    /// and the 3D orbit rate; pointer positions stay in pixels
+    pub fn set_scale_factor(&self, scale_factor: f32) -> Result<(), PhysicsError> {
+        crate::set_scale_factor_internal(scale_factor)
+            .then_some(())
+            .ok_or_else(|| PhysicsError::InvalidArgument { reason: "scale factor must be finite and above 0".into() })
+    }
+
+    /// `UIDevice.batteryLevel` (None when unknown), whether the battery
+    /// charges, `ProcessInfo.thermalState` and `isLowPowerModeEnabled`, for
+    /// the performance governor (see `governor`)
+    pub fn set_power_state(&self, battery: Option<f32>, charging: bool, thermal: ThermalState, low_power_mode: bool) -> Result<(), PhysicsError> {
+        let power = governor::PowerState::new(battery.unwrap_or(-1.0), charging, thermal.into(), low_power_mode)
+            .ok_or_else(|| PhysicsError::InvalidArgument { reason: "battery must be from 0 to 1".into() })?;
+        crate::set_power_internal(power);
+        Ok(())
+    }
+
+    pub fn set_governor_enabled(&self, enabled: bool) {
+        crate::set_governor_enabled_internal(enabled);
+    }
+
+    /// Power state, throttle level and the limits in force as JSON
+    pub fn governor_stats(&self) -> String {
+        crate::governor_stats_internal()
+    }
+
+    /// `pixels` is `width * height` RGBA8 pixels (e.g. a camera frame),
+    /// shown behind the scene until replaced or cleared
+    pub fn set_background_rgba(&self, pixels: Vec<u8>, width: u32, height: u32) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_background_internal(width, height, width, background::PixelLayout::Rgba8, &pixels).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    pub fn clear_background(&self) {
+        crate::clear_background_internal();
+    }
+
+    pub fn set_background_fit(&self, fit: BackgroundFit) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_background_fit_internal(fit.into()).then_some(()).ok_or(PhysicsError::NotInitialized)
+    }
+
+    /// Render and present a frame now and return it as a PNG
+    pub fn take_screenshot(&self) -> Result<Vec<u8>, PhysicsError> {
+        ensure_running()?;
+        crate::take_screenshot_internal().map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Write the next frame presented to `path` as a PNG
+    pub fn save_screenshot(&self, path: String) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::request_screenshot_internal(crate::capture::ScreenshotTarget::File(path.into())).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Looping GIF of the last `seconds` of the rewind history at `fps`,
+    /// `scale` times the surface size
+    pub fn export_clip(&self, seconds: f32, fps: u32, scale: f32) -> Result<Vec<u8>, PhysicsError> {
+        ensure_running()?;
+        crate::export_clip_internal(seconds, fps, scale).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// `auto` raises the count above `velocity_threshold` m/s, up to `max_substeps`
+    pub fn set_substeps(&self, substeps: u32, auto: bool, velocity_threshold: f32, max_substeps: u32) -> Result<(), PhysicsError> {
+        let config = crate::substep::SubstepConfig { substeps, auto, velocity_threshold, max_substeps };
+        crate::set_substeps_internal(config)
+            .then_some(())
+            .ok_or(PhysicsError::NotInitialized)
+    }
+
+    pub fn solver_config(&self) -> Result<SolverSettings, PhysicsError> {
+        crate::solver_config_internal().map(Into::into).ok_or(PhysicsError::NotInitialized)
+    }
+
+    pub fn state_checksum(&self) -> Result<u64, PhysicsError> {
+        ensure_running()?;
+        Ok(crate::state_checksum_internal())
+    }
+
+    // --- Bodies ---
+
+    pub fn spawn_box(&self, x: f32, y: f32, half_extent: f32) -> Result<u64, PhysicsError> {
+        require_finite("position", &[x, y])?;
+        if !half_extent.is_finite() || half_extent <= 0.0 {
+            return Err(PhysicsError::InvalidArgument { reason: "half_extent must be > 0".into() });
+        }
+        crate::spawn_body_internal(x, y, half_extent).ok_or(PhysicsError::NotInitialized)
+    }
+
+    /// A box of any body type; a nonzero surface velocity makes it a conveyor
+    pub fn spawn_box_with(
+        &self,
+        x: f32,
+        y: f32,
+        half_extent: f32,
+        body_type: BodyType,
+        surface_vx: f32,
+        surface_vy: f32,
+    ) -> Result<u64, PhysicsError> {
+        require_finite("position", &[x, y])?;
+        require_finite("surface velocity", &[surface_vx, surface_vy])?;
+        if !half_extent.is_finite() || half_extent <= 0.0 {
+            return Err(PhysicsError::InvalidArgument { reason: "half_extent must be > 0".into() });
+        }
+        let surface_velocity = [surface_vx, surface_vy];
+        crate::spawn_body_with_internal(x, y, half_extent, body_type.into(), surface_velocity, crate::groups::DEFAULT)
+            .ok_or(PhysicsError::NotInitialized)
+    }
+
+    /// Spawn a body shaped like the outline `points` (x, y pairs relative to
+    /// (x, y), concave or not), split into convex pieces
+    pub fn spawn_polygon(&self, x: f32, y: f32, points: Vec<f32>, body_type: BodyType) -> Result<u64, PhysicsError> {
+        ensure_running()?;
+        require_finite("position", &[x, y])?;
+        let outline: Vec<[f32; 2]> = points.chunks_exact(2).map(|p| [p[0], p[1]]).collect();
+        crate::spawn_polygon_internal(x, y, &outline, body_type.into(), crate::groups::DEFAULT)
+            .map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    pub fn set_surface_velocity(&self, id: u64, vx: f32, vy: f32) -> Result<(), PhysicsError> {
+        require_finite("surface velocity", &[vx, vy])?;
+        crate::set_surface_velocity_internal(id, vx, vy)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    pub fn entity_ids(&self) -> Vec<u64> {
+        crate::entity_ids_internal()
+    }
+
+    pub fn is_entity_alive(&self, id: u64) -> bool {
+        crate::is_entity_alive_internal(id)
+    }
+
+    pub fn reset_count(&self) -> u32 {
+        crate::reset_count_internal()
+    }
+
+    // --- Trails ---
+
+    /// Fading trail through the body's last `length` positions (2..=1024) in
+    /// `color` (0xRRGGBBAA); calling it again restyles the trail
+    pub fn enable_trail(&self, id: u64, length: u32, color: u32) -> Result<(), PhysicsError> {
+        crate::enable_trail_internal(id, length, crate::text::unpack_rgba(color))
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Returns false if the body had no trail
+    pub fn disable_trail(&self, id: u64) -> Result<bool, PhysicsError> {
+        ensure_running()?;
+        Ok(crate::disable_trail_internal(id))
+    }
+
+    // --- Trajectory Prediction ---
+
+    /// Where the body goes over up to `steps` fixed steps (at most 600) if
+    /// launched at (vx, vy) now; the simulation is untouched. `show` also
+    /// draws the arc as dots until the next prediction or
+    /// `clear_trajectory_preview`.
+    pub fn predict_trajectory(&self, id: u64, vx: f32, vy: f32, steps: u32, show: bool) -> Result<Vec<Point>, PhysicsError> {
+        require_finite("launch velocity", &[vx, vy])?;
+        ensure_running()?;
+        let points = crate::predict_trajectory_internal(id, vx, vy, steps, show).ok_or(PhysicsError::UnknownEntity { id })?;
+        Ok(points.into_iter().map(|[x, y]| Point { x, y }).collect())
+    }
+
+    pub fn clear_trajectory_preview(&self) {
+        crate::clear_trajectory_preview_internal();
+    }
+
+    // --- Slingshot ---
+
+    /// Press on a dynamic body, drag to aim with the predicted arc drawn and
+    /// release to launch it; drag flings are off meanwhile
+    pub fn enable_slingshot_mode(&self, enabled: bool) {
+        crate::enable_slingshot_mode_internal(enabled);
+    }
+
+    /// `max_impulse` (N·s) at a pull of `max_pull` m; `arc_samples`
+    /// predicted steps are drawn while aiming (0 hides the arc)
+    pub fn set_slingshot_params(&self, max_impulse: f32, max_pull: f32, arc_samples: u32) -> Result<(), PhysicsError> {
+        crate::set_slingshot_params_internal(max_impulse, max_pull, arc_samples)
+            .then_some(())
+            .ok_or(PhysicsError::InvalidArgument { reason: "max_impulse and max_pull must be finite and > 0".into() })
+    }
+
+    // --- Contact Filtering ---
+
+    /// Bodies pass through a one-way platform from below and land on its top
+    pub fn set_one_way(&self, id: u64, enabled: bool) -> Result<(), PhysicsError> {
+        crate::set_one_way_internal(id, enabled)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Let two bodies pass through each other; returns false if already ignored
+    pub fn ignore_collision(&self, id_a: u64, id_b: u64) -> Result<bool, PhysicsError> {
+        if id_a == id_b {
+            return Err(PhysicsError::InvalidArgument { reason: "a body cannot ignore itself".into() });
+        }
+        ensure_running()?;
+        Ok(crate::set_collision_ignored_internal(id_a, id_b, true))
+    }
+
+    /// Returns false if the pair was not ignored
+    pub fn restore_collision(&self, id_a: u64, id_b: u64) -> Result<bool, PhysicsError> {
+        ensure_running()?;
+        Ok(crate::set_collision_ignored_internal(id_a, id_b, false))
+    }
+
+    // --- Lifetimes ---
+
+    /// Despawn the body after `seconds`; None cancels a pending lifetime
+    pub fn set_lifetime(&self, id: u64, seconds: Option<f32>) -> Result<(), PhysicsError> {
+        if let Some(seconds) = seconds {
+            if !seconds.is_finite() || seconds <= 0.0 {
+                return Err(PhysicsError::InvalidArgument { reason: "lifetime must be > 0 seconds".into() });
+            }
+        }
+        crate::set_lifetime_internal(id, seconds.unwrap_or(0.0))
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Despawn dynamic bodies leaving the rectangle; `enabled` false stops it
+    pub fn set_despawn_bounds(
+        &self,
+        enabled: bool,
+        min_x: f32,
+        min_y: f32,
+        max_x: f32,
+        max_y: f32,
+    ) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_despawn_bounds_internal(enabled, min_x, min_y, max_x, max_y)
+            .then_some(())
+            .ok_or(PhysicsError::InvalidArgument { reason: "bounds must be a finite, non-empty rectangle".into() })
+    }
+
+    // --- Hierarchy ---
+
+    /// Quad without a body, for attaching to one; returns its entity id
+    pub fn spawn_decoration(&self, x: f32, y: f32, half_extent: f32, material_id: u32) -> Result<u64, PhysicsError> {
+        require_finite("decoration", &[x, y, half_extent])?;
+        if half_extent <= 0.0 {
+            return Err(PhysicsError::InvalidArgument { reason: "half_extent must be > 0".into() });
+        }
+        crate::spawn_decoration_internal(x, y, half_extent, material_id).ok_or(PhysicsError::NotInitialized)
+    }
+
+    pub fn remove_decoration(&self, id: u64) -> Result<(), PhysicsError> {
+        crate::remove_decoration_internal(id)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// `child` follows `parent` at the offset, in the parent's frame
+    pub fn attach_entity(
+        &self,
+        child: u64,
+        parent: u64,
+        offset_x: f32,
+        offset_y: f32,
+        rotation: f32,
+    ) -> Result<(), PhysicsError> {
+        require_finite("attachment offset", &[offset_x, offset_y, rotation])?;
+        ensure_running()?;
+        crate::attach_entity_internal(child, parent, offset_x, offset_y, rotation)
+            .then_some(())
+            .ok_or(PhysicsError::InvalidArgument {
+                reason: format!("cannot attach {} to {}: unknown entity or loop", child, parent),
+            })
+    }
+
+    pub fn detach_entity(&self, child: u64) -> Result<(), PhysicsError> {
+        crate::detach_entity_internal(child)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id: child })
+    }
+
+    // --- Audio ---
+
+    /// Queue an event for impacts between `min_speed` and `max_speed` (full
+    /// loudness); disabling drops anything still queued
+    pub fn set_audio_events(&self, enabled: bool, min_speed: f32, max_speed: f32) -> Result<(), PhysicsError> {
+        require_finite("audio speeds", &[min_speed, max_speed])?;
+        if min_speed < 0.0 || min_speed >= max_speed {
+            return Err(PhysicsError::InvalidArgument { reason: "audio speeds need 0 <= min < max".into() });
+        }
+        ensure_running()?;
+        crate::set_audio_events_internal(enabled, min_speed, max_speed)
+            .then_some(())
+            .ok_or(PhysicsError::NotInitialized)
+    }
+
+    pub fn set_audio_listener(&self, x: f32, y: f32) -> Result<(), PhysicsError> {
+        require_finite("listener", &[x, y])?;
+        ensure_running()?;
+        crate::set_audio_listener_internal(x, y)
+            .then_some(())
+            .ok_or(PhysicsError::NotInitialized)
+    }
+
+    pub fn set_audio_attenuation(&self, reference_distance: f32, max_distance: f32, rolloff: f32) -> Result<(), PhysicsError> {
+        require_finite("attenuation", &[reference_distance, max_distance, rolloff])?;
+        if reference_distance <= 0.0 || reference_distance > max_distance || rolloff < 0.0 {
+            return Err(PhysicsError::InvalidArgument {
+                reason: "attenuation needs 0 < reference <= max distance and rolloff >= 0".into(),
+            });
+        }
+        ensure_running()?;
+        crate::set_audio_attenuation_internal(reference_distance, max_distance, rolloff)
+            .then_some(())
+            .ok_or(PhysicsError::NotInitialized)
+    }
+
+    /// Take up to `max` queued events, oldest first
+    pub fn poll_audio_events(&self, max: u32) -> Vec<AudioEvent> {
+        crate::poll_audio_events_internal(max as usize).into_iter().map(Into::into).collect()
+    }
+
+    // --- Destruction ---
+
+    /// A dynamic box that breaks apart when a collision impulse reaches `fracture.threshold`
+    pub fn spawn_destructible(&self, x: f32, y: f32, half_extent: f32, fracture: FractureConfig) -> Result<u64, PhysicsError> {
+        let fracture = valid_fracture(fracture)?;
+        let id = self.spawn_box(x, y, half_extent)?;
+        crate::set_destructible_internal(id, Some(fracture));
+        Ok(id)
+    }
+
+    pub fn set_destructible(&self, id: u64, fracture: FractureConfig) -> Result<(), PhysicsError> {
+        let fracture = valid_fracture(fracture)?;
+        crate::set_destructible_internal(id, Some(fracture))
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    pub fn clear_destructible(&self, id: u64) -> Result<(), PhysicsError> {
+        crate::set_destructible_internal(id, None)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Take up to `max` queued fractures, oldest first
+    pub fn poll_fracture_events(&self, max: u32) -> Vec<FractureEvent> {
+        crate::poll_fracture_events_internal(max as usize).into_iter().map(Into::into).collect()
+    }
+
+    // --- Triggers ---
+
+    /// Take up to `max` queued trigger zone events, oldest first
+    pub fn poll_trigger_events(&self, max: u32) -> Vec<TriggerEvent> {
+        crate::poll_trigger_events_internal(max as usize).into_iter().map(Into::into).collect()
+    }
+
+    /// Won once a watched body entered a goal zone, lost once one entered a hazard
+    pub fn level_outcome(&self) -> Result<Outcome, PhysicsError> {
+        crate::level_outcome_internal().map(Into::into).ok_or(PhysicsError::NotInitialized)
+    }
+
+    // --- Scenes ---
+
+    /// Register a JSON `SceneDesc` (`level`, `bodies`, `gravity` or
+    /// `gravityPreset`, `tweens`, `stateMachines`) under `name`, replacing a scene of the same name
+    pub fn register_scene_json(&self, name: String, json: String) -> Result<(), PhysicsError> {
+        crate::register_scene_json_internal(&name, &json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Returns false for unknown names
+    pub fn remove_scene(&self, name: String) -> bool {
+        crate::remove_scene_internal(&name)
+    }
+
+    /// Tear down the running simulation and build scene `name`, fading it in
+    /// over `fade_seconds`; returns the number of bodies spawned
+    pub fn switch_scene(&self, name: String, fade_seconds: f32) -> Result<u32, PhysicsError> {
+        ensure_running()?;
+        crate::switch_scene_internal(&name, fade_seconds)
+            .map(|count| count as u32)
+            .map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Switch to the scene after the current one; returns its name, or None
+    /// after the last scene
+    pub fn load_next_scene(&self, fade_seconds: f32) -> Result<Option<String>, PhysicsError> {
+        ensure_running()?;
+        crate::load_next_scene_internal(fade_seconds).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    // --- Prefabs ---
+
+    /// Register a JSON `PrefabDesc` (`shape`, `bodyType`, `groups`,
+    /// `materialId`, `clip`, `movement`, `stateMachine`, `parts`) under `name`
+    pub fn register_prefab_json(&self, name: String, json: String) -> Result<(), PhysicsError> {
+        crate::register_prefab_json_internal(&name, &json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Returns false for unknown names
+    pub fn remove_prefab(&self, name: String) -> bool {
+        crate::remove_prefab_internal(&name)
+    }
+
+    /// Spawn prefab `name` with its parts; returns the prefab's own body
+    pub fn spawn_prefab(&self, name: String, x: f32, y: f32, angle: f32) -> Result<u64, PhysicsError> {
+        ensure_running()?;
+        crate::spawn_prefab_internal(&name, x, y, angle).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    // --- Assets ---
+
+    /// Keep `bytes` as asset `name` ("textures/ball.png") for scenes and
+    /// textures loaded by name; hosts with a C asset loader registered need not
+    pub fn preload_asset(&self, name: String, bytes: Vec<u8>) -> Result<(), PhysicsError> {
+        crate::assets::preload(&name, bytes).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    pub fn unload_asset(&self, name: String) -> bool {
+        crate::assets::unload(&name)
+    }
+
+    /// Register texture asset `name` (PNG or KTX2, or the best compressed
+    /// variant of a PNG) as a material; returns its id, the same one on later
+    /// loads
+    pub fn load_texture_asset(&self, name: String) -> Result<u32, PhysicsError> {
+        ensure_running()?;
+        crate::load_texture_asset_internal(&name).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Register scene asset `name` under its file name without the extension
+    /// and switch to it, loading the textures it names; returns the number
+    /// of bodies spawned
+    pub fn load_scene_asset(&self, name: String) -> Result<u32, PhysicsError> {
+        ensure_running()?;
+        crate::load_scene_asset_internal(&name)
+            .map(|count| count as u32)
+            .map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    // --- Tweens ---
+
+    /// Ease `property` of the entity (ignored for camera properties) from
+    /// `from` to `to` over `duration` simulated seconds; returns the tween id.
+    /// Scale, rotation and camera zoom take one value, camera position two
+    /// and color three (RGB, 0..1).
+    pub fn animate_property(
+        &self,
+        entity_id: u64,
+        property: TweenProperty,
+        from: Vec<f32>,
+        to: Vec<f32>,
+        duration: f32,
+        easing: Easing,
+    ) -> Result<u64, PhysicsError> {
+        ensure_running()?;
+        let property: crate::tween::TweenProperty = property.into();
+        crate::tween::Tween::new(entity_id, property, &from, &to, duration, 0.0, easing.into())
+            .map_err(|reason| PhysicsError::InvalidArgument { reason })?;
+        crate::animate_property_internal(entity_id, property, &from, &to, duration, easing.into())
+            .ok_or(PhysicsError::UnknownEntity { id: entity_id })
+    }
+
+    /// Returns false for unknown or finished tweens
+    pub fn cancel_tween(&self, tween_id: u64) -> bool {
+        crate::cancel_tween_internal(tween_id)
+    }
+
+    // --- State machines ---
+
+    /// Give the entity a state machine from a JSON `StateMachineDesc`
+    /// (`states`, `initial`), replacing any it has
+    pub fn set_state_machine_json(&self, entity_id: u64, json: String) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_state_machine_json_internal(entity_id, &json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Returns false for entities without a machine
+    pub fn remove_state_machine(&self, entity_id: u64) -> bool {
+        crate::entity_state_internal(entity_id).is_some() && crate::set_state_machine_internal(entity_id, None).is_ok()
+    }
+
+    /// Send `event` to the entity's machine, or to every machine for entity
+    /// 0; returns how many received it
+    pub fn send_state_event(&self, entity_id: u64, event: String) -> u32 {
+        crate::send_state_event_internal(entity_id, &event) as u32
+    }
+
+    /// Name of the state the entity's machine is in
+    pub fn entity_state(&self, entity_id: u64) -> Option<String> {
+        crate::entity_state_internal(entity_id).map(|(_, name)| name)
+    }
+
+    /// Drive the body with a JSON `MovementDesc`: a path (`linear`,
+    /// `circular`, ...) or a steering behavior (`seek`, `flee`, `wander`);
+    /// `none` hands it back to physics
+    pub fn set_movement_json(&self, entity_id: u64, json: String) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_movement_json_internal(entity_id, &json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Run a packed command buffer (see `command_buffer`) in one call.
+    /// Returns one result per command: the entity id for spawns, 1 for other
+    /// commands that took effect and 0 for ones that failed.
+    pub fn submit_commands(&self, commands: Vec<u8>) -> Result<Vec<u64>, PhysicsError> {
+        ensure_running()?;
+        crate::submit_commands_internal(&commands).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    // --- Projectiles ---
+
+    /// Preallocate `capacity` projectiles of `radius`; 0 removes the pool
+    pub fn configure_projectiles(&self, capacity: u32, radius: f32) -> Result<(), PhysicsError> {
+        if capacity > crate::projectiles::MAX_CAPACITY || !radius.is_finite() || radius <= 0.0 {
+            return Err(PhysicsError::InvalidArgument {
+                reason: format!("capacity must be at most {} and radius > 0", crate::projectiles::MAX_CAPACITY),
+            });
+        }
+        ensure_running()?;
+        crate::configure_projectiles_internal(capacity, radius)
+            .then_some(())
+            .ok_or(PhysicsError::NotInitialized)
+    }
+
+    /// Launch a pooled projectile, parked again after `ttl` seconds (0 for
+    /// never) or on its first collision. None when every projectile is in flight.
+    pub fn fire_projectile(
+        &self,
+        origin_x: f32,
+        origin_y: f32,
+        vx: f32,
+        vy: f32,
+        ttl: f32,
+    ) -> Result<Option<u64>, PhysicsError> {
+        require_finite("origin", &[origin_x, origin_y])?;
+        require_finite("velocity", &[vx, vy])?;
+        ensure_running()?;
+        Ok(crate::fire_projectile_internal(origin_x, origin_y, vx, vy, ttl))
+    }
+
+    pub fn projectile_stats(&self) -> ProjectileStats {
+        crate::projectile_stats_internal().into()
+    }
+
+    // --- Groups ---
+
+    /// Bit `n` of `groups` puts the body in group `n`. The bulk methods act on
+    /// bodies sharing a bit with their mask; `u32::MAX` matches every body.
+    pub fn set_body_groups(&self, id: u64, groups: u32) -> Result<(), PhysicsError> {
+        crate::set_body_groups_internal(id, groups)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    pub fn body_groups(&self, id: u64) -> Result<u32, PhysicsError> {
+        crate::body_groups_internal(id).ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    pub fn group_ids(&self, mask: u32) -> Vec<u64> {
+        crate::group_ids_internal(mask)
+    }
+
+    /// Returns the number of bodies removed
+    pub fn despawn_group(&self, mask: u32) -> Result<u32, PhysicsError> {
+        ensure_running()?;
+        Ok(crate::despawn_group_internal(mask))
+    }
+
+    /// Freeze the group's dynamic bodies in place or release them. Returns
+    /// the number of bodies changed.
+    pub fn set_group_paused(&self, mask: u32, paused: bool) -> Result<u32, PhysicsError> {
+        ensure_running()?;
+        Ok(crate::set_group_paused_internal(mask, paused))
+    }
+
+    pub fn apply_impulse_to_group(&self, mask: u32, impulse_x: f32, impulse_y: f32) -> Result<u32, PhysicsError> {
+        require_finite("impulse", &[impulse_x, impulse_y])?;
+        ensure_running()?;
+        Ok(crate::apply_impulse_to_group_internal(mask, impulse_x, impulse_y))
+    }
+
+    pub fn body(&self, id: u64) -> Result<BodyState, PhysicsError> {
+        let info = crate::body_info_internal(id).ok_or(PhysicsError::UnknownEntity { id })?;
+        Ok(BodyState {
+            x: info.x,
+            y: info.y,
+            vx: info.vx,
+            vy: info.vy,
+            rotation: info.rotation,
+            sleeping: info.sleeping,
+        })
+    }
+
+    pub fn apply_impulse(&self, id: u64, x: f32, y: f32) -> Result<(), PhysicsError> {
+        require_finite("impulse", &[x, y])?;
+        crate::apply_impulse_internal(id, x, y)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Spring of `stiffness` N/m pulling the body toward the point; returns the spring id
+    pub fn create_spring_to_point(&self, id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> Result<u64, PhysicsError> {
+        require_finite("spring", &[world_x, world_y, stiffness, damping])?;
+        if stiffness < 0.0 || damping < 0.0 {
+            return Err(PhysicsError::InvalidArgument { reason: "stiffness and damping must be >= 0".into() });
+        }
+        crate::create_spring_to_point_internal(id, world_x, world_y, stiffness, damping).ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    pub fn update_spring(&self, spring_id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> Result<(), PhysicsError> {
+        require_finite("spring", &[world_x, world_y, stiffness, damping])?;
+        crate::update_spring_internal(spring_id, world_x, world_y, stiffness, damping)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id: spring_id })
+    }
+
+    pub fn remove_spring(&self, spring_id: u64) -> Result<(), PhysicsError> {
+        crate::remove_spring_internal(spring_id)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id: spring_id })
+    }
+
+    /// Box from a JSON `SceneBody` as a step `undo` can take back; returns its id
+    pub fn edit_spawn_body(&self, json: String) -> Result<u64, PhysicsError> {
+        ensure_running()?;
+        crate::edit_spawn_body_json_internal(&json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Make a box match a JSON `SceneBody` as an undoable step; `merge` joins
+    /// a change right after another change to the same box into its step
+    pub fn edit_set_body(&self, id: u64, json: String, merge: bool) -> Result<(), PhysicsError> {
+        crate::edit_body_json_internal(id, &json, merge).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    pub fn edit_remove_body(&self, id: u64) -> Result<(), PhysicsError> {
+        crate::edit_remove_body_internal(id)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    pub fn edit_attach_spring(&self, id: u64, world_x: f32, world_y: f32, stiffness: f32, damping: f32) -> Result<u64, PhysicsError> {
+        require_finite("spring", &[world_x, world_y, stiffness, damping])?;
+        if stiffness < 0.0 || damping < 0.0 {
+            return Err(PhysicsError::InvalidArgument { reason: "stiffness and damping must be >= 0".into() });
+        }
+        crate::edit_attach_spring_internal(id, world_x, world_y, stiffness, damping).ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// The box as a JSON `SceneBody`, for `edit_set_body`
+    pub fn body_desc(&self, id: u64) -> Result<String, PhysicsError> {
+        crate::body_desc_json_internal(id).ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Take back the last edit step; false when there is none or it no longer applies
+    pub fn undo(&self) -> bool {
+        crate::undo_internal()
+    }
+
+    pub fn redo(&self) -> bool {
+        crate::redo_internal()
+    }
+
+    pub fn clear_history(&self) {
+        crate::clear_history_internal();
+    }
+
+    /// Undo and redo counts and the next steps' labels as JSON
+    pub fn history_state(&self) -> String {
+        crate::history_state_internal()
+    }
+
+    /// Soft blob from a JSON `SoftBodyDesc` object; returns the blob id
+    pub fn spawn_soft_body_json(&self, json: String) -> Result<u64, PhysicsError> {
+        ensure_running()?;
+        crate::spawn_soft_body_json_internal(&json).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    pub fn remove_soft_body(&self, soft_body_id: u64) -> Result<(), PhysicsError> {
+        crate::remove_soft_body_internal(soft_body_id)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id: soft_body_id })
+    }
+
+    pub fn wake(&self, id: u64) -> Result<(), PhysicsError> {
+        crate::wake_body_internal(id)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    pub fn sleep(&self, id: u64) -> Result<(), PhysicsError> {
+        crate::sleep_body_internal(id)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    pub fn set_sleep_enabled(&self, enabled: bool) {
+        crate::set_sleep_enabled_internal(enabled);
+    }
+
+    /// Register a `width` x `height` RGBA8 texture and return its material id
+    pub fn register_material(&self, width: u32, height: u32, rgba: Vec<u8>) -> Result<u32, PhysicsError> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            return Err(PhysicsError::InvalidArgument { reason: "rgba must hold width * height * 4 bytes".into() });
+        }
+        Ok(crate::register_material_internal(width, height, &rgba))
+    }
+
+    pub fn set_material(&self, id: u64, material_id: u32) -> Result<(), PhysicsError> {
+        crate::set_entity_material_internal(id, material_id)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Trace the texture's pixels with alpha above `alpha_threshold` into the
+    /// collision shape of bodies drawn with it; returns the convex piece count
+    pub fn generate_collider_from_texture(&self, texture_id: u32, alpha_threshold: u8, simplification: f32) -> Result<u32, PhysicsError> {
+        crate::generate_collider_from_texture_internal(texture_id, alpha_threshold, simplification)
+            .map(|pieces| pieces as u32)
+            .map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Filter the texture between texels with `filter` and between mip
+    /// levels with `mipmap_filter`, and wrap it with `wrap_u` and `wrap_v`
+    pub fn set_texture_sampling(
+        &self,
+        material_id: u32,
+        filter: TextureFilter,
+        mipmap_filter: TextureFilter,
+        wrap_u: TextureWrap,
+        wrap_v: TextureWrap,
+    ) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        let sampling = texture_sampling::TextureSampling {
+            filter: filter.into(),
+            mipmap_filter: mipmap_filter.into(),
+            wrap_u: wrap_u.into(),
+            wrap_v: wrap_v.into(),
+        };
+        crate::set_texture_sampling_internal(material_id, sampling)
+            .then_some(())
+            .ok_or_else(|| PhysicsError::InvalidArgument { reason: format!("unknown material {}", material_id) })
+    }
+
+    /// Draw the texture rect `u`, `v`, `width`, `height` (0..1, v down) on a
+    /// quad `draw_width` x `draw_height` times the entity's square one
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_sprite_region(&self, id: u64, u: f32, v: f32, width: f32, height: f32, draw_width: f32, draw_height: f32) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        let region = crate::SpriteRegion { rect: [u, v, width, height], size: [draw_width, draw_height], ..Default::default() };
+        crate::set_sprite_region_internal(id, Some(region)).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Keep the region's borders (fractions of it) from stretching
+    pub fn set_nine_slice(&self, id: u64, left: f32, top: f32, right: f32, bottom: f32) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_nine_slice_internal(id, [left, top, right, bottom]).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    pub fn clear_sprite_region(&self, id: u64) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_sprite_region_internal(id, None).map_err(|_| PhysicsError::UnknownEntity { id })
+    }
+
+    /// Draw the entity in layer `order` of `kind`; lower orders draw first
+    pub fn set_layer(&self, id: u64, kind: LayerKind, order: i32) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_layer_internal(id, layers::Layer::new(kind.into(), order))
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    /// Scroll a layer by `factor_x` and `factor_y` of the camera's movement;
+    /// 1 moves with the world and 0 stays put on screen
+    pub fn set_layer_parallax(&self, kind: LayerKind, order: i32, factor_x: f32, factor_y: f32) -> Result<(), PhysicsError> {
+        ensure_running()?;
+        crate::set_layer_parallax_internal(layers::Layer::new(kind.into(), order), [factor_x, factor_y])
+            .map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// .gltf or .glb file bytes; returns the model id for `attach_model`
+    pub fn load_model(&self, bytes: Vec<u8>) -> Result<u32, PhysicsError> {
+        crate::load_model_internal(&bytes).map_err(|reason| PhysicsError::InvalidArgument { reason })
+    }
+
+    /// Draw the body with the model in the 3D pass; model 0 removes it
+    pub fn attach_model(&self, id: u64, model_id: u32, scale: f32) -> Result<(), PhysicsError> {
+        require_finite("scale", &[scale])?;
+        crate::attach_model_internal(id, model_id, scale)
+            .then_some(())
+            .ok_or(PhysicsError::UnknownEntity { id })
+    }
+
+    // --- Queries ---
+
+    pub fn query_aabb(&self, min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Vec<u64> {
+        crate::query_aabb_internal(min_x, min_y, max_x, max_y, crate::groups::ALL)
+    }
+
+    pub fn query_circle(&self, center_x: f32, center_y: f32, radius: f32) -> Vec<u64> {
+        crate::query_circle_internal(center_x, center_y, radius, crate::groups::ALL)
+    }
+
+    pub fn query_aabb_in_groups(&self, min_x: f32, min_y: f32, max_x: f32, max_y: f32, mask: u32) -> Vec<u64> {
+        crate::query_aabb_internal(min_x, min_y, max_x, max_y, mask)
+    }
+
+    pub fn query_circle_in_groups(&self, center_x: f32, center_y: f32, radius: f32, mask: u32) -> Vec<u64> {
+        crate::query_circle_internal(center_x, center_y, radius, mask)
+    }
+
+    // --- Text ---
+
+    /// Add or replace label `id` in `color` (0xRRGGBBAA); it stays until cleared
+    pub fn draw_text(&self, id: u64, text: String, placement: TextPlacement, size: f32, color: u32) -> Result<(), PhysicsError> {
+        let (anchor, x, y) = match placement {
+            TextPlacement::Screen { x, y } => (TextAnchor::Screen, x, y),
+            TextPlacement::World { x, y } => (TextAnchor::World, x, y),
+            TextPlacement::Entity { id, offset_x, offset_y } => (TextAnchor::Body(id), offset_x, offset_y),
+        };
+        require_finite("text position", &[x, y, size])?;
+        if size <= 0.0 {
+            return Err(PhysicsError::InvalidArgument { reason: "text size must be > 0".into() });
+        }
+        crate::draw_text_internal(id, &text, anchor, x, y, size, crate::text::unpack_rgba(color))
+            .then_some(())
+            .ok_or(PhysicsError::InvalidArgument {
+                reason: format!("at most {} text labels", crate::text::MAX_LABELS),
+            })
+    }
+
+    /// Returns false if there was no label `id`
+    pub fn clear_text(&self, id: u64) -> bool {
+        crate::clear_text_internal(id)
+    }
+
+    pub fn clear_all_text(&self) {
+        crate::clear_all_text_internal();
+    }
+
+    // --- Camera Effects ---
+
+    /// Add trauma in 0..1; the shake grows with its square and decays over time
+    pub fn add_camera_shake(&self, strength: f32) -> Result<(), PhysicsError> {
+        crate::add_camera_shake_internal(strength)
+            .then_some(())
+            .ok_or(PhysicsError::InvalidArgument { reason: "shake strength must be finite and >= 0".into() })
+    }
+
+    /// Zoom in by `amount` (0.2 is 20% closer, negative zooms out) and ease back
+    pub fn punch_camera_zoom(&self, amount: f32) -> Result<(), PhysicsError> {
+        require_finite("zoom punch", &[amount])?;
+        crate::punch_camera_zoom_internal(amount);
+        Ok(())
+    }
+
+    pub fn set_camera_shake(&self, max_offset: f32, max_rotation: f32, frequency: f32) -> Result<(), PhysicsError> {
+        crate::set_camera_shake_internal(max_offset, max_rotation, frequency)
+            .then_some(())
+            .ok_or(PhysicsError::InvalidArgument {
+                reason: "shake amplitudes must be >= 0 and frequency > 0".into(),
+            })
+    }
+
+    pub fn set_camera_effects_decay(&self, trauma_decay: f32, zoom_decay: f32) -> Result<(), PhysicsError> {
+        crate::set_camera_effects_decay_internal(trauma_decay, zoom_decay)
+            .then_some(())
+            .ok_or(PhysicsError::InvalidArgument { reason: "decay rates must be > 0".into() })
+    }
+
+    /// Shake on collisions above `min_impulse` N·s; 0 `trauma_per_impulse` turns it off
+    pub fn set_camera_impact_shake(&self, min_impulse: f32, trauma_per_impulse: f32) -> Result<(), PhysicsError> {
+        crate::set_camera_impact_shake_internal(min_impulse, trauma_per_impulse)
+            .then_some(())
+            .ok_or(PhysicsError::InvalidArgument { reason: "impact shake values must be >= 0".into() })
+    }
+
+    pub fn clear_camera_effects(&self) {
+        crate::clear_camera_effects_internal();
+    }
+
+    // --- Input ---
+
+    pub fn on_pointer_event(&self, action: PointerAction, x: f32, y: f32) {
+        let event_type = match action {
+            PointerAction::Down => 0,
+            PointerAction::Move => 1,
+            PointerAction::Up => 2,
+        };
+        crate::on_pointer_event_internal(event_type, x, y, 0);
+    }
+
+    pub fn on_key_event(&self, action: KeyAction, key_code: i32) {
+        let event_type = match action {
+            KeyAction::Down => 0,
+            KeyAction::Up => 1,
+        };
+        crate::on_key_event_internal(event_type, key_code);
+    }
+
+    /// One call per pointer; `time_seconds` is the platform event time
+    pub fn on_touch_event(&self, action: TouchAction, pointer_id: i32, x: f32, y: f32, time_seconds: f64) {
+        let action = match action {
+            TouchAction::Down => 0,
+            TouchAction::Move => 1,
+            TouchAction::Up => 2,
+            TouchAction::Cancel => 3,
+        };
+        crate::on_touch_event_internal(action, pointer_id, x, y, time_seconds);
+    }
+
+    pub fn set_gestures(&self, tap_spawn: bool, fling: bool, pinch_zoom: bool, pan: bool) {
+        crate::set_gestures_internal(tap_spawn, fling, pinch_zoom, pan);
+    }
+}
+
+#[cfg(feature = "scripting")]
+#[uniffi::export]
+impl PhysicsEngine {
+    pub fn load_script(&self, source: String) -> Result<(), PhysicsError> {
+        crate::scripting::load_script(&source).map_err(|message| PhysicsError::Script { message })
+    }
+
+    pub fn unload_script(&self) {
+        crate::scripting::unload_script();
+    }
+}
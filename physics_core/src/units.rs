@@ -0,0 +1,87 @@
+//! World units and scale
+//!
+//! The simulation works in meters, kilograms and seconds. The world scale
+//! says how many meters the screen shows: `ndc_per_meter` normalized device
+//! units per meter, where the main view is 2 NDC tall. The default of 1
+//! is the original layout, with the walls just outside -1..1, 0.1 m boxes
+//! and a camera 2.2 m tall; a scale of 0.1 shows ten times as much world
+//! with everything scene-sized ten times bigger. Gravity stays 9.81 m/s² at
+//! every scale, so a larger world also looks slower, as it would.
+//!
+//! A scale given in pixels per meter is turned into NDC with the surface
+//! height at the time, so a later resize keeps the same meters in view.
+
+use bevy_ecs::prelude::*;
+
+/// Gravity of a new simulation, in m/s² along -Y
+pub const DEFAULT_GRAVITY: f32 = 9.81;
+
+/// Height of the main view in NDC
+const VIEW_NDC: f32 = 2.0;
+/// Camera height at startup, in NDC; a margin past the walls
+const CAMERA_HEIGHT_NDC: f32 = 2.2;
+/// Centre lines of the default scene's walls, in NDC
+const WALL_NDC: f32 = 1.1;
+/// Half extent of the default scene's boxes, in NDC
+const BOX_HALF_EXTENT_NDC: f32 = 0.05;
+/// How far the camera may zoom in and out, as view heights in NDC
+const ZOOM_LIMITS_NDC: (f32, f32) = (0.2, 20.0);
+
+/// Also a resource of every scene world, holding the scale it was built at
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct WorldScale {
+    ndc_per_meter: f32,
+}
+
+impl Default for WorldScale {
+    fn default() -> Self {
+        Self { ndc_per_meter: 1.0 }
+    }
+}
+
+impl WorldScale {
+    /// None unless `ndc_per_meter` is finite and above 0
+    pub fn new(ndc_per_meter: f32) -> Option<Self> {
+        (ndc_per_meter.is_finite() && ndc_per_meter > 0.0).then_some(Self { ndc_per_meter })
+    }
+
+    /// `pixels_per_meter` on a surface `surface_height` pixels tall
+    pub fn from_pixels_per_meter(pixels_per_meter: f32, surface_height: u32) -> Option<Self> {
+        Self::new(pixels_per_meter * VIEW_NDC / surface_height.max(1) as f32)
+    }
+
+    pub fn ndc_per_meter(&self) -> f32 {
+        self.ndc_per_meter
+    }
+
+    /// Pixels per meter on a surface `surface_height` pixels tall, at zoom 1
+    pub fn pixels_per_meter(&self, surface_height: u32) -> f32 {
+        self.ndc_per_meter * surface_height.max(1) as f32 / VIEW_NDC
+    }
+
+    /// Meters spanned by `ndc` normalized device units
+    pub fn meters(&self, ndc: f32) -> f32 {
+        ndc / self.ndc_per_meter
+    }
+
+    /// Meters the camera shows vertically at startup
+    pub fn camera_height(&self) -> f32 {
+        self.meters(CAMERA_HEIGHT_NDC)
+    }
+
+    /// Smallest and largest view height the camera zooms to, in meters
+    pub fn zoom_limits(&self) -> (f32, f32) {
+        (self.meters(ZOOM_LIMITS_NDC.0), self.meters(ZOOM_LIMITS_NDC.1))
+    }
+
+    /// Where the default scene's walls are centred, in meters from the origin
+    pub fn wall_position(&self) -> f32 {
+        self.meters(WALL_NDC)
+    }
+
+    /// Half extent of the default scene's boxes, tap-spawned boxes and
+    /// bodies drawn without a `Scale`, in meters
+    pub fn box_half_extent(&self) -> f32 {
+        self.meters(BOX_HALF_EXTENT_NDC)
+    }
+}
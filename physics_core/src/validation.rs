@@ -0,0 +1,147 @@
+//! Checks on values coming in from the bindings
+//!
+//! A NaN position or a negative size handed to Rapier does not fail; it
+//! turns the body's pose into NaN, and the solver spreads it to everything
+//! the body touches a step later. JS hosts hit this most, since wasm-bindgen
+//! turns `undefined` into NaN for an `f32` parameter. The `*_internal`
+//! functions the bindings share check what they get here first and return
+//! their usual failure value (false, 0, None) for bad input, after keeping an
+//! `InputError` that the host can read back with `get_last_error`.
+//!
+//! The last error is per thread, like `errno`: read it on the thread that
+//! made the failing call. A call that succeeds leaves it alone.
+
+use std::cell::RefCell;
+use std::fmt;
+
+/// Error codes on the bindings (same values as `PHYSICS_CORE_ERROR_*`)
+pub const ERROR_NONE: i32 = 0;
+/// NaN or infinite number
+pub const ERROR_NOT_FINITE: i32 = 1;
+/// Size, radius or scale that is zero or negative
+pub const ERROR_INVALID_SIZE: i32 = 2;
+/// Time step that is zero, negative or not finite
+pub const ERROR_INVALID_TIME_STEP: i32 = 3;
+/// Entity id that is malformed or names no live entity
+pub const ERROR_UNKNOWN_ENTITY: i32 = 4;
+/// Number outside the range the parameter allows
+pub const ERROR_OUT_OF_RANGE: i32 = 5;
+pub const ERROR_NULL_POINTER: i32 = 6;
+/// String that is not valid UTF-8
+pub const ERROR_INVALID_STRING: i32 = 7;
+
+thread_local! {
+    static LAST: RefCell<Option<InputError>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    /// One of `ERROR_*`
+    pub code: i32,
+    /// Names the parameter and what was wrong with it
+    pub message: String,
+}
+
+impl InputError {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn unknown_entity(entity_id: u64) -> Self {
+        Self::new(ERROR_UNKNOWN_ENTITY, format!("no entity with id {}", entity_id))
+    }
+
+    pub fn null_pointer(name: &str) -> Self {
+        Self::new(ERROR_NULL_POINTER, format!("{} is null", name))
+    }
+
+    pub fn invalid_string(name: &str) -> Self {
+        Self::new(ERROR_INVALID_STRING, format!("{} is not valid UTF-8", name))
+    }
+
+    /// `{"code": 1, "message": "..."}`
+    pub fn to_json(&self) -> String {
+        serde_json::json!({ "code": self.code, "message": self.message }).to_string()
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Every value is finite; `name` covers them all, e.g. "position"
+pub fn finite(name: &str, values: &[f32]) -> Result<(), InputError> {
+    match values.iter().find(|v| !v.is_finite()) {
+        Some(value) => Err(InputError::new(ERROR_NOT_FINITE, format!("{} must be finite, got {}", name, value))),
+        None => Ok(()),
+    }
+}
+
+/// A finite size greater than zero
+pub fn size(name: &str, value: f32) -> Result<(), InputError> {
+    finite(name, &[value])?;
+    if value <= 0.0 {
+        return Err(InputError::new(ERROR_INVALID_SIZE, format!("{} must be > 0, got {}", name, value)));
+    }
+    Ok(())
+}
+
+/// A finite value of zero or more
+pub fn non_negative(name: &str, value: f32) -> Result<(), InputError> {
+    in_range(name, value, 0.0, f32::INFINITY)
+}
+
+/// A finite value in `min..=max`
+pub fn in_range(name: &str, value: f32, min: f32, max: f32) -> Result<(), InputError> {
+    finite(name, &[value])?;
+    if value < min || value > max {
+        let message = if max == f32::INFINITY {
+            format!("{} must be >= {}, got {}", name, min, value)
+        } else {
+            format!("{} must be in {}..={}, got {}", name, min, max, value)
+        };
+        return Err(InputError::new(ERROR_OUT_OF_RANGE, message));
+    }
+    Ok(())
+}
+
+/// A finite time step greater than zero
+pub fn time_step(dt: f32) -> Result<(), InputError> {
+    if !dt.is_finite() || dt <= 0.0 {
+        return Err(InputError::new(ERROR_INVALID_TIME_STEP, format!("time step must be finite and > 0, got {}", dt)));
+    }
+    Ok(())
+}
+
+/// Keep `error` as this thread's last error and log it; returns its message
+/// for callers reporting errors as strings
+pub fn reject(error: InputError) -> String {
+    log::warn!("Rejected input: {}", error);
+    let message = error.message.clone();
+    LAST.with(|last| *last.borrow_mut() = Some(error));
+    message
+}
+
+/// True for `Ok`; an error is kept as by `reject`
+pub fn accept(result: Result<(), InputError>) -> bool {
+    match result {
+        Ok(()) => true,
+        Err(error) => {
+            reject(error);
+            false
+        }
+    }
+}
+
+/// This thread's latest rejected input since start or `clear`
+pub fn last_error() -> Option<InputError> {
+    LAST.with(|last| last.borrow().clone())
+}
+
+pub fn clear() {
+    LAST.with(|last| *last.borrow_mut() = None);
+}
@@ -0,0 +1,211 @@
+//! Extra views of the running simulation
+//!
+//! The main surface owns everything that can be shared: device, pipelines,
+//! the instance buffer and materials. A view adds only what belongs to one
+//! surface (the surface itself, a camera uniform and a multisample target)
+//! and draws the same bodies with the scene pass after the main frame is
+//! presented, so a minimap or a second `SurfaceView` costs one extra pass.
+//! Views leave out bloom, text, debug overlays and the scene fade.
+//!
+//! A view must accept the main surface's format; its sample count follows
+//! the main surface too, since the pipelines are built for it.
+
+use serde::Deserialize;
+use wgpu::util::DeviceExt;
+
+use crate::camera::{Camera, CameraUniform};
+use crate::{world_scale, SurfaceSource, WgpuState};
+
+/// Most views besides the main surface
+pub const MAX_VIEWS: usize = 8;
+
+/// Where a view looks; missing fields keep the defaults
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ViewCamera {
+    pub center_x: f32,
+    pub center_y: f32,
+    /// World units visible vertically
+    pub view_height: f32,
+}
+
+impl Default for ViewCamera {
+    /// What the main camera shows at startup at the default world scale
+    fn default() -> Self {
+        Self { center_x: 0.0, center_y: 0.0, view_height: 2.2 }
+    }
+}
+
+impl ViewCamera {
+    pub fn is_valid(&self) -> bool {
+        self.center_x.is_finite() && self.center_y.is_finite() && self.view_height.is_finite() && self.view_height > 0.0
+    }
+
+    /// Orthographic camera for a `width` x `height` surface
+    pub(crate) fn camera(&self, width: u32, height: u32) -> Camera {
+        let mut camera = Camera::orthographic(width.max(1) as f32 / height.max(1) as f32, &world_scale());
+        camera.set_view_height(self.view_height);
+        camera.look_at(self.center_x, self.center_y);
+        camera
+    }
+}
+
+/// Views by id. Ids start at 1 and are not reused, so a stale id never
+/// reaches a newer view.
+#[derive(Debug)]
+pub struct ViewSlots<T> {
+    views: Vec<(u32, T)>,
+    next_id: u32,
+}
+
+impl<T> Default for ViewSlots<T> {
+    fn default() -> Self {
+        Self { views: Vec::new(), next_id: 1 }
+    }
+}
+
+impl<T> ViewSlots<T> {
+    /// Store `view` under a new id; None when `MAX_VIEWS` are open
+    pub fn insert(&mut self, view: T) -> Option<u32> {
+        if self.views.len() >= MAX_VIEWS {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.views.push((id, view));
+        Some(id)
+    }
+
+    /// Store `view` under an id handed out earlier, as after a device rebuild
+    pub fn restore(&mut self, id: u32, view: T) {
+        self.next_id = self.next_id.max(id + 1);
+        self.views.push((id, view));
+    }
+
+    pub fn remove(&mut self, id: u32) -> Option<T> {
+        let index = self.views.iter().position(|(view_id, _)| *view_id == id)?;
+        Some(self.views.remove(index).1)
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut T> {
+        self.views.iter_mut().find(|(view_id, _)| *view_id == id).map(|(_, view)| view)
+    }
+
+    pub fn len(&self) -> usize {
+        self.views.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+
+    /// Views in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &T)> {
+        self.views.iter().map(|(id, view)| (*id, view))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u32, &mut T)> {
+        self.views.iter_mut().map(|(id, view)| (*id, view))
+    }
+
+    /// Take every view out, keeping the id counter
+    pub fn drain(&mut self) -> Vec<(u32, T)> {
+        std::mem::take(&mut self.views)
+    }
+}
+
+/// One extra surface and what it needs of its own
+pub(crate) struct SurfaceView {
+    /// Kept so the surface can be created again after a device rebuild
+    pub(crate) source: SurfaceSource,
+    surface: wgpu::Surface<'static>,
+    pub(crate) config: wgpu::SurfaceConfiguration,
+    pub(crate) camera: ViewCamera,
+    camera_buffer: wgpu::Buffer,
+    pub(crate) camera_bind_group: wgpu::BindGroup,
+    pub(crate) msaa_view: Option<wgpu::TextureView>,
+}
+
+impl SurfaceView {
+    /// Create a surface from `source` that draws with the main surface's
+    /// device and pipelines
+    pub(crate) fn new(
+        state: &WgpuState,
+        source: SurfaceSource,
+        width: u32,
+        height: u32,
+        camera: ViewCamera,
+    ) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err(format!("invalid size {}x{}", width, height));
+        }
+        if !camera.is_valid() {
+            return Err("invalid camera".to_string());
+        }
+        let surface = source.create_surface(&state.instance).map_err(|e| format!("{:?}", e))?;
+        let caps = surface.get_capabilities(&state.adapter);
+        if !caps.formats.contains(&state.config.format) {
+            return Err(format!("surface does not support {:?}", state.config.format));
+        }
+        let max_dimension = state.device.limits().max_texture_dimension_2d;
+        let config = wgpu::SurfaceConfiguration {
+            width: width.min(max_dimension),
+            height: height.min(max_dimension),
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            ..state.config.clone()
+        };
+        surface.configure(&state.device, &config);
+
+        let camera_buffer = state.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("View Camera Buffer"),
+            contents: bytemuck::cast_slice(&[CameraUniform::new()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let camera_bind_group = state.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &state.camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: camera_buffer.as_entire_binding() }],
+            label: Some("view_camera_bind_group"),
+        });
+        let msaa_view = crate::render_settings::create_msaa_view(&state.device, &config, state.sample_count);
+        let view = Self { source, surface, config, camera, camera_buffer, camera_bind_group, msaa_view };
+        view.write_camera(&state.queue);
+        Ok(view)
+    }
+
+    fn write_camera(&self, queue: &wgpu::Queue) {
+        let mut uniform = CameraUniform::new();
+        uniform.update_view_proj(&self.camera.camera(self.config.width, self.config.height));
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub(crate) fn set_camera(&mut self, queue: &wgpu::Queue, camera: ViewCamera) {
+        self.camera = camera;
+        self.write_camera(queue);
+    }
+
+    pub(crate) fn resize(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, width: u32, height: u32, sample_count: u32) {
+        let max_dimension = device.limits().max_texture_dimension_2d;
+        self.config.width = width.min(max_dimension);
+        self.config.height = height.min(max_dimension);
+        self.surface.configure(device, &self.config);
+        self.msaa_view = crate::render_settings::create_msaa_view(device, &self.config, sample_count);
+        self.write_camera(queue);
+    }
+
+    /// Next texture to draw into. A lost or outdated surface is reconfigured
+    /// and skips this frame.
+    pub(crate) fn acquire(&mut self, device: &wgpu::Device) -> Option<wgpu::SurfaceTexture> {
+        match self.surface.get_current_texture() {
+            Ok(output) => Some(output),
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Timeout) => {
+                self.surface.configure(device, &self.config);
+                None
+            }
+            Err(e) => {
+                log::warn!("View failed to get current texture: {:?}", e);
+                None
+            }
+        }
+    }
+}
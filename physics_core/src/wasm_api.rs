@@ -0,0 +1,1432 @@
+//! Typed JavaScript / TypeScript API (`wasm_support` feature)
+//!
+//! The scalar `wasm_*` exports in `lib.rs` stay for the Kotlin/JS host; the
+//! exports here take and return objects for hand-written web code. Structs cross
+//! the boundary as plain JS objects through serde-wasm-bindgen, and the
+//! `typescript_custom_section` below gives them real types in the generated
+//! `physics_core.d.ts`. Entity ids are `bigint`, as everywhere else.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use js_sys::Function;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::assets;
+use crate::gpu_options;
+use crate::plugins::{self, HookPoint};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &str = r#"
+export interface BodyDesc {
+  x: number;
+  y: number;
+  halfExtent?: number;
+  vx?: number;
+  vy?: number;
+  materialId?: number;
+  /** Defaults to "dynamic" */
+  bodyType?: BodyType;
+  /** Conveyor velocity of the body's surface */
+  surfaceVx?: number;
+  surfaceVy?: number;
+  /** Group bits, see wasm_set_body_groups; defaults to 1 */
+  groups?: number;
+  /** Makes the body break apart on strong impacts */
+  fracture?: Fracture;
+  /** A one-way platform: bodies pass through from below and land on top */
+  oneWay?: boolean;
+}
+
+/**
+ * How a destructible body breaks. Missing fields keep the defaults
+ * (threshold 0.004 N·s, 4 fragments, minHalfExtent 0.01, 8 particles).
+ */
+export interface Fracture {
+  /** Collision impulse that breaks the body, N·s */
+  threshold?: number;
+  /** Target fragment count, 4..64; rounded down to a square grid */
+  fragments?: number;
+  /** Bodies whose fragments would be smaller than this do not break */
+  minHalfExtent?: number;
+  /** Debris thrown per fracture, 0..64 */
+  particles?: number;
+}
+
+export type BodyType = "dynamic" | "fixed" | "kinematic";
+
+export interface BodyState {
+  id: bigint;
+  x: number;
+  y: number;
+  vx: number;
+  vy: number;
+  rotation: number;
+  sleeping: boolean;
+}
+
+export type StressLayout = "pyramid" | "stack" | "rain";
+export type GravityPreset = "earth" | "moon" | "zeroG";
+
+export interface SceneConfig {
+  /** World scale on the current surface; applied before `stress` */
+  pixelsPerMeter?: number;
+  gravity?: [number, number, number];
+  /** Applied after `gravity` */
+  gravityPreset?: GravityPreset;
+  timeScale?: number;
+  paused?: boolean;
+  sleepEnabled?: boolean;
+  stress?: { bodies: number; layout: StressLayout };
+  solver?: SolverConfig;
+  substeps?: SubstepConfig;
+  forceFields?: ForceFields;
+  timeDilation?: TimeDilation;
+  water?: Water;
+  level?: LevelDesc;
+  /** Dynamic bodies leaving this rectangle are despawned; null stops that */
+  despawnBounds?: { min: [number, number]; max: [number, number] } | null;
+}
+
+/** Tile cells are row-major from the top row; 0 is empty */
+export type LevelShape =
+  | { type: "polyline"; points: [number, number][]; closed?: boolean }
+  | { type: "heightfield"; originX: number; spacing: number; heights: number[] }
+  | {
+      type: "terrain";
+      originX: number;
+      spacing: number;
+      /** Ground heights from originX on; ignored when `noise` is set */
+      heights?: number[];
+      /** Hills generated instead of listing heights */
+      noise?: { seed: number; samples: number; base: number; amplitude: number; wavelength: number; octaves: number };
+      /** Lower edge of the drawn fill; defaults to -1 */
+      bottom?: number;
+      /** Linear RGBA of the fill */
+      color?: [number, number, number, number];
+    }
+  /** Solid area inside a closed outline, convex or not */
+  | { type: "polygon"; points: [number, number][] }
+  | {
+      type: "tiles";
+      origin: [number, number];
+      tileSize: number;
+      columns: number;
+      cells: number[];
+      /** Atlas texture from registerMaterial; cell value n draws atlas cell n - 1 */
+      materialId?: number;
+      atlasColumns?: number;
+      atlasRows?: number;
+    };
+
+export interface TriggerZone {
+  name?: string;
+  /** Defaults to "sensor" */
+  kind?: "sensor" | "goal" | "hazard";
+  min: [number, number];
+  max: [number, number];
+  /** Group bits of the bodies the zone watches; defaults to all */
+  mask?: number;
+}
+
+export interface LevelDesc {
+  shapes: LevelShape[];
+  /** Defaults to true */
+  keepWalls?: boolean;
+  /** Replace the trigger zones; entering a goal wins, a hazard loses */
+  triggers?: TriggerZone[];
+}
+
+/** A named scene for wasm_register_scene; every field is optional */
+export interface SceneDesc {
+  level?: LevelDesc;
+  bodies?: {
+    x?: number;
+    y?: number;
+    halfExtent?: number;
+    vx?: number;
+    vy?: number;
+    bodyType?: "dynamic" | "fixed" | "kinematic";
+    groups?: number;
+    materialId?: number;
+  }[];
+  /** Gravity while the scene runs; omitted keeps the current one */
+  gravity?: [number, number];
+  /** Named gravity instead of `gravity` */
+  gravityPreset?: GravityPreset;
+  /** Started as the scene loads; `body` indexes `bodies` */
+  tweens?: {
+    body?: number;
+    property: "scale" | "rotation" | "color" | "cameraZoom" | "cameraPosition";
+    from: number[];
+    to: number[];
+    duration: number;
+    delay?: number;
+    easing?: Easing;
+  }[];
+  /** Each listed body gets its own copy of the machine */
+  stateMachines?: (StateMachineDesc & { bodies: number[] })[];
+  /** Level-of-detail thresholds while the scene runs; omitted keeps the current ones */
+  lod?: {
+    /** Farther bodies from the streaming focus are drawn as plain quads; null draws all in full */
+    renderDistance?: number | null;
+    /** Farther bodies fall asleep sooner; null simulates all alike */
+    physicsDistance?: number | null;
+    /** 1..100, multiplies the sleep thresholds past physicsDistance; defaults to 4 */
+    sleepBias?: number;
+  };
+  /** Registered when the scene loads, replacing prefabs of the same names */
+  prefabs?: { [name: string]: PrefabDesc };
+  /** Copies of the scene's prefabs spawned after `bodies` */
+  instances?: { prefab: string; x?: number; y?: number; angle?: number }[];
+  /** Texture asset names by the materialId the scene's bodies and prefabs use for them */
+  textures?: { [materialId: string]: string };
+}
+
+/** Movement a state switches to, starting where the entity is */
+export type MovementDesc =
+  | { type: "none" }
+  | { type: "linear"; velocity: [number, number] }
+  | { type: "sinusoidal"; amplitude: number; frequency: number; directionX: number }
+  | { type: "circular"; radius: number; angularSpeed: number }
+  | { type: "horizontalRandom"; speed: number; moveDuration: number; pauseDuration: number; seed?: number }
+  | ({ type: "seek"; arriveRadius?: number } & SteeringTarget)
+  | ({ type: "flee"; panicRadius?: number } & SteeringTarget)
+  | { type: "wander"; speed: number; turnRate: number; maxAcceleration?: number; seed?: number; avoid?: number };
+
+/** `target` 0 or omitted chases the nearest body in `targetGroups` */
+export interface SteeringTarget {
+  target?: number;
+  targetGroups?: number;
+  maxSpeed: number;
+  /** Omitted or 0 turns instantly */
+  maxAcceleration?: number;
+  /** Look-ahead of the obstacle feelers; omitted or 0 runs into things */
+  avoid?: number;
+}
+
+export interface StateDesc {
+  name: string;
+  /** Omitted fields keep what the previous state set */
+  movement?: MovementDesc;
+  clip?: { firstFrame?: number; frameCount?: number; frameDuration?: number; looping?: boolean };
+  materialId?: number;
+  /** Checked in order; the first that holds is taken */
+  transitions?: {
+    to: string;
+    /** Seconds in the state, group mask of a body hit, or a host event */
+    when: { after: number } | { collision: number } | { event: string };
+  }[];
+}
+
+export interface StateMachineDesc {
+  states: StateDesc[];
+  /** Defaults to the first state */
+  initial?: string;
+}
+
+export type PrefabShape =
+  | { type: "box"; halfExtent: number }
+  /** Outline around the prefab's origin, concave or not */
+  | { type: "polygon"; points: [number, number][] };
+
+/** Missing fields keep the defaults: a dynamic box of half extent 0.05 in group 1 */
+export interface PrefabDesc {
+  shape?: PrefabShape;
+  bodyType?: BodyType;
+  groups?: number;
+  materialId?: number;
+  /** Sprite sheet cells the body plays */
+  clip?: { firstFrame?: number; frameCount?: number; frameDuration?: number; looping?: boolean };
+  movement?: MovementDesc;
+  stateMachine?: StateMachineDesc;
+  /** Other prefabs spawned with this one, at (x, y) turned by `angle` radians in its frame */
+  parts?: { prefab: string; x?: number; y?: number; angle?: number }[];
+}
+
+export type Easing =
+  | "linear"
+  | "quadIn"
+  | "quadOut"
+  | "quadInOut"
+  | "cubicIn"
+  | "cubicOut"
+  | "cubicInOut"
+  | "sineInOut"
+  | "backOut"
+  | "elasticOut"
+  | "bounceOut";
+
+export type VolumeKind = "attract" | "repel" | "vortex";
+
+/** Fields missing from a wind, volume or water entry keep their defaults */
+export interface ForceFields {
+  wind?: { velocity?: [number, number]; gustiness?: number; gustFrequency?: number; drag?: number };
+  volumes?: { center?: [number, number]; radius?: number; kind?: VolumeKind; strength?: number }[];
+  buoyancy?: { minX?: number; maxX?: number; surfaceY?: number; fluidDensity?: number; drag?: number }[];
+}
+
+/**
+ * Water pools with a wavy surface, buoyancy and splashes. Missing fields keep
+ * the defaults (x -1..1, surface -0.5, bottom -1, 64 columns, density 2).
+ */
+export interface Water {
+  regions?: {
+    minX?: number;
+    maxX?: number;
+    surfaceY?: number;
+    bottomY?: number;
+    columns?: number;
+    fluidDensity?: number;
+    drag?: number;
+    stiffness?: number;
+    damping?: number;
+    waveSpeed?: number;
+    /** Fraction of a crossing body's vertical speed given to the surface */
+    splash?: number;
+    /** Linear RGBA */
+    color?: [number, number, number, number];
+  }[];
+}
+
+/**
+ * A map of the whole world in a corner of the canvas. Missing fields keep the
+ * defaults (200x150 px in the top-right corner, every group visible, bounds
+ * fitted to the bodies each frame).
+ */
+export interface Minimap {
+  corner?: "topLeft" | "topRight" | "bottomLeft" | "bottomRight";
+  /** Size including the border, px */
+  width?: number;
+  height?: number;
+  /** Gap to the canvas edges, px */
+  margin?: number;
+  borderWidth?: number;
+  /** Linear RGBA */
+  borderColor?: [number, number, number, number];
+  background?: [number, number, number, number];
+  /** Only bodies in one of these groups are shown */
+  visibleGroups?: number;
+  /** [minX, minY, maxX, maxY]; fitted to the bodies when missing */
+  bounds?: [number, number, number, number];
+  /** Smallest blip side, px */
+  minBlipSize?: number;
+}
+
+/** Key codes are the host's, as passed to the key event functions */
+export interface KeyBinding {
+  keyCode: number;
+  player: 1 | 2;
+  action: "up" | "down" | "left" | "right" | "jump";
+}
+
+/**
+ * Two players sharing the canvas. Missing fields keep the defaults: side by
+ * side, cameras like the main one, W/A/S/D and space for player 1 and the
+ * D-pad and enter for player 2 (Android key codes).
+ */
+export interface SplitScreen {
+  /** "sideBySide" puts player 1 on the left, "stacked" on top */
+  layout?: "sideBySide" | "stacked";
+  players?: [PlayerView, PlayerView];
+  bindings?: KeyBinding[];
+}
+
+export interface PlayerView {
+  /** Body the camera follows */
+  follow?: bigint;
+  centerX?: number;
+  centerY?: number;
+  viewHeight?: number;
+}
+
+/**
+ * Where an extra view looks. Missing fields keep the defaults: centered on
+ * the origin, 2.2 world units tall.
+ */
+export interface ViewCamera {
+  centerX?: number;
+  centerY?: number;
+  /** World units visible vertically */
+  viewHeight?: number;
+}
+
+/**
+ * A soft blob: a ring of small boxes tied with springs and inflated by an
+ * internal pressure. Missing fields keep the defaults (radius 0.2, 16
+ * segments, stiffness 400, damping 2, pressure 40).
+ */
+export interface SoftBodyDesc {
+  x?: number;
+  y?: number;
+  radius?: number;
+  /** Ring nodes, 3..64 */
+  segments?: number;
+  nodeHalfExtent?: number;
+  stiffness?: number;
+  damping?: number;
+  /** 0 makes a loose ring that collapses */
+  pressure?: number;
+  /** Linear RGBA of the fill; the rim is drawn darker */
+  color?: [number, number, number, number];
+}
+
+/**
+ * Slow-motion zones; bodies inside run at `scale` (0.01..1) times normal
+ * speed and the slowest zone wins where they overlap. Missing fields keep the
+ * defaults (min [-0.5, -0.5], max [0.5, 0.5], scale 0.25).
+ */
+export interface TimeDilation {
+  zones?: { min?: [number, number]; max?: [number, number]; scale?: number }[];
+}
+
+/** Missing fields keep the defaults (1 substep, auto above 3 m/s, at most 8) */
+export interface SubstepConfig {
+  substeps?: number;
+  auto?: boolean;
+  velocityThreshold?: number;
+  maxSubsteps?: number;
+}
+
+/** Missing fields take Rapier's defaults; erp 0 keeps the default stiffness */
+export interface SolverConfig {
+  solverIterations?: number;
+  frictionIterations?: number;
+  internalPgsIterations?: number;
+  erp?: number;
+  allowedLinearError?: number;
+  maxCcdSubsteps?: number;
+  contactDamping?: number;
+}
+
+export type Backend = "vulkan" | "metal" | "dx12" | "gl" | "webgpu";
+
+export interface InitOptions {
+  backends?: Backend[];
+  powerPreference?: "none" | "low-power" | "high-performance";
+  forceFallbackAdapter?: boolean;
+}
+
+export interface AdapterInfo {
+  name: string;
+  backend: string;
+  deviceType: string;
+  driver: string;
+  driverInfo: string;
+  surfaceFormats: string[];
+  surfaceFormat: string;
+  caps: { computeShaders: boolean; storageBuffers: boolean; bloom: boolean; gpuCulling: boolean };
+  limits: Record<string, number>;
+}
+
+export interface FrameStats {
+  fps: number;
+  cpuFrameMs: number;
+  gpuTiming: boolean;
+  gpuPasses: { pass: string; ms: number }[];
+  gpuTotalMs: number;
+  drawnInstances: number;
+  culledInstances: number;
+}
+
+export interface LogLine {
+  level: "ERROR" | "WARN" | "INFO" | "DEBUG" | "TRACE";
+  /** Module that logged */
+  target: string;
+  message: string;
+}
+
+export interface ProjectileStats {
+  capacity: number;
+  active: number;
+  peakActive: number;
+  fired: number;
+  expired: number;
+  hit: number;
+  dropped: number;
+}
+
+/** Collision sound; volume and pan are relative to wasm_set_audio_listener */
+export interface AudioEvent {
+  entityA: bigint;
+  entityB: bigint;
+  materialA: number;
+  materialB: number;
+  x: number;
+  y: number;
+  impactSpeed: number;
+  impulse: number;
+  /** 0..1, distance attenuation included */
+  volume: number;
+  /** Playback rate, 1 for a default-sized body */
+  pitch: number;
+  /** -1 (left) to 1 (right) */
+  pan: number;
+  distance: number;
+}
+
+/** A destructible body broke; `entity` no longer exists */
+export interface FractureEvent {
+  entity: bigint;
+  x: number;
+  y: number;
+  impulse: number;
+  halfExtent: number;
+  fragments: number;
+  fragmentHalfExtent: number;
+}
+
+/** A watched body entered or left trigger zone `zone` of the level */
+export interface TriggerEvent {
+  entity: bigint;
+  /** Index into the level's `triggers` */
+  zone: number;
+  /** 0 sensor, 1 goal, 2 hazard */
+  kind: number;
+  entered: boolean;
+}
+
+export type Outcome = "playing" | "won" | "lost";
+
+export type CollisionCallback = (a: bigint, b: bigint) => void;
+export type AudioEventCallback = (events: AudioEvent[]) => void;
+export type BodySleepCallback = (id: bigint) => void;
+export type ResetCallback = (resets: number) => void;
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "BodyDesc")]
+    pub type JsBodyDesc;
+    #[wasm_bindgen(typescript_type = "BodyState | undefined")]
+    pub type JsBodyState;
+    #[wasm_bindgen(typescript_type = "BodyState[]")]
+    pub type JsBodyStates;
+    #[wasm_bindgen(typescript_type = "SceneConfig")]
+    pub type JsSceneConfig;
+    #[wasm_bindgen(typescript_type = "LevelDesc")]
+    pub type JsLevelDesc;
+    #[wasm_bindgen(typescript_type = "SceneDesc")]
+    pub type JsSceneDesc;
+    #[wasm_bindgen(typescript_type = "StateMachineDesc | null")]
+    pub type JsStateMachineDesc;
+    #[wasm_bindgen(typescript_type = "MovementDesc")]
+    pub type JsMovementDesc;
+    #[wasm_bindgen(typescript_type = "ForceFields")]
+    pub type JsForceFields;
+    #[wasm_bindgen(typescript_type = "TimeDilation")]
+    pub type JsTimeDilation;
+    #[wasm_bindgen(typescript_type = "Water")]
+    pub type JsWater;
+    #[wasm_bindgen(typescript_type = "SoftBodyDesc")]
+    pub type JsSoftBodyDesc;
+    #[wasm_bindgen(typescript_type = "ViewCamera")]
+    pub type JsViewCamera;
+    #[wasm_bindgen(typescript_type = "Minimap | null")]
+    pub type JsMinimap;
+    #[wasm_bindgen(typescript_type = "SplitScreen | null")]
+    pub type JsSplitScreen;
+    #[wasm_bindgen(typescript_type = "Fracture")]
+    pub type JsFracture;
+    #[wasm_bindgen(typescript_type = "FractureEvent[]")]
+    pub type JsFractureEvents;
+    #[wasm_bindgen(typescript_type = "TriggerEvent[]")]
+    pub type JsTriggerEvents;
+    #[wasm_bindgen(typescript_type = "Outcome")]
+    pub type JsOutcome;
+    #[wasm_bindgen(typescript_type = "InitOptions")]
+    pub type JsInitOptions;
+    #[wasm_bindgen(typescript_type = "AdapterInfo | undefined")]
+    pub type JsAdapterInfo;
+    #[wasm_bindgen(typescript_type = "FrameStats | undefined")]
+    pub type JsFrameStats;
+    #[wasm_bindgen(typescript_type = "LogLine[]")]
+    pub type JsLogLines;
+    #[wasm_bindgen(typescript_type = "ProjectileStats")]
+    pub type JsProjectileStats;
+    #[wasm_bindgen(typescript_type = "AudioEvent[]")]
+    pub type JsAudioEvents;
+    #[wasm_bindgen(typescript_type = "AudioEventCallback | null")]
+    pub type JsAudioEventCallback;
+    #[wasm_bindgen(typescript_type = "CollisionCallback | null")]
+    pub type JsCollisionCallback;
+    #[wasm_bindgen(typescript_type = "BodySleepCallback | null")]
+    pub type JsBodySleepCallback;
+    #[wasm_bindgen(typescript_type = "ResetCallback | null")]
+    pub type JsResetCallback;
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BodyDesc {
+    pub x: f32,
+    pub y: f32,
+    pub half_extent: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub material_id: u32,
+    pub body_type: BodyType,
+    pub surface_vx: f32,
+    pub surface_vy: f32,
+    pub groups: u32,
+    pub fracture: Option<crate::fracture::FractureConfig>,
+    pub one_way: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyType {
+    #[default]
+    Dynamic,
+    Fixed,
+    Kinematic,
+}
+
+impl From<BodyType> for crate::rapier::prelude::RigidBodyType {
+    fn from(body_type: BodyType) -> Self {
+        match body_type {
+            BodyType::Dynamic => Self::Dynamic,
+            BodyType::Fixed => Self::Fixed,
+            BodyType::Kinematic => Self::KinematicPositionBased,
+        }
+    }
+}
+
+impl Default for BodyDesc {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            half_extent: 0.05,
+            vx: 0.0,
+            vy: 0.0,
+            material_id: crate::material::DEFAULT_MATERIAL,
+            body_type: BodyType::Dynamic,
+            surface_vx: 0.0,
+            surface_vy: 0.0,
+            groups: crate::groups::DEFAULT,
+            fracture: None,
+            one_way: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BodyState {
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub rotation: f32,
+    pub sleeping: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct StressConfig {
+    pub bodies: u32,
+    pub layout: crate::stress::StressLayout,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SceneConfig {
+    /// Applied before `stress`, so a generated scene is built at it
+    pub pixels_per_meter: Option<f32>,
+    pub gravity: Option<[f32; 3]>,
+    pub gravity_preset: Option<crate::gravity::GravityPreset>,
+    pub time_scale: Option<f32>,
+    pub paused: Option<bool>,
+    pub sleep_enabled: Option<bool>,
+    pub stress: Option<StressConfig>,
+    pub solver: Option<crate::solver_config::SolverConfig>,
+    pub substeps: Option<crate::substep::SubstepConfig>,
+    pub force_fields: Option<crate::force_fields::ForceFieldConfig>,
+    pub time_dilation: Option<crate::time_dilation::TimeDilationConfig>,
+    pub water: Option<crate::water::WaterConfig>,
+    pub level: Option<crate::level_geometry::LevelDesc>,
+    /// Outer None leaves the bounds alone, inner None turns them off
+    #[serde(default, deserialize_with = "double_option")]
+    pub despawn_bounds: Option<Option<DespawnBounds>>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DespawnBounds {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+/// Tell a missing field (no change) from an explicit null (off)
+fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Vulkan,
+    Metal,
+    Dx12,
+    Gl,
+    Webgpu,
+}
+
+impl Backend {
+    fn bit(self) -> u32 {
+        match self {
+            Backend::Vulkan => gpu_options::BACKEND_VULKAN,
+            Backend::Metal => gpu_options::BACKEND_METAL,
+            Backend::Dx12 => gpu_options::BACKEND_DX12,
+            Backend::Gl => gpu_options::BACKEND_GL,
+            Backend::Webgpu => gpu_options::BACKEND_BROWSER_WEBGPU,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PowerPreference {
+    None,
+    LowPower,
+    HighPerformance,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InitOptions {
+    /// Empty selects every backend
+    pub backends: Vec<Backend>,
+    pub power_preference: Option<PowerPreference>,
+    pub force_fallback_adapter: bool,
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    // Entity ids exceed 2^53, so send 64-bit integers as bigint
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true);
+    value.serialize(&serializer).map_err(Into::into)
+}
+
+fn body_state(id: u64) -> Option<BodyState> {
+    crate::body_info_internal(id).map(|info| BodyState {
+        id,
+        x: info.x,
+        y: info.y,
+        vx: info.vx,
+        vy: info.vy,
+        rotation: info.rotation,
+        sleeping: info.sleeping,
+    })
+}
+
+/// Spawn a box; returns its entity id
+#[wasm_bindgen]
+pub fn wasm_spawn_body(desc: JsBodyDesc) -> Result<u64, JsValue> {
+    let desc: BodyDesc = serde_wasm_bindgen::from_value(desc.into())?;
+    let fracture = match desc.fracture.map(crate::fracture::FractureConfig::validated) {
+        Some(None) => return Err(JsValue::from_str("wasm_spawn_body: invalid fracture")),
+        fracture => fracture.flatten(),
+    };
+    // Checked here too so the error names the bad field
+    let valid = crate::validation::finite("position", &[desc.x, desc.y])
+        .and(crate::validation::size("halfExtent", desc.half_extent))
+        .and(crate::validation::finite("velocity", &[desc.vx, desc.vy, desc.surface_vx, desc.surface_vy]));
+    if let Err(error) = valid {
+        return Err(JsValue::from_str(&format!("wasm_spawn_body: {}", crate::validation::reject(error))));
+    }
+    let surface_velocity = [desc.surface_vx, desc.surface_vy];
+    let id = crate::spawn_body_with_internal(
+        desc.x,
+        desc.y,
+        desc.half_extent,
+        desc.body_type.into(),
+        surface_velocity,
+        desc.groups,
+    )
+    .ok_or_else(|| JsValue::from_str("wasm_spawn_body: simulation not running"))?;
+    crate::with_rigid_body_mut(id, |rb| rb.set_linvel(crate::precision::planar([desc.vx, desc.vy]), true));
+    if desc.material_id != crate::material::DEFAULT_MATERIAL {
+        crate::set_entity_material_internal(id, desc.material_id);
+    }
+    if fracture.is_some() {
+        crate::set_destructible_internal(id, fracture);
+    }
+    if desc.one_way {
+        crate::set_one_way_internal(id, true);
+    }
+    Ok(id)
+}
+
+/// Make an existing body break on strong impacts; see wasm_clear_destructible
+#[wasm_bindgen]
+pub fn wasm_set_destructible(id: u64, fracture: JsFracture) -> Result<bool, JsValue> {
+    let fracture: crate::fracture::FractureConfig = serde_wasm_bindgen::from_value(fracture.into())?;
+    Ok(crate::set_destructible_internal(id, Some(fracture)))
+}
+
+/// Take up to `max` queued fractures, oldest first
+#[wasm_bindgen]
+pub fn wasm_poll_fracture_events(max: u32) -> Result<JsFractureEvents, JsValue> {
+    Ok(to_js(&crate::poll_fracture_events_internal(max as usize))?.unchecked_into())
+}
+
+/// Take up to `max` queued trigger zone events, oldest first
+#[wasm_bindgen]
+pub fn wasm_poll_trigger_events(max: u32) -> Result<JsTriggerEvents, JsValue> {
+    Ok(to_js(&crate::poll_trigger_events_internal(max as usize))?.unchecked_into())
+}
+
+#[wasm_bindgen]
+pub fn wasm_level_outcome() -> Result<JsOutcome, JsValue> {
+    Ok(to_js(&crate::level_outcome_internal().unwrap_or_default())?.unchecked_into())
+}
+
+#[wasm_bindgen]
+pub fn wasm_body_state(id: u64) -> Result<JsBodyState, JsValue> {
+    match body_state(id) {
+        Some(state) => Ok(to_js(&state)?.unchecked_into()),
+        None => Ok(JsValue::UNDEFINED.unchecked_into()),
+    }
+}
+
+/// State of every body in the scene
+#[wasm_bindgen]
+pub fn wasm_body_states() -> Result<JsBodyStates, JsValue> {
+    let states: Vec<BodyState> = crate::entity_ids_internal().into_iter().filter_map(body_state).collect();
+    Ok(to_js(&states)?.unchecked_into())
+}
+
+/// Adapter selection for the next `wasm_init`
+#[wasm_bindgen]
+pub fn wasm_configure_gpu(options: JsInitOptions) -> Result<(), JsValue> {
+    let options: InitOptions = serde_wasm_bindgen::from_value(options.into())?;
+    let backends = options.backends.iter().fold(0, |bits, backend| bits | backend.bit());
+    let power_preference = match options.power_preference {
+        Some(PowerPreference::None) => gpu_options::POWER_PREFERENCE_NONE,
+        Some(PowerPreference::LowPower) => gpu_options::POWER_PREFERENCE_LOW_POWER,
+        Some(PowerPreference::HighPerformance) | None => gpu_options::POWER_PREFERENCE_HIGH_PERFORMANCE,
+    };
+    crate::set_gpu_init_options_internal(backends, power_preference, options.force_fallback_adapter);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn wasm_adapter_info() -> Result<JsAdapterInfo, JsValue> {
+    match crate::adapter_info_internal() {
+        Some(json) => Ok(js_sys::JSON::parse(&json)?.unchecked_into()),
+        None => Ok(JsValue::UNDEFINED.unchecked_into()),
+    }
+}
+
+#[wasm_bindgen]
+pub fn wasm_frame_stats() -> Result<JsFrameStats, JsValue> {
+    match crate::frame_stats_internal() {
+        Some(json) => Ok(js_sys::JSON::parse(&json)?.unchecked_into()),
+        None => Ok(JsValue::UNDEFINED.unchecked_into()),
+    }
+}
+
+/// Last log lines, oldest first; `level` 1 (errors only) to 5 (trace), 0 for all
+#[wasm_bindgen]
+pub fn wasm_recent_logs(level: u32) -> Result<JsLogLines, JsValue> {
+    Ok(js_sys::JSON::parse(&crate::recent_logs_internal(level))?.unchecked_into())
+}
+
+/// Preallocate `capacity` projectiles of `radius`; 0 removes the pool
+#[wasm_bindgen]
+pub fn wasm_configure_projectiles(capacity: u32, radius: f32) -> bool {
+    crate::configure_projectiles_internal(capacity, radius)
+}
+
+/// Launch a pooled projectile that is parked again after `ttl` seconds (0 for
+/// never) or on its first collision. Undefined when the pool is exhausted.
+#[wasm_bindgen]
+pub fn wasm_fire_projectile(origin_x: f32, origin_y: f32, vx: f32, vy: f32, ttl: f32) -> Option<u64> {
+    crate::fire_projectile_internal(origin_x, origin_y, vx, vy, ttl)
+}
+
+#[wasm_bindgen]
+pub fn wasm_projectile_stats() -> Result<JsProjectileStats, JsValue> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Stats {
+        capacity: u32,
+        active: u32,
+        peak_active: u32,
+        // Plain numbers rather than bigints; exact up to 2^53 shots
+        fired: f64,
+        expired: f64,
+        hit: f64,
+        dropped: f64,
+    }
+    let stats = crate::projectile_stats_internal();
+    let stats = Stats {
+        capacity: stats.capacity,
+        active: stats.active,
+        peak_active: stats.peak_active,
+        fired: stats.fired as f64,
+        expired: stats.expired as f64,
+        hit: stats.hit as f64,
+        dropped: stats.dropped as f64,
+    };
+    Ok(to_js(&stats)?.unchecked_into())
+}
+
+/// Take up to `max` queued collision sounds, oldest first
+#[wasm_bindgen]
+pub fn wasm_poll_audio_events(max: u32) -> Result<JsAudioEvents, JsValue> {
+    Ok(to_js(&crate::poll_audio_events_internal(max as usize))?.unchecked_into())
+}
+
+/// Replace the static level geometry; returns the number of colliders made
+#[wasm_bindgen]
+pub fn wasm_load_level(level: JsLevelDesc) -> Result<u32, JsValue> {
+    let level: crate::level_geometry::LevelDesc = serde_wasm_bindgen::from_value(level.into())?;
+    crate::load_level_internal(&level).map(|count| count as u32).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Register `scene` under `name`, replacing a scene of the same name
+#[wasm_bindgen]
+pub fn wasm_register_scene(name: &str, scene: JsSceneDesc) -> Result<(), JsValue> {
+    let scene: crate::scenes::SceneDesc = serde_wasm_bindgen::from_value(scene.into())?;
+    crate::register_scene_internal(name, scene).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn wasm_remove_scene(name: &str) -> bool {
+    crate::remove_scene_internal(name)
+}
+
+/// Rebuild the simulation as scene `name`, fading in over `fade_seconds`;
+/// returns the number of bodies spawned
+#[wasm_bindgen]
+pub fn wasm_switch_scene(name: &str, fade_seconds: f32) -> Result<u32, JsValue> {
+    crate::switch_scene_internal(name, fade_seconds).map(|count| count as u32).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Switch to the scene after the current one; returns its name, or
+/// undefined after the last scene
+#[wasm_bindgen]
+pub fn wasm_load_next_scene(fade_seconds: f32) -> Result<Option<String>, JsValue> {
+    crate::load_next_scene_internal(fade_seconds).map_err(|e| JsValue::from_str(&e))
+}
+
+thread_local! {
+    static ASSET_BASE_URL: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// URL asset names are fetched relative to; empty, the default, fetches
+/// them relative to the page
+#[wasm_bindgen]
+pub fn wasm_set_asset_base_url(url: &str) {
+    ASSET_BASE_URL.with(|base| *base.borrow_mut() = url.trim_end_matches('/').to_string());
+}
+
+async fn fetch_asset_bytes(name: &str) -> Result<Vec<u8>, JsValue> {
+    assets::check_name(name).map_err(|e| JsValue::from_str(&e))?;
+    let url = ASSET_BASE_URL.with(|base| match base.borrow().as_str() {
+        "" => name.to_string(),
+        base => format!("{}/{}", base, name),
+    });
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window to fetch from"))?;
+    let response: web_sys::Response = wasm_bindgen_futures::JsFuture::from(window.fetch_with_str(&url)).await?.dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!("{}: HTTP {}", url, response.status())));
+    }
+    let buffer = wasm_bindgen_futures::JsFuture::from(response.array_buffer()?).await?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+/// Fetch asset `name` ("textures/ball.png") into the preloaded assets, where
+/// scenes and textures loaded by name read it
+#[wasm_bindgen]
+pub async fn wasm_fetch_asset(name: String) -> Result<(), JsValue> {
+    let bytes = fetch_asset_bytes(&name).await?;
+    assets::preload(&name, bytes).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Fetch the best variant of texture `name` the device samples (see
+/// `ktx2_lite::variant_names`) unless one is preloaded; fails with the
+/// error of the last variant tried when none can be fetched
+async fn fetch_texture_variant(name: &str) -> Result<(), JsValue> {
+    let variants = crate::texture_variant_names(name);
+    if variants.iter().any(|variant| assets::is_preloaded(variant)) {
+        return Ok(());
+    }
+    let mut error = JsValue::UNDEFINED;
+    for variant in variants {
+        match wasm_fetch_asset(variant).await {
+            Ok(()) => return Ok(()),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+/// Fetch texture asset `name`, or its best compressed variant, unless it is
+/// preloaded and register it as a material; resolves to its id
+#[wasm_bindgen]
+pub async fn wasm_fetch_texture_asset(name: String) -> Result<u32, JsValue> {
+    fetch_texture_variant(&name).await?;
+    crate::load_texture_asset_internal(&name).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Fetch scene asset `name` and the textures it names unless they are
+/// preloaded, then register it under its file name without the extension
+/// and switch to it; resolves to the number of bodies spawned. Textures
+/// that fail to fetch leave their bodies with the default material.
+#[wasm_bindgen]
+pub async fn wasm_fetch_scene_asset(name: String) -> Result<u32, JsValue> {
+    if !assets::is_preloaded(&name) {
+        wasm_fetch_asset(name.clone()).await?;
+    }
+    let text = assets::read_text(&name).map_err(|e| JsValue::from_str(&e))?;
+    let scene = crate::parse_scene(&name, &text, name.to_ascii_lowercase().ends_with(".ron")).map_err(|e| JsValue::from_str(&e))?;
+    for texture in scene.textures.values() {
+        if let Err(e) = fetch_texture_variant(texture).await {
+            log::warn!("wasm_fetch_scene_asset: {}: {:?}", texture, e);
+        }
+    }
+    crate::load_scene_asset_internal(&name).map(|count| count as u32).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Spawn a body shaped like the outline `points` (x, y pairs relative to
+/// (x, y), concave or not); `body_type` is 0 dynamic, 1 fixed, 2 kinematic.
+/// Returns its entity id.
+#[wasm_bindgen]
+pub fn wasm_spawn_polygon(x: f32, y: f32, points: &[f32], body_type: i32) -> Result<u64, JsValue> {
+    let body_type = crate::body_type_from_raw(body_type).ok_or_else(|| JsValue::from_str("wasm_spawn_polygon: unknown body type"))?;
+    let outline: Vec<[f32; 2]> = points.chunks_exact(2).map(|p| [p[0], p[1]]).collect();
+    crate::spawn_polygon_internal(x, y, &outline, body_type, crate::groups::DEFAULT).map_err(|e| JsValue::from_str(&e))
+}
+
+/// .gltf or .glb file bytes; returns the model id for `wasm_attach_model`
+#[wasm_bindgen]
+pub fn wasm_load_model(bytes: &[u8]) -> Result<u32, JsValue> {
+    crate::load_model_internal(bytes).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Draw the body with the model in the 3D pass; model 0 removes it
+#[wasm_bindgen]
+pub fn wasm_attach_model(entity_id: u64, model_id: u32, scale: f32) -> bool {
+    crate::attach_model_internal(entity_id, model_id, scale)
+}
+
+/// Start or stop the 3D demo; returns the number of bodies dropped
+#[wasm_bindgen]
+pub fn wasm_set_3d_mode(enabled: bool, body_count: u32) -> Result<u32, JsValue> {
+    crate::set_3d_mode_internal(enabled, body_count).map(|count| count as u32).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Orbit and zoom the 3D demo's camera, e.g. from wheel events
+#[wasm_bindgen]
+pub fn wasm_orbit_3d_camera(dx: f32, dy: f32, zoom: f32) -> bool {
+    crate::orbit_3d_camera_internal(dx, dy, zoom)
+}
+
+/// Ease `property` (0 scale, 1 rotation, 2 RGB color, 3 camera zoom, 4
+/// camera position) of the entity from `from` to `to` over `duration`
+/// seconds; `easing` codes match the C header. Undefined for unknown
+/// entities, codes or value counts.
+#[wasm_bindgen]
+pub fn wasm_animate_property(entity_id: u64, property: u32, from: &[f32], to: &[f32], duration: f32, easing: u32) -> Option<u64> {
+    let property = crate::tween::TweenProperty::from_u32(property)?;
+    let easing = crate::tween::Easing::from_u32(easing)?;
+    crate::animate_property_internal(entity_id, property, from, to, duration, easing)
+}
+
+#[wasm_bindgen]
+pub fn wasm_cancel_tween(tween_id: u64) -> bool {
+    crate::cancel_tween_internal(tween_id)
+}
+
+/// Give the entity a state machine, replacing any it has; null removes it
+#[wasm_bindgen]
+pub fn wasm_set_state_machine(entity_id: u64, machine: JsStateMachineDesc) -> Result<(), JsValue> {
+    let machine: Option<crate::state_machine::StateMachineDesc> = serde_wasm_bindgen::from_value(machine.into())?;
+    crate::set_state_machine_internal(entity_id, machine).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Send `event` to the entity's machine, or to every machine for entity 0;
+/// returns how many received it
+#[wasm_bindgen]
+pub fn wasm_send_state_event(entity_id: u64, event: &str) -> u32 {
+    crate::send_state_event_internal(entity_id, event) as u32
+}
+
+/// Name of the state the entity's machine is in
+#[wasm_bindgen]
+pub fn wasm_entity_state(entity_id: u64) -> Option<String> {
+    crate::entity_state_internal(entity_id).map(|(_, name)| name)
+}
+
+/// Drive the body with a path or steering movement, starting where it is
+#[wasm_bindgen]
+pub fn wasm_set_movement(entity_id: u64, movement: JsMovementDesc) -> Result<(), JsValue> {
+    let movement: crate::state_machine::MovementDesc = serde_wasm_bindgen::from_value(movement.into())?;
+    crate::set_movement_internal(entity_id, movement).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Run a packed command buffer (see `command_buffer`; fill it through a
+/// little-endian `DataView`). Returns one result per command: the entity id
+/// for spawns, 1 for other commands that took effect and 0 for ones that failed.
+#[wasm_bindgen]
+pub fn wasm_submit_commands(commands: &[u8]) -> Result<Vec<u64>, JsValue> {
+    crate::submit_commands_internal(commands).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Share every body's transform through a region rewritten each frame, with
+/// room for `capacity` bodies (0 frees it). Returns a little-endian view of
+/// it (layout in `transform_export`), or undefined when freed.
+#[wasm_bindgen]
+pub fn wasm_enable_transform_export(capacity: u32) -> Option<js_sys::DataView> {
+    crate::enable_transform_export_internal(capacity)?;
+    wasm_transform_export_view()
+}
+
+/// A fresh view of the shared transform region. Views go stale when wasm
+/// memory grows, so take one per frame rather than keeping it.
+#[wasm_bindgen]
+pub fn wasm_transform_export_view() -> Option<js_sys::DataView> {
+    let (ptr, len) = crate::transform_export_region_internal()?;
+    let memory: js_sys::WebAssembly::Memory = wasm_bindgen::memory().unchecked_into();
+    Some(js_sys::DataView::new(&memory.buffer().unchecked_into(), ptr as usize, len))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn canvas_by_id(canvas_id: &str) -> Result<web_sys::HtmlCanvasElement, JsValue> {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(canvas_id))
+        .ok_or_else(|| JsValue::from_str(&format!("canvas '{}' not found", canvas_id)))?
+        .dyn_into()
+        .map_err(|_| JsValue::from_str(&format!("'{}' is not a canvas", canvas_id)))
+}
+
+/// Draw the scene into another canvas as well, through its own camera.
+/// Views draw bodies only, after the main canvas. Returns the view id.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn wasm_add_view(canvas_id: &str, width: u32, height: u32, camera: JsViewCamera) -> Result<u32, JsValue> {
+    let camera: crate::views::ViewCamera = serde_wasm_bindgen::from_value(camera.into())?;
+    let source = crate::SurfaceSource::Canvas(canvas_by_id(canvas_id)?);
+    crate::add_view_internal(source, width, height, camera).ok_or_else(|| {
+        JsValue::from_str("wasm_add_view: not initialized, invalid size or camera, or too many views")
+    })
+}
+
+#[wasm_bindgen]
+pub fn wasm_remove_view(view_id: u32) -> bool {
+    crate::remove_view_internal(view_id)
+}
+
+/// Call when the view's canvas changes size
+#[wasm_bindgen]
+pub fn wasm_resize_view(view_id: u32, width: u32, height: u32) -> bool {
+    crate::resize_view_internal(view_id, width, height)
+}
+
+#[wasm_bindgen]
+pub fn wasm_set_view_camera(view_id: u32, camera: JsViewCamera) -> Result<bool, JsValue> {
+    let camera: crate::views::ViewCamera = serde_wasm_bindgen::from_value(camera.into())?;
+    Ok(crate::set_view_camera_internal(view_id, camera))
+}
+
+/// Split the canvas between two players, or go back to one camera with null
+#[wasm_bindgen]
+pub fn wasm_set_split_screen(split: JsSplitScreen) -> Result<bool, JsValue> {
+    let split: Option<crate::split_screen::SplitScreen> = serde_wasm_bindgen::from_value(split.into())?;
+    Ok(crate::set_split_screen_internal(split))
+}
+
+/// Hand the body to player 1 or 2; 0 lets it answer to both players
+#[wasm_bindgen]
+pub fn wasm_set_player(entity_id: u64, player: u8) -> bool {
+    crate::set_player_internal(entity_id, player)
+}
+
+/// Every body's state for a remote simulation, keeping only what changed
+/// since `baseline_tick` (a tick returned by the remote `wasm_apply_snapshot`,
+/// or 0 for everything). Undefined before `wasm_init`.
+#[wasm_bindgen]
+pub fn wasm_get_snapshot_delta(baseline_tick: u64) -> Option<Vec<u8>> {
+    crate::snapshot_delta_internal(baseline_tick)
+}
+
+/// Apply a remote snapshot; returns its tick to send back as the next baseline
+#[wasm_bindgen]
+pub fn wasm_apply_snapshot(bytes: &[u8]) -> Result<u64, JsValue> {
+    crate::apply_snapshot_internal(bytes).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Keep `window` steps of input and predicted states for rolling back (0
+/// turns it off); predictions within `tolerance` stand
+#[wasm_bindgen]
+pub fn wasm_set_prediction(window: u32, tolerance: f32) -> bool {
+    crate::set_prediction_internal(window, tolerance)
+}
+
+#[wasm_bindgen]
+pub fn wasm_set_predicted(entity_id: u64, predicted: bool) -> bool {
+    crate::set_predicted_internal(entity_id, predicted)
+}
+
+/// Apply an authoritative snapshot, rolling back and re-simulating if a
+/// predicted body was off; returns its tick to send back as the next baseline
+#[wasm_bindgen]
+pub fn wasm_reconcile_snapshot(bytes: &[u8]) -> Result<u64, JsValue> {
+    crate::reconcile_snapshot_internal(bytes).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Looping GIF of the last `seconds` of the rewind history at `fps`, `scale`
+/// times the canvas size, e.g. for a download link or a bug report
+#[wasm_bindgen]
+pub fn wasm_export_clip(seconds: f32, fps: u32, scale: f32) -> Result<Vec<u8>, JsValue> {
+    crate::export_clip_internal(seconds, fps, scale).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Show the minimap, or hide it with null
+#[wasm_bindgen]
+pub fn wasm_set_minimap(config: JsMinimap) -> Result<bool, JsValue> {
+    let config: Option<crate::minimap::MinimapConfig> = serde_wasm_bindgen::from_value(config.into())?;
+    Ok(crate::set_minimap_internal(config))
+}
+
+/// Replace every wind, force volume and water region
+#[wasm_bindgen]
+pub fn wasm_set_force_fields(fields: JsForceFields) -> Result<bool, JsValue> {
+    let fields: crate::force_fields::ForceFieldConfig = serde_wasm_bindgen::from_value(fields.into())?;
+    Ok(crate::set_force_fields_internal(fields))
+}
+
+/// Replace every water region; the new water starts calm
+#[wasm_bindgen]
+pub fn wasm_set_water(config: JsWater) -> Result<bool, JsValue> {
+    let config: crate::water::WaterConfig = serde_wasm_bindgen::from_value(config.into())?;
+    Ok(crate::set_water_internal(config))
+}
+
+/// Spawn a soft blob; returns its id for `wasm_remove_soft_body`
+#[wasm_bindgen]
+pub fn wasm_spawn_soft_body(desc: JsSoftBodyDesc) -> Result<u64, JsValue> {
+    let desc: crate::soft_body::SoftBodyDesc = serde_wasm_bindgen::from_value(desc.into())?;
+    crate::spawn_soft_body_internal(desc)
+        .ok_or_else(|| JsValue::from_str("wasm_spawn_soft_body: simulation not running or invalid description"))
+}
+
+/// Replace every slow-motion zone
+#[wasm_bindgen]
+pub fn wasm_set_time_dilation(config: JsTimeDilation) -> Result<bool, JsValue> {
+    let config: crate::time_dilation::TimeDilationConfig = serde_wasm_bindgen::from_value(config.into())?;
+    Ok(crate::set_time_dilation_internal(config))
+}
+
+/// Apply the fields present in `config`; missing ones are left unchanged
+#[wasm_bindgen]
+pub fn wasm_configure_scene(config: JsSceneConfig) -> Result<(), JsValue> {
+    let config: SceneConfig = serde_wasm_bindgen::from_value(config.into())?;
+    if let Some(pixels_per_meter) = config.pixels_per_meter {
+        crate::set_world_scale_internal(pixels_per_meter);
+    }
+    if let Some(stress) = config.stress {
+        crate::spawn_stress_scene_internal(stress.bodies, stress.layout as i32);
+    }
+    if let Some([x, y, z]) = config.gravity {
+        crate::set_gravity_vector_internal(x, y, z);
+    }
+    if let Some([x, y, z]) = config.gravity_preset.map(|preset| preset.gravity()) {
+        crate::set_gravity_vector_internal(x, y, z);
+    }
+    if let Some(enabled) = config.sleep_enabled {
+        crate::set_sleep_enabled_internal(enabled);
+    }
+    if let Some(solver) = config.solver {
+        crate::set_solver_config_internal(solver);
+    }
+    if let Some(substeps) = config.substeps {
+        crate::set_substeps_internal(substeps);
+    }
+    if let Some(fields) = config.force_fields {
+        crate::set_force_fields_internal(fields);
+    }
+    if let Some(time_dilation) = config.time_dilation {
+        crate::set_time_dilation_internal(time_dilation);
+    }
+    if let Some(water) = config.water {
+        crate::set_water_internal(water);
+    }
+    if let Some(level) = config.level {
+        crate::load_level_internal(&level).map_err(|e| JsValue::from_str(&e))?;
+    }
+    if let Some(bounds) = config.despawn_bounds {
+        let ok = match bounds {
+            Some(DespawnBounds { min, max }) => crate::set_despawn_bounds_internal(true, min[0], min[1], max[0], max[1]),
+            None => crate::set_despawn_bounds_internal(false, 0.0, 0.0, 0.0, 0.0),
+        };
+        if !ok {
+            return Err(JsValue::from_str("wasm_configure_scene: despawnBounds must be a finite, non-empty rectangle"));
+        }
+    }
+    if let Ok(mut guard) = crate::PHYSICS_STATE.lock() {
+        if let Some(physics) = guard.0.as_mut() {
+            if let Some(scale) = config.time_scale {
+                physics.time_scale = scale.max(0.0);
+                physics.time_ramp = None;
+            }
+            if let Some(paused) = config.paused {
+                physics.paused = paused;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct JsEvents {
+    on_collision: Option<Function>,
+    on_body_sleep: Option<Function>,
+    on_audio: Option<Function>,
+    on_reset: Option<Function>,
+    sleeping: HashSet<u64>,
+    hook_id: Option<u32>,
+    reset_hook_id: Option<u32>,
+}
+
+thread_local! {
+    static JS_EVENTS: RefCell<JsEvents> = RefCell::new(JsEvents::default());
+}
+
+/// Call `callback(a, b)` after each step for every new contact (0 = wall);
+/// `null` removes it
+#[wasm_bindgen]
+pub fn wasm_on_collision(callback: JsCollisionCallback) {
+    let callback: JsValue = callback.into();
+    JS_EVENTS.with(|events| events.borrow_mut().on_collision = callback.dyn_into().ok());
+    update_event_hook();
+}
+
+/// Call `callback(id)` after each step for every body that fell asleep;
+/// `null` removes it
+#[wasm_bindgen]
+pub fn wasm_on_body_sleep(callback: JsBodySleepCallback) {
+    let callback: JsValue = callback.into();
+    JS_EVENTS.with(|events| {
+        let mut events = events.borrow_mut();
+        events.on_body_sleep = callback.dyn_into().ok();
+        // Bodies already asleep now should not be reported as new
+        events.sleeping = crate::sleeping_bodies_internal().into_iter().collect();
+    });
+    update_event_hook();
+}
+
+/// Call `callback(events)` after each step that produced collision sounds,
+/// instead of queueing them for wasm_poll_audio_events; `null` removes it
+#[wasm_bindgen]
+pub fn wasm_on_audio_event(callback: JsAudioEventCallback) {
+    let callback: JsValue = callback.into();
+    JS_EVENTS.with(|events| events.borrow_mut().on_audio = callback.dyn_into().ok());
+    update_event_hook();
+}
+
+/// Call `callback(resets)` once at the first frame after the scene was reset
+/// or replaced, when every entity id held from before is dead; `null`
+/// removes it
+#[wasm_bindgen]
+pub fn wasm_on_reset(callback: JsResetCallback) {
+    let callback: JsValue = callback.into();
+    JS_EVENTS.with(|events| {
+        let mut events = events.borrow_mut();
+        events.on_reset = callback.dyn_into().ok();
+        match (events.on_reset.is_some(), events.reset_hook_id) {
+            (true, None) => {
+                events.reset_hook_id = Some(plugins::register_hook(HookPoint::Reset, |_, _| dispatch_reset()));
+            }
+            (false, Some(id)) => {
+                plugins::unregister_hook(id);
+                events.reset_hook_id = None;
+            }
+            _ => {}
+        }
+    });
+}
+
+fn dispatch_reset() {
+    let on_reset = JS_EVENTS.with(|events| {
+        let mut events = events.borrow_mut();
+        // The old scene's bodies are gone, asleep or not
+        events.sleeping.clear();
+        events.on_reset.clone()
+    });
+    if let Some(callback) = on_reset {
+        if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from(crate::reset_count_internal())) {
+            log::warn!("reset callback threw: {:?}", e);
+        }
+    }
+}
+
+/// Keep one PostPhysics hook registered while any callback is set
+fn update_event_hook() {
+    JS_EVENTS.with(|events| {
+        let mut events = events.borrow_mut();
+        let wanted = events.on_collision.is_some() || events.on_body_sleep.is_some() || events.on_audio.is_some();
+        match (wanted, events.hook_id) {
+            (true, None) => {
+                events.hook_id = Some(plugins::register_hook(HookPoint::PostPhysics, |_, _| dispatch_events()));
+            }
+            (false, Some(id)) => {
+                plugins::unregister_hook(id);
+                events.hook_id = None;
+            }
+            _ => {}
+        }
+    });
+}
+
+fn dispatch_events() {
+    // Callbacks may call back into this module, so clone them out first
+    let (on_collision, on_body_sleep, on_audio) = JS_EVENTS.with(|events| {
+        let events = events.borrow();
+        (events.on_collision.clone(), events.on_body_sleep.clone(), events.on_audio.clone())
+    });
+
+    if let Some(callback) = on_collision {
+        for (a, b) in crate::recent_collisions_internal() {
+            if let Err(e) = callback.call2(&JsValue::NULL, &JsValue::from(a), &JsValue::from(b)) {
+                log::warn!("collision callback threw: {:?}", e);
+            }
+        }
+    }
+
+    if let Some(callback) = on_audio {
+        let events = crate::poll_audio_events_internal(crate::audio::MAX_QUEUED);
+        if !events.is_empty() {
+            match to_js(&events) {
+                Ok(events) => {
+                    if let Err(e) = callback.call1(&JsValue::NULL, &events) {
+                        log::warn!("audio event callback threw: {:?}", e);
+                    }
+                }
+                Err(e) => log::warn!("audio events: {:?}", e),
+            }
+        }
+    }
+
+    if let Some(callback) = on_body_sleep {
+        let sleeping: HashSet<u64> = crate::sleeping_bodies_internal().into_iter().collect();
+        let newly_asleep: Vec<u64> = JS_EVENTS.with(|events| {
+            let mut events = events.borrow_mut();
+            let fresh = sleeping.difference(&events.sleeping).copied().collect();
+            events.sleeping = sleeping;
+            fresh
+        });
+        for id in newly_asleep {
+            if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from(id)) {
+                log::warn!("body sleep callback threw: {:?}", e);
+            }
+        }
+    }
+}
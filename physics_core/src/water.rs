@@ -0,0 +1,533 @@
+//! Stylized water: a springy surface, buoyancy and splashes
+//!
+//! Each `WaterRegion` fills `min_x..max_x` from `bottom_y` up to a surface
+//! made of `columns` vertical springs. Every column is pulled back to rest by
+//! `stiffness` and slowed by `damping`, and neighbours pass height
+//! differences along at `wave_speed`, so a poke travels across the pool and
+//! reflects off its walls.
+//!
+//! Bodies are floated with the same Archimedes model as the flat buoyancy
+//! regions in force_fields.rs, except the submerged depth is measured against
+//! the moving surface. A body crossing the surface kicks the columns under it
+//! by `splash` times its vertical speed and throws droplets, which fly
+//! ballistically until they land back in the water or expire. Droplets are
+//! drawn with the surface and never collide with anything.
+
+use serde::Deserialize;
+
+use crate::force_fields::BodySample;
+
+/// Most regions kept; extra regions in a config are ignored
+pub const MAX_REGIONS: usize = 16;
+pub const MAX_COLUMNS: u32 = 512;
+/// Most droplets in flight; new splashes throw none while full
+pub const MAX_DROPLETS: usize = 1024;
+
+/// Droplet half size, world units
+const DROPLET_SIZE: f32 = 0.01;
+/// Seconds a droplet lives at most
+const DROPLET_LIFE: f32 = 1.0;
+/// Slower crossings splash the surface but throw no droplets
+const DROPLET_MIN_SPEED: f32 = 0.5;
+/// Longest surface integration step, seconds
+const MAX_SURFACE_DT: f32 = 1.0 / 120.0;
+const MAX_SURFACE_SUBSTEPS: u32 = 64;
+/// Stiffer springs would need smaller steps than `MAX_SURFACE_DT`
+pub const MAX_STIFFNESS: f32 = 1000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WaterRegion {
+    pub min_x: f32,
+    pub max_x: f32,
+    /// Rest height of the surface
+    pub surface_y: f32,
+    pub bottom_y: f32,
+    /// Surface springs across the width
+    pub columns: u32,
+    /// Relative to the bodies' density of 1; above 1 they float
+    pub fluid_density: f32,
+    /// Velocity damping per second while fully submerged
+    pub drag: f32,
+    /// Pull of each column back to rest, 1/s²
+    pub stiffness: f32,
+    /// Column velocity damping, 1/s
+    pub damping: f32,
+    /// How fast waves travel along the surface, m/s
+    pub wave_speed: f32,
+    /// Fraction of a crossing body's vertical speed given to the surface
+    pub splash: f32,
+    /// Linear RGBA; alpha below 1 shows the bodies underneath
+    pub color: [f32; 4],
+}
+
+impl Default for WaterRegion {
+    fn default() -> Self {
+        Self {
+            min_x: -1.0,
+            max_x: 1.0,
+            surface_y: -0.5,
+            bottom_y: -1.0,
+            columns: 64,
+            fluid_density: 2.0,
+            drag: 2.0,
+            stiffness: 30.0,
+            damping: 1.5,
+            wave_speed: 1.0,
+            splash: 0.3,
+            color: [0.15, 0.45, 0.85, 0.6],
+        }
+    }
+}
+
+impl WaterRegion {
+    /// Edges in order, a bottom under the surface, at least two columns and
+    /// no negative rates; non-finite values fall back to the defaults
+    pub fn sanitized(self) -> Self {
+        let default = Self::default();
+        let finite = |v: f32, fallback: f32| if v.is_finite() { v } else { fallback };
+        let non_negative = |v: f32, fallback: f32| finite(v, fallback).max(0.0);
+        let (min_x, max_x) = (finite(self.min_x, default.min_x), finite(self.max_x, default.max_x));
+        let surface_y = finite(self.surface_y, default.surface_y);
+        Self {
+            min_x: min_x.min(max_x),
+            max_x: min_x.max(max_x),
+            surface_y,
+            bottom_y: finite(self.bottom_y, default.bottom_y).min(surface_y),
+            columns: self.columns.clamp(2, MAX_COLUMNS),
+            fluid_density: non_negative(self.fluid_density, default.fluid_density),
+            drag: non_negative(self.drag, default.drag),
+            stiffness: non_negative(self.stiffness, default.stiffness).min(MAX_STIFFNESS),
+            damping: non_negative(self.damping, default.damping),
+            wave_speed: non_negative(self.wave_speed, default.wave_speed),
+            splash: non_negative(self.splash, default.splash),
+            color: self.color.map(|c| finite(c, 1.0).clamp(0.0, 1.0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WaterConfig {
+    pub regions: Vec<WaterRegion>,
+}
+
+/// One region's surface; heights are offsets from `surface_y`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaterSurface {
+    region: WaterRegion,
+    heights: Vec<f32>,
+    velocities: Vec<f32>,
+}
+
+impl WaterSurface {
+    pub fn new(region: WaterRegion) -> Self {
+        let region = region.sanitized();
+        let columns = region.columns as usize;
+        Self { region, heights: vec![0.0; columns], velocities: vec![0.0; columns] }
+    }
+
+    pub fn region(&self) -> &WaterRegion {
+        &self.region
+    }
+
+    /// Column offsets from the rest height, left to right
+    pub fn heights(&self) -> &[f32] {
+        &self.heights
+    }
+
+    fn spacing(&self) -> f32 {
+        (self.region.max_x - self.region.min_x) / (self.heights.len() - 1) as f32
+    }
+
+    pub fn contains_x(&self, x: f32) -> bool {
+        (self.region.min_x..=self.region.max_x).contains(&x)
+    }
+
+    /// World x of column `i`
+    pub fn column_x(&self, i: usize) -> f32 {
+        self.region.min_x + i as f32 * self.spacing()
+    }
+
+    /// Surface height at `x`, interpolated between columns and clamped to the region
+    pub fn surface_at(&self, x: f32) -> f32 {
+        let spacing = self.spacing();
+        let t = if spacing > 0.0 { ((x - self.region.min_x) / spacing).max(0.0) } else { 0.0 };
+        let last = self.heights.len() - 1;
+        let i = (t as usize).min(last - 1);
+        let frac = (t - i as f32).clamp(0.0, 1.0);
+        self.region.surface_y + self.heights[i] + (self.heights[i + 1] - self.heights[i]) * frac
+    }
+
+    /// Fraction of a box of half extent `half_extent` at `position` under the surface
+    pub fn submerged_fraction(&self, position: [f32; 2], half_extent: f32) -> f32 {
+        if !self.contains_x(position[0]) || half_extent <= 0.0 || position[1] + half_extent < self.region.bottom_y {
+            return 0.0;
+        }
+        ((self.surface_at(position[0]) - (position[1] - half_extent)) / (2.0 * half_extent)).clamp(0.0, 1.0)
+    }
+
+    /// Push the columns within `half_extent` of `x` by `velocity` m/s
+    pub fn disturb(&mut self, x: f32, velocity: f32, half_extent: f32) {
+        if !velocity.is_finite() {
+            return;
+        }
+        let spacing = self.spacing();
+        for i in 0..self.heights.len() {
+            if (self.column_x(i) - x).abs() <= half_extent.max(spacing / 2.0) {
+                self.velocities[i] += velocity;
+            }
+        }
+    }
+
+    /// Advance the springs and waves by `dt` seconds
+    pub fn step(&mut self, dt: f32) {
+        if !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        let spacing = self.spacing();
+        let WaterRegion { stiffness, damping, wave_speed, surface_y, bottom_y, .. } = self.region;
+        // Explicit waves are stable while a substep moves them less than a column
+        let stable_dt = if wave_speed > 0.0 { 0.5 * spacing / wave_speed } else { MAX_SURFACE_DT };
+        let substeps = (dt / stable_dt.min(MAX_SURFACE_DT)).ceil().clamp(1.0, MAX_SURFACE_SUBSTEPS as f32) as u32;
+        let h = dt / substeps as f32;
+        // Past the substep cap, slow the waves down rather than let them blow up
+        let wave = if spacing > 0.0 { wave_speed * wave_speed / (spacing * spacing) } else { 0.0 };
+        let wave = wave.min(0.75 / (h * h));
+        // Waves never rise more than the pool is deep
+        let limit = (surface_y - bottom_y).max(spacing);
+
+        let last = self.heights.len() - 1;
+        for _ in 0..substeps {
+            for i in 0..=last {
+                let left = self.heights[i.saturating_sub(1)];
+                let right = self.heights[(i + 1).min(last)];
+                let height = self.heights[i];
+                let acceleration = -stiffness * height - damping * self.velocities[i] + wave * (left + right - 2.0 * height);
+                self.velocities[i] += acceleration * h;
+            }
+            for (height, velocity) in self.heights.iter_mut().zip(&self.velocities) {
+                *height = (*height + velocity * h).clamp(-limit, limit);
+            }
+        }
+    }
+}
+
+/// Spray thrown by a splash
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Droplet {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    /// Seconds left
+    pub life: f32,
+    /// Index of the region it came from, for its color
+    pub region: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct WaterVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// Every water region in the scene plus the droplets in flight
+#[derive(Debug, Clone, PartialEq)]
+pub struct Water {
+    surfaces: Vec<WaterSurface>,
+    droplets: Vec<Droplet>,
+    /// Fraction of the usual droplets a splash throws
+    droplet_scale: f32,
+    rng: u32,
+}
+
+impl Default for Water {
+    fn default() -> Self {
+        Self::new(WaterConfig::default())
+    }
+}
+
+impl Water {
+    /// Calm water for `config`, regions past `MAX_REGIONS` dropped
+    pub fn new(config: WaterConfig) -> Self {
+        Self {
+            surfaces: config.regions.into_iter().take(MAX_REGIONS).map(WaterSurface::new).collect(),
+            droplets: Vec::new(),
+            droplet_scale: 1.0,
+            rng: 0x9E37_79B9,
+        }
+    }
+
+    /// The sanitized regions
+    pub fn config(&self) -> WaterConfig {
+        WaterConfig { regions: self.surfaces.iter().map(|surface| surface.region).collect() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.surfaces.is_empty()
+    }
+
+    pub fn surfaces(&self) -> &[WaterSurface] {
+        &self.surfaces
+    }
+
+    pub fn droplets(&self) -> &[Droplet] {
+        &self.droplets
+    }
+
+    /// Throw `scale` (0 to 1) times the usual droplets per splash
+    pub fn set_droplet_scale(&mut self, scale: f32) {
+        self.droplet_scale = if scale.is_finite() { scale.clamp(0.0, 1.0) } else { 1.0 };
+    }
+
+    /// Buoyancy and drag on `body` under `gravity`
+    pub fn force_on(&self, body: &BodySample, gravity: [f32; 2]) -> [f32; 2] {
+        let volume = 8.0 * body.half_extent.powi(3);
+        let mut force = [0.0, 0.0];
+        for surface in &self.surfaces {
+            let submerged = surface.submerged_fraction(body.position, body.half_extent);
+            if submerged == 0.0 {
+                continue;
+            }
+            let displaced = surface.region.fluid_density * volume * submerged;
+            let damping = surface.region.drag * body.mass * submerged;
+            force[0] += -gravity[0] * displaced - damping * body.velocity[0];
+            force[1] += -gravity[1] * displaced - damping * body.velocity[1];
+        }
+        force
+    }
+
+    /// Whether a box at `position` reaches below any surface
+    pub fn is_touching(&self, position: [f32; 2], half_extent: f32) -> bool {
+        self.surfaces.iter().any(|surface| surface.submerged_fraction(position, half_extent) > 0.0)
+    }
+
+    /// A body of `half_extent` crossed the surface at `x` with vertical speed
+    /// `velocity_y`: kick the surface and throw droplets
+    pub fn splash(&mut self, x: f32, velocity_y: f32, half_extent: f32) {
+        let Some(index) = self.surfaces.iter().position(|surface| surface.contains_x(x)) else {
+            return;
+        };
+        let surface = &mut self.surfaces[index];
+        surface.disturb(x, velocity_y * surface.region.splash, half_extent);
+
+        let speed = velocity_y.abs();
+        if speed < DROPLET_MIN_SPEED || !speed.is_finite() {
+            return;
+        }
+        let y = surface.surface_at(x);
+        let count = ((speed * 4.0).min(12.0) * self.droplet_scale) as usize;
+        let count = count.min(MAX_DROPLETS - self.droplets.len());
+        for _ in 0..count {
+            let spread = self.random() * 2.0 - 1.0;
+            let lift = 0.3 + 0.5 * self.random();
+            self.droplets.push(Droplet {
+                position: [x + spread * half_extent, y],
+                velocity: [spread * speed * 0.3, lift * speed],
+                life: DROPLET_LIFE,
+                region: index,
+            });
+        }
+    }
+
+    /// Advance the surfaces and droplets by `dt` seconds
+    pub fn step(&mut self, dt: f32, gravity: [f32; 2]) {
+        if !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        for surface in &mut self.surfaces {
+            surface.step(dt);
+        }
+        let surfaces = &self.surfaces;
+        self.droplets.retain_mut(|droplet| {
+            droplet.velocity[0] += gravity[0] * dt;
+            droplet.velocity[1] += gravity[1] * dt;
+            droplet.position[0] += droplet.velocity[0] * dt;
+            droplet.position[1] += droplet.velocity[1] * dt;
+            droplet.life -= dt;
+            let landed = droplet.velocity[1] < 0.0
+                && surfaces.iter().any(|s| s.contains_x(droplet.position[0]) && droplet.position[1] < s.surface_at(droplet.position[0]));
+            droplet.life > 0.0 && !landed
+        });
+    }
+
+    /// Triangles for every surface strip (lighter at the top) and droplet
+    pub fn vertices(&self) -> Vec<WaterVertex> {
+        let mut vertices = Vec::new();
+        for surface in &self.surfaces {
+            let region = &surface.region;
+            let top = lighten(region.color);
+            for i in 0..surface.heights.len() - 1 {
+                let (x0, x1) = (surface.column_x(i), surface.column_x(i + 1));
+                let (y0, y1) = (region.surface_y + surface.heights[i], region.surface_y + surface.heights[i + 1]);
+                push_quad(&mut vertices, [x0, x1], region.bottom_y, [y0, y1], [region.color, top]);
+            }
+        }
+        for droplet in &self.droplets {
+            let Some(surface) = self.surfaces.get(droplet.region) else {
+                continue;
+            };
+            let color = lighten(surface.region.color);
+            let [x, y] = droplet.position;
+            let top = y + DROPLET_SIZE;
+            push_quad(&mut vertices, [x - DROPLET_SIZE, x + DROPLET_SIZE], y - DROPLET_SIZE, [top, top], [color, color]);
+        }
+        vertices
+    }
+
+    /// Uniform in 0..1 (xorshift), so splashes look the same on every platform
+    fn random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        (self.rng >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+/// Two triangles from a flat `bottom` up to `top` at `x[0]` and `x[1]`,
+/// shaded from `colors[0]` at the bottom to `colors[1]` at the top
+fn push_quad(vertices: &mut Vec<WaterVertex>, x: [f32; 2], bottom: f32, top: [f32; 2], colors: [[f32; 4]; 2]) {
+    let [low, high] = colors;
+    let corners = [
+        ([x[0], bottom], low),
+        ([x[1], bottom], low),
+        ([x[1], top[1]], high),
+        ([x[0], bottom], low),
+        ([x[1], top[1]], high),
+        ([x[0], top[0]], high),
+    ];
+    vertices.extend(corners.map(|(position, color)| WaterVertex { position, color }));
+}
+
+/// Surface tint: a third of the way to white, a little more opaque
+fn lighten(color: [f32; 4]) -> [f32; 4] {
+    let [r, g, b, a] = color;
+    let mix = |c: f32| c + (1.0 - c) / 3.0;
+    [mix(r), mix(g), mix(b), (a + 0.2).min(1.0)]
+}
+
+impl WaterVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<WaterVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Vertex capacity of a new pass; the buffer doubles as needed
+const INITIAL_VERTICES: u32 = 4096;
+
+/// Draws the water mesh inside the main pass, over the bodies
+pub(crate) struct WaterPass {
+    /// Pipeline matching the main pass (swapchain format, MSAA sample count)
+    pipeline: wgpu::RenderPipeline,
+    /// Pipeline matching the HDR bloom target, when bloom is available
+    hdr_pipeline: Option<wgpu::RenderPipeline>,
+    vertex_buffer: wgpu::Buffer,
+    vertex_capacity: u32,
+    vertex_count: u32,
+}
+
+impl WaterPass {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        output_format: wgpu::TextureFormat,
+        sample_count: u32,
+        hdr_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Water Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("../shaders/water.wgsl"))),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Water Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let make_pipeline = |label: &str, format: wgpu::TextureFormat, count: u32| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_water"),
+                    buffers: &[WaterVertex::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_water"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let pipeline = make_pipeline("Water Pipeline", output_format, sample_count);
+        let hdr_pipeline = hdr_format.map(|format| make_pipeline("HDR Water Pipeline", format, 1));
+        Self {
+            pipeline,
+            hdr_pipeline,
+            vertex_buffer: Self::create_vertex_buffer(device, INITIAL_VERTICES),
+            vertex_capacity: INITIAL_VERTICES,
+            vertex_count: 0,
+        }
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, capacity: u32) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Water Vertex Buffer"),
+            size: capacity as u64 * std::mem::size_of::<WaterVertex>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Upload this frame's mesh
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, vertices: &[WaterVertex]) {
+        self.vertex_count = vertices.len() as u32;
+        if vertices.is_empty() {
+            return;
+        }
+        if self.vertex_count > self.vertex_capacity {
+            self.vertex_capacity = self.vertex_count.next_power_of_two();
+            self.vertex_buffer = Self::create_vertex_buffer(device, self.vertex_capacity);
+        }
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vertices));
+    }
+
+    /// Draw the prepared mesh. Leaves the water pipeline and camera (group 0) bound.
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, camera_bind_group: &wgpu::BindGroup, hdr: bool) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        let Some(pipeline) = (if hdr { self.hdr_pipeline.as_ref() } else { Some(&self.pipeline) }) else {
+            return;
+        };
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+    }
+}
@@ -0,0 +1,40 @@
+//! Integration tests for named asset reads
+
+use physics_core::assets;
+
+#[test]
+fn test_asset_names_stay_inside_the_asset_root() {
+    for name in ["textures/ball.png", "scene.json", "levels/world 1/intro.ron"] {
+        assert!(assets::check_name(name).is_ok(), "{}", name);
+    }
+    for name in ["", "/etc/passwd", "../scene.json", "levels/../../x", "a//b", "./a", "dir/", "a\\b", "C:/scene.json"] {
+        assert!(assets::check_name(name).is_err(), "{}", name);
+    }
+}
+
+#[test]
+fn test_reads_take_preloaded_bytes_then_the_loader() {
+    assets::preload("levels/preloaded.json", b"{}".to_vec()).unwrap();
+    assert!(assets::preload("../outside.json", Vec::new()).is_err());
+
+    assets::set_loader(Some(Box::new(|name: &str| match name {
+        "textures/ball.png" => Ok(vec![1, 2, 3]),
+        _ => Err(format!("{}: missing", name)),
+    })));
+    assert!(assets::has_loader());
+    assert_eq!(assets::read("textures/ball.png").unwrap(), vec![1, 2, 3]);
+    assert_eq!(assets::read_text("levels/preloaded.json").unwrap(), "{}");
+    assert!(assets::read("textures/box.png").unwrap_err().contains("missing"));
+    // Names are checked before the loader sees them
+    assert!(assets::read("/textures/ball.png").is_err());
+
+    // Without a loader the desktop build reads from the working directory,
+    // which is the package root under cargo test
+    assets::set_loader(None);
+    assert!(assets::read_text("Cargo.toml").unwrap().contains("physics_core"));
+    assert!(assets::read("textures/ball.png").is_err());
+
+    assert!(assets::unload("levels/preloaded.json"));
+    assert!(!assets::is_preloaded("levels/preloaded.json"));
+    assert!(!assets::unload("levels/preloaded.json"));
+}
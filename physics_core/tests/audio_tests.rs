@@ -0,0 +1,87 @@
+//! Integration tests for collision audio events
+
+use physics_core::audio::{AudioConfig, AudioEvent, AudioQueue, Impact, MAX_QUEUED};
+
+fn enabled() -> AudioConfig {
+    AudioConfig { enabled: true, ..AudioConfig::default() }
+}
+
+fn impact(x: f32, y: f32, speed: f32) -> Impact {
+    Impact { a: 1, b: 2, point: [x, y], speed, impulse: speed * 0.5 }
+}
+
+#[test]
+fn test_attenuation_is_full_inside_reference_and_zero_past_max() {
+    let config = enabled().with_attenuation(1.0, 4.0, 1.0).unwrap();
+    assert_eq!(config.attenuation(0.0), 1.0);
+    assert_eq!(config.attenuation(1.0), 1.0);
+    assert!((config.attenuation(2.0) - 0.5).abs() < 1e-6);
+    assert!((config.attenuation(4.0) - 0.25).abs() < 1e-6);
+    assert_eq!(config.attenuation(4.01), 0.0);
+
+    let flat = config.with_attenuation(1.0, 4.0, 0.0).unwrap();
+    assert_eq!(flat.attenuation(3.0), 1.0);
+}
+
+#[test]
+fn test_loudness_ramps_between_speed_thresholds() {
+    let config = enabled().with_speeds(1.0, 3.0).unwrap();
+    assert_eq!(config.loudness(0.5), 0.0);
+    assert!((config.loudness(2.0) - 0.5).abs() < 1e-6);
+    assert_eq!(config.loudness(10.0), 1.0);
+}
+
+#[test]
+fn test_soft_distant_or_disabled_impacts_make_no_event() {
+    let config = enabled();
+    assert!(config.event(&impact(0.0, 0.0, 0.1), [0, 0], 0.05).is_none());
+    assert!(config.event(&impact(10.0, 0.0, 5.0), [0, 0], 0.05).is_none());
+    assert!(config.event(&impact(0.0, 0.0, f32::NAN), [0, 0], 0.05).is_none());
+    assert!(AudioConfig::default().event(&impact(0.0, 0.0, 5.0), [0, 0], 0.05).is_none());
+}
+
+#[test]
+fn test_event_carries_materials_volume_pan_and_pitch() {
+    let config = enabled();
+    let event = config.event(&impact(1.0, 0.0, 5.0), [3, 0], 0.05).unwrap();
+    assert_eq!((event.entity_a, event.entity_b), (1, 2));
+    assert_eq!((event.material_a, event.material_b), (3, 0));
+    assert_eq!((event.x, event.y, event.distance), (1.0, 0.0, 1.0));
+    assert!((event.volume - 0.5).abs() < 1e-6);
+    assert!(event.pan > 0.0 && event.pan < 1.0);
+    assert_eq!(event.pitch, 1.0);
+
+    let left = config.event(&impact(-1.0, 0.0, 5.0), [0, 0], 0.05).unwrap();
+    assert_eq!(left.pan, -event.pan);
+
+    let small = config.event(&impact(0.0, 0.0, 5.0), [0, 0], 0.0125).unwrap();
+    let large = config.event(&impact(0.0, 0.0, 5.0), [0, 0], 1.0).unwrap();
+    assert_eq!(small.pitch, 2.0);
+    assert_eq!(large.pitch, 0.5);
+}
+
+#[test]
+fn test_invalid_settings_are_rejected() {
+    let config = AudioConfig::default();
+    assert!(config.with_speeds(2.0, 1.0).is_none());
+    assert!(config.with_speeds(-1.0, 1.0).is_none());
+    assert!(config.with_speeds(0.0, f32::INFINITY).is_none());
+    assert!(config.with_attenuation(0.0, 1.0, 1.0).is_none());
+    assert!(config.with_attenuation(2.0, 1.0, 1.0).is_none());
+    assert!(config.with_attenuation(1.0, 2.0, -1.0).is_none());
+}
+
+#[test]
+fn test_queue_drops_oldest_when_full_and_drains_in_order() {
+    let mut queue = AudioQueue::new();
+    for i in 0..MAX_QUEUED as u64 + 2 {
+        queue.push(AudioEvent { entity_a: i, ..AudioEvent::default() });
+    }
+    assert_eq!(queue.len(), MAX_QUEUED);
+    assert_eq!(queue.dropped(), 2);
+
+    let first: Vec<u64> = queue.drain(3).iter().map(|e| e.entity_a).collect();
+    assert_eq!(first, vec![2, 3, 4]);
+    assert_eq!(queue.drain(usize::MAX).len(), MAX_QUEUED - 3);
+    assert!(queue.is_empty());
+}
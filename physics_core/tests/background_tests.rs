@@ -0,0 +1,46 @@
+//! Integration tests for placing and packing background images
+
+use physics_core::background::{pack_rgba, BackgroundFit, PixelLayout};
+
+fn assert_rect(actual: [f32; 4], expected: [f32; 4]) {
+    assert!(actual.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-6), "{:?} is not {:?}", actual, expected);
+}
+
+#[test]
+fn test_fit_codes() {
+    assert_eq!(BackgroundFit::from_i32(0), Some(BackgroundFit::Fill));
+    assert_eq!(BackgroundFit::from_i32(2), Some(BackgroundFit::Stretch));
+    assert_eq!(BackgroundFit::from_i32(3), None);
+}
+
+#[test]
+fn test_fill_crops_and_fit_letterboxes() {
+    // A 4:3 camera frame on a 3:4 portrait view
+    let (image, view) = ((400, 300), (300, 400));
+    assert_rect(BackgroundFit::Fill.uv_rect(image, view), [0.21875, 0.0, 0.78125, 1.0]);
+    assert_rect(BackgroundFit::Fit.uv_rect(image, view), [0.0, -7.0 / 18.0, 1.0, 25.0 / 18.0]);
+    assert_rect(BackgroundFit::Stretch.uv_rect(image, view), [0.0, 0.0, 1.0, 1.0]);
+    // Same aspect ratio: every fit shows the whole image
+    for fit in [BackgroundFit::Fill, BackgroundFit::Fit] {
+        assert_rect(fit.uv_rect((640, 480), (1280, 960)), [0.0, 0.0, 1.0, 1.0]);
+    }
+}
+
+#[test]
+fn test_pack_rgba_drops_stride_padding_and_fills_alpha() {
+    // Two rows of one pixel, two pixels apart
+    let rgbx = [1, 2, 3, 0, 9, 9, 9, 9, 4, 5, 6, 0];
+    assert_eq!(pack_rgba(&rgbx, 1, 2, 2, PixelLayout::Rgbx8), Some(vec![1, 2, 3, 255, 4, 5, 6, 255]));
+    let rgb = [1, 2, 3, 4, 5, 6];
+    assert_eq!(pack_rgba(&rgb, 2, 1, 2, PixelLayout::Rgb8), Some(vec![1, 2, 3, 255, 4, 5, 6, 255]));
+    let rgba = [1, 2, 3, 4];
+    assert_eq!(pack_rgba(&rgba, 1, 1, 1, PixelLayout::Rgba8), Some(rgba.to_vec()));
+}
+
+#[test]
+fn test_pack_rgba_rejects_short_buffers() {
+    assert_eq!(pack_rgba(&[0; 7], 2, 1, 2, PixelLayout::Rgba8), None);
+    assert_eq!(pack_rgba(&[0; 16], 2, 2, 1, PixelLayout::Rgba8), None);
+    // The last row needs no padding after it
+    assert!(pack_rgba(&[0; 12], 1, 2, 2, PixelLayout::Rgba8).is_some());
+}
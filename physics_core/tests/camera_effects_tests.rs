@@ -0,0 +1,113 @@
+//! Integration tests for camera shake and zoom punches
+
+use physics_core::camera_effects::{CameraEffects, CameraEffectsConfig, CameraOffset, MAX_ZOOM_PUNCH};
+
+#[test]
+fn test_trauma_is_clamped_and_decays_linearly() {
+    let mut effects = CameraEffects::new(CameraEffectsConfig::default().with_decay(1.0, 6.0).unwrap());
+    effects.add_trauma(0.7);
+    effects.add_trauma(0.7);
+    effects.add_trauma(-1.0);
+    effects.add_trauma(f32::NAN);
+    assert_eq!(effects.trauma(), 1.0);
+
+    effects.update(0.25);
+    assert!((effects.trauma() - 0.75).abs() < 1e-6);
+    effects.update(1.0);
+    assert_eq!(effects.trauma(), 0.0);
+    assert!(!effects.is_active());
+    assert!(effects.offset().is_zero());
+}
+
+#[test]
+fn test_shake_stays_within_amplitude_and_scales_with_trauma_squared() {
+    let config = CameraEffectsConfig::default().with_shake(0.2, 0.1, 10.0).unwrap();
+    let mut effects = CameraEffects::new(config.with_decay(1e-6, 6.0).unwrap());
+    effects.add_trauma(0.5);
+    let mut moved = false;
+    for _ in 0..200 {
+        effects.update(0.01);
+        let offset = effects.offset();
+        assert!(offset.dx.abs() <= 0.2 * 0.25 + 1e-6);
+        assert!(offset.dy.abs() <= 0.2 * 0.25 + 1e-6);
+        assert!(offset.rotation.abs() <= 0.1 * 0.25 + 1e-6);
+        moved |= offset.dx != 0.0 && offset.dy != 0.0;
+    }
+    assert!(moved);
+}
+
+#[test]
+fn test_shake_is_smooth_between_frames() {
+    let mut effects = CameraEffects::new(CameraEffectsConfig::default().with_decay(1e-6, 6.0).unwrap());
+    effects.add_trauma(1.0);
+    effects.update(0.001);
+    let mut last = effects.offset();
+    for _ in 0..500 {
+        effects.update(0.001);
+        let offset = effects.offset();
+        // 15 Hz noise over 1 ms moves a small fraction of the 0.1 amplitude
+        assert!((offset.dx - last.dx).abs() < 0.01);
+        last = offset;
+    }
+}
+
+#[test]
+fn test_impacts_add_trauma_only_when_enabled_and_above_threshold() {
+    let mut effects = CameraEffects::default();
+    effects.on_impact(10.0);
+    assert_eq!(effects.trauma(), 0.0);
+
+    let config = CameraEffectsConfig::default().with_impact_shake(1.0, 0.5).unwrap();
+    assert_eq!(config.impact_trauma(0.5), 0.0);
+    assert_eq!(config.impact_trauma(1.5), 0.25);
+    assert_eq!(config.impact_trauma(100.0), 1.0);
+
+    effects.set_config(config);
+    effects.on_impact(1.5);
+    assert_eq!(effects.trauma(), 0.25);
+}
+
+#[test]
+fn test_zoom_punch_rises_smoothly_and_settles_back() {
+    let mut effects = CameraEffects::default();
+    effects.punch_zoom(0.3);
+    assert_eq!(effects.offset().zoom, 0.0);
+
+    let mut peak = 0.0f32;
+    for _ in 0..120 {
+        effects.update(1.0 / 60.0);
+        let zoom = effects.offset().zoom;
+        assert!(zoom >= 0.0 && zoom < 0.3);
+        peak = peak.max(zoom);
+    }
+    assert!(peak > 0.1);
+    assert_eq!(effects.offset(), CameraOffset::default());
+    assert!(!effects.is_active());
+
+    effects.punch_zoom(5.0);
+    effects.update(1.0);
+    assert!(effects.offset().zoom <= MAX_ZOOM_PUNCH);
+}
+
+#[test]
+fn test_invalid_settings_are_rejected() {
+    let config = CameraEffectsConfig::default();
+    assert!(config.with_shake(-0.1, 0.1, 10.0).is_none());
+    assert!(config.with_shake(0.1, 0.1, 0.0).is_none());
+    assert!(config.with_decay(0.0, 1.0).is_none());
+    assert!(config.with_decay(1.0, f32::INFINITY).is_none());
+    assert!(config.with_impact_shake(-1.0, 0.5).is_none());
+    assert!(config.with_impact_shake(1.0, f32::NAN).is_none());
+}
+
+#[test]
+fn test_clear_stops_everything() {
+    let mut effects = CameraEffects::default();
+    effects.add_trauma(1.0);
+    effects.punch_zoom(0.5);
+    effects.update(0.05);
+    assert!(effects.is_active());
+    effects.clear();
+    assert!(!effects.is_active());
+    assert!(effects.offset().is_zero());
+}
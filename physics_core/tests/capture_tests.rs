@@ -0,0 +1,78 @@
+//! Integration tests for frame capture encoding
+
+use physics_core::capture::{encode_png, screenshot_path, unpad_rows, CaptureConfig, MAX_FPS};
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(bytes[..4].try_into().unwrap())
+}
+
+#[test]
+fn test_config_validation_and_names() {
+    let config: CaptureConfig = serde_json::from_str(r#"{"fps": 30, "directory": "out", "maxFrames": 90}"#).unwrap();
+    assert!(config.is_valid());
+    assert_eq!(config.prefix, "frame_");
+    assert!((config.step() - 1.0 / 30.0).abs() < 1e-7);
+    assert_eq!(config.file_name(42), "frame_000042.png");
+
+    assert!(!CaptureConfig { fps: 0, ..CaptureConfig::default() }.is_valid());
+    assert!(!CaptureConfig { fps: MAX_FPS + 1, ..CaptureConfig::default() }.is_valid());
+    assert!(!CaptureConfig { prefix: "../frame_".to_string(), ..CaptureConfig::default() }.is_valid());
+}
+
+#[test]
+fn test_start_needs_somewhere_to_send_frames() {
+    assert!(physics_core::capture::start(CaptureConfig::default(), None).is_err());
+    assert!(physics_core::capture::start(CaptureConfig { fps: 0, ..CaptureConfig::default() }, None).is_err());
+    assert!(!physics_core::capture::is_active());
+}
+
+#[test]
+fn test_screenshot_paths_are_named_by_time() {
+    let path = screenshot_path(std::path::Path::new("shots"), 1_700_000_000_123);
+    assert_eq!(path, std::path::Path::new("shots").join("screenshot_1700000000123.png"));
+    assert_ne!(screenshot_path(std::path::Path::new("shots"), 1), screenshot_path(std::path::Path::new("shots"), 2));
+}
+
+#[test]
+fn test_unpad_rows_drops_padding_and_swaps_bgra() {
+    // 2x2 BGRA pixels in rows padded to 12 bytes
+    let data = [
+        1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, //
+        9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0, 0,
+    ];
+    assert_eq!(unpad_rows(&data, 2, 2, 12, false), vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+    assert_eq!(unpad_rows(&data, 2, 2, 12, true), vec![3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]);
+}
+
+#[test]
+fn test_png_structure() {
+    let rgba: Vec<u8> = (0..3 * 2 * 4).map(|i| i as u8).collect();
+    let png = encode_png(3, 2, &rgba);
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+    // IHDR: 3x2, 8-bit RGBA
+    assert_eq!(be_u32(&png[8..]), 13);
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(be_u32(&png[16..]), 3);
+    assert_eq!(be_u32(&png[20..]), 2);
+    assert_eq!(&png[24..29], &[8, 6, 0, 0, 0]);
+
+    // IDAT holds one final stored block with each row behind a 0 filter byte
+    let idat = &png[33..];
+    let len = be_u32(idat) as usize;
+    assert_eq!(&idat[4..8], b"IDAT");
+    let zlib = &idat[8..8 + len];
+    assert_eq!(&zlib[..3], &[0x78, 0x01, 1]);
+    let stored = u16::from_le_bytes([zlib[3], zlib[4]]) as usize;
+    assert_eq!(stored, 2 * (1 + 3 * 4));
+    assert_eq!(u16::from_le_bytes([zlib[5], zlib[6]]), !(stored as u16));
+    let raw = &zlib[7..7 + stored];
+    assert_eq!(raw[0], 0);
+    assert_eq!(&raw[1..13], &rgba[..12]);
+    assert_eq!(raw[13], 0);
+    assert_eq!(&raw[14..], &rgba[12..]);
+    assert_eq!(zlib.len(), 7 + stored + 4);
+
+    // IEND is empty and its CRC is fixed
+    assert_eq!(&png[png.len() - 12..], &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]);
+}
@@ -0,0 +1,46 @@
+//! Integration tests for the desktop app's command line
+
+use physics_core::cli::{self, Benchmark, BenchmarkReport, Command, DesktopOptions};
+use physics_core::gpu_options;
+
+fn parse(args: &[&str]) -> Result<Command, String> {
+    cli::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn test_no_arguments_run_with_the_defaults() {
+    assert_eq!(parse(&[]), Ok(Command::Run(DesktopOptions::default())));
+    assert_eq!(parse(&["--width", "800", "--help"]), Ok(Command::Help));
+}
+
+#[test]
+fn test_window_and_gpu_options() {
+    let Ok(Command::Run(options)) = parse(&["--size", "1280x720", "--no-vsync", "--scene=levels/a.json", "--backend", "Vulkan", "--backend=gl"]) else {
+        panic!("options not parsed");
+    };
+    assert_eq!((options.width, options.height, options.vsync), (1280, 720, false));
+    assert_eq!(options.scene.as_deref(), Some(std::path::Path::new("levels/a.json")));
+    assert_eq!(options.backends, gpu_options::BACKEND_VULKAN | gpu_options::BACKEND_GL);
+}
+
+#[test]
+fn test_invalid_arguments_are_rejected() {
+    for args in [&["--width"][..], &["--width", "0"], &["--size", "800"], &["--backend", "glide"], &["--frames", "10"], &["--benchmark", "-1"], &["--fullscreen"]] {
+        assert!(parse(args).is_err(), "{:?} was accepted", args);
+    }
+}
+
+#[test]
+fn test_benchmark_mode() {
+    assert_eq!(parse(&["--benchmark", "500"]), Ok(Command::Benchmark(Benchmark { bodies: 500, frames: 600 })));
+    assert_eq!(parse(&["--frames", "60", "--benchmark", "10"]), Ok(Command::Benchmark(Benchmark { bodies: 10, frames: 60 })));
+}
+
+#[test]
+fn test_benchmark_report() {
+    let frame_ms: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+    let report = BenchmarkReport::from_frame_times(10, &frame_ms);
+    assert_eq!((report.frames, report.p95_ms, report.max_ms), (100, 95.0, 100.0));
+    assert!((report.mean_ms - 50.5).abs() < 1e-9);
+    assert_eq!(BenchmarkReport::from_frame_times(10, &[]).p95_ms, 0.0);
+}
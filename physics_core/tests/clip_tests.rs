@@ -0,0 +1,81 @@
+//! Integration tests for clip frame picking and GIF encoding
+
+use physics_core::clip::{downscale, is_valid, pick_frames, ClipFrame, GifWriter, MAX_FPS};
+
+#[test]
+fn test_invalid_clips() {
+    assert!(is_valid(2.0, 15, 0.5));
+    assert!(!is_valid(0.0, 15, 0.5));
+    assert!(!is_valid(f32::INFINITY, 15, 0.5));
+    assert!(!is_valid(2.0, 0, 0.5));
+    assert!(!is_valid(2.0, MAX_FPS + 1, 0.5));
+    assert!(!is_valid(2.0, 15, 0.0));
+    assert!(!is_valid(2.0, 15, 1.5));
+    assert!(!is_valid(2.0, 15, f32::NAN));
+}
+
+#[test]
+fn test_frames_sample_the_newest_seconds() {
+    // Two seconds of 60 Hz steps, sampled at 10 fps for the last second
+    let steps = vec![1.0 / 60.0; 121];
+    let frames = pick_frames(&steps, 1.0, 10);
+    assert_eq!(frames.len(), 10);
+    assert_eq!(frames.last(), Some(&ClipFrame { index: 120, delay: 10 }));
+    assert_eq!(frames[0].index, 66);
+    assert!(frames.windows(2).all(|pair| pair[1].index - pair[0].index == 6));
+    assert_eq!(frames.iter().map(|frame| frame.delay as u32).sum::<u32>(), 100);
+}
+
+#[test]
+fn test_long_steps_hold_their_frame() {
+    // 10 Hz steps at 30 fps: each entry shows until the next one is due
+    let steps = vec![0.1; 6];
+    let frames = pick_frames(&steps, 10.0, 30);
+    assert_eq!(frames.iter().map(|frame| frame.index).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5]);
+    assert!(frames[1..5].iter().all(|frame| frame.delay == 10));
+    // The history only covers half a second
+    assert_eq!(frames.iter().map(|frame| frame.delay as u32).sum::<u32>(), 50);
+
+    assert!(pick_frames(&[], 1.0, 30).is_empty());
+    assert_eq!(pick_frames(&[0.1], 1.0, 30), vec![ClipFrame { index: 0, delay: 3 }]);
+}
+
+#[test]
+fn test_downscale_averages_covered_pixels() {
+    // 2x2 of gray levels 0, 40, 80, 120 down to one pixel
+    let rgba = [0, 0, 0, 255, 40, 40, 40, 255, 80, 80, 80, 255, 120, 120, 120, 255];
+    assert_eq!(downscale(&rgba, 2, 2, 0.5), (1, 1, vec![60, 60, 60, 255]));
+    assert_eq!(downscale(&rgba, 2, 2, 1.0), (2, 2, rgba.to_vec()));
+    // Never below one pixel
+    assert_eq!(downscale(&rgba, 2, 2, 0.01).0, 1);
+}
+
+#[test]
+fn test_gif_structure() {
+    assert!(GifWriter::new(0, 10).is_none());
+    assert!(GifWriter::new(70_000, 10).is_none());
+
+    let mut gif = GifWriter::new(3, 2).unwrap();
+    let red: Vec<u8> = [255, 0, 0, 255].repeat(6);
+    gif.add_frame(&red, 4);
+    gif.add_frame(&red, 7);
+    let bytes = gif.finish();
+
+    assert_eq!(&bytes[..6], b"GIF89a");
+    assert_eq!(&bytes[6..10], &[3, 0, 2, 0]);
+    assert_eq!(bytes[10], 0xF7);
+    // Global palette: black first, pure red among the colors
+    assert_eq!(&bytes[13..16], &[0, 0, 0]);
+    assert!(bytes[13..13 + 768].chunks(3).any(|color| color == [255, 0, 0]));
+    let after_palette = 13 + 768;
+    assert_eq!(&bytes[after_palette + 3..after_palette + 14], b"NETSCAPE2.0");
+
+    // Each frame starts with a graphic control extension carrying its delay
+    let delays: Vec<u16> = bytes
+        .windows(6)
+        .filter(|window| window[..3] == [0x21, 0xF9, 4])
+        .map(|window| u16::from_le_bytes([window[4], window[5]]))
+        .collect();
+    assert_eq!(delays, vec![4, 7]);
+    assert_eq!(bytes.last(), Some(&0x3B));
+}
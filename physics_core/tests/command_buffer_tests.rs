@@ -0,0 +1,75 @@
+//! Integration tests for packed command buffers
+
+use physics_core::command_buffer::{decode, Command, CommandWriter, IMPULSE, MAX_COMMANDS, SPAWN};
+
+#[test]
+fn test_round_trip() {
+    let commands = [
+        Command::Spawn { x: 1.0, y: -2.0, half_extent: 0.25, body_type: 1, groups: 6 },
+        Command::Impulse { entity_id: u64::MAX, x: 0.5, y: 3.0 },
+        Command::SetVelocity { entity_id: 42, vx: -1.0, vy: 0.0 },
+        Command::SetPosition { entity_id: 43, x: 7.0, y: 8.0 },
+        Command::SetGroups { entity_id: 44, groups: u32::MAX },
+        Command::SetMaterial { entity_id: 45, material_id: 3 },
+    ];
+    let mut writer = CommandWriter::new();
+    for command in commands {
+        writer.push(command);
+    }
+    assert_eq!(decode(writer.as_bytes()).unwrap(), commands);
+}
+
+#[test]
+fn test_layout_is_packed_little_endian() {
+    let mut writer = CommandWriter::new();
+    writer.push(Command::Impulse { entity_id: 0x0102, x: 1.0, y: 0.0 });
+    let bytes = writer.into_bytes();
+    assert_eq!(bytes.len(), 1 + 8 + 4 + 4);
+    assert_eq!(bytes[0], IMPULSE);
+    assert_eq!(&bytes[1..3], &[0x02, 0x01]);
+    assert_eq!(&bytes[9..13], &1.0f32.to_le_bytes());
+
+    let mut spawn = vec![SPAWN];
+    for field in [0.0f32, 0.0, 0.5] {
+        spawn.extend(field.to_le_bytes());
+    }
+    spawn.extend(0u32.to_le_bytes());
+    spawn.extend(1u32.to_le_bytes());
+    assert_eq!(spawn.len(), 21);
+    assert_eq!(
+        decode(&spawn).unwrap(),
+        vec![Command::Spawn { x: 0.0, y: 0.0, half_extent: 0.5, body_type: 0, groups: 1 }]
+    );
+}
+
+#[test]
+fn test_empty_buffer_is_no_commands() {
+    assert_eq!(decode(&[]).unwrap(), Vec::new());
+}
+
+#[test]
+fn test_malformed_buffers_are_rejected_whole() {
+    let mut writer = CommandWriter::new();
+    writer.push(Command::SetGroups { entity_id: 1, groups: 2 });
+    let valid = writer.into_bytes();
+
+    let mut unknown = valid.clone();
+    unknown.push(99);
+    let error = decode(&unknown).unwrap_err();
+    assert!(error.contains("99") && error.contains(&valid.len().to_string()));
+
+    let truncated = &valid[..valid.len() - 1];
+    assert!(decode(truncated).unwrap_err().contains("byte 0"));
+    assert!(decode(&[0]).is_err());
+}
+
+#[test]
+fn test_command_limit() {
+    let mut writer = CommandWriter::new();
+    for _ in 0..MAX_COMMANDS {
+        writer.push(Command::SetGroups { entity_id: 1, groups: 1 });
+    }
+    assert_eq!(decode(writer.as_bytes()).unwrap().len(), MAX_COMMANDS);
+    writer.push(Command::SetGroups { entity_id: 1, groups: 1 });
+    assert!(decode(writer.as_bytes()).is_err());
+}
@@ -0,0 +1,42 @@
+//! Integration tests for the ignored collision pairs
+
+use physics_core::contact_filter::IgnoredPairs;
+
+#[test]
+fn test_pairs_are_unordered() {
+    let mut pairs = IgnoredPairs::default();
+    assert!(pairs.insert(7, 3));
+    assert!(pairs.contains(3, 7));
+    assert!(pairs.contains(7, 3));
+    // The same pair in the other order is already there
+    assert!(!pairs.insert(3, 7));
+    assert_eq!(pairs.len(), 1);
+}
+
+#[test]
+fn test_a_body_cannot_ignore_itself() {
+    let mut pairs = IgnoredPairs::default();
+    assert!(!pairs.insert(5, 5));
+    assert!(!pairs.contains(5, 5));
+    assert!(pairs.is_empty());
+}
+
+#[test]
+fn test_remove_restores_the_pair() {
+    let mut pairs = IgnoredPairs::default();
+    pairs.insert(1, 2);
+    pairs.insert(1, 3);
+    assert!(pairs.remove(2, 1));
+    assert!(!pairs.remove(2, 1));
+    assert!(!pairs.contains(1, 2));
+    assert!(pairs.contains(1, 3));
+}
+
+#[test]
+fn test_involves_finds_either_side() {
+    let mut pairs = IgnoredPairs::default();
+    pairs.insert(4, 9);
+    assert!(pairs.involves(4));
+    assert!(pairs.involves(9));
+    assert!(!pairs.involves(5));
+}
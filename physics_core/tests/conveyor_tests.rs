@@ -0,0 +1,68 @@
+//! Integration tests for conveyor surfaces
+
+use physics_core::conveyor::{self, SurfaceVelocities, SurfaceVelocityHooks};
+use physics_core::rapier::prelude::*;
+
+#[test]
+fn test_tangent_velocity_drops_normal_component() {
+    let tangent = conveyor::contact_tangent_velocity(vector![2.0, 1.0, 0.0], Vector::zeros(), vector![0.0, 1.0, 0.0]);
+    assert_eq!(tangent, vector![2.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_tangent_velocity_is_relative() {
+    let belt = vector![1.0, 0.0, 0.0];
+    let normal = vector![0.0, 1.0, 0.0];
+    assert_eq!(conveyor::contact_tangent_velocity(Vector::zeros(), belt, normal), -belt);
+    assert_eq!(conveyor::contact_tangent_velocity(belt, belt, normal), Vector::zeros());
+}
+
+#[test]
+fn test_belt_carries_resting_box() {
+    let mut bodies = RigidBodySet::new();
+    let mut colliders = ColliderSet::new();
+    let floor = bodies.insert(RigidBodyBuilder::fixed().build());
+    let belt = colliders.insert_with_parent(
+        ColliderBuilder::cuboid(4.0, 0.01, 0.1)
+            .friction(1.0)
+            .active_hooks(ActiveHooks::MODIFY_SOLVER_CONTACTS)
+            .build(),
+        floor,
+        &mut bodies,
+    );
+    let crate_body = bodies.insert(RigidBodyBuilder::dynamic().translation(vector![0.0, 0.06, 0.0]).build());
+    colliders.insert_with_parent(ColliderBuilder::cuboid(0.05, 0.05, 0.05).friction(1.0).build(), crate_body, &mut bodies);
+
+    let mut velocities = SurfaceVelocities::new();
+    velocities.insert(belt, vector![1.0, 0.0, 0.0]);
+    let hooks = SurfaceVelocityHooks { velocities: &velocities };
+
+    let mut pipeline = PhysicsPipeline::new();
+    let mut islands = IslandManager::new();
+    let mut broad_phase = DefaultBroadPhase::new();
+    let mut narrow_phase = NarrowPhase::new();
+    let mut impulse_joints = ImpulseJointSet::new();
+    let mut multibody_joints = MultibodyJointSet::new();
+    let mut ccd_solver = CCDSolver::new();
+    for _ in 0..60 {
+        pipeline.step(
+            &vector![0.0, -9.81, 0.0],
+            &IntegrationParameters::default(),
+            &mut islands,
+            &mut broad_phase,
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut ccd_solver,
+            None,
+            &hooks,
+            &(),
+        );
+    }
+
+    let moved = bodies[crate_body].translation().x;
+    assert!(moved > 0.5, "box moved {moved}");
+    assert!(bodies[crate_body].linvel().x > 0.9);
+}
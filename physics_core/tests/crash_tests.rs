@@ -0,0 +1,44 @@
+//! Integration tests for panic capture at the binding boundary
+
+use physics_core::crash::{self, CrashReport};
+
+#[test]
+fn test_guard_returns_the_fallback_and_keeps_a_report() {
+    let dir = std::env::temp_dir().join(format!("physicsfx_crash_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    crash::set_directory(Some(dir.clone()));
+
+    assert_eq!(crash::guard("physics_core_spawn_body", || 7u64), 7);
+    assert_eq!(crash::guard("physics_core_spawn_body", || -> u64 { panic!("no world: {}", 3) }), 0);
+    assert!(!crash::guard("physics_core_set_water", || -> bool { panic!("bad water") }));
+    let name: *mut std::os::raw::c_char = crash::guard("physics_core_get_info", || panic!("no info"));
+    assert!(name.is_null());
+
+    let report = crash::last_report().unwrap();
+    assert_eq!(report.message, "no info");
+    assert_eq!(report.entry_point.as_deref(), Some("physics_core_get_info"));
+    assert!(report.location.as_deref().unwrap().contains("crash_tests.rs"));
+
+    let json: serde_json::Value = serde_json::from_str(&crash::last_report_json().unwrap()).unwrap();
+    assert_eq!(json["entryPoint"], "physics_core_get_info");
+    let file = std::fs::read_to_string(dir.join(crash::FILE_NAME)).unwrap();
+    assert!(file.starts_with("panicked at "));
+    assert!(file.contains(": no info\n"));
+
+    crash::set_directory(None);
+    crash::clear();
+    assert!(crash::last_report().is_none());
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_report_text_leaves_out_missing_parts() {
+    let report = CrashReport {
+        message: "index out of bounds".into(),
+        location: Some("src/lib.rs:10:5".into()),
+        thread: None,
+        entry_point: Some("wgpu_render".into()),
+        backtrace: String::new(),
+    };
+    assert_eq!(report.to_text(), "panicked at src/lib.rs:10:5: index out of bounds\nentry point: wgpu_render\n");
+}
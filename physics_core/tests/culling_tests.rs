@@ -0,0 +1,47 @@
+//! Integration tests for culling instances against the camera rects
+
+use physics_core::culling::{runs, visible, ViewRect, MERGE_GAP};
+
+#[test]
+fn test_camera_rect() {
+    let view = ViewRect::camera([1.0, 0.0], 2.0, 2.0, 0.0);
+    assert_eq!(view, ViewRect { min: [-1.0, -1.0], max: [3.0, 1.0] });
+    assert!(view.touches([3.5, 0.0], 0.6));
+    assert!(!view.touches([3.5, 0.0], 0.4));
+    assert!(!view.touches([0.0, -1.5], 0.4));
+
+    // A quarter turn swaps width and height
+    let rolled = ViewRect::camera([0.0, 0.0], 2.0, 2.0, std::f32::consts::FRAC_PI_2);
+    assert!((rolled.max[0] - 1.0).abs() < 1e-5 && (rolled.max[1] - 2.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_visible_against_any_view() {
+    let views = [ViewRect::camera([0.0, 0.0], 2.0, 1.0, 0.0), ViewRect::camera([10.0, 0.0], 2.0, 1.0, 0.0)];
+    let bounds = [([0.0, 0.0], 0.1), ([5.0, 0.0], 0.1), ([10.5, 0.5], 0.1), ([5.0, 0.0], 4.5)];
+    assert_eq!(visible(bounds, &views), vec![true, false, true, true]);
+    assert_eq!(visible(bounds, &[]), vec![false; 4]);
+}
+
+#[test]
+fn test_runs_join_small_gaps() {
+    let mut shown = vec![false; 40];
+    for slot in [1, 2, 5, 20, 39] {
+        shown[slot] = true;
+    }
+    assert_eq!(runs(&shown, 0..40), vec![1..6, 20..21, 39..40]);
+    // Runs stay inside the batch
+    assert_eq!(runs(&shown, 3..21), vec![5..6, 20..21]);
+    assert_eq!(runs(&shown, 6..20), vec![]);
+    // Slots past the visibility list count as hidden
+    assert_eq!(runs(&shown, 38..50), vec![39..40]);
+
+    let gap = MERGE_GAP as usize;
+    let mut shown = vec![false; gap + 3];
+    shown[0] = true;
+    shown[gap + 1] = true;
+    assert_eq!(runs(&shown, 0..shown.len() as u32).len(), 1);
+    shown.push(true);
+    shown[gap + 1] = false;
+    assert_eq!(runs(&shown, 0..shown.len() as u32).len(), 2);
+}
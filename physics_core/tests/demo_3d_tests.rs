@@ -0,0 +1,95 @@
+//! Integration tests for the 3D demo layout, meshes and orbit camera
+
+use physics_core::demo_3d::{cuboid_mesh, generate, sphere_mesh, DemoShape, OrbitCamera, OrbitControls, BODY_HALF_EXTENT, GROUND_Y, MAX_BODIES};
+use physics_core::gltf_lite::ModelMesh;
+
+/// Every triangle with any area faces away from the mesh's center
+fn faces_outward(mesh: &ModelMesh) -> bool {
+    mesh.indices.chunks_exact(3).all(|triangle| {
+        let [a, b, c] = [0, 1, 2].map(|k| mesh.positions[triangle[k] as usize]);
+        let (e1, e2) = ([b[0] - a[0], b[1] - a[1], b[2] - a[2]], [c[0] - a[0], c[1] - a[1], c[2] - a[2]]);
+        let normal = [e1[1] * e2[2] - e1[2] * e2[1], e1[2] * e2[0] - e1[0] * e2[2], e1[0] * e2[1] - e1[1] * e2[0]];
+        let length = normal.iter().map(|v| v * v).sum::<f32>().sqrt();
+        let center: Vec<f32> = (0..3).map(|k| a[k] + b[k] + c[k]).collect();
+        length < 1e-6 || (0..3).map(|k| normal[k] * center[k]).sum::<f32>() > 0.0
+    })
+}
+
+#[test]
+fn test_meshes_face_outward() {
+    let cube = cuboid_mesh([1.0, 0.02, 1.0], [1.0, 0.0, 0.0]);
+    assert_eq!((cube.positions.len(), cube.indices.len()), (24, 36));
+    assert!(cube.positions.iter().all(|p| p[0].abs() == 1.0 && p[1].abs() == 0.02 && p[2].abs() == 1.0));
+    assert!(faces_outward(&cube));
+
+    let sphere = sphere_mesh(8, 12, [0.0, 0.0, 1.0]);
+    assert!(sphere.positions.iter().all(|p| (p.iter().map(|v| v * v).sum::<f32>() - 1.0).abs() < 1e-5));
+    assert_eq!(sphere.normals, sphere.positions);
+    assert!(faces_outward(&sphere));
+    assert!(!cube.double_sided && !sphere.double_sided);
+}
+
+#[test]
+fn test_layout_starts_above_the_ground_without_overlaps() {
+    let bodies = generate(100);
+    assert_eq!(bodies.len(), 100);
+    assert!(bodies.iter().any(|b| b.shape == DemoShape::Box) && bodies.iter().any(|b| b.shape == DemoShape::Sphere));
+    // Even rotated boxes clear each other and the ground
+    let reach = BODY_HALF_EXTENT * 3f32.sqrt();
+    assert!(bodies.iter().all(|b| b.position[1] - reach > GROUND_Y));
+    for (i, a) in bodies.iter().enumerate() {
+        for b in &bodies[i + 1..] {
+            let distance = (0..3).map(|k| (a.position[k] - b.position[k]).powi(2)).sum::<f32>().sqrt();
+            assert!(distance > 2.0 * reach);
+        }
+    }
+    assert_eq!(generate(1_000_000).len(), MAX_BODIES as usize);
+    assert!(generate(0).is_empty());
+}
+
+#[test]
+fn test_orbit_camera_limits() {
+    let mut camera = OrbitCamera::default();
+    let distance = |camera: &OrbitCamera| {
+        let eye = camera.eye();
+        (0..3).map(|k| (eye[k] - camera.target[k]).powi(2)).sum::<f32>().sqrt()
+    };
+    assert!((distance(&camera) - camera.distance).abs() < 1e-4);
+
+    // Never under the ground or over the top
+    camera.orbit(0.0, -10_000.0);
+    assert!(camera.eye()[1] > camera.target[1]);
+    camera.orbit(0.0, 10_000.0);
+    assert!(camera.pitch < std::f32::consts::FRAC_PI_2);
+
+    let before = camera.distance;
+    camera.zoom(2.0);
+    assert_eq!(camera.distance, before / 2.0);
+    camera.zoom(1e6);
+    camera.zoom(0.0);
+    camera.zoom(f32::NAN);
+    assert!(camera.distance > 0.0 && camera.distance.is_finite());
+    camera.zoom(1e-6);
+    assert!(camera.distance < 100.0);
+}
+
+#[test]
+fn test_pointer_drags_orbit() {
+    let mut controls = OrbitControls::default();
+    let start = controls.camera;
+    // Moves without a press are hover, not drags
+    assert!(!controls.pointer(1, 10.0, 10.0));
+    assert_eq!(controls.camera, start);
+
+    controls.pointer(0, 100.0, 100.0);
+    assert!(controls.pointer(1, 150.0, 100.0));
+    assert!(controls.camera.yaw < start.yaw);
+    assert_eq!(controls.camera.pitch, start.pitch);
+    assert!(controls.pointer(1, 150.0, 120.0));
+    assert!(controls.camera.pitch > start.pitch);
+
+    controls.pointer(2, 150.0, 120.0);
+    let released = controls.camera;
+    assert!(!controls.pointer(1, 300.0, 300.0));
+    assert_eq!(controls.camera, released);
+}
@@ -0,0 +1,41 @@
+//! Integration tests for the simulation checksum
+
+use physics_core::determinism::{run_canned_scene, Fnv1a};
+
+/// Canned scene before its first step: the floor and 25 boxes at rest in
+/// their start poses. Pins the scene layout and the hash format on every
+/// platform, independent of the solver.
+const CANNED_SCENE_START_CHECKSUM: u64 = 0x3ccc_84ae_1677_4df8;
+
+#[test]
+fn test_fnv1a_reference_values() {
+    assert_eq!(Fnv1a::new().finish(), 0xcbf2_9ce4_8422_2325);
+
+    let mut hasher = Fnv1a::new();
+    hasher.write_bytes(b"a");
+    assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+}
+
+#[test]
+fn test_signed_zero_hashes_equal() {
+    let mut positive = Fnv1a::new();
+    positive.write_f32(0.0);
+    let mut negative = Fnv1a::new();
+    negative.write_f32(-0.0);
+    assert_eq!(positive.finish(), negative.finish());
+}
+
+#[test]
+fn test_canned_scene_matches_golden_checksum() {
+    assert_eq!(run_canned_scene(0), CANNED_SCENE_START_CHECKSUM);
+}
+
+#[test]
+fn test_canned_scene_is_repeatable() {
+    assert_eq!(run_canned_scene(120), run_canned_scene(120));
+}
+
+#[test]
+fn test_canned_scene_checksum_changes_with_steps() {
+    assert_ne!(run_canned_scene(60), run_canned_scene(61));
+}
@@ -0,0 +1,100 @@
+//! Integration tests for the scene editor mode
+
+use physics_core::editor::{EditPose, Editor, EditorTool, MAX_HALF_EXTENT, ROTATE_STEP};
+
+fn pose(x: f32, y: f32) -> EditPose {
+    EditPose { x, y, half_extent: 0.1, angle: 0.0 }
+}
+
+#[test]
+fn test_editor_pauses_and_restores() {
+    let mut editor = Editor::new();
+    assert_eq!(editor.set_enabled(true, false), Some(true));
+    assert_eq!(editor.set_enabled(true, false), None);
+    editor.select(Some(7));
+    assert_eq!(editor.set_enabled(false, true), Some(false));
+    assert_eq!(editor.selected(), None);
+
+    // A simulation paused before stays paused
+    editor.set_enabled(true, true);
+    assert_eq!(editor.set_enabled(false, true), Some(true));
+}
+
+#[test]
+fn test_tools_drag_the_pressed_body() {
+    let mut editor = Editor::new();
+    assert_eq!(editor.press([1.0, 1.0], Some((3, pose(1.0, 1.0)))), None);
+    assert_eq!(editor.selected(), Some(3));
+    let (entity, moved) = editor.drag_to([1.5, 0.75]).unwrap();
+    assert_eq!((entity, moved.x, moved.y), (3, 1.5, 0.75));
+    editor.release();
+    assert_eq!(editor.drag_to([2.0, 2.0]), None);
+
+    editor.tool = EditorTool::Scale;
+    editor.press([1.1, 1.0], Some((3, pose(1.0, 1.0))));
+    let scaled = editor.drag_to([1.3, 1.0]).unwrap().1;
+    assert!((scaled.half_extent - 0.3).abs() < 1e-5);
+    assert_eq!(editor.drag_to([100.0, 1.0]).unwrap().1.half_extent, MAX_HALF_EXTENT);
+
+    editor.tool = EditorTool::Rotate;
+    editor.press([2.0, 1.0], Some((3, pose(1.0, 1.0))));
+    let turned = editor.drag_to([1.0, 2.0]).unwrap().1;
+    assert!((turned.angle - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+    assert_eq!((turned.x, turned.half_extent), (1.0, 0.1));
+}
+
+#[test]
+fn test_snapping() {
+    let mut editor = Editor::new();
+    editor.snap = 0.25;
+    editor.press([0.0, 0.0], Some((1, pose(0.0, 0.0))));
+    let moved = editor.drag_to([0.3, -0.6]).unwrap().1;
+    assert_eq!((moved.x, moved.y), (0.25, -0.5));
+
+    editor.tool = EditorTool::Rotate;
+    editor.press([1.0, 0.0], Some((1, pose(0.0, 0.0))));
+    let turned = editor.drag_to([1.0, 0.3]).unwrap().1;
+    assert!((turned.angle - ROTATE_STEP).abs() < 1e-5);
+}
+
+#[test]
+fn test_place_tool_spawns_on_empty_space_only() {
+    let mut editor = Editor::new();
+    editor.tool = EditorTool::Place;
+    editor.place_half_extent = 0.2;
+    let placed = editor.press([0.5, 0.5], None).unwrap();
+    assert_eq!(placed, EditPose { x: 0.5, y: 0.5, half_extent: 0.2, angle: 0.0 });
+    editor.placed(9, [0.5, 0.5], placed);
+    assert_eq!(editor.selected(), Some(9));
+    assert_eq!(editor.drag_to([0.6, 0.5]).unwrap().1.x, 0.6);
+
+    // Pressing on a body picks it up instead
+    assert_eq!(editor.press([0.0, 0.0], Some((4, pose(0.0, 0.0)))), None);
+    assert_eq!(editor.drag().unwrap().tool, EditorTool::Move);
+    editor.forget(4);
+    assert_eq!((editor.selected(), editor.drag()), (None, None));
+
+    // Other tools clear the selection on empty space
+    editor.tool = EditorTool::Move;
+    editor.select(Some(9));
+    assert_eq!(editor.press([0.0, 0.0], None), None);
+    assert_eq!(editor.selected(), None);
+}
+
+#[test]
+fn test_saving_over_a_scene_keeps_everything_but_bodies() {
+    let bodies = serde_json::json!([{ "x": 1.0 }]);
+    let none = serde_json::json!([]);
+    let base = r#"{ "level": { "shapes": [] }, "bodies": [{ "x": 0.0 }, { "x": 2.0 }] }"#;
+    let saved: serde_json::Value = serde_json::from_str(&physics_core::editor::scene_json(Some(base), bodies.clone(), none.clone(), [0.0, -9.8]).unwrap()).unwrap();
+    assert_eq!(saved["bodies"], bodies);
+    assert_eq!(saved["level"]["shapes"], serde_json::json!([]));
+    assert!(saved.get("gravity").is_none());
+    assert!(saved.get("instances").is_none());
+
+    let instances = serde_json::json!([{ "prefab": "crate", "x": 1.0 }]);
+    let fresh: serde_json::Value = serde_json::from_str(&physics_core::editor::scene_json(None, bodies, instances.clone(), [0.0, -9.8]).unwrap()).unwrap();
+    assert_eq!(fresh["gravity"][1].as_f64().unwrap() as f32, -9.8);
+    assert_eq!(fresh["instances"], instances);
+    assert!(physics_core::editor::scene_json(Some("[1, 2]"), none.clone(), none, [0.0, 0.0]).is_err());
+}
@@ -0,0 +1,74 @@
+//! Integration tests for wind, force volumes and buoyancy
+
+use physics_core::force_fields::{self, BodySample, BuoyancyRegion, ForceFieldConfig, ForceVolume, VolumeKind, Wind};
+
+const GRAVITY: [f32; 2] = [0.0, -9.81];
+
+fn body_at(x: f32, y: f32) -> BodySample {
+    BodySample { position: [x, y], velocity: [0.0, 0.0], mass: 0.001, half_extent: 0.05 }
+}
+
+#[test]
+fn test_gust_noise_is_smooth_and_bounded() {
+    let mut previous = force_fields::gust_noise(0.0);
+    for i in 1..1000 {
+        let value = force_fields::gust_noise(i as f32 * 0.01);
+        assert!((-1.0..=1.0).contains(&value));
+        assert!((value - previous).abs() < 0.05);
+        previous = value;
+    }
+    assert_eq!(force_fields::gust_noise(3.7), force_fields::gust_noise(3.7));
+}
+
+#[test]
+fn test_wind_pushes_toward_wind_speed() {
+    let config = ForceFieldConfig {
+        wind: Some(Wind { velocity: [2.0, 0.0], gustiness: 0.0, ..Default::default() }),
+        ..Default::default()
+    };
+    let still = config.force_on(&body_at(0.0, 0.0), GRAVITY, 0.0);
+    assert!(still[0] > 0.0 && still[1] == 0.0);
+
+    let mut moving = body_at(0.0, 0.0);
+    moving.velocity = [2.0, 0.0];
+    assert_eq!(config.force_on(&moving, GRAVITY, 0.0), [0.0, 0.0]);
+}
+
+#[test]
+fn test_volumes_fall_off_to_zero_at_radius() {
+    let attract = ForceVolume { center: [0.0, 0.0], radius: 1.0, kind: VolumeKind::Attract, strength: 10.0 };
+    let a = attract.acceleration_at([0.5, 0.0]);
+    assert!((a[0] + 5.0).abs() < 1e-5 && a[1] == 0.0);
+    assert_eq!(attract.acceleration_at([1.0, 0.0]), [0.0, 0.0]);
+    assert_eq!(attract.acceleration_at([0.0, 0.0]), [0.0, 0.0]);
+
+    let repel = ForceVolume { kind: VolumeKind::Repel, ..attract };
+    assert!(repel.acceleration_at([0.5, 0.0])[0] > 0.0);
+
+    // Counterclockwise: a body on the +x axis is pushed toward +y
+    let vortex = ForceVolume { kind: VolumeKind::Vortex, ..attract };
+    let v = vortex.acceleration_at([0.5, 0.0]);
+    assert!(v[0].abs() < 1e-6 && v[1] > 0.0);
+}
+
+#[test]
+fn test_buoyancy_scales_with_submerged_fraction() {
+    let water = BuoyancyRegion { surface_y: 0.0, fluid_density: 2.0, drag: 0.0, ..Default::default() };
+    assert_eq!(water.submerged_fraction([0.0, 1.0], 0.05), 0.0);
+    assert_eq!(water.submerged_fraction([0.0, -1.0], 0.05), 1.0);
+    assert!((water.submerged_fraction([0.0, 0.0], 0.05) - 0.5).abs() < 1e-6);
+    assert_eq!(water.submerged_fraction([5.0, -1.0], 0.05), 0.0);
+
+    // Fully submerged at density 2: lift is twice the box's weight
+    let config = ForceFieldConfig { buoyancy: vec![water], ..Default::default() };
+    let body = body_at(0.0, -1.0);
+    let lift = config.force_on(&body, GRAVITY, 0.0);
+    assert!((lift[1] - 2.0 * body.mass * 9.81).abs() < 1e-6);
+}
+
+#[test]
+fn test_empty_config_exerts_no_force() {
+    let config = ForceFieldConfig::default();
+    assert!(config.is_empty());
+    assert_eq!(config.force_on(&body_at(0.0, 0.0), GRAVITY, 1.0), [0.0, 0.0]);
+}
@@ -0,0 +1,97 @@
+//! Integration tests for fracture layout, momentum and the event queue
+
+use physics_core::fracture::{
+    fragment_offsets, point_velocity, FractureConfig, FractureEvent, FractureQueue, MAX_FRAGMENTS, MAX_PARTICLES, MAX_QUEUED,
+};
+
+fn config(fragments: u32, min_half_extent: f32) -> FractureConfig {
+    FractureConfig { fragments, min_half_extent, ..FractureConfig::default() }
+}
+
+#[test]
+fn test_validated_rejects_bad_thresholds_and_clamps_counts() {
+    assert!(FractureConfig { threshold: 0.0, ..FractureConfig::default() }.validated().is_none());
+    assert!(FractureConfig { threshold: f32::NAN, ..FractureConfig::default() }.validated().is_none());
+    assert!(FractureConfig { min_half_extent: -1.0, ..FractureConfig::default() }.validated().is_none());
+
+    let clamped = FractureConfig { fragments: 1, particles: 1000, ..FractureConfig::default() }.validated().unwrap();
+    assert_eq!(clamped.fragments, 4);
+    assert_eq!(clamped.particles, MAX_PARTICLES);
+    let clamped = FractureConfig { fragments: 1000, ..FractureConfig::default() }.validated().unwrap();
+    assert_eq!(clamped.fragments, MAX_FRAGMENTS);
+}
+
+#[test]
+fn test_grid_rounds_down_and_respects_min_size() {
+    assert_eq!(config(4, 0.01).grid(0.1), Some(2));
+    assert_eq!(config(10, 0.01).grid(0.1), Some(3));
+    assert_eq!(config(16, 0.01).grid(0.1), Some(4));
+    // 4x4 pieces would be 0.025, below the minimum
+    assert_eq!(config(16, 0.03).grid(0.1), Some(3));
+    assert_eq!(config(16, 0.06).grid(0.1), None);
+}
+
+#[test]
+fn test_offsets_tile_the_square_around_its_center() {
+    let offsets = fragment_offsets(0.1, 2, 0.0);
+    let expected = [[-0.05, -0.05], [0.05, -0.05], [-0.05, 0.05], [0.05, 0.05]];
+    for (offset, expected) in offsets.iter().zip(expected) {
+        assert!((offset[0] - expected[0]).abs() < 1e-6 && (offset[1] - expected[1]).abs() < 1e-6);
+    }
+
+    let offsets = fragment_offsets(0.3, 3, 0.7);
+    assert_eq!(offsets.len(), 9);
+    let sum = offsets.iter().fold([0.0f32, 0.0], |acc, o| [acc[0] + o[0], acc[1] + o[1]]);
+    assert!(sum[0].abs() < 1e-6 && sum[1].abs() < 1e-6);
+    // The middle piece stays put and the corners keep their distance
+    assert!(offsets[4][0].abs() < 1e-6 && offsets[4][1].abs() < 1e-6);
+    let corner = (offsets[0][0].powi(2) + offsets[0][1].powi(2)).sqrt();
+    assert!((corner - 0.2 * 2f32.sqrt()).abs() < 1e-5);
+}
+
+#[test]
+fn test_rotated_offsets_turn_with_the_body() {
+    let offsets = fragment_offsets(0.1, 2, std::f32::consts::FRAC_PI_2);
+    // The bottom-left piece ends up bottom-right after a quarter turn
+    assert!((offsets[0][0] - 0.05).abs() < 1e-6 && (offsets[0][1] + 0.05).abs() < 1e-6);
+}
+
+#[test]
+fn test_fragments_conserve_linear_momentum() {
+    let linvel = [1.5, -2.0];
+    let offsets = fragment_offsets(0.2, 4, 0.3);
+    let sum = offsets
+        .iter()
+        .map(|&offset| point_velocity(linvel, 3.0, offset))
+        .fold([0.0f32, 0.0], |acc, v| [acc[0] + v[0], acc[1] + v[1]]);
+    let n = offsets.len() as f32;
+    assert!((sum[0] / n - linvel[0]).abs() < 1e-5);
+    assert!((sum[1] / n - linvel[1]).abs() < 1e-5);
+}
+
+#[test]
+fn test_point_velocity_adds_spin() {
+    assert_eq!(point_velocity([0.0, 0.0], 2.0, [1.0, 0.0]), [0.0, 2.0]);
+    assert_eq!(point_velocity([1.0, 0.0], 2.0, [0.0, 1.0]), [-1.0, 0.0]);
+    assert_eq!(point_velocity([1.0, 1.0], 0.0, [5.0, 5.0]), [1.0, 1.0]);
+}
+
+#[test]
+fn test_queue_drops_the_oldest_when_full() {
+    let mut queue = FractureQueue::new();
+    for i in 0..MAX_QUEUED as u64 + 3 {
+        queue.push(FractureEvent { entity: i, ..FractureEvent::default() });
+    }
+    assert_eq!(queue.len(), MAX_QUEUED);
+    assert_eq!(queue.dropped(), 3);
+    let events = queue.drain(2);
+    assert_eq!(events.iter().map(|e| e.entity).collect::<Vec<_>>(), vec![3, 4]);
+    assert_eq!(queue.drain(usize::MAX).len(), MAX_QUEUED - 2);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_missing_json_fields_take_defaults() {
+    let config: FractureConfig = serde_json::from_str(r#"{"threshold": 0.5, "minHalfExtent": 0.02}"#).unwrap();
+    assert_eq!(config, FractureConfig { threshold: 0.5, min_half_extent: 0.02, ..FractureConfig::default() });
+}
@@ -0,0 +1,77 @@
+//! Integration tests for touch gesture recognition
+
+use physics_core::gestures::{Gesture, GestureRecognizer};
+
+#[test]
+fn test_quick_press_is_tap() {
+    let mut recognizer = GestureRecognizer::default();
+    assert!(recognizer.touch_down(0, 100.0, 100.0, 0.0).is_empty());
+    assert!(recognizer.touch_move(0, 103.0, 101.0, 0.05).is_empty());
+    assert_eq!(recognizer.touch_up(0, 103.0, 101.0, 0.1), vec![Gesture::Tap { x: 103.0, y: 101.0 }]);
+}
+
+#[test]
+fn test_long_press_is_not_tap() {
+    let mut recognizer = GestureRecognizer::default();
+    recognizer.touch_down(0, 100.0, 100.0, 0.0);
+    assert!(recognizer.touch_up(0, 100.0, 100.0, 1.0).is_empty());
+}
+
+#[test]
+fn test_drag_reports_release_velocity() {
+    let mut recognizer = GestureRecognizer::default();
+    recognizer.touch_down(0, 0.0, 0.0, 0.0);
+    assert_eq!(recognizer.touch_move(0, 50.0, 0.0, 0.05), vec![Gesture::DragStart { x: 0.0, y: 0.0 }]);
+    recognizer.touch_move(0, 100.0, 0.0, 0.1);
+    match recognizer.touch_up(0, 150.0, 0.0, 0.15)[..] {
+        [Gesture::DragEnd { x, vx, vy, .. }] => {
+            assert_eq!(x, 150.0);
+            assert!((vx - 1000.0).abs() < 1.0, "vx = {}", vx);
+            assert_eq!(vy, 0.0);
+        }
+        ref other => panic!("unexpected gestures {:?}", other),
+    }
+}
+
+#[test]
+fn test_two_fingers_pinch_and_pan() {
+    let mut recognizer = GestureRecognizer::default();
+    recognizer.touch_down(0, 100.0, 100.0, 0.0);
+    recognizer.touch_down(1, 200.0, 100.0, 0.01);
+    let gestures = recognizer.touch_move(1, 300.0, 100.0, 0.05);
+    assert_eq!(
+        gestures,
+        vec![
+            Gesture::Pinch { factor: 2.0, center_x: 200.0, center_y: 100.0 },
+            Gesture::Pan { dx: 50.0, dy: 0.0 },
+        ]
+    );
+
+    // Lifting one finger never turns the leftover finger into a tap or drag
+    assert!(recognizer.touch_up(1, 300.0, 100.0, 0.1).is_empty());
+    assert!(recognizer.touch_move(0, 400.0, 100.0, 0.15).is_empty());
+    assert!(recognizer.touch_up(0, 400.0, 100.0, 0.2).is_empty());
+}
+
+#[test]
+fn test_second_finger_ends_drag() {
+    let mut recognizer = GestureRecognizer::default();
+    recognizer.touch_down(0, 0.0, 0.0, 0.0);
+    recognizer.touch_move(0, 40.0, 0.0, 0.05);
+    let gestures = recognizer.touch_down(1, 100.0, 100.0, 0.1);
+    assert_eq!(gestures, vec![Gesture::DragEnd { x: 40.0, y: 0.0, vx: 0.0, vy: 0.0 }]);
+}
+
+#[test]
+fn test_tap_slop_scales_with_density() {
+    // 20 px is past the default 16 px slop, but inside it at 2x
+    let mut recognizer = GestureRecognizer::default();
+    recognizer.touch_down(0, 100.0, 100.0, 0.0);
+    assert_eq!(recognizer.touch_move(0, 120.0, 100.0, 0.05), vec![Gesture::DragStart { x: 100.0, y: 100.0 }]);
+
+    let mut recognizer = GestureRecognizer::default();
+    recognizer.set_scale_factor(2.0);
+    recognizer.touch_down(0, 100.0, 100.0, 0.0);
+    assert!(recognizer.touch_move(0, 120.0, 100.0, 0.05).is_empty());
+    assert_eq!(recognizer.touch_up(0, 120.0, 100.0, 0.1), vec![Gesture::Tap { x: 120.0, y: 100.0 }]);
+}
@@ -0,0 +1,110 @@
+//! Integration tests for glTF model loading
+
+use physics_core::gltf_lite::parse;
+
+/// A 2x2 square in the xy plane: 4 float positions then 6 u16 indices
+fn square_buffer() -> Vec<u8> {
+    let positions: [f32; 12] = [0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 2.0, 2.0, 0.0, 0.0, 2.0, 0.0];
+    let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+    let mut bytes: Vec<u8> = positions.iter().flat_map(|v| v.to_le_bytes()).collect();
+    bytes.extend(indices.iter().flat_map(|i| i.to_le_bytes()));
+    bytes
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for k in 0..chunk.len() + 1 {
+            out.push(ALPHABET[(n >> (18 - 6 * k) & 63) as usize] as char);
+        }
+    }
+    while !out.len().is_multiple_of(4) {
+        out.push('=');
+    }
+    out
+}
+
+fn document(buffer: &str, node: &str) -> String {
+    format!(
+        r#"{{
+            "scenes": [{{"nodes": [0]}}],
+            "nodes": [{node}],
+            "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}, "indices": 1, "material": 0}}]}}],
+            "materials": [{{"pbrMetallicRoughness": {{"baseColorFactor": [1, 0.5, 0, 1]}}, "doubleSided": true}}],
+            "buffers": [{buffer}],
+            "bufferViews": [
+                {{"buffer": 0, "byteLength": 48}},
+                {{"buffer": 0, "byteOffset": 48, "byteLength": 12}}
+            ],
+            "accessors": [
+                {{"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3"}},
+                {{"bufferView": 1, "componentType": 5123, "count": 6, "type": "SCALAR"}}
+            ]
+        }}"#
+    )
+}
+
+fn embedded(node: &str) -> String {
+    let buffer = format!(r#"{{"uri": "data:application/octet-stream;base64,{}", "byteLength": 60}}"#, base64(&square_buffer()));
+    document(&buffer, node)
+}
+
+fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+    let mut json = json.as_bytes().to_vec();
+    json.resize(json.len().div_ceil(4) * 4, b' ');
+    let mut bytes = b"glTF".to_vec();
+    bytes.extend(2u32.to_le_bytes());
+    bytes.extend(((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+    bytes.extend((json.len() as u32).to_le_bytes());
+    bytes.extend(b"JSON");
+    bytes.extend(json);
+    bytes.extend((bin.len() as u32).to_le_bytes());
+    bytes.extend(b"BIN\0");
+    bytes.extend(bin);
+    bytes
+}
+
+#[test]
+fn test_embedded_gltf_is_centered_and_fitted() {
+    let mesh = parse(embedded(r#"{"mesh": 0}"#).as_bytes()).unwrap();
+    assert_eq!(mesh.positions, vec![[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0]]);
+    assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+    // No normals in the file, so they come from the counter-clockwise faces
+    assert!(mesh.normals.iter().all(|n| *n == [0.0, 0.0, 1.0]));
+    assert!(mesh.colors.iter().all(|c| *c == [1.0, 0.5, 0.0]));
+    assert!(mesh.double_sided);
+}
+
+#[test]
+fn test_glb_applies_node_transforms() {
+    // Half a turn around y faces the square away from the viewer
+    let json = document(r#"{"byteLength": 60}"#, r#"{"mesh": 0, "rotation": [0, 1, 0, 0], "translation": [5, 5, 5]}"#);
+    let mesh = parse(&glb(&json, &square_buffer())).unwrap();
+    assert_eq!(mesh.positions.len(), 4);
+    assert!(mesh.positions.iter().all(|p| p.iter().all(|v| v.abs() <= 1.0 + 1e-6)));
+    assert!(mesh.normals.iter().all(|n| (n[2] + 1.0).abs() < 1e-6));
+
+    // A mirroring scale flips the winding so faces still point out
+    let json = document(r#"{"byteLength": 60}"#, r#"{"mesh": 0, "scale": [-1, 1, 1]}"#);
+    let mesh = parse(&glb(&json, &square_buffer())).unwrap();
+    assert_eq!(mesh.indices, vec![0, 2, 1, 0, 3, 2]);
+    assert!(mesh.normals.iter().all(|n| *n == [0.0, 0.0, 1.0]));
+}
+
+#[test]
+fn test_unsupported_or_broken_files_are_rejected() {
+    assert!(parse(b"not a model").is_err());
+    assert!(parse(b"glTF\x01\0\0\0\x0c\0\0\0").is_err());
+    let external = document(r#"{"uri": "square.bin", "byteLength": 60}"#, r#"{"mesh": 0}"#);
+    assert!(parse(external.as_bytes()).unwrap_err().contains("external"));
+
+    // Index 9 of a 4-vertex square
+    let mut buffer = square_buffer();
+    buffer[58] = 9;
+    let json = document(r#"{"byteLength": 60}"#, r#"{"mesh": 0}"#);
+    assert!(parse(&glb(&json, &buffer)).is_err());
+    // Not enough bytes behind the accessors
+    assert!(parse(&glb(&json, &buffer[..40])).is_err());
+}
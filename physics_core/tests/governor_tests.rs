@@ -0,0 +1,78 @@
+//! Integration tests for the power-state performance governor
+
+use physics_core::governor::{Governor, PowerState, ThermalStatus, ThrottleLevel, RECOVERY_SECONDS};
+
+fn power(battery: f32, charging: bool, thermal: ThermalStatus) -> PowerState {
+    PowerState::new(battery, charging, thermal, false).unwrap()
+}
+
+#[test]
+fn test_power_state_picks_level() {
+    let none = ThrottleLevel::None;
+    assert_eq!(power(-1.0, false, ThermalStatus::None).level(none), ThrottleLevel::None);
+    assert_eq!(power(0.15, false, ThermalStatus::None).level(none), ThrottleLevel::Light);
+    assert_eq!(power(0.05, false, ThermalStatus::None).level(none), ThrottleLevel::Heavy);
+    // A charging battery is never low
+    assert_eq!(power(0.05, true, ThermalStatus::None).level(none), ThrottleLevel::None);
+    assert_eq!(power(0.9, true, ThermalStatus::Moderate).level(none), ThrottleLevel::Light);
+    assert_eq!(power(0.9, true, ThermalStatus::Severe).level(none), ThrottleLevel::Heavy);
+    assert_eq!(PowerState::new(-1.0, false, ThermalStatus::None, true).unwrap().level(none), ThrottleLevel::Light);
+
+    assert!(PowerState::new(1.5, false, ThermalStatus::None, false).is_none());
+    assert!(PowerState::new(f32::NAN, false, ThermalStatus::None, false).is_none());
+    assert_eq!(ThermalStatus::from_i32(4), Some(ThermalStatus::Critical));
+    assert_eq!(ThermalStatus::from_i32(7), None);
+}
+
+#[test]
+fn test_battery_has_to_climb_past_the_margin_to_recover() {
+    // 21% is above the low threshold, but not by the margin
+    assert_eq!(power(0.21, false, ThermalStatus::None).level(ThrottleLevel::Light), ThrottleLevel::Light);
+    assert_eq!(power(0.26, false, ThermalStatus::None).level(ThrottleLevel::Light), ThrottleLevel::None);
+    assert_eq!(power(0.12, false, ThermalStatus::None).level(ThrottleLevel::Heavy), ThrottleLevel::Heavy);
+}
+
+#[test]
+fn test_governor_throttles_at_once_and_recovers_slowly() {
+    let mut governor = Governor::new();
+    governor.set_power(power(0.9, false, ThermalStatus::Severe));
+    assert!(governor.update(0.016));
+    assert_eq!(governor.level(), ThrottleLevel::Heavy);
+    assert_eq!(governor.throttle().max_fps, 30);
+
+    governor.set_power(power(0.9, false, ThermalStatus::None));
+    assert!(!governor.update(RECOVERY_SECONDS / 2.0));
+    // A hot spell in between starts the wait over
+    governor.set_power(power(0.9, false, ThermalStatus::Severe));
+    assert!(!governor.update(0.016));
+    governor.set_power(power(0.9, false, ThermalStatus::None));
+    assert!(!governor.update(RECOVERY_SECONDS / 2.0));
+    assert_eq!(governor.level(), ThrottleLevel::Heavy);
+    assert!(governor.update(RECOVERY_SECONDS / 2.0));
+    assert_eq!(governor.level(), ThrottleLevel::None);
+    assert_eq!(governor.stats().changes, 2);
+}
+
+#[test]
+fn test_disabled_governor_does_not_throttle() {
+    let mut governor = Governor::new();
+    governor.set_power(PowerState::new(-1.0, false, ThermalStatus::None, true).unwrap());
+    assert!(governor.update(0.016));
+    governor.set_enabled(false);
+    assert_eq!(governor.level(), ThrottleLevel::None);
+    assert!(!governor.update(0.016));
+    let stats = governor.stats();
+    assert!(!stats.enabled && stats.power.low_power_mode);
+    assert!(stats.to_json().contains("\"level\":\"none\""));
+}
+
+#[test]
+fn test_throttle_limits() {
+    let light = ThrottleLevel::Light.throttle();
+    assert_eq!(light.solver_iterations(4), 2);
+    assert_eq!(light.solver_iterations(1), 1);
+    assert_eq!(light.particles(8), 4);
+    assert_eq!(ThrottleLevel::Heavy.throttle().particles(8), 0);
+    let none = ThrottleLevel::None.throttle();
+    assert_eq!((none.solver_iterations(8), none.particles(8), none.frame_interval_ms()), (8, 8, None));
+}
@@ -0,0 +1,45 @@
+//! Integration tests for adapter capability detection
+
+use physics_core::gpu_caps::{compression_features, texture_compression, GpuCaps};
+use physics_core::ktx2_lite::TextureCompression;
+
+#[test]
+fn test_webgl2_has_no_compute_instance_path() {
+    let caps = GpuCaps::new(wgpu::DownlevelFlags::empty(), &wgpu::Limits::downlevel_webgl2_defaults(), false);
+    assert!(!caps.instance_compute());
+    assert!(!caps.bloom);
+    assert!(!caps.gpu_culling);
+    let usage = caps.instance_buffer_usage();
+    assert_eq!(usage, wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST);
+}
+
+#[test]
+fn test_full_adapter_gets_storage_instances() {
+    let caps = GpuCaps::new(wgpu::DownlevelFlags::all(), &wgpu::Limits::default(), true);
+    assert!(caps.instance_compute());
+    assert!(caps.bloom);
+    assert!(caps.gpu_culling);
+    assert!(caps.instance_buffer_usage().contains(wgpu::BufferUsages::STORAGE));
+}
+
+#[test]
+fn test_compute_without_storage_buffers_stays_on_cpu_path() {
+    let mut limits = wgpu::Limits::default();
+    limits.max_storage_buffers_per_shader_stage = 0;
+    let caps = GpuCaps::new(wgpu::DownlevelFlags::COMPUTE_SHADERS, &limits, false);
+    assert!(caps.compute_shaders);
+    assert!(!caps.instance_compute());
+    assert!(!caps.instance_buffer_usage().contains(wgpu::BufferUsages::STORAGE));
+}
+
+#[test]
+fn test_only_compression_the_adapter_has_is_requested() {
+    let adapter = wgpu::Features::TEXTURE_COMPRESSION_ASTC | wgpu::Features::TEXTURE_COMPRESSION_ETC2 | wgpu::Features::TIMESTAMP_QUERY;
+    let requested = compression_features(adapter);
+    assert_eq!(requested, wgpu::Features::TEXTURE_COMPRESSION_ASTC | wgpu::Features::TEXTURE_COMPRESSION_ETC2);
+    assert_eq!(texture_compression(requested), TextureCompression { astc: true, etc2: true, bc: false });
+    assert_eq!(compression_features(wgpu::Features::empty()), wgpu::Features::empty());
+    // Nothing is compressed until the device's features are known
+    let caps = GpuCaps::new(wgpu::DownlevelFlags::all(), &wgpu::Limits::default(), true);
+    assert_eq!(caps.texture_compression, TextureCompression::default());
+}
@@ -0,0 +1,38 @@
+//! Integration tests for the GPU cull pass's uniform and draw args
+
+use physics_core::culling::ViewRect;
+use physics_core::gpu_culling::{reset_args, CullParams, MAX_RECTS};
+
+fn rect(min: [f32; 2], max: [f32; 2]) -> ViewRect {
+    ViewRect { min, max }
+}
+
+#[test]
+fn test_params_match_the_shader_uniform() {
+    // rects, five scalars, padded to 16 bytes
+    assert_eq!(std::mem::size_of::<CullParams>(), MAX_RECTS * 16 + 32);
+
+    let params = CullParams::new(&[rect([-1.0, -2.0], [1.0, 2.0])], 3, 100, 1.5, 0.25);
+    assert_eq!(params.rects[0], [-1.0, -2.0, 1.0, 2.0]);
+    assert_eq!((params.rect_count, params.batch_count, params.instance_count), (1, 3, 100));
+    assert_eq!((params.grow, params.shift), (1.5, 0.25));
+}
+
+#[test]
+fn test_extra_rects_fold_into_the_last() {
+    let mut rects: Vec<ViewRect> = (0..MAX_RECTS).map(|i| rect([i as f32, 0.0], [i as f32 + 1.0, 1.0])).collect();
+    rects.push(rect([-5.0, -5.0], [0.0, 0.0]));
+    let params = CullParams::new(&rects, 1, 1, 1.0, 0.0);
+    assert_eq!(params.rect_count, MAX_RECTS as u32);
+    assert_eq!(params.rects[MAX_RECTS - 2], [(MAX_RECTS - 2) as f32, 0.0, (MAX_RECTS - 1) as f32, 1.0]);
+    // Still covers both the last rect and the extra one
+    let last = (MAX_RECTS - 1) as f32;
+    assert_eq!(params.rects[MAX_RECTS - 1], [-5.0, -5.0, last + 1.0, 1.0]);
+}
+
+#[test]
+fn test_reset_args_count_nothing() {
+    let args = reset_args(3, 6);
+    assert_eq!(args.len(), 3);
+    assert!(args.iter().all(|a| a.index_count == 6 && a.instance_count == 0 && a.first_instance == 0));
+}
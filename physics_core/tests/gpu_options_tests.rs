@@ -0,0 +1,40 @@
+//! Integration tests for adapter selection options
+
+use physics_core::gpu_options::{self, GpuInitOptions};
+
+#[test]
+fn test_zero_backend_bits_select_everything() {
+    assert_eq!(gpu_options::backends_from_bits(0), wgpu::Backends::all());
+    // Bits outside the known set are ignored, which leaves nothing selected
+    assert_eq!(gpu_options::backends_from_bits(1 << 20), wgpu::Backends::all());
+}
+
+#[test]
+fn test_backend_bits_map_to_wgpu_backends() {
+    let backends = gpu_options::backends_from_bits(gpu_options::BACKEND_VULKAN | gpu_options::BACKEND_GL);
+    assert_eq!(backends, wgpu::Backends::VULKAN | wgpu::Backends::GL);
+    assert_eq!(
+        gpu_options::backends_from_bits(gpu_options::BACKEND_BROWSER_WEBGPU),
+        wgpu::Backends::BROWSER_WEBGPU
+    );
+}
+
+#[test]
+fn test_power_preference_values() {
+    assert_eq!(
+        gpu_options::power_preference_from_raw(gpu_options::POWER_PREFERENCE_LOW_POWER),
+        wgpu::PowerPreference::LowPower
+    );
+    assert_eq!(
+        gpu_options::power_preference_from_raw(gpu_options::POWER_PREFERENCE_NONE),
+        wgpu::PowerPreference::None
+    );
+    assert_eq!(gpu_options::power_preference_from_raw(42), wgpu::PowerPreference::HighPerformance);
+}
+
+#[test]
+fn test_from_raw_defaults_match_default() {
+    let options = GpuInitOptions::from_raw(0, gpu_options::POWER_PREFERENCE_HIGH_PERFORMANCE, false);
+    assert_eq!(options, GpuInitOptions::default());
+    assert!(GpuInitOptions::from_raw(0, 0, true).force_fallback_adapter);
+}
@@ -0,0 +1,46 @@
+//! Integration tests for GPU pass timing and frame statistics
+
+use physics_core::gpu_timing::{self, FrameStats};
+
+#[test]
+fn test_ticks_convert_to_milliseconds() {
+    // 1 ns per tick
+    let ms = gpu_timing::ticks_to_ms(&[1_000, 2_001_000, 5_000_000, 5_500_000], 1.0);
+    assert_eq!(ms.len(), 2);
+    assert!((ms[0] - 2.0).abs() < 1e-4);
+    assert!((ms[1] - 0.5).abs() < 1e-4);
+}
+
+#[test]
+fn test_backwards_ticks_read_as_zero() {
+    assert_eq!(gpu_timing::ticks_to_ms(&[500, 100], 1.0), vec![0.0]);
+}
+
+#[test]
+fn test_timestamps_need_both_features() {
+    assert!(gpu_timing::timestamp_features(wgpu::Features::TIMESTAMP_QUERY).is_empty());
+    let both = wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+    assert_eq!(gpu_timing::timestamp_features(both | wgpu::Features::DEPTH_CLIP_CONTROL), both);
+}
+
+#[test]
+fn test_pass_timings_are_smoothed_and_dropped_when_missing() {
+    let mut stats = FrameStats::default();
+    stats.record_gpu_passes(&[("Main", 4.0), ("Post", 2.0)]);
+    assert_eq!(stats.gpu_total_ms, 6.0);
+
+    stats.record_gpu_passes(&[("Main", 14.0)]);
+    assert_eq!(stats.gpu_passes.len(), 1);
+    // First sample is taken as is, later ones move a tenth of the way
+    assert!((stats.gpu_passes[0].ms - 5.0).abs() < 1e-4);
+    assert!((stats.gpu_total_ms - 5.0).abs() < 1e-4);
+}
+
+#[test]
+fn test_stats_json_uses_camel_case() {
+    let mut stats = FrameStats::default();
+    stats.record_cpu_frame(3.0);
+    let json = stats.to_json();
+    assert!(json.contains("\"cpuFrameMs\":3.0"));
+    assert!(json.contains("\"gpuPasses\":[]"));
+}
@@ -0,0 +1,34 @@
+//! Integration tests for the gravity presets
+
+use physics_core::gravity::GravityPreset;
+
+#[test]
+fn test_presets_by_code_point_down() {
+    assert_eq!(GravityPreset::from_i32(0), Some(GravityPreset::Earth));
+    assert_eq!(GravityPreset::from_i32(2), Some(GravityPreset::ZeroG));
+    assert_eq!(GravityPreset::from_i32(3), None);
+    assert_eq!(GravityPreset::from_i32(-1), None);
+    assert_eq!(GravityPreset::Earth.gravity(), [0.0, -9.81, 0.0]);
+    assert_eq!(GravityPreset::ZeroG.gravity(), [0.0, 0.0, 0.0]);
+    let [x, y, z] = GravityPreset::Moon.gravity();
+    assert!(x == 0.0 && z == 0.0 && y < 0.0 && y > GravityPreset::Earth.gravity()[1]);
+}
+
+#[test]
+fn test_matching_finds_the_preset_of_a_vector() {
+    for preset in GravityPreset::ALL {
+        assert_eq!(GravityPreset::matching(preset.gravity()), Some(preset));
+    }
+    // Slider rounding still shows the preset
+    assert_eq!(GravityPreset::matching([0.0, -9.8105, 0.0]), Some(GravityPreset::Earth));
+    assert_eq!(GravityPreset::matching([0.0, -9.0, 0.0]), None);
+    // Sideways gravity of Earth's strength is not Earth
+    assert_eq!(GravityPreset::matching([-9.81, 0.0, 0.0]), None);
+}
+
+#[test]
+fn test_presets_parse_by_name() {
+    let presets: Vec<GravityPreset> = serde_json::from_str(r#"["earth", "moon", "zeroG"]"#).unwrap();
+    assert_eq!(presets, GravityPreset::ALL);
+    assert!(serde_json::from_str::<GravityPreset>(r#""mars""#).is_err());
+}
@@ -0,0 +1,61 @@
+//! Integration tests for body groups
+
+use physics_core::groups::{self, Groups};
+use physics_core::rapier::prelude::*;
+
+#[test]
+fn test_masks_match_on_any_shared_bit() {
+    let debris_and_wave = Groups(0b0110);
+    assert!(debris_and_wave.matches(0b0010));
+    assert!(debris_and_wave.matches(0b1100));
+    assert!(!debris_and_wave.matches(0b1001));
+    assert!(!debris_and_wave.matches(0));
+}
+
+#[test]
+fn test_all_matches_bodies_outside_every_group() {
+    assert!(Groups(0).matches(groups::ALL));
+    assert!(!Groups(0).matches(groups::DEFAULT));
+    assert!(Groups::default().matches(groups::DEFAULT));
+}
+
+#[test]
+fn test_paused_body_holds_still_and_resumes_with_its_velocity() {
+    let mut bodies = RigidBodySet::new();
+    let mut colliders = ColliderSet::new();
+    let body = bodies.insert(RigidBodyBuilder::dynamic().linvel(vector![1.0, 2.0, 0.0]).build());
+    colliders.insert_with_parent(ColliderBuilder::cuboid(0.05, 0.05, 0.05).build(), body, &mut bodies);
+
+    let paused = groups::pause(&mut bodies[body]).expect("dynamic body pauses");
+    assert!(groups::pause(&mut bodies[body]).is_none(), "already paused");
+
+    let mut pipeline = PhysicsPipeline::new();
+    let mut islands = IslandManager::new();
+    let mut broad_phase = DefaultBroadPhase::new();
+    let mut narrow_phase = NarrowPhase::new();
+    let mut impulse_joints = ImpulseJointSet::new();
+    let mut multibody_joints = MultibodyJointSet::new();
+    let mut ccd_solver = CCDSolver::new();
+    for _ in 0..60 {
+        pipeline.step(
+            &vector![0.0, -9.81, 0.0],
+            &IntegrationParameters::default(),
+            &mut islands,
+            &mut broad_phase,
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut ccd_solver,
+            None,
+            &(),
+            &(),
+        );
+    }
+    assert_eq!(*bodies[body].translation(), Vector::zeros());
+
+    groups::resume(&mut bodies[body], paused);
+    assert!(bodies[body].is_dynamic());
+    assert_eq!(*bodies[body].linvel(), vector![1.0, 2.0, 0.0]);
+}
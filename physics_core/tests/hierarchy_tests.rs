@@ -0,0 +1,68 @@
+//! Integration tests for parent/child attachment
+
+use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_2;
+
+use physics_core::hierarchy::{self, Pose2D};
+
+fn assert_pose_near(actual: Pose2D, expected: Pose2D) {
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+    assert!(
+        close(actual.translation[0], expected.translation[0])
+            && close(actual.translation[1], expected.translation[1])
+            && close(actual.rotation, expected.rotation),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn test_offset_turns_with_the_parent() {
+    let parent = Pose2D { translation: [1.0, 2.0], rotation: FRAC_PI_2 };
+    let offset = Pose2D { translation: [0.5, 0.0], rotation: 0.25 };
+    assert_pose_near(parent.then(offset), Pose2D { translation: [1.0, 2.5], rotation: FRAC_PI_2 + 0.25 });
+    assert_pose_near(Pose2D::default().then(offset), offset);
+}
+
+#[test]
+fn test_resolve_composes_the_whole_chain() {
+    // 3 -> 2 -> 1 (root)
+    let links: HashMap<u32, (u32, Pose2D)> = [
+        (2, (1, Pose2D { translation: [1.0, 0.0], rotation: FRAC_PI_2 })),
+        (3, (2, Pose2D { translation: [1.0, 0.0], rotation: 0.0 })),
+    ]
+    .into();
+    let root = |e: u32| (e == 1).then_some(Pose2D { translation: [10.0, 0.0], rotation: 0.0 });
+    let link = |e: u32| links.get(&e).copied();
+
+    assert_pose_near(hierarchy::resolve(1, link, root).unwrap(), Pose2D { translation: [10.0, 0.0], rotation: 0.0 });
+    assert_pose_near(
+        hierarchy::resolve(3, link, root).unwrap(),
+        Pose2D { translation: [11.0, 1.0], rotation: FRAC_PI_2 },
+    );
+}
+
+#[test]
+fn test_resolve_fails_without_a_root_or_past_the_depth_limit() {
+    let orphan = |e: u32| (e == 1).then_some((7, Pose2D::default()));
+    assert!(hierarchy::resolve(1, orphan, |e| (e == 0).then_some(Pose2D::default())).is_none());
+
+    let chain = |depth: u32| move |e: u32| (e > 0 && e <= depth).then(|| (e - 1, Pose2D::default()));
+    let root = |e: u32| (e == 0).then_some(Pose2D::default());
+    let max = hierarchy::MAX_DEPTH as u32;
+    assert!(hierarchy::resolve(max, chain(max), root).is_some());
+    assert!(hierarchy::resolve(max + 1, chain(max + 1), root).is_none());
+}
+
+#[test]
+fn test_cycles_and_self_attachment_are_detected() {
+    // 2 -> 1, 3 -> 2
+    let parents: HashMap<u32, u32> = [(2, 1), (3, 2)].into();
+    let parent_of = |e: u32| parents.get(&e).copied();
+
+    assert!(hierarchy::creates_cycle(1, 1, parent_of));
+    assert!(hierarchy::creates_cycle(1, 3, parent_of));
+    assert!(!hierarchy::creates_cycle(3, 1, parent_of));
+    assert!(!hierarchy::creates_cycle(4, 3, parent_of));
+}
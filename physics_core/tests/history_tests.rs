@@ -0,0 +1,80 @@
+//! Integration tests for the undo/redo history
+
+use physics_core::history::{Edit, History, SpringEdit, MAX_STEPS};
+
+fn change(entity: u64, before: i32, after: i32) -> Edit<i32> {
+    Edit::Change { entity, before, after }
+}
+
+#[test]
+fn test_undo_and_redo_move_steps_between_stacks() {
+    let mut history = History::new();
+    history.record(Edit::Spawn { entity: 1, body: 10 }, false);
+    history.record(change(1, 10, 11), false);
+
+    let step = history.take_undo().unwrap();
+    assert_eq!(step, change(1, 10, 11));
+    history.undone(step);
+    let state = history.state();
+    assert_eq!((state.undo, state.redo, state.undo_label, state.redo_label), (1, 1, Some("spawn"), Some("change")));
+
+    let step = history.take_redo().unwrap();
+    history.redone(step);
+    assert_eq!((history.state().undo, history.state().redo), (2, 0));
+
+    // Recording something new drops what could be redone
+    let step = history.take_undo().unwrap();
+    history.undone(step);
+    history.record(change(1, 10, 12), false);
+    assert_eq!(history.take_redo(), None);
+}
+
+#[test]
+fn test_changes_merge_into_the_step_before() {
+    let mut history = History::new();
+    history.record(Edit::Spawn { entity: 1, body: 0 }, false);
+    history.record(change(1, 0, 5), true);
+    assert_eq!(history.take_undo(), Some(Edit::Spawn { entity: 1, body: 5 }));
+
+    history.record(change(2, 0, 1), false);
+    history.record(change(2, 1, 2), true);
+    history.record(change(2, 2, 3), true);
+    // Another body or an unmerged change starts a new step
+    history.record(change(3, 0, 1), true);
+    history.record(change(3, 1, 2), false);
+    assert_eq!(history.state().undo, 3);
+    history.take_undo();
+    history.take_undo();
+    assert_eq!(history.take_undo(), Some(change(2, 0, 3)));
+}
+
+#[test]
+fn test_renaming_follows_respawned_bodies_and_springs() {
+    let mut history = History::new();
+    let spring = SpringEdit { entity: 1, point: [0.0, 1.0], stiffness: 50.0, damping: 1.0 };
+    history.record(Edit::Spring { spring: 7, desc: spring }, false);
+    history.record(change(1, 0, 1), false);
+    history.record(Edit::Remove { entity: 1, body: 1 }, false);
+
+    // Undoing the removal spawned the body again as 4
+    let mut step = history.take_undo().unwrap();
+    step.rename_entity(1, 4);
+    history.rename_entity(1, 4);
+    history.undone(step);
+    assert_eq!(history.take_redo(), Some(Edit::Remove { entity: 4, body: 1 }));
+    assert_eq!(history.take_undo(), Some(change(4, 0, 1)));
+
+    history.rename_spring(7, 9);
+    assert_eq!(history.take_undo(), Some(Edit::Spring { spring: 9, desc: SpringEdit { entity: 4, ..spring } }));
+}
+
+#[test]
+fn test_oldest_steps_drop_off() {
+    let mut history = History::new();
+    for i in 0..MAX_STEPS as i32 + 5 {
+        history.record(change(1, i, i + 1), false);
+    }
+    assert_eq!(history.state().undo, MAX_STEPS);
+    history.clear();
+    assert!(history.state().to_json().contains("\"undo\":0"));
+}
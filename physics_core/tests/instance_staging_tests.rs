@@ -0,0 +1,83 @@
+//! Integration tests for the instance staging array
+
+use physics_core::instance_staging::{InstanceStaging, Upload};
+
+fn staging(n: u32) -> InstanceStaging<u32, f32> {
+    let mut staging = InstanceStaging::new();
+    staging.rebuild((0..n).map(|i| (i, i as f32)));
+    staging
+}
+
+#[test]
+fn test_rebuild_requests_full_upload_once() {
+    let mut staging = staging(10);
+    assert_eq!(staging.take_upload(), Upload::Full);
+    assert_eq!(staging.take_upload(), Upload::None);
+}
+
+#[test]
+fn test_unchanged_update_stays_clean() {
+    let mut staging = staging(10);
+    staging.take_upload();
+    assert!(staging.update(3, |v| *v = 3.0));
+    assert_eq!(staging.take_upload(), Upload::None);
+}
+
+#[test]
+fn test_dirty_slots_become_merged_ranges() {
+    let mut staging = staging(100);
+    staging.take_upload();
+    for key in [2, 4, 50, 90] {
+        staging.update(key, |v| *v += 1.0);
+    }
+    assert_eq!(staging.take_upload(), Upload::Ranges(vec![2..5, 50..51, 90..91]));
+    assert_eq!(staging.data()[50], 51.0);
+    assert_eq!(staging.take_upload(), Upload::None);
+}
+
+#[test]
+fn test_mostly_dirty_falls_back_to_full() {
+    let mut staging = staging(10);
+    staging.take_upload();
+    for key in 0..6 {
+        staging.update(key, |v| *v += 1.0);
+    }
+    assert_eq!(staging.take_upload(), Upload::Full);
+}
+
+#[test]
+fn test_unknown_key_and_mark_all_dirty() {
+    let mut staging = staging(4);
+    staging.take_upload();
+    assert!(!staging.update(99, |v| *v = 0.0));
+    assert_eq!(staging.slot(2), Some(2));
+    staging.mark_all_dirty();
+    assert_eq!(staging.take_upload(), Upload::Full);
+}
+
+#[test]
+fn test_undrawn_slots_stay_dirty_until_drawn() {
+    let mut staging = staging(100);
+    staging.take_upload();
+    for key in [10, 30, 60, 80] {
+        staging.update(key, |v| *v += 1.0);
+    }
+    // Slots 60 and 80 are off screen: only the others are written
+    assert_eq!(staging.take_upload_drawn(|slot| slot < 50), Upload::Ranges(vec![10..11, 30..31]));
+    assert_eq!(staging.take_upload_drawn(|slot| slot < 50), Upload::None);
+    // Once they are drawn again they go up, even without a new change
+    assert_eq!(staging.take_upload_drawn(|_| true), Upload::Ranges(vec![60..61, 80..81]));
+    assert_eq!(staging.take_upload(), Upload::None);
+}
+
+#[test]
+fn test_update_each_marks_only_changed_slots() {
+    let mut staging = staging(100);
+    staging.take_upload();
+    staging.update_each(|v| {
+        if *v == 5.0 || *v == 40.0 {
+            *v = -1.0;
+        }
+    });
+    assert_eq!(staging.take_upload(), Upload::Ranges(vec![5..6, 40..41]));
+}
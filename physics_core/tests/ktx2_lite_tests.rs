@@ -0,0 +1,132 @@
+//! Integration tests for KTX2 reading, block decoding and format selection
+
+use physics_core::ktx2_lite::{decode, parse, variant_names, TextureCompression, TextureFormat};
+
+/// KTX2 file with `levels` stored back to back after the level index
+fn ktx2(vk_format: u32, width: u32, height: u32, levels: &[&[u8]]) -> Vec<u8> {
+    let mut bytes = vec![0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+    for value in [vk_format, 1, width, height, 0, 0, 1, levels.len() as u32, 0] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    // Empty data format descriptor, key/value and supercompression data
+    bytes.resize(80, 0);
+    let mut offset = 80 + 24 * levels.len();
+    for level in levels {
+        for value in [offset, level.len(), level.len()] {
+            bytes.extend_from_slice(&(value as u64).to_le_bytes());
+        }
+        offset += level.len();
+    }
+    for level in levels {
+        bytes.extend_from_slice(level);
+    }
+    bytes
+}
+
+/// BC1 block: red for index 0, blue for index 1
+fn bc1_block(indices: u32) -> [u8; 8] {
+    let mut block = [0; 8];
+    block[..2].copy_from_slice(&0xF800u16.to_le_bytes());
+    block[2..4].copy_from_slice(&0x001Fu16.to_le_bytes());
+    block[4..].copy_from_slice(&indices.to_le_bytes());
+    block
+}
+
+#[test]
+fn test_ktx2_levels_are_read_and_checked() {
+    let (base, mip) = ([7u8; 16], [9u8; 8]);
+    // 8x4 BC1 with a 4x2 mip, which still takes a whole block
+    let texture = parse(&ktx2(134, 8, 4, &[&base, &mip])).unwrap();
+    assert_eq!((texture.format, texture.srgb, texture.width, texture.height), (TextureFormat::Bc1, true, 8, 4));
+    assert_eq!(texture.levels, vec![base.to_vec(), mip.to_vec()]);
+    assert_eq!(texture.level_size(1), (4, 2));
+
+    assert!(parse(b"\x89PNG\r\n\x1a\n").is_err());
+    assert!(parse(&ktx2(134, 8, 4, &[&base[..8]])).unwrap_err().contains("expected 16"));
+    // Basis Universal files have no Vulkan format
+    assert!(parse(&ktx2(0, 8, 4, &[&base])).is_err());
+    let mut zstd = ktx2(134, 8, 4, &[&base]);
+    zstd[44] = 2;
+    assert!(parse(&zstd).unwrap_err().contains("supercompression"));
+    let mut truncated = ktx2(134, 8, 4, &[&base]);
+    truncated.truncate(truncated.len() - 1);
+    assert!(parse(&truncated).is_err());
+    // A 4x4 image has three levels at most
+    assert!(parse(&ktx2(37, 4, 4, &[&[0; 64], &[0; 16], &[0; 4], &[0; 4]])).is_err());
+}
+
+#[test]
+fn test_bc_blocks_decode_to_rgba() {
+    // Pixel 1 is blue and pixel 2 the two-thirds mix, the rest red
+    let rgba = decode(TextureFormat::Bc1, 4, 4, &bc1_block(0b10_01_00)).unwrap();
+    assert_eq!(&rgba[..12], &[255, 0, 0, 255, 0, 0, 255, 255, 170, 0, 85, 255]);
+    assert_eq!(&rgba[60..], &[255, 0, 0, 255]);
+
+    // With the smaller color first index 3 is transparent
+    let mut block = bc1_block(3);
+    block.swap(0, 2);
+    block.swap(1, 3);
+    assert_eq!(&decode(TextureFormat::Bc1, 4, 4, &block).unwrap()[..4], &[0, 0, 0, 0]);
+
+    // BC3: alpha 255 and 0 with indices 0, 1 and 7 for the first pixels
+    let mut block = [0u8; 16];
+    block[..3].copy_from_slice(&[255, 0, 0b11_001_000]);
+    block[3] = 0b1;
+    block[8..].copy_from_slice(&bc1_block(0));
+    let rgba = decode(TextureFormat::Bc3, 4, 4, &block).unwrap();
+    assert_eq!([rgba[3], rgba[7], rgba[11], rgba[15]], [255, 0, 36, 255]);
+    assert_eq!(&rgba[..3], &[255, 0, 0]);
+
+    // Images that are not a multiple of the block size are cropped
+    assert_eq!(decode(TextureFormat::Bc1, 2, 3, &bc1_block(0)).unwrap().len(), 2 * 3 * 4);
+    assert!(decode(TextureFormat::Bc1, 8, 4, &bc1_block(0)).is_err());
+    assert!(decode(TextureFormat::Astc4x4, 4, 4, &[0; 16]).is_err());
+}
+
+#[test]
+fn test_etc2_blocks_decode_to_rgba() {
+    // Individual mode: left half red 15 and right half 0, table 0, every
+    // pixel +2
+    let block = [0xF0, 0, 0, 0, 0, 0, 0, 0];
+    let rgba = decode(TextureFormat::Etc2Rgb8, 4, 4, &block).unwrap();
+    assert_eq!(&rgba[..4], &[255, 2, 2, 255]);
+    assert_eq!(&rgba[8..12], &[2, 2, 2, 255]);
+
+    // Flipped differential: top red 16, bottom 16 - 1, index 3 (-8) on the
+    // first pixel only
+    let block = [0x87, 0, 0, 0b11, 0, 0x01, 0, 0x01];
+    let rgba = decode(TextureFormat::Etc2Rgb8, 4, 4, &block).unwrap();
+    assert_eq!(&rgba[..4], &[124, 0, 0, 255]);
+    assert_eq!(&rgba[4..8], &[134, 2, 2, 255]);
+    assert_eq!(&rgba[48..52], &[125, 2, 2, 255]);
+
+    // Blue overflowing makes a planar block: red 130 at the origin fading
+    // to black at the other edges
+    let block = [0x40, 0, 0x04, 0b10, 0, 0, 0, 0];
+    let rgba = decode(TextureFormat::Etc2Rgb8, 4, 4, &block).unwrap();
+    assert_eq!(&rgba[..8], &[130, 0, 0, 255, 98, 0, 0, 255]);
+    assert_eq!(&rgba[60..], &[0, 0, 0, 255]);
+
+    // EAC alpha: base 128, multiplier 1, table 0, index 0 is -3
+    let mut block = [0u8; 16];
+    block[..2].copy_from_slice(&[128, 0x10]);
+    let rgba = decode(TextureFormat::Etc2Rgba8, 4, 4, &block).unwrap();
+    assert!(rgba.chunks(4).all(|pixel| pixel[3] == 125));
+}
+
+#[test]
+fn test_best_supported_variant_comes_first() {
+    let phone = TextureCompression { astc: true, etc2: true, bc: false };
+    assert_eq!(
+        variant_names("textures/ball.png", phone),
+        vec!["textures/ball.astc.ktx2", "textures/ball.etc2.ktx2", "textures/ball.png"]
+    );
+    let desktop = TextureCompression { bc: true, ..Default::default() };
+    assert_eq!(variant_names("ball.PNG", desktop), vec!["ball.bc.ktx2", "ball.PNG"]);
+    assert_eq!(variant_names("textures/ball.png", TextureCompression::default()), vec!["textures/ball.png"]);
+    assert_eq!(variant_names("textures/ball.etc2.ktx2", phone), vec!["textures/ball.etc2.ktx2"]);
+
+    assert!(phone.supports(TextureFormat::Etc2Rgba8) && !phone.supports(TextureFormat::Bc7));
+    assert!(TextureCompression::default().supports(TextureFormat::Rgba8));
+    assert!(TextureFormat::Bc3.decodable() && !TextureFormat::Bc7.decodable());
+}
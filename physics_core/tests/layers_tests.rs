@@ -0,0 +1,66 @@
+//! Integration tests for draw layers and parallax
+
+use bevy_ecs::entity::Entity;
+use physics_core::layers::{sort_into_batches, DrawKey, Layer, LayerKind, LayerParallax, LayerSplit};
+use physics_core::material::DrawBatch;
+
+fn key(kind: LayerKind, order: i32, material_id: u32, entity: u32) -> DrawKey {
+    DrawKey { layer: Layer::new(kind, order), material_id, entity: Entity::from_raw(entity) }
+}
+
+#[test]
+fn test_layers_sort_before_materials_and_split_batches() {
+    let mut items = vec![
+        (key(LayerKind::Foreground, 0, 1, 0), 'f'),
+        (key(LayerKind::World, 0, 1, 5), 'c'),
+        (key(LayerKind::Background, 2, 1, 1), 'b'),
+        (key(LayerKind::World, 0, 1, 3), 'w'),
+        (key(LayerKind::Background, -1, 1, 2), 's'),
+        (key(LayerKind::World, 0, 0, 4), 'd'),
+    ];
+    let (batches, split) = sort_into_batches(&mut items);
+
+    // Within a layer and material the entity decides, whatever order the
+    // query returned them in
+    let order: String = items.iter().map(|(_, c)| *c).collect();
+    assert_eq!(order, "sbdwcf");
+    // One material across four layers is still four batches
+    let ranges: Vec<_> = batches.iter().map(|batch| (batch.material_id, batch.instance_range())).collect();
+    assert_eq!(ranges, vec![(1, 0..1), (1, 1..2), (0, 2..3), (1, 3..5), (1, 5..6)]);
+    assert_eq!(split, LayerSplit { world: 2, foreground: 5 });
+    assert_eq!(split.ranges(&batches), [0..2, 2..4, 4..5]);
+
+    let (batches, split) = sort_into_batches::<char>(&mut []);
+    assert!(batches.is_empty());
+    assert_eq!(split.ranges(&batches), [0..0, 0..0, 0..0]);
+}
+
+#[test]
+fn test_culled_runs_fall_in_their_layer() {
+    let split = LayerSplit { world: 2, foreground: 8 };
+    let run = |first_instance, instance_count| DrawBatch { material_id: 0, first_instance, instance_count };
+    // Runs left after culling: the start of the world layer and the whole
+    // foreground are out of sight
+    let batches = [run(0, 1), run(3, 2), run(6, 1)];
+    assert_eq!(split.ranges(&batches), [0..1, 1..3, 3..3]);
+}
+
+#[test]
+fn test_parallax_defaults_to_moving_with_the_world() {
+    let sky = Layer::new(LayerKind::Background, 0);
+    let mut parallax = LayerParallax::default();
+    assert_eq!(parallax.factor(sky), [1.0, 1.0]);
+
+    parallax.set(sky, [0.25, 0.0]).unwrap();
+    assert_eq!(parallax.factor(sky), [0.25, 0.0]);
+    assert_eq!(parallax.factor(Layer::new(LayerKind::Background, 1)), [1.0, 1.0]);
+    assert!(parallax.set(sky, [f32::NAN, 1.0]).is_err());
+    assert_eq!(parallax.factor(sky), [0.25, 0.0]);
+
+    parallax.set(sky, [1.0, 1.0]).unwrap();
+    assert_eq!(parallax.factor(sky), [1.0, 1.0]);
+
+    assert_eq!(LayerKind::from_i32(2), Some(LayerKind::Foreground));
+    assert_eq!(LayerKind::from_i32(3), None);
+    assert_eq!(Layer::default(), Layer::new(LayerKind::World, 0));
+}
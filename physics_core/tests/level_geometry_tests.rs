@@ -0,0 +1,80 @@
+//! Integration tests for static level geometry
+
+use physics_core::level_geometry::{self, LevelDesc, LevelShape, TileRect};
+use physics_core::tilemap::Tilemap;
+
+fn tiles(tile_size: f32, columns: u32, cells: Vec<u8>) -> LevelShape {
+    LevelShape::Tiles(Tilemap {
+        origin: [-1.0, 1.0],
+        tile_size,
+        columns,
+        cells,
+        material_id: 0,
+        atlas_columns: 1,
+        atlas_rows: 1,
+    })
+}
+
+#[test]
+fn test_solid_block_merges_into_one_rect() {
+    let rects = level_geometry::merge_tiles(3, &[1, 1, 1, 1, 1, 1]);
+    assert_eq!(rects, vec![TileRect { column: 0, row: 0, width: 3, height: 2 }]);
+}
+
+#[test]
+fn test_merged_rects_cover_every_solid_cell_once() {
+    #[rustfmt::skip]
+    let cells = [
+        1, 1, 0, 0, 1,
+        1, 1, 0, 1, 1,
+        0, 0, 0, 1, 1,
+        1, 1, 1, 1, 1,
+    ];
+    let rects = level_geometry::merge_tiles(5, &cells);
+    let mut covered = [0u8; 20];
+    for rect in &rects {
+        for row in rect.row..rect.row + rect.height {
+            for column in rect.column..rect.column + rect.width {
+                covered[(row * 5 + column) as usize] += 1;
+            }
+        }
+    }
+    let solid: Vec<u8> = cells.iter().map(|&c| (c != 0) as u8).collect();
+    assert_eq!(covered.to_vec(), solid);
+    assert!(rects.len() < cells.iter().filter(|&&c| c != 0).count());
+}
+
+#[test]
+fn test_empty_grid_and_zero_columns_give_no_rects() {
+    assert!(level_geometry::merge_tiles(4, &[0; 8]).is_empty());
+    assert!(level_geometry::merge_tiles(0, &[1, 1]).is_empty());
+}
+
+#[test]
+fn test_each_shape_becomes_one_collider() {
+    let desc = LevelDesc {
+        shapes: vec![
+            LevelShape::Polyline { points: vec![[-1.0, 0.0], [0.0, -0.5], [1.0, 0.0]], closed: false },
+            LevelShape::Heightfield { origin_x: -1.0, spacing: 0.1, heights: vec![-0.9; 21] },
+            tiles(0.1, 2, vec![1, 0, 1, 1]),
+        ],
+        keep_walls: true,
+        ..Default::default()
+    };
+    assert_eq!(level_geometry::build_colliders(&desc).unwrap().len(), 3);
+}
+
+#[test]
+fn test_invalid_shapes_are_rejected() {
+    let one_point = LevelDesc {
+        shapes: vec![LevelShape::Polyline { points: vec![[0.0, 0.0]], closed: false }],
+        ..Default::default()
+    };
+    assert!(level_geometry::build_colliders(&one_point).is_err());
+
+    let ragged = LevelDesc {
+        shapes: vec![tiles(0.1, 3, vec![1; 4])],
+        ..Default::default()
+    };
+    assert!(level_geometry::build_colliders(&ragged).is_err());
+}
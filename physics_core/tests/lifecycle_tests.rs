@@ -0,0 +1,34 @@
+//! Integration tests for app pause and resume
+
+use physics_core::lifecycle::Lifecycle;
+
+#[test]
+fn test_frozen_pause_skips_updates_and_resume_drops_the_gap() {
+    let mut lifecycle = Lifecycle::default();
+    assert_eq!(lifecycle.frame_dt(0.016), Some(0.016));
+    assert!(lifecycle.pause(true));
+    assert!(lifecycle.is_paused());
+    assert_eq!(lifecycle.frame_dt(0.016), None);
+
+    assert!(lifecycle.resume());
+    // The host's clock ran on through the pause
+    assert_eq!(lifecycle.frame_dt(30.0), Some(0.0));
+    assert_eq!(lifecycle.frame_dt(0.016), Some(0.016));
+}
+
+#[test]
+fn test_unfrozen_pause_keeps_stepping() {
+    let mut lifecycle = Lifecycle::default();
+    assert!(lifecycle.pause(false));
+    assert_eq!(lifecycle.frame_dt(0.016), Some(0.016));
+    // Pausing again only changes whether physics freezes
+    assert!(!lifecycle.pause(true));
+    assert_eq!(lifecycle.frame_dt(0.016), None);
+}
+
+#[test]
+fn test_resume_without_pause_changes_nothing() {
+    let mut lifecycle = Lifecycle::default();
+    assert!(!lifecycle.resume());
+    assert_eq!(lifecycle.frame_dt(0.5), Some(0.5));
+}
@@ -0,0 +1,36 @@
+//! Integration tests for timed and out-of-bounds despawning
+
+use physics_core::lifetime::{DespawnBounds, Lifetime};
+
+#[test]
+fn test_lifetime_expires_after_its_duration() {
+    let mut lifetime = Lifetime::new(0.5).unwrap();
+    assert!(!lifetime.tick(0.2));
+    assert!(!lifetime.tick(0.2));
+    assert!(lifetime.tick(0.2));
+}
+
+#[test]
+fn test_non_positive_or_infinite_lifetimes_are_rejected() {
+    assert_eq!(Lifetime::new(0.0), None);
+    assert_eq!(Lifetime::new(-1.0), None);
+    assert_eq!(Lifetime::new(f32::INFINITY), None);
+    assert_eq!(Lifetime::new(f32::NAN), None);
+}
+
+#[test]
+fn test_bounds_accept_corners_in_any_order() {
+    let bounds = DespawnBounds::new([2.0, -1.0], [-2.0, 1.0]).unwrap();
+    assert_eq!(bounds.min, [-2.0, -1.0]);
+    assert_eq!(bounds.max, [2.0, 1.0]);
+    assert!(bounds.contains(0.0, 0.0));
+    assert!(bounds.contains(2.0, 1.0));
+    assert!(!bounds.contains(0.0, -1.5));
+    assert!(!bounds.contains(2.5, 0.0));
+}
+
+#[test]
+fn test_empty_or_non_finite_bounds_are_rejected() {
+    assert_eq!(DespawnBounds::new([0.0, 0.0], [0.0, 1.0]), None);
+    assert_eq!(DespawnBounds::new([0.0, 0.0], [f32::NAN, 1.0]), None);
+}
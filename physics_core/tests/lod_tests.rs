@@ -0,0 +1,33 @@
+//! Integration tests for far-body level of detail
+
+use physics_core::lod::{is_far, LodConfig, HYSTERESIS};
+use physics_core::scenes::SceneDesc;
+
+#[test]
+fn test_far_bodies_need_to_come_back_inside() {
+    let threshold = Some(10.0);
+    assert!(!is_far(threshold, 9.9, false));
+    assert!(is_far(threshold, 10.1, false));
+    // Just inside the threshold stays far until past the hysteresis band
+    let band = 10.0 * (1.0 - HYSTERESIS);
+    assert!(is_far(threshold, 9.9, true));
+    assert!(is_far(threshold, band + 0.01, true));
+    assert!(!is_far(threshold, band - 0.01, true));
+    // No threshold turns LOD off, even for bodies marked far
+    assert!(!is_far(None, 1e9, true));
+}
+
+#[test]
+fn test_scenes_carry_lod_thresholds() {
+    let desc: SceneDesc = serde_json::from_str(r#"{"lod": {"renderDistance": 12, "physicsDistance": 20}}"#).unwrap();
+    let lod = desc.lod.unwrap();
+    assert_eq!((lod.render_distance, lod.physics_distance), (Some(12.0), Some(20.0)));
+    assert_eq!(lod.sleep_bias, LodConfig::default().sleep_bias);
+    assert!(desc.validate().is_ok());
+    assert!(SceneDesc::default().lod.is_none());
+
+    for bad in [r#"{"lod": {"renderDistance": 0}}"#, r#"{"lod": {"physicsDistance": -3}}"#, r#"{"lod": {"sleepBias": 0.5}}"#] {
+        let desc: SceneDesc = serde_json::from_str(bad).unwrap();
+        assert!(desc.validate().is_err(), "{}", bad);
+    }
+}
@@ -0,0 +1,60 @@
+//! Integration tests for the in-app log console's capture
+
+use log::{Level, LevelFilter};
+use physics_core::log_console::{self, level_filter_from_u32, LogBuffer, LogLine};
+
+fn line(level: Level, message: &str) -> LogLine {
+    LogLine { level, target: "test".into(), message: message.into() }
+}
+
+#[test]
+fn test_buffer_keeps_the_newest_lines() {
+    let mut buffer = LogBuffer::new(3);
+    for i in 0..5 {
+        buffer.push(line(Level::Info, &i.to_string()));
+    }
+    assert_eq!(buffer.len(), 3);
+    let kept: Vec<_> = buffer.filtered(LevelFilter::Trace).map(|l| l.message.as_str()).collect();
+    assert_eq!(kept, ["2", "3", "4"]);
+}
+
+#[test]
+fn test_filter_keeps_levels_at_least_as_severe() {
+    let mut buffer = LogBuffer::new(10);
+    for level in [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace] {
+        buffer.push(line(level, level.as_str()));
+    }
+    let levels = |max| buffer.filtered(max).map(|l| l.level).collect::<Vec<_>>();
+    assert_eq!(levels(LevelFilter::Warn), [Level::Error, Level::Warn]);
+    assert_eq!(levels(LevelFilter::Trace).len(), 5);
+    assert!(levels(LevelFilter::Off).is_empty());
+
+    assert_eq!(level_filter_from_u32(1), LevelFilter::Error);
+    assert_eq!(level_filter_from_u32(3), LevelFilter::Info);
+    assert_eq!(level_filter_from_u32(0), LevelFilter::Trace);
+}
+
+#[test]
+fn test_installed_logger_captures_records() {
+    assert!(log_console::install(None, LevelFilter::Debug));
+    // Only one logger per process
+    assert!(!log_console::install(None, LevelFilter::Debug));
+
+    log::warn!("low on bodies: {}", 3);
+    log::debug!("stepping");
+    log::trace!("not kept, above the max level");
+
+    let lines = log_console::recent(LevelFilter::Trace);
+    let messages: Vec<_> = lines.iter().map(|l| l.message.as_str()).collect();
+    assert!(messages.contains(&"low on bodies: 3"));
+    assert!(messages.contains(&"stepping"));
+    assert!(!messages.iter().any(|m| m.starts_with("not kept")));
+
+    let json: serde_json::Value = serde_json::from_str(&log_console::recent_json(LevelFilter::Warn)).unwrap();
+    let warning = json.as_array().unwrap().iter().find(|l| l["message"] == "low on bodies: 3").unwrap();
+    assert_eq!(warning["level"], "WARN");
+    assert!(log_console::to_text(&lines).contains("WARN  log_console_tests: low on bodies: 3\n"));
+
+    log_console::clear();
+    assert!(log_console::recent(LevelFilter::Trace).is_empty());
+}
@@ -0,0 +1,49 @@
+//! Integration tests for the runtime log filter
+
+use log::{Level, LevelFilter};
+use physics_core::log_console;
+use physics_core::log_filter::LogFilter;
+
+#[test]
+fn test_longest_directive_wins_for_a_target_and_its_children() {
+    let filter = LogFilter::parse("physics=debug, render=warn, physics_core::scenes=trace, error").unwrap();
+    assert_eq!(filter.default, LevelFilter::Error);
+    assert_eq!(filter.level_for("physics"), LevelFilter::Debug);
+    assert_eq!(filter.level_for("physics::solver"), LevelFilter::Debug);
+    // A longer name sharing the prefix is a different target
+    assert_eq!(filter.level_for("physics_core"), LevelFilter::Error);
+    assert_eq!(filter.level_for("physics_core::scenes"), LevelFilter::Trace);
+    assert!(filter.enabled("render", Level::Warn));
+    assert!(!filter.enabled("render", Level::Info));
+    assert_eq!(filter.max_level(), LevelFilter::Trace);
+}
+
+#[test]
+fn test_parse_rejects_bad_directives() {
+    assert_eq!(LogFilter::parse("").unwrap(), LogFilter::default());
+    assert_eq!(LogFilter::parse("gpu=OFF").unwrap().level_for("gpu"), LevelFilter::Off);
+    assert!(LogFilter::parse("physics=loud").is_err());
+    assert!(LogFilter::parse("=debug").is_err());
+    assert!(LogFilter::parse("verbose").is_err());
+}
+
+#[test]
+fn test_filter_replaces_the_installed_levels() {
+    log_console::install(None, LevelFilter::Info);
+    log_console::set_filter(Some(LogFilter::parse("physics=debug,render=warn").unwrap()));
+    assert_eq!(log::max_level(), LevelFilter::Debug);
+    log::debug!(target: "physics", "step 1");
+    log::info!(target: "render", "frame 1");
+    log::debug!(target: "init", "device ready");
+    assert!(log_console::allows("physics", Level::Debug));
+    assert!(!log_console::allows("render", Level::Info));
+
+    let messages: Vec<_> = log_console::recent(LevelFilter::Trace).into_iter().map(|l| l.message).collect();
+    assert!(messages.contains(&"step 1".to_string()));
+    assert!(!messages.contains(&"frame 1".to_string()));
+    assert!(!messages.contains(&"device ready".to_string()));
+
+    log_console::set_filter(None);
+    assert_eq!(log::max_level(), LevelFilter::Info);
+    assert!(log_console::allows("render", Level::Info));
+}
@@ -0,0 +1,45 @@
+//! Integration tests for material draw batching
+
+use physics_core::material::{sort_into_batches, DrawBatch};
+
+#[test]
+fn test_empty_input_has_no_batches() {
+    let mut items: Vec<(u32, char)> = Vec::new();
+    assert!(sort_into_batches(&mut items).is_empty());
+}
+
+#[test]
+fn test_single_material_is_one_batch() {
+    let mut items = vec![(0, 'a'), (0, 'b'), (0, 'c')];
+    let batches = sort_into_batches(&mut items);
+    assert_eq!(
+        batches,
+        vec![DrawBatch {
+            material_id: 0,
+            first_instance: 0,
+            instance_count: 3
+        }]
+    );
+}
+
+#[test]
+fn test_interleaved_materials_are_grouped() {
+    let mut items = vec![(2, 'a'), (0, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+    let batches = sort_into_batches(&mut items);
+
+    assert_eq!(batches.len(), 3);
+    assert_eq!(batches[0].material_id, 0);
+    assert_eq!(batches[0].instance_range(), 0..2);
+    assert_eq!(batches[1].material_id, 1);
+    assert_eq!(batches[1].instance_range(), 2..3);
+    assert_eq!(batches[2].material_id, 2);
+    assert_eq!(batches[2].instance_range(), 3..5);
+}
+
+#[test]
+fn test_sort_is_stable_within_batch() {
+    let mut items = vec![(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd')];
+    sort_into_batches(&mut items);
+    let order: Vec<char> = items.iter().map(|(_, c)| *c).collect();
+    assert_eq!(order, vec!['b', 'd', 'a', 'c']);
+}
@@ -0,0 +1,89 @@
+//! Integration tests for minimap layout
+
+use physics_core::minimap::{content_bounds, ortho, Blip, Corner, MinimapConfig};
+
+fn blip(x: f32, y: f32) -> Blip {
+    Blip { position: [x, y], size: 0.1, rotation: 0.0, color: [1.0, 0.0, 0.0, 1.0] }
+}
+
+fn config() -> MinimapConfig {
+    MinimapConfig { width: 200, height: 100, margin: 10, border_width: 2, ..MinimapConfig::default() }
+}
+
+#[test]
+fn test_json_defaults() {
+    let config: MinimapConfig = serde_json::from_str(r#"{"corner": "bottomLeft", "visibleGroups": 2}"#).unwrap();
+    assert_eq!(config.corner, Corner::BottomLeft);
+    assert_eq!(config.visible_groups, 2);
+    assert_eq!(config.width, MinimapConfig::default().width);
+    assert!(config.is_valid());
+    let hidden: Option<MinimapConfig> = serde_json::from_str("null").unwrap();
+    assert!(hidden.is_none());
+}
+
+#[test]
+fn test_validation() {
+    assert!(!MinimapConfig { width: 4, border_width: 2, ..config() }.is_valid());
+    assert!(!MinimapConfig { bounds: Some([1.0, 0.0, 1.0, 1.0]), ..config() }.is_valid());
+    assert!(!MinimapConfig { background: [f32::NAN, 0.0, 0.0, 1.0], ..config() }.is_valid());
+    assert!(!MinimapConfig { min_blip_size: -1.0, ..config() }.is_valid());
+    assert!(MinimapConfig { bounds: Some([-5.0, -1.0, 5.0, 1.0]), ..config() }.is_valid());
+}
+
+#[test]
+fn test_viewport_corners() {
+    let corner = |corner| MinimapConfig { corner, ..config() }.viewport(800, 600);
+    assert_eq!(corner(Corner::TopLeft), Some([10, 10, 200, 100]));
+    assert_eq!(corner(Corner::TopRight), Some([590, 10, 200, 100]));
+    assert_eq!(corner(Corner::BottomLeft), Some([10, 490, 200, 100]));
+    assert_eq!(corner(Corner::BottomRight), Some([590, 490, 200, 100]));
+    // Too small a surface hides the map rather than clipping it
+    assert_eq!(config().viewport(215, 600), None);
+    assert_eq!(config().viewport(220, 120), Some([10, 10, 200, 100]));
+}
+
+#[test]
+fn test_world_rect_fits_content_inside_border() {
+    let config = config();
+    // Wide content fills the inner width
+    let rect = config.world_rect([-4.0, -1.0, 4.0, 1.0]);
+    let world_per_px = (rect[2] - rect[0]) / 200.0;
+    assert!((world_per_px - 8.0 / 196.0).abs() < 1e-6);
+    assert!(((rect[3] - rect[1]) / 100.0 - world_per_px).abs() < 1e-6);
+    assert!(((rect[0] + rect[2]) * 0.5).abs() < 1e-6);
+
+    // A single point still shows a sensible area around it
+    let rect = config.world_rect([3.0, 3.0, 3.0, 3.0]);
+    assert!(rect[3] - rect[1] > 0.9);
+    assert!(((rect[1] + rect[3]) * 0.5 - 3.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_vertices_and_bounds() {
+    let config = MinimapConfig { min_blip_size: 4.0, ..config() };
+    let blips = [blip(0.0, 0.0), blip(2.0, -1.0)];
+    let bounds = content_bounds(&blips).unwrap();
+    assert!(bounds[0] < 0.0 && bounds[2] > 2.1 && bounds[1] < -1.0 && bounds[3] > 0.1);
+    assert_eq!(content_bounds(&[]), None);
+
+    let rect = config.world_rect(bounds);
+    let vertices = config.vertices(rect, &blips);
+    assert_eq!(vertices.len(), 6 * 4);
+    assert_eq!(vertices[0].color, config.border_color);
+    assert_eq!(vertices[6].color, config.background);
+    // Blips are grown to the minimum pixel size around their quad's center
+    let world_per_px = (rect[2] - rect[0]) / 200.0;
+    let blip_vertices = &vertices[12..18];
+    let width = blip_vertices[1].position[0] - blip_vertices[0].position[0];
+    assert!((width - (4.0 * world_per_px).max(0.1)).abs() < 1e-5);
+    assert!((blip_vertices[0].position[0] + width * 0.5 - 0.05).abs() < 1e-5);
+}
+
+#[test]
+fn test_ortho_maps_rect_to_clip_space() {
+    let m = ortho([-2.0, 1.0, 6.0, 5.0]);
+    let clip = |x: f32, y: f32| [m[0][0] * x + m[3][0], m[1][1] * y + m[3][1]];
+    assert_eq!(clip(-2.0, 1.0), [-1.0, -1.0]);
+    assert_eq!(clip(6.0, 5.0), [1.0, 1.0]);
+    assert_eq!(clip(2.0, 3.0), [0.0, 0.0]);
+}
@@ -0,0 +1,98 @@
+//! Integration tests for snapshot deltas
+
+use physics_core::netcode::{BodyUpdate, Delta, NetBody, NetCorrection, NetHistory, FIELD_ALL, FIELD_LINVEL, HISTORY, SNAP_DISTANCE};
+
+fn body(entity_id: u64, x: f32) -> NetBody {
+    NetBody { entity_id, position: [x, 1.0], rotation: 0.25, linvel: [0.0, -1.0], angvel: 0.5 }
+}
+
+#[test]
+fn test_full_snapshot_round_trip() {
+    let bodies = [body(1, 0.0), body(2, 3.0)];
+    let delta = Delta::between(7, None, &bodies);
+    assert_eq!(delta.baseline, 0);
+    assert!(delta.changed.iter().all(|update| update.fields() == FIELD_ALL));
+
+    let bytes = delta.encode();
+    assert_eq!(bytes.len(), 29 + 2 * (9 + 24));
+    let decoded = Delta::decode(&bytes).unwrap();
+    assert_eq!(decoded, delta);
+    assert_eq!(decoded.rebuild(&[]), bodies);
+}
+
+#[test]
+fn test_delta_sends_only_changes() {
+    let old = [body(1, 0.0), body(2, 3.0), body(3, 5.0)];
+    let mut moved = body(2, 3.0);
+    moved.linvel = [2.0, -1.0];
+    let current = [body(1, 0.0), moved, body(4, 9.0)];
+
+    let delta = Delta::between(8, Some((7, &old)), &current);
+    assert_eq!(delta.baseline, 7);
+    assert_eq!(delta.removed, vec![3]);
+    assert_eq!(delta.changed.len(), 2);
+    assert_eq!(delta.changed[0], BodyUpdate { entity_id: 2, linvel: Some([2.0, -1.0]), ..BodyUpdate::default() });
+    assert_eq!(delta.changed[0].fields(), FIELD_LINVEL);
+    // Bodies new since the baseline are sent whole
+    assert_eq!(delta.changed[1].fields(), FIELD_ALL);
+
+    let bytes = delta.encode();
+    assert!(bytes.len() < Delta::between(8, None, &current).encode().len());
+    assert_eq!(Delta::decode(&bytes).unwrap().rebuild(&old), current);
+}
+
+#[test]
+fn test_decode_rejects_bad_input() {
+    let bytes = Delta::between(1, None, &[body(1, 0.0)]).encode();
+    assert!(Delta::decode(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Delta::decode(&[bytes.as_slice(), &[0]].concat()).is_err());
+    assert!(Delta::decode(b"nope").is_err());
+
+    let mut version = bytes.clone();
+    version[4] = 99;
+    assert!(Delta::decode(&version).is_err());
+
+    let mut nan = bytes.clone();
+    nan[29 + 9..29 + 13].copy_from_slice(&f32::NAN.to_le_bytes());
+    assert!(Delta::decode(&nan).is_err());
+
+    // A huge count must fail on the missing records, not allocate for them
+    let mut count = bytes;
+    count[21..25].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(Delta::decode(&count).is_err());
+}
+
+#[test]
+fn test_history_drops_oldest_and_rewound_ticks() {
+    let mut history = NetHistory::default();
+    for tick in 1..=HISTORY as u64 + 2 {
+        history.record(tick, vec![body(tick, 0.0)]);
+    }
+    assert_eq!(history.len(), HISTORY);
+    assert!(history.get(2).is_none());
+    assert_eq!(history.get(3), Some(&[body(3, 0.0)][..]));
+
+    history.record(10, Vec::new());
+    assert_eq!(history.latest_tick(), Some(10));
+    assert!(history.get(11).is_none());
+    assert_eq!(history.get(10), Some(&[][..]));
+}
+
+#[test]
+fn test_small_errors_ease_out() {
+    let current = body(1, 0.0);
+    let far = body(1, SNAP_DISTANCE * 2.0);
+    assert!(NetCorrection::between(&current, &far).is_none());
+
+    let mut target = body(1, 0.1);
+    target.rotation = current.rotation + std::f32::consts::TAU - 0.05;
+    let mut correction = NetCorrection::between(&current, &target).unwrap();
+    // The rotation error wraps to the short way round
+    assert!((correction.rotation + 0.05).abs() < 1e-5);
+
+    let mut moved = 0.0;
+    while let Some((offset, _)) = correction.step() {
+        moved += offset[0];
+    }
+    assert!((moved - 0.1).abs() < 1e-3);
+}
@@ -0,0 +1,285 @@
+//! Integration tests for the physics step on the headless test world
+
+use physics_core::groups;
+use physics_core::test_support::{TestWorld, WALL_POSITION};
+use physics_core::time_control::STEP_DT;
+use physics_core::units::WorldScale;
+use proptest::prelude::*;
+
+const HALF_EXTENT: f32 = 0.05;
+
+/// Up to 20 boxes on a 0.3 grid, each moved by less than the gap so none
+/// start overlapping, with a velocity each
+fn scene() -> impl Strategy<Value = Vec<([f32; 2], [f32; 2])>> {
+    let cells: Vec<[f32; 2]> = (0..4)
+        .flat_map(|y| (0..5).map(move |x| [x as f32 * 0.3 - 0.6, y as f32 * 0.3 - 0.45]))
+        .collect();
+    proptest::sample::subsequence(cells, 1..=20).prop_flat_map(|cells| {
+        let n = cells.len();
+        (
+            Just(cells),
+            prop::collection::vec([-0.04f32..0.04, -0.04f32..0.04], n),
+            prop::collection::vec([-8.0f32..8.0, -8.0f32..8.0], n),
+        )
+            .prop_map(|(cells, jitter, velocities)| {
+                cells
+                    .iter()
+                    .zip(&jitter)
+                    .zip(velocities)
+                    .map(|((cell, jitter), velocity)| ([cell[0] + jitter[0], cell[1] + jitter[1]], velocity))
+                    .collect()
+            })
+    })
+}
+
+fn world_for(scene: &[([f32; 2], [f32; 2])]) -> TestWorld {
+    let positions: Vec<[f32; 2]> = scene.iter().map(|(position, _)| *position).collect();
+    let mut world = TestWorld::new(&positions, HALF_EXTENT);
+    for (i, (_, velocity)) in scene.iter().enumerate() {
+        world.set_velocity(i, *velocity);
+    }
+    world
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn prop_no_body_escapes_the_walls(scene in scene(), gravity in [-20.0f32..20.0, -20.0f32..20.0]) {
+        let mut world = world_for(&scene);
+        world.set_gravity(gravity);
+        for _ in 0..240 {
+            world.step(STEP_DT);
+        }
+        for (i, body) in world.bodies().iter().enumerate() {
+            let [x, y] = body.position;
+            prop_assert!(x.abs() < WALL_POSITION && y.abs() < WALL_POSITION, "body {} left the box at ({}, {})", i, x, y);
+        }
+    }
+
+    #[test]
+    fn prop_energy_does_not_grow_below_full_restitution(scene in scene(), restitution in 0.0f32..0.9) {
+        let mut world = world_for(&scene);
+        world.set_restitution(restitution);
+        let start = world.energy();
+        let mut previous = start;
+        for _ in 0..8 {
+            for _ in 0..30 {
+                world.step(STEP_DT);
+            }
+            // The solver is not exactly conservative from one step to the next
+            let energy = world.energy();
+            prop_assert!(energy <= previous + 0.02 * previous.abs().max(1.0), "energy rose from {} to {}", previous, energy);
+            previous = energy;
+        }
+        prop_assert!(previous < start, "energy {} after 4s, {} at the start", previous, start);
+    }
+
+    #[test]
+    fn prop_clock_adds_up_the_scaled_frame_times(
+        frames in prop::collection::vec(prop_oneof![4 => 0.0f32..0.1, 1 => Just(-0.05f32), 1 => Just(f32::NAN)], 1..40),
+        time_scale in 0.0f32..4.0,
+    ) {
+        let mut world = TestWorld::new(&[[0.0, 0.0]], HALF_EXTENT);
+        world.set_time_scale(time_scale);
+        let mut elapsed = 0.0;
+        for &real_dt in &frames {
+            prop_assert_eq!(world.frame(real_dt), 1);
+            let time = world.time();
+            // NaN and negative frame times count as empty frames
+            let real_dt = if real_dt.is_finite() { real_dt.max(0.0) } else { 0.0 };
+            let expected = real_dt * time_scale;
+            prop_assert!((time.delta - expected).abs() < 1e-6);
+            elapsed += expected;
+        }
+        let time = world.time();
+        prop_assert!((time.elapsed - elapsed).abs() < 1e-3);
+        prop_assert_eq!((time.frames, time.steps), (frames.len() as u64, frames.len() as u64));
+        prop_assert!(world.bodies().iter().all(|body| body.position.iter().all(|v| v.is_finite())));
+    }
+}
+
+#[test]
+fn test_paused_frame_runs_only_queued_steps() {
+    let mut world = TestWorld::new(&[[0.0, 0.5]], HALF_EXTENT);
+    world.set_paused(true);
+    assert_eq!(world.frame(0.1), 0);
+    assert_eq!(world.bodies()[0].position, [0.0, 0.5]);
+
+    world.queue_steps(3);
+    assert_eq!(world.frame(0.1), 3);
+    let time = world.time();
+    assert_eq!(time.steps, 3);
+    assert!((time.elapsed - 3.0 * STEP_DT).abs() < 1e-6);
+    assert!((time.real_elapsed - 0.2).abs() < 1e-6);
+    assert!(world.bodies()[0].position[1] < 0.5);
+
+    // Queued steps are used up by the frame that runs them
+    assert_eq!(world.frame(0.1), 0);
+}
+
+#[test]
+fn test_paused_simulation_keeps_animating() {
+    let mut world = TestWorld::new(&[[0.0, 0.5]], HALF_EXTENT);
+    world.set_time_scale(0.5);
+    world.set_paused(true);
+    assert_eq!(world.frame(0.1), 0);
+    let time = world.time();
+    assert!((time.animation_delta - 0.05).abs() < 1e-6);
+    assert_eq!((time.delta, time.steps), (0.0, 0));
+    assert_eq!(world.bodies()[0].position, [0.0, 0.5]);
+
+    world.set_animation_paused(true);
+    world.frame(0.1);
+    assert_eq!(world.time().animation_delta, 0.0);
+}
+
+#[test]
+fn test_paused_animation_does_not_stop_the_simulation() {
+    let mut world = TestWorld::new(&[[0.0, 0.5]], HALF_EXTENT);
+    world.set_animation_paused(true);
+    assert_eq!(world.frame(STEP_DT), 1);
+    let time = world.time();
+    assert_eq!((time.delta, time.animation_delta), (STEP_DT, 0.0));
+    assert!(world.bodies()[0].position[1] < 0.5);
+}
+
+#[test]
+fn test_queued_steps_run_before_the_frame_step_and_ignore_time_scale() {
+    let mut world = TestWorld::new(&[[0.0, 0.0]], HALF_EXTENT);
+    world.set_time_scale(0.5);
+    world.queue_steps(2);
+    assert_eq!(world.frame(0.02), 3);
+    let time = world.time();
+    // The last step run is the frame's own, scaled
+    assert!((time.delta - 0.01).abs() < 1e-6);
+    assert!((time.elapsed - (2.0 * STEP_DT + 0.01)).abs() < 1e-6);
+}
+
+#[test]
+fn test_body_at_rest_on_the_floor_stays_there() {
+    let mut world = TestWorld::new(&[[0.0, -1.09 + HALF_EXTENT]], HALF_EXTENT);
+    world.set_restitution(0.0);
+    for _ in 0..120 {
+        world.frame(STEP_DT);
+    }
+    let body = world.bodies()[0];
+    assert!((body.position[1] - (-1.09 + HALF_EXTENT)).abs() < 0.01, "body moved to {:?}", body.position);
+    assert!(body.velocity[1].abs() < 0.05);
+}
+
+#[test]
+fn test_ids_from_before_a_reset_stay_dead() {
+    let positions = [[0.0, 0.0], [0.3, 0.0]];
+    let world = TestWorld::new(&positions, HALF_EXTENT);
+    let old = world.entity_ids();
+    assert!(old.iter().all(|&id| world.is_alive(id)));
+
+    // The new scene reuses the same entity slots with newer generations
+    let world = world.reset(&positions, HALF_EXTENT);
+    let new = world.entity_ids();
+    assert_eq!(world.resets(), 1);
+    assert!(new.iter().all(|&id| world.is_alive(id)));
+    assert!(old.iter().all(|&id| !world.is_alive(id) && !new.contains(&id)));
+    assert!(!world.is_alive(0));
+}
+
+#[test]
+fn test_walls_follow_the_world_scale() {
+    // At 0.1 NDC per meter the floor's centre line is 11 m down
+    let scale = WorldScale::new(0.1).unwrap();
+    let half_extent = scale.box_half_extent();
+    let mut world = TestWorld::with_scale(&[[0.0, 5.0]], half_extent, scale);
+    world.set_restitution(0.0);
+    for _ in 0..600 {
+        world.step(STEP_DT);
+    }
+    let body = world.bodies()[0];
+    // Resting on the floor, whose top is 0.1 m above its centre line
+    let rest = -scale.wall_position() + 0.1 + half_extent;
+    assert!((body.position[1] - rest).abs() < 0.05, "body came to rest at {:?}", body.position);
+}
+
+#[test]
+fn test_bodies_never_sleep_while_sleeping_is_off() {
+    let resting = [[0.0, -1.09 + HALF_EXTENT]];
+    let mut asleep = TestWorld::new(&resting, HALF_EXTENT);
+    let mut awake = TestWorld::new(&resting, HALF_EXTENT);
+    asleep.set_restitution(0.0);
+    awake.set_restitution(0.0);
+    awake.set_sleep_enabled(false);
+    for _ in 0..600 {
+        asleep.frame(STEP_DT);
+        awake.frame(STEP_DT);
+    }
+    let id = asleep.entity_ids()[0];
+    assert!(asleep.body_info(id).unwrap().sleeping);
+    let id = awake.entity_ids()[0];
+    assert!(!awake.body_info(id).unwrap().sleeping);
+
+    // Turning it off wakes bodies that are already asleep
+    asleep.set_sleep_enabled(false);
+    let id = asleep.entity_ids()[0];
+    assert!(!asleep.body_info(id).unwrap().sleeping);
+}
+
+#[test]
+fn test_sleep_and_wake_flip_the_body_info() {
+    let mut world = TestWorld::new(&[[0.0, 0.5]], HALF_EXTENT);
+    let id = world.entity_ids()[0];
+    let info = world.body_info(id).unwrap();
+    assert_eq!((info.x, info.y, info.sleeping), (0.0, 0.5, false));
+
+    assert!(world.sleep_body(id));
+    assert!(world.body_info(id).unwrap().sleeping);
+    assert!(world.wake_body(id));
+    assert!(!world.body_info(id).unwrap().sleeping);
+}
+
+#[test]
+fn test_sleep_calls_reject_unknown_ids() {
+    let world = TestWorld::new(&[[0.0, 0.0]], HALF_EXTENT);
+    let stale = world.entity_ids()[0];
+    let mut world = world.reset(&[[0.0, 0.0]], HALF_EXTENT);
+    for id in [0, stale, u64::MAX] {
+        assert!(!world.sleep_body(id));
+        assert!(!world.wake_body(id));
+        assert!(world.body_info(id).is_none());
+    }
+}
+
+fn sorted(mut ids: Vec<u64>) -> Vec<u64> {
+    ids.sort_unstable();
+    ids
+}
+
+#[test]
+fn test_area_queries_return_the_overlapping_bodies() {
+    let mut world = TestWorld::new(&[[-0.5, 0.0], [0.0, 0.0], [0.5, 0.5]], HALF_EXTENT);
+    let ids = world.entity_ids();
+
+    let hits = sorted(vec![ids[0], ids[1]]);
+    assert_eq!(sorted(world.query_aabb([-0.6, -0.1], [0.1, 0.1], groups::ALL)), hits);
+    // Corners may come in either order
+    assert_eq!(sorted(world.query_aabb([0.1, 0.1], [-0.6, -0.1], groups::ALL)), hits);
+    assert!(world.query_aabb([0.7, -0.5], [0.9, -0.3], groups::ALL).is_empty());
+
+    assert_eq!(world.query_circle([0.5, 0.5], 0.1, groups::ALL), vec![ids[2]]);
+    assert!(world.query_circle([0.0, 0.25], 0.1, groups::ALL).is_empty());
+
+    // Bodies outside the mask's groups are skipped
+    assert!(world.query_aabb([-0.6, -0.1], [0.1, 0.1], 0).is_empty());
+    // Invalid shapes find nothing
+    assert!(world.query_circle([0.0, 0.0], -1.0, groups::ALL).is_empty());
+    assert!(world.query_aabb([f32::NAN, 0.0], [1.0, 1.0], groups::ALL).is_empty());
+}
+
+#[test]
+fn test_area_queries_leave_out_the_walls() {
+    let mut world = TestWorld::new(&[[0.0, 0.0], [0.3, -0.9]], HALF_EXTENT);
+    let ids = world.entity_ids();
+    // The walls have colliders but no entity
+    assert_eq!(sorted(world.query_aabb([-2.0, -2.0], [2.0, 2.0], groups::ALL)), sorted(ids.clone()));
+    assert!(world.query_circle([-WALL_POSITION, 0.0], 0.2, groups::ALL).is_empty());
+    assert_eq!(world.query_circle([0.3, -WALL_POSITION], 0.3, groups::ALL), vec![ids[1]]);
+}
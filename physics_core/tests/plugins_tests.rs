@@ -0,0 +1,44 @@
+//! Integration tests for the hook registry
+
+use std::sync::Arc;
+
+use physics_core::plugins::{HookPoint, HookRegistry};
+
+#[test]
+fn test_ids_are_unique_and_nonzero() {
+    let mut registry = HookRegistry::new();
+    let a = registry.register(HookPoint::PrePhysics, Arc::new(|_, _| {}));
+    let b = registry.register(HookPoint::PrePhysics, Arc::new(|_, _| {}));
+    assert_ne!(a, 0);
+    assert_ne!(a, b);
+    assert_eq!(registry.len(), 2);
+}
+
+#[test]
+fn test_hooks_filtered_by_point() {
+    let mut registry = HookRegistry::new();
+    registry.register(HookPoint::PrePhysics, Arc::new(|_, _| {}));
+    registry.register(HookPoint::PreRender, Arc::new(|_, _| {}));
+    registry.register(HookPoint::PrePhysics, Arc::new(|_, _| {}));
+    assert_eq!(registry.hooks_for(HookPoint::PrePhysics).len(), 2);
+    assert_eq!(registry.hooks_for(HookPoint::PostPhysics).len(), 0);
+}
+
+#[test]
+fn test_unregister() {
+    let mut registry = HookRegistry::new();
+    let id = registry.register(HookPoint::PostPhysics, Arc::new(|_, _| {}));
+    assert!(registry.unregister(id));
+    assert!(!registry.unregister(id));
+    assert!(registry.is_empty());
+    // Ids are not reused after removal
+    assert_ne!(registry.register(HookPoint::PostPhysics, Arc::new(|_, _| {})), id);
+}
+
+#[test]
+fn test_hook_point_codes_round_trip() {
+    for point in [HookPoint::PrePhysics, HookPoint::PostPhysics, HookPoint::PreRender, HookPoint::Reset] {
+        assert_eq!(HookPoint::from_u32(point.as_u32()), Some(point));
+    }
+    assert_eq!(HookPoint::from_u32(4), None);
+}
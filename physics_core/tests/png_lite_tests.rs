@@ -0,0 +1,112 @@
+//! Integration tests for PNG decoding
+
+use physics_core::capture::encode_png;
+use physics_core::png_lite::decode;
+
+/// PNG around a zlib stream; the decoder does not check CRCs, so they are left 0
+fn png(width: u32, height: u32, bit_depth: u8, color_type: u8, extra: &[(&[u8; 4], &[u8])], zlib: &[u8]) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+    let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+    let chunks = [(b"IHDR", header.as_slice())].into_iter().chain(extra.iter().copied()).chain([(b"IDAT", zlib), (b"IEND", &[][..])]);
+    for (kind, data) in chunks {
+        bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(kind);
+        bytes.extend_from_slice(data);
+        bytes.extend_from_slice(&[0; 4]);
+    }
+    bytes
+}
+
+/// Scanlines in one stored deflate block
+fn stored(raw: &[u8]) -> Vec<u8> {
+    let len = raw.len() as u16;
+    let mut zlib = vec![0x78, 0x01, 1];
+    zlib.extend_from_slice(&len.to_le_bytes());
+    zlib.extend_from_slice(&(!len).to_le_bytes());
+    zlib.extend_from_slice(raw);
+    zlib.extend_from_slice(&[0; 4]);
+    zlib
+}
+
+#[test]
+fn test_round_trips_captured_frames() {
+    let rgba: Vec<u8> = (0..3 * 2 * 4).map(|i| (i * 11) as u8).collect();
+    let image = decode(&encode_png(3, 2, &rgba)).unwrap();
+    assert_eq!((image.width, image.height), (3, 2));
+    assert_eq!(image.rgba, rgba);
+}
+
+#[test]
+fn test_inflates_fixed_and_dynamic_huffman_blocks() {
+    // zlib level 9 of 8x8 grayscale scanlines, compressed with fixed codes
+    let fixed = [
+        0x78, 0xda, 0x63, 0x60, 0xb0, 0x81, 0xc1, 0x0a, 0x08, 0x64, 0xa8, 0xd8, 0x02, 0x81, 0x0c, 0x30, 0xc8, 0x40, 0x84, 0x1a, 0x00, 0xe6,
+        0xe6, 0x16, 0x81,
+    ];
+    let image = decode(&png(8, 8, 8, 0, &[], &fixed)).unwrap();
+    for (i, pixel) in image.rgba.chunks(4).enumerate() {
+        let (x, y) = (i % 8, i / 8);
+        let gray = ((x * x + y) % 4 * 60) as u8;
+        assert_eq!(pixel, [gray, gray, gray, 255]);
+    }
+
+    // ... and scanlines from a small alphabet, compressed with dynamic codes
+    let dynamic = [
+        0x78, 0xda, 0x2d, 0x8a, 0x49, 0x0e, 0x00, 0x00, 0x0c, 0x01, 0xe7, 0xe6, 0xdb, 0x7e, 0xae, 0xda, 0xd4, 0x92, 0x20, 0x20, 0x28, 0x18,
+        0xd0, 0xd3, 0x44, 0x5d, 0x17, 0x0d, 0x57, 0x62, 0x5d, 0xd7, 0xe3, 0xb2, 0xd3, 0x53, 0xa5, 0xf5, 0x00, 0x06, 0xce, 0x0e, 0x81,
+    ];
+    let image = decode(&png(8, 8, 8, 0, &[], &dynamic)).unwrap();
+    let alphabet = [0, 0, 0, 0, 255, 255, 128, 7];
+    for (i, pixel) in image.rgba.chunks(4).enumerate() {
+        let (x, y) = (i % 8, i / 8);
+        assert_eq!(pixel[0], alphabet[(x * 5 + y * 3 + x * y * 7) % 8]);
+    }
+}
+
+#[test]
+fn test_undoes_row_filters() {
+    // Two-pixel RGB rows: none, sub, up, average, paeth
+    let raw = [
+        0, 10, 20, 30, 40, 50, 60, //
+        1, 5, 5, 5, 1, 1, 1, //
+        2, 1, 2, 3, 4, 5, 6, //
+        3, 10, 10, 10, 10, 10, 10, //
+        4, 1, 1, 1, 1, 1, 1,
+    ];
+    let image = decode(&png(2, 5, 8, 2, &[], &stored(&raw))).unwrap();
+    let rows: Vec<[u8; 6]> = image.rgba.chunks(8).map(|p| [p[0], p[1], p[2], p[4], p[5], p[6]]).collect();
+    assert_eq!(rows[0], [10, 20, 30, 40, 50, 60]);
+    assert_eq!(rows[1], [5, 5, 5, 6, 6, 6]);
+    assert_eq!(rows[2], [6, 7, 8, 10, 11, 12]);
+    // The first pixel has nothing to its left, so it averages up with 0
+    assert_eq!(rows[3], [13, 13, 14, 21, 22, 23]);
+    assert_eq!(rows[4], [14, 14, 15, 22, 23, 24]);
+}
+
+#[test]
+fn test_expands_palette_gray_alpha_and_16_bit() {
+    let palette: &[u8] = &[255, 0, 0, 0, 0, 255];
+    let image = decode(&png(2, 1, 8, 3, &[(b"PLTE", palette), (b"tRNS", &[128])], &stored(&[0, 0, 1]))).unwrap();
+    assert_eq!(image.rgba, [255, 0, 0, 128, 0, 0, 255, 255]);
+
+    let image = decode(&png(1, 1, 8, 4, &[], &stored(&[0, 90, 200]))).unwrap();
+    assert_eq!(image.rgba, [90, 90, 90, 200]);
+
+    let image = decode(&png(1, 1, 16, 2, &[], &stored(&[0, 1, 2, 3, 4, 5, 6]))).unwrap();
+    assert_eq!(image.rgba, [1, 3, 5, 255]);
+}
+
+#[test]
+fn test_rejects_unsupported_and_broken_files() {
+    assert!(decode(b"GIF89a").is_err());
+    assert!(decode(&png(2, 2, 8, 2, &[], &[0x78, 0x01, 1, 2])).is_err());
+    // Palette image without a palette
+    assert!(decode(&png(1, 1, 8, 3, &[], &stored(&[0, 0]))).is_err());
+    assert!(decode(&png(1, 1, 4, 0, &[], &stored(&[0, 0]))).is_err());
+    assert!(decode(&png(0, 1, 8, 0, &[], &stored(&[0]))).is_err());
+    // Too few scanlines
+    assert!(decode(&png(1, 2, 8, 0, &[], &stored(&[0, 9]))).is_err());
+}
@@ -0,0 +1,53 @@
+//! Integration tests for springs to world points
+
+use physics_core::point_springs;
+use physics_core::rapier::prelude::*;
+
+#[test]
+fn test_spring_ids_round_trip_and_skip_zero() {
+    let handle = ImpulseJointHandle::from_raw_parts(0, 0);
+    assert_ne!(point_springs::spring_id(handle), 0);
+    assert_eq!(point_springs::spring_handle(point_springs::spring_id(handle)), Some(handle));
+
+    let handle = ImpulseJointHandle::from_raw_parts(41, 7);
+    assert_eq!(point_springs::spring_handle(point_springs::spring_id(handle)), Some(handle));
+    assert_eq!(point_springs::spring_handle(0), None);
+}
+
+#[test]
+fn test_spring_pulls_body_to_point() {
+    let mut bodies = RigidBodySet::new();
+    let mut colliders = ColliderSet::new();
+    let mut impulse_joints = ImpulseJointSet::new();
+    let anchor = bodies.insert(RigidBodyBuilder::fixed().build());
+    let body = bodies.insert(RigidBodyBuilder::dynamic().build());
+    colliders.insert_with_parent(ColliderBuilder::cuboid(0.05, 0.05, 0.05).build(), body, &mut bodies);
+    impulse_joints.insert(anchor, body, point_springs::spring_joint([0.5, 0.3], 5.0, 0.5), true);
+
+    let mut pipeline = PhysicsPipeline::new();
+    let mut islands = IslandManager::new();
+    let mut broad_phase = DefaultBroadPhase::new();
+    let mut narrow_phase = NarrowPhase::new();
+    let mut multibody_joints = MultibodyJointSet::new();
+    let mut ccd_solver = CCDSolver::new();
+    for _ in 0..600 {
+        pipeline.step(
+            &Vector::zeros(),
+            &IntegrationParameters::default(),
+            &mut islands,
+            &mut broad_phase,
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut ccd_solver,
+            None,
+            &(),
+            &(),
+        );
+    }
+
+    let position = bodies[body].translation();
+    assert!((position.x - 0.5).abs() < 0.02 && (position.y - 0.3).abs() < 0.02, "ended at {position:?}");
+}
@@ -0,0 +1,75 @@
+//! Integration tests for convex decomposition of polygon outlines
+
+use physics_core::level_geometry::{self, LevelDesc};
+use physics_core::polygon::{decompose, MAX_POINTS};
+
+fn area(points: &[[f32; 2]]) -> f32 {
+    let n = points.len();
+    (0..n).map(|i| points[i][0] * points[(i + 1) % n][1] - points[(i + 1) % n][0] * points[i][1]).sum::<f32>() / 2.0
+}
+
+fn assert_convex_ccw(piece: &[[f32; 2]]) {
+    let n = piece.len();
+    assert!(n >= 3);
+    for i in 0..n {
+        let (a, b, c) = (piece[i], piece[(i + 1) % n], piece[(i + 2) % n]);
+        let turn = (b[0] - a[0]) * (c[1] - b[1]) - (b[1] - a[1]) * (c[0] - b[0]);
+        assert!(turn > 0.0, "piece {:?} is not convex at {:?}", piece, b);
+    }
+}
+
+/// L-shaped outline, clockwise, with a repeated point and one in the middle of an edge
+const L_SHAPE: [[f32; 2]; 8] = [[0.0, 0.0], [0.0, 2.0], [1.0, 2.0], [1.0, 1.0], [2.0, 1.0], [2.0, 0.0], [1.0, 0.0], [1.0, 0.0]];
+
+#[test]
+fn test_convex_outline_stays_whole() {
+    let square = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+    let pieces = decompose(&square).unwrap();
+    assert_eq!(pieces.len(), 1);
+    assert_eq!(pieces[0].len(), 4);
+    assert_convex_ccw(&pieces[0]);
+}
+
+#[test]
+fn test_concave_outline_splits_into_convex_pieces() {
+    let pieces = decompose(&L_SHAPE).unwrap();
+    assert_eq!(pieces.len(), 2);
+    pieces.iter().for_each(|piece| assert_convex_ccw(piece));
+    let total: f32 = pieces.iter().map(|piece| area(piece)).sum();
+    assert!((total - 3.0).abs() < 1e-5);
+
+    // A comb with four teeth needs at least one piece per tooth
+    let mut comb = vec![[0.0, 0.0], [8.0, 0.0]];
+    for tooth in (0..4).rev() {
+        let x = tooth as f32 * 2.0;
+        comb.extend([[x + 1.0, 2.0], [x + 1.0, 3.0], [x, 3.0]]);
+        if tooth > 0 {
+            comb.push([x, 2.0]);
+        }
+    }
+    comb.insert(2, [8.0, 2.0]);
+    let pieces = decompose(&comb).unwrap();
+    assert!(pieces.len() >= 4 && pieces.len() <= 8, "{} pieces", pieces.len());
+    pieces.iter().for_each(|piece| assert_convex_ccw(piece));
+    let total: f32 = pieces.iter().map(|piece| area(piece)).sum();
+    assert!((total - area(&comb)).abs() < 1e-4);
+}
+
+#[test]
+fn test_invalid_outlines_are_rejected() {
+    assert!(decompose(&[[0.0, 0.0], [1.0, 0.0]]).is_err());
+    assert!(decompose(&[[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]]).is_err());
+    assert!(decompose(&[[0.0, 0.0], [1.0, f32::NAN], [0.0, 1.0]]).is_err());
+    assert!(decompose(&vec![[0.0, 0.0]; MAX_POINTS + 1]).is_err());
+    // Bow tie
+    assert!(decompose(&[[0.0, 0.0], [1.0, 1.0], [1.0, 0.0], [0.0, 1.0]]).is_err());
+}
+
+#[test]
+fn test_polygon_from_level_json() {
+    let desc: LevelDesc = serde_json::from_str(
+        r#"{"shapes": [{"type": "polygon", "points": [[0, 0], [0, 2], [1, 2], [1, 1], [2, 1], [2, 0]]}]}"#,
+    )
+    .unwrap();
+    assert_eq!(level_geometry::build_colliders(&desc).unwrap().len(), 1);
+}
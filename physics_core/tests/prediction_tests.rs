@@ -0,0 +1,60 @@
+//! Integration tests for the prediction frame buffer
+
+use physics_core::events::{GameEvent, InputEventType};
+use physics_core::netcode::NetBody;
+use physics_core::prediction::{Frame, Prediction, MAX_WINDOW};
+
+fn body(entity_id: u64, x: f32) -> NetBody {
+    NetBody { entity_id, position: [x, 0.0], rotation: 0.0, linvel: [1.0, 0.0], angvel: 0.0 }
+}
+
+fn frame(tick: u64, x: f32) -> Frame {
+    Frame { tick, dt: 1.0 / 60.0, inputs: vec![GameEvent::new_key(InputEventType::KeyDown, tick as i32)], predicted: vec![body(1, x)] }
+}
+
+#[test]
+fn test_invalid_settings() {
+    assert!(Prediction::new(MAX_WINDOW + 1, 0.01).is_none());
+    assert!(Prediction::new(10, -1.0).is_none());
+    assert!(Prediction::new(10, f32::NAN).is_none());
+    assert!(!Prediction::new(0, 0.01).unwrap().is_enabled());
+}
+
+#[test]
+fn test_window_keeps_recent_frames() {
+    let mut prediction = Prediction::new(3, 0.01).unwrap();
+    for tick in 1..=5 {
+        prediction.record(frame(tick, tick as f32));
+    }
+    assert!(prediction.frame(2).is_none());
+    let replay: Vec<u64> = prediction.frames_after(3).map(|frame| frame.tick).collect();
+    assert_eq!(replay, vec![4, 5]);
+
+    // Re-running from tick 3 replaces the frames that were rolled back
+    prediction.record(frame(4, 9.0));
+    assert!(prediction.frame(5).is_none());
+    assert_eq!(prediction.frame(4).unwrap().predicted, vec![body(1, 9.0)]);
+    assert_eq!(prediction.frame(4).unwrap().inputs[0].key_code, Some(4));
+}
+
+#[test]
+fn test_disabled_prediction_records_nothing() {
+    let mut prediction = Prediction::new(0, 0.01).unwrap();
+    prediction.record(frame(1, 0.0));
+    assert!(prediction.frame(1).is_none());
+}
+
+#[test]
+fn test_misprediction_uses_tolerance() {
+    let mut prediction = Prediction::new(8, 0.05).unwrap();
+    prediction.record(frame(1, 1.0));
+    assert_eq!(prediction.mispredicted(2, &[body(1, 1.0)]), None);
+    assert_eq!(prediction.mispredicted(1, &[body(1, 1.04)]), Some(false));
+    assert_eq!(prediction.mispredicted(1, &[body(1, 1.1)]), Some(true));
+
+    let mut spinning = body(1, 1.0);
+    spinning.angvel = 0.5;
+    assert_eq!(prediction.mispredicted(1, &[spinning]), Some(true));
+    // Bodies the authority does not list are not held against the prediction
+    assert_eq!(prediction.mispredicted(1, &[body(2, 5.0)]), Some(false));
+}
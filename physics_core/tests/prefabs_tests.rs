@@ -0,0 +1,88 @@
+//! Integration tests for prefab descriptions and their expansion into bodies
+
+use physics_core::hierarchy::{Pose2D, MAX_DEPTH};
+use physics_core::prefabs::{PrefabDesc, PrefabLibrary, PrefabPart, PrefabShape, MAX_PREFABS};
+
+fn part(prefab: &str, x: f32, y: f32) -> PrefabPart {
+    PrefabPart { prefab: prefab.to_string(), x, y, angle: 0.0 }
+}
+
+fn with_parts(parts: Vec<PrefabPart>) -> PrefabDesc {
+    PrefabDesc { parts, ..Default::default() }
+}
+
+#[test]
+fn test_prefab_json_with_defaults() {
+    let desc: PrefabDesc = serde_json::from_str(
+        r#"{"shape": {"type": "polygon", "points": [[0, 0], [0.1, 0], [0, 0.1]]}, "bodyType": "fixed",
+            "clip": {"firstFrame": 4, "frameCount": 4}, "movement": {"type": "linear", "velocity": [1, 0]},
+            "parts": [{"prefab": "wheel", "x": 0.1}]}"#,
+    )
+    .unwrap();
+    assert!(matches!(desc.shape, PrefabShape::Polygon { ref points } if points.len() == 3));
+    assert_eq!(desc.groups, 1);
+    assert_eq!(desc.clip.unwrap().frame_duration, 0.1);
+    assert_eq!(desc.parts, vec![part("wheel", 0.1, 0.0)]);
+    desc.validate().unwrap();
+
+    let plain: PrefabDesc = serde_json::from_str("{}").unwrap();
+    assert_eq!(plain.shape, PrefabShape::Box { half_extent: 0.05 });
+}
+
+#[test]
+fn test_invalid_prefabs_are_turned_down() {
+    let mut library = PrefabLibrary::default();
+    let flat = PrefabDesc { shape: PrefabShape::Box { half_extent: 0.0 }, ..Default::default() };
+    assert!(library.insert("flat", flat).is_err());
+    let line = PrefabDesc { shape: PrefabShape::Polygon { points: vec![[0.0, 0.0], [1.0, 0.0]] }, ..Default::default() };
+    assert!(library.insert("line", line).is_err());
+    assert!(library.insert("", PrefabDesc::default()).is_err());
+    assert!(library.insert("lost", with_parts(vec![part("wheel", f32::NAN, 0.0)])).is_err());
+
+    for i in 0..MAX_PREFABS {
+        library.insert(&i.to_string(), PrefabDesc::default()).unwrap();
+    }
+    assert!(library.insert("one more", PrefabDesc::default()).is_err());
+    // Replacing a prefab needs no room
+    library.insert("0", with_parts(vec![part("1", 0.0, 0.0)])).unwrap();
+}
+
+#[test]
+fn test_parts_spawn_posed_and_attached_to_their_prefab() {
+    let mut library = PrefabLibrary::default();
+    library.insert("cart", with_parts(vec![part("wheel", 0.2, 0.0), part("rider", 0.0, 0.1)])).unwrap();
+    library.insert("rider", with_parts(vec![part("hat", 0.0, 0.05)])).unwrap();
+    library.insert("wheel", PrefabDesc::default()).unwrap();
+    library.insert("hat", PrefabDesc::default()).unwrap();
+
+    let spawns = library.expand("cart", Pose2D { translation: [1.0, 1.0], rotation: std::f32::consts::FRAC_PI_2 }).unwrap();
+    assert_eq!(spawns.len(), 4);
+    assert_eq!(spawns[0].parent, None);
+    // The cart is turned a quarter, so the wheel to its right sits above it
+    let [x, y] = spawns[1].pose.translation;
+    assert!((x - 1.0).abs() < 1e-6 && (y - 1.2).abs() < 1e-6);
+    assert_eq!(spawns[1].parent.map(|(parent, offset)| (parent, offset.translation)), Some((0, [0.2, 0.0])));
+    // The hat rides on the rider, not the cart
+    assert_eq!(spawns[3].parent.map(|(parent, _)| parent), Some(2));
+    let [x, y] = spawns[3].pose.translation;
+    assert!((x - 0.85).abs() < 1e-6 && (y - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_loops_and_unknown_parts_fail_to_expand() {
+    let mut library = PrefabLibrary::default();
+    library.insert("a", with_parts(vec![part("b", 0.0, 0.0)])).unwrap();
+    assert!(library.expand("a", Pose2D::default()).unwrap_err().contains("unknown prefab 'b'"));
+    library.insert("b", with_parts(vec![part("a", 0.0, 0.0)])).unwrap();
+    assert!(library.expand("a", Pose2D::default()).is_err());
+
+    // A chain as deep as attachments follow is fine
+    let mut chain = PrefabLibrary::default();
+    for depth in 0..MAX_DEPTH {
+        chain.insert(&depth.to_string(), with_parts(vec![part(&(depth + 1).to_string(), 0.0, 0.1)])).unwrap();
+    }
+    chain.insert(&MAX_DEPTH.to_string(), PrefabDesc::default()).unwrap();
+    assert_eq!(chain.expand("0", Pose2D::default()).unwrap().len(), MAX_DEPTH + 1);
+    assert!(library.remove("a"));
+    assert_eq!(library.names(), vec!["b"]);
+}
@@ -0,0 +1,50 @@
+//! Integration tests for the projectile pool
+
+use physics_core::projectiles::ProjectilePool;
+
+#[test]
+fn test_fire_takes_free_slots_until_exhausted() {
+    let mut pool = ProjectilePool::new([10u64, 11, 12]);
+    assert_eq!(pool.fire(1.0), Some(10));
+    assert_eq!(pool.fire(1.0), Some(11));
+    assert_eq!(pool.fire(1.0), Some(12));
+    assert_eq!(pool.fire(1.0), None);
+
+    let stats = pool.stats();
+    assert_eq!((stats.capacity, stats.active, stats.peak_active), (3, 3, 3));
+    assert_eq!((stats.fired, stats.dropped), (3, 1));
+    assert_eq!(pool.parked().count(), 0);
+}
+
+#[test]
+fn test_projectiles_expire_after_their_ttl() {
+    let mut pool = ProjectilePool::new([1u64, 2]);
+    pool.fire(0.1);
+    pool.fire(0.25);
+    assert!(pool.tick(0.05).is_empty());
+    assert_eq!(pool.tick(0.1), vec![1]);
+    assert_eq!(pool.tick(0.1), vec![2]);
+
+    let stats = pool.stats();
+    assert_eq!((stats.active, stats.expired, stats.peak_active), (0, 2, 2));
+}
+
+#[test]
+fn test_zero_ttl_lives_until_hit() {
+    let mut pool = ProjectilePool::new([7u64]);
+    pool.fire(0.0);
+    assert!(pool.tick(1.0e6).is_empty());
+    assert!(pool.hit(7));
+    assert!(!pool.hit(7), "a parked projectile is not hit twice");
+    assert!(!pool.hit(99), "unknown keys are ignored");
+    assert_eq!(pool.stats().hit, 1);
+}
+
+#[test]
+fn test_released_slots_are_reused() {
+    let mut pool = ProjectilePool::new([1u64, 2]);
+    let first = pool.fire(1.0).unwrap();
+    pool.hit(first);
+    assert_eq!(pool.fire(1.0), Some(first));
+    assert_eq!(pool.stats().active, 1);
+}
@@ -0,0 +1,87 @@
+//! Integration tests for render graph scheduling
+
+use physics_core::render_graph::{GraphError, PassId, RenderGraph, Resource};
+
+#[test]
+fn test_empty_graph_schedules_nothing() {
+    let graph = RenderGraph::new();
+    assert!(graph.is_empty());
+    assert_eq!(graph.schedule(), Ok(Vec::new()));
+}
+
+#[test]
+fn test_independent_passes_keep_insertion_order() {
+    let mut graph = RenderGraph::new();
+    graph
+        .add_pass(PassId::Compute, &[], &[Resource::ComputeBuffer])
+        .add_pass(PassId::Main, &[Resource::Instances], &[Resource::Backbuffer]);
+    assert_eq!(graph.schedule(), Ok(vec![PassId::Compute, PassId::Main]));
+}
+
+#[test]
+fn test_reader_runs_after_writer_added_later() {
+    let mut graph = RenderGraph::new();
+    graph
+        .add_pass(PassId::Post, &[Resource::SceneColor], &[Resource::Backbuffer])
+        .add_pass(PassId::Main, &[Resource::Instances], &[Resource::SceneColor]);
+    assert_eq!(graph.schedule(), Ok(vec![PassId::Main, PassId::Post]));
+}
+
+#[test]
+fn test_backbuffer_writers_chain_in_order() {
+    let mut graph = RenderGraph::new();
+    graph
+        .add_pass(PassId::Main, &[Resource::Instances], &[Resource::SceneColor])
+        .add_pass(PassId::Post, &[Resource::SceneColor], &[Resource::Backbuffer])
+        .add_pass(PassId::Debug, &[Resource::Backbuffer], &[Resource::Backbuffer])
+        .add_pass(PassId::Egui, &[Resource::Backbuffer], &[Resource::Backbuffer]);
+    assert_eq!(
+        graph.schedule(),
+        Ok(vec![PassId::Main, PassId::Post, PassId::Debug, PassId::Egui])
+    );
+}
+
+#[test]
+fn test_text_draws_over_post_and_under_egui() {
+    let mut graph = RenderGraph::new();
+    graph
+        .add_pass(PassId::Main, &[Resource::Instances], &[Resource::SceneColor])
+        .add_pass(PassId::Post, &[Resource::SceneColor], &[Resource::Backbuffer])
+        .add_pass(PassId::Text, &[Resource::Backbuffer], &[Resource::Backbuffer])
+        .add_pass(PassId::Egui, &[Resource::Backbuffer], &[Resource::Backbuffer]);
+    assert_eq!(
+        graph.schedule(),
+        Ok(vec![PassId::Main, PassId::Post, PassId::Text, PassId::Egui])
+    );
+}
+
+#[test]
+fn test_missing_producer_is_rejected() {
+    let mut graph = RenderGraph::new();
+    graph.add_pass(PassId::Post, &[Resource::SceneColor], &[Resource::Backbuffer]);
+    assert_eq!(
+        graph.schedule(),
+        Err(GraphError::MissingProducer(PassId::Post, Resource::SceneColor))
+    );
+}
+
+#[test]
+fn test_duplicate_pass_is_rejected() {
+    let mut graph = RenderGraph::new();
+    graph
+        .add_pass(PassId::Main, &[], &[Resource::Backbuffer])
+        .add_pass(PassId::Main, &[], &[Resource::Backbuffer]);
+    assert_eq!(graph.schedule(), Err(GraphError::DuplicatePass(PassId::Main)));
+}
+
+#[test]
+fn test_cycle_is_rejected() {
+    let mut graph = RenderGraph::new();
+    graph
+        .add_pass(PassId::Main, &[Resource::ComputeBuffer], &[Resource::SceneColor])
+        .add_pass(PassId::Compute, &[Resource::SceneColor], &[Resource::ComputeBuffer]);
+    assert_eq!(
+        graph.schedule(),
+        Err(GraphError::Cycle(vec![PassId::Main, PassId::Compute]))
+    );
+}
@@ -0,0 +1,34 @@
+//! Integration tests for safe-area insets
+
+use physics_core::safe_area::SafeArea;
+
+#[test]
+fn test_insets_must_be_finite_and_not_negative() {
+    assert!(SafeArea::new(0.0, 44.0, 0.0, 34.0, false).is_some());
+    assert!(SafeArea::new(-1.0, 0.0, 0.0, 0.0, false).is_none());
+    assert!(SafeArea::new(0.0, f32::NAN, 0.0, 0.0, false).is_none());
+    assert!(SafeArea::new(0.0, 0.0, f32::INFINITY, 0.0, true).is_none());
+}
+
+#[test]
+fn test_content_rect_leaves_out_the_insets() {
+    // A phone in landscape with the notch on the left and a home indicator
+    let area = SafeArea::new(132.0, 0.0, 0.0, 63.4, false).unwrap();
+    assert_eq!(area.content_rect(2532, 1170), [132, 0, 2400, 1107]);
+    assert_eq!(SafeArea::default().content_rect(800, 600), [0, 0, 800, 600]);
+}
+
+#[test]
+fn test_insets_covering_the_surface_fall_back_to_all_of_it() {
+    let area = SafeArea::new(500.0, 0.0, 500.0, 0.0, true).unwrap();
+    assert_eq!(area.content_rect(800, 600), [0, 0, 800, 600]);
+    assert_eq!(area.content_rect(1200, 600), [500, 0, 200, 600]);
+}
+
+#[test]
+fn test_world_rect_only_insets_the_world_when_asked() {
+    let hud_only = SafeArea::new(10.0, 20.0, 30.0, 40.0, false).unwrap();
+    assert_eq!(hud_only.world_rect(400, 300), [0, 0, 400, 300]);
+    let world = SafeArea { inset_world: true, ..hud_only };
+    assert_eq!(world.world_rect(400, 300), [10, 20, 360, 240]);
+}
@@ -0,0 +1,160 @@
+//! Integration tests for the scene library, level progression and the fade
+
+use physics_core::gravity::GravityPreset;
+use physics_core::scenes::{Fade, ResetMode, SceneBody, SceneBodyType, SceneDesc, SceneLibrary, MAX_SCENES};
+
+fn library(names: &[&str]) -> SceneLibrary {
+    let mut library = SceneLibrary::default();
+    for name in names {
+        library.insert(name, SceneDesc::default()).unwrap();
+    }
+    library
+}
+
+#[test]
+fn test_next_walks_registration_order() {
+    let mut library = library(&["level1", "level2", "level3"]);
+    assert_eq!(library.next(), Some("level1"));
+    library.set_current("level2");
+    assert_eq!(library.current(), Some("level2"));
+    assert_eq!(library.next(), Some("level3"));
+    library.set_current("level3");
+    assert_eq!(library.next(), None);
+}
+
+#[test]
+fn test_replacing_keeps_the_position() {
+    let mut library = library(&["a", "b"]);
+    let desc = SceneDesc { gravity: Some([0.0, -1.0]), ..Default::default() };
+    library.insert("a", desc.clone()).unwrap();
+    assert_eq!(library.names(), vec!["a", "b"]);
+    assert_eq!(library.get("a"), Some(&desc));
+}
+
+#[test]
+fn test_removing_the_current_scene_forgets_it() {
+    let mut library = library(&["a", "b"]);
+    library.set_current("b");
+    assert!(library.remove("b"));
+    assert!(!library.remove("b"));
+    assert_eq!(library.current(), None);
+    // Unknown names are not remembered as current
+    library.set_current("missing");
+    assert_eq!(library.current(), None);
+}
+
+#[test]
+fn test_loaded_scene_outlives_removal_and_replacement() {
+    let mut library = library(&["a"]);
+    assert_eq!(library.loaded(), None);
+    let desc = SceneDesc { gravity: Some([0.0, -1.0]), ..Default::default() };
+    library.insert("a", desc.clone()).unwrap();
+    library.set_current("a");
+    library.insert("a", SceneDesc::default()).unwrap();
+    assert!(library.remove("a"));
+    assert_eq!(library.loaded(), Some(&desc));
+
+    library.set_current("missing");
+    assert_eq!(library.loaded(), Some(&desc));
+    library.clear_loaded();
+    assert_eq!(library.loaded(), None);
+}
+
+#[test]
+fn test_reset_mode_codes() {
+    assert_eq!(ResetMode::from_i32(0), Some(ResetMode::Scene));
+    assert_eq!(ResetMode::from_i32(1), Some(ResetMode::Pristine));
+    assert_eq!(ResetMode::from_i32(2), Some(ResetMode::Demo));
+    assert_eq!(ResetMode::from_i32(3), None);
+}
+
+#[test]
+fn test_insert_rejects_bad_scenes() {
+    let mut library = SceneLibrary::default();
+    assert!(library.insert("", SceneDesc::default()).is_err());
+    let bad_body = SceneDesc { bodies: vec![SceneBody { half_extent: 0.0, ..Default::default() }], ..Default::default() };
+    assert!(library.insert("a", bad_body).is_err());
+    let bad_gravity = SceneDesc { gravity: Some([f32::NAN, 0.0]), ..Default::default() };
+    assert!(library.insert("a", bad_gravity).is_err());
+    let both_gravities = SceneDesc { gravity: Some([0.0, -1.0]), gravity_preset: Some(GravityPreset::Moon), ..Default::default() };
+    assert!(library.insert("a", both_gravities).is_err());
+    assert!(library.names().is_empty());
+
+    for i in 0..MAX_SCENES {
+        library.insert(&i.to_string(), SceneDesc::default()).unwrap();
+    }
+    assert!(library.insert("one more", SceneDesc::default()).is_err());
+}
+
+#[test]
+fn test_fade_clears_up_over_its_duration() {
+    let mut fade = Fade::default();
+    assert_eq!(fade.alpha(), 0.0);
+    fade.start(2.0);
+    assert_eq!(fade.alpha(), 1.0);
+    fade.advance(0.5);
+    assert!((fade.alpha() - 0.75).abs() < 1e-6);
+    fade.advance(f32::NAN);
+    fade.advance(5.0);
+    assert_eq!(fade.alpha(), 0.0);
+    // Zero and non-finite times are hard cuts
+    fade.start(f32::INFINITY);
+    assert_eq!(fade.alpha(), 0.0);
+}
+
+#[test]
+fn test_scene_parses_from_json() {
+    let desc: SceneDesc = serde_json::from_str(
+        r#"{"level": {"shapes": [], "keepWalls": false}, "bodies": [{"x": 0.5, "bodyType": "fixed"}, {}], "gravity": [0, -3]}"#,
+    )
+    .unwrap();
+    assert!(!desc.level.keep_walls);
+    assert_eq!(desc.bodies[0].body_type, SceneBodyType::Fixed);
+    assert_eq!(desc.bodies[0].x, 0.5);
+    assert_eq!(desc.bodies[1], SceneBody::default());
+    assert_eq!(desc.gravity, Some([0.0, -3.0]));
+    assert!(desc.validate().is_ok());
+
+    let desc: SceneDesc = serde_json::from_str(r#"{"gravityPreset": "zeroG"}"#).unwrap();
+    assert_eq!(desc.gravity_preset, Some(GravityPreset::ZeroG));
+}
+
+#[test]
+fn test_scene_instances_use_the_scene_prefabs() {
+    let desc: SceneDesc = serde_json::from_str(
+        r#"{"prefabs": {"cart": {"parts": [{"prefab": "wheel", "x": 0.1}]}, "wheel": {"shape": {"type": "box", "halfExtent": 0.02}}},
+            "instances": [{"prefab": "cart", "x": 1}, {"prefab": "wheel", "y": 2, "angle": 0.5}]}"#,
+    )
+    .unwrap();
+    assert_eq!(desc.instances[1].angle, 0.5);
+    assert!(desc.validate().is_ok());
+
+    let mut missing = desc.clone();
+    missing.prefabs.remove("wheel");
+    assert!(missing.validate().unwrap_err().contains("wheel"));
+    let mut lost = desc;
+    lost.instances[0].x = f32::NAN;
+    assert!(lost.validate().is_err());
+}
+
+#[test]
+fn test_scene_textures_swap_to_the_registered_materials() {
+    let desc: SceneDesc = serde_json::from_str(
+        r#"{"textures": {"3": "textures/ball.png"}, "bodies": [{"materialId": 3}, {"materialId": 7}],
+            "prefabs": {"ball": {"materialId": 3}}}"#,
+    )
+    .unwrap();
+    assert_eq!(desc.textures[&3], "textures/ball.png");
+    assert!(desc.validate().is_ok());
+
+    let loaded = desc.with_materials(&[(3, 12)].into_iter().collect());
+    assert_eq!(loaded.bodies.iter().map(|body| body.material_id).collect::<Vec<_>>(), vec![12, 7]);
+    assert_eq!(loaded.prefabs["ball"].material_id, 12);
+
+    let mut default = desc.clone();
+    default.textures.insert(0, "textures/box.png".into());
+    assert!(default.validate().is_err());
+    let mut outside = desc;
+    outside.textures.insert(4, "../secrets.png".into());
+    assert!(outside.validate().is_err());
+}
@@ -0,0 +1,64 @@
+//! Integration tests for saving and loading settings
+
+use physics_core::render_settings::RenderSettings;
+use physics_core::settings_store::{CameraSettings, PanelLayout, PhysicsSettings, SavedSettings, SettingsStore, FILE_NAME};
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("physicsfx_settings_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_settings_round_trip_through_a_file() {
+    let dir = temp_dir("round_trip");
+    let store = SettingsStore::in_directory(&dir);
+    assert_eq!(store, SettingsStore::File(dir.join(FILE_NAME)));
+    assert!(store.load().is_none());
+
+    let mut physics = PhysicsSettings { gravity: [0.0, -4.0, 0.0], time_scale: 0.5, sleep_enabled: false, ..Default::default() };
+    physics.solver.solver_iterations = 8;
+    let settings = SavedSettings {
+        panel: Some(PanelLayout { x: 10.0, y: 20.0, width: 300.0, height: 400.0 }),
+        render: Some(RenderSettings { bloom_enabled: true, culling_enabled: false, ..Default::default() }),
+        camera: Some(CameraSettings { x: 1.5, y: -2.0, view_height: 6.0 }),
+        physics: Some(physics),
+    };
+    store.save(&settings).unwrap();
+    assert_eq!(store.load(), Some(settings.clone()));
+
+    // Saving again replaces the document
+    let fewer = SavedSettings { camera: None, ..settings };
+    store.save(&fewer).unwrap();
+    assert_eq!(store.load(), Some(fewer));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_corrupt_documents_restore_nothing() {
+    let dir = temp_dir("corrupt");
+    let store = SettingsStore::in_directory(&dir);
+    std::fs::write(dir.join(FILE_NAME), "{\"panel\": ").unwrap();
+    assert!(store.load().is_none());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_partial_documents_keep_defaults_and_drop_bad_parts() {
+    let settings = SavedSettings::from_json(
+        r#"{"render": {"bloomEnabled": true}, "camera": {"x": 1, "y": 2, "viewHeight": 0},
+            "panel": {"x": 0, "y": 0, "width": -5, "height": 10}, "physics": {"timeScale": -1}}"#,
+    )
+    .unwrap();
+    let render = settings.render.unwrap();
+    assert!(render.bloom_enabled);
+    assert_eq!(render.msaa_samples, RenderSettings::default().msaa_samples);
+    assert!(settings.camera.is_none());
+    assert!(settings.panel.is_none());
+    let physics = settings.physics.unwrap();
+    assert_eq!(physics.time_scale, 1.0);
+    assert_eq!(physics.gravity, PhysicsSettings::default().gravity);
+
+    assert_eq!(SavedSettings::from_json("{}").unwrap(), SavedSettings::default());
+    assert!(SavedSettings::from_json("\"nope\"").is_err());
+}
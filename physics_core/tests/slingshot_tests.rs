@@ -0,0 +1,69 @@
+//! Integration tests for the slingshot aim and launch impulse
+
+use physics_core::slingshot::{Slingshot, SlingshotConfig};
+
+fn config() -> SlingshotConfig {
+    SlingshotConfig { max_impulse: 2.0, max_pull: 1.0, arc_samples: 30 }
+}
+
+fn close(a: [f32; 2], b: [f32; 2]) -> bool {
+    (a[0] - b[0]).abs() < 1e-6 && (a[1] - b[1]).abs() < 1e-6
+}
+
+#[test]
+fn test_impulse_points_from_pointer_to_anchor() {
+    // Pulled down and left, launched up and right
+    let impulse = config().launch_impulse([0.0, 0.0], [-0.3, -0.4]);
+    // Half a full pull: half the impulse
+    assert!(close(impulse, [0.6, 0.8]));
+}
+
+#[test]
+fn test_impulse_is_capped_at_a_full_pull() {
+    let impulse = config().launch_impulse([1.0, 1.0], [1.0, -5.0]);
+    assert!(close(impulse, [0.0, 2.0]));
+    assert_eq!(config().launch_impulse([1.0, 1.0], [1.0, 1.0]), [0.0, 0.0]);
+}
+
+#[test]
+fn test_validated_rejects_bad_tuning() {
+    assert!(config().validated().is_some());
+    assert!(SlingshotConfig { max_impulse: 0.0, ..config() }.validated().is_none());
+    assert!(SlingshotConfig { max_pull: f32::NAN, ..config() }.validated().is_none());
+    assert!(SlingshotConfig { max_pull: -1.0, ..config() }.validated().is_none());
+}
+
+#[test]
+fn test_press_is_ignored_while_disabled() {
+    let mut slingshot = Slingshot::default();
+    slingshot.press(7, [0.0, 0.0]);
+    assert!(slingshot.aim().is_none());
+    assert!(slingshot.drag([1.0, 1.0]).is_none());
+    assert!(slingshot.release([1.0, 1.0]).is_none());
+}
+
+#[test]
+fn test_drag_then_release_launches_once() {
+    let mut slingshot = Slingshot::default();
+    slingshot.set_enabled(true);
+    slingshot.set_config(config());
+    slingshot.press(7, [0.0, 0.0]);
+    let aim = slingshot.drag([0.0, -0.25]).unwrap();
+    assert_eq!(aim.entity, 7);
+    assert!(close(aim.impulse, [0.0, 0.5]));
+    // The release position wins over the last drag
+    let launched = slingshot.release([-0.5, 0.0]).unwrap();
+    assert!(close(launched.impulse, [1.0, 0.0]));
+    assert!(slingshot.aim().is_none());
+    assert!(slingshot.release([-0.5, 0.0]).is_none());
+}
+
+#[test]
+fn test_disabling_drops_the_aim() {
+    let mut slingshot = Slingshot::default();
+    slingshot.set_enabled(true);
+    slingshot.press(3, [0.0, 0.0]);
+    slingshot.set_enabled(false);
+    assert!(!slingshot.enabled());
+    assert!(slingshot.aim().is_none());
+}
@@ -0,0 +1,67 @@
+//! Integration tests for the snapshot history ring and entity restore
+
+use bevy_ecs::world::World;
+use physics_core::snapshot::{resolve_entities, SnapshotRing};
+
+fn ring(capacity: usize, count: u32) -> SnapshotRing<u32> {
+    let mut ring = SnapshotRing::new(capacity);
+    for i in 0..count {
+        ring.push(i);
+    }
+    ring
+}
+
+#[test]
+fn test_full_ring_drops_oldest() {
+    let ring = ring(3, 5);
+    assert_eq!(ring.len(), 3);
+    assert_eq!(ring.get(0), Some(&4));
+    assert_eq!(ring.get(2), Some(&2));
+    assert_eq!(ring.get(3), None);
+}
+
+#[test]
+fn test_zero_capacity_keeps_nothing() {
+    let ring = ring(0, 5);
+    assert!(ring.is_empty());
+}
+
+#[test]
+fn test_discard_newest_keeps_at_least_one() {
+    let mut ring = ring(10, 6);
+    assert_eq!(ring.discard_newest(2), 2);
+    assert_eq!(ring.get(0), Some(&3));
+    assert_eq!(ring.discard_newest(100), 3);
+    assert_eq!(ring.len(), 1);
+    assert_eq!(ring.get(0), Some(&0));
+    assert_eq!(ring.discard_newest(1), 0);
+}
+
+#[test]
+fn test_shrinking_capacity_drops_oldest() {
+    let mut ring = ring(10, 6);
+    ring.set_capacity(2);
+    assert_eq!(ring.len(), 2);
+    assert_eq!(ring.get(1), Some(&4));
+}
+
+#[test]
+fn test_missing_entities_come_back_under_new_ids() {
+    let mut world = World::new();
+    let kept = world.spawn_empty().id();
+    let gone = world.spawn_empty().id();
+    world.despawn(gone);
+    // Takes the despawned entity's index with a newer generation
+    let reused = world.spawn_empty().id();
+
+    let entities = resolve_entities(&mut world, &[kept.to_bits(), gone.to_bits()]).unwrap();
+    assert_eq!(entities[&kept.to_bits()], kept);
+    let respawned = entities[&gone.to_bits()];
+    assert!(respawned != gone && respawned != reused);
+    assert!(world.get_entity(respawned).is_ok());
+
+    // Generation 0 is never a valid entity; nothing is spawned
+    let count = world.entities().len();
+    assert!(resolve_entities(&mut world, &[gone.to_bits(), 1]).is_err());
+    assert_eq!(world.entities().len(), count);
+}
@@ -0,0 +1,94 @@
+//! Integration tests for the soft-body blob geometry and pressure
+
+use physics_core::soft_body::{fill_vertices, polygon_area, pressure_forces, ring_springs, SoftBodyDesc, MAX_SEGMENTS, MIN_SEGMENTS};
+
+fn unit_square() -> Vec<[f32; 2]> {
+    vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+}
+
+#[test]
+fn test_polygon_area_is_signed_by_winding() {
+    let square = unit_square();
+    assert_eq!(polygon_area(&square), 1.0);
+    let reversed: Vec<_> = square.into_iter().rev().collect();
+    assert_eq!(polygon_area(&reversed), -1.0);
+    assert_eq!(polygon_area(&[]), 0.0);
+}
+
+#[test]
+fn test_ring_is_counter_clockwise_around_the_center() {
+    let desc = SoftBodyDesc { x: 1.0, y: 2.0, radius: 0.5, segments: 32, ..SoftBodyDesc::default() };
+    let ring = desc.ring();
+    assert_eq!(ring.len(), 32);
+    assert!((ring[0][0] - 1.5).abs() < 1e-6 && (ring[0][1] - 2.0).abs() < 1e-6);
+    for [x, y] in &ring {
+        assert!((((x - 1.0).powi(2) + (y - 2.0).powi(2)).sqrt() - 0.5).abs() < 1e-5);
+    }
+    // Close to the circle's area, and positive
+    let area = polygon_area(&ring);
+    assert!(area > 0.0 && (area - std::f32::consts::PI * 0.25).abs() < 0.01);
+}
+
+#[test]
+fn test_validated_rejects_bad_numbers_and_clamps_the_rest() {
+    assert!(SoftBodyDesc { radius: 0.0, ..SoftBodyDesc::default() }.validated().is_none());
+    assert!(SoftBodyDesc { x: f32::NAN, ..SoftBodyDesc::default() }.validated().is_none());
+    assert!(SoftBodyDesc { pressure: -1.0, ..SoftBodyDesc::default() }.validated().is_none());
+    assert!(SoftBodyDesc { node_half_extent: 0.0, ..SoftBodyDesc::default() }.validated().is_none());
+
+    let desc = SoftBodyDesc { segments: 1, color: [2.0, -1.0, f32::NAN, 0.5], ..SoftBodyDesc::default() }
+        .validated()
+        .unwrap();
+    assert_eq!(desc.segments, MIN_SEGMENTS);
+    assert_eq!(desc.color, [1.0, 0.0, 1.0, 0.5]);
+    let desc = SoftBodyDesc { segments: 1000, ..SoftBodyDesc::default() }.validated().unwrap();
+    assert_eq!(desc.segments, MAX_SEGMENTS);
+}
+
+#[test]
+fn test_compressed_ring_is_pushed_outward() {
+    let square = unit_square();
+    let forces = pressure_forces(&square, 2.0, 10.0);
+    // Half the rest area: each corner gets two half-edges of push, 0.5 * 10 * 0.5 each
+    assert_eq!(forces[0], [-2.5, -2.5]);
+    assert_eq!(forces[2], [2.5, 2.5]);
+    // Pressure is internal: the forces cancel out
+    let sum = forces.iter().fold([0.0, 0.0], |acc, f| [acc[0] + f[0], acc[1] + f[1]]);
+    assert_eq!(sum, [0.0, 0.0]);
+}
+
+#[test]
+fn test_ring_at_rest_area_feels_no_pressure() {
+    let square = unit_square();
+    assert!(pressure_forces(&square, 1.0, 10.0).iter().all(|f| *f == [0.0, 0.0]));
+    assert!(pressure_forces(&square, 0.5, 10.0).iter().all(|f| *f == [0.0, 0.0]));
+    assert!(pressure_forces(&square, 0.0, 10.0).iter().all(|f| *f == [0.0, 0.0]));
+    assert!(pressure_forces(&square[..2], 2.0, 10.0).iter().all(|f| *f == [0.0, 0.0]));
+}
+
+#[test]
+fn test_springs_connect_neighbours_without_duplicates() {
+    assert_eq!(ring_springs(3, 10.0), vec![(0, 1, 10.0), (1, 2, 10.0), (2, 0, 10.0)]);
+    assert_eq!(ring_springs(4, 10.0).len(), 6);
+    let springs = ring_springs(8, 10.0);
+    assert_eq!(springs.len(), 16);
+    assert_eq!(springs.iter().filter(|s| s.2 < 10.0).count(), 8);
+}
+
+#[test]
+fn test_fill_is_a_fan_with_a_darker_rim() {
+    let color = [1.0, 0.5, 0.25, 1.0];
+    let vertices = fill_vertices(&unit_square(), color);
+    assert_eq!(vertices.len(), 12);
+    assert_eq!(vertices[0].position, [0.5, 0.5]);
+    assert_eq!(vertices[0].color, color);
+    assert_eq!(vertices[1].position, [0.0, 0.0]);
+    assert!(vertices[1].color[0] < color[0] && vertices[1].color[3] == color[3]);
+    assert!(fill_vertices(&unit_square()[..2], color).is_empty());
+}
+
+#[test]
+fn test_missing_json_fields_take_defaults() {
+    let desc: SoftBodyDesc = serde_json::from_str(r#"{"x": 0.5, "segments": 8}"#).unwrap();
+    assert_eq!(desc, SoftBodyDesc { x: 0.5, segments: 8, ..SoftBodyDesc::default() });
+}
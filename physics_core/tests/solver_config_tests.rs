@@ -0,0 +1,63 @@
+//! Integration tests for solver settings
+
+use physics_core::solver_config::{self, SolverConfig};
+use physics_core::precision::to_f32;
+use physics_core::rapier::prelude::{IntegrationParameters, Real};
+
+#[test]
+fn test_default_config_leaves_rapier_defaults() {
+    let defaults = IntegrationParameters::default();
+    let mut params = IntegrationParameters::default();
+    SolverConfig::default().apply(&mut params);
+    assert_eq!(params.num_solver_iterations, defaults.num_solver_iterations);
+    assert_eq!(params.contact_natural_frequency, defaults.contact_natural_frequency);
+    assert_eq!(params.contact_damping_ratio, defaults.contact_damping_ratio);
+    assert!((params.normalized_allowed_linear_error - defaults.normalized_allowed_linear_error).abs() < 1e-9);
+    assert_eq!(params.max_ccd_substeps, defaults.max_ccd_substeps);
+}
+
+#[test]
+fn test_erp_survives_a_change_of_step_length() {
+    let config = SolverConfig { erp: 0.4, ..Default::default() };
+    let mut params = IntegrationParameters { dt: 1.0 / 60.0, ..Default::default() };
+    config.apply(&mut params);
+    let at_60 = params.contact_natural_frequency;
+    params.dt = 1.0 / 120.0;
+    config.apply(&mut params);
+    // Halving dt doubles the spring frequency for the same erp
+    assert!((params.contact_natural_frequency / at_60 - 2.0).abs() < 1e-3);
+
+    // And the frequency reproduces the requested erp
+    let omega = params.contact_natural_frequency * 2.0 * std::f64::consts::PI as Real;
+    let erp = params.dt * omega / (params.dt * omega + 4.0 * params.contact_damping_ratio);
+    assert!((erp - 0.4).abs() < 1e-4);
+}
+
+#[test]
+fn test_sanitized_clamps_out_of_range_values() {
+    let config = SolverConfig {
+        solver_iterations: 0,
+        friction_iterations: 1000,
+        internal_pgs_iterations: 0,
+        erp: 3.0,
+        allowed_linear_error: -1.0,
+        max_ccd_substeps: 0,
+        contact_damping: f32::NAN,
+    }
+    .sanitized();
+    assert_eq!(config.solver_iterations, 1);
+    assert_eq!(config.friction_iterations, solver_config::MAX_ITERATIONS);
+    assert_eq!(config.internal_pgs_iterations, 1);
+    assert!(config.erp < 1.0);
+    assert_eq!(config.allowed_linear_error, 0.0);
+    assert_eq!(config.max_ccd_substeps, 1);
+    assert_eq!(config.contact_damping, SolverConfig::default().contact_damping);
+}
+
+#[test]
+fn test_zero_dt_falls_back_to_default_frequency() {
+    assert_eq!(
+        solver_config::natural_frequency_for_erp(0.5, 5.0, 0.0),
+        to_f32(IntegrationParameters::default().contact_natural_frequency)
+    );
+}
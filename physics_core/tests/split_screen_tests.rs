@@ -0,0 +1,77 @@
+//! Integration tests for split-screen layout and input routing
+
+use physics_core::events::{GameEvent, InputEventType};
+use physics_core::split_screen::{Action, KeyBinding, PlayerView, SplitLayout, SplitScreen, DIVIDER};
+
+fn split(layout: SplitLayout) -> SplitScreen {
+    SplitScreen { layout, surface_size: (800, 600), ..SplitScreen::default() }
+}
+
+#[test]
+fn test_viewports() {
+    let [left, right] = split(SplitLayout::SideBySide).viewports().unwrap();
+    assert_eq!(left, [0, 0, 399, 600]);
+    assert_eq!(right, [399 + DIVIDER, 0, 399, 600]);
+    let [top, bottom] = split(SplitLayout::Stacked).viewports().unwrap();
+    assert_eq!(top, [0, 0, 800, 299]);
+    assert_eq!(bottom, [0, 299 + DIVIDER, 800, 299]);
+    // Odd sizes give the extra pixel to player 2
+    let odd = SplitScreen { surface_size: (101, 50), ..SplitScreen::default() };
+    assert_eq!(odd.viewports().unwrap().map(|[_, _, width, _]| width), [49, 50]);
+    assert!(SplitScreen::default().viewports().is_none());
+}
+
+#[test]
+fn test_pointer_routing_by_half() {
+    let side = split(SplitLayout::SideBySide);
+    let press = |x, y| GameEvent::new_pointer(InputEventType::PointerDown, x, y);
+    assert_eq!(side.route(&press(100.0, 500.0)), Some((1, Action::Jump.impulse())));
+    assert_eq!(side.route(&press(700.0, 10.0)), Some((2, Action::Jump.impulse())));
+    let stacked = split(SplitLayout::Stacked);
+    assert_eq!(stacked.route(&press(700.0, 10.0)).map(|(player, _)| player), Some(1));
+    assert_eq!(stacked.route(&press(10.0, 590.0)).map(|(player, _)| player), Some(2));
+    assert_eq!(side.route(&GameEvent::new_pointer(InputEventType::PointerMove, 700.0, 10.0)), None);
+}
+
+#[test]
+fn test_key_routing() {
+    let split = split(SplitLayout::SideBySide);
+    let key = |code| split.route(&GameEvent::new_key(InputEventType::KeyDown, code));
+    // Default bindings: W for player 1, D-pad left for player 2
+    assert_eq!(key(51), Some((1, [0.0, 0.5])));
+    assert_eq!(key(21), Some((2, [-0.5, 0.0])));
+    assert_eq!(key(1000), None);
+    assert_eq!(split.route(&GameEvent::new_key(InputEventType::KeyUp, 51)), None);
+}
+
+#[test]
+fn test_json_config() {
+    let split: SplitScreen = serde_json::from_str(
+        r#"{"layout": "stacked", "players": [{"follow": 7}, {"centerX": 2, "viewHeight": 4}],
+            "bindings": [{"keyCode": 10, "player": 2, "action": "jump"}]}"#,
+    )
+    .unwrap();
+    assert_eq!(split.layout, SplitLayout::Stacked);
+    assert_eq!(split.players[0].follow, Some(7));
+    assert_eq!(split.players[1].camera(None).center_x, 2.0);
+    assert_eq!(split.players[1].camera(Some([5.0, 6.0])).center_y, 6.0);
+    assert_eq!(split.bindings, vec![KeyBinding { key_code: 10, player: 2, action: Action::Jump }]);
+    assert!(split.is_valid());
+
+    let defaults: SplitScreen = serde_json::from_str("{}").unwrap();
+    assert_eq!(defaults, SplitScreen::default());
+}
+
+#[test]
+fn test_validation() {
+    let bad_player = SplitScreen {
+        bindings: vec![KeyBinding { key_code: 1, player: 3, action: Action::Up }],
+        ..SplitScreen::default()
+    };
+    assert!(!bad_player.is_valid());
+    let bad_camera = SplitScreen {
+        players: [PlayerView { view_height: 0.0, ..PlayerView::default() }, PlayerView::default()],
+        ..SplitScreen::default()
+    };
+    assert!(!bad_camera.is_valid());
+}
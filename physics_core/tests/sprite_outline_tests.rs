@@ -0,0 +1,89 @@
+//! Integration tests for tracing collision outlines from sprite alpha
+
+use physics_core::sprite_outline::{pieces, simplify, trace};
+
+fn area(points: &[[f32; 2]]) -> f32 {
+    let n = points.len();
+    (0..n).map(|i| points[i][0] * points[(i + 1) % n][1] - points[(i + 1) % n][0] * points[i][1]).sum::<f32>() / 2.0
+}
+
+/// RGBA image from rows of '#' (opaque), '+' (half transparent) and '.' (clear)
+fn image(rows: &[&str]) -> (u32, u32, Vec<u8>) {
+    let rgba = rows
+        .iter()
+        .flat_map(|row| row.chars())
+        .flat_map(|c| [255, 255, 255, match c {
+            '#' => 255,
+            '+' => 128,
+            _ => 0,
+        }])
+        .collect();
+    (rows[0].len() as u32, rows.len() as u32, rgba)
+}
+
+#[test]
+fn test_opaque_sprite_fills_the_quad() {
+    let (w, h, rgba) = image(&["####"; 4]);
+    let outlines = trace(w, h, &rgba, 0);
+    assert_eq!(outlines.len(), 1);
+    // The outline runs along the image border with half-pixel chamfers at the corners
+    assert!((area(&outlines[0]) - (16.0 - 4.0 * 0.125)).abs() < 1e-5);
+
+    let pieces = pieces(w, h, &rgba, 0, 0.0).unwrap();
+    assert_eq!(pieces.len(), 1);
+    let xs = pieces[0].iter().map(|p| p[0]);
+    assert_eq!(xs.clone().fold(f32::MAX, f32::min), -1.0);
+    assert_eq!(xs.fold(f32::MIN, f32::max), 1.0);
+    assert!(area(&pieces[0]) > 3.8);
+}
+
+#[test]
+fn test_concave_sprite_splits_into_convex_pieces() {
+    let (w, h, rgba) = image(&[
+        "##......", //
+        "##......",
+        "##......",
+        "########",
+        "########",
+    ]);
+    let pieces = pieces(w, h, &rgba, 0, 0.5).unwrap();
+    assert!(pieces.len() >= 2);
+    for piece in &pieces {
+        assert!(area(piece) > 0.0);
+    }
+    // Nothing solid in the clear top right
+    assert!(pieces.iter().flatten().all(|p| p[0] < -0.4 || p[1] < 0.0));
+}
+
+#[test]
+fn test_threshold_islands_and_holes() {
+    let (w, h, rgba) = image(&[
+        "###.....",
+        "#.#.+++.",
+        "###.+++.",
+        "........",
+    ]);
+    // The faint island is solid only for thresholds below its alpha, and the hole is filled
+    let outlines = trace(w, h, &rgba, 0);
+    assert_eq!(outlines.len(), 2);
+    assert!(outlines.iter().all(|outline| area(outline) > 0.0));
+    assert_eq!(trace(w, h, &rgba, 200).len(), 1);
+    assert!((area(&trace(w, h, &rgba, 200)[0]) - (9.0 - 4.0 * 0.125)).abs() < 1e-5);
+}
+
+#[test]
+fn test_invalid_input_is_rejected() {
+    let (w, h, rgba) = image(&["....", "...."]);
+    assert!(pieces(w, h, &rgba, 0, 0.5).is_err());
+    let (w, h, rgba) = image(&["####", "####"]);
+    assert!(pieces(w, h, &rgba, 0, -1.0).is_err());
+    assert!(pieces(w, h + 1, &rgba, 0, 0.5).is_err());
+    assert!(pieces(w, h, &rgba, 255, 0.5).is_err());
+}
+
+#[test]
+fn test_simplify_drops_points_on_straight_runs() {
+    let square = [[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [2.0, 1.0], [2.0, 2.0], [1.0, 2.05], [0.0, 2.0], [0.0, 1.0]];
+    assert_eq!(simplify(&square, 0.0).len(), 5);
+    assert_eq!(simplify(&square, 0.1).len(), 4);
+}
@@ -0,0 +1,43 @@
+//! Integration tests for sprite regions and nine-slice sampling
+
+use physics_core::sprite_region::{slice_axis, SpriteRegion};
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-5
+}
+
+#[test]
+fn test_regions_inside_the_texture_are_valid() {
+    let panel = SpriteRegion { rect: [0.5, 0.0, 0.25, 0.25], size: [4.0, 1.0], borders: [0.25, 0.25, 0.25, 0.25] };
+    panel.validate().unwrap();
+    assert_eq!(panel.uv(), ([0.5, 0.0], [0.25, 0.25]));
+    assert_eq!(panel.slices(), ([0.25, 0.25], [0.25, 0.25]));
+    SpriteRegion::default().validate().unwrap();
+
+    assert!(SpriteRegion { rect: [0.9, 0.0, 0.2, 0.2], ..Default::default() }.validate().is_err());
+    assert!(SpriteRegion { rect: [0.0, 0.0, 0.0, 1.0], ..Default::default() }.validate().is_err());
+    assert!(SpriteRegion { size: [2.0, f32::NAN], ..Default::default() }.validate().is_err());
+    assert!(SpriteRegion { borders: [0.6, 0.0, 0.5, 0.0], ..Default::default() }.validate().is_err());
+    assert!(SpriteRegion { borders: [-0.1, 0.0, 0.0, 0.0], ..Default::default() }.validate().is_err());
+}
+
+#[test]
+fn test_nine_slice_keeps_borders_and_stretches_the_middle() {
+    // Four squares wide with quarter borders: each border spans a quarter
+    // of the square, so 1/16 of the quad, and the middle half of the
+    // region covers the remaining 14/16
+    assert!(close(slice_axis(0.0, 4.0, 0.25, 0.25), 0.0));
+    assert!(close(slice_axis(0.25 / 4.0, 4.0, 0.25, 0.25), 0.25));
+    assert!(close(slice_axis(0.5, 4.0, 0.25, 0.25), 0.5));
+    assert!(close(slice_axis(1.0 - 0.125 / 4.0, 4.0, 0.25, 0.25), 0.875));
+    assert!(close(slice_axis(1.0, 4.0, 0.25, 0.25), 1.0));
+
+    // Without borders the region stretches, and at the square's own size
+    // nothing moves
+    assert!(close(slice_axis(0.3, 4.0, 0.0, 0.0), 0.3));
+    assert!(close(slice_axis(0.3, 1.0, 0.25, 0.25), 0.3));
+
+    // Half the borders' length: both shrink alike and meet in the middle
+    assert!(close(slice_axis(0.25, 0.25, 0.25, 0.25), 0.125));
+    assert!(close(slice_axis(0.75, 0.25, 0.25, 0.25), 0.875));
+}
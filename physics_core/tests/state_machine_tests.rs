@@ -0,0 +1,128 @@
+//! Integration tests for entity state machines
+
+use physics_core::scenes::SceneDesc;
+use physics_core::state_machine::{MovementDesc, StateMachine, StateMachineDesc};
+use physics_core::SpriteSheetComponent;
+
+const ENEMY: &str = r#"{
+    "initial": "patrol",
+    "states": [
+        {"name": "idle", "transitions": [{"to": "patrol", "when": {"event": "wake"}}]},
+        {"name": "patrol",
+         "movement": {"type": "horizontalRandom", "speed": 0.2, "moveDuration": 1, "pauseDuration": 0.5},
+         "clip": {"firstFrame": 4, "frameCount": 4},
+         "transitions": [
+            {"to": "hurt", "when": {"collision": 4}},
+            {"to": "idle", "when": {"after": 2}}
+         ]},
+        {"name": "hurt", "movement": {"type": "none"}, "materialId": 2,
+         "transitions": [{"to": "patrol", "when": {"after": 0.5}}]}
+    ]
+}"#;
+
+fn enemy() -> StateMachine {
+    StateMachine::new(serde_json::from_str(ENEMY).unwrap()).unwrap()
+}
+
+#[test]
+fn test_starts_in_initial_state_with_hooks_pending() {
+    let mut machine = enemy();
+    assert_eq!(machine.current_name(), "patrol");
+    let entered = machine.take_entered().unwrap();
+    assert_eq!(entered.clip.unwrap().first_frame, 4);
+    assert!(machine.take_entered().is_none());
+}
+
+#[test]
+fn test_timeout_moves_on_and_resets_time() {
+    let mut machine = enemy();
+    assert_eq!(machine.update(1.5, &[]), None);
+    assert_eq!(machine.update(0.5, &[]), Some(0));
+    assert_eq!(machine.current_name(), "idle");
+    assert_eq!(machine.time_in_state(), 0.0);
+    assert!(machine.take_entered().is_some());
+}
+
+#[test]
+fn test_collisions_match_the_other_bodys_groups() {
+    let mut machine = enemy();
+    // Walls (0) and bodies in other groups do not count
+    assert_eq!(machine.update(0.1, &[0, 1]), None);
+    assert_eq!(machine.update(0.1, &[1 | 4]), Some(2));
+    let hurt = machine.take_entered().unwrap();
+    assert_eq!(hurt.movement, Some(MovementDesc::None));
+    assert_eq!(hurt.material_id, Some(2));
+}
+
+#[test]
+fn test_earlier_transitions_win() {
+    let mut machine = enemy();
+    // Timed out and hit in the same step: the collision is listed first
+    assert_eq!(machine.update(5.0, &[4]), Some(2));
+}
+
+#[test]
+fn test_unhandled_events_are_dropped() {
+    let mut machine = enemy();
+    machine.update(2.0, &[]);
+    assert_eq!(machine.current_name(), "idle");
+    machine.send("alarm");
+    assert_eq!(machine.update(0.1, &[]), None);
+    machine.send("wake");
+    machine.send("wake");
+    assert_eq!(machine.update(0.1, &[]), Some(1));
+    // Sent while patrolling, where nothing listens for it
+    machine.send("wake");
+    assert_eq!(machine.update(2.0, &[]), Some(0));
+    assert_eq!(machine.update(0.1, &[]), None);
+}
+
+#[test]
+fn test_validation() {
+    let parse = |json: &str| serde_json::from_str::<StateMachineDesc>(json).unwrap().validate();
+    assert!(parse(ENEMY).is_ok());
+    assert!(parse(r#"{"states": []}"#).is_err());
+    assert!(parse(r#"{"states": [{"name": "a"}, {"name": "a"}]}"#).is_err());
+    assert!(parse(r#"{"states": [{"name": "a"}], "initial": "b"}"#).is_err());
+    assert!(parse(r#"{"states": [{"name": "a", "transitions": [{"to": "b", "when": {"after": 1}}]}]}"#).is_err());
+    assert!(parse(r#"{"states": [{"name": "a", "transitions": [{"to": "a", "when": {"after": -1}}]}]}"#).is_err());
+    assert!(parse(r#"{"states": [{"name": "a", "clip": {"frameCount": 0}}]}"#).is_err());
+    assert!(parse(
+        r#"{"states": [{"name": "a", "movement": {"type": "horizontalRandom", "speed": 1, "moveDuration": 0, "pauseDuration": 0}}]}"#
+    )
+    .is_err());
+    assert!(StateMachine::new(StateMachineDesc::default()).is_err());
+}
+
+#[test]
+fn test_movement_desc_builds_strategies() {
+    let linear = MovementDesc::Linear { velocity: [1.0, -1.0] }.strategy().unwrap();
+    assert_eq!(linear.name(), "Linear");
+    assert_eq!(linear.calculate_position((1.0, 1.0), 2.0), (3.0, -1.0));
+    assert!(MovementDesc::None.strategy().is_none());
+}
+
+#[test]
+fn test_sprite_sheet_clip_offsets_frames() {
+    let mut sheet = SpriteSheetComponent::new(4, 4, 4, 0.1, true);
+    sheet.first_frame = 8;
+    assert_eq!(sheet.frame_for_time(0.0, 1.0), 8);
+    assert_eq!(sheet.frame_for_time(0.35, 1.0), 11);
+    assert_eq!(sheet.frame_for_time(0.45, 1.0), 8);
+    // Third row of the 4x4 sheet
+    let (u, v, _, _) = sheet.uv_for_frame(9);
+    assert!((u - 0.25).abs() < 1e-6 && (v - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn test_scene_state_machines_are_validated() {
+    let json = format!(r#"{{"bodies": [{{}}, {{}}], "stateMachines": [{{"bodies": [0, 1], {}}}]}}"#, &ENEMY.trim()[1..ENEMY.trim().len() - 1]);
+    let desc: SceneDesc = serde_json::from_str(&json).unwrap();
+    assert_eq!(desc.state_machines[0].bodies, vec![0, 1]);
+    assert_eq!(desc.state_machines[0].machine.states.len(), 3);
+    assert!(desc.validate().is_ok());
+
+    let mut bad = desc;
+    bad.state_machines[0].bodies.push(2);
+    assert!(bad.validate().is_err());
+}
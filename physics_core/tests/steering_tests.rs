@@ -0,0 +1,131 @@
+//! Integration tests for steering behaviors, driven by a scripted scene
+
+use physics_core::state_machine::MovementDesc;
+use physics_core::steering::{
+    approach, AvoidObstacles, BodyView, Flee, RayHit, Seek, SteeringContext, SteeringQueries, SteeringTarget, Wander,
+};
+use physics_core::MovementStrategy;
+
+/// One other body at `target` and, optionally, a wall at x = `wall_x`
+struct Scene {
+    target: (f32, f32),
+    wall_x: Option<f32>,
+}
+
+impl SteeringQueries for Scene {
+    fn body(&self, entity_id: u64) -> Option<BodyView> {
+        (entity_id == 7).then_some(BodyView { position: self.target, velocity: (0.0, 0.0) })
+    }
+
+    fn nearest(&self, mask: u32, _from: (f32, f32)) -> Option<BodyView> {
+        (mask & 2 != 0).then_some(BodyView { position: self.target, velocity: (0.0, 0.0) })
+    }
+
+    fn cast_ray(&self, origin: (f32, f32), direction: (f32, f32), max_distance: f32) -> Option<RayHit> {
+        let wall = self.wall_x?;
+        if direction.0 <= 0.0 {
+            return None;
+        }
+        let distance = (wall - origin.0) / direction.0;
+        (distance >= 0.0 && distance <= max_distance).then_some(RayHit { distance, normal: (-1.0, 0.0) })
+    }
+}
+
+fn context<'a>(scene: &'a Scene, velocity: (f32, f32)) -> SteeringContext<'a> {
+    SteeringContext { position: (0.0, 0.0), velocity, elapsed: 0.0, dt: 0.1, queries: scene }
+}
+
+fn seek(target: SteeringTarget) -> Seek {
+    Seek { target, max_speed: 2.0, max_acceleration: 0.0, arrive_radius: 0.0 }
+}
+
+#[test]
+fn test_seek_heads_for_the_target_at_full_speed() {
+    let scene = Scene { target: (3.0, 4.0), wall_x: None };
+    let (vx, vy) = seek(SteeringTarget::Entity(7)).steer(&context(&scene, (0.0, 0.0))).unwrap();
+    assert!((vx - 1.2).abs() < 1e-5 && (vy - 1.6).abs() < 1e-5);
+}
+
+#[test]
+fn test_seek_finds_the_nearest_in_groups_and_stops_without_one() {
+    let scene = Scene { target: (-1.0, 0.0), wall_x: None };
+    let (vx, _) = seek(SteeringTarget::Nearest(2)).steer(&context(&scene, (0.0, 0.0))).unwrap();
+    assert!((vx + 2.0).abs() < 1e-5);
+    let stopped = seek(SteeringTarget::Nearest(4)).steer(&context(&scene, (1.0, 1.0))).unwrap();
+    assert_eq!(stopped, (0.0, 0.0));
+}
+
+#[test]
+fn test_seek_arrives_slowly() {
+    let scene = Scene { target: (0.5, 0.0), wall_x: None };
+    let behavior = Seek { arrive_radius: 1.0, ..seek(SteeringTarget::Entity(7)) };
+    let (vx, _) = behavior.steer(&context(&scene, (0.0, 0.0))).unwrap();
+    assert!((vx - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_acceleration_limits_the_turn() {
+    let (vx, vy) = approach((0.0, 0.0), (10.0, 0.0), 5.0, 0.1);
+    assert!((vx - 0.5).abs() < 1e-5 && vy == 0.0);
+    assert_eq!(approach((0.0, 0.0), (0.2, 0.0), 5.0, 0.1), (0.2, 0.0));
+    assert_eq!(approach((1.0, 0.0), (10.0, 0.0), 0.0, 0.1), (10.0, 0.0));
+}
+
+#[test]
+fn test_flee_only_inside_panic_radius() {
+    let behavior = Flee { target: SteeringTarget::Entity(7), max_speed: 1.0, max_acceleration: 0.0, panic_radius: 2.0 };
+    let near = Scene { target: (1.0, 0.0), wall_x: None };
+    assert_eq!(behavior.steer(&context(&near, (0.0, 0.0))), Some((-1.0, 0.0)));
+    let far = Scene { target: (5.0, 0.0), wall_x: None };
+    assert_eq!(behavior.steer(&context(&far, (0.0, 0.0))), Some((0.0, 0.0)));
+}
+
+#[test]
+fn test_wander_keeps_its_speed_and_turns_smoothly() {
+    let scene = Scene { target: (0.0, 0.0), wall_x: None };
+    let behavior = Wander { speed: 0.5, max_acceleration: 0.0, turn_rate: 1.0, seed: 3 };
+    let (vx, vy) = behavior.steer(&context(&scene, (0.0, 0.0))).unwrap();
+    assert!(((vx * vx + vy * vy).sqrt() - 0.5).abs() < 1e-5);
+    for i in 0..100 {
+        let t = i as f32 * 0.1;
+        assert!((behavior.heading(t + 0.01) - behavior.heading(t)).abs() <= 0.01 + 1e-4);
+    }
+    let other = Wander { seed: 4, ..behavior };
+    assert_ne!(other.heading(0.0), behavior.heading(0.0));
+}
+
+#[test]
+fn test_avoidance_bends_away_from_walls() {
+    let open = Scene { target: (5.0, 0.0), wall_x: None };
+    let walled = Scene { target: (5.0, 0.0), wall_x: Some(0.5) };
+    let behavior = AvoidObstacles { behavior: Box::new(seek(SteeringTarget::Entity(7))), look_ahead: 1.0 };
+    let (vx, vy) = behavior.steer(&context(&open, (0.0, 0.0))).unwrap();
+    assert!((vx - 2.0).abs() < 1e-5 && vy == 0.0);
+    let (vx, vy) = behavior.steer(&context(&walled, (0.0, 0.0))).unwrap();
+    // Same speed, less of it toward the wall
+    assert!(((vx * vx + vy * vy).sqrt() - 2.0).abs() < 1e-4);
+    assert!(vx < 2.0);
+}
+
+#[test]
+fn test_path_strategies_do_not_steer() {
+    let scene = Scene { target: (0.0, 0.0), wall_x: None };
+    let linear = MovementDesc::Linear { velocity: [1.0, 0.0] }.strategy().unwrap();
+    assert_eq!(linear.steer(&context(&scene, (0.0, 0.0))), None);
+}
+
+#[test]
+fn test_steering_descs() {
+    let desc: MovementDesc =
+        serde_json::from_str(r#"{"type": "seek", "targetGroups": 2, "maxSpeed": 1, "avoid": 0.5}"#).unwrap();
+    assert!(desc.validate().is_ok());
+    assert_eq!(desc.strategy().unwrap().name(), "AvoidObstacles");
+    let wander: MovementDesc = serde_json::from_str(r#"{"type": "wander", "speed": 1, "turnRate": 2}"#).unwrap();
+    assert_eq!(wander.strategy().unwrap().name(), "Wander");
+
+    // Seek and flee need someone to react to
+    let untargeted: MovementDesc = serde_json::from_str(r#"{"type": "flee", "maxSpeed": 1}"#).unwrap();
+    assert!(untargeted.validate().is_err());
+    let negative: MovementDesc = serde_json::from_str(r#"{"type": "seek", "target": 7, "maxSpeed": -1}"#).unwrap();
+    assert!(negative.validate().is_err());
+}
@@ -0,0 +1,80 @@
+//! Integration tests for chunk streaming
+
+use physics_core::streaming::{ChunkCoord, ChunkStreamer, StreamFocus, StreamingConfig, MAX_RADIUS};
+
+fn config(load_radius: u32, unload_radius: u32) -> StreamingConfig {
+    StreamingConfig {
+        chunk_size: 1.0,
+        load_radius,
+        unload_radius,
+    }
+}
+
+#[test]
+fn test_chunk_of_floors_negative_positions() {
+    assert_eq!(ChunkCoord::of(0.5, 0.5, 1.0), ChunkCoord { x: 0, y: 0 });
+    assert_eq!(ChunkCoord::of(-0.5, 1.5, 1.0), ChunkCoord { x: -1, y: 1 });
+    assert_eq!(ChunkCoord::of(-2.0, -2.0, 2.0), ChunkCoord { x: -1, y: -1 });
+}
+
+#[test]
+fn test_first_update_activates_load_radius() {
+    let mut streamer = ChunkStreamer::new(config(1, 2));
+    let changes = streamer.update(0.5, 0.5);
+    assert_eq!(changes.activated.len(), 9);
+    assert!(changes.deactivated.is_empty());
+    assert!(streamer.is_position_active(1.5, -0.5));
+    assert!(!streamer.is_position_active(2.5, 0.5));
+}
+
+#[test]
+fn test_hysteresis_keeps_chunks_until_unload_radius() {
+    let mut streamer = ChunkStreamer::new(config(0, 1));
+    streamer.update(0.5, 0.5);
+
+    // One chunk over: the old chunk is within the unload radius and stays active
+    let changes = streamer.update(1.5, 0.5);
+    assert_eq!(changes.activated, vec![ChunkCoord { x: 1, y: 0 }]);
+    assert!(changes.deactivated.is_empty());
+    assert!(streamer.is_active(ChunkCoord { x: 0, y: 0 }));
+
+    // Two chunks away from the origin chunk: now it unloads
+    let changes = streamer.update(2.5, 0.5);
+    assert_eq!(changes.deactivated, vec![ChunkCoord { x: 0, y: 0 }]);
+    assert_eq!(streamer.active_chunk_count(), 2);
+}
+
+#[test]
+fn test_stationary_focus_reports_no_changes() {
+    let mut streamer = ChunkStreamer::new(config(1, 2));
+    streamer.update(0.0, 0.0);
+    assert!(streamer.update(0.2, 0.1).is_empty());
+}
+
+#[test]
+fn test_config_is_sanitized() {
+    let sanitized = StreamingConfig {
+        chunk_size: -1.0,
+        load_radius: 3,
+        unload_radius: 1,
+    }
+    .sanitized();
+    assert_eq!(sanitized.chunk_size, StreamingConfig::default().chunk_size);
+    assert_eq!(sanitized.unload_radius, 3);
+}
+
+#[test]
+fn test_huge_radii_and_far_foci_are_clamped() {
+    let streamer = ChunkStreamer::new(config(u32::MAX, u32::MAX));
+    assert_eq!(streamer.config().load_radius, MAX_RADIUS);
+    assert_eq!(streamer.config().unload_radius, MAX_RADIUS);
+    assert_eq!(config(2, 1).sanitized().unload_radius, 2);
+
+    // Chunks past the edge of the grid saturate instead of overflowing
+    let mut streamer = ChunkStreamer::new(config(2, 2));
+    let changes = streamer.update(f32::MAX, -f32::MAX);
+    assert_eq!(changes.activated.len(), 9);
+    assert!(streamer.is_active(ChunkCoord { x: i32::MAX, y: i32::MIN }));
+
+    assert_eq!(StreamFocus::default(), StreamFocus::Camera);
+}
@@ -0,0 +1,47 @@
+//! Integration tests for the stress scene generator
+
+use physics_core::stress::{generate, StressLayout, AREA_MAX, AREA_MIN};
+
+const LAYOUTS: [StressLayout; 3] = [StressLayout::Pyramid, StressLayout::Stack, StressLayout::Rain];
+
+#[test]
+fn test_generates_requested_count() {
+    for layout in LAYOUTS {
+        for n in [0, 1, 7, 100, 2500] {
+            assert_eq!(generate(n, layout).positions.len(), n as usize, "{:?} n={}", layout, n);
+        }
+    }
+}
+
+#[test]
+fn test_boxes_fit_inside_area() {
+    for layout in LAYOUTS {
+        let scene = generate(1000, layout);
+        let h = scene.half_extent;
+        for &[x, y] in &scene.positions {
+            assert!(x - h >= AREA_MIN[0] - 1e-4 && x + h <= AREA_MAX[0] + 1e-4, "{:?} x={}", layout, x);
+            assert!(y - h >= AREA_MIN[1] - 1e-4 && y + h <= AREA_MAX[1] + 1e-4, "{:?} y={}", layout, y);
+        }
+    }
+}
+
+#[test]
+fn test_boxes_do_not_overlap() {
+    for layout in LAYOUTS {
+        let scene = generate(500, layout);
+        let size = scene.half_extent * 2.0;
+        for (i, a) in scene.positions.iter().enumerate() {
+            for b in &scene.positions[i + 1..] {
+                let overlaps = (a[0] - b[0]).abs() < size - 1e-5 && (a[1] - b[1]).abs() < size - 1e-5;
+                assert!(!overlaps, "{:?}: {:?} overlaps {:?}", layout, a, b);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_layout_codes() {
+    assert_eq!(StressLayout::from_i32(0), Some(StressLayout::Pyramid));
+    assert_eq!(StressLayout::from_i32(2), Some(StressLayout::Rain));
+    assert_eq!(StressLayout::from_i32(3), None);
+}
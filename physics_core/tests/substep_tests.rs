@@ -0,0 +1,37 @@
+//! Integration tests for physics substepping
+
+use physics_core::substep::{self, SubstepConfig};
+
+#[test]
+fn test_slow_bodies_use_base_substeps() {
+    let config = SubstepConfig { substeps: 2, ..Default::default() };
+    assert_eq!(config.substeps_for(0.0), 2);
+    assert_eq!(config.substeps_for(config.velocity_threshold), 2);
+}
+
+#[test]
+fn test_auto_scales_with_speed_up_to_max() {
+    let config = SubstepConfig::default();
+    // Twice the threshold needs twice the substeps
+    assert_eq!(config.substeps_for(config.velocity_threshold * 2.0), 2);
+    assert_eq!(config.substeps_for(config.velocity_threshold * 2.5), 3);
+    assert_eq!(config.substeps_for(1000.0), config.max_substeps);
+    assert_eq!(config.substeps_for(f32::INFINITY), config.substeps);
+}
+
+#[test]
+fn test_fixed_mode_ignores_speed() {
+    let config = SubstepConfig { substeps: 4, auto: false, ..Default::default() };
+    assert_eq!(config.substeps_for(1000.0), 4);
+}
+
+#[test]
+fn test_sanitized_clamps_counts_and_threshold() {
+    let config = SubstepConfig { substeps: 0, auto: true, velocity_threshold: -1.0, max_substeps: 100 }.sanitized();
+    assert_eq!(config.substeps, 1);
+    assert_eq!(config.max_substeps, substep::MAX_SUBSTEPS);
+    assert_eq!(config.velocity_threshold, SubstepConfig::default().velocity_threshold);
+
+    let config = SubstepConfig { substeps: 6, max_substeps: 2, ..Default::default() }.sanitized();
+    assert_eq!(config.max_substeps, 6);
+}
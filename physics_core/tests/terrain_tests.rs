@@ -0,0 +1,62 @@
+//! Integration tests for terrain generation and its fill mesh
+
+use physics_core::level_geometry::{self, LevelDesc, LevelShape};
+use physics_core::terrain::{Terrain, TerrainNoise};
+
+fn noise(seed: u32) -> TerrainNoise {
+    TerrainNoise { seed, samples: 200, ..TerrainNoise::default() }
+}
+
+#[test]
+fn test_noise_is_seeded_and_bounded() {
+    let terrain = Terrain { noise: Some(noise(7)), ..Terrain::default() };
+    let heights = terrain.heights();
+    assert_eq!(heights.len(), 200);
+    assert_eq!(heights, terrain.heights());
+    assert_ne!(heights, Terrain { noise: Some(noise(8)), ..Terrain::default() }.heights());
+
+    let noise = noise(7);
+    assert!(heights.iter().all(|h| (h - noise.base).abs() <= noise.amplitude + 1e-5));
+    // Rolling hills, not a flat line
+    let (low, high) = heights.iter().fold((f32::MAX, f32::MIN), |(low, high), &h| (low.min(h), high.max(h)));
+    assert!(high - low > noise.amplitude * 0.25);
+    // Neighbouring samples stay close for a smooth surface
+    assert!(heights.windows(2).all(|pair| (pair[1] - pair[0]).abs() < noise.amplitude));
+}
+
+#[test]
+fn test_invalid_terrain_is_rejected() {
+    assert!(Terrain::default().validate().is_err());
+    assert!(Terrain { heights: vec![0.0, 0.1], ..Terrain::default() }.validate().is_ok());
+    assert!(Terrain { heights: vec![0.0, f32::NAN], ..Terrain::default() }.validate().is_err());
+    assert!(Terrain { heights: vec![0.0, 0.1], spacing: 0.0, ..Terrain::default() }.validate().is_err());
+    assert!(Terrain { noise: Some(TerrainNoise { samples: 1, ..noise(1) }), ..Terrain::default() }.validate().is_err());
+    assert!(Terrain { noise: Some(TerrainNoise { octaves: 0, ..noise(1) }), ..Terrain::default() }.validate().is_err());
+    assert!(Terrain { noise: Some(TerrainNoise { wavelength: -1.0, ..noise(1) }), ..Terrain::default() }.validate().is_err());
+}
+
+#[test]
+fn test_fill_reaches_down_to_bottom() {
+    let terrain = Terrain { origin_x: -1.0, spacing: 0.5, heights: vec![0.0, 0.2, 0.1], bottom: -1.0, ..Terrain::default() };
+    let vertices = terrain.vertices();
+    assert_eq!(vertices.len(), 2 * 6);
+    assert!(vertices.iter().all(|v| v.color == terrain.color));
+    let xs: Vec<f32> = vertices.iter().map(|v| v.position[0]).collect();
+    assert_eq!(xs.iter().cloned().fold(f32::MAX, f32::min), -1.0);
+    assert_eq!(xs.iter().cloned().fold(f32::MIN, f32::max), 0.0);
+    assert_eq!(vertices.iter().filter(|v| v.position[1] == -1.0).count(), 6);
+    assert!(vertices.iter().any(|v| v.position == [-0.5, 0.2]));
+}
+
+#[test]
+fn test_terrain_from_level_json() {
+    let desc: LevelDesc = serde_json::from_str(
+        r#"{"shapes": [{"type": "terrain", "spacing": 0.02, "noise": {"seed": 3, "samples": 101}}]}"#,
+    )
+    .unwrap();
+    let LevelShape::Terrain(terrain) = &desc.shapes[0] else {
+        panic!("expected terrain");
+    };
+    assert_eq!(terrain.points().last().unwrap()[0], -1.0 + 100.0 * 0.02);
+    assert_eq!(level_geometry::build_colliders(&desc).unwrap().len(), 1);
+}
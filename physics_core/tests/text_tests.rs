@@ -0,0 +1,80 @@
+//! Integration tests for text layout and the label layer
+
+use physics_core::text::{self, TextAnchor, TextLabel, TextLayer};
+
+const WHITE: [f32; 4] = [1.0; 4];
+
+fn label(text: &str) -> TextLabel {
+    TextLabel::new(text, TextAnchor::Screen, [0.0, 0.0], 16.0, WHITE).unwrap()
+}
+
+#[test]
+fn test_atlas_holds_every_printable_glyph() {
+    let pixels = text::atlas_pixels();
+    assert_eq!(pixels.len(), (text::ATLAS_WIDTH * text::ATLAS_HEIGHT) as usize);
+
+    let coverage = |c: char| {
+        let [u0, v0, ..] = text::glyph_uv(c);
+        let (x0, y0) = ((u0 * text::ATLAS_WIDTH as f32) as u32, (v0 * text::ATLAS_HEIGHT as f32) as u32);
+        (0..text::GLYPH_PIXELS)
+            .flat_map(|y| (0..text::GLYPH_PIXELS).map(move |x| (x0 + x, y0 + y)))
+            .filter(|&(x, y)| pixels[(y * text::ATLAS_WIDTH + x) as usize] > 0)
+            .count()
+    };
+    assert_eq!(coverage(' '), 0);
+    assert!(('!'..='~').all(|c| coverage(c) > 0));
+}
+
+#[test]
+fn test_unsupported_characters_use_the_question_mark() {
+    assert_eq!(text::glyph_uv('é'), text::glyph_uv('?'));
+    assert_eq!(text::glyph_uv('\t'), text::glyph_uv('?'));
+    assert_ne!(text::glyph_uv('A'), text::glyph_uv('B'));
+}
+
+#[test]
+fn test_layout_places_glyphs_on_a_monospaced_grid() {
+    let quads = text::layout("Hi 7\nok");
+    let origins: Vec<[f32; 2]> = quads.iter().map(|quad| quad.min).collect();
+    assert_eq!(origins, vec![[0.0, 0.0], [1.0, 0.0], [3.0, 0.0], [0.0, 1.0], [1.0, 1.0]]);
+    assert!(quads.iter().all(|quad| quad.max == [quad.min[0] + 1.0, quad.min[1] + 1.0]));
+    assert_eq!(text::measure("Hi 7\nok"), [4.0, 2.0]);
+    assert_eq!(text::measure(""), [0.0, 1.0]);
+}
+
+#[test]
+fn test_labels_are_validated_and_trimmed() {
+    assert!(TextLabel::new("x", TextAnchor::World, [f32::NAN, 0.0], 1.0, WHITE).is_none());
+    assert!(TextLabel::new("x", TextAnchor::World, [0.0, 0.0], 0.0, WHITE).is_none());
+
+    let long = "a".repeat(text::MAX_CHARS + 10);
+    let trimmed = TextLabel::new(&long, TextAnchor::Body(7), [0.0, 0.5], 0.2, [2.0, -1.0, 0.5, f32::NAN]).unwrap();
+    assert_eq!(trimmed.text.chars().count(), text::MAX_CHARS);
+    assert_eq!(trimmed.color, [1.0, 0.0, 0.5, 1.0]);
+}
+
+#[test]
+fn test_layer_replaces_by_id_and_caps_new_labels() {
+    let mut layer = TextLayer::new();
+    assert!(layer.set(1, label("Score: 0")));
+    assert!(layer.set(1, label("Score: 10")));
+    assert_eq!(layer.len(), 1);
+    assert_eq!(layer.get(1).unwrap().text, "Score: 10");
+
+    for id in 2..=text::MAX_LABELS as u64 {
+        assert!(layer.set(id, label("x")));
+    }
+    assert!(!layer.set(u64::MAX, label("one too many")));
+    assert!(layer.set(1, label("still replaceable")));
+
+    assert!(layer.remove(1));
+    assert!(!layer.remove(1));
+    layer.clear();
+    assert!(layer.is_empty());
+}
+
+#[test]
+fn test_colors_unpack_from_rgba_hex() {
+    assert_eq!(text::unpack_rgba(0xFF00_80FF), [1.0, 0.0, 128.0 / 255.0, 1.0]);
+    assert_eq!(text::unpack_rgba(0), [0.0; 4]);
+}
@@ -0,0 +1,28 @@
+//! Integration tests for mip chain sizes and material sampler settings
+
+use physics_core::texture_sampling::{mip_level_count, TextureFilter, TextureSampling, TextureWrap};
+
+#[test]
+fn test_mip_chains_go_down_to_one_pixel() {
+    assert_eq!(mip_level_count(256, 256), 9);
+    // 300, 150, 75, 37, 18, 9, 4, 2, 1
+    assert_eq!(mip_level_count(300, 20), 9);
+    assert_eq!(mip_level_count(1, 1), 1);
+    assert_eq!(mip_level_count(0, 0), 1);
+}
+
+#[test]
+fn test_sampling_codes_map_to_sampler_modes() {
+    let default = TextureSampling::default().descriptor();
+    assert_eq!((default.min_filter, default.mipmap_filter), (wgpu::FilterMode::Linear, wgpu::FilterMode::Linear));
+    assert_eq!(default.address_mode_u, wgpu::AddressMode::ClampToEdge);
+
+    let tiled = TextureSampling::from_codes(0, 0, 1, 2).unwrap();
+    assert_eq!(tiled, TextureSampling { wrap_u: TextureWrap::Repeat, wrap_v: TextureWrap::Mirror, ..TextureSampling::PIXEL_ART });
+    let descriptor = tiled.descriptor();
+    assert_eq!((descriptor.mag_filter, descriptor.min_filter), (wgpu::FilterMode::Nearest, wgpu::FilterMode::Nearest));
+    assert_eq!((descriptor.address_mode_u, descriptor.address_mode_v), (wgpu::AddressMode::Repeat, wgpu::AddressMode::MirrorRepeat));
+
+    assert_eq!(TextureFilter::from_i32(2), None);
+    assert!(TextureSampling::from_codes(1, 1, 0, 3).is_none());
+}
@@ -0,0 +1,61 @@
+//! Integration tests for drawn and editable tile grids
+
+use physics_core::tilemap::Tilemap;
+
+fn grid(cells: Vec<u8>) -> Tilemap {
+    Tilemap {
+        origin: [-1.0, 1.0],
+        tile_size: 0.5,
+        columns: 2,
+        cells,
+        material_id: 0,
+        atlas_columns: 2,
+        atlas_rows: 2,
+    }
+}
+
+#[test]
+fn test_quads_sit_on_their_cells_with_atlas_uvs() {
+    let quads = grid(vec![1, 0, 0, 4]).quads();
+    assert_eq!(quads.len(), 2);
+    // Cell (0, 0): top-left, so its bottom-left corner is one tile below the origin
+    assert_eq!(quads[0].corner, [-1.0, 0.5]);
+    assert_eq!(quads[0].uv_offset, [0.0, 0.0]);
+    assert_eq!(quads[0].uv_scale, [0.5, 0.5]);
+    // Cell (1, 1) with tile 4: last atlas cell
+    assert_eq!(quads[1].corner, [-0.5, 0.0]);
+    assert_eq!(quads[1].uv_offset, [0.5, 0.5]);
+}
+
+#[test]
+fn test_set_reports_shape_changes_only() {
+    let mut tiles = grid(vec![1, 0, 0, 0]);
+    assert!(!tiles.set(0, 0, 2));
+    assert!(tiles.set(0, 0, 0));
+    assert!(tiles.set(1, 1, 3));
+    assert!(!tiles.set(5, 0, 1));
+    assert_eq!(tiles.get(1, 1), Some(3));
+    assert_eq!(tiles.get(0, 2), None);
+}
+
+#[test]
+fn test_cell_at_maps_world_points() {
+    let tiles = grid(vec![0; 4]);
+    assert_eq!(tiles.cell_at(-0.9, 0.9), Some((0, 0)));
+    assert_eq!(tiles.cell_at(-0.1, 0.1), Some((1, 1)));
+    assert_eq!(tiles.cell_at(0.1, 0.9), None);
+    assert_eq!(tiles.cell_at(-0.9, 1.1), None);
+}
+
+#[test]
+fn test_empty_grid_has_no_collider() {
+    assert!(grid(vec![0; 4]).collider().is_none());
+    assert!(grid(vec![0, 1, 0, 0]).collider().is_some());
+}
+
+#[test]
+fn test_validate_rejects_bad_atlas_and_ragged_rows() {
+    assert!(grid(vec![1; 4]).validate().is_ok());
+    assert!(grid(vec![1; 3]).validate().is_err());
+    assert!(Tilemap { atlas_rows: 0, ..grid(vec![1; 4]) }.validate().is_err());
+}
@@ -0,0 +1,48 @@
+//! Integration tests for accelerometer-driven gravity
+
+use physics_core::tilt::{device_to_screen, TiltGravity, STANDARD_GRAVITY};
+
+fn approx(a: [f32; 3], b: [f32; 3]) -> bool {
+    a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-4)
+}
+
+#[test]
+fn test_upright_phone_pulls_down() {
+    let mut tilt = TiltGravity::new(1.0, 1.0);
+    let gravity = tilt.update([0.0, STANDARD_GRAVITY, 0.0], 0);
+    assert!(approx(gravity, [0.0, -STANDARD_GRAVITY, 0.0]), "{:?}", gravity);
+}
+
+#[test]
+fn test_display_rotation_remaps_axes() {
+    let accel = [1.0, 2.0, 3.0];
+    assert_eq!(device_to_screen(accel, 0), [1.0, 2.0, 3.0]);
+    assert_eq!(device_to_screen(accel, 90), [-2.0, 1.0, 3.0]);
+    assert_eq!(device_to_screen(accel, 180), [-1.0, -2.0, 3.0]);
+    assert_eq!(device_to_screen(accel, 270), [2.0, -1.0, 3.0]);
+    assert_eq!(device_to_screen(accel, -90), device_to_screen(accel, 270));
+}
+
+#[test]
+fn test_landscape_phone_still_pulls_down_on_screen() {
+    // Rotated a quarter turn counter-clockwise: the device's +x now points up
+    let mut tilt = TiltGravity::new(1.0, 1.0);
+    let gravity = tilt.update([STANDARD_GRAVITY, 0.0, 0.0], 90);
+    assert!(approx(gravity, [0.0, -STANDARD_GRAVITY, 0.0]), "{:?}", gravity);
+}
+
+#[test]
+fn test_smoothing_eases_towards_new_sample() {
+    let mut tilt = TiltGravity::new(1.0, 0.5);
+    tilt.update([0.0, 10.0, 0.0], 0);
+    let gravity = tilt.update([10.0, 10.0, 0.0], 0);
+    assert!(approx(gravity, [-5.0, -10.0, 0.0]), "{:?}", gravity);
+}
+
+#[test]
+fn test_non_finite_samples_are_ignored() {
+    let mut tilt = TiltGravity::new(2.0, 1.0);
+    tilt.update([0.0, 5.0, 0.0], 0);
+    let gravity = tilt.update([f32::NAN, 0.0, 0.0], 0);
+    assert!(approx(gravity, [0.0, -10.0, 0.0]), "{:?}", gravity);
+}
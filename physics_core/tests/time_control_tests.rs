@@ -0,0 +1,86 @@
+//! Integration tests for the simulation clock and time-scale ramps
+
+use physics_core::time_control::{Time, TimeScaleRamp};
+
+#[test]
+fn test_ramp_reaches_target() {
+    let mut ramp = TimeScaleRamp::new(1.0, 0.2, 0.5);
+    assert!(!ramp.is_done());
+    for _ in 0..10 {
+        ramp.advance(0.1);
+    }
+    assert!(ramp.is_done());
+    assert_eq!(ramp.value(), 0.2);
+}
+
+#[test]
+fn test_ramp_is_monotonic_and_eased() {
+    let mut ramp = TimeScaleRamp::new(0.0, 1.0, 1.0);
+    let mut previous = ramp.value();
+    let first = ramp.advance(0.1);
+    assert!(first < 0.1, "smoothstep starts slower than linear: {}", first);
+    for _ in 0..9 {
+        let value = ramp.advance(0.1);
+        assert!(value >= previous);
+        previous = value;
+    }
+}
+
+#[test]
+fn test_ramp_midpoint_is_halfway() {
+    let mut ramp = TimeScaleRamp::new(2.0, 4.0, 1.0);
+    assert!((ramp.advance(0.5) - 3.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_zero_duration_jumps() {
+    let ramp = TimeScaleRamp::new(1.0, 3.0, 0.0);
+    assert!(ramp.is_done());
+    assert_eq!(ramp.value(), 3.0);
+}
+
+#[test]
+fn test_clock_counts_frames_and_steps() {
+    let mut time = Time::default();
+    time.begin_frame(0.02, 0.5, false);
+    time.begin_step(0.01);
+    assert_eq!(time.delta, 0.01);
+    time.begin_frame(0.02, 0.5, false);
+    assert_eq!(time.delta, 0.0);
+    time.begin_step(0.01);
+    assert!((time.elapsed - 0.02).abs() < 1e-6);
+    assert!((time.real_elapsed - 0.04).abs() < 1e-6);
+    assert_eq!((time.frames, time.steps), (2, 2));
+}
+
+#[test]
+fn test_paused_frames_only_advance_real_time() {
+    let mut time = Time::default();
+    time.begin_frame(0.1, 1.0, true);
+    time.begin_frame(f32::NAN, 1.0, true);
+    time.begin_frame(-1.0, 1.0, true);
+    assert!(time.paused);
+    assert_eq!((time.delta, time.elapsed, time.steps), (0.0, 0.0, 0));
+    assert!((time.real_elapsed - 0.1).abs() < 1e-6);
+}
+
+#[test]
+fn test_idle_run_animates_without_a_step() {
+    let mut time = Time::default();
+    time.begin_frame(0.1, 0.5, true);
+    time.begin_idle(0.05);
+    assert!(!time.stepping);
+    assert_eq!((time.delta, time.animation_delta), (0.0, 0.05));
+    assert_eq!((time.elapsed, time.steps), (0.0, 0));
+}
+
+#[test]
+fn test_paused_animation_gets_no_time_in_steps_or_idle_runs() {
+    let mut time = Time { animation_paused: true, ..Time::default() };
+    time.begin_frame(0.02, 1.0, false);
+    time.begin_step(0.02);
+    assert!(time.stepping);
+    assert_eq!((time.delta, time.animation_delta), (0.02, 0.0));
+    time.begin_idle(0.02);
+    assert_eq!(time.animation_delta, 0.0);
+}
@@ -0,0 +1,46 @@
+//! Integration tests for slow-motion zones
+
+use physics_core::time_dilation::{DilationZone, TimeDilationConfig, MIN_SCALE};
+
+fn zone(min: [f32; 2], max: [f32; 2], scale: f32) -> DilationZone {
+    DilationZone { min, max, scale }
+}
+
+#[test]
+fn test_scale_is_one_outside_every_zone() {
+    let config = TimeDilationConfig { zones: vec![zone([0.0, 0.0], [1.0, 1.0], 0.5)] };
+    assert_eq!(config.scale_at([0.5, 0.5]), 0.5);
+    assert_eq!(config.scale_at([1.0, 1.0]), 0.5);
+    assert_eq!(config.scale_at([1.5, 0.5]), 1.0);
+    assert_eq!(TimeDilationConfig::default().scale_at([0.0, 0.0]), 1.0);
+}
+
+#[test]
+fn test_slowest_overlapping_zone_wins() {
+    let config = TimeDilationConfig {
+        zones: vec![zone([-1.0, -1.0], [1.0, 1.0], 0.5), zone([0.0, 0.0], [2.0, 2.0], 0.1)],
+    };
+    assert_eq!(config.scale_at([-0.5, -0.5]), 0.5);
+    assert_eq!(config.scale_at([0.5, 0.5]), 0.1);
+    assert_eq!(config.scale_at([1.5, 1.5]), 0.1);
+}
+
+#[test]
+fn test_sanitized_orders_corners_and_clamps_scale() {
+    let config = TimeDilationConfig {
+        zones: vec![zone([1.0, 2.0], [-1.0, -2.0], 0.0), zone([0.0, 0.0], [1.0, 1.0], 3.0), zone([0.0, 0.0], [1.0, 1.0], f32::NAN)],
+    }
+    .sanitized();
+    assert_eq!(config.zones[0], zone([-1.0, -2.0], [1.0, 2.0], MIN_SCALE));
+    assert_eq!(config.zones[1].scale, 1.0);
+    assert_eq!(config.zones[2].scale, 1.0);
+}
+
+#[test]
+fn test_missing_json_fields_take_defaults() {
+    let config: TimeDilationConfig = serde_json::from_str(r#"{"zones": [{"scale": 0.5}, {}]}"#).unwrap();
+    assert_eq!(config.zones.len(), 2);
+    assert_eq!(config.zones[0], DilationZone { scale: 0.5, ..DilationZone::default() });
+    assert_eq!(config.zones[1], DilationZone::default());
+    assert!(serde_json::from_str::<TimeDilationConfig>("{}").unwrap().is_empty());
+}
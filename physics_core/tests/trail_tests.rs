@@ -0,0 +1,61 @@
+//! Integration tests for the trail point ring and ribbon mesh
+
+use physics_core::trail::{ribbon_vertices, PointRing};
+
+#[test]
+fn test_ring_keeps_the_newest_points_oldest_first() {
+    let mut ring = PointRing::new(3);
+    assert!(ring.is_empty());
+    assert_eq!(ring.last(), None);
+    for i in 0..5 {
+        ring.push([i as f32, 0.0]);
+    }
+    assert_eq!(ring.len(), 3);
+    assert_eq!(ring.to_vec(), vec![[2.0, 0.0], [3.0, 0.0], [4.0, 0.0]]);
+    assert_eq!(ring.last(), Some([4.0, 0.0]));
+}
+
+#[test]
+fn test_ring_before_wrapping_and_after_clear() {
+    let mut ring = PointRing::new(4);
+    ring.push([1.0, 1.0]);
+    ring.push([2.0, 2.0]);
+    assert_eq!(ring.to_vec(), vec![[1.0, 1.0], [2.0, 2.0]]);
+    assert_eq!(ring.last(), Some([2.0, 2.0]));
+    ring.clear();
+    assert!(ring.is_empty());
+    assert_eq!(ring.capacity(), 4);
+}
+
+#[test]
+fn test_zero_capacity_ring_stays_empty() {
+    let mut ring = PointRing::new(0);
+    ring.push([1.0, 1.0]);
+    assert!(ring.is_empty());
+    assert_eq!(ring.last(), None);
+}
+
+#[test]
+fn test_ribbon_has_two_triangles_per_segment_around_the_line() {
+    let vertices = ribbon_vertices(&[[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]], [1.0, 1.0, 1.0, 1.0], 0.2);
+    assert_eq!(vertices.len(), 12);
+    for vertex in &vertices {
+        assert!((vertex.position[1].abs() - 0.1).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_ribbon_fades_from_the_oldest_point() {
+    let color = [0.2, 0.4, 0.6, 0.8];
+    let vertices = ribbon_vertices(&[[0.0, 0.0], [0.0, 1.0], [0.0, 2.0]], color, 0.1);
+    assert_eq!(vertices[0].color, [0.2, 0.4, 0.6, 0.0]);
+    assert_eq!(vertices[2].color, [0.2, 0.4, 0.6, 0.4]);
+    assert_eq!(vertices.last().unwrap().color, color);
+}
+
+#[test]
+fn test_ribbon_skips_repeated_points_and_short_lines() {
+    assert!(ribbon_vertices(&[[0.0, 0.0]], [1.0; 4], 0.1).is_empty());
+    assert!(ribbon_vertices(&[], [1.0; 4], 0.1).is_empty());
+    assert_eq!(ribbon_vertices(&[[0.0, 0.0], [0.0, 0.0], [1.0, 0.0]], [1.0; 4], 0.1).len(), 6);
+}
@@ -0,0 +1,40 @@
+//! Integration tests for the dotted trajectory preview mesh
+
+use physics_core::trajectory::dot_vertices;
+
+fn line(n: usize) -> Vec<[f32; 2]> {
+    (0..n).map(|i| [i as f32, 0.0]).collect()
+}
+
+#[test]
+fn test_every_nth_point_gets_a_square() {
+    let vertices = dot_vertices(&line(7), [1.0; 4], 0.2, 3);
+    // Points 0, 3 and 6
+    assert_eq!(vertices.len(), 18);
+    let xs: Vec<f32> = vertices.chunks(6).map(|dot| dot.iter().map(|v| v.position[0]).sum::<f32>() / 6.0).collect();
+    assert_eq!(xs.len(), 3);
+    assert!((xs[1] - 3.0).abs() < 0.1);
+    for vertex in &vertices[..6] {
+        assert!((vertex.position[0].abs() - 0.1).abs() < 1e-6 && (vertex.position[1].abs() - 0.1).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_dots_fade_toward_the_end() {
+    let color = [0.5, 0.5, 0.5, 0.8];
+    let vertices = dot_vertices(&line(5), color, 0.1, 4);
+    assert_eq!(vertices[0].color, color);
+    let last = vertices.last().unwrap().color;
+    assert!((last[3] - 0.2).abs() < 1e-6);
+    assert_eq!(&last[..3], &color[..3]);
+}
+
+#[test]
+fn test_degenerate_inputs() {
+    assert!(dot_vertices(&[], [1.0; 4], 0.1, 3).is_empty());
+    // A single point is drawn at full alpha; spacing 0 behaves like 1
+    let single = dot_vertices(&[[1.0, 2.0]], [1.0; 4], 0.1, 0);
+    assert_eq!(single.len(), 6);
+    assert_eq!(single[0].color[3], 1.0);
+    assert_eq!(dot_vertices(&line(3), [1.0; 4], 0.1, 0).len(), 18);
+}
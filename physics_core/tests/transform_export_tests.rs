@@ -0,0 +1,72 @@
+//! Integration tests for the shared transform region
+
+use physics_core::transform_export::{ExportedTransform, TransformExport, BLOCK_SIZE, MAX_CAPACITY};
+
+fn transform(entity_id: u64, x: f32) -> ExportedTransform {
+    ExportedTransform { entity_id, x, y: 1.0, rotation: 0.5, vx: -1.0, vy: 2.0, groups: 3 }
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+#[test]
+fn test_capacity_limits() {
+    assert!(TransformExport::new(0).is_none());
+    assert!(TransformExport::new(MAX_CAPACITY + 1).is_none());
+    let export = TransformExport::new(4).unwrap();
+    assert_eq!(export.len(), BLOCK_SIZE * (1 + 2 * 5));
+    assert_eq!(export.as_ptr() as usize % 8, 0);
+}
+
+#[test]
+fn test_publish_flips_buffers_in_place() {
+    let mut export = TransformExport::new(4).unwrap();
+    let ptr = export.as_ptr();
+    assert_eq!(export.latest(), (0, 0, &[][..]));
+
+    assert_eq!(export.publish(&[transform(7, 1.0)]), 1);
+    let first = export.front();
+    assert_eq!(export.latest(), (1, 1, &[transform(7, 1.0)][..]));
+
+    export.publish(&[transform(7, 2.0), transform(8, 3.0)]);
+    assert_ne!(export.front(), first);
+    assert_eq!(export.latest(), (2, 2, &[transform(7, 2.0), transform(8, 3.0)][..]));
+    // Hosts keep mapping the same allocation
+    assert_eq!(export.as_ptr(), ptr);
+}
+
+#[test]
+fn test_overflow_is_counted_but_not_written() {
+    let mut export = TransformExport::new(2).unwrap();
+    let transforms: Vec<_> = (1..=5).map(|id| transform(id, id as f32)).collect();
+    export.publish(&transforms);
+    let (_, total, records) = export.latest();
+    assert_eq!(total, 5);
+    assert_eq!(records, &transforms[..2]);
+}
+
+#[test]
+fn test_byte_layout() {
+    let mut export = TransformExport::new(2).unwrap();
+    export.publish(&[transform(0x0102_0304, 4.0)]);
+    let bytes = export.as_bytes();
+    let front = u32_at(bytes, 0) as usize;
+    assert_eq!(front, export.front());
+    assert_eq!(u32_at(bytes, 4), 2);
+    assert_eq!(u32_at(bytes, 8), BLOCK_SIZE as u32);
+
+    let buffer = BLOCK_SIZE + front * BLOCK_SIZE * 3;
+    assert_eq!(u64_at(bytes, buffer), 1);
+    assert_eq!(u32_at(bytes, buffer + 8), 1);
+    assert_eq!(u32_at(bytes, buffer + 12), 1);
+    let record = buffer + BLOCK_SIZE;
+    assert_eq!(u64_at(bytes, record), 0x0102_0304);
+    assert_eq!(f32::from_bits(u32_at(bytes, record + 8)), 4.0);
+    assert_eq!(f32::from_bits(u32_at(bytes, record + 16)), 0.5);
+    assert_eq!(u32_at(bytes, record + 28), 3);
+}
@@ -0,0 +1,100 @@
+//! Integration tests for trigger zone events and the level outcome
+
+use physics_core::triggers::{Outcome, TriggerKind, TriggerZone, Triggers, MAX_QUEUED};
+
+fn zone(kind: TriggerKind, mask: u32) -> TriggerZone {
+    TriggerZone { name: "zone".into(), kind, min: [0.0, 0.0], max: [1.0, 1.0], mask }
+}
+
+fn triggers(zones: Vec<TriggerZone>) -> Triggers {
+    let mut triggers = Triggers::default();
+    triggers.set_zones(zones);
+    triggers
+}
+
+#[test]
+fn test_enter_and_exit_are_reported_once() {
+    let mut triggers = triggers(vec![zone(TriggerKind::Sensor, u32::MAX)]);
+    triggers.update(&[(7, [-1.0, 0.5], 1)]);
+    assert!(triggers.drain(usize::MAX).is_empty());
+
+    triggers.update(&[(7, [0.5, 0.5], 1)]);
+    triggers.update(&[(7, [0.6, 0.5], 1)]);
+    let events = triggers.drain(usize::MAX);
+    assert_eq!(events.len(), 1);
+    assert!(events[0].entered && events[0].entity == 7 && events[0].zone == 0);
+
+    triggers.update(&[(7, [2.0, 0.5], 1)]);
+    let events = triggers.drain(usize::MAX);
+    assert_eq!(events.len(), 1);
+    assert!(!events[0].entered);
+}
+
+#[test]
+fn test_removed_bodies_leave_the_zone() {
+    let mut triggers = triggers(vec![zone(TriggerKind::Sensor, u32::MAX)]);
+    triggers.update(&[(3, [0.5, 0.5], 1), (4, [0.5, 0.5], 1)]);
+    assert_eq!(triggers.drain(usize::MAX).len(), 2);
+    triggers.update(&[(4, [0.5, 0.5], 1)]);
+    let events = triggers.drain(usize::MAX);
+    assert_eq!(events.len(), 1);
+    assert!(events[0].entity == 3 && !events[0].entered);
+}
+
+#[test]
+fn test_mask_selects_the_watched_bodies() {
+    let mut triggers = triggers(vec![zone(TriggerKind::Sensor, 0b10)]);
+    triggers.update(&[(1, [0.5, 0.5], 0b01), (2, [0.5, 0.5], 0b11)]);
+    let events = triggers.drain(usize::MAX);
+    assert_eq!(events.iter().map(|e| e.entity).collect::<Vec<_>>(), vec![2]);
+}
+
+#[test]
+fn test_first_goal_or_hazard_decides_the_level() {
+    let goal = zone(TriggerKind::Goal, u32::MAX);
+    let hazard = TriggerZone { min: [2.0, 0.0], max: [3.0, 1.0], ..zone(TriggerKind::Hazard, u32::MAX) };
+    let mut triggers = triggers(vec![goal, hazard]);
+    assert_eq!(triggers.outcome(), Outcome::Playing);
+    triggers.update(&[(1, [0.5, 0.5], 1)]);
+    assert_eq!(triggers.outcome(), Outcome::Won);
+    // Already decided
+    triggers.update(&[(1, [2.5, 0.5], 1)]);
+    assert_eq!(triggers.outcome(), Outcome::Won);
+    let kinds: Vec<u32> = triggers.drain(usize::MAX).iter().map(|e| e.kind).collect();
+    assert_eq!(kinds, vec![TriggerKind::Goal as u32, TriggerKind::Goal as u32, TriggerKind::Hazard as u32]);
+
+    // A new level starts over
+    triggers.set_zones(vec![zone(TriggerKind::Hazard, u32::MAX)]);
+    assert_eq!(triggers.outcome(), Outcome::Playing);
+    triggers.update(&[(1, [0.5, 0.5], 1)]);
+    assert_eq!(triggers.outcome(), Outcome::Lost);
+}
+
+#[test]
+fn test_validate_rejects_empty_and_non_finite_zones() {
+    assert!(zone(TriggerKind::Sensor, 1).validate().is_ok());
+    assert!(TriggerZone { max: [0.0, 1.0], ..zone(TriggerKind::Sensor, 1) }.validate().is_err());
+    assert!(TriggerZone { min: [f32::NAN, 0.0], ..zone(TriggerKind::Sensor, 1) }.validate().is_err());
+    assert!(TriggerZone::default().validate().is_err());
+}
+
+#[test]
+fn test_queue_drops_the_oldest_when_full() {
+    let mut triggers = triggers(vec![zone(TriggerKind::Sensor, u32::MAX)]);
+    let inside: Vec<(u64, [f32; 2], u32)> = (0..MAX_QUEUED as u64 + 2).map(|id| (id, [0.5, 0.5], 1)).collect();
+    triggers.update(&inside);
+    assert_eq!(triggers.dropped(), 2);
+    assert_eq!(triggers.drain(1)[0].entity, 2);
+}
+
+#[test]
+fn test_zones_parse_from_level_json() {
+    let desc: physics_core::level_geometry::LevelDesc = serde_json::from_str(
+        r#"{"shapes": [], "triggers": [{"name": "exit", "kind": "goal", "min": [0, 0], "max": [1, 1], "mask": 2}, {"min": [0, 0], "max": [1, 1]}]}"#,
+    )
+    .unwrap();
+    assert_eq!(desc.triggers[0].kind, TriggerKind::Goal);
+    assert_eq!(desc.triggers[0].mask, 2);
+    assert_eq!(desc.triggers[1].kind, TriggerKind::Sensor);
+    assert_eq!(desc.triggers[1].mask, u32::MAX);
+}
@@ -0,0 +1,141 @@
+//! Integration tests for easing curves and tween bookkeeping
+
+use physics_core::scenes::SceneDesc;
+use physics_core::tween::{Appearance, Easing, Tween, TweenProperty, Tweens, MAX_TWEENS};
+
+const ALL_EASINGS: [Easing; 11] = [
+    Easing::Linear,
+    Easing::QuadIn,
+    Easing::QuadOut,
+    Easing::QuadInOut,
+    Easing::CubicIn,
+    Easing::CubicOut,
+    Easing::CubicInOut,
+    Easing::SineInOut,
+    Easing::BackOut,
+    Easing::ElasticOut,
+    Easing::BounceOut,
+];
+
+#[test]
+fn test_every_easing_starts_at_zero_and_ends_at_one() {
+    for easing in ALL_EASINGS {
+        assert!(easing.apply(0.0).abs() < 1e-5, "{:?}", easing);
+        assert!((easing.apply(1.0) - 1.0).abs() < 1e-5, "{:?}", easing);
+        // Progress outside 0..=1 is clamped
+        assert_eq!(easing.apply(-1.0), easing.apply(0.0));
+        assert_eq!(easing.apply(2.0), easing.apply(1.0));
+    }
+}
+
+#[test]
+fn test_easing_codes_round_trip() {
+    for easing in ALL_EASINGS {
+        assert_eq!(Easing::from_u32(easing as u32), Some(easing));
+    }
+    assert_eq!(Easing::from_u32(11), None);
+    assert_eq!(TweenProperty::from_u32(4), Some(TweenProperty::CameraPosition));
+    assert_eq!(TweenProperty::from_u32(5), None);
+}
+
+#[test]
+fn test_in_and_out_curves_lean_the_right_way() {
+    assert!(Easing::QuadIn.apply(0.5) < 0.5);
+    assert!(Easing::QuadOut.apply(0.5) > 0.5);
+    assert!((Easing::QuadInOut.apply(0.5) - 0.5).abs() < 1e-5);
+    assert!((Easing::SineInOut.apply(0.5) - 0.5).abs() < 1e-5);
+    // Back overshoots before settling
+    assert!((0..100).any(|i| Easing::BackOut.apply(i as f32 / 100.0) > 1.0));
+}
+
+#[test]
+fn test_tween_holds_from_during_delay() {
+    let mut tween = Tween::new(1, TweenProperty::Scale, &[0.0], &[2.0], 1.0, 0.5, Easing::Linear).unwrap();
+    tween.advance(0.25);
+    assert_eq!(tween.value()[0], 0.0);
+    tween.advance(0.75);
+    assert!((tween.value()[0] - 1.0).abs() < 1e-5);
+    assert!(!tween.finished());
+    tween.advance(10.0);
+    assert_eq!(tween.value()[0], 2.0);
+    assert!(tween.finished());
+}
+
+#[test]
+fn test_zero_duration_jumps_to_end() {
+    let mut tween = Tween::new(1, TweenProperty::Rotation, &[0.0], &[1.0], 0.0, 0.0, Easing::CubicIn).unwrap();
+    tween.advance(1.0 / 60.0);
+    assert_eq!(tween.value()[0], 1.0);
+    assert!(tween.finished());
+}
+
+#[test]
+fn test_rejects_wrong_value_counts_and_bad_times() {
+    assert!(Tween::new(1, TweenProperty::Color, &[1.0], &[0.0], 1.0, 0.0, Easing::Linear).is_err());
+    assert!(Tween::new(0, TweenProperty::CameraPosition, &[0.0, 0.0], &[1.0], 1.0, 0.0, Easing::Linear).is_err());
+    assert!(Tween::new(1, TweenProperty::Scale, &[f32::NAN], &[1.0], 1.0, 0.0, Easing::Linear).is_err());
+    assert!(Tween::new(1, TweenProperty::Scale, &[0.0], &[1.0], -1.0, 0.0, Easing::Linear).is_err());
+    assert!(Tween::new(1, TweenProperty::Scale, &[0.0], &[1.0], 1.0, f32::INFINITY, Easing::Linear).is_err());
+}
+
+#[test]
+fn test_new_tween_replaces_one_on_the_same_property() {
+    let mut tweens = Tweens::default();
+    let scale = |to: f32| Tween::new(7, TweenProperty::Scale, &[0.0], &[to], 1.0, 0.0, Easing::Linear).unwrap();
+    let first = tweens.start(scale(1.0)).unwrap();
+    let color = Tween::new(7, TweenProperty::Color, &[0.0; 3], &[1.0; 3], 1.0, 0.0, Easing::Linear).unwrap();
+    tweens.start(color).unwrap();
+    let second = tweens.start(scale(2.0)).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(tweens.len(), 2);
+    assert!(!tweens.cancel(first));
+    assert!(tweens.cancel(second));
+    assert_eq!(tweens.len(), 1);
+}
+
+#[test]
+fn test_finished_tweens_report_their_end_once() {
+    let mut tweens = Tweens::default();
+    tweens.start(Tween::new(3, TweenProperty::Scale, &[0.0], &[1.0], 0.1, 0.0, Easing::QuadOut).unwrap());
+    let values = tweens.advance(1.0);
+    assert_eq!(values, vec![(3, TweenProperty::Scale, [1.0, 0.0, 0.0, 0.0])]);
+    assert!(tweens.is_empty());
+    assert!(tweens.advance(1.0).is_empty());
+}
+
+#[test]
+fn test_tween_limit() {
+    let mut tweens = Tweens::default();
+    for target in 0..MAX_TWEENS as u64 {
+        let tween = Tween::new(target + 1, TweenProperty::Scale, &[0.0], &[1.0], 1.0, 0.0, Easing::Linear).unwrap();
+        assert!(tweens.start(tween).is_some());
+    }
+    let extra = Tween::new(u64::MAX, TweenProperty::Scale, &[0.0], &[1.0], 1.0, 0.0, Easing::Linear).unwrap();
+    assert_eq!(tweens.start(extra), None);
+}
+
+#[test]
+fn test_packed_tint_puts_red_in_the_lowest_byte() {
+    assert_eq!(Appearance::default().packed_tint(), u32::MAX);
+    let red = Appearance { scale: 1.0, tint: [1.0, 0.0, 2.0] };
+    assert_eq!(red.packed_tint().to_le_bytes(), [255, 0, 255, 255]);
+}
+
+#[test]
+fn test_scene_intro_tweens_are_validated() {
+    let json = r#"{"bodies": [{"x": 0, "y": 1}], "tweens": [
+        {"body": 0, "property": "scale", "from": [0], "to": [1], "duration": 0.5, "easing": "backOut"},
+        {"property": "cameraPosition", "from": [0, 2], "to": [0, 0], "duration": 1, "delay": 0.5}
+    ]}"#;
+    let desc: SceneDesc = serde_json::from_str(json).unwrap();
+    assert_eq!(desc.tweens.len(), 2);
+    assert_eq!(desc.tweens[1].easing, Easing::Linear);
+    assert!(desc.validate().is_ok());
+
+    let mut bad_body = desc.clone();
+    bad_body.tweens[0].body = 1;
+    assert!(bad_body.validate().is_err());
+    let mut bad_values = desc;
+    bad_values.tweens[1].to = vec![0.0];
+    assert!(bad_values.validate().is_err());
+}
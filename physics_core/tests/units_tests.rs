@@ -0,0 +1,42 @@
+//! Integration tests for the world scale
+
+use physics_core::units::WorldScale;
+
+#[test]
+fn test_default_scale_keeps_the_original_layout() {
+    let scale = WorldScale::default();
+    assert_eq!(scale.ndc_per_meter(), 1.0);
+    assert_eq!(scale.wall_position(), 1.1);
+    assert_eq!(scale.box_half_extent(), 0.05);
+    assert_eq!(scale.camera_height(), 2.2);
+    assert_eq!(scale.zoom_limits(), (0.2, 20.0));
+}
+
+#[test]
+fn test_smaller_scale_shows_more_meters() {
+    let scale = WorldScale::new(0.1).unwrap();
+    assert!((scale.wall_position() - 11.0).abs() < 1e-4);
+    assert!((scale.box_half_extent() - 0.5).abs() < 1e-6);
+    assert!((scale.camera_height() - 22.0).abs() < 1e-4);
+    let (min, max) = scale.zoom_limits();
+    assert!((min - 2.0).abs() < 1e-5 && (max - 200.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_pixels_per_meter_round_trip_through_the_surface_height() {
+    // 100 px/m on a 1000 px surface: the 2 NDC view is 10 m tall
+    let scale = WorldScale::from_pixels_per_meter(100.0, 1000).unwrap();
+    assert!((scale.ndc_per_meter() - 0.2).abs() < 1e-6);
+    assert!((scale.pixels_per_meter(1000) - 100.0).abs() < 1e-3);
+    // Twice the pixels shows the same meters bigger
+    assert!((scale.pixels_per_meter(2000) - 200.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_scale_rejects_non_positive_and_non_finite_values() {
+    assert!(WorldScale::new(0.0).is_none());
+    assert!(WorldScale::new(-1.0).is_none());
+    assert!(WorldScale::new(f32::NAN).is_none());
+    assert!(WorldScale::new(f32::INFINITY).is_none());
+    assert!(WorldScale::from_pixels_per_meter(-50.0, 800).is_none());
+}
@@ -0,0 +1,44 @@
+//! Integration tests for the checks on values from the bindings
+
+use physics_core::validation::{self, InputError};
+
+#[test]
+fn test_checks_pick_the_specific_code() {
+    assert!(validation::finite("position", &[1.0, -2.0]).is_ok());
+    assert_eq!(validation::finite("position", &[1.0, f32::NAN]).unwrap_err().code, validation::ERROR_NOT_FINITE);
+    assert_eq!(validation::size("half extent", -0.5).unwrap_err().code, validation::ERROR_INVALID_SIZE);
+    assert_eq!(validation::size("half extent", 0.0).unwrap_err().code, validation::ERROR_INVALID_SIZE);
+    // NaN is reported as NaN, not as a bad size
+    assert_eq!(validation::size("half extent", f32::NAN).unwrap_err().code, validation::ERROR_NOT_FINITE);
+    assert_eq!(validation::time_step(0.0).unwrap_err().code, validation::ERROR_INVALID_TIME_STEP);
+    assert_eq!(validation::time_step(f32::INFINITY).unwrap_err().code, validation::ERROR_INVALID_TIME_STEP);
+    assert!(validation::time_step(1.0 / 60.0).is_ok());
+    assert_eq!(validation::non_negative("stiffness", -1.0).unwrap_err().code, validation::ERROR_OUT_OF_RANGE);
+    assert!(validation::in_range("alpha", 0.5, 0.0, 1.0).is_ok());
+
+    let error = validation::in_range("alpha", 2.0, 0.0, 1.0).unwrap_err();
+    assert_eq!(error.message, "alpha must be in 0..=1, got 2");
+    assert_eq!(validation::finite("position", &[f32::NAN]).unwrap_err().to_string(), "position must be finite, got NaN");
+}
+
+#[test]
+fn test_rejected_input_stays_as_the_last_error_of_its_thread() {
+    validation::clear();
+    assert!(validation::accept(validation::finite("impulse", &[0.0, 1.0])));
+    assert!(validation::last_error().is_none());
+
+    assert!(!validation::accept(validation::size("radius", -1.0)));
+    // Later successes leave it alone
+    assert!(validation::accept(validation::time_step(0.016)));
+    assert_eq!(validation::last_error().unwrap().code, validation::ERROR_INVALID_SIZE);
+
+    let message = validation::reject(InputError::unknown_entity(42));
+    assert_eq!(message, "no entity with id 42");
+    let json: serde_json::Value = serde_json::from_str(&validation::last_error().unwrap().to_json()).unwrap();
+    assert_eq!(json["code"], validation::ERROR_UNKNOWN_ENTITY);
+    assert_eq!(json["message"], "no entity with id 42");
+
+    std::thread::spawn(|| assert!(validation::last_error().is_none())).join().unwrap();
+    validation::clear();
+    assert!(validation::last_error().is_none());
+}
@@ -0,0 +1,58 @@
+//! Integration tests for view ids and cameras
+
+use physics_core::views::{ViewCamera, ViewSlots, MAX_VIEWS};
+
+#[test]
+fn test_ids_start_at_one_and_are_not_reused() {
+    let mut slots = ViewSlots::default();
+    assert!(slots.is_empty());
+    assert_eq!(slots.insert("minimap"), Some(1));
+    assert_eq!(slots.insert("second"), Some(2));
+    assert_eq!(slots.remove(1), Some("minimap"));
+    assert_eq!(slots.remove(1), None);
+    assert_eq!(slots.insert("third"), Some(3));
+    assert_eq!(slots.iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![2, 3]);
+    *slots.get_mut(3).unwrap() = "renamed";
+    assert_eq!(slots.get_mut(3).copied(), Some("renamed"));
+    assert!(slots.get_mut(1).is_none());
+}
+
+#[test]
+fn test_view_limit() {
+    let mut slots = ViewSlots::default();
+    for _ in 0..MAX_VIEWS {
+        assert!(slots.insert(()).is_some());
+    }
+    assert_eq!(slots.insert(()), None);
+    slots.remove(1);
+    assert_eq!(slots.insert(()), Some(MAX_VIEWS as u32 + 1));
+}
+
+#[test]
+fn test_restore_keeps_ids_after_rebuild() {
+    let mut slots = ViewSlots::default();
+    slots.insert('a');
+    slots.insert('b');
+    let drained = slots.drain();
+    assert!(slots.is_empty());
+    for (id, view) in drained.into_iter().rev() {
+        slots.restore(id, view);
+    }
+    assert_eq!(slots.len(), 2);
+    assert_eq!(slots.get_mut(1).copied(), Some('a'));
+    assert_eq!(slots.insert('c'), Some(3));
+
+    let mut fresh = ViewSlots::default();
+    fresh.restore(5, 'x');
+    assert_eq!(fresh.insert('y'), Some(6));
+}
+
+#[test]
+fn test_camera_validation_and_json() {
+    assert!(ViewCamera::default().is_valid());
+    assert!(!ViewCamera { view_height: 0.0, ..ViewCamera::default() }.is_valid());
+    assert!(!ViewCamera { center_x: f32::NAN, ..ViewCamera::default() }.is_valid());
+
+    let camera: ViewCamera = serde_json::from_str(r#"{"centerX": 3.0, "viewHeight": 10.0}"#).unwrap();
+    assert_eq!(camera, ViewCamera { center_x: 3.0, center_y: 0.0, view_height: 10.0 });
+}
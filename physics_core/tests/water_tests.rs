@@ -0,0 +1,132 @@
+//! Integration tests for the water surface, buoyancy and splashes
+
+use physics_core::force_fields::BodySample;
+use physics_core::water::{Water, WaterConfig, WaterRegion, WaterSurface, MAX_COLUMNS, MAX_REGIONS};
+
+const GRAVITY: [f32; 2] = [0.0, -9.81];
+
+fn pool() -> WaterRegion {
+    WaterRegion { min_x: 0.0, max_x: 2.0, surface_y: 0.0, bottom_y: -1.0, columns: 21, ..WaterRegion::default() }
+}
+
+fn water() -> Water {
+    Water::new(WaterConfig { regions: vec![pool()] })
+}
+
+#[test]
+fn test_sanitized_fixes_bad_regions() {
+    let region = WaterRegion {
+        min_x: 1.0,
+        max_x: -1.0,
+        surface_y: 0.0,
+        bottom_y: 0.5,
+        columns: 0,
+        stiffness: -1.0,
+        wave_speed: f32::NAN,
+        color: [2.0, -1.0, 0.5, f32::NAN],
+        ..WaterRegion::default()
+    }
+    .sanitized();
+    assert_eq!((region.min_x, region.max_x), (-1.0, 1.0));
+    assert_eq!(region.bottom_y, 0.0);
+    assert_eq!(region.columns, 2);
+    assert_eq!(region.stiffness, 0.0);
+    assert_eq!(region.wave_speed, WaterRegion::default().wave_speed);
+    assert_eq!(region.color, [1.0, 0.0, 0.5, 1.0]);
+    assert_eq!(WaterRegion { columns: 10_000, ..pool() }.sanitized().columns, MAX_COLUMNS);
+}
+
+#[test]
+fn test_calm_surface_is_flat_and_submersion_follows_it() {
+    let surface = WaterSurface::new(pool());
+    assert_eq!(surface.column_x(20), 2.0);
+    assert_eq!(surface.surface_at(0.73), 0.0);
+    assert_eq!(surface.submerged_fraction([1.0, 0.0], 0.1), 0.5);
+    assert_eq!(surface.submerged_fraction([1.0, -0.5], 0.1), 1.0);
+    assert_eq!(surface.submerged_fraction([1.0, 0.5], 0.1), 0.0);
+    assert_eq!(surface.submerged_fraction([3.0, -0.5], 0.1), 0.0);
+    assert_eq!(surface.submerged_fraction([1.0, -2.0], 0.1), 0.0);
+}
+
+#[test]
+fn test_disturbance_spreads_to_neighbours_and_settles() {
+    let mut surface = WaterSurface::new(pool());
+    surface.disturb(1.0, -1.0, 0.0);
+    for _ in 0..10 {
+        surface.step(1.0 / 60.0);
+    }
+    assert!(surface.heights()[10] < 0.0);
+    assert!(surface.heights()[12] != 0.0);
+    assert!(surface.heights().iter().all(|h| h.is_finite()));
+
+    for _ in 0..1200 {
+        surface.step(1.0 / 60.0);
+    }
+    assert!(surface.heights().iter().all(|h| h.abs() < 1e-3));
+}
+
+#[test]
+fn test_large_steps_stay_bounded() {
+    let mut surface = WaterSurface::new(WaterRegion { columns: MAX_COLUMNS, wave_speed: 50.0, ..pool() });
+    surface.disturb(1.0, -100.0, 0.2);
+    for _ in 0..100 {
+        surface.step(0.5);
+    }
+    assert!(surface.heights().iter().all(|h| h.is_finite() && h.abs() <= 1.0));
+}
+
+#[test]
+fn test_submerged_bodies_are_pushed_up_and_slowed() {
+    let water = water();
+    let body = BodySample { position: [1.0, -0.5], velocity: [1.0, 0.0], mass: 0.008, half_extent: 0.1 };
+    let force = water.force_on(&body, GRAVITY);
+    // Density 2: buoyancy is twice the weight; drag opposes the motion
+    assert!((force[1] - 2.0 * 0.008 * 9.81).abs() < 1e-5);
+    assert!(force[0] < 0.0);
+
+    let dry = BodySample { position: [1.0, 1.0], ..body };
+    assert_eq!(water.force_on(&dry, GRAVITY), [0.0, 0.0]);
+    assert!(water.is_touching(body.position, body.half_extent));
+    assert!(!water.is_touching(dry.position, dry.half_extent));
+}
+
+#[test]
+fn test_splash_throws_droplets_that_fall_back() {
+    let mut water = water();
+    water.splash(1.0, -0.2, 0.05);
+    assert!(water.droplets().is_empty());
+
+    water.splash(1.0, -3.0, 0.05);
+    let thrown = water.droplets().len();
+    assert!(thrown > 0);
+    assert!(water.droplets().iter().all(|d| d.velocity[1] > 0.0));
+    assert_eq!(water.vertices().len(), (20 + thrown) * 6);
+
+    for _ in 0..120 {
+        water.step(1.0 / 60.0, GRAVITY);
+    }
+    assert!(water.droplets().is_empty());
+}
+
+#[test]
+fn test_splash_outside_every_region_does_nothing() {
+    let mut water = water();
+    water.splash(5.0, -3.0, 0.05);
+    assert!(water.droplets().is_empty());
+    assert!(water.surfaces()[0].heights().iter().all(|&h| h == 0.0));
+}
+
+#[test]
+fn test_config_round_trips_and_caps_regions() {
+    let config = WaterConfig { regions: vec![pool(); MAX_REGIONS + 3] };
+    let water = Water::new(config);
+    assert_eq!(water.surfaces().len(), MAX_REGIONS);
+    assert_eq!(water.config().regions[0], pool());
+    assert!(Water::default().is_empty());
+}
+
+#[test]
+fn test_missing_json_fields_take_defaults() {
+    let config: WaterConfig = serde_json::from_str(r#"{"regions": [{"surfaceY": 0.25}]}"#).unwrap();
+    assert_eq!(config.regions, vec![WaterRegion { surface_y: 0.25, ..WaterRegion::default() }]);
+}